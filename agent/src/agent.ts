@@ -1,12 +1,24 @@
-import { CModuleTracer, HookMode, type FunctionTarget } from './cmodule-tracer.js';
+import { CModuleTracer, HookMode, hookKey, type FunctionTarget } from './cmodule-tracer.js';
+import { CoverageCollector, type CoverageMessage } from './coverage.js';
+import { FlightRecorder, type FlightRecorderMessage } from './flight-recorder.js';
+import { HeapTracker, type HeapMessage } from './heap-tracker.js';
 import { createPlatformAdapter, type PlatformAdapter } from './platform.js';
+import { NetworkTracer, type NetworkMessage } from './network-tracer.js';
+import { ExceptionTracer, describeThrown, type ExceptionsMessage } from './exception-tracer.js';
+import { SignalTracer, type SignalsMessage } from './signal-tracer.js';
+import { LockTracer, type LocksMessage } from './lock-tracer.js';
+import { PanicHooks } from './panic-hooks.js';
 import { RateTracker } from './rate-tracker.js';
-import { findGlobalExport } from './utils.js';
+import { SignalSafetyAuditor, type SignalSafetyMessage } from './signal-safety.js';
+import { SyscallTracer, type SyscallsMessage } from './syscall-tracer.js';
+import { renderLayout, type TypeLayout } from './type-layout.js';
+import { EventIdAllocator, findGlobalExport, setReloadGeneration, signExtend } from './utils.js';
 import { Tracer, type ResolvedTarget as TracerResolvedTarget } from './tracers/tracer.js';
 import { NativeTracer } from './tracers/native-tracer.js';
 import { PythonTracer } from './tracers/python-tracer.js';
 import { V8Tracer } from './tracers/v8-tracer.js';
 import { JscTracer } from './tracers/jsc-tracer.js';
+import { JavaTracer } from './tracers/java-tracer.js';
 
 interface HookInstruction {
   action: 'add' | 'remove';
@@ -26,8 +38,9 @@ interface OutputEvent {
   sessionId: string;
   timestampNs: number;
   threadId: number;
-  eventType: 'stdout' | 'stderr';
+  eventType: 'stdout' | 'stderr' | 'aux_output';
   text: string;
+  channel?: string;  // "fd:N" for aux_output
 }
 
 interface BacktraceFrame {
@@ -38,6 +51,21 @@ interface BacktraceFrame {
   lineNumber: number | null;
 }
 
+interface CrashThread {
+  id: number;
+  name: string | null;
+  state: string;
+  crashed: boolean;
+  backtrace: BacktraceFrame[];
+}
+
+interface CrashModule {
+  name: string;
+  base: string;
+  size: number;
+  path: string;
+}
+
 interface CrashEvent {
   id: string;
   timestampNs: number;
@@ -55,8 +83,31 @@ interface CrashEvent {
   exceptionType?: string;
   exceptionMessage?: string | null;
   throwBacktrace?: BacktraceFrame[];
+  threads?: CrashThread[];
+  modules?: CrashModule[];
+  globals?: CrashGlobal[];
+}
+
+// crashCapture: a global's bytes as read by the crash handler
+interface CrashGlobal {
+  name: string;
+  address?: string;
+  size?: number;
+  byteSize?: number;
+  typeName?: string | null;
+  data?: string;
+  error?: string;
 }
 
+// Caps for the all-thread snapshot in crash summaries
+const CRASH_MAX_THREADS = 64;
+const CRASH_MAX_THREAD_FRAMES = 32;
+
+// Stack sampling: frames kept per sample, and the symbol cache size at which
+// it is reset (hot stacks repeat, so most lookups hit)
+const SAMPLE_MAX_FRAMES = 64;
+const SAMPLE_SYMBOL_CACHE_MAX = 50000;
+
 interface ReadRecipe {
   label: string;
   address: string;  // hex
@@ -74,6 +125,7 @@ interface ReadRecipe {
     typeName?: string;
     isTruncatedStruct?: boolean;
   }>;
+  layout?: TypeLayout;  // DWARF type tree for aggregates, enums and strings
 }
 
 interface ReadMemoryMessage {
@@ -82,6 +134,7 @@ interface ReadMemoryMessage {
   poll?: {
     intervalMs: number;
     durationMs: number;
+    pollId: string;  // Tags this poll's snapshot events
   };
 }
 
@@ -152,23 +205,79 @@ interface SetLogpointMessage {
   id: string;
   message: string;
   condition?: string;
+  hitCount?: number;  // First hit logged (0: from the first)
+  everyN?: number;    // Log every Nth hit from there (0: every hit)
+  maxHits?: number;   // Messages before the logpoint goes quiet (0: no limit)
+  params?: LogpointParam[];
   funcName?: string;
   file?: string;
   line?: number;
   imageBase?: string;
 }
 
+// A `{name}` placeholder resolved from DWARF: argument register `arg`, then
+// each 'deref' reads a pointer and each number adds a member offset
+interface LogpointParam {
+  name: string;
+  arg: number;
+  ops: Array<'deref' | number>;
+  size: number;
+  typeKind: string;
+}
+
 interface LogpointState {
   id: string;
   address: NativePointer;
   message: string;
   condition?: string;
+  hitCount: number;
+  everyN: number;
+  maxHits: number;
+  hits: number;     // Hits that passed the condition
+  logged: number;
+  params: LogpointParam[];
   listener: InvocationListener;
+  detached: boolean; // maxHits reached; the hook is gone but the logpoint stays listed
   funcName?: string;
   file?: string;
   line?: number;
 }
 
+interface WatchpointSpec {
+  id: string;
+  label: string;
+  address: string;   // hex
+  size: number;
+  noSlide?: boolean; // true for raw user-provided addresses
+}
+
+interface SetWatchpointsMessage {
+  watchpoints: WatchpointSpec[];  // Full replacement set
+  imageBase?: string;
+}
+
+// Regions carry static addresses; unresolved names arrive with `error` only
+interface SetCrashCaptureMessage {
+  regions: CrashGlobal[];
+  imageBase?: string;
+}
+
+interface SetSamplingMessage {
+  hz: number;                    // 0 stops sampling
+  threadIds?: number[] | null;   // Only these threads
+  threadName?: string | null;    // Only threads whose name contains this
+}
+
+interface SetThreadFilterMessage {
+  ids?: number[] | null;          // Trace these threads...
+  nameContains?: string | null;   // ...and threads whose name contains this; neither = all
+}
+
+interface SetRateLimitMessage {
+  maxPerSecond?: number | null;   // Calls recorded per function per second
+  sampleEvery?: number | null;    // Record 1 call in K per function; neither = all
+}
+
 interface OneShotAddress {
   address: string;
   noSlide?: boolean;  // true for runtime addresses (e.g., return address)
@@ -186,6 +295,23 @@ interface InstallStepHooksMessage {
   returnAddress?: string | null; // Carried forward from original BP for step-out support
 }
 
+// debug_eval: call a function in the target. Integer/pointer args only,
+// sent as decimal strings so 64-bit values survive JSON.
+interface CallFunctionMessage {
+  address: string;
+  args: string[];
+  imageBase?: string;
+}
+
+interface ReadFrameMessage {
+  threadId: number;
+  /** 0 = where the thread paused, N = its Nth caller */
+  frame: number;
+  /** Paused on a function's first instruction, before its prologue */
+  atEntry: boolean;
+  imageBase?: string;
+}
+
 // Type aliases for Tracer interface compatibility
 type ResolvedTarget = FunctionTarget;
 type BreakpointMessage = SetBreakpointMessage;
@@ -193,7 +319,7 @@ type LogpointMessage = SetLogpointMessage;
 type StepHooksMessage = InstallStepHooksMessage;
 
 // Runtime detection types
-type RuntimeType = 'native' | 'cpython' | 'v8' | 'jsc';
+type RuntimeType = 'native' | 'cpython' | 'v8' | 'jsc' | 'jvm';
 
 /**
  * Detect the target process runtime by probing globals and native symbols.
@@ -236,6 +362,12 @@ function detectRuntime(): RuntimeType {
     return 'jsc';
   }
 
+  // Check for the JVM. At spawn only the java launcher (libjli) is mapped;
+  // libjvm is dlopened later, so either one counts.
+  if (findGlobalExport('JLI_Launch') || findGlobalExport('JNI_CreateJavaVM')) {
+    return 'jvm';
+  }
+
   // Default to native (C/C++/Rust/etc.)
   return 'native';
 }
@@ -253,6 +385,8 @@ function createTracer(runtime: RuntimeType, agent: any): Tracer {
       return new V8Tracer(agent);
     case 'jsc':
       return new JscTracer(agent);
+    case 'jvm':
+      return new JavaTracer(agent);
     default:
       return new NativeTracer(agent);
   }
@@ -261,7 +395,9 @@ function createTracer(runtime: RuntimeType, agent: any): Tracer {
 class StrobeAgent {
   private sessionId: string = '';
   private sessionStartNs: number = 0;
-  private eventSeq: number = 0;
+  private logpointIds = new EventIdAllocator('logpoint');
+  private watchpointIds = new EventIdAllocator('watchpoint');
+  private sampleIds = new EventIdAllocator('sample');
   private platform: PlatformAdapter;
   private cmoduleTracer: CModuleTracer;  // Internal CModule-based tracer
   public tracer: Tracer;                  // Public Tracer interface
@@ -275,23 +411,37 @@ class StrobeAgent {
   private breakpoints: Map<string, BreakpointState> = new Map(); // id → state
   private breakpointsByAddress: Map<string, string> = new Map(); // address → id
   private pausedThreads: Map<number, string> = new Map(); // threadId → breakpointId
+  // Registers of each paused native thread, for debug_memory locals
+  private pausedRegisters: Map<number, Record<string, string>> = new Map();
   private logpoints: Map<string, LogpointState> = new Map(); // id → state
   private steppingThreads: Set<number> = new Set(); // threads with active step hooks
 
+  // Data watchpoints (MemoryAccessMonitor). Ranges are runtime addresses.
+  private watchpoints: Array<{ spec: WatchpointSpec; base: NativePointer; end: NativePointer }> = [];
+  private watchpointRearmPending: boolean = false;
+
   // Output event buffering (low-frequency, stays in JS)
   private outputBuffer: OutputEvent[] = [];
-  private outputIdCounter: number = 0;
+  private outputIds = new EventIdAllocator('out');
   private outputFlushInterval: number = 10; // ms
   private maxOutputBufferSize: number = 1000;
 
   // Re-entrancy guard for write(2) interception
   private inOutputCapture: boolean = false;
 
+  // Extra fds captured as aux_output (set via initialize's captureFds)
+  private captureFds: Set<number> = new Set();
+
+  // crashCapture globals, slid to runtime addresses, read by buildCrashEvent
+  private crashRegions: Array<{ global: CrashGlobal; base: NativePointer | null }> = [];
+
   // Last C++ exception captured by __cxa_throw hook (overwritten each throw)
   private lastException: {
     type: string;
     message: string | null;
     backtrace: BacktraceFrame[];
+    threadId: number;
+    at: number;
   } | null = null;
 
   // Per-session output capture limit (50MB)
@@ -305,6 +455,101 @@ class StrobeAgent {
   private outputFlushTimer: ReturnType<typeof setInterval> | null = null;
   private samplingStatsTimer: ReturnType<typeof setInterval> | null = null;
 
+  // Stack sampling timer (debug_trace sampling) and its symbol cache
+  private stackSamplingTimer: ReturnType<typeof setInterval> | null = null;
+  public coverage: CoverageCollector = new CoverageCollector();
+  public heap: HeapTracker = new HeapTracker(symbolizeFrame, () => this.getTimestampNs());
+  // Holds traced calls until a trigger fires (debug_trace flightRecorder)
+  private flightRecorder: FlightRecorder = new FlightRecorder((events) => {
+    send({ type: 'events', events });
+  });
+  private sampleSymbols: Map<string, BacktraceFrame> = new Map();
+  // Flags non-async-signal-safe calls inside signal handlers (debug_trace signalSafety)
+  private signalSafetyIds = new EventIdAllocator('sigsafe');
+  private signalSafety: SignalSafetyAuditor = new SignalSafetyAuditor((event) => {
+    send({ type: 'events', events: [{
+      id: this.signalSafetyIds.next(this.sessionId, event.threadId),
+      timestampNs: this.getTimestampNs(),
+      threadName: null,
+      pid: Process.id,
+      ...event,
+    }] });
+  }, symbolizeFrame);
+  // libc file/socket calls traced by @syscalls: patterns
+  private syscallIds = new EventIdAllocator('sys');
+  private syscalls: SyscallTracer = new SyscallTracer((event) => {
+    send({ type: 'events', events: [{
+      id: this.syscallIds.next(this.sessionId, event.threadId),
+      threadName: null,
+      pid: Process.id,
+      ...event,
+    }] });
+  }, () => this.getTimestampNs());
+  // Connects, DNS lookups and TLS traffic traced by the @network pattern
+  private networkIds = new EventIdAllocator('net');
+  private network: NetworkTracer = new NetworkTracer((event) => {
+    send({ type: 'events', events: [{
+      id: this.networkIds.next(this.sessionId, event.threadId),
+      threadName: null,
+      pid: Process.id,
+      ...event,
+    }] });
+  }, () => this.getTimestampNs());
+
+  // C++ throws and the catch site each reached, traced by the @exceptions pattern
+  private exceptionIds = new EventIdAllocator('exc');
+  private exceptions: ExceptionTracer = new ExceptionTracer((event) => {
+    const id = this.exceptionIds.next(this.sessionId, event.threadId);
+    send({ type: 'events', events: [{
+      id,
+      callId: id,
+      timestampNs: this.getTimestampNs(),
+      threadName: null,
+      pid: Process.id,
+      ...event,
+    }] });
+    return id;
+  }, symbolizeFrame);
+
+  // Handler deliveries and kill/raise calls, traced by the @signals pattern
+  private signalIds = new EventIdAllocator('sig');
+  private signals: SignalTracer = new SignalTracer((event) => {
+    send({ type: 'events', events: [{
+      id: this.signalIds.next(this.sessionId, event.threadId),
+      threadName: null,
+      pid: Process.id,
+      ...event,
+    }] });
+  }, (addr) => symbolizeFrame(addr).name, () => this.getTimestampNs());
+
+  // Contended and blocked lock acquisitions, traced by the @locks pattern
+  private lockIds = new EventIdAllocator('lock');
+  private locks: LockTracer = new LockTracer((event) => {
+    send({ type: 'events', events: [{
+      id: this.lockIds.next(this.sessionId, event.threadId),
+      threadName: null,
+      pid: Process.id,
+      ...event,
+    }] });
+  }, (addr) => symbolizeFrame(addr).name, () => this.getTimestampNs());
+
+  // rust_panic, failed asserts and abort, hooked in every native process
+  private panicIds = new EventIdAllocator('panic');
+  private panicHooks: PanicHooks = new PanicHooks((event) => {
+    send({ type: 'events', events: [{
+      id: this.panicIds.next(this.sessionId, event.threadId),
+      timestampNs: this.getTimestampNs(),
+      threadName: null,
+      pid: Process.id,
+      ...event,
+    }] });
+  }, symbolizeFrame, (threadId) => {
+    // The exception being thrown when this thread aborted: std::terminate runs
+    // right after an uncaught throw, on the throwing thread
+    const e = this.lastException;
+    return e !== null && e.threadId === threadId && Date.now() - e.at < 1000 ? e : null;
+  });
+
   // Pre-resolved libc functions for crash file writing.
   // Resolved during init so the exception handler doesn't need to call
   // Process.enumerateModules() which is unsafe in signal handler context on Linux.
@@ -315,7 +560,10 @@ class StrobeAgent {
   constructor() {
     this.platform = createPlatformAdapter();
     this.cmoduleTracer = new CModuleTracer((events) => {
-      send({ type: 'events', events });
+      const passthrough = this.flightRecorder.enabled ? this.flightRecorder.record(events) : events;
+      if (passthrough.length > 0) {
+        send({ type: 'events', events: passthrough });
+      }
     }, this.platform);
 
     // Create language-appropriate tracer based on runtime detection
@@ -361,6 +609,12 @@ class StrobeAgent {
       // backtrace before stack unwinding destroys the context.
       this.installThrowHook();
 
+      // Panics, failed assertions and aborts are recorded whatever is traced
+      const panicHooks = this.panicHooks.install();
+      if (panicHooks.length > 0) {
+        send({ type: 'log', message: `Panic hooks active (${panicHooks.join(', ')})` });
+      }
+
       // Intercept write(2) for stdout/stderr capture (non-fatal if it fails,
       // e.g. with ASAN-instrumented binaries where write() isn't hookable)
       try {
@@ -373,8 +627,10 @@ class StrobeAgent {
     }
   }
 
-  initialize(sessionId: string): void {
+  initialize(sessionId: string, captureFds: number[] = [], reloadGeneration: number = 0): void {
     this.sessionId = sessionId;
+    setReloadGeneration(reloadGeneration);
+    this.captureFds = new Set(captureFds);
     this.sessionStartNs = Date.now() * 1000000;
 
     // Initialize Tracer interface
@@ -449,11 +705,16 @@ class StrobeAgent {
               nameRaw: func.nameRaw,
               sourceFile: func.sourceFile,
               lineNumber: func.lineNumber,
+              argLayouts: func.argLayouts,
+              returnLayout: func.returnLayout,
+              goidOffset: func.goidOffset,
+              module: func.module,
             }, mode);
             if (funcId !== null) {
+              const key = hookKey(func);
               this.funcIdToName.set(funcId, func.name);
-              this.funcIdToAddress.set(funcId, func.address);
-              this.addressToFuncId.set(func.address, funcId);
+              this.funcIdToAddress.set(funcId, key);
+              this.addressToFuncId.set(key, funcId);
               installed++;
             } else {
               failed++;
@@ -490,7 +751,7 @@ class StrobeAgent {
         if (message.functions) {
           for (const func of message.functions) {
             // Look up funcId from address, then remove via tracer interface
-            const funcId = this.addressToFuncId.get(func.address);
+            const funcId = this.addressToFuncId.get(hookKey(func));
             if (funcId !== undefined) {
               this.tracer.removeHook(funcId);
             }
@@ -540,6 +801,10 @@ class StrobeAgent {
   private bufferOutputEvent(event: OutputEvent): void {
     this.outputBuffer.push(event);
 
+    if (event.eventType === 'stderr' && this.flightRecorder.matchesOutput(event.text)) {
+      this.flushFlightRecorder('output');
+    }
+
     if (this.outputBuffer.length >= this.maxOutputBufferSize) {
       this.flushOutput();
     }
@@ -554,10 +819,6 @@ class StrobeAgent {
     send({ type: 'events', events });
   }
 
-  private generateOutputEventId(): string {
-    return `${this.sessionId}-out-${++this.outputIdCounter}`;
-  }
-
   private getTimestampNs(): number {
     return Date.now() * 1000000 - this.sessionStartNs;
   }
@@ -572,6 +833,11 @@ class StrobeAgent {
         // before the OS kills the process (especially on Linux).
         this.writeCrashFile(crashEvent);
 
+        // The calls leading up to the crash are what the flight recorder is for
+        try {
+          this.flushFlightRecorder('crash');
+        } catch (_) {}
+
         // Flush any buffered output events (stdout/stderr) before the crash event.
         // Without this, output written just before the crash is lost because the
         // periodic flush timer never fires — the process is about to die.
@@ -614,74 +880,15 @@ class StrobeAgent {
       return;
     }
 
-    // Pre-resolve __cxa_demangle for type name demangling
-    const cxaDemanglePtr = findGlobalExport('__cxa_demangle');
-    let demangleFn: NativeFunction<NativePointer, [NativePointer, NativePointer, NativePointer, NativePointer]> | null = null;
-    if (cxaDemanglePtr) {
-      demangleFn = new NativeFunction(cxaDemanglePtr, 'pointer', ['pointer', 'pointer', 'pointer', 'pointer']);
-    }
-
-    const freePtr = findGlobalExport('free');
-    let freeFn: NativeFunction<void, [NativePointer]> | null = null;
-    if (freePtr) {
-      freeFn = new NativeFunction(freePtr, 'void', ['pointer']);
-    }
-
     const self = this;
 
     Interceptor.attach(cxaThrow, {
       onEnter(args) {
         try {
-          const thrownException = args[0]; // void* — the exception object
-          const tinfo = args[1];           // std::type_info*
-
-          // 1. Read exception type name from type_info
-          // Itanium ABI layout: [vtable_ptr, const char* __name]
-          let exceptionType = '<unknown>';
-          try {
-            const namePtr = tinfo.add(Process.pointerSize).readPointer();
-            const mangledName = namePtr.readCString();
-            if (mangledName) {
-              // Try to demangle
-              if (demangleFn) {
-                const mangledBuf = Memory.allocUtf8String(mangledName);
-                const statusBuf = Memory.alloc(4);
-                const result = demangleFn(mangledBuf, NULL, NULL, statusBuf);
-                const status = statusBuf.readS32();
-                if (status === 0 && !result.isNull()) {
-                  exceptionType = result.readCString() ?? mangledName;
-                  if (freeFn) freeFn(result);
-                } else {
-                  exceptionType = mangledName;
-                }
-              } else {
-                exceptionType = mangledName;
-              }
-            }
-          } catch (_) {
-            // type_info read failed
-          }
-
-          // 2. Try to read what() for std::exception subclasses
-          // Itanium ABI vtable: vptr[0] = complete dtor, vptr[1] = deleting dtor, vptr[2] = what()
-          let message: string | null = null;
-          try {
-            const vptr = thrownException.readPointer();
-            if (!vptr.isNull()) {
-              const whatFnPtr = vptr.add(Process.pointerSize * 2).readPointer();
-              if (!whatFnPtr.isNull()) {
-                const whatNative = new NativeFunction(whatFnPtr, 'pointer', ['pointer']);
-                const resultPtr = whatNative(thrownException) as NativePointer;
-                if (!resultPtr.isNull()) {
-                  message = resultPtr.readCString();
-                }
-              }
-            }
-          } catch (_) {
-            // what() failed — expected for non-std::exception types (throw 42, etc.)
-          }
+          // 1. Exception type name from RTTI, what() for std::exception subclasses
+          const { type: exceptionType, message } = describeThrown(args[0], args[1]);
 
-          // 3. Capture throw-site backtrace
+          // 2. Capture throw-site backtrace
           let backtrace: BacktraceFrame[] = [];
           try {
             const frames = Thread.backtrace(this.context, Backtracer.ACCURATE);
@@ -700,7 +907,9 @@ class StrobeAgent {
           }
 
           // Store — subsequent throws overwrite; only the last (uncaught) one matters
-          self.lastException = { type: exceptionType, message, backtrace };
+          self.lastException = {
+            type: exceptionType, message, backtrace, threadId: this.threadId, at: Date.now(),
+          };
         } catch (_) {
           // Never crash the target process
         }
@@ -749,41 +958,18 @@ class StrobeAgent {
     let backtrace: BacktraceFrame[] = [];
     try {
       const frames = Thread.backtrace(details.context, Backtracer.ACCURATE);
-      backtrace = frames.map((addr: NativePointer) => {
-        const sym = DebugSymbol.fromAddress(addr);
-        return {
-          address: addr.toString(),
-          moduleName: sym.moduleName,
-          name: sym.name,
-          fileName: sym.fileName,
-          lineNumber: sym.lineNumber,
-        };
-      });
+      backtrace = frames.map(symbolizeFrame);
     } catch (e) {
       // Backtrace may fail in some crash scenarios
     }
 
+    const crashThreadId = Process.getCurrentThreadId();
+    const threads = this.snapshotThreads(crashThreadId, backtrace);
+    const modules = this.snapshotModules();
+
     // Capture register state from crash context
-    const registers: Record<string, string> = {};
+    const registers = captureRegisters(details.context);
     const ctx = details.context as any;
-    // ARM64 registers
-    if (Process.arch === 'arm64') {
-      for (let i = 0; i <= 28; i++) {
-        const regName = `x${i}`;
-        if (ctx[regName]) registers[regName] = ctx[regName].toString();
-      }
-      if (ctx.fp) registers.fp = ctx.fp.toString();
-      if (ctx.lr) registers.lr = ctx.lr.toString();
-      if (ctx.sp) registers.sp = ctx.sp.toString();
-      if (ctx.pc) registers.pc = ctx.pc.toString();
-    }
-    // x86_64 registers
-    else if (Process.arch === 'x64') {
-      for (const reg of ['rax','rbx','rcx','rdx','rsi','rdi','rbp','rsp',
-                         'r8','r9','r10','r11','r12','r13','r14','r15','rip']) {
-        if (ctx[reg]) registers[reg] = ctx[reg].toString();
-      }
-    }
 
     // Read stack frame memory around frame pointer (for local variable resolution)
     let frameMemory: string | null = null;
@@ -815,7 +1001,7 @@ class StrobeAgent {
     const crashEvent: CrashEvent = {
       id: eventId,
       timestampNs: timestamp,
-      threadId: Process.getCurrentThreadId(),
+      threadId: crashThreadId,
       threadName: null,
       eventType: 'crash',
       pid: Process.id,
@@ -826,8 +1012,14 @@ class StrobeAgent {
       frameMemory,
       frameBase,
       memoryAccess,
+      threads,
+      modules,
     };
 
+    if (this.crashRegions.length > 0) {
+      crashEvent.globals = this.readCrashRegions();
+    }
+
     // Enrich with C++ exception info captured by __cxa_throw hook
     if (this.lastException) {
       crashEvent.exceptionType = this.lastException.type;
@@ -838,15 +1030,75 @@ class StrobeAgent {
     return crashEvent;
   }
 
+  /** Dump the crashCapture globals; an unreadable region reports why instead. */
+  private readCrashRegions(): CrashGlobal[] {
+    return this.crashRegions.map(({ global, base }) => {
+      if (base === null || global.error) return global;
+      try {
+        const data = base.readByteArray(global.size!);
+        return data ? { ...global, data: _arrayBufferToHex(data) } : { ...global, error: 'read failed' };
+      } catch (e: any) {
+        return { ...global, error: e.message };
+      }
+    });
+  }
+
+  /** Backtraces of every thread at crash time (crashing thread reuses its accurate trace). */
+  private snapshotThreads(crashThreadId: number, crashBacktrace: BacktraceFrame[]): CrashThread[] {
+    const threads: CrashThread[] = [];
+    try {
+      for (const t of Process.enumerateThreads().slice(0, CRASH_MAX_THREADS)) {
+        const crashed = t.id === crashThreadId;
+        let bt: BacktraceFrame[] = crashed ? crashBacktrace : [];
+        if (!crashed) {
+          try {
+            bt = Thread.backtrace(t.context, Backtracer.FUZZY)
+              .slice(0, CRASH_MAX_THREAD_FRAMES)
+              .map(symbolizeFrame);
+          } catch (e) {
+            // Thread may have exited or have an unwalkable stack
+          }
+        }
+        threads.push({
+          id: t.id,
+          name: (t as any).name ?? null,
+          state: t.state,
+          crashed,
+          backtrace: bt,
+        });
+      }
+    } catch (e) {
+      // Thread enumeration may fail in some crash scenarios
+    }
+    return threads;
+  }
+
+  private snapshotModules(): CrashModule[] {
+    try {
+      return Process.enumerateModules().map((m) => ({
+        name: m.name,
+        base: m.base.toString(),
+        size: m.size,
+        path: m.path,
+      }));
+    } catch (e) {
+      return [];
+    }
+  }
+
   private createOutputEvent(fd: number, text: string): OutputEvent {
-    return {
-      id: this.generateOutputEventId(),
+    const event: OutputEvent = {
+      id: this.outputIds.next(this.sessionId, Process.getCurrentThreadId()),
       sessionId: this.sessionId,
       timestampNs: this.getTimestampNs(),
       threadId: Process.getCurrentThreadId(),
-      eventType: fd === 1 ? 'stdout' : 'stderr',
+      eventType: fd === 1 ? 'stdout' : fd === 2 ? 'stderr' : 'aux_output',
       text,
     };
+    if (event.eventType === 'aux_output') {
+      event.channel = `fd:${fd}`;
+    }
+    return event;
   }
 
   /** Clean shutdown: flush all buffered data before script teardown. */
@@ -864,6 +1116,12 @@ class StrobeAgent {
       clearInterval(this.activePollTimer);
       this.activePollTimer = null;
     }
+    if (this.stackSamplingTimer !== null) {
+      clearInterval(this.stackSamplingTimer);
+      this.stackSamplingTimer = null;
+    }
+    this.coverage.dispose();
+    this.heap.dispose();
 
     // Flush CModule ring buffer (final drain) and stop its timer
     this.tracer.dispose();
@@ -889,6 +1147,94 @@ class StrobeAgent {
     send({ type: 'read_response', results });
   }
 
+  // Objective-C methods by selector pattern, answered with objc_response.
+  // Implementations are reported relative to their module, so the daemon
+  // hooks them like any other module-relative target.
+  resolveObjc(pattern: string): void {
+    try {
+      if (typeof ObjC === 'undefined' || !ObjC.available) {
+        send({ type: 'objc_response', error: 'The Objective-C runtime is not loaded in this process' });
+        return;
+      }
+      const targets: { name: string; module: string; offset: string }[] = [];
+      for (const match of new ApiResolver('objc').enumerateMatches(pattern)) {
+        const address = match.address.strip();
+        const module = Process.findModuleByAddress(address);
+        if (module === null) continue;
+        targets.push({
+          name: match.name,
+          module: module.name,
+          offset: address.sub(module.base).toString(),
+        });
+      }
+      send({ type: 'objc_response', targets });
+    } catch (e: any) {
+      send({ type: 'objc_response', error: e.message });
+    }
+  }
+
+  callFunction(message: CallFunctionMessage): void {
+    if (message.imageBase) {
+      this.tracer.setImageBase(message.imageBase);
+    }
+    try {
+      const address = ptr(message.address).add(this.tracer.getSlide());
+      const argTypes = message.args.map(() => 'int64');
+      const fn = new NativeFunction(address, 'int64', argTypes as any);
+      const ret = fn(...message.args.map(a => int64(a)));
+      send({ type: 'call_response', value: ret.toString() });
+    } catch (e: any) {
+      send({ type: 'call_response', error: e.message });
+    }
+  }
+
+  /**
+   * A paused thread's frame for debug_memory locals, answered with
+   * frame_response. Callers are found by walking the frame-pointer chain,
+   * so frames of code built without frame pointers come out wrong. Only
+   * frame 0 has its registers; a caller's are whatever they were at the
+   * pause.
+   */
+  readFrame(message: ReadFrameMessage): void {
+    if (message.imageBase) {
+      this.tracer.setImageBase(message.imageBase);
+    }
+    try {
+      const registers = this.pausedRegisters.get(message.threadId);
+      if (registers === undefined) {
+        send({ type: 'frame_response', error: `Thread ${message.threadId} is not paused in native code` });
+        return;
+      }
+      const arm64 = Process.arch === 'arm64';
+      let pc = ptr(arm64 ? registers.pc : registers.rip);
+      let fp = ptr(arm64 ? registers.fp : registers.rbp);
+      for (let i = 0; i < message.frame; i++) {
+        let returnAddress: NativePointer;
+        if (i === 0 && message.atEntry) {
+          // The prologue hasn't run: fp is still the caller's
+          returnAddress = arm64 ? ptr(registers.lr) : ptr(registers.rsp).readPointer();
+        } else {
+          if (fp.isNull()) throw new Error(`frame ${message.frame} is past the outermost frame`);
+          returnAddress = fp.add(Process.pointerSize).readPointer();
+          fp = fp.readPointer();
+        }
+        // The call instruction, not the one after it, gives the caller's line
+        pc = returnAddress.strip().sub(1);
+      }
+      if (fp.isNull()) throw new Error(`frame ${message.frame} has no frame pointer`);
+      const data = fp.sub(512).readByteArray(640);
+      send({
+        type: 'frame_response',
+        address: pc.sub(this.tracer.getSlide()).toString(),
+        registers: message.frame === 0 ? registers : {},
+        frameBase: fp.toString(),
+        frameMemory: data ? _arrayBufferToHex(data) : null,
+      });
+    } catch (e: any) {
+      send({ type: 'frame_response', error: e.message });
+    }
+  }
+
   private readSingleTarget(recipe: ReadRecipe, slide: NativePointer): any {
     try {
       // Raw user-provided addresses are already absolute — don't apply ASLR slide
@@ -896,6 +1242,19 @@ class StrobeAgent {
         ? ptr(recipe.address)
         : ptr(recipe.address).add(slide);
 
+      if (recipe.layout) {
+        const layout = recipe.layout;
+        // Struct pointers keep answering with the fields of what they point at
+        if (layout.kind === 'pointer' && layout.pointee) {
+          const target = baseAddr.readPointer();
+          if (target.isNull()) {
+            return { label: recipe.label, error: `Null pointer at ${recipe.label}` };
+          }
+          return { label: recipe.label, fields: renderLayout(target, layout.pointee) };
+        }
+        return { label: recipe.label, value: renderLayout(baseAddr, layout) };
+      }
+
       // Handle struct reads
       if (recipe.struct && recipe.fields) {
         const structPtr = baseAddr.readPointer();
@@ -933,7 +1292,7 @@ class StrobeAgent {
       if (recipe.typeKind === 'bytes') {
         const bytes = baseAddr.readByteArray(recipe.size);
         if (!bytes) return { label: recipe.label, error: 'Failed to read bytes' };
-        return { label: recipe.label, value: _arrayBufferToHex(bytes), isBytes: true };
+        return { label: recipe.label, value: _arrayBufferToHex(bytes), isBytes: true, address: baseAddr.toString() };
       }
 
       const value = this.readTypedValue(baseAddr, recipe.size, recipe.typeKind);
@@ -943,6 +1302,28 @@ class StrobeAgent {
     }
   }
 
+  private renderParam(args: InvocationArguments, param: LogpointParam): string {
+    try {
+      const value = args[param.arg];
+      let addr: NativePointer | null = null;
+      for (const op of param.ops) {
+        addr = op === 'deref' ? (addr === null ? value : addr.readPointer()) : addr!.add(op);
+        if (addr.isNull()) return '<null>';
+      }
+      if (addr !== null) return String(this.readTypedValue(addr, param.size, param.typeKind));
+      // Held in the register itself
+      if (param.typeKind === 'pointer') return value.toString();
+      if (param.size === 8) {
+        const negative = param.typeKind === 'int' && value.shr(63).toUInt32() === 1;
+        return negative ? `-${ptr(0).sub(value).toString(10)}` : value.toString(10);
+      }
+      const bits = value.toUInt32() & (param.size === 4 ? 0xffffffff : (1 << (param.size * 8)) - 1);
+      return String(param.typeKind === 'int' ? signExtend(bits, param.size) : bits >>> 0);
+    } catch {
+      return '<unreadable>';
+    }
+  }
+
   private readTypedValue(addr: NativePointer, size: number, typeKind: string): any {
     // Note: Process.findRangeByAddress() can hang on large macOS binaries with
     // unmapped addresses — skip the pre-check and rely on try/catch in the caller.
@@ -1041,7 +1422,7 @@ class StrobeAgent {
   private startReadPoll(
     recipes: ReadRecipe[],
     slide: NativePointer,
-    poll: { intervalMs: number; durationMs: number }
+    poll: { intervalMs: number; durationMs: number; pollId: string }
   ): void {
     // Cancel any existing poll before starting a new one
     if (this.activePollTimer !== null) {
@@ -1061,6 +1442,8 @@ class StrobeAgent {
         return;
       }
 
+      // One timestamp for the whole pass so targets line up per sample
+      const timestampNs = this.getTimestampNs();
       const data: Record<string, any> = {};
       for (const recipe of recipes) {
         const result = this.readSingleTarget(recipe, slide);
@@ -1077,10 +1460,11 @@ class StrobeAgent {
       send({
         type: 'events',
         events: [{
-          id: `${this.sessionId}-snap-${sampleCount}`,
-          timestampNs: this.getTimestampNs(),
+          id: `${this.sessionId}-${poll.pollId}-${sampleCount}`,
+          timestampNs,
           threadId: Process.getCurrentThreadId(),
           eventType: 'variable_snapshot',
+          pollId: poll.pollId,
           data,
         }],
       });
@@ -1107,7 +1491,7 @@ class StrobeAgent {
         if (self.inOutputCapture) return;
 
         const fd = args[0].toInt32();
-        if (fd !== 1 && fd !== 2) return;
+        if (fd !== 1 && fd !== 2 && !self.captureFds.has(fd)) return;
 
         // Check per-session output limit
         if (self.outputBytesCapture >= self.maxOutputBytes) return;
@@ -1143,6 +1527,7 @@ class StrobeAgent {
           }
 
           if (text.length === 0) return;
+          if (fd === 2) self.panicHooks.noteStderr(text);
 
           self.outputBytesCapture += count;
 
@@ -1198,6 +1583,7 @@ class StrobeAgent {
         // Notify daemon of pause
         const threadId = Process.getCurrentThreadId();
         self.pausedThreads.set(threadId, bp.id);
+        self.pausedRegisters.set(threadId, captureRegisters(this.context));
 
         // Capture return address for step-out support
         // ARM64: LR register, x86_64: [RBP+8] - Frida's returnAddress handles both
@@ -1231,6 +1617,8 @@ class StrobeAgent {
           }
         }
 
+        self.flushFlightRecorder('breakpoint');
+
         send({
           type: 'paused',
           threadId,
@@ -1252,6 +1640,7 @@ class StrobeAgent {
         op.wait(); // CRITICAL: Blocks native thread, releases JS lock
 
         self.pausedThreads.delete(threadId);
+        self.pausedRegisters.delete(threadId);
       },
     });
 
@@ -1289,6 +1678,7 @@ class StrobeAgent {
       onEnter: (args) => {
         const lp = this.logpoints.get(msg.id);
         if (!lp) return;
+        if (lp.maxHits > 0 && lp.logged >= lp.maxHits) return;
 
         // Evaluate condition if present
         if (lp.condition) {
@@ -1297,13 +1687,20 @@ class StrobeAgent {
             for (let i = 0; i < 10; i++) {
               try { argsArray.push(args[i]); } catch { break; }
             }
-            const result = new Function('args', `return (${lp.condition})`)(argsArray);
+            const result = compileCondition(lp.condition)(argsArray, this.readWatch);
             if (!Boolean(result)) return;
           } catch {
             return; // Condition evaluation failed, skip
           }
         }
 
+        // Hit filters: from the hitCount-th hit, every everyN-th one
+        lp.hits++;
+        const first = Math.max(lp.hitCount, 1);
+        if (lp.hits < first) return;
+        if (lp.everyN > 1 && (lp.hits - first) % lp.everyN !== 0) return;
+        lp.logged++;
+
         // Evaluate message template
         let evaluatedMessage = lp.message;
         try {
@@ -1318,6 +1715,10 @@ class StrobeAgent {
           });
           // Replace {threadId} placeholder
           evaluatedMessage = evaluatedMessage.replace(/\{threadId\}/g, String(Process.getCurrentThreadId()));
+          // Replace named parameter placeholders
+          for (const param of lp.params) {
+            evaluatedMessage = evaluatedMessage.split(`{${param.name}}`).join(this.renderParam(args, param));
+          }
         } catch (e) {
           evaluatedMessage = `[logpoint eval error: ${e}]`;
         }
@@ -1326,7 +1727,7 @@ class StrobeAgent {
         send({
           type: 'events',
           events: [{
-            id: `${this.sessionId}-logpoint-${++this.eventSeq}`,
+            id: this.logpointIds.next(this.sessionId, Process.getCurrentThreadId()),
             timestampNs: this.getTimestampNs(),
             threadId: Process.getCurrentThreadId(),
             eventType: 'logpoint',
@@ -1337,6 +1738,12 @@ class StrobeAgent {
             line: lp.line,
           }],
         });
+
+        // Nothing more to log, so stop paying for the hook
+        if (lp.maxHits > 0 && lp.logged >= lp.maxHits && !lp.detached) {
+          lp.detached = true;
+          lp.listener.detach();
+        }
       },
     });
 
@@ -1345,7 +1752,14 @@ class StrobeAgent {
       address,
       message: msg.message,
       condition: msg.condition,
+      hitCount: msg.hitCount ?? 0,
+      everyN: msg.everyN ?? 0,
+      maxHits: msg.maxHits ?? 0,
+      hits: 0,
+      logged: 0,
+      params: msg.params ?? [],
       listener,
+      detached: false,
       funcName: msg.funcName,
       file: msg.file,
       line: msg.line,
@@ -1364,12 +1778,300 @@ class StrobeAgent {
     const lp = this.logpoints.get(id);
     if (!lp) return;
 
-    lp.listener.detach();
+    if (!lp.detached) lp.listener.detach();
     this.logpoints.delete(id);
 
     send({ type: 'logpointRemoved', id });
   }
 
+  /**
+   * Replace the active data watchpoints. MemoryAccessMonitor works at page
+   * granularity and disarms a page after its first access, so every hit
+   * (including reads or writes to neighbouring data on the same page) re-arms
+   * the whole monitor on the next tick.
+   */
+  setWatchpoints(msg: SetWatchpointsMessage): void {
+    if (msg.imageBase) {
+      this.tracer.setImageBase(msg.imageBase);
+    }
+    const slide = this.tracer.getSlide();
+
+    try {
+      MemoryAccessMonitor.disable();
+    } catch (_) {
+      // Not enabled yet
+    }
+
+    this.watchpoints = msg.watchpoints.map(spec => {
+      const base = spec.noSlide ? ptr(spec.address) : ptr(spec.address).add(slide);
+      return { spec, base, end: base.add(spec.size) };
+    });
+
+    if (this.watchpoints.length > 0) {
+      try {
+        this.armWatchpoints();
+      } catch (e: any) {
+        this.watchpoints = [];
+        send({ type: 'log', message: `setWatchpoints error: ${e.message}` });
+      }
+    }
+
+    send({ type: 'watchpointsSet', activeCount: this.watchpoints.length });
+  }
+
+  /**
+   * Start, retarget or stop periodic stack sampling. Every tick snapshots the
+   * selected threads and sends one batch of sample events; the leaf frame is
+   * the thread's pc, the rest comes from the fuzzy backtracer since a
+   * suspended thread's frame pointers can't be trusted.
+   */
+  setSampling(msg: SetSamplingMessage): void {
+    if (this.stackSamplingTimer !== null) {
+      clearInterval(this.stackSamplingTimer);
+      this.stackSamplingTimer = null;
+    }
+
+    if (msg.hz > 0) {
+      const ids = msg.threadIds && msg.threadIds.length > 0 ? new Set(msg.threadIds) : null;
+      const name = msg.threadName || null;
+      this.stackSamplingTimer = setInterval(
+        () => this.takeSamples(ids, name),
+        Math.max(1, Math.round(1000 / msg.hz)));
+      (this.stackSamplingTimer as any).unref?.();
+    } else {
+      this.sampleSymbols.clear();
+    }
+
+    send({ type: 'samplingSet', hz: msg.hz });
+  }
+
+  /** Restrict traced calls (native tracing) to some threads. */
+  setThreadFilter(msg: SetThreadFilterMessage): void {
+    this.cmoduleTracer.setThreadFilter(msg.ids, msg.nameContains);
+    send({ type: 'threadFilterSet' });
+  }
+
+  /** Cap recorded calls per traced function (native tracing). */
+  setRateLimit(msg: SetRateLimitMessage): void {
+    this.cmoduleTracer.setRateLimit(msg.maxPerSecond, msg.sampleEvery);
+    send({ type: 'rateLimitSet' });
+  }
+
+  setSignalSafety(msg: SignalSafetyMessage): void {
+    let handlers = 0;
+    try {
+      handlers = this.signalSafety.configure(msg);
+    } catch (e: any) {
+      send({ type: 'log', message: `setSignalSafety error: ${e.message}` });
+    }
+    send({ type: 'signalSafetySet', activeCount: handlers });
+  }
+
+  setSyscalls(msg: SyscallsMessage): void {
+    let hooked = 0;
+    try {
+      hooked = this.syscalls.configure(msg);
+    } catch (e: any) {
+      send({ type: 'log', message: `setSyscalls error: ${e.message}` });
+    }
+    send({ type: 'syscallsSet', activeCount: hooked });
+  }
+
+  setNetwork(msg: NetworkMessage): void {
+    let hooked = 0;
+    try {
+      hooked = this.network.configure(msg);
+    } catch (e: any) {
+      send({ type: 'log', message: `setNetwork error: ${e.message}` });
+    }
+    send({ type: 'networkSet', activeCount: hooked });
+  }
+
+  setExceptions(msg: ExceptionsMessage): void {
+    let hooked = 0;
+    try {
+      hooked = this.exceptions.configure(msg);
+    } catch (e: any) {
+      send({ type: 'log', message: `setExceptions error: ${e.message}` });
+    }
+    send({ type: 'exceptionsSet', activeCount: hooked });
+  }
+
+  setSignals(msg: SignalsMessage): void {
+    let hooked = 0;
+    try {
+      hooked = this.signals.configure(msg);
+    } catch (e: any) {
+      send({ type: 'log', message: `setSignals error: ${e.message}` });
+    }
+    send({ type: 'signalsSet', activeCount: hooked });
+  }
+
+  setLocks(msg: LocksMessage): void {
+    let hooked = 0;
+    try {
+      hooked = this.locks.configure(msg);
+    } catch (e: any) {
+      send({ type: 'log', message: `setLocks error: ${e.message}` });
+    }
+    send({ type: 'locksSet', activeCount: hooked });
+  }
+
+  /** Point the crash handler at the globals it dumps into the crash event. */
+  setCrashCapture(msg: SetCrashCaptureMessage): void {
+    if (msg.imageBase) {
+      this.tracer.setImageBase(msg.imageBase);
+    }
+    const slide = this.tracer.getSlide();
+    this.crashRegions = msg.regions.map(global => ({
+      global,
+      base: global.address ? ptr(global.address).add(slide) : null,
+    }));
+    const active = this.crashRegions.filter(r => r.base !== null).length;
+    send({ type: 'crashCaptureSet', activeCount: active });
+  }
+
+  /** Buffer traced calls (native tracing) until a trigger fires. */
+  setFlightRecorder(msg: FlightRecorderMessage): void {
+    if (!msg.enabled) {
+      this.flushFlightRecorder('disabled');
+    }
+    this.flightRecorder.configure(msg);
+    send({ type: 'flightRecorderSet' });
+  }
+
+  /** Drain in-flight traced calls into the recorder, then send them all. */
+  flushFlightRecorder(reason: string): number {
+    if (!this.flightRecorder.enabled) return 0;
+    this.cmoduleTracer.drainNow();
+    return this.flightRecorder.flush(reason);
+  }
+
+  private takeSamples(ids: Set<number> | null, name: string | null): void {
+    let threads: ThreadDetails[];
+    try {
+      threads = Process.enumerateThreads();
+    } catch (_) {
+      return;
+    }
+
+    const timestampNs = this.getTimestampNs();
+    const events: any[] = [];
+    for (const t of threads) {
+      if (ids && !ids.has(t.id)) continue;
+      const threadName: string | null = (t as any).name ?? null;
+      if (name && !(threadName && threadName.includes(name))) continue;
+
+      const leaf = this.symbolizeSample(t.context.pc);
+      let callers: BacktraceFrame[] = [];
+      try {
+        callers = Thread.backtrace(t.context, Backtracer.FUZZY)
+          .slice(0, SAMPLE_MAX_FRAMES - 1)
+          .map(addr => this.symbolizeSample(addr));
+      } catch (_) {
+        // Unwalkable stack — keep the leaf frame alone
+      }
+
+      events.push({
+        id: this.sampleIds.next(this.sessionId, t.id),
+        timestampNs,
+        threadId: t.id,
+        threadName,
+        eventType: 'sample',
+        pid: Process.id,
+        functionName: leaf.name ?? leaf.address,
+        file: leaf.fileName,
+        line: leaf.lineNumber,
+        backtrace: [leaf, ...callers],
+      });
+    }
+
+    if (events.length > 0) {
+      send({ type: 'events', events });
+    }
+  }
+
+  private symbolizeSample(addr: NativePointer): BacktraceFrame {
+    const key = addr.toString();
+    let frame = this.sampleSymbols.get(key);
+    if (frame === undefined) {
+      if (this.sampleSymbols.size >= SAMPLE_SYMBOL_CACHE_MAX) {
+        this.sampleSymbols.clear();
+      }
+      frame = symbolizeFrame(addr);
+      this.sampleSymbols.set(key, frame);
+    }
+    return frame;
+  }
+
+  private armWatchpoints(): void {
+    const ranges = this.watchpoints.map(wp => ({ base: wp.base, size: wp.spec.size }));
+    MemoryAccessMonitor.enable(ranges, {
+      onAccess: (details) => this.onWatchpointAccess(details),
+    });
+  }
+
+  private onWatchpointAccess(details: MemoryAccessDetails): void {
+    const wp = this.watchpoints.find(
+      w => details.address.compare(w.base) >= 0 && details.address.compare(w.end) < 0
+    );
+
+    if (wp && details.operation === 'write') {
+      let backtrace: BacktraceFrame[] = [];
+      const ctx = (details as any).context as CpuContext | undefined;
+      if (ctx) {
+        try {
+          backtrace = Thread.backtrace(ctx, Backtracer.ACCURATE).map((addr: NativePointer) => {
+            const sym = DebugSymbol.fromAddress(addr);
+            return {
+              address: addr.toString(),
+              moduleName: sym.moduleName,
+              name: sym.name,
+              fileName: sym.fileName,
+              lineNumber: sym.lineNumber,
+            };
+          });
+        } catch (_) {
+          // Backtrace may fail in some contexts
+        }
+      }
+      const from = DebugSymbol.fromAddress(details.from);
+
+      send({
+        type: 'events',
+        events: [{
+          id: this.watchpointIds.next(this.sessionId, Process.getCurrentThreadId()),
+          timestampNs: this.getTimestampNs(),
+          threadId: Process.getCurrentThreadId(),
+          eventType: 'watchpoint',
+          pid: Process.id,
+          watchpointId: wp.spec.id,
+          label: wp.spec.label,
+          address: details.address.toString(),
+          functionName: from.name ?? details.from.toString(),
+          file: from.fileName,
+          line: from.lineNumber,
+          backtrace,
+        }],
+      });
+    }
+
+    // The page is now unprotected; re-arm once per tick regardless of which
+    // access tripped it, otherwise later writes would go unnoticed.
+    if (this.watchpointRearmPending) return;
+    this.watchpointRearmPending = true;
+    setTimeout(() => {
+      this.watchpointRearmPending = false;
+      if (this.watchpoints.length === 0) return;
+      try {
+        MemoryAccessMonitor.disable();
+        this.armWatchpoints();
+      } catch (e: any) {
+        send({ type: 'log', message: `watchpoint re-arm failed: ${e.message}` });
+      }
+    }, 0);
+  }
+
   /** Called by PythonTracer's bpHitCallback when a Python breakpoint is reached. */
   emitBreakpointHit(id: string, line: number): void {
     send({
@@ -1455,6 +2157,7 @@ class StrobeAgent {
 
             const tid = Process.getCurrentThreadId();
             self.pausedThreads.set(tid, stepId);
+            self.pausedRegisters.set(tid, captureRegisters(this.context));
 
             // For noSlide entries (return addresses), convert runtime → DWARF-static
             // so the daemon can compute next-line for further stepping.
@@ -1483,6 +2186,7 @@ class StrobeAgent {
             op.wait();
 
             self.pausedThreads.delete(tid);
+            self.pausedRegisters.delete(tid);
           },
         });
         listeners.push(stepListener);
@@ -1545,6 +2249,13 @@ class StrobeAgent {
     return this.cmoduleTracer.activeHookCount();
   }
 
+  /** `watch:<label>` in a condition; throws for a label with no watch. */
+  private readWatch = (label: string): unknown => {
+    const value = this.cmoduleTracer.readWatch(label);
+    if (value === undefined) throw new Error(`no watch labelled '${label}'`);
+    return value;
+  };
+
   private evaluateCondition(condition: string, args: InvocationArguments, breakpointId?: string): boolean {
     try {
       // Convert args to array for Function context
@@ -1557,7 +2268,7 @@ class StrobeAgent {
         }
       }
 
-      const result = new Function('args', `return (${condition})`)(argsArray);
+      const result = compileCondition(condition)(argsArray, this.readWatch);
       return Boolean(result);
     } catch (e) {
       send({
@@ -1571,6 +2282,52 @@ class StrobeAgent {
   }
 }
 
+// `watch:<label>` in a breakpoint or logpoint condition: a label is a name
+// with any `.member` / `->member` / `::` parts, like the watch expressions
+const WATCH_REF = /(^|[^\w$.])watch:([A-Za-z_$][\w$.:]*(?:->[A-Za-z_$][\w$.:]*)*)/g;
+
+/** A condition as a function of the arguments and a watch reader. */
+function compileCondition(condition: string): (args: any[], watch: (label: string) => unknown) => unknown {
+  const body = condition.replace(WATCH_REF, (_m, before, label) => `${before}watch(${JSON.stringify(label)})`);
+  return new Function('args', 'watch', `return (${body})`) as (args: any[], watch: (label: string) => unknown) => unknown;
+}
+
+function symbolizeFrame(addr: NativePointer): BacktraceFrame {
+  const sym = DebugSymbol.fromAddress(addr);
+  return {
+    address: addr.toString(),
+    moduleName: sym.moduleName,
+    name: sym.name,
+    fileName: sym.fileName,
+    lineNumber: sym.lineNumber,
+  };
+}
+
+/** General-purpose registers of a native CPU context, as hex strings. */
+function captureRegisters(context: CpuContext): Record<string, string> {
+  const registers: Record<string, string> = {};
+  const ctx = context as any;
+  // ARM64 registers
+  if (Process.arch === 'arm64') {
+    for (let i = 0; i <= 28; i++) {
+      const regName = `x${i}`;
+      if (ctx[regName]) registers[regName] = ctx[regName].toString();
+    }
+    if (ctx.fp) registers.fp = ctx.fp.toString();
+    if (ctx.lr) registers.lr = ctx.lr.toString();
+    if (ctx.sp) registers.sp = ctx.sp.toString();
+    if (ctx.pc) registers.pc = ctx.pc.toString();
+  }
+  // x86_64 registers
+  else if (Process.arch === 'x64') {
+    for (const reg of ['rax','rbx','rcx','rdx','rsi','rdi','rbp','rsp',
+                       'r8','r9','r10','r11','r12','r13','r14','r15','rip']) {
+      if (ctx[reg]) registers[reg] = ctx[reg].toString();
+    }
+  }
+  return registers;
+}
+
 function _arrayBufferToHex(buffer: ArrayBuffer): string {
   const bytes = new Uint8Array(buffer);
   let hex = '';
@@ -1593,10 +2350,10 @@ try {
 send({ type: 'agent_loaded', message: 'Strobe agent loaded and ready' });
 
 // Message handler
-recv('initialize', (message: { sessionId: string }) => {
+recv('initialize', (message: { sessionId: string; captureFds?: number[]; reloadGeneration?: number }) => {
   send({ type: 'log', message: 'Received initialize: ' + JSON.stringify(message) });
   try {
-    agent.initialize(message.sessionId);
+    agent.initialize(message.sessionId, message.captureFds ?? [], message.reloadGeneration ?? 0);
     send({ type: 'log', message: 'Initialize completed successfully' });
   } catch (e: any) {
     send({ type: 'log', message: 'Initialize CRASHED: ' + e.message + '\n' + e.stack });
@@ -1668,6 +2425,90 @@ function onRemoveLogpointMessage(message: RemoveBreakpointMessage): void {
 }
 recv('removeLogpoint', onRemoveLogpointMessage);
 
+function onSetWatchpointsMessage(message: SetWatchpointsMessage): void {
+  recv('setWatchpoints', onSetWatchpointsMessage);
+  agent.setWatchpoints(message);
+}
+recv('setWatchpoints', onSetWatchpointsMessage);
+
+function onSetSamplingMessage(message: SetSamplingMessage): void {
+  recv('setSampling', onSetSamplingMessage);
+  agent.setSampling(message);
+}
+recv('setSampling', onSetSamplingMessage);
+
+function onSetThreadFilterMessage(message: SetThreadFilterMessage): void {
+  recv('setThreadFilter', onSetThreadFilterMessage);
+  agent.setThreadFilter(message);
+}
+recv('setThreadFilter', onSetThreadFilterMessage);
+
+function onSetRateLimitMessage(message: SetRateLimitMessage): void {
+  recv('setRateLimit', onSetRateLimitMessage);
+  agent.setRateLimit(message);
+}
+recv('setRateLimit', onSetRateLimitMessage);
+
+function onSetSignalSafetyMessage(message: SignalSafetyMessage): void {
+  recv('setSignalSafety', onSetSignalSafetyMessage);
+  agent.setSignalSafety(message);
+}
+recv('setSignalSafety', onSetSignalSafetyMessage);
+
+function onSetSyscallsMessage(message: SyscallsMessage): void {
+  recv('setSyscalls', onSetSyscallsMessage);
+  agent.setSyscalls(message);
+}
+recv('setSyscalls', onSetSyscallsMessage);
+
+function onSetNetworkMessage(message: NetworkMessage): void {
+  recv('setNetwork', onSetNetworkMessage);
+  agent.setNetwork(message);
+}
+recv('setNetwork', onSetNetworkMessage);
+
+function onSetExceptionsMessage(message: ExceptionsMessage): void {
+  recv('setExceptions', onSetExceptionsMessage);
+  agent.setExceptions(message);
+}
+recv('setExceptions', onSetExceptionsMessage);
+
+function onSetSignalsMessage(message: SignalsMessage): void {
+  recv('setSignals', onSetSignalsMessage);
+  agent.setSignals(message);
+}
+recv('setSignals', onSetSignalsMessage);
+
+function onSetLocksMessage(message: LocksMessage): void {
+  recv('setLocks', onSetLocksMessage);
+  agent.setLocks(message);
+}
+recv('setLocks', onSetLocksMessage);
+
+function onSetCrashCaptureMessage(message: SetCrashCaptureMessage): void {
+  recv('setCrashCapture', onSetCrashCaptureMessage);
+  agent.setCrashCapture(message);
+}
+recv('setCrashCapture', onSetCrashCaptureMessage);
+
+function onSetFlightRecorderMessage(message: FlightRecorderMessage): void {
+  recv('setFlightRecorder', onSetFlightRecorderMessage);
+  agent.setFlightRecorder(message);
+}
+recv('setFlightRecorder', onSetFlightRecorderMessage);
+
+// Explicit flush, answered with flight_recorder_response
+function onFlushFlightRecorderMessage(): void {
+  recv('flushFlightRecorder', onFlushFlightRecorderMessage);
+  try {
+    const flushed = agent.flushFlightRecorder('request');
+    send({ type: 'flight_recorder_response', flushed });
+  } catch (e: any) {
+    send({ type: 'flight_recorder_response', error: e.message });
+  }
+}
+recv('flushFlightRecorder', onFlushFlightRecorderMessage);
+
 // Phase 2: Step hook installation (sent as separate message before resume)
 function onInstallStepHooksMessage(message: InstallStepHooksMessage): void {
   recv('installStepHooks', onInstallStepHooksMessage);
@@ -1692,6 +2533,41 @@ function onEvalVariableMessage(message: { expr: string; label?: string }): void
 }
 recv('eval_variable', onEvalVariableMessage);
 
+// Objective-C pattern lookup for debug_trace
+function onResolveObjcMessage(message: { pattern: string }): void {
+  recv('resolve_objc', onResolveObjcMessage);
+  agent.resolveObjc(message.pattern);
+}
+recv('resolve_objc', onResolveObjcMessage);
+
+// debug_eval function calls, answered with call_response
+function onCallFunctionMessage(message: CallFunctionMessage): void {
+  recv('call_function', onCallFunctionMessage);
+  agent.callFunction(message);
+}
+recv('call_function', onCallFunctionMessage);
+
+// debug_memory locals of a paused thread, answered with frame_response
+function onReadFrameMessage(message: ReadFrameMessage): void {
+  recv('read_frame', onReadFrameMessage);
+  agent.readFrame(message);
+}
+recv('read_frame', onReadFrameMessage);
+
+// Stalker coverage: start/snapshot/stop, answered with coverage_response
+function onCoverageMessage(message: CoverageMessage): void {
+  recv('coverage', onCoverageMessage);
+  agent.coverage.handle(message);
+}
+recv('coverage', onCoverageMessage);
+
+// Allocation tracking: start/report/stop, answered with heap_response
+function onHeapMessage(message: HeapMessage): void {
+  recv('heap', onHeapMessage);
+  agent.heap.handle(message);
+}
+recv('heap', onHeapMessage);
+
 // Runtime resolve message handler for agent-side resolution fallback
 function onResolveMessage(message: { patterns: string[] }): void {
   recv('resolve', onResolveMessage);
@@ -4,28 +4,56 @@
  * Uses a native CModule with a shared ring buffer to record function
  * enter/leave events at near-zero per-call overhead. A JS timer drains
  * the ring buffer every 10ms and forwards structured event JSON to the
- * daemon via send().
+ * daemon via send(). Count-mode hooks bypass the ring buffer and only bump
+ * a per-hook counter, reported as a call_counts event once a second.
+ *
+ * RT-mode hooks are for real-time threads (audio callbacks): they are only
+ * ever native, never fall back to a JS hook, and make no calls out of the
+ * hook, so the hooked thread does no more than a few stores into the
+ * preallocated ring and never waits on a lock held by a lower-priority thread.
+ * Calls from normal hooks that land on a thread with a real-time scheduling
+ * policy are reported once per thread and function as rt_warning events.
  */
 
-import { ObjectSerializer, TypeInfo } from './object-serializer.js';
+import { ObjectSerializer, TypeInfo, type ContainerLayout } from './object-serializer.js';
 import { PlatformAdapter } from './platform.js';
-import { reinterpretAsFloat, signExtend } from './utils.js';
+import { EventIdAllocator, reinterpretAsFloat, signExtend } from './utils.js';
 
 // ---------------------------------------------------------------------------
 // Public types
 // ---------------------------------------------------------------------------
 
-export type HookMode = 'full' | 'light';
+export type HookMode = 'full' | 'light' | 'count' | 'rt';
 
 /** Callback for per-function rate checking. Returns true if the event should be recorded. */
 export type RateCheckFn = (funcId: number) => boolean;
 
+/** Per-function call counts for the user rate limit, over one-second windows. */
+interface RateWindow {
+  start: number;           // Window start, ms
+  calls: number;           // Calls seen since the limit was set (drives 1-in-K)
+  recorded: number;        // Calls kept in this window
+  dropped: number;         // Calls dropped in this window
+  droppedBefore: boolean;  // The previous window dropped calls
+}
+
+const RATE_WINDOW_MS = 1000;
+
 export interface FunctionTarget {
   address: string;
   name: string;
   nameRaw?: string;
   sourceFile?: string;
   lineNumber?: number;
+  argLayouts?: (ContainerLayout | null)[];  // DWARF container layouts for arg0/arg1
+  returnLayout?: ContainerLayout;           // Set only for pointer/reference returns
+  goidOffset?: number;                      // Go: offset of goid in runtime.g (set only on Go functions)
+  module?: string;                          // Symbol module (symbolExtra); address is relative to its base
+}
+
+/** Hook map key: module-relative addresses can collide with main-binary ones. */
+export function hookKey(func: { address: string; module?: string }): string {
+  return func.module ? `${func.module}!${func.address}` : func.address;
 }
 
 interface TraceEvent {
@@ -34,8 +62,11 @@ interface TraceEvent {
   timestampNs: number;
   threadId: number;
   threadName?: string | null;
+  queueName?: string;
   parentEventId: string | null;
   eventType: 'function_enter' | 'function_exit';
+  // Shared by a call's enter and exit: the enter's id
+  callId?: string;
   functionName: string;
   functionNameRaw?: string;
   sourceFile?: string;
@@ -44,7 +75,30 @@ interface TraceEvent {
   returnValue?: string;
   durationNs?: number;
   sampled?: boolean;
-  watchValues?: Record<string, number | string>;
+  watchValues?: Record<string, unknown>;
+}
+
+interface CallCountsEvent {
+  id: string;
+  sessionId: string;
+  timestampNs: number;
+  pid: number;
+  eventType: 'call_counts';
+  counts: Array<{ function: string; address: string; count: number }>;
+}
+
+interface RtWarningEvent {
+  id: string;
+  sessionId: string;
+  timestampNs: number;
+  threadId: number;
+  threadName?: string | null;
+  pid: number;
+  eventType: 'rt_warning';
+  functionName: string;
+  policy: string;
+  jsHook: boolean;
+  text: string;
 }
 
 interface WatchConfig {
@@ -53,6 +107,41 @@ interface WatchConfig {
   typeKind: 'int' | 'uint' | 'float' | 'pointer';
   isGlobal: boolean;
   onFuncIds: Set<number>;
+  delta: DeltaFilter | null;
+}
+
+// Differential watch: a value is emitted only when it moved by at least
+// `threshold` since the last emitted value; reads in between are counted.
+interface DeltaFilter {
+  threshold: number;
+  last: unknown;
+  emitted: boolean;
+  skipped: number;
+}
+
+function makeDeltaFilter(threshold?: number): DeltaFilter | null {
+  return threshold && threshold > 0
+    ? { threshold, last: undefined, emitted: false, skipped: 0 }
+    : null;
+}
+
+// Returns the value to record ({ value, skipped }), or undefined to drop it.
+// Non-numeric values are emitted whenever they differ from the last one.
+function applyDelta(filter: DeltaFilter, value: unknown): unknown {
+  if (filter.emitted) {
+    const changed = typeof value === 'number' && typeof filter.last === 'number'
+      ? Math.abs(value - filter.last) >= filter.threshold
+      : value !== filter.last;
+    if (!changed) {
+      filter.skipped++;
+      return undefined;
+    }
+  }
+  const recorded = { value, skipped: filter.skipped };
+  filter.last = value;
+  filter.emitted = true;
+  filter.skipped = 0;
+  return recorded;
 }
 
 // ---------------------------------------------------------------------------
@@ -60,8 +149,14 @@ interface WatchConfig {
 // ---------------------------------------------------------------------------
 
 const RING_CAPACITY = 16384;
-const ENTRY_SIZE = 80;
+const ENTRY_SIZE = 104;
 const HEADER_SIZE = 128;
+// Header slot holding dispatch_queue_get_label (NULL when libdispatch is absent)
+const QUEUE_LABEL_FN_OFFSET = 104;
+// Header slot holding the goid offset in Go's runtime.g (0 until a Go function is hooked)
+const GOID_OFFSET_OFFSET = 112;
+// Header slot holding dispatch_get_current_queue (NULL when libdispatch is absent)
+const CURRENT_QUEUE_FN_OFFSET = 120;
 const RING_BUFFER_SIZE = HEADER_SIZE + RING_CAPACITY * ENTRY_SIZE;
 
 // Adaptive sampling thresholds
@@ -74,18 +169,55 @@ const MIN_SAMPLE_INTERVAL = 1;
 
 const DRAIN_INTERVAL_MS = 10;
 
+// Count mode: one 32-bit counter per counted hook. Totals are accumulated in
+// JS from wrapping deltas, so a counter only has to survive one flush interval.
+const COUNT_CAPACITY = 8192;
+const COUNT_FLUSH_INTERVAL_MS = 1000;
+
+// rt_warning events: one per (thread, function), capped per session
+const MAX_RT_WARNINGS = 256;
+
+// Resolved dispatch queue labels, per (queue, label pointer); cleared when full
+const MAX_QUEUE_LABELS = 1024;
+
+// Thread filter table read by the hooks: mode, allowed count, blocked count,
+// then the allowed and blocked thread ids. Mode 0 is off, 1 admits only the
+// allowed ids, 2 also admits threads not yet decided so the drain can match
+// their names and file them under allowed or blocked.
+const THREAD_FILTER_ALLOWED = 64;
+const THREAD_FILTER_BLOCKED = 256;
+const THREAD_FILTER_SIZE = (3 + THREAD_FILTER_ALLOWED + THREAD_FILTER_BLOCKED) * 4;
+
 // ---------------------------------------------------------------------------
 // CModule C source
 // ---------------------------------------------------------------------------
-// Single CModule with onEnter + onLeave. Per-hook mode (full vs light) is
-// encoded in the data pointer's low bit:
-//   data = (func_id << 1) | is_light
-// The shift limits func_id to 2^30 (1 billion) to prevent signed 32-bit overflow
+// Single CModule with onEnter + onLeave. Per-hook mode (full, light or rt) and
+// whether the hook is a Go function are encoded in the data pointer's low bits:
+//   data = (func_id << 3) | (is_rt << 2) | (is_go << 1) | is_light
+// The shift limits func_id to 2^28 to prevent signed 32-bit overflow
 // (2^31 - 1 = 2,147,483,647). In practice, hook cap of 100 means we never approach this.
+// RT hooks skip the dispatch queue label lookup: it calls into libdispatch.
+// Go functions also record the running goroutine's id, read from the g pointer
+// the register ABI (Go 1.17+) keeps in a fixed register.
 // In onEnter: light hooks check sampling, full hooks don't.
 // In onLeave: both modes write exit events. Per-invocation data pairs enter/exit
 // so exits are only written when the corresponding enter passed sampling.
 
+/**
+ * STROBE_G_REG reads Go's current g pointer: Go 1.17+ keeps it in R14 on
+ * x64 and X28 on arm64. Elsewhere no goroutine id is recorded.
+ */
+function goRegisterPreamble(): string {
+  switch (Process.arch) {
+    case 'x64':
+      return '#define STROBE_G_REG(ic) ((guint64)(ic)->cpu_context->r14)\n';
+    case 'arm64':
+      return '#define STROBE_G_REG(ic) ((guint64)(ic)->cpu_context->x[28])\n';
+    default:
+      return '#define STROBE_G_REG(ic) ((guint64)0)\n';
+  }
+}
+
 const CMODULE_SOURCE = `
 #include <gum/guminterceptor.h>
 #include <glib.h>
@@ -95,6 +227,8 @@ extern volatile gint overflow_count;
 extern volatile gint sample_interval;
 extern volatile gint global_counter;
 extern guint8 *ring_data;
+extern volatile gint call_counts[${COUNT_CAPACITY}];
+extern volatile gint thread_filter[3 + ${THREAD_FILTER_ALLOWED} + ${THREAD_FILTER_BLOCKED}];
 
 extern volatile gint watch_count;
 extern guint64 watch_addrs[4];
@@ -102,8 +236,14 @@ extern guint8 watch_sizes[4];
 extern guint8 watch_deref_depths[4];
 extern guint64 watch_deref_offsets[4];
 
+typedef const char * (*StrobeQueueLabelFn)(gpointer queue);
+typedef gpointer (*StrobeCurrentQueueFn)(void);
+extern gpointer queue_label_fn;
+extern gpointer current_queue_fn;
+extern guint64 goid_offset;
+
 #define RING_CAPACITY 16384
-#define ENTRY_SIZE 80
+#define ENTRY_SIZE 104
 
 typedef struct {
   guint64 timestamp;
@@ -121,9 +261,12 @@ typedef struct {
   guint64 watch1;
   guint64 watch2;
   guint64 watch3;
+  guint64 queue_label;
+  guint64 goid;
+  guint64 queue;
 } TraceEntry;
 
-static void write_entry(guint32 func_id, GumInvocationContext *ic,
+static void write_entry(guint32 func_id, guint8 is_go, guint8 is_rt, GumInvocationContext *ic,
                          guint8 etype, guint8 samp,
                          guint64 a0, guint64 a1, guint64 rv) {
   gint pos = g_atomic_int_add(&write_idx, 1);
@@ -141,6 +284,17 @@ static void write_entry(guint32 func_id, GumInvocationContext *ic,
   e->arg1       = a1;
   e->retval     = rv;
 
+  /* Label of the dispatch queue running this call (DISPATCH_CURRENT_QUEUE_LABEL == NULL) */
+  gpointer qfn = queue_label_fn;
+  e->queue_label = (qfn != NULL && !is_rt) ? (guint64)(gsize)((StrobeQueueLabelFn)qfn)(NULL) : 0;
+  gpointer cqfn = current_queue_fn;
+  e->queue = (cqfn != NULL && !is_rt) ? (guint64)(gsize)((StrobeCurrentQueueFn)cqfn)() : 0;
+
+  /* Goroutine id: runtime.g lives in a register under Go's register ABI */
+  guint64 g = is_go ? STROBE_G_REG(ic) : 0;
+  guint64 off = goid_offset;
+  e->goid = (g != 0 && off != 0) ? *(volatile guint64*)(gpointer)(g + off) : 0;
+
   /* Read watch values */
   guint32 wc = (guint32)g_atomic_int_add(&watch_count, 0);
 
@@ -192,12 +346,33 @@ static void write_entry(guint32 func_id, GumInvocationContext *ic,
   }
 }
 
+/* Thread filter: filtered-out threads never reach the ring */
+static gboolean thread_admitted(GumInvocationContext *ic) {
+  gint mode = g_atomic_int_add(&thread_filter[0], 0);
+  if (mode == 0) return TRUE;
+  guint32 tid = gum_invocation_context_get_thread_id(ic);
+  gint allowed = g_atomic_int_add(&thread_filter[1], 0);
+  for (gint i = 0; i < allowed; i++) {
+    if ((guint32)thread_filter[3 + i] == tid) return TRUE;
+  }
+  if (mode == 1) return FALSE;
+  gint blocked = g_atomic_int_add(&thread_filter[2], 0);
+  for (gint i = 0; i < blocked; i++) {
+    if ((guint32)thread_filter[3 + ${THREAD_FILTER_ALLOWED} + i] == tid) return FALSE;
+  }
+  return TRUE;
+}
+
 void onEnter(GumInvocationContext *ic) {
   gsize raw = (gsize)gum_invocation_context_get_listener_function_data(ic);
-  guint32 func_id = (guint32)(raw >> 1);
+  guint32 func_id = (guint32)(raw >> 3);
   guint8 is_light = (guint8)(raw & 1);
+  guint8 is_go = (guint8)((raw >> 1) & 1);
+  guint8 is_rt = (guint8)((raw >> 2) & 1);
   guint8 *inv = (guint8 *)gum_invocation_context_get_listener_invocation_data(ic, 1);
 
+  if (!thread_admitted(ic)) { *inv = 0; return; }
+
   if (is_light) {
     gint interval = g_atomic_int_add(&sample_interval, 0);
     if (interval > 1) {
@@ -205,32 +380,44 @@ void onEnter(GumInvocationContext *ic) {
       if ((count % interval) != 0) { *inv = 0; return; }
     }
     *inv = 1;
-    write_entry(func_id, ic, 0, interval > 1 ? 1 : 0,
+    write_entry(func_id, is_go, is_rt, ic, 0, interval > 1 ? 1 : 0,
       (guint64)gum_invocation_context_get_nth_argument(ic, 0),
       (guint64)gum_invocation_context_get_nth_argument(ic, 1),
       0);
   } else {
     *inv = 1;
-    write_entry(func_id, ic, 0, 0,
+    write_entry(func_id, is_go, is_rt, ic, 0, 0,
       (guint64)gum_invocation_context_get_nth_argument(ic, 0),
       (guint64)gum_invocation_context_get_nth_argument(ic, 1),
       0);
   }
 }
 
+/* Count mode: listener data is the counter slot; no ring entry, no onLeave */
+void onCount(GumInvocationContext *ic) {
+  gsize slot = (gsize)gum_invocation_context_get_listener_function_data(ic);
+  g_atomic_int_add(&call_counts[slot], 1);
+}
+
+guint64 strobe_now(void) {
+  return strobe_timestamp();
+}
+
 void onLeave(GumInvocationContext *ic) {
   guint8 *inv = (guint8 *)gum_invocation_context_get_listener_invocation_data(ic, 1);
   if (!*inv) return;
 
   gsize raw = (gsize)gum_invocation_context_get_listener_function_data(ic);
-  guint32 func_id = (guint32)(raw >> 1);
+  guint32 func_id = (guint32)(raw >> 3);
   guint8 is_light = (guint8)(raw & 1);
+  guint8 is_go = (guint8)((raw >> 1) & 1);
+  guint8 is_rt = (guint8)((raw >> 2) & 1);
   guint8 sampled = 0;
   if (is_light) {
     gint interval = g_atomic_int_add(&sample_interval, 0);
     sampled = interval > 1 ? 1 : 0;
   }
-  write_entry(func_id, ic, 1, sampled, 0, 0,
+  write_entry(func_id, is_go, is_rt, ic, 1, sampled, 0, 0,
     (guint64)gum_invocation_context_get_return_value(ic));
 }
 `;
@@ -266,8 +453,28 @@ export class CModuleTracer {
   private funcRegistry: Map<number, FunctionTarget> = new Map();
   private nextFuncId: number = 1;
 
-  // Hook tracking: address string -> { listener, funcId }
-  private hooks: Map<string, { listener: InvocationListener; funcId: number; funcName: string }> = new Map();
+  // Hook tracking: address string -> { listener, funcId, counter slot for count hooks }
+  private hooks: Map<string, { listener: InvocationListener; funcId: number; funcName: string; countSlot?: number }> = new Map();
+
+  // Real-time threads: funcIds hooked in rt mode, scheduling policy by thread
+  // (null: normal), and the (thread, funcId) pairs already warned about
+  private platform: PlatformAdapter;
+  private rtFuncIds: Set<number> = new Set();
+  private rtPolicies: Map<number, string | null> = new Map();
+  private rtWarned: Set<string> = new Set();
+  private rtWarningIds = new EventIdAllocator('rtwarn');
+
+  // Count mode: counter table, slot allocation, and running totals by address.
+  // Totals outlive their hook so removed functions keep their final count.
+  private countTable: NativePointer;
+  private countSlots: Map<number, { address: string; last: number }> = new Map();
+  private freeCountSlots: number[] = [];
+  private nextCountSlot: number = 0;
+  private countTotals: Map<string, { name: string; total: number }> = new Map();
+  private countsDirty: boolean = false;
+  private countIds = new EventIdAllocator('counts');
+  private countTimer: ReturnType<typeof setInterval> | null = null;
+  private nowFn: NativeFunction<UInt64, []> | null = null;
 
   // ASLR
   private aslrSlide: NativePointer = ptr(0);
@@ -275,7 +482,9 @@ export class CModuleTracer {
 
   // Session
   private sessionId: string = '';
-  private eventIdCounter: number = 0;
+  private eventIds = new EventIdAllocator('fn');
+  // Events drained since the thread stacks were last cleared
+  private drainedSinceCleanup: number = 0;
 
   // Timestamp conversion
   private ticksToNs: number = 1.0;
@@ -289,11 +498,11 @@ export class CModuleTracer {
   private drainTimer: ReturnType<typeof setInterval> | null = null;
 
   // Callback for emitting events to the daemon
-  private onEvents: (events: TraceEvent[]) => void;
+  private onEvents: (events: Array<TraceEvent | CallCountsEvent | RtWarningEvent>) => void;
 
   // Per-thread depth stacks for parent tracking during drain
   // Map<threadId, Array<{ eventId: string; depth: number; timestampNs: number }>>
-  private threadStacks: Map<number, Array<{ eventId: string; depth: number; timestampNs: number }>> = new Map();
+  private threadStacks: Map<number, Array<{ eventId: string; depth: number; timestampNs: number; thinned?: boolean }>> = new Map();
 
   // Watch configurations (up to 4 CModule watches)
   private watchConfigs: (WatchConfig | null)[] = [null, null, null, null];
@@ -304,6 +513,7 @@ export class CModuleTracer {
     compiledFn: () => any;
     isGlobal: boolean;
     onFuncIds: Set<number>;
+    delta: DeltaFilter | null;
   }> = [];
 
   // Object serializer for deep argument inspection
@@ -312,11 +522,31 @@ export class CModuleTracer {
   // Rate check callback for hot function detection
   private rateCheck: RateCheckFn | null = null;
 
+  // Threads whose calls are emitted (null: all threads). Other threads' calls
+  // are dropped in drain before they get an event id or touch the stacks.
+  private threadFilter: { ids: Set<number>; name: string | null } | null = null;
+  // Hook-side copy of the thread filter (see THREAD_FILTER_SIZE)
+  private threadFilterTable: NativePointer;
+  private filedThreads: Set<number> = new Set();
+
+  // Per-function cap on recorded calls (null: record every call). Decided at
+  // function_enter; the matching exit follows the enter's decision.
+  private rateLimit: { maxPerSecond: number; sampleEvery: number } | null = null;
+  private rateWindows: Map<number, RateWindow> = new Map();
+  // Per-thread depths of enters dropped by the rate limit, to drop their exits
+  private droppedDepths: Map<number, number[]> = new Map();
+
   // Thread name cache: threadId -> name
   private threadNames: Map<number, string | null> = new Map();
 
+  // libdispatch: label lookup for the JS fallback, label cache by C-string address
+  private queueLabelFn: NativeFunction<NativePointer, [NativePointerValue]> | null = null;
+  private currentQueueFn: NativeFunction<NativePointer, []> | null = null;
+  private queueLabels: Map<string, string | null> = new Map();
+
   constructor(onEvents: (events: any[]) => void, platform: PlatformAdapter) {
     this.onEvents = onEvents;
+    this.platform = platform;
 
     // --- Allocate ring buffer shared memory ---
     this.ringBuffer = Memory.alloc(RING_BUFFER_SIZE);
@@ -347,6 +577,27 @@ export class CModuleTracer {
     // Initialize sample_interval to 1
     this.sampleIntervalPtr.writeU32(1);
 
+    // Count-mode counter table (zeroed on slot allocation)
+    this.countTable = Memory.alloc(COUNT_CAPACITY * 4);
+
+    this.threadFilterTable = Memory.alloc(THREAD_FILTER_SIZE);
+    this.threadFilterTable.writeS32(0);
+
+    // GCD: thread ids say little in libdispatch-heavy apps, so each entry
+    // also records the current queue and its label when libdispatch is loaded.
+    const queueLabelFnPtr = this.ringBuffer.add(QUEUE_LABEL_FN_OFFSET);
+    const getLabel = Module.findExportByName(null, 'dispatch_queue_get_label');
+    queueLabelFnPtr.writePointer(getLabel ?? NULL);
+    if (getLabel) {
+      this.queueLabelFn = new NativeFunction(getLabel, 'pointer', ['pointer']);
+    }
+    const currentQueueFnPtr = this.ringBuffer.add(CURRENT_QUEUE_FN_OFFSET);
+    const getQueue = Module.findExportByName(null, 'dispatch_get_current_queue');
+    currentQueueFnPtr.writePointer(getQueue ?? NULL);
+    if (getQueue) {
+      this.currentQueueFn = new NativeFunction(getQueue, 'pointer', []);
+    }
+
     // Allocate a pointer-to-pointer for ring_data extern symbol.
     // CModule declares `extern guint8 *ring_data;` — it's a pointer variable
     // whose *value* is the address of the data region.
@@ -357,7 +608,7 @@ export class CModuleTracer {
     this.ticksToNs = platform.getTicksToNs();
 
     // --- Create CModule with platform-specific timing preamble ---
-    const fullSource = platform.getCModuleTimingPreamble() + CMODULE_SOURCE;
+    const fullSource = platform.getCModuleTimingPreamble() + goRegisterPreamble() + CMODULE_SOURCE;
     try {
       this.cm = new CModule(fullSource, {
         ...platform.getCModuleTimingSymbols(),
@@ -371,7 +622,13 @@ export class CModuleTracer {
         watch_sizes:          this.watchSizesPtr,
         watch_deref_depths:   this.watchDerefDepthsPtr,
         watch_deref_offsets:  this.watchDerefOffsetsPtr,
+        queue_label_fn:       this.ringBuffer.add(QUEUE_LABEL_FN_OFFSET),
+        current_queue_fn:     this.ringBuffer.add(CURRENT_QUEUE_FN_OFFSET),
+        goid_offset:          this.ringBuffer.add(GOID_OFFSET_OFFSET),
+        call_counts:          this.countTable,
+        thread_filter:        this.threadFilterTable,
       });
+      this.nowFn = new NativeFunction((this.cm as any).strobe_now, 'uint64', []);
     } catch (e) {
       send({ type: 'log', message: `CModule creation failed, using JS fallback: ${e}` });
       this.cm = null;
@@ -380,6 +637,9 @@ export class CModuleTracer {
     // --- Start drain timer ---
     this.drainTimer = setInterval(() => this.drain(), DRAIN_INTERVAL_MS);
     (this.drainTimer as any).unref?.();
+
+    this.countTimer = setInterval(() => this.flushCounts(), COUNT_FLUSH_INTERVAL_MS);
+    (this.countTimer as any).unref?.();
   }
 
   // -----------------------------------------------------------------------
@@ -394,6 +654,22 @@ export class CModuleTracer {
     this.imageBaseSet = true;
   }
 
+  /**
+   * Static address -> runtime address. Main-binary addresses get the ASLR
+   * slide; symbol-module addresses are relative to the loaded module's base.
+   * Throws when the module isn't loaded.
+   */
+  runtimeAddress(address: string, module?: string): NativePointer {
+    if (!module) {
+      return ptr(address).add(this.aslrSlide);
+    }
+    const loaded = Process.findModuleByName(module);
+    if (loaded === null) {
+      throw new Error(`${module} is not loaded`);
+    }
+    return loaded.base.add(ptr(address));
+  }
+
   getSlide(): NativePointer {
     return this.aslrSlide;
   }
@@ -411,25 +687,125 @@ export class CModuleTracer {
     this.rateCheck = fn;
   }
 
+  setThreadFilter(ids: number[] | null | undefined, nameContains: string | null | undefined): void {
+    const idSet = new Set(ids ?? []);
+    this.threadFilter = idSet.size > 0 || nameContains
+      ? { ids: idSet, name: nameContains || null }
+      : null;
+    this.resetThreadFilterTable();
+  }
+
+  /**
+   * Load the explicit ids into the hook-side table. Threads matched by name
+   * are filed by the drain as it sees them.
+   */
+  private resetThreadFilterTable(): void {
+    const table = this.threadFilterTable;
+    const filter = this.threadFilter;
+    table.writeS32(0);
+    this.filedThreads.clear();
+    if (!filter) return;
+    const ids = [...filter.ids].slice(0, THREAD_FILTER_ALLOWED);
+    ids.forEach((id, i) => table.add((3 + i) * 4).writeU32(id));
+    table.add(4).writeS32(ids.length);
+    table.add(8).writeS32(0);
+    // More explicit ids than fit: leave the rest to the drain
+    const complete = ids.length === filter.ids.size;
+    table.writeS32(filter.name || !complete ? 2 : 1);
+  }
+
+  /** File a thread the drain has matched against the filter, so its hooks decide alone. */
+  private rememberThreadDecision(threadId: number, admitted: boolean): void {
+    if (this.filedThreads.has(threadId)) return;
+    this.filedThreads.add(threadId);
+    const table = this.threadFilterTable;
+    const countOffset = admitted ? 4 : 8;
+    const base = admitted ? 3 : 3 + THREAD_FILTER_ALLOWED;
+    const capacity = admitted ? THREAD_FILTER_ALLOWED : THREAD_FILTER_BLOCKED;
+    const count = table.add(countOffset).readS32();
+    if (count >= capacity) return;
+    table.add((base + count) * 4).writeU32(threadId);
+    table.add(countOffset).writeS32(count + 1);
+  }
+
+  /** Hook-side filter check for the JS fallback hooks. */
+  private threadAdmitted(threadId: number): boolean {
+    const filter = this.threadFilter;
+    if (!filter || filter.ids.has(threadId)) return true;
+    if (!filter.name) return false;
+    const name = this.threadNames.get(threadId);
+    return name === undefined || (name !== null && name.includes(filter.name));
+  }
+
+  setRateLimit(maxPerSecond: number | null | undefined, sampleEvery: number | null | undefined): void {
+    const every = sampleEvery && sampleEvery > 1 ? sampleEvery : 1;
+    this.rateLimit = maxPerSecond || every > 1
+      ? { maxPerSecond: maxPerSecond || Infinity, sampleEvery: every }
+      : null;
+    this.rateWindows.clear();
+    this.droppedDepths.clear();
+  }
+
+  /** Whether this call of funcId is recorded under the rate limit. */
+  private admitCall(funcId: number, nowMs: number): boolean {
+    const limit = this.rateLimit!;
+    let window = this.rateWindows.get(funcId);
+    if (!window) {
+      window = { start: nowMs, calls: 0, recorded: 0, dropped: 0, droppedBefore: false };
+      this.rateWindows.set(funcId, window);
+    } else if (nowMs - window.start >= RATE_WINDOW_MS) {
+      window.droppedBefore = window.dropped > 0;
+      window.start = nowMs;
+      window.recorded = 0;
+      window.dropped = 0;
+    }
+    const keep = window.calls++ % limit.sampleEvery === 0 && window.recorded < limit.maxPerSecond;
+    if (keep) window.recorded++;
+    else window.dropped++;
+    return keep;
+  }
+
+  /** Recorded calls of funcId stand for more calls than were recorded. */
+  private isThinned(funcId: number): boolean {
+    const window = this.rateWindows.get(funcId);
+    return this.rateLimit!.sampleEvery > 1
+      || (window !== undefined && (window.dropped > 0 || window.droppedBefore));
+  }
+
   installHook(func: FunctionTarget, mode: HookMode = 'full'): number | null {
-    const existing = this.hooks.get(func.address);
+    const key = hookKey(func);
+    const existing = this.hooks.get(key);
     if (existing) {
       return existing.funcId; // Already hooked
     }
 
     const funcId = this.nextFuncId++;
 
-    // funcId << 1 must not overflow signed 32-bit.
-    // JS << operates on int32, so (funcId << 1) overflows sign bit at 2^30.
-    // Guard at 2^29 to ensure (funcId << 1) | 1 stays positive.
-    if (funcId >= (1 << 29)) {
+    // funcId << 3 must not overflow signed 32-bit.
+    // JS << operates on int32, so (funcId << 3) overflows sign bit at 2^28.
+    // Guard at 2^28 to ensure (funcId << 3) | 7 stays positive.
+    if (funcId >= (1 << 28)) {
       return null;
     }
 
-    this.funcRegistry.set(funcId, func);
+    let addr: NativePointer;
+    try {
+      addr = this.runtimeAddress(func.address, func.module);
+    } catch (e: any) {
+      send({ type: 'log', message: `Hook ${func.name} not installed: ${e.message}` });
+      return null;
+    }
 
-    // Adjust address for ASLR: runtime addr = static addr + slide
-    const addr = ptr(func.address).add(this.aslrSlide);
+    if (mode === 'count') {
+      return this.installCountHook(func, addr, funcId);
+    }
+    if (mode === 'rt' && !this.cm) {
+      // A JS hook is exactly what must not run on a real-time thread
+      send({ type: 'log', message: `rt hook for ${func.name} skipped: CModule unavailable` });
+      return null;
+    }
+
+    this.funcRegistry.set(funcId, func);
 
     try {
       let listener: InvocationListener;
@@ -437,14 +813,21 @@ export class CModuleTracer {
       if (this.cm) {
         // Native CModule path — high performance
         const isLight = mode === 'light' ? 1 : 0;
-        const data = ptr((funcId << 1) | isLight);
+        const isGo = func.goidOffset ? 1 : 0;
+        const isRt = mode === 'rt' ? 1 : 0;
+        if (func.goidOffset) {
+          // One Go runtime per process, so the offset is process-wide
+          this.ringBuffer.add(GOID_OFFSET_OFFSET).writeU64(uint64(func.goidOffset));
+        }
+        const data = ptr((funcId << 3) | (isRt << 2) | (isGo << 1) | isLight);
         listener = Interceptor.attach(addr, this.cm as any, data);
       } else {
         // JS fallback path — used when CModule is unavailable
         listener = this.installJsHook(addr, funcId, mode);
       }
 
-      this.hooks.set(func.address, { listener, funcId, funcName: func.name });
+      this.hooks.set(key, { listener, funcId, funcName: func.name });
+      if (mode === 'rt') this.rtFuncIds.add(funcId);
       return funcId;
     } catch (_e) {
       // Silently skip functions that can't be hooked
@@ -453,14 +836,53 @@ export class CModuleTracer {
     }
   }
 
+  /**
+   * Count-only hook: increments the function's counter slot on entry and
+   * writes nothing to the ring buffer. Not in funcRegistry, so the drain
+   * never sees it.
+   */
+  private installCountHook(func: FunctionTarget, addr: NativePointer, funcId: number): number | null {
+    const slot = this.freeCountSlots.pop() ?? (this.nextCountSlot < COUNT_CAPACITY ? this.nextCountSlot++ : null);
+    if (slot === null) return null;
+
+    const counter = this.countTable.add(slot * 4);
+    counter.writeU32(0);
+
+    try {
+      const listener = this.cm
+        ? Interceptor.attach(addr, { onEnter: (this.cm as any).onCount }, ptr(slot))
+        : Interceptor.attach(addr, {
+            onEnter() { counter.writeU32((counter.readU32() + 1) >>> 0); }
+          });
+
+      const key = hookKey(func);
+      this.countSlots.set(slot, { address: key, last: 0 });
+      if (!this.countTotals.has(key)) {
+        this.countTotals.set(key, { name: func.name, total: 0 });
+      }
+      this.hooks.set(key, { listener, funcId, funcName: func.name, countSlot: slot });
+      return funcId;
+    } catch (_e) {
+      this.freeCountSlots.push(slot);
+      return null;
+    }
+  }
+
   /** JS-based hook fallback: writes to the same ring buffer as CModule. */
   private installJsHook(addr: NativePointer, funcId: number, mode: HookMode): InvocationListener {
     const writeIdxPtr = this.writeIdxPtr;
     const ringDataPtr = this.ringDataPtr;
     const isLight = mode === 'light';
+    const queueLabelFn = this.queueLabelFn;
+    const currentQueueLabel = (): NativePointer => queueLabelFn ? queueLabelFn(NULL) : NULL;
+    const currentQueueFn = this.currentQueueFn;
+    const currentQueue = (): NativePointer => currentQueueFn ? currentQueueFn() : NULL;
+    const admitted = (threadId: number): boolean => this.threadAdmitted(threadId);
 
     return Interceptor.attach(addr, {
       onEnter(args) {
+        (this as any)._strobeSkip = !admitted(Process.getCurrentThreadId());
+        if ((this as any)._strobeSkip) return;
         const idx = (writeIdxPtr.readU32() % RING_CAPACITY);
         const entryPtr = ringDataPtr.add(idx * ENTRY_SIZE);
         const now = uint64(Date.now() * 1000000);  // ms -> ns
@@ -475,11 +897,15 @@ export class CModuleTracer {
         entryPtr.add(44).writeU8(0);               // eventType = enter
         entryPtr.add(45).writeU8(0);               // sampled
         entryPtr.add(46).writeU8(0);               // watchEntryCount
+        entryPtr.add(80).writePointer(currentQueueLabel());       // queue label
+        entryPtr.add(88).writeU64(uint64(0));                     // goid (not tracked in JS mode)
+        entryPtr.add(96).writePointer(currentQueue());            // queue
 
         writeIdxPtr.writeU32(writeIdxPtr.readU32() + 1);
         (this as any)._strobeEntryIdx = idx;
       },
       onLeave(retval) {
+        if ((this as any)._strobeSkip) return;
         const idx = (writeIdxPtr.readU32() % RING_CAPACITY);
         const entryPtr = ringDataPtr.add(idx * ENTRY_SIZE);
         const now = uint64(Date.now() * 1000000);
@@ -494,6 +920,9 @@ export class CModuleTracer {
         entryPtr.add(44).writeU8(1);               // eventType = exit
         entryPtr.add(45).writeU8(0);
         entryPtr.add(46).writeU8(0);
+        entryPtr.add(80).writePointer(currentQueueLabel());
+        entryPtr.add(88).writeU64(uint64(0));
+        entryPtr.add(96).writePointer(currentQueue());
 
         writeIdxPtr.writeU32(writeIdxPtr.readU32() + 1);
       }
@@ -505,7 +934,13 @@ export class CModuleTracer {
     if (entry) {
       entry.listener.detach();
       this.funcRegistry.delete(entry.funcId);
+      this.rtFuncIds.delete(entry.funcId);
       this.hooks.delete(address);
+      if (entry.countSlot !== undefined) {
+        this.collectCounts();
+        this.countSlots.delete(entry.countSlot);
+        this.freeCountSlots.push(entry.countSlot);
+      }
     }
   }
 
@@ -519,8 +954,17 @@ export class CModuleTracer {
     }
     this.hooks.clear();
     this.funcRegistry.clear();
+    this.rtFuncIds.clear();
+    this.rtWarned.clear();
     this.nextFuncId = 1;
     this.threadStacks.clear();
+    this.rateWindows.clear();
+    this.droppedDepths.clear();
+
+    this.collectCounts();
+    this.countSlots.clear();
+    this.freeCountSlots = [];
+    this.nextCountSlot = 0;
 
     // Clear watch state
     this.watchCountPtr.writeU32(0);
@@ -528,29 +972,86 @@ export class CModuleTracer {
     this.exprWatches = [];
   }
 
+  /** Drain the ring now instead of waiting for the next timer tick. */
+  drainNow(): void {
+    this.drain();
+  }
+
   /** Final drain — flush any buffered events before script teardown. */
   dispose(): void {
     if (this.drainTimer !== null) {
       clearInterval(this.drainTimer);
       this.drainTimer = null;
     }
+    if (this.countTimer !== null) {
+      clearInterval(this.countTimer);
+      this.countTimer = null;
+    }
     this.drain();
+    this.flushCounts();
+  }
+
+  /** Fold counter deltas since the last read into the running totals. */
+  private collectCounts(): void {
+    for (const [slot, state] of this.countSlots) {
+      const raw = this.countTable.add(slot * 4).readU32();
+      const delta = (raw - state.last) >>> 0;
+      if (delta === 0) continue;
+      state.last = raw;
+      this.countTotals.get(state.address)!.total += delta;
+      this.countsDirty = true;
+    }
+  }
+
+  /** Send running totals as one call_counts event if anything changed. */
+  private flushCounts(): void {
+    this.collectCounts();
+    if (!this.countsDirty) return;
+    this.countsDirty = false;
+
+    const counts = [];
+    for (const [address, entry] of this.countTotals) {
+      if (entry.total > 0) counts.push({ function: entry.name, address, count: entry.total });
+    }
+    const timestampNs = this.nowFn
+      ? Math.round(this.nowFn().toNumber() * this.ticksToNs)
+      : Date.now() * 1000000;
+
+    this.onEvents([{
+      // Not tied to a thread
+      id: this.countIds.next(this.sessionId, 0),
+      sessionId: this.sessionId,
+      timestampNs,
+      pid: Process.id,
+      eventType: 'call_counts',
+      counts,
+    }]);
   }
 
   updateWatches(watches: Array<{
     address: string; size: number; label: string;
     derefDepth: number; derefOffset: number;
     typeKind: string; isGlobal: boolean; noSlide?: boolean; onFuncIds?: number[]; onPatterns?: string[];
+    deltaThreshold?: number; module?: string;
   }>): void {
     if (watches.length > 4) throw new Error('Max 4 CModule watches');
 
+    // Resolve before disabling so an unloaded module leaves the old watches armed
+    const runtimeAddrs = watches.map(w => {
+      try {
+        return w.noSlide ? ptr(w.address) : this.runtimeAddress(w.address, w.module);
+      } catch (e: any) {
+        throw new Error(`Watch "${w.label}": ${e.message}`);
+      }
+    });
+
     // Atomic disable
     this.watchCountPtr.writeU32(0);
 
     for (let i = 0; i < 4; i++) {
       if (i < watches.length) {
         const w = watches[i];
-        const runtimeAddr = w.noSlide ? ptr(w.address) : ptr(w.address).add(this.aslrSlide);
+        const runtimeAddr = runtimeAddrs[i];
 
         // Validate address is readable before installing in CModule.
         // readByteArray uses gum_memory_read (setjmp-based safe access) —
@@ -594,6 +1095,7 @@ export class CModuleTracer {
           // Treat as global if no patterns/funcIds provided or empty set
           isGlobal: w.isGlobal || resolvedFuncIds.size === 0,
           onFuncIds: resolvedFuncIds,
+          delta: makeDeltaFilter(w.deltaThreshold),
         };
       } else {
         this.watchAddrsPtr.add(i * 8).writeU64(uint64(0));
@@ -673,7 +1175,7 @@ export class CModuleTracer {
   }
 
   updateExprWatches(exprs: Array<{
-    expr: string; label: string; isGlobal: boolean; onPatterns?: string[];
+    expr: string; label: string; isGlobal: boolean; onPatterns?: string[]; deltaThreshold?: number;
   }>): void {
     this.exprWatches = exprs.map(e => {
       const resolvedFuncIds = (e.onPatterns && e.onPatterns.length > 0)
@@ -685,6 +1187,7 @@ export class CModuleTracer {
         compiledFn: new Function('return ' + e.expr) as () => any,
         isGlobal: e.isGlobal || resolvedFuncIds.size === 0,
         onFuncIds: resolvedFuncIds,
+        delta: makeDeltaFilter(e.deltaThreshold),
       };
     });
   }
@@ -699,8 +1202,12 @@ export class CModuleTracer {
 
     // Periodic cleanup: clear thread stacks every 50k events to prevent
     // unbounded growth from missed function exits (exception unwinding, ring overflow)
-    if (this.eventIdCounter % 50000 === 0) {
+    if (this.drainedSinceCleanup >= 50000) {
+      this.drainedSinceCleanup = 0;
       this.threadStacks.clear();
+      this.droppedDepths.clear();
+      this.rtPolicies.clear();  // Thread ids get reused
+      this.resetThreadFilterTable();
     }
 
     const writeIdx = this.writeIdxPtr.readU32();
@@ -716,8 +1223,10 @@ export class CModuleTracer {
       // Skip ahead — only read the most recent RING_CAPACITY entries
       count = RING_CAPACITY;
     }
+    this.drainedSinceCleanup += count;
 
-    const events: TraceEvent[] = [];
+    const events: Array<TraceEvent | RtWarningEvent> = [];
+    const nowMs = Date.now();
 
     for (let i = 0; i < count; i++) {
       const idx = (readIdx + i) % RING_CAPACITY;
@@ -738,16 +1247,13 @@ export class CModuleTracer {
       const eventType  = entryPtr.add(44).readU8();
       const sampled    = entryPtr.add(45).readU8();
       const watchEntryCount = entryPtr.add(46).readU8();
+      const queueName  = this.resolveQueueLabel(entryPtr.add(96).readPointer(),
+                                                entryPtr.add(80).readPointer());
+      const goid       = entryPtr.add(88).readU64();
 
       const func = this.funcRegistry.get(funcId);
       if (!func) continue;
 
-      // Hot function detection: check if this call should be recorded
-      if (this.rateCheck) {
-        const shouldRecord = this.rateCheck(funcId);
-        if (!shouldRecord) continue;
-      }
-
       // Resolve thread name (cached).
       // Avoid Process.enumerateThreads() — it triggers gum_detect_pthread_internals()
       // which is fatal on ASAN-instrumented binaries. Read /proc directly instead.
@@ -759,7 +1265,51 @@ export class CModuleTracer {
         this.threadNames.set(threadId, threadName ?? null);
       }
 
-      const eventId = this.generateEventId();
+      const filter = this.threadFilter;
+      if (filter && !filter.ids.has(threadId)) {
+        const admitted = !!(filter.name && threadName && threadName.includes(filter.name));
+        if (filter.name) this.rememberThreadDecision(threadId, admitted);
+        if (!admitted) continue;
+      }
+      if (eventType === 0 && !this.rtFuncIds.has(funcId)) {
+        const warning = this.checkRealtimeThread(threadId, threadName, func.name, timestamp);
+        if (warning) events.push(warning);
+      }
+      // Goroutines migrate between OS threads; the goroutine says more
+      if (!goid.equals(0)) {
+        threadName = `goroutine ${goid.toString()}`;
+      }
+
+      // User rate limit: keep or drop the whole call, decided at its enter
+      let thinned = false;
+      if (this.rateLimit) {
+        let dropped = this.droppedDepths.get(threadId);
+        if (eventType === 0) {
+          while (dropped && dropped.length > 0 && dropped[dropped.length - 1] >= depth) {
+            dropped.pop();
+          }
+          if (!this.admitCall(funcId, nowMs)) {
+            if (!dropped) {
+              dropped = [];
+              this.droppedDepths.set(threadId, dropped);
+            }
+            dropped.push(depth);
+            continue;
+          }
+          thinned = this.isThinned(funcId);
+        } else if (dropped && dropped.length > 0 && dropped[dropped.length - 1] === depth) {
+          dropped.pop();
+          continue;
+        }
+      }
+
+      // Hot function detection: check if this call should be recorded
+      if (this.rateCheck) {
+        const shouldRecord = this.rateCheck(funcId);
+        if (!shouldRecord) continue;
+      }
+
+      const eventId = this.eventIds.next(this.sessionId, threadId);
       const timestampNs = Math.round(timestamp * this.ticksToNs);
 
       // Determine parent event using per-thread depth stacks
@@ -780,7 +1330,7 @@ export class CModuleTracer {
         // Parent is top of stack (the caller)
         parentEventId = stack.length > 0 ? stack[stack.length - 1].eventId : null;
         // Push ourselves (with timestamp for durationNs computation)
-        stack.push({ eventId, depth, timestampNs });
+        stack.push({ eventId, depth, timestampNs, thinned });
 
         const event: TraceEvent = {
           id: eventId,
@@ -788,19 +1338,21 @@ export class CModuleTracer {
           timestampNs,
           threadId,
           threadName,
+          queueName,
           parentEventId,
           eventType: 'function_enter',
+          callId: eventId,
           functionName: func.name,
           functionNameRaw: func.nameRaw,
           sourceFile: func.sourceFile,
           lineNumber: func.lineNumber,
-          arguments: this.serializeArguments(arg0, arg1),
+          arguments: this.serializeArguments(arg0, arg1, func),
         };
-        if (sampled) event.sampled = true;
+        if (sampled || thinned) event.sampled = true;
 
         // Read watch values
         if (watchEntryCount > 0 || this.exprWatches.length > 0) {
-          const watchValues: Record<string, number | string> = {};
+          const watchValues: Record<string, unknown> = {};
 
           // CModule watches
           for (let w = 0; w < watchEntryCount && w < 4; w++) {
@@ -809,14 +1361,19 @@ export class CModuleTracer {
             if (!cfg.isGlobal && !cfg.onFuncIds.has(funcId)) continue;
 
             const raw = entryPtr.add(48 + w * 8).readU64();
-            watchValues[cfg.label] = this.formatWatchValue(raw, cfg);
+            const value = this.formatWatchValue(raw, cfg);
+            const recorded = cfg.delta ? applyDelta(cfg.delta, value) : value;
+            if (recorded !== undefined) watchValues[cfg.label] = recorded;
           }
 
           // JS expression watches
           for (const ew of this.exprWatches) {
             if (!ew.isGlobal && !ew.onFuncIds.has(funcId)) continue;
-            try { watchValues[ew.label] = ew.compiledFn(); }
-            catch (e: any) { watchValues[ew.label] = '<error>'; }
+            let value: unknown;
+            try { value = ew.compiledFn(); }
+            catch (e: any) { value = '<error>'; }
+            const recorded = ew.delta ? applyDelta(ew.delta, value) : value;
+            if (recorded !== undefined) watchValues[ew.label] = recorded;
           }
 
           if (Object.keys(watchValues).length > 0) {
@@ -834,6 +1391,7 @@ export class CModuleTracer {
         if (stack.length > 0 && stack[stack.length - 1].depth === depth) {
           const enterEntry = stack.pop()!;
           enterEventId = enterEntry.eventId;
+          thinned = enterEntry.thinned ?? false;
           // Issue 7: compute durationNs from enter timestamp
           durationNs = timestampNs - enterEntry.timestampNs;
           if (durationNs < 0) durationNs = undefined; // clock skew safety
@@ -847,16 +1405,18 @@ export class CModuleTracer {
           timestampNs,
           threadId,
           threadName,
+          queueName,
           parentEventId,
           eventType: 'function_exit',
           functionName: func.name,
           functionNameRaw: func.nameRaw,
           sourceFile: func.sourceFile,
           lineNumber: func.lineNumber,
-          returnValue: '0x' + retval.toString(16),
+          returnValue: this.serializeReturnValue(retval, func),
           durationNs,
         };
-        if (sampled) event.sampled = true;
+        if (enterEventId !== null) event.callId = enterEventId;
+        if (sampled || thinned) event.sampled = true;
         events.push(event);
       }
     }
@@ -873,6 +1433,42 @@ export class CModuleTracer {
     this.adaptSampling(count);
   }
 
+  /**
+   * An rt_warning the first time a normal hook of `funcName` fires on a
+   * thread with a real-time scheduling policy; null otherwise.
+   */
+  private checkRealtimeThread(
+    threadId: number, threadName: string | null | undefined, funcName: string, timestamp: number,
+  ): RtWarningEvent | null {
+    let policy = this.rtPolicies.get(threadId);
+    if (policy === undefined) {
+      policy = this.platform.realtimePolicy(threadId);
+      this.rtPolicies.set(threadId, policy);
+    }
+    if (policy === null) return null;
+
+    const key = `${threadId}:${funcName}`;
+    if (this.rtWarned.has(key) || this.rtWarned.size >= MAX_RT_WARNINGS) return null;
+    this.rtWarned.add(key);
+
+    const jsHook = this.cm === null;
+    return {
+      id: this.rtWarningIds.next(this.sessionId, threadId),
+      sessionId: this.sessionId,
+      timestampNs: Math.round(timestamp * this.ticksToNs),
+      threadId,
+      threadName,
+      pid: Process.id,
+      eventType: 'rt_warning',
+      functionName: funcName,
+      policy,
+      jsHook,
+      text: `${funcName} runs on ${policy} thread ${threadName ?? threadId} with a normal hook`
+        + (jsHook ? ' (JS on the real-time thread)' : '')
+        + '; trace it with mode \'rt\'',
+    };
+  }
+
   // -----------------------------------------------------------------------
   // Adaptive sampling
   // -----------------------------------------------------------------------
@@ -910,6 +1506,35 @@ export class CModuleTracer {
   // Helpers
   // -----------------------------------------------------------------------
 
+  /**
+   * Current value of the watch labelled `label`, read the way function
+   * entries read it (a null pointer reads as 0). Undefined when no watch has
+   * that label.
+   */
+  readWatch(label: string): unknown {
+    const i = this.watchConfigs.findIndex(cfg => cfg !== null && cfg.label === label);
+    if (i < 0) {
+      const ew = this.exprWatches.find(e => e.label === label);
+      return ew === undefined ? undefined : ew.compiledFn();
+    }
+    const cfg = this.watchConfigs[i]!;
+    let addr = ptr(this.watchAddrsPtr.add(i * 8).readU64().toString());
+    if (!addr.isNull() && this.watchDerefDepthsPtr.add(i).readU8() > 0) {
+      const target = addr.readPointer();
+      addr = target.isNull()
+        ? NULL
+        : target.add(ptr(this.watchDerefOffsetsPtr.add(i * 8).readU64().toString()));
+    }
+    let raw = uint64(0);
+    if (!addr.isNull()) {
+      if (cfg.size === 1) raw = uint64(addr.readU8());
+      else if (cfg.size === 2) raw = uint64(addr.readU16());
+      else if (cfg.size === 4) raw = uint64(addr.readU32());
+      else raw = addr.readU64();
+    }
+    return this.formatWatchValue(raw, cfg);
+  }
+
   private formatWatchValue(raw: UInt64, cfg: WatchConfig): number | string {
     if (cfg.typeKind === 'float') {
       const lo = raw.and(0xFFFFFFFF).toNumber();
@@ -922,20 +1547,22 @@ export class CModuleTracer {
     return raw.toNumber();
   }
 
-  private serializeArguments(arg0: UInt64, arg1: UInt64): string[] {
+  private serializeArguments(arg0: UInt64, arg1: UInt64, func: FunctionTarget): string[] {
     if (!this.objectSerializer) {
       return ['0x' + arg0.toString(16), '0x' + arg1.toString(16)];
     }
 
     const results: string[] = [];
-    for (const rawArg of [arg0, arg1]) {
+    const rawArgs = [arg0, arg1];
+    for (let i = 0; i < rawArgs.length; i++) {
+      const rawArg = rawArgs[i];
       const addr = ptr(rawArg.toString());
-      // Without DWARF type info for arguments, treat as generic pointer
-      const typeInfo: TypeInfo = {
-        typeKind: 'pointer',
-        byteSize: 8,
-        typeName: 'void*',
-      };
+      const layout = func.argLayouts?.[i];
+      // Container arguments arrive as the object's address; anything else
+      // without DWARF type info is treated as a generic pointer.
+      const typeInfo: TypeInfo = layout
+        ? { typeKind: 'struct', byteSize: 0, typeName: layout.kind, container: layout }
+        : { typeKind: 'pointer', byteSize: 8, typeName: 'void*' };
       try {
         const serialized = this.objectSerializer.serialize(addr, typeInfo);
         results.push(typeof serialized === 'string' ? serialized : JSON.stringify(serialized));
@@ -947,6 +1574,47 @@ export class CModuleTracer {
     return results;
   }
 
+  private serializeReturnValue(retval: UInt64, func: FunctionTarget): string {
+    const raw = '0x' + retval.toString(16);
+    if (!this.objectSerializer || !func.returnLayout) {
+      return raw;
+    }
+    const layout = func.returnLayout;
+    try {
+      const serialized = this.objectSerializer.serialize(ptr(retval.toString()),
+        { typeKind: 'struct', byteSize: 0, typeName: layout.kind, container: layout });
+      return typeof serialized === 'string' ? serialized : JSON.stringify(serialized);
+    } catch (e) {
+      return raw;
+    } finally {
+      this.objectSerializer.reset();
+    }
+  }
+
+  /**
+   * Queue labels are C strings owned by the queue. Cache by queue and label
+   * address: either can be reused once a queue is freed, but not usually both
+   * at once. A queue freed before the drain can leave a dangling pointer, so
+   * reads are guarded.
+   */
+  private resolveQueueLabel(queue: NativePointer, labelPtr: NativePointer): string | undefined {
+    if (labelPtr.isNull()) return undefined;
+    const key = `${queue}:${labelPtr}`;
+    let label = this.queueLabels.get(key);
+    if (label === undefined) {
+      try {
+        label = labelPtr.readUtf8String(256) || null;
+      } catch {
+        label = null;
+      }
+      if (this.queueLabels.size >= MAX_QUEUE_LABELS) {
+        this.queueLabels.clear();
+      }
+      this.queueLabels.set(key, label);
+    }
+    return label ?? undefined;
+  }
+
   /** Read thread name from /proc without calling Process.enumerateThreads(). */
   private readThreadName(threadId: number): string | null {
     if (Process.platform !== 'linux') {
@@ -974,9 +1642,4 @@ export class CModuleTracer {
       return buf.readUtf8String(n)!.trim() || null;
     } catch { return null; }
   }
-
-  private generateEventId(): string {
-    const sid = this.sessionId || 'uninitialized';
-    return `${sid}-${++this.eventIdCounter}`;
-  }
 }
@@ -0,0 +1,182 @@
+/**
+ * Basic-block coverage with Stalker. A block is recorded the first time
+ * Stalker compiles it, which is exactly "executed at least once" — later runs
+ * of the block reuse the compiled copy and cost nothing here.
+ */
+
+export type CoverageScope = 'usercode' | 'all';
+
+export interface CoverageMessage {
+  action: 'start' | 'snapshot' | 'stop';
+  scope?: CoverageScope;
+}
+
+interface CoverageModule {
+  path: string;
+  base: string;
+  size: number;
+  main?: boolean;
+}
+
+const MAX_BLOCKS = 2_000_000;
+// drcov stores block sizes as u16 and module offsets as u32
+const MAX_BLOCK_SIZE = 0xFFFF;
+const MAX_MODULE_OFFSET = 0xFFFFFFFF;
+// New threads are picked up on this interval
+const THREAD_POLL_MS = 250;
+
+export class CoverageCollector {
+  private scope: CoverageScope = 'usercode';
+  private active = false;
+  private followed: Set<number> = new Set();
+  // block start address → size in bytes
+  private blocks: Map<string, number> = new Map();
+  private truncated = false;
+  private mainStart: NativePointer = NULL;
+  private mainEnd: NativePointer = NULL;
+  private threadTimer: ReturnType<typeof setInterval> | null = null;
+
+  handle(msg: CoverageMessage): void {
+    try {
+      switch (msg.action) {
+        case 'start':
+          if (this.active) throw new Error('coverage is already running; stop it first');
+          this.start(msg.scope ?? 'usercode');
+          send({ type: 'coverage_response', ...this.status() });
+          break;
+        case 'snapshot':
+        case 'stop': {
+          if (!this.active) throw new Error('coverage is not running; start it first');
+          // Blocks are recorded at compile time, so the report is complete without a flush
+          const response = { type: 'coverage_response', ...this.status(), ...this.report() };
+          if (msg.action === 'stop') {
+            this.stop();
+            this.blocks.clear();
+            response.active = false;
+          }
+          send(response);
+          break;
+        }
+      }
+    } catch (e: any) {
+      send({ type: 'coverage_response', error: e.message });
+    }
+  }
+
+  dispose(): void {
+    if (this.active) this.stop();
+  }
+
+  private start(scope: CoverageScope): void {
+    const main = Process.mainModule;
+    this.scope = scope;
+    this.mainStart = main.base;
+    this.mainEnd = main.base.add(main.size);
+    this.blocks.clear();
+    this.truncated = false;
+    this.active = true;
+
+    this.followThreads();
+    this.threadTimer = setInterval(() => this.followThreads(), THREAD_POLL_MS);
+    (this.threadTimer as any).unref?.();
+  }
+
+  private stop(): void {
+    if (this.threadTimer !== null) {
+      clearInterval(this.threadTimer);
+      this.threadTimer = null;
+    }
+    for (const threadId of this.followed) {
+      try {
+        Stalker.unfollow(threadId);
+      } catch (_) {
+        // Thread already exited
+      }
+    }
+    this.followed.clear();
+    Stalker.flush();
+    Stalker.garbageCollect();
+    this.active = false;
+  }
+
+  private followThreads(): void {
+    const self = Process.getCurrentThreadId();
+    let threads: ThreadDetails[];
+    try {
+      threads = Process.enumerateThreads();
+    } catch (_) {
+      return;
+    }
+    for (const t of threads) {
+      if (t.id === self || this.followed.has(t.id)) continue;
+      try {
+        Stalker.follow(t.id, { transform: (iterator: any) => this.transform(iterator) });
+        this.followed.add(t.id);
+      } catch (_) {
+        // Thread exited between enumeration and follow
+      }
+    }
+  }
+
+  private transform(iterator: any): void {
+    let instruction = iterator.next();
+    if (instruction === null) return;
+    const start: NativePointer = instruction.address;
+    let end: NativePointer = start;
+    while (instruction !== null) {
+      end = instruction.address.add(instruction.size);
+      iterator.keep();
+      instruction = iterator.next();
+    }
+    this.record(start, end);
+  }
+
+  private record(start: NativePointer, end: NativePointer): void {
+    if (this.scope === 'usercode' && (start.compare(this.mainStart) < 0 || start.compare(this.mainEnd) >= 0)) {
+      return;
+    }
+    const key = start.toString();
+    if (this.blocks.has(key)) return;
+    if (this.blocks.size >= MAX_BLOCKS) {
+      this.truncated = true;
+      return;
+    }
+    this.blocks.set(key, Math.min(end.sub(start).toInt32(), MAX_BLOCK_SIZE));
+  }
+
+  private status(): { active: boolean; scope: CoverageScope; threads: number } {
+    return { active: this.active, scope: this.scope, threads: this.followed.size };
+  }
+
+  /** Blocks grouped by module, as [module index, offset, size] triples. */
+  private report(): { modules: CoverageModule[]; blocks: Array<[number, number, number]>; truncated: boolean } {
+    const moduleMap = new ModuleMap();
+    const modules: CoverageModule[] = [];
+    const indexByPath: Map<string, number> = new Map();
+    const blocks: Array<[number, number, number]> = [];
+
+    for (const [key, size] of this.blocks) {
+      const address = ptr(key);
+      const module = moduleMap.find(address);
+      if (module === null) continue;  // JIT or freed code
+      const delta = address.sub(module.base);
+      if (delta.compare(ptr(MAX_MODULE_OFFSET)) > 0) continue;
+      const offset = delta.toUInt32();
+
+      let index = indexByPath.get(module.path);
+      if (index === undefined) {
+        index = modules.length;
+        indexByPath.set(module.path, index);
+        modules.push({
+          path: module.path,
+          base: module.base.toString(),
+          size: module.size,
+          main: module.base.equals(this.mainStart) || undefined,
+        });
+      }
+      blocks.push([index, offset, size]);
+    }
+
+    return { modules, blocks, truncated: this.truncated };
+  }
+}
@@ -0,0 +1,296 @@
+/**
+ * C++ exception propagation (`@exceptions` trace pattern). Each `__cxa_throw`
+ * is recorded with the thrown type (read from its RTTI and demangled), what()
+ * for std::exception subclasses and the throw-site backtrace. The
+ * `__cxa_begin_catch` that receives the same object is recorded as its catch,
+ * with the throw's id as callId, the catching function and the frames the
+ * exception unwound through. A `throw;` inside a handler is a rethrow of the
+ * caught exception and keeps its callId.
+ *
+ * An exception thrown before tracing started has no throw event, so its catch
+ * is not reported either. Exceptions carried by std::exception_ptr are not
+ * followed.
+ */
+
+import { findGlobalExport } from './utils.js';
+
+export interface ExceptionsMessage {
+  enabled: boolean;
+}
+
+export interface ExceptionFrame {
+  address: string;
+  moduleName: string | null;
+  name: string | null;
+  fileName: string | null;
+  lineNumber: number | null;
+}
+
+export interface ThrownObject {
+  type: string;
+  message: string | null;
+}
+
+interface InFlight {
+  id: string;
+  type: string;
+  message: string | null;
+  throwFunction: string | null;
+  // Frame names from the (re)throw site outwards
+  frames: string[];
+  key: string;
+}
+
+type ExceptionKind = 'throw' | 'catch' | 'rethrow';
+
+const MAX_FRAMES = 32;
+// Thrown objects waiting for their catch; oldest are dropped first
+const MAX_IN_FLIGHT = 1024;
+// sizeof(_Unwind_Exception) on 64-bit targets; the thrown object follows it
+const UNWIND_HEADER_SIZE = 32;
+
+type DemangleFn = NativeFunction<NativePointer, [NativePointer, NativePointer, NativePointer, NativePointer]>;
+let demangleFn: DemangleFn | null | undefined;
+let freeFn: NativeFunction<void, [NativePointer]> | null = null;
+
+function demangle(mangled: string): string {
+  if (demangleFn === undefined) {
+    const demanglePtr = findGlobalExport('__cxa_demangle');
+    demangleFn = demanglePtr !== null
+      ? new NativeFunction(demanglePtr, 'pointer', ['pointer', 'pointer', 'pointer', 'pointer'])
+      : null;
+    const freePtr = findGlobalExport('free');
+    freeFn = freePtr !== null ? new NativeFunction(freePtr, 'void', ['pointer']) : null;
+  }
+  if (demangleFn === null) return mangled;
+  const statusBuf = Memory.alloc(4);
+  const result = demangleFn(Memory.allocUtf8String(mangled), NULL, NULL, statusBuf);
+  if (statusBuf.readS32() !== 0 || result.isNull()) return mangled;
+  const name = result.readCString() ?? mangled;
+  if (freeFn !== null) freeFn(result);
+  return name;
+}
+
+/**
+ * Type name and what() of the object passed to
+ * `__cxa_throw(void* thrown, std::type_info* tinfo, void (*dest)(void*))`.
+ */
+export function describeThrown(thrown: NativePointer, tinfo: NativePointer): ThrownObject {
+  // Itanium ABI type_info layout: [vtable_ptr, const char* __name]
+  let type = '<unknown>';
+  try {
+    const mangled = tinfo.add(Process.pointerSize).readPointer().readCString();
+    if (mangled) type = demangle(mangled);
+  } catch (_) {
+    // type_info read failed
+  }
+
+  // Itanium ABI vtable: vptr[0] = complete dtor, vptr[1] = deleting dtor, vptr[2] = what()
+  let message: string | null = null;
+  try {
+    const vptr = thrown.readPointer();
+    if (!vptr.isNull()) {
+      const whatFnPtr = vptr.add(Process.pointerSize * 2).readPointer();
+      if (!whatFnPtr.isNull()) {
+        const what = new NativeFunction(whatFnPtr, 'pointer', ['pointer']);
+        const result = what(thrown) as NativePointer;
+        if (!result.isNull()) message = result.readCString();
+      }
+    }
+  } catch (_) {
+    // Expected for non-std::exception types (throw 42, etc.)
+  }
+  return { type, message };
+}
+
+export class ExceptionTracer {
+  private hooks: InvocationListener[] = [];
+  // Thrown object address -> its throw, until a catch receives it
+  private inFlight: Map<string, InFlight> = new Map();
+  // threadId -> handlers running on it, innermost last (null: not traced)
+  private handlers: Map<number, Array<InFlight | null>> = new Map();
+
+  /** `emit` returns the event id it assigned. */
+  constructor(
+    private readonly emit: (event: any) => string,
+    private readonly symbolize: (addr: NativePointer) => ExceptionFrame,
+  ) {}
+
+  /** Returns how many functions are hooked. */
+  configure(msg: ExceptionsMessage): number {
+    for (const l of this.hooks) l.detach();
+    this.hooks = [];
+    this.inFlight.clear();
+    this.handlers.clear();
+    if (!msg.enabled) return 0;
+
+    this.hookThrow();
+    this.hookBeginCatch();
+    this.hookEndCatch();
+    this.hookRethrow();
+    return this.hooks.length;
+  }
+
+  private attach(name: string, callbacks: InvocationListenerCallbacks): void {
+    const addr = findGlobalExport(name);
+    if (addr === null) return;
+    try {
+      this.hooks.push(Interceptor.attach(addr, callbacks));
+    } catch (_) {
+      // Not hookable; the rest still work
+    }
+  }
+
+  private hookThrow(): void {
+    const self = this;
+    this.attach('__cxa_throw', {
+      onEnter(args) {
+        try {
+          const thrown = describeThrown(args[0], args[1]);
+          const backtrace = self.backtrace(this.context);
+          const throwFunction = backtrace[0]?.name ?? null;
+          const detail = thrown.message !== null ? `: ${thrown.message}` : '';
+          const id = self.emit({
+            threadId: this.threadId,
+            eventType: 'exception',
+            functionName: throwFunction ?? '<unknown>',
+            kind: 'throw' as ExceptionKind,
+            exceptionType: thrown.type,
+            exceptionMessage: thrown.message,
+            throwFunction,
+            backtrace,
+            text: `${thrown.type}${detail} thrown in ${throwFunction ?? '<unknown>'}`,
+          });
+          self.track({
+            id,
+            type: thrown.type,
+            message: thrown.message,
+            throwFunction,
+            frames: frameNames(backtrace),
+            key: args[0].toString(),
+          });
+        } catch (_) {
+          // Never fail the throwing thread
+        }
+      },
+    });
+  }
+
+  private hookBeginCatch(): void {
+    const self = this;
+    // __cxa_begin_catch(void* unwind_header) returns the caught object
+    this.attach('__cxa_begin_catch', {
+      onEnter(args) {
+        this.unwindHeader = args[0];
+      },
+      onLeave(retval) {
+        let entry: InFlight | null = null;
+        try {
+          entry = self.take(retval.toString());
+          if (entry === null && Process.pointerSize === 8) {
+            entry = self.take((this.unwindHeader as NativePointer).add(UNWIND_HEADER_SIZE).toString());
+          }
+          if (entry !== null) {
+            const catchFunction = self.symbolize(this.returnAddress).name;
+            const end = catchFunction !== null ? entry.frames.indexOf(catchFunction) : -1;
+            const propagation = end >= 0 ? entry.frames.slice(0, end + 1) : entry.frames;
+            self.emit({
+              threadId: this.threadId,
+              eventType: 'exception',
+              functionName: catchFunction ?? '<unknown>',
+              kind: 'catch' as ExceptionKind,
+              callId: entry.id,
+              exceptionType: entry.type,
+              exceptionMessage: entry.message,
+              throwFunction: entry.throwFunction,
+              catchFunction,
+              propagation,
+              text: `${entry.type} thrown in ${entry.throwFunction ?? '<unknown>'} caught in ${catchFunction ?? '<unknown>'}`,
+            });
+          }
+        } catch (_) {
+          // Reported as not traced
+        }
+        self.handlerStack(this.threadId).push(entry);
+      },
+    });
+  }
+
+  private hookEndCatch(): void {
+    const self = this;
+    this.attach('__cxa_end_catch', {
+      onEnter() {
+        self.handlerStack(this.threadId).pop();
+      },
+    });
+  }
+
+  private hookRethrow(): void {
+    const self = this;
+    // `throw;` rethrows the exception of the innermost running handler
+    this.attach('__cxa_rethrow', {
+      onEnter() {
+        try {
+          const stack = self.handlerStack(this.threadId);
+          const entry = stack[stack.length - 1] ?? null;
+          if (entry === null) return;
+          const backtrace = self.backtrace(this.context);
+          const rethrowFunction = backtrace[0]?.name ?? null;
+          self.emit({
+            threadId: this.threadId,
+            eventType: 'exception',
+            functionName: rethrowFunction ?? '<unknown>',
+            kind: 'rethrow' as ExceptionKind,
+            callId: entry.id,
+            exceptionType: entry.type,
+            exceptionMessage: entry.message,
+            throwFunction: entry.throwFunction,
+            backtrace,
+            text: `${entry.type} rethrown in ${rethrowFunction ?? '<unknown>'}`,
+          });
+          // The next catch reports the path from here
+          self.track({ ...entry, frames: frameNames(backtrace) });
+        } catch (_) {
+          // Never fail the throwing thread
+        }
+      },
+    });
+  }
+
+  private backtrace(context: CpuContext): ExceptionFrame[] {
+    try {
+      return Thread.backtrace(context, Backtracer.ACCURATE).slice(0, MAX_FRAMES).map(this.symbolize);
+    } catch (_) {
+      return [];
+    }
+  }
+
+  private track(entry: InFlight): void {
+    this.inFlight.delete(entry.key);
+    if (this.inFlight.size >= MAX_IN_FLIGHT) {
+      const oldest = this.inFlight.keys().next().value;
+      if (oldest !== undefined) this.inFlight.delete(oldest);
+    }
+    this.inFlight.set(entry.key, entry);
+  }
+
+  private take(key: string): InFlight | null {
+    const entry = this.inFlight.get(key);
+    if (entry === undefined) return null;
+    this.inFlight.delete(key);
+    return entry;
+  }
+
+  private handlerStack(threadId: number): Array<InFlight | null> {
+    let stack = this.handlers.get(threadId);
+    if (stack === undefined) {
+      stack = [];
+      this.handlers.set(threadId, stack);
+    }
+    return stack;
+  }
+}
+
+function frameNames(frames: ExceptionFrame[]): string[] {
+  return frames.map((f) => f.name ?? f.address);
+}
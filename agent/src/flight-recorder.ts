@@ -0,0 +1,113 @@
+/**
+ * Flight recorder: traced calls are kept in a fixed-size ring in the agent
+ * instead of being sent, and only leave it when something worth explaining
+ * happens (crash, breakpoint hit, matching stderr line, explicit flush).
+ * Everything that isn't a traced call passes straight through.
+ */
+
+export interface FlightRecorderMessage {
+  enabled: boolean;
+  capacity?: number | null;        // Traced events kept (daemon resolves the default)
+  flushOnOutput?: string[] | null; // stderr substrings that trigger a flush
+}
+
+const DEFAULT_CAPACITY = 50_000;
+// Keeps each send() comfortably below Frida's message size limits
+const FLUSH_CHUNK = 5_000;
+
+export class FlightRecorder {
+  private ring: any[] = [];
+  private head = 0;   // Next slot to write
+  private size = 0;
+  private capacity = DEFAULT_CAPACITY;
+  private triggers: string[] = [];
+  private active = false;
+  // Events overwritten since the last flush
+  private overwritten = 0;
+
+  constructor(private readonly emit: (events: any[]) => void) {}
+
+  get enabled(): boolean {
+    return this.active;
+  }
+
+  configure(msg: FlightRecorderMessage): void {
+    this.triggers = msg.flushOnOutput ?? [];
+    if (!msg.enabled) {
+      this.active = false;
+      this.ring = [];
+      this.head = 0;
+      this.size = 0;
+      this.overwritten = 0;
+      return;
+    }
+
+    const capacity = msg.capacity ?? DEFAULT_CAPACITY;
+    if (!this.active || capacity !== this.capacity) {
+      // Keep the newest events that fit the new capacity
+      const kept = this.ordered().slice(-capacity);
+      this.overwritten += this.size - kept.length;
+      this.capacity = capacity;
+      this.ring = kept;
+      this.size = kept.length;
+      this.head = kept.length % capacity;
+    }
+    this.active = true;
+  }
+
+  /** Buffer traced calls; returns the events that should be sent as usual. */
+  record(events: any[]): any[] {
+    const passthrough: any[] = [];
+    for (const event of events) {
+      if (event.eventType !== 'function_enter' && event.eventType !== 'function_exit') {
+        passthrough.push(event);
+        continue;
+      }
+      this.ring[this.head] = event;
+      this.head = (this.head + 1) % this.capacity;
+      if (this.size < this.capacity) {
+        this.size++;
+      } else {
+        this.overwritten++;
+      }
+    }
+    return passthrough;
+  }
+
+  /** Whether a stderr write should trigger a flush. */
+  matchesOutput(text: string): boolean {
+    if (!this.active) return false;
+    for (const trigger of this.triggers) {
+      if (text.includes(trigger)) return true;
+    }
+    return false;
+  }
+
+  /**
+   * Send everything buffered, oldest first, and empty the ring. Trigger
+   * flushes (anything but an explicit request) are announced to the daemon.
+   */
+  flush(reason: string): number {
+    const events = this.ordered();
+    const overwritten = this.overwritten;
+    this.ring = [];
+    this.head = 0;
+    this.size = 0;
+    this.overwritten = 0;
+
+    for (let i = 0; i < events.length; i += FLUSH_CHUNK) {
+      this.emit(events.slice(i, i + FLUSH_CHUNK));
+    }
+    if (reason !== 'request' && events.length > 0) {
+      send({ type: 'flight_recorder_flushed', reason, events: events.length, overwritten });
+    }
+    return events.length;
+  }
+
+  private ordered(): any[] {
+    if (this.size < this.capacity) {
+      return this.ring.slice(0, this.size);
+    }
+    return this.ring.slice(this.head).concat(this.ring.slice(0, this.head));
+  }
+}
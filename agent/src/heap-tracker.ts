@@ -0,0 +1,461 @@
+/**
+ * Allocation tracking: hooks the C allocator (malloc/calloc/realloc/free),
+ * C++ operator new/delete and, when the binary has them, Rust's global
+ * allocator shims. Every live allocation remembers its size and call site
+ * (the allocator's return address), and each call site keeps running totals,
+ * so a report can list the biggest allocators and what is still live.
+ *
+ * Allocators nest (operator new and __rust_alloc end up in malloc), so only
+ * the outermost allocator call on a thread is recorded — the call site is
+ * then the user's code rather than the allocator's own.
+ *
+ * Each allocation also keeps a short backtrace (interned, so identical stacks
+ * share one entry). Unwinding is the expensive part, so a busy call site is
+ * only unwound on a sample of its allocations; the others take the stack last
+ * unwound there. At session stop the daemon asks for the live allocations
+ * grouped by backtrace signature and stores each group as a `leak` event.
+ */
+
+import { findGlobalExport } from './utils.js';
+
+export type HeapAction = 'start' | 'report' | 'stop';
+
+export interface HeapMessage {
+  action: HeapAction;
+  limit?: number;
+  // report/stop: also group live allocations by backtrace
+  leaks?: boolean;
+}
+
+interface HeapFrame {
+  address: string;
+  moduleName: string | null;
+  name: string | null;
+  fileName: string | null;
+  lineNumber: number | null;
+}
+
+interface SiteStats {
+  allocations: number;
+  frees: number;
+  bytes: number;
+  largest: number;
+  liveBytes: number;
+  liveCount: number;
+}
+
+interface LiveAllocation {
+  size: number;
+  site: string;
+  // Index into stacks, -1 when past MAX_STACKS
+  stack: number;
+}
+
+interface LeakGroup {
+  count: number;
+  bytes: number;
+  largest: number;
+  samples: string[];
+}
+
+// Past these, allocations are still counted but no longer tracked individually
+const MAX_LIVE_ALLOCATIONS = 2_000_000;
+const MAX_SITES = 50_000;
+const OVERFLOW_SITE = '0x0';
+const DEFAULT_LIMIT = 20;
+// Example addresses listed per leaking call site
+const MAX_SAMPLES = 5;
+const MAX_STACK_FRAMES = 8;
+const MAX_STACKS = 20_000;
+const MAX_LEAK_GROUPS = 500;
+// Every call site unwinds its first allocations, then one in STACK_SAMPLE_EVERY
+const STACK_SAMPLE_FIRST = 8;
+const STACK_SAMPLE_EVERY = 16;
+
+type Hook = 'alloc' | 'calloc' | 'realloc' | 'free' | 'rustAlloc' | 'rustRealloc' | 'rustDealloc';
+
+const C_ALLOCATORS: Array<[string, Hook]> = [
+  ['malloc', 'alloc'], ['calloc', 'calloc'], ['realloc', 'realloc'], ['free', 'free'],
+  ['_Znwm', 'alloc'], ['_Znam', 'alloc'], ['_ZdlPv', 'free'], ['_ZdaPv', 'free'],
+  ['_ZdlPvm', 'free'], ['_ZdaPvm', 'free'],
+];
+
+// Matched by suffix: v0-mangled builds decorate the shim names
+const RUST_ALLOCATORS: Array<[string, Hook]> = [
+  ['__rust_alloc', 'rustAlloc'], ['__rust_alloc_zeroed', 'rustAlloc'],
+  ['__rust_realloc', 'rustRealloc'], ['__rust_dealloc', 'rustDealloc'],
+];
+
+export class HeapTracker {
+  private active = false;
+  private listeners: InvocationListener[] = [];
+  private hooked: string[] = [];
+  // Allocator calls in progress per thread; only depth 0 is recorded
+  private depth: Map<number, number> = new Map();
+  private live: Map<string, LiveAllocation> = new Map();
+  private sites: Map<string, SiteStats> = new Map();
+  private stackIds: Map<string, number> = new Map();
+  private stacks: NativePointer[][] = [];
+  // Stack last unwound per call site, reused between samples
+  private siteStacks: Map<string, number> = new Map();
+  private allocations = 0;
+  private frees = 0;
+  private bytesAllocated = 0;
+  private liveBytes = 0;
+  private peakLiveBytes = 0;
+  // Frees of blocks allocated before tracking started (or past the live cap)
+  private untrackedFrees = 0;
+  private truncated = false;
+
+  constructor(
+    private readonly symbolize: (addr: NativePointer) => HeapFrame,
+    private readonly now: () => number,
+  ) {}
+
+  handle(msg: HeapMessage): void {
+    try {
+      switch (msg.action) {
+        case 'start':
+          if (this.active) throw new Error('heap tracking is already running; stop it first');
+          this.start();
+          send({ type: 'heap_response', ...this.status() });
+          break;
+        case 'report':
+        case 'stop': {
+          if (!this.active) throw new Error('heap tracking is not running; start it first');
+          const response: any = { type: 'heap_response', ...this.status(), ...this.report(msg.limit ?? DEFAULT_LIMIT) };
+          if (msg.leaks) {
+            response.leakGroups = this.leakGroups();
+            response.timestampNs = this.now();
+          }
+          if (msg.action === 'stop') {
+            this.stop();
+            response.active = false;
+          }
+          send(response);
+          break;
+        }
+      }
+    } catch (e: any) {
+      send({ type: 'heap_response', error: e.message });
+    }
+  }
+
+  dispose(): void {
+    if (this.active) this.stop();
+  }
+
+  private start(): void {
+    this.reset();
+    for (const [name, hook] of C_ALLOCATORS) {
+      const addr = findGlobalExport(name);
+      if (addr) this.attach(name, addr, hook);
+    }
+    for (const [name, hook] of RUST_ALLOCATORS) {
+      const addr = findRustShim(name);
+      if (addr) this.attach(name, addr, hook);
+    }
+    if (this.listeners.length === 0) throw new Error('no allocator functions found to hook');
+    this.active = true;
+  }
+
+  private stop(): void {
+    for (const l of this.listeners) l.detach();
+    Interceptor.flush();
+    this.listeners = [];
+    this.active = false;
+    this.reset();
+  }
+
+  private reset(): void {
+    this.hooked = [];
+    this.depth.clear();
+    this.live.clear();
+    this.sites.clear();
+    this.stackIds.clear();
+    this.stacks = [];
+    this.siteStacks.clear();
+    this.allocations = 0;
+    this.frees = 0;
+    this.bytesAllocated = 0;
+    this.liveBytes = 0;
+    this.peakLiveBytes = 0;
+    this.untrackedFrees = 0;
+    this.truncated = false;
+  }
+
+  private attach(name: string, addr: NativePointer, hook: Hook): void {
+    const self = this;
+    try {
+      this.listeners.push(Interceptor.attach(addr, {
+        onEnter(args) {
+          const depth = self.depth.get(this.threadId) ?? 0;
+          this.outer = depth === 0;
+          // Counted even when recording throws, so onLeave's decrement pairs up
+          try {
+            if (!this.outer) return;
+            switch (hook) {
+              case 'alloc':
+              case 'rustAlloc':
+                this.size = sizeArg(args[0]);
+                break;
+              case 'calloc':
+                this.size = sizeArg(args[0]) * sizeArg(args[1]);
+                break;
+              case 'realloc':
+                this.old = args[0];
+                this.size = sizeArg(args[1]);
+                break;
+              case 'rustRealloc':
+                this.old = args[0];
+                this.size = sizeArg(args[3]);
+                break;
+              case 'free':
+              case 'rustDealloc':
+                self.release(args[0]);
+                break;
+            }
+          } finally {
+            self.depth.set(this.threadId, depth + 1);
+          }
+        },
+        onLeave(retval) {
+          try {
+            if (!this.outer) return;
+            switch (hook) {
+              case 'alloc':
+              case 'calloc':
+              case 'rustAlloc':
+                self.allocate(retval, this.size, this.returnAddress, this.context);
+                break;
+              case 'realloc':
+              case 'rustRealloc':
+                if (this.old.isNull()) {
+                  self.allocate(retval, this.size, this.returnAddress, this.context);
+                } else if (!retval.isNull()) {
+                  self.release(this.old);
+                  self.allocate(retval, this.size, this.returnAddress, this.context);
+                } else if (this.size === 0) {
+                  // realloc(p, 0) freed p
+                  self.release(this.old);
+                }
+                break;
+            }
+          } finally {
+            const depth = (self.depth.get(this.threadId) ?? 1) - 1;
+            if (depth === 0) self.depth.delete(this.threadId);
+            else self.depth.set(this.threadId, depth);
+          }
+        },
+      }));
+      this.hooked.push(name);
+    } catch (e: any) {
+      send({ type: 'log', message: `heap: cannot hook ${name}: ${e.message}` });
+    }
+  }
+
+
+  private allocate(addr: NativePointer, size: number, returnAddress: NativePointer, context: CpuContext): void {
+    if (addr.isNull()) return;
+    this.allocations++;
+    this.bytesAllocated += size;
+
+    let siteKey = returnAddress.toString();
+    let site = this.sites.get(siteKey);
+    if (site === undefined) {
+      if (this.sites.size >= MAX_SITES) {
+        this.truncated = true;
+        siteKey = OVERFLOW_SITE;
+        site = this.sites.get(siteKey);
+      }
+      if (site === undefined) {
+        site = { allocations: 0, frees: 0, bytes: 0, largest: 0, liveBytes: 0, liveCount: 0 };
+        this.sites.set(siteKey, site);
+      }
+    }
+    site.allocations++;
+    site.bytes += size;
+    if (size > site.largest) site.largest = size;
+
+    if (this.live.size >= MAX_LIVE_ALLOCATIONS) {
+      this.truncated = true;
+      return;
+    }
+    let stack = this.siteStacks.get(siteKey);
+    if (stack === undefined || site.allocations <= STACK_SAMPLE_FIRST
+        || site.allocations % STACK_SAMPLE_EVERY === 0) {
+      stack = this.internStack(returnAddress, context);
+      this.siteStacks.set(siteKey, stack);
+    }
+    this.live.set(addr.toString(), { size, site: siteKey, stack });
+    site.liveBytes += size;
+    site.liveCount++;
+    this.liveBytes += size;
+    if (this.liveBytes > this.peakLiveBytes) this.peakLiveBytes = this.liveBytes;
+  }
+
+  /** The allocating stack, starting at the call site, as an index into stacks. */
+  private internStack(returnAddress: NativePointer, context: CpuContext): number {
+    let frames: NativePointer[] = [returnAddress];
+    try {
+      const callers = Thread.backtrace(context, Backtracer.FUZZY);
+      const start = callers.length > 0 && callers[0].equals(returnAddress) ? 1 : 0;
+      frames = frames.concat(callers.slice(start, start + MAX_STACK_FRAMES - 1));
+    } catch (_) {
+      // Unwinding failed; the call site alone still groups
+    }
+    const key = frames.join(',');
+    let id = this.stackIds.get(key);
+    if (id === undefined) {
+      if (this.stacks.length >= MAX_STACKS) {
+        this.truncated = true;
+        return -1;
+      }
+      id = this.stacks.length;
+      this.stacks.push(frames);
+      this.stackIds.set(key, id);
+    }
+    return id;
+  }
+
+  private release(addr: NativePointer): void {
+    if (addr.isNull()) return;
+    this.frees++;
+    const key = addr.toString();
+    const allocation = this.live.get(key);
+    if (allocation === undefined) {
+      this.untrackedFrees++;
+      return;
+    }
+    this.live.delete(key);
+    this.liveBytes -= allocation.size;
+    const site = this.sites.get(allocation.site);
+    if (site !== undefined) {
+      site.frees++;
+      site.liveBytes -= allocation.size;
+      site.liveCount--;
+    }
+  }
+
+  private status(): { active: boolean; hooked: string[] } {
+    return { active: this.active, hooked: this.hooked };
+  }
+
+  /**
+   * Totals, the `limit` call sites that allocated the most bytes, and the
+   * `limit` call sites holding the most live bytes — with a few example
+   * addresses each, ready for debug_memory.
+   */
+  private report(limit: number): any {
+    const entries = [...this.sites.entries()];
+    const describe = (key: string, site: SiteStats) => {
+      const frame = key === OVERFLOW_SITE
+        ? { address: key, moduleName: null, name: '<other call sites>', fileName: null, lineNumber: null }
+        : this.symbolize(ptr(key));
+      return { ...frame, ...site };
+    };
+
+    const topSites = entries
+      .sort((a, b) => b[1].bytes - a[1].bytes)
+      .slice(0, limit)
+      .map(([key, site]) => describe(key, site));
+
+    const leakSites = entries
+      .filter(([, site]) => site.liveCount > 0)
+      .sort((a, b) => b[1].liveBytes - a[1].liveBytes)
+      .slice(0, limit);
+    const samples: Map<string, string[]> = new Map(leakSites.map(([key]) => [key, []]));
+    let pending = leakSites.length;
+    for (const [addr, allocation] of this.live) {
+      if (pending === 0) break;
+      const list = samples.get(allocation.site);
+      if (list === undefined || list.length >= MAX_SAMPLES) continue;
+      list.push(addr);
+      if (list.length === MAX_SAMPLES) pending--;
+    }
+    const leaks = leakSites.map(([key, site]) => ({ ...describe(key, site), samples: samples.get(key) }));
+
+    return {
+      totals: {
+        allocations: this.allocations,
+        frees: this.frees,
+        bytesAllocated: this.bytesAllocated,
+        liveBytes: this.liveBytes,
+        liveCount: this.live.size,
+        peakLiveBytes: this.peakLiveBytes,
+        untrackedFrees: this.untrackedFrees,
+        sites: this.sites.size,
+      },
+      topSites,
+      leaks,
+      truncated: this.truncated,
+    };
+  }
+
+  /**
+   * Live allocations grouped by allocating stack, most bytes first. The
+   * signature hashes module+offset of each frame, so the same leak has the
+   * same signature in every run of the binary.
+   */
+  private leakGroups(): any[] {
+    const groups: Map<number, LeakGroup> = new Map();
+    for (const [addr, allocation] of this.live) {
+      let group = groups.get(allocation.stack);
+      if (group === undefined) {
+        group = { count: 0, bytes: 0, largest: 0, samples: [] };
+        groups.set(allocation.stack, group);
+      }
+      group.count++;
+      group.bytes += allocation.size;
+      if (allocation.size > group.largest) group.largest = allocation.size;
+      if (group.samples.length < MAX_SAMPLES) group.samples.push(addr);
+    }
+
+    const modules = new ModuleMap();
+    return [...groups.entries()]
+      .sort((a, b) => b[1].bytes - a[1].bytes)
+      .slice(0, MAX_LEAK_GROUPS)
+      .map(([stack, group]) => {
+        const frames = stack >= 0 ? this.stacks[stack] : [];
+        const stable = frames.map((frame) => {
+          const module = modules.find(frame);
+          return module === null ? frame.toString() : `${module.name}+${frame.sub(module.base)}`;
+        });
+        return {
+          signature: fnv1a(stable.length > 0 ? stable.join(';') : 'unknown'),
+          ...group,
+          backtrace: frames.map(this.symbolize),
+        };
+      });
+  }
+}
+
+// Allocation sizes are size_t; a 32-bit read would wrap sizes past 4 GiB
+function sizeArg(arg: NativePointer): number {
+  return uint64(arg.toString()).toNumber();
+}
+
+// 32-bit FNV-1a as 8 hex digits
+function fnv1a(text: string): string {
+  let hash = 0x811c9dc5;
+  for (let i = 0; i < text.length; i++) {
+    hash ^= text.charCodeAt(i);
+    hash = Math.imul(hash, 0x01000193);
+  }
+  return (hash >>> 0).toString(16).padStart(8, '0');
+}
+
+function findRustShim(name: string): NativePointer | null {
+  const exported = findGlobalExport(name);
+  if (exported) return exported;
+  try {
+    const main = Process.mainModule;
+    for (const addr of DebugSymbol.findFunctionsMatching(`*${name}`)) {
+      const sym = DebugSymbol.fromAddress(addr);
+      if (sym.moduleName === main.name && sym.name !== null && sym.name.endsWith(name)) return addr;
+    }
+  } catch (_) {
+    // No symbol table
+  }
+  return null;
+}
@@ -0,0 +1,253 @@
+/**
+ * Lock contention (`@locks` trace pattern). pthread_mutex_lock/unlock are
+ * hooked to time each acquisition and to remember which thread holds which
+ * mutex (trylock and condvar waits keep that up to date); an acquisition that waited on another thread's lock, or took longer
+ * than CONTENDED_WAIT_NS, is recorded with the wait, the call site and the
+ * holder. Rust's parking_lot and std locks only leave their fast path when
+ * contended, so their slow-path functions are hooked instead, where the
+ * symbol table has them (the holder isn't known there).
+ *
+ * A thread still waiting after BLOCKED_AFTER_NS is reported once as blocked,
+ * so a deadlocked test shows who waits on what while it hangs.
+ */
+
+import { findGlobalExport } from './utils.js';
+
+export interface LocksMessage {
+  enabled: boolean;
+}
+
+// Uncontended pthread locks take well under a microsecond
+const CONTENDED_WAIT_NS = 50_000;
+const BLOCKED_AFTER_NS = 1_000_000_000;
+const BLOCKED_CHECK_MS = 500;
+// Matches hooked per slow-path pattern (generic instantiations)
+const MAX_SLOW_PATH_MATCHES = 16;
+// Rust lock slow paths, entered only when the lock is taken; self is the first argument
+const SLOW_PATHS: Array<{ pattern: string; primitive: string }> = [
+  { pattern: '*parking_lot*RawMutex*lock_slow*', primitive: 'parking_lot::Mutex' },
+  { pattern: '*parking_lot*RawRwLock*lock_exclusive_slow*', primitive: 'parking_lot::RwLock (write)' },
+  { pattern: '*parking_lot*RawRwLock*lock_shared_slow*', primitive: 'parking_lot::RwLock (read)' },
+  { pattern: '*std*sys*Mutex*lock_contended*', primitive: 'std::sync::Mutex' },
+  { pattern: '*std*sys*RwLock*write_contended*', primitive: 'std::sync::RwLock (write)' },
+  { pattern: '*std*sys*RwLock*read_contended*', primitive: 'std::sync::RwLock (read)' },
+];
+
+type LockKind = 'contended' | 'blocked';
+
+interface Holder {
+  threadId: number;
+  site: NativePointer;
+}
+
+interface Wait {
+  lock: string;
+  primitive: string;
+  site: NativePointer;
+  since: number;
+  holder: Holder | null;
+  reported: boolean;
+}
+
+export class LockTracer {
+  private hooks: InvocationListener[] = [];
+  // pthread mutex address -> the thread that last acquired it
+  private owners: Map<string, Holder> = new Map();
+  // threadId -> the lock it is waiting for
+  private waiting: Map<number, Wait> = new Map();
+  private blockedTimer: ReturnType<typeof setInterval> | null = null;
+
+  constructor(
+    private readonly emit: (event: any) => void,
+    private readonly symbolName: (addr: NativePointer) => string | null,
+    private readonly now: () => number,
+  ) {}
+
+  /** Returns how many functions are hooked. */
+  configure(msg: LocksMessage): number {
+    for (const l of this.hooks) l.detach();
+    this.hooks = [];
+    this.owners.clear();
+    this.waiting.clear();
+    if (this.blockedTimer !== null) {
+      clearInterval(this.blockedTimer);
+      this.blockedTimer = null;
+    }
+    if (!msg.enabled) return 0;
+
+    this.hookPthreadMutex();
+    this.hookSlowPaths();
+    if (this.hooks.length > 0) {
+      this.blockedTimer = setInterval(() => this.reportBlocked(), BLOCKED_CHECK_MS);
+    }
+    return this.hooks.length;
+  }
+
+  private hookPthreadMutex(): void {
+    const self = this;
+    const lock = findGlobalExport('pthread_mutex_lock');
+    const unlock = findGlobalExport('pthread_mutex_unlock');
+    if (lock === null || unlock === null) return;
+    try {
+      this.hooks.push(Interceptor.attach(lock, {
+        onEnter(args) {
+          this.key = args[0].toString();
+          this.site = this.returnAddress;
+          self.waiting.set(this.threadId, {
+            lock: this.key,
+            primitive: 'pthread_mutex_lock',
+            site: this.returnAddress,
+            since: self.now(),
+            holder: self.owners.get(this.key) ?? null,
+            reported: false,
+          });
+        },
+        onLeave(retval) {
+          const wait = self.waiting.get(this.threadId);
+          self.waiting.delete(this.threadId);
+          if (retval.toInt32() !== 0) return;
+          self.owners.set(this.key, { threadId: this.threadId, site: this.site });
+          if (wait === undefined) return;
+          const waitNs = self.now() - wait.since;
+          const heldElsewhere = wait.holder !== null && wait.holder.threadId !== this.threadId;
+          if (heldElsewhere || waitNs >= CONTENDED_WAIT_NS) {
+            self.report(this.threadId, wait, 'contended', waitNs);
+          }
+        },
+      }));
+      this.hooks.push(Interceptor.attach(unlock, {
+        onEnter(args) {
+          self.owners.delete(args[0].toString());
+        },
+      }));
+    } catch (_) {
+      // Not hookable on this platform
+      return;
+    }
+    this.hookOwnershipChanges();
+  }
+
+  /**
+   * Keep `owners` right across the calls that take or drop a mutex without
+   * pthread_mutex_lock/unlock: a successful trylock acquires it, and a
+   * condvar wait releases it while waiting and holds it again on return
+   * (timed out or not). Without this every mutex used with a condvar would
+   * look held by a thread parked in pthread_cond_wait.
+   */
+  private hookOwnershipChanges(): void {
+    const self = this;
+    const trylock = findGlobalExport('pthread_mutex_trylock');
+    if (trylock !== null) {
+      try {
+        this.hooks.push(Interceptor.attach(trylock, {
+          onEnter(args) {
+            this.key = args[0].toString();
+            this.site = this.returnAddress;
+          },
+          onLeave(retval) {
+            if (retval.toInt32() === 0) {
+              self.owners.set(this.key, { threadId: this.threadId, site: this.site });
+            }
+          },
+        }));
+      } catch (_) {
+        // Not hookable; ownership may then be stale for trylock users
+      }
+    }
+    for (const name of ['pthread_cond_wait', 'pthread_cond_timedwait']) {
+      const addr = findGlobalExport(name);
+      if (addr === null) continue;
+      try {
+        this.hooks.push(Interceptor.attach(addr, {
+          onEnter(args) {
+            this.key = args[1].toString();
+            this.site = this.returnAddress;
+            self.owners.delete(this.key);
+          },
+          onLeave() {
+            self.owners.set(this.key, { threadId: this.threadId, site: this.site });
+          },
+        }));
+      } catch (_) {
+        // Not hookable; the rest still work
+      }
+    }
+  }
+
+  private hookSlowPaths(): void {
+    const self = this;
+    const hooked = new Set<string>();
+    for (const { pattern, primitive } of SLOW_PATHS) {
+      let matches: NativePointer[] = [];
+      try {
+        matches = DebugSymbol.findFunctionsMatching(pattern).slice(0, MAX_SLOW_PATH_MATCHES);
+      } catch (_) {
+        // No symbol table
+      }
+      for (const addr of matches) {
+        if (hooked.has(addr.toString())) continue;
+        hooked.add(addr.toString());
+        try {
+          this.hooks.push(Interceptor.attach(addr, {
+            onEnter(args) {
+              self.waiting.set(this.threadId, {
+                lock: args[0].toString(),
+                primitive,
+                site: this.returnAddress,
+                since: self.now(),
+                holder: null,
+                reported: false,
+              });
+            },
+            onLeave() {
+              const wait = self.waiting.get(this.threadId);
+              self.waiting.delete(this.threadId);
+              if (wait !== undefined) self.report(this.threadId, wait, 'contended', self.now() - wait.since);
+            },
+          }));
+        } catch (_) {
+          // Not hookable; the rest still work
+        }
+      }
+    }
+  }
+
+  /** Threads waiting longer than BLOCKED_AFTER_NS, reported once per wait. */
+  private reportBlocked(): void {
+    const now = this.now();
+    for (const [threadId, wait] of this.waiting) {
+      if (wait.reported || now - wait.since < BLOCKED_AFTER_NS) continue;
+      wait.reported = true;
+      this.report(threadId, wait, 'blocked', now - wait.since);
+    }
+  }
+
+  private report(threadId: number, wait: Wait, kind: LockKind, waitNs: number): void {
+    try {
+      const site = this.symbolName(wait.site) ?? wait.site.toString();
+      const holder = wait.holder !== null && wait.holder.threadId !== threadId ? wait.holder : null;
+      const holderSite = holder !== null ? this.symbolName(holder.site) ?? holder.site.toString() : null;
+      const ms = (waitNs / 1e6).toFixed(2);
+      const by = holder !== null ? ` held by thread ${holder.threadId} (${holderSite})` : '';
+      this.emit({
+        threadId,
+        timestampNs: wait.since,
+        eventType: 'lock',
+        functionName: site,
+        kind,
+        lock: wait.lock,
+        primitive: wait.primitive,
+        waitNs,
+        // A blocked thread's wait isn't over yet
+        durationNs: kind === 'contended' ? waitNs : null,
+        holderThreadId: holder !== null ? holder.threadId : null,
+        holderSite,
+        text: kind === 'contended'
+          ? `${site} waited ${ms}ms for ${wait.primitive} ${wait.lock}${by}`
+          : `${site} blocked for ${ms}ms on ${wait.primitive} ${wait.lock}${by}`,
+      });
+    } catch (_) {
+      // Never fail the locking thread
+    }
+  }
+}
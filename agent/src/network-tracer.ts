@@ -0,0 +1,281 @@
+/**
+ * Network activity (`@network` trace pattern): which hosts the process talks
+ * to and how much flows. Hooks getaddrinfo (DNS), connect, and the TLS
+ * libraries' write/read — OpenSSL/BoringSSL everywhere, SecureTransport on
+ * macOS — reading the server name (SNI) the client sets on each connection.
+ *
+ * DNS answers are remembered so a connect to 93.184.216.34 is reported as
+ * example.com. TLS libraries loaded after tracing starts are picked up at the
+ * next connect.
+ */
+
+import { findGlobalExport } from './utils.js';
+import { parseSockaddr } from './syscall-tracer.js';
+
+export interface NetworkMessage {
+  enabled: boolean;
+}
+
+type NetworkKind = 'dns' | 'connect' | 'tls';
+
+interface Peer {
+  host: string | null;
+  address: string | null;
+  port: number | null;
+}
+
+// SSL_set_tlsext_host_name(ssl, name) is SSL_ctrl(ssl, 55, 0, name)
+const SSL_CTRL_SET_TLSEXT_HOSTNAME = 55;
+const EINPROGRESS = Process.platform === 'darwin' ? 36 : 115;
+// Remembered DNS answers, sockets and TLS connections
+const MAX_ENTRIES = 4096;
+const MAX_ADDRESSES = 16;
+
+export class NetworkTracer {
+  private hooks: InvocationListener[] = [];
+  private tlsHooked = false;
+  // IP -> host name from getaddrinfo
+  private names: Map<string, string> = new Map();
+  // fd -> connected peer
+  private sockets: Map<number, Peer> = new Map();
+  // SSL* / SSLContextRef -> server name
+  private serverNames: Map<string, string> = new Map();
+
+  constructor(
+    private readonly emit: (event: any) => void,
+    private readonly now: () => number,
+  ) {}
+
+  /** Returns how many functions are hooked. */
+  configure(msg: NetworkMessage): number {
+    for (const l of this.hooks) l.detach();
+    this.hooks = [];
+    this.tlsHooked = false;
+    this.names.clear();
+    this.sockets.clear();
+    this.serverNames.clear();
+    if (!msg.enabled) return 0;
+
+    this.hookDns();
+    this.hookConnect();
+    this.hookTls();
+    return this.hooks.length;
+  }
+
+  private attach(name: string, callbacks: InvocationListenerCallbacks): boolean {
+    const addr = findGlobalExport(name);
+    if (!addr) return false;
+    try {
+      this.hooks.push(Interceptor.attach(addr, callbacks));
+      return true;
+    } catch (_) {
+      // Not hookable on this platform
+      return false;
+    }
+  }
+
+  private hookDns(): void {
+    const self = this;
+    this.attach('getaddrinfo', {
+      onEnter(args) {
+        this.startNs = self.now();
+        this.node = args[0].isNull() ? null : args[0].readUtf8String();
+        this.service = args[1].isNull() ? null : args[1].readUtf8String();
+        this.res = args[3];
+      },
+      onLeave(retval) {
+        if (this.node === null) return;
+        const result = retval.toInt32();
+        const addresses = result === 0 ? self.readAddrinfo(this.res.readPointer()) : [];
+        for (const address of addresses) self.remember(self.names, address, this.node);
+        const port = this.service !== null && /^\d+$/.test(this.service) ? parseInt(this.service, 10) : null;
+        self.record(this, 'getaddrinfo', 'dns', {
+          host: this.node,
+          port,
+          addresses,
+          result,
+          text: result === 0
+            ? `DNS ${this.node} -> ${addresses.join(', ') || 'no addresses'}`
+            : `DNS ${this.node} failed (${result})`,
+        });
+      },
+    });
+  }
+
+  /** IPs of a getaddrinfo result list, deduplicated. */
+  private readAddrinfo(list: NativePointer): string[] {
+    // struct addrinfo: ai_addr and ai_canonname swap places on Darwin
+    const ptrSize = Process.pointerSize;
+    const addrOffset = Process.platform === 'darwin' ? 16 + 2 * ptrSize : 16 + ptrSize;
+    const nextOffset = 16 + 3 * ptrSize;
+    const addresses: string[] = [];
+    let entry = list;
+    try {
+      while (!entry.isNull() && addresses.length < MAX_ADDRESSES) {
+        const parsed = parseSockaddr(entry.add(addrOffset).readPointer());
+        if (parsed !== null && parsed.inet && !addresses.includes(parsed.host)) {
+          addresses.push(parsed.host);
+        }
+        entry = entry.add(nextOffset).readPointer();
+      }
+    } catch (_) {
+      // A truncated list still names what it could read
+    }
+    return addresses;
+  }
+
+  private hookConnect(): void {
+    const self = this;
+    this.attach('connect', {
+      onEnter(args) {
+        this.startNs = self.now();
+        this.fd = args[0].toInt32();
+        this.peer = parseSockaddr(args[1]);
+      },
+      onLeave(retval) {
+        if (!self.tlsHooked) self.hookTls();
+        const peer = this.peer;
+        if (peer === null || !peer.inet) return;
+        const result = retval.toInt32();
+        const connected = result === 0 || this.errno === EINPROGRESS;
+        const host = self.names.get(peer.host) ?? null;
+        if (connected) {
+          self.remember(self.sockets, this.fd, { host, address: peer.host, port: peer.port });
+        }
+        const target = `${host ?? peer.host}:${peer.port}`;
+        self.record(this, 'connect', 'connect', {
+          host,
+          address: peer.host,
+          port: peer.port,
+          result,
+          text: connected ? `connect ${target}` : `connect ${target} failed (errno ${this.errno})`,
+        });
+      },
+    });
+    this.attach('close', {
+      onEnter(args) {
+        self.sockets.delete(args[0].toInt32());
+      },
+    });
+  }
+
+  private hookTls(): void {
+    if (findGlobalExport('SSL_write') !== null) {
+      this.tlsHooked = true;
+      this.hookOpenSsl();
+    }
+    if (Process.platform === 'darwin' && findGlobalExport('SSLWrite') !== null) {
+      this.tlsHooked = true;
+      this.hookSecureTransport();
+    }
+  }
+
+  private hookOpenSsl(): void {
+    const self = this;
+    const getFdAddr = findGlobalExport('SSL_get_fd');
+    const getFd = getFdAddr ? new NativeFunction(getFdAddr, 'int', ['pointer']) : null;
+
+    this.attach('SSL_ctrl', {
+      onEnter(args) {
+        if (args[1].toInt32() !== SSL_CTRL_SET_TLSEXT_HOSTNAME || args[3].isNull()) return;
+        const name = args[3].readUtf8String();
+        if (name) self.remember(self.serverNames, args[0].toString(), name);
+      },
+    });
+    this.attach('SSL_free', {
+      onEnter(args) {
+        self.serverNames.delete(args[0].toString());
+      },
+    });
+
+    const peerOf = (ssl: NativePointer): Peer => {
+      let socket: Peer | undefined;
+      if (getFd) {
+        try {
+          socket = self.sockets.get(getFd(ssl) as number);
+        } catch (_) {
+          // No fd (memory BIO)
+        }
+      }
+      const serverName = self.serverNames.get(ssl.toString()) ?? null;
+      return {
+        host: serverName ?? socket?.host ?? null,
+        address: socket?.address ?? null,
+        port: socket?.port ?? null,
+      };
+    };
+    for (const [name, direction] of [['SSL_write', 'out'], ['SSL_read', 'in']] as const) {
+      this.attach(name, {
+        onEnter(args) {
+          this.startNs = self.now();
+          this.ssl = args[0];
+        },
+        onLeave(retval) {
+          const bytes = retval.toInt32();
+          if (bytes <= 0) return;
+          self.recordTls(this, name, direction, peerOf(this.ssl), bytes);
+        },
+      });
+    }
+  }
+
+  private hookSecureTransport(): void {
+    const self = this;
+    this.attach('SSLSetPeerDomainName', {
+      onEnter(args) {
+        if (args[1].isNull()) return;
+        const name = args[1].readUtf8String(args[2].toInt32());
+        if (name) self.remember(self.serverNames, args[0].toString(), name);
+      },
+    });
+    // OSStatus SSLWrite/SSLRead(ctx, data, length, size_t *processed)
+    for (const [name, direction] of [['SSLWrite', 'out'], ['SSLRead', 'in']] as const) {
+      this.attach(name, {
+        onEnter(args) {
+          this.startNs = self.now();
+          this.ctx = args[0];
+          this.processed = args[3];
+        },
+        onLeave() {
+          if (this.processed.isNull()) return;
+          const bytes = this.processed.readULong().toNumber();
+          if (bytes <= 0) return;
+          const host = self.serverNames.get(this.ctx.toString()) ?? null;
+          self.recordTls(this, name, direction, { host, address: null, port: null }, bytes);
+        },
+      });
+    }
+  }
+
+  private recordTls(call: InvocationContext, fn: string, direction: 'in' | 'out', peer: Peer, bytes: number): void {
+    const target = peer.host ?? peer.address ?? 'unknown host';
+    const port = peer.port !== null ? `:${peer.port}` : '';
+    this.record(call, fn, 'tls', {
+      host: peer.host,
+      address: peer.address,
+      port: peer.port,
+      bytes,
+      direction,
+      result: bytes,
+      text: `TLS ${target}${port} ${direction === 'out' ? 'sent' : 'received'} ${bytes} bytes`,
+    });
+  }
+
+  private record(call: InvocationContext, fn: string, kind: NetworkKind, details: Record<string, unknown>): void {
+    const startNs: number = call.startNs;
+    this.emit({
+      threadId: call.threadId,
+      timestampNs: startNs,
+      eventType: 'network',
+      functionName: fn,
+      kind,
+      durationNs: this.now() - startNs,
+      ...details,
+    });
+  }
+
+  private remember<K, V>(map: Map<K, V>, key: K, value: V): void {
+    if (!map.has(key) && map.size >= MAX_ENTRIES) return;
+    map.set(key, value);
+  }
+}
@@ -16,8 +16,28 @@ export type TypeInfo = {
   pointedType?: TypeInfo;
   arrayLength?: number;
   elementType?: TypeInfo;
+  container?: ContainerLayout;
 };
 
+/**
+ * Standard-library container layout resolved from DWARF by the daemon.
+ * Offsets are relative to the container object; map valueOffset is relative
+ * to the node's pair<const K, V>.
+ */
+export type ContainerLayout =
+  | { kind: 'ptrLenString'; ptrOffset: number; lenOffset: number }
+  | { kind: 'libcxxString' }
+  | { kind: 'beginEndVector'; beginOffset: number; endOffset: number; element: TypeInfo }
+  | { kind: 'ptrLenVector'; ptrOffset: number; lenOffset: number; element: TypeInfo }
+  | { kind: 'libstdcxxMap'; headerOffset: number; countOffset: number;
+      key: TypeInfo; value: TypeInfo; valueOffset: number }
+  | { kind: 'libcxxMap'; beginOffset: number; endOffset: number; countOffset: number;
+      key: TypeInfo; value: TypeInfo; valueOffset: number };
+
+const MAX_STRING_BYTES = 256;
+const MAX_CONTAINER_ELEMENTS = 100;
+const MAX_MAP_ENTRIES = 50;
+
 export type SerializedValue = string | number | Record<string, any> | any[];
 
 export class ObjectSerializer {
@@ -51,6 +71,10 @@ export class ObjectSerializer {
   }
 
   private serializeValue(address: NativePointer, typeInfo: TypeInfo): SerializedValue {
+    if (typeInfo.container) {
+      return this.serializeContainer(address, typeInfo.container);
+    }
+
     switch (typeInfo.typeKind) {
       case 'int':
         return this.readInteger(address, typeInfo.byteSize, typeInfo.signed !== false);
@@ -184,6 +208,145 @@ export class ObjectSerializer {
     return result;
   }
 
+  private serializeContainer(addr: NativePointer, layout: ContainerLayout): SerializedValue {
+    if (!this.isReadable(addr)) {
+      return `<invalid ptr ${addr}>`;
+    }
+    try {
+      switch (layout.kind) {
+        case 'ptrLenString':
+          return this.readString(
+            addr.add(layout.ptrOffset).readPointer(),
+            Number(addr.add(layout.lenOffset).readU64()));
+
+        case 'libcxxString': {
+          // Short strings keep size << 1 in byte 0 and the chars inline at +1;
+          // long strings are { cap | 1, size, data }.
+          const flag = addr.readU8();
+          if ((flag & 1) === 0) {
+            return this.readString(addr.add(1), flag >> 1);
+          }
+          const ps = Process.pointerSize;
+          return this.readString(addr.add(2 * ps).readPointer(), Number(addr.add(ps).readU64()));
+        }
+
+        case 'beginEndVector': {
+          const begin = addr.add(layout.beginOffset).readPointer();
+          const end = addr.add(layout.endOffset).readPointer();
+          const stride = layout.element.byteSize;
+          const count = stride > 0 ? Math.floor(end.sub(begin).toUInt32() / stride) : 0;
+          return this.readElements(begin, count, layout.element);
+        }
+
+        case 'ptrLenVector':
+          return this.readElements(
+            addr.add(layout.ptrOffset).readPointer(),
+            Number(addr.add(layout.lenOffset).readU64()),
+            layout.element);
+
+        case 'libstdcxxMap': {
+          // _Rb_tree_node_base: { color, parent, left, right }
+          const header = addr.add(layout.headerOffset);
+          const ps = Process.pointerSize;
+          return this.readTree(
+            header.add(2 * ps).readPointer(), header,
+            Number(addr.add(layout.countOffset).readU64()),
+            { left: 2 * ps, right: 3 * ps, parent: ps }, layout);
+        }
+
+        case 'libcxxMap': {
+          // __tree_node_base: { left, right, parent, is_black }
+          const ps = Process.pointerSize;
+          return this.readTree(
+            addr.add(layout.beginOffset).readPointer(), addr.add(layout.endOffset),
+            Number(addr.add(layout.countOffset).readU64()),
+            { left: 0, right: ps, parent: 2 * ps }, layout);
+        }
+      }
+    } catch (e) {
+      return `<read error: ${e}>`;
+    }
+  }
+
+  private readString(data: NativePointer, length: number): string {
+    if (length === 0) return '';
+    if (!this.isReadable(data)) return `<invalid ptr ${data}>`;
+    const n = Math.min(length, MAX_STRING_BYTES);
+    const text = data.readUtf8String(n) ?? '';
+    return length > n ? `${text}...(${length} bytes)` : text;
+  }
+
+  private readElements(data: NativePointer, count: number, element: TypeInfo): any[] {
+    if (count === 0) return [];
+    if (!this.isReadable(data)) return [`<invalid ptr ${data}>`];
+    const result: any[] = [];
+    const shown = Math.min(count, MAX_CONTAINER_ELEMENTS);
+    for (let i = 0; i < shown; i++) {
+      try {
+        result.push(this.serialize(data.add(i * element.byteSize), element));
+      } catch (e) {
+        result.push(`<error at index ${i}>`);
+        break;
+      }
+    }
+    if (count > shown) result.push(`<${count - shown} more>`);
+    return result;
+  }
+
+  /** In-order walk of an rb-tree from the leftmost node until the end sentinel. */
+  private readTree(
+    first: NativePointer,
+    endNode: NativePointer,
+    count: number,
+    links: { left: number; right: number; parent: number },
+    layout: { key: TypeInfo; value: TypeInfo; valueOffset: number },
+  ): Record<string, any> | any[] {
+    const valueBase = 4 * Process.pointerSize;
+    const entries: Array<[any, any]> = [];
+    let node = first;
+    while (!node.equals(endNode) && !node.isNull() && entries.length < MAX_MAP_ENTRIES) {
+      const pair = node.add(valueBase);
+      entries.push([
+        this.serialize(pair, layout.key),
+        this.serialize(pair.add(layout.valueOffset), layout.value),
+      ]);
+
+      // Successor: leftmost of right subtree, else climb until we come from a left child.
+      const right = node.add(links.right).readPointer();
+      if (!right.isNull()) {
+        node = right;
+        for (let left = node.add(links.left).readPointer(); !left.isNull();
+             left = node.add(links.left).readPointer()) {
+          node = left;
+        }
+      } else {
+        let parent = node.add(links.parent).readPointer();
+        while (!parent.equals(endNode) && node.equals(parent.add(links.right).readPointer())) {
+          node = parent;
+          parent = node.add(links.parent).readPointer();
+        }
+        node = parent;
+      }
+    }
+
+    // Scalar keys read naturally as an object; anything else stays a pair list.
+    const scalarKeys = entries.every(([k]) => typeof k === 'string' || typeof k === 'number');
+    const result: Record<string, any> | any[] = scalarKeys
+      ? Object.fromEntries(entries.map(([k, v]) => [String(k), v]))
+      : entries.map(([k, v]) => ({ key: k, value: v }));
+    if (count > entries.length) {
+      const more = `<${count - entries.length} more>`;
+      if (Array.isArray(result)) result.push(more); else result['...'] = more;
+    }
+    return result;
+  }
+
+  private isReadable(addr: NativePointer): boolean {
+    if (addr.isNull()) return false;
+    const range = Process.findRangeByAddress(addr);
+    return range !== null && range.protection.includes('r');
+  }
+
   reset(): void {
     this.visited.clear();
     this.currentDepth = 0;
@@ -0,0 +1,176 @@
+/**
+ * Panics and failed assertions, recorded in every native process whatever is
+ * traced: Rust's `rust_panic`, the C assert handlers (`__assert_fail` on
+ * glibc, `__assert_rtn` on macOS) and `abort`. An abort that follows an
+ * uncaught C++ exception carries the exception captured at `__cxa_throw`.
+ *
+ * `rust_panic` doesn't expose the message, so the "thread 'x' panicked at"
+ * text the default panic hook writes to stderr just before is used. The test
+ * harness captures that output, so panics inside `cargo test` tests often
+ * come without a message; the backtrace still says where.
+ */
+
+import { findGlobalExport } from './utils.js';
+
+export interface PanicFrame {
+  address: string;
+  moduleName: string | null;
+  name: string | null;
+  fileName: string | null;
+  lineNumber: number | null;
+}
+
+export interface ThrownException {
+  type: string;
+  message: string | null;
+}
+
+type PanicKind = 'rust_panic' | 'assert' | 'abort';
+
+const MAX_FRAMES = 32;
+// A panic or assert ends in abort() soon after; don't report the abort again
+const ABORT_AFTER_PANIC_MS = 2000;
+// Panic text on stderr this recent belongs to the panic being reported
+const PANIC_OUTPUT_MS = 1000;
+// Unmangled std symbols present in every binary that links Rust's std
+const RUST_STD_SYMBOLS = ['rust_begin_unwind', '__rust_start_panic'];
+
+export class PanicHooks {
+  private hooks: InvocationListener[] = [];
+  // threadId -> when it last reported a panic or assert
+  private lastPanic: Map<number, number> = new Map();
+  private panicOutput: { threadId: number; text: string; at: number } | null = null;
+
+  constructor(
+    private readonly emit: (event: any) => void,
+    private readonly symbolize: (addr: NativePointer) => PanicFrame,
+    private readonly lastException: (threadId: number) => ThrownException | null,
+  ) {}
+
+  /** Returns the hooked function names. */
+  install(): string[] {
+    const installed: string[] = [];
+    if (this.hookRustPanic()) installed.push('rust_panic');
+    if (this.hookAssert('__assert_fail', 0, 1, 2)) installed.push('__assert_fail');
+    if (this.hookAssert('__assert_rtn', 3, 1, 2)) installed.push('__assert_rtn');
+    if (this.hookAbort()) installed.push('abort');
+    return installed;
+  }
+
+  /** Called with each stderr write; remembers the default Rust panic hook's message. */
+  noteStderr(text: string): void {
+    if (!text.includes('panicked at')) return;
+    this.panicOutput = { threadId: Process.getCurrentThreadId(), text: text.trim(), at: Date.now() };
+  }
+
+  private attach(addr: NativePointer, onEnter: (this: InvocationContext, args: InvocationArguments) => void): boolean {
+    try {
+      this.hooks.push(Interceptor.attach(addr, { onEnter }));
+      return true;
+    } catch (_) {
+      return false;
+    }
+  }
+
+  private hookRustPanic(): boolean {
+    const addr = findRustPanic();
+    if (addr === null) return false;
+    const self = this;
+    return this.attach(addr, function () {
+      const output = self.panicOutput;
+      const message = output !== null && output.threadId === this.threadId
+        && Date.now() - output.at < PANIC_OUTPUT_MS ? output.text : null;
+      self.panicOutput = null;
+      self.report(this, 'rust_panic', 'rust_panic', message ?? `thread ${this.threadId} panicked`, {});
+    });
+  }
+
+  private hookAssert(name: string, exprArg: number, fileArg: number, lineArg: number): boolean {
+    const addr = findGlobalExport(name);
+    if (addr === null) return false;
+    const self = this;
+    return this.attach(addr, function (args) {
+      const read = (p: NativePointer): string | null => {
+        try {
+          return p.isNull() ? null : p.readUtf8String();
+        } catch (_) {
+          return null;
+        }
+      };
+      const assertion = read(args[exprArg]);
+      const file = read(args[fileArg]);
+      const line = args[lineArg].toInt32();
+      const where = file !== null ? ` at ${file}:${line}` : '';
+      self.report(this, name, 'assert', `assertion failed: ${assertion ?? '?'}${where}`, {
+        assertion,
+        file,
+        line,
+      });
+    });
+  }
+
+  private hookAbort(): boolean {
+    const addr = findGlobalExport('abort');
+    if (addr === null) return false;
+    const self = this;
+    return this.attach(addr, function () {
+      const last = self.lastPanic.get(this.threadId);
+      if (last !== undefined && Date.now() - last < ABORT_AFTER_PANIC_MS) return;
+      const exception = self.lastException(this.threadId);
+      const message = exception !== null
+        ? `terminate called after throwing '${exception.type}'${exception.message !== null ? `: ${exception.message}` : ''}`
+        : 'abort() called';
+      self.report(this, 'abort', 'abort', message, exception !== null
+        ? { exceptionType: exception.type, exceptionMessage: exception.message }
+        : {});
+    });
+  }
+
+  private report(
+    call: InvocationContext, fn: string, kind: PanicKind, message: string, details: Record<string, unknown>,
+  ): void {
+    try {
+      let backtrace: PanicFrame[] = [];
+      try {
+        backtrace = Thread.backtrace(call.context, Backtracer.ACCURATE)
+          .slice(0, MAX_FRAMES)
+          .map(this.symbolize);
+      } catch (_) {
+        // Reported without a backtrace
+      }
+      if (kind !== 'abort') this.lastPanic.set(call.threadId, Date.now());
+      this.emit({
+        threadId: call.threadId,
+        eventType: 'panic',
+        functionName: fn,
+        kind,
+        text: message,
+        backtrace,
+        ...details,
+      });
+    } catch (_) {
+      // Never fail the panicking thread any further
+    }
+  }
+}
+
+/**
+ * `rust_panic` is internal to std: in the symbol table, not always exported.
+ * Searching the debug info is slow, so it is the last resort and only for
+ * binaries that link std.
+ */
+function findRustPanic(): NativePointer | null {
+  const exported = findGlobalExport('rust_panic');
+  if (exported !== null) return exported;
+  const main = Process.mainModule;
+  const local = main.findSymbolByName('rust_panic');
+  if (local !== null) return local;
+  const isRust = RUST_STD_SYMBOLS.some(name =>
+    main.findExportByName(name) !== null || main.findSymbolByName(name) !== null);
+  if (!isRust) return null;
+  try {
+    return DebugSymbol.findFunctionsNamed('rust_panic')[0] ?? null;
+  } catch (_) {
+    return null;
+  }
+}
@@ -17,11 +17,23 @@ export interface PlatformAdapter {
 
   /** Resolve write(2) for output capture, or null if unavailable */
   resolveWritePtr(): NativePointer | null;
+
+  /** The thread's real-time scheduling policy, or null for a normal thread */
+  realtimePolicy(threadId: number): string | null;
 }
 
+// thread_policy_get flavor of CoreAudio IO threads and other real-time threads
+const THREAD_TIME_CONSTRAINT_POLICY = 2;
+const THREAD_TIME_CONSTRAINT_POLICY_COUNT = 4;
+
+// sched_getscheduler() results; SCHED_RESET_ON_FORK may be or'ed in
+const LINUX_RT_POLICIES: Record<number, string> = { 1: 'SCHED_FIFO', 2: 'SCHED_RR', 6: 'SCHED_DEADLINE' };
+const SCHED_RESET_ON_FORK = 0x40000000;
+
 class DarwinPlatform implements PlatformAdapter {
   private libSystem: Module;
   private ticksToNs_: number = 1.0;
+  private threadPolicyGet: NativeFunction<number, [number, number, NativePointer, NativePointer, NativePointer]> | null = null;
 
   constructor() {
     this.libSystem = Process.getModuleByName('libSystem.B.dylib');
@@ -55,6 +67,27 @@ static unsigned long long strobe_timestamp(void) {
     }
   }
 
+  realtimePolicy(threadId: number): string | null {
+    if (this.threadPolicyGet === null) {
+      const addr = this.libSystem.findExportByName('thread_policy_get');
+      if (!addr) return null;
+      this.threadPolicyGet = new NativeFunction(addr, 'int', ['uint', 'uint', 'pointer', 'pointer', 'pointer']);
+    }
+    // Thread ids are mach thread ports; get_default stays set when the
+    // thread never had a time-constraint policy applied
+    const info = Memory.alloc(THREAD_TIME_CONSTRAINT_POLICY_COUNT * 4);
+    const count = Memory.alloc(4);
+    const getDefault = Memory.alloc(4);
+    count.writeU32(THREAD_TIME_CONSTRAINT_POLICY_COUNT);
+    getDefault.writeU32(0);
+    try {
+      const kr = this.threadPolicyGet(threadId, THREAD_TIME_CONSTRAINT_POLICY, info, count, getDefault);
+      return kr === 0 && getDefault.readU32() === 0 ? 'time-constraint' : null;
+    } catch {
+      return null;
+    }
+  }
+
   private computeTimebaseRatio(): number {
     try {
       const timebaseInfoPtr = this.libSystem.getExportByName('mach_timebase_info');
@@ -77,6 +110,8 @@ static unsigned long long strobe_timestamp(void) {
 }
 
 class LinuxPlatform implements PlatformAdapter {
+  private schedGetScheduler: NativeFunction<number, [number]> | null = null;
+
   getCModuleTimingPreamble(): string {
     return `
 struct timespec { long tv_sec; long tv_nsec; };
@@ -119,6 +154,79 @@ static unsigned long long strobe_timestamp(void) {
     }
     return findGlobalExport('write');
   }
+
+  realtimePolicy(threadId: number): string | null {
+    if (this.schedGetScheduler === null) {
+      const addr = findGlobalExport('sched_getscheduler');
+      if (!addr) return null;
+      this.schedGetScheduler = new NativeFunction(addr, 'int', ['int']);
+    }
+    try {
+      // Takes a tid as well as a pid
+      const policy = this.schedGetScheduler(threadId);
+      return policy < 0 ? null : LINUX_RT_POLICIES[policy & ~SCHED_RESET_ON_FORK] ?? null;
+    } catch {
+      return null;
+    }
+  }
+}
+
+class WindowsPlatform implements PlatformAdapter {
+  private kernel32: Module;
+  private ticksToNs_: number = 1.0;
+
+  constructor() {
+    this.kernel32 = Process.getModuleByName('kernel32.dll');
+    this.ticksToNs_ = this.computeTickRatio();
+  }
+
+  getCModuleTimingPreamble(): string {
+    return `
+extern int QueryPerformanceCounter(unsigned long long *count);
+static unsigned long long strobe_timestamp(void) {
+  unsigned long long ticks;
+  QueryPerformanceCounter(&ticks);
+  return ticks;
+}
+`;
+  }
+
+  getCModuleTimingSymbols(): Record<string, NativePointer> {
+    return {
+      QueryPerformanceCounter: this.kernel32.getExportByName('QueryPerformanceCounter'),
+    };
+  }
+
+  getTicksToNs(): number {
+    return this.ticksToNs_;
+  }
+
+  resolveWritePtr(): NativePointer | null {
+    // Console output goes through WriteFile/WriteConsoleW, not a write(2);
+    // the Device-level output capture (raw_on_output) covers stdout/stderr
+    return null;
+  }
+
+  realtimePolicy(_threadId: number): string | null {
+    return null;
+  }
+
+  private computeTickRatio(): number {
+    try {
+      const queryFrequency = new NativeFunction(
+        this.kernel32.getExportByName('QueryPerformanceFrequency'), 'int', ['pointer']);
+      const frequency = Memory.alloc(8);
+      if (queryFrequency(frequency) !== 0) {
+        const ticksPerSec = frequency.readU64().toNumber();
+        if (ticksPerSec > 0) {
+          return 1e9 / ticksPerSec;
+        }
+      }
+    } catch {
+      // Fall back to ratio 1.0
+    }
+    return 1.0;
+  }
 }
 
 /** Create the platform adapter for the current OS. */
@@ -128,6 +236,8 @@ export function createPlatformAdapter(): PlatformAdapter {
       return new DarwinPlatform();
     case 'linux':
       return new LinuxPlatform();
+    case 'windows':
+      return new WindowsPlatform();
     default:
       throw new Error(`Unsupported platform: ${Process.platform}`);
   }
@@ -0,0 +1,260 @@
+/**
+ * Signal-handler safety audit: find the handlers the process registers
+ * (sigaction/signal, plus whatever is installed when the audit starts), track
+ * which threads are currently running one, and report calls to functions that
+ * are not async-signal-safe made from inside a handler.
+ *
+ * The unsafe-function hooks are only installed the first time a handler runs,
+ * so a process that never receives a signal pays for the handler hooks alone.
+ * Each (handler, function, call site) is reported once.
+ */
+
+import { findGlobalExport } from './utils.js';
+
+export interface SignalSafetyMessage {
+  enabled: boolean;
+  functions?: string[] | null;  // Replaces the built-in list
+}
+
+export interface SignalSafetyFrame {
+  address: string;
+  moduleName: string | null;
+  name: string | null;
+  fileName: string | null;
+  lineNumber: number | null;
+}
+
+// Not async-signal-safe per POSIX, grouped by why they're dangerous
+const UNSAFE_FUNCTIONS: Record<string, string> = {
+  malloc: 'allocation', calloc: 'allocation', realloc: 'allocation', free: 'allocation',
+  posix_memalign: 'allocation', aligned_alloc: 'allocation',
+  _Znwm: 'allocation', _Znam: 'allocation', _ZdlPv: 'allocation', _ZdaPv: 'allocation',
+  printf: 'stdio', fprintf: 'stdio', vprintf: 'stdio', vfprintf: 'stdio',
+  puts: 'stdio', fputs: 'stdio', fputc: 'stdio', putchar: 'stdio', fwrite: 'stdio', fflush: 'stdio',
+  syslog: 'stdio',
+  pthread_mutex_lock: 'lock', pthread_mutex_trylock: 'lock', pthread_mutex_unlock: 'lock',
+  pthread_rwlock_rdlock: 'lock', pthread_rwlock_wrlock: 'lock', pthread_rwlock_unlock: 'lock',
+  pthread_cond_wait: 'lock', pthread_cond_signal: 'lock', pthread_cond_broadcast: 'lock',
+  exit: 'exit', atexit: 'exit',
+  localtime: 'libc-state', gmtime: 'libc-state', strerror: 'libc-state', getenv: 'libc-state',
+};
+
+// Leaving a handler without returning: the thread is no longer inside it
+const JUMP_FUNCTIONS = ['longjmp', '_longjmp', 'siglongjmp'];
+
+const LINUX_SIGNALS = [
+  '', 'SIGHUP', 'SIGINT', 'SIGQUIT', 'SIGILL', 'SIGTRAP', 'SIGABRT', 'SIGBUS', 'SIGFPE',
+  'SIGKILL', 'SIGUSR1', 'SIGSEGV', 'SIGUSR2', 'SIGPIPE', 'SIGALRM', 'SIGTERM', 'SIGSTKFLT',
+  'SIGCHLD', 'SIGCONT', 'SIGSTOP', 'SIGTSTP', 'SIGTTIN', 'SIGTTOU', 'SIGURG', 'SIGXCPU',
+  'SIGXFSZ', 'SIGVTALRM', 'SIGPROF', 'SIGWINCH', 'SIGIO', 'SIGPWR', 'SIGSYS',
+];
+const DARWIN_SIGNALS = [
+  '', 'SIGHUP', 'SIGINT', 'SIGQUIT', 'SIGILL', 'SIGTRAP', 'SIGABRT', 'SIGEMT', 'SIGFPE',
+  'SIGKILL', 'SIGBUS', 'SIGSEGV', 'SIGSYS', 'SIGPIPE', 'SIGALRM', 'SIGTERM', 'SIGURG',
+  'SIGSTOP', 'SIGTSTP', 'SIGCONT', 'SIGCHLD', 'SIGTTIN', 'SIGTTOU', 'SIGIO', 'SIGXCPU',
+  'SIGXFSZ', 'SIGVTALRM', 'SIGPROF', 'SIGWINCH', 'SIGINFO', 'SIGUSR1', 'SIGUSR2',
+];
+
+const MAX_SIGNAL = 64;
+const MAX_REPORTS = 1000;
+// struct sigaction is at most 152 bytes (glibc); the handler is its first field everywhere
+const SIGACTION_BUFFER_SIZE = 256;
+
+interface ActiveHandler {
+  signal: number;
+  handler: NativePointer;
+}
+
+export class SignalSafetyAuditor {
+  private functions: Map<string, string> = new Map();
+  private registrationHooks: InvocationListener[] = [];
+  private handlerHooks: Map<string, InvocationListener> = new Map();
+  private unsafeHooks: InvocationListener[] = [];
+  private unsafeArmed = false;
+  // Per thread, the handlers it is running (nested signals stack up)
+  private active: Map<number, ActiveHandler[]> = new Map();
+  private reported: Set<string> = new Set();
+
+  constructor(
+    private readonly emit: (event: any) => void,
+    private readonly symbolize: (addr: NativePointer) => SignalSafetyFrame,
+  ) {}
+
+  configure(msg: SignalSafetyMessage): number {
+    this.disable();
+    if (!msg.enabled) return 0;
+
+    this.functions = new Map();
+    if (msg.functions && msg.functions.length > 0) {
+      for (const f of msg.functions) this.functions.set(f, UNSAFE_FUNCTIONS[f] ?? 'custom');
+    } else {
+      for (const [f, category] of Object.entries(UNSAFE_FUNCTIONS)) this.functions.set(f, category);
+    }
+
+    this.hookRegistration();
+    this.scanInstalledHandlers();
+    return this.handlerHooks.size;
+  }
+
+  private disable(): void {
+    for (const l of this.registrationHooks) l.detach();
+    for (const listener of this.handlerHooks.values()) listener.detach();
+    for (const l of this.unsafeHooks) l.detach();
+    this.registrationHooks = [];
+    this.handlerHooks.clear();
+    this.unsafeHooks = [];
+    this.unsafeArmed = false;
+    this.active.clear();
+    this.reported.clear();
+  }
+
+  private hookRegistration(): void {
+    const self = this;
+    const sigaction = findGlobalExport('sigaction');
+    if (sigaction) {
+      this.registrationHooks.push(Interceptor.attach(sigaction, {
+        onEnter(args) {
+          const act = args[1];
+          if (act.isNull()) return;
+          try {
+            self.watchHandler(args[0].toInt32(), act.readPointer());
+          } catch (_) {
+            // Unreadable struct — the call itself will fail with EFAULT
+          }
+        },
+      }));
+    }
+    const signal = findGlobalExport('signal');
+    if (signal) {
+      this.registrationHooks.push(Interceptor.attach(signal, {
+        onEnter(args) {
+          self.watchHandler(args[0].toInt32(), args[1]);
+        },
+      }));
+    }
+  }
+
+  /** Handlers registered before the audit started, read back through sigaction(sig, NULL, &old). */
+  private scanInstalledHandlers(): void {
+    const addr = findGlobalExport('sigaction');
+    if (!addr) return;
+    const sigaction = new NativeFunction(addr, 'int', ['int', 'pointer', 'pointer']);
+    const old = Memory.alloc(SIGACTION_BUFFER_SIZE);
+    for (let sig = 1; sig <= MAX_SIGNAL; sig++) {
+      try {
+        if (sigaction(sig, NULL, old) === 0) {
+          this.watchHandler(sig, old.readPointer());
+        }
+      } catch (_) {
+        // Past the platform's last signal
+      }
+    }
+  }
+
+  private watchHandler(signal: number, handler: NativePointer): void {
+    // SIG_DFL (0), SIG_IGN (1), SIG_ERR (-1) aren't code
+    if (handler.isNull() || handler.equals(ptr(1)) || handler.equals(ptr(-1))) return;
+
+    const key = handler.toString();
+    if (this.handlerHooks.has(key)) return;
+    // Frida's own exception handler chains to the app's; leave it alone
+    const module = Process.findModuleByAddress(handler);
+    if (module && module.name.toLowerCase().includes('frida')) return;
+
+    const self = this;
+    try {
+      const listener = Interceptor.attach(handler, {
+        onEnter(args) {
+          const stack = self.active.get(this.threadId) ?? [];
+          stack.push({ signal: args[0].toInt32(), handler });
+          self.active.set(this.threadId, stack);
+          self.armUnsafeHooks();
+        },
+        onLeave() {
+          const stack = self.active.get(this.threadId);
+          if (!stack) return;
+          stack.pop();
+          if (stack.length === 0) self.active.delete(this.threadId);
+        },
+      });
+      this.handlerHooks.set(key, listener);
+    } catch (e: any) {
+      send({ type: 'log', message: `signalSafety: cannot hook handler ${key} for signal ${signal}: ${e.message}` });
+    }
+  }
+
+  private armUnsafeHooks(): void {
+    if (this.unsafeArmed) return;
+    this.unsafeArmed = true;
+
+    const self = this;
+    for (const [name, category] of this.functions) {
+      const addr = findGlobalExport(name);
+      if (!addr) continue;
+      try {
+        this.unsafeHooks.push(Interceptor.attach(addr, {
+          onEnter() {
+            if (self.active.size === 0) return;
+            const stack = self.active.get(this.threadId);
+            if (!stack || stack.length === 0) return;
+            self.report(name, category, stack[stack.length - 1], this.threadId, this.returnAddress, this.context);
+          },
+        }));
+      } catch (_) {
+        // Not hookable on this platform
+      }
+    }
+    for (const name of JUMP_FUNCTIONS) {
+      const addr = findGlobalExport(name);
+      if (!addr) continue;
+      try {
+        this.unsafeHooks.push(Interceptor.attach(addr, {
+          onEnter() {
+            self.active.delete(this.threadId);
+          },
+        }));
+      } catch (_) {
+        // Not hookable on this platform
+      }
+    }
+  }
+
+  private report(
+    fn: string,
+    category: string,
+    current: ActiveHandler,
+    threadId: number,
+    callSite: NativePointer,
+    context: CpuContext,
+  ): void {
+    if (this.reported.size >= MAX_REPORTS) return;
+    const key = `${current.handler}|${fn}|${callSite}`;
+    if (this.reported.has(key)) return;
+    this.reported.add(key);
+
+    let backtrace: SignalSafetyFrame[] = [];
+    try {
+      backtrace = Thread.backtrace(context, Backtracer.ACCURATE).map(this.symbolize);
+    } catch (_) {
+      // Unwalkable stack — the handler and function still identify the bug
+    }
+    const handler = this.symbolize(current.handler);
+    const signal = signalName(current.signal);
+    this.emit({
+      threadId,
+      eventType: 'signal_safety',
+      functionName: fn,
+      signal,
+      text: `${fn}() called inside the ${signal} handler ${handler.name ?? handler.address}: not async-signal-safe (${category})`,
+      handler: handler.name,
+      handlerAddress: handler.address,
+      category,
+      backtrace,
+    });
+  }
+}
+
+export function signalName(signal: number): string {
+  const names = Process.platform === 'darwin' ? DARWIN_SIGNALS : LINUX_SIGNALS;
+  return names[signal] || `signal ${signal}`;
+}
@@ -0,0 +1,222 @@
+/**
+ * Signal timeline (`@signals` trace pattern). Records each signal that
+ * reaches one of the process's handlers — the handlers registered through
+ * sigaction/signal while tracing, plus those already installed — and each
+ * signal the process sends with kill, killpg, raise or pthread_kill.
+ *
+ * Handlers installed with SA_SIGINFO receive a siginfo_t, which names the
+ * sending process for signals sent by kill/sigqueue; other handlers only
+ * know the signal number. Signals left at their default action or ignored
+ * never run code in the process and are not seen; fatal ones are still
+ * recorded as crashes.
+ */
+
+import { findGlobalExport } from './utils.js';
+import { signalName } from './signal-safety.js';
+
+export interface SignalsMessage {
+  enabled: boolean;
+}
+
+const IS_DARWIN = Process.platform === 'darwin';
+const MAX_SIGNAL = 64;
+const SIGACTION_BUFFER_SIZE = 256;
+// struct sigaction: handler first; sa_flags after a 128-byte sigset_t on
+// glibc, after a 4-byte one on Darwin
+const SA_FLAGS_OFFSET = IS_DARWIN ? 12 : Process.pointerSize + 128;
+const SA_SIGINFO = IS_DARWIN ? 0x40 : 4;
+// siginfo_t: si_signo, si_errno, si_code, then si_pid/si_uid (after padding on 64-bit Linux)
+const SI_CODE_OFFSET = 8;
+const SI_PID_OFFSET = IS_DARWIN ? 12 : (Process.pointerSize === 8 ? 16 : 12);
+// SI_USER / SI_QUEUE on Darwin; on Linux every code <= 0 comes from a process
+const DARWIN_USER_CODES = [0x10001, 0x10002];
+// Calls that send a signal and where their target and signal are
+const SENDERS: Array<{ name: string; target: number | null; signal: number }> = [
+  { name: 'kill', target: 0, signal: 1 },
+  { name: 'killpg', target: 0, signal: 1 },
+  { name: 'raise', target: null, signal: 0 },
+  { name: 'pthread_kill', target: null, signal: 1 },
+];
+
+type SignalKind = 'delivered' | 'sent';
+
+export class SignalTracer {
+  private hooks: InvocationListener[] = [];
+  // Handler address -> hook; one hook per handler whatever signals it serves
+  private handlerHooks: Map<string, InvocationListener> = new Map();
+  // Handler address -> installed with SA_SIGINFO
+  private siginfo: Map<string, boolean> = new Map();
+  // Threads inside a sending call; raise() is pthread_kill() on Darwin
+  private sending: Set<number> = new Set();
+
+  constructor(
+    private readonly emit: (event: any) => void,
+    private readonly symbolName: (addr: NativePointer) => string | null,
+    private readonly now: () => number,
+  ) {}
+
+  /** Returns how many functions are hooked. */
+  configure(msg: SignalsMessage): number {
+    for (const l of this.hooks) l.detach();
+    for (const l of this.handlerHooks.values()) l.detach();
+    this.hooks = [];
+    this.handlerHooks.clear();
+    this.siginfo.clear();
+    this.sending.clear();
+    if (!msg.enabled) return 0;
+
+    this.hookRegistration();
+    this.scanInstalledHandlers();
+    this.hookSenders();
+    return this.hooks.length + this.handlerHooks.size;
+  }
+
+  private hookRegistration(): void {
+    const self = this;
+    const sigaction = findGlobalExport('sigaction');
+    if (sigaction) {
+      this.hooks.push(Interceptor.attach(sigaction, {
+        onEnter(args) {
+          const act = args[1];
+          if (act.isNull()) return;
+          try {
+            const flags = act.add(SA_FLAGS_OFFSET).readS32();
+            self.watchHandler(act.readPointer(), (flags & SA_SIGINFO) !== 0);
+          } catch (_) {
+            // Unreadable struct — the call itself will fail with EFAULT
+          }
+        },
+      }));
+    }
+    const signal = findGlobalExport('signal');
+    if (signal) {
+      this.hooks.push(Interceptor.attach(signal, {
+        onEnter(args) {
+          self.watchHandler(args[1], false);
+        },
+      }));
+    }
+  }
+
+  /** Handlers registered before tracing started, read back through sigaction(sig, NULL, &old). */
+  private scanInstalledHandlers(): void {
+    const addr = findGlobalExport('sigaction');
+    if (!addr) return;
+    const sigaction = new NativeFunction(addr, 'int', ['int', 'pointer', 'pointer']);
+    const old = Memory.alloc(SIGACTION_BUFFER_SIZE);
+    for (let sig = 1; sig <= MAX_SIGNAL; sig++) {
+      try {
+        if (sigaction(sig, NULL, old) === 0) {
+          const flags = old.add(SA_FLAGS_OFFSET).readS32();
+          this.watchHandler(old.readPointer(), (flags & SA_SIGINFO) !== 0);
+        }
+      } catch (_) {
+        // Past the platform's last signal
+      }
+    }
+  }
+
+  private watchHandler(handler: NativePointer, siginfo: boolean): void {
+    // SIG_DFL (0), SIG_IGN (1), SIG_ERR (-1) aren't code
+    if (handler.isNull() || handler.equals(ptr(1)) || handler.equals(ptr(-1))) return;
+
+    const key = handler.toString();
+    this.siginfo.set(key, siginfo);
+    if (this.handlerHooks.has(key)) return;
+    // Frida's own exception handler chains to the app's; leave it alone
+    const module = Process.findModuleByAddress(handler);
+    if (module && module.name.toLowerCase().includes('frida')) return;
+
+    const self = this;
+    const name = this.symbolName(handler);
+    try {
+      this.handlerHooks.set(key, Interceptor.attach(handler, {
+        onEnter(args) {
+          self.delivered(this.threadId, args, key, name ?? key);
+        },
+      }));
+    } catch (e: any) {
+      send({ type: 'log', message: `@signals: cannot hook handler ${key}: ${e.message}` });
+    }
+  }
+
+  private delivered(threadId: number, args: InvocationArguments, key: string, handler: string): void {
+    try {
+      const signo = args[0].toInt32();
+      const signal = signalName(signo);
+      let senderPid: number | null = null;
+      let senderUid: number | null = null;
+      let code: number | null = null;
+      const info = args[1];
+      if (this.siginfo.get(key) === true && !info.isNull()) {
+        code = info.add(SI_CODE_OFFSET).readS32();
+        const fromProcess = IS_DARWIN ? DARWIN_USER_CODES.includes(code) : code <= 0;
+        if (fromProcess) {
+          senderPid = info.add(SI_PID_OFFSET).readS32();
+          senderUid = info.add(SI_PID_OFFSET + 4).readU32();
+        }
+      }
+      const from = senderPid !== null ? ` from pid ${senderPid}` : '';
+      this.emit({
+        threadId,
+        timestampNs: this.now(),
+        eventType: 'signal',
+        functionName: handler,
+        kind: 'delivered' as SignalKind,
+        signal,
+        signo,
+        handler,
+        senderPid,
+        senderUid,
+        code,
+        text: `${signal}${from} delivered to ${handler}`,
+      });
+    } catch (_) {
+      // Never fail inside a signal handler
+    }
+  }
+
+  private hookSenders(): void {
+    const self = this;
+    for (const sender of SENDERS) {
+      const addr = findGlobalExport(sender.name);
+      if (!addr) continue;
+      try {
+        this.hooks.push(Interceptor.attach(addr, {
+          onEnter(args) {
+            this.nested = self.sending.has(this.threadId);
+            self.sending.add(this.threadId);
+            this.signo = args[sender.signal].toInt32();
+            // A signal raised at itself is handled before the call returns
+            this.at = self.now();
+            this.targetPid = sender.target !== null ? args[sender.target].toInt32() : Process.id;
+          },
+          onLeave(retval) {
+            if (this.nested) return;
+            self.sending.delete(this.threadId);
+            // kill(pid, 0) only checks that the process exists
+            if (this.signo === 0) return;
+            const signal = signalName(this.signo);
+            const target = sender.name === 'killpg'
+              ? `process group ${this.targetPid}`
+              : this.targetPid === Process.id ? 'itself' : `pid ${this.targetPid}`;
+            self.emit({
+              threadId: this.threadId,
+              timestampNs: this.at,
+              eventType: 'signal',
+              functionName: sender.name,
+              kind: 'sent' as SignalKind,
+              signal,
+              signo: this.signo,
+              targetPid: this.targetPid,
+              result: retval.toInt32(),
+              text: `${sender.name}() sent ${signal} to ${target}`,
+            });
+          },
+        }));
+      } catch (_) {
+        // Not hookable on this platform
+      }
+    }
+  }
+}
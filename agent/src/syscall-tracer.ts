@@ -0,0 +1,272 @@
+/**
+ * Syscall tracing (`@syscalls:file`, `@syscalls:net` trace patterns): hooks
+ * the libc wrappers for file and socket calls and reports each call with its
+ * fd, the path or peer address behind it, the byte count and the result.
+ *
+ * Paths and addresses are remembered per fd from the open/connect/accept that
+ * created it, so a read or send names what it talks to. Calls on stdout and
+ * stderr are skipped — that output is already captured.
+ */
+
+import { findGlobalExport } from './utils.js';
+
+export type SyscallGroup = 'file' | 'net';
+
+export interface SyscallsMessage {
+  groups: SyscallGroup[];
+}
+
+type ArgKind =
+  | 'path'      // open(path, ...), creat(path, ...)
+  | 'atPath'    // openat(dirfd, path, ...)
+  | 'fd'        // close(fd)
+  | 'fdBytes'   // read/write/send/recv(fd, buf, count, ...)
+  | 'fdAddr'    // connect/bind(fd, addr, len)
+  | 'fdAccept'  // accept(fd, addr, lenp)
+  | 'socket'    // socket(domain, type, protocol)
+  | 'fdOnly';   // listen(fd, backlog)
+
+interface CallSpec {
+  kind: ArgKind;
+  // Exports tried in order; the first found is hooked
+  exports: string[];
+}
+
+const FILE_CALLS: Record<string, CallSpec> = {
+  open: { kind: 'path', exports: ['open', 'open64'] },
+  openat: { kind: 'atPath', exports: ['openat', 'openat64'] },
+  creat: { kind: 'path', exports: ['creat', 'creat64'] },
+  read: { kind: 'fdBytes', exports: ['read'] },
+  write: { kind: 'fdBytes', exports: ['write'] },
+  pread: { kind: 'fdBytes', exports: ['pread', 'pread64'] },
+  pwrite: { kind: 'fdBytes', exports: ['pwrite', 'pwrite64'] },
+  close: { kind: 'fd', exports: ['close'] },
+};
+
+const NET_CALLS: Record<string, CallSpec> = {
+  socket: { kind: 'socket', exports: ['socket'] },
+  connect: { kind: 'fdAddr', exports: ['connect'] },
+  bind: { kind: 'fdAddr', exports: ['bind'] },
+  listen: { kind: 'fdOnly', exports: ['listen'] },
+  accept: { kind: 'fdAccept', exports: ['accept'] },
+  accept4: { kind: 'fdAccept', exports: ['accept4'] },
+  send: { kind: 'fdBytes', exports: ['send'] },
+  sendto: { kind: 'fdBytes', exports: ['sendto'] },
+  recv: { kind: 'fdBytes', exports: ['recv'] },
+  recvfrom: { kind: 'fdBytes', exports: ['recvfrom'] },
+  close: { kind: 'fd', exports: ['close'] },
+};
+
+const AF_INET = 2;
+const AF_INET6 = Process.platform === 'darwin' ? 30 : 10;
+const AF_UNIX = 1;
+const EINPROGRESS = Process.platform === 'darwin' ? 36 : 115;
+const MAX_FDS = 4096;
+
+interface FdInfo {
+  path?: string;
+  address?: string;
+}
+
+export class SyscallTracer {
+  private groups: Set<SyscallGroup> = new Set();
+  private hooks: InvocationListener[] = [];
+  private fds: Map<number, FdInfo> = new Map();
+
+  constructor(
+    private readonly emit: (event: any) => void,
+    private readonly now: () => number,
+  ) {}
+
+  /** Replace the traced groups. Returns how many calls are hooked. */
+  configure(msg: SyscallsMessage): number {
+    for (const l of this.hooks) l.detach();
+    this.hooks = [];
+    this.groups = new Set(msg.groups);
+    if (this.groups.size === 0) {
+      this.fds.clear();
+      return 0;
+    }
+
+    const calls: Map<string, [CallSpec, SyscallGroup]> = new Map();
+    if (this.groups.has('file')) {
+      for (const [name, spec] of Object.entries(FILE_CALLS)) calls.set(name, [spec, 'file']);
+    }
+    if (this.groups.has('net')) {
+      for (const [name, spec] of Object.entries(NET_CALLS)) {
+        if (!calls.has(name)) calls.set(name, [spec, 'net']);
+      }
+    }
+    for (const [name, [spec, group]] of calls) {
+      this.hook(name, spec, group);
+    }
+    return this.hooks.length;
+  }
+
+  private hook(name: string, spec: CallSpec, group: SyscallGroup): void {
+    const addr = spec.exports.map((e) => findGlobalExport(e)).find((a) => a !== null);
+    if (!addr) return;
+    const self = this;
+    try {
+      this.hooks.push(Interceptor.attach(addr, {
+        onEnter(args) {
+          this.startNs = self.now();
+          this.fd = -1;
+          this.path = null;
+          this.address = null;
+          this.bytes = null;
+          switch (spec.kind) {
+            case 'path':
+              this.path = readCString(args[0]);
+              break;
+            case 'atPath':
+              this.path = readCString(args[1]);
+              break;
+            case 'socket':
+              break;
+            case 'fdAddr':
+              this.fd = args[0].toInt32();
+              this.address = formatSockaddr(args[1]);
+              break;
+            case 'fdAccept':
+              this.fd = args[0].toInt32();
+              this.addrOut = args[1];
+              break;
+            case 'fdBytes':
+              this.fd = args[0].toInt32();
+              this.bytes = args[2].toUInt32();
+              break;
+            default:
+              this.fd = args[0].toInt32();
+          }
+        },
+        onLeave(retval) {
+          // stdout/stderr are captured as output events already
+          if (this.fd === 1 || this.fd === 2) return;
+          const result = retval.toInt32();
+          self.record(name, group, spec.kind, this, result, result < 0 ? this.errno : null);
+        },
+      }));
+    } catch (_) {
+      // Not hookable on this platform
+    }
+  }
+
+  private record(
+    name: string,
+    group: SyscallGroup,
+    kind: ArgKind,
+    call: InvocationContext,
+    result: number,
+    errno: number | null,
+  ): void {
+    let fd: number = call.fd;
+    let path: string | null = call.path;
+    let address: string | null = call.address;
+
+    if (result >= 0) {
+      if (kind === 'path' || kind === 'atPath') {
+        fd = result;
+        this.remember(fd, { path: path ?? undefined });
+      } else if (kind === 'socket') {
+        fd = result;
+        this.remember(fd, {});
+      } else if (kind === 'fdAccept') {
+        address = call.addrOut && !call.addrOut.isNull() ? formatSockaddr(call.addrOut) : null;
+        this.remember(result, { address: address ?? undefined });
+      } else if (kind === 'fdAddr' && name === 'connect') {
+        this.remember(fd, { address: address ?? undefined });
+      }
+    } else if (kind === 'fdAddr' && name === 'connect' && errno === EINPROGRESS) {
+      // Non-blocking connect: the socket is still headed there
+      this.remember(fd, { address: address ?? undefined });
+    }
+
+    const known = fd >= 0 ? this.fds.get(fd) : undefined;
+    path = path ?? known?.path ?? null;
+    address = address ?? known?.address ?? null;
+    if (kind === 'fd' && result === 0) this.fds.delete(fd);
+
+    const target = path ?? address;
+    const shownFd = kind === 'path' || kind === 'atPath' || kind === 'socket' ? '' : `${fd}`;
+    const argText = [shownFd && target ? `${shownFd} ${target}` : shownFd || target || '', call.bytes !== null ? `${call.bytes}` : '']
+      .filter((part) => part !== '')
+      .join(', ');
+    const startNs: number = call.startNs;
+    this.emit({
+      threadId: call.threadId,
+      timestampNs: startNs,
+      eventType: 'syscall',
+      functionName: name,
+      group,
+      fd: fd >= 0 ? fd : null,
+      path,
+      address,
+      bytes: result >= 0 && kind === 'fdBytes' ? result : null,
+      result,
+      errno,
+      durationNs: this.now() - startNs,
+      text: `${name}(${argText}) = ${result}${errno !== null ? ` (errno ${errno})` : ''}`,
+    });
+  }
+
+  private remember(fd: number, info: FdInfo): void {
+    if (fd < 0) return;
+    if (!this.fds.has(fd) && this.fds.size >= MAX_FDS) return;
+    this.fds.set(fd, info);
+  }
+}
+
+function readCString(p: NativePointer): string | null {
+  if (p.isNull()) return null;
+  try {
+    return p.readUtf8String();
+  } catch (_) {
+    return null;
+  }
+}
+
+export interface SocketAddress {
+  // IP for inet families, socket path for AF_UNIX
+  host: string;
+  port: number | null;
+  inet: boolean;
+}
+
+/** The family, host and port of a struct sockaddr; null when unreadable. */
+export function parseSockaddr(addr: NativePointer): SocketAddress | null {
+  if (addr.isNull()) return null;
+  try {
+    // Darwin's sockaddr starts with a length byte, then a 1-byte family
+    const family = Process.platform === 'darwin' ? addr.add(1).readU8() : addr.readU16();
+    if (family === AF_INET) {
+      const port = (addr.add(2).readU8() << 8) | addr.add(3).readU8();
+      const ip = [0, 1, 2, 3].map((i) => addr.add(4 + i).readU8()).join('.');
+      return { host: ip, port, inet: true };
+    }
+    if (family === AF_INET6) {
+      const port = (addr.add(2).readU8() << 8) | addr.add(3).readU8();
+      const groups: string[] = [];
+      for (let i = 0; i < 8; i++) {
+        const hi = addr.add(8 + i * 2).readU8();
+        const lo = addr.add(9 + i * 2).readU8();
+        groups.push(((hi << 8) | lo).toString(16));
+      }
+      return { host: groups.join(':'), port, inet: true };
+    }
+    if (family === AF_UNIX) {
+      return { host: addr.add(2).readUtf8String() ?? '', port: null, inet: false };
+    }
+    return { host: `family ${family}`, port: null, inet: false };
+  } catch (_) {
+    return null;
+  }
+}
+
+/** "1.2.3.4:80", "[::1]:443" or the unix socket path; null when unreadable. */
+export function formatSockaddr(addr: NativePointer): string | null {
+  const parsed = parseSockaddr(addr);
+  if (parsed === null) return null;
+  if (parsed.port === null) return parsed.host;
+  return parsed.host.includes(':') ? `[${parsed.host}]:${parsed.port}` : `${parsed.host}:${parsed.port}`;
+}
@@ -0,0 +1,284 @@
+// agent/src/tracers/java-tracer.ts
+// JVM tracer — hooks Java methods through Frida's Java bridge (Java.use).
+//
+// Targets arrive from the daemon's JavaResolver as `package.Class.method`
+// names (nested classes as `Outer$Inner`). Every overload of the method is
+// replaced with a wrapper that records entry/exit, serializing arguments and
+// the return value with toString(). The JVM is usually not up yet when the
+// agent loads (the launcher dlopens libjvm), so hooks queue until
+// Java.available turns true and classes that fail to load are retried.
+
+import { Tracer, ResolvedTarget, HookMode, BreakpointMessage,
+         StepHooksMessage, LogpointMessage, TracerCapabilities } from './tracer.js';
+import { EventIdAllocator } from '../utils.js';
+
+interface JavaHook {
+  funcId: number;
+  target: ResolvedTarget;
+  className: string;
+  methodName: string;
+  installed: boolean;
+  failure?: string;
+}
+
+const MAX_VALUE_LENGTH = 1024;
+const PENDING_POLL_MS = 200;
+
+function javaBridge(): any {
+  try {
+    return typeof Java !== 'undefined' ? Java : null;
+  } catch {
+    return null;
+  }
+}
+
+function describe(value: any): string {
+  let text: string;
+  try {
+    if (value === null || value === undefined) {
+      text = 'null';
+    } else if (typeof value === 'object' && typeof value.toString === 'function') {
+      text = String(value.toString());
+    } else {
+      text = String(value);
+    }
+  } catch (e: any) {
+    text = `<toString failed: ${e.message}>`;
+  }
+  return text.length > MAX_VALUE_LENGTH ? text.slice(0, MAX_VALUE_LENGTH) + '...' : text;
+}
+
+export class JavaTracer implements Tracer {
+  private agent: any;
+  private hooks: Map<number, JavaHook> = new Map();
+  private nextFuncId: number = 1;
+  private sessionId: string = '';
+  private eventIds = new EventIdAllocator('java');
+  private eventBuffer: any[] = [];
+  private flushTimer: ReturnType<typeof setInterval> | null = null;
+  private pendingTimer: ReturnType<typeof setInterval> | null = null;
+
+  constructor(agent: any) { this.agent = agent; }
+
+  initialize(sessionId: string): void {
+    this.sessionId = sessionId;
+    this.flushTimer = setInterval(() => this.flushEvents(), 50);
+    if (!javaBridge()) {
+      send({ type: 'log', message: 'JavaTracer: Java bridge not present in this Frida runtime — tracing unavailable' });
+    }
+  }
+
+  dispose(): void {
+    if (this.flushTimer) { clearInterval(this.flushTimer); this.flushTimer = null; }
+    if (this.pendingTimer) { clearInterval(this.pendingTimer); this.pendingTimer = null; }
+    this.removeAllHooks();
+    this.flushEvents();
+  }
+
+  installHook(target: ResolvedTarget, _mode: HookMode): number | null {
+    const dot = target.name.lastIndexOf('.');
+    if (dot <= 0) return null;
+    const funcId = this.nextFuncId++;
+    this.hooks.set(funcId, {
+      funcId,
+      target,
+      className: target.name.slice(0, dot),
+      methodName: target.name.slice(dot + 1),
+      installed: false,
+    });
+    this.installPending();
+    return funcId;
+  }
+
+  removeHook(id: number): void {
+    const hook = this.hooks.get(id);
+    if (!hook) return;
+    this.hooks.delete(id);
+    if (hook.installed) this.restore(hook);
+  }
+
+  removeAllHooks(): void {
+    for (const hook of this.hooks.values()) {
+      if (hook.installed) this.restore(hook);
+    }
+    this.hooks.clear();
+  }
+
+  activeHookCount(): number { return this.hooks.size; }
+
+  installBreakpoint(_msg: BreakpointMessage): void {}
+  removeBreakpoint(_id: string): void {}
+  installStepHooks(_msg: StepHooksMessage): void {}
+  installLogpoint(_msg: LogpointMessage): void {}
+  removeLogpoint(_id: string): void {}
+
+  // `com.example.Config.level` reads the static field `level`
+  readVariable(expr: string): any {
+    const java = javaBridge();
+    if (!java || !java.available) return { error: 'JVM not initialized yet' };
+    const dot = expr.lastIndexOf('.');
+    if (dot <= 0) return { error: `Expected Class.field, got '${expr}'` };
+    let result: any;
+    java.performNow(() => {
+      try {
+        const field = java.use(expr.slice(0, dot))[expr.slice(dot + 1)];
+        result = field === undefined
+          ? { error: `No static field '${expr.slice(dot + 1)}'` }
+          : describe(field.value);
+      } catch (e: any) {
+        result = { error: e.message };
+      }
+    });
+    return result;
+  }
+
+  writeVariable(expr: string, value: any): void {
+    const java = javaBridge();
+    if (!java || !java.available) throw new Error('JVM not initialized yet');
+    const dot = expr.lastIndexOf('.');
+    if (dot <= 0) throw new Error(`Expected Class.field, got '${expr}'`);
+    java.performNow(() => {
+      java.use(expr.slice(0, dot))[expr.slice(dot + 1)].value = value;
+    });
+  }
+
+  setImageBase(_base: string): void {}
+  getSlide(): NativePointer { return ptr(0); }
+
+  private installPending(): void {
+    const java = javaBridge();
+    const pending = [...this.hooks.values()].filter(h => !h.installed);
+    if (pending.length === 0) {
+      if (this.pendingTimer) { clearInterval(this.pendingTimer); this.pendingTimer = null; }
+      return;
+    }
+    if (!java || !java.available) {
+      if (java && !this.pendingTimer) {
+        this.pendingTimer = setInterval(() => this.installPending(), PENDING_POLL_MS);
+      }
+      return;
+    }
+
+    java.performNow(() => {
+      for (const hook of pending) {
+        try {
+          this.hookMethod(java, hook);
+          hook.installed = true;
+        } catch (e: any) {
+          // Usually the class isn't loadable yet — retried on the next tick
+          if (hook.failure !== e.message) {
+            hook.failure = e.message;
+            send({ type: 'log', message: `JavaTracer: ${hook.target.name} not hooked yet: ${e.message}` });
+          }
+        }
+      }
+    });
+
+    const stillPending = pending.some(h => !h.installed);
+    if (stillPending && !this.pendingTimer) {
+      this.pendingTimer = setInterval(() => this.installPending(), PENDING_POLL_MS);
+    } else if (!stillPending && this.pendingTimer) {
+      clearInterval(this.pendingTimer);
+      this.pendingTimer = null;
+    }
+  }
+
+  private hookMethod(java: any, hook: JavaHook): void {
+    const method = java.use(hook.className)[hook.methodName];
+    if (!method || !method.overloads) {
+      throw new Error(`no method '${hook.methodName}' on ${hook.className}`);
+    }
+    const self = this;
+    for (const overload of method.overloads) {
+      overload.implementation = function (this: any, ...args: any[]) {
+        const threadId = Process.getCurrentThreadId();
+        const callId = self.emit(hook, threadId, 'entry', { arguments: args.map(describe) });
+        const start = Date.now();
+        let ret: any;
+        try {
+          ret = overload.apply(this, args);
+        } catch (e) {
+          self.emit(hook, threadId, 'exit', {
+            callId,
+            returnValue: `threw ${describe(e)}`,
+            durationNs: (Date.now() - start) * 1_000_000,
+          });
+          throw e;
+        }
+        self.emit(hook, threadId, 'exit', {
+          callId,
+          returnValue: overload.returnType.name === 'V' ? null : describe(ret),
+          durationNs: (Date.now() - start) * 1_000_000,
+        });
+        return ret;
+      };
+    }
+  }
+
+  private restore(hook: JavaHook): void {
+    const java = javaBridge();
+    if (!java || !java.available) return;
+    try {
+      java.performNow(() => {
+        for (const overload of java.use(hook.className)[hook.methodName].overloads) {
+          overload.implementation = null;
+        }
+      });
+    } catch {}
+  }
+
+  /** Returns the event id; an entry's id is the call id its exit repeats. */
+  private emit(hook: JavaHook, threadId: number, event: 'entry' | 'exit', fields: any): string {
+    const id = this.eventIds.next(this.sessionId, threadId);
+    this.eventBuffer.push({
+      id,
+      callId: id,
+      sessionId: this.sessionId,
+      timestampNs: Date.now() * 1_000_000,
+      threadId,
+      eventType: event === 'entry' ? 'function_enter' : 'function_exit',
+      functionName: hook.target.name,
+      sourceFile: hook.target.file,
+      lineNumber: hook.target.line,
+      pid: Process.id,
+      ...fields,
+    });
+    if (this.eventBuffer.length >= 50) this.flushEvents();
+    return id;
+  }
+
+  private flushEvents(): void {
+    if (this.eventBuffer.length === 0) return;
+    const events = this.eventBuffer;
+    this.eventBuffer = [];
+    send({ type: 'events', events });
+  }
+
+  resolvePattern(_pattern: string): ResolvedTarget[] {
+    return [];
+  }
+
+  getCapabilities(): TracerCapabilities {
+    if (!javaBridge()) {
+      return {
+        functionTracing: false,
+        breakpoints: false,
+        stepping: false,
+        runtimeDetail: 'JVM (Java bridge unavailable)',
+        limitations: [
+          "This Frida build doesn't ship the Java bridge, so Java methods can't be hooked. " +
+          "Use a Frida release that bundles the Java bridge in its script runtime.",
+        ],
+      };
+    }
+    return {
+      functionTracing: true,
+      breakpoints: false,
+      stepping: false,
+      runtimeDetail: 'JVM (Java bridge)',
+      limitations: [
+        "Breakpoints and stepping are not supported for Java. Use debug_trace — calls record arguments and return values via toString().",
+      ],
+    };
+  }
+}
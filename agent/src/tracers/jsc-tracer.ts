@@ -8,7 +8,7 @@
 
 import { Tracer, ResolvedTarget, HookMode, BreakpointMessage,
          StepHooksMessage, LogpointMessage, TracerCapabilities } from './tracer.js';
-import { findGlobalExport } from '../utils.js';
+import { EventIdAllocator, findGlobalExport } from '../utils.js';
 
 interface JscHook {
   funcId: number;
@@ -22,7 +22,7 @@ export class JscTracer implements Tracer {
   private hooksByName: Map<string, JscHook> = new Map(); // name → hook for fast lookup
   private nextFuncId: number = 1;
   private sessionId: string = '';
-  private eventIdCounter: number = 0;
+  private eventIds = new EventIdAllocator('jsc');
   private eventBuffer: any[] = [];
   private flushTimer: ReturnType<typeof setInterval> | null = null;
   private interceptor: InvocationListener | null = null;
@@ -221,7 +221,7 @@ export class JscTracer implements Tracer {
     if (!hook) return;
 
     this.eventBuffer.push({
-      id: `${this.sessionId}-jsc-${++this.eventIdCounter}`,
+      id: this.eventIds.next(this.sessionId, Process.getCurrentThreadId()),
       sessionId: this.sessionId,
       timestampNs: Date.now() * 1_000_000,
       threadId: Process.getCurrentThreadId(),
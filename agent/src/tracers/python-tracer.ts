@@ -1,9 +1,25 @@
 // agent/src/tracers/python-tracer.ts
 // Python tracer using sys.settrace + NativeCallback (version-independent)
+//
+// Hooked functions report entry (repr-limited arguments), exit (return value
+// or the exception that unwound the frame) and duration. On 3.12+ the events
+// come from sys.monitoring PY_START/PY_RETURN/PY_UNWIND; older versions use
+// the call/return/exception events of the settrace function. A generator or
+// coroutine reports each stretch between resuming and yielding as a call
+// (PY_RESUME/PY_YIELD; settrace already sees them as call/return).
 
 import { Tracer, ResolvedTarget, HookMode, BreakpointMessage, StepHooksMessage,
          LogpointMessage, ReadMemoryMessage, WriteMemoryMessage, TracerCapabilities } from './tracer.js';
-import { findGlobalExport } from '../utils.js';
+import { EventIdAllocator, findGlobalExport } from '../utils.js';
+
+// Event kinds passed from Python to the trace callback
+const PY_EVENT_ENTRY = 0;
+const PY_EVENT_EXIT = 1;
+// reprlib limit for argument / return value strings
+const MAX_REPR_STRING = 200;
+// Code objects whose hook match is cached; the cache starts over when full
+// so code created at runtime (exec, lambdas in loops) can't grow it forever
+const MAX_MATCH_CACHE = 10000;
 
 interface PythonHook {
   funcId: number;
@@ -45,8 +61,11 @@ export class PythonTracer implements Tracer {
   private nextFuncId: number = 1;
   private stepState: StepState = { active: false, threadId: 0 };
   private sessionId: string = '';
-  private eventIdCounter: number = 0;
+  private traceIds = new EventIdAllocator('py');
+  private logpointIds = new EventIdAllocator('pylp');
   private eventBuffer: any[] = [];
+  // Per thread: call ids of hooked frames not yet exited, innermost last
+  private openCalls: Map<number, string[]> = new Map();
   private flushTimer: ReturnType<typeof setInterval> | null = null;
   private traceCallback: NativePointer | null = null;
   private logCallback: NativePointer | null = null;
@@ -119,22 +138,26 @@ export class PythonTracer implements Tracer {
     this.flushTimer = setInterval(() => this.flushEvents(), 50);
 
     // Create NativeCallback that Python's trace function will call
-    // Signature: void callback(const char* file, const char* func, int line, int funcId)
+    // Signature: void callback(const char* file, const char* func, int line, int funcId,
+    //                          int kind, const char* payloadJson)
     const self = this;
     this.traceCallback = new NativeCallback(
-      function (filePtr: NativePointer, funcPtr: NativePointer, line: number, funcId: number) {
+      function (filePtr: NativePointer, funcPtr: NativePointer, line: number, funcId: number,
+                kind: number, payloadPtr: NativePointer) {
         try {
           const file = filePtr.readUtf8String() || '';
           const funcName = funcPtr.readUtf8String() || '';
+          const payload = payloadPtr.isNull() ? {} : JSON.parse(payloadPtr.readUtf8String() || '{}');
           const hook = self.hooks.get(funcId);
           if (hook) {
-            self.emitTraceEvent(funcId, hook, { file, funcName, line }, 'entry');
+            self.emitTraceEvent(funcId, hook, { file, funcName, line, ...payload },
+              kind === PY_EVENT_EXIT ? 'exit' : 'entry');
           }
         } catch (e) {
           // Silent — don't break Python execution
         }
       },
-      'void', ['pointer', 'pointer', 'int', 'int']
+      'void', ['pointer', 'pointer', 'int', 'int', 'int', 'pointer']
     ) as NativePointer;
 
     const self2 = this;
@@ -207,9 +230,14 @@ export class PythonTracer implements Tracer {
       bpEntries.push(`('${file}', ${bp.line}, '${bp.id}', '${cond}', ${bp.hitCount || 0})`);
     }
 
+    // Hook matches are cached per code object; sys.monitoring disabled the
+    // unmatched ones, so re-enable them to be matched against the new list.
     return `_strobe_hooks = [${hookEntries.join(', ')}]
 _strobe_logpoints = [${logpointEntries.join(', ')}]
-_strobe_breakpoints = [${bpEntries.join(', ')}]`;
+_strobe_breakpoints = [${bpEntries.join(', ')}]
+_strobe_match_cache = {}
+if getattr(__import__('builtins'), '_strobe_monitoring_active', False):
+    __import__('sys').monitoring.restart_events()`;
   }
 
   /**
@@ -262,13 +290,94 @@ else:
     const dataAssignments = this.buildTraceDataAssignments();
 
     // Common preamble: callbacks, data lists, bp event, GIL helpers
-    const preamble = `
+    const header = `
 import sys, ctypes, threading, builtins as _b
+import json as _strobe_json, reprlib as _strobe_reprlib, time as _strobe_time
 
-# Trace callback: void(char*, char*, int, int)
-_STROBE_CB_TYPE = ctypes.CFUNCTYPE(None, ctypes.c_char_p, ctypes.c_char_p, ctypes.c_int, ctypes.c_int)
+# Trace callback: void(char* file, char* func, int line, int funcId, int kind, char* payloadJson)
+_STROBE_CB_TYPE = ctypes.CFUNCTYPE(None, ctypes.c_char_p, ctypes.c_char_p, ctypes.c_int, ctypes.c_int, ctypes.c_int, ctypes.c_char_p)
 _strobe_cb = _STROBE_CB_TYPE(${callbackAddr})
+`;
+
+    // Shared by both tracing modes: hook matching, repr-limited values and
+    // the per-thread stack of hooked calls used for durations and exceptions
+    const eventHelpers = `
+_strobe_reprs = _strobe_reprlib.Repr()
+_strobe_reprs.maxstring = ${MAX_REPR_STRING}
+_strobe_reprs.maxother = ${MAX_REPR_STRING}
+_strobe_reprs.maxlevel = 3
+_strobe_reprs.maxlist = _strobe_reprs.maxtuple = _strobe_reprs.maxdict = _strobe_reprs.maxset = 10
+_strobe_tls = threading.local()
+# False once sys.monitoring reports calls; settrace then only serves breakpoints/logpoints
+_strobe_settrace_calls = True
+
+def _strobe_match(code):
+    fid = _strobe_match_cache.get(code)
+    if fid is None:
+        fid = 0
+        fname = code.co_filename
+        fline = code.co_firstlineno
+        cname = getattr(code, 'co_qualname', code.co_name)
+        for file_pat, line_pat, hook_id, name_pat in _strobe_hooks:
+            if fname.endswith(file_pat) and (cname == name_pat or fline == line_pat or (fline > 0 and abs(fline - line_pat) <= 5)):
+                fid = hook_id
+                break
+        if len(_strobe_match_cache) >= ${MAX_MATCH_CACHE}:
+            _strobe_match_cache.clear()
+        _strobe_match_cache[code] = fid
+    return fid
+
+def _strobe_repr(value):
+    try:
+        return _strobe_reprs.repr(value)
+    except Exception as e:
+        return f'<repr failed: {type(e).__name__}>'
+
+def _strobe_args(frame):
+    if frame is None:
+        return None
+    co = frame.f_code
+    n = co.co_argcount + co.co_kwonlyargcount + (1 if co.co_flags & 0x04 else 0) + (1 if co.co_flags & 0x08 else 0)
+    loc = frame.f_locals
+    return [_strobe_repr(loc[name]) for name in co.co_varnames[:n] if name in loc]
+
+def _strobe_emit(code, fid, kind, payload):
+    cname = getattr(code, 'co_qualname', code.co_name)
+    _strobe_cb(code.co_filename.encode('utf-8'), cname.encode('utf-8'), code.co_firstlineno, fid, kind, _strobe_json.dumps(payload).encode('utf-8'))
+
+def _strobe_enter(code, frame, fid):
+    # repr() runs user code — calls made from it aren't reported
+    if getattr(_strobe_tls, 'busy', False):
+        return
+    _strobe_tls.busy = True
+    try:
+        stack = getattr(_strobe_tls, 'stack', None)
+        if stack is None:
+            stack = _strobe_tls.stack = []
+        stack.append([code, _strobe_time.perf_counter_ns(), None])
+        args = _strobe_args(frame)
+        _strobe_emit(code, fid, ${PY_EVENT_ENTRY}, {} if args is None else {'arguments': args})
+    finally:
+        _strobe_tls.busy = False
+
+def _strobe_leave(code, fid, value=None, exc=None):
+    if getattr(_strobe_tls, 'busy', False):
+        return
+    _strobe_tls.busy = True
+    try:
+        if exc is not None:
+            payload = {'exceptionType': type(exc).__qualname__, 'exceptionMessage': str(exc)[:${MAX_REPR_STRING * 2}]}
+        else:
+            payload = {'returnValue': _strobe_repr(value)}
+        stack = getattr(_strobe_tls, 'stack', None)
+        if stack and stack[-1][0] is code:
+            payload['durationNs'] = _strobe_time.perf_counter_ns() - stack.pop()[1]
+        _strobe_emit(code, fid, ${PY_EVENT_EXIT}, payload)
+    finally:
+        _strobe_tls.busy = False
+`;
 
+    const preamble = header + eventHelpers + `
 # Logpoint callback: void(char*, int, char*)
 _STROBE_LOG_CB_TYPE = ctypes.CFUNCTYPE(None, ctypes.c_char_p, ctypes.c_int, ctypes.c_char_p)
 _strobe_log_cb = _STROBE_LOG_CB_TYPE(${logCallbackAddr})
@@ -291,15 +400,30 @@ ${dataAssignments}
 def _strobe_trace(frame, event, arg):
     try:
         if event == 'call':
-            co = frame.f_code
-            fname = co.co_filename
-            fline = co.co_firstlineno
-            cname = getattr(co, 'co_qualname', co.co_name)
-            for file_pat, line_pat, fid, name_pat in _strobe_hooks:
-                if fname.endswith(file_pat) and (cname == name_pat or fline == line_pat or (fline > 0 and abs(fline - line_pat) <= 5)):
-                    _strobe_cb(fname.encode('utf-8'), cname.encode('utf-8'), frame.f_lineno, fid)
-                    break
+            if _strobe_settrace_calls:
+                fid = _strobe_match(frame.f_code)
+                if fid:
+                    _strobe_enter(frame.f_code, frame, fid)
+        elif event == 'exception':
+            # Remember the in-flight exception; 'return' with None means it unwound the frame
+            stack = getattr(_strobe_tls, 'stack', None)
+            if _strobe_settrace_calls and stack and stack[-1][0] is frame.f_code:
+                stack[-1][2] = arg[1]
+        elif event == 'return':
+            if _strobe_settrace_calls:
+                fid = _strobe_match(frame.f_code)
+                if fid:
+                    stack = getattr(_strobe_tls, 'stack', None)
+                    exc = stack[-1][2] if stack and stack[-1][0] is frame.f_code else None
+                    if exc is not None and arg is None:
+                        _strobe_leave(frame.f_code, fid, exc=exc)
+                    else:
+                        _strobe_leave(frame.f_code, fid, value=arg)
         elif event == 'line':
+            # Execution resumed in this frame, so the exception was handled
+            stack = getattr(_strobe_tls, 'stack', None)
+            if stack and stack[-1][2] is not None and stack[-1][0] is frame.f_code:
+                stack[-1][2] = None
             fname = frame.f_code.co_filename
             fline = frame.f_lineno
             for lp_file, lp_line, lp_id, lp_msg in _strobe_logpoints:
@@ -361,19 +485,66 @@ for _tid in range(6):
 if _strobe_tool_id >= 0:
     try:
         setattr(_b, '_strobe_tool_id', _strobe_tool_id)
-        sys.monitoring.set_events(_strobe_tool_id, sys.monitoring.events.PY_START)
-
+        _E = sys.monitoring.events
+
+        def _strobe_frame_of(code):
+            # Callbacks run on top of the monitored frame
+            f = sys._getframe(1)
+            for _ in range(4):
+                if f is None or f.f_code is code:
+                    return f
+                f = f.f_back
+            return None
+
+        # Unmatched code objects are DISABLEd; restart_events() re-arms them on hook changes
         def _strobe_on_start(code, offset):
-            fname = code.co_filename
-            fline = code.co_firstlineno
-            cname = getattr(code, 'co_qualname', code.co_name)
-            for file_pat, line_pat, fid, name_pat in _strobe_hooks:
-                if fname.endswith(file_pat) and (cname == name_pat or fline == line_pat or (fline > 0 and abs(fline - line_pat) <= 5)):
-                    _strobe_cb(fname.encode('utf-8'), cname.encode('utf-8'), fline, fid)
-                    return
-
-        sys.monitoring.register_callback(_strobe_tool_id, sys.monitoring.events.PY_START, _strobe_on_start)
+            fid = _strobe_match(code)
+            if not fid:
+                return sys.monitoring.DISABLE
+            _strobe_enter(code, _strobe_frame_of(code), fid)
+
+        def _strobe_on_return(code, offset, retval):
+            fid = _strobe_match(code)
+            if not fid:
+                return sys.monitoring.DISABLE
+            _strobe_leave(code, fid, value=retval)
+
+        # PY_UNWIND can't be disabled — keep the unmatched path cheap
+        def _strobe_on_unwind(code, offset, exc):
+            fid = _strobe_match(code)
+            if fid:
+                _strobe_leave(code, fid, exc=exc)
+
+        # Generators and coroutines leave their frame at every yield/await and
+        # come back through PY_RESUME (or PY_THROW), not PY_START
+        def _strobe_on_yield(code, offset, value):
+            fid = _strobe_match(code)
+            if not fid:
+                return sys.monitoring.DISABLE
+            _strobe_leave(code, fid, value=value)
+
+        def _strobe_on_resume(code, offset):
+            fid = _strobe_match(code)
+            if not fid:
+                return sys.monitoring.DISABLE
+            _strobe_enter(code, None, fid)
+
+        # Like PY_UNWIND, PY_THROW can't be disabled
+        def _strobe_on_throw(code, offset, exc):
+            fid = _strobe_match(code)
+            if fid:
+                _strobe_enter(code, None, fid)
+
+        sys.monitoring.register_callback(_strobe_tool_id, _E.PY_START, _strobe_on_start)
+        sys.monitoring.register_callback(_strobe_tool_id, _E.PY_RETURN, _strobe_on_return)
+        sys.monitoring.register_callback(_strobe_tool_id, _E.PY_UNWIND, _strobe_on_unwind)
+        sys.monitoring.register_callback(_strobe_tool_id, _E.PY_YIELD, _strobe_on_yield)
+        sys.monitoring.register_callback(_strobe_tool_id, _E.PY_RESUME, _strobe_on_resume)
+        sys.monitoring.register_callback(_strobe_tool_id, _E.PY_THROW, _strobe_on_throw)
+        sys.monitoring.set_events(_strobe_tool_id, _E.PY_START | _E.PY_RETURN | _E.PY_UNWIND
+                                  | _E.PY_YIELD | _E.PY_RESUME | _E.PY_THROW)
         setattr(_b, '_strobe_monitoring_active', True)
+        _strobe_settrace_calls = False
         _strobe_monitoring_ok = True
     except Exception:
         try:
@@ -449,9 +620,18 @@ else:
   }
 
   private emitTraceEvent(funcId: number, hook: PythonHook, frameInfo: any, event: 'entry' | 'exit'): void {
-    const eventId = `${this.sessionId}-py-${++this.eventIdCounter}`;
-    const timestampNs = Date.now() * 1000000; // ms → ns
     const threadId = Process.getCurrentThreadId();
+    const eventId = this.traceIds.next(this.sessionId, threadId);
+    const timestampNs = Date.now() * 1000000; // ms → ns
+    const open = this.openCalls.get(threadId) ?? [];
+    let callId: string | undefined;
+    if (event === 'entry') {
+      callId = eventId;
+      open.push(eventId);
+      this.openCalls.set(threadId, open);
+    } else {
+      callId = open.pop();
+    }
 
     const traceEvent: any = {
       id: eventId,
@@ -459,11 +639,16 @@ else:
       timestampNs,
       threadId,
       eventType: event === 'entry' ? 'function_enter' : 'function_exit',
+      callId,
       functionName: hook.target.name || frameInfo.funcName,
       sourceFile: frameInfo.file,
       lineNumber: frameInfo.line,
       pid: Process.id,
     };
+    // Payload from the Python side: repr strings, or the exception that unwound the frame
+    for (const key of ['arguments', 'returnValue', 'exceptionType', 'exceptionMessage', 'durationNs']) {
+      if (frameInfo[key] !== undefined) traceEvent[key] = frameInfo[key];
+    }
 
     this.eventBuffer.push(traceEvent);
 
@@ -481,7 +666,7 @@ else:
   }
 
   private emitLogpointEvent(lpId: string, line: number, msg: string): void {
-    const eventId = `${this.sessionId}-pylp-${++this.eventIdCounter}`;
+    const eventId = this.logpointIds.next(this.sessionId, Process.getCurrentThreadId());
     this.eventBuffer.push({
       id: eventId,
       sessionId: this.sessionId,
@@ -539,6 +724,7 @@ else:
       this.runPython(`
 try:
     _strobe_hooks = [h for h in _strobe_hooks if h[2] != ${id}]
+    _strobe_match_cache = {}
 except NameError:
     pass
 `);
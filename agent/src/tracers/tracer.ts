@@ -1,6 +1,8 @@
 // agent/src/tracers/tracer.ts
 // Core tracer interface — all language tracers implement this contract.
 
+import type { ContainerLayout } from '../object-serializer.js';
+
 export type HookMode = 'full' | 'light';
 
 export interface ResolvedTarget {
@@ -14,6 +16,13 @@ export interface ResolvedTarget {
   nameRaw?: string;
   sourceFile?: string;
   lineNumber?: number;
+  // Native only: DWARF container layouts for arguments / return value
+  argLayouts?: (ContainerLayout | null)[];
+  returnLayout?: ContainerLayout;
+  // Go only: offset of goid in runtime.g
+  goidOffset?: number;
+  // Native only: symbol module the address is relative to
+  module?: string;
 }
 
 export interface Tracer {
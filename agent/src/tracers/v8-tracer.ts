@@ -12,6 +12,7 @@
 
 import { Tracer, ResolvedTarget, HookMode, BreakpointMessage,
          StepHooksMessage, LogpointMessage, TracerCapabilities } from './tracer.js';
+import { EventIdAllocator } from '../utils.js';
 
 interface V8Hook {
   funcId: number;
@@ -49,8 +50,11 @@ export class V8Tracer implements Tracer {
   private hooks: Map<number, V8Hook> = new Map();
   private nextFuncId: number = 1;
   private sessionId: string = '';
-  private eventIdCounter: number = 0;
+  private traceIds = new EventIdAllocator('v8');
+  private logpointIds = new EventIdAllocator('lp');
   private eventBuffer: any[] = [];
+  // ESM hooks report enter and exit separately: open call ids per function
+  private esmCalls: Map<number, string[]> = new Map();
   private flushTimer: ReturnType<typeof setInterval> | null = null;
   // Track wrapped functions to avoid double-wrapping
   private wrappedFns: WeakSet<Function> = new WeakSet();
@@ -108,10 +112,12 @@ export class V8Tracer implements Tracer {
           const nameMatch = hook.target.name === funcName;
           const fileMatch = hook.target.file && cleanFile.endsWith(hook.target.file);
           if (nameMatch && fileMatch) {
+            const open = self2.esmCalls.get(hook.funcId) ?? [];
             if (event === 'enter') {
-              self2.emitEvent(hook.funcId, hook, cleanFile, 'entry');
+              open.push(self2.emitEvent(hook.funcId, hook, cleanFile, 'entry'));
+              self2.esmCalls.set(hook.funcId, open);
             } else if (event === 'exit') {
-              self2.emitEvent(hook.funcId, hook, cleanFile, 'exit');
+              self2.emitEvent(hook.funcId, hook, cleanFile, 'exit', open.pop());
             }
             return;
           }
@@ -550,7 +556,7 @@ export class V8Tracer implements Tracer {
 
     // Emit logpoint event
     this.eventBuffer.push({
-      id: `${this.sessionId}-lp-${++this.eventIdCounter}`,
+      id: this.logpointIds.next(this.sessionId, 0),
       sessionId: this.sessionId,
       timestampNs: Date.now() * 1_000_000,
       threadId: 0,
@@ -657,25 +663,25 @@ export class V8Tracer implements Tracer {
           const self = this;
           const wrapped = new Proxy(val, {
             apply(target, thisArg, args) {
-              self.emitEvent(hook.funcId, hook, filename, 'entry');
+              const callId = self.emitEvent(hook.funcId, hook, filename, 'entry');
               let result: any;
               try {
                 result = Reflect.apply(target, thisArg, args);
               } catch (e) {
-                self.emitEvent(hook.funcId, hook, filename, 'exit');
+                self.emitEvent(hook.funcId, hook, filename, 'exit', callId);
                 throw e;
               }
               // Handle async functions
               if (result && typeof result.then === 'function') {
                 return result.then((v: any) => {
-                  self.emitEvent(hook.funcId, hook, filename, 'exit');
+                  self.emitEvent(hook.funcId, hook, filename, 'exit', callId);
                   return v;
                 }, (e: any) => {
-                  self.emitEvent(hook.funcId, hook, filename, 'exit');
+                  self.emitEvent(hook.funcId, hook, filename, 'exit', callId);
                   throw e;
                 });
               }
-              self.emitEvent(hook.funcId, hook, filename, 'exit');
+              self.emitEvent(hook.funcId, hook, filename, 'exit', callId);
               return result;
             }
           });
@@ -705,9 +711,17 @@ export class V8Tracer implements Tracer {
     }
   }
 
-  private emitEvent(funcId: number, hook: V8Hook, filename: string, event: 'entry' | 'exit'): void {
+  /**
+   * Returns the event id. An entry is its own call id; an exit takes its
+   * entry's id as `callId` when known.
+   */
+  private emitEvent(
+    funcId: number, hook: V8Hook, filename: string, event: 'entry' | 'exit', callId?: string,
+  ): string {
+    const id = this.traceIds.next(this.sessionId, 0);
     this.eventBuffer.push({
-      id: `${this.sessionId}-v8-${++this.eventIdCounter}`,
+      id,
+      callId: event === 'entry' ? id : callId,
       sessionId: this.sessionId,
       timestampNs: Date.now() * 1_000_000,
       threadId: 0, // Node.js is single-threaded for JS (worker_threads aside)
@@ -718,6 +732,7 @@ export class V8Tracer implements Tracer {
       pid: process.pid,
     });
     if (this.eventBuffer.length >= 50) this.flushEvents();
+    return id;
   }
 
   private flushEvents(): void {
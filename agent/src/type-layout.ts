@@ -0,0 +1,114 @@
+/**
+ * Render live memory through a DWARF type layout built by the daemon: structs
+ * and unions become objects, arrays lists, enums their enumerator name, and
+ * char pointers/arrays strings. Unreadable parts become `<error: ...>` strings
+ * so one bad pointer doesn't lose the rest of the tree.
+ */
+
+const MAX_STRING_BYTES = 256;
+
+export type TypeLayout =
+  | { kind: 'int'; size: number; signed: boolean }
+  | { kind: 'float'; size: number }
+  | { kind: 'bool'; size: number }
+  | { kind: 'char'; size: number }
+  | { kind: 'enum'; size: number; signed: boolean; typeName: string | null; enumerators: { name: string; value: number }[] }
+  | { kind: 'cString' }
+  | { kind: 'charArray'; len: number }
+  | { kind: 'pointer'; typeName: string | null; pointee?: TypeLayout }
+  | { kind: 'struct' | 'union'; typeName: string | null; size: number; fields: LayoutField[] }
+  | { kind: 'array'; count: number; shown: number; stride: number; element: TypeLayout }
+  | { kind: 'opaque'; typeName: string | null; size: number };
+
+export interface LayoutField {
+  name: string;
+  offset: number;
+  bitOffset?: number;
+  bitSize?: number;
+  layout: TypeLayout;
+}
+
+export function renderLayout(addr: NativePointer, layout: TypeLayout): any {
+  try {
+    switch (layout.kind) {
+      case 'int':
+        return readInt(addr, layout.size, layout.signed);
+      case 'float':
+        return layout.size === 4 ? addr.readFloat() : addr.readDouble();
+      case 'bool':
+        return readInt(addr, layout.size, false) !== 0;
+      case 'char': {
+        const code = addr.readU8();
+        return code >= 0x20 && code < 0x7f ? String.fromCharCode(code) : code;
+      }
+      case 'enum': {
+        const value = readInt(addr, layout.size, layout.signed);
+        const match = layout.enumerators.find(e => e.value === value);
+        return match ? match.name : value;
+      }
+      case 'cString': {
+        const target = addr.readPointer();
+        return target.isNull() ? null : target.readCString(MAX_STRING_BYTES);
+      }
+      case 'charArray':
+        return addr.readCString(Math.min(layout.len, MAX_STRING_BYTES));
+      case 'pointer': {
+        const target = addr.readPointer();
+        if (!layout.pointee || target.isNull()) return target.toString();
+        return { address: target.toString(), target: renderLayout(target, layout.pointee) };
+      }
+      case 'struct':
+      case 'union': {
+        const obj: Record<string, any> = {};
+        for (const field of layout.fields) {
+          obj[field.name] = field.bitSize !== undefined
+            ? readBitfield(addr.add(field.offset), field)
+            : renderLayout(addr.add(field.offset), field.layout);
+        }
+        return obj;
+      }
+      case 'array': {
+        const items: any[] = [];
+        for (let i = 0; i < layout.shown; i++) {
+          items.push(renderLayout(addr.add(i * layout.stride), layout.element));
+        }
+        if (layout.count > layout.shown) items.push(`<${layout.count - layout.shown} more>`);
+        return items;
+      }
+      case 'opaque':
+        return `<${layout.typeName ?? `${layout.size} bytes`}>`;
+    }
+  } catch (e: any) {
+    return `<error: ${e.message}>`;
+  }
+}
+
+function readInt(addr: NativePointer, size: number, signed: boolean): number {
+  switch (size) {
+    case 1: return signed ? addr.readS8() : addr.readU8();
+    case 2: return signed ? addr.readS16() : addr.readU16();
+    case 4: return signed ? addr.readS32() : addr.readU32();
+    default: return signed ? addr.readS64().toNumber() : addr.readU64().toNumber();
+  }
+}
+
+// Bits are numbered from the least significant bit of the first byte
+function readBitfield(addr: NativePointer, field: LayoutField): any {
+  const bitOffset = field.bitOffset ?? 0;
+  const bitSize = field.bitSize ?? 0;
+  const bytes = new Uint8Array(addr.readByteArray(Math.ceil((bitOffset + bitSize) / 8))!);
+  let value = 0;
+  for (let bit = 0; bit < bitSize && bit < 52; bit++) {
+    const pos = bitOffset + bit;
+    if (bytes[pos >> 3] & (1 << (pos & 7))) value += 2 ** bit;
+  }
+  const layout = field.layout;
+  if (layout.kind === 'bool') return value !== 0;
+  const signed = (layout.kind === 'int' || layout.kind === 'enum') && layout.signed;
+  if (signed && bitSize < 52 && value >= 2 ** (bitSize - 1)) value -= 2 ** bitSize;
+  if (layout.kind === 'enum') {
+    const match = layout.enumerators.find(e => e.value === value);
+    if (match) return match.name;
+  }
+  return value;
+}
@@ -20,6 +20,33 @@ export function signExtend(value: number, byteSize: number): number {
   return value;
 }
 
+// How many times the agent was reloaded into this process before this script
+// (initialize's reloadGeneration)
+let reloadGeneration = 0;
+
+export function setReloadGeneration(generation: number): void {
+  reloadGeneration = generation;
+}
+
+/**
+ * Deterministic event ids: `<session>-<stream>-<pid>-<generation>-<thread>-<seq>`,
+ * with the sequence counted per thread. The daemon inserts with INSERT OR
+ * IGNORE on the id, so a batch that is written twice stores each event once.
+ * A reloaded script counts from 1 again; the reload generation keeps its ids
+ * apart from the ones the previous script already used.
+ */
+export class EventIdAllocator {
+  private seqs: Map<number, number> = new Map();
+
+  constructor(private readonly stream: string) {}
+
+  next(sessionId: string, threadId: number): string {
+    const seq = (this.seqs.get(threadId) ?? 0) + 1;
+    this.seqs.set(threadId, seq);
+    return `${sessionId || 'uninitialized'}-${this.stream}-${Process.id}-${reloadGeneration}-${threadId}-${seq}`;
+  }
+}
+
 /**
  * Find an export across all loaded modules.
  * Replaces Module.findExportByName(null, name) which was removed as a
@@ -0,0 +1,73 @@
+//! Run a program under strobe without the daemon and print what it did.
+//!
+//!     cargo run --example embedded -- ./target/debug/myapp 'myapp::*'
+//!
+//! Launches the program with the given trace patterns staged, waits for it to
+//! exit (or 10 seconds), then prints its output and the traced calls.
+
+use std::time::Duration;
+
+use strobe::embed::Strobe;
+use strobe::mcp::{DebugLaunchRequest, DebugQueryRequest, DebugTraceRequest};
+
+#[tokio::main]
+async fn main() -> strobe::Result<()> {
+    let mut args = std::env::args().skip(1);
+    let Some(command) = args.next() else {
+        eprintln!("usage: embedded <program> [trace-pattern...]");
+        std::process::exit(2);
+    };
+    let patterns: Vec<String> = args.collect();
+
+    let data_dir = std::env::temp_dir().join("strobe-embedded-example");
+    let strobe = Strobe::open(&data_dir)?;
+
+    // Staged patterns are hooked before the program starts running.
+    if !patterns.is_empty() {
+        strobe
+            .trace(DebugTraceRequest {
+                add: Some(patterns),
+                ..Default::default()
+            })
+            .await?;
+    }
+
+    let project_root = std::env::current_dir()?.to_string_lossy().into_owned();
+    let launch = strobe
+        .launch(DebugLaunchRequest {
+            command,
+            project_root,
+            ..Default::default()
+        })
+        .await?;
+    println!("session {} (pid {})", launch.session_id, launch.pid);
+
+    let deadline = tokio::time::Instant::now() + Duration::from_secs(10);
+    while tokio::time::Instant::now() < deadline && strobe::process::is_alive(launch.pid) {
+        tokio::time::sleep(Duration::from_millis(200)).await;
+    }
+
+    let events = strobe
+        .query(DebugQueryRequest {
+            session_id: launch.session_id.clone(),
+            limit: Some(200),
+            ..Default::default()
+        })
+        .await?;
+    for event in &events.events {
+        let kind = event["eventType"].as_str().unwrap_or("?");
+        match kind {
+            "stdout" | "stderr" => print!("[{}] {}", kind, event["text"].as_str().unwrap_or("")),
+            _ => println!(
+                "[{}] {}",
+                kind,
+                event["function"].as_str().unwrap_or_default()
+            ),
+        }
+    }
+    println!("{} events recorded", events.total_count);
+
+    strobe.stop(&launch.session_id, false).await?;
+    strobe.shutdown().await;
+    Ok(())
+}
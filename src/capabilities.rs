@@ -40,6 +40,19 @@ pub fn derive_capabilities(language: Language, command: &str) -> RuntimeCapabili
                 "Memory read/write (raw addresses) not available for Python. Use debug_memory with variable names to inspect Python objects.".to_string(),
             ],
         },
+        Language::Java => RuntimeCapabilities {
+            runtime: "jvm".to_string(),
+            runtime_detail: Some("Java (JVM)".to_string()),
+            function_tracing: CapabilityLevel::Full,
+            breakpoints: CapabilityLevel::None,
+            stepping: CapabilityLevel::None,
+            output_capture: CapabilityLevel::Full,
+            limitations: vec![
+                "Java patterns resolve against .java sources under projectRoot (e.g. 'com.example.Service.handle*'). \
+                 Classes without source in the project (dependencies, JDK) can't be traced by pattern.".to_string(),
+                "Breakpoints and stepping are not supported for Java. Use debug_trace — calls record their arguments and return values via toString().".to_string(),
+            ],
+        },
         Language::JavaScript if is_bun => RuntimeCapabilities {
             runtime: "jsc".to_string(),
             runtime_detail: Some("Bun (JSC)".to_string()),
@@ -74,6 +87,22 @@ pub fn derive_capabilities(language: Language, command: &str) -> RuntimeCapabili
     }
 }
 
+/// Capabilities for a session spawned without Frida (`instrumentation: "none"`).
+///
+/// Only output capture is available; everything else requires re-running
+/// with Frida attached.
+pub fn uninstrumented_capabilities(language: Language, command: &str) -> RuntimeCapabilities {
+    let mut caps = derive_capabilities(language, command);
+    caps.function_tracing = CapabilityLevel::None;
+    caps.breakpoints = CapabilityLevel::None;
+    caps.stepping = CapabilityLevel::None;
+    caps.limitations = vec![
+        "Process was started with instrumentation: 'none' — tracing, breakpoints, stepping, watches and memory access are unavailable. \
+         Re-run debug_test without instrumentation (or with instrumentation: 'frida') to enable them.".to_string(),
+    ];
+    caps
+}
+
 /// Merge agent-reported capabilities into the baseline.
 ///
 /// The agent sends a `capabilities` message after tracer.initialize() with
@@ -1,5 +1,8 @@
-use serde::Deserialize;
-use std::path::Path;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use std::time::SystemTime;
 
 pub const MAX_EVENT_LIMIT: usize = 10_000_000;
 
@@ -7,41 +10,161 @@ pub const MAX_EVENT_LIMIT: usize = 10_000_000;
 #[derive(Debug, Clone, PartialEq)]
 pub struct StrobeSettings {
     pub events_max_per_session: usize,
+    /// Function and variable events older than this many minutes are thinned
+    /// to every `events_downsample_keep_every`-th call; output, crashes and
+    /// other events are kept. None = FIFO eviction only.
+    /// Configurable via .strobe/settings.json "events.downsampleAfterMinutes".
+    pub events_downsample_after_minutes: Option<u64>,
+    pub events_downsample_keep_every: u32,
     pub test_status_retry_ms: u64,
     /// Override the adapter's default hard timeout for test runs (milliseconds).
     /// None = use the adapter default (e.g. 600s for Playwright, 60-300s for bun).
     /// Configurable via .strobe/settings.json "test.timeoutMs".
     pub test_timeout_ms: Option<u64>,
+    /// Statement timeout for debug_query; slower queries return partial results.
+    pub query_timeout_ms: u64,
     pub vision_enabled: bool,
     pub vision_confidence_threshold: f32,
     pub vision_iou_merge_threshold: f32,
     pub vision_sidecar_idle_timeout_seconds: u64,
+    /// Run the sidecar's OCR pass with vision so nodes carry the text drawn
+    /// in them. "vision.ocr" in settings.json.
+    pub vision_ocr: bool,
+    /// Where detection runs: "sidecar", "onnx" or "http". "vision.backend"
+    /// in settings.json.
+    pub vision_backend: crate::ui::vision::VisionBackendKind,
+    /// Exported model for the onnx backend. None = the sidecar's model
+    /// directory. "vision.onnxModel" in settings.json.
+    pub vision_onnx_model: Option<String>,
+    /// Detection service URL for the http backend. "vision.endpoint" in
+    /// settings.json.
+    pub vision_endpoint: Option<String>,
+    /// Port for the REST API on 127.0.0.1. None = no HTTP listener.
+    /// Configurable via ~/.strobe/settings.json "http.port".
+    pub http_port: Option<u16>,
+    /// fsync policy for the event database. "db.syncMode" in
+    /// ~/.strobe/settings.json: "off", "normal" or "full".
+    pub db_sync_mode: crate::db::SyncMode,
+    /// Store large event arguments, backtraces and locals zstd-compressed.
+    /// "db.compressPayloads" in ~/.strobe/settings.json.
+    pub db_compress_payloads: bool,
+    /// Default stack sampling rate (samples/s per thread) for debug_trace
+    /// `sampling` requests that omit `hz`. "sampling.hz" in settings.json.
+    pub sampling_hz: u32,
+    /// Named debug_launch configurations ("profiles" in settings.json). A
+    /// project profile replaces a global one of the same name.
+    pub profiles: BTreeMap<String, LaunchProfile>,
+}
+
+/// A named launch configuration: `debug_launch({ profile })` fills in
+/// whatever the call leaves out.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct LaunchProfile {
+    pub command: Option<String>,
+    pub args: Option<Vec<String>>,
+    pub cwd: Option<String>,
+    /// Merged under the call's `env`
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
+    #[serde(default)]
+    pub trace_patterns: Vec<String>,
+    /// debug_trace watch objects, e.g. `{ "variable": "gTempo" }`
+    #[serde(default)]
+    pub watches: Vec<serde_json::Value>,
+    /// debug_breakpoint objects, e.g. `{ "function": "audio::render" }`
+    #[serde(default)]
+    pub breakpoints: Vec<serde_json::Value>,
 }
 
 impl Default for StrobeSettings {
     fn default() -> Self {
         Self {
             events_max_per_session: 200_000,
+            events_downsample_after_minutes: None,
+            events_downsample_keep_every: 10,
             test_status_retry_ms: 5_000,
             test_timeout_ms: None,
+            query_timeout_ms: 5_000,
             vision_enabled: false,
             vision_confidence_threshold: 0.3,
             vision_iou_merge_threshold: 0.5,
             vision_sidecar_idle_timeout_seconds: 300,
+            vision_ocr: true,
+            vision_backend: crate::ui::vision::VisionBackendKind::Sidecar,
+            vision_onnx_model: None,
+            vision_endpoint: None,
+            http_port: None,
+            db_sync_mode: crate::db::SyncMode::Normal,
+            db_compress_payloads: true,
+            sampling_hz: 100,
+            profiles: BTreeMap::new(),
         }
     }
 }
 
+impl StrobeSettings {
+    /// Every setting under its settings.json key, as JSON.
+    pub fn keyed_values(&self) -> Vec<(&'static str, serde_json::Value)> {
+        use serde_json::json;
+        // Through the shortest decimal form: 0.3f32 widens to 0.30000001192092896
+        let float = |v: f32| json!(v.to_string().parse::<f64>().unwrap_or_default());
+        vec![
+            ("events.maxPerSession", json!(self.events_max_per_session)),
+            (
+                "events.downsampleAfterMinutes",
+                json!(self.events_downsample_after_minutes),
+            ),
+            (
+                "events.downsampleKeepEvery",
+                json!(self.events_downsample_keep_every),
+            ),
+            ("test.statusRetryMs", json!(self.test_status_retry_ms)),
+            ("test.timeoutMs", json!(self.test_timeout_ms)),
+            ("query.timeoutMs", json!(self.query_timeout_ms)),
+            ("vision.enabled", json!(self.vision_enabled)),
+            (
+                "vision.confidenceThreshold",
+                float(self.vision_confidence_threshold),
+            ),
+            (
+                "vision.iouMergeThreshold",
+                float(self.vision_iou_merge_threshold),
+            ),
+            (
+                "vision.sidecarIdleTimeoutSeconds",
+                json!(self.vision_sidecar_idle_timeout_seconds),
+            ),
+            ("vision.ocr", json!(self.vision_ocr)),
+            ("vision.backend", json!(self.vision_backend.as_str())),
+            ("vision.onnxModel", json!(self.vision_onnx_model)),
+            ("vision.endpoint", json!(self.vision_endpoint)),
+            ("http.port", json!(self.http_port)),
+            ("db.syncMode", json!(self.db_sync_mode.as_str())),
+            ("db.compressPayloads", json!(self.db_compress_payloads)),
+            ("sampling.hz", json!(self.sampling_hz)),
+            ("profiles", json!(self.profiles)),
+        ]
+    }
+}
+
 /// Raw JSON representation — all fields optional for partial overrides.
 #[derive(Debug, Deserialize, Default)]
+#[cfg_attr(test, derive(Serialize))]
 struct SettingsFile {
     #[serde(rename = "events.maxPerSession")]
     events_max_per_session: Option<usize>,
+    #[serde(rename = "events.downsampleAfterMinutes")]
+    events_downsample_after_minutes: Option<u64>,
+    #[serde(rename = "events.downsampleKeepEvery")]
+    events_downsample_keep_every: Option<u32>,
     #[serde(rename = "test.statusRetryMs")]
     test_status_retry_ms: Option<u64>,
     /// Override adapter default timeout for test runs (30s–3600s).
     #[serde(rename = "test.timeoutMs")]
     test_timeout_ms: Option<u64>,
+    #[serde(rename = "query.timeoutMs")]
+    query_timeout_ms: Option<u64>,
     #[serde(rename = "vision.enabled")]
     vision_enabled: Option<bool>,
     #[serde(rename = "vision.confidenceThreshold")]
@@ -50,99 +173,507 @@ struct SettingsFile {
     vision_iou_merge_threshold: Option<f32>,
     #[serde(rename = "vision.sidecarIdleTimeoutSeconds")]
     vision_sidecar_idle_timeout_seconds: Option<u64>,
+    #[serde(rename = "vision.ocr")]
+    vision_ocr: Option<bool>,
+    #[serde(rename = "vision.backend")]
+    vision_backend: Option<String>,
+    #[serde(rename = "vision.onnxModel")]
+    vision_onnx_model: Option<String>,
+    #[serde(rename = "vision.endpoint")]
+    vision_endpoint: Option<String>,
+    #[serde(rename = "http.port")]
+    http_port: Option<u64>,
+    #[serde(rename = "db.syncMode")]
+    db_sync_mode: Option<String>,
+    #[serde(rename = "db.compressPayloads")]
+    db_compress_payloads: Option<bool>,
+    #[serde(rename = "sampling.hz")]
+    sampling_hz: Option<u32>,
+    /// Parsed one by one so a broken profile doesn't discard the file
+    profiles: Option<BTreeMap<String, serde_json::Value>>,
+}
+
+/// Where a setting's value came from, in precedence order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ConfigSource {
+    Default,
+    /// ~/.strobe/settings.json
+    Global,
+    /// <projectRoot>/.strobe/settings.json
+    Project,
+    /// A `STROBE_*` environment variable
+    Env,
+}
+
+/// A settings file or environment variable that was consulted.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigLayer {
+    pub source: ConfigSource,
+    /// File path or variable name
+    pub location: String,
+    /// False for a missing file
+    pub loaded: bool,
+}
+
+/// A setting that was skipped: unknown key, wrong type or invalid value.
+/// The value from the layer below stays in effect.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigIssue {
+    pub source: ConfigSource,
+    pub location: String,
+    /// None when the whole file is unreadable
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub key: Option<String>,
+    pub message: String,
+}
+
+/// Resolved settings with their provenance, for `debug_config`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigReport {
+    pub settings: StrobeSettings,
+    /// In the order they were applied
+    pub layers: Vec<ConfigLayer>,
+    /// Layer that set each key; keys left out have their default
+    pub sources: HashMap<String, ConfigSource>,
+    pub issues: Vec<ConfigIssue>,
+}
+
+impl ConfigReport {
+    pub fn source_of(&self, key: &str) -> ConfigSource {
+        self.sources
+            .get(key)
+            .copied()
+            .unwrap_or(ConfigSource::Default)
+    }
 }
 
-/// Resolve settings: defaults → user global → project-local.
+/// Resolve settings: defaults → user global → project-local → environment.
 pub fn resolve(project_root: Option<&Path>) -> StrobeSettings {
+    report(project_root).settings
+}
+
+/// Like `resolve`, with where each value came from and what was skipped.
+pub fn report(project_root: Option<&Path>) -> ConfigReport {
     let global_path = dirs::home_dir().map(|h| h.join(".strobe/settings.json"));
     let project_path = project_root.map(|r| r.join(".strobe/settings.json"));
-    resolve_with_paths(global_path.as_deref(), project_path.as_deref())
+    resolve_cached(global_path.as_deref(), project_path.as_deref())
+}
+
+/// `STROBE_` and the key in upper snake case: "events.maxPerSession" is
+/// STROBE_EVENTS_MAX_PER_SESSION.
+pub fn env_var_name(key: &str) -> String {
+    let mut name = String::from("STROBE_");
+    for c in key.chars() {
+        match c {
+            '.' => name.push('_'),
+            c if c.is_ascii_uppercase() => {
+                name.push('_');
+                name.push(c);
+            }
+            c => name.push(c.to_ascii_uppercase()),
+        }
+    }
+    name
+}
+
+/// Settings set through the environment, as (variable, value). Profiles
+/// can't be.
+fn env_overrides() -> Vec<(String, String)> {
+    setting_keys()
+        .into_iter()
+        .filter(|key| *key != "profiles")
+        .filter_map(|key| {
+            let name = env_var_name(key);
+            std::env::var(&name).ok().map(|value| (name, value))
+        })
+        .collect()
+}
+
+/// Modification time and length of a settings file; None when it's missing.
+type FileStamp = Option<(SystemTime, u64)>;
+
+struct CachedSettings {
+    stamps: [FileStamp; 2],
+    env: Vec<(String, String)>,
+    report: ConfigReport,
+}
+
+/// Resolved settings per (global, project) file pair. Settings are resolved
+/// on every launch, test run and query; the files are only parsed again when
+/// one of them or the environment changes.
+fn settings_cache() -> &'static Mutex<HashMap<(Option<PathBuf>, Option<PathBuf>), CachedSettings>> {
+    static CACHE: OnceLock<Mutex<HashMap<(Option<PathBuf>, Option<PathBuf>), CachedSettings>>> =
+        OnceLock::new();
+    CACHE.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+fn file_stamp(path: Option<&Path>) -> FileStamp {
+    let meta = std::fs::metadata(path?).ok()?;
+    Some((meta.modified().ok()?, meta.len()))
+}
+
+fn resolve_cached(global_path: Option<&Path>, project_path: Option<&Path>) -> ConfigReport {
+    let stamps = [file_stamp(global_path), file_stamp(project_path)];
+    let env = env_overrides();
+    let key = (
+        global_path.map(Path::to_path_buf),
+        project_path.map(Path::to_path_buf),
+    );
+    let mut cache = settings_cache().lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(cached) = cache.get(&key) {
+        if cached.stamps == stamps && cached.env == env {
+            return cached.report.clone();
+        }
+    }
+    let report = resolve_layers(global_path, project_path, &env);
+    cache.insert(
+        key,
+        CachedSettings {
+            stamps,
+            env,
+            report: report.clone(),
+        },
+    );
+    report
 }
 
 /// Testable resolver that accepts explicit file paths (no home dir dependency).
 fn resolve_with_paths(global_path: Option<&Path>, project_path: Option<&Path>) -> StrobeSettings {
+    resolve_layers(global_path, project_path, &[]).settings
+}
+
+fn resolve_layers(
+    global_path: Option<&Path>,
+    project_path: Option<&Path>,
+    env: &[(String, String)],
+) -> ConfigReport {
     let mut settings = StrobeSettings::default();
+    let mut layers = Vec::new();
+    let mut sources = HashMap::new();
+    let mut issues = Vec::new();
 
-    if let Some(path) = global_path {
-        apply_file(&mut settings, path);
+    let files = [
+        (ConfigSource::Global, global_path),
+        (ConfigSource::Project, project_path),
+    ];
+    for (source, path) in files {
+        let Some(path) = path else {
+            continue;
+        };
+        let location = path.display().to_string();
+        let content = std::fs::read_to_string(path).ok();
+        layers.push(ConfigLayer {
+            source,
+            location: location.clone(),
+            loaded: content.is_some(),
+        });
+        let Some(content) = content else {
+            continue;
+        };
+        let mut layer = Layer {
+            source,
+            location,
+            sources: &mut sources,
+            issues: &mut issues,
+        };
+        match serde_json::from_str::<serde_json::Map<String, serde_json::Value>>(&content) {
+            Ok(values) => layer.apply(&mut settings, values),
+            Err(e) => layer.file_invalid(e.to_string()),
+        }
     }
-    if let Some(path) = project_path {
-        apply_file(&mut settings, path);
+
+    for (name, raw) in env {
+        let Some(key) = setting_keys()
+            .into_iter()
+            .find(|k| env_var_name(k) == *name)
+        else {
+            continue;
+        };
+        // Numbers and booleans are JSON; anything else is a plain string
+        let value = serde_json::from_str::<serde_json::Value>(raw)
+            .ok()
+            .filter(|v| check_type(key, v).is_ok())
+            .unwrap_or_else(|| serde_json::Value::String(raw.clone()));
+        layers.push(ConfigLayer {
+            source: ConfigSource::Env,
+            location: name.clone(),
+            loaded: true,
+        });
+        let mut layer = Layer {
+            source: ConfigSource::Env,
+            location: name.clone(),
+            sources: &mut sources,
+            issues: &mut issues,
+        };
+        layer.apply(
+            &mut settings,
+            serde_json::Map::from_iter([(key.to_string(), value)]),
+        );
     }
 
-    settings
+    ConfigReport {
+        settings,
+        layers,
+        sources,
+        issues,
+    }
 }
 
-fn apply_file(settings: &mut StrobeSettings, path: &Path) {
-    let Ok(content) = std::fs::read_to_string(path) else {
-        return;
-    };
-    let Ok(file) = serde_json::from_str::<SettingsFile>(&content) else {
-        tracing::warn!("Invalid settings file, ignoring: {}", path.display());
-        return;
-    };
-    if let Some(v) = file.events_max_per_session {
-        if v > 0 && v <= MAX_EVENT_LIMIT {
-            settings.events_max_per_session = v;
-        } else {
-            tracing::warn!(
-                "events.maxPerSession ({}) out of range (1..{}), using default",
-                v,
-                MAX_EVENT_LIMIT
-            );
-        }
+/// Keys settings.json accepts.
+fn setting_keys() -> Vec<&'static str> {
+    StrobeSettings::default()
+        .keyed_values()
+        .into_iter()
+        .map(|(key, _)| key)
+        .collect()
+}
+
+/// Whether `value` has the type settings.json expects for `key`.
+fn check_type(key: &str, value: &serde_json::Value) -> std::result::Result<(), String> {
+    let single = serde_json::Map::from_iter([(key.to_string(), value.clone())]);
+    serde_json::from_value::<SettingsFile>(serde_json::Value::Object(single))
+        .map(|_| ())
+        .map_err(|e| e.to_string())
+}
+
+/// One settings file or variable being applied on top of the layers below.
+struct Layer<'a> {
+    source: ConfigSource,
+    location: String,
+    sources: &'a mut HashMap<String, ConfigSource>,
+    issues: &'a mut Vec<ConfigIssue>,
+}
+
+impl Layer<'_> {
+    /// Record that this layer set `key`.
+    fn set(&mut self, key: &str) {
+        self.sources.insert(key.to_string(), self.source);
     }
-    if let Some(v) = file.test_status_retry_ms {
-        if v >= 500 && v <= 60_000 {
-            settings.test_status_retry_ms = v;
-        } else {
-            tracing::warn!(
-                "test.statusRetryMs ({}) out of range (500..60000), using default",
-                v
-            );
-        }
+
+    /// Record a skipped setting.
+    fn invalid(&mut self, key: &str, message: String) {
+        tracing::warn!("{}: {}: {}, ignoring", self.location, key, message);
+        self.issues.push(ConfigIssue {
+            source: self.source,
+            location: self.location.clone(),
+            key: Some(key.to_string()),
+            message,
+        });
     }
-    if let Some(v) = file.test_timeout_ms {
-        if v >= 30_000 && v <= 3_600_000 {
-            settings.test_timeout_ms = Some(v);
-        } else {
-            tracing::warn!(
-                "test.timeoutMs ({}) out of range (30000..3600000), using adapter default",
-                v
-            );
-        }
+
+    fn file_invalid(&mut self, message: String) {
+        tracing::warn!(
+            "Invalid settings file, ignoring: {}: {}",
+            self.location,
+            message
+        );
+        self.issues.push(ConfigIssue {
+            source: self.source,
+            location: self.location.clone(),
+            key: None,
+            message,
+        });
     }
-    if let Some(v) = file.vision_enabled {
-        settings.vision_enabled = v;
-    }
-    if let Some(v) = file.vision_confidence_threshold {
-        if v > 0.0 && v <= 1.0 {
-            settings.vision_confidence_threshold = v;
-        } else {
-            tracing::warn!(
-                "vision.confidenceThreshold ({}) out of range (0.0..1.0), using default",
-                v
-            );
+
+    /// Apply the keys that are known and well-typed; report the others.
+    fn apply(
+        &mut self,
+        settings: &mut StrobeSettings,
+        values: serde_json::Map<String, serde_json::Value>,
+    ) {
+        let keys = setting_keys();
+        let mut valid = serde_json::Map::new();
+        for (key, value) in values {
+            if !keys.iter().any(|k| *k == key) {
+                self.invalid(&key, "unknown setting".to_string());
+            } else if let Err(e) = check_type(&key, &value) {
+                self.invalid(&key, e);
+            } else {
+                valid.insert(key, value);
+            }
         }
+        let file = serde_json::from_value(serde_json::Value::Object(valid)).unwrap_or_default();
+        self.apply_file(settings, file);
     }
-    if let Some(v) = file.vision_iou_merge_threshold {
-        if v > 0.0 && v <= 1.0 {
-            settings.vision_iou_merge_threshold = v;
-        } else {
-            tracing::warn!(
-                "vision.iouMergeThreshold ({}) out of range (0.0..1.0), using default",
-                v
-            );
+
+    fn apply_file(&mut self, settings: &mut StrobeSettings, file: SettingsFile) {
+        if let Some(v) = file.events_max_per_session {
+            if v > 0 && v <= MAX_EVENT_LIMIT {
+                settings.events_max_per_session = v;
+                self.set("events.maxPerSession");
+            } else {
+                self.invalid(
+                    "events.maxPerSession",
+                    format!("{} out of range (1..{})", v, MAX_EVENT_LIMIT),
+                );
+            }
         }
-    }
-    if let Some(v) = file.vision_sidecar_idle_timeout_seconds {
-        if v >= 30 && v <= 3600 {
-            settings.vision_sidecar_idle_timeout_seconds = v;
-        } else {
-            tracing::warn!(
-                "vision.sidecarIdleTimeoutSeconds ({}) out of range (30..3600), using default",
-                v
-            );
+        if let Some(v) = file.events_downsample_after_minutes {
+            if (1..=1440).contains(&v) {
+                settings.events_downsample_after_minutes = Some(v);
+                self.set("events.downsampleAfterMinutes");
+            } else {
+                self.invalid(
+                    "events.downsampleAfterMinutes",
+                    format!("{} out of range (1..1440)", v),
+                );
+            }
+        }
+        if let Some(v) = file.events_downsample_keep_every {
+            if (2..=1000).contains(&v) {
+                settings.events_downsample_keep_every = v;
+                self.set("events.downsampleKeepEvery");
+            } else {
+                self.invalid(
+                    "events.downsampleKeepEvery",
+                    format!("{} out of range (2..1000)", v),
+                );
+            }
+        }
+        if let Some(v) = file.test_status_retry_ms {
+            if v >= 500 && v <= 60_000 {
+                settings.test_status_retry_ms = v;
+                self.set("test.statusRetryMs");
+            } else {
+                self.invalid(
+                    "test.statusRetryMs",
+                    format!("{} out of range (500..60000)", v),
+                );
+            }
+        }
+        if let Some(v) = file.test_timeout_ms {
+            if v >= 30_000 && v <= 3_600_000 {
+                settings.test_timeout_ms = Some(v);
+                self.set("test.timeoutMs");
+            } else {
+                self.invalid(
+                    "test.timeoutMs",
+                    format!("{} out of range (30000..3600000)", v),
+                );
+            }
+        }
+        if let Some(v) = file.query_timeout_ms {
+            if (100..=60_000).contains(&v) {
+                settings.query_timeout_ms = v;
+                self.set("query.timeoutMs");
+            } else {
+                self.invalid(
+                    "query.timeoutMs",
+                    format!("{} out of range (100..60000)", v),
+                );
+            }
+        }
+        if let Some(v) = file.vision_enabled {
+            settings.vision_enabled = v;
+            self.set("vision.enabled");
+        }
+        if let Some(v) = file.vision_confidence_threshold {
+            if v > 0.0 && v <= 1.0 {
+                settings.vision_confidence_threshold = v;
+                self.set("vision.confidenceThreshold");
+            } else {
+                self.invalid(
+                    "vision.confidenceThreshold",
+                    format!("{} out of range (0.0..1.0)", v),
+                );
+            }
+        }
+        if let Some(v) = file.vision_iou_merge_threshold {
+            if v > 0.0 && v <= 1.0 {
+                settings.vision_iou_merge_threshold = v;
+                self.set("vision.iouMergeThreshold");
+            } else {
+                self.invalid(
+                    "vision.iouMergeThreshold",
+                    format!("{} out of range (0.0..1.0)", v),
+                );
+            }
+        }
+        if let Some(v) = file.vision_sidecar_idle_timeout_seconds {
+            if v >= 30 && v <= 3600 {
+                settings.vision_sidecar_idle_timeout_seconds = v;
+                self.set("vision.sidecarIdleTimeoutSeconds");
+            } else {
+                self.invalid(
+                    "vision.sidecarIdleTimeoutSeconds",
+                    format!("{} out of range (30..3600)", v),
+                );
+            }
+        }
+        if let Some(v) = file.vision_ocr {
+            settings.vision_ocr = v;
+            self.set("vision.ocr");
+        }
+        if let Some(v) = file.vision_backend {
+            match crate::ui::vision::VisionBackendKind::from_str(&v) {
+                Some(kind) => {
+                    settings.vision_backend = kind;
+                    self.set("vision.backend");
+                }
+                None => self.invalid(
+                    "vision.backend",
+                    format!("{:?} must be \"sidecar\", \"onnx\" or \"http\"", v),
+                ),
+            }
+        }
+        if let Some(v) = file.vision_onnx_model {
+            settings.vision_onnx_model = Some(v);
+            self.set("vision.onnxModel");
+        }
+        if let Some(v) = file.vision_endpoint {
+            if v.starts_with("http://") || v.starts_with("https://") {
+                settings.vision_endpoint = Some(v);
+                self.set("vision.endpoint");
+            } else {
+                self.invalid(
+                    "vision.endpoint",
+                    format!("{:?} must be an http:// or https:// URL", v),
+                );
+            }
+        }
+        if let Some(v) = file.http_port {
+            if (1024..=65535).contains(&v) {
+                settings.http_port = Some(v as u16);
+                self.set("http.port");
+            } else {
+                self.invalid("http.port", format!("{} out of range (1024..65535)", v));
+            }
+        }
+        if let Some(v) = file.db_compress_payloads {
+            settings.db_compress_payloads = v;
+            self.set("db.compressPayloads");
+        }
+        if let Some(v) = file.db_sync_mode {
+            match crate::db::SyncMode::from_str(&v) {
+                Some(mode) => {
+                    settings.db_sync_mode = mode;
+                    self.set("db.syncMode");
+                }
+                None => self.invalid(
+                    "db.syncMode",
+                    format!("{:?} must be \"off\", \"normal\" or \"full\"", v),
+                ),
+            }
+        }
+        if let Some(v) = file.sampling_hz {
+            if (1..=1000).contains(&v) {
+                settings.sampling_hz = v;
+                self.set("sampling.hz");
+            } else {
+                self.invalid("sampling.hz", format!("{} out of range (1..1000)", v));
+            }
+        }
+        for (name, value) in file.profiles.unwrap_or_default() {
+            match serde_json::from_value::<LaunchProfile>(value) {
+                Ok(profile) => {
+                    settings.profiles.insert(name, profile);
+                    self.set("profiles");
+                }
+                Err(e) => self.invalid(&format!("profiles.{}", name), e.to_string()),
+            }
         }
     }
 }
@@ -284,6 +815,52 @@ mod tests {
         assert_eq!(settings.test_timeout_ms, None);
     }
 
+    #[test]
+    fn test_query_timeout_ms_config() {
+        let dir = tempdir().unwrap();
+        let file = dir.path().join("settings.json");
+
+        assert_eq!(resolve_with_paths(None, None).query_timeout_ms, 5_000);
+
+        std::fs::write(&file, r#"{"query.timeoutMs": 750}"#).unwrap();
+        assert_eq!(resolve_with_paths(Some(&file), None).query_timeout_ms, 750);
+
+        std::fs::write(&file, r#"{"query.timeoutMs": 10}"#).unwrap();
+        assert_eq!(
+            resolve_with_paths(Some(&file), None).query_timeout_ms,
+            5_000
+        );
+    }
+
+    #[test]
+    fn test_downsample_config() {
+        let dir = tempdir().unwrap();
+        let file = dir.path().join("settings.json");
+
+        let settings = resolve_with_paths(None, None);
+        assert_eq!(settings.events_downsample_after_minutes, None);
+        assert_eq!(settings.events_downsample_keep_every, 10);
+
+        std::fs::write(
+            &file,
+            r#"{"events.downsampleAfterMinutes": 30, "events.downsampleKeepEvery": 50}"#,
+        )
+        .unwrap();
+        let settings = resolve_with_paths(Some(&file), None);
+        assert_eq!(settings.events_downsample_after_minutes, Some(30));
+        assert_eq!(settings.events_downsample_keep_every, 50);
+
+        // Out of range — downsampling stays off, keep-every stays default
+        std::fs::write(
+            &file,
+            r#"{"events.downsampleAfterMinutes": 0, "events.downsampleKeepEvery": 1}"#,
+        )
+        .unwrap();
+        let settings = resolve_with_paths(Some(&file), None);
+        assert_eq!(settings.events_downsample_after_minutes, None);
+        assert_eq!(settings.events_downsample_keep_every, 10);
+    }
+
     #[test]
     fn test_vision_defaults() {
         let settings = StrobeSettings::default();
@@ -291,6 +868,7 @@ mod tests {
         assert_eq!(settings.vision_confidence_threshold, 0.3);
         assert_eq!(settings.vision_iou_merge_threshold, 0.5);
         assert_eq!(settings.vision_sidecar_idle_timeout_seconds, 300);
+        assert!(settings.vision_ocr);
     }
 
     #[test]
@@ -303,7 +881,8 @@ mod tests {
             "vision.enabled": true,
             "vision.confidenceThreshold": 0.5,
             "vision.iouMergeThreshold": 0.7,
-            "vision.sidecarIdleTimeoutSeconds": 600
+            "vision.sidecarIdleTimeoutSeconds": 600,
+            "vision.ocr": false
         }"#,
         )
         .unwrap();
@@ -313,6 +892,7 @@ mod tests {
         assert_eq!(settings.vision_confidence_threshold, 0.5);
         assert_eq!(settings.vision_iou_merge_threshold, 0.7);
         assert_eq!(settings.vision_sidecar_idle_timeout_seconds, 600);
+        assert!(!settings.vision_ocr);
     }
 
     #[test]
@@ -331,6 +911,42 @@ mod tests {
         assert_eq!(settings.vision_iou_merge_threshold, 0.5); // default
     }
 
+    #[test]
+    fn test_vision_backend_settings() {
+        use crate::ui::vision::VisionBackendKind;
+        let dir = tempdir().unwrap();
+        let file = dir.path().join("settings.json");
+
+        std::fs::write(
+            &file,
+            r#"{
+            "vision.backend": "http",
+            "vision.endpoint": "https://vision.example.com/detect",
+            "vision.onnxModel": "/models/icons.onnx"
+        }"#,
+        )
+        .unwrap();
+        let settings = resolve_with_paths(Some(&file), None);
+        assert_eq!(settings.vision_backend, VisionBackendKind::Http);
+        assert_eq!(
+            settings.vision_endpoint.as_deref(),
+            Some("https://vision.example.com/detect")
+        );
+        assert_eq!(
+            settings.vision_onnx_model.as_deref(),
+            Some("/models/icons.onnx")
+        );
+
+        std::fs::write(
+            &file,
+            r#"{"vision.backend": "gpu", "vision.endpoint": "vision.local:8080"}"#,
+        )
+        .unwrap();
+        let settings = resolve_with_paths(Some(&file), None);
+        assert_eq!(settings.vision_backend, VisionBackendKind::Sidecar);
+        assert_eq!(settings.vision_endpoint, None);
+    }
+
     #[test]
     fn test_vision_idle_timeout_out_of_range() {
         let dir = tempdir().unwrap();
@@ -346,4 +962,225 @@ mod tests {
         let settings = resolve_with_paths(Some(&file), None);
         assert_eq!(settings.vision_sidecar_idle_timeout_seconds, 300); // default
     }
+
+    #[test]
+    fn test_http_port_config() {
+        let dir = tempdir().unwrap();
+        let file = dir.path().join("settings.json");
+
+        std::fs::write(&file, r#"{"http.port": 7878}"#).unwrap();
+        assert_eq!(resolve_with_paths(Some(&file), None).http_port, Some(7878));
+
+        // Privileged or out-of-range ports leave the listener off without
+        // discarding the rest of the file
+        std::fs::write(&file, r#"{"http.port": 80, "query.timeoutMs": 900}"#).unwrap();
+        let settings = resolve_with_paths(Some(&file), None);
+        assert_eq!(settings.http_port, None);
+        assert_eq!(settings.query_timeout_ms, 900);
+        std::fs::write(&file, r#"{"http.port": 70000}"#).unwrap();
+        assert_eq!(resolve_with_paths(Some(&file), None).http_port, None);
+    }
+
+    #[test]
+    fn test_db_settings() {
+        use crate::db::SyncMode;
+        let dir = tempdir().unwrap();
+        let file = dir.path().join("settings.json");
+
+        assert_eq!(
+            resolve_with_paths(None, None).db_sync_mode,
+            SyncMode::Normal
+        );
+        std::fs::write(&file, r#"{"db.syncMode": "full"}"#).unwrap();
+        assert_eq!(
+            resolve_with_paths(Some(&file), None).db_sync_mode,
+            SyncMode::Full
+        );
+        std::fs::write(&file, r#"{"db.syncMode": "always"}"#).unwrap();
+        assert_eq!(
+            resolve_with_paths(Some(&file), None).db_sync_mode,
+            SyncMode::Normal
+        );
+
+        assert!(resolve_with_paths(None, None).db_compress_payloads);
+        std::fs::write(&file, r#"{"db.compressPayloads": false}"#).unwrap();
+        assert!(!resolve_with_paths(Some(&file), None).db_compress_payloads);
+    }
+
+    #[test]
+    fn test_profiles_config() {
+        let dir = tempdir().unwrap();
+        let global = dir.path().join("global.json");
+        let project = dir.path().join("project.json");
+        std::fs::write(
+            &global,
+            r#"{"profiles": {
+                "audio-debug": {"command": "/bin/old"},
+                "quick": {"command": "/bin/app", "args": ["--fast"]}
+            }}"#,
+        )
+        .unwrap();
+        std::fs::write(
+            &project,
+            r#"{"profiles": {
+                "audio-debug": {
+                    "command": "./build/app",
+                    "env": {"AUDIO_DEBUG": "1"},
+                    "tracePatterns": ["audio::*"],
+                    "watches": [{"variable": "gTempo"}],
+                    "breakpoints": [{"function": "audio::render"}]
+                },
+                "broken": {"command": 42},
+                "typo": {"comand": "/bin/app"}
+            }, "sampling.hz": 50}"#,
+        )
+        .unwrap();
+
+        let settings = resolve_with_paths(Some(&global), Some(&project));
+        assert_eq!(settings.sampling_hz, 50);
+        assert_eq!(settings.profiles.len(), 2);
+        let audio = &settings.profiles["audio-debug"];
+        assert_eq!(audio.command.as_deref(), Some("./build/app"));
+        assert_eq!(audio.env["AUDIO_DEBUG"], "1");
+        assert_eq!(audio.trace_patterns, vec!["audio::*"]);
+        assert_eq!(audio.watches.len(), 1);
+        assert_eq!(audio.breakpoints.len(), 1);
+        assert_eq!(
+            settings.profiles["quick"].args,
+            Some(vec!["--fast".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_sampling_hz_config() {
+        let dir = tempdir().unwrap();
+        let file = dir.path().join("settings.json");
+        assert_eq!(resolve_with_paths(None, None).sampling_hz, 100);
+
+        std::fs::write(&file, r#"{"sampling.hz": 250}"#).unwrap();
+        assert_eq!(resolve_with_paths(Some(&file), None).sampling_hz, 250);
+
+        std::fs::write(&file, r#"{"sampling.hz": 0}"#).unwrap();
+        assert_eq!(resolve_with_paths(Some(&file), None).sampling_hz, 100);
+        std::fs::write(&file, r#"{"sampling.hz": 5000}"#).unwrap();
+        assert_eq!(resolve_with_paths(Some(&file), None).sampling_hz, 100);
+    }
+
+    #[test]
+    fn test_resolve_cached_follows_file_changes() {
+        let dir = tempdir().unwrap();
+        let project = dir.path().join("settings.json");
+        let sampling_hz = || resolve_cached(None, Some(&project)).settings.sampling_hz;
+        assert_eq!(sampling_hz(), 100);
+
+        std::fs::write(&project, r#"{"sampling.hz": 250}"#).unwrap();
+        assert_eq!(sampling_hz(), 250);
+        assert_eq!(sampling_hz(), 250);
+
+        // A different length is a change even within the mtime granularity
+        std::fs::write(&project, r#"{"sampling.hz": 1000}"#).unwrap();
+        assert_eq!(sampling_hz(), 1000);
+
+        std::fs::remove_file(&project).unwrap();
+        assert_eq!(sampling_hz(), 100);
+    }
+
+    #[test]
+    fn test_report_layers_and_issues() {
+        let dir = tempdir().unwrap();
+        let global = dir.path().join("global.json");
+        let project = dir.path().join("project.json");
+        std::fs::write(
+            &global,
+            r#"{"sampling.hz": 250, "query.timeoutMs": 750, "sampling.rate": 9}"#,
+        )
+        .unwrap();
+        std::fs::write(
+            &project,
+            r#"{"events.maxPerSession": "lots", "query.timeoutMs": 900}"#,
+        )
+        .unwrap();
+        let env = [
+            ("STROBE_SAMPLING_HZ".to_string(), "500".to_string()),
+            ("STROBE_VISION_BACKEND".to_string(), "http".to_string()),
+            ("STROBE_HTTP_PORT".to_string(), "80".to_string()),
+        ];
+
+        let report = resolve_layers(Some(&global), Some(&project), &env);
+        assert_eq!(report.settings.sampling_hz, 500);
+        assert_eq!(report.settings.query_timeout_ms, 900);
+        assert_eq!(
+            report.settings.vision_backend,
+            crate::ui::vision::VisionBackendKind::Http
+        );
+        assert_eq!(report.settings.events_max_per_session, 200_000);
+        assert_eq!(report.settings.http_port, None);
+
+        assert_eq!(report.source_of("sampling.hz"), ConfigSource::Env);
+        assert_eq!(report.source_of("query.timeoutMs"), ConfigSource::Project);
+        assert_eq!(report.source_of("vision.backend"), ConfigSource::Env);
+        assert_eq!(
+            report.source_of("events.maxPerSession"),
+            ConfigSource::Default
+        );
+        assert_eq!(report.layers.len(), 5);
+        assert!(report.layers.iter().all(|l| l.loaded));
+
+        let issues: Vec<(ConfigSource, Option<&str>)> = report
+            .issues
+            .iter()
+            .map(|i| (i.source, i.key.as_deref()))
+            .collect();
+        assert_eq!(
+            issues,
+            vec![
+                (ConfigSource::Global, Some("sampling.rate")),
+                (ConfigSource::Project, Some("events.maxPerSession")),
+                (ConfigSource::Env, Some("http.port")),
+            ]
+        );
+        assert_eq!(report.issues[0].message, "unknown setting");
+        assert!(report.issues[1].message.contains("invalid type"));
+        assert_eq!(report.issues[2].location, "STROBE_HTTP_PORT");
+
+        // An unreadable file is one issue without a key
+        std::fs::write(&project, "{ nope").unwrap();
+        let report = resolve_layers(None, Some(&project), &[]);
+        assert_eq!(report.issues.len(), 1);
+        assert_eq!(report.issues[0].key, None);
+        let missing = dir.path().join("missing.json");
+        let report = resolve_layers(Some(&missing), None, &[]);
+        assert!(!report.layers[0].loaded);
+        assert!(report.issues.is_empty());
+    }
+
+    #[test]
+    fn test_env_var_name() {
+        assert_eq!(
+            env_var_name("events.maxPerSession"),
+            "STROBE_EVENTS_MAX_PER_SESSION"
+        );
+        assert_eq!(env_var_name("http.port"), "STROBE_HTTP_PORT");
+        assert_eq!(env_var_name("vision.onnxModel"), "STROBE_VISION_ONNX_MODEL");
+
+        // Values that aren't JSON of the right type are strings
+        let env = [("STROBE_VISION_ONNX_MODEL".to_string(), "123".to_string())];
+        let settings = resolve_layers(None, None, &env).settings;
+        assert_eq!(settings.vision_onnx_model.as_deref(), Some("123"));
+    }
+
+    #[test]
+    fn test_setting_keys_match_settings_file() {
+        let file = serde_json::to_value(SettingsFile::default()).unwrap();
+        let mut file_keys: Vec<&str> = file
+            .as_object()
+            .unwrap()
+            .keys()
+            .map(String::as_str)
+            .collect();
+        let mut keys = setting_keys();
+        file_keys.sort();
+        keys.sort();
+        assert_eq!(file_keys, keys);
+    }
 }
@@ -0,0 +1,233 @@
+//! Happens-before ordering of traced calls in the event writer.
+//!
+//! The agent drains its ring buffers in batches, so a function_exit can reach
+//! the writer before the function_enter it closes. The writer stores events in
+//! arrival order (that order becomes `seq`), which would put the exit first
+//! and break call-tree reconstruction. `CallOrderer` holds such an exit — and
+//! every later call event of the same thread, to keep that thread's sequence
+//! intact — until the enter shows up, then releases them enter-first.
+//!
+//! An enter that never arrives (evicted by the agent, lost with a crashed
+//! process) must not stall the thread, so held events are released after
+//! `HOLD`. Output and other non-call events are never held.
+
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::time::{Duration, Instant};
+
+use crate::db::{Event, EventType};
+
+/// How long an exit waits for its enter before it is stored as an orphan.
+/// Many agent drain intervals (10ms) plus channel latency.
+pub const HOLD: Duration = Duration::from_millis(500);
+/// Held call events per thread; past this the oldest is released early.
+const MAX_HELD_PER_THREAD: usize = 10_000;
+/// Open calls remembered. Forgetting them (the set is cleared when full)
+/// only delays their exits by `HOLD`.
+const MAX_OPEN_CALLS: usize = 200_000;
+
+type ThreadKey = (Option<u32>, i64);
+
+#[derive(Default)]
+pub struct CallOrderer {
+    /// Enter ids whose exit has not been released yet
+    open: HashSet<String>,
+    /// Per-thread call events waiting behind an exit whose enter is missing
+    held: HashMap<ThreadKey, VecDeque<(Event, Instant)>>,
+    /// Exits released without their enter (after HOLD or at shutdown)
+    orphans_released: u64,
+}
+
+impl CallOrderer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Exits stored without their enter so far.
+    pub fn orphans_released(&self) -> u64 {
+        self.orphans_released
+    }
+
+    /// Accept one event; returns the events now ready to store, in order.
+    pub fn push(&mut self, event: Event, now: Instant) -> Vec<Event> {
+        if !matches!(
+            event.event_type,
+            EventType::FunctionEnter | EventType::FunctionExit
+        ) {
+            return vec![event];
+        }
+
+        if event.event_type == EventType::FunctionEnter {
+            if self.open.len() >= MAX_OPEN_CALLS {
+                self.open.clear();
+            }
+            self.open.insert(event.id.clone());
+        }
+
+        let key = (event.pid, event.thread_id);
+        let Some(queue) = self.held.get_mut(&key) else {
+            if self.is_ready(&event) {
+                return vec![self.release(event)];
+            }
+            self.held.entry(key).or_default().push_back((event, now));
+            return Vec::new();
+        };
+
+        // A late enter goes right before the exit waiting for it
+        let waiting_exit = (event.event_type == EventType::FunctionEnter)
+            .then(|| {
+                queue.iter().position(|(held, _)| {
+                    held.event_type == EventType::FunctionExit
+                        && held.parent_event_id.as_deref() == Some(event.id.as_str())
+                })
+            })
+            .flatten();
+        match waiting_exit {
+            Some(index) => queue.insert(index, (event, now)),
+            None => queue.push_back((event, now)),
+        }
+
+        let mut ready = Vec::new();
+        self.drain_thread(key, now, false, &mut ready);
+        ready
+    }
+
+    /// Release events held longer than `HOLD`, plus whatever they unblock.
+    pub fn expire(&mut self, now: Instant) -> Vec<Event> {
+        let mut ready = Vec::new();
+        let keys: Vec<ThreadKey> = self.held.keys().copied().collect();
+        for key in keys {
+            self.drain_thread(key, now, false, &mut ready);
+        }
+        ready
+    }
+
+    /// Release everything, keeping per-thread order (writer shutdown).
+    pub fn drain_all(&mut self) -> Vec<Event> {
+        let mut ready = Vec::new();
+        let now = Instant::now();
+        let keys: Vec<ThreadKey> = self.held.keys().copied().collect();
+        for key in keys {
+            self.drain_thread(key, now, true, &mut ready);
+        }
+        ready
+    }
+
+    fn drain_thread(&mut self, key: ThreadKey, now: Instant, force: bool, out: &mut Vec<Event>) {
+        let Some(mut queue) = self.held.remove(&key) else {
+            return;
+        };
+        while let Some((event, since)) = queue.front() {
+            let ready = force
+                || self.is_ready(event)
+                || now.duration_since(*since) >= HOLD
+                || queue.len() > MAX_HELD_PER_THREAD;
+            if !ready {
+                break;
+            }
+            let (event, _) = queue.pop_front().expect("front exists");
+            out.push(self.release(event));
+        }
+        if !queue.is_empty() {
+            self.held.insert(key, queue);
+        }
+    }
+
+    /// Enters and exits whose enter was seen are ready; so are exits that
+    /// name no enter (nothing to wait for).
+    fn is_ready(&self, event: &Event) -> bool {
+        event.event_type != EventType::FunctionExit
+            || event
+                .parent_event_id
+                .as_ref()
+                .is_none_or(|enter| self.open.contains(enter))
+    }
+
+    fn release(&mut self, event: Event) -> Event {
+        if event.event_type == EventType::FunctionExit {
+            if let Some(ref enter) = event.parent_event_id {
+                if !self.open.remove(enter) {
+                    self.orphans_released += 1;
+                }
+            }
+        }
+        event
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn call(id: &str, event_type: EventType, thread_id: i64, parent: Option<&str>) -> Event {
+        Event {
+            id: id.to_string(),
+            session_id: "s".to_string(),
+            thread_id,
+            event_type,
+            parent_event_id: parent.map(String::from),
+            ..Event::default()
+        }
+    }
+
+    fn ids(events: &[Event]) -> Vec<&str> {
+        events.iter().map(|e| e.id.as_str()).collect()
+    }
+
+    #[test]
+    fn test_in_order_calls_pass_through() {
+        let mut orderer = CallOrderer::new();
+        let now = Instant::now();
+        let out = orderer.push(call("a", EventType::FunctionEnter, 1, None), now);
+        assert_eq!(ids(&out), ["a"]);
+        let out = orderer.push(call("a-x", EventType::FunctionExit, 1, Some("a")), now);
+        assert_eq!(ids(&out), ["a-x"]);
+        assert_eq!(orderer.orphans_released(), 0);
+    }
+
+    #[test]
+    fn test_early_exit_waits_for_its_enter() {
+        let mut orderer = CallOrderer::new();
+        let now = Instant::now();
+
+        // Exit of b arrives first; a later call on the same thread queues behind it
+        assert!(orderer
+            .push(call("b-x", EventType::FunctionExit, 1, Some("b")), now)
+            .is_empty());
+        assert!(orderer
+            .push(call("c", EventType::FunctionEnter, 1, None), now)
+            .is_empty());
+        // Other threads are unaffected
+        let other = orderer.push(call("d", EventType::FunctionEnter, 2, None), now);
+        assert_eq!(ids(&other), ["d"]);
+
+        let out = orderer.push(call("b", EventType::FunctionEnter, 1, None), now);
+        assert_eq!(ids(&out), ["b", "b-x", "c"]);
+        assert_eq!(orderer.orphans_released(), 0);
+    }
+
+    #[test]
+    fn test_missing_enter_released_after_hold() {
+        let mut orderer = CallOrderer::new();
+        let start = Instant::now();
+        orderer.push(
+            call("gone-x", EventType::FunctionExit, 1, Some("gone")),
+            start,
+        );
+        orderer.push(call("e", EventType::FunctionEnter, 1, None), start);
+
+        assert!(orderer.expire(start + HOLD / 2).is_empty());
+        let out = orderer.expire(start + HOLD);
+        assert_eq!(ids(&out), ["gone-x", "e"]);
+        assert_eq!(orderer.orphans_released(), 1);
+    }
+
+    #[test]
+    fn test_non_call_events_never_held() {
+        let mut orderer = CallOrderer::new();
+        let now = Instant::now();
+        orderer.push(call("x", EventType::FunctionExit, 1, Some("y")), now);
+        let out = orderer.push(call("out", EventType::Stdout, 1, None), now);
+        assert_eq!(ids(&out), ["out"]);
+        assert_eq!(ids(&orderer.drain_all()), ["x"]);
+    }
+}
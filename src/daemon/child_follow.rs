@@ -0,0 +1,144 @@
+//! Following processes spawned by traced tests.
+//!
+//! Spawn gating attaches the agent to every fork/exec child of a session and
+//! hands the still-suspended child here. For debug_test sessions, a child whose
+//! executable lives in the project gets the session's trace patterns hooked
+//! (resolved against its own DWARF) before it runs, and is recorded under the
+//! test that was running when it appeared so its events carry `test_name`.
+//! Every child is resumed, hooked or not.
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::{Arc, Mutex, RwLock};
+
+use super::session_manager::{batches_by_mode, dwarf_handle_for, read_lock, write_lock};
+use crate::db::Database;
+use crate::dwarf::{DwarfHandle, DwarfParser};
+use crate::frida_collector::{process_executable, AttachedChild, FridaSpawner};
+use crate::test::TestProgress;
+
+/// What following a child needs from the session manager, cloned into the
+/// task that drains the spawner's child channel.
+#[derive(Clone)]
+pub struct ChildFollower {
+    pub db: Database,
+    pub spawner: Arc<tokio::sync::RwLock<Option<FridaSpawner>>>,
+    pub dwarf_cache: Arc<RwLock<HashMap<String, DwarfHandle>>>,
+    pub patterns: Arc<RwLock<HashMap<String, Vec<String>>>>,
+    pub counted_patterns: Arc<RwLock<HashMap<String, Vec<String>>>>,
+    pub rt_patterns: Arc<RwLock<HashMap<String, Vec<String>>>>,
+    pub serialization_depths: Arc<RwLock<HashMap<String, u32>>>,
+    pub child_pids: Arc<RwLock<HashMap<String, Vec<u32>>>>,
+    /// Live progress of debug_test sessions, per session
+    pub test_progress: Arc<RwLock<HashMap<String, Arc<Mutex<TestProgress>>>>>,
+    /// Owning test per followed child pid, per session
+    pub child_tests: Arc<RwLock<HashMap<String, HashMap<u32, String>>>>,
+}
+
+impl ChildFollower {
+    pub async fn run(self, mut rx: tokio::sync::mpsc::UnboundedReceiver<AttachedChild>) {
+        while let Some(child) = rx.recv().await {
+            // Hooking waits on a DWARF parse; don't hold up the next child.
+            tokio::spawn(self.clone().follow(child));
+        }
+    }
+
+    async fn follow(self, child: AttachedChild) {
+        let (session_id, pid) = (child.session_id.clone(), child.pid);
+        write_lock(&self.child_pids)
+            .entry(session_id.clone())
+            .or_default()
+            .push(pid);
+
+        let progress = read_lock(&self.test_progress).get(&session_id).cloned();
+        let guard = self.spawner.read().await;
+        let Some(spawner) = guard.as_ref() else {
+            return;
+        };
+        if let Some(progress) = progress {
+            let test = progress.lock().ok().and_then(|p| p.latest_test());
+            if let Some(test) = test {
+                tracing::info!(
+                    "Child {} of session {} belongs to test {}",
+                    pid,
+                    session_id,
+                    test
+                );
+                write_lock(&self.child_tests)
+                    .entry(session_id.clone())
+                    .or_default()
+                    .insert(pid, test);
+            }
+            if let Err(e) = self.hook(spawner, child).await {
+                tracing::warn!("Could not trace child {} of {}: {}", pid, session_id, e);
+            }
+        }
+        if let Err(e) = spawner.resume(pid).await {
+            tracing::warn!("Failed to resume child {}: {}", pid, e);
+        }
+    }
+
+    /// Install the session's patterns in a child running a project binary.
+    async fn hook(&self, spawner: &FridaSpawner, child: AttachedChild) -> crate::Result<()> {
+        let session_id = child.session_id.clone();
+        let patterns = read_lock(&self.patterns)
+            .get(&session_id)
+            .cloned()
+            .unwrap_or_default();
+        if patterns.is_empty() {
+            return Ok(());
+        }
+        let Some(session) = self.db.get_session(&session_id)? else {
+            return Ok(());
+        };
+        let Some(executable) = process_executable(child.pid) else {
+            return Ok(());
+        };
+        // Shells, compilers and system tools have nothing to match the patterns.
+        if !executable.starts_with(&session.project_root) {
+            tracing::debug!(
+                "Child {} runs {} outside the project, not tracing",
+                child.pid,
+                executable.display()
+            );
+            return Ok(());
+        }
+
+        let binary = executable.to_string_lossy().into_owned();
+        let image_base = DwarfParser::extract_image_base(Path::new(&binary)).unwrap_or(0);
+        let dwarf = dwarf_handle_for(
+            &self.dwarf_cache,
+            &binary,
+            Some(&session.project_root),
+            None,
+        );
+        let key = spawner.follow_child(child, dwarf, image_base)?;
+
+        let counted = read_lock(&self.counted_patterns)
+            .get(&session_id)
+            .cloned()
+            .unwrap_or_default();
+        let rt = read_lock(&self.rt_patterns)
+            .get(&session_id)
+            .cloned()
+            .unwrap_or_default();
+        let depth = read_lock(&self.serialization_depths)
+            .get(&session_id)
+            .copied();
+        for (batch, mode) in batches_by_mode(patterns, &counted, &rt) {
+            if batch.is_empty() {
+                continue;
+            }
+            let result = spawner
+                .add_patterns(&key, &batch, depth, None, mode)
+                .await?;
+            tracing::info!(
+                "Hooked {} functions in child {} ({})",
+                result.installed,
+                key,
+                binary
+            );
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,399 @@
+//! Crash summaries written to disk alongside crash events.
+//!
+//! Session databases are evicted (FIFO limits, `debug_delete_session`), but a
+//! crash is often investigated later. When the event writer sees a crash it
+//! writes a self-contained JSON summary to `<crash_dir>/<session>/<event>.json`
+//! and stores that path on the event as `dump_path`.
+//!
+//! The agent sends the all-thread backtraces and module list inside the crash
+//! event's `text` JSON (next to `frameMemory`/`frameBase`); they are moved into
+//! the dump and stripped from the stored event so the events table stays small.
+//! `crashCapture` globals are copied into the dump and kept on the event.
+//!
+//! Frames in the binary under test are mapped to file:line through its DWARF
+//! line tables before the dump is written; Frida's own symbolizer only knows
+//! the function for most of them. The faulting pc's location is kept on the
+//! event as `pcLocation`.
+//!
+//! Each crash is also counted in the `crashes` table under its signature, so a
+//! repeat of a known crash can be recognised across sessions.
+
+use std::path::{Path, PathBuf};
+
+use crate::db::{Database, Event, Session};
+use crate::dwarf::{DwarfHandle, SourceLocation};
+
+/// Keys of the crash `text` JSON that only belong in the dump file.
+const DUMP_ONLY_KEYS: &[&str] = &["threads", "modules"];
+
+/// Event-writer hook for crash events: map frames to source lines through the
+/// session binary's symbols (`symbols`), write the on-disk summary and count
+/// the crash against its signature in the project's crash history. Failures
+/// are logged, never propagated — the event itself must still be stored.
+pub fn handle_crash_event(
+    db: &Database,
+    crash_dir: &Path,
+    event: &mut Event,
+    symbols: impl FnOnce(&Session) -> DwarfHandle,
+) {
+    let session = db.get_session(&event.session_id).ok().flatten();
+    if let Some(ref session) = session {
+        symbolize_frames(event, &session.binary_path, &symbols(session));
+    }
+    match write_crash_dump(crash_dir, event, session.as_ref()) {
+        Ok(path) => tracing::info!("Crash summary written to {}", path.display()),
+        Err(e) => tracing::warn!("Failed to write crash summary: {}", e),
+    }
+    let Some(session) = session else {
+        return;
+    };
+    match db.record_crash(&session.project_root, event) {
+        Ok(record) => tracing::info!(
+            "Crash {} in {}: {}",
+            record.signature,
+            session.project_root,
+            record.history(chrono::Utc::now().timestamp())
+        ),
+        Err(e) => tracing::warn!("Failed to record crash history: {}", e),
+    }
+}
+
+/// Fill in `fileName`/`lineNumber` (and a missing `name`) of the backtrace
+/// frames that fall in the main binary, on the crashing thread, the throw
+/// site and the other threads, and record the faulting pc's location. The
+/// binary's load address comes from the crash's module list; frames are
+/// return addresses, so the call instruction before them is looked up.
+pub fn symbolize_frames(event: &mut Event, binary_path: &str, dwarf: &DwarfHandle) {
+    let Some(mut extra) = event
+        .text
+        .as_deref()
+        .and_then(|t| serde_json::from_str::<serde_json::Value>(t).ok())
+    else {
+        return;
+    };
+    let Some(base) = main_module_base(&extra, binary_path) else {
+        return;
+    };
+    let locate = |address: u64| dwarf.addr2line(address, base);
+
+    for backtrace in [&mut event.backtrace, &mut event.throw_backtrace]
+        .into_iter()
+        .flatten()
+    {
+        symbolize_backtrace(backtrace, &locate);
+    }
+    if let Some(threads) = extra.get_mut("threads").and_then(|t| t.as_array_mut()) {
+        for thread in threads {
+            if let Some(backtrace) = thread.get_mut("backtrace") {
+                symbolize_backtrace(backtrace, &locate);
+            }
+        }
+    }
+
+    let pc = event.registers.as_ref().and_then(|r| {
+        ["pc", "rip"]
+            .iter()
+            .find_map(|reg| r.get(*reg).and_then(|v| v.as_str()).and_then(parse_hex))
+    });
+    if let (Some(loc), Some(obj)) = (pc.and_then(locate), extra.as_object_mut()) {
+        obj.insert(
+            "pcLocation".to_string(),
+            serde_json::json!({
+                "function": loc.function,
+                "file": loc.file,
+                "line": loc.line,
+                "column": loc.column,
+            }),
+        );
+    }
+    event.text = Some(extra.to_string());
+}
+
+/// Load address of the session binary in the crash's module list.
+fn main_module_base(extra: &serde_json::Value, binary_path: &str) -> Option<u64> {
+    let file_name = Path::new(binary_path).file_name()?.to_str()?;
+    let modules = extra.get("modules")?.as_array()?;
+    let field = |m: &serde_json::Value, key: &str| m.get(key).and_then(|v| v.as_str());
+    let main = modules
+        .iter()
+        .find(|m| field(m, "path") == Some(binary_path))
+        .or_else(|| modules.iter().find(|m| field(m, "name") == Some(file_name)))?;
+    field(main, "base").and_then(parse_hex)
+}
+
+fn symbolize_backtrace(
+    backtrace: &mut serde_json::Value,
+    locate: &impl Fn(u64) -> Option<SourceLocation>,
+) {
+    let Some(frames) = backtrace.as_array_mut() else {
+        return;
+    };
+    for frame in frames {
+        let Some(address) = frame
+            .get("address")
+            .and_then(|a| a.as_str())
+            .and_then(parse_hex)
+        else {
+            continue;
+        };
+        let Some(loc) = locate(address.saturating_sub(1)) else {
+            continue;
+        };
+        let Some(obj) = frame.as_object_mut() else {
+            continue;
+        };
+        if obj.get("name").is_none_or(|n| n.is_null()) {
+            obj.insert("name".to_string(), loc.function.into());
+        }
+        obj.insert("fileName".to_string(), loc.file.into());
+        obj.insert("lineNumber".to_string(), loc.line.into());
+    }
+}
+
+fn parse_hex(s: &str) -> Option<u64> {
+    u64::from_str_radix(s.trim_start_matches("0x"), 16).ok()
+}
+
+/// Build the summary document for a crash event.
+pub fn build_summary(event: &Event, session: Option<&Session>) -> serde_json::Value {
+    let extra = event
+        .text
+        .as_deref()
+        .and_then(|t| serde_json::from_str::<serde_json::Value>(t).ok())
+        .unwrap_or(serde_json::Value::Null);
+
+    serde_json::json!({
+        "version": 1,
+        "sessionId": event.session_id,
+        "eventId": event.id,
+        "binaryPath": session.map(|s| s.binary_path.as_str()),
+        "projectRoot": session.map(|s| s.project_root.as_str()),
+        "pid": event.pid,
+        "timestamp_ns": event.timestamp_ns,
+        "writtenAt": chrono::Utc::now().to_rfc3339(),
+        "threadId": event.thread_id,
+        "signal": event.signal,
+        "faultAddress": event.fault_address,
+        "registers": event.registers,
+        "backtrace": event.backtrace,
+        "exceptionType": event.exception_type,
+        "exceptionMessage": event.exception_message,
+        "throwBacktrace": event.throw_backtrace,
+        "globals": extra.get("globals").cloned().unwrap_or(serde_json::json!([])),
+        "threads": extra.get("threads").cloned().unwrap_or(serde_json::json!([])),
+        "modules": extra.get("modules").cloned().unwrap_or(serde_json::json!([])),
+    })
+}
+
+/// Write the crash summary for `event` under `crash_dir`, set `event.dump_path`
+/// and drop the dump-only keys from `event.text`. Returns the written path.
+pub fn write_crash_dump(
+    crash_dir: &Path,
+    event: &mut Event,
+    session: Option<&Session>,
+) -> std::io::Result<PathBuf> {
+    let dir = crash_dir.join(sanitize(&event.session_id));
+    std::fs::create_dir_all(&dir)?;
+    let path = dir.join(format!("{}.json", sanitize(&event.id)));
+
+    let summary = build_summary(event, session);
+    let body = serde_json::to_vec_pretty(&summary).map_err(std::io::Error::other)?;
+    std::fs::write(&path, body)?;
+
+    strip_dump_only_keys(event);
+    event.dump_path = Some(path.to_string_lossy().into_owned());
+    Ok(path)
+}
+
+fn strip_dump_only_keys(event: &mut Event) {
+    let Some(mut value) = event
+        .text
+        .as_deref()
+        .and_then(|t| serde_json::from_str::<serde_json::Value>(t).ok())
+    else {
+        return;
+    };
+    let Some(obj) = value.as_object_mut() else {
+        return;
+    };
+    for key in DUMP_ONLY_KEYS {
+        obj.remove(*key);
+    }
+    event.text = if obj.values().all(|v| v.is_null()) {
+        None
+    } else {
+        Some(value.to_string())
+    };
+}
+
+/// Session and event IDs become path components; keep them to one safe segment.
+fn sanitize(component: &str) -> String {
+    let cleaned: String = component
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    match cleaned.trim_start_matches('.') {
+        "" => "_".to_string(),
+        s => s.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::EventType;
+    use tempfile::tempdir;
+
+    fn crash_event() -> Event {
+        Event {
+            id: "app-1-crash-42".to_string(),
+            session_id: "app-1".to_string(),
+            timestamp_ns: 1000,
+            thread_id: 7,
+            event_type: EventType::Crash,
+            pid: Some(1234),
+            signal: Some("access-violation".to_string()),
+            fault_address: Some("0x0".to_string()),
+            backtrace: Some(serde_json::json!([{"address": "0x1000", "name": "main"}])),
+            text: Some(
+                serde_json::json!({
+                    "frameMemory": "00ff",
+                    "frameBase": "0x7ff0",
+                    "globals": [{"name": "gEngineState", "address": "0x4010", "size": 4, "data": "03000000"}],
+                    "threads": [{"id": 7, "crashed": true}, {"id": 8, "backtrace": []}],
+                    "modules": [{"name": "app", "base": "0x1000", "size": 4096}],
+                })
+                .to_string(),
+            ),
+            ..Event::default()
+        }
+    }
+
+    #[test]
+    fn test_write_crash_dump_links_event_and_strips_text() {
+        let dir = tempdir().unwrap();
+        let mut event = crash_event();
+        let path = write_crash_dump(dir.path(), &mut event, None).unwrap();
+
+        assert_eq!(path, dir.path().join("app-1").join("app-1-crash-42.json"));
+        assert_eq!(
+            event.dump_path.as_deref(),
+            Some(path.to_string_lossy().as_ref())
+        );
+
+        let dump: serde_json::Value =
+            serde_json::from_slice(&std::fs::read(&path).unwrap()).unwrap();
+        assert_eq!(dump["signal"], "access-violation");
+        assert_eq!(dump["threads"].as_array().unwrap().len(), 2);
+        assert_eq!(dump["modules"][0]["name"], "app");
+        assert_eq!(dump["backtrace"][0]["name"], "main");
+        assert_eq!(dump["globals"][0]["data"], "03000000");
+
+        let text: serde_json::Value = serde_json::from_str(event.text.as_ref().unwrap()).unwrap();
+        assert_eq!(text["frameMemory"], "00ff");
+        // Globals stay on the event so debug_query can show them
+        assert_eq!(text["globals"][0]["name"], "gEngineState");
+        assert!(text.get("threads").is_none());
+        assert!(text.get("modules").is_none());
+    }
+
+    #[test]
+    fn test_text_dropped_when_only_dump_keys_present() {
+        let dir = tempdir().unwrap();
+        let mut event = crash_event();
+        event.text = Some(serde_json::json!({"frameMemory": null, "modules": []}).to_string());
+        write_crash_dump(dir.path(), &mut event, None).unwrap();
+        assert!(event.text.is_none());
+    }
+
+    fn symbols() -> DwarfHandle {
+        use crate::dwarf::{DwarfParser, FunctionInfo, LineEntry};
+        use std::collections::HashMap;
+        use std::sync::{Arc, Mutex};
+
+        let line = |address: u64, line: u32| LineEntry {
+            address,
+            file: "/proj/src/app.c".to_string(),
+            line,
+            column: 0,
+            is_statement: true,
+        };
+        let run = FunctionInfo {
+            name: "app::run".to_string(),
+            name_raw: None,
+            low_pc: 0x1000,
+            high_pc: 0x1040,
+            source_file: Some("/proj/src/app.c".to_string()),
+            line_number: Some(10),
+        };
+        DwarfHandle::ready(Arc::new(DwarfParser {
+            functions_by_addr: vec![(run.low_pc, run.high_pc)],
+            functions: vec![run],
+            functions_by_name: HashMap::new(),
+            variables: vec![],
+            variables_by_name: HashMap::new(),
+            struct_members: Mutex::new(HashMap::new()),
+            lazy_struct_info: HashMap::new(),
+            type_layouts: Mutex::new(HashMap::new()),
+            function_params: Mutex::new(HashMap::new()),
+            line_table: Mutex::new(Some(vec![
+                line(0x1000, 10),
+                line(0x1010, 11),
+                line(0x1030, 12),
+            ])),
+            image_base: 0x1000,
+            binary_path: None,
+            go_functions: Default::default(),
+            goid_offset: None,
+            swift: false,
+        }))
+    }
+
+    #[test]
+    fn test_symbolize_frames_maps_main_binary_frames_to_lines() {
+        let mut event = crash_event();
+        event.registers = Some(serde_json::json!({"rip": "0x10030"}));
+        event.backtrace = Some(serde_json::json!([
+            // Return address just past the call on line 11
+            {"address": "0x10015", "moduleName": "app", "name": null, "fileName": null, "lineNumber": null},
+            {"address": "0x7f0010", "moduleName": "libc.so.6", "name": "__libc_start_main"},
+        ]));
+        event.text = Some(
+            serde_json::json!({
+                "threads": [{"id": 8, "backtrace": [{"address": "0x10010", "name": "app::run"}]}],
+                "modules": [
+                    {"name": "libc.so.6", "path": "/lib/libc.so.6", "base": "0x7f0000", "size": 4096},
+                    {"name": "app", "path": "/bin/app", "base": "0x10000", "size": 4096},
+                ],
+            })
+            .to_string(),
+        );
+
+        symbolize_frames(&mut event, "/bin/app", &symbols());
+
+        let backtrace = event.backtrace.as_ref().unwrap();
+        assert_eq!(backtrace[0]["name"], "app::run");
+        assert_eq!(backtrace[0]["fileName"], "/proj/src/app.c");
+        assert_eq!(backtrace[0]["lineNumber"], 11);
+        assert!(backtrace[1].get("fileName").is_none());
+
+        let text: serde_json::Value = serde_json::from_str(event.text.as_ref().unwrap()).unwrap();
+        // The call before return address 0x10010 is still on line 10
+        assert_eq!(text["threads"][0]["backtrace"][0]["lineNumber"], 10);
+        assert_eq!(text["pcLocation"]["function"], "app::run");
+        assert_eq!(text["pcLocation"]["line"], 12);
+    }
+
+    #[test]
+    fn test_sanitize_path_components() {
+        assert_eq!(sanitize("app-2026-01-01h10"), "app-2026-01-01h10");
+        assert_eq!(sanitize("../etc/passwd"), "_etc_passwd");
+        assert_eq!(sanitize(".."), "_");
+    }
+}
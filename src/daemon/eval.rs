@@ -0,0 +1,677 @@
+//! Expression language for `debug_eval`: a gdb-`print` subset that is parsed
+//! and evaluated daemon-side.
+//!
+//! ```text
+//! expr    := or
+//! or      := and ("||" and)*
+//! and     := cmp ("&&" cmp)*
+//! cmp     := sum (("==" | "!=" | "<" | "<=" | ">" | ">=") sum)?
+//! sum     := product (("+" | "-") product)*
+//! product := unary (("*" | "/" | "%") unary)*
+//! unary   := ("-" | "!") unary | atom
+//! atom    := number | "true" | "false" | "(" expr ")"
+//!          | name "(" args ")"          call into the target
+//!          | name (("->" | ".") field)* DWARF variable / member path, or argN
+//! ```
+//!
+//! Evaluation needs no round trips of its own: variables are read in one
+//! batch beforehand and calls are made bottom-up (see `calls`), so
+//! `evaluate` only looks values up in an `Env`.
+
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::{Error, Result};
+
+/// Calls per expression; each one runs code in the target.
+pub const MAX_EVAL_CALLS: usize = 8;
+/// Arguments per call (all passed as 64-bit integers).
+pub const MAX_CALL_ARGS: usize = 6;
+const MAX_NESTING: usize = 32;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    Int(i64),
+    Float(f64),
+    Bool(bool),
+    /// Global, paused-frame local, member path (`gClock->counter`, `buf.len`)
+    /// or paused-frame argument (`arg0`)
+    Var(String),
+    Call(String, Vec<Expr>),
+    Unary(UnOp, Box<Expr>),
+    Binary(BinOp, Box<Expr>, Box<Expr>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnOp {
+    Neg,
+    Not,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BinOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Rem,
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    And,
+    Or,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Int(i64),
+    Float(f64),
+    Bool(bool),
+    /// Anything that isn't a scalar (structs, strings); only valid as the result
+    Other(serde_json::Value),
+}
+
+impl Value {
+    /// Convert a debug_read / captured-argument value. Pointers come back as
+    /// hex strings and count as integers.
+    pub fn from_json(value: &serde_json::Value) -> Self {
+        match value {
+            serde_json::Value::Bool(b) => Value::Bool(*b),
+            serde_json::Value::Number(n) => match n.as_i64() {
+                Some(i) => Value::Int(i),
+                None => match n.as_u64() {
+                    Some(u) => Value::Int(u as i64),
+                    None => Value::Float(n.as_f64().unwrap_or(f64::NAN)),
+                },
+            },
+            serde_json::Value::String(s) => parse_hex(s)
+                .map(|u| Value::Int(u as i64))
+                .unwrap_or_else(|| Value::Other(value.clone())),
+            other => Value::Other(other.clone()),
+        }
+    }
+
+    /// A scalar held in a register, as `size` bytes of `type_kind`.
+    pub fn from_bits(bits: u64, size: u8, type_kind: &crate::dwarf::TypeKind) -> Self {
+        use crate::dwarf::TypeKind;
+        match (type_kind, size) {
+            (TypeKind::Float, 4) => Value::Float(f32::from_bits(bits as u32) as f64),
+            (TypeKind::Float, _) => Value::Float(f64::from_bits(bits)),
+            (TypeKind::Integer { signed: true }, 1) => Value::Int(bits as i8 as i64),
+            (TypeKind::Integer { signed: true }, 2) => Value::Int(bits as i16 as i64),
+            (TypeKind::Integer { signed: true }, 4) => Value::Int(bits as i32 as i64),
+            (_, 1) => Value::Int(bits as u8 as i64),
+            (_, 2) => Value::Int(bits as u16 as i64),
+            (_, 4) => Value::Int(bits as u32 as i64),
+            _ => Value::Int(bits as i64),
+        }
+    }
+
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Value::Int(_) => "int",
+            Value::Float(_) => "float",
+            Value::Bool(_) => "bool",
+            Value::Other(_) => "value",
+        }
+    }
+
+    pub fn to_json(&self) -> serde_json::Value {
+        match self {
+            Value::Int(i) => serde_json::json!(i),
+            Value::Float(f) => serde_json::json!(f),
+            Value::Bool(b) => serde_json::json!(b),
+            Value::Other(v) => v.clone(),
+        }
+    }
+
+    pub fn as_int(&self) -> Result<i64> {
+        match self {
+            Value::Int(i) => Ok(*i),
+            Value::Bool(b) => Ok(*b as i64),
+            Value::Float(f) => Ok(*f as i64),
+            Value::Other(v) => Err(not_scalar(v)),
+        }
+    }
+
+    fn truthy(&self) -> Result<bool> {
+        match self {
+            Value::Int(i) => Ok(*i != 0),
+            Value::Float(f) => Ok(*f != 0.0),
+            Value::Bool(b) => Ok(*b),
+            Value::Other(v) => Err(not_scalar(v)),
+        }
+    }
+}
+
+fn not_scalar(v: &serde_json::Value) -> Error {
+    let mut shown = v.to_string();
+    if shown.len() > 60 {
+        shown.truncate(60);
+        shown.push_str("...");
+    }
+    Error::ValidationError(format!(
+        "{} is not a number; read a field instead: 'ptr->field' on a pointer, or 'local.field' on a paused-frame local",
+        shown
+    ))
+}
+
+fn parse_hex(s: &str) -> Option<u64> {
+    let digits = s.strip_prefix("0x").or_else(|| s.strip_prefix("0X"))?;
+    u64::from_str_radix(digits, 16).ok()
+}
+
+/// Values an expression's variables and calls resolved to.
+#[derive(Debug, Default)]
+pub struct Env {
+    pub vars: HashMap<String, Value>,
+    /// Keyed by `call_key`, so identical calls are made once
+    pub calls: HashMap<String, Value>,
+}
+
+pub fn call_key(name: &str, args: &[i64]) -> String {
+    let args: Vec<String> = args.iter().map(|a| a.to_string()).collect();
+    format!("{}({})", name, args.join(", "))
+}
+
+pub fn parse(src: &str) -> Result<Expr> {
+    let tokens = tokenize(src)?;
+    let mut parser = Parser {
+        tokens,
+        pos: 0,
+        depth: 0,
+    };
+    let expr = parser.expr()?;
+    if let Some(tok) = parser.peek() {
+        return Err(syntax(format!("unexpected '{}'", tok)));
+    }
+    if calls(&expr).len() > MAX_EVAL_CALLS {
+        return Err(syntax(format!(
+            "at most {} calls per expression",
+            MAX_EVAL_CALLS
+        )));
+    }
+    Ok(expr)
+}
+
+/// Variable names in the expression, deduplicated, in source order.
+pub fn variables(expr: &Expr) -> Vec<String> {
+    fn walk(expr: &Expr, out: &mut Vec<String>) {
+        match expr {
+            Expr::Var(name) => {
+                if !out.contains(name) {
+                    out.push(name.clone());
+                }
+            }
+            Expr::Call(_, args) => args.iter().for_each(|a| walk(a, out)),
+            Expr::Unary(_, e) => walk(e, out),
+            Expr::Binary(_, l, r) => {
+                walk(l, out);
+                walk(r, out);
+            }
+            Expr::Int(_) | Expr::Float(_) | Expr::Bool(_) => {}
+        }
+    }
+    let mut out = Vec::new();
+    walk(expr, &mut out);
+    out
+}
+
+/// Calls in the expression, innermost first: by the time a call comes up,
+/// every call in its arguments has a result in the `Env`.
+pub fn calls(expr: &Expr) -> Vec<(&str, &[Expr])> {
+    fn walk<'a>(expr: &'a Expr, out: &mut Vec<(&'a str, &'a [Expr])>) {
+        match expr {
+            Expr::Call(name, args) => {
+                args.iter().for_each(|a| walk(a, out));
+                out.push((name, args));
+            }
+            Expr::Unary(_, e) => walk(e, out),
+            Expr::Binary(_, l, r) => {
+                walk(l, out);
+                walk(r, out);
+            }
+            Expr::Var(_) | Expr::Int(_) | Expr::Float(_) | Expr::Bool(_) => {}
+        }
+    }
+    let mut out = Vec::new();
+    walk(expr, &mut out);
+    out
+}
+
+pub fn evaluate(expr: &Expr, env: &Env) -> Result<Value> {
+    match expr {
+        Expr::Int(i) => Ok(Value::Int(*i)),
+        Expr::Float(f) => Ok(Value::Float(*f)),
+        Expr::Bool(b) => Ok(Value::Bool(*b)),
+        Expr::Var(name) => env
+            .vars
+            .get(name)
+            .cloned()
+            .ok_or_else(|| Error::ValidationError(format!("'{}' has no value", name))),
+        Expr::Call(name, args) => {
+            let args = args
+                .iter()
+                .map(|a| evaluate(a, env)?.as_int())
+                .collect::<Result<Vec<_>>>()?;
+            let key = call_key(name, &args);
+            env.calls
+                .get(&key)
+                .cloned()
+                .ok_or_else(|| Error::ValidationError(format!("{} was not called", key)))
+        }
+        Expr::Unary(op, e) => {
+            let v = evaluate(e, env)?;
+            match op {
+                UnOp::Not => Ok(Value::Bool(!v.truthy()?)),
+                UnOp::Neg => match v {
+                    Value::Float(f) => Ok(Value::Float(-f)),
+                    other => Ok(Value::Int(other.as_int()?.wrapping_neg())),
+                },
+            }
+        }
+        Expr::Binary(BinOp::And, l, r) => Ok(Value::Bool(
+            evaluate(l, env)?.truthy()? && evaluate(r, env)?.truthy()?,
+        )),
+        Expr::Binary(BinOp::Or, l, r) => Ok(Value::Bool(
+            evaluate(l, env)?.truthy()? || evaluate(r, env)?.truthy()?,
+        )),
+        Expr::Binary(op, l, r) => binary(*op, evaluate(l, env)?, evaluate(r, env)?),
+    }
+}
+
+fn binary(op: BinOp, l: Value, r: Value) -> Result<Value> {
+    use BinOp::*;
+    // Integer arithmetic unless either side is a float, like C
+    if matches!(l, Value::Float(_)) || matches!(r, Value::Float(_)) {
+        let (a, b) = (as_float(&l)?, as_float(&r)?);
+        return Ok(match op {
+            Add => Value::Float(a + b),
+            Sub => Value::Float(a - b),
+            Mul => Value::Float(a * b),
+            Div => Value::Float(a / b),
+            Rem => Value::Float(a % b),
+            Eq => Value::Bool(a == b),
+            Ne => Value::Bool(a != b),
+            Lt => Value::Bool(a < b),
+            Le => Value::Bool(a <= b),
+            Gt => Value::Bool(a > b),
+            Ge => Value::Bool(a >= b),
+            And | Or => unreachable!("short-circuit ops are evaluated by the caller"),
+        });
+    }
+    let (a, b) = (l.as_int()?, r.as_int()?);
+    if matches!(op, Div | Rem) && b == 0 {
+        return Err(Error::ValidationError("division by zero".to_string()));
+    }
+    Ok(match op {
+        Add => Value::Int(a.wrapping_add(b)),
+        Sub => Value::Int(a.wrapping_sub(b)),
+        Mul => Value::Int(a.wrapping_mul(b)),
+        Div => Value::Int(a.wrapping_div(b)),
+        Rem => Value::Int(a.wrapping_rem(b)),
+        Eq => Value::Bool(a == b),
+        Ne => Value::Bool(a != b),
+        Lt => Value::Bool(a < b),
+        Le => Value::Bool(a <= b),
+        Gt => Value::Bool(a > b),
+        Ge => Value::Bool(a >= b),
+        And | Or => unreachable!("short-circuit ops are evaluated by the caller"),
+    })
+}
+
+fn as_float(v: &Value) -> Result<f64> {
+    match v {
+        Value::Float(f) => Ok(*f),
+        other => Ok(other.as_int()? as f64),
+    }
+}
+
+fn syntax(msg: String) -> Error {
+    Error::ValidationError(format!("debug_eval: {}", msg))
+}
+
+// ---------------------------------------------------------------------------
+// Lexer / parser
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Int(i64),
+    Float(f64),
+    Name(String),
+    Op(&'static str),
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::Int(i) => write!(f, "{}", i),
+            Token::Float(x) => write!(f, "{}", x),
+            Token::Name(n) => write!(f, "{}", n),
+            Token::Op(op) => write!(f, "{}", op),
+        }
+    }
+}
+
+// Longest first so "<=" wins over "<"
+const OPERATORS: &[&str] = &[
+    "->", ".", "==", "!=", "<=", ">=", "&&", "||", "+", "-", "*", "/", "%", "<", ">", "!", "(",
+    ")", ",",
+];
+
+fn tokenize(src: &str) -> Result<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut rest = src.trim_start();
+    while !rest.is_empty() {
+        let c = rest.chars().next().unwrap();
+        if c.is_ascii_digit() {
+            let len = rest
+                .find(|ch: char| !(ch.is_ascii_alphanumeric() || ch == '.'))
+                .unwrap_or(rest.len());
+            let lit = &rest[..len];
+            let token = if let Some(v) = parse_hex(lit) {
+                Token::Int(v as i64)
+            } else if let Ok(v) = lit.parse::<i64>() {
+                Token::Int(v)
+            } else if let Ok(v) = lit.parse::<f64>() {
+                Token::Float(v)
+            } else {
+                return Err(syntax(format!("bad number '{}'", lit)));
+            };
+            tokens.push(token);
+            rest = &rest[len..];
+        } else if c.is_ascii_alphabetic() || c == '_' {
+            // `::` belongs to the name (namespaced globals)
+            let mut len = 0;
+            let bytes = rest.as_bytes();
+            while len < bytes.len() {
+                let b = bytes[len];
+                if b.is_ascii_alphanumeric() || b == b'_' {
+                    len += 1;
+                } else if rest[len..].starts_with("::") {
+                    len += 2;
+                } else {
+                    break;
+                }
+            }
+            tokens.push(Token::Name(rest[..len].to_string()));
+            rest = &rest[len..];
+        } else if let Some(op) = OPERATORS.iter().find(|op| rest.starts_with(**op)) {
+            tokens.push(Token::Op(op));
+            rest = &rest[op.len()..];
+        } else {
+            return Err(syntax(format!("unexpected character '{}'", c)));
+        }
+        rest = rest.trim_start();
+    }
+    if tokens.is_empty() {
+        return Err(syntax("empty expression".to_string()));
+    }
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+    depth: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn eat(&mut self, op: &str) -> bool {
+        if matches!(self.peek(), Some(Token::Op(o)) if *o == op) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, op: &str) -> Result<()> {
+        if self.eat(op) {
+            Ok(())
+        } else {
+            Err(syntax(match self.peek() {
+                Some(tok) => format!("expected '{}', found '{}'", op, tok),
+                None => format!("expected '{}' at end of expression", op),
+            }))
+        }
+    }
+
+    fn binary_level(
+        &mut self,
+        ops: &[(&str, BinOp)],
+        next: fn(&mut Self) -> Result<Expr>,
+        repeat: bool,
+    ) -> Result<Expr> {
+        let mut lhs = next(self)?;
+        'outer: loop {
+            for (tok, op) in ops {
+                if self.eat(tok) {
+                    let rhs = next(self)?;
+                    lhs = Expr::Binary(*op, Box::new(lhs), Box::new(rhs));
+                    if repeat {
+                        continue 'outer;
+                    }
+                    return Ok(lhs);
+                }
+            }
+            return Ok(lhs);
+        }
+    }
+
+    fn expr(&mut self) -> Result<Expr> {
+        self.depth += 1;
+        if self.depth > MAX_NESTING {
+            return Err(syntax("expression nested too deeply".to_string()));
+        }
+        let expr = self.binary_level(&[("||", BinOp::Or)], Self::and, true);
+        self.depth -= 1;
+        expr
+    }
+
+    fn and(&mut self) -> Result<Expr> {
+        self.binary_level(&[("&&", BinOp::And)], Self::cmp, true)
+    }
+
+    fn cmp(&mut self) -> Result<Expr> {
+        self.binary_level(
+            &[
+                ("==", BinOp::Eq),
+                ("!=", BinOp::Ne),
+                ("<=", BinOp::Le),
+                (">=", BinOp::Ge),
+                ("<", BinOp::Lt),
+                (">", BinOp::Gt),
+            ],
+            Self::sum,
+            false,
+        )
+    }
+
+    fn sum(&mut self) -> Result<Expr> {
+        self.binary_level(&[("+", BinOp::Add), ("-", BinOp::Sub)], Self::product, true)
+    }
+
+    fn product(&mut self) -> Result<Expr> {
+        self.binary_level(
+            &[("*", BinOp::Mul), ("/", BinOp::Div), ("%", BinOp::Rem)],
+            Self::unary,
+            true,
+        )
+    }
+
+    fn unary(&mut self) -> Result<Expr> {
+        if self.eat("-") {
+            return Ok(Expr::Unary(UnOp::Neg, Box::new(self.unary()?)));
+        }
+        if self.eat("!") {
+            return Ok(Expr::Unary(UnOp::Not, Box::new(self.unary()?)));
+        }
+        self.atom()
+    }
+
+    fn atom(&mut self) -> Result<Expr> {
+        let Some(tok) = self.peek().cloned() else {
+            return Err(syntax("expression ends early".to_string()));
+        };
+        self.pos += 1;
+        match tok {
+            Token::Int(i) => Ok(Expr::Int(i)),
+            Token::Float(f) => Ok(Expr::Float(f)),
+            Token::Op("(") => {
+                let inner = self.expr()?;
+                self.expect(")")?;
+                Ok(inner)
+            }
+            Token::Name(name) if name == "true" => Ok(Expr::Bool(true)),
+            Token::Name(name) if name == "false" => Ok(Expr::Bool(false)),
+            Token::Name(name) => {
+                if self.eat("(") {
+                    let mut args = Vec::new();
+                    if !self.eat(")") {
+                        loop {
+                            args.push(self.expr()?);
+                            if self.eat(")") {
+                                break;
+                            }
+                            self.expect(",")?;
+                        }
+                    }
+                    if args.len() > MAX_CALL_ARGS {
+                        return Err(syntax(format!(
+                            "{}() takes at most {} arguments here",
+                            name, MAX_CALL_ARGS
+                        )));
+                    }
+                    return Ok(Expr::Call(name, args));
+                }
+                let mut path = name;
+                while let Some(sep) = ["->", "."].into_iter().find(|sep| self.eat(sep)) {
+                    match self.peek().cloned() {
+                        Some(Token::Name(field)) => {
+                            self.pos += 1;
+                            path.push_str(sep);
+                            path.push_str(&field);
+                        }
+                        _ => {
+                            return Err(syntax(format!("expected a field after '{}{}'", path, sep)))
+                        }
+                    }
+                }
+                Ok(Expr::Var(path))
+            }
+            Token::Op(op) => Err(syntax(format!("unexpected '{}'", op))),
+        }
+    }
+}
+
+/// `arg0`, `arg1`, ...: arguments captured when the thread paused.
+pub fn argument_index(name: &str) -> Option<u32> {
+    name.strip_prefix("arg")?.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn eval(src: &str, vars: &[(&str, Value)]) -> Result<Value> {
+        let env = Env {
+            vars: vars
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.clone()))
+                .collect(),
+            calls: HashMap::new(),
+        };
+        evaluate(&parse(src)?, &env)
+    }
+
+    #[test]
+    fn test_eval_arithmetic_and_precedence() {
+        assert_eq!(eval("1 + 2 * 3", &[]).unwrap(), Value::Int(7));
+        assert_eq!(eval("(1 + 2) * 3", &[]).unwrap(), Value::Int(9));
+        assert_eq!(eval("10 - 4 - 3", &[]).unwrap(), Value::Int(3));
+        assert_eq!(eval("0x10 % 5", &[]).unwrap(), Value::Int(1));
+        assert_eq!(eval("7 / 2.0", &[]).unwrap(), Value::Float(3.5));
+        assert_eq!(eval("-3 < 2 && !false", &[]).unwrap(), Value::Bool(true));
+        assert!(eval("1 / 0", &[]).is_err());
+    }
+
+    #[test]
+    fn test_eval_variables() {
+        let vars = [
+            ("gTempo", Value::Float(120.0)),
+            ("gClock->counter", Value::Int(48000)),
+            ("audio::gFrames", Value::Int(512)),
+        ];
+        assert_eq!(
+            eval("gClock->counter / audio::gFrames", &vars).unwrap(),
+            Value::Int(93)
+        );
+        assert_eq!(eval("gTempo >= 120", &vars).unwrap(), Value::Bool(true));
+        assert!(eval("missing + 1", &vars).is_err());
+
+        let expr = parse("gClock->counter + gClock->counter * gTempo").unwrap();
+        assert_eq!(variables(&expr), vec!["gClock->counter", "gTempo"]);
+
+        let expr = parse("buf.len > chain->next->params->gain").unwrap();
+        assert_eq!(
+            variables(&expr),
+            vec!["buf.len", "chain->next->params->gain"]
+        );
+    }
+
+    #[test]
+    fn test_eval_calls_innermost_first() {
+        let expr = parse("size(lookup(arg0, 2)) > 0").unwrap();
+        let names: Vec<&str> = calls(&expr).iter().map(|(n, _)| *n).collect();
+        assert_eq!(names, vec!["lookup", "size"]);
+
+        let mut env = Env::default();
+        env.vars.insert("arg0".to_string(), Value::Int(7));
+        env.calls
+            .insert(call_key("lookup", &[7, 2]), Value::Int(0x1000));
+        env.calls.insert(call_key("size", &[0x1000]), Value::Int(3));
+        assert_eq!(evaluate(&expr, &env).unwrap(), Value::Bool(true));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(parse("").is_err());
+        assert!(parse("1 +").is_err());
+        assert!(parse("a->").is_err());
+        assert!(parse("a.").is_err());
+        assert!(parse("a.1").is_err());
+        assert!(parse("f(1, 2").is_err());
+        assert!(parse("a = 1").is_err());
+        assert!(parse("f(1,2,3,4,5,6,7)").is_err());
+    }
+
+    #[test]
+    fn test_value_from_json() {
+        assert_eq!(
+            Value::from_json(&serde_json::json!("0x7ff0")),
+            Value::Int(0x7ff0)
+        );
+        assert_eq!(Value::from_json(&serde_json::json!(1.5)), Value::Float(1.5));
+        assert!(matches!(
+            Value::from_json(&serde_json::json!({"x": 1})),
+            Value::Other(_)
+        ));
+        let signed = crate::dwarf::TypeKind::Integer { signed: true };
+        assert_eq!(Value::from_bits(0xffff_ffff, 4, &signed), Value::Int(-1));
+        assert_eq!(
+            Value::from_bits(1.5f32.to_bits() as u64, 4, &crate::dwarf::TypeKind::Float),
+            Value::Float(1.5)
+        );
+        assert_eq!(argument_index("arg2"), Some(2));
+        assert_eq!(argument_index("argv"), None);
+    }
+}
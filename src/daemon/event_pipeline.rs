@@ -0,0 +1,275 @@
+//! The path from event producers to a session's database writer.
+//!
+//! Producers (agent messages, output and pipe readers) push into a bounded
+//! queue through `EventSender`. The agent's message thread must never block,
+//! so a full queue drops the event and counts it. The writer drains the
+//! queue in batches sized to the backlog: small while tracing is light, to
+//! keep events queryable within milliseconds, and up to `MAX_BATCH` under
+//! load, where one transaction per batch is what keeps up. It also runs the
+//! WAL checkpoints itself, between batches instead of inside whichever commit
+//! crosses the threshold: every `CHECKPOINT_EVERY` events, and when idle once
+//! `CHECKPOINT_INTERVAL` has passed with anything written. SQLite's automatic
+//! checkpoint stays on at a high threshold as a backstop.
+//!
+//! `WriterStats` is shared by both ends and shown in debug_session status.
+
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use tokio::sync::mpsc;
+use tokio::sync::mpsc::error::{SendError, TrySendError};
+
+use crate::db::Event;
+
+/// Events a session's writer queue holds before producers start dropping.
+pub const QUEUE_CAPACITY: usize = 10_000;
+/// Batch size while the writer keeps up.
+pub const MIN_BATCH: usize = 100;
+/// Largest batch; bounds how long one transaction holds the write lock.
+pub const MAX_BATCH: usize = 5_000;
+/// Events written between passive WAL checkpoints.
+pub const CHECKPOINT_EVERY: u64 = 50_000;
+/// Longest an idle writer leaves written events without a checkpoint.
+pub const CHECKPOINT_INTERVAL: std::time::Duration = std::time::Duration::from_secs(5);
+
+/// Counters for one session's writer.
+#[derive(Debug, Default)]
+pub struct WriterStats {
+    capacity: usize,
+    queued: AtomicU64,
+    dropped: AtomicU64,
+    flushed: AtomicU64,
+    batches: AtomicU64,
+    batch_size: AtomicUsize,
+    checkpoints: AtomicU64,
+    since_checkpoint: AtomicU64,
+}
+
+impl WriterStats {
+    /// Events sent but not yet taken by the writer.
+    pub fn queued(&self) -> u64 {
+        self.queued.load(Ordering::Relaxed)
+    }
+
+    /// The writer took `count` events off the queue.
+    pub fn record_received(&self, count: usize) {
+        let _ = self
+            .queued
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |q| {
+                Some(q.saturating_sub(count as u64))
+            });
+    }
+
+    /// A batch of `count` events was committed. Returns whether a WAL
+    /// checkpoint is due.
+    pub fn record_flush(&self, count: usize) -> bool {
+        self.flushed.fetch_add(count as u64, Ordering::Relaxed);
+        self.batches.fetch_add(1, Ordering::Relaxed);
+        let since = self
+            .since_checkpoint
+            .fetch_add(count as u64, Ordering::Relaxed)
+            + count as u64;
+        if since >= CHECKPOINT_EVERY {
+            self.since_checkpoint.store(0, Ordering::Relaxed);
+            return true;
+        }
+        false
+    }
+
+    /// Whether anything was committed since the last checkpoint; resets the
+    /// count, for the writer's timed checkpoint.
+    pub fn take_uncheckpointed(&self) -> bool {
+        self.since_checkpoint.swap(0, Ordering::Relaxed) > 0
+    }
+
+    pub fn record_checkpoint(&self) {
+        self.checkpoints.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// A batch of `count` events could not be written and was given up on.
+    pub fn record_dropped(&self, count: usize) {
+        self.dropped.fetch_add(count as u64, Ordering::Relaxed);
+    }
+
+    pub fn set_batch_size(&self, size: usize) {
+        self.batch_size.store(size, Ordering::Relaxed);
+    }
+
+    pub fn snapshot(&self) -> crate::mcp::WriterStatus {
+        crate::mcp::WriterStatus {
+            queued: self.queued(),
+            capacity: self.capacity,
+            dropped: self.dropped.load(Ordering::Relaxed),
+            flushed: self.flushed.load(Ordering::Relaxed),
+            batches: self.batches.load(Ordering::Relaxed),
+            batch_size: self.batch_size.load(Ordering::Relaxed),
+            checkpoints: self.checkpoints.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Sending half of a writer queue; counts what it queues and drops.
+#[derive(Debug, Clone)]
+pub struct EventSender {
+    tx: mpsc::Sender<Event>,
+    stats: Arc<WriterStats>,
+}
+
+impl EventSender {
+    /// Queue without waiting; a full or closed queue drops the event.
+    pub fn try_send(&self, event: Event) -> Result<(), TrySendError<Event>> {
+        let result = self.tx.try_send(event);
+        self.count(result.is_ok());
+        result
+    }
+
+    /// Queue, waiting for room. Only for threads that may block.
+    pub fn blocking_send(&self, event: Event) -> Result<(), SendError<Event>> {
+        let result = self.tx.blocking_send(event);
+        self.count(result.is_ok());
+        result
+    }
+
+    /// The writer has shut down.
+    pub fn is_closed(&self) -> bool {
+        self.tx.is_closed()
+    }
+
+    pub fn stats(&self) -> Arc<WriterStats> {
+        Arc::clone(&self.stats)
+    }
+
+    fn count(&self, sent: bool) {
+        let counter = if sent {
+            &self.stats.queued
+        } else {
+            &self.stats.dropped
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// A writer queue holding up to `capacity` events.
+pub fn event_channel(capacity: usize) -> (EventSender, mpsc::Receiver<Event>) {
+    let (tx, rx) = mpsc::channel(capacity);
+    let stats = Arc::new(WriterStats {
+        capacity,
+        ..WriterStats::default()
+    });
+    stats.set_batch_size(MIN_BATCH);
+    (EventSender { tx, stats }, rx)
+}
+
+/// Batch size that follows the backlog: doubles while the queue holds more
+/// than a batch, halves once it is mostly drained.
+#[derive(Debug)]
+pub struct BatchSizer {
+    size: usize,
+}
+
+impl Default for BatchSizer {
+    fn default() -> Self {
+        Self { size: MIN_BATCH }
+    }
+}
+
+impl BatchSizer {
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Resize for `backlog` queued events; returns the new size.
+    pub fn adjust(&mut self, backlog: u64) -> usize {
+        let backlog = backlog as usize;
+        if backlog > self.size {
+            self.size = (self.size * 2).min(MAX_BATCH);
+        } else if backlog < self.size / 4 {
+            self.size = (self.size / 2).max(MIN_BATCH);
+        }
+        self.size
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(id: &str) -> Event {
+        Event {
+            id: id.to_string(),
+            session_id: "s1".to_string(),
+            ..Event::default()
+        }
+    }
+
+    #[test]
+    fn test_sender_counts_queued_and_dropped() {
+        let (tx, mut rx) = event_channel(2);
+        let stats = tx.stats();
+        assert!(tx.try_send(event("a")).is_ok());
+        assert!(tx.try_send(event("b")).is_ok());
+        assert!(tx.try_send(event("c")).is_err());
+
+        let snapshot = stats.snapshot();
+        assert_eq!(snapshot.queued, 2);
+        assert_eq!(snapshot.capacity, 2);
+        assert_eq!(snapshot.dropped, 1);
+
+        rx.try_recv().unwrap();
+        stats.record_received(1);
+        assert_eq!(stats.queued(), 1);
+
+        drop(rx);
+        assert!(tx.is_closed());
+        assert!(tx.try_send(event("d")).is_err());
+        assert_eq!(stats.snapshot().dropped, 2);
+    }
+
+    #[test]
+    fn test_checkpoint_due_every_n_events() {
+        let stats = WriterStats::default();
+        let batch = (CHECKPOINT_EVERY / 4) as usize;
+        assert!(!stats.record_flush(batch));
+        assert!(!stats.record_flush(batch));
+        assert!(!stats.record_flush(batch));
+        assert!(stats.record_flush(batch));
+        assert!(!stats.record_flush(batch));
+
+        let snapshot = stats.snapshot();
+        assert_eq!(snapshot.flushed, CHECKPOINT_EVERY + batch as u64);
+        assert_eq!(snapshot.batches, 5);
+
+        // The timed checkpoint only fires after something was written
+        assert!(stats.take_uncheckpointed());
+        assert!(!stats.take_uncheckpointed());
+    }
+
+    #[test]
+    fn test_failed_batches_count_as_dropped() {
+        let stats = WriterStats::default();
+        stats.record_dropped(100);
+        assert_eq!(stats.snapshot().dropped, 100);
+        assert_eq!(stats.snapshot().flushed, 0);
+    }
+
+    #[test]
+    fn test_batch_size_follows_backlog() {
+        let mut sizer = BatchSizer::default();
+        assert_eq!(sizer.adjust(0), MIN_BATCH);
+        assert_eq!(sizer.adjust(1_000), 2 * MIN_BATCH);
+        assert_eq!(sizer.adjust(1_000), 4 * MIN_BATCH);
+        for _ in 0..10 {
+            sizer.adjust(QUEUE_CAPACITY as u64);
+        }
+        assert_eq!(sizer.size(), MAX_BATCH);
+
+        // Backlog between a quarter batch and a batch holds the size
+        assert_eq!(sizer.adjust(2_000), MAX_BATCH);
+        sizer.adjust(0);
+        assert_eq!(sizer.size(), MAX_BATCH / 2);
+        for _ in 0..10 {
+            sizer.adjust(0);
+        }
+        assert_eq!(sizer.size(), MIN_BATCH);
+    }
+}
@@ -0,0 +1,804 @@
+//! Optional REST listener for non-MCP clients (CI scripts, dashboards).
+//!
+//! Enabled by `"http.port"` in ~/.strobe/settings.json, or by starting the
+//! daemon with `strobe daemon --web [port]`, which also prints the dashboard
+//! URL with its token. Binds 127.0.0.1 only
+//! and requires `Authorization: Bearer <token>`, where the token is generated
+//! at daemon start into ~/.strobe/http.token (mode 0600) — a launch endpoint
+//! must not be reachable by any page a local browser happens to load.
+//!
+//! ```text
+//! GET  /dashboard         → read-only web UI over the endpoints below
+//! GET  /v1/sessions       → { "sessions": [{ sessionId, pid, status, eventCount, ... }] }
+//! GET  /v1/tests          → { "testRuns": [{ testRunId, status, passed, failed, ... }] }
+//! GET  /v1/tools          → { "tools": [{ name, description, inputSchema }] }
+//! POST /v1/tools/{name}   body: tool arguments → tool result JSON
+//! GET|POST /v1/sessions/{id}/events
+//!                         body: debug_query filters → text/event-stream
+//! ```
+//! Errors are `{ "error": { code, message } }` with a matching HTTP status.
+//!
+//! The events endpoint pushes a session's events as Server-Sent Events as
+//! the writer stores them: `id:` is the event's seq, `event:` its eventType
+//! and `data:` the event as debug_query returns it. It starts after
+//! `Last-Event-ID` or the body's `afterSeq`, else with new events only, and
+//! ends with an `end` event once the session stops.
+//!
+//! The dashboard page itself is served without the token (it holds no data);
+//! it takes the token from its URL fragment, `/dashboard#token=...`, and sends
+//! it with every API call.
+//! All HTTP requests share one connection identity, so patterns staged with
+//! debug_trace before a launch apply to the next HTTP launch, and sessions
+//! outlive the request that started them (stop them with debug_session).
+
+use super::server::Daemon;
+use crate::mcp::{DebugQueryRequest, ErrorCode, McpError};
+#[cfg(unix)]
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+
+const DAEMON_USAGE: &str = "Usage: strobe daemon [--web [port]]";
+/// Port for `--web` when none is given.
+pub(super) const DEFAULT_WEB_PORT: u16 = 7878;
+
+/// Connection id every HTTP request runs under.
+const HTTP_CONNECTION_ID: &str = "http";
+
+const DASHBOARD_HTML: &str = include_str!("dashboard.html");
+
+/// Tools that answer with MCP content blocks (screenshots) rather than JSON.
+const MCP_ONLY_TOOLS: &[&str] = &["debug_ui", "debug_ui_action"];
+
+const MAX_HEADER_LINES: usize = 64;
+const MAX_HEADER_LINE_BYTES: usize = 8 * 1024;
+const MAX_BODY_BYTES: usize = 4 * 1024 * 1024;
+/// Deadline for receiving a whole request; tool execution is not bounded.
+pub(super) const READ_TIMEOUT: Duration = Duration::from_secs(10);
+/// Events read per query while an event stream catches up.
+const STREAM_BATCH: u32 = 500;
+/// Comment line sent on idle streams so proxies and clients keep them open.
+const STREAM_KEEPALIVE: Duration = Duration::from_secs(15);
+
+#[derive(Debug, PartialEq)]
+enum Route<'a> {
+    Dashboard,
+    ListSessions,
+    ListTestRuns,
+    ListTools,
+    CallTool(&'a str),
+    StreamEvents(&'a str),
+    NotFound,
+    MethodNotAllowed,
+}
+
+fn route<'a>(method: &str, path: &'a str) -> Route<'a> {
+    let path = path.split('?').next().unwrap_or(path);
+    match path.trim_end_matches('/') {
+        "" | "/dashboard" if method == "GET" => Route::Dashboard,
+        "/v1/sessions" if method == "GET" => Route::ListSessions,
+        "/v1/sessions" => Route::MethodNotAllowed,
+        "/v1/tests" if method == "GET" => Route::ListTestRuns,
+        "/v1/tests" => Route::MethodNotAllowed,
+        "/v1/tools" if method == "GET" => Route::ListTools,
+        "/v1/tools" => Route::MethodNotAllowed,
+        p => {
+            if let Some(name) = p.strip_prefix("/v1/tools/") {
+                if name.is_empty() || name.contains('/') {
+                    Route::NotFound
+                } else if method == "POST" {
+                    Route::CallTool(name)
+                } else {
+                    Route::MethodNotAllowed
+                }
+            } else if let Some(id) = p
+                .strip_prefix("/v1/sessions/")
+                .and_then(|rest| rest.strip_suffix("/events"))
+            {
+                if id.is_empty() || id.contains('/') {
+                    Route::NotFound
+                } else if method == "GET" || method == "POST" {
+                    Route::StreamEvents(id)
+                } else {
+                    Route::MethodNotAllowed
+                }
+            } else {
+                Route::NotFound
+            }
+        }
+    }
+}
+
+pub(super) fn status_for(code: ErrorCode) -> u16 {
+    match code {
+        ErrorCode::ValidationError | ErrorCode::InvalidPattern => 400,
+        ErrorCode::SessionNotFound | ErrorCode::TestRunNotFound => 404,
+        ErrorCode::SessionExists => 409,
+        _ => 500,
+    }
+}
+
+fn reason(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        408 => "Request Timeout",
+        409 => "Conflict",
+        413 => "Payload Too Large",
+        _ => "Internal Server Error",
+    }
+}
+
+pub(super) struct Request {
+    pub(super) method: String,
+    pub(super) path: String,
+    pub(super) authorization: Option<String>,
+    /// `Last-Event-ID`, sent by SSE clients when reconnecting
+    pub(super) last_event_id: Option<String>,
+    pub(super) body: Vec<u8>,
+}
+
+pub(super) struct Response {
+    pub(super) status: u16,
+    pub(super) body: serde_json::Value,
+}
+
+impl Response {
+    pub(super) fn ok(body: serde_json::Value) -> Self {
+        Self { status: 200, body }
+    }
+
+    pub(super) fn error(status: u16, code: ErrorCode, message: impl Into<String>) -> Self {
+        let error = McpError {
+            code,
+            message: message.into(),
+        };
+        Self {
+            status,
+            body: serde_json::json!({ "error": error }),
+        }
+    }
+}
+
+impl From<crate::Error> for Response {
+    fn from(err: crate::Error) -> Self {
+        let error: McpError = err.into();
+        Self::error(status_for(error.code), error.code, error.message)
+    }
+}
+
+/// `strobe daemon` arguments: the port to serve the web UI on, if `--web`
+/// was given.
+pub(super) fn parse_daemon_args(args: &[String]) -> crate::Result<Option<u16>> {
+    let usage = || crate::Error::ValidationError(DAEMON_USAGE.to_string());
+    match args {
+        [] => Ok(None),
+        [flag] if flag == "--web" => Ok(Some(DEFAULT_WEB_PORT)),
+        [flag, port] if flag == "--web" => port
+            .parse::<u16>()
+            .ok()
+            .filter(|&p| p >= 1024)
+            .map(Some)
+            .ok_or_else(|| {
+                crate::Error::ValidationError(format!("Invalid port '{}' (1024..65535)", port))
+            }),
+        _ => Err(usage()),
+    }
+}
+
+/// Load the bearer token, creating it on first use. The file is created
+/// private, so the token is never readable by others even briefly.
+pub(super) fn load_or_create_token(path: &Path) -> crate::Result<String> {
+    use std::io::Write;
+
+    if let Ok(existing) = std::fs::read_to_string(path) {
+        let existing = existing.trim();
+        if !existing.is_empty() {
+            return Ok(existing.to_string());
+        }
+        // Empty leftover from an interrupted write
+        std::fs::remove_file(path)?;
+    }
+    let token = uuid::Uuid::new_v4().simple().to_string();
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    // On Windows the profile directory's ACL already keeps it private
+    #[cfg(unix)]
+    options.mode(0o600);
+    options.open(path)?.write_all(token.as_bytes())?;
+    Ok(token)
+}
+
+/// Constant-time comparison so the token can't be recovered byte by byte.
+pub(super) fn token_matches(authorization: Option<&str>, token: &str) -> bool {
+    let Some(given) = authorization.and_then(|h| h.strip_prefix("Bearer ")) else {
+        return false;
+    };
+    let given = given.trim().as_bytes();
+    given.len() == token.len()
+        && given
+            .iter()
+            .zip(token.as_bytes())
+            .fold(0u8, |acc, (a, b)| acc | (a ^ b))
+            == 0
+}
+
+pub(super) async fn serve(daemon: Arc<Daemon>, listener: TcpListener, token: Arc<String>) {
+    loop {
+        let stream = match listener.accept().await {
+            Ok((stream, _)) => stream,
+            Err(e) => {
+                tracing::error!("HTTP accept error: {}", e);
+                tokio::time::sleep(Duration::from_millis(100)).await;
+                continue;
+            }
+        };
+        let daemon = Arc::clone(&daemon);
+        let token = Arc::clone(&token);
+        tokio::spawn(async move {
+            if let Err(e) = handle_connection(&daemon, stream, &token).await {
+                tracing::debug!("HTTP connection error: {}", e);
+            }
+        });
+    }
+}
+
+/// One request per connection (`Connection: close`).
+async fn handle_connection(daemon: &Daemon, stream: TcpStream, token: &str) -> crate::Result<()> {
+    let (reader, mut writer) = stream.into_split();
+    let mut reader = BufReader::new(reader);
+
+    let response = match tokio::time::timeout(READ_TIMEOUT, read_request(&mut reader)).await {
+        Err(_) => Response::error(
+            408,
+            ErrorCode::ValidationError,
+            "Request not received in time",
+        ),
+        Ok(Err(response)) => response,
+        Ok(Ok(request)) => {
+            let route = route(&request.method, &request.path);
+            if route == Route::Dashboard {
+                return write_page(&mut writer, DASHBOARD_HTML).await;
+            }
+            if !token_matches(request.authorization.as_deref(), token) {
+                Response::error(
+                    401,
+                    ErrorCode::ValidationError,
+                    "Missing or wrong bearer token (see ~/.strobe/http.token)",
+                )
+            } else if let Route::StreamEvents(session_id) = route {
+                daemon.touch_activity().await;
+                match open_stream(daemon, &request, session_id) {
+                    Ok(stream) => return stream_events(daemon, &mut writer, stream).await,
+                    Err(response) => response,
+                }
+            } else {
+                handle_request(daemon, &request).await
+            }
+        }
+    };
+
+    write_response(&mut writer, &response).await
+}
+
+/// Write a JSON response and close the connection.
+pub(super) async fn write_response<W>(writer: &mut W, response: &Response) -> crate::Result<()>
+where
+    W: AsyncWrite + Unpin,
+{
+    let body = serde_json::to_vec(&response.body)?;
+    let head = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        response.status,
+        reason(response.status),
+        body.len()
+    );
+    writer.write_all(head.as_bytes()).await?;
+    writer.write_all(&body).await?;
+    writer.shutdown().await?;
+    Ok(())
+}
+
+pub(super) async fn read_request<R>(reader: &mut R) -> std::result::Result<Request, Response>
+where
+    R: tokio::io::AsyncBufRead + Unpin,
+{
+    let bad = |message: &str| Response::error(400, ErrorCode::ValidationError, message);
+
+    let request_line = read_line(reader)
+        .await
+        .ok_or_else(|| bad("Malformed request line"))?;
+    let mut parts = request_line.split_whitespace();
+    let (Some(method), Some(path)) = (parts.next(), parts.next()) else {
+        return Err(bad("Malformed request line"));
+    };
+    let (method, path) = (method.to_string(), path.to_string());
+
+    let mut content_length = 0usize;
+    let mut authorization = None;
+    let mut last_event_id = None;
+    for _ in 0..=MAX_HEADER_LINES {
+        let line = read_line(reader)
+            .await
+            .ok_or_else(|| bad("Malformed headers"))?;
+        if line.is_empty() {
+            let mut body = vec![0u8; content_length];
+            reader
+                .read_exact(&mut body)
+                .await
+                .map_err(|_| bad("Body shorter than Content-Length"))?;
+            return Ok(Request {
+                method,
+                path,
+                authorization,
+                last_event_id,
+                body,
+            });
+        }
+        let Some((name, value)) = line.split_once(':') else {
+            return Err(bad("Malformed header"));
+        };
+        let value = value.trim();
+        if name.eq_ignore_ascii_case("content-length") {
+            content_length = value.parse().map_err(|_| bad("Invalid Content-Length"))?;
+            if content_length > MAX_BODY_BYTES {
+                return Err(Response::error(
+                    413,
+                    ErrorCode::ValidationError,
+                    format!("Body exceeds {} bytes", MAX_BODY_BYTES),
+                ));
+            }
+        } else if name.eq_ignore_ascii_case("transfer-encoding") {
+            return Err(bad("Chunked bodies are not supported; send Content-Length"));
+        } else if name.eq_ignore_ascii_case("authorization") {
+            authorization = Some(value.to_string());
+        } else if name.eq_ignore_ascii_case("last-event-id") {
+            last_event_id = Some(value.to_string());
+        }
+    }
+    Err(bad("Too many headers"))
+}
+
+/// Read one CRLF- or LF-terminated line, bounded by MAX_HEADER_LINE_BYTES.
+async fn read_line<R>(reader: &mut R) -> Option<String>
+where
+    R: tokio::io::AsyncBufRead + Unpin,
+{
+    let mut line = Vec::new();
+    let mut limited = (&mut *reader).take(MAX_HEADER_LINE_BYTES as u64);
+    limited.read_until(b'\n', &mut line).await.ok()?;
+    if line.last() != Some(&b'\n') {
+        return None;
+    }
+    let line = String::from_utf8(line).ok()?;
+    Some(line.trim_end_matches(['\r', '\n']).to_string())
+}
+
+async fn handle_request(daemon: &Daemon, request: &Request) -> Response {
+    daemon.touch_activity().await;
+    match route(&request.method, &request.path) {
+        Route::ListSessions => match daemon.session_overview() {
+            Ok(sessions) => Response::ok(serde_json::json!({ "sessions": sessions })),
+            Err(e) => e.into(),
+        },
+        Route::ListTestRuns => {
+            Response::ok(serde_json::json!({ "testRuns": daemon.test_run_overview().await }))
+        }
+        Route::ListTools => {
+            let tools: Vec<_> = daemon
+                .tool_definitions()
+                .into_iter()
+                .filter(|t| !MCP_ONLY_TOOLS.contains(&t.name.as_str()))
+                .collect();
+            Response::ok(serde_json::json!({ "tools": tools }))
+        }
+        Route::CallTool(name) if MCP_ONLY_TOOLS.contains(&name) => Response::error(
+            404,
+            ErrorCode::ValidationError,
+            format!(
+                "{} returns MCP content and is only available over MCP",
+                name
+            ),
+        ),
+        Route::CallTool(name) => {
+            let args = match parse_body(&request.body) {
+                Ok(args) => args,
+                Err(response) => return response,
+            };
+            if !daemon.has_tool(name) {
+                return Response::error(
+                    404,
+                    ErrorCode::ValidationError,
+                    format!("Unknown tool: {}", name),
+                );
+            }
+            match daemon.call_tool(name, &args, HTTP_CONNECTION_ID).await {
+                Ok(value) => Response::ok(value),
+                Err(e) => e.into(),
+            }
+        }
+        // Served by handle_connection before requests are dispatched here
+        Route::Dashboard | Route::StreamEvents(_) => {
+            unreachable!("dashboard and event streams are not routed here")
+        }
+        Route::NotFound => Response::error(
+            404,
+            ErrorCode::ValidationError,
+            "Not found. Use GET /v1/tools, POST /v1/tools/{name}, GET /v1/sessions, GET /v1/sessions/{id}/events, GET /v1/tests or GET /dashboard",
+        ),
+        Route::MethodNotAllowed => Response::error(
+            405,
+            ErrorCode::ValidationError,
+            "Use GET /v1/tools, POST /v1/tools/{name}, GET /v1/sessions, GET /v1/sessions/{id}/events, GET /v1/tests or GET /dashboard",
+        ),
+    }
+}
+
+/// Static page. The CSP keeps it to inline code and same-origin requests.
+async fn write_page<W>(writer: &mut W, html: &str) -> crate::Result<()>
+where
+    W: AsyncWrite + Unpin,
+{
+    let head = format!(
+        "HTTP/1.1 200 OK\r\nContent-Type: text/html; charset=utf-8\r\nContent-Length: {}\r\nCache-Control: no-store\r\nContent-Security-Policy: default-src 'none'; script-src 'unsafe-inline'; style-src 'unsafe-inline'; connect-src 'self'; frame-ancestors 'none'\r\nConnection: close\r\n\r\n",
+        html.len()
+    );
+    writer.write_all(head.as_bytes()).await?;
+    writer.write_all(html.as_bytes()).await?;
+    writer.shutdown().await?;
+    Ok(())
+}
+
+/// A request body as a JSON object; empty means no arguments.
+pub(super) fn parse_body(body: &[u8]) -> std::result::Result<serde_json::Value, Response> {
+    if body.iter().all(u8::is_ascii_whitespace) {
+        return Ok(serde_json::json!({}));
+    }
+    match serde_json::from_slice::<serde_json::Value>(body) {
+        Ok(args @ serde_json::Value::Object(_)) => Ok(args),
+        Ok(_) => Err(Response::error(
+            400,
+            ErrorCode::ValidationError,
+            "Body must be a JSON object of tool arguments",
+        )),
+        Err(e) => Err(Response::error(
+            400,
+            ErrorCode::ValidationError,
+            format!("Invalid JSON body: {}", e),
+        )),
+    }
+}
+
+struct EventStream {
+    filter: DebugQueryRequest,
+    signal: Arc<tokio::sync::Notify>,
+    /// Seq of the last event sent
+    cursor: i64,
+}
+
+/// Resolve an events request into its filter and starting cursor, or the
+/// error to answer with before any stream headers go out.
+fn open_stream(
+    daemon: &Daemon,
+    request: &Request,
+    session_id: &str,
+) -> std::result::Result<EventStream, Response> {
+    let mut args = parse_body(&request.body)?;
+    args["sessionId"] = serde_json::json!(session_id);
+    let filter: DebugQueryRequest = serde_json::from_value(args).map_err(|e| {
+        Response::error(
+            400,
+            ErrorCode::ValidationError,
+            format!("Invalid filter: {}", e),
+        )
+    })?;
+    let signal = daemon.open_event_stream(&filter)?;
+    let resume = match request.last_event_id.as_deref() {
+        Some(id) => Some(id.parse::<i64>().map_err(|_| {
+            Response::error(400, ErrorCode::ValidationError, "Invalid Last-Event-ID")
+        })?),
+        None => filter.after_seq,
+    };
+    let cursor = match resume {
+        Some(seq) => seq,
+        None => daemon.latest_event_seq(session_id)?,
+    };
+    Ok(EventStream {
+        filter,
+        signal,
+        cursor,
+    })
+}
+
+/// Send the session's events as they are stored until it stops or the
+/// client goes away.
+async fn stream_events<W>(
+    daemon: &Daemon,
+    writer: &mut W,
+    mut stream: EventStream,
+) -> crate::Result<()>
+where
+    W: AsyncWrite + Unpin,
+{
+    writer
+        .write_all(b"HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nCache-Control: no-cache\r\nConnection: close\r\n\r\n")
+        .await?;
+    let session_id = stream.filter.session_id.clone();
+    loop {
+        // Register for the next store before reading, so events written
+        // between the read and the wait still wake us.
+        let notified = stream.signal.notified();
+        tokio::pin!(notified);
+        notified.as_mut().enable();
+
+        // Checked before reading: everything stored before the session
+        // stopped is sent before the end event.
+        let running = daemon.session_running(&session_id);
+        let batch = daemon.event_stream_batch(&stream.filter, stream.cursor, STREAM_BATCH)?;
+        let caught_up = (batch.len() as u32) < STREAM_BATCH;
+        if !batch.is_empty() {
+            let mut frames = String::new();
+            for (seq, event) in &batch {
+                let kind = event["eventType"].as_str().unwrap_or("event");
+                frames.push_str(&format!(
+                    "id: {}\nevent: {}\ndata: {}\n\n",
+                    seq, kind, event
+                ));
+                stream.cursor = *seq;
+            }
+            writer.write_all(frames.as_bytes()).await?;
+        }
+        if !caught_up {
+            continue;
+        }
+        if !running {
+            let end = serde_json::json!({ "sessionId": session_id, "lastSeq": stream.cursor });
+            writer
+                .write_all(format!("event: end\ndata: {}\n\n", end).as_bytes())
+                .await?;
+            break;
+        }
+        tokio::select! {
+            _ = &mut notified => {}
+            _ = tokio::time::sleep(STREAM_KEEPALIVE) => {
+                daemon.touch_activity().await;
+                writer.write_all(b": keepalive\n\n").await?;
+            }
+        }
+    }
+    writer.shutdown().await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_route() {
+        assert_eq!(route("GET", "/v1/tools"), Route::ListTools);
+        assert_eq!(route("GET", "/v1/tools/?x=1"), Route::ListTools);
+        assert_eq!(
+            route("POST", "/v1/tools/debug_query"),
+            Route::CallTool("debug_query")
+        );
+        assert_eq!(
+            route("GET", "/v1/tools/debug_query"),
+            Route::MethodNotAllowed
+        );
+        assert_eq!(route("POST", "/v1/tools/a/b"), Route::NotFound);
+        assert_eq!(
+            route("GET", "/v1/sessions/app-1/events"),
+            Route::StreamEvents("app-1")
+        );
+        assert_eq!(
+            route("POST", "/v1/sessions/app-1/events/"),
+            Route::StreamEvents("app-1")
+        );
+        assert_eq!(
+            route("DELETE", "/v1/sessions/app-1/events"),
+            Route::MethodNotAllowed
+        );
+        assert_eq!(route("GET", "/v1/sessions//events"), Route::NotFound);
+        assert_eq!(route("GET", "/"), Route::Dashboard);
+        assert_eq!(route("GET", "/dashboard"), Route::Dashboard);
+        assert_eq!(route("POST", "/dashboard"), Route::NotFound);
+        assert_eq!(route("GET", "/v1/sessions"), Route::ListSessions);
+        assert_eq!(route("POST", "/v1/sessions"), Route::MethodNotAllowed);
+        assert_eq!(route("GET", "/v1/tests"), Route::ListTestRuns);
+        assert_eq!(route("POST", "/v1/tests"), Route::MethodNotAllowed);
+        assert_eq!(route("GET", "/v2"), Route::NotFound);
+    }
+
+    #[test]
+    fn test_parse_daemon_args() {
+        let args = |list: &[&str]| list.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        assert_eq!(parse_daemon_args(&args(&[])).unwrap(), None);
+        assert_eq!(
+            parse_daemon_args(&args(&["--web"])).unwrap(),
+            Some(DEFAULT_WEB_PORT)
+        );
+        assert_eq!(
+            parse_daemon_args(&args(&["--web", "9090"])).unwrap(),
+            Some(9090)
+        );
+        assert!(parse_daemon_args(&args(&["--web", "80"])).is_err());
+        assert!(parse_daemon_args(&args(&["--web", "abc"])).is_err());
+        assert!(parse_daemon_args(&args(&["--port", "9090"])).is_err());
+    }
+
+    #[test]
+    fn test_token_matches() {
+        assert!(token_matches(Some("Bearer abc123"), "abc123"));
+        assert!(!token_matches(Some("Bearer abc124"), "abc123"));
+        assert!(!token_matches(Some("Bearer abc"), "abc123"));
+        assert!(!token_matches(Some("abc123"), "abc123"));
+        assert!(!token_matches(None, "abc123"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_token_file_is_private_and_stable() {
+        use std::os::unix::fs::PermissionsExt;
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("http.token");
+        let token = load_or_create_token(&path).unwrap();
+        assert_eq!(token.len(), 32);
+        let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+        assert_eq!(load_or_create_token(&path).unwrap(), token);
+
+        // An empty file is replaced, still private
+        std::fs::write(&path, "").unwrap();
+        let replaced = load_or_create_token(&path).unwrap();
+        assert_ne!(replaced, token);
+        let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+
+    #[tokio::test]
+    async fn test_read_request() {
+        let raw = b"POST /v1/tools/debug_query HTTP/1.1\r\nHost: x\r\nauthorization: Bearer t\r\nContent-Length: 2\r\n\r\n{}";
+        let mut reader = BufReader::new(&raw[..]);
+        let request = read_request(&mut reader).await.ok().unwrap();
+        assert_eq!(request.method, "POST");
+        assert_eq!(request.path, "/v1/tools/debug_query");
+        assert_eq!(request.authorization.as_deref(), Some("Bearer t"));
+        assert_eq!(request.body, b"{}");
+        assert_eq!(request.last_event_id, None);
+
+        let raw = b"GET /v1/sessions/s/events HTTP/1.1\r\nLast-Event-ID: 42\r\n\r\n";
+        let mut reader = BufReader::new(&raw[..]);
+        let request = read_request(&mut reader).await.ok().unwrap();
+        assert_eq!(request.last_event_id.as_deref(), Some("42"));
+        assert!(request.body.is_empty());
+
+        let raw = b"POST /v1/tools/x HTTP/1.1\r\nContent-Length: 99999999\r\n\r\n";
+        let mut reader = BufReader::new(&raw[..]);
+        assert_eq!(read_request(&mut reader).await.err().unwrap().status, 413);
+
+        let raw = b"POST /v1/tools/x HTTP/1.1\r\nContent-Length: 10\r\n\r\n{}";
+        let mut reader = BufReader::new(&raw[..]);
+        assert_eq!(read_request(&mut reader).await.err().unwrap().status, 400);
+    }
+
+    #[tokio::test]
+    async fn test_stream_resumes_and_ends_with_session() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("strobe.db");
+        let daemon = Daemon::embedded(&path).unwrap();
+        let db = crate::db::Database::open(&path).unwrap();
+        db.create_session("s1", "/bin/app", "/home", 1).unwrap();
+        let events: Vec<_> = ["main", "parse", "main"]
+            .iter()
+            .enumerate()
+            .map(|(i, function)| crate::db::Event {
+                id: format!("e{}", i),
+                session_id: "s1".into(),
+                timestamp_ns: i as i64,
+                thread_id: 1,
+                event_type: crate::db::EventType::FunctionEnter,
+                function_name: function.to_string(),
+                ..Default::default()
+            })
+            .collect();
+        db.insert_events_batch(&events).unwrap();
+        db.update_session_status("s1", crate::db::SessionStatus::Exited)
+            .unwrap();
+
+        let request = |last_event_id: Option<&str>, body: &[u8]| Request {
+            method: "POST".into(),
+            path: "/v1/sessions/s1/events".into(),
+            authorization: None,
+            last_event_id: last_event_id.map(String::from),
+            body: body.to_vec(),
+        };
+
+        // Resume after seq 1, keeping only `main`: just e2, then the end event.
+        let stream = open_stream(
+            &daemon,
+            &request(Some("1"), br#"{"function":{"equals":"main"}}"#),
+            "s1",
+        )
+        .ok()
+        .unwrap();
+        let mut out = Vec::new();
+        stream_events(&daemon, &mut out, stream).await.unwrap();
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.starts_with("HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\n"));
+        let (_, body) = out.split_once("\r\n\r\n").unwrap();
+        let frames: Vec<&str> = body.trim_end().split("\n\n").collect();
+        assert_eq!(frames.len(), 2, "{}", body);
+        assert!(frames[0].starts_with("id: 3\nevent: function_enter\ndata: {"));
+        assert!(frames[0].contains(r#""id":"e2""#));
+        assert_eq!(
+            frames[1],
+            r#"event: end
+data: {"lastSeq":3,"sessionId":"s1"}"#
+        );
+
+        // Without a cursor only new events are sent.
+        let stream = open_stream(&daemon, &request(None, b""), "s1")
+            .ok()
+            .unwrap();
+        assert_eq!(stream.cursor, 3);
+
+        let status = |r: std::result::Result<EventStream, Response>| r.err().unwrap().status;
+        assert_eq!(
+            status(open_stream(&daemon, &request(None, b""), "nope")),
+            404
+        );
+        assert_eq!(
+            status(open_stream(&daemon, &request(Some("x"), b""), "s1")),
+            400
+        );
+        assert_eq!(
+            status(open_stream(
+                &daemon,
+                &request(None, br#"{"timeFrom":"-5s"}"#),
+                "s1"
+            )),
+            400
+        );
+    }
+
+    #[tokio::test]
+    async fn test_dashboard_page_and_session_overview() {
+        let mut out = Vec::new();
+        write_page(&mut out, DASHBOARD_HTML).await.unwrap();
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.starts_with("HTTP/1.1 200 OK\r\nContent-Type: text/html"));
+        assert!(out.contains("connect-src 'self'"));
+        assert!(out.ends_with("</html>\n"));
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("strobe.db");
+        let daemon = Daemon::embedded(&path).unwrap();
+        let db = crate::db::Database::open(&path).unwrap();
+        db.create_session("live", "/bin/app", "/home", 1).unwrap();
+        db.create_session("done", "/bin/app", "/home", 2).unwrap();
+        db.update_session_status("done", crate::db::SessionStatus::Exited)
+            .unwrap();
+        db.insert_event(&crate::db::Event {
+            id: "e1".into(),
+            session_id: "live".into(),
+            event_type: crate::db::EventType::Stdout,
+            text: Some("hi\n".into()),
+            ..Default::default()
+        })
+        .unwrap();
+
+        // Exited sessions that were not retained are gone from the list
+        let sessions = daemon.session_overview().unwrap();
+        assert_eq!(sessions.len(), 1);
+        assert_eq!(sessions[0]["sessionId"], "live");
+        assert_eq!(sessions[0]["status"], "running");
+        assert_eq!(sessions[0]["eventCount"], 1);
+    }
+}
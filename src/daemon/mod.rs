@@ -1,8 +1,23 @@
+mod call_order;
+mod child_follow;
+mod crash_dump;
+mod eval;
+mod event_pipeline;
+mod http;
+mod output_triggers;
 mod server;
 mod session_manager;
+mod settings_reload;
+mod share;
+mod structured_logs;
+pub mod transport;
 
+pub use event_pipeline::{event_channel, EventSender, WriterStats};
 pub use server::Daemon;
-pub use session_manager::{ActiveWatchState, PauseInfo, SessionManager};
+pub use session_manager::{
+    ActiveWatchState, LogpointHits, OutputChannels, PauseInfo, SessionManager,
+};
+pub use share::serve_session;
 
 #[cfg(test)]
 mod tests {
@@ -0,0 +1,357 @@
+//! Launch-time output triggers (`debug_launch({ onOutput })`).
+//!
+//! The event writer scans each stdout/stderr event for the session's trigger
+//! strings. A match is stored as an `output_trigger` event right after the
+//! output that caused it, then the trigger's action runs. The daemon sees a
+//! line only after the process wrote it, so `pause` freezes the process just
+//! past that point rather than on the exact instruction — a breakpoint is the
+//! tool for that.
+
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+
+use super::ActiveWatchState;
+use crate::db::{Database, Event, EventType};
+use crate::frida_collector::FridaSpawner;
+use crate::mcp::{OutputTrigger, OutputTriggerAction};
+
+/// Per-session trigger matcher. Output arrives in arbitrary chunks, so the end
+/// of each stream is carried over to catch matches split across two events.
+pub struct OutputTriggerSet {
+    triggers: Vec<OutputTrigger>,
+    /// Bytes kept from the previous chunk: longest pattern minus one
+    carry: usize,
+    /// Stream (stdout/stderr, pid) -> tail of the last chunk
+    tails: HashMap<(&'static str, Option<u32>), String>,
+    fired: u64,
+}
+
+impl OutputTriggerSet {
+    pub fn new(triggers: Vec<OutputTrigger>) -> Self {
+        let carry = triggers
+            .iter()
+            .map(|t| t.pattern.len().saturating_sub(1))
+            .max()
+            .unwrap_or(0);
+        Self {
+            triggers,
+            carry,
+            tails: HashMap::new(),
+            fired: 0,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.triggers.is_empty()
+    }
+
+    /// Match an event against every trigger. Returns one marker event per
+    /// trigger that fired, paired with the action to run.
+    pub fn scan(&mut self, event: &Event) -> Vec<(OutputTriggerAction, Event)> {
+        if !matches!(event.event_type, EventType::Stdout | EventType::Stderr) {
+            return Vec::new();
+        }
+        let Some(text) = event.text.as_deref() else {
+            return Vec::new();
+        };
+
+        let key = (event.event_type.as_str(), event.pid);
+        let tail = self.tails.remove(&key).unwrap_or_default();
+        let haystack = format!("{}{}", tail, text);
+
+        let mut fired = Vec::new();
+        for trigger in &self.triggers {
+            // Only count matches that end in the new chunk; the tail alone was
+            // already scanned with the previous event.
+            let hit = haystack
+                .match_indices(trigger.pattern.as_str())
+                .any(|(pos, m)| pos + m.len() > tail.len());
+            if hit {
+                self.fired += 1;
+                fired.push((trigger.action, marker_event(event, trigger, self.fired)));
+            }
+        }
+
+        let mut start = haystack.len().saturating_sub(self.carry);
+        while !haystack.is_char_boundary(start) {
+            start += 1;
+        }
+        self.tails.insert(key, haystack[start..].to_string());
+        fired
+    }
+}
+
+/// What the trigger actions need from the session manager, cloned into the
+/// event writer. Actions run on their own task so a slow agent read never
+/// stalls event ingestion.
+#[derive(Clone)]
+pub struct TriggerActions {
+    pub db: Database,
+    pub spawner: Arc<tokio::sync::RwLock<Option<FridaSpawner>>>,
+    pub watches: Arc<RwLock<HashMap<String, Vec<ActiveWatchState>>>>,
+    /// Pids stopped by a `pause` trigger, per session
+    pub stopped: Arc<RwLock<HashMap<String, Vec<u32>>>>,
+}
+
+impl TriggerActions {
+    /// Run the actions fired by one output event, in trigger order, so a
+    /// snapshot listed before a pause reads state before the process stops.
+    pub async fn run(self, fired: Vec<(OutputTriggerAction, Event)>) {
+        for (action, marker) in fired {
+            match action {
+                OutputTriggerAction::Annotate => {}
+                OutputTriggerAction::SnapshotWatches => {
+                    if let Err(e) = self.snapshot_watches(&marker).await {
+                        tracing::warn!(
+                            "Output trigger {}: watch snapshot failed: {}",
+                            marker.id,
+                            e
+                        );
+                    }
+                }
+                OutputTriggerAction::Pause => self.pause(&marker),
+            }
+        }
+    }
+
+    async fn snapshot_watches(&self, marker: &Event) -> crate::Result<()> {
+        let watches = self
+            .watches
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(&marker.session_id)
+            .cloned()
+            .unwrap_or_default();
+        if watches.is_empty() {
+            tracing::info!(
+                "Output trigger {}: no active watches to snapshot",
+                marker.id
+            );
+            return Ok(());
+        }
+        let message = serde_json::json!({
+            "type": "read_memory",
+            "recipes": watch_recipes(&watches),
+        });
+
+        let response = {
+            let guard = self.spawner.read().await;
+            let spawner = guard
+                .as_ref()
+                .ok_or_else(|| crate::Error::Frida("No Frida spawner available".to_string()))?;
+            spawner
+                .read_memory(&marker.session_id, serde_json::to_string(&message)?)
+                .await?
+        };
+
+        self.db.insert_event(&Event {
+            id: format!("{}-snap", marker.id),
+            session_id: marker.session_id.clone(),
+            timestamp_ns: marker.timestamp_ns,
+            thread_id: marker.thread_id,
+            event_type: EventType::VariableSnapshot,
+            arguments: Some(snapshot_data(&response)),
+            pid: marker.pid,
+            ..Event::default()
+        })
+    }
+
+    fn pause(&self, marker: &Event) {
+        let pid = marker.pid.or_else(|| {
+            self.db
+                .get_session(&marker.session_id)
+                .ok()
+                .flatten()
+                .map(|s| s.pid)
+                .filter(|&p| p != 0)
+        });
+        let Some(pid) = pid else {
+            tracing::warn!("Output trigger {}: no pid to pause", marker.id);
+            return;
+        };
+        if signal_pids(&[pid], crate::process::suspend).is_empty() {
+            return;
+        }
+        tracing::info!("Output trigger {}: stopped pid {}", marker.id, pid);
+        let mut stopped = self.stopped.write().unwrap_or_else(|e| e.into_inner());
+        let pids = stopped.entry(marker.session_id.clone()).or_default();
+        if !pids.contains(&pid) {
+            pids.push(pid);
+        }
+    }
+}
+
+fn action_name(action: OutputTriggerAction) -> &'static str {
+    match action {
+        OutputTriggerAction::Pause => "pause",
+        OutputTriggerAction::SnapshotWatches => "snapshot-watches",
+        OutputTriggerAction::Annotate => "annotate",
+    }
+}
+
+fn marker_event(source: &Event, trigger: &OutputTrigger, n: u64) -> Event {
+    Event {
+        id: format!("{}-trigger-{}", source.session_id, n),
+        session_id: source.session_id.clone(),
+        timestamp_ns: source.timestamp_ns,
+        thread_id: source.thread_id,
+        event_type: EventType::OutputTrigger,
+        text: source.text.clone(),
+        arguments: Some(serde_json::json!({
+            "match": trigger.pattern,
+            "action": action_name(trigger.action),
+            "sourceEventId": source.id,
+        })),
+        pid: source.pid,
+        ..Event::default()
+    }
+}
+
+/// `read_memory` recipes for the session's active watches.
+pub fn watch_recipes(watches: &[ActiveWatchState]) -> Vec<serde_json::Value> {
+    watches
+        .iter()
+        .map(|w| {
+            serde_json::json!({
+                "label": w.label,
+                "address": format!("0x{:x}", w.address),
+                "size": w.size,
+                "typeKind": w.type_kind_str,
+                "derefDepth": w.deref_depth,
+                "derefOffset": w.deref_offset,
+                "noSlide": w.no_slide,
+            })
+        })
+        .collect()
+}
+
+/// Variable-snapshot payload (label -> value), in the same shape the agent
+/// uses for polled reads.
+pub fn snapshot_data(read_response: &serde_json::Value) -> serde_json::Value {
+    let mut data = serde_json::Map::new();
+    for result in read_response
+        .get("results")
+        .and_then(|r| r.as_array())
+        .into_iter()
+        .flatten()
+    {
+        let label = result.get("label").and_then(|l| l.as_str()).unwrap_or("?");
+        let value = if let Some(err) = result.get("error").and_then(|e| e.as_str()) {
+            serde_json::json!(format!("<error: {}>", err))
+        } else if let Some(fields) = result.get("fields") {
+            fields.clone()
+        } else {
+            result
+                .get("value")
+                .cloned()
+                .unwrap_or(serde_json::Value::Null)
+        };
+        data.insert(label.to_string(), value);
+    }
+    serde_json::Value::Object(data)
+}
+
+/// Apply `op` (`process::suspend` or `process::resume`) to each pid,
+/// returning the pids it succeeded for.
+pub fn signal_pids(pids: &[u32], op: fn(u32) -> std::io::Result<()>) -> Vec<u32> {
+    pids.iter()
+        .copied()
+        .filter(|&pid| match op(pid) {
+            Ok(()) => true,
+            Err(e) => {
+                tracing::warn!("Failed to signal pid {}: {}", pid, e);
+                false
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn trigger(pattern: &str, action: OutputTriggerAction) -> OutputTrigger {
+        OutputTrigger {
+            pattern: pattern.to_string(),
+            action,
+        }
+    }
+
+    fn output(id: &str, event_type: EventType, text: &str) -> Event {
+        Event {
+            id: id.to_string(),
+            session_id: "s1".to_string(),
+            timestamp_ns: 42,
+            thread_id: 1,
+            event_type,
+            text: Some(text.to_string()),
+            pid: Some(100),
+            ..Event::default()
+        }
+    }
+
+    #[test]
+    fn test_scan_emits_marker_per_matching_trigger() {
+        let mut set = OutputTriggerSet::new(vec![
+            trigger("Segmentation fault imminent", OutputTriggerAction::Pause),
+            trigger("fault", OutputTriggerAction::Annotate),
+            trigger("never", OutputTriggerAction::SnapshotWatches),
+        ]);
+        let fired = set.scan(&output(
+            "o1",
+            EventType::Stderr,
+            "warn: Segmentation fault imminent\n",
+        ));
+        assert_eq!(fired.len(), 2);
+        assert_eq!(fired[0].0, OutputTriggerAction::Pause);
+
+        let marker = &fired[0].1;
+        assert_eq!(marker.event_type, EventType::OutputTrigger);
+        assert_eq!(marker.id, "s1-trigger-1");
+        assert_eq!(marker.pid, Some(100));
+        let args = marker.arguments.as_ref().unwrap();
+        assert_eq!(args["action"], "pause");
+        assert_eq!(args["sourceEventId"], "o1");
+        assert_eq!(fired[1].1.id, "s1-trigger-2");
+    }
+
+    #[test]
+    fn test_scan_matches_across_chunks_without_refiring() {
+        let mut set = OutputTriggerSet::new(vec![trigger("READY", OutputTriggerAction::Annotate)]);
+        assert!(set
+            .scan(&output("o1", EventType::Stdout, "server RE"))
+            .is_empty());
+        assert_eq!(set.scan(&output("o2", EventType::Stdout, "ADY\n")).len(), 1);
+        // The carried tail still contains "ADY" but no new match ends here
+        assert!(set
+            .scan(&output("o3", EventType::Stdout, "ok\n"))
+            .is_empty());
+        // Streams are independent: stderr does not continue stdout's tail
+        set.scan(&output("o4", EventType::Stdout, "RE"));
+        assert!(set.scan(&output("o5", EventType::Stderr, "ADY")).is_empty());
+    }
+
+    #[test]
+    fn test_scan_ignores_non_output_events() {
+        let mut set = OutputTriggerSet::new(vec![trigger("x", OutputTriggerAction::Annotate)]);
+        let mut event = output("o1", EventType::Logpoint, "x");
+        assert!(set.scan(&event).is_empty());
+        event.event_type = EventType::Stdout;
+        event.text = Some("ünïcödé x".to_string());
+        assert_eq!(set.scan(&event).len(), 1);
+    }
+
+    #[test]
+    fn test_snapshot_data_shapes_read_results() {
+        let data = snapshot_data(&serde_json::json!({
+            "results": [
+                { "label": "counter", "value": 7 },
+                { "label": "cfg", "fields": { "mode": 2 } },
+                { "label": "bad", "error": "access violation" }
+            ]
+        }));
+        assert_eq!(data["counter"], 7);
+        assert_eq!(data["cfg"]["mode"], 2);
+        assert_eq!(data["bad"], "<error: access violation>");
+    }
+}
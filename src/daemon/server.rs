@@ -2,12 +2,10 @@ use super::SessionManager;
 use crate::mcp::*;
 use crate::Result;
 use std::collections::{HashMap, HashSet};
-use std::os::unix::io::AsRawFd;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::time::Duration;
-use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
-use tokio::net::{UnixListener, UnixStream};
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader};
 use tokio::sync::{mpsc, RwLock};
 use tokio::time::Instant;
 
@@ -45,8 +43,8 @@ pub struct Daemon {
     test_runs: Arc<tokio::sync::RwLock<HashMap<String, crate::test::TestRun>>>,
     /// Signaled by idle_timeout_loop to tell the accept loop to exit
     shutdown_signal: Arc<tokio::sync::Notify>,
-    /// Vision sidecar for UI element detection
-    vision_sidecar: Arc<std::sync::Mutex<crate::ui::vision::VisionSidecar>>,
+    /// Vision backend for UI element detection, per "vision.backend"
+    vision: Arc<std::sync::Mutex<crate::ui::vision::VisionPipeline>>,
     /// Per-connection out-of-band senders. Tool handlers use these to emit
     /// notifications/progress (MCP 2025-06-18) on long-running operations
     /// without blocking the synchronous request/response loop.
@@ -54,6 +52,421 @@ pub struct Daemon {
 }
 
 fn format_event(event: &crate::db::Event, verbose: bool) -> serde_json::Value {
+    let mut value = format_event_fields(event, verbose);
+    if let Some(seq) = event.seq {
+        value["seq"] = serde_json::json!(seq);
+    }
+    if let Some(ref test_name) = event.test_name {
+        value["testName"] = serde_json::json!(test_name);
+    }
+    if let Some(ref call_id) = event.call_id {
+        value["callId"] = serde_json::json!(call_id);
+    }
+    value
+}
+
+/// One completed call for the `paired` query format: the function_exit with
+/// the arguments of its function_enter. Without a stored enter (evicted, or
+/// traced before call ids) the arguments and enter time are left out.
+fn format_paired_call(
+    exit: &crate::db::Event,
+    enter: Option<&crate::db::Event>,
+) -> serde_json::Value {
+    let mut value = serde_json::json!({
+        "callId": exit.call_id,
+        "function": exit.function_name,
+        "sourceFile": exit.source_file,
+        "line": exit.line_number,
+        "threadId": exit.thread_id,
+        "enterTimestampNs": enter.map(|e| e.timestamp_ns),
+        "exitTimestampNs": exit.timestamp_ns,
+        "duration_ns": exit.duration_ns,
+        "arguments": enter.and_then(|e| e.arguments.clone()),
+        "returnValue": exit.return_value,
+    });
+    if let Some(pid) = exit.pid {
+        value["pid"] = serde_json::json!(pid);
+    }
+    if let Some(ref exception) = exit.exception_type {
+        value["exceptionType"] = serde_json::json!(exception);
+    }
+    if let Some(seq) = exit.seq {
+        value["seq"] = serde_json::json!(seq);
+    }
+    value
+}
+
+/// Keep only the `select`ed fields of a formatted event. Dotted paths walk into
+/// nested objects and arrays; each hit is keyed by its full path, and paths
+/// the event doesn't have are left out.
+fn select_event_fields(event: &serde_json::Value, paths: &[String]) -> serde_json::Value {
+    let mut selected = serde_json::Map::new();
+    for path in paths {
+        let found = path
+            .split('.')
+            .try_fold(event, |value, segment| match value {
+                serde_json::Value::Object(map) => map.get(segment),
+                serde_json::Value::Array(items) => items.get(segment.parse::<usize>().ok()?),
+                _ => None,
+            });
+        if let Some(value) = found.filter(|v| !v.is_null()) {
+            selected.insert(path.clone(), value.clone());
+        }
+    }
+    serde_json::Value::Object(selected)
+}
+
+/// A debug_query request's timeFrom/timeTo as absolute timestamps.
+fn resolve_time_bounds(
+    db: &crate::db::Database,
+    req: &DebugQueryRequest,
+) -> Result<(Option<i64>, Option<i64>)> {
+    // Resolve a time value: integer (absolute ns) or string ("-5s", "-1m", "-500ms")
+    fn resolve_time_value(value: &serde_json::Value, latest_ns: i64) -> Option<i64> {
+        match value {
+            serde_json::Value::Number(n) => n.as_i64(),
+            serde_json::Value::String(s) => {
+                let s = s.trim();
+                if !s.starts_with('-') {
+                    return s.parse::<i64>().ok();
+                }
+                let (num_str, multiplier) = if s.ends_with("ms") {
+                    (&s[1..s.len() - 2], 1_000_000i64)
+                } else if s.ends_with('s') {
+                    (&s[1..s.len() - 1], 1_000_000_000i64)
+                } else if s.ends_with('m') {
+                    (&s[1..s.len() - 1], 60_000_000_000i64)
+                } else {
+                    return None;
+                };
+                let num: i64 = num_str.parse().ok()?;
+                Some(latest_ns - num * multiplier)
+            }
+            _ => None,
+        }
+    }
+
+    // Relative values count back from the session's latest event
+    let latest_ns = if req.time_from.is_some() || req.time_to.is_some() {
+        db.get_latest_timestamp(&req.session_id)?
+    } else {
+        0
+    };
+    Ok((
+        req.time_from
+            .as_ref()
+            .and_then(|v| resolve_time_value(v, latest_ns)),
+        req.time_to
+            .as_ref()
+            .and_then(|v| resolve_time_value(v, latest_ns)),
+    ))
+}
+
+/// debug_query against the event store alone: filters, paging, cursors and
+/// the crash summary, without `pids` (that needs the live session). Shared by
+/// the tool and `strobe serve`, which runs without a daemon. `filters` is the
+/// request as sent, minus sessionId, for the slow-query log.
+pub(crate) fn query_stored_events(
+    db: &crate::db::Database,
+    req: &DebugQueryRequest,
+    filters: serde_json::Value,
+    timeout: Duration,
+) -> Result<DebugQueryResponse> {
+    let limit = req.limit.unwrap_or(50).min(500);
+    let offset = req.offset.unwrap_or(0);
+    let (timestamp_from_ns, timestamp_to_ns) = resolve_time_bounds(db, req)?;
+    let log_fields = req.log_field_filters();
+
+    let events = db.query_events_guarded(&req.session_id, timeout, |q| {
+        let mut q = apply_query_filters(q, req, &log_fields, timestamp_from_ns, timestamp_to_ns);
+        if let Some(after) = req.after_event_id {
+            q.after_rowid = Some(after);
+        }
+        q.after_seq = req.after_seq;
+        q.limit(limit).offset(offset)
+    })?;
+
+    // Count with same filters (except limit/offset) for accurate totalCount.
+    // Skipped once the main query has already used up the timeout.
+    let count = db.count_filtered_events_guarded(
+        &req.session_id,
+        timeout.saturating_sub(events.elapsed),
+        |q| {
+            let mut q =
+                apply_query_filters(q, req, &log_fields, timestamp_from_ns, timestamp_to_ns);
+            if let Some(after) = req.after_event_id {
+                q.after_rowid = Some(after);
+            }
+            q.after_seq = req.after_seq;
+            q
+        },
+    )?;
+
+    let timed_out = events.timed_out || count.timed_out;
+    db.record_query_timing(
+        &req.session_id,
+        filters,
+        events.elapsed + count.elapsed,
+        timed_out,
+    );
+
+    let events = events.value;
+    let (total_count, has_more) = match count.value {
+        Some(total) => (total, (offset as u64 + events.len() as u64) < total),
+        // Count timed out: report what we know; a full page implies more
+        None => (
+            offset as u64 + events.len() as u64,
+            events.len() as u32 >= limit,
+        ),
+    };
+
+    // Convert to appropriate format. A selection picks from the verbose form
+    // so any field can be named.
+    let verbose = req.verbose.unwrap_or(false) || req.select.is_some();
+    let format_with_history = |e: &crate::db::Event, verbose: bool| {
+        let mut value = format_event(e, verbose);
+        if e.event_type == crate::db::EventType::Crash {
+            if let Some(history) = db.crash_history(e) {
+                value["history"] = serde_json::json!(history);
+            }
+        }
+        match req.select {
+            Some(ref paths) => select_event_fields(&value, paths),
+            None => value,
+        }
+    };
+    // Paired: the page holds exits; attach each one's enter
+    let event_values: Vec<serde_json::Value> = if req.format == Some(QueryFormat::Paired) {
+        let call_ids: Vec<String> = events.iter().filter_map(|e| e.call_id.clone()).collect();
+        let enters = db.call_enters(&req.session_id, &call_ids)?;
+        events
+            .iter()
+            .map(|exit| {
+                let enter = exit.call_id.as_ref().and_then(|id| enters.get(id));
+                let call = format_paired_call(exit, enter);
+                match req.select {
+                    Some(ref paths) => select_event_fields(&call, paths),
+                    None => call,
+                }
+            })
+            .collect()
+    } else {
+        events
+            .iter()
+            .map(|e| format_with_history(e, verbose))
+            .collect()
+    };
+
+    // Compute cursor fields
+    let last_event_id = events.iter().filter_map(|e| e.rowid).max();
+    let last_seq = events.iter().filter_map(|e| e.seq).max();
+
+    let events_dropped = if let Some(after) = req.after_seq {
+        let min_seq = db.min_seq_for_session(&req.session_id)?;
+        Some(match min_seq {
+            Some(min) => after + 1 < min,
+            None => after > 0,
+        })
+    } else if let Some(after) = req.after_event_id {
+        let min_rowid = db.min_rowid_for_session(&req.session_id)?;
+        Some(match min_rowid {
+            Some(min) => after + 1 < min,
+            None => after > 0, // All events evicted → dropped if cursor was set
+        })
+    } else {
+        None
+    };
+
+    // Always check for crash events regardless of eventType filter
+    let crash = if req.event_type.as_ref() != Some(&EventTypeFilter::Crash) {
+        let crash_events = db
+            .query_events(&req.session_id, |q| {
+                q.event_type(crate::db::EventType::Crash).limit(1)
+            })
+            .unwrap_or_default();
+        crash_events.first().map(|e| format_with_history(e, true))
+    } else {
+        None // Already included in the main events list
+    };
+
+    Ok(DebugQueryResponse {
+        events: event_values,
+        total_count,
+        has_more,
+        pids: None,
+        last_event_id,
+        last_seq,
+        events_dropped,
+        crash,
+        partial: timed_out.then_some(true),
+    })
+}
+
+/// debug_query with `distinct` or `groupBy`: the values of one field among
+/// the matching events, counted in SQL rather than paged through.
+pub(crate) fn query_event_groups(
+    db: &crate::db::Database,
+    req: &DebugQueryRequest,
+    filters: serde_json::Value,
+    timeout: Duration,
+) -> Result<crate::mcp::DebugQueryGroupsResponse> {
+    let Some((field, distinct)) = req.group_field() else {
+        return Err(crate::Error::ValidationError(
+            "distinct or groupBy is required".to_string(),
+        ));
+    };
+    let limit = req.limit.unwrap_or(50).min(500);
+    let offset = req.offset.unwrap_or(0);
+    let (timestamp_from_ns, timestamp_to_ns) = resolve_time_bounds(db, req)?;
+    let log_fields = req.log_field_filters();
+
+    let result =
+        db.group_events_guarded(&req.session_id, timeout, field.column(), distinct, |q| {
+            let mut q =
+                apply_query_filters(q, req, &log_fields, timestamp_from_ns, timestamp_to_ns);
+            if let Some(after) = req.after_event_id {
+                q.after_rowid = Some(after);
+            }
+            q.after_seq = req.after_seq;
+            q.limit(limit).offset(offset)
+        })?;
+    db.record_query_timing(&req.session_id, filters, result.elapsed, result.timed_out);
+
+    let crate::db::EventGroups { groups, total } = result.value;
+    let has_more = (offset as u64 + groups.len() as u64) < total;
+    let (values, groups) = if distinct {
+        (
+            Some(groups.into_iter().map(|(value, _)| value).collect()),
+            None,
+        )
+    } else {
+        let groups = groups
+            .into_iter()
+            .map(|(value, count)| crate::mcp::QueryGroup { value, count })
+            .collect();
+        (None, Some(groups))
+    };
+    Ok(crate::mcp::DebugQueryGroupsResponse {
+        field,
+        values,
+        groups,
+        total_groups: total,
+        has_more,
+        partial: result.timed_out.then_some(true),
+    })
+}
+
+/// Apply a debug_query request's filters (everything except the cursor and
+/// paging) to an event query. Time bounds are passed already resolved.
+fn apply_query_filters(
+    mut q: crate::db::EventQuery,
+    req: &DebugQueryRequest,
+    log_fields: &[(String, String)],
+    timestamp_from_ns: Option<i64>,
+    timestamp_to_ns: Option<i64>,
+) -> crate::db::EventQuery {
+    if let Some(ref et) = req.event_type {
+        q = q.event_type(match et {
+            EventTypeFilter::FunctionEnter => crate::db::EventType::FunctionEnter,
+            EventTypeFilter::FunctionExit => crate::db::EventType::FunctionExit,
+            EventTypeFilter::Stdout => crate::db::EventType::Stdout,
+            EventTypeFilter::Stderr => crate::db::EventType::Stderr,
+            EventTypeFilter::Crash => crate::db::EventType::Crash,
+            EventTypeFilter::VariableSnapshot => crate::db::EventType::VariableSnapshot,
+            EventTypeFilter::Pause => crate::db::EventType::Pause,
+            EventTypeFilter::Logpoint => crate::db::EventType::Logpoint,
+            EventTypeFilter::ConditionError => crate::db::EventType::ConditionError,
+            EventTypeFilter::AuxOutput => crate::db::EventType::AuxOutput,
+            EventTypeFilter::Watchpoint => crate::db::EventType::Watchpoint,
+            EventTypeFilter::OutputTrigger => crate::db::EventType::OutputTrigger,
+            EventTypeFilter::CallCounts => crate::db::EventType::CallCounts,
+            EventTypeFilter::Sample => crate::db::EventType::Sample,
+            EventTypeFilter::SignalSafety => crate::db::EventType::SignalSafety,
+            EventTypeFilter::Leak => crate::db::EventType::Leak,
+            EventTypeFilter::Syscall => crate::db::EventType::Syscall,
+            EventTypeFilter::Network => crate::db::EventType::Network,
+            EventTypeFilter::RtWarning => crate::db::EventType::RtWarning,
+            EventTypeFilter::Recovery => crate::db::EventType::Recovery,
+            EventTypeFilter::Panic => crate::db::EventType::Panic,
+            EventTypeFilter::Exception => crate::db::EventType::Exception,
+            EventTypeFilter::Signal => crate::db::EventType::Signal,
+            EventTypeFilter::Lock => crate::db::EventType::Lock,
+            EventTypeFilter::ConfigChange => crate::db::EventType::ConfigChange,
+        });
+    }
+    if let Some(ref f) = req.function {
+        if let Some(ref eq) = f.equals {
+            q = q.function_equals(eq);
+        }
+        if let Some(ref contains) = f.contains {
+            q = q.function_contains(contains);
+        }
+    }
+    if let Some(ref sf) = req.source_file {
+        if let Some(ref contains) = sf.contains {
+            q = q.source_file_contains(contains);
+        }
+    }
+    if let Some(ref tn) = req.thread_name {
+        if let Some(ref contains) = tn.contains {
+            q = q.thread_name_contains(contains);
+        }
+    }
+    if let Some(ref qn) = req.queue_name {
+        if let Some(ref eq) = qn.equals {
+            q = q.queue_name_equals(eq);
+        }
+        if let Some(ref contains) = qn.contains {
+            q = q.queue_name_contains(contains);
+        }
+    }
+    if let Some(ref text) = req.text {
+        if let Some(ref contains) = text.contains {
+            q = q.text_contains(contains);
+        }
+        if let Some(ref re) = text.regex {
+            q = q.text_regex(re);
+        }
+    }
+    for (key, value) in log_fields {
+        q = q.log_field(key, value);
+    }
+    if let Some(from) = timestamp_from_ns {
+        q.timestamp_from_ns = Some(from);
+    }
+    if let Some(to) = timestamp_to_ns {
+        q.timestamp_to_ns = Some(to);
+    }
+    if let Some(dur) = req.min_duration_ns {
+        q.min_duration_ns = Some(dur);
+    }
+    if let Some(pid) = req.pid {
+        q.pid_equals = Some(pid);
+    }
+    if let Some(ref test_name) = req.test_name {
+        q = q.test_name_equals(test_name);
+    }
+    if let Some(ref call_id) = req.call_id {
+        q = q.call_id_equals(call_id);
+    }
+    // Paired results are built from exits, one per completed call
+    if req.format == Some(QueryFormat::Paired) {
+        q = q.event_type(crate::db::EventType::FunctionExit);
+    }
+    q
+}
+
+/// Global regions dumped by `crashCapture`, carried in the crash event's text JSON.
+fn crash_globals(event: &crate::db::Event) -> Option<serde_json::Value> {
+    event
+        .text
+        .as_deref()
+        .and_then(|t| serde_json::from_str::<serde_json::Value>(t).ok())
+        .and_then(|v| v.get("globals").cloned())
+        .filter(|g| !g.is_null())
+}
+
+fn format_event_fields(event: &crate::db::Event, verbose: bool) -> serde_json::Value {
     if event.event_type == crate::db::EventType::Crash {
         return serde_json::json!({
             "id": event.id,
@@ -66,9 +479,11 @@ fn format_event(event: &crate::db::Event, verbose: bool) -> serde_json::Value {
             "registers": event.registers,
             "backtrace": event.backtrace,
             "locals": event.locals,
+            "globals": crash_globals(event),
             "exceptionType": event.exception_type,
             "exceptionMessage": event.exception_message,
             "throwBacktrace": event.throw_backtrace,
+            "dumpPath": event.dump_path,
         });
     }
 
@@ -86,7 +501,7 @@ fn format_event(event: &crate::db::Event, verbose: bool) -> serde_json::Value {
     if event.event_type == crate::db::EventType::Stdout
         || event.event_type == crate::db::EventType::Stderr
     {
-        return serde_json::json!({
+        let mut v = serde_json::json!({
             "id": event.id,
             "timestamp_ns": event.timestamp_ns,
             "eventType": event.event_type.as_str(),
@@ -94,6 +509,272 @@ fn format_event(event: &crate::db::Event, verbose: bool) -> serde_json::Value {
             "pid": event.pid,
             "text": event.text,
         });
+        if let Some(ref fields) = event.log_fields {
+            v["fields"] = fields.clone();
+        }
+        return v;
+    }
+
+    if event.event_type == crate::db::EventType::AuxOutput {
+        return serde_json::json!({
+            "id": event.id,
+            "timestamp_ns": event.timestamp_ns,
+            "eventType": "aux_output",
+            "channel": event.channel,
+            "threadId": event.thread_id,
+            "pid": event.pid,
+            "text": event.text,
+        });
+    }
+
+    if event.event_type == crate::db::EventType::Watchpoint {
+        return serde_json::json!({
+            "id": event.id,
+            "timestamp_ns": event.timestamp_ns,
+            "eventType": "watchpoint",
+            "threadId": event.thread_id,
+            "pid": event.pid,
+            "watchpointId": event.breakpoint_id,
+            "label": event.text,
+            "address": event.fault_address,
+            "function": event.function_name,
+            "sourceFile": event.source_file,
+            "line": event.line_number,
+            "backtrace": event.backtrace,
+        });
+    }
+
+    if event.event_type == crate::db::EventType::OutputTrigger {
+        let trigger = event.arguments.as_ref();
+        return serde_json::json!({
+            "id": event.id,
+            "timestamp_ns": event.timestamp_ns,
+            "eventType": "output_trigger",
+            "threadId": event.thread_id,
+            "pid": event.pid,
+            "match": trigger.and_then(|t| t.get("match")),
+            "action": trigger.and_then(|t| t.get("action")),
+            "sourceEventId": trigger.and_then(|t| t.get("sourceEventId")),
+            "text": event.text,
+        });
+    }
+
+    if event.event_type == crate::db::EventType::CallCounts {
+        return serde_json::json!({
+            "id": event.id,
+            "timestamp_ns": event.timestamp_ns,
+            "eventType": "call_counts",
+            "pid": event.pid,
+            "counts": event.arguments,
+        });
+    }
+
+    if event.event_type == crate::db::EventType::Sample {
+        return serde_json::json!({
+            "id": event.id,
+            "timestamp_ns": event.timestamp_ns,
+            "eventType": "sample",
+            "threadId": event.thread_id,
+            "threadName": event.thread_name,
+            "pid": event.pid,
+            "function": event.function_name,
+            "sourceFile": event.source_file,
+            "line": event.line_number,
+            "backtrace": event.backtrace,
+        });
+    }
+
+    if event.event_type == crate::db::EventType::SignalSafety {
+        let details = event.arguments.as_ref();
+        return serde_json::json!({
+            "id": event.id,
+            "timestamp_ns": event.timestamp_ns,
+            "eventType": "signal_safety",
+            "threadId": event.thread_id,
+            "pid": event.pid,
+            "function": event.function_name,
+            "signal": event.signal,
+            "handler": details.and_then(|d| d.get("handler")),
+            "handlerAddress": details.and_then(|d| d.get("handlerAddress")),
+            "category": details.and_then(|d| d.get("category")),
+            "message": event.text,
+            "backtrace": event.backtrace,
+        });
+    }
+
+    if event.event_type == crate::db::EventType::Syscall {
+        let call = event.arguments.as_ref();
+        return serde_json::json!({
+            "id": event.id,
+            "timestamp_ns": event.timestamp_ns,
+            "eventType": "syscall",
+            "threadId": event.thread_id,
+            "pid": event.pid,
+            "function": event.function_name,
+            "group": call.and_then(|c| c.get("group")),
+            "fd": call.and_then(|c| c.get("fd")),
+            "path": call.and_then(|c| c.get("path")),
+            "address": call.and_then(|c| c.get("address")),
+            "bytes": call.and_then(|c| c.get("bytes")),
+            "result": event.return_value,
+            "errno": call.and_then(|c| c.get("errno")),
+            "duration_ns": event.duration_ns,
+            "text": event.text,
+        });
+    }
+
+    if event.event_type == crate::db::EventType::Network {
+        let details = event.arguments.as_ref();
+        return serde_json::json!({
+            "id": event.id,
+            "timestamp_ns": event.timestamp_ns,
+            "eventType": "network",
+            "threadId": event.thread_id,
+            "pid": event.pid,
+            "function": event.function_name,
+            "kind": details.and_then(|d| d.get("kind")),
+            "host": details.and_then(|d| d.get("host")),
+            "port": details.and_then(|d| d.get("port")),
+            "address": details.and_then(|d| d.get("address")),
+            "addresses": details.and_then(|d| d.get("addresses")),
+            "bytes": details.and_then(|d| d.get("bytes")),
+            "direction": details.and_then(|d| d.get("direction")),
+            "result": event.return_value,
+            "duration_ns": event.duration_ns,
+            "text": event.text,
+        });
+    }
+
+    if event.event_type == crate::db::EventType::RtWarning {
+        let details = event.arguments.as_ref();
+        return serde_json::json!({
+            "id": event.id,
+            "timestamp_ns": event.timestamp_ns,
+            "eventType": "rt_warning",
+            "threadId": event.thread_id,
+            "threadName": event.thread_name,
+            "pid": event.pid,
+            "function": event.function_name,
+            "policy": details.and_then(|d| d.get("policy")),
+            "jsHook": details.and_then(|d| d.get("jsHook")),
+            "message": event.text,
+        });
+    }
+
+    if event.event_type == crate::db::EventType::Panic {
+        let details = event.arguments.as_ref();
+        return serde_json::json!({
+            "id": event.id,
+            "timestamp_ns": event.timestamp_ns,
+            "eventType": "panic",
+            "threadId": event.thread_id,
+            "pid": event.pid,
+            "function": event.function_name,
+            "kind": details.and_then(|d| d.get("kind")),
+            "file": details.and_then(|d| d.get("file")),
+            "line": details.and_then(|d| d.get("line")),
+            "exceptionType": event.exception_type,
+            "exceptionMessage": event.exception_message,
+            "message": event.text,
+            "backtrace": event.backtrace,
+        });
+    }
+
+    if event.event_type == crate::db::EventType::Exception {
+        let details = event.arguments.as_ref();
+        return serde_json::json!({
+            "id": event.id,
+            "timestamp_ns": event.timestamp_ns,
+            "eventType": "exception",
+            "threadId": event.thread_id,
+            "pid": event.pid,
+            "function": event.function_name,
+            "kind": details.and_then(|d| d.get("kind")),
+            "exceptionType": event.exception_type,
+            "exceptionMessage": event.exception_message,
+            "throwFunction": details.and_then(|d| d.get("throwFunction")),
+            "catchFunction": details.and_then(|d| d.get("catchFunction")),
+            "propagation": details.and_then(|d| d.get("propagation")),
+            "backtrace": event.backtrace,
+            "message": event.text,
+        });
+    }
+
+    if event.event_type == crate::db::EventType::Signal {
+        let details = event.arguments.as_ref();
+        return serde_json::json!({
+            "id": event.id,
+            "timestamp_ns": event.timestamp_ns,
+            "eventType": "signal",
+            "threadId": event.thread_id,
+            "pid": event.pid,
+            "function": event.function_name,
+            "signal": event.signal,
+            "kind": details.and_then(|d| d.get("kind")),
+            "handler": details.and_then(|d| d.get("handler")),
+            "senderPid": details.and_then(|d| d.get("senderPid")),
+            "senderUid": details.and_then(|d| d.get("senderUid")),
+            "targetPid": details.and_then(|d| d.get("targetPid")),
+            "result": event.return_value,
+            "message": event.text,
+        });
+    }
+
+    if event.event_type == crate::db::EventType::Lock {
+        let details = event.arguments.as_ref();
+        return serde_json::json!({
+            "id": event.id,
+            "timestamp_ns": event.timestamp_ns,
+            "eventType": "lock",
+            "threadId": event.thread_id,
+            "pid": event.pid,
+            "function": event.function_name,
+            "kind": details.and_then(|d| d.get("kind")),
+            "lock": details.and_then(|d| d.get("lock")),
+            "primitive": details.and_then(|d| d.get("primitive")),
+            "waitNs": details.and_then(|d| d.get("waitNs")),
+            "holderThreadId": details.and_then(|d| d.get("holderThreadId")),
+            "holderSite": details.and_then(|d| d.get("holderSite")),
+            "message": event.text,
+        });
+    }
+
+    if event.event_type == crate::db::EventType::Recovery {
+        return serde_json::json!({
+            "id": event.id,
+            "timestamp_ns": event.timestamp_ns,
+            "eventType": "recovery",
+            "message": event.text,
+        });
+    }
+
+    if event.event_type == crate::db::EventType::ConfigChange {
+        return serde_json::json!({
+            "id": event.id,
+            "timestamp_ns": event.timestamp_ns,
+            "eventType": "config_change",
+            "message": event.text,
+        });
+    }
+
+    if event.event_type == crate::db::EventType::Leak {
+        let details = event.arguments.as_ref();
+        return serde_json::json!({
+            "id": event.id,
+            "timestamp_ns": event.timestamp_ns,
+            "eventType": "leak",
+            "pid": event.pid,
+            "function": event.function_name,
+            "sourceFile": event.source_file,
+            "line": event.line_number,
+            "signature": details.and_then(|d| d.get("signature")),
+            "count": details.and_then(|d| d.get("count")),
+            "bytes": details.and_then(|d| d.get("bytes")),
+            "largest": details.and_then(|d| d.get("largest")),
+            "samples": details.and_then(|d| d.get("samples")),
+            "message": event.text,
+            "backtrace": event.backtrace,
+        });
     }
 
     if event.event_type == crate::db::EventType::Pause {
@@ -159,6 +840,9 @@ fn format_event(event: &crate::db::Event, verbose: bool) -> serde_json::Value {
             "returnValue": event.return_value,
             "watchValues": event.watch_values,
             "logpointMessage": event.logpoint_message,
+            "queue": event.queue_name,
+            "exceptionType": event.exception_type,
+            "exceptionMessage": event.exception_message,
         })
     } else {
         let mut obj = serde_json::json!({
@@ -185,6 +869,12 @@ fn format_event(event: &crate::db::Event, verbose: bool) -> serde_json::Value {
         if let Some(ref msg) = event.logpoint_message {
             obj["logpointMessage"] = serde_json::Value::String(msg.clone());
         }
+        if let Some(ref queue) = event.queue_name {
+            obj["queue"] = serde_json::Value::String(queue.clone());
+        }
+        if let Some(ref exception) = event.exception_type {
+            obj["exceptionType"] = serde_json::Value::String(exception.clone());
+        }
         obj
     }
 }
@@ -207,17 +897,265 @@ pub fn parse_type_hint(hint: &str) -> (u8, String) {
     }
 }
 
-fn hook_status_message(
-    installed: u32,
-    matched: u32,
-    patterns_empty: bool,
-    capabilities: Option<&crate::mcp::RuntimeCapabilities>,
-) -> String {
-    // When function tracing is unavailable for this runtime, give prescriptive guidance
-    if let Some(caps) = capabilities {
-        if matches!(caps.function_tracing, crate::mcp::CapabilityLevel::None) && !patterns_empty {
-            let first_limitation = caps
-                .limitations
+/// Instrumentation files and agent bundles are project artifacts: relative
+/// paths resolve against the project root, and the result must stay inside it
+/// like an `outputPath`.
+fn instrumentation_path(path: &str, project_root: &str) -> Result<PathBuf> {
+    inside_project(path, project_root).ok_or_else(|| {
+        crate::Error::ValidationError(format!(
+            "path {} is outside the project root {}",
+            path, project_root
+        ))
+    })
+}
+
+/// Where a tool writes a caller-chosen `outputPath`. Relative paths resolve
+/// against the project root, and the result must stay inside it: the daemon
+/// must not become a way to overwrite arbitrary files.
+fn project_output_path(path: &str, project_root: &str) -> Result<PathBuf> {
+    inside_project(path, project_root).ok_or_else(|| {
+        crate::Error::ValidationError(format!(
+            "outputPath {} is outside the project root {}",
+            path, project_root
+        ))
+    })
+}
+
+/// `path` resolved against `project_root`; None when it leaves the root.
+fn inside_project(path: &str, project_root: &str) -> Option<PathBuf> {
+    let root = Path::new(project_root);
+    let path = Path::new(path);
+    let escapes = path
+        .components()
+        .any(|c| matches!(c, std::path::Component::ParentDir));
+    let resolved = if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        root.join(path)
+    };
+    (!escapes && resolved.starts_with(root)).then_some(resolved)
+}
+
+fn load_instrumentation_file(path: &str, project_root: &str) -> Result<InstrumentationSnapshot> {
+    let path = instrumentation_path(path, project_root)?;
+    let content = std::fs::read_to_string(&path).map_err(|e| {
+        crate::Error::ValidationError(format!(
+            "Cannot read instrumentationFile {}: {}",
+            path.display(),
+            e
+        ))
+    })?;
+    let snapshot: InstrumentationSnapshot = serde_json::from_str(&content).map_err(|e| {
+        crate::Error::ValidationError(format!(
+            "Invalid instrumentationFile {}: {}",
+            path.display(),
+            e
+        ))
+    })?;
+    snapshot.validate()?;
+    Ok(snapshot)
+}
+
+/// Profiles live in the project's settings, so their relative paths are
+/// relative to the project root, not to the daemon's working directory.
+fn profile_path(path: &str, project_root: &str) -> String {
+    let path = Path::new(path);
+    if path.is_absolute() {
+        return path.to_string_lossy().into_owned();
+    }
+    Path::new(project_root)
+        .join(path.strip_prefix(".").unwrap_or(path))
+        .to_string_lossy()
+        .into_owned()
+}
+
+/// Fill in what a `profile` launch leaves out from the named profile in the
+/// project's settings. Returns the effective launch arguments and the profile's
+/// patterns, watches and breakpoints as a snapshot to apply after spawn.
+fn resolve_launch_profile(
+    args: &serde_json::Value,
+) -> Result<(serde_json::Value, Option<InstrumentationSnapshot>)> {
+    let Some(name) = args.get("profile").and_then(|p| p.as_str()) else {
+        return Ok((args.clone(), None));
+    };
+    let project_root = args
+        .get("projectRoot")
+        .and_then(|r| r.as_str())
+        .filter(|r| !r.is_empty())
+        .ok_or_else(|| {
+            crate::Error::ValidationError(
+                "projectRoot is required to look up a profile".to_string(),
+            )
+        })?;
+    let settings = crate::config::resolve(Some(Path::new(project_root)));
+    let profile = settings.profiles.get(name).ok_or_else(|| {
+        let known: Vec<&str> = settings.profiles.keys().map(String::as_str).collect();
+        crate::Error::ValidationError(if known.is_empty() {
+            format!(
+                "Unknown profile '{}': no profiles in {}/.strobe/settings.json",
+                name, project_root
+            )
+        } else {
+            format!("Unknown profile '{}'. Profiles: {}", name, known.join(", "))
+        })
+    })?;
+
+    let mut args = args.clone();
+    let unset =
+        |v: Option<&serde_json::Value>| v.map_or(true, |v| v.is_null() || v.as_str() == Some(""));
+    if unset(args.get("command")) {
+        let command = profile.command.as_deref().ok_or_else(|| {
+            crate::Error::ValidationError(format!(
+                "Profile '{}' has no command; pass command",
+                name
+            ))
+        })?;
+        // A bare name is looked up on PATH; anything with a directory is a
+        // project path
+        args["command"] = if Path::new(command).components().count() > 1 {
+            serde_json::json!(profile_path(command, project_root))
+        } else {
+            serde_json::json!(command)
+        };
+    }
+    if unset(args.get("args")) {
+        if let Some(ref profile_args) = profile.args {
+            args["args"] = serde_json::json!(profile_args);
+        }
+    }
+    if unset(args.get("cwd")) {
+        if let Some(ref cwd) = profile.cwd {
+            args["cwd"] = serde_json::json!(profile_path(cwd, project_root));
+        }
+    }
+    if !profile.env.is_empty() {
+        let mut env = serde_json::json!(profile.env);
+        if let Some(overrides) = args.get("env").and_then(|e| e.as_object()) {
+            for (k, v) in overrides {
+                env[k] = v.clone();
+            }
+        }
+        args["env"] = env;
+    }
+
+    if profile.trace_patterns.is_empty()
+        && profile.watches.is_empty()
+        && profile.breakpoints.is_empty()
+    {
+        return Ok((args, None));
+    }
+    let invalid = |what: &str, e: serde_json::Error| {
+        crate::Error::ValidationError(format!("Profile '{}' has invalid {}: {}", name, what, e))
+    };
+    let snapshot = InstrumentationSnapshot {
+        version: INSTRUMENTATION_SNAPSHOT_VERSION,
+        session_id: format!("profile:{}", name),
+        binary_path: args["command"].as_str().unwrap_or_default().to_string(),
+        exported_at: String::new(),
+        patterns: profile.trace_patterns.clone(),
+        counted_patterns: Vec::new(),
+        rt_patterns: Vec::new(),
+        watches: serde_json::from_value(serde_json::json!(profile.watches))
+            .map_err(|e| invalid("watches", e))?,
+        watchpoints: Vec::new(),
+        breakpoints: serde_json::from_value(serde_json::json!(profile.breakpoints))
+            .map_err(|e| invalid("breakpoints", e))?,
+        logpoints: Vec::new(),
+        settings: InstrumentationSettings::default(),
+    };
+    snapshot.validate()?;
+    Ok((args, Some(snapshot)))
+}
+
+/// What debug_launch started, with cwd resolved the way the spawner resolves
+/// it (the daemon's by default). Only the request's `env` overrides are
+/// kept: the daemon's own environment may hold secrets, and a relaunch layers
+/// the overrides over the daemon's environment as it is then.
+fn launch_record(req: &DebugLaunchRequest, args: &serde_json::Value) -> crate::db::SessionLaunch {
+    let mut argv = vec![req.command.clone()];
+    argv.extend(req.args.iter().flatten().cloned());
+    let cwd = req.cwd.clone().unwrap_or_else(|| {
+        std::env::current_dir()
+            .map(|dir| dir.to_string_lossy().into_owned())
+            .unwrap_or_default()
+    });
+    let env = req
+        .env
+        .iter()
+        .flatten()
+        .map(|(k, v)| (k.clone(), v.clone()))
+        .collect();
+    let mut request = args.clone();
+    if let Some(obj) = request.as_object_mut() {
+        obj.remove("env");
+    }
+    crate::db::SessionLaunch {
+        argv,
+        cwd,
+        env,
+        request,
+    }
+}
+
+/// A test run's progress (running) or summary (finished), as listed by the
+/// HTTP API and kept on investigations.
+fn test_run_entry(run: &crate::test::TestRun) -> serde_json::Value {
+    let mut entry = serde_json::json!({
+        "testRunId": run.id,
+        "sessionId": run.session_id,
+        "projectRoot": run.project_root,
+    });
+    match &run.state {
+        crate::test::TestRunState::Running { progress } => {
+            let p = progress.lock().unwrap();
+            let mut current: Vec<&String> = p.running_tests.keys().collect();
+            current.sort();
+            current.truncate(5);
+            entry["status"] = "running".into();
+            entry["phase"] = match p.phase {
+                crate::test::TestPhase::Compiling => "compiling",
+                crate::test::TestPhase::Running => "running",
+                crate::test::TestPhase::SuitesFinished => "suites_finished",
+            }
+            .into();
+            entry["passed"] = p.passed.into();
+            entry["failed"] = p.failed.into();
+            entry["skipped"] = p.skipped.into();
+            entry["elapsedMs"] = p.elapsed_ms().into();
+            entry["currentTests"] = serde_json::json!(current);
+            entry["warnings"] = p.warnings.len().into();
+        }
+        crate::test::TestRunState::Completed { response, .. } => {
+            entry["status"] = "completed".into();
+            entry["framework"] = response
+                .get("framework")
+                .cloned()
+                .unwrap_or(serde_json::Value::Null);
+            if let Some(summary) = response.get("summary") {
+                for key in ["passed", "failed", "skipped", "durationMs"] {
+                    entry[key] = summary.get(key).cloned().unwrap_or_default();
+                }
+            }
+        }
+        crate::test::TestRunState::Failed { error, .. } => {
+            entry["status"] = "failed".into();
+            entry["error"] = error.clone().into();
+        }
+    }
+    entry
+}
+
+fn hook_status_message(
+    installed: u32,
+    matched: u32,
+    patterns_empty: bool,
+    capabilities: Option<&crate::mcp::RuntimeCapabilities>,
+) -> String {
+    // When function tracing is unavailable for this runtime, give prescriptive guidance
+    if let Some(caps) = capabilities {
+        if matches!(caps.function_tracing, crate::mcp::CapabilityLevel::None) && !patterns_empty {
+            let first_limitation = caps
+                .limitations
                 .first()
                 .map(|s| s.as_str())
                 .unwrap_or("Function tracing is not available for this runtime.");
@@ -241,35 +1179,142 @@ fn hook_status_message(
     }
 }
 
-/// Probe whether a daemon is actually serving requests on `socket_path`.
-///
-/// This is more reliable than PID-based liveness on macOS: a "UE" zombie
-/// (process stuck inside `proc_exit`) still appears in the kernel proc table
-/// with `pbi_status == SRUN` and `kill(pid, 0) == 0`, so naive checks lie.
-/// What it cannot do is `accept()` — so we just try to connect with a short
-/// timeout. A real daemon accepts and we return true; a stale socket either
-/// fails to connect or hangs past the timeout, and we return false.
-fn daemon_socket_responsive(socket_path: &std::path::Path) -> bool {
-    if !socket_path.exists() {
-        return false;
-    }
-    // Use the std (blocking) UnixStream so this works before the tokio runtime
-    // is fully wired into the lock-acquisition path.
-    std::os::unix::net::UnixStream::connect_addr(
-        &match std::os::unix::net::SocketAddr::from_pathname(socket_path) {
-            Ok(addr) => addr,
-            Err(_) => return false,
-        },
-    )
-    .map(|s| {
-        let _ = s.set_read_timeout(Some(Duration::from_millis(500)));
-        let _ = s.shutdown(std::net::Shutdown::Both);
+/// SIGTERM on Unix. Windows has no equivalent, so there it never fires and
+/// Ctrl-C or the idle timeout end the daemon.
+struct TerminateSignal {
+    #[cfg(unix)]
+    inner: tokio::signal::unix::Signal,
+}
+
+impl TerminateSignal {
+    fn new() -> std::io::Result<Self> {
+        Ok(Self {
+            #[cfg(unix)]
+            inner: tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())?,
+        })
+    }
+
+    async fn recv(&mut self) {
+        #[cfg(unix)]
+        self.inner.recv().await;
+        #[cfg(not(unix))]
+        std::future::pending::<()>().await;
+    }
+}
+
+/// debug_ui mode 'record': a frame sequence in
+/// <projectRoot>/recordings/<sessionId>-<timestamp>/. Capture stops early if
+/// the request is dropped.
+async fn debug_ui_record(
+    req: &crate::mcp::DebugUiRequest,
+    session: &crate::db::Session,
+) -> Result<Vec<McpContent>> {
+    let start = std::time::Instant::now();
+    let pid = session.pid;
+    let bounds = crop_bounds(req, pid).await?;
+    let dir = Path::new(&session.project_root)
+        .join("recordings")
+        .join(format!(
+            "{}-{}",
+            req.session_id,
+            chrono::Utc::now().format("%Y%m%d-%H%M%S%.3f")
+        ));
+    let duration = std::time::Duration::from_millis(
+        req.duration_ms.unwrap_or(crate::mcp::DEFAULT_UI_RECORD_MS),
+    );
+    let fps = req.fps.unwrap_or(crate::mcp::DEFAULT_UI_RECORD_FPS);
+
+    let stop = Arc::new(std::sync::atomic::AtomicBool::new(false));
+    let _stop_on_drop = crate::ui::record::StopOnDrop(Arc::clone(&stop));
+    let recording = tokio::task::spawn_blocking(move || {
+        crate::ui::record::record(&dir, duration, fps, &stop, || match bounds {
+            Some(ref bounds) => crate::ui::capture::capture_element_screenshot(pid, bounds),
+            None => crate::ui::capture::capture_window_screenshot(pid),
+        })
     })
-    .is_ok()
+    .await
+    .map_err(|e| crate::Error::Internal(format!("Recording task failed: {}", e)))??;
+
+    let response = crate::mcp::DebugUiResponse {
+        tree: None,
+        stats: Some(crate::mcp::UiStats {
+            ax_nodes: 0,
+            vision_nodes: 0,
+            merged_nodes: 0,
+            ocr_texts: 0,
+            latency_ms: start.elapsed().as_millis() as u64,
+        }),
+        diff: None,
+        action: None,
+        recording: Some(recording),
+    };
+    Ok(vec![McpContent::Text {
+        text: serde_json::to_string_pretty(&response)?,
+    }])
+}
+
+/// Screen rect to crop debug_ui screenshots to: the bounds of the element
+/// `id` names, else `region`, else None for the whole window.
+async fn crop_bounds(
+    req: &crate::mcp::DebugUiRequest,
+    pid: u32,
+) -> Result<Option<crate::ui::tree::Rect>> {
+    let Some(ref target_id) = req.id else {
+        return Ok(req.region.clone());
+    };
+    let nodes = query_ui_tree(pid).await?;
+    let node = crate::ui::tree::find_node_by_id(&nodes, target_id).ok_or_else(|| {
+        crate::Error::UiQueryFailed(format!(
+            "Element '{}' not found. Use debug_ui with mode=tree to see current element IDs.",
+            target_id
+        ))
+    })?;
+    Ok(Some(node.bounds.ok_or_else(|| {
+        crate::Error::UiQueryFailed(format!(
+            "Element '{}' has no bounds (may be off-screen or invisible)",
+            target_id
+        ))
+    })?))
+}
+
+/// The accessibility tree of `pid`: a blocking AX walk on macOS, AT-SPI over
+/// D-Bus on Linux, UI_NOT_AVAILABLE elsewhere.
+async fn query_ui_tree(pid: u32) -> Result<Vec<crate::ui::tree::UiNode>> {
+    #[cfg(target_os = "macos")]
+    return tokio::task::spawn_blocking(move || crate::ui::accessibility::query_ax_tree(pid))
+        .await
+        .map_err(|e| crate::Error::Internal(format!("AX query task failed: {}", e)))?;
+
+    #[cfg(not(target_os = "macos"))]
+    return crate::ui::accessibility::query_ax_tree(pid).await;
 }
 
 impl Daemon {
-    pub async fn run() -> Result<()> {
+    /// A daemon with no socket or idle shutdown, for in-process use
+    /// (`crate::embed`). Its socket and PID paths are never written.
+    pub(crate) fn embedded(db_path: &Path) -> Result<Self> {
+        let dir = db_path.parent().unwrap_or_else(|| Path::new("."));
+        Ok(Self {
+            socket_path: dir.join("embedded.sock"),
+            pid_path: dir.join("embedded.pid"),
+            session_manager: Arc::new(SessionManager::new(db_path)?),
+            last_activity: Arc::new(RwLock::new(Instant::now())),
+            pending_patterns: Arc::new(RwLock::new(HashMap::new())),
+            connection_sessions: Arc::new(RwLock::new(HashMap::new())),
+            test_runs: Arc::new(tokio::sync::RwLock::new(HashMap::new())),
+            shutdown_signal: Arc::new(tokio::sync::Notify::new()),
+            vision: Arc::new(std::sync::Mutex::new(
+                crate::ui::vision::VisionPipeline::new(),
+            )),
+            notification_senders: Arc::new(RwLock::new(HashMap::new())),
+        })
+    }
+
+    /// Run the daemon. `args` are what follows `strobe daemon`: `--web [port]`
+    /// serves the HTTP API and dashboard even without `http.port` set.
+    pub async fn run(args: &[String]) -> Result<()> {
+        let web_port = super::http::parse_daemon_args(args)?;
+
         let strobe_dir = dirs::home_dir()
             .unwrap_or_else(|| PathBuf::from("."))
             .join(".strobe");
@@ -294,9 +1339,7 @@ impl Daemon {
             .write(true)
             .open(&lock_path)?;
 
-        let mut lock_result =
-            unsafe { libc::flock(lock_file.as_raw_fd(), libc::LOCK_EX | libc::LOCK_NB) };
-        if lock_result != 0 {
+        if lock_file.try_lock().is_err() {
             // Another fd holds the lock. Is there actually a daemon serving
             // requests, or is this a stale UE zombie still pinning the inode?
             //
@@ -305,8 +1348,7 @@ impl Daemon {
             // P_WEXIT flag set. The only signal that's reliably different is
             // "can a real client open a connection?" — a healthy daemon
             // accepts on `strobe.sock`; a zombie does not.
-            let socket_path_check = strobe_dir.join("strobe.sock");
-            if daemon_socket_responsive(&socket_path_check) {
+            if super::transport::responsive(&super::transport::endpoint(&strobe_dir)) {
                 tracing::info!("Another daemon is already running (socket responsive), exiting");
                 return Ok(());
             }
@@ -320,9 +1362,7 @@ impl Daemon {
                 .create(true)
                 .write(true)
                 .open(&lock_path)?;
-            lock_result =
-                unsafe { libc::flock(lock_file.as_raw_fd(), libc::LOCK_EX | libc::LOCK_NB) };
-            if lock_result != 0 {
+            if lock_file.try_lock().is_err() {
                 tracing::error!("Failed to acquire daemon.lock even after recreating it");
                 return Ok(());
             }
@@ -331,13 +1371,10 @@ impl Daemon {
         // (graceful shutdown or fatal error) the fd closes and the lock releases.
         let _lock_file = lock_file;
 
-        let socket_path = strobe_dir.join("strobe.sock");
+        let socket_path = super::transport::endpoint(&strobe_dir);
         let pid_path = strobe_dir.join("strobe.pid");
         let db_path = strobe_dir.join("strobe.db");
 
-        // Remove stale socket
-        let _ = std::fs::remove_file(&socket_path);
-
         // Write PID file
         std::fs::write(&pid_path, std::process::id().to_string())?;
 
@@ -352,23 +1389,33 @@ impl Daemon {
             connection_sessions: Arc::new(RwLock::new(HashMap::new())),
             test_runs: Arc::new(tokio::sync::RwLock::new(HashMap::new())),
             shutdown_signal: Arc::new(tokio::sync::Notify::new()),
-            vision_sidecar: Arc::new(std::sync::Mutex::new(
-                crate::ui::vision::VisionSidecar::new(),
+            vision: Arc::new(std::sync::Mutex::new(
+                crate::ui::vision::VisionPipeline::new(),
             )),
             notification_senders: Arc::new(RwLock::new(HashMap::new())),
         });
 
-        let listener = UnixListener::bind(&socket_path)?;
+        let mut listener = super::transport::Listener::bind(&socket_path)?;
         tracing::info!("Daemon listening on {:?}", socket_path);
 
+        if let Some(port) = web_port.or(crate::config::resolve(None).http_port) {
+            daemon
+                .start_http(&strobe_dir, port, web_port.is_some())
+                .await;
+        }
+
         // Spawn idle timeout checker
         let daemon_clone = Arc::clone(&daemon);
         tokio::spawn(async move {
             daemon_clone.idle_timeout_loop().await;
         });
 
-        let mut sigterm =
-            tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())?;
+        // Apply settings.json edits to running sessions
+        tokio::spawn(super::settings_reload::watch_settings(Arc::clone(
+            &daemon.session_manager,
+        )));
+
+        let mut sigterm = TerminateSignal::new()?;
         let shutdown = Arc::clone(&daemon.shutdown_signal);
         let mut consecutive_accept_errors: u32 = 0;
 
@@ -376,7 +1423,7 @@ impl Daemon {
             tokio::select! {
                 result = listener.accept() => {
                     match result {
-                        Ok((stream, _)) => {
+                        Ok(stream) => {
                             consecutive_accept_errors = 0;
                             let daemon = Arc::clone(&daemon);
                             tokio::spawn(async move {
@@ -419,14 +1466,52 @@ impl Daemon {
         Ok(())
     }
 
+    /// Start the REST listener on 127.0.0.1. A port already in use is logged
+    /// and skipped; the MCP socket keeps working either way. With
+    /// `print_dashboard`, the dashboard URL and the token file's path go to
+    /// stderr; the token itself stays out of terminal scrollback and logs.
+    async fn start_http(
+        self: &Arc<Self>,
+        strobe_dir: &std::path::Path,
+        port: u16,
+        print_dashboard: bool,
+    ) {
+        let token_path = strobe_dir.join("http.token");
+        let token = match super::http::load_or_create_token(&token_path) {
+            Ok(token) => Arc::new(token),
+            Err(e) => {
+                tracing::error!("HTTP listener disabled: cannot write token: {}", e);
+                return;
+            }
+        };
+        match tokio::net::TcpListener::bind(("127.0.0.1", port)).await {
+            Ok(listener) => {
+                tracing::info!("HTTP API listening on 127.0.0.1:{}", port);
+                if print_dashboard {
+                    eprintln!(
+                        "Dashboard: http://127.0.0.1:{}/dashboard (token in {})",
+                        port,
+                        token_path.display()
+                    );
+                }
+                tokio::spawn(super::http::serve(Arc::clone(self), listener, token));
+            }
+            Err(e) => tracing::error!("HTTP listener disabled: cannot bind port {}: {}", port, e),
+        }
+    }
+
+    pub(super) async fn touch_activity(&self) {
+        *self.last_activity.write().await = Instant::now();
+    }
+
     async fn idle_timeout_loop(&self) {
         loop {
             tokio::time::sleep(Duration::from_secs(60)).await;
 
             // Check vision sidecar idle timeout
             let settings = crate::config::resolve(None);
-            if let Ok(mut sidecar) = self.vision_sidecar.lock() {
-                sidecar.check_idle_timeout(settings.vision_sidecar_idle_timeout_seconds);
+            if let Ok(mut vision) = self.vision.lock() {
+                vision.check_idle_timeout(settings.vision_sidecar_idle_timeout_seconds);
             }
 
             let last = *self.last_activity.read().await;
@@ -461,9 +1546,14 @@ impl Daemon {
             let _ = self.session_manager.stop_session(id).await;
         }
 
-        // Phase 4: Shutdown vision sidecar if running
-        if let Ok(mut sidecar) = self.vision_sidecar.lock() {
-            sidecar.shutdown();
+        // Phase 3: Move the WAL into the database so the next start opens clean
+        if let Err(e) = self.session_manager.db().checkpoint_wal() {
+            tracing::warn!("WAL checkpoint at shutdown failed: {}", e);
+        }
+
+        // Phase 4: Shutdown vision backend (sidecar process) if running
+        if let Ok(mut vision) = self.vision.lock() {
+            vision.shutdown();
         }
 
         self.cleanup();
@@ -475,8 +1565,11 @@ impl Daemon {
         let _ = std::fs::remove_file(&self.pid_path);
     }
 
-    async fn handle_connection(&self, stream: UnixStream) -> Result<()> {
-        let (reader, mut writer) = stream.into_split();
+    async fn handle_connection<S>(&self, stream: S) -> Result<()>
+    where
+        S: AsyncRead + AsyncWrite + Send + 'static,
+    {
+        let (reader, mut writer) = tokio::io::split(stream);
         let mut reader = BufReader::new(reader);
         let mut line = String::new();
         let mut initialized = false;
@@ -816,16 +1909,53 @@ impl Daemon {
 
 If behavior requires user action (button press, network event), tell the user what to trigger.
 
+First time using strobe on this machine? `debug_tutorial` walks through every tool on a bundled demo program and verifies each step.
+
 ## Patterns
 
 - `foo::bar` — exact | `foo::*` — direct children | `foo::**` — all descendants
 - `*::validate` — named function, one level | `@file:parser.cpp` — by source file
 - `*` stops at `::`, `**` crosses it. Start with 1-3 specific patterns, widen incrementally.
+- Python/JS/Java sessions use dots: `mypkg.handlers.*` (`*` stops at `.`). `::` patterns are rewritten to dots there.
+- Don't know the names? `debug_symbols({ sessionId, action: \"functions\", query: \"render\" })` searches the debug symbols (substring, `regex: true`, or `file`); `action: \"globals\"` lists globals with types and addresses for watches.
+- Unsure a pattern is right? `debug_trace({ sessionId, lint: ["audio::*", "@file:src"] })` checks it against common mistakes and the debug symbols and suggests a rewrite. `debug_trace({ sessionId, preview: ["audio::**"] })` lists what a broad pattern would hook, and how many, before you add it. Patterns that can never match (bare `*`, parameter lists) are rejected from `add` with INVALID_PATTERN.
 
 ## Limits
 
 - Aim for <50 hooks (fast, stable). 100+ risks crashes. Hard cap: 100 per debug_trace call.
-- Default 200k events/session (FIFO). Configure via .strobe/settings.json. Use 500k for audio/DSP; avoid 1M+.
+- Just need call counts? `debug_trace({ sessionId, add: [\"dsp::**\"], mode: \"count\" })` hooks up to 5000 native functions with in-agent counters and no per-call events. `debug_session` status lists the top totals; `call_counts` events hold the rest (refreshed each second). To switch a pattern between modes, remove it first.
+- Tracing audio callbacks or other real-time threads? Use `mode: \"rt\"`: native-only hooks that run no JS and take no locks on the hooked thread (enter/exit go through a preallocated lock-free ring drained by the agent thread). Normal hooks that fire on a SCHED_FIFO/SCHED_RR/time-constraint thread are reported once per thread and function as `rt_warning` events.
+- Busy threads drowning the trace? `debug_trace({ sessionId, add, threads: { nameContains: \"midi\" } })` records only calls from matching threads (or `ids`); `threads: {}` lifts the filter.
+- Hot function eating the event budget? `debug_trace({ sessionId, rateLimit: { maxPerSecond: 100 } })` records at most 100 calls per function per second (or `sampleEvery: K` for 1 in K); kept calls are marked `sampled`. `rateLimit: {}` records everything again.
+- Intermittent bug? `debug_trace({ sessionId, add: [...], flightRecorder: { enabled: true } })` keeps traced calls in a ring buffer in the agent and only writes them out when the process crashes, hits a breakpoint or prints an assertion/panic to stderr — then query the lead-up as usual. `flush: true` writes the buffer out on demand.
+- Don't know where to look? `debug_trace({ sessionId, sampling: { enabled: true } })` samples every thread's stack (default 100 Hz, settings \"sampling.hz\") without hooks. Each backtrace is a `sample` event named after its leaf frame; narrow with `threadIds`/`threadName`, stop with `enabled: false`.
+- Which files and sockets does it touch? `debug_trace({ sessionId, add: [\"@syscalls:file\"] })` hooks open/read/write/close in libc (`@syscalls:net`: socket/connect/accept/send/recv) and records each call as a `syscall` event with fd, path or peer address, byte count, result, errno and duration. Remove it like any pattern.
+- Who does it talk to? `debug_trace({ sessionId, add: [\"@network\"] })` records each connect (with the host name from the preceding DNS lookup), each `getaddrinfo` and the TLS server name and bytes of every SSL_read/SSL_write (OpenSSL/BoringSSL, SecureTransport on macOS) as `network` events.
+- Exception swallowed somewhere? `debug_trace({ sessionId, add: [\"@exceptions\"] })` records every C++ throw (type from RTTI, `what()`, backtrace) as an `exception` event with `kind: \"throw\"`, and the `catch` that received it with the same `callId`, its `catchFunction` and the `propagation` frames the exception unwound through. `debug_query({ sessionId, callId })` on a throw shows where it ended up; a throw with no catch and no crash is still in flight.
+- Who sent that SIGTERM? `debug_trace({ sessionId, add: [\"@signals\"] })` records each signal that reaches one of the process's handlers as a `signal` event (`kind: \"delivered\"`, with `senderPid` for signals sent by another process) and each kill/raise/pthread_kill it makes (`kind: \"sent\"`, with `targetPid`).
+- Test hangs on a lock? `debug_trace({ sessionId, add: [\"@locks\"] })` times every pthread_mutex_lock (plus the contended paths of Rust parking_lot/std locks) and records each acquisition that waited on another thread as a `lock` event with the call site, `waitNs` and the holder's thread and call site; a thread still waiting after a second is reported with `kind: \"blocked\"`. debug_session status `lockContention` ranks the most contended call sites. Every lock call runs a JS hook, so expect a slowdown in lock-heavy code.
+- Deadlocks or corruption around signals? `debug_trace({ sessionId, signalSafety: { enabled: true } })` watches the process's signal handlers and reports each call to a non-async-signal-safe function (malloc, printf, mutexes, exit, ...) made inside one as a `signal_safety` event with the handler, signal and backtrace. `functions` replaces the built-in list.
+- Default 200k events/session (FIFO). Configure via .strobe/settings.json. Use 500k for audio/DSP; avoid 1M+. For long sessions, `events.downsampleAfterMinutes` keeps every k-th call older than that (output and crashes kept); status `downsampledUntilNs` marks where full detail starts.
+- Status `writer.dropped` growing = tracing outruns the event writer; narrow patterns or use `mode: \"count\"`.
+- Status `interrupted` = the daemon died while the session ran; its events end at the `recovery` event.
+
+## Output Triggers
+
+`debug_launch({ onOutput: [{ match: \"Segmentation fault imminent\", action: \"pause\" }] })` acts when that text shows up on stdout/stderr: `pause` freezes the process (resume with `debug_continue`), `snapshot-watches` records current watch values, `annotate` only marks the spot. Each hit is an `output_trigger` event next to the line. The daemon sees output after it is written, so the process has moved on slightly — use a breakpoint when the exact instruction matters.
+
+## Sharing a Setup
+
+`debug_session({ action: \"export-instrumentation\", sessionId })` writes the session's patterns, watches, watchpoints, breakpoints, logpoints and settings to `.strobe/instrumentation/<sessionId>.json`. A teammate passes it as `debug_launch({ instrumentationFile })` to observe the same things. Raw-address watches are left out (listed under `omitted`).
+
+`debug_launch({ inheritInstrumentation: true })` carries the previous session's setup over the same way, from the last stopped session of the same binary in the same project: patterns, variable watches, watchpoints, breakpoints and logpoints are re-resolved against the new process (new ASLR slide included), and `instrumentationFrom` names the session they came from. `debug_session` relaunch does this by default.
+
+Long launch configurations can be named in `.strobe/settings.json` `profiles` (command, args, cwd, env, tracePatterns, watches, breakpoints) and started with `debug_launch({ profile: \"audio-debug\", projectRoot })`; call arguments override the profile.
+
+For a quick repro loop, `debug_session({ action: \"relaunch\", sessionId })` launches a stopped session's command again with the same argv, cwd and environment; the response adds `relaunchedFrom`.
+
+## Investigations
+
+One bug hunt often spans several launches, a crash, test reruns and notes. `debug_investigation({ action: \"create\", title, projectRoot })` returns an `investigationId`; link artifacts with `add-session` (sessionId), `add-test-run` (testRunId) and `add-note` (note, e.g. an export path or a finding). `summarize` lists everything with each session's current status and crash count, `list` shows investigations newest first, and `close` records a `conclusion`. Sessions of an open investigation are retained when stopped unless you pass `retain: false`.
 
 ## Watches
 
@@ -833,20 +1963,40 @@ Read globals during function execution (requires DWARF symbols). Max 32 watches.
 - `{ variable: \"gCounter\" }` — named variable | `{ variable: \"gClock->counter\" }` — pointer chain
 - `{ address: \"0x1234\", type: \"f64\", label: \"tempo\" }` — raw address | `{ expr: \"...\", label: \"x\" }` — JS expression
 - Scope with `on`: `{ variable: \"gTempo\", on: [\"audio::*\"] }`
+- Who writes it? `debug_watchpoint({ add: [{ variable: \"gCounter\" }] })` records each write with writer + backtrace (max 16).
 
 ## Queries
 
-- eventType: `stderr`/`stdout` (always captured), `function_enter`/`function_exit` (when tracing), `pause`/`logpoint`/`condition_error`/`variable_snapshot`/`crash`
-- Filters: `function: { contains }`, `sourceFile: { contains }`, `verbose: true`
+- eventType: `stderr`/`stdout` (always captured), `aux_output` (launch `captureFds`/`capturePipes`), `function_enter`/`function_exit` (when tracing), `pause`/`logpoint`/`condition_error`/`variable_snapshot`/`crash`, `watchpoint` (debug_watchpoint writes), `output_trigger` (launch `onOutput` hits), `call_counts` (count-mode running totals), `sample` (stack samples from debug_trace `sampling`), `signal_safety` (debug_trace `signalSafety` findings), `syscall` (`@syscalls:` file/socket calls), `network` (`@network` connects, DNS lookups, TLS traffic), `rt_warning` (normal hooks firing on real-time threads), `recovery` (where an `interrupted` session's recording was cut off by a daemon crash), `panic` (Rust panics, failed C asserts and aborts — always recorded, no trace patterns needed), `exception` (`@exceptions` C++ throws, catches and rethrows), `signal` (`@signals` handler deliveries with the sender pid, kill/raise calls), `lock` (`@locks` contended and blocked mutex waits), `config_change` (settings file edits applied to the running session), `leak` (memory still allocated when a session with debug_heap tracking stops with `retain: true`)
+- Filters: `function: { contains }`, `sourceFile: { contains }`, `queueName: { equals | contains }` (GCD queue label — use instead of threadId on macOS), `verbose: true`
+- Structured logs: launch with `logFormat: \"json\"` or `\"logfmt\"`, then filter with `fields: { level: \"error\", module: \"auth\" }`.
+- Output search: `text: { contains: \"connection refused\" }` (indexed, case-insensitive) or `text: { regex }` over stdout/stderr/aux_output. Use it instead of paging through large sessions.
+- Only need a few fields? `select: [\"function\", \"duration_ns\", \"watchValues.gTempo\"]` returns just those per event (dotted paths reach into arguments, watch values, log fields) — far smaller responses for latency studies.
+- Arguments and return value side by side? `format: \"paired\"` returns one record per completed call (`arguments`, `returnValue`, `duration_ns`, enter/exit timestamps). Enter and exit events share a `callId`; `callId: \"...\"` fetches both.
+- Overview first? `groupBy: \"function\"` (or `\"threadName\"`, `\"sourceFile\"`) counts matching events per value in one call; `distinct: \"function\"` just lists which functions fired. All filters apply.
+- Several lookups at once? `debug_batch({ sessionId, calls: [{ tool: \"debug_session\", arguments: { action: \"status\" } }, { tool: \"debug_query\", arguments: { eventType: \"stderr\" } }] })` runs them in order in one round trip.
 - Default 50 events. Paginate with `offset`/`afterEventId`. Check `hasMore`.
+- Every event has a per-session `seq`. Incremental consumers should poll with `afterSeq: <lastSeq>` — it survives daemon restarts, rowid cursors don't.
+- Queries stop at `query.timeoutMs` (default 5s) and return `partial: true` with the rows read so far. Narrow the filter (eventType, time range, cursor); `debug_health` lists recent slow queries.
+- Crash events carry `dumpPath`: a JSON summary (all thread backtraces, module list) under `~/.strobe/crashes/<session>/` that outlives session deletion.
+- Call tree: `debug_timeline({ sessionId, eventId, action })` walks from a traced call to its `parent`, `children`, `prev`/`next` sibling, or the `stack` at that moment — no parentEventId bookkeeping needed.
+- Which code did that failing test actually run? `debug_coverage({ sessionId, action: \"start\" })` before, `action: \"stop\"` after: covered/total per source file plus a drcov (lighthouse) or lcov (genhtml) file.
+- Memory growing? `debug_heap({ sessionId, action: \"start\" })`, exercise the code, then `action: \"report\"`: top allocating call sites and the ones still holding live bytes (`leaks`, with sample addresses for `debug_memory`).
+- Regressed since a known-good run? Retain both sessions (same traces), then `debug_diff({ baseSessionId, sessionId })` lists functions only one run called, duration regressions and diverging return values.
+- Crash events also carry `history` (\"seen 3 times since Tuesday\"). `debug_crashes({ projectRoot })` lists every known crash signature for the project.
 
 ## Running Tests
 
 ALWAYS use `debug_test` — never `cargo test` or test binaries via bash. Only one test run at a time per project.
 `debug_test` returns a `testRunId` immediately. Poll with `debug_test({ action: \"status\", testRunId })` — server blocks up to 15s.
 Status includes `progress.currentTest`, `progress.warnings` (stuck detection), and `sessionId` for live tracing.
-When stuck warnings appear: add traces to investigate, then stop the session.
+When stuck warnings appear: add traces to investigate, then stop the session. A deadlock diagnosis on Linux names the threads, the mutexes they hold and the ones they wait for; `suggestedTraces` lists patterns worth adding (e.g. `@locks`).
 Do NOT pass `framework` unless auto-detection fails. For C++, provide `command` (path to test binary).
+Pass `instrumentation: \"none\"` for a quick pass/fail run without Frida (no tracing on that session).
+Large suite? `order: \"fail-fast-smart\"` runs likely failures first; the result lists them under `prioritized`.
+With `tracePatterns`, the result's `returnAnomalies` lists traced functions that return null, return an unusual value or run slower than in the last green traced run — check them before adding more traces.
+`toolchainChanges` lists compiler, runtime or lockfile changes since the project's previous run — a new failure or slowdown that comes with one may not be the code's fault.
+Helper binaries a test spawns (inside the project) are attached with the run's `trace` patterns before they start. Their events carry `pid` and `testName`; query them with `debug_query({ sessionId, testName })`.
 
 ## UI (macOS only)
 
@@ -858,7 +2008,116 @@ Do NOT pass `framework` unless auto-detection fails. For C++, provide `command`
     }
 
     async fn handle_tools_list(&self) -> Result<serde_json::Value> {
-        let tools = vec![
+        let response = McpToolsListResponse {
+            tools: self.tool_definitions(),
+        };
+        Ok(serde_json::to_value(response)?)
+    }
+
+    /// Running and retained sessions with their event and hook counts, for
+    /// the dashboard's session list. Running sessions come first.
+    pub(super) fn session_overview(&self) -> Result<Vec<serde_json::Value>> {
+        let db = self.session_manager.db();
+        let mut sessions = db.get_running_sessions()?;
+        for retained in db.list_retained_sessions()? {
+            if !sessions.iter().any(|s| s.id == retained.id) {
+                sessions.push(retained);
+            }
+        }
+        sessions
+            .iter()
+            .map(|s| {
+                Ok(serde_json::json!({
+                    "sessionId": s.id,
+                    "binaryPath": s.binary_path,
+                    "pid": s.pid,
+                    "status": s.status.as_str(),
+                    "startedAt": s.started_at,
+                    "retained": s.retained,
+                    "eventCount": db.count_session_events(&s.id)?,
+                    "hookedFunctions": self.session_manager.get_hook_count(&s.id),
+                }))
+            })
+            .collect()
+    }
+
+    /// Every tracked test run with its progress (running) or summary
+    /// (finished), for the HTTP API. Unlike debug_test status, reading it
+    /// doesn't mark finished runs as fetched.
+    pub(super) async fn test_run_overview(&self) -> Vec<serde_json::Value> {
+        let runs = self.test_runs.read().await;
+        let mut overview: Vec<serde_json::Value> = runs.values().map(test_run_entry).collect();
+        overview.sort_by(|a, b| a["testRunId"].as_str().cmp(&b["testRunId"].as_str()));
+        overview
+    }
+
+    /// Validate an event stream's debug_query filter and return the signal
+    /// fired whenever the session's writer stores new events.
+    pub(super) fn open_event_stream(
+        &self,
+        req: &DebugQueryRequest,
+    ) -> Result<Arc<tokio::sync::Notify>> {
+        req.validate()?;
+        if req.time_from.is_some() || req.time_to.is_some() {
+            return Err(crate::Error::ValidationError(
+                "timeFrom/timeTo are not supported on event streams; resume with afterSeq"
+                    .to_string(),
+            ));
+        }
+        self.require_session(&req.session_id)?;
+        Ok(self.session_manager.event_signal(&req.session_id))
+    }
+
+    /// Highest seq stored for the session (0 if none): where a live-only
+    /// stream starts.
+    pub(super) fn latest_event_seq(&self, session_id: &str) -> Result<i64> {
+        self.session_manager.db().max_seq_for_session(session_id)
+    }
+
+    /// Whether the session still exists and is running. Streams end once
+    /// this turns false and they have drained what was stored.
+    pub(super) fn session_running(&self, session_id: &str) -> bool {
+        matches!(
+            self.session_manager.get_session(session_id),
+            Ok(Some(session)) if session.status == crate::db::SessionStatus::Running
+        )
+    }
+
+    /// Next batch of a stream: up to `limit` events after `after_seq` that
+    /// match the filter, formatted as debug_query returns them.
+    pub(super) fn event_stream_batch(
+        &self,
+        req: &DebugQueryRequest,
+        after_seq: i64,
+        limit: u32,
+    ) -> Result<Vec<(i64, serde_json::Value)>> {
+        let log_fields = req.log_field_filters();
+        let verbose = req.verbose.unwrap_or(false) || req.select.is_some();
+        let events = self.session_manager.db().query_events_since_seq(
+            &req.session_id,
+            after_seq,
+            limit,
+            |q| apply_query_filters(q, req, &log_fields, None, None),
+        )?;
+        Ok(events
+            .iter()
+            .filter_map(|e| {
+                let value = format_event(e, verbose);
+                let value = match req.select {
+                    Some(ref paths) => select_event_fields(&value, paths),
+                    None => value,
+                };
+                Some((e.seq?, value))
+            })
+            .collect())
+    }
+
+    pub(super) fn has_tool(&self, name: &str) -> bool {
+        self.tool_definitions().iter().any(|t| t.name == name)
+    }
+
+    pub(super) fn tool_definitions(&self) -> Vec<McpTool> {
+        vec![
             // ---- Primary tools (8) ----
             McpTool {
                 name: "debug_launch".to_string(),
@@ -866,25 +2125,58 @@ Do NOT pass `framework` unless auto-detection fails. For C++, provide `command`
                 input_schema: serde_json::json!({
                     "type": "object",
                     "properties": {
-                        "command": { "type": "string", "description": "Path to executable" },
+                        "command": { "type": "string", "description": "Path to executable (optional with a profile that sets one)" },
                         "args": { "type": "array", "items": { "type": "string" }, "description": "Command line arguments" },
                         "cwd": { "type": "string", "description": "Working directory" },
                         "projectRoot": { "type": "string", "description": "Root directory for user code detection" },
                         "env": { "type": "object", "description": "Additional environment variables" },
-                        "symbolsPath": { "type": "string", "description": "Explicit path to debug symbols (.dSYM bundle, DWARF file, or directory containing .dSYM bundles). Use when automatic symbol resolution fails." }
+                        "symbolsPath": { "type": "string", "description": "Explicit path to debug symbols (.dSYM bundle, DWARF file, or directory containing .dSYM bundles). Use when automatic symbol resolution fails." },
+                        "symbolExtra": { "type": "array", "items": { "type": "string" }, "maxItems": 16, "description": "Absolute paths of shared libraries (e.g. a preloaded libengine.dylib) whose debug symbols are indexed alongside the main binary, so trace patterns and watches resolve into them from the start. Indexing status per library is in debug_session status. Native binaries only." },
+                        "crashCapture": {
+                            "type": "object",
+                            "description": "Globals whose memory the crash handler dumps into the crash event (`globals`: hex bytes per variable, with address, size and DWARF type) for postmortem decoding. Structs and arrays are captured whole. Launch waits for debug symbols to resolve them; skipped variables and failures come back in `warnings`. Native binaries only.",
+                            "properties": {
+                                "variables": { "type": "array", "items": { "type": "string" }, "minItems": 1, "maxItems": 16, "description": "Global names, e.g. [\"gEngineState\", \"gRingBuffer\"]" },
+                                "maxBytes": { "type": "integer", "minimum": 1, "maximum": 1048576, "description": "Byte budget across all variables, in order (default 65536). The variable crossing it is truncated; later ones are skipped." }
+                            },
+                            "required": ["variables"]
+                        },
+                        "captureFds": { "type": "array", "items": { "type": "integer", "minimum": 3 }, "maxItems": 8, "description": "Extra file descriptors to capture (e.g. [3] for a diagnostics fd). Stored as eventType 'aux_output' with channel 'fd:N'. Native binaries only." },
+                        "capturePipes": { "type": "array", "items": { "type": "string" }, "maxItems": 8, "description": "Absolute named-pipe (FIFO) paths to read; created if missing. Stored as eventType 'aux_output' with channel 'pipe:<path>'." },
+                        "onOutput": {
+                            "type": "array",
+                            "maxItems": 16,
+                            "description": "Run an action when a stdout/stderr chunk contains `match` (plain, case-sensitive). Each hit is recorded as an 'output_trigger' event.",
+                            "items": {
+                                "type": "object",
+                                "properties": {
+                                    "match": { "type": "string", "description": "Substring to look for" },
+                                    "action": { "type": "string", "enum": ["pause", "snapshot-watches", "annotate"], "description": "pause: SIGSTOP the process (resume with debug_continue); snapshot-watches: record active watch values as a variable_snapshot event; annotate: only record the marker" }
+                                },
+                                "required": ["match", "action"]
+                            }
+                        },
+                        "logFormat": { "type": "string", "enum": ["json", "logfmt"], "description": "Parse each stdout/stderr line as a structured log record (one event per line). Parsed fields appear as 'fields' on output events and can be filtered with debug_query fields." },
+                        "instrumentationFile": { "type": "string", "description": "File from debug_session export-instrumentation (relative to projectRoot; must stay inside it). Re-applies its patterns, watches, watchpoints, breakpoints, logpoints and settings to the new session." },
+                        "inheritInstrumentation": { "type": "boolean", "description": "true re-applies what this binary's last stopped session in the same projectRoot observed (patterns, variable watches, watchpoints, breakpoints, logpoints), resolved again against the new process; the response names the session under instrumentationFrom. Default: false" },
+                        "profile": { "type": "string", "description": "Named profile from .strobe/settings.json `profiles` (command, args, cwd, env, tracePatterns, watches, breakpoints). Arguments given in the call win; env is merged over the profile's" }
                     },
-                    "required": ["command", "projectRoot"]
+                    "required": ["projectRoot"]
                 }),
             },
             McpTool {
                 name: "debug_session".to_string(),
-                description: "Manage debug sessions: get status, stop, list retained, or delete. Use action to select operation.".to_string(),
+                description: "Manage debug sessions: get status, stop, relaunch a stopped session with the same argv/cwd/env, list retained, delete, export the active instrumentation to a file, reload the agent script (agent development), or check that traced calls pair up. Use action to select operation.".to_string(),
                 input_schema: serde_json::json!({
                     "type": "object",
                     "properties": {
-                        "action": { "type": "string", "enum": ["status", "stop", "list", "delete"], "description": "Action to perform" },
-                        "sessionId": { "type": "string", "description": "Session ID (required for status/stop/delete)" },
-                        "retain": { "type": "boolean", "description": "Retain session data for post-mortem debugging (default: false, only for action: 'stop')" }
+                        "action": { "type": "string", "enum": ["status", "stop", "list", "delete", "export-instrumentation", "reload-agent", "consistency-check", "relaunch"], "description": "Action to perform. relaunch starts a stopped or retained session's command again with the argv, cwd and environment it was launched with. reload-agent replaces the running agent script in place and re-installs the session's patterns, watches, watchpoints, breakpoints and logpoints. consistency-check counts orphan function_exit events (enter missing), unclosed function_enter events and exits stored before their enter, per thread" },
+                        "sessionId": { "type": "string", "description": "Session ID (required for all actions but list)" },
+                        "retain": { "type": "boolean", "description": "Retain session data for post-mortem debugging (default: false, or true for sessions of an open debug_investigation; only for action: 'stop')" },
+                        "path": { "type": "string", "description": "Output file for export-instrumentation, relative to the project root and inside it (default: .strobe/instrumentation/<sessionId>.json). For reload-agent: agent bundle to load, e.g. a freshly built agent/dist/agent.js (default: the agent built into the daemon)" },
+                        "repair": { "type": "boolean", "description": "consistency-check only: also move exits stored before their enter back after it (default: false)" },
+                        "inheritInstrumentation": { "type": "boolean", "description": "relaunch only: re-apply the binary's last trace patterns, watches, watchpoints, breakpoints and logpoints (default: true)" },
+                        "projectRoot": { "type": "string", "description": "list only: just the retained sessions of this project" }
                     },
                     "required": ["action"]
                 }),
@@ -896,9 +2188,59 @@ Do NOT pass `framework` unless auto-detection fails. For C++, provide `command`
                     "type": "object",
                     "properties": {
                         "sessionId": { "type": "string", "description": "Session ID. Omit to set pending patterns for the next debug_launch. Provide to modify a running session." },
-                        "add": { "type": "array", "items": { "type": "string" }, "description": "Patterns to start tracing (e.g. \"mymodule::*\", \"*::init\", \"@usercode\")" },
+                        "add": { "type": "array", "items": { "type": "string" }, "description": "Patterns to start tracing (e.g. \"mymodule::*\", \"*::init\", \"@usercode\"). \"@syscalls:file\" / \"@syscalls:net\" trace libc file / socket calls as syscall events; \"@network\" records connects, DNS lookups and TLS server names and bytes as network events; \"@exceptions\" records C++ throws and the catch site each one reached as exception events; \"@signals\" records handler deliveries and kill/raise calls as signal events; \"@locks\" records contended and blocked mutex waits as lock events (all require sessionId)" },
                         "remove": { "type": "array", "items": { "type": "string" }, "description": "Patterns to stop tracing" },
-                        "serializationDepth": { "type": "integer", "description": "Maximum depth for recursive argument serialization (default: 3, max: 10)", "minimum": 1, "maximum": 10 },
+                        "mode": { "type": "string", "enum": ["events", "count", "rt"], "description": "How the 'add' patterns are hooked. events (default): function_enter/exit events. count: in-agent call counters only, no per-call events, up to 5000 native functions per call; totals appear in debug_session status and as periodic call_counts events. rt: function_enter/exit events from native-only hooks safe on real-time (audio) threads — no JS, no locks, no calls out of the hook. Requires sessionId." },
+                        "sampling": {
+                            "type": "object",
+                            "description": "Periodic stack sampling of the target's threads, recorded as 'sample' events (function = leaf frame, backtrace = full stack). Needs no trace patterns. Requires sessionId.",
+                            "properties": {
+                                "enabled": { "type": "boolean", "description": "true starts or retargets sampling, false stops it" },
+                                "hz": { "type": "integer", "minimum": 1, "maximum": 1000, "description": "Samples per second per thread (default: settings \"sampling.hz\", 100)" },
+                                "threadIds": { "type": "array", "items": { "type": "integer" }, "description": "Only sample these OS thread ids" },
+                                "threadName": { "type": "string", "description": "Only sample threads whose name contains this substring" }
+                            },
+                            "required": ["enabled"]
+                        },
+                        "threads": {
+                            "type": "object",
+                            "description": "Only record traced calls from matching threads (name or id); the agent drops the rest before they are sent. Pass {} to trace all threads again. Native tracing only. Requires sessionId.",
+                            "properties": {
+                                "nameContains": { "type": "string", "description": "Threads whose name contains this substring" },
+                                "ids": { "type": "array", "items": { "type": "integer" }, "description": "OS thread ids" }
+                            }
+                        },
+                        "rateLimit": {
+                            "type": "object",
+                            "description": "Per-function cap on recorded calls, so a hot loop doesn't use up the event budget. The agent keeps or drops each call whole (enter and exit); kept calls of a function that is being thinned are marked sampled: true. Pass {} to record every call again. Native tracing only. Requires sessionId.",
+                            "properties": {
+                                "maxPerSecond": { "type": "integer", "minimum": 1, "maximum": 1000000, "description": "Record at most N calls per function per second" },
+                                "sampleEvery": { "type": "integer", "minimum": 1, "maximum": 1000000, "description": "Record one call in every K per function" }
+                            }
+                        },
+                        "flightRecorder": {
+                            "type": "object",
+                            "description": "Always-on tracing for intermittent bugs: traced calls stay in an in-memory ring buffer in the agent and are written to the database only when a trigger fires (crash, breakpoint hit, a stderr line containing one of flushOnOutput, or flush: true). Output and crashes are recorded as usual. enabled: false writes out what is buffered and records directly again. Native tracing only. Requires sessionId.",
+                            "properties": {
+                                "enabled": { "type": "boolean" },
+                                "capacity": { "type": "integer", "minimum": 1, "maximum": 1000000, "description": "Traced events kept; older ones are overwritten (default: 50000)" },
+                                "flushOnOutput": { "type": "array", "items": { "type": "string" }, "maxItems": 16, "description": "stderr substrings that flush the buffer (default: Assertion, assertion failed, panicked at, AddressSanitizer). [] disables the stderr trigger" }
+                            },
+                            "required": ["enabled"]
+                        },
+                        "flush": { "type": "boolean", "description": "Write the flight recorder buffer to the database now. Requires sessionId" },
+                        "signalSafety": {
+                            "type": "object",
+                            "description": "Audit signal handlers: hooks sigaction/signal and the handlers already installed, and while a thread runs a handler records every call to a function that is not async-signal-safe as a signal_safety event (handler, signal, backtrace), once per handler/function/call site. The function hooks are installed the first time a handler runs. enabled: false stops the audit. Native only. Requires sessionId.",
+                            "properties": {
+                                "enabled": { "type": "boolean" },
+                                "functions": { "type": "array", "items": { "type": "string" }, "minItems": 1, "maxItems": 64, "description": "Exported functions to flag instead of the built-in list (malloc/free/new/delete, stdio, pthread mutex/rwlock/cond, exit/atexit, localtime/gmtime/strerror/getenv)" }
+                            },
+                            "required": ["enabled"]
+                        },
+                        "lint": { "type": "array", "items": { "type": "string" }, "maxItems": 100, "description": "Check patterns for common mistakes (bare *, directory-wide @file:, parameter lists, template arguments, and with sessionId namespaces missing from the debug symbols) and get rewrite suggestions. Nothing is hooked; can't be combined with add/remove" },
+                        "preview": { "type": "array", "items": { "type": "string" }, "maxItems": 20, "description": "List the functions these patterns would hook in the session (names, source files, per-file counts) and the distinct total against the per-call hook limit. Nothing is hooked; needs sessionId and can't be combined with add/remove/lint" },
+                        "serializationDepth": { "type": "integer", "description": "Maximum depth for recursive argument serialization (default: 3, max: 10). Setting it also decodes std::string/vector/map and Rust String/Vec arguments from DWARF instead of showing raw pointers", "minimum": 1, "maximum": 10 },
                         "projectRoot": { "type": "string", "description": "Root directory for user code detection" },
                         "watches": {
                             "type": "object",
@@ -918,7 +2260,8 @@ Do NOT pass `framework` unless auto-detection fails. For C++, provide `command`
                                                 "type": "array",
                                                 "items": { "type": "string" },
                                                 "description": "Optional function patterns to scope this watch (e.g. ['NoteOn', 'audio::*']). Supports wildcards: * (shallow, stops at ::), ** (deep, crosses ::). If omitted, watch is global (captured on all traced functions)."
-                                            }
+                                            },
+                                            "deltaThreshold": { "type": "number", "description": "Record the value only when it changed by at least this much since the last recorded value (e.g. 100 for a noisy counter). Recorded values become { value, skipped } where skipped counts the reads dropped in between." }
                                         }
                                     }
                                 },
@@ -939,7 +2282,7 @@ Do NOT pass `framework` unless auto-detection fails. For C++, provide `command`
                     "type": "object",
                     "properties": {
                         "sessionId": { "type": "string" },
-                        "eventType": { "type": "string", "enum": ["function_enter", "function_exit", "stdout", "stderr", "crash", "variable_snapshot", "pause", "logpoint", "condition_error"] },
+                        "eventType": { "type": "string", "enum": ["function_enter", "function_exit", "stdout", "stderr", "aux_output", "crash", "variable_snapshot", "pause", "logpoint", "condition_error", "watchpoint", "output_trigger", "call_counts", "sample", "signal_safety", "leak", "syscall", "network", "rt_warning", "recovery", "panic", "exception", "signal", "lock", "config_change"] },
                         "function": {
                             "type": "object",
                             "properties": {
@@ -968,6 +2311,27 @@ Do NOT pass `framework` unless auto-detection fails. For C++, provide `command`
                                 "contains": { "type": "string" }
                             }
                         },
+                        "queueName": {
+                            "type": "object",
+                            "description": "libdispatch queue label the call ran on (GCD apps, e.g. \"com.apple.main-thread\")",
+                            "properties": {
+                                "equals": { "type": "string" },
+                                "contains": { "type": "string" }
+                            }
+                        },
+                        "fields": {
+                            "type": "object",
+                            "description": "Structured log field equality on stdout/stderr lines parsed by launch logFormat, e.g. { \"level\": \"error\", \"module\": \"auth\" }. Values match case-insensitively",
+                            "additionalProperties": { "type": ["string", "number", "boolean"] }
+                        },
+                        "text": {
+                            "type": "object",
+                            "description": "Search stdout/stderr/aux_output text. contains is indexed and case-insensitive; regex uses Rust regex syntax",
+                            "properties": {
+                                "contains": { "type": "string" },
+                                "regex": { "type": "string" }
+                            }
+                        },
                         "timeFrom": {
                             "description": "Filter from this time. Integer (absolute ns) or string (\"-5s\", \"-1m\", \"-500ms\")"
                         },
@@ -982,17 +2346,48 @@ Do NOT pass `framework` unless auto-detection fails. For C++, provide `command`
                             "type": "integer",
                             "description": "Filter by process ID (for multi-process sessions)"
                         },
+                        "testName": {
+                            "type": "string",
+                            "description": "debug_test sessions: events from helper processes spawned while this test was running"
+                        },
+                        "callId": {
+                            "type": "string",
+                            "description": "Both events of one traced call: function_enter and function_exit carry the same callId"
+                        },
+                        "format": {
+                            "type": "string",
+                            "enum": ["events", "paired"],
+                            "default": "events",
+                            "description": "paired: one record per completed call with arguments (from the enter), returnValue and duration_ns instead of separate enter/exit events"
+                        },
                         "limit": { "type": "integer", "default": 50, "maximum": 500 },
                         "offset": { "type": "integer" },
                         "verbose": { "type": "boolean", "default": false },
-                        "afterEventId": { "type": "integer", "description": "Cursor: return only events with rowid > afterEventId (for incremental polling)" }
+                        "afterEventId": { "type": "integer", "description": "Cursor: return only events with rowid > afterEventId (for incremental polling)" },
+                        "afterSeq": { "type": "integer", "description": "Cursor: return only events with seq > afterSeq, oldest first, so paging with lastSeq never skips events. Seqs are per-session, assigned at write time and stay monotonic across daemon restarts — prefer this for exactly-once consumers" },
+                        "select": {
+                            "type": "array",
+                            "items": { "type": "string" },
+                            "maxItems": 32,
+                            "description": "Return only these fields per event, e.g. [\"function\", \"duration_ns\", \"watchValues.gTempo\"]. Dotted paths select into JSON values (arguments, watchValues, fields); missing paths are omitted"
+                        },
+                        "distinct": {
+                            "type": "string",
+                            "enum": ["function", "threadName", "sourceFile"],
+                            "description": "Overview instead of events: the sorted distinct values of this field among matching events (\"which functions fired?\"). limit/offset page through the values"
+                        },
+                        "groupBy": {
+                            "type": "string",
+                            "enum": ["function", "threadName", "sourceFile"],
+                            "description": "Overview instead of events: matching events counted per value of this field, most frequent first. limit/offset page through the groups"
+                        }
                     },
                     "required": ["sessionId"]
                 }),
             },
             McpTool {
                 name: "debug_breakpoint".to_string(),
-                description: "Set or remove breakpoints and logpoints. Pauses execution when hit (breakpoint) or logs a message without pausing (logpoint, when 'message' is present). Use debug_continue to resume after breakpoint pause. Supports function names, file:line, conditions, and hit counts.".to_string(),
+                description: "Set or remove breakpoints and logpoints. Pauses execution when hit (breakpoint) or logs a message without pausing (logpoint, when 'message' is present). Use debug_continue to resume after breakpoint pause. Supports function names, function+offset, file:line, raw addresses (breakpoints only), conditions, and hit counts.".to_string(),
                 input_schema: serde_json::json!({
                     "type": "object",
                     "properties": {
@@ -1003,11 +2398,15 @@ Do NOT pass `framework` unless auto-detection fails. For C++, provide `command`
                                 "type": "object",
                                 "properties": {
                                     "function": { "type": "string", "description": "Function name or pattern" },
+                                    "offset": { "type": "integer", "minimum": 0, "description": "Bytes into 'function' to break at, e.g. 0x24 from a disassembly (breakpoints only)" },
+                                    "address": { "type": "string", "description": "Static address in the binary, hex (e.g. '0x10000abcd'), for code whose line info is broken or missing (breakpoints only)" },
                                     "file": { "type": "string", "description": "Source file path" },
                                     "line": { "type": "integer", "description": "Line number (required with file)" },
-                                    "condition": { "type": "string", "description": "JS condition: e.g. 'args[0] > 100'" },
-                                    "hitCount": { "type": "integer", "description": "Break after N hits (breakpoints only)" },
-                                    "message": { "type": "string", "description": "Log message template — if present, creates a logpoint instead of breakpoint. Use {args[0]} etc for arguments." }
+                                    "condition": { "type": "string", "description": "JS condition: e.g. 'args[0] > 100'. watch:<label> reads a watch set with debug_trace, e.g. 'watch:gTempo > 200' (native sessions)" },
+                                    "hitCount": { "type": "integer", "minimum": 1, "description": "Breakpoints: pause on the Nth hit only. Logpoints: log from the Nth hit on" },
+                                    "everyN": { "type": "integer", "minimum": 1, "description": "Logpoints only: log every Nth hit, e.g. 1000 for an audio callback" },
+                                    "maxHits": { "type": "integer", "minimum": 1, "description": "Logpoints only: stop logging after this many messages" },
+                                    "message": { "type": "string", "description": "Log message template — if present, creates a logpoint instead of breakpoint. Use {args[0]} etc for arguments, or a function's parameters by name with members ({velocity}, {buffer.sample_rate})." }
                                 }
                             }
                         },
@@ -1020,26 +2419,64 @@ Do NOT pass `framework` unless auto-detection fails. For C++, provide `command`
                     "required": ["sessionId"]
                 }),
             },
+            McpTool {
+                name: "debug_watchpoint".to_string(),
+                description: "Set or remove data watchpoints. Records a 'watchpoint' event with the writing function and backtrace whenever the watched variable or address range is written. Does not pause. Native binaries only.".to_string(),
+                input_schema: serde_json::json!({
+                    "type": "object",
+                    "properties": {
+                        "sessionId": { "type": "string" },
+                        "add": {
+                            "type": "array",
+                            "items": {
+                                "type": "object",
+                                "properties": {
+                                    "variable": { "type": "string", "description": "Global/static variable name (DWARF)" },
+                                    "address": { "type": "string", "description": "Raw runtime address (hex), e.g. from debug_memory" },
+                                    "size": { "type": "integer", "description": "Bytes to watch (default: variable size; required with address)" },
+                                    "label": { "type": "string", "description": "Display name for events" }
+                                }
+                            }
+                        },
+                        "remove": {
+                            "type": "array",
+                            "items": { "type": "string" },
+                            "description": "Watchpoint IDs to remove"
+                        }
+                    },
+                    "required": ["sessionId"]
+                }),
+            },
             McpTool {
                 name: "debug_continue".to_string(),
-                description: "Resume execution after a breakpoint pause. Supports stepping: continue (resume all), step-over (next line), step-into (into calls), step-out (to caller).".to_string(),
+                description: "Resume execution after a breakpoint pause. Supports stepping: continue (resume all), step-over (next line), step-into (into calls), step-out (to caller). `until` runs to a function or line and pauses there once; `count` repeats a step and returns where the last one paused.".to_string(),
                 input_schema: serde_json::json!({
                     "type": "object",
                     "properties": {
                         "sessionId": { "type": "string" },
-                        "action": { "type": "string", "enum": ["continue", "step-over", "step-into", "step-out"], "description": "Default: continue" }
+                        "action": { "type": "string", "enum": ["continue", "step-over", "step-into", "step-out"], "description": "Default: continue" },
+                        "until": {
+                            "type": "object",
+                            "description": "With action continue: pause at this function or file+line (a temporary breakpoint, removed by the next debug_continue)",
+                            "properties": {
+                                "function": { "type": "string" },
+                                "file": { "type": "string" },
+                                "line": { "type": "integer" }
+                            }
+                        },
+                        "count": { "type": "integer", "minimum": 1, "maximum": 100, "description": "With a step action: take this many steps in one call, waiting for each. Stops early at a breakpoint, or returns status running if a step doesn't pause within 10s" }
                     },
                     "required": ["sessionId"]
                 }),
             },
             McpTool {
                 name: "debug_memory".to_string(),
-                description: "Read or write memory in a running process. Supports DWARF-resolved variables, pointer chains, struct expansion, raw addresses, and polling mode for timeline integration.".to_string(),
+                description: "Read or write memory in a running process. Supports DWARF-resolved variables, pointer chains, struct expansion, raw addresses, annotated hexdumps of variables or memory regions, and polling mode for timeline integration. A poll reads every target at one shared timestamp per tick; action 'series' returns a poll's samples as aligned columns for plotting, or writes them as CSV. Action 'locals' reads the local variables of a paused thread's frame: frame 0 where it paused, or a caller up the stack.".to_string(),
                 input_schema: serde_json::json!({
                     "type": "object",
                     "properties": {
                         "sessionId": { "type": "string" },
-                        "action": { "type": "string", "enum": ["read", "write"], "description": "Default: read" },
+                        "action": { "type": "string", "enum": ["read", "write", "series", "locals"], "description": "Default: read. series: samples of a poll (pollId, default the latest) as timestampsNs plus one column per target. locals: DWARF locals of a paused native thread's frame (threadId, frame)" },
                         "targets": {
                             "type": "array",
                             "items": {
@@ -1049,21 +2486,50 @@ Do NOT pass `framework` unless auto-detection fails. For C++, provide `command`
                                     "address": { "type": "string", "description": "Hex address for raw memory reads" },
                                     "size": { "type": "integer", "description": "Size in bytes (required for raw address)" },
                                     "type": { "type": "string", "description": "Type: i8/u8/i16/u16/i32/u32/i64/u64/f32/f64/pointer/bytes" },
+                                    "format": { "type": "string", "enum": ["value", "hexdump"], "description": "'hexdump' returns offset/hex/ASCII lines annotated with struct member names (default: value). Reads up to 4096 bytes: the variable's size, or 'size' for addresses" },
+                                    "region": {
+                                        "type": "object",
+                                        "description": "Raw memory range dumped as a hexdump, instead of variable/address",
+                                        "properties": {
+                                            "base": { "type": "string", "description": "Hex start address" },
+                                            "length": { "type": "integer", "description": "Bytes to dump (1-4096)", "minimum": 1, "maximum": 4096 }
+                                        },
+                                        "required": ["base", "length"]
+                                    },
                                     "value": { "description": "Value to write (required for action: 'write')" }
                                 }
                             },
-                            "description": "1-16 read/write targets"
+                            "description": "1-16 read/write targets (required for read and write)"
                         },
-                        "depth": { "type": "integer", "description": "Struct traversal depth (default 1, max 5)", "minimum": 1, "maximum": 5 },
+                        "depth": { "type": "integer", "description": "Struct nesting levels rendered from the DWARF type (default 1, max 5). Struct/union/array globals, enums (by enumerator name), char* and char[N] (as strings) and struct pointers are read as a tree; structs past the depth show as <TypeName>", "minimum": 1, "maximum": 5 },
                         "poll": {
                             "type": "object",
                             "properties": {
                                 "intervalMs": { "type": "integer", "description": "Poll interval in ms (50-5000)", "minimum": 50, "maximum": 5000 },
                                 "durationMs": { "type": "integer", "description": "Poll duration in ms (100-30000)", "minimum": 100, "maximum": 30000 }
                             }
-                        }
+                        },
+                        "pollId": { "type": "string", "description": "series: poll to read, as returned when it started (default: the session's latest poll)" },
+                        "csv": { "type": "boolean", "description": "series: write a CSV file (timestamp_ns, then one column per target) and return its path instead of the columns" },
+                        "outputPath": { "type": "string", "description": "series with csv: destination file inside the project; relative paths resolve against projectRoot (default: <projectRoot>/exports/<sessionId>-<pollId>.csv)" },
+                        "threadId": { "type": "integer", "description": "locals: paused thread (default: first paused thread)" },
+                        "frame": { "type": "integer", "description": "locals: 0 = where the thread paused (default), N = its Nth caller. Callers are found through frame pointers; only their stack locals are shown", "minimum": 0, "maximum": 64 }
+                    },
+                    "required": ["sessionId"]
+                }),
+            },
+            McpTool {
+                name: "debug_eval".to_string(),
+                description: "Evaluate an expression in a native session, typically while paused at a breakpoint. Supports globals and pointer chains via DWARF ('gClock->counter'), the paused frame's locals and their members ('buf.len', 'chain->next->params->gain'; a caller's with frame: N), the paused frame's arguments (arg0, arg1, ...), integer/float arithmetic, comparisons, && / || / !, and calls into the target with up to 6 integer/pointer arguments ('lookup(arg0, 2)'). Every call in the expression runs, even behind && / ||; a call that needs a lock the paused thread holds will hang.".to_string(),
+                input_schema: serde_json::json!({
+                    "type": "object",
+                    "properties": {
+                        "sessionId": { "type": "string" },
+                        "expr": { "type": "string", "description": "Expression, e.g. 'gBuffer->len * 2 > arg1'" },
+                        "threadId": { "type": "integer", "description": "Paused thread for locals and argN (default: first paused thread)" },
+                        "frame": { "type": "integer", "description": "Frame whose locals names resolve to: 0 = where the thread paused (default), N = its Nth caller", "minimum": 0, "maximum": 64 }
                     },
-                    "required": ["sessionId", "targets"]
+                    "required": ["sessionId", "expr"]
                 }),
             },
             McpTool {
@@ -1072,14 +2538,14 @@ Do NOT pass `framework` unless auto-detection fails. For C++, provide `command`
                 input_schema: serde_json::json!({
                     "type": "object",
                     "properties": {
-                        "action": { "type": "string", "enum": ["run", "status"], "description": "Action: 'run' (default) starts a test, 'status' polls for results" },
+                        "action": { "type": "string", "enum": ["run", "status", "list"], "description": "Action: 'run' (default) starts a test, 'status' polls for results, 'list' returns the test inventory (names, files, estimated durations, last status) without running anything" },
                         "testRunId": { "type": "string", "description": "Test run ID (required for action: 'status')" },
-                        "projectRoot": { "type": "string", "description": "Project root for adapter detection (required for action: 'run')" },
+                        "projectRoot": { "type": "string", "description": "Project root for adapter detection (required for action: 'run' and 'list')" },
                         "framework": { "type": "string", "enum": ["cargo", "catch2", "pytest", "unittest", "vitest", "jest", "bun", "deno", "go", "mocha", "gtest"], "description": "Override auto-detection. Usually not needed — framework is detected from projectRoot or command." },
                         "level": { "type": "string", "enum": ["unit", "integration", "e2e"], "description": "Filter: unit, integration, e2e. Omit for all." },
                         "test": { "type": "string", "description": "Run a single test by name (substring match — e.g. 'stuck_detector' runs all tests containing that string)" },
                         "command": { "type": "string", "description": "Path to test binary. Required for C++/Catch2 projects." },
-                        "tracePatterns": { "type": "array", "items": { "type": "string" }, "description": "Trace patterns to apply immediately (tests always run inside Frida)" },
+                        "tracePatterns": { "type": "array", "items": { "type": "string" }, "description": "Trace patterns to apply immediately (requires instrumentation: 'frida')" },
                         "watches": {
                             "type": "object",
                             "description": "Watch variables during test execution",
@@ -1089,36 +2555,232 @@ Do NOT pass `framework` unless auto-detection fails. For C++, provide `command`
                             }
                         },
                         "env": { "type": "object", "description": "Additional environment variables" },
+                        "instrumentation": { "type": "string", "enum": ["frida", "none"], "description": "'frida' (default) runs tests inside Frida so traces can be added live. 'none' runs a plain subprocess — faster startup and works where Frida cannot attach, but tracing, breakpoints, watches and memory access are unavailable." },
+                        "order": { "type": "string", "enum": ["default", "fail-fast-smart"], "description": "'fail-fast-smart' runs recently-failed tests, then tests touched by recent git changes, first so failures show up in early status polls. Full-suite runs only; reordering currently supported for pytest (other frameworks run unchanged)." },
                         "timeout": { "type": "integer", "description": "Hard timeout in milliseconds. Overrides adapter default and settings.json. Falls back to: settings.json test.timeoutMs → adapter default (e.g. 600s Playwright, 60-300s bun)." }
                     }
                 }),
             },
             McpTool {
-                name: "debug_ui".to_string(),
-                description: "Query the UI state of a running process. Returns accessibility tree (native widgets) and/or a screenshot saved as PNG to <projectRoot>/screenshots/. Use mode to select output. Pass 'id' with screenshot/both mode to crop to a specific element.".to_string(),
+                name: "debug_export".to_string(),
+                description: "Export a session's events to a trace file for external viewers. 'chrome_trace' writes Chrome Trace Event JSON (function enter/exit as nested spans per thread, stdout/stderr/crashes as instant markers) — open it in ui.perfetto.dev or chrome://tracing. Returns the file path.".to_string(),
                 input_schema: serde_json::json!({
                     "type": "object",
                     "properties": {
-                        "sessionId": { "type": "string", "description": "Session ID (from debug_launch)" },
-                        "mode": { "type": "string", "enum": ["tree", "screenshot", "both"], "description": "Output mode: tree (UI element hierarchy), screenshot (PNG image), or both" },
-                        "id": { "type": "string", "description": "Target node ID from debug_ui tree. When provided with screenshot or both mode, crops the screenshot to this element's bounds." },
-                        "verbose": { "type": "boolean", "description": "Return JSON instead of compact text (default: false)" }
+                        "sessionId": { "type": "string", "description": "Session ID (running or retained)" },
+                        "format": { "type": "string", "enum": ["chrome_trace"], "description": "Export format (default: chrome_trace)" },
+                        "outputPath": { "type": "string", "description": "Destination file (default: /tmp/strobe/exports/<sessionId>.trace.json)" }
                     },
-                    "required": ["sessionId", "mode"]
+                    "required": ["sessionId"]
                 }),
             },
             McpTool {
-                name: "debug_ui_action".to_string(),
-                description: "Perform a UI action on a running process. Actions: click, set_value, type, key, scroll, drag. Uses accessibility actions when available, falls back to synthesized input events. Returns before/after node state for verification.".to_string(),
+                name: "debug_crashes".to_string(),
+                description: "List known crash signatures for a project, deduplicated across sessions (signal + top 5 frames). Each entry has occurrence count, first/last seen, the last session and its crash dump path, and a history line like \"seen 14 times since Tuesday\".".to_string(),
                 input_schema: serde_json::json!({
                     "type": "object",
                     "properties": {
-                        "sessionId": { "type": "string", "description": "Session ID (from debug_launch)" },
-                        "action": { "type": "string", "enum": ["click", "set_value", "type", "key", "scroll", "drag"], "description": "Action to perform" },
-                        "id": { "type": "string", "description": "Target node ID from debug_ui tree. Required for all except 'key'." },
-                        "value": { "description": "Value to set (number or string). Required for 'set_value'." },
-                        "text": { "type": "string", "description": "Text to type. Required for 'type'." },
-                        "key": { "type": "string", "description": "Key name (e.g. 's', 'return', 'escape'). Required for 'key'." },
+                        "projectRoot": { "type": "string", "description": "Project root to list crashes for" },
+                        "sessionId": { "type": "string", "description": "Alternatively, use this session's project root" },
+                        "limit": { "type": "integer", "default": 20, "maximum": 200 }
+                    }
+                }),
+            },
+            McpTool {
+                name: "debug_health".to_string(),
+                description: "Daemon health: active sessions, the debug_query statement timeout, and the most recent slow (>250ms) or timed-out debug_query calls with their filters — use it to spot filters that need narrowing (add eventType, timeFrom/timeTo or a cursor).".to_string(),
+                input_schema: serde_json::json!({
+                    "type": "object",
+                    "properties": {}
+                }),
+            },
+            McpTool {
+                name: "debug_config".to_string(),
+                description: "Effective settings and where each value came from: built-in default, ~/.strobe/settings.json, the project's .strobe/settings.json, or a STROBE_* environment variable (e.g. STROBE_EVENTS_MAX_PER_SESSION), later layers winning. Also lists every setting that was skipped — unknown key, wrong type, out of range — which is otherwise only a daemon log warning.".to_string(),
+                input_schema: serde_json::json!({
+                    "type": "object",
+                    "properties": {
+                        "projectRoot": { "type": "string", "description": "Include this project's settings file" },
+                        "sessionId": { "type": "string", "description": "Alternatively, use this session's project root" }
+                    }
+                }),
+            },
+            McpTool {
+                name: "debug_timeline".to_string(),
+                description: "Walk the recorded call tree from a traced call (function_enter or function_exit id from debug_query): parent, children, prev/next sibling on the same thread, or the call stack at a point in time. Each call comes with its exit (return value, duration) when recorded.".to_string(),
+                input_schema: serde_json::json!({
+                    "type": "object",
+                    "properties": {
+                        "sessionId": { "type": "string" },
+                        "action": { "type": "string", "enum": ["event", "parent", "children", "prev", "next", "stack"], "description": "event: the call itself. parent: its caller. children: its direct callees in order. prev/next: neighbouring call under the same caller. stack: calls active at eventId's time, or at timestampNs on threadId, innermost first" },
+                        "eventId": { "type": "string", "description": "Call to navigate from" },
+                        "timestampNs": { "type": "integer", "description": "stack: point in time (use with threadId)" },
+                        "threadId": { "type": "integer", "description": "stack: thread to rebuild" },
+                        "limit": { "type": "integer", "default": 50, "maximum": 200, "description": "children page size" },
+                        "offset": { "type": "integer", "description": "children page offset" },
+                        "verbose": { "type": "boolean", "description": "Full event fields (arguments, parentEventId, ...)" }
+                    },
+                    "required": ["sessionId", "action"]
+                }),
+            },
+            McpTool {
+                name: "debug_diff".to_string(),
+                description: "Compare the traces of two sessions of the same binary (\"it worked yesterday\"): functions called in only one of them, per-function average duration regressions, and functions whose return values differ for the same call index. Trace the same patterns in both; retained sessions work.".to_string(),
+                input_schema: serde_json::json!({
+                    "type": "object",
+                    "properties": {
+                        "baseSessionId": { "type": "string", "description": "Known-good session" },
+                        "sessionId": { "type": "string", "description": "Session to compare against the base" },
+                        "minRatio": { "type": "number", "minimum": 1.0, "description": "Minimum avg duration ratio reported as a regression (default 1.5; increases under 10µs are ignored)" },
+                        "limit": { "type": "integer", "default": 50, "maximum": 500, "description": "Entries per list" }
+                    },
+                    "required": ["baseSessionId", "sessionId"]
+                }),
+            },
+            McpTool {
+                name: "debug_coverage".to_string(),
+                description: "Basic-block coverage of a running session via Frida Stalker: start following all threads, then snapshot or stop to get covered/total per source file (lines or functions, via debug symbols) and a DRCOV or lcov file. Stalker slows the process down noticeably while collecting.".to_string(),
+                input_schema: serde_json::json!({
+                    "type": "object",
+                    "properties": {
+                        "sessionId": { "type": "string" },
+                        "action": { "type": "string", "enum": ["start", "snapshot", "stop"], "description": "start: begin collecting. snapshot: report and write the file, keep collecting. stop: report, write, and stop collecting" },
+                        "scope": { "type": "string", "enum": ["usercode", "all"], "description": "start: usercode (default) records the executable and reports project sources only; all records every module" },
+                        "granularity": { "type": "string", "enum": ["block", "function"], "description": "block (default): per-file covered/total source lines. function: per-file covered/total functions" },
+                        "format": { "type": "string", "enum": ["drcov", "lcov"], "description": "drcov (default, any binary) or lcov (needs debug symbols)" },
+                        "outputPath": { "type": "string", "description": "Destination file inside the project; relative paths resolve against projectRoot (default /tmp/strobe/coverage/<sessionId>.drcov or .info). The response lists at most 200 files; the file has every block" }
+                    },
+                    "required": ["sessionId", "action"]
+                }),
+            },
+            McpTool {
+                name: "debug_heap".to_string(),
+                description: "Allocation tracking for a running session: hooks malloc/calloc/realloc/free, C++ new/delete and Rust's allocator shims, and records each allocation's size and call site. report/stop list the call sites that allocated the most bytes and those still holding live (possibly leaked) memory, with example addresses. Stopping a session while tracking runs includes a final report. Every allocation goes through a hook, so allocation-heavy code slows down.".to_string(),
+                input_schema: serde_json::json!({
+                    "type": "object",
+                    "properties": {
+                        "sessionId": { "type": "string" },
+                        "action": { "type": "string", "enum": ["start", "report", "stop"], "description": "start: hook the allocators. report: top allocators and live allocations so far, keep tracking. stop: report and unhook" },
+                        "limit": { "type": "integer", "minimum": 1, "maximum": 200, "description": "report/stop: call sites per list (default 20)" }
+                    },
+                    "required": ["sessionId", "action"]
+                }),
+            },
+            McpTool {
+                name: "debug_symbols".to_string(),
+                description: "Search a native session's debug symbols without hooking anything: functions by name (case-insensitive substring or regex) and source file, globals with types, sizes and static addresses, or function/global/source-file counts for the executable and each symbolExtra library. Use it to find names for trace patterns, breakpoints and watches.".to_string(),
+                input_schema: serde_json::json!({
+                    "type": "object",
+                    "properties": {
+                        "sessionId": { "type": "string" },
+                        "action": { "type": "string", "enum": ["functions", "globals", "modules"], "description": "functions: search functions. globals: search global variables. modules: counts per indexed binary" },
+                        "query": { "type": "string", "maxLength": 256, "description": "functions/globals: substring of the name, case-insensitive (default: all). Demangled and linkage names both match" },
+                        "regex": { "type": "boolean", "description": "Read query as a regular expression (case-sensitive)" },
+                        "file": { "type": "string", "maxLength": 256, "description": "functions: only those from source files whose path contains this" },
+                        "limit": { "type": "integer", "minimum": 1, "maximum": 1000, "description": "functions/globals: results listed (default 50); matched counts them all" }
+                    },
+                    "required": ["sessionId", "action"]
+                }),
+            },
+            McpTool {
+                name: "debug_batch".to_string(),
+                description: "Run several tool calls in one round trip, in order, e.g. debug_session status then debug_query stderr then debug_query stdout. sessionId is checked once and filled into calls that don't set their own. Stops at the first failing call; its error is the last result and the rest are skipped. debug_ui, debug_ui_action and nested batches are not allowed.".to_string(),
+                input_schema: serde_json::json!({
+                    "type": "object",
+                    "properties": {
+                        "sessionId": { "type": "string", "description": "Shared session for every call" },
+                        "calls": {
+                            "type": "array",
+                            "minItems": 1,
+                            "maxItems": 16,
+                            "items": {
+                                "type": "object",
+                                "properties": {
+                                    "tool": { "type": "string", "description": "Tool name, e.g. \"debug_query\"" },
+                                    "arguments": { "type": "object", "description": "That tool's arguments" }
+                                },
+                                "required": ["tool"]
+                            }
+                        }
+                    },
+                    "required": ["calls"]
+                }),
+            },
+            McpTool {
+                name: "debug_investigation".to_string(),
+                description: "Group everything from one bug hunt — launches, the crash session, test runs, reruns, notes, export paths — into a named investigation, so it can be listed and summarized together instead of as loose sessions. Sessions added to an open investigation are retained when stopped unless retain: false is passed.".to_string(),
+                input_schema: serde_json::json!({
+                    "type": "object",
+                    "properties": {
+                        "action": { "type": "string", "enum": ["create", "add-session", "add-test-run", "add-note", "summarize", "list", "close"], "description": "create: start one (returns investigationId). add-session / add-test-run / add-note: link an artifact. summarize: everything linked, with each session's current status, event and crash counts. list: investigations, newest first. close: mark it done, optionally with a conclusion" },
+                        "investigationId": { "type": "string", "description": "Required for all actions but create and list" },
+                        "title": { "type": "string", "description": "create: what is being investigated" },
+                        "projectRoot": { "type": "string", "description": "create: project it belongs to (or pass sessionId). list: only this project" },
+                        "sessionId": { "type": "string", "description": "add-session: session to link. create: use this session's project root" },
+                        "testRunId": { "type": "string", "description": "add-test-run: test run to link; its outcome is recorded" },
+                        "note": { "type": "string", "description": "add-note: the note. add-session / add-test-run: annotation for the artifact" },
+                        "conclusion": { "type": "string", "description": "close: what was found" },
+                        "limit": { "type": "integer", "default": 20, "maximum": 200, "description": "list: investigations to return" }
+                    },
+                    "required": ["action"]
+                }),
+            },
+            McpTool {
+                name: "debug_tutorial".to_string(),
+                description: "Guided first run on a bundled demo program: launch → read stderr → trace → watch → breakpoint. action 'start' builds the demo (needs a C compiler) and returns the steps; action 'check' verifies a step's checkpoint against your session.".to_string(),
+                input_schema: serde_json::json!({
+                    "type": "object",
+                    "properties": {
+                        "action": { "type": "string", "enum": ["start", "check"], "description": "start (default): build the demo and list the steps. check: verify one step" },
+                        "step": { "type": "integer", "minimum": 1, "maximum": 5, "description": "Step to check" },
+                        "sessionId": { "type": "string", "description": "The demo session (required for check)" },
+                        "answer": { "type": "string", "description": "Answer for steps that ask a question" }
+                    }
+                }),
+            },
+            McpTool {
+                name: "debug_ui".to_string(),
+                description: "Query the UI state of a running process. Returns accessibility tree (native widgets) and/or a screenshot saved as PNG to <projectRoot>/screenshots/. Use mode to select output. Pass 'id' with screenshot/both mode to crop to a specific element (or window), or 'region' to crop to a screen rect, for a much smaller image. mode 'diff' captures the tree, performs 'action' and/or waits 'waitMs', captures again and returns only the added, removed and changed nodes. mode 'record' saves 'fps' screenshots a second for 'durationMs' as PNG frames under <projectRoot>/recordings/, for flicker and animation bugs.".to_string(),
+                input_schema: serde_json::json!({
+                    "type": "object",
+                    "properties": {
+                        "sessionId": { "type": "string", "description": "Session ID (from debug_launch)" },
+                        "mode": { "type": "string", "enum": ["tree", "screenshot", "both", "diff", "record"], "description": "Output mode: tree (UI element hierarchy), screenshot (PNG image), both, diff (changes across an action or wait), or record (frame sequence)" },
+                        "id": { "type": "string", "description": "Target node ID from debug_ui tree. When provided with screenshot, both or record mode, crops the screenshot to this element's bounds." },
+                        "region": {
+                            "type": "object",
+                            "description": "screenshot/both/record mode: crop to this rect, in the screen coordinates of the tree's bounds",
+                            "properties": {
+                                "x": { "type": "number" },
+                                "y": { "type": "number" },
+                                "w": { "type": "number" },
+                                "h": { "type": "number" }
+                            },
+                            "required": ["x", "y", "w", "h"]
+                        },
+                        "verbose": { "type": "boolean", "description": "Return JSON instead of compact text (default: false)" },
+                        "vision": { "type": "boolean", "description": "Add vision-detected elements and the text recognized on screen to the tree (needs vision.enabled in settings)" },
+                        "text": { "type": "string", "description": "tree/both mode: return only the elements whose title, value or on-screen text contains this (case-insensitive), e.g. 'Error 42'. On-screen text needs vision: true." },
+                        "action": { "type": "object", "description": "diff mode: debug_ui_action arguments to perform between the snapshots (sessionId optional), e.g. { action: 'click', id: 'btn_a1b2' }" },
+                        "waitMs": { "type": "integer", "minimum": 0, "maximum": crate::mcp::MAX_UI_DIFF_WAIT_MS, "description": "diff mode: time to wait before the second snapshot, after the action if any" },
+                        "durationMs": { "type": "integer", "minimum": 1, "maximum": crate::mcp::MAX_UI_RECORD_MS, "description": "record mode: how long to capture (default: 2000)" },
+                        "fps": { "type": "integer", "minimum": 1, "maximum": crate::mcp::MAX_UI_RECORD_FPS, "description": "record mode: frames per second (default: 10). Unchanged frames point at the previous file." }
+                    },
+                    "required": ["sessionId", "mode"]
+                }),
+            },
+            McpTool {
+                name: "debug_ui_action".to_string(),
+                description: "Perform a UI action on a running process. Actions: click, set_value, type, key, scroll, drag, focus. Uses accessibility actions when available, falls back to synthesized input events (except focus, which fails rather than click). Returns before/after node state for verification.".to_string(),
+                input_schema: serde_json::json!({
+                    "type": "object",
+                    "properties": {
+                        "sessionId": { "type": "string", "description": "Session ID (from debug_launch)" },
+                        "action": { "type": "string", "enum": ["click", "set_value", "type", "key", "scroll", "drag", "focus"], "description": "Action to perform" },
+                        "id": { "type": "string", "description": "Target node ID from debug_ui tree. Required for all except 'key'." },
+                        "value": { "description": "Value to set (number or string). Required for 'set_value'." },
+                        "text": { "type": "string", "description": "Text to type. Required for 'type'." },
+                        "key": { "type": "string", "description": "Key name (e.g. 's', 'return', 'escape'). Required for 'key'." },
                         "modifiers": { "type": "array", "items": { "type": "string" }, "description": "Modifier keys: 'cmd', 'shift', 'alt', 'ctrl'" },
                         "direction": { "type": "string", "enum": ["up", "down", "left", "right"], "description": "Scroll direction. Required for 'scroll'." },
                         "amount": { "type": "integer", "description": "Scroll amount in lines (default: 3)" },
@@ -1128,10 +2790,7 @@ Do NOT pass `framework` unless auto-detection fails. For C++, provide `command`
                     "required": ["sessionId", "action"]
                 }),
             },
-        ];
-
-        let response = McpToolsListResponse { tools };
-        Ok(serde_json::to_value(response)?)
+        ]
     }
 
     async fn handle_tools_call(
@@ -1157,14 +2816,6 @@ Do NOT pass `framework` unless auto-detection fails. For C++, provide `command`
         };
 
         let result = match call.name.as_str() {
-            "debug_launch" => self.tool_debug_launch(&call.arguments, connection_id).await,
-            "debug_trace" => self.tool_debug_trace(&call.arguments, connection_id).await,
-            "debug_query" => self.tool_debug_query(&call.arguments).await,
-            "debug_session" => self.tool_debug_session(&call.arguments).await,
-            "debug_test" => self.tool_debug_test(&call.arguments, connection_id).await,
-            "debug_memory" => self.tool_debug_memory(&call.arguments).await,
-            "debug_breakpoint" => self.tool_debug_breakpoint(&call.arguments).await,
-            "debug_continue" => self.tool_debug_continue(&call.arguments).await,
             "debug_ui" => match self.tool_debug_ui(&call.arguments).await {
                 Ok(content) => {
                     let response = McpToolCallResponse {
@@ -1185,7 +2836,7 @@ Do NOT pass `framework` unless auto-detection fails. For C++, provide `command`
                 }
                 Err(e) => Err(e),
             },
-            _ => Err(crate::Error::Frida(format!("Unknown tool: {}", call.name))),
+            name => self.call_tool(name, &call.arguments, connection_id).await,
         };
 
         // The response is about to be written; stop streaming progress.
@@ -1218,6 +2869,41 @@ Do NOT pass `framework` unless auto-detection fails. For C++, provide `command`
         }
     }
 
+    /// Run a tool that answers with JSON (everything but the debug_ui tools).
+    /// Shared by MCP tools/call and the HTTP listener.
+    pub(crate) async fn call_tool(
+        &self,
+        name: &str,
+        args: &serde_json::Value,
+        connection_id: &str,
+    ) -> crate::Result<serde_json::Value> {
+        match name {
+            "debug_launch" => self.tool_debug_launch(args, connection_id).await,
+            "debug_trace" => self.tool_debug_trace(args, connection_id).await,
+            "debug_query" => self.tool_debug_query(args).await,
+            "debug_session" => self.tool_debug_session(args, connection_id).await,
+            "debug_test" => self.tool_debug_test(args, connection_id).await,
+            "debug_memory" => self.tool_debug_memory(args).await,
+            "debug_eval" => self.tool_debug_eval(args).await,
+            "debug_breakpoint" => self.tool_debug_breakpoint(args).await,
+            "debug_continue" => self.tool_debug_continue(args).await,
+            "debug_watchpoint" => self.tool_debug_watchpoint(args).await,
+            "debug_export" => self.tool_debug_export(args).await,
+            "debug_crashes" => self.tool_debug_crashes(args).await,
+            "debug_health" => self.tool_debug_health().await,
+            "debug_config" => self.tool_debug_config(args).await,
+            "debug_tutorial" => self.tool_debug_tutorial(args).await,
+            "debug_diff" => self.tool_debug_diff(args).await,
+            "debug_timeline" => self.tool_debug_timeline(args).await,
+            "debug_coverage" => self.tool_debug_coverage(args).await,
+            "debug_heap" => self.tool_debug_heap(args).await,
+            "debug_symbols" => self.tool_debug_symbols(args).await,
+            "debug_batch" => self.tool_debug_batch(args, connection_id).await,
+            "debug_investigation" => self.tool_debug_investigation(args).await,
+            _ => Err(crate::Error::Frida(format!("Unknown tool: {}", name))),
+        }
+    }
+
     fn require_session(&self, session_id: &str) -> crate::Result<crate::db::Session> {
         self.session_manager
             .get_session(session_id)?
@@ -1231,7 +2917,7 @@ Do NOT pass `framework` unless auto-detection fails. For C++, provide `command`
         }
     }
 
-    async fn handle_disconnect(&self, connection_id: &str) {
+    pub(crate) async fn handle_disconnect(&self, connection_id: &str) {
         // Collect all needed state in a single lock pass, following the global
         // lock order: connection_sessions → pending_patterns → test_runs.
         // This prevents ABBA deadlocks with tool_debug_launch which uses the
@@ -1305,7 +2991,9 @@ Do NOT pass `framework` unless auto-detection fails. For C++, provide `command`
         args: &serde_json::Value,
         connection_id: &str,
     ) -> Result<serde_json::Value> {
-        let req: DebugLaunchRequest = serde_json::from_value(args.clone())?;
+        let (args, profile_instrumentation) = resolve_launch_profile(args)?;
+        let args = &args;
+        let mut req: DebugLaunchRequest = serde_json::from_value(args.clone())?;
         req.validate()?;
 
         // Validate paths: reject path traversal attempts
@@ -1319,6 +3007,8 @@ Do NOT pass `framework` unless auto-detection fails. For C++, provide `command`
                 "projectRoot must not contain '..' components".to_string(),
             ));
         }
+        // One spelling per project, whatever path the client sent
+        req.project_root = crate::db::normalize_project_root(&req.project_root);
         if let Some(ref sp) = req.symbols_path {
             if sp.contains("..") {
                 return Err(crate::Error::ValidationError(
@@ -1326,6 +3016,33 @@ Do NOT pass `framework` unless auto-detection fails. For C++, provide `command`
                 ));
             }
         }
+        if let Some(missing) = req
+            .symbol_extra
+            .iter()
+            .flatten()
+            .find(|p| !std::path::Path::new(p).is_file())
+        {
+            return Err(crate::Error::ValidationError(format!(
+                "symbolExtra: '{}' is not a file",
+                missing
+            )));
+        }
+
+        // Load the instrumentation file before spawning so a bad file fails the launch cleanly
+        let instrumentation = req
+            .instrumentation_file
+            .as_deref()
+            .map(|file| load_instrumentation_file(file, &req.project_root))
+            .transpose()?;
+        let instrumentation = match (instrumentation, profile_instrumentation) {
+            (Some(mut file), Some(profile)) => {
+                file.patterns.extend(profile.patterns);
+                file.watches.extend(profile.watches);
+                file.breakpoints.extend(profile.breakpoints);
+                Some(file)
+            }
+            (file, profile) => file.or(profile),
+        };
 
         // Enforce global session limit
         // Note: There's a small TOCTOU window between this check and the session
@@ -1375,6 +3092,29 @@ Do NOT pass `framework` unless auto-detection fails. For C++, provide `command`
             }
         }
 
+        // Opt-in: re-resolve what this binary's last session in the same
+        // project was observing
+        let mut warnings = Vec::new();
+        let mut instrumentation_from = None;
+        let instrumentation = match instrumentation {
+            Some(snapshot) => Some(snapshot),
+            None if req.inherit_instrumentation == Some(true) => self
+                .session_manager
+                .carried_instrumentation(&req.project_root, &req.command)
+                .map(|carried| {
+                    let from = carried.snapshot.session_id.clone();
+                    warnings.extend(
+                        carried
+                            .omitted
+                            .iter()
+                            .map(|item| format!("Not carried over from {}: {}", from, item)),
+                    );
+                    instrumentation_from = Some(from);
+                    carried.snapshot
+                }),
+            None => None,
+        };
+
         // Extract binary name from path
         let binary_name = std::path::Path::new(&req.command)
             .file_name()
@@ -1392,6 +3132,28 @@ Do NOT pass `framework` unless auto-detection fails. For C++, provide `command`
             &req.project_root,
             0, // PID not known yet, updated after spawn
         )?;
+        self.session_manager
+            .db()
+            .set_session_launch(&session_id, &launch_record(&req, args))?;
+
+        if req.capture_fds.is_some() || req.capture_pipes.is_some() {
+            self.session_manager.set_output_channels(
+                &session_id,
+                crate::daemon::OutputChannels {
+                    fds: req.capture_fds.clone().unwrap_or_default(),
+                    pipes: req.capture_pipes.clone().unwrap_or_default(),
+                },
+            );
+        }
+
+        if let Some(ref triggers) = req.on_output {
+            self.session_manager
+                .set_output_triggers(&session_id, triggers.clone());
+        }
+
+        if let Some(format) = req.log_format {
+            self.session_manager.set_log_format(&session_id, format);
+        }
 
         // Launch always starts fast (no DWARF blocking, no initial hooks).
         // DWARF parsing happens in the background.
@@ -1436,15 +3198,30 @@ Do NOT pass `framework` unless auto-detection fails. For C++, provide `command`
                 .push(session_id.clone());
         }
 
-        // Get and clear this connection's pending patterns
+        // Index symbolExtra libraries before pending patterns are resolved
+        if let Some(ref extra) = req.symbol_extra {
+            self.session_manager
+                .load_symbol_modules(&session_id, extra, &req.project_root)
+                .await;
+        }
+
+        // Get and clear this connection's pending patterns, spelled for the
+        // language detected at spawn
         let mut pending_patterns: Vec<String> = {
             let mut all_pending = self.pending_patterns.write().await;
-            match all_pending.remove(connection_id) {
-                Some(patterns) => patterns.into_iter().collect(),
-                None => Vec::new(),
+            let mut patterns = all_pending.remove(connection_id).unwrap_or_default();
+            if let Some(ref snapshot) = instrumentation {
+                patterns.extend(snapshot.patterns.iter().cloned());
             }
+            let patterns: Vec<String> = patterns.into_iter().collect();
+            self.session_manager
+                .normalize_patterns(&session_id, &patterns)
         };
         pending_patterns.sort();
+        pending_patterns.dedup();
+        let serialization_depth = instrumentation
+            .as_ref()
+            .and_then(|s| s.settings.serialization_depth);
 
         // Capture count before move
         let patterns_count = pending_patterns.len();
@@ -1453,12 +3230,28 @@ Do NOT pass `framework` unless auto-detection fails. For C++, provide `command`
         if !pending_patterns.is_empty() {
             self.session_manager
                 .add_patterns(&session_id, &pending_patterns)?;
+            if let Some(ref snapshot) = instrumentation {
+                self.session_manager.set_pattern_mode(
+                    &session_id,
+                    &self
+                        .session_manager
+                        .normalize_patterns(&session_id, &snapshot.counted_patterns),
+                    crate::mcp::TraceMode::Count,
+                );
+                self.session_manager.set_pattern_mode(
+                    &session_id,
+                    &self
+                        .session_manager
+                        .normalize_patterns(&session_id, &snapshot.rt_patterns),
+                    crate::mcp::TraceMode::Rt,
+                );
+            }
 
             let sm = Arc::clone(&self.session_manager);
             let sid = session_id.clone();
             tokio::spawn(async move {
                 match sm
-                    .update_frida_patterns(&sid, Some(&pending_patterns), None, None)
+                    .update_frida_patterns(&sid, Some(&pending_patterns), None, serialization_depth)
                     .await
                 {
                     Ok(result) => {
@@ -1484,6 +3277,22 @@ Do NOT pass `framework` unless auto-detection fails. For C++, provide `command`
             });
         }
 
+        // Crash capture waits for DWARF so a failure reaches the launch response
+        if let Some(capture) = req.crash_capture {
+            match self
+                .session_manager
+                .set_crash_capture_async(&session_id, &capture)
+                .await
+            {
+                Ok(skipped) if skipped.is_empty() => {}
+                Ok(skipped) => warnings.push(format!(
+                    "crashCapture skips {} (see the crash event's globals)",
+                    skipped.join(", ")
+                )),
+                Err(e) => warnings.push(format!("crashCapture not armed: {}", e)),
+            }
+        }
+
         let (pending_count, next_steps) = if !had_pending_patterns {
             (None, Some("Query stderr/stdout with debug_query first. Add trace patterns with debug_trace only if output is insufficient.".to_string()))
         } else {
@@ -1493,6 +3302,13 @@ Do NOT pass `framework` unless auto-detection fails. For C++, provide `command`
             )
         };
 
+        if let Some(snapshot) = instrumentation {
+            warnings.extend(
+                self.apply_instrumentation(&session_id, connection_id, snapshot)
+                    .await,
+            );
+        }
+
         let capabilities = self.session_manager.get_capabilities(&session_id);
 
         let response = DebugLaunchResponse {
@@ -1501,11 +3317,158 @@ Do NOT pass `framework` unless auto-detection fails. For C++, provide `command`
             pending_patterns_applied: pending_count,
             next_steps,
             capabilities,
+            instrumentation_from,
+            warnings,
         };
 
         Ok(serde_json::to_value(response)?)
     }
 
+    /// Re-apply the non-pattern parts of an instrumentation snapshot to a freshly
+    /// launched session (patterns go through the pending-pattern path). Each part
+    /// is applied independently; failures come back as launch warnings.
+    async fn apply_instrumentation(
+        &self,
+        session_id: &str,
+        connection_id: &str,
+        snapshot: InstrumentationSnapshot,
+    ) -> Vec<String> {
+        let mut warnings = Vec::new();
+
+        if let Some(limit) = snapshot.settings.event_limit {
+            self.session_manager.set_event_limit(session_id, limit);
+        }
+
+        if !snapshot.watches.is_empty() {
+            let args = serde_json::json!({
+                "sessionId": session_id,
+                "watches": { "add": snapshot.watches },
+            });
+            match self.tool_debug_trace(&args, connection_id).await {
+                Ok(response) => {
+                    if let Some(w) = response["warnings"].as_array() {
+                        warnings.extend(w.iter().filter_map(|w| w.as_str().map(String::from)));
+                    }
+                }
+                Err(e) => warnings.push(format!("Watches not applied: {}", e)),
+            }
+        }
+
+        if !snapshot.watchpoints.is_empty() {
+            if let Err(e) = self
+                .session_manager
+                .update_watchpoints_async(session_id, snapshot.watchpoints, vec![])
+                .await
+            {
+                warnings.push(format!("Watchpoints not applied: {}", e));
+            }
+        }
+
+        for bp in snapshot.breakpoints {
+            let target = bp
+                .function
+                .clone()
+                .or_else(|| bp.address.clone())
+                .or_else(|| {
+                    bp.file
+                        .as_ref()
+                        .map(|f| format!("{}:{}", f, bp.line.unwrap_or(0)))
+                })
+                .unwrap_or_default();
+            let address = bp.address_value();
+            if let Err(e) = self
+                .session_manager
+                .set_breakpoint_async(
+                    session_id,
+                    None,
+                    bp.function,
+                    bp.offset,
+                    address,
+                    bp.file,
+                    bp.line,
+                    bp.condition,
+                    bp.hit_count,
+                )
+                .await
+            {
+                warnings.push(format!("Breakpoint {} not applied: {}", target, e));
+            }
+        }
+
+        for lp in snapshot.logpoints {
+            let target = lp
+                .function
+                .clone()
+                .or_else(|| {
+                    lp.file
+                        .as_ref()
+                        .map(|f| format!("{}:{}", f, lp.line.unwrap_or(0)))
+                })
+                .unwrap_or_default();
+            if let Err(e) = self
+                .session_manager
+                .set_logpoint_async(
+                    session_id,
+                    None,
+                    lp.function,
+                    lp.file,
+                    lp.line,
+                    lp.message,
+                    lp.condition,
+                    crate::daemon::session_manager::LogpointHits::new(
+                        lp.hit_count,
+                        lp.every_n,
+                        lp.max_hits,
+                    ),
+                )
+                .await
+            {
+                warnings.push(format!("Logpoint {} not applied: {}", target, e));
+            }
+        }
+
+        warnings
+    }
+
+    /// Static lint for every pattern, plus a namespace check against the
+    /// session's DWARF index when it is a native session.
+    async fn lint_trace_patterns(
+        &self,
+        patterns: &[String],
+        session_id: Option<&str>,
+    ) -> DebugTraceLintResponse {
+        let dwarf = match session_id {
+            Some(sid)
+                if self
+                    .session_manager
+                    .get_capabilities(sid)
+                    .is_some_and(|c| c.runtime == "native") =>
+            {
+                self.session_manager.get_dwarf(sid).await.ok().flatten()
+            }
+            _ => None,
+        };
+
+        let mut lints = Vec::new();
+        let mut clean = Vec::new();
+        for pattern in patterns {
+            let mut found = crate::symbols::lint_pattern(pattern);
+            if let (true, Some(dwarf)) = (found.is_empty(), dwarf.as_ref()) {
+                found.extend(crate::symbols::lint_namespace(pattern, dwarf));
+            }
+            if found.is_empty() {
+                clean.push(pattern.clone());
+            } else {
+                lints.extend(found);
+            }
+        }
+        DebugTraceLintResponse {
+            lints,
+            clean,
+            symbols_checked: dwarf.is_some(),
+        }
+    }
+
     async fn tool_debug_trace(
         &self,
         args: &serde_json::Value,
@@ -1515,6 +3478,19 @@ Do NOT pass `framework` unless auto-detection fails. For C++, provide `command`
 
         // Validate request first
         req.validate()?;
+        if let Some(ref patterns) = req.lint {
+            let response = self
+                .lint_trace_patterns(patterns, req.session_id.as_deref())
+                .await;
+            return Ok(serde_json::to_value(response)?);
+        }
+        if let (Some(ref patterns), Some(ref sid)) = (&req.preview, &req.session_id) {
+            let response = self.session_manager.preview_patterns(sid, patterns).await?;
+            return Ok(serde_json::to_value(response)?);
+        }
+        if let Some(ref sid) = req.session_id {
+            self.session_manager.require_instrumented(sid)?;
+        }
 
         match req.session_id {
             // No session ID - modify pending patterns for this connection's next launch
@@ -1543,10 +3519,29 @@ Do NOT pass `framework` unless auto-detection fails. For C++, provide `command`
                 let response = DebugTraceResponse {
                     mode: "pending".to_string(),
                     active_patterns: patterns,
+                    counted_patterns: vec![],
+                    rt_patterns: vec![],
                     hooked_functions: 0, // Not hooked yet, just pending
                     matched_functions: None,
                     active_watches: vec![],
-                    warnings: vec![],
+                    warnings: req
+                        .add
+                        .iter()
+                        .flatten()
+                        .flat_map(|p| crate::symbols::lint_pattern(p))
+                        .map(|l| l.describe())
+                        .collect(),
+                    sampling: None,
+                    threads: None,
+                    rate_limit: None,
+                    flight_recorder: None,
+                    signal_safety: None,
+                    syscalls: vec![],
+                    network: false,
+                    exceptions: false,
+                    signals: false,
+                    locks: false,
+                    flushed_events: None,
                     event_limit: crate::config::StrobeSettings::default().events_max_per_session,
                     status: Some(status_msg),
                 };
@@ -1557,11 +3552,83 @@ Do NOT pass `framework` unless auto-detection fails. For C++, provide `command`
                 // Verify session exists
                 let _ = self.require_session(session_id)?;
 
+                // Filter threads before new hooks start producing events
+                if let Some(threads) = req.threads.clone() {
+                    self.session_manager
+                        .set_thread_filter_async(session_id, threads)
+                        .await?;
+                }
+                if let Some(rate_limit) = req.rate_limit.clone() {
+                    self.session_manager
+                        .set_rate_limit_async(session_id, rate_limit)
+                        .await?;
+                }
+                let mut flushed_events = None;
+                if let Some(recorder) = req.flight_recorder.clone() {
+                    let flushed = self
+                        .session_manager
+                        .set_flight_recorder_async(session_id, recorder)
+                        .await?;
+                    if flushed > 0 {
+                        flushed_events = Some(flushed);
+                    }
+                }
+                if req.flush == Some(true) {
+                    let flushed = self
+                        .session_manager
+                        .flush_flight_recorder(session_id)
+                        .await?;
+                    flushed_events = Some(flushed_events.unwrap_or(0) + flushed);
+                }
+
+                // @syscalls:, @network, @exceptions, @signals and @locks hook
+                // libc/TLS/C++ runtime calls in the agent; the rest are function
+                // patterns
+                let added = crate::mcp::MetaPatterns::split(req.add.as_deref().unwrap_or_default());
+                let removed =
+                    crate::mcp::MetaPatterns::split(req.remove.as_deref().unwrap_or_default());
+                let add = added
+                    .function_patterns()
+                    .map(|p| self.session_manager.normalize_patterns(session_id, &p));
+                let remove = removed
+                    .function_patterns()
+                    .map(|p| self.session_manager.normalize_patterns(session_id, &p));
+                if !added.syscalls.is_empty() || !removed.syscalls.is_empty() {
+                    self.session_manager
+                        .update_syscall_groups_async(session_id, &added.syscalls, &removed.syscalls)
+                        .await?;
+                }
+                if added.network || removed.network {
+                    self.session_manager
+                        .set_network_tracing_async(session_id, added.network)
+                        .await?;
+                }
+                if added.exceptions || removed.exceptions {
+                    self.session_manager
+                        .set_exception_tracing_async(session_id, added.exceptions)
+                        .await?;
+                }
+                if added.signals || removed.signals {
+                    self.session_manager
+                        .set_signal_tracing_async(session_id, added.signals)
+                        .await?;
+                }
+                if added.locks || removed.locks {
+                    self.session_manager
+                        .set_lock_tracing_async(session_id, added.locks)
+                        .await?;
+                }
+
                 // Update patterns in session manager
-                if let Some(ref add) = req.add {
+                if let Some(ref add) = add {
                     self.session_manager.add_patterns(session_id, add)?;
+                    self.session_manager.set_pattern_mode(
+                        session_id,
+                        add,
+                        req.mode.unwrap_or_default(),
+                    );
                 }
-                if let Some(ref remove) = req.remove {
+                if let Some(ref remove) = remove {
                     self.session_manager.remove_patterns(session_id, remove)?;
                 }
 
@@ -1570,8 +3637,8 @@ Do NOT pass `framework` unless auto-detection fails. For C++, provide `command`
                     .session_manager
                     .update_frida_patterns(
                         session_id,
-                        req.add.as_deref(),
-                        req.remove.as_deref(),
+                        add.as_deref(),
+                        remove.as_deref(),
                         req.serialization_depth,
                     )
                     .await
@@ -1652,6 +3719,7 @@ Do NOT pass `framework` unless auto-detection fails. For C++, provide `command`
                             }
 
                             let on_patterns = watch_target.on.clone();
+                            let delta_threshold = watch_target.delta_threshold;
 
                             // 1) Address-based watch: raw address, no DWARF needed
                             if let Some(ref addr_str) = watch_target.address {
@@ -1683,6 +3751,8 @@ Do NOT pass `framework` unless auto-detection fails. For C++, provide `command`
                                     type_name: Some(type_hint.to_string()),
                                     on_patterns: on_patterns.clone(),
                                     no_slide: true,
+                                    delta_threshold,
+                                    module: None,
                                 });
 
                                 state_watches.push(crate::daemon::ActiveWatchState {
@@ -1697,6 +3767,9 @@ Do NOT pass `framework` unless auto-detection fails. For C++, provide `command`
                                     is_expr: false,
                                     expr: None,
                                     no_slide: true,
+                                    variable: None,
+                                    delta_threshold,
+                                    module: None,
                                 });
 
                                 active_watches.push(crate::mcp::ActiveWatch {
@@ -1705,6 +3778,7 @@ Do NOT pass `framework` unless auto-detection fails. For C++, provide `command`
                                     size,
                                     type_name: Some(type_hint.to_string()),
                                     on: on_patterns,
+                                    delta_threshold,
                                 });
                                 continue;
                             }
@@ -1730,6 +3804,7 @@ Do NOT pass `framework` unless auto-detection fails. For C++, provide `command`
                                         expr: expr.clone(),
                                         is_global,
                                         on_patterns: on_patterns.clone(),
+                                        delta_threshold,
                                     });
 
                                     active_watches.push(crate::mcp::ActiveWatch {
@@ -1738,6 +3813,7 @@ Do NOT pass `framework` unless auto-detection fails. For C++, provide `command`
                                         size: 0,
                                         type_name: None,
                                         on: on_patterns,
+                                        delta_threshold,
                                     });
                                     continue;
                                 }
@@ -1777,7 +3853,18 @@ Do NOT pass `framework` unless auto-detection fails. For C++, provide `command`
                                 break;
                             };
 
-                            let recipe = dwarf.resolve_watch_expression(name)?;
+                            // Not in the main binary: try the symbolExtra libraries
+                            let (recipe, module) = match dwarf.resolve_watch_expression(name) {
+                                Ok(recipe) => (recipe, None),
+                                Err(e) => match self
+                                    .session_manager
+                                    .resolve_module_watch(session_id, name)
+                                    .await
+                                {
+                                    Some((recipe, module)) => (recipe, Some(module)),
+                                    None => return Err(e),
+                                },
+                            };
 
                             let label =
                                 watch_target.label.as_ref().unwrap_or(&recipe.label).clone();
@@ -1804,6 +3891,8 @@ Do NOT pass `framework` unless auto-detection fails. For C++, provide `command`
                                 type_name: recipe.type_name.clone(),
                                 on_patterns: on_patterns.clone(),
                                 no_slide: false,
+                                delta_threshold,
+                                module: module.clone(),
                             });
 
                             state_watches.push(crate::daemon::ActiveWatchState {
@@ -1818,6 +3907,9 @@ Do NOT pass `framework` unless auto-detection fails. For C++, provide `command`
                                 is_expr: false,
                                 expr: None,
                                 no_slide: false,
+                                variable: Some(name.clone()),
+                                delta_threshold,
+                                module,
                             });
 
                             active_watches.push(crate::mcp::ActiveWatch {
@@ -1826,6 +3918,7 @@ Do NOT pass `framework` unless auto-detection fails. For C++, provide `command`
                                 size: recipe.final_size,
                                 type_name: recipe.type_name,
                                 on: on_patterns,
+                                delta_threshold,
                             });
                         }
 
@@ -1859,6 +3952,8 @@ Do NOT pass `framework` unless auto-detection fails. For C++, provide `command`
                                     type_name: w.type_name.clone(),
                                     on_patterns: w.on_patterns.clone(),
                                     no_slide: w.no_slide,
+                                    delta_threshold: w.delta_threshold,
+                                    module: w.module.clone(),
                                 })
                                 .collect();
 
@@ -1871,9 +3966,24 @@ Do NOT pass `framework` unless auto-detection fails. For C++, provide `command`
                     }
                 }
 
-                // Combine hook warnings and watch warnings
-                let mut all_warnings = hook_result.warnings;
+                if let Some(sampling) = req.sampling.clone() {
+                    self.session_manager
+                        .set_sampling_async(session_id, sampling, settings.sampling_hz)
+                        .await?;
+                }
+                if let Some(audit) = req.signal_safety.clone() {
+                    self.session_manager
+                        .set_signal_safety_async(session_id, audit)
+                        .await?;
+                }
+
+                // Combine hook warnings and watch warnings
+                let mut all_warnings = hook_result.warnings;
                 all_warnings.extend(watch_warnings);
+                if let Some(ref add) = req.add {
+                    let lint = self.lint_trace_patterns(add, Some(session_id)).await;
+                    all_warnings.extend(lint.lints.iter().map(|l| l.describe()));
+                }
 
                 // Add capability-based warnings when tracing is unavailable
                 let caps = self.session_manager.get_capabilities(session_id);
@@ -1881,6 +3991,54 @@ Do NOT pass `framework` unless auto-detection fails. For C++, provide `command`
                     if matches!(c.function_tracing, crate::mcp::CapabilityLevel::None) {
                         all_warnings.extend(c.limitations.clone());
                     }
+                    if req.threads.is_some() && c.runtime != "native" {
+                        all_warnings.push(format!(
+                            "threads filters native function tracing only; {} calls are recorded from every thread",
+                            c.runtime
+                        ));
+                    }
+                    if req.rate_limit.is_some() && c.runtime != "native" {
+                        all_warnings.push(format!(
+                            "rateLimit applies to native function tracing only; every {} call is recorded",
+                            c.runtime
+                        ));
+                    }
+                    if req.flight_recorder.is_some() && c.runtime != "native" {
+                        all_warnings.push(format!(
+                            "flightRecorder buffers native function tracing only; {} calls are recorded immediately",
+                            c.runtime
+                        ));
+                    }
+                    if req.signal_safety.is_some() && c.runtime != "native" {
+                        all_warnings.push(format!(
+                            "signalSafety audits native signal handlers only; {} handlers are not checked",
+                            c.runtime
+                        ));
+                    }
+                    if (!added.syscalls.is_empty() || added.network) && c.runtime != "native" {
+                        all_warnings.push(format!(
+                            "@syscalls: and @network hook libc and the TLS libraries; {} I/O that bypasses them is not seen",
+                            c.runtime
+                        ));
+                    }
+                    if added.exceptions && c.runtime != "native" {
+                        all_warnings.push(format!(
+                            "@exceptions hooks the C++ runtime; {} exceptions are not seen",
+                            c.runtime
+                        ));
+                    }
+                    if added.signals && c.runtime != "native" {
+                        all_warnings.push(format!(
+                            "@signals hooks native signal handlers; signals the {} runtime handles itself are not seen",
+                            c.runtime
+                        ));
+                    }
+                    if added.locks && c.runtime != "native" {
+                        all_warnings.push(format!(
+                            "@locks hooks pthread mutexes; {} runtime locks that don't use them are not seen",
+                            c.runtime
+                        ));
+                    }
                 }
 
                 let status_msg = hook_status_message(
@@ -1893,6 +4051,8 @@ Do NOT pass `framework` unless auto-detection fails. For C++, provide `command`
                 let response = DebugTraceResponse {
                     mode: "runtime".to_string(),
                     active_patterns: patterns,
+                    counted_patterns: self.session_manager.get_counted_patterns(session_id),
+                    rt_patterns: self.session_manager.get_rt_patterns(session_id),
                     hooked_functions: hook_result.installed,
                     matched_functions: if hook_result.matched != hook_result.installed {
                         Some(hook_result.matched)
@@ -1901,6 +4061,17 @@ Do NOT pass `framework` unless auto-detection fails. For C++, provide `command`
                     },
                     active_watches,
                     warnings: all_warnings,
+                    sampling: self.session_manager.get_sampling(session_id),
+                    threads: self.session_manager.get_thread_filter(session_id),
+                    rate_limit: self.session_manager.get_rate_limit(session_id),
+                    flight_recorder: self.session_manager.get_flight_recorder(session_id),
+                    signal_safety: self.session_manager.get_signal_safety(session_id),
+                    syscalls: self.session_manager.get_syscall_groups(session_id),
+                    network: self.session_manager.is_network_tracing(session_id),
+                    exceptions: self.session_manager.is_exception_tracing(session_id),
+                    signals: self.session_manager.is_signal_tracing(session_id),
+                    locks: self.session_manager.is_lock_tracing(session_id),
+                    flushed_events,
                     event_limit,
                     status: Some(status_msg),
                 };
@@ -1911,211 +4082,27 @@ Do NOT pass `framework` unless auto-detection fails. For C++, provide `command`
     }
 
     async fn tool_debug_query(&self, args: &serde_json::Value) -> Result<serde_json::Value> {
-        // Resolve a time value: integer (absolute ns) or string ("-5s", "-1m", "-500ms")
-        fn resolve_time_value(value: &serde_json::Value, latest_ns: i64) -> Option<i64> {
-            match value {
-                serde_json::Value::Number(n) => n.as_i64(),
-                serde_json::Value::String(s) => {
-                    let s = s.trim();
-                    if !s.starts_with('-') {
-                        return s.parse::<i64>().ok();
-                    }
-                    let (num_str, multiplier) = if s.ends_with("ms") {
-                        (&s[1..s.len() - 2], 1_000_000i64)
-                    } else if s.ends_with('s') {
-                        (&s[1..s.len() - 1], 1_000_000_000i64)
-                    } else if s.ends_with('m') {
-                        (&s[1..s.len() - 1], 60_000_000_000i64)
-                    } else {
-                        return None;
-                    };
-                    let num: i64 = num_str.parse().ok()?;
-                    Some(latest_ns - num * multiplier)
-                }
-                _ => None,
-            }
-        }
-
         let req: DebugQueryRequest = serde_json::from_value(args.clone())?;
+        req.validate()?;
 
-        // Verify session exists
-        let _ = self.require_session(&req.session_id)?;
-
-        let limit = req.limit.unwrap_or(50).min(500);
-        let offset = req.offset.unwrap_or(0);
-
-        // Resolve relative time values
-        let latest_ns = if req.time_from.is_some() || req.time_to.is_some() {
-            self.session_manager
-                .db()
-                .get_latest_timestamp(&req.session_id)?
-        } else {
-            0
-        };
-        let timestamp_from_ns = req
-            .time_from
-            .as_ref()
-            .and_then(|v| resolve_time_value(v, latest_ns));
-        let timestamp_to_ns = req
-            .time_to
-            .as_ref()
-            .and_then(|v| resolve_time_value(v, latest_ns));
-
-        let events = self
-            .session_manager
-            .db()
-            .query_events(&req.session_id, |mut q| {
-                if let Some(ref et) = req.event_type {
-                    q = q.event_type(match et {
-                        EventTypeFilter::FunctionEnter => crate::db::EventType::FunctionEnter,
-                        EventTypeFilter::FunctionExit => crate::db::EventType::FunctionExit,
-                        EventTypeFilter::Stdout => crate::db::EventType::Stdout,
-                        EventTypeFilter::Stderr => crate::db::EventType::Stderr,
-                        EventTypeFilter::Crash => crate::db::EventType::Crash,
-                        EventTypeFilter::VariableSnapshot => crate::db::EventType::VariableSnapshot,
-                        EventTypeFilter::Pause => crate::db::EventType::Pause,
-                        EventTypeFilter::Logpoint => crate::db::EventType::Logpoint,
-                        EventTypeFilter::ConditionError => crate::db::EventType::ConditionError,
-                    });
-                }
-                if let Some(ref f) = req.function {
-                    if let Some(ref eq) = f.equals {
-                        q = q.function_equals(eq);
-                    }
-                    if let Some(ref contains) = f.contains {
-                        q = q.function_contains(contains);
-                    }
-                }
-                if let Some(ref sf) = req.source_file {
-                    if let Some(ref contains) = sf.contains {
-                        q = q.source_file_contains(contains);
-                    }
-                }
-                if let Some(ref tn) = req.thread_name {
-                    if let Some(ref contains) = tn.contains {
-                        q = q.thread_name_contains(contains);
-                    }
-                }
-                if let Some(from) = timestamp_from_ns {
-                    q.timestamp_from_ns = Some(from);
-                }
-                if let Some(to) = timestamp_to_ns {
-                    q.timestamp_to_ns = Some(to);
-                }
-                if let Some(dur) = req.min_duration_ns {
-                    q.min_duration_ns = Some(dur);
-                }
-                if let Some(pid) = req.pid {
-                    q.pid_equals = Some(pid);
-                }
-                if let Some(after) = req.after_event_id {
-                    q.after_rowid = Some(after);
-                }
-                q.limit(limit).offset(offset)
-            })?;
-
-        // Count with same filters (except limit/offset) for accurate totalCount
-        let total_count =
-            self.session_manager
-                .db()
-                .count_filtered_events(&req.session_id, |mut q| {
-                    if let Some(ref et) = req.event_type {
-                        q = q.event_type(match et {
-                            EventTypeFilter::FunctionEnter => crate::db::EventType::FunctionEnter,
-                            EventTypeFilter::FunctionExit => crate::db::EventType::FunctionExit,
-                            EventTypeFilter::Stdout => crate::db::EventType::Stdout,
-                            EventTypeFilter::Stderr => crate::db::EventType::Stderr,
-                            EventTypeFilter::Crash => crate::db::EventType::Crash,
-                            EventTypeFilter::VariableSnapshot => {
-                                crate::db::EventType::VariableSnapshot
-                            }
-                            EventTypeFilter::Pause => crate::db::EventType::Pause,
-                            EventTypeFilter::Logpoint => crate::db::EventType::Logpoint,
-                            EventTypeFilter::ConditionError => crate::db::EventType::ConditionError,
-                        });
-                    }
-                    if let Some(ref f) = req.function {
-                        if let Some(ref eq) = f.equals {
-                            q = q.function_equals(eq);
-                        }
-                        if let Some(ref contains) = f.contains {
-                            q = q.function_contains(contains);
-                        }
-                    }
-                    if let Some(ref sf) = req.source_file {
-                        if let Some(ref contains) = sf.contains {
-                            q = q.source_file_contains(contains);
-                        }
-                    }
-                    if let Some(ref tn) = req.thread_name {
-                        if let Some(ref contains) = tn.contains {
-                            q = q.thread_name_contains(contains);
-                        }
-                    }
-                    if let Some(from) = timestamp_from_ns {
-                        q.timestamp_from_ns = Some(from);
-                    }
-                    if let Some(to) = timestamp_to_ns {
-                        q.timestamp_to_ns = Some(to);
-                    }
-                    if let Some(dur) = req.min_duration_ns {
-                        q.min_duration_ns = Some(dur);
-                    }
-                    if let Some(pid) = req.pid {
-                        q.pid_equals = Some(pid);
-                    }
-                    if let Some(after) = req.after_event_id {
-                        q.after_rowid = Some(after);
-                    }
-                    q
-                })?;
-        let has_more = (offset as u64 + events.len() as u64) < total_count;
-
-        // Convert to appropriate format
-        let verbose = req.verbose.unwrap_or(false);
-        let event_values: Vec<serde_json::Value> =
-            events.iter().map(|e| format_event(e, verbose)).collect();
-
-        // Compute cursor fields
-        let last_event_id = events.iter().filter_map(|e| e.rowid).max();
-
-        let events_dropped = if let Some(after) = req.after_event_id {
-            let min_rowid = self
-                .session_manager
-                .db()
-                .min_rowid_for_session(&req.session_id)?;
-            Some(match min_rowid {
-                Some(min) => after + 1 < min,
-                None => after > 0, // All events evicted → dropped if cursor was set
-            })
-        } else {
-            None
-        };
+        let session = self.require_session(&req.session_id)?;
+        let timeout = Duration::from_millis(
+            crate::config::resolve(Some(std::path::Path::new(&session.project_root)))
+                .query_timeout_ms,
+        );
 
-        // Always check for crash events regardless of eventType filter
-        let crash = if req.event_type.as_ref() != Some(&EventTypeFilter::Crash) {
-            let crash_events = self
-                .session_manager
-                .db()
-                .query_events(&req.session_id, |q| {
-                    q.event_type(crate::db::EventType::Crash).limit(1)
-                })
-                .unwrap_or_default();
-            crash_events.first().map(|e| format_event(e, true))
-        } else {
-            None // Already included in the main events list
-        };
+        let mut filters = args.clone();
+        if let Some(obj) = filters.as_object_mut() {
+            obj.remove("sessionId");
+        }
+        if req.group_field().is_some() {
+            let response = query_event_groups(self.session_manager.db(), &req, filters, timeout)?;
+            return Ok(serde_json::to_value(response)?);
+        }
+        let mut response = query_stored_events(self.session_manager.db(), &req, filters, timeout)?;
 
         let pids = self.session_manager.get_all_pids(&req.session_id);
-        let response = DebugQueryResponse {
-            events: event_values,
-            total_count,
-            has_more,
-            pids: if pids.len() > 1 { Some(pids) } else { None },
-            last_event_id,
-            events_dropped,
-            crash,
-        };
+        response.pids = if pids.len() > 1 { Some(pids) } else { None };
 
         Ok(serde_json::to_value(response)?)
     }
@@ -2123,8 +4110,19 @@ Do NOT pass `framework` unless auto-detection fails. For C++, provide `command`
     async fn tool_debug_memory(&self, args: &serde_json::Value) -> Result<serde_json::Value> {
         let req: crate::mcp::DebugMemoryRequest = serde_json::from_value(args.clone())?;
         req.validate()?;
+        // Series reads stored events, so it also works on retained sessions
+        if req.action != crate::mcp::MemoryAction::Series {
+            self.session_manager.require_instrumented(&req.session_id)?;
+        }
 
         match req.action {
+            crate::mcp::MemoryAction::Locals => {
+                let locals = self
+                    .session_manager
+                    .frame_locals(&req.session_id, req.thread_id, req.frame.unwrap_or(0))
+                    .await?;
+                Ok(serde_json::to_value(locals)?)
+            }
             crate::mcp::MemoryAction::Read => {
                 let read_req = crate::mcp::DebugReadRequest {
                     session_id: req.session_id,
@@ -2136,6 +4134,8 @@ Do NOT pass `framework` unless auto-detection fails. For C++, provide `command`
                             address: t.address,
                             size: t.size,
                             type_hint: t.type_hint,
+                            format: t.format,
+                            region: t.region,
                         })
                         .collect(),
                     depth: req.depth,
@@ -2163,10 +4163,67 @@ Do NOT pass `framework` unless auto-detection fails. For C++, provide `command`
                     .execute_debug_write(&serde_json::to_value(write_req)?)
                     .await
             }
+            crate::mcp::MemoryAction::Series => self.memory_poll_series(req).await,
         }
     }
 
-    async fn tool_debug_session(&self, args: &serde_json::Value) -> Result<serde_json::Value> {
+    /// debug_memory action "series": a poll's samples as aligned columns, or
+    /// as a CSV file.
+    async fn memory_poll_series(
+        &self,
+        req: crate::mcp::DebugMemoryRequest,
+    ) -> Result<serde_json::Value> {
+        let session = self.require_session(&req.session_id)?;
+        let series = self
+            .session_manager
+            .db()
+            .memory_poll_series(&req.session_id, req.poll_id.as_deref())?
+            .ok_or_else(|| {
+                crate::Error::ValidationError(match req.poll_id {
+                    Some(ref id) => format!("No samples for poll '{}'", id),
+                    None => format!(
+                        "Session {} has no debug_memory poll samples",
+                        req.session_id
+                    ),
+                })
+            })?;
+        if !req.csv.unwrap_or(false) {
+            return Ok(serde_json::to_value(series)?);
+        }
+
+        let path = match req.output_path {
+            Some(p) => project_output_path(&p, &session.project_root)?,
+            None => Path::new(&session.project_root)
+                .join("exports")
+                .join(format!("{}-{}.csv", req.session_id, series.poll_id)),
+        };
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        tokio::fs::write(&path, series.to_csv()).await?;
+        let response = crate::mcp::MemorySeriesExport {
+            path: path.to_string_lossy().into_owned(),
+            samples: series.timestamps_ns.len(),
+            columns: series.columns.keys().cloned().collect(),
+            poll_id: series.poll_id,
+        };
+        Ok(serde_json::to_value(response)?)
+    }
+
+    async fn tool_debug_eval(&self, args: &serde_json::Value) -> Result<serde_json::Value> {
+        let req: crate::mcp::DebugEvalRequest = serde_json::from_value(args.clone())?;
+        req.validate()?;
+        self.session_manager.require_instrumented(&req.session_id)?;
+
+        let response = self.session_manager.evaluate_expression(&req).await?;
+        Ok(serde_json::to_value(response)?)
+    }
+
+    async fn tool_debug_session(
+        &self,
+        args: &serde_json::Value,
+        connection_id: &str,
+    ) -> Result<serde_json::Value> {
         let req: DebugSessionRequest = serde_json::from_value(args.clone())?;
         req.validate()?;
 
@@ -2177,8 +4234,147 @@ Do NOT pass `framework` unless auto-detection fails. For C++, provide `command`
                 Ok(serde_json::to_value(status)?)
             }
             SessionAction::Stop => self.tool_debug_stop(args).await,
-            SessionAction::List => self.tool_debug_list_sessions().await,
+            SessionAction::List => {
+                self.tool_debug_list_sessions(req.project_root.as_deref())
+                    .await
+            }
             SessionAction::Delete => self.tool_debug_delete_session(args).await,
+            SessionAction::Relaunch => {
+                let session_id = req.session_id.as_deref().unwrap();
+                let session = self.require_session(session_id)?;
+                if session.status == crate::db::SessionStatus::Running {
+                    return Err(crate::Error::ValidationError(format!(
+                        "Session {} is still running. Stop it (retain: true keeps its events) before relaunching.",
+                        session_id
+                    )));
+                }
+                let launch = self
+                    .session_manager
+                    .db()
+                    .get_session_launch(session_id)?
+                    .ok_or_else(|| {
+                        crate::Error::ValidationError(format!(
+                            "Session {} was not started by debug_launch and has no launch parameters to replay",
+                            session_id
+                        ))
+                    })?;
+
+                let Some((command, command_args)) = launch.argv.split_first() else {
+                    return Err(crate::Error::ValidationError(format!(
+                        "Session {} has an empty recorded argv",
+                        session_id
+                    )));
+                };
+                let mut launch_args = launch.request;
+                launch_args["command"] = serde_json::json!(command);
+                launch_args["args"] = serde_json::json!(command_args);
+                launch_args["cwd"] = serde_json::json!(launch.cwd);
+                launch_args["env"] = serde_json::json!(launch.env);
+                // A relaunch inherits unless asked not to
+                launch_args["inheritInstrumentation"] =
+                    serde_json::json!(req.inherit_instrumentation.unwrap_or(true));
+
+                let mut response =
+                    Box::pin(self.tool_debug_launch(&launch_args, connection_id)).await?;
+                response["relaunchedFrom"] = serde_json::json!(session_id);
+                Ok(response)
+            }
+            SessionAction::ExportInstrumentation => {
+                let session_id = req.session_id.as_deref().unwrap();
+                let session = self.require_session(session_id)?;
+                let (snapshot, omitted) =
+                    self.session_manager.instrumentation_snapshot(session_id)?;
+
+                let path = match req.path {
+                    Some(ref p) => instrumentation_path(p, &session.project_root)?,
+                    None => std::path::Path::new(&session.project_root)
+                        .join(".strobe/instrumentation")
+                        .join(format!("{}.json", session_id)),
+                };
+                if let Some(parent) = path.parent() {
+                    std::fs::create_dir_all(parent)?;
+                }
+                std::fs::write(&path, serde_json::to_vec_pretty(&snapshot)?)?;
+
+                Ok(serde_json::to_value(ExportInstrumentationResponse {
+                    path: path.to_string_lossy().into_owned(),
+                    patterns: snapshot.patterns.len(),
+                    watches: snapshot.watches.len(),
+                    watchpoints: snapshot.watchpoints.len(),
+                    breakpoints: snapshot.breakpoints.len(),
+                    logpoints: snapshot.logpoints.len(),
+                    omitted,
+                })?)
+            }
+            SessionAction::ReloadAgent => {
+                let session_id = req.session_id.as_deref().unwrap();
+                let session = self.require_session(session_id)?;
+                let (agent, source) = match req.path {
+                    Some(ref p) => {
+                        let path = instrumentation_path(p, &session.project_root)?;
+                        let source = std::fs::read_to_string(&path).map_err(|e| {
+                            crate::Error::ValidationError(format!(
+                                "Cannot read agent bundle {}: {}",
+                                path.display(),
+                                e
+                            ))
+                        })?;
+                        (path.to_string_lossy().into_owned(), Some(source))
+                    }
+                    None => ("built-in".to_string(), None),
+                };
+                let response = self
+                    .session_manager
+                    .reload_agent(session_id, &agent, source)
+                    .await?;
+                Ok(serde_json::to_value(response)?)
+            }
+            SessionAction::ConsistencyCheck => {
+                let session_id = req.session_id.as_deref().unwrap();
+                let session = self.require_session(session_id)?;
+                let db = self.session_manager.db();
+                let before = db.call_consistency(session_id)?;
+                let repaired_pairs = match req.repair {
+                    Some(true) => Some(db.repair_call_order(session_id)?),
+                    _ => None,
+                };
+
+                let mut note = String::from(
+                    "Orphan exits usually mean the enter was evicted by the event limit (oldest trace events go first).",
+                );
+                if session.status == crate::db::SessionStatus::Running {
+                    note.push_str(" Calls still in progress count as unclosed enters.");
+                } else {
+                    note.push_str(
+                        " Unclosed enters are calls that never returned before the process ended.",
+                    );
+                }
+                if before.misordered > 0 && repaired_pairs.is_none() {
+                    note.push_str(" Pass repair: true to fix misordered pairs.");
+                }
+
+                Ok(serde_json::to_value(
+                    crate::mcp::ConsistencyCheckResponse {
+                        session_id: session_id.to_string(),
+                        orphan_exits: before.orphan_exits,
+                        unclosed_enters: before.unclosed_enters,
+                        misordered_pairs: before.misordered,
+                        repaired_pairs,
+                        threads: before
+                            .threads
+                            .into_iter()
+                            .take(crate::mcp::MAX_CONSISTENCY_THREADS)
+                            .map(|t| crate::mcp::ThreadConsistency {
+                                pid: t.pid,
+                                thread_id: t.thread_id,
+                                orphan_exits: t.orphan_exits,
+                                unclosed_enters: t.unclosed_enters,
+                            })
+                            .collect(),
+                        note,
+                    },
+                )?)
+            }
         }
     }
 
@@ -2188,11 +4384,51 @@ Do NOT pass `framework` unless auto-detection fails. For C++, provide `command`
         // Verify session exists
         let _ = self.require_session(&req.session_id)?;
 
+        // Sessions of an open investigation are kept for its summary
+        let retain = match req.retain {
+            Some(retain) => retain,
+            None => self
+                .session_manager
+                .db()
+                .investigation_for_session(&req.session_id)?
+                .is_some_and(|inv| inv.status == crate::db::InvestigationStatus::Open),
+        };
+
+        // Last heap report while the agent is still there: whatever is live now
+        // leaked. A retained session keeps the leaks as `leak` events.
+        let mut leak_events = None;
+        let heap = if self.session_manager.is_heap_tracking(&req.session_id) {
+            match self
+                .session_manager
+                .heap(&req.session_id, crate::mcp::HeapAction::Stop, 20, retain)
+                .await
+            {
+                Ok(payload) => {
+                    if retain {
+                        match self.session_manager.record_leaks(&req.session_id, &payload) {
+                            Ok(count) => leak_events = Some(count),
+                            Err(e) => {
+                                tracing::warn!("Storing leaks of {} failed: {}", req.session_id, e)
+                            }
+                        }
+                    }
+                    Some(crate::mcp::DebugHeapResponse::from_agent(
+                        &req.session_id,
+                        &payload,
+                    ))
+                }
+                Err(e) => {
+                    tracing::warn!("Final heap report for {} failed: {}", req.session_id, e);
+                    None
+                }
+            }
+        } else {
+            None
+        };
+
         // Stop Frida session
         self.session_manager.stop_frida(&req.session_id).await?;
 
-        let retain = req.retain.unwrap_or(false);
-
         // Mark session as retained BEFORE stop_session, which deletes the DB rows.
         // When retaining, we skip the DB deletion so events remain queryable.
         if retain {
@@ -2223,13 +4459,33 @@ Do NOT pass `framework` unless auto-detection fails. For C++, provide `command`
         let response = DebugStopResponse {
             success: true,
             events_collected,
+            heap,
+            leak_events,
         };
 
         Ok(serde_json::to_value(response)?)
     }
 
-    async fn tool_debug_list_sessions(&self) -> Result<serde_json::Value> {
-        let sessions = self.session_manager.db().list_retained_sessions()?;
+    async fn tool_debug_list_sessions(
+        &self,
+        project_root: Option<&str>,
+    ) -> Result<serde_json::Value> {
+        let project_root = project_root.map(crate::db::normalize_project_root);
+        let sessions = self
+            .session_manager
+            .db()
+            .list_retained_sessions_for(project_root.as_deref())?;
+        let investigations: HashMap<&str, String> = sessions
+            .iter()
+            .filter_map(|s| {
+                let inv = self
+                    .session_manager
+                    .db()
+                    .investigation_for_session(&s.id)
+                    .ok()??;
+                Some((s.id.as_str(), inv.id))
+            })
+            .collect();
 
         let session_list: Vec<serde_json::Value> = sessions
             .iter()
@@ -2237,12 +4493,14 @@ Do NOT pass `framework` unless auto-detection fails. For C++, provide `command`
                 serde_json::json!({
                     "sessionId": s.id,
                     "binaryPath": s.binary_path,
+                    "projectRoot": s.project_root,
                     "pid": s.pid,
                     "startedAt": s.started_at,
                     "endedAt": s.ended_at,
                     "status": s.status.as_str(),
                     "retainedAt": s.retained_at,
                     "sizeBytes": s.size_bytes,
+                    "investigationId": investigations.get(s.id.as_str()),
                 })
             })
             .collect();
@@ -2286,8 +4544,9 @@ Do NOT pass `framework` unless auto-detection fails. For C++, provide `command`
         args: &serde_json::Value,
         connection_id: &str,
     ) -> Result<serde_json::Value> {
-        let req: crate::mcp::DebugTestRequest = serde_json::from_value(args.clone())?;
+        let mut req: crate::mcp::DebugTestRequest = serde_json::from_value(args.clone())?;
         req.validate()?;
+        req.project_root = crate::db::normalize_project_root(&req.project_root);
 
         match req.action.as_ref().unwrap_or(&crate::mcp::TestAction::Run) {
             crate::mcp::TestAction::Run => self.tool_debug_test_run(args, connection_id).await,
@@ -2296,9 +4555,55 @@ Do NOT pass `framework` unless auto-detection fails. For C++, provide `command`
                 let status_req = serde_json::json!({ "testRunId": test_run_id });
                 self.tool_debug_test_status(&status_req).await
             }
+            crate::mcp::TestAction::List => self.tool_debug_test_list(&req).await,
         }
     }
 
+    /// Test inventory per detected adapter — names, files, baseline durations
+    /// and last status — without running anything.
+    async fn tool_debug_test_list(
+        &self,
+        req: &crate::mcp::DebugTestRequest,
+    ) -> Result<serde_json::Value> {
+        let session_manager = std::sync::Arc::clone(&self.session_manager);
+        let req = req.clone();
+        // List modes may compile the project — keep it off the async workers
+        let inventories = tokio::task::spawn_blocking(move || {
+            let runner = crate::test::TestRunner::new();
+            let project_root = std::path::Path::new(&req.project_root);
+            let history = session_manager
+                .db()
+                .get_test_history(&req.project_root)
+                .unwrap_or_default();
+            runner
+                .detect_adapters(
+                    project_root,
+                    req.framework.as_deref(),
+                    req.command.as_deref(),
+                )
+                .map(|adapters| {
+                    adapters
+                        .into_iter()
+                        .map(|adapter| {
+                            crate::test::inventory::build_inventory(
+                                adapter,
+                                project_root,
+                                req.command.as_deref(),
+                                &history,
+                            )
+                        })
+                        .collect::<Vec<_>>()
+                })
+        })
+        .await
+        .map_err(|e| crate::Error::Internal(format!("Test listing task failed: {}", e)))??;
+
+        Ok(serde_json::json!({
+            "adapters": inventories,
+            "hint": "Nothing was run. Pass a name (or substring) as `test` with action: 'run' to run a subset.",
+        }))
+    }
+
     async fn tool_debug_test_run(
         &self,
         args: &serde_json::Value,
@@ -2307,7 +4612,11 @@ Do NOT pass `framework` unless auto-detection fails. For C++, provide `command`
         // Cleanup stale runs
         self.cleanup_stale_test_runs().await;
 
-        let req: crate::mcp::DebugTestRequest = serde_json::from_value(args.clone())?;
+        let mut req: crate::mcp::DebugTestRequest = serde_json::from_value(args.clone())?;
+        req.project_root = crate::db::normalize_project_root(&req.project_root);
+        self.session_manager
+            .db()
+            .register_project(&req.project_root, None)?;
 
         // Detect framework name for the start response (outside lock)
         let runner = crate::test::TestRunner::new();
@@ -2380,6 +4689,10 @@ Do NOT pass `framework` unless auto-detection fails. For C++, provide `command`
         let run_id = test_run_id.clone();
         let test_runs = std::sync::Arc::clone(&self.test_runs);
         let req_clone = req.clone();
+        // Only surfaced in responses for non-default runs
+        let uninstrumented = req
+            .instrumentation
+            .filter(|i| *i == crate::mcp::TestInstrumentation::None);
 
         tokio::spawn(async move {
             let runner = crate::test::TestRunner::new();
@@ -2402,6 +4715,8 @@ Do NOT pass `framework` unless auto-detection fails. For C++, provide `command`
                     &connection_id_owned,
                     &session_id_clone,
                     progress_clone,
+                    req_clone.instrumentation.unwrap_or_default(),
+                    req_clone.order.unwrap_or_default(),
                 )
                 .await;
 
@@ -2410,8 +4725,32 @@ Do NOT pass `framework` unless auto-detection fails. For C++, provide `command`
             // can interfere with subsequent test runs on the same connection.
             let _ = session_manager.stop_frida(&session_id_clone).await;
 
-            // Record baselines for completed tests
+            // Record baselines for completed tests, noting toolchain changes
+            // since the previous run first
+            let mut toolchain_changes = Vec::new();
             if let Ok(ref run_result) = run_result {
+                let previous = session_manager
+                    .db()
+                    .last_test_provenance(project_root.to_str().unwrap_or("."))
+                    .ok()
+                    .flatten()
+                    .and_then(|p| {
+                        serde_json::from_str::<crate::test::provenance::Provenance>(&p).ok()
+                    });
+                if let Some(previous) = previous {
+                    toolchain_changes = crate::test::provenance::describe_changes(
+                        &previous,
+                        &run_result.provenance,
+                    );
+                }
+                if !run_result.provenance.is_empty() {
+                    if let Ok(provenance) = serde_json::to_string(&run_result.provenance) {
+                        let _ = session_manager.db().record_test_provenance(
+                            project_root.to_str().unwrap_or("."),
+                            &provenance,
+                        );
+                    }
+                }
                 for test_detail in &run_result.result.all_tests {
                     let _ = session_manager.db().record_test_baseline(
                         &test_detail.name,
@@ -2494,9 +4833,34 @@ Do NOT pass `framework` unless auto-detection fails. For C++, provide `command`
                             exception_message: crash.exception_message.clone(),
                             top_frame,
                             throw_top_frame,
+                            dump_path: crash.dump_path.clone(),
+                            history: session_manager.db().crash_history(crash),
                         })
                     });
 
+                    // Compare traced functions with the last green run, then
+                    // keep this run as a baseline
+                    let return_anomalies = match run_result.session_id.as_deref() {
+                        Some(sid) if uninstrumented.is_none() => {
+                            let summary = &run_result.result.summary;
+                            let passed =
+                                summary.failed == 0 && summary.passed > 0 && crash_info.is_none();
+                            session_manager
+                                .db()
+                                .check_return_anomalies(
+                                    project_root.to_str().unwrap_or("."),
+                                    req_clone.test.as_deref(),
+                                    sid,
+                                    passed,
+                                )
+                                .unwrap_or_else(|e| {
+                                    tracing::warn!("Return value baselines for {}: {}", sid, e);
+                                    Vec::new()
+                                })
+                        }
+                        _ => Vec::new(),
+                    };
+
                     let response = crate::mcp::DebugTestResponse {
                         framework: run_result.framework,
                         summary: Some(run_result.result.summary),
@@ -2508,6 +4872,12 @@ Do NOT pass `framework` unless auto-detection fails. For C++, provide `command`
                         project: None,
                         hint,
                         crash_info,
+                        instrumentation: uninstrumented,
+                        prioritized: run_result.prioritized,
+                        return_anomalies,
+                        provenance: (!run_result.provenance.is_empty())
+                            .then_some(run_result.provenance),
+                        toolchain_changes,
                     };
 
                     match serde_json::to_value(response) {
@@ -2537,6 +4907,7 @@ Do NOT pass `framework` unless auto-detection fails. For C++, provide `command`
             test_run_id,
             status: "running".to_string(),
             framework: framework_name,
+            instrumentation: uninstrumented,
         };
 
         Ok(serde_json::to_value(response)?)
@@ -2744,6 +5115,7 @@ Do NOT pass `framework` unless auto-detection fails. For C++, provide `command`
     async fn tool_debug_breakpoint(&self, args: &serde_json::Value) -> Result<serde_json::Value> {
         let req: crate::mcp::DebugBreakpointRequest = serde_json::from_value(args.clone())?;
         req.validate()?;
+        self.session_manager.require_instrumented(&req.session_id)?;
 
         let mut all_breakpoints = Vec::new();
         let mut all_logpoints = Vec::new();
@@ -2763,17 +5135,25 @@ Do NOT pass `framework` unless auto-detection fails. For C++, provide `command`
                             target.line,
                             message,
                             target.condition,
+                            crate::daemon::session_manager::LogpointHits::new(
+                                target.hit_count,
+                                target.every_n,
+                                target.max_hits,
+                            ),
                         )
                         .await?;
                     all_logpoints.push(logpoint);
                 } else {
                     // Breakpoint path: no message
+                    let address = target.address_value();
                     let breakpoint = self
                         .session_manager
                         .set_breakpoint_async(
                             &req.session_id,
                             None,
                             target.function,
+                            target.offset,
+                            address,
                             target.file,
                             target.line,
                             target.condition,
@@ -2800,84 +5180,834 @@ Do NOT pass `framework` unless auto-detection fails. For C++, provide `command`
             }
         }
 
-        // Return current breakpoints if none were just added
-        if all_breakpoints.is_empty() {
-            all_breakpoints = self
-                .session_manager
-                .get_breakpoints(&req.session_id)
-                .into_iter()
-                .map(|bp| crate::mcp::BreakpointInfo {
-                    id: bp.id,
-                    function: match &bp.target {
-                        crate::daemon::session_manager::BreakpointTarget::Function(f) => {
-                            Some(f.clone())
-                        }
-                        _ => None,
-                    },
-                    file: match &bp.target {
-                        crate::daemon::session_manager::BreakpointTarget::Line { file, .. } => {
-                            Some(file.clone())
-                        }
-                        _ => None,
-                    },
-                    line: match &bp.target {
-                        crate::daemon::session_manager::BreakpointTarget::Line { line, .. } => {
-                            Some(*line)
-                        }
-                        _ => None,
-                    },
-                    address: format!("0x{:x}", bp.address),
-                })
-                .collect();
-        }
+        // Return current breakpoints if none were just added
+        if all_breakpoints.is_empty() {
+            all_breakpoints = self
+                .session_manager
+                .get_breakpoints(&req.session_id)
+                .into_iter()
+                .map(|bp| crate::mcp::BreakpointInfo {
+                    id: bp.id,
+                    function: match &bp.target {
+                        crate::daemon::session_manager::BreakpointTarget::Function(f)
+                        | crate::daemon::session_manager::BreakpointTarget::Offset {
+                            function: f,
+                            ..
+                        } => Some(f.clone()),
+                        _ => None,
+                    },
+                    offset: match &bp.target {
+                        crate::daemon::session_manager::BreakpointTarget::Offset {
+                            offset, ..
+                        } => Some(*offset),
+                        _ => None,
+                    },
+                    file: match &bp.target {
+                        crate::daemon::session_manager::BreakpointTarget::Line { file, .. } => {
+                            Some(file.clone())
+                        }
+                        _ => None,
+                    },
+                    line: match &bp.target {
+                        crate::daemon::session_manager::BreakpointTarget::Line { line, .. } => {
+                            Some(*line)
+                        }
+                        _ => None,
+                    },
+                    address: format!("0x{:x}", bp.address),
+                })
+                .collect();
+        }
+
+        // Return current logpoints if none were just added
+        if all_logpoints.is_empty() {
+            all_logpoints = self
+                .session_manager
+                .get_logpoints(&req.session_id)
+                .into_iter()
+                .map(|lp| crate::mcp::LogpointInfo {
+                    id: lp.id,
+                    message: lp.message,
+                    function: match &lp.target {
+                        crate::daemon::session_manager::BreakpointTarget::Function(f) => {
+                            Some(f.clone())
+                        }
+                        _ => None,
+                    },
+                    file: match &lp.target {
+                        crate::daemon::session_manager::BreakpointTarget::Line { file, .. } => {
+                            Some(file.clone())
+                        }
+                        _ => None,
+                    },
+                    line: match &lp.target {
+                        crate::daemon::session_manager::BreakpointTarget::Line { line, .. } => {
+                            Some(*line)
+                        }
+                        _ => None,
+                    },
+                    address: format!("0x{:x}", lp.address),
+                })
+                .collect();
+        }
+
+        Ok(serde_json::to_value(crate::mcp::DebugBreakpointResponse {
+            breakpoints: all_breakpoints,
+            logpoints: all_logpoints,
+        })?)
+    }
+
+    async fn tool_debug_watchpoint(&self, args: &serde_json::Value) -> Result<serde_json::Value> {
+        let req: crate::mcp::DebugWatchpointRequest = serde_json::from_value(args.clone())?;
+        req.validate()?;
+        self.session_manager.require_instrumented(&req.session_id)?;
+
+        let add = req.add.unwrap_or_default();
+        let remove = req.remove.unwrap_or_default();
+
+        // Nothing to change: just list what's active
+        let watchpoints = if add.is_empty() && remove.is_empty() {
+            self.session_manager
+                .get_watchpoints(&req.session_id)
+                .iter()
+                .map(|wp| wp.info())
+                .collect()
+        } else {
+            self.session_manager
+                .update_watchpoints_async(&req.session_id, add, remove)
+                .await?
+        };
+
+        Ok(serde_json::to_value(crate::mcp::DebugWatchpointResponse {
+            watchpoints,
+        })?)
+    }
+
+    async fn tool_debug_continue(&self, args: &serde_json::Value) -> Result<serde_json::Value> {
+        let req: crate::mcp::DebugContinueRequest = serde_json::from_value(args.clone())?;
+        req.validate()?;
+        self.session_manager.require_instrumented(&req.session_id)?;
+
+        let response = match (req.count, req.action) {
+            (Some(count), Some(action)) => {
+                self.session_manager
+                    .debug_step_repeat_async(&req.session_id, action, count)
+                    .await?
+            }
+            (_, action) => {
+                self.session_manager
+                    .debug_continue_async(&req.session_id, action, req.until)
+                    .await?
+            }
+        };
+
+        Ok(serde_json::to_value(response)?)
+    }
+
+    async fn tool_debug_crashes(&self, args: &serde_json::Value) -> Result<serde_json::Value> {
+        let req: crate::mcp::DebugCrashesRequest = serde_json::from_value(args.clone())?;
+        req.validate()?;
+        let project_root = match (req.project_root, req.session_id) {
+            (Some(root), _) if !root.is_empty() => crate::db::normalize_project_root(&root),
+            (_, Some(session_id)) => self.require_session(&session_id)?.project_root,
+            _ => unreachable!("validated above"),
+        };
+        let limit = req.limit.unwrap_or(20).min(200) as usize;
+
+        let now = chrono::Utc::now().timestamp();
+        let crashes = self
+            .session_manager
+            .db()
+            .list_crashes(&project_root, limit)?
+            .into_iter()
+            .map(|record| crate::mcp::CrashHistoryEntry {
+                history: record.history(now),
+                record,
+            })
+            .collect();
+
+        let response = crate::mcp::DebugCrashesResponse {
+            project_root,
+            crashes,
+        };
+        Ok(serde_json::to_value(response)?)
+    }
+
+    async fn tool_debug_investigation(
+        &self,
+        args: &serde_json::Value,
+    ) -> Result<serde_json::Value> {
+        let req: crate::mcp::DebugInvestigationRequest = serde_json::from_value(args.clone())?;
+        req.validate()?;
+        let db = self.session_manager.db();
+
+        if req.action == InvestigationAction::Create {
+            let project_root = match (req.project_root, req.session_id) {
+                (Some(root), _) if !root.is_empty() => crate::db::normalize_project_root(&root),
+                (_, Some(session_id)) => self.require_session(&session_id)?.project_root,
+                _ => unreachable!("validated above"),
+            };
+            let id = format!("inv-{}", &uuid::Uuid::new_v4().to_string()[..8]);
+            let investigation =
+                db.create_investigation(&id, req.title.as_deref().unwrap(), &project_root)?;
+            return Ok(serde_json::to_value(investigation)?);
+        }
+        if req.action == InvestigationAction::List {
+            let limit = req.limit.unwrap_or(20).min(200) as usize;
+            let investigations = db
+                .list_investigations(
+                    req.project_root
+                        .as_deref()
+                        .map(crate::db::normalize_project_root)
+                        .as_deref(),
+                    limit,
+                )?
+                .into_iter()
+                .map(|investigation| {
+                    Ok(crate::mcp::InvestigationListEntry {
+                        items: db.investigation_item_count(&investigation.id)?,
+                        investigation,
+                    })
+                })
+                .collect::<Result<Vec<_>>>()?;
+            return Ok(serde_json::to_value(
+                crate::mcp::DebugInvestigationListResponse { investigations },
+            )?);
+        }
+
+        let investigation_id = req.investigation_id.as_deref().unwrap();
+        let investigation = db.get_investigation(investigation_id)?.ok_or_else(|| {
+            crate::Error::ValidationError(format!(
+                "Investigation {} not found. List them with action: 'list'.",
+                investigation_id
+            ))
+        })?;
+
+        let link = match req.action {
+            InvestigationAction::AddSession => {
+                let session_id = req.session_id.as_deref().unwrap();
+                self.require_session(session_id)?;
+                Some((
+                    crate::db::InvestigationItemKind::Session,
+                    Some(session_id),
+                    None,
+                ))
+            }
+            InvestigationAction::AddTestRun => {
+                let test_run_id = req.test_run_id.as_deref().unwrap();
+                let details = self
+                    .test_runs
+                    .read()
+                    .await
+                    .get(test_run_id)
+                    .map(test_run_entry)
+                    .ok_or_else(|| crate::Error::TestRunNotFound(test_run_id.to_string()))?;
+                Some((
+                    crate::db::InvestigationItemKind::TestRun,
+                    Some(test_run_id),
+                    Some(details),
+                ))
+            }
+            InvestigationAction::AddNote => {
+                Some((crate::db::InvestigationItemKind::Note, None, None))
+            }
+            _ => None,
+        };
+        if let Some((kind, ref_id, details)) = link {
+            if investigation.status == crate::db::InvestigationStatus::Closed {
+                return Err(crate::Error::ValidationError(format!(
+                    "Investigation {} is closed",
+                    investigation_id
+                )));
+            }
+            let items = db.investigation_item_count(investigation_id)?;
+            if items >= crate::db::MAX_INVESTIGATION_ITEMS {
+                return Err(crate::Error::ValidationError(format!(
+                    "Investigation {} already has {} items",
+                    investigation_id,
+                    crate::db::MAX_INVESTIGATION_ITEMS
+                )));
+            }
+            let added = db.add_investigation_item(
+                investigation_id,
+                kind,
+                ref_id,
+                req.note.as_deref(),
+                details.as_ref(),
+            )?;
+            return Ok(serde_json::to_value(crate::mcp::InvestigationItemAdded {
+                investigation_id: investigation_id.to_string(),
+                added,
+                items: items + added as usize,
+            })?);
+        }
+
+        let investigation = if req.action == InvestigationAction::Close {
+            db.close_investigation(investigation_id, req.conclusion.as_deref())?;
+            db.get_investigation(investigation_id)?
+                .unwrap_or(investigation)
+        } else {
+            investigation
+        };
+        let summary = self.investigation_summary(investigation).await?;
+        Ok(serde_json::to_value(summary)?)
+    }
+
+    /// An investigation with each linked session's current state; test runs
+    /// the daemon still tracks are refreshed.
+    async fn investigation_summary(
+        &self,
+        investigation: crate::db::Investigation,
+    ) -> Result<crate::mcp::InvestigationSummary> {
+        use crate::db::InvestigationItemKind;
+        let db = self.session_manager.db();
+        let items = db.investigation_items(&investigation.id)?;
+        let runs = self.test_runs.read().await;
+
+        let mut summary = crate::mcp::InvestigationSummary {
+            investigation,
+            sessions: 0,
+            test_runs: 0,
+            notes: 0,
+            crashes: 0,
+            items: Vec::new(),
+        };
+        for mut item in items {
+            let mut session = None;
+            let mut deleted = false;
+            match item.kind {
+                InvestigationItemKind::Session => {
+                    summary.sessions += 1;
+                    let id = item.ref_id.as_deref().unwrap_or_default();
+                    match db.get_session(id)? {
+                        Some(s) => {
+                            let crashes = db.count_filtered_events(id, |q| {
+                                q.event_type(crate::db::EventType::Crash)
+                            })?;
+                            summary.crashes += crashes;
+                            session = Some(crate::mcp::InvestigationSessionInfo {
+                                binary_path: s.binary_path,
+                                status: s.status.as_str().to_string(),
+                                started_at: s.started_at,
+                                ended_at: s.ended_at,
+                                retained: s.retained,
+                                event_count: db.count_session_events(id)?,
+                                crashes,
+                            });
+                        }
+                        None => deleted = true,
+                    }
+                }
+                InvestigationItemKind::TestRun => {
+                    summary.test_runs += 1;
+                    if let Some(run) = item.ref_id.as_deref().and_then(|id| runs.get(id)) {
+                        item.details = Some(test_run_entry(run));
+                    }
+                }
+                InvestigationItemKind::Note => summary.notes += 1,
+            }
+            summary.items.push(crate::mcp::InvestigationSummaryItem {
+                item,
+                session,
+                deleted,
+            });
+        }
+        Ok(summary)
+    }
+
+    async fn tool_debug_health(&self) -> Result<serde_json::Value> {
+        let db = self.session_manager.db();
+        let response = crate::mcp::DebugHealthResponse {
+            active_sessions: self.session_manager.get_running_sessions()?.len(),
+            query_timeout_ms: crate::config::resolve(None).query_timeout_ms,
+            slow_query_threshold_ms: crate::db::SLOW_QUERY_THRESHOLD.as_millis() as u64,
+            slow_queries: db.slow_queries(),
+        };
+        Ok(serde_json::to_value(response)?)
+    }
+
+    async fn tool_debug_config(&self, args: &serde_json::Value) -> Result<serde_json::Value> {
+        let req: crate::mcp::DebugConfigRequest = serde_json::from_value(args.clone())?;
+        let project_root = match (req.project_root, req.session_id) {
+            (Some(root), _) if !root.is_empty() => Some(crate::db::normalize_project_root(&root)),
+            (_, Some(session_id)) if !session_id.is_empty() => {
+                Some(self.require_session(&session_id)?.project_root)
+            }
+            _ => None,
+        };
+
+        let report = crate::config::report(project_root.as_deref().map(Path::new));
+        let settings = report
+            .settings
+            .keyed_values()
+            .into_iter()
+            .map(|(key, value)| {
+                let setting = crate::mcp::ConfigSetting {
+                    value,
+                    source: report.source_of(key),
+                };
+                (key.to_string(), setting)
+            })
+            .collect();
+        let response = crate::mcp::DebugConfigResponse {
+            project_root,
+            layers: report.layers,
+            settings,
+            issues: report.issues,
+        };
+        Ok(serde_json::to_value(response)?)
+    }
+
+    async fn tool_debug_timeline(&self, args: &serde_json::Value) -> Result<serde_json::Value> {
+        use crate::db::{EventType, SiblingDirection};
+        use crate::mcp::TimelineAction;
+
+        let req: crate::mcp::DebugTimelineRequest = serde_json::from_value(args.clone())?;
+        req.validate()?;
+        self.require_session(&req.session_id)?;
+        let sid = req.session_id.as_str();
+        let db = self.session_manager.db();
+        let verbose = req.verbose.unwrap_or(false);
+
+        // Resolve the origin to its call: exits point at their enter.
+        let origin = match req.event_id.as_deref() {
+            Some(id) => {
+                let event = db.get_event(sid, id)?.ok_or_else(|| {
+                    crate::Error::ValidationError(format!("Event '{}' not found in session", id))
+                })?;
+                let call = match event.event_type {
+                    EventType::FunctionEnter => Some(event),
+                    EventType::FunctionExit => match event.parent_event_id {
+                        Some(ref enter) => db.get_event(sid, enter)?,
+                        None => None,
+                    },
+                    _ => None,
+                };
+                Some(call.ok_or_else(|| {
+                    crate::Error::ValidationError(format!(
+                        "Event '{}' is not a traced call (function_enter/function_exit)",
+                        id
+                    ))
+                })?)
+            }
+            None => None,
+        };
+
+        let mut has_more = false;
+        let calls = match (req.action, origin) {
+            (TimelineAction::Stack, Some(call)) => {
+                db.call_stack_at(sid, call.thread_id, call.timestamp_ns)?
+            }
+            (TimelineAction::Stack, None) => db.call_stack_at(
+                sid,
+                req.thread_id.unwrap_or_default(),
+                req.timestamp_ns.unwrap_or_default(),
+            )?,
+            (_, None) => unreachable!("validated above"),
+            (TimelineAction::Event, Some(call)) => vec![call],
+            (TimelineAction::Parent, Some(call)) => match call.parent_event_id.as_deref() {
+                Some(parent) => db.get_event(sid, parent)?.into_iter().collect(),
+                None => Vec::new(),
+            },
+            (TimelineAction::Children, Some(call)) => {
+                let limit = req.limit.unwrap_or(50);
+                let mut children =
+                    db.call_children(sid, &call.id, req.offset.unwrap_or(0), limit)?;
+                has_more = children.len() > limit as usize;
+                children.truncate(limit as usize);
+                children
+            }
+            (TimelineAction::Prev, Some(call)) => db
+                .call_sibling(sid, &call, SiblingDirection::Prev)?
+                .into_iter()
+                .collect(),
+            (TimelineAction::Next, Some(call)) => db
+                .call_sibling(sid, &call, SiblingDirection::Next)?
+                .into_iter()
+                .collect(),
+        };
+
+        let calls = calls
+            .iter()
+            .map(|call| {
+                let mut v = format_event(call, verbose);
+                if let Some(exit) = db.call_exit(sid, &call.id)? {
+                    v["exit"] = serde_json::json!({
+                        "id": exit.id,
+                        "timestamp_ns": exit.timestamp_ns,
+                        "duration_ns": exit.duration_ns,
+                        "returnValue": exit.return_value,
+                    });
+                }
+                Ok(v)
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let response = crate::mcp::DebugTimelineResponse { calls, has_more };
+        Ok(serde_json::to_value(response)?)
+    }
+
+    async fn tool_debug_coverage(&self, args: &serde_json::Value) -> Result<serde_json::Value> {
+        use crate::export::coverage::{to_drcov, CoverageData, SourceCoverage};
+        use crate::mcp::{
+            CoverageAction, CoverageFormat, CoverageGranularity, CoverageScope, MAX_COVERAGE_FILES,
+        };
+
+        let req: crate::mcp::DebugCoverageRequest = serde_json::from_value(args.clone())?;
+        req.validate()?;
+        let session = self.require_session(&req.session_id)?;
+        if session.status != crate::db::SessionStatus::Running {
+            return Err(crate::Error::ValidationError(format!(
+                "Process not running (PID {} exited). Coverage is collected from a live process.",
+                session.pid
+            )));
+        }
+
+        let payload = self
+            .session_manager
+            .coverage(&req.session_id, req.action, req.scope.unwrap_or_default())
+            .await?;
+        let scope: CoverageScope = payload
+            .get("scope")
+            .and_then(|v| serde_json::from_value(v.clone()).ok())
+            .unwrap_or_default();
+        let mut response = crate::mcp::DebugCoverageResponse {
+            session_id: req.session_id.clone(),
+            active: payload
+                .get("active")
+                .and_then(|v| v.as_bool())
+                .unwrap_or(false),
+            scope,
+            threads: payload.get("threads").and_then(|v| v.as_u64()).unwrap_or(0) as u32,
+            blocks: None,
+            granularity: None,
+            covered: None,
+            total: None,
+            files: Vec::new(),
+            files_omitted: None,
+            path: None,
+            format: None,
+            warnings: Vec::new(),
+        };
+        if req.action == CoverageAction::Start {
+            return Ok(serde_json::to_value(response)?);
+        }
+
+        let data = CoverageData::from_agent(&payload)?;
+        if payload.get("truncated").and_then(|v| v.as_bool()) == Some(true) {
+            response
+                .warnings
+                .push("Block limit reached; later blocks were not recorded".to_string());
+        }
+        let granularity = req.granularity.unwrap_or_default();
+        let format = req.format.unwrap_or_default();
+
+        let source = match self.session_manager.get_dwarf(&req.session_id).await? {
+            Some(dwarf) => {
+                let project_root =
+                    (scope == CoverageScope::Usercode).then_some(session.project_root.as_str());
+                let with_lines = granularity == CoverageGranularity::Block;
+                Some(
+                    tokio::task::spawn_blocking({
+                        let data = data.clone();
+                        let project_root = project_root.map(String::from);
+                        move || {
+                            SourceCoverage::build(
+                                &data,
+                                &dwarf,
+                                project_root.as_deref(),
+                                with_lines,
+                            )
+                        }
+                    })
+                    .await
+                    .map_err(|e| {
+                        crate::Error::Internal(format!("Coverage mapping failed: {}", e))
+                    })?,
+                )
+            }
+            None => {
+                response.warnings.push(
+                    "No debug symbols: per-file coverage unavailable (the drcov file still lists every block)"
+                        .to_string(),
+                );
+                None
+            }
+        };
+
+        let document =
+            match (format, &source) {
+                (CoverageFormat::Drcov, _) => to_drcov(&data),
+                (CoverageFormat::Lcov, Some(source)) => source.to_lcov().into_bytes(),
+                (CoverageFormat::Lcov, None) => return Err(crate::Error::ValidationError(
+                    "lcov needs debug symbols to map blocks to source lines; use format 'drcov'"
+                        .to_string(),
+                )),
+            };
+        let path = match req.output_path {
+            Some(p) => project_output_path(&p, &session.project_root)?,
+            None => std::path::PathBuf::from("/tmp/strobe/coverage").join(format!(
+                "{}.{}",
+                req.session_id,
+                format.extension()
+            )),
+        };
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        tokio::fs::write(&path, document).await?;
+
+        if let Some(source) = source {
+            let mut files = source.files(granularity);
+            response.covered = Some(files.iter().map(|f| f.covered).sum());
+            response.total = Some(files.iter().map(|f| f.total).sum());
+            if files.len() > MAX_COVERAGE_FILES {
+                response.files_omitted = Some(files.len() - MAX_COVERAGE_FILES);
+                response.warnings.push(format!(
+                    "Listing the first {} of {} files; the full report is in {}",
+                    MAX_COVERAGE_FILES,
+                    files.len(),
+                    path.display()
+                ));
+                files.truncate(MAX_COVERAGE_FILES);
+            }
+            response.files = files;
+            response.granularity = Some(granularity);
+        }
+        response.blocks = Some(data.blocks.len() as u64);
+        response.path = Some(path.to_string_lossy().into_owned());
+        response.format = Some(format);
+        Ok(serde_json::to_value(response)?)
+    }
+
+    async fn tool_debug_heap(&self, args: &serde_json::Value) -> Result<serde_json::Value> {
+        let req: crate::mcp::DebugHeapRequest = serde_json::from_value(args.clone())?;
+        req.validate()?;
+        let session = self.require_session(&req.session_id)?;
+        if session.status != crate::db::SessionStatus::Running {
+            return Err(crate::Error::ValidationError(format!(
+                "Process not running (PID {} exited). Allocations are tracked in a live process.",
+                session.pid
+            )));
+        }
+
+        let payload = self
+            .session_manager
+            .heap(&req.session_id, req.action, req.limit.unwrap_or(20), false)
+            .await?;
+        let response = crate::mcp::DebugHeapResponse::from_agent(&req.session_id, &payload);
+        Ok(serde_json::to_value(response)?)
+    }
+
+    async fn tool_debug_symbols(&self, args: &serde_json::Value) -> Result<serde_json::Value> {
+        let req: crate::mcp::DebugSymbolsRequest = serde_json::from_value(args.clone())?;
+        req.validate()?;
+        self.require_session(&req.session_id)?;
+        if let Some(caps) = self.session_manager.get_capabilities(&req.session_id) {
+            if caps.runtime != "native" {
+                return Err(crate::Error::ValidationError(format!(
+                    "debug_symbols reads native debug symbols; use trace pattern lint/preview for {} sessions",
+                    caps.runtime
+                )));
+            }
+        }
+
+        let response = self.session_manager.search_symbols(&req).await?;
+        Ok(serde_json::to_value(response)?)
+    }
+
+    async fn tool_debug_batch(
+        &self,
+        args: &serde_json::Value,
+        connection_id: &str,
+    ) -> Result<serde_json::Value> {
+        let req: crate::mcp::DebugBatchRequest = serde_json::from_value(args.clone())?;
+        req.validate()?;
+        if let Some(ref session_id) = req.session_id {
+            self.require_session(session_id)?;
+        }
+
+        let mut results = Vec::with_capacity(req.calls.len());
+        for (i, call) in req.calls.iter().enumerate() {
+            // Boxed: call_tool is what dispatched this batch
+            let outcome =
+                Box::pin(self.call_tool(&call.tool, &req.arguments(i), connection_id)).await;
+            let failed = outcome.is_err();
+            results.push(match outcome {
+                Ok(value) => crate::mcp::BatchResult {
+                    tool: call.tool.clone(),
+                    result: Some(value),
+                    error: None,
+                },
+                Err(e) => crate::mcp::BatchResult {
+                    tool: call.tool.clone(),
+                    result: None,
+                    error: Some(e.into()),
+                },
+            });
+            if failed {
+                break;
+            }
+        }
+
+        let response = crate::mcp::DebugBatchResponse {
+            skipped: req.calls.len() - results.len(),
+            results,
+        };
+        Ok(serde_json::to_value(response)?)
+    }
+
+    async fn tool_debug_diff(&self, args: &serde_json::Value) -> Result<serde_json::Value> {
+        let req: crate::mcp::DebugDiffRequest = serde_json::from_value(args.clone())?;
+        req.validate()?;
+        let base = self.require_session(&req.base_session_id)?;
+        let session = self.require_session(&req.session_id)?;
+
+        let binary_name = |s: &crate::db::Session| {
+            std::path::Path::new(&s.binary_path)
+                .file_name()
+                .map(|n| n.to_owned())
+        };
+        if binary_name(&base) != binary_name(&session) {
+            return Err(crate::Error::ValidationError(format!(
+                "Sessions run different binaries ({} vs {}); debug_diff compares runs of the same program",
+                base.binary_path, session.binary_path
+            )));
+        }
+
+        let defaults = crate::db::DiffOptions::default();
+        let options = crate::db::DiffOptions {
+            min_ratio: req.min_ratio.unwrap_or(defaults.min_ratio),
+            limit: req.limit.map(|l| l as usize).unwrap_or(defaults.limit),
+            ..defaults
+        };
+        let diff = self.session_manager.db().diff_sessions(
+            &req.base_session_id,
+            &req.session_id,
+            options,
+        )?;
 
-        // Return current logpoints if none were just added
-        if all_logpoints.is_empty() {
-            all_logpoints = self
-                .session_manager
-                .get_logpoints(&req.session_id)
-                .into_iter()
-                .map(|lp| crate::mcp::LogpointInfo {
-                    id: lp.id,
-                    message: lp.message,
-                    function: match &lp.target {
-                        crate::daemon::session_manager::BreakpointTarget::Function(f) => {
-                            Some(f.clone())
-                        }
-                        _ => None,
-                    },
-                    file: match &lp.target {
-                        crate::daemon::session_manager::BreakpointTarget::Line { file, .. } => {
-                            Some(file.clone())
-                        }
-                        _ => None,
-                    },
-                    line: match &lp.target {
-                        crate::daemon::session_manager::BreakpointTarget::Line { line, .. } => {
-                            Some(*line)
-                        }
-                        _ => None,
-                    },
-                    address: format!("0x{:x}", lp.address),
+        let response = crate::mcp::DebugDiffResponse {
+            base_session_id: req.base_session_id,
+            session_id: req.session_id,
+            diff,
+        };
+        Ok(serde_json::to_value(response)?)
+    }
+
+    async fn tool_debug_tutorial(&self, args: &serde_json::Value) -> Result<serde_json::Value> {
+        use crate::tutorial;
+
+        let req: crate::mcp::DebugTutorialRequest = serde_json::from_value(args.clone())?;
+        req.validate()?;
+
+        let dir = tutorial::tutorial_dir();
+        let build_dir = dir.clone();
+        let binary = tokio::task::spawn_blocking(move || tutorial::build_demo(&build_dir))
+            .await
+            .map_err(|e| crate::Error::Internal(format!("Tutorial build task failed: {}", e)))??;
+        let session_id = req.session_id.as_deref();
+        let step_info = |step: u32| {
+            tutorial::STEPS
+                .get(step as usize - 1)
+                .map(|s| crate::mcp::TutorialStepInfo {
+                    step,
+                    title: s.title.to_string(),
+                    instructions: s.render(&binary, &dir, session_id),
                 })
-                .collect();
-        }
+        };
 
-        Ok(serde_json::to_value(crate::mcp::DebugBreakpointResponse {
-            breakpoints: all_breakpoints,
-            logpoints: all_logpoints,
-        })?)
+        let mut response = crate::mcp::DebugTutorialResponse {
+            binary: binary.to_string_lossy().to_string(),
+            project_root: dir.to_string_lossy().to_string(),
+            steps: Vec::new(),
+            checkpoint: None,
+            next: None,
+        };
+
+        match req.action.unwrap_or_default() {
+            crate::mcp::TutorialAction::Start => {
+                response.steps = (1..=tutorial::STEPS.len() as u32)
+                    .filter_map(step_info)
+                    .collect();
+                response.next = step_info(1);
+            }
+            crate::mcp::TutorialAction::Check => {
+                let step = req.step.unwrap_or(1);
+                let sid = session_id.unwrap_or_default();
+                let session = self.require_session(sid)?;
+                let db = self.session_manager.db();
+                let facts = tutorial::SessionFacts {
+                    is_demo: std::path::Path::new(&session.binary_path)
+                        .file_name()
+                        .is_some_and(|n| n == tutorial::DEMO_BINARY_NAME),
+                    running: session.status == crate::db::SessionStatus::Running,
+                    rejection_logged: db.count_filtered_events(sid, |q| {
+                        q.event_type(crate::db::EventType::Stderr)
+                            .text_contains("rejected")
+                    })? > 0,
+                    traced_calls: db.count_filtered_events(sid, |q| {
+                        q.event_type(crate::db::EventType::FunctionEnter)
+                            .function_contains("process_order")
+                    })?,
+                    watching_revenue: self
+                        .session_manager
+                        .get_watches(sid)
+                        .iter()
+                        .any(|w| w.label.contains("g_revenue_cents")),
+                    breakpoint_hits: db.count_filtered_events(sid, |q| {
+                        q.event_type(crate::db::EventType::Pause)
+                    })?,
+                };
+                let (passed, detail) = tutorial::check(step, &facts, req.answer.as_deref());
+                response.checkpoint = Some(crate::mcp::TutorialCheckpoint {
+                    step,
+                    passed,
+                    detail,
+                });
+                response.next = if passed {
+                    step_info(step + 1)
+                } else {
+                    step_info(step)
+                };
+            }
+        }
+        Ok(serde_json::to_value(response)?)
     }
 
-    async fn tool_debug_continue(&self, args: &serde_json::Value) -> Result<serde_json::Value> {
-        let req: crate::mcp::DebugContinueRequest = serde_json::from_value(args.clone())?;
+    async fn tool_debug_export(&self, args: &serde_json::Value) -> Result<serde_json::Value> {
+        let req: crate::mcp::DebugExportRequest = serde_json::from_value(args.clone())?;
         req.validate()?;
+        let session = self.require_session(&req.session_id)?;
 
-        let response = self
+        let settings = crate::config::resolve(Some(std::path::Path::new(&session.project_root)));
+        let limit = settings.events_max_per_session as u32;
+        let events = self
             .session_manager
-            .debug_continue_async(&req.session_id, req.action)
-            .await?;
+            .db()
+            .query_events(&req.session_id, |q| q.limit_uncapped(limit))?;
+
+        let document = match req.format {
+            crate::mcp::ExportFormat::ChromeTrace => {
+                crate::export::chrome_trace::to_chrome_trace(&req.session_id, &events)
+            }
+        };
 
+        let path = match req.output_path {
+            Some(p) => std::path::PathBuf::from(p),
+            None => std::path::PathBuf::from("/tmp/strobe/exports")
+                .join(format!("{}.trace.json", req.session_id)),
+        };
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&path, serde_json::to_vec(&document)?)?;
+
+        let response = crate::mcp::DebugExportResponse {
+            path: path.to_string_lossy().into_owned(),
+            format: req.format,
+            event_count: events.len(),
+        };
         Ok(serde_json::to_value(response)?)
     }
 
@@ -2893,6 +6023,13 @@ Do NOT pass `framework` unless auto-detection fails. For C++, provide `command`
             )));
         }
 
+        if req.mode == crate::mcp::UiMode::Diff {
+            return self.debug_ui_diff(&req, session.pid).await;
+        }
+        if req.mode == crate::mcp::UiMode::Record {
+            return debug_ui_record(&req, &session).await;
+        }
+
         let start = std::time::Instant::now();
         let vision_requested = req.vision.unwrap_or(false);
         let verbose = req.verbose.unwrap_or(false);
@@ -2902,6 +6039,7 @@ Do NOT pass `framework` unless auto-detection fails. For C++, provide `command`
         let mut ax_count = 0;
         let mut vision_count = 0;
         let mut merged_count = 0;
+        let mut ocr_count = 0;
 
         let needs_tree = matches!(
             req.mode,
@@ -2924,7 +6062,7 @@ Do NOT pass `framework` unless auto-detection fails. For C++, provide `command`
                         crate::Error::Internal(format!("AX query task failed: {}", e))
                     })??;
 
-            #[cfg(target_os = "linux")]
+            #[cfg(not(target_os = "macos"))]
             let nodes = crate::ui::accessibility::query_ax_tree(pid).await?;
 
             ax_count = crate::ui::tree::count_nodes(&nodes);
@@ -2982,22 +6120,26 @@ Do NOT pass `framework` unless auto-detection fails. For C++, provide `command`
                     base64::engine::general_purpose::STANDARD.encode(&png_bytes)
                 };
 
-                // Run vision detection
-                let vision_elements = {
-                    let mut sidecar = self.vision_sidecar.lock().unwrap();
-                    sidecar.detect(
+                // Run vision detection (and OCR)
+                let detection = {
+                    let mut vision = self.vision.lock().unwrap();
+                    vision.backend(&settings)?.analyze(
                         &screenshot_b64,
                         settings.vision_confidence_threshold,
                         settings.vision_iou_merge_threshold,
+                        settings.vision_ocr,
                     )?
                 };
 
                 // COMP-1: Merge vision into tree and capture accurate stats
                 let (actual_merged, actual_added) = crate::ui::merge::merge_vision_into_tree(
                     &mut final_nodes,
-                    &vision_elements,
+                    &detection.elements,
                     settings.vision_iou_merge_threshold as f64,
                 );
+                // Text goes into the merged tree, vision nodes included
+                crate::ui::merge::merge_text_into_tree(&mut final_nodes, &detection.texts);
+                ocr_count = detection.texts.len();
 
                 // Stats semantics:
                 // - vision_nodes: total vision elements added (pure vision nodes)
@@ -3006,8 +6148,14 @@ Do NOT pass `framework` unless auto-detection fails. For C++, provide `command`
                 merged_count = actual_merged;
             }
 
+            if let Some(ref text) = req.text {
+                final_nodes = crate::ui::tree::find_nodes_by_text(&final_nodes, text);
+            }
+
             tree_output = Some(if verbose {
                 crate::ui::tree::format_json(&final_nodes)?
+            } else if req.text.is_some() && final_nodes.is_empty() {
+                "No elements contain this text\n".to_string()
             } else {
                 crate::ui::tree::format_compact(&final_nodes)
             });
@@ -3016,33 +6164,7 @@ Do NOT pass `framework` unless auto-detection fails. For C++, provide `command`
         // Capture screenshot as base64 PNG
         if needs_screenshot {
             let pid = session.pid;
-            let element_bounds = if let Some(ref target_id) = req.id {
-                // Resolve element bounds from AX tree
-                let target_id = target_id.clone();
-
-                #[cfg(target_os = "macos")]
-                let nodes = tokio::task::spawn_blocking(move || {
-                    crate::ui::accessibility::query_ax_tree(pid)
-                })
-                .await
-                .map_err(|e| crate::Error::Internal(format!("AX query task failed: {}", e)))??;
-
-                #[cfg(target_os = "linux")]
-                let nodes = crate::ui::accessibility::query_ax_tree(pid).await?;
-
-                let node = crate::ui::tree::find_node_by_id(&nodes, &target_id)
-                    .ok_or_else(|| crate::Error::UiQueryFailed(
-                        format!("Element '{}' not found. Use debug_ui with mode=tree to see current element IDs.", target_id)
-                    ))?;
-                Some(node.bounds.ok_or_else(|| {
-                    crate::Error::UiQueryFailed(format!(
-                        "Element '{}' has no bounds (may be off-screen or invisible)",
-                        target_id
-                    ))
-                })?)
-            } else {
-                None
-            };
+            let element_bounds = crop_bounds(&req, pid).await?;
 
             let png_bytes = tokio::task::spawn_blocking(move || {
                 if let Some(bounds) = element_bounds {
@@ -3070,8 +6192,12 @@ Do NOT pass `framework` unless auto-detection fails. For C++, provide `command`
                     ax_nodes: ax_count,
                     vision_nodes: vision_count,
                     merged_nodes: merged_count,
+                    ocr_texts: ocr_count,
                     latency_ms,
                 }),
+                diff: None,
+                action: None,
+                recording: None,
             };
             content.push(McpContent::Text {
                 text: serde_json::to_string_pretty(&text_response)?,
@@ -3088,6 +6214,48 @@ Do NOT pass `framework` unless auto-detection fails. For C++, provide `command`
         Ok(content)
     }
 
+    /// debug_ui mode 'diff': snapshot, action and/or wait, snapshot, diff.
+    async fn debug_ui_diff(
+        &self,
+        req: &crate::mcp::DebugUiRequest,
+        pid: u32,
+    ) -> Result<Vec<McpContent>> {
+        let start = std::time::Instant::now();
+        let action = req.diff_action()?;
+
+        let before = query_ui_tree(pid).await?;
+        let action_result = match action {
+            Some(ref action) => Some(crate::ui::input::execute_ui_action(pid, action).await?),
+            None => None,
+        };
+        if let Some(wait_ms) = req.wait_ms {
+            tokio::time::sleep(std::time::Duration::from_millis(wait_ms)).await;
+        }
+        let after = query_ui_tree(pid).await?;
+
+        let diff = crate::ui::tree::diff_trees(&before, &after);
+        let response = crate::mcp::DebugUiResponse {
+            tree: None,
+            stats: Some(crate::mcp::UiStats {
+                ax_nodes: crate::ui::tree::count_nodes(&after),
+                vision_nodes: 0,
+                merged_nodes: 0,
+                ocr_texts: 0,
+                latency_ms: start.elapsed().as_millis() as u64,
+            }),
+            diff: Some(if req.verbose.unwrap_or(false) {
+                serde_json::to_string_pretty(&diff)?
+            } else {
+                crate::ui::tree::format_diff_compact(&diff)
+            }),
+            action: action_result,
+            recording: None,
+        };
+        Ok(vec![McpContent::Text {
+            text: serde_json::to_string_pretty(&response)?,
+        }])
+    }
+
     async fn tool_debug_ui_action(&self, args: &serde_json::Value) -> Result<Vec<McpContent>> {
         let req: crate::mcp::DebugUiActionRequest = serde_json::from_value(args.clone())?;
         req.validate()?;
@@ -3119,24 +6287,7 @@ mod tests {
     /// Create a test Daemon with a temp database
     fn test_daemon() -> (Daemon, tempfile::TempDir) {
         let dir = tempdir().unwrap();
-        let db_path = dir.path().join("test.db");
-        let session_manager = Arc::new(SessionManager::new(&db_path).unwrap());
-
-        let daemon = Daemon {
-            socket_path: dir.path().join("test.sock"),
-            pid_path: dir.path().join("test.pid"),
-            session_manager,
-            last_activity: Arc::new(RwLock::new(Instant::now())),
-            pending_patterns: Arc::new(RwLock::new(HashMap::new())),
-            connection_sessions: Arc::new(RwLock::new(HashMap::new())),
-            test_runs: Arc::new(tokio::sync::RwLock::new(HashMap::new())),
-            shutdown_signal: Arc::new(tokio::sync::Notify::new()),
-            vision_sidecar: Arc::new(std::sync::Mutex::new(
-                crate::ui::vision::VisionSidecar::new(),
-            )),
-            notification_senders: Arc::new(RwLock::new(HashMap::new())),
-        };
-
+        let daemon = Daemon::embedded(&dir.path().join("test.db")).unwrap();
         (daemon, dir)
     }
 
@@ -3164,6 +6315,39 @@ mod tests {
         .to_string()
     }
 
+    #[test]
+    fn test_select_event_fields() {
+        let event = serde_json::json!({
+            "id": "e1",
+            "function": "audio::render",
+            "duration_ns": 1200,
+            "arguments": [64, "stereo"],
+            "watchValues": { "gTempo": 120, "gGain": 0.5 },
+            "returnValue": null,
+        });
+        let paths: Vec<String> = [
+            "function",
+            "duration_ns",
+            "watchValues.gTempo",
+            "arguments.1",
+            "returnValue",
+            "watchValues.missing",
+            "function.name",
+        ]
+        .iter()
+        .map(|p| p.to_string())
+        .collect();
+        assert_eq!(
+            select_event_fields(&event, &paths),
+            serde_json::json!({
+                "function": "audio::render",
+                "duration_ns": 1200,
+                "watchValues.gTempo": 120,
+                "arguments.1": "stereo",
+            })
+        );
+    }
+
     #[tokio::test]
     async fn test_initialize_enforcement_rejects_before_init() {
         let (daemon, _dir) = test_daemon();
@@ -3368,8 +6552,6 @@ mod tests {
 
     #[test]
     fn test_daemon_lock_prevents_duplicates() {
-        use std::os::unix::io::AsRawFd;
-
         let dir = tempdir().unwrap();
         let lock_path = dir.path().join("daemon.lock");
 
@@ -3380,8 +6562,7 @@ mod tests {
             .open(&lock_path)
             .unwrap();
 
-        let result1 = unsafe { libc::flock(lock_file1.as_raw_fd(), libc::LOCK_EX | libc::LOCK_NB) };
-        assert_eq!(result1, 0, "First lock should succeed");
+        assert!(lock_file1.try_lock().is_ok(), "First lock should succeed");
 
         // Second lock acquisition should fail
         let lock_file2 = std::fs::OpenOptions::new()
@@ -3390,14 +6571,18 @@ mod tests {
             .open(&lock_path)
             .unwrap();
 
-        let result2 = unsafe { libc::flock(lock_file2.as_raw_fd(), libc::LOCK_EX | libc::LOCK_NB) };
-        assert_ne!(result2, 0, "Second lock should fail while first is held");
+        assert!(
+            lock_file2.try_lock().is_err(),
+            "Second lock should fail while first is held"
+        );
 
         // After dropping first lock, acquisition should succeed
         drop(lock_file1);
 
-        let result3 = unsafe { libc::flock(lock_file2.as_raw_fd(), libc::LOCK_EX | libc::LOCK_NB) };
-        assert_eq!(result3, 0, "Lock should succeed after release");
+        assert!(
+            lock_file2.try_lock().is_ok(),
+            "Lock should succeed after release"
+        );
     }
 
     #[tokio::test]
@@ -3424,8 +6609,8 @@ mod tests {
             connection_sessions: Arc::new(RwLock::new(HashMap::new())),
             test_runs: Arc::new(tokio::sync::RwLock::new(HashMap::new())),
             shutdown_signal: Arc::new(tokio::sync::Notify::new()),
-            vision_sidecar: Arc::new(std::sync::Mutex::new(
-                crate::ui::vision::VisionSidecar::new(),
+            vision: Arc::new(std::sync::Mutex::new(
+                crate::ui::vision::VisionPipeline::new(),
             )),
             notification_senders: Arc::new(RwLock::new(HashMap::new())),
         };
@@ -3457,6 +6642,241 @@ mod tests {
         assert!(result.unwrap().unwrap(), "Task should return true");
     }
 
+    #[tokio::test]
+    async fn test_debug_export_writes_chrome_trace() {
+        let (daemon, dir) = test_daemon();
+        let session_id = "export-session";
+        daemon
+            .session_manager
+            .create_session(session_id, "/bin/app", "/tmp", 1234)
+            .unwrap();
+        for (id, ts, event_type) in [
+            ("e1", 1_000, crate::db::EventType::FunctionEnter),
+            ("e2", 9_000, crate::db::EventType::FunctionExit),
+        ] {
+            daemon
+                .session_manager
+                .db()
+                .insert_event(&crate::db::Event {
+                    id: id.to_string(),
+                    session_id: session_id.to_string(),
+                    timestamp_ns: ts,
+                    thread_id: 1,
+                    event_type,
+                    function_name: "main".to_string(),
+                    ..crate::db::Event::default()
+                })
+                .unwrap();
+        }
+
+        let out = dir.path().join("trace.json");
+        let result = daemon
+            .tool_debug_export(&serde_json::json!({
+                "sessionId": session_id,
+                "outputPath": out.to_str().unwrap(),
+            }))
+            .await
+            .unwrap();
+        assert_eq!(result["eventCount"], 2);
+        assert_eq!(result["format"], "chrome_trace");
+
+        let written: serde_json::Value =
+            serde_json::from_slice(&std::fs::read(&out).unwrap()).unwrap();
+        let events = written["traceEvents"].as_array().unwrap();
+        assert_eq!(events[0]["ph"], "B");
+        assert_eq!(events[1]["ph"], "E");
+    }
+
+    #[tokio::test]
+    async fn test_debug_batch_shares_session_and_stops_on_error() {
+        let (daemon, _dir) = test_daemon();
+        let session_id = "batch-session";
+        daemon
+            .session_manager
+            .create_session(session_id, "/bin/app", "/tmp", 1234)
+            .unwrap();
+
+        let missing = daemon
+            .tool_debug_batch(
+                &serde_json::json!({
+                    "sessionId": "nope",
+                    "calls": [{ "tool": "debug_query" }]
+                }),
+                "conn-1",
+            )
+            .await;
+        assert!(matches!(missing, Err(crate::Error::SessionNotFound(_))));
+
+        let result = daemon
+            .tool_debug_batch(
+                &serde_json::json!({
+                    "sessionId": session_id,
+                    "calls": [
+                        { "tool": "debug_query", "arguments": { "eventType": "stderr" } },
+                        { "tool": "debug_query", "arguments": { "sessionId": "other" } },
+                        { "tool": "debug_query", "arguments": { "eventType": "stdout" } }
+                    ]
+                }),
+                "conn-1",
+            )
+            .await
+            .unwrap();
+        let results = result["results"].as_array().unwrap();
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0]["result"]["totalCount"], 0);
+        assert_eq!(results[1]["error"]["code"], "SESSION_NOT_FOUND");
+        assert_eq!(result["skipped"], 1);
+    }
+
+    #[test]
+    fn test_resolve_launch_profile() {
+        let dir = tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join(".strobe")).unwrap();
+        std::fs::write(
+            dir.path().join(".strobe/settings.json"),
+            r#"{"profiles": {
+                "audio-debug": {
+                    "command": "./build/app",
+                    "cwd": "build",
+                    "args": ["--loopback"],
+                    "env": {"AUDIO_DEBUG": "1", "LEVEL": "info"},
+                    "tracePatterns": ["audio::*"],
+                    "watches": [{"variable": "gTempo"}],
+                    "breakpoints": [{"function": "audio::render"}]
+                },
+                "plain": {"command": "python3"}
+            }}"#,
+        )
+        .unwrap();
+        let root = dir.path().to_str().unwrap();
+
+        let (args, snapshot) = resolve_launch_profile(&serde_json::json!({
+            "profile": "audio-debug",
+            "projectRoot": root,
+            "args": ["--other"],
+            "env": { "LEVEL": "debug" }
+        }))
+        .unwrap();
+        assert_eq!(args["command"], format!("{}/build/app", root));
+        assert_eq!(args["cwd"], format!("{}/build", root));
+        assert_eq!(args["args"], serde_json::json!(["--other"]));
+        assert_eq!(args["env"]["AUDIO_DEBUG"], "1");
+        assert_eq!(args["env"]["LEVEL"], "debug");
+        let snapshot = snapshot.unwrap();
+        assert_eq!(snapshot.patterns, vec!["audio::*"]);
+        assert_eq!(snapshot.watches.len(), 1);
+        assert_eq!(snapshot.breakpoints.len(), 1);
+
+        // Nothing to install: the launch may still inherit instrumentation
+        let (args, snapshot) =
+            resolve_launch_profile(&serde_json::json!({ "profile": "plain", "projectRoot": root }))
+                .unwrap();
+        assert_eq!(args["command"], "python3");
+        assert!(snapshot.is_none());
+
+        let unknown =
+            resolve_launch_profile(&serde_json::json!({ "profile": "nope", "projectRoot": root }));
+        assert!(unknown
+            .unwrap_err()
+            .to_string()
+            .contains("audio-debug, plain"));
+
+        let plain = serde_json::json!({ "command": "/bin/app", "projectRoot": root });
+        assert_eq!(resolve_launch_profile(&plain).unwrap().0, plain);
+    }
+
+    #[test]
+    fn test_project_output_path_stays_in_project() {
+        assert_eq!(
+            project_output_path("out/cov.drcov", "/proj").unwrap(),
+            PathBuf::from("/proj/out/cov.drcov")
+        );
+        assert_eq!(
+            project_output_path("/proj/cov.info", "/proj").unwrap(),
+            PathBuf::from("/proj/cov.info")
+        );
+        for outside in [
+            "/etc/cron.d/job",
+            "../cov.info",
+            "/proj/../etc/passwd",
+            "/project2/x",
+        ] {
+            let err = project_output_path(outside, "/proj").unwrap_err();
+            assert!(
+                err.to_string().contains("outside the project root"),
+                "{}",
+                outside
+            );
+        }
+    }
+
+    #[test]
+    fn test_instrumentation_path_stays_in_project() {
+        assert_eq!(
+            instrumentation_path("setup.json", "/proj").unwrap(),
+            PathBuf::from("/proj/setup.json")
+        );
+        assert!(instrumentation_path("/tmp/setup.json", "/proj").is_err());
+        assert!(instrumentation_path("../setup.json", "/proj").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_debug_investigation_links_and_summarizes() {
+        let (daemon, _dir) = test_daemon();
+        daemon
+            .session_manager
+            .create_session("app-1", "/bin/app", "/proj", 1234)
+            .unwrap();
+
+        let created = daemon
+            .tool_debug_investigation(&serde_json::json!({
+                "action": "create", "title": "crash on resize", "sessionId": "app-1"
+            }))
+            .await
+            .unwrap();
+        assert_eq!(created["projectRoot"], "/proj");
+        assert_eq!(created["status"], "open");
+        let id = created["id"].as_str().unwrap().to_string();
+
+        for args in [
+            serde_json::json!({ "action": "add-session", "investigationId": id, "sessionId": "app-1" }),
+            serde_json::json!({ "action": "add-session", "investigationId": id, "sessionId": "gone-1" }),
+            serde_json::json!({ "action": "add-note", "investigationId": id, "note": "only with 2 windows" }),
+        ] {
+            let _ = daemon.tool_debug_investigation(&args).await;
+        }
+
+        let summary = daemon
+            .tool_debug_investigation(
+                &serde_json::json!({ "action": "close", "investigationId": id, "conclusion": "layout off-by-one" }),
+            )
+            .await
+            .unwrap();
+        assert_eq!(summary["status"], "closed");
+        assert_eq!(summary["conclusion"], "layout off-by-one");
+        // The unknown session was rejected; the note and the real session remain
+        assert_eq!(summary["sessions"], 1);
+        assert_eq!(summary["notes"], 1);
+        assert_eq!(summary["items"][0]["session"]["binaryPath"], "/bin/app");
+        assert_eq!(summary["items"][1]["note"], "only with 2 windows");
+
+        let closed = daemon
+            .tool_debug_investigation(
+                &serde_json::json!({ "action": "add-note", "investigationId": id, "note": "late" }),
+            )
+            .await;
+        assert!(closed.is_err());
+
+        let listed = daemon
+            .tool_debug_investigation(
+                &serde_json::json!({ "action": "list", "projectRoot": "/proj" }),
+            )
+            .await
+            .unwrap();
+        assert_eq!(listed["investigations"][0]["id"], id.as_str());
+        assert_eq!(listed["investigations"][0]["items"], 2);
+    }
+
     // ---- E2E MCP tool handler tests for debug_ui ----
 
     fn make_debug_ui_call(session_id: &str, mode: &str, id: i64) -> String {
@@ -4403,4 +7823,123 @@ mod tests {
         )
         .await;
     }
+
+    #[tokio::test]
+    async fn test_http_api_calls_tools() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let (daemon, _dir) = test_daemon();
+        let listener = tokio::net::TcpListener::bind(("127.0.0.1", 0))
+            .await
+            .unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(super::super::http::serve(
+            Arc::new(daemon),
+            listener,
+            Arc::new("secret".to_string()),
+        ));
+
+        let send = |method: &'static str,
+                    path: &'static str,
+                    token: &'static str,
+                    body: &'static str| async move {
+            let mut stream = tokio::net::TcpStream::connect(addr).await.unwrap();
+            let request = format!(
+                "{} {} HTTP/1.1\r\nHost: localhost\r\nAuthorization: Bearer {}\r\nContent-Length: {}\r\n\r\n{}",
+                method,
+                path,
+                token,
+                body.len(),
+                body
+            );
+            stream.write_all(request.as_bytes()).await.unwrap();
+            let mut response = String::new();
+            stream.read_to_string(&mut response).await.unwrap();
+            let (head, body) = response.split_once("\r\n\r\n").unwrap();
+            let status: u16 = head.split_whitespace().nth(1).unwrap().parse().unwrap();
+            (
+                status,
+                serde_json::from_str::<serde_json::Value>(body).unwrap(),
+            )
+        };
+
+        let (status, body) = send("POST", "/v1/tools/debug_health", "wrong", "").await;
+        assert_eq!(status, 401);
+        assert_eq!(body["error"]["code"], "VALIDATION_ERROR");
+
+        let (status, body) = send("POST", "/v1/tools/debug_health", "secret", "{}").await;
+        assert_eq!(status, 200);
+        assert_eq!(body["activeSessions"], 0);
+
+        let (status, body) = send("GET", "/v1/tools", "secret", "").await;
+        assert_eq!(status, 200);
+        let names: Vec<&str> = body["tools"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|t| t["name"].as_str().unwrap())
+            .collect();
+        assert!(names.contains(&"debug_launch"));
+        assert!(!names.contains(&"debug_ui"));
+
+        let (status, _) = send("POST", "/v1/tools/debug_query", "secret", "[1]").await;
+        assert_eq!(status, 400);
+        let (status, _) = send("POST", "/v1/tools/debug_nope", "secret", "").await;
+        assert_eq!(status, 404);
+        let (status, _) = send("POST", "/v1/tools/debug_ui", "secret", "").await;
+        assert_eq!(status, 404);
+    }
+
+    #[tokio::test]
+    async fn test_test_run_overview() {
+        let (daemon, _dir) = test_daemon();
+        let progress = Arc::new(std::sync::Mutex::new(crate::test::TestProgress::new()));
+        {
+            let mut p = progress.lock().unwrap();
+            p.phase = crate::test::TestPhase::Running;
+            p.passed = 3;
+            p.start_test("parser::handles_empty".to_string());
+        }
+        {
+            let mut runs = daemon.test_runs.write().await;
+            let run = |id: &str, state| crate::test::TestRun {
+                id: id.to_string(),
+                state,
+                fetched: false,
+                session_id: None,
+                project_root: "/proj".to_string(),
+                connection_id: "c1".to_string(),
+            };
+            runs.insert(
+                "test-a".to_string(),
+                run("test-a", crate::test::TestRunState::Running { progress }),
+            );
+            runs.insert(
+                "test-b".to_string(),
+                run(
+                    "test-b",
+                    crate::test::TestRunState::Completed {
+                        response: serde_json::json!({
+                            "framework": "cargo",
+                            "summary": { "passed": 10, "failed": 1, "skipped": 0, "durationMs": 420 }
+                        }),
+                        completed_at: std::time::Instant::now(),
+                    },
+                ),
+            );
+        }
+
+        let overview = daemon.test_run_overview().await;
+        assert_eq!(overview.len(), 2);
+        assert_eq!(overview[0]["status"], "running");
+        assert_eq!(overview[0]["phase"], "running");
+        assert_eq!(overview[0]["passed"], 3);
+        assert_eq!(overview[0]["currentTests"][0], "parser::handles_empty");
+        assert_eq!(overview[1]["status"], "completed");
+        assert_eq!(overview[1]["framework"], "cargo");
+        assert_eq!(overview[1]["failed"], 1);
+        assert_eq!(overview[1]["durationMs"], 420);
+        // Listing doesn't count as fetching the results
+        assert!(!daemon.test_runs.read().await["test-b"].fetched);
+    }
 }
@@ -1,16 +1,57 @@
+use super::call_order::CallOrderer;
+use super::child_follow::ChildFollower;
+use super::crash_dump;
+use super::event_pipeline::{self, BatchSizer, EventSender, WriterStats};
+use super::output_triggers::{self, OutputTriggerSet, TriggerActions};
+use super::structured_logs::LineBuffer;
 use crate::db::{Database, Event, Session, SessionStatus};
 use crate::dwarf::{DwarfHandle, DwarfParser};
-use crate::frida_collector::{FridaSpawner, HookResult};
-use crate::symbols::{DwarfResolver, JsResolver, Language, PythonResolver, SymbolResolver};
+use crate::frida_collector::{FridaSpawner, HookResult, SymbolModule};
+use crate::symbols::{
+    DwarfResolver, JavaResolver, JsResolver, Language, PdbResolver, PythonResolver, SymbolResolver,
+};
 use crate::Result;
 use chrono::{Timelike, Utc};
-use std::collections::HashMap;
-use std::path::Path;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
-use std::sync::RwLock;
+use std::sync::{Mutex, RwLock};
 use std::time::Instant;
 use tokio::sync::mpsc;
 
+/// Functions listed in debug_session status; debug_query has the full set
+const MAX_STATUS_CALL_COUNTS: usize = 50;
+
+/// Tries per event batch before the writer drops it
+const WRITER_INSERT_ATTEMPTS: u32 = 2;
+
+/// Ids of the temporary breakpoints debug_continue `until` sets
+const UNTIL_BREAKPOINT_PREFIX: &str = "until-";
+
+/// Ids the agent gives one-shot step pauses
+const STEP_BREAKPOINT_PREFIX: &str = "step-";
+
+/// How long a repeated step waits for each step to pause
+const STEP_PAUSE_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+
+/// Passive WAL checkpoint from an event writer; failures only delay it.
+fn checkpoint(db: &Database, stats: &WriterStats) {
+    match db.checkpoint_wal() {
+        Ok(()) => stats.record_checkpoint(),
+        Err(e) => tracing::warn!("WAL checkpoint failed: {}", e),
+    }
+}
+
+/// Register naming of paused-frame and crash register maps
+const FRAME_ARCH: &str = if cfg!(target_arch = "aarch64") {
+    "arm64"
+} else {
+    "x64"
+};
+
+/// How often the writer ages out old trace detail when downsampling is on
+const DOWNSAMPLE_INTERVAL: std::time::Duration = std::time::Duration::from_secs(30);
+
 /// Map TypeKind to the string the agent expects.
 fn type_kind_to_agent_str(tk: &crate::dwarf::TypeKind) -> &'static str {
     match tk {
@@ -43,13 +84,319 @@ fn hex_to_bytes(hex: &str) -> std::result::Result<Vec<u8>, String> {
         .collect()
 }
 
+/// A hexdump read: field annotations (offset, path) from the variable's type,
+/// and how many bytes past `MAX_HEXDUMP_BYTES` were left out.
+struct HexdumpTarget {
+    annotations: Vec<(u64, String)>,
+    omitted: u64,
+}
+
+/// Byte read for a region or `format: "hexdump"` target. Variables are read
+/// over their whole storage (structs and arrays included) unless `size` says
+/// otherwise, and annotated with their member offsets.
+async fn hexdump_recipe(
+    target: &crate::mcp::ReadTarget,
+    label: &str,
+    dwarf: Option<&Arc<DwarfParser>>,
+) -> Result<(serde_json::Value, HexdumpTarget)> {
+    use crate::mcp::{MAX_HEXDUMP_BYTES, MAX_READ_DEPTH};
+
+    let absolute = |address: u64, length: u32| {
+        serde_json::json!({
+            "label": label,
+            "address": format!("0x{:x}", address),
+            "size": length,
+            "typeKind": "bytes",
+            "derefDepth": 0,
+            "derefOffset": 0,
+            "noSlide": true,
+        })
+    };
+    let plain = |recipe| {
+        (
+            recipe,
+            HexdumpTarget {
+                annotations: Vec::new(),
+                omitted: 0,
+            },
+        )
+    };
+
+    if let Some(ref region) = target.region {
+        let base = region.base_address().unwrap_or(0);
+        return Ok(plain(absolute(base, region.length)));
+    }
+    if let Some(ref address) = target.address {
+        let base = u64::from_str_radix(address.trim_start_matches("0x"), 16)
+            .map_err(|_| crate::Error::ValidationError(format!("Invalid address '{}'", address)))?;
+        return Ok(plain(absolute(base, target.size.unwrap_or(16))));
+    }
+
+    let parser = Arc::clone(
+        dwarf.ok_or_else(|| crate::Error::ReadFailed("No debug symbols available".to_string()))?,
+    );
+    let name = label.to_string();
+    let (region, layout) = tokio::task::spawn_blocking(move || {
+        let region = parser.resolve_global_regions(std::slice::from_ref(&name))?;
+        let layout = parser.resolve_read_layout(&name, MAX_READ_DEPTH)?;
+        Ok::<_, crate::Error>((region.into_iter().next(), layout))
+    })
+    .await
+    .map_err(|e| crate::Error::Internal(format!("Global resolution panicked: {}", e)))??;
+    let region = region
+        .ok_or_else(|| crate::Error::ReadFailed(format!("Variable '{}' not found", label)))?;
+
+    let wanted = target.size.map(u64::from).unwrap_or(region.byte_size);
+    let length = wanted.min(MAX_HEXDUMP_BYTES as u64);
+    let annotations = layout
+        .map(|(_, layout)| layout.field_offsets())
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|(offset, _)| *offset < length)
+        .collect();
+    Ok((
+        serde_json::json!({
+            "label": label,
+            "address": format!("0x{:x}", region.address),
+            "size": length,
+            "typeKind": "bytes",
+            "derefDepth": 0,
+            "derefOffset": 0,
+        }),
+        HexdumpTarget {
+            annotations,
+            omitted: wanted - length,
+        },
+    ))
+}
+
+/// `hexdump -C` style lines: address, 16 bytes in two groups of 8, ASCII
+/// gutter, then the members that start on the line.
+fn hexdump_lines(base: u64, bytes: &[u8], annotations: &[(u64, String)]) -> Vec<String> {
+    const MAX_NOTES_PER_LINE: usize = 4;
+
+    bytes
+        .chunks(16)
+        .enumerate()
+        .map(|(i, chunk)| {
+            let offset = i as u64 * 16;
+            let mut hex = String::with_capacity(49);
+            for (j, byte) in chunk.iter().enumerate() {
+                if j == 8 {
+                    hex.push(' ');
+                }
+                hex.push_str(&format!("{:02x} ", byte));
+            }
+            let ascii: String = chunk
+                .iter()
+                .map(|&b| {
+                    if (0x20..0x7f).contains(&b) {
+                        b as char
+                    } else {
+                        '.'
+                    }
+                })
+                .collect();
+            let mut line = format!("0x{:012x}  {:<49} |{:<16}|", base + offset, hex, ascii);
+
+            let notes: Vec<&str> = annotations
+                .iter()
+                .filter(|(at, _)| (offset..offset + 16).contains(at))
+                .map(|(_, name)| name.as_str())
+                .collect();
+            if !notes.is_empty() {
+                line.push_str("  ");
+                line.push_str(&notes[..notes.len().min(MAX_NOTES_PER_LINE)].join(", "));
+                if notes.len() > MAX_NOTES_PER_LINE {
+                    line.push_str(&format!(", +{}", notes.len() - MAX_NOTES_PER_LINE));
+                }
+            }
+            line
+        })
+        .collect()
+}
+
+/// Candidate named `{placeholder}`s of a logpoint message (`{velocity}`,
+/// `{buffer.sample_rate}`), leaving out the built-in `{args[N]}` and
+/// `{threadId}`. Only those naming a parameter are substituted.
+fn template_placeholders(message: &str) -> Vec<&str> {
+    let is_name = |part: &str| {
+        part.chars()
+            .next()
+            .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+            && part.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+    };
+    let mut names = Vec::new();
+    let mut rest = message;
+    while let Some(open) = rest.find('{') {
+        rest = &rest[open + 1..];
+        let Some(close) = rest.find(['{', '}']) else {
+            break;
+        };
+        let body = &rest[..close];
+        if rest[close..].starts_with('}')
+            && body != "threadId"
+            && body.split("->").flat_map(|p| p.split('.')).all(is_name)
+            && !names.contains(&body)
+        {
+            names.push(body);
+        }
+    }
+    names
+}
+
+/// Argument paths are read by the agent at function entry, not from a paused frame.
+fn argument_root() -> crate::Error {
+    crate::Error::Internal("Argument paths are read by the agent at function entry".to_string())
+}
+
+/// One `leak` event per group of never-freed allocations sharing a backtrace,
+/// named after the allocating frame.
+fn leak_events(session_id: &str, pid: Option<u32>, payload: &serde_json::Value) -> Vec<Event> {
+    let timestamp_ns = payload
+        .get("timestampNs")
+        .and_then(|v| v.as_i64())
+        .unwrap_or(0);
+    let Some(groups) = payload.get("leakGroups").and_then(|v| v.as_array()) else {
+        return Vec::new();
+    };
+    groups
+        .iter()
+        .enumerate()
+        .map(|(rank, group)| {
+            let signature = group
+                .get("signature")
+                .and_then(|v| v.as_str())
+                .unwrap_or("unknown");
+            let count = group.get("count").and_then(|v| v.as_u64()).unwrap_or(0);
+            let bytes = group.get("bytes").and_then(|v| v.as_u64()).unwrap_or(0);
+            let frame = group
+                .get("backtrace")
+                .and_then(|v| v.as_array())
+                .and_then(|frames| frames.first());
+            let frame_str = |key: &str| {
+                frame
+                    .and_then(|f| f.get(key))
+                    .and_then(|v| v.as_str())
+                    .filter(|s| !s.is_empty())
+            };
+            Event {
+                id: format!("{}-leak-{}-{}", session_id, rank, signature),
+                session_id: session_id.to_string(),
+                timestamp_ns,
+                event_type: crate::db::EventType::Leak,
+                function_name: frame_str("name")
+                    .or_else(|| frame_str("address"))
+                    .unwrap_or("<unknown>")
+                    .to_string(),
+                source_file: frame_str("fileName").map(String::from),
+                line_number: frame
+                    .and_then(|f| f.get("lineNumber"))
+                    .and_then(|v| v.as_i64())
+                    .filter(|&l| l > 0)
+                    .map(|l| l as i32),
+                text: Some(format!(
+                    "{} allocation{} ({} bytes) never freed",
+                    count,
+                    if count == 1 { "" } else { "s" },
+                    bytes
+                )),
+                arguments: Some(serde_json::json!({
+                    "signature": signature,
+                    "count": count,
+                    "bytes": bytes,
+                    "largest": group.get("largest"),
+                    "samples": group.get("samples"),
+                })),
+                backtrace: group.get("backtrace").cloned(),
+                pid,
+                ..Event::default()
+            }
+        })
+        .collect()
+}
+
+/// Labels a breakpoint or logpoint condition reads with `watch:<label>`,
+/// matched the way the agent rewrites them (a name with any `.member`,
+/// `->member` or `::` parts).
+fn condition_watch_labels(condition: &str) -> Vec<&str> {
+    let bytes = condition.as_bytes();
+    let is_word = |b: u8| b.is_ascii_alphanumeric() || b == b'_' || b == b'$';
+    let starts_name = |at: usize| {
+        bytes
+            .get(at)
+            .is_some_and(|&b| is_word(b) && !b.is_ascii_digit())
+    };
+    let mut labels = Vec::new();
+    for (at, prefix) in condition.match_indices("watch:") {
+        if at > 0 && (is_word(bytes[at - 1]) || bytes[at - 1] == b'.') {
+            continue;
+        }
+        let start = at + prefix.len();
+        if !starts_name(start) {
+            continue;
+        }
+        let mut end = start;
+        while end < bytes.len() {
+            if is_word(bytes[end]) || bytes[end] == b'.' || bytes[end] == b':' {
+                end += 1;
+            } else if bytes[end..].starts_with(b"->") && starts_name(end + 2) {
+                end += 2;
+            } else {
+                break;
+            }
+        }
+        labels.push(&condition[start..end]);
+    }
+    labels
+}
+
+/// Agent-side crash capture regions, one per requested name in order. Sizes
+/// are charged against `budget`: the region crossing it is truncated, later
+/// ones are skipped. Unresolved names keep an `error` so the crash shows why.
+fn crash_capture_regions(
+    names: &[String],
+    resolved: &[crate::dwarf::GlobalRegion],
+    budget: usize,
+) -> Vec<serde_json::Value> {
+    let mut remaining = budget as u64;
+    names
+        .iter()
+        .map(|name| {
+            let Some(region) = resolved.iter().find(|r| &r.name == name) else {
+                return serde_json::json!({
+                    "name": name,
+                    "error": "no global with a static address in DWARF",
+                });
+            };
+            if remaining == 0 || region.byte_size == 0 {
+                let error = if remaining == 0 {
+                    "over the maxBytes budget"
+                } else {
+                    "type has no static size"
+                };
+                return serde_json::json!({ "name": name, "error": error });
+            }
+            let size = region.byte_size.min(remaining);
+            remaining -= size;
+            serde_json::json!({
+                "name": name,
+                "address": format!("0x{:x}", region.address),
+                "size": size,
+                "byteSize": region.byte_size,
+                "typeName": region.type_name,
+            })
+        })
+        .collect()
+}
+
 /// Acquire a read lock, recovering from poisoned state.
-fn read_lock<T>(lock: &RwLock<T>) -> std::sync::RwLockReadGuard<'_, T> {
+pub(super) fn read_lock<T>(lock: &RwLock<T>) -> std::sync::RwLockReadGuard<'_, T> {
     lock.read().unwrap_or_else(|e| e.into_inner())
 }
 
 /// Acquire a write lock, recovering from poisoned state.
-fn write_lock<T>(lock: &RwLock<T>) -> std::sync::RwLockWriteGuard<'_, T> {
+pub(super) fn write_lock<T>(lock: &RwLock<T>) -> std::sync::RwLockWriteGuard<'_, T> {
     lock.write().unwrap_or_else(|e| e.into_inner())
 }
 
@@ -61,6 +408,13 @@ pub fn detect_language(command: &str, project_root: &Path) -> Language {
     if cmd_lower.contains("python") || command.ends_with(".py") {
         return Language::Python;
     }
+    let basename = Path::new(command)
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or("");
+    if basename == "java" || command.ends_with(".jar") {
+        return Language::Java;
+    }
     if cmd_lower.contains("node")
         || cmd_lower.contains("bun")
         || command.ends_with(".js")
@@ -88,6 +442,30 @@ pub fn detect_language(command: &str, project_root: &Path) -> Language {
     Language::Native
 }
 
+/// A `.jar` launched directly runs as `java -jar <jar> args...`. Frida's spawn
+/// does no PATH lookup, so `java` comes from JAVA_HOME first, then PATH.
+fn jar_command(command: &str, args: &[String]) -> Option<(String, Vec<String>)> {
+    if !command.ends_with(".jar") {
+        return None;
+    }
+    let from_home = std::env::var("JAVA_HOME")
+        .ok()
+        .map(|home| Path::new(&home).join("bin/java"));
+    let from_path = std::env::var_os("PATH").and_then(|path| {
+        std::env::split_paths(&path)
+            .map(|dir| dir.join("java"))
+            .find(|p| p.is_file())
+    });
+    let java = from_home
+        .filter(|p| p.is_file())
+        .or(from_path)
+        .map(|p| p.to_string_lossy().to_string())
+        .unwrap_or_else(|| "java".to_string());
+    let mut java_args = vec!["-jar".to_string(), command.to_string()];
+    java_args.extend(args.iter().cloned());
+    Some((java, java_args))
+}
+
 #[derive(Clone)]
 pub struct ActiveWatchState {
     pub label: String,
@@ -101,18 +479,105 @@ pub struct ActiveWatchState {
     pub is_expr: bool,
     pub expr: Option<String>,
     pub no_slide: bool,
+    /// DWARF variable expression the watch was resolved from (None for raw addresses)
+    pub variable: Option<String>,
+    pub delta_threshold: Option<f64>,
+    /// Symbol module the variable lives in; `address` is then relative to its base
+    pub module: Option<String>,
+}
+
+/// Auxiliary output channels captured as `aux_output` events, beyond stdout/stderr.
+#[derive(Clone, Default)]
+pub struct OutputChannels {
+    /// Extra file descriptors intercepted by the agent's write(2) hook
+    pub fds: Vec<i32>,
+    /// Named pipes (FIFOs) read by the daemon
+    pub pipes: Vec<String>,
 }
 
-/// Check if a process is alive. Returns true if the process exists,
-/// even if we lack permission to signal it (EPERM).
-fn is_process_alive(pid: u32) -> bool {
-    let result = unsafe { libc::kill(pid as i32, 0) };
-    if result == 0 {
-        return true; // Process exists and we can signal it
+/// What the last stopped session of a binary was observing, kept symbolically
+/// so the next debug_launch of that binary re-resolves it in the new process.
+#[derive(Debug, Clone)]
+pub struct CarriedInstrumentation {
+    pub snapshot: crate::mcp::InstrumentationSnapshot,
+    /// Raw-address items, which can't follow the process across a relaunch
+    pub omitted: Vec<String>,
+}
+
+/// Create the FIFO at `path` if missing; reject existing non-FIFO files.
+#[cfg(unix)]
+fn ensure_fifo(path: &str) -> Result<()> {
+    use std::os::unix::fs::FileTypeExt;
+    match std::fs::metadata(path) {
+        Ok(meta) if meta.file_type().is_fifo() => Ok(()),
+        Ok(_) => Err(crate::Error::ValidationError(format!(
+            "capturePipes: '{}' exists and is not a named pipe. Remove it or choose another path.",
+            path
+        ))),
+        Err(_) => {
+            let c_path = std::ffi::CString::new(path).map_err(|_| {
+                crate::Error::ValidationError(format!("capturePipes: invalid path '{}'", path))
+            })?;
+            if unsafe { libc::mkfifo(c_path.as_ptr(), 0o600) } != 0 {
+                return Err(crate::Error::Io(std::io::Error::last_os_error()));
+            }
+            Ok(())
+        }
     }
-    // Check errno: EPERM means alive but no permission, ESRCH means dead
-    let err = std::io::Error::last_os_error();
-    matches!(err.raw_os_error(), Some(libc::EPERM))
+}
+
+/// Read a named pipe on a background thread, forwarding each chunk as an
+/// aux_output event. Opened non-blocking so writers may come and go; the
+/// thread exits once the session's event writer shuts down.
+#[cfg(unix)]
+fn spawn_pipe_reader(session_id: &str, index: usize, path: &str, tx: EventSender) -> Result<()> {
+    use std::io::Read;
+    use std::os::unix::fs::OpenOptionsExt;
+
+    let mut file = std::fs::OpenOptions::new()
+        .read(true)
+        .custom_flags(libc::O_NONBLOCK)
+        .open(path)?;
+    let sid = session_id.to_string();
+    let channel = format!("pipe:{}", path);
+    let start_ns = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_nanos() as i64;
+
+    std::thread::spawn(move || {
+        let mut buf = vec![0u8; 64 * 1024];
+        let mut counter = 0u64;
+        while !tx.is_closed() {
+            let n = match file.read(&mut buf) {
+                Ok(n) if n > 0 => n,
+                // 0 = no writer attached yet (or it closed); WouldBlock = writer idle
+                _ => {
+                    std::thread::sleep(std::time::Duration::from_millis(50));
+                    continue;
+                }
+            };
+            let now_ns = (std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_nanos() as i64)
+                - start_ns;
+            let event = Event {
+                id: format!("{}-pipe{}-{}", sid, index, counter),
+                session_id: sid.clone(),
+                timestamp_ns: now_ns,
+                event_type: crate::db::EventType::AuxOutput,
+                text: Some(String::from_utf8_lossy(&buf[..n]).into_owned()),
+                channel: Some(channel.clone()),
+                ..Event::default()
+            };
+            counter += 1;
+            if tx.blocking_send(event).is_err() {
+                break;
+            }
+        }
+    });
+    Ok(())
 }
 
 /// Generate the ESM hook registration script for Node.js sessions.
@@ -297,10 +762,87 @@ fn kill_orphans_by_cmdline(pattern: &str, killed: &mut std::collections::HashSet
     }
 }
 
+/// Split `patterns` into one install batch per trace mode: count-mode and
+/// rt-mode patterns go to their own batches, everything else records events.
+pub(super) fn batches_by_mode(
+    patterns: Vec<String>,
+    counted: &[String],
+    rt: &[String],
+) -> [(Vec<String>, crate::mcp::TraceMode); 3] {
+    let (count_patterns, rest): (Vec<String>, Vec<String>) =
+        patterns.into_iter().partition(|p| counted.contains(p));
+    let (rt_patterns, event_patterns): (Vec<String>, Vec<String>) =
+        rest.into_iter().partition(|p| rt.contains(p));
+    [
+        (event_patterns, crate::mcp::TraceMode::Events),
+        (count_patterns, crate::mcp::TraceMode::Count),
+        (rt_patterns, crate::mcp::TraceMode::Rt),
+    ]
+}
+
+/// Background DWARF parse of `binary_path`, shared across sessions through
+/// `dwarf_cache` (keyed on mtime so rebuilds reparse).
+pub(super) fn dwarf_handle_for(
+    dwarf_cache: &RwLock<HashMap<String, DwarfHandle>>,
+    binary_path: &str,
+    search_root: Option<&str>,
+    symbols_path: Option<&str>,
+) -> DwarfHandle {
+    // Include mtime and symbols_path in cache key so rebuilds and symbol overrides invalidate correctly
+    let mtime = std::fs::metadata(binary_path)
+        .and_then(|m| m.modified())
+        .ok();
+    let cache_key = match (mtime, symbols_path) {
+        (Some(t), Some(sp)) => format!(
+            "{}@{}@sym:{}",
+            binary_path,
+            t.duration_since(std::time::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs(),
+            sp
+        ),
+        (Some(t), None) => format!(
+            "{}@{}",
+            binary_path,
+            t.duration_since(std::time::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs()
+        ),
+        (None, Some(sp)) => format!("{}@sym:{}", binary_path, sp),
+        (None, None) => binary_path.to_string(),
+    };
+
+    // Fast path: read lock only
+    {
+        let cache = read_lock(dwarf_cache);
+        if let Some(handle) = cache.get(&cache_key) {
+            if !handle.is_failed() {
+                return handle.clone();
+            }
+        }
+    }
+
+    // Slow path: write lock with double-check
+    let mut cache = write_lock(dwarf_cache);
+    if let Some(handle) = cache.get(&cache_key) {
+        if !handle.is_failed() {
+            return handle.clone();
+        }
+    }
+
+    let handle = DwarfHandle::spawn_parse(binary_path, search_root, symbols_path);
+    cache.insert(cache_key, handle.clone());
+    handle
+}
+
 pub struct SessionManager {
     db: Database,
     /// Active trace patterns per session
     patterns: Arc<RwLock<HashMap<String, Vec<String>>>>,
+    /// Patterns hooked in count mode per session (subset of `patterns`)
+    counted_patterns: Arc<RwLock<HashMap<String, Vec<String>>>>,
+    /// Patterns hooked in rt mode per session (subset of `patterns`)
+    rt_patterns: Arc<RwLock<HashMap<String, Vec<String>>>>,
     /// Cached DWARF handles per binary (background-parsed)
     dwarf_cache: Arc<RwLock<HashMap<String, DwarfHandle>>>,
     /// Hooked function count per session
@@ -309,10 +851,24 @@ pub struct SessionManager {
     watches: Arc<RwLock<HashMap<String, Vec<ActiveWatchState>>>>,
     /// Per-session event limits (for dynamic configuration)
     event_limits: Arc<RwLock<HashMap<String, usize>>>,
+    /// Event writer counters per session
+    writer_stats: Arc<RwLock<HashMap<String, Arc<WriterStats>>>>,
+    /// Aging policy per session, for sessions whose settings enable it
+    downsample_policies: Arc<RwLock<HashMap<String, crate::db::DownsamplePolicy>>>,
+    /// Settings each session last had applied, the baseline for reloads
+    applied_settings: Arc<RwLock<HashMap<String, crate::config::StrobeSettings>>>,
+    /// Last serialization depth requested via debug_trace, per session
+    serialization_depths: Arc<RwLock<HashMap<String, u32>>>,
     /// Frida spawner for managing instrumented processes (lazily initialized)
     frida_spawner: Arc<tokio::sync::RwLock<Option<FridaSpawner>>>,
     /// Child PIDs per session (parent PID is in the Session struct)
     child_pids: Arc<RwLock<HashMap<String, Vec<u32>>>>,
+    /// Live progress of debug_test sessions (owning test of spawned children)
+    test_progress: Arc<RwLock<HashMap<String, Arc<Mutex<crate::test::TestProgress>>>>>,
+    /// Owning test per followed child pid, per session (tags the child's events)
+    child_tests: Arc<RwLock<HashMap<String, HashMap<u32, String>>>>,
+    /// Fired by a session's writer after each stored batch (event streams)
+    event_signals: Arc<RwLock<HashMap<String, Arc<tokio::sync::Notify>>>>,
     /// Cancellation tokens for database writer tasks per session
     writer_cancel_tokens: Arc<RwLock<HashMap<String, tokio::sync::watch::Sender<bool>>>>,
     /// JoinHandles for database writer tasks per session (for awaiting completion)
@@ -321,6 +877,36 @@ pub struct SessionManager {
     breakpoints: Arc<RwLock<HashMap<String, HashMap<String, Breakpoint>>>>,
     /// Logpoints per session
     logpoints: Arc<RwLock<HashMap<String, HashMap<String, Logpoint>>>>,
+    /// Data watchpoints per session (in agent order)
+    watchpoints: Arc<RwLock<HashMap<String, Vec<Watchpoint>>>>,
+    /// Active stack sampling per session (hz resolved)
+    sampling: Arc<RwLock<HashMap<String, crate::mcp::SamplingConfig>>>,
+    /// Threads whose traced calls the agent records, per session
+    thread_filters: Arc<RwLock<HashMap<String, crate::mcp::ThreadFilter>>>,
+    /// Per-function limits on recorded traced calls, per session
+    rate_limits: Arc<RwLock<HashMap<String, crate::mcp::RateLimit>>>,
+    /// Flight recorder configuration (defaults resolved), per session
+    flight_recorders: Arc<RwLock<HashMap<String, crate::mcp::FlightRecorderConfig>>>,
+    /// Active signal-handler safety audit, per session
+    signal_safety: Arc<RwLock<HashMap<String, crate::mcp::SignalSafetyConfig>>>,
+    /// `@syscalls:` groups traced, per session
+    syscall_groups: Arc<RwLock<HashMap<String, BTreeSet<crate::mcp::SyscallGroup>>>>,
+    /// Sessions tracing `@network`
+    network_tracing: Arc<RwLock<HashSet<String>>>,
+    /// Sessions tracing `@exceptions`
+    exception_tracing: Arc<RwLock<HashSet<String>>>,
+    /// Sessions tracing `@signals`
+    signal_tracing: Arc<RwLock<HashSet<String>>>,
+    /// Sessions tracing `@locks`
+    lock_tracing: Arc<RwLock<HashSet<String>>>,
+    /// debug_launch `crashCapture`, per session
+    crash_captures: Arc<RwLock<HashMap<String, crate::mcp::CrashCapture>>>,
+    /// Sessions with allocation tracking running (debug_heap)
+    heap_tracking: Arc<RwLock<HashSet<String>>>,
+    /// Instrumentation of the last stopped session, per (project root, binary path)
+    carried_instrumentation: Arc<RwLock<HashMap<(String, String), CarriedInstrumentation>>>,
+    /// Libraries indexed alongside the main binary (debug_launch `symbolExtra`)
+    symbol_modules: Arc<RwLock<HashMap<String, Vec<SymbolModule>>>>,
     /// Paused threads per session
     paused_threads: Arc<RwLock<HashMap<String, HashMap<u64, PauseInfo>>>>,
     /// Language per session (native, python, javascript)
@@ -331,33 +917,83 @@ pub struct SessionManager {
     esm_hook_paths: Arc<RwLock<HashMap<String, String>>>,
     /// Runtime capabilities per session (derived at spawn, enriched by agent)
     capabilities: Arc<RwLock<HashMap<String, crate::mcp::RuntimeCapabilities>>>,
+    /// Sessions spawned without Frida (instrumentation: "none")
+    uninstrumented: Arc<RwLock<HashSet<String>>>,
+    /// Extra fds / named pipes to capture per session (set before spawn)
+    output_channels: Arc<RwLock<HashMap<String, OutputChannels>>>,
+    /// Launch `onOutput` triggers per session (set before spawn)
+    output_triggers: Arc<RwLock<HashMap<String, Vec<crate::mcp::OutputTrigger>>>>,
+    /// Structured log format per session (launch `logFormat`)
+    log_formats: Arc<RwLock<HashMap<String, crate::mcp::LogFormat>>>,
+    /// Pids SIGSTOPped by a `pause` output trigger, resumed by debug_continue
+    trigger_stopped: Arc<RwLock<HashMap<String, Vec<u32>>>>,
+    /// Where crash summaries are written (`crashes/` next to the database)
+    crash_dir: PathBuf,
 }
 
 impl SessionManager {
     pub fn new(db_path: &Path) -> Result<Self> {
         let db = Database::open(db_path)?;
-
-        // Clean up any sessions left as 'running' from a previous daemon instance
-        db.cleanup_stale_sessions()?;
+        let crash_dir = db_path
+            .parent()
+            .unwrap_or_else(|| Path::new("."))
+            .join("crashes");
+
+        let settings = crate::config::resolve(None);
+        db.set_sync_mode(settings.db_sync_mode)?;
+        db.set_payload_compression(settings.db_compress_payloads);
+        // Sessions left 'running' by a previous daemon that didn't shut down
+        db.recover_interrupted_sessions()?;
+        db.checkpoint_wal()?;
 
         Ok(Self {
             db,
             patterns: Arc::new(RwLock::new(HashMap::new())),
+            counted_patterns: Arc::new(RwLock::new(HashMap::new())),
+            rt_patterns: Arc::new(RwLock::new(HashMap::new())),
             dwarf_cache: Arc::new(RwLock::new(HashMap::new())),
             hook_counts: Arc::new(RwLock::new(HashMap::new())),
             watches: Arc::new(RwLock::new(HashMap::new())),
             event_limits: Arc::new(RwLock::new(HashMap::new())),
+            writer_stats: Arc::new(RwLock::new(HashMap::new())),
+            downsample_policies: Arc::new(RwLock::new(HashMap::new())),
+            applied_settings: Arc::new(RwLock::new(HashMap::new())),
+            serialization_depths: Arc::new(RwLock::new(HashMap::new())),
             frida_spawner: Arc::new(tokio::sync::RwLock::new(None)),
             child_pids: Arc::new(RwLock::new(HashMap::new())),
+            test_progress: Arc::new(RwLock::new(HashMap::new())),
+            child_tests: Arc::new(RwLock::new(HashMap::new())),
+            event_signals: Arc::new(RwLock::new(HashMap::new())),
             writer_cancel_tokens: Arc::new(RwLock::new(HashMap::new())),
             writer_handles: Arc::new(tokio::sync::RwLock::new(HashMap::new())),
             breakpoints: Arc::new(RwLock::new(HashMap::new())),
             logpoints: Arc::new(RwLock::new(HashMap::new())),
+            watchpoints: Arc::new(RwLock::new(HashMap::new())),
+            sampling: Arc::new(RwLock::new(HashMap::new())),
+            thread_filters: Arc::new(RwLock::new(HashMap::new())),
+            rate_limits: Arc::new(RwLock::new(HashMap::new())),
+            flight_recorders: Arc::new(RwLock::new(HashMap::new())),
+            signal_safety: Arc::new(RwLock::new(HashMap::new())),
+            syscall_groups: Arc::new(RwLock::new(HashMap::new())),
+            network_tracing: Arc::new(RwLock::new(HashSet::new())),
+            exception_tracing: Arc::new(RwLock::new(HashSet::new())),
+            signal_tracing: Arc::new(RwLock::new(HashSet::new())),
+            lock_tracing: Arc::new(RwLock::new(HashSet::new())),
+            crash_captures: Arc::new(RwLock::new(HashMap::new())),
+            heap_tracking: Arc::new(RwLock::new(HashSet::new())),
+            carried_instrumentation: Arc::new(RwLock::new(HashMap::new())),
+            symbol_modules: Arc::new(RwLock::new(HashMap::new())),
             paused_threads: Arc::new(RwLock::new(HashMap::new())),
             languages: Arc::new(RwLock::new(HashMap::new())),
             resolvers: Arc::new(RwLock::new(HashMap::new())),
             esm_hook_paths: Arc::new(RwLock::new(HashMap::new())),
             capabilities: Arc::new(RwLock::new(HashMap::new())),
+            uninstrumented: Arc::new(RwLock::new(HashSet::new())),
+            output_channels: Arc::new(RwLock::new(HashMap::new())),
+            output_triggers: Arc::new(RwLock::new(HashMap::new())),
+            log_formats: Arc::new(RwLock::new(HashMap::new())),
+            trigger_stopped: Arc::new(RwLock::new(HashMap::new())),
+            crash_dir,
         })
     }
 
@@ -393,7 +1029,7 @@ impl SessionManager {
         // Clean up stale sessions on the same binary (dead process still marked Running)
         if let Some(existing) = self.db.get_session_by_binary(binary_path)? {
             if existing.status == SessionStatus::Running {
-                let pid_alive = is_process_alive(existing.pid);
+                let pid_alive = crate::process::is_alive(existing.pid);
                 if !pid_alive {
                     tracing::warn!(
                         "Session {} has dead PID {}, marking as stopped",
@@ -415,11 +1051,39 @@ impl SessionManager {
         write_lock(&self.hook_counts).insert(id.to_string(), 0);
         write_lock(&self.watches).insert(id.to_string(), Vec::new());
         let settings = crate::config::resolve(Some(std::path::Path::new(project_root)));
-        write_lock(&self.event_limits).insert(id.to_string(), settings.events_max_per_session);
+        self.apply_settings(id, &settings);
 
         Ok(session)
     }
 
+    /// Set a session's event limit and downsampling from its settings. The
+    /// writer picks both up while running, so settings reloads go through
+    /// here too, comparing against `applied_settings`.
+    pub fn apply_settings(&self, id: &str, settings: &crate::config::StrobeSettings) {
+        write_lock(&self.applied_settings).insert(id.to_string(), settings.clone());
+        write_lock(&self.event_limits).insert(id.to_string(), settings.events_max_per_session);
+        let mut policies = write_lock(&self.downsample_policies);
+        match settings.events_downsample_after_minutes {
+            Some(minutes) => {
+                policies.insert(
+                    id.to_string(),
+                    crate::db::DownsamplePolicy {
+                        after_ns: minutes as i64 * 60_000_000_000,
+                        keep_every: settings.events_downsample_keep_every,
+                    },
+                );
+            }
+            None => {
+                policies.remove(id);
+            }
+        }
+    }
+
+    /// The settings last passed to `apply_settings` for a session.
+    pub fn applied_settings(&self, id: &str) -> Option<crate::config::StrobeSettings> {
+        read_lock(&self.applied_settings).get(id).cloned()
+    }
+
     pub fn get_session(&self, id: &str) -> Result<Option<Session>> {
         self.db.get_session(id)
     }
@@ -434,6 +1098,7 @@ impl SessionManager {
 
     pub async fn stop_session(&self, id: &str) -> Result<u64> {
         self.flush_writer(id).await;
+        self.remember_instrumentation(id);
         let count = self.db.count_session_events(id)?;
         self.db.delete_session(id)?;
         self.cleanup_session_state(id);
@@ -444,6 +1109,7 @@ impl SessionManager {
     /// Cleans up in-memory state and flushes the writer, but does NOT delete from DB.
     pub async fn stop_session_retain(&self, id: &str) -> Result<u64> {
         self.flush_writer(id).await;
+        self.remember_instrumentation(id);
         let count = self.db.count_session_events(id)?;
         self.db.mark_session_stopped(id)?;
         self.cleanup_session_state(id);
@@ -467,18 +1133,82 @@ impl SessionManager {
         }
     }
 
+    /// Keep a stopping session's instrumentation for the next launch of its
+    /// binary in the same project. Only sessions whose process was spawned
+    /// count, so a failed launch doesn't clear it; one that ran with nothing
+    /// installed does.
+    fn remember_instrumentation(&self, id: &str) {
+        let project_root = match self.db.get_session(id) {
+            Ok(Some(session)) if session.pid != 0 => session.project_root,
+            _ => return,
+        };
+        let Ok((snapshot, omitted)) = self.instrumentation_snapshot(id) else {
+            return;
+        };
+        let key = (project_root, snapshot.binary_path.clone());
+        let mut carried = write_lock(&self.carried_instrumentation);
+        if snapshot.is_empty() && omitted.is_empty() {
+            carried.remove(&key);
+        } else {
+            carried.insert(key, CarriedInstrumentation { snapshot, omitted });
+        }
+    }
+
+    /// Instrumentation left by the last stopped session of `binary_path`
+    /// launched from `project_root`.
+    pub fn carried_instrumentation(
+        &self,
+        project_root: &str,
+        binary_path: &str,
+    ) -> Option<CarriedInstrumentation> {
+        read_lock(&self.carried_instrumentation)
+            .get(&(project_root.to_string(), binary_path.to_string()))
+            .cloned()
+    }
+
     /// Clean up all in-memory state for a session.
     fn cleanup_session_state(&self, id: &str) {
         write_lock(&self.patterns).remove(id);
         write_lock(&self.hook_counts).remove(id);
         write_lock(&self.watches).remove(id);
         write_lock(&self.event_limits).remove(id);
+        write_lock(&self.downsample_policies).remove(id);
+        write_lock(&self.applied_settings).remove(id);
+        write_lock(&self.writer_stats).remove(id);
+        write_lock(&self.serialization_depths).remove(id);
         write_lock(&self.child_pids).remove(id);
+        write_lock(&self.test_progress).remove(id);
+        write_lock(&self.child_tests).remove(id);
+        if let Some(signal) = write_lock(&self.event_signals).remove(id) {
+            // Wake streams so they notice the session ended
+            signal.notify_waiters();
+        }
         write_lock(&self.breakpoints).remove(id);
         write_lock(&self.logpoints).remove(id);
+        write_lock(&self.watchpoints).remove(id);
+        write_lock(&self.sampling).remove(id);
+        write_lock(&self.thread_filters).remove(id);
+        write_lock(&self.rate_limits).remove(id);
+        write_lock(&self.flight_recorders).remove(id);
+        write_lock(&self.signal_safety).remove(id);
+        write_lock(&self.syscall_groups).remove(id);
+        write_lock(&self.network_tracing).remove(id);
+        write_lock(&self.exception_tracing).remove(id);
+        write_lock(&self.signal_tracing).remove(id);
+        write_lock(&self.lock_tracing).remove(id);
+        write_lock(&self.crash_captures).remove(id);
+        write_lock(&self.heap_tracking).remove(id);
+        write_lock(&self.symbol_modules).remove(id);
         write_lock(&self.paused_threads).remove(id);
         write_lock(&self.languages).remove(id);
         write_lock(&self.resolvers).remove(id);
+        write_lock(&self.uninstrumented).remove(id);
+        write_lock(&self.output_channels).remove(id);
+        write_lock(&self.output_triggers).remove(id);
+        write_lock(&self.log_formats).remove(id);
+        write_lock(&self.counted_patterns).remove(id);
+        write_lock(&self.rt_patterns).remove(id);
+        self.resume_trigger_stopped(id);
         if let Some(path) = write_lock(&self.esm_hook_paths).remove(id) {
             let _ = std::fs::remove_file(&path);
         }
@@ -491,6 +1221,40 @@ impl SessionManager {
             .push(pid);
     }
 
+    /// Register a debug_test session's progress so children it spawns are
+    /// traced and attributed to the test running at the time.
+    pub fn set_test_progress(
+        &self,
+        session_id: &str,
+        progress: Arc<Mutex<crate::test::TestProgress>>,
+    ) {
+        write_lock(&self.test_progress).insert(session_id.to_string(), progress);
+    }
+
+    fn child_follower(&self) -> ChildFollower {
+        ChildFollower {
+            db: self.db.clone(),
+            spawner: Arc::clone(&self.frida_spawner),
+            dwarf_cache: Arc::clone(&self.dwarf_cache),
+            patterns: Arc::clone(&self.patterns),
+            counted_patterns: Arc::clone(&self.counted_patterns),
+            rt_patterns: Arc::clone(&self.rt_patterns),
+            serialization_depths: Arc::clone(&self.serialization_depths),
+            child_pids: Arc::clone(&self.child_pids),
+            test_progress: Arc::clone(&self.test_progress),
+            child_tests: Arc::clone(&self.child_tests),
+        }
+    }
+
+    /// Notified whenever new events for the session have been stored.
+    pub fn event_signal(&self, session_id: &str) -> Arc<tokio::sync::Notify> {
+        Arc::clone(
+            write_lock(&self.event_signals)
+                .entry(session_id.to_string())
+                .or_default(),
+        )
+    }
+
     pub fn get_all_pids(&self, session_id: &str) -> Vec<u32> {
         let mut pids = vec![];
         if let Ok(Some(session)) = self.get_session(session_id) {
@@ -502,6 +1266,20 @@ impl SessionManager {
         pids
     }
 
+    /// Spell patterns the way the session's language names functions, so
+    /// `mypkg::handlers::*` and `mypkg.handlers.*` are the same pattern in a
+    /// Python session. Sessions without a detected language count as native.
+    pub fn normalize_patterns(&self, session_id: &str, patterns: &[String]) -> Vec<String> {
+        let language = read_lock(&self.languages)
+            .get(session_id)
+            .copied()
+            .unwrap_or(Language::Native);
+        patterns
+            .iter()
+            .map(|p| language.normalize_pattern(p))
+            .collect()
+    }
+
     pub fn add_patterns(&self, session_id: &str, patterns: &[String]) -> Result<()> {
         let mut all_patterns = write_lock(&self.patterns);
         let session_patterns = all_patterns.entry(session_id.to_string()).or_default();
@@ -520,6 +1298,12 @@ impl SessionManager {
         if let Some(session_patterns) = all_patterns.get_mut(session_id) {
             session_patterns.retain(|p| !patterns.contains(p));
         }
+        if let Some(counted) = write_lock(&self.counted_patterns).get_mut(session_id) {
+            counted.retain(|p| !patterns.contains(p));
+        }
+        if let Some(rt) = write_lock(&self.rt_patterns).get_mut(session_id) {
+            rt.retain(|p| !patterns.contains(p));
+        }
         Ok(())
     }
 
@@ -530,6 +1314,41 @@ impl SessionManager {
             .unwrap_or_default()
     }
 
+    /// Record how `patterns` are hooked. Must run before update_frida_patterns
+    /// installs them, which splits each batch by mode.
+    pub fn set_pattern_mode(
+        &self,
+        session_id: &str,
+        patterns: &[String],
+        mode: crate::mcp::TraceMode,
+    ) {
+        for (lock, lock_mode) in [
+            (&self.counted_patterns, crate::mcp::TraceMode::Count),
+            (&self.rt_patterns, crate::mcp::TraceMode::Rt),
+        ] {
+            let mut all = write_lock(lock);
+            let moded = all.entry(session_id.to_string()).or_default();
+            moded.retain(|p| !patterns.contains(p));
+            if mode == lock_mode {
+                moded.extend(patterns.iter().cloned());
+            }
+        }
+    }
+
+    pub fn get_counted_patterns(&self, session_id: &str) -> Vec<String> {
+        read_lock(&self.counted_patterns)
+            .get(session_id)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    pub fn get_rt_patterns(&self, session_id: &str) -> Vec<String> {
+        read_lock(&self.rt_patterns)
+            .get(session_id)
+            .cloned()
+            .unwrap_or_default()
+    }
+
     pub fn set_hook_count(&self, session_id: &str, count: u32) {
         write_lock(&self.hook_counts).insert(session_id.to_string(), count);
     }
@@ -585,114 +1404,331 @@ impl SessionManager {
         search_root: Option<&str>,
         symbols_path: Option<&str>,
     ) -> DwarfHandle {
-        // Include mtime and symbols_path in cache key so rebuilds and symbol overrides invalidate correctly
-        let mtime = std::fs::metadata(binary_path)
-            .and_then(|m| m.modified())
-            .ok();
-        let cache_key = match (mtime, symbols_path) {
-            (Some(t), Some(sp)) => format!(
-                "{}@{}@sym:{}",
-                binary_path,
-                t.duration_since(std::time::UNIX_EPOCH)
-                    .unwrap_or_default()
-                    .as_secs(),
-                sp
-            ),
-            (Some(t), None) => format!(
-                "{}@{}",
-                binary_path,
-                t.duration_since(std::time::UNIX_EPOCH)
-                    .unwrap_or_default()
-                    .as_secs()
-            ),
-            (None, Some(sp)) => format!("{}@sym:{}", binary_path, sp),
-            (None, None) => binary_path.to_string(),
-        };
-
-        // Fast path: read lock only
-        {
-            let cache = read_lock(&self.dwarf_cache);
-            if let Some(handle) = cache.get(&cache_key) {
-                if !handle.is_failed() {
-                    return handle.clone();
-                }
-            }
-        }
-
-        // Slow path: write lock with double-check
-        let mut cache = write_lock(&self.dwarf_cache);
-        if let Some(handle) = cache.get(&cache_key) {
-            if !handle.is_failed() {
-                return handle.clone();
-            }
-        }
-
-        let handle = DwarfHandle::spawn_parse(binary_path, search_root, symbols_path);
-        cache.insert(cache_key, handle.clone());
-        handle
+        dwarf_handle_for(&self.dwarf_cache, binary_path, search_root, symbols_path)
     }
 
     pub fn db(&self) -> &Database {
         &self.db
     }
 
-    /// Spawn a process with Frida attached.
-    /// DWARF parsing happens in the background — launch is fast (~1s).
-    pub async fn spawn_with_frida(
-        &self,
-        session_id: &str,
-        command: &str,
-        args: &[String],
-        cwd: Option<&str>,
-        project_root: &str,
-        env: Option<&std::collections::HashMap<String, String>>,
-        defer_resume: bool,
-        symbols_path: Option<&str>,
-    ) -> Result<u32> {
-        // Kill orphaned instances from previous runs (PPID == 1 means parent died).
-        // Checks: exact binary name, known test fixtures, and target/debug/deps binaries.
-        reap_orphaned_processes(command, project_root);
-
-        // Detect language from command and project signals
-        let language = detect_language(command, Path::new(project_root));
-        write_lock(&self.languages).insert(session_id.to_string(), language);
-        tracing::info!(
-            "Detected language for session {}: {:?}",
-            session_id,
-            language
-        );
+    /// Writer-side counters for a session whose writer has been started.
+    pub fn writer_status(&self, session_id: &str) -> Option<crate::mcp::WriterStatus> {
+        read_lock(&self.writer_stats)
+            .get(session_id)
+            .map(|stats| stats.snapshot())
+    }
 
-        // Derive baseline runtime capabilities (enriched later by agent)
-        let caps = crate::capabilities::derive_capabilities(language, command);
-        write_lock(&self.capabilities).insert(session_id.to_string(), caps);
+    /// Create the event channel and database writer task for a session.
+    /// The writer batches inserts and is cancelled/awaited by `stop_session`.
+    async fn start_event_writer(&self, session_id: &str) -> EventSender {
+        let (tx, mut rx) = event_pipeline::event_channel(event_pipeline::QUEUE_CAPACITY);
+        let writer_stats = tx.stats();
+        write_lock(&self.writer_stats).insert(session_id.to_string(), Arc::clone(&writer_stats));
 
-        // Extract image base cheaply (<10ms) — only reads __TEXT segment address
-        let image_base = DwarfParser::extract_image_base(Path::new(command)).unwrap_or(0);
+        // Spawn database writer task with automatic event limit enforcement
+        let db = self.db.clone();
+        let event_limits = Arc::clone(&self.event_limits);
+        let downsample_policies = Arc::clone(&self.downsample_policies);
+        let writer_session_id = session_id.to_string();
+        let crash_dir = self.crash_dir.clone();
+        let dwarf_cache = Arc::clone(&self.dwarf_cache);
+        let mut triggers = OutputTriggerSet::new(
+            read_lock(&self.output_triggers)
+                .get(session_id)
+                .cloned()
+                .unwrap_or_default(),
+        );
+        let mut lines = LineBuffer::new(read_lock(&self.log_formats).get(session_id).copied());
+        let child_tests = Arc::clone(&self.child_tests);
+        let stored_signal = self.event_signal(session_id);
+        let trigger_actions = TriggerActions {
+            db: self.db.clone(),
+            spawner: Arc::clone(&self.frida_spawner),
+            watches: Arc::clone(&self.watches),
+            stopped: Arc::clone(&self.trigger_stopped),
+        };
+        let (cancel_tx, mut cancel_rx) = tokio::sync::watch::channel(false);
+        write_lock(&self.writer_cancel_tokens).insert(session_id.to_string(), cancel_tx);
 
-        // Start background DWARF parse (or get cached handle)
-        let dwarf_handle =
-            self.get_or_start_dwarf_parse_with_symbols(command, Some(project_root), symbols_path);
+        let writer_handle = tokio::spawn(async move {
+            let mut batch = Vec::with_capacity(event_pipeline::MIN_BATCH);
+            let mut sizer = BatchSizer::default();
+            let mut call_order = CallOrderer::new();
+            let mut cached_limit = crate::config::StrobeSettings::default().events_max_per_session;
+            let mut batches_since_refresh = 0u32;
+            let mut last_downsample = Instant::now();
+            let mut last_checkpoint = Instant::now();
 
-        // For native binaries, instantiate DwarfResolver once parse completes
-        if language == Language::Native {
-            let mut dwarf_clone = dwarf_handle.clone();
-            let resolvers = Arc::clone(&self.resolvers);
-            let sid = session_id.to_string();
-            tokio::spawn(async move {
-                // Wait for DWARF parse to complete
-                match dwarf_clone.get().await {
-                    Ok(_) => {
-                        let resolver = Arc::new(DwarfResolver::new(dwarf_clone, image_base));
-                        write_lock(&resolvers)
-                            .insert(sid.clone(), resolver as Arc<dyn SymbolResolver>);
-                        tracing::debug!("DwarfResolver instantiated for session {}", sid);
-                    }
-                    Err(e) => {
-                        tracing::warn!("DWARF parse failed for session {}: {}", sid, e);
-                    }
-                }
-            });
-        } else if language == Language::Python {
+            let flush_batch = |batch: &mut Vec<Event>,
+                               cached_limit: &mut usize,
+                               batches_since_refresh: &mut u32| {
+                if batch.is_empty() {
+                    return;
+                }
+                if *batches_since_refresh >= 10 {
+                    let session_id = &batch[0].session_id;
+                    *cached_limit = read_lock(&event_limits)
+                        .get(session_id)
+                        .copied()
+                        .unwrap_or(crate::config::StrobeSettings::default().events_max_per_session);
+                    *batches_since_refresh = 0;
+                }
+                *batches_since_refresh += 1;
+                // Inserts are idempotent on event id, so a failed batch can be
+                // retried whole without duplicating rows that made it in.
+                for attempt in 1..=WRITER_INSERT_ATTEMPTS {
+                    match db.insert_events_with_limit(batch, *cached_limit) {
+                        Ok(stats) => {
+                            if stats.events_deleted > 0 {
+                                tracing::warn!(
+                                    "Event limit cleanup: deleted {} old events from {} session(s) to stay within {} event limit",
+                                    stats.events_deleted, stats.sessions_cleaned.len(), cached_limit
+                                );
+                            }
+                            if stats.duplicates_ignored > 0 {
+                                tracing::debug!(
+                                    "Skipped {} duplicate event(s) already stored",
+                                    stats.duplicates_ignored
+                                );
+                            }
+                            stored_signal.notify_waiters();
+                            if writer_stats.record_flush(batch.len()) {
+                                checkpoint(&db, &writer_stats);
+                            }
+                            break;
+                        }
+                        Err(e) if attempt < WRITER_INSERT_ATTEMPTS => {
+                            tracing::warn!(
+                                "Failed to insert events (attempt {}), retrying: {}",
+                                attempt,
+                                e
+                            );
+                        }
+                        Err(e) => {
+                            tracing::error!(
+                                "Failed to insert events, dropping {}: {}",
+                                batch.len(),
+                                e
+                            );
+                            writer_stats.record_dropped(batch.len());
+                        }
+                    }
+                }
+                batch.clear();
+            };
+
+            let maybe_downsample = |last_downsample: &mut Instant| {
+                if last_downsample.elapsed() < DOWNSAMPLE_INTERVAL {
+                    return;
+                }
+                *last_downsample = Instant::now();
+                // Looked up each time: a settings reload can change it
+                let Some(policy) = read_lock(&downsample_policies)
+                    .get(&writer_session_id)
+                    .copied()
+                else {
+                    return;
+                };
+                match db.downsample_events(&writer_session_id, policy) {
+                    Ok(0) => {}
+                    Ok(deleted) => tracing::debug!(
+                        "Downsampled {} old trace event(s) in session {}",
+                        deleted,
+                        writer_session_id
+                    ),
+                    Err(e) => tracing::warn!("Failed to downsample events: {}", e),
+                }
+            };
+
+            loop {
+                let (received, expired_lines) = tokio::select! {
+                    Some(event) = rx.recv() => {
+                        // Take what is already queued, up to a batch, in one go
+                        let now = Instant::now();
+                        let mut received = call_order.push(event, now);
+                        let mut taken = 1;
+                        while taken < sizer.size() {
+                            let Ok(event) = rx.try_recv() else {
+                                break;
+                            };
+                            received.extend(call_order.push(event, now));
+                            taken += 1;
+                        }
+                        // Sustained traffic never reaches the idle branch, so
+                        // release held events past their deadline here too
+                        received.extend(call_order.expire(now));
+                        writer_stats.record_received(taken);
+                        (received, lines.expire(now))
+                    }
+                    _ = tokio::time::sleep(std::time::Duration::from_millis(10)) => {
+                        batch.extend(call_order.expire(Instant::now()));
+                        flush_batch(&mut batch, &mut cached_limit, &mut batches_since_refresh);
+                        writer_stats.set_batch_size(sizer.adjust(writer_stats.queued()));
+                        maybe_downsample(&mut last_downsample);
+                        if last_checkpoint.elapsed() >= event_pipeline::CHECKPOINT_INTERVAL {
+                            last_checkpoint = Instant::now();
+                            if writer_stats.take_uncheckpointed() {
+                                checkpoint(&db, &writer_stats);
+                            }
+                        }
+                        // An unfinished line still goes through the triggers
+                        let expired = lines.expire(Instant::now());
+                        if expired.is_empty() {
+                            continue;
+                        }
+                        (Vec::new(), expired)
+                    }
+                    _ = cancel_rx.changed() => {
+                        batch.extend(call_order.drain_all());
+                        batch.extend(lines.drain_all());
+                        flush_batch(&mut batch, &mut cached_limit, &mut batches_since_refresh);
+                        checkpoint(&db, &writer_stats);
+                        break;
+                    }
+                };
+                let mut events = Vec::with_capacity(received.len() + expired_lines.len());
+                for mut event in received {
+                    if event.event_type == crate::db::EventType::Crash {
+                        crash_dump::handle_crash_event(&db, &crash_dir, &mut event, |s| {
+                            dwarf_handle_for(
+                                &dwarf_cache,
+                                &s.binary_path,
+                                Some(&s.project_root),
+                                None,
+                            )
+                        });
+                    }
+                    if let Some(pid) = event.pid {
+                        event.test_name = read_lock(&child_tests)
+                            .get(&event.session_id)
+                            .and_then(|children| children.get(&pid))
+                            .cloned();
+                    }
+                    events.extend(lines.push(event, Instant::now()));
+                }
+                events.extend(expired_lines);
+                for event in events {
+                    let fired = if triggers.is_empty() {
+                        Vec::new()
+                    } else {
+                        triggers.scan(&event)
+                    };
+                    batch.push(event);
+                    if !fired.is_empty() {
+                        batch.extend(fired.iter().map(|(_, marker)| marker.clone()));
+                        if fired
+                            .iter()
+                            .any(|(action, _)| *action != crate::mcp::OutputTriggerAction::Annotate)
+                        {
+                            // The marker must be stored before a snapshot that refers to it
+                            flush_batch(&mut batch, &mut cached_limit, &mut batches_since_refresh);
+                            tokio::spawn(trigger_actions.clone().run(fired));
+                        }
+                    }
+                }
+                if batch.len() >= sizer.size() {
+                    flush_batch(&mut batch, &mut cached_limit, &mut batches_since_refresh);
+                    writer_stats.set_batch_size(sizer.adjust(writer_stats.queued()));
+                    maybe_downsample(&mut last_downsample);
+                }
+            }
+            if call_order.orphans_released() > 0 {
+                tracing::debug!(
+                    "Stored {} function_exit event(s) whose enter never arrived",
+                    call_order.orphans_released()
+                );
+            }
+        });
+
+        // Store writer handle so we can await completion during stop
+        self.writer_handles
+            .write()
+            .await
+            .insert(session_id.to_string(), writer_handle);
+
+        tx
+    }
+
+    /// Spawn a process with Frida attached.
+    /// DWARF parsing happens in the background — launch is fast (~1s).
+    pub async fn spawn_with_frida(
+        &self,
+        session_id: &str,
+        command: &str,
+        args: &[String],
+        cwd: Option<&str>,
+        project_root: &str,
+        env: Option<&std::collections::HashMap<String, String>>,
+        defer_resume: bool,
+        symbols_path: Option<&str>,
+    ) -> Result<u32> {
+        // Kill orphaned instances from previous runs (PPID == 1 means parent died).
+        // Checks: exact binary name, known test fixtures, and target/debug/deps binaries.
+        reap_orphaned_processes(command, project_root);
+
+        // Detect language from command and project signals
+        let language = detect_language(command, Path::new(project_root));
+        write_lock(&self.languages).insert(session_id.to_string(), language);
+        tracing::info!(
+            "Detected language for session {}: {:?}",
+            session_id,
+            language
+        );
+
+        // Derive baseline runtime capabilities (enriched later by agent)
+        let caps = crate::capabilities::derive_capabilities(language, command);
+        write_lock(&self.capabilities).insert(session_id.to_string(), caps);
+
+        let jar = jar_command(command, args);
+        let (command, args) = match &jar {
+            Some((java, java_args)) => (java.as_str(), java_args.as_slice()),
+            None => (command, args),
+        };
+
+        // Extract image base cheaply (<10ms) — only reads __TEXT segment address
+        let image_base = DwarfParser::extract_image_base(Path::new(command)).unwrap_or(0);
+
+        // Start background DWARF parse (or get cached handle)
+        let dwarf_handle =
+            self.get_or_start_dwarf_parse_with_symbols(command, Some(project_root), symbols_path);
+
+        // For native binaries, instantiate DwarfResolver once parse completes,
+        // or PdbResolver for Windows binaries, which keep symbols in a PDB
+        if language == Language::Native {
+            let mut dwarf_clone = dwarf_handle.clone();
+            let resolvers = Arc::clone(&self.resolvers);
+            let sid = session_id.to_string();
+            let binary = PathBuf::from(command);
+            tokio::spawn(async move {
+                // Wait for DWARF parse to complete
+                match dwarf_clone.get().await {
+                    Ok(_) => {
+                        let resolver = Arc::new(DwarfResolver::new(dwarf_clone, image_base));
+                        write_lock(&resolvers)
+                            .insert(sid.clone(), resolver as Arc<dyn SymbolResolver>);
+                        tracing::debug!("DwarfResolver instantiated for session {}", sid);
+                    }
+                    Err(e) => {
+                        match tokio::task::spawn_blocking(move || PdbResolver::load(&binary)).await
+                        {
+                            Ok(Ok(resolver)) => {
+                                let count = resolver.function_count();
+                                write_lock(&resolvers).insert(
+                                    sid.clone(),
+                                    Arc::new(resolver) as Arc<dyn SymbolResolver>,
+                                );
+                                tracing::info!(
+                                    "PdbResolver instantiated for session {} ({} functions)",
+                                    sid,
+                                    count
+                                );
+                            }
+                            _ => tracing::warn!("DWARF parse failed for session {}: {}", sid, e),
+                        }
+                    }
+                }
+            });
+        } else if language == Language::Python {
             // For Python, instantiate PythonResolver by parsing project .py files.
             // Await completion so resolver is ready before patterns are added.
             let resolvers = Arc::clone(&self.resolvers);
@@ -738,6 +1774,28 @@ impl SessionManager {
                 Ok(Err(e)) => tracing::warn!("JS resolver parse failed for {}: {}", sid, e),
                 Err(e) => tracing::warn!("JS resolver task panicked for {}: {}", sid, e),
             }
+        } else if language == Language::Java {
+            let resolvers = Arc::clone(&self.resolvers);
+            let sid = session_id.to_string();
+            let project_root_path = Path::new(project_root).to_path_buf();
+            match tokio::task::spawn_blocking(move || {
+                JavaResolver::from_project(&project_root_path)
+            })
+            .await
+            {
+                Ok(Ok(resolver)) => {
+                    let count = resolver.method_count();
+                    write_lock(&resolvers)
+                        .insert(sid.clone(), Arc::new(resolver) as Arc<dyn SymbolResolver>);
+                    tracing::info!(
+                        "JavaResolver instantiated for session {} ({} methods)",
+                        sid,
+                        count
+                    );
+                }
+                Ok(Err(e)) => tracing::warn!("Java resolver parse failed for {}: {}", sid, e),
+                Err(e) => tracing::warn!("Java resolver task panicked for {}: {}", sid, e),
+            }
         }
 
         // For Node.js sessions, inject ESM hook script via NODE_OPTIONS.
@@ -772,75 +1830,19 @@ impl SessionManager {
         }
         let effective_env = esm_env_overlay.as_ref().or(env);
 
-        // Create event channel
-        let (tx, mut rx) = mpsc::channel::<Event>(10000);
-
-        // Spawn database writer task with automatic event limit enforcement
-        let db = self.db.clone();
-        let event_limits = Arc::clone(&self.event_limits);
-        let (cancel_tx, mut cancel_rx) = tokio::sync::watch::channel(false);
-        write_lock(&self.writer_cancel_tokens).insert(session_id.to_string(), cancel_tx);
-
-        let writer_handle = tokio::spawn(async move {
-            let mut batch = Vec::with_capacity(100);
-            let mut cached_limit = crate::config::StrobeSettings::default().events_max_per_session;
-            let mut batches_since_refresh = 0u32;
-
-            let flush_batch = |batch: &mut Vec<Event>,
-                               cached_limit: &mut usize,
-                               batches_since_refresh: &mut u32| {
-                if batch.is_empty() {
-                    return;
-                }
-                if *batches_since_refresh >= 10 {
-                    let session_id = &batch[0].session_id;
-                    *cached_limit = read_lock(&event_limits)
-                        .get(session_id)
-                        .copied()
-                        .unwrap_or(crate::config::StrobeSettings::default().events_max_per_session);
-                    *batches_since_refresh = 0;
-                }
-                *batches_since_refresh += 1;
-                match db.insert_events_with_limit(batch, *cached_limit) {
-                    Ok(stats) => {
-                        if stats.events_deleted > 0 {
-                            tracing::warn!(
-                                "Event limit cleanup: deleted {} old events from {} session(s) to stay within {} event limit",
-                                stats.events_deleted, stats.sessions_cleaned.len(), cached_limit
-                            );
-                        }
-                    }
-                    Err(e) => {
-                        tracing::error!("Failed to insert events: {}", e);
-                    }
-                }
-                batch.clear();
-            };
-
-            loop {
-                tokio::select! {
-                    Some(event) = rx.recv() => {
-                        batch.push(event);
-                        if batch.len() >= 100 {
-                            flush_batch(&mut batch, &mut cached_limit, &mut batches_since_refresh);
-                        }
-                    }
-                    _ = tokio::time::sleep(std::time::Duration::from_millis(10)) => {
-                        flush_batch(&mut batch, &mut cached_limit, &mut batches_since_refresh);
-                    }
-                    _ = cancel_rx.changed() => {
-                        flush_batch(&mut batch, &mut cached_limit, &mut batches_since_refresh);
-                        break;
-                    }
-                }
-            }
-        });
+        let tx = self.start_event_writer(session_id).await;
 
-        // Store writer handle so we can await completion during stop
-        self.writer_handles
-            .write()
-            .await
-            .insert(session_id.to_string(), writer_handle);
+        // Auxiliary output: pipes are read daemon-side, fds are hooked by the agent
+        let channels = read_lock(&self.output_channels)
+            .get(session_id)
+            .cloned()
+            .unwrap_or_default();
+        // FIFOs are Unix-only; launch validation rejects capturePipes on Windows
+        #[cfg(unix)]
+        for (i, path) in channels.pipes.iter().enumerate() {
+            ensure_fifo(path)?;
+            spawn_pipe_reader(session_id, i, path, tx.clone())?;
+        }
 
         // Create pause notification channel for breakpoint support
         let (pause_tx, mut pause_rx) =
@@ -882,7 +1884,13 @@ impl SessionManager {
         // Ensure FridaSpawner exists (brief write lock for lazy init only)
         {
             let mut guard = self.frida_spawner.write().await;
-            guard.get_or_insert_with(FridaSpawner::new);
+            if guard.is_none() {
+                let spawner = FridaSpawner::new();
+                let (child_tx, child_rx) = tokio::sync::mpsc::unbounded_channel();
+                spawner.set_child_follower(child_tx);
+                tokio::spawn(self.child_follower().run(child_rx));
+                *guard = Some(spawner);
+            }
         }
         // Use read lock for the actual spawn — allows concurrent Frida operations
         let guard = self.frida_spawner.read().await;
@@ -901,56 +1909,230 @@ impl SessionManager {
                 defer_resume,
                 Some(pause_tx),
                 language,
+                channels.fds,
             )
             .await
     }
 
-    /// Resume a process that was spawned with defer_resume=true.
-    pub async fn resume_process(&self, pid: u32) -> Result<()> {
-        let guard = self.frida_spawner.read().await;
-        match guard.as_ref() {
-            Some(spawner) => spawner.resume(pid).await,
-            None => Err(crate::Error::Frida(
-                "No Frida spawner initialized".to_string(),
-            )),
-        }
-    }
-
-    /// Update Frida trace patterns
-    pub async fn update_frida_patterns(
+    /// Spawn a plain subprocess without Frida (`instrumentation: "none"`).
+    ///
+    /// stdout/stderr are piped into the session's event stream exactly like
+    /// Frida output capture, so progress parsing and debug_query keep working.
+    /// The child is not waited on here — the caller reaps it via waitpid.
+    pub async fn spawn_plain(
         &self,
         session_id: &str,
-        add: Option<&[String]>,
-        remove: Option<&[String]>,
-        serialization_depth: Option<u32>,
-    ) -> Result<HookResult> {
-        // Get resolver for this session (if available)
-        let resolver = {
-            let resolvers = read_lock(&self.resolvers);
-            resolvers.get(session_id).cloned()
-        };
+        command: &str,
+        args: &[String],
+        cwd: Option<&str>,
+        project_root: &str,
+        env: Option<&HashMap<String, String>>,
+    ) -> Result<u32> {
+        reap_orphaned_processes(command, project_root);
 
-        let guard = self.frida_spawner.read().await;
-        let spawner = match guard.as_ref() {
-            Some(s) => s,
-            None => {
-                return Ok(HookResult {
-                    installed: 0,
-                    matched: 0,
-                    warnings: vec![],
-                })
-            }
-        };
+        let language = detect_language(command, Path::new(project_root));
+        write_lock(&self.languages).insert(session_id.to_string(), language);
+        write_lock(&self.capabilities).insert(
+            session_id.to_string(),
+            crate::capabilities::uninstrumented_capabilities(language, command),
+        );
+        write_lock(&self.uninstrumented).insert(session_id.to_string());
 
-        if let Some(patterns) = add {
-            return spawner
-                .add_patterns(
-                    session_id,
-                    patterns,
-                    serialization_depth,
-                    resolver.as_ref().map(|v| &**v),
-                )
-                .await;
+        let tx = self.start_event_writer(session_id).await;
+
+        let mut cmd = std::process::Command::new(command);
+        cmd.args(args)
+            .stdin(std::process::Stdio::null())
+            .stdout(std::process::Stdio::piped())
+            .stderr(std::process::Stdio::piped());
+        if let Some(dir) = cwd {
+            cmd.current_dir(dir);
+        }
+        if let Some(vars) = env {
+            cmd.env_clear().envs(vars);
+        }
+        let mut child = cmd
+            .spawn()
+            .map_err(|e| crate::Error::Internal(format!("Failed to spawn '{}': {}", command, e)))?;
+        let pid = child.id();
+
+        let start_ns = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos() as i64;
+        let counter = Arc::new(std::sync::atomic::AtomicU64::new(0));
+
+        let streams: [(Option<Box<dyn std::io::Read + Send>>, crate::db::EventType); 2] = [
+            (
+                child
+                    .stdout
+                    .take()
+                    .map(|s| Box::new(s) as Box<dyn std::io::Read + Send>),
+                crate::db::EventType::Stdout,
+            ),
+            (
+                child
+                    .stderr
+                    .take()
+                    .map(|s| Box::new(s) as Box<dyn std::io::Read + Send>),
+                crate::db::EventType::Stderr,
+            ),
+        ];
+        for (stream, event_type) in streams {
+            let Some(stream) = stream else { continue };
+            let tx = tx.clone();
+            let counter = Arc::clone(&counter);
+            let sid = session_id.to_string();
+            std::thread::spawn(move || {
+                use std::io::BufRead;
+                let mut reader = std::io::BufReader::new(stream);
+                let mut line = Vec::new();
+                loop {
+                    line.clear();
+                    match reader.read_until(b'\n', &mut line) {
+                        Ok(0) | Err(_) => break,
+                        Ok(_) => {}
+                    }
+                    let n = counter.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+                    let now_ns = (std::time::SystemTime::now()
+                        .duration_since(std::time::UNIX_EPOCH)
+                        .unwrap_or_default()
+                        .as_nanos() as i64)
+                        - start_ns;
+                    let event = Event {
+                        id: format!("{}-output-{}", sid, n),
+                        session_id: sid.clone(),
+                        timestamp_ns: now_ns,
+                        event_type: event_type.clone(),
+                        text: Some(String::from_utf8_lossy(&line).into_owned()),
+                        pid: Some(pid),
+                        ..Event::default()
+                    };
+                    if tx.blocking_send(event).is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+
+        tracing::info!(
+            "Spawned uninstrumented process {} for session {}",
+            pid,
+            session_id
+        );
+        Ok(pid)
+    }
+
+    /// Configure auxiliary output capture. Must be called before spawning.
+    pub fn set_output_channels(&self, session_id: &str, channels: OutputChannels) {
+        write_lock(&self.output_channels).insert(session_id.to_string(), channels);
+    }
+
+    pub fn set_output_triggers(&self, session_id: &str, triggers: Vec<crate::mcp::OutputTrigger>) {
+        write_lock(&self.output_triggers).insert(session_id.to_string(), triggers);
+    }
+
+    pub fn set_log_format(&self, session_id: &str, format: crate::mcp::LogFormat) {
+        write_lock(&self.log_formats).insert(session_id.to_string(), format);
+    }
+
+    /// SIGCONT every process stopped by a `pause` output trigger. Returns the resumed pids.
+    pub fn resume_trigger_stopped(&self, session_id: &str) -> Vec<u32> {
+        let pids = write_lock(&self.trigger_stopped)
+            .remove(session_id)
+            .unwrap_or_default();
+        output_triggers::signal_pids(&pids, crate::process::resume)
+    }
+
+    /// Whether the session was spawned without Frida.
+    pub fn is_uninstrumented(&self, session_id: &str) -> bool {
+        read_lock(&self.uninstrumented).contains(session_id)
+    }
+
+    /// Reject operations that need Frida on sessions spawned with `instrumentation: "none"`.
+    pub fn require_instrumented(&self, session_id: &str) -> Result<()> {
+        if self.is_uninstrumented(session_id) {
+            return Err(crate::Error::ValidationError(format!(
+                "Session '{}' was started with instrumentation: 'none'. \
+                 Re-run debug_test without instrumentation: 'none' to use tracing, breakpoints, watches or memory access.",
+                session_id
+            )));
+        }
+        Ok(())
+    }
+
+    /// Resume a process that was spawned with defer_resume=true.
+    pub async fn resume_process(&self, pid: u32) -> Result<()> {
+        let guard = self.frida_spawner.read().await;
+        match guard.as_ref() {
+            Some(spawner) => spawner.resume(pid).await,
+            None => Err(crate::Error::Frida(
+                "No Frida spawner initialized".to_string(),
+            )),
+        }
+    }
+
+    /// Update Frida trace patterns
+    pub async fn update_frida_patterns(
+        &self,
+        session_id: &str,
+        add: Option<&[String]>,
+        remove: Option<&[String]>,
+        serialization_depth: Option<u32>,
+    ) -> Result<HookResult> {
+        if let Some(depth) = serialization_depth {
+            write_lock(&self.serialization_depths).insert(session_id.to_string(), depth);
+        }
+
+        // Get resolver for this session (if available)
+        let resolver = {
+            let resolvers = read_lock(&self.resolvers);
+            resolvers.get(session_id).cloned()
+        };
+
+        let guard = self.frida_spawner.read().await;
+        let spawner = match guard.as_ref() {
+            Some(s) => s,
+            None => {
+                return Ok(HookResult {
+                    installed: 0,
+                    matched: 0,
+                    warnings: vec![],
+                })
+            }
+        };
+
+        if let Some(patterns) = add {
+            let batches = batches_by_mode(
+                patterns.to_vec(),
+                &self.get_counted_patterns(session_id),
+                &self.get_rt_patterns(session_id),
+            );
+
+            let mut result = HookResult {
+                installed: 0,
+                matched: 0,
+                warnings: vec![],
+            };
+            for (batch, mode) in batches {
+                if batch.is_empty() {
+                    continue;
+                }
+                let batch_result = spawner
+                    .add_patterns(
+                        session_id,
+                        &batch,
+                        serialization_depth,
+                        resolver.as_ref().map(|v| &**v),
+                        mode,
+                    )
+                    .await?;
+                // installed is the agent's running total (0 if nothing was sent)
+                result.installed = result.installed.max(batch_result.installed);
+                result.matched += batch_result.matched;
+                result.warnings.extend(batch_result.warnings);
+            }
+            return Ok(result);
         }
 
         if let Some(patterns) = remove {
@@ -971,6 +2153,35 @@ impl SessionManager {
         })
     }
 
+    /// What `debug_trace({ add: patterns })` would hook in this session,
+    /// without hooking it. Meta-patterns (`@syscalls:`, `@locks`, ...) hook
+    /// runtime functions in the agent and are only noted.
+    pub async fn preview_patterns(
+        &self,
+        session_id: &str,
+        patterns: &[String],
+    ) -> Result<crate::mcp::DebugTracePreviewResponse> {
+        let split = crate::mcp::MetaPatterns::split(patterns);
+        let functions = self.normalize_patterns(session_id, &split.functions);
+        let resolver = read_lock(&self.resolvers).get(session_id).cloned();
+
+        let guard = self.frida_spawner.read().await;
+        let spawner = guard
+            .as_ref()
+            .ok_or_else(|| crate::Error::Internal("Frida spawner not initialized".to_string()))?;
+        let mut response = spawner
+            .preview_patterns(session_id, &functions, resolver.as_deref())
+            .await?;
+        let meta = patterns.iter().filter(|p| !split.functions.contains(p));
+        response.warnings.extend(meta.map(|p| {
+            format!(
+                "{} hooks runtime functions in the agent; it isn't matched against symbols",
+                p
+            )
+        }));
+        Ok(response)
+    }
+
     /// Update Frida watches
     pub async fn update_frida_watches(
         &self,
@@ -1026,7 +2237,16 @@ impl SessionManager {
             .get(&req.session_id)
             .copied()
             .unwrap_or(Language::Native);
-        if lang == Language::Python || lang == Language::JavaScript {
+        if lang == Language::Python || lang == Language::JavaScript || lang == Language::Java {
+            if req
+                .targets
+                .iter()
+                .any(|t| t.region.is_some() || t.format == Some(ReadFormat::Hexdump))
+            {
+                return Err(crate::Error::ValidationError(
+                    "Hexdump and region reads need a native session".to_string(),
+                ));
+            }
             return self.execute_interpreted_read(&req).await;
         }
 
@@ -1036,8 +2256,30 @@ impl SessionManager {
 
         // Get DWARF parser for variable resolution
         let dwarf = self.get_dwarf(&req.session_id).await?;
+        // Labels answered with a hexdump instead of a value
+        let mut hexdumps: HashMap<String, HexdumpTarget> = HashMap::new();
 
         for target in &req.targets {
+            if target.region.is_some() || target.format == Some(ReadFormat::Hexdump) {
+                let label = target
+                    .variable
+                    .clone()
+                    .or_else(|| target.address.clone())
+                    .or_else(|| target.region.as_ref().map(|r| r.base.clone()))
+                    .unwrap_or_default();
+                match hexdump_recipe(target, &label, dwarf.as_ref()).await {
+                    Ok((recipe, hexdump)) => {
+                        recipes.push(recipe);
+                        hexdumps.insert(label, hexdump);
+                    }
+                    Err(e) => response_results.push(ReadResult {
+                        target: label,
+                        error: Some(e.to_string()),
+                        ..Default::default()
+                    }),
+                }
+                continue;
+            }
             if let Some(ref var_name) = target.variable {
                 let dwarf_ref = match dwarf.as_ref() {
                     Some(d) => d,
@@ -1051,6 +2293,28 @@ impl SessionManager {
                     }
                 };
 
+                // Aggregates, enums, strings and struct pointers render from the type tree
+                let parser = Arc::clone(dwarf_ref);
+                let name = var_name.clone();
+                let layout =
+                    tokio::task::spawn_blocking(move || parser.resolve_read_layout(&name, depth))
+                        .await
+                        .map_err(|e| {
+                            crate::Error::Internal(format!("Type layout panicked: {}", e))
+                        })?;
+                if let Ok(Some((address, layout))) = layout {
+                    recipes.push(serde_json::json!({
+                        "label": var_name,
+                        "address": format!("0x{:x}", address),
+                        "size": 0,
+                        "typeKind": "layout",
+                        "derefDepth": 0,
+                        "derefOffset": 0,
+                        "layout": layout,
+                    }));
+                    continue;
+                }
+
                 match dwarf_ref.resolve_read_target(var_name, depth) {
                     Ok((recipe, struct_fields)) => {
                         let type_kind_str = type_kind_to_agent_str(&recipe.type_kind);
@@ -1128,10 +2392,12 @@ impl SessionManager {
             msg["imageBase"] = serde_json::json!(format!("0x{:x}", d.image_base));
         }
 
+        let poll_id = format!("poll-{}", &uuid::Uuid::new_v4().to_string()[..8]);
         if let Some(ref poll) = req.poll {
             msg["poll"] = serde_json::json!({
                 "intervalMs": poll.interval_ms,
                 "durationMs": poll.duration_ms,
+                "pollId": poll_id,
             });
         }
 
@@ -1149,8 +2415,13 @@ impl SessionManager {
                 duration_ms: poll.duration_ms,
                 expected_samples: expected,
                 event_type: "variable_snapshot".to_string(),
-                hint: "Use debug_query({ eventType: 'variable_snapshot' }) to see results"
-                    .to_string(),
+                hint: format!(
+                    "Each sample reads every target at one timestamp. When done, \
+                     debug_memory({{ action: 'series', pollId: '{}' }}) returns them as \
+                     aligned columns (csv: true writes a CSV file)",
+                    poll_id
+                ),
+                poll_id,
             };
             return Ok(serde_json::to_value(response)?);
         }
@@ -1166,6 +2437,34 @@ impl SessionManager {
 
                 if let Some(err) = result.get("error").and_then(|v| v.as_str()) {
                     read_result.error = Some(err.to_string());
+                } else if let Some(hexdump) = hexdumps.get(label) {
+                    let address = result
+                        .get("address")
+                        .and_then(|v| v.as_str())
+                        .and_then(|a| u64::from_str_radix(a.trim_start_matches("0x"), 16).ok())
+                        .unwrap_or(0);
+                    match result
+                        .get("value")
+                        .and_then(|v| v.as_str())
+                        .map(hex_to_bytes)
+                    {
+                        Some(Ok(bytes)) => {
+                            let mut lines = hexdump_lines(address, &bytes, &hexdump.annotations);
+                            if hexdump.omitted > 0 {
+                                lines.push(format!(
+                                    "... {} more bytes (hexdump shows at most {})",
+                                    hexdump.omitted, MAX_HEXDUMP_BYTES
+                                ));
+                            }
+                            read_result.address = Some(format!("0x{:x}", address));
+                            read_result.size = Some(bytes.len() as u32);
+                            read_result.hexdump = Some(lines);
+                        }
+                        Some(Err(e)) => {
+                            read_result.error = Some(format!("Failed to decode bytes: {}", e))
+                        }
+                        None => read_result.error = Some("No bytes returned".to_string()),
+                    }
                 } else if let Some(fields) = result.get("fields") {
                     read_result.fields = Some(fields.clone());
                 } else if let Some(value) = result.get("value") {
@@ -1465,6 +2764,8 @@ impl SessionManager {
 
     /// Stop Frida session
     pub async fn stop_frida(&self, session_id: &str) -> Result<()> {
+        // A stopped process cannot handle the detach/kill handshake
+        self.resume_trigger_stopped(session_id);
         let guard = self.frida_spawner.read().await;
         match guard.as_ref() {
             Some(spawner) => spawner.stop(session_id).await,
@@ -1546,12 +2847,6 @@ impl SessionManager {
 
         if let Some(pc) = crash_pc {
             if let Ok(locals_info) = dwarf.parse_locals_at_pc(pc) {
-                let arch = if cfg!(target_arch = "aarch64") {
-                    "arm64"
-                } else {
-                    "x64"
-                };
-
                 // Extract frame_memory and frame_base from the crash event's text field
                 // (stored by parse_event as JSON with frameMemory/frameBase keys)
                 let (frame_memory, frame_base) = event
@@ -1576,7 +2871,7 @@ impl SessionManager {
                     event.registers.as_ref().unwrap_or(&serde_json::Value::Null),
                     frame_memory.as_deref(),
                     frame_base.as_deref(),
-                    arch,
+                    FRAME_ARCH,
                 );
                 if !locals.is_empty() {
                     self.db
@@ -1589,12 +2884,55 @@ impl SessionManager {
 
     // ========== Phase 2: Active debugging (async API) ==========
 
-    /// Set a breakpoint at a function or source line
+    /// Every `watch:<label>` in a breakpoint or logpoint condition must name
+    /// one of the session's watches; the agent reads them each time the
+    /// condition runs.
+    fn check_condition_watches(
+        &self,
+        session_id: &str,
+        lang: Language,
+        condition: Option<&str>,
+    ) -> Result<()> {
+        let labels = condition.map(condition_watch_labels).unwrap_or_default();
+        if labels.is_empty() {
+            return Ok(());
+        }
+        if lang != Language::Native {
+            return Err(crate::Error::ValidationError(format!(
+                "watch: conditions need a native session, not {}",
+                lang
+            )));
+        }
+        let watches = self.get_watches(session_id);
+        for label in labels {
+            if !watches.iter().any(|w| w.label == label) {
+                let known: Vec<&str> = watches.iter().map(|w| w.label.as_str()).collect();
+                return Err(crate::Error::ValidationError(if known.is_empty() {
+                    format!(
+                        "Condition reads watch '{}', but the session has no watches; add it with debug_trace first",
+                        label
+                    )
+                } else {
+                    format!(
+                        "Condition reads watch '{}', which isn't set. Watches: {}",
+                        label,
+                        known.join(", ")
+                    )
+                }));
+            }
+        }
+        Ok(())
+    }
+
+    /// Set a breakpoint at a function (or `offset` bytes into it), a source
+    /// line, or a static address
     pub async fn set_breakpoint_async(
         &self,
         session_id: &str,
         id: Option<String>,
         function: Option<String>,
+        offset: Option<u64>,
+        address: Option<u64>,
         file: Option<String>,
         line: Option<u32>,
         condition: Option<String>,
@@ -1611,7 +2949,19 @@ impl SessionManager {
             .get(session_id)
             .copied()
             .unwrap_or(Language::Native);
+        if lang == Language::Java {
+            return Err(crate::Error::ValidationError(
+                "Breakpoints and logpoints are not supported for Java sessions. Use debug_trace to record calls with their arguments".to_string(),
+            ));
+        }
+        self.check_condition_watches(session_id, lang, condition.as_deref())?;
         if lang == Language::Python || lang == Language::JavaScript {
+            if offset.is_some() || address.is_some() {
+                return Err(crate::Error::ValidationError(format!(
+                    "Address and offset breakpoints need native code; use 'function' or 'file'+'line' in {} sessions",
+                    lang
+                )));
+            }
             return self
                 .set_interpreted_breakpoint(
                     session_id, id, function, file, line, condition, hit_count,
@@ -1629,10 +2979,24 @@ impl SessionManager {
 
         // Save function name for later use (before it's moved into the match)
         let function_name_for_target = function.clone();
+        let address_target = address;
 
         // Resolve target to address
         let (address, resolved_function, resolved_file, resolved_line) =
-            if let Some(func_pattern) = function {
+            if let Some(address) = address {
+                // Raw address: named after whatever DWARF knows about it, if anything
+                let func = dwarf
+                    .functions
+                    .iter()
+                    .find(|f| address >= f.low_pc && address < f.high_pc);
+                let location = dwarf.resolve_address(address);
+                (
+                    address,
+                    func.map(|f| f.name.clone()),
+                    location.as_ref().map(|(file, _, _)| file.clone()),
+                    location.map(|(_, line, _)| line),
+                )
+            } else if let Some(func_pattern) = function {
                 // Function breakpoint: resolve via DWARF function table
                 let matches = dwarf.find_by_pattern(&func_pattern);
                 if matches.is_empty() {
@@ -1642,12 +3006,31 @@ impl SessionManager {
                     )));
                 }
                 let func = &matches[0];
-                (
-                    func.low_pc,
-                    Some(func.name.clone()),
-                    func.source_file.clone(),
-                    func.line_number.map(|l| l as u32),
-                )
+                match offset.filter(|&o| o > 0) {
+                    Some(offset) => {
+                        if func.high_pc > func.low_pc && offset >= func.high_pc - func.low_pc {
+                            return Err(crate::Error::ValidationError(format!(
+                                "Offset 0x{:x} is past the end of '{}' ({} bytes)",
+                                offset,
+                                func.name,
+                                func.high_pc - func.low_pc
+                            )));
+                        }
+                        let location = dwarf.resolve_address(func.low_pc + offset);
+                        (
+                            func.low_pc + offset,
+                            Some(func.name.clone()),
+                            location.as_ref().map(|(file, _, _)| file.clone()),
+                            location.map(|(_, line, _)| line),
+                        )
+                    }
+                    None => (
+                        func.low_pc,
+                        Some(func.name.clone()),
+                        func.source_file.clone(),
+                        func.line_number.map(|l| l as u32),
+                    ),
+                }
             } else if let (Some(file_path), Some(line_num)) = (file, line) {
                 // Line breakpoint: resolve via DWARF line table
                 let result = dwarf.resolve_line(&file_path, line_num).ok_or_else(|| {
@@ -1661,7 +3044,7 @@ impl SessionManager {
                 (result.0, None, Some(file_path), Some(result.1))
             } else {
                 return Err(crate::Error::ValidationError(
-                    "Breakpoint must specify either function or file+line".to_string(),
+                    "Breakpoint must specify either function, file+line or address".to_string(),
                 ));
             };
 
@@ -1688,15 +3071,19 @@ impl SessionManager {
         spawner.set_breakpoint(session_id, message).await?;
 
         // Store breakpoint in session state
+        let offset = offset.filter(|&o| o > 0);
         let bp = Breakpoint {
             id: breakpoint_id.clone(),
-            target: if let Some(f) = function_name_for_target {
-                BreakpointTarget::Function(f)
-            } else {
-                BreakpointTarget::Line {
+            target: match (address_target, function_name_for_target, offset) {
+                (Some(address), _, _) => BreakpointTarget::Address(address),
+                (None, Some(function), Some(offset)) => {
+                    BreakpointTarget::Offset { function, offset }
+                }
+                (None, Some(f), None) => BreakpointTarget::Function(f),
+                (None, None, _) => BreakpointTarget::Line {
                     file: resolved_file.clone().unwrap(),
                     line: resolved_line.unwrap(),
-                }
+                },
             },
             address: runtime_address,
             condition,
@@ -1709,6 +3096,7 @@ impl SessionManager {
         Ok(crate::mcp::BreakpointInfo {
             id: breakpoint_id,
             function: resolved_function,
+            offset,
             file: resolved_file,
             line: resolved_line,
             address: format!("0x{:x}", runtime_address),
@@ -1813,21 +3201,170 @@ impl SessionManager {
         Ok(crate::mcp::BreakpointInfo {
             id: breakpoint_id,
             function: func_name,
+            offset: None,
             file: Some(resolved_file),
             line: Some(resolved_line),
             address: "interpreted".to_string(),
         })
     }
 
-    /// Continue execution after a breakpoint pause
+    /// Continue execution after a breakpoint pause. With `until`, a
+    /// temporary breakpoint is set at the target first; the next
+    /// debug_continue removes it whether or not it was hit.
     pub async fn debug_continue_async(
         &self,
         session_id: &str,
         action: Option<String>,
+        until: Option<crate::mcp::ContinueUntil>,
+    ) -> Result<crate::mcp::DebugContinueResponse> {
+        let stale: Vec<String> = self
+            .get_breakpoints(session_id)
+            .into_iter()
+            .map(|bp| bp.id)
+            .filter(|id| id.starts_with(UNTIL_BREAKPOINT_PREFIX))
+            .collect();
+        let until_bp = match until {
+            Some(target) => Some(
+                self.set_breakpoint_async(
+                    session_id,
+                    Some(format!(
+                        "{}{}",
+                        UNTIL_BREAKPOINT_PREFIX,
+                        uuid::Uuid::new_v4()
+                    )),
+                    target.function,
+                    None,
+                    None,
+                    target.file,
+                    target.line,
+                    None,
+                    None,
+                )
+                .await?,
+            ),
+            None => None,
+        };
+
+        let result = self.resume_paused_async(session_id, action).await;
+
+        // Removed once the threads run, so a thread paused at one can step away from it
+        for id in &stale {
+            self.remove_breakpoint(session_id, id).await;
+        }
+        if result.is_err() {
+            if let Some(bp) = &until_bp {
+                self.remove_breakpoint(session_id, &bp.id).await;
+            }
+        }
+        let mut response = result?;
+        response.until = until_bp;
+        Ok(response)
+    }
+
+    /// Repeat a step action `count` times, waiting for each step to pause.
+    /// Stops early when a thread pauses at a breakpoint instead, or when a
+    /// step doesn't pause within STEP_PAUSE_TIMEOUT (its hooks stay armed).
+    pub async fn debug_step_repeat_async(
+        &self,
+        session_id: &str,
+        action: String,
+        count: u32,
+    ) -> Result<crate::mcp::DebugContinueResponse> {
+        let mut response = None;
+        for step in 1..=count {
+            let mut resumed = self
+                .debug_continue_async(session_id, Some(action.clone()), None)
+                .await?;
+            resumed.steps = Some(step - 1);
+            let Some(pause) = self.wait_for_pause(session_id, STEP_PAUSE_TIMEOUT).await else {
+                return Ok(resumed);
+            };
+            let at_breakpoint = !pause.breakpoint_id.starts_with(STEP_BREAKPOINT_PREFIX);
+            response = Some(self.paused_response(session_id, pause, step).await);
+            if at_breakpoint {
+                break;
+            }
+        }
+        response
+            .ok_or_else(|| crate::Error::ValidationError("count must be at least 1".to_string()))
+    }
+
+    /// The first thread to pause within `timeout`.
+    async fn wait_for_pause(
+        &self,
+        session_id: &str,
+        timeout: std::time::Duration,
+    ) -> Option<PauseInfo> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            if let Some(info) = self
+                .get_all_paused_threads(session_id)
+                .into_values()
+                .min_by_key(|info| info.paused_at)
+            {
+                return Some(info);
+            }
+            if Instant::now() >= deadline {
+                return None;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        }
+    }
+
+    /// Response for a thread paused after `steps` steps. Step pauses carry
+    /// only their DWARF address, so the line is looked up here.
+    async fn paused_response(
+        &self,
+        session_id: &str,
+        pause: PauseInfo,
+        steps: u32,
+    ) -> crate::mcp::DebugContinueResponse {
+        let (mut file, mut line) = (pause.file, pause.line);
+        if let (None, Some(address)) = (&file, pause.address) {
+            if let Ok(Some(session)) = self.db.get_session(session_id) {
+                let mut dwarf_handle = self
+                    .get_or_start_dwarf_parse(&session.binary_path, Some(&session.project_root));
+                if let Ok(dwarf) = dwarf_handle.get().await {
+                    if let Some((f, l, _)) = dwarf.resolve_address(address) {
+                        file = Some(f);
+                        line = Some(l);
+                    }
+                }
+            }
+        }
+        crate::mcp::DebugContinueResponse {
+            status: "paused".to_string(),
+            breakpoint_id: Some(pause.breakpoint_id),
+            file,
+            line,
+            function: pause.func_name,
+            until: None,
+            steps: Some(steps),
+        }
+    }
+
+    async fn resume_paused_async(
+        &self,
+        session_id: &str,
+        action: Option<String>,
     ) -> Result<crate::mcp::DebugContinueResponse> {
+        // Processes frozen by a `pause` output trigger resume first
+        let resumed = self.resume_trigger_stopped(session_id);
+
         // Get all paused threads for this session
         let paused = self.get_all_paused_threads(session_id);
 
+        if paused.is_empty() && !resumed.is_empty() {
+            return Ok(crate::mcp::DebugContinueResponse {
+                status: "running".to_string(),
+                breakpoint_id: None,
+                file: None,
+                line: None,
+                function: None,
+                until: None,
+                steps: None,
+            });
+        }
         if paused.is_empty() {
             return Err(crate::Error::ValidationError(
                 "No paused threads in this session".to_string(),
@@ -1864,6 +3401,8 @@ impl SessionManager {
                 file: None,
                 line: None,
                 function: None,
+                until: None,
+                steps: None,
             });
         }
 
@@ -1890,6 +3429,8 @@ impl SessionManager {
                 file: None,
                 line: None,
                 function: None,
+                until: None,
+                steps: None,
             });
         }
 
@@ -2045,6 +3586,8 @@ impl SessionManager {
             file: None,
             line: None,
             function: None,
+            until: None,
+            steps: None,
         })
     }
 
@@ -2058,6 +3601,7 @@ impl SessionManager {
         line: Option<u32>,
         message: String,
         condition: Option<String>,
+        hits: LogpointHits,
     ) -> Result<crate::mcp::LogpointInfo> {
         let session = self
             .db
@@ -2069,7 +3613,19 @@ impl SessionManager {
             .get(session_id)
             .copied()
             .unwrap_or(Language::Native);
+        if lang == Language::Java {
+            return Err(crate::Error::ValidationError(
+                "Breakpoints and logpoints are not supported for Java sessions. Use debug_trace to record calls with their arguments".to_string(),
+            ));
+        }
+        self.check_condition_watches(session_id, lang, condition.as_deref())?;
         if lang == Language::Python || lang == Language::JavaScript {
+            if hits != LogpointHits::default() {
+                return Err(crate::Error::ValidationError(format!(
+                    "hitCount, everyN and maxHits on logpoints need native code; not supported in {} sessions",
+                    lang
+                )));
+            }
             return self
                 .set_interpreted_logpoint(session_id, id, function, file, line, message, condition)
                 .await;
@@ -2116,6 +3672,42 @@ impl SessionManager {
 
         let runtime_address = address;
 
+        // Placeholders naming a parameter of the function are read at entry;
+        // any other `{word}` stays literal text
+        let named = template_placeholders(&message);
+        let declared = match function_name_for_target {
+            Some(_) if !named.is_empty() => dwarf.parse_params(address)?,
+            _ => Vec::new(),
+        };
+        let convention = crate::dwarf::CallingConvention::native();
+        let mut params = Vec::new();
+        for name in named {
+            let Some(path) = crate::dwarf::resolve_param_path(&declared, name, convention) else {
+                continue;
+            };
+            let path = path?;
+            let crate::dwarf::LocalRoot::Argument(arg) = path.root else {
+                return Err(crate::Error::Internal(
+                    "Parameter path without an argument register".to_string(),
+                ));
+            };
+            let ops: Vec<serde_json::Value> = path
+                .ops
+                .iter()
+                .map(|op| match op {
+                    crate::dwarf::PathOp::Deref => serde_json::json!("deref"),
+                    crate::dwarf::PathOp::Offset(offset) => serde_json::json!(offset),
+                })
+                .collect();
+            params.push(serde_json::json!({
+                "name": name,
+                "arg": arg,
+                "ops": ops,
+                "size": path.size,
+                "typeKind": type_kind_to_agent_str(&path.type_kind),
+            }));
+        }
+
         // Send setLogpoint message to agent
         let spawner_guard = self.frida_spawner.read().await;
         let spawner = spawner_guard
@@ -2128,6 +3720,10 @@ impl SessionManager {
             "id": logpoint_id,
             "message": message,
             "condition": condition,
+            "hitCount": hits.hit_count,
+            "everyN": hits.every_n,
+            "maxHits": hits.max_hits,
+            "params": params,
             "funcName": resolved_function,
             "file": resolved_file,
             "line": resolved_line,
@@ -2150,6 +3746,7 @@ impl SessionManager {
             address: runtime_address,
             message: message.clone(),
             condition,
+            hits,
         };
 
         self.add_logpoint(session_id, lp)?;
@@ -2251,6 +3848,7 @@ impl SessionManager {
             address: 0,
             message: message.clone(),
             condition,
+            hits: LogpointHits::default(),
         };
 
         self.add_logpoint(session_id, lp)?;
@@ -2272,7 +3870,9 @@ impl SessionManager {
         let session_bps = guard
             .entry(session_id.to_string())
             .or_insert_with(HashMap::new);
-        if session_bps.len() >= crate::mcp::MAX_BREAKPOINTS_PER_SESSION {
+        if session_bps.len() >= crate::mcp::MAX_BREAKPOINTS_PER_SESSION
+            && !session_bps.contains_key(&breakpoint.id)
+        {
             return Err(crate::Error::ValidationError(format!(
                 "Session has {} breakpoints (max {})",
                 session_bps.len(),
@@ -2358,7 +3958,12 @@ impl SessionManager {
             .map(|bp| crate::mcp::BreakpointInfo {
                 id: bp.id,
                 function: match &bp.target {
-                    BreakpointTarget::Function(f) => Some(f.clone()),
+                    BreakpointTarget::Function(f)
+                    | BreakpointTarget::Offset { function: f, .. } => Some(f.clone()),
+                    _ => None,
+                },
+                offset: match &bp.target {
+                    BreakpointTarget::Offset { offset, .. } => Some(*offset),
                     _ => None,
                 },
                 file: match &bp.target {
@@ -2410,6 +4015,7 @@ impl SessionManager {
                 size: w.size,
                 type_name: w.type_name,
                 on: w.on_patterns,
+                delta_threshold: w.delta_threshold,
             })
             .collect();
 
@@ -2432,7 +4038,9 @@ impl SessionManager {
         // Determine status (with crash detection for dead processes)
         let (status, crash_info) = if !paused_threads.is_empty() {
             ("paused".to_string(), None)
-        } else if is_process_alive(session.pid) {
+        } else if session.status == SessionStatus::Interrupted {
+            ("interrupted".to_string(), None)
+        } else if crate::process::is_alive(session.pid) {
             ("running".to_string(), None)
         } else {
             // Check if the process crashed
@@ -2474,93 +4082,1846 @@ impl SessionManager {
                     exception_message: crash.exception_message.clone(),
                     top_frame,
                     throw_top_frame,
+                    dump_path: crash.dump_path.clone(),
+                    history: self.db().crash_history(crash),
                 };
                 ("crashed".to_string(), Some(summary))
             } else {
-                ("exited".to_string(), None)
-            }
-        };
+                ("exited".to_string(), None)
+            }
+        };
+
+        let capabilities = self.get_capabilities(session_id);
+
+        Ok(crate::mcp::SessionStatusResponse {
+            status,
+            pid: session.pid,
+            event_count,
+            hooked_functions,
+            downsampled_until_ns: self.db.downsampled_until(session_id)?,
+            trace_patterns,
+            counted_patterns: self.get_counted_patterns(session_id),
+            rt_patterns: self.get_rt_patterns(session_id),
+            call_counts: self.latest_call_counts(session_id)?,
+            sampling: self.get_sampling(session_id),
+            threads: self.get_thread_filter(session_id),
+            rate_limit: self.get_rate_limit(session_id),
+            flight_recorder: self.get_flight_recorder(session_id),
+            signal_safety: self.get_signal_safety(session_id),
+            syscalls: self.get_syscall_groups(session_id),
+            network: self.is_network_tracing(session_id),
+            exceptions: self.is_exception_tracing(session_id),
+            signals: self.is_signal_tracing(session_id),
+            locks: self.is_lock_tracing(session_id),
+            lock_contention: self
+                .db
+                .lock_contention(session_id, crate::db::MAX_LOCK_SITES)?,
+            symbol_modules: self.symbol_module_status(session_id),
+            breakpoints,
+            logpoints,
+            watches,
+            paused_threads,
+            crash_info,
+            capabilities,
+            writer: self.writer_status(session_id),
+        })
+    }
+
+    /// Top count-mode totals from each process's most recent call_counts
+    /// event. The agent sends running totals, so older events add nothing.
+    fn latest_call_counts(&self, session_id: &str) -> Result<Vec<crate::mcp::CallCount>> {
+        let events = self.db.query_events(session_id, |q| {
+            q.event_type(crate::db::EventType::CallCounts).limit(256)
+        })?;
+
+        // Newest first: keep the first event seen per process
+        let mut seen = HashSet::new();
+        let latest: Vec<_> = events.into_iter().filter(|e| seen.insert(e.pid)).collect();
+        let multi_process = latest.len() > 1;
+
+        let mut counts: Vec<crate::mcp::CallCount> = latest
+            .iter()
+            .flat_map(|e| {
+                e.arguments
+                    .as_ref()
+                    .and_then(|a| a.as_array())
+                    .into_iter()
+                    .flatten()
+                    .filter_map(move |c| {
+                        Some(crate::mcp::CallCount {
+                            function: c.get("function")?.as_str()?.to_string(),
+                            count: c.get("count")?.as_u64()?,
+                            pid: if multi_process { e.pid } else { None },
+                        })
+                    })
+            })
+            .collect();
+        counts.sort_by(|a, b| {
+            b.count
+                .cmp(&a.count)
+                .then_with(|| a.function.cmp(&b.function))
+        });
+        counts.truncate(MAX_STATUS_CALL_COUNTS);
+        Ok(counts)
+    }
+
+    pub fn get_breakpoints(&self, session_id: &str) -> Vec<Breakpoint> {
+        let guard = read_lock(&self.breakpoints);
+        guard
+            .get(session_id)
+            .map(|bps| bps.values().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    pub fn get_breakpoint(&self, session_id: &str, breakpoint_id: &str) -> Option<Breakpoint> {
+        let guard = read_lock(&self.breakpoints);
+        guard
+            .get(session_id)
+            .and_then(|bps| bps.get(breakpoint_id))
+            .cloned()
+    }
+
+    // Logpoint management
+    pub fn add_logpoint(&self, session_id: &str, logpoint: Logpoint) -> Result<()> {
+        let mut guard = write_lock(&self.logpoints);
+        let session_lps = guard
+            .entry(session_id.to_string())
+            .or_insert_with(HashMap::new);
+        if session_lps.len() >= crate::mcp::MAX_LOGPOINTS_PER_SESSION
+            && !session_lps.contains_key(&logpoint.id)
+        {
+            return Err(crate::Error::ValidationError(format!(
+                "Session has {} logpoints (max {})",
+                session_lps.len(),
+                crate::mcp::MAX_LOGPOINTS_PER_SESSION
+            )));
+        }
+        session_lps.insert(logpoint.id.clone(), logpoint);
+        Ok(())
+    }
+
+    pub async fn remove_logpoint(&self, session_id: &str, logpoint_id: &str) {
+        // Send removal to agent via spawner pipeline (best-effort)
+        let send_result = async {
+            let spawner_guard = self.frida_spawner.read().await;
+            if let Some(spawner) = spawner_guard.as_ref() {
+                spawner.remove_logpoint(session_id, logpoint_id).await
+            } else {
+                Ok(())
+            }
+        }
+        .await;
+
+        if let Err(e) = send_result {
+            tracing::warn!(
+                "Failed to send logpoint removal to agent: {} (cleaning up state anyway)",
+                e
+            );
+        }
+
+        let mut guard = write_lock(&self.logpoints);
+        if let Some(session_lps) = guard.get_mut(session_id) {
+            session_lps.remove(logpoint_id);
+        }
+    }
+
+    pub fn get_logpoints(&self, session_id: &str) -> Vec<Logpoint> {
+        let guard = read_lock(&self.logpoints);
+        guard
+            .get(session_id)
+            .map(|lps| lps.values().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// Snapshot the session's active instrumentation for export. Watches and
+    /// watchpoints on raw runtime addresses cannot be replayed in a new process;
+    /// they are left out and described in the returned list.
+    pub fn instrumentation_snapshot(
+        &self,
+        session_id: &str,
+    ) -> Result<(crate::mcp::InstrumentationSnapshot, Vec<String>)> {
+        let session = self
+            .db
+            .get_session(session_id)?
+            .ok_or_else(|| crate::Error::SessionNotFound(session_id.to_string()))?;
+        let mut omitted = Vec::new();
+
+        let mut patterns = self.get_patterns(session_id);
+        patterns.sort();
+        let mut counted_patterns = self.get_counted_patterns(session_id);
+        counted_patterns.sort();
+        let mut rt_patterns = self.get_rt_patterns(session_id);
+        rt_patterns.sort();
+
+        let mut watches = Vec::new();
+        for w in self.get_watches(session_id) {
+            match w.variable {
+                Some(variable) => watches.push(crate::mcp::WatchTarget {
+                    variable: Some(variable),
+                    address: None,
+                    type_hint: None,
+                    label: Some(w.label),
+                    expr: None,
+                    on: w.on_patterns,
+                    delta_threshold: w.delta_threshold,
+                }),
+                None => omitted.push(format!(
+                    "watch '{}' (raw address 0x{:x})",
+                    w.label, w.address
+                )),
+            }
+        }
+
+        let mut watchpoints = Vec::new();
+        for wp in self.get_watchpoints(session_id) {
+            match wp.variable {
+                Some(variable) => watchpoints.push(crate::mcp::WatchpointTarget {
+                    variable: Some(variable),
+                    address: None,
+                    size: Some(wp.size),
+                    label: Some(wp.label),
+                }),
+                None => omitted.push(format!(
+                    "watchpoint '{}' (raw address 0x{:x})",
+                    wp.label, wp.address
+                )),
+            }
+        }
+
+        let target_parts = |target: BreakpointTarget| match target {
+            BreakpointTarget::Function(f) => (Some(f), None, None, None, None),
+            BreakpointTarget::Line { file, line } => (None, None, None, Some(file), Some(line)),
+            BreakpointTarget::Offset { function, offset } => {
+                (Some(function), Some(offset), None, None, None)
+            }
+            BreakpointTarget::Address(address) => (None, None, Some(address), None, None),
+        };
+
+        let mut breakpoints: Vec<crate::mcp::BreakpointTarget> = self
+            .get_breakpoints(session_id)
+            .into_iter()
+            .map(|bp| {
+                let (function, offset, address, file, line) = target_parts(bp.target);
+                crate::mcp::BreakpointTarget {
+                    function,
+                    offset,
+                    address: address.map(|a| format!("0x{:x}", a)),
+                    file,
+                    line,
+                    condition: bp.condition,
+                    hit_count: (bp.hit_count > 0).then_some(bp.hit_count),
+                    message: None,
+                }
+            })
+            .collect();
+        breakpoints
+            .sort_by(|a, b| (&a.function, &a.file, a.line).cmp(&(&b.function, &b.file, b.line)));
+
+        let mut logpoints: Vec<crate::mcp::LogpointTarget> = self
+            .get_logpoints(session_id)
+            .into_iter()
+            .map(|lp| {
+                let (function, _, _, file, line) = target_parts(lp.target);
+                let nonzero = |n: u32| (n > 0).then_some(n);
+                crate::mcp::LogpointTarget {
+                    message: lp.message,
+                    function,
+                    file,
+                    line,
+                    condition: lp.condition,
+                    hit_count: nonzero(lp.hits.hit_count),
+                    every_n: nonzero(lp.hits.every_n),
+                    max_hits: nonzero(lp.hits.max_hits),
+                }
+            })
+            .collect();
+        logpoints
+            .sort_by(|a, b| (&a.function, &a.file, a.line).cmp(&(&b.function, &b.file, b.line)));
+
+        let event_limit = self.get_event_limit(session_id);
+        let default_limit = crate::config::StrobeSettings::default().events_max_per_session;
+        let settings = crate::mcp::InstrumentationSettings {
+            event_limit: (event_limit != default_limit).then_some(event_limit),
+            serialization_depth: read_lock(&self.serialization_depths)
+                .get(session_id)
+                .copied(),
+        };
+
+        Ok((
+            crate::mcp::InstrumentationSnapshot {
+                version: crate::mcp::INSTRUMENTATION_SNAPSHOT_VERSION,
+                session_id: session_id.to_string(),
+                binary_path: session.binary_path,
+                exported_at: Utc::now().to_rfc3339(),
+                patterns,
+                counted_patterns,
+                rt_patterns,
+                watches,
+                watchpoints,
+                breakpoints,
+                logpoints,
+                settings,
+            },
+            omitted,
+        ))
+    }
+
+    /// Swap the session's agent script for `source` (the built-in agent when
+    /// None) and re-install the session's instrumentation on the new script.
+    /// Items that fail to re-apply come back as warnings.
+    pub async fn reload_agent(
+        &self,
+        session_id: &str,
+        agent: &str,
+        source: Option<String>,
+    ) -> Result<crate::mcp::ReloadAgentResponse> {
+        self.require_instrumented(session_id)?;
+        let session = self
+            .db
+            .get_session(session_id)?
+            .ok_or_else(|| crate::Error::SessionNotFound(session_id.to_string()))?;
+        if session.status != crate::db::SessionStatus::Running {
+            return Err(crate::Error::ValidationError(format!(
+                "Session '{}' is not running",
+                session_id
+            )));
+        }
+        // Paused threads wait inside the old script; unloading it would
+        // release them without their breakpoint state.
+        if !self.get_all_paused_threads(session_id).is_empty() {
+            return Err(crate::Error::ValidationError(
+                "Session has paused threads. Resume them with debug_continue before reloading the agent"
+                    .to_string(),
+            ));
+        }
+
+        let started = std::time::Instant::now();
+        {
+            let guard = self.frida_spawner.read().await;
+            let spawner = guard.as_ref().ok_or_else(|| {
+                crate::Error::Internal("Frida spawner not initialized".to_string())
+            })?;
+            spawner.reload_agent(session_id, source).await?;
+        }
+        let reload_ms = started.elapsed().as_millis() as u64;
+
+        let started = std::time::Instant::now();
+        let mut warnings = Vec::new();
+
+        // The recorded allocations went with the old agent
+        if write_lock(&self.heap_tracking).remove(session_id) {
+            warnings.push(
+                "Heap tracking stopped with the old agent; start it again with debug_heap"
+                    .to_string(),
+            );
+        }
+
+        // Before the hooks, so filtered threads never flood the new agent
+        if let Some(filter) = self.get_thread_filter(session_id) {
+            if let Err(e) = self.send_thread_filter(session_id, &filter).await {
+                warnings.push(format!("Thread filter not re-applied: {}", e));
+            }
+        }
+        if let Some(limit) = self.get_rate_limit(session_id) {
+            if let Err(e) = self.send_rate_limit(session_id, &limit).await {
+                warnings.push(format!("Rate limit not re-applied: {}", e));
+            }
+        }
+        if let Some(recorder) = self.get_flight_recorder(session_id) {
+            if let Err(e) = self.send_flight_recorder(session_id, &recorder).await {
+                warnings.push(format!("Flight recorder not re-applied: {}", e));
+            }
+        }
+        if let Some(audit) = self.get_signal_safety(session_id) {
+            if let Err(e) = self.send_signal_safety(session_id, &audit).await {
+                warnings.push(format!("Signal safety audit not re-applied: {}", e));
+            }
+        }
+        let syscalls = self.get_syscall_groups(session_id);
+        if !syscalls.is_empty() {
+            if let Err(e) = self.send_syscall_groups(session_id, &syscalls).await {
+                warnings.push(format!("Syscall tracing not re-applied: {}", e));
+            }
+        }
+        if self.is_network_tracing(session_id) {
+            if let Err(e) = self.send_network_tracing(session_id, true).await {
+                warnings.push(format!("Network tracing not re-applied: {}", e));
+            }
+        }
+        if self.is_exception_tracing(session_id) {
+            if let Err(e) = self.send_exception_tracing(session_id, true).await {
+                warnings.push(format!("Exception tracing not re-applied: {}", e));
+            }
+        }
+        if self.is_signal_tracing(session_id) {
+            if let Err(e) = self.send_signal_tracing(session_id, true).await {
+                warnings.push(format!("Signal tracing not re-applied: {}", e));
+            }
+        }
+        if self.is_lock_tracing(session_id) {
+            if let Err(e) = self.send_lock_tracing(session_id, true).await {
+                warnings.push(format!("Lock tracing not re-applied: {}", e));
+            }
+        }
+        if let Some(capture) = self.get_crash_capture(session_id) {
+            if let Err(e) = self.set_crash_capture_async(session_id, &capture).await {
+                warnings.push(format!("Crash capture not re-applied: {}", e));
+            }
+        }
+
+        let patterns = self.get_patterns(session_id);
+        let mut hooked_functions = 0;
+        if !patterns.is_empty() {
+            let depth = read_lock(&self.serialization_depths)
+                .get(session_id)
+                .copied();
+            match self
+                .update_frida_patterns(session_id, Some(&patterns), None, depth)
+                .await
+            {
+                Ok(result) => {
+                    hooked_functions = result.installed;
+                    self.set_hook_count(session_id, result.installed);
+                    warnings.extend(result.warnings);
+                }
+                Err(e) => warnings.push(format!("Patterns not re-applied: {}", e)),
+            }
+        }
+
+        let watches = self.get_watches(session_id);
+        if !watches.is_empty() {
+            let mut frida_watches = Vec::new();
+            let mut expr_watches = Vec::new();
+            for w in &watches {
+                match (w.is_expr, &w.expr) {
+                    (true, Some(expr)) => {
+                        expr_watches.push(crate::frida_collector::ExprWatchTarget {
+                            label: w.label.clone(),
+                            expr: expr.clone(),
+                            is_global: w.on_patterns.as_ref().is_none_or(|p| p.is_empty()),
+                            on_patterns: w.on_patterns.clone(),
+                            delta_threshold: w.delta_threshold,
+                        })
+                    }
+                    _ => frida_watches.push(crate::frida_collector::WatchTarget {
+                        label: w.label.clone(),
+                        address: w.address,
+                        size: w.size,
+                        type_kind_str: w.type_kind_str.clone(),
+                        deref_depth: w.deref_depth,
+                        deref_offset: w.deref_offset,
+                        type_name: w.type_name.clone(),
+                        on_patterns: w.on_patterns.clone(),
+                        no_slide: w.no_slide,
+                        delta_threshold: w.delta_threshold,
+                        module: w.module.clone(),
+                    }),
+                }
+            }
+            if let Err(e) = self
+                .update_frida_watches(session_id, frida_watches, expr_watches)
+                .await
+            {
+                warnings.push(format!("Watches not re-applied: {}", e));
+            }
+        }
+
+        let watchpoints = self.get_watchpoints(session_id).len();
+        if watchpoints > 0 {
+            if let Err(e) = self
+                .update_watchpoints_async(session_id, vec![], vec![])
+                .await
+            {
+                warnings.push(format!("Watchpoints not re-applied: {}", e));
+            }
+        }
+
+        if let Some(sampling) = self.get_sampling(session_id) {
+            if let Err(e) = self.send_sampling(session_id, &sampling).await {
+                warnings.push(format!("Sampling not re-applied: {}", e));
+            }
+        }
+
+        let target_parts = |target: BreakpointTarget| match target {
+            BreakpointTarget::Function(f) => (Some(f), None, None, None, None),
+            BreakpointTarget::Line { file, line } => (None, None, None, Some(file), Some(line)),
+            BreakpointTarget::Offset { function, offset } => {
+                (Some(function), Some(offset), None, None, None)
+            }
+            BreakpointTarget::Address(address) => (None, None, Some(address), None, None),
+        };
+
+        // Re-setting under the same id replaces the stored entry
+        let breakpoints = self.get_breakpoints(session_id);
+        for bp in &breakpoints {
+            let (function, offset, address, file, line) = target_parts(bp.target.clone());
+            if let Err(e) = self
+                .set_breakpoint_async(
+                    session_id,
+                    Some(bp.id.clone()),
+                    function,
+                    offset,
+                    address,
+                    file,
+                    line,
+                    bp.condition.clone(),
+                    Some(bp.hit_count),
+                )
+                .await
+            {
+                warnings.push(format!("Breakpoint {} not re-applied: {}", bp.id, e));
+            }
+        }
+
+        let logpoints = self.get_logpoints(session_id);
+        for lp in &logpoints {
+            let (function, _, _, file, line) = target_parts(lp.target.clone());
+            if let Err(e) = self
+                .set_logpoint_async(
+                    session_id,
+                    Some(lp.id.clone()),
+                    function,
+                    file,
+                    line,
+                    lp.message.clone(),
+                    lp.condition.clone(),
+                    lp.hits,
+                )
+                .await
+            {
+                warnings.push(format!("Logpoint {} not re-applied: {}", lp.id, e));
+            }
+        }
+
+        Ok(crate::mcp::ReloadAgentResponse {
+            session_id: session_id.to_string(),
+            agent: agent.to_string(),
+            reload_ms,
+            reapply_ms: started.elapsed().as_millis() as u64,
+            patterns: patterns.len(),
+            hooked_functions,
+            watches: watches.len(),
+            watchpoints,
+            breakpoints: breakpoints.len(),
+            logpoints: logpoints.len(),
+            warnings,
+        })
+    }
+
+    /// Add and/or remove data watchpoints, then push the full set to the agent.
+    /// Variables resolve to DWARF-static addresses (the agent applies the ASLR
+    /// slide); raw addresses are runtime addresses and are sent with noSlide.
+    pub async fn update_watchpoints_async(
+        &self,
+        session_id: &str,
+        add: Vec<crate::mcp::WatchpointTarget>,
+        remove: Vec<String>,
+    ) -> Result<Vec<crate::mcp::WatchpointInfo>> {
+        let session = self
+            .db
+            .get_session(session_id)?
+            .ok_or_else(|| crate::Error::SessionNotFound(session_id.to_string()))?;
+
+        let lang = read_lock(&self.languages)
+            .get(session_id)
+            .copied()
+            .unwrap_or(Language::Native);
+        if lang != Language::Native {
+            return Err(crate::Error::ValidationError(
+                "Watchpoints are only supported for native binaries".to_string(),
+            ));
+        }
+
+        let mut image_base = None;
+        let mut added = Vec::with_capacity(add.len());
+        for target in add {
+            let watchpoint = if let Some(variable) = target.variable {
+                let mut dwarf_handle = self
+                    .get_or_start_dwarf_parse(&session.binary_path, Some(&session.project_root));
+                let dwarf = dwarf_handle.get().await?;
+                let recipe = dwarf.resolve_watch_expression(&variable)?;
+                if !recipe.deref_chain.is_empty() {
+                    return Err(crate::Error::ValidationError(format!(
+                        "Cannot watch '{}': pointer dereferences resolve at runtime. \
+                         Read the pointer with debug_memory and watch the raw address instead.",
+                        variable
+                    )));
+                }
+                image_base = Some(dwarf.image_base);
+                Watchpoint {
+                    id: format!("wp-{}", uuid::Uuid::new_v4()),
+                    label: target.label.unwrap_or(recipe.label),
+                    address: recipe.base_address,
+                    size: target.size.unwrap_or(recipe.final_size as u32),
+                    no_slide: false,
+                    variable: Some(variable),
+                }
+            } else if let Some(address) = target.address {
+                let hex = address.trim_start_matches("0x").trim_start_matches("0X");
+                let parsed = u64::from_str_radix(hex, 16).map_err(|_| {
+                    crate::Error::ValidationError(format!(
+                        "Invalid watchpoint address '{}'",
+                        address
+                    ))
+                })?;
+                Watchpoint {
+                    id: format!("wp-{}", uuid::Uuid::new_v4()),
+                    label: target.label.unwrap_or(address),
+                    address: parsed,
+                    size: target.size.unwrap_or(8),
+                    no_slide: true,
+                    variable: None,
+                }
+            } else {
+                return Err(crate::Error::ValidationError(
+                    "Watchpoint must specify either variable or address".to_string(),
+                ));
+            };
+            added.push(watchpoint);
+        }
+
+        let mut next: Vec<Watchpoint> = self
+            .get_watchpoints(session_id)
+            .into_iter()
+            .filter(|wp| !remove.contains(&wp.id))
+            .collect();
+        next.extend(added);
+
+        if next.len() > crate::mcp::MAX_WATCHPOINTS_PER_SESSION {
+            return Err(crate::Error::ValidationError(format!(
+                "Too many watchpoints: {} (max {})",
+                next.len(),
+                crate::mcp::MAX_WATCHPOINTS_PER_SESSION
+            )));
+        }
+
+        // The agent needs the image base to slide DWARF addresses, also when
+        // the set is only re-sent (removal, agent reload).
+        if image_base.is_none() && next.iter().any(|wp| !wp.no_slide) {
+            let mut dwarf_handle =
+                self.get_or_start_dwarf_parse(&session.binary_path, Some(&session.project_root));
+            image_base = Some(dwarf_handle.get().await?.image_base);
+        }
+
+        let spawner_guard = self.frida_spawner.read().await;
+        let spawner = spawner_guard
+            .as_ref()
+            .ok_or_else(|| crate::Error::Internal("Frida spawner not initialized".to_string()))?;
+
+        let message = serde_json::json!({
+            "type": "setWatchpoints",
+            "imageBase": image_base.map(|b| format!("0x{:x}", b)),
+            "watchpoints": next.iter().map(|wp| serde_json::json!({
+                "id": wp.id,
+                "label": wp.label,
+                "address": format!("0x{:x}", wp.address),
+                "size": wp.size,
+                "noSlide": wp.no_slide,
+            })).collect::<Vec<_>>(),
+        });
+        spawner.send_hook_message(session_id, message).await?;
+
+        let infos = next.iter().map(Watchpoint::info).collect();
+        write_lock(&self.watchpoints).insert(session_id.to_string(), next);
+        Ok(infos)
+    }
+
+    /// Start, retarget or stop stack sampling. Omitted `hz` falls back to
+    /// `default_hz`; returns the configuration now active (None once stopped).
+    pub async fn set_sampling_async(
+        &self,
+        session_id: &str,
+        config: crate::mcp::SamplingConfig,
+        default_hz: u32,
+    ) -> Result<Option<crate::mcp::SamplingConfig>> {
+        let active = crate::mcp::SamplingConfig {
+            hz: Some(if config.enabled {
+                config.hz.unwrap_or(default_hz)
+            } else {
+                0
+            }),
+            ..config
+        };
+        self.send_sampling(session_id, &active).await?;
+
+        let mut guard = write_lock(&self.sampling);
+        if active.enabled {
+            guard.insert(session_id.to_string(), active.clone());
+            Ok(Some(active))
+        } else {
+            guard.remove(session_id);
+            Ok(None)
+        }
+    }
+
+    async fn send_sampling(
+        &self,
+        session_id: &str,
+        config: &crate::mcp::SamplingConfig,
+    ) -> Result<()> {
+        let spawner_guard = self.frida_spawner.read().await;
+        let spawner = spawner_guard
+            .as_ref()
+            .ok_or_else(|| crate::Error::Internal("Frida spawner not initialized".to_string()))?;
+
+        // hz 0 stops the agent's sampling timer
+        let message = serde_json::json!({
+            "type": "setSampling",
+            "hz": if config.enabled { config.hz.unwrap_or(0) } else { 0 },
+            "threadIds": config.thread_ids,
+            "threadName": config.thread_name,
+        });
+        spawner.send_hook_message(session_id, message).await
+    }
+
+    pub fn get_sampling(&self, session_id: &str) -> Option<crate::mcp::SamplingConfig> {
+        read_lock(&self.sampling).get(session_id).cloned()
+    }
+
+    /// Restrict traced calls to the filter's threads; an empty filter traces
+    /// every thread again. Returns the filter now active.
+    pub async fn set_thread_filter_async(
+        &self,
+        session_id: &str,
+        filter: crate::mcp::ThreadFilter,
+    ) -> Result<Option<crate::mcp::ThreadFilter>> {
+        self.send_thread_filter(session_id, &filter).await?;
+
+        let mut guard = write_lock(&self.thread_filters);
+        if filter.is_empty() {
+            guard.remove(session_id);
+            Ok(None)
+        } else {
+            guard.insert(session_id.to_string(), filter.clone());
+            Ok(Some(filter))
+        }
+    }
+
+    async fn send_thread_filter(
+        &self,
+        session_id: &str,
+        filter: &crate::mcp::ThreadFilter,
+    ) -> Result<()> {
+        let spawner_guard = self.frida_spawner.read().await;
+        let spawner = spawner_guard
+            .as_ref()
+            .ok_or_else(|| crate::Error::Internal("Frida spawner not initialized".to_string()))?;
+
+        let message = serde_json::json!({
+            "type": "setThreadFilter",
+            "nameContains": filter.name_contains,
+            "ids": filter.ids,
+        });
+        spawner.send_hook_message(session_id, message).await
+    }
+
+    pub fn get_thread_filter(&self, session_id: &str) -> Option<crate::mcp::ThreadFilter> {
+        read_lock(&self.thread_filters).get(session_id).cloned()
+    }
+
+    /// Limit recorded calls per traced function; an empty limit records
+    /// every call again. Returns the limit now active.
+    pub async fn set_rate_limit_async(
+        &self,
+        session_id: &str,
+        limit: crate::mcp::RateLimit,
+    ) -> Result<Option<crate::mcp::RateLimit>> {
+        self.send_rate_limit(session_id, &limit).await?;
+
+        let mut guard = write_lock(&self.rate_limits);
+        if limit.is_empty() {
+            guard.remove(session_id);
+            Ok(None)
+        } else {
+            guard.insert(session_id.to_string(), limit.clone());
+            Ok(Some(limit))
+        }
+    }
+
+    async fn send_rate_limit(&self, session_id: &str, limit: &crate::mcp::RateLimit) -> Result<()> {
+        let spawner_guard = self.frida_spawner.read().await;
+        let spawner = spawner_guard
+            .as_ref()
+            .ok_or_else(|| crate::Error::Internal("Frida spawner not initialized".to_string()))?;
+
+        let message = serde_json::json!({
+            "type": "setRateLimit",
+            "maxPerSecond": limit.max_per_second,
+            "sampleEvery": limit.sample_every,
+        });
+        spawner.send_hook_message(session_id, message).await
+    }
+
+    pub fn get_rate_limit(&self, session_id: &str) -> Option<crate::mcp::RateLimit> {
+        read_lock(&self.rate_limits).get(session_id).cloned()
+    }
+
+    /// Turn the agent's flight recorder on (or reconfigure it) or off.
+    /// Turning it off writes out what was buffered; returns how many events
+    /// that flushed.
+    pub async fn set_flight_recorder_async(
+        &self,
+        session_id: &str,
+        recorder: crate::mcp::FlightRecorderConfig,
+    ) -> Result<u64> {
+        if !recorder.enabled {
+            let flushed = if self.get_flight_recorder(session_id).is_some() {
+                self.flush_flight_recorder(session_id).await?
+            } else {
+                0
+            };
+            self.send_flight_recorder(session_id, &recorder).await?;
+            write_lock(&self.flight_recorders).remove(session_id);
+            return Ok(flushed);
+        }
+
+        let recorder = recorder.resolved();
+        self.send_flight_recorder(session_id, &recorder).await?;
+        write_lock(&self.flight_recorders).insert(session_id.to_string(), recorder);
+        Ok(0)
+    }
+
+    async fn send_flight_recorder(
+        &self,
+        session_id: &str,
+        recorder: &crate::mcp::FlightRecorderConfig,
+    ) -> Result<()> {
+        let spawner_guard = self.frida_spawner.read().await;
+        let spawner = spawner_guard
+            .as_ref()
+            .ok_or_else(|| crate::Error::Internal("Frida spawner not initialized".to_string()))?;
+
+        let message = serde_json::json!({
+            "type": "setFlightRecorder",
+            "enabled": recorder.enabled,
+            "capacity": recorder.capacity,
+            "flushOnOutput": recorder.flush_on_output,
+        });
+        spawner.send_hook_message(session_id, message).await
+    }
+
+    /// Send the flight recorder's buffered events to the database now.
+    /// Returns how many were flushed.
+    pub async fn flush_flight_recorder(&self, session_id: &str) -> Result<u64> {
+        if self.get_flight_recorder(session_id).is_none() {
+            return Err(crate::Error::ValidationError(
+                "No flight recorder is active in this session. Enable it with flightRecorder: { enabled: true }"
+                    .to_string(),
+            ));
+        }
+        let message = serde_json::json!({ "type": "flushFlightRecorder" });
+        let response = self
+            .send_read_memory(session_id, serde_json::to_string(&message)?)
+            .await?;
+        if let Some(err) = response.get("error").and_then(|v| v.as_str()) {
+            return Err(crate::Error::Frida(format!(
+                "Flight recorder flush failed: {}",
+                err
+            )));
+        }
+        Ok(response
+            .get("flushed")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(0))
+    }
+
+    pub fn get_flight_recorder(
+        &self,
+        session_id: &str,
+    ) -> Option<crate::mcp::FlightRecorderConfig> {
+        read_lock(&self.flight_recorders).get(session_id).cloned()
+    }
+
+    /// Start or stop the signal-handler safety audit. Returns the audit now
+    /// active (None once stopped).
+    pub async fn set_signal_safety_async(
+        &self,
+        session_id: &str,
+        audit: crate::mcp::SignalSafetyConfig,
+    ) -> Result<Option<crate::mcp::SignalSafetyConfig>> {
+        self.send_signal_safety(session_id, &audit).await?;
+
+        let mut guard = write_lock(&self.signal_safety);
+        if audit.enabled {
+            guard.insert(session_id.to_string(), audit.clone());
+            Ok(Some(audit))
+        } else {
+            guard.remove(session_id);
+            Ok(None)
+        }
+    }
+
+    async fn send_signal_safety(
+        &self,
+        session_id: &str,
+        audit: &crate::mcp::SignalSafetyConfig,
+    ) -> Result<()> {
+        let spawner_guard = self.frida_spawner.read().await;
+        let spawner = spawner_guard
+            .as_ref()
+            .ok_or_else(|| crate::Error::Internal("Frida spawner not initialized".to_string()))?;
+
+        let message = serde_json::json!({
+            "type": "setSignalSafety",
+            "enabled": audit.enabled,
+            "functions": audit.functions,
+        });
+        spawner.send_hook_message(session_id, message).await
+    }
+
+    pub fn get_signal_safety(&self, session_id: &str) -> Option<crate::mcp::SignalSafetyConfig> {
+        read_lock(&self.signal_safety).get(session_id).cloned()
+    }
+
+    /// Add and remove traced `@syscalls:` groups. The agent gets the whole
+    /// resulting set and hooks its libc calls. Returns the groups now traced.
+    pub async fn update_syscall_groups_async(
+        &self,
+        session_id: &str,
+        add: &[crate::mcp::SyscallGroup],
+        remove: &[crate::mcp::SyscallGroup],
+    ) -> Result<Vec<crate::mcp::SyscallGroup>> {
+        let mut groups: BTreeSet<_> = read_lock(&self.syscall_groups)
+            .get(session_id)
+            .cloned()
+            .unwrap_or_default();
+        groups.extend(add.iter().copied());
+        for group in remove {
+            groups.remove(group);
+        }
+        let groups: Vec<_> = groups.into_iter().collect();
+        self.send_syscall_groups(session_id, &groups).await?;
+
+        let mut guard = write_lock(&self.syscall_groups);
+        if groups.is_empty() {
+            guard.remove(session_id);
+        } else {
+            guard.insert(session_id.to_string(), groups.iter().copied().collect());
+        }
+        Ok(groups)
+    }
+
+    async fn send_syscall_groups(
+        &self,
+        session_id: &str,
+        groups: &[crate::mcp::SyscallGroup],
+    ) -> Result<()> {
+        let spawner_guard = self.frida_spawner.read().await;
+        let spawner = spawner_guard
+            .as_ref()
+            .ok_or_else(|| crate::Error::Internal("Frida spawner not initialized".to_string()))?;
+
+        let message = serde_json::json!({
+            "type": "setSyscalls",
+            "groups": groups,
+        });
+        spawner.send_hook_message(session_id, message).await
+    }
+
+    pub fn get_syscall_groups(&self, session_id: &str) -> Vec<crate::mcp::SyscallGroup> {
+        read_lock(&self.syscall_groups)
+            .get(session_id)
+            .map(|groups| groups.iter().copied().collect())
+            .unwrap_or_default()
+    }
+
+    /// Start or stop `@network` tracing (connects, DNS lookups, TLS traffic).
+    pub async fn set_network_tracing_async(&self, session_id: &str, enabled: bool) -> Result<()> {
+        self.send_network_tracing(session_id, enabled).await?;
+
+        let mut guard = write_lock(&self.network_tracing);
+        if enabled {
+            guard.insert(session_id.to_string());
+        } else {
+            guard.remove(session_id);
+        }
+        Ok(())
+    }
+
+    async fn send_network_tracing(&self, session_id: &str, enabled: bool) -> Result<()> {
+        let spawner_guard = self.frida_spawner.read().await;
+        let spawner = spawner_guard
+            .as_ref()
+            .ok_or_else(|| crate::Error::Internal("Frida spawner not initialized".to_string()))?;
+
+        let message = serde_json::json!({
+            "type": "setNetwork",
+            "enabled": enabled,
+        });
+        spawner.send_hook_message(session_id, message).await
+    }
+
+    pub fn is_network_tracing(&self, session_id: &str) -> bool {
+        read_lock(&self.network_tracing).contains(session_id)
+    }
+
+    /// Start or stop `@exceptions` tracing (C++ throws and their catch sites).
+    pub async fn set_exception_tracing_async(&self, session_id: &str, enabled: bool) -> Result<()> {
+        self.send_exception_tracing(session_id, enabled).await?;
+
+        let mut guard = write_lock(&self.exception_tracing);
+        if enabled {
+            guard.insert(session_id.to_string());
+        } else {
+            guard.remove(session_id);
+        }
+        Ok(())
+    }
+
+    async fn send_exception_tracing(&self, session_id: &str, enabled: bool) -> Result<()> {
+        let spawner_guard = self.frida_spawner.read().await;
+        let spawner = spawner_guard
+            .as_ref()
+            .ok_or_else(|| crate::Error::Internal("Frida spawner not initialized".to_string()))?;
+
+        let message = serde_json::json!({
+            "type": "setExceptions",
+            "enabled": enabled,
+        });
+        spawner.send_hook_message(session_id, message).await
+    }
+
+    pub fn is_exception_tracing(&self, session_id: &str) -> bool {
+        read_lock(&self.exception_tracing).contains(session_id)
+    }
+
+    /// Start or stop `@signals` tracing (handler deliveries, kill/raise).
+    pub async fn set_signal_tracing_async(&self, session_id: &str, enabled: bool) -> Result<()> {
+        self.send_signal_tracing(session_id, enabled).await?;
+
+        let mut guard = write_lock(&self.signal_tracing);
+        if enabled {
+            guard.insert(session_id.to_string());
+        } else {
+            guard.remove(session_id);
+        }
+        Ok(())
+    }
+
+    async fn send_signal_tracing(&self, session_id: &str, enabled: bool) -> Result<()> {
+        let spawner_guard = self.frida_spawner.read().await;
+        let spawner = spawner_guard
+            .as_ref()
+            .ok_or_else(|| crate::Error::Internal("Frida spawner not initialized".to_string()))?;
+
+        let message = serde_json::json!({
+            "type": "setSignals",
+            "enabled": enabled,
+        });
+        spawner.send_hook_message(session_id, message).await
+    }
+
+    pub fn is_signal_tracing(&self, session_id: &str) -> bool {
+        read_lock(&self.signal_tracing).contains(session_id)
+    }
+
+    /// Start or stop `@locks` tracing (contended and blocked mutex waits).
+    pub async fn set_lock_tracing_async(&self, session_id: &str, enabled: bool) -> Result<()> {
+        self.send_lock_tracing(session_id, enabled).await?;
+
+        let mut guard = write_lock(&self.lock_tracing);
+        if enabled {
+            guard.insert(session_id.to_string());
+        } else {
+            guard.remove(session_id);
+        }
+        Ok(())
+    }
+
+    async fn send_lock_tracing(&self, session_id: &str, enabled: bool) -> Result<()> {
+        let spawner_guard = self.frida_spawner.read().await;
+        let spawner = spawner_guard
+            .as_ref()
+            .ok_or_else(|| crate::Error::Internal("Frida spawner not initialized".to_string()))?;
+
+        let message = serde_json::json!({
+            "type": "setLocks",
+            "enabled": enabled,
+        });
+        spawner.send_hook_message(session_id, message).await
+    }
+
+    pub fn is_lock_tracing(&self, session_id: &str) -> bool {
+        read_lock(&self.lock_tracing).contains(session_id)
+    }
+
+    /// Resolve `crashCapture` globals via DWARF and hand their regions to the
+    /// agent's crash handler. Returns the names that could not be captured.
+    /// Kept per session so an agent reload re-arms it.
+    pub async fn set_crash_capture_async(
+        &self,
+        session_id: &str,
+        capture: &crate::mcp::CrashCapture,
+    ) -> Result<Vec<String>> {
+        let dwarf = self
+            .get_dwarf(session_id)
+            .await?
+            .ok_or(crate::Error::NoDebugSymbols)?;
+        let variables = capture.variables.clone();
+        let parser = Arc::clone(&dwarf);
+        let resolved =
+            tokio::task::spawn_blocking(move || parser.resolve_global_regions(&variables))
+                .await
+                .map_err(|e| {
+                    crate::Error::Internal(format!("Global resolution panicked: {}", e))
+                })??;
+
+        let regions = crash_capture_regions(&capture.variables, &resolved, capture.budget());
+        let skipped = regions
+            .iter()
+            .filter(|r| r.get("error").is_some())
+            .filter_map(|r| r["name"].as_str().map(String::from))
+            .collect();
+
+        let spawner_guard = self.frida_spawner.read().await;
+        let spawner = spawner_guard
+            .as_ref()
+            .ok_or_else(|| crate::Error::Internal("Frida spawner not initialized".to_string()))?;
+        let message = serde_json::json!({
+            "type": "setCrashCapture",
+            "imageBase": format!("0x{:x}", dwarf.image_base),
+            "regions": regions,
+        });
+        spawner.send_hook_message(session_id, message).await?;
+        write_lock(&self.crash_captures).insert(session_id.to_string(), capture.clone());
+        Ok(skipped)
+    }
+
+    pub fn get_crash_capture(&self, session_id: &str) -> Option<crate::mcp::CrashCapture> {
+        read_lock(&self.crash_captures).get(session_id).cloned()
+    }
+
+    /// Start indexing the debug_launch `symbolExtra` libraries and hand them
+    /// to the spawner, so patterns match into them before they're loaded.
+    pub async fn load_symbol_modules(
+        &self,
+        session_id: &str,
+        paths: &[String],
+        project_root: &str,
+    ) {
+        let modules: Vec<SymbolModule> = paths
+            .iter()
+            .map(|path| SymbolModule {
+                path: path.clone(),
+                name: Path::new(path)
+                    .file_name()
+                    .and_then(|n| n.to_str())
+                    .unwrap_or(path)
+                    .to_string(),
+                image_base: DwarfParser::extract_image_base(Path::new(path)).unwrap_or(0),
+                dwarf_handle: self.get_or_start_dwarf_parse(path, Some(project_root)),
+            })
+            .collect();
+
+        if let Some(spawner) = self.frida_spawner.read().await.as_ref() {
+            spawner.set_symbol_modules(session_id, modules.clone());
+        }
+        write_lock(&self.symbol_modules).insert(session_id.to_string(), modules);
+    }
+
+    pub fn symbol_module_status(&self, session_id: &str) -> Vec<crate::mcp::SymbolModuleStatus> {
+        let modules = read_lock(&self.symbol_modules);
+        let Some(modules) = modules.get(session_id) else {
+            return Vec::new();
+        };
+        modules
+            .iter()
+            .map(|m| {
+                let (status, functions, error) = match m.dwarf_handle.try_borrow_parser() {
+                    None => ("indexing", None, None),
+                    Some(Ok(dwarf)) => ("ready", Some(dwarf.functions.len()), None),
+                    Some(Err(e)) => ("failed", None, Some(e)),
+                };
+                crate::mcp::SymbolModuleStatus {
+                    path: m.path.clone(),
+                    status: status.to_string(),
+                    functions,
+                    error,
+                }
+            })
+            .collect()
+    }
+
+    /// `debug_symbols`: search the session's executable and its symbol
+    /// modules, or count what each of them holds. Modules still indexing are
+    /// skipped with a warning.
+    pub async fn search_symbols(
+        &self,
+        req: &crate::mcp::DebugSymbolsRequest,
+    ) -> Result<crate::mcp::DebugSymbolsResponse> {
+        use crate::mcp::{
+            DebugSymbolsResponse, SymbolFunction, SymbolGlobal, SymbolModuleSummary, SymbolsAction,
+        };
+
+        let session = self
+            .get_session(&req.session_id)?
+            .ok_or_else(|| crate::Error::SessionNotFound(req.session_id.clone()))?;
+        let main = self
+            .get_dwarf(&req.session_id)
+            .await?
+            .ok_or(crate::Error::NoDebugSymbols)?;
+        let modules = read_lock(&self.symbol_modules)
+            .get(&req.session_id)
+            .cloned()
+            .unwrap_or_default();
+
+        let mut response = DebugSymbolsResponse::default();
+        if req.action == SymbolsAction::Modules {
+            let summary = |path: &str, dwarf: &DwarfParser| SymbolModuleSummary {
+                path: path.to_string(),
+                status: "ready".to_string(),
+                functions: Some(dwarf.functions.len()),
+                globals: Some(dwarf.variables.len()),
+                source_files: Some(dwarf.source_file_count()),
+                error: None,
+            };
+            response.modules.push(summary(&session.binary_path, &main));
+            for module in &modules {
+                let (status, error) = match module.dwarf_handle.try_borrow_parser() {
+                    Some(Ok(dwarf)) => {
+                        response.modules.push(summary(&module.path, &dwarf));
+                        continue;
+                    }
+                    Some(Err(e)) => ("failed", Some(e)),
+                    None => ("indexing", None),
+                };
+                response.modules.push(SymbolModuleSummary {
+                    path: module.path.clone(),
+                    status: status.to_string(),
+                    functions: None,
+                    globals: None,
+                    source_files: None,
+                    error,
+                });
+            }
+            return Ok(response);
+        }
+
+        // The executable first, then each indexed symbol module by file name
+        let mut binaries: Vec<(Option<String>, Arc<DwarfParser>)> = vec![(None, main)];
+        for module in &modules {
+            match module.dwarf_handle.try_borrow_parser() {
+                Some(Ok(dwarf)) => binaries.push((Some(module.name.clone()), dwarf)),
+                Some(Err(_)) => {}
+                None => response
+                    .warnings
+                    .push(format!("{} is still indexing; not searched", module.path)),
+            }
+        }
+
+        let query = crate::dwarf::SymbolQuery::new(req.query.as_deref(), req.regex == Some(true))?;
+        let limit = req.limit.unwrap_or(50) as usize;
+        let mut matched = 0;
+        for (module, dwarf) in &binaries {
+            if req.action == SymbolsAction::Functions {
+                let found = dwarf.search_functions(&query, req.file.as_deref());
+                matched += found.len();
+                let room = limit.saturating_sub(response.functions.len());
+                let listed = found.into_iter().take(room).map(|f| SymbolFunction {
+                    name: f.name.clone(),
+                    address: format!("0x{:x}", f.low_pc),
+                    size: f.high_pc.saturating_sub(f.low_pc),
+                    file: f.source_file.clone(),
+                    line: f.line_number,
+                    module: module.clone(),
+                });
+                response.functions.extend(listed);
+            } else {
+                let found = dwarf.search_globals(&query);
+                matched += found.len();
+                let room = limit.saturating_sub(response.globals.len());
+                let listed = found.into_iter().take(room).map(|v| SymbolGlobal {
+                    name: v.name.clone(),
+                    address: format!("0x{:x}", v.address),
+                    size: v.byte_size,
+                    type_name: v.type_name.clone(),
+                    file: v.source_file.clone(),
+                    module: module.clone(),
+                });
+                response.globals.extend(listed);
+            }
+        }
+        response.matched = Some(matched);
+        Ok(response)
+    }
+
+    /// Resolve a watch expression in the session's symbol modules. The base
+    /// address comes back relative to the module it was found in.
+    pub async fn resolve_module_watch(
+        &self,
+        session_id: &str,
+        expr: &str,
+    ) -> Option<(crate::dwarf::WatchRecipe, String)> {
+        let modules = read_lock(&self.symbol_modules)
+            .get(session_id)
+            .cloned()
+            .unwrap_or_default();
+        for module in modules {
+            let mut handle = module.dwarf_handle.clone();
+            let Ok(dwarf) = handle.get().await else {
+                continue;
+            };
+            if let Ok(mut recipe) = dwarf.resolve_watch_expression(expr) {
+                if recipe.base_address > module.image_base {
+                    recipe.base_address -= module.image_base;
+                    return Some((recipe, module.name));
+                }
+            }
+        }
+        None
+    }
+
+    /// Evaluate a debug_eval expression. Names are looked up first among the
+    /// locals of the paused frame, whose member paths are followed with raw
+    /// reads, then as globals, read in one debug_read batch. `argN` comes from
+    /// the paused thread's captured arguments, and calls are made in the
+    /// target innermost-first before the rest is folded daemon-side.
+    pub async fn evaluate_expression(
+        &self,
+        req: &crate::mcp::DebugEvalRequest,
+    ) -> Result<crate::mcp::DebugEvalResponse> {
+        use super::eval::{self, Env, Value};
+
+        let lang = read_lock(&self.languages)
+            .get(&req.session_id)
+            .copied()
+            .unwrap_or(Language::Native);
+        if lang != Language::Native {
+            return Err(crate::Error::ValidationError(
+                "debug_eval works on native sessions. For Python/JS, pass the expression to debug_memory as a variable".to_string(),
+            ));
+        }
+        let expr = eval::parse(&req.expr)?;
+        let mut env = Env::default();
+
+        let paused = self.get_all_paused_threads(&req.session_id);
+        let pause = match req.thread_id {
+            Some(tid) => Some(paused.get(&tid).ok_or_else(|| {
+                crate::Error::ValidationError(format!("Thread {} is not paused", tid))
+            })?),
+            None => paused.keys().min().and_then(|tid| paused.get(tid)),
+        };
+
+        let mut globals = Vec::new();
+        for name in eval::variables(&expr) {
+            let Some(index) = eval::argument_index(&name) else {
+                globals.push(name);
+                continue;
+            };
+            if req.frame.unwrap_or(0) > 0 {
+                return Err(crate::Error::ValidationError(format!(
+                    "'{}' is an argument of frame 0; name the caller's parameter instead",
+                    name
+                )));
+            }
+            let pause = pause.ok_or_else(|| {
+                crate::Error::ValidationError(format!(
+                    "'{}' needs a paused thread. Set a breakpoint and wait for it to hit",
+                    name
+                ))
+            })?;
+            let arg = pause
+                .arguments
+                .iter()
+                .find(|a| a.index == index)
+                .ok_or_else(|| {
+                    crate::Error::ValidationError(format!(
+                        "'{}' was not captured at the breakpoint ({} arguments available)",
+                        name,
+                        pause.arguments.len()
+                    ))
+                })?;
+            let value = match Value::from_json(&serde_json::json!(arg.value)) {
+                Value::Other(raw) => arg
+                    .value
+                    .parse::<i64>()
+                    .map(Value::Int)
+                    .or_else(|_| arg.value.parse::<f64>().map(Value::Float))
+                    .unwrap_or(Value::Other(raw)),
+                value => value,
+            };
+            env.vars.insert(name, value);
+        }
+
+        // Locals shadow globals of the same name, as in the source
+        if !globals.is_empty() && (pause.is_some() || req.frame.is_some()) {
+            let read = match self.get_dwarf(&req.session_id).await? {
+                Some(dwarf) => {
+                    self.read_paused_frame(
+                        &req.session_id,
+                        req.thread_id,
+                        req.frame.unwrap_or(0),
+                        &dwarf,
+                    )
+                    .await
+                }
+                None => Err(crate::Error::ValidationError(
+                    "Locals need debug symbols for the binary".to_string(),
+                )),
+            };
+            let frame = match read {
+                Ok(frame) => Some(frame),
+                Err(e) if req.frame.is_some() => return Err(e),
+                // The names may all be globals
+                Err(e) => {
+                    tracing::debug!("debug_eval: no frame locals: {}", e);
+                    None
+                }
+            };
+            if let Some(frame) = frame {
+                let frame_base = frame
+                    .frame_base
+                    .as_deref()
+                    .and_then(|s| u64::from_str_radix(s.trim_start_matches("0x"), 16).ok())
+                    .filter(|_| !frame.before_prologue);
+                let mut rest = Vec::new();
+                for name in globals {
+                    match crate::dwarf::resolve_local_path(
+                        &frame.locals,
+                        &name,
+                        &frame.registers,
+                        frame_base,
+                        FRAME_ARCH,
+                    ) {
+                        Some(path) => {
+                            let value = self.read_local_path(&req.session_id, &path?).await?;
+                            env.vars.insert(name, value);
+                        }
+                        None => rest.push(name),
+                    }
+                }
+                globals = rest;
+            }
+        }
+
+        if !globals.is_empty() {
+            let targets: Vec<serde_json::Value> = globals
+                .iter()
+                .map(|v| serde_json::json!({ "variable": v }))
+                .collect();
+            let read = self
+                .execute_debug_read(&serde_json::json!({
+                    "sessionId": req.session_id,
+                    "targets": targets,
+                }))
+                .await?;
+            let read: crate::mcp::DebugReadResponse = serde_json::from_value(read)?;
+            for result in read.results {
+                if let Some(err) = result.error {
+                    return Err(crate::Error::ValidationError(format!(
+                        "{}: {}",
+                        result.target, err
+                    )));
+                }
+                let value = match (result.value, result.fields) {
+                    (Some(value), _) => Value::from_json(&value),
+                    (None, Some(fields)) => Value::Other(fields),
+                    (None, None) => Value::Other(serde_json::Value::Null),
+                };
+                env.vars.insert(result.target, value);
+            }
+        }
+
+        let mut made = Vec::new();
+        let calls = eval::calls(&expr);
+        if !calls.is_empty() {
+            let dwarf = self.get_dwarf(&req.session_id).await?.ok_or_else(|| {
+                crate::Error::ValidationError(
+                    "Function calls need debug symbols for the binary".to_string(),
+                )
+            })?;
+            for (name, args) in calls {
+                let args = args
+                    .iter()
+                    .map(|a| eval::evaluate(a, &env)?.as_int())
+                    .collect::<Result<Vec<_>>>()?;
+                let key = eval::call_key(name, &args);
+                if env.calls.contains_key(&key) {
+                    continue;
+                }
+                let func = dwarf.find_by_name(name).into_iter().next().ok_or_else(|| {
+                    crate::Error::ValidationError(format!(
+                        "No function named '{}' in debug symbols",
+                        name
+                    ))
+                })?;
+                let message = serde_json::json!({
+                    "type": "call_function",
+                    "address": format!("0x{:x}", func.low_pc),
+                    "args": args.iter().map(|a| a.to_string()).collect::<Vec<_>>(),
+                    "imageBase": format!("0x{:x}", dwarf.image_base),
+                });
+                let response = self
+                    .send_read_memory(&req.session_id, serde_json::to_string(&message)?)
+                    .await?;
+                if let Some(err) = response.get("error").and_then(|v| v.as_str()) {
+                    return Err(crate::Error::Frida(format!("{} failed: {}", key, err)));
+                }
+                let value = response
+                    .get("value")
+                    .and_then(|v| v.as_str())
+                    .and_then(|s| s.parse::<i64>().ok())
+                    .ok_or_else(|| crate::Error::Frida(format!("{} returned no value", key)))?;
+                made.push(format!("{} = {}", key, value));
+                env.calls.insert(key, Value::Int(value));
+            }
+        }
+
+        let value = eval::evaluate(&expr, &env)?;
+        Ok(crate::mcp::DebugEvalResponse {
+            expr: req.expr.clone(),
+            value: value.to_json(),
+            kind: value.kind().to_string(),
+            hex: match value {
+                Value::Int(i) => Some(format!("0x{:x}", i)),
+                _ => None,
+            },
+            calls: made,
+        })
+    }
+
+    /// Follow a paused-frame local's member path: each pointer on the way is
+    /// read from the target, then the value (or a struct's members) at the end.
+    async fn read_local_path(
+        &self,
+        session_id: &str,
+        path: &crate::dwarf::LocalPath,
+    ) -> Result<super::eval::Value> {
+        use super::eval::Value;
+        use crate::dwarf::{LocalRoot, PathOp, TypeKind};
+
+        let mut at = path.root;
+        for op in &path.ops {
+            at = match (*op, at) {
+                (PathOp::Deref, LocalRoot::Value(pointer)) => LocalRoot::Memory(pointer),
+                (PathOp::Deref, LocalRoot::Memory(address)) => {
+                    let read = self
+                        .read_absolute(session_id, &[(address, 8, TypeKind::Pointer)])
+                        .await?;
+                    match Value::from_json(&read[0]) {
+                        Value::Int(pointer) => LocalRoot::Memory(pointer as u64),
+                        _ => {
+                            return Err(crate::Error::ReadFailed(format!(
+                                "No pointer at 0x{:x}",
+                                address
+                            )))
+                        }
+                    }
+                }
+                (PathOp::Offset(offset), LocalRoot::Memory(address)) => {
+                    LocalRoot::Memory(address.wrapping_add(offset))
+                }
+                (PathOp::Offset(_), LocalRoot::Value(_)) => {
+                    return Err(crate::Error::ValidationError(
+                        "A struct held in a register has no members to address".to_string(),
+                    ))
+                }
+                (_, LocalRoot::Argument(_)) => return Err(argument_root()),
+            };
+            if at == LocalRoot::Memory(0) {
+                return Err(crate::Error::ReadFailed(
+                    "Null pointer in the member path".to_string(),
+                ));
+            }
+        }
+
+        match at {
+            LocalRoot::Argument(_) => Err(argument_root()),
+            LocalRoot::Value(bits) => Ok(Value::from_bits(bits, path.size, &path.type_kind)),
+            LocalRoot::Memory(address) if path.fields.is_empty() => {
+                let read = self
+                    .read_absolute(session_id, &[(address, path.size, path.type_kind.clone())])
+                    .await?;
+                Ok(Value::from_json(&read[0]))
+            }
+            LocalRoot::Memory(address) => {
+                let scalars: Vec<_> = path
+                    .fields
+                    .iter()
+                    .filter(|f| !f.is_truncated_struct)
+                    .collect();
+                let reads: Vec<(u64, u8, TypeKind)> = scalars
+                    .iter()
+                    .map(|f| (address.wrapping_add(f.offset), f.size, f.type_kind.clone()))
+                    .collect();
+                let values = self.read_absolute(session_id, &reads).await?;
+                let mut fields = serde_json::Map::new();
+                for field in &path.fields {
+                    fields.insert(field.name.clone(), serde_json::json!("<struct>"));
+                }
+                for (field, value) in scalars.iter().zip(values) {
+                    fields.insert(field.name.clone(), value);
+                }
+                Ok(Value::Other(serde_json::Value::Object(fields)))
+            }
+        }
+    }
+
+    /// Read scalars at absolute addresses in one agent round trip.
+    async fn read_absolute(
+        &self,
+        session_id: &str,
+        reads: &[(u64, u8, crate::dwarf::TypeKind)],
+    ) -> Result<Vec<serde_json::Value>> {
+        let recipes: Vec<serde_json::Value> = reads
+            .iter()
+            .map(|(address, size, type_kind)| {
+                serde_json::json!({
+                    "label": format!("0x{:x}", address),
+                    "address": format!("0x{:x}", address),
+                    "size": size,
+                    "typeKind": type_kind_to_agent_str(type_kind),
+                    "derefDepth": 0,
+                    "derefOffset": 0,
+                    "noSlide": true,
+                })
+            })
+            .collect();
+        let message = serde_json::json!({ "type": "read_memory", "recipes": recipes });
+        let response = self
+            .send_read_memory(session_id, serde_json::to_string(&message)?)
+            .await?;
+        let results = response
+            .get("results")
+            .and_then(|v| v.as_array())
+            .filter(|r| r.len() == reads.len())
+            .ok_or_else(|| crate::Error::Frida("Memory read returned no results".to_string()))?;
+        results
+            .iter()
+            .map(|r| match r.get("error").and_then(|e| e.as_str()) {
+                Some(err) => Err(crate::Error::ReadFailed(err.to_string())),
+                None => Ok(r.get("value").cloned().unwrap_or(serde_json::Value::Null)),
+            })
+            .collect()
+    }
+
+    /// Local variables of a paused thread's frame (debug_memory action
+    /// "locals"). The agent walks the thread's frame-pointer chain to `frame`
+    /// and sends back the stack around that frame, which is decoded with the
+    /// same DWARF locations as crash locals.
+    pub async fn frame_locals(
+        &self,
+        session_id: &str,
+        thread_id: Option<u64>,
+        frame: u32,
+    ) -> Result<crate::mcp::FrameLocalsResponse> {
+        let lang = read_lock(&self.languages)
+            .get(session_id)
+            .copied()
+            .unwrap_or(Language::Native);
+        if lang != Language::Native {
+            return Err(crate::Error::ValidationError(
+                "action 'locals' works on native sessions. For Python/JS, read the variable by name".to_string(),
+            ));
+        }
+        let dwarf = self.get_dwarf(session_id).await?.ok_or_else(|| {
+            crate::Error::ValidationError("Locals need debug symbols for the binary".to_string())
+        })?;
+        let paused = self
+            .read_paused_frame(session_id, thread_id, frame, &dwarf)
+            .await?;
+        let (thread_id, address, locals_info) = (paused.thread_id, paused.address, &paused.locals);
+        let locals = crate::dwarf::resolve_crash_locals(
+            locals_info,
+            &paused.registers,
+            // At entry the stack slots belong to the caller
+            if paused.before_prologue {
+                None
+            } else {
+                paused.frame_memory.as_deref()
+            },
+            paused.frame_base.as_deref(),
+            FRAME_ARCH,
+        );
+        let unavailable: Vec<String> = locals_info
+            .iter()
+            .map(|l| l.name.clone())
+            .filter(|name| !locals.iter().any(|v| v["name"] == name.as_str()))
+            .collect();
 
-        let capabilities = self.get_capabilities(session_id);
+        let note = if locals_info.is_empty() {
+            Some("No DWARF locals for this function".to_string())
+        } else if paused.before_prologue {
+            Some("Paused at function entry, before its locals are set up; step once (debug_continue action 'step-over') to read them".to_string())
+        } else if frame > 0 && !unavailable.is_empty() {
+            Some(
+                "Callers' registers aren't restored, so only their stack locals are shown"
+                    .to_string(),
+            )
+        } else {
+            None
+        };
 
-        Ok(crate::mcp::SessionStatusResponse {
-            status,
-            pid: session.pid,
-            event_count,
-            hooked_functions,
-            trace_patterns,
-            breakpoints,
-            logpoints,
-            watches,
-            paused_threads,
-            crash_info,
-            capabilities,
+        let (file, line) = match dwarf.resolve_address(address) {
+            Some((file, line, _)) => (Some(file), Some(line)),
+            None => (None, None),
+        };
+        Ok(crate::mcp::FrameLocalsResponse {
+            thread_id,
+            frame,
+            function: dwarf
+                .functions
+                .iter()
+                .find(|f| address >= f.low_pc && address < f.high_pc)
+                .map(|f| f.name.clone()),
+            file,
+            line,
+            address: format!("0x{:x}", address),
+            locals,
+            unavailable,
+            note,
         })
     }
 
-    pub fn get_breakpoints(&self, session_id: &str) -> Vec<Breakpoint> {
-        let guard = read_lock(&self.breakpoints);
-        guard
-            .get(session_id)
-            .map(|bps| bps.values().cloned().collect())
-            .unwrap_or_default()
-    }
+    /// Have the agent read `frame` of a paused thread (default: the first
+    /// paused one) and look up the DWARF locals of the function it is in.
+    async fn read_paused_frame(
+        &self,
+        session_id: &str,
+        thread_id: Option<u64>,
+        frame: u32,
+        dwarf: &DwarfParser,
+    ) -> Result<PausedFrame> {
+        let paused = self.get_all_paused_threads(session_id);
+        let (thread_id, pause) = match thread_id {
+            Some(tid) => (
+                tid,
+                paused.get(&tid).ok_or_else(|| {
+                    crate::Error::ValidationError(format!("Thread {} is not paused", tid))
+                })?,
+            ),
+            None => paused
+                .iter()
+                .min_by_key(|(tid, _)| **tid)
+                .map(|(tid, pause)| (*tid, pause))
+                .ok_or_else(|| {
+                    crate::Error::ValidationError(
+                        "No paused thread. Set a breakpoint and wait for it to hit".to_string(),
+                    )
+                })?,
+        };
 
-    pub fn get_breakpoint(&self, session_id: &str, breakpoint_id: &str) -> Option<Breakpoint> {
-        let guard = read_lock(&self.breakpoints);
-        guard
-            .get(session_id)
-            .and_then(|bps| bps.get(breakpoint_id))
-            .cloned()
-    }
+        // Before the prologue the frame pointer is still the caller's
+        let at_entry = !pause.breakpoint_id.starts_with(STEP_BREAKPOINT_PREFIX)
+            && self
+                .get_breakpoint(session_id, &pause.breakpoint_id)
+                .is_some_and(|bp| dwarf.functions.iter().any(|f| f.low_pc == bp.address));
 
-    // Logpoint management
-    pub fn add_logpoint(&self, session_id: &str, logpoint: Logpoint) -> Result<()> {
-        let mut guard = write_lock(&self.logpoints);
-        let session_lps = guard
-            .entry(session_id.to_string())
-            .or_insert_with(HashMap::new);
-        if session_lps.len() >= crate::mcp::MAX_LOGPOINTS_PER_SESSION {
-            return Err(crate::Error::ValidationError(format!(
-                "Session has {} logpoints (max {})",
-                session_lps.len(),
-                crate::mcp::MAX_LOGPOINTS_PER_SESSION
+        let message = serde_json::json!({
+            "type": "read_frame",
+            "threadId": thread_id,
+            "frame": frame,
+            "atEntry": at_entry,
+            "imageBase": format!("0x{:x}", dwarf.image_base),
+        });
+        let response = self
+            .send_read_memory(session_id, serde_json::to_string(&message)?)
+            .await?;
+        if let Some(err) = response.get("error").and_then(|v| v.as_str()) {
+            return Err(crate::Error::Frida(format!(
+                "Reading frame {} failed: {}",
+                frame, err
             )));
         }
-        session_lps.insert(logpoint.id.clone(), logpoint);
-        Ok(())
+        let address = response
+            .get("address")
+            .and_then(|v| v.as_str())
+            .and_then(|s| u64::from_str_radix(s.trim_start_matches("0x"), 16).ok())
+            .ok_or_else(|| crate::Error::Frida("Frame read returned no address".to_string()))?;
+        let text = |key: &str| {
+            response
+                .get(key)
+                .and_then(|v| v.as_str())
+                .map(str::to_string)
+        };
+
+        Ok(PausedFrame {
+            thread_id,
+            before_prologue: at_entry && frame == 0,
+            address,
+            locals: dwarf.parse_locals_at_pc(address)?,
+            registers: response
+                .get("registers")
+                .cloned()
+                .unwrap_or(serde_json::Value::Null),
+            frame_base: text("frameBase"),
+            frame_memory: text("frameMemory"),
+        })
     }
 
-    pub async fn remove_logpoint(&self, session_id: &str, logpoint_id: &str) {
-        // Send removal to agent via spawner pipeline (best-effort)
-        let send_result = async {
-            let spawner_guard = self.frida_spawner.read().await;
-            if let Some(spawner) = spawner_guard.as_ref() {
-                spawner.remove_logpoint(session_id, logpoint_id).await
-            } else {
-                Ok(())
-            }
+    /// Start, snapshot or stop Stalker coverage in the agent. The agent keeps
+    /// the state; snapshot and stop answer with the blocks recorded so far.
+    pub async fn coverage(
+        &self,
+        session_id: &str,
+        action: crate::mcp::CoverageAction,
+        scope: crate::mcp::CoverageScope,
+    ) -> Result<serde_json::Value> {
+        let message = serde_json::json!({
+            "type": "coverage",
+            "action": action,
+            "scope": scope,
+        });
+        let response = self
+            .send_read_memory(session_id, serde_json::to_string(&message)?)
+            .await?;
+        if let Some(err) = response.get("error").and_then(|v| v.as_str()) {
+            return Err(crate::Error::Frida(format!("Coverage failed: {}", err)));
         }
-        .await;
+        Ok(response)
+    }
 
-        if let Err(e) = send_result {
-            tracing::warn!(
-                "Failed to send logpoint removal to agent: {} (cleaning up state anyway)",
-                e
-            );
+    /// Start, report or stop allocation tracking in the agent. The agent keeps
+    /// the allocations; report and stop answer with the top call sites, and
+    /// with `leaks` also the live allocations grouped by backtrace.
+    pub async fn heap(
+        &self,
+        session_id: &str,
+        action: crate::mcp::HeapAction,
+        limit: u32,
+        leaks: bool,
+    ) -> Result<serde_json::Value> {
+        let message = serde_json::json!({
+            "type": "heap",
+            "action": action,
+            "limit": limit,
+            "leaks": leaks,
+        });
+        let response = self
+            .send_read_memory(session_id, serde_json::to_string(&message)?)
+            .await?;
+        if let Some(err) = response.get("error").and_then(|v| v.as_str()) {
+            return Err(crate::Error::Frida(format!(
+                "Heap tracking failed: {}",
+                err
+            )));
         }
-
-        let mut guard = write_lock(&self.logpoints);
-        if let Some(session_lps) = guard.get_mut(session_id) {
-            session_lps.remove(logpoint_id);
+        let mut tracking = write_lock(&self.heap_tracking);
+        if response.get("active").and_then(|v| v.as_bool()) == Some(true) {
+            tracking.insert(session_id.to_string());
+        } else {
+            tracking.remove(session_id);
         }
+        Ok(response)
     }
 
-    pub fn get_logpoints(&self, session_id: &str) -> Vec<Logpoint> {
-        let guard = read_lock(&self.logpoints);
-        guard
+    pub fn is_heap_tracking(&self, session_id: &str) -> bool {
+        read_lock(&self.heap_tracking).contains(session_id)
+    }
+
+    /// Store the `leakGroups` of a heap report as `leak` events. Returns how
+    /// many were written.
+    pub fn record_leaks(&self, session_id: &str, payload: &serde_json::Value) -> Result<usize> {
+        let pid = self.get_session(session_id)?.map(|s| s.pid);
+        let events = leak_events(session_id, pid, payload);
+        self.db.insert_events_batch(&events)?;
+        Ok(events.len())
+    }
+
+    pub fn get_watchpoints(&self, session_id: &str) -> Vec<Watchpoint> {
+        read_lock(&self.watchpoints)
             .get(session_id)
-            .map(|lps| lps.values().cloned().collect())
+            .cloned()
             .unwrap_or_default()
     }
 
@@ -2622,7 +5983,17 @@ pub struct Breakpoint {
 #[derive(Debug, Clone)]
 pub enum BreakpointTarget {
     Function(String),
-    Line { file: String, line: u32 },
+    Line {
+        file: String,
+        line: u32,
+    },
+    /// `offset` bytes into the first function matching `function`
+    Offset {
+        function: String,
+        offset: u64,
+    },
+    /// Static address, for code without usable line info
+    Address(u64),
 }
 
 #[derive(Debug, Clone)]
@@ -2632,6 +6003,51 @@ pub struct Logpoint {
     pub address: u64,
     pub message: String,
     pub condition: Option<String>,
+    pub hits: LogpointHits,
+}
+
+/// Which hits of a logpoint are logged; 0 leaves that filter off.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LogpointHits {
+    /// First hit (1-based) that is logged
+    pub hit_count: u32,
+    /// Log every Nth hit from `hit_count` on
+    pub every_n: u32,
+    /// Messages after which the logpoint goes quiet
+    pub max_hits: u32,
+}
+
+impl LogpointHits {
+    pub fn new(hit_count: Option<u32>, every_n: Option<u32>, max_hits: Option<u32>) -> Self {
+        Self {
+            hit_count: hit_count.unwrap_or(0),
+            every_n: every_n.unwrap_or(0),
+            max_hits: max_hits.unwrap_or(0),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Watchpoint {
+    pub id: String,
+    pub label: String,
+    /// DWARF-static for variables; runtime address when `no_slide` is set
+    pub address: u64,
+    pub size: u32,
+    pub no_slide: bool,
+    /// DWARF variable the watchpoint was resolved from (None for raw addresses)
+    pub variable: Option<String>,
+}
+
+impl Watchpoint {
+    pub fn info(&self) -> crate::mcp::WatchpointInfo {
+        crate::mcp::WatchpointInfo {
+            id: self.id.clone(),
+            label: self.label.clone(),
+            address: format!("0x{:x}", self.address),
+            size: self.size,
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -2648,10 +6064,147 @@ pub struct PauseInfo {
     pub arguments: Vec<crate::mcp::CapturedArg>,
 }
 
+/// A paused thread's frame as the agent read it, with the DWARF locals of
+/// the function it is in.
+struct PausedFrame {
+    thread_id: u64,
+    /// Paused on the function's first instruction: its stack slots aren't set up
+    before_prologue: bool,
+    /// DWARF-static pc of the frame
+    address: u64,
+    locals: Vec<crate::dwarf::LocalVariableInfo>,
+    /// Frame 0 only; callers' registers aren't recovered
+    registers: serde_json::Value,
+    frame_base: Option<String>,
+    /// Hex of [frame_base - 512, frame_base + 128]
+    frame_memory: Option<String>,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_crash_capture_regions_budget() {
+        let region = |name: &str, byte_size| crate::dwarf::GlobalRegion {
+            name: name.to_string(),
+            address: 0x1000,
+            byte_size,
+            type_name: None,
+        };
+        let names: Vec<String> = ["gState", "gMissing", "gRing", "gLate"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let resolved = vec![
+            region("gRing", 4096),
+            region("gState", 64),
+            region("gLate", 8),
+        ];
+
+        let regions = crash_capture_regions(&names, &resolved, 1024);
+        assert_eq!(regions[0]["size"], 64);
+        assert!(regions[1]["error"].as_str().unwrap().contains("DWARF"));
+        // Truncated to what is left of the budget; the full size stays visible
+        assert_eq!(regions[2]["size"], 960);
+        assert_eq!(regions[2]["byteSize"], 4096);
+        assert_eq!(regions[2]["address"], "0x1000");
+        assert!(regions[3]["error"].as_str().unwrap().contains("budget"));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_pipe_reader_emits_aux_output() {
+        let dir = tempfile::tempdir().unwrap();
+        let fifo = dir.path().join("diag.fifo");
+        let fifo = fifo.to_str().unwrap();
+        ensure_fifo(fifo).unwrap();
+        // Idempotent on an existing FIFO
+        ensure_fifo(fifo).unwrap();
+
+        let (tx, mut rx) = event_pipeline::event_channel(16);
+        spawn_pipe_reader("s1", 0, fifo, tx).unwrap();
+        std::fs::write(fifo, "diag line\n").unwrap();
+
+        let event = tokio::time::timeout(std::time::Duration::from_secs(2), rx.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(event.event_type, crate::db::EventType::AuxOutput);
+        assert_eq!(event.text.as_deref(), Some("diag line\n"));
+        assert_eq!(event.channel, Some(format!("pipe:{}", fifo)));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_ensure_fifo_rejects_regular_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("plain.txt");
+        std::fs::write(&path, "x").unwrap();
+        assert!(ensure_fifo(path.to_str().unwrap()).is_err());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_spawn_plain_captures_output() {
+        let dir = tempfile::tempdir().unwrap();
+        let sm = SessionManager::new(&dir.path().join("strobe.db")).unwrap();
+        let session_id = "test-plain";
+        sm.create_session(session_id, "/bin/sh", "/tmp", 0).unwrap();
+
+        let pid = sm
+            .spawn_plain(
+                session_id,
+                "/bin/sh",
+                &["-c".to_string(), "echo hello; echo oops >&2".to_string()],
+                None,
+                "/tmp",
+                None,
+            )
+            .await
+            .unwrap();
+        let mut status = 0;
+        unsafe { libc::waitpid(pid as i32, &mut status, 0) };
+
+        let mut events = vec![];
+        for _ in 0..100 {
+            events = sm
+                .db()
+                .query_events(session_id, |q| q.text_output())
+                .unwrap();
+            if events.len() >= 2 {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        }
+        assert!(events
+            .iter()
+            .any(|e| e.event_type == crate::db::EventType::Stdout
+                && e.text.as_deref() == Some("hello\n")));
+        assert!(events
+            .iter()
+            .any(|e| e.event_type == crate::db::EventType::Stderr
+                && e.text.as_deref() == Some("oops\n")));
+
+        // Counted right after the commit the query above saw
+        let mut writer = sm.writer_status(session_id).unwrap();
+        for _ in 0..50 {
+            if writer.flushed >= 2 {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+            writer = sm.writer_status(session_id).unwrap();
+        }
+        assert!(writer.flushed >= 2);
+        assert_eq!(writer.dropped, 0);
+        assert_eq!(writer.queued, 0);
+
+        assert!(sm.is_uninstrumented(session_id));
+        let err = sm.require_instrumented(session_id).unwrap_err();
+        assert!(err.to_string().contains("instrumentation: 'none'"));
+        assert!(sm.require_instrumented("other-session").is_ok());
+    }
+
     #[tokio::test]
     async fn test_breakpoint_state_management() {
         let temp_dir = std::env::temp_dir();
@@ -2744,6 +6297,7 @@ mod tests {
             address: 0x2000,
             message: "hit: {args[0]}".to_string(),
             condition: None,
+            hits: LogpointHits::default(),
         };
 
         // Add logpoint
@@ -2763,6 +6317,378 @@ mod tests {
         let _ = std::fs::remove_file(&db_path);
     }
 
+    #[test]
+    fn test_session_status_reports_latest_call_counts() {
+        let dir = tempfile::tempdir().unwrap();
+        let sm = SessionManager::new(&dir.path().join("strobe.db")).unwrap();
+        let session_id = "count-1";
+        sm.create_session(session_id, "/bin/app", "/nonexistent-project", 0)
+            .unwrap();
+
+        let patterns = vec!["dsp::**".to_string(), "app::run".to_string()];
+        sm.add_patterns(session_id, &patterns).unwrap();
+        sm.set_pattern_mode(session_id, &patterns[..1], crate::mcp::TraceMode::Count);
+
+        let counts = |id: &str, ts: i64, mix: u64| Event {
+            id: id.to_string(),
+            session_id: session_id.to_string(),
+            timestamp_ns: ts,
+            event_type: crate::db::EventType::CallCounts,
+            arguments: Some(serde_json::json!([
+                { "function": "dsp::mix", "address": "0x10", "count": mix },
+                { "function": "dsp::fill", "address": "0x20", "count": 7 }
+            ])),
+            pid: Some(100),
+            ..Event::default()
+        };
+        sm.db()
+            .insert_events_batch(&[counts("c1", 1_000, 5), counts("c2", 2_000, 90)])
+            .unwrap();
+
+        let status = sm.session_status(session_id).unwrap();
+        assert_eq!(status.counted_patterns, vec!["dsp::**".to_string()]);
+        assert_eq!(status.call_counts.len(), 2);
+        assert_eq!(status.call_counts[0].function, "dsp::mix");
+        assert_eq!(status.call_counts[0].count, 90);
+        assert_eq!(status.call_counts[0].pid, None);
+
+        sm.remove_patterns(session_id, &patterns[..1]).unwrap();
+        assert!(sm.get_counted_patterns(session_id).is_empty());
+    }
+
+    #[test]
+    fn test_pattern_modes_batch_separately() {
+        let dir = tempfile::tempdir().unwrap();
+        let sm = SessionManager::new(&dir.path().join("strobe.db")).unwrap();
+        let session_id = "rt-1";
+        let patterns = vec![
+            "audio::render".to_string(),
+            "dsp::**".to_string(),
+            "app::run".to_string(),
+        ];
+        sm.add_patterns(session_id, &patterns).unwrap();
+        sm.set_pattern_mode(session_id, &patterns[..1], crate::mcp::TraceMode::Rt);
+        sm.set_pattern_mode(session_id, &patterns[1..2], crate::mcp::TraceMode::Count);
+
+        let [events, count, rt] = batches_by_mode(
+            patterns.clone(),
+            &sm.get_counted_patterns(session_id),
+            &sm.get_rt_patterns(session_id),
+        );
+        assert_eq!(events.0, vec!["app::run".to_string()]);
+        assert_eq!(count.0, vec!["dsp::**".to_string()]);
+        assert_eq!(rt.0, vec!["audio::render".to_string()]);
+        assert_eq!(rt.1, crate::mcp::TraceMode::Rt);
+
+        // A pattern is in one mode at a time
+        sm.set_pattern_mode(session_id, &patterns[..1], crate::mcp::TraceMode::Count);
+        assert!(sm.get_rt_patterns(session_id).is_empty());
+        assert_eq!(sm.get_counted_patterns(session_id).len(), 2);
+    }
+
+    #[test]
+    fn test_instrumentation_snapshot_omits_runtime_addresses() {
+        let dir = tempfile::tempdir().unwrap();
+        let sm = SessionManager::new(&dir.path().join("strobe.db")).unwrap();
+        let session_id = "snap-1";
+        sm.create_session(session_id, "/bin/app", "/nonexistent-project", 0)
+            .unwrap();
+
+        sm.add_patterns(session_id, &["net::*".to_string(), "app::run".to_string()])
+            .unwrap();
+        let watch = |label: &str, variable: Option<&str>| ActiveWatchState {
+            label: label.to_string(),
+            address: 0x4000,
+            size: 4,
+            type_kind_str: "int".to_string(),
+            deref_depth: 0,
+            deref_offset: 0,
+            type_name: None,
+            on_patterns: Some(vec!["net::*".to_string()]),
+            is_expr: false,
+            expr: None,
+            no_slide: variable.is_none(),
+            variable: variable.map(String::from),
+            delta_threshold: None,
+            module: None,
+        };
+        sm.set_watches(
+            session_id,
+            vec![watch("counter", Some("g_counter")), watch("raw", None)],
+        );
+        sm.add_breakpoint(
+            session_id,
+            Breakpoint {
+                id: "bp-1".to_string(),
+                target: BreakpointTarget::Line {
+                    file: "net.cpp".to_string(),
+                    line: 12,
+                },
+                address: 0x1000,
+                condition: Some("args[0] > 1".to_string()),
+                hit_count: 3,
+                hits: 0,
+            },
+        )
+        .unwrap();
+        sm.add_logpoint(
+            session_id,
+            Logpoint {
+                id: "lp-1".to_string(),
+                target: BreakpointTarget::Function("app::run".to_string()),
+                address: 0x2000,
+                message: "run {args[0]}".to_string(),
+                condition: None,
+                hits: LogpointHits::new(None, Some(100), None),
+            },
+        )
+        .unwrap();
+        sm.set_event_limit(session_id, 500_000);
+
+        let (snapshot, omitted) = sm.instrumentation_snapshot(session_id).unwrap();
+        assert_eq!(snapshot.patterns, vec!["app::run", "net::*"]);
+        assert_eq!(snapshot.watches.len(), 1);
+        assert_eq!(snapshot.watches[0].variable.as_deref(), Some("g_counter"));
+        assert!(snapshot.watches[0].address.is_none());
+        assert_eq!(omitted.len(), 1);
+        assert!(omitted[0].contains("raw"));
+        assert_eq!(snapshot.breakpoints[0].line, Some(12));
+        assert_eq!(snapshot.breakpoints[0].hit_count, Some(3));
+        assert_eq!(snapshot.logpoints[0].function.as_deref(), Some("app::run"));
+        assert_eq!(snapshot.logpoints[0].every_n, Some(100));
+        assert_eq!(snapshot.logpoints[0].hit_count, None);
+        assert_eq!(snapshot.settings.event_limit, Some(500_000));
+        assert!(snapshot.validate().is_ok());
+
+        assert!(sm.instrumentation_snapshot("missing").is_err());
+    }
+
+    #[test]
+    fn test_hexdump_lines() {
+        let mut bytes = b"RIFF\x24\x08\x00\x00WAVEfmt ".to_vec();
+        bytes.extend_from_slice(&[0xff; 4]);
+        let lines = hexdump_lines(
+            0x7ff8_0000_1000,
+            &bytes,
+            &[
+                (0, "magic".to_string()),
+                (4, "size".to_string()),
+                (16, "rate".to_string()),
+            ],
+        );
+        assert_eq!(lines.len(), 2);
+        assert_eq!(
+            lines[0],
+            "0x7ff800001000  52 49 46 46 24 08 00 00  57 41 56 45 66 6d 74 20  |RIFF$...WAVEfmt |  magic, size"
+        );
+        assert_eq!(
+            lines[1],
+            "0x7ff800001010  ff ff ff ff                                       |....            |  rate"
+        );
+    }
+
+    #[test]
+    fn test_template_placeholders() {
+        assert_eq!(
+            template_placeholders("note {velocity} at {buffer.sample_rate}Hz on {threadId}"),
+            vec!["velocity", "buffer.sample_rate"]
+        );
+        assert_eq!(
+            template_placeholders("{args[0]} {self->next->len} {velocity} {{x}"),
+            vec!["self->next->len", "velocity", "x"]
+        );
+        assert!(template_placeholders("{ not a name } {1x} {a..b} {").is_empty());
+    }
+
+    #[test]
+    fn test_condition_watch_labels() {
+        assert_eq!(condition_watch_labels("watch:gTempo > 200"), vec!["gTempo"]);
+        assert_eq!(
+            condition_watch_labels("watch:gClock->counter>=3 && watch:gBuf.len<args[1]"),
+            vec!["gClock->counter", "gBuf.len"]
+        );
+        assert_eq!(
+            condition_watch_labels("watch:audio::gGain>1"),
+            vec!["audio::gGain"]
+        );
+        assert_eq!(condition_watch_labels("(watch:gTempo>200)"), vec!["gTempo"]);
+        assert!(condition_watch_labels("args[0] > 100").is_empty());
+        assert!(condition_watch_labels("stopwatch:x > 1 || watch:1").is_empty());
+    }
+
+    #[test]
+    fn test_leak_events() {
+        let payload = serde_json::json!({
+            "timestampNs": 5_000,
+            "leakGroups": [
+                {
+                    "signature": "1a2b3c4d",
+                    "count": 3,
+                    "bytes": 384,
+                    "largest": 128,
+                    "samples": ["0x1000", "0x2000", "0x3000"],
+                    "backtrace": [
+                        { "address": "0x4010", "name": "cache::insert", "fileName": "cache.cpp", "lineNumber": 42 },
+                        { "address": "0x5020", "name": "main", "fileName": "", "lineNumber": 0 }
+                    ]
+                },
+                { "signature": "deadbeef", "count": 1, "bytes": 16, "largest": 16, "backtrace": [] }
+            ]
+        });
+        let events = leak_events("s-1", Some(7), &payload);
+        assert_eq!(events.len(), 2);
+
+        assert_eq!(events[0].event_type, crate::db::EventType::Leak);
+        assert_eq!(events[0].timestamp_ns, 5_000);
+        assert_eq!(events[0].function_name, "cache::insert");
+        assert_eq!(events[0].source_file.as_deref(), Some("cache.cpp"));
+        assert_eq!(events[0].line_number, Some(42));
+        assert_eq!(events[0].pid, Some(7));
+        assert_eq!(
+            events[0].text.as_deref(),
+            Some("3 allocations (384 bytes) never freed")
+        );
+        assert_eq!(
+            events[0].arguments.as_ref().unwrap()["signature"],
+            "1a2b3c4d"
+        );
+
+        assert_eq!(events[1].function_name, "<unknown>");
+        assert_eq!(events[1].source_file, None);
+        assert_eq!(
+            events[1].text.as_deref(),
+            Some("1 allocation (16 bytes) never freed")
+        );
+
+        assert!(leak_events("s-1", None, &serde_json::json!({})).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_instrumentation_carried_to_next_launch() {
+        let dir = tempfile::tempdir().unwrap();
+        let sm = SessionManager::new(&dir.path().join("strobe.db")).unwrap();
+        sm.create_session("app-1", "/bin/app", "/nonexistent-project", 0)
+            .unwrap();
+        sm.update_session_pid("app-1", 100).unwrap();
+        sm.add_patterns("app-1", &["net::*".to_string()]).unwrap();
+        sm.add_breakpoint(
+            "app-1",
+            Breakpoint {
+                id: "bp-1".to_string(),
+                target: BreakpointTarget::Function("net::send".to_string()),
+                address: 0x1000,
+                condition: None,
+                hit_count: 0,
+                hits: 0,
+            },
+        )
+        .unwrap();
+        sm.stop_session("app-1").await.unwrap();
+
+        let carried = sm
+            .carried_instrumentation("/nonexistent-project", "/bin/app")
+            .unwrap();
+        assert_eq!(carried.snapshot.session_id, "app-1");
+        assert_eq!(carried.snapshot.patterns, vec!["net::*"]);
+        assert_eq!(
+            carried.snapshot.breakpoints[0].function.as_deref(),
+            Some("net::send")
+        );
+        assert!(sm
+            .carried_instrumentation("/nonexistent-project", "/bin/other")
+            .is_none());
+        // Another project launching the same binary doesn't inherit it
+        assert!(sm
+            .carried_instrumentation("/other-project", "/bin/app")
+            .is_none());
+
+        // A launch that never spawned leaves it alone
+        sm.create_session("app-2", "/bin/app", "/nonexistent-project", 0)
+            .unwrap();
+        sm.stop_session("app-2").await.unwrap();
+        assert!(sm
+            .carried_instrumentation("/nonexistent-project", "/bin/app")
+            .is_some());
+
+        // A session that ran with nothing installed clears it
+        sm.create_session("app-3", "/bin/app", "/nonexistent-project", 0)
+            .unwrap();
+        sm.update_session_pid("app-3", 101).unwrap();
+        sm.stop_session_retain("app-3").await.unwrap();
+        assert!(sm
+            .carried_instrumentation("/nonexistent-project", "/bin/app")
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn test_reload_agent_preconditions() {
+        let dir = tempfile::tempdir().unwrap();
+        let sm = SessionManager::new(&dir.path().join("strobe.db")).unwrap();
+        let session_id = "reload-1";
+        sm.create_session(session_id, "/bin/app", "/nonexistent-project", 0)
+            .unwrap();
+
+        sm.add_paused_thread(
+            session_id,
+            7,
+            PauseInfo {
+                breakpoint_id: "bp1".to_string(),
+                func_name: None,
+                file: None,
+                line: None,
+                paused_at: Instant::now(),
+                return_address: None,
+                address: None,
+                backtrace: Vec::new(),
+                arguments: Vec::new(),
+            },
+        );
+        let err = sm
+            .reload_agent(session_id, "built-in", None)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("paused threads"), "{}", err);
+
+        sm.remove_paused_thread(session_id, 7);
+        sm.db()
+            .update_session_status(session_id, crate::db::SessionStatus::Exited)
+            .unwrap();
+        let err = sm
+            .reload_agent(session_id, "built-in", None)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("not running"), "{}", err);
+
+        assert!(matches!(
+            sm.reload_agent("missing", "built-in", None).await,
+            Err(crate::Error::SessionNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_breakpoint_replaced_by_id_at_limit() {
+        let dir = tempfile::tempdir().unwrap();
+        let sm = SessionManager::new(&dir.path().join("strobe.db")).unwrap();
+        let bp = |id: String| Breakpoint {
+            id,
+            target: BreakpointTarget::Function("f".to_string()),
+            address: 0x1000,
+            condition: None,
+            hit_count: 0,
+            hits: 0,
+        };
+        for i in 0..crate::mcp::MAX_BREAKPOINTS_PER_SESSION {
+            sm.add_breakpoint("s", bp(format!("bp-{}", i))).unwrap();
+        }
+        assert!(sm.add_breakpoint("s", bp("bp-new".to_string())).is_err());
+        // Re-setting an existing breakpoint (agent reload) is not a new one
+        sm.add_breakpoint("s", bp("bp-0".to_string())).unwrap();
+        assert_eq!(
+            sm.get_breakpoints("s").len(),
+            crate::mcp::MAX_BREAKPOINTS_PER_SESSION
+        );
+    }
+
     #[test]
     fn test_pause_with_return_address() {
         let temp_dir = std::env::temp_dir();
@@ -2837,6 +6763,50 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_detect_language_java_command() {
+        let temp = tempfile::tempdir().unwrap();
+        assert_eq!(
+            detect_language("/usr/lib/jvm/bin/java", temp.path()),
+            Language::Java
+        );
+        assert_eq!(
+            detect_language("/opt/app/service.jar", temp.path()),
+            Language::Java
+        );
+        // "javascript" must not be taken for a JVM
+        assert_eq!(
+            detect_language("./javascript-runner", temp.path()),
+            Language::Native
+        );
+
+        let (java, args) = jar_command("/opt/app/service.jar", &["--port".to_string()]).unwrap();
+        assert!(java.ends_with("java"));
+        assert_eq!(args, vec!["-jar", "/opt/app/service.jar", "--port"]);
+        assert!(jar_command("/usr/bin/java", &[]).is_none());
+    }
+
+    #[test]
+    fn test_normalize_patterns_per_language() {
+        let dir = tempfile::tempdir().unwrap();
+        let sm = SessionManager::new(&dir.path().join("strobe.db")).unwrap();
+        write_lock(&sm.languages).insert("py".to_string(), Language::Python);
+        write_lock(&sm.languages).insert("native".to_string(), Language::Native);
+
+        let patterns = vec![
+            "mypkg::handlers::*".to_string(),
+            "mypkg.models.**".to_string(),
+            "@file:handlers.py".to_string(),
+        ];
+        assert_eq!(
+            sm.normalize_patterns("py", &patterns),
+            vec!["mypkg.handlers.*", "mypkg.models.**", "@file:handlers.py"]
+        );
+        // Native names keep their `::`, and Go-style dots stay as written
+        assert_eq!(sm.normalize_patterns("native", &patterns), patterns);
+        assert_eq!(sm.normalize_patterns("unknown", &patterns), patterns);
+    }
+
     #[test]
     fn test_detect_language_project_files() {
         use std::fs;
@@ -0,0 +1,269 @@
+//! Settings hot reload.
+//!
+//! The daemon watches ~/.strobe and the `.strobe` directory of each running
+//! session's project for settings.json changes. On a change it resolves the
+//! settings again and applies what a running session can take: the event
+//! limit, downsampling, and the database's fsync and compression policy.
+//! Most other settings are read on every call already; `http.port` needs a
+//! daemon restart. Each running session whose settings changed since they
+//! were last applied to it (at launch, or by an earlier reload) gets a
+//! `config_change` event listing them. Daemon-wide keys are left out of
+//! those: a project can't change them.
+
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use notify::Watcher;
+
+use super::SessionManager;
+use crate::config::StrobeSettings;
+use crate::db::{Event, EventType};
+
+/// How often the watched directories are matched to the running sessions.
+/// Settings are also re-resolved then, which covers edits notify misses.
+const RESCAN_INTERVAL: Duration = Duration::from_secs(5);
+/// Editors save in several steps (temp file, rename); act once they settle.
+const SETTLE_DELAY: Duration = Duration::from_millis(200);
+/// Only read from ~/.strobe; project settings.json values for these are ignored.
+const DAEMON_WIDE_KEYS: &[&str] = &["db.syncMode", "db.compressPayloads", "http.port"];
+
+/// Global settings last applied, to tell what a reload changed. Sessions'
+/// baselines live in the SessionManager.
+pub struct SettingsReloader {
+    global: StrobeSettings,
+}
+
+impl SettingsReloader {
+    pub fn new() -> Self {
+        Self {
+            global: crate::config::resolve(None),
+        }
+    }
+
+    /// Resolve the settings again and apply whatever changed.
+    pub fn reload(&mut self, session_manager: &SessionManager) -> crate::Result<()> {
+        let global = crate::config::resolve(None);
+        let changes = describe_changes(&self.global, &global, &[]);
+        if !changes.is_empty() {
+            tracing::info!("Settings reloaded: {}", changes.join("; "));
+            let db = session_manager.db();
+            if global.db_sync_mode != self.global.db_sync_mode {
+                db.set_sync_mode(global.db_sync_mode)?;
+            }
+            db.set_payload_compression(global.db_compress_payloads);
+            if global.http_port != self.global.http_port {
+                tracing::warn!("http.port changed; restart the daemon to apply it");
+            }
+            self.global = global;
+        }
+
+        for session in session_manager.get_running_sessions()? {
+            let settings = crate::config::resolve(Some(Path::new(&session.project_root)));
+            // A session without a baseline wasn't created by this daemon
+            let Some(previous) = session_manager.applied_settings(&session.id) else {
+                session_manager.apply_settings(&session.id, &settings);
+                continue;
+            };
+            let changes = describe_changes(&previous, &settings, DAEMON_WIDE_KEYS);
+            if previous != settings {
+                session_manager.apply_settings(&session.id, &settings);
+            }
+            if !changes.is_empty() {
+                session_manager
+                    .db()
+                    .insert_event(&config_change_event(&session, &changes))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// One line per setting that differs, except the `skip` keys:
+/// "sampling.hz: 100 → 250".
+fn describe_changes(old: &StrobeSettings, new: &StrobeSettings, skip: &[&str]) -> Vec<String> {
+    old.keyed_values()
+        .into_iter()
+        .zip(new.keyed_values())
+        .filter(|((key, before), (_, after))| before != after && !skip.contains(key))
+        .map(|((key, before), (_, after))| match key {
+            // Profiles are too long to inline; debug_config shows them
+            "profiles" => "profiles".to_string(),
+            "http.port" => format!("{}: {} → {} (after a daemon restart)", key, before, after),
+            _ => format!("{}: {} → {}", key, before, after),
+        })
+        .collect()
+}
+
+fn config_change_event(session: &crate::db::Session, changes: &[String]) -> Event {
+    let now_ns = chrono::Utc::now().timestamp_nanos_opt().unwrap_or_default();
+    Event {
+        id: format!("{}-config-{}", session.id, now_ns),
+        session_id: session.id.clone(),
+        // Daemon-side events count from the session's start
+        timestamp_ns: now_ns - session.started_at * 1_000_000_000,
+        event_type: EventType::ConfigChange,
+        text: Some(format!("Settings changed: {}", changes.join("; "))),
+        ..Event::default()
+    }
+}
+
+/// Directories whose settings.json applies: ~/.strobe and each running
+/// session's project `.strobe`.
+fn settings_dirs(session_manager: &SessionManager) -> BTreeSet<PathBuf> {
+    let mut dirs: BTreeSet<PathBuf> = dirs::home_dir()
+        .map(|h| h.join(".strobe"))
+        .into_iter()
+        .collect();
+    for session in session_manager.get_running_sessions().unwrap_or_default() {
+        dirs.insert(Path::new(&session.project_root).join(".strobe"));
+    }
+    dirs
+}
+
+/// Watch the settings files until the daemon exits, reloading on changes.
+pub async fn watch_settings(session_manager: Arc<SessionManager>) {
+    let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+    let mut watcher =
+        match notify::recommended_watcher(move |result: notify::Result<notify::Event>| {
+            let Ok(event) = result else {
+                return;
+            };
+            if event
+                .paths
+                .iter()
+                .any(|p| p.file_name().is_some_and(|n| n == "settings.json"))
+            {
+                let _ = tx.send(());
+            }
+        }) {
+            Ok(watcher) => watcher,
+            Err(e) => {
+                tracing::warn!("Settings hot reload unavailable: {}", e);
+                return;
+            }
+        };
+
+    let mut reloader = SettingsReloader::new();
+    let mut watched: BTreeSet<PathBuf> = BTreeSet::new();
+    let mut rescan = tokio::time::interval(RESCAN_INTERVAL);
+    loop {
+        tokio::select! {
+            Some(()) = rx.recv() => {
+                tokio::time::sleep(SETTLE_DELAY).await;
+                while rx.try_recv().is_ok() {}
+            }
+            _ = rescan.tick() => {
+                let wanted = settings_dirs(&session_manager);
+                for dir in watched.difference(&wanted) {
+                    let _ = watcher.unwatch(dir);
+                }
+                watched.retain(|dir| wanted.contains(dir));
+                // A .strobe directory created later is picked up on a later scan
+                for dir in wanted {
+                    if !watched.contains(&dir)
+                        && dir.is_dir()
+                        && watcher
+                            .watch(&dir, notify::RecursiveMode::NonRecursive)
+                            .is_ok()
+                    {
+                        watched.insert(dir);
+                    }
+                }
+            }
+        }
+        // Reads settings files and writes events: keep it off the runtime
+        let manager = Arc::clone(&session_manager);
+        let result = tokio::task::spawn_blocking(move || {
+            let result = reloader.reload(&manager);
+            (reloader, result)
+        })
+        .await;
+        match result {
+            Ok((returned, result)) => {
+                reloader = returned;
+                if let Err(e) = result {
+                    tracing::warn!("Settings reload failed: {}", e);
+                }
+            }
+            Err(e) => {
+                tracing::error!("Settings reload task failed: {}", e);
+                return;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_describe_changes() {
+        let old = StrobeSettings::default();
+        assert!(describe_changes(&old, &old, &[]).is_empty());
+
+        let mut new = old.clone();
+        new.events_max_per_session = 500_000;
+        new.http_port = Some(8080);
+        new.profiles
+            .insert("demo".to_string(), crate::config::LaunchProfile::default());
+        assert_eq!(
+            describe_changes(&old, &new, &[]),
+            vec![
+                "events.maxPerSession: 200000 → 500000",
+                "http.port: null → 8080 (after a daemon restart)",
+                "profiles",
+            ]
+        );
+        assert_eq!(
+            describe_changes(&old, &new, DAEMON_WIDE_KEYS),
+            vec!["events.maxPerSession: 200000 → 500000", "profiles"]
+        );
+    }
+
+    #[test]
+    fn test_reload_applies_session_changes() {
+        let dir = tempfile::tempdir().unwrap();
+        let manager = SessionManager::new(&dir.path().join("test.db")).unwrap();
+        let project = dir.path().join("proj");
+        std::fs::create_dir_all(project.join(".strobe")).unwrap();
+        let settings_file = project.join(".strobe/settings.json");
+        std::fs::write(&settings_file, r#"{"events.maxPerSession": 300000}"#).unwrap();
+        manager
+            .create_session("s1", "/bin/app", project.to_str().unwrap(), 1234)
+            .unwrap();
+        assert_eq!(manager.get_event_limit("s1"), 300_000);
+
+        // Compared against what the session launched with, even on the
+        // reloader's first pass; project-level db keys are not reported
+        let mut reloader = SettingsReloader::new();
+        std::fs::write(
+            &settings_file,
+            r#"{"events.maxPerSession": 400000, "sampling.hz": 250, "db.syncMode": "full"}"#,
+        )
+        .unwrap();
+        reloader.reload(&manager).unwrap();
+
+        assert_eq!(manager.get_event_limit("s1"), 400_000);
+        let events = manager
+            .db()
+            .query_events("s1", |q| q.event_type(EventType::ConfigChange))
+            .unwrap();
+        assert_eq!(events.len(), 1);
+        let text = events[0].text.as_deref().unwrap();
+        assert_eq!(
+            text,
+            "Settings changed: events.maxPerSession: 300000 → 400000; sampling.hz: 100 → 250"
+        );
+
+        // Nothing changed, nothing recorded
+        reloader.reload(&manager).unwrap();
+        let events = manager
+            .db()
+            .query_events("s1", |q| q.event_type(EventType::ConfigChange))
+            .unwrap();
+        assert_eq!(events.len(), 1);
+    }
+}
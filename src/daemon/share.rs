@@ -0,0 +1,458 @@
+//! `strobe serve --session <id> --port N`: share one retained session
+//! read-only over HTTP, so someone else on the network can query the capture
+//! without exporting and importing files.
+//!
+//! Runs without the daemon, straight on ~/.strobe/strobe.db opened
+//! read-only. Binds 127.0.0.1 unless `--bind` names another interface. Every
+//! request needs the token printed at startup, either as `Authorization:
+//! Bearer` or as `?token=` (for browsers).
+//!
+//! ```text
+//! GET       /v1/session   → { sessionId, binaryPath, status, eventCount, ... }
+//! GET|POST  /v1/events    body or ?filter=<json>: debug_query filters
+//!                         → the debug_query response
+//! ```
+
+use super::http::{self, Request, Response};
+use crate::db::Database;
+use crate::mcp::{DebugQueryRequest, ErrorCode};
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::BufReader;
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::Semaphore;
+
+const USAGE: &str = "Usage: strobe serve --session <sessionId> --port <port> [--bind <address>]";
+/// Loopback only; exposing the session to the network takes `--bind`.
+const DEFAULT_BIND: &str = "127.0.0.1";
+/// Connections served at once; further clients wait in the accept backlog.
+const MAX_CONNECTIONS: usize = 16;
+
+#[derive(Debug, PartialEq)]
+struct ShareOptions {
+    session_id: String,
+    port: u16,
+    bind: String,
+}
+
+fn parse_args(args: &[String]) -> crate::Result<ShareOptions> {
+    let usage = || crate::Error::ValidationError(USAGE.to_string());
+    let mut session_id = None;
+    let mut port = None;
+    let mut bind = DEFAULT_BIND.to_string();
+    let mut args = args.iter();
+    while let Some(flag) = args.next() {
+        let value = args.next().ok_or_else(usage)?;
+        match flag.as_str() {
+            "--session" => session_id = Some(value.clone()),
+            "--port" => {
+                port = Some(value.parse::<u16>().map_err(|_| {
+                    crate::Error::ValidationError(format!("Invalid port '{}'", value))
+                })?)
+            }
+            "--bind" => bind = value.clone(),
+            _ => return Err(usage()),
+        }
+    }
+    Ok(ShareOptions {
+        session_id: session_id.ok_or_else(usage)?,
+        port: port.ok_or_else(usage)?,
+        bind,
+    })
+}
+
+struct Share {
+    db: Database,
+    session_id: String,
+    token: String,
+    query_timeout: Duration,
+}
+
+pub async fn serve_session(args: &[String]) -> crate::Result<()> {
+    let options = parse_args(args)?;
+    let db_path = dirs::home_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join(".strobe/strobe.db");
+    if !db_path.exists() {
+        return Err(crate::Error::SessionNotFound(options.session_id));
+    }
+    let db = Database::open_read_only(&db_path)?;
+    let session = db
+        .get_session(&options.session_id)?
+        .ok_or_else(|| crate::Error::SessionNotFound(options.session_id.clone()))?;
+    if !session.retained {
+        return Err(crate::Error::ValidationError(format!(
+            "Session '{}' is not retained. Stop it with debug_session({{ action: \"stop\", retain: true }}) first, so the daemon keeps it while it is shared.",
+            session.id
+        )));
+    }
+
+    let listener = TcpListener::bind((options.bind.as_str(), options.port)).await?;
+    let share = Arc::new(Share {
+        query_timeout: Duration::from_millis(
+            crate::config::resolve(Some(std::path::Path::new(&session.project_root)))
+                .query_timeout_ms,
+        ),
+        db,
+        session_id: session.id,
+        token: uuid::Uuid::new_v4().simple().to_string(),
+    });
+
+    let addr = listener.local_addr()?;
+    println!(
+        "Serving session {} read-only on http://{}",
+        share.session_id, addr
+    );
+    println!("  GET       /v1/session");
+    println!("  GET|POST  /v1/events   (debug_query filters as JSON body or ?filter=<json>)");
+    println!(
+        "Token: {}  (Authorization: Bearer <token>, or ?token=<token> in a browser)",
+        share.token
+    );
+    println!("Press Ctrl-C to stop.");
+
+    let slots = Arc::new(Semaphore::new(MAX_CONNECTIONS));
+    loop {
+        let permit = tokio::select! {
+            permit = Arc::clone(&slots).acquire_owned() => {
+                permit.expect("connection semaphore is never closed")
+            }
+            _ = tokio::signal::ctrl_c() => return Ok(()),
+        };
+        tokio::select! {
+            accepted = listener.accept() => match accepted {
+                Ok((stream, peer)) => {
+                    let share = Arc::clone(&share);
+                    tokio::spawn(async move {
+                        if let Err(e) = handle_connection(share, stream, peer).await {
+                            tracing::debug!("Share connection error: {}", e);
+                        }
+                        drop(permit);
+                    });
+                }
+                Err(e) => {
+                    tracing::error!("Share accept error: {}", e);
+                    tokio::time::sleep(Duration::from_millis(100)).await;
+                }
+            },
+            _ = tokio::signal::ctrl_c() => return Ok(()),
+        }
+    }
+}
+
+/// One request per connection (`Connection: close`). Queries run on the
+/// blocking pool so a slow one doesn't stall the runtime.
+async fn handle_connection(
+    share: Arc<Share>,
+    stream: TcpStream,
+    peer: SocketAddr,
+) -> crate::Result<()> {
+    let (reader, mut writer) = stream.into_split();
+    let mut reader = BufReader::new(reader);
+    let response =
+        match tokio::time::timeout(http::READ_TIMEOUT, http::read_request(&mut reader)).await {
+            Err(_) => Response::error(
+                408,
+                ErrorCode::ValidationError,
+                "Request not received in time",
+            ),
+            Ok(Err(response)) => response,
+            Ok(Ok(request)) => {
+                tracing::info!("{} {} {}", peer, request.method, path_only(&request.path));
+                tokio::task::spawn_blocking(move || handle_request(&share, &request))
+                    .await
+                    .unwrap_or_else(|e| {
+                        Response::error(
+                            500,
+                            ErrorCode::InternalError,
+                            format!("Request failed: {}", e),
+                        )
+                    })
+            }
+        };
+    http::write_response(&mut writer, &response).await
+}
+
+fn handle_request(share: &Share, request: &Request) -> Response {
+    let authorization = request
+        .authorization
+        .clone()
+        .or_else(|| query_param(&request.path, "token").map(|token| format!("Bearer {}", token)));
+    if !http::token_matches(authorization.as_deref(), &share.token) {
+        return Response::error(
+            401,
+            ErrorCode::ValidationError,
+            "Missing or wrong token (printed by strobe serve)",
+        );
+    }
+
+    let method = request.method.as_str();
+    match (path_only(&request.path).trim_end_matches('/'), method) {
+        ("" | "/v1/session", "GET") => match session_overview(share) {
+            Ok(overview) => Response::ok(overview),
+            Err(e) => e.into(),
+        },
+        ("/v1/events", "GET" | "POST") => match query(share, request) {
+            Ok(events) => Response::ok(events),
+            Err(response) => response,
+        },
+        ("" | "/v1/session" | "/v1/events", _) => Response::error(
+            405,
+            ErrorCode::ValidationError,
+            "This share is read-only. Use GET /v1/session or GET|POST /v1/events",
+        ),
+        _ => Response::error(
+            404,
+            ErrorCode::ValidationError,
+            "Not found. Use GET /v1/session or GET|POST /v1/events",
+        ),
+    }
+}
+
+fn session_overview(share: &Share) -> crate::Result<serde_json::Value> {
+    let session = share
+        .db
+        .get_session(&share.session_id)?
+        .ok_or_else(|| crate::Error::SessionNotFound(share.session_id.clone()))?;
+    Ok(serde_json::json!({
+        "sessionId": session.id,
+        "binaryPath": session.binary_path,
+        "projectRoot": session.project_root,
+        "pid": session.pid,
+        "status": session.status.as_str(),
+        "startedAt": session.started_at,
+        "endedAt": session.ended_at,
+        "retainedAt": session.retained_at,
+        "eventCount": share.db.count_session_events(&session.id)?,
+    }))
+}
+
+/// debug_query over the shared session; filters come from the body, else
+/// from `?filter=`. Any sessionId in them is replaced by the shared one.
+fn query(share: &Share, request: &Request) -> std::result::Result<serde_json::Value, Response> {
+    let mut args = match query_param(&request.path, "filter") {
+        Some(filter) if request.body.iter().all(u8::is_ascii_whitespace) => {
+            http::parse_body(filter.as_bytes())?
+        }
+        _ => http::parse_body(&request.body)?,
+    };
+    if let Some(obj) = args.as_object_mut() {
+        obj.remove("sessionId");
+    }
+    let filters = args.clone();
+    args["sessionId"] = serde_json::json!(share.session_id);
+
+    let req: DebugQueryRequest = serde_json::from_value(args).map_err(|e| {
+        Response::error(
+            400,
+            ErrorCode::ValidationError,
+            format!("Invalid filter: {}", e),
+        )
+    })?;
+    req.validate()?;
+    if req.group_field().is_some() {
+        let response =
+            super::server::query_event_groups(&share.db, &req, filters, share.query_timeout)?;
+        return Ok(serde_json::to_value(response).map_err(crate::Error::from)?);
+    }
+    let response =
+        super::server::query_stored_events(&share.db, &req, filters, share.query_timeout)?;
+    Ok(serde_json::to_value(response).map_err(crate::Error::from)?)
+}
+
+fn path_only(path: &str) -> &str {
+    path.split('?').next().unwrap_or(path)
+}
+
+/// Percent-decoded value of a query string parameter.
+fn query_param(path: &str, name: &str) -> Option<String> {
+    let (_, query) = path.split_once('?')?;
+    query.split('&').find_map(|pair| {
+        let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+        (key == name).then(|| percent_decode(value))
+    })
+}
+
+fn percent_decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes
+            .get(i + 1..i + 3)
+            .and_then(|h| std::str::from_utf8(h).ok())
+            .and_then(|h| u8::from_str_radix(h, 16).ok());
+        match (bytes[i], hex) {
+            (b'%', Some(byte)) => {
+                out.push(byte);
+                i += 3;
+                continue;
+            }
+            (b'+', _) => out.push(b' '),
+            (byte, _) => out.push(byte),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_parse_args() {
+        let options = parse_args(&args(&["--session", "app-1", "--port", "7880"])).unwrap();
+        assert_eq!(
+            options,
+            ShareOptions {
+                session_id: "app-1".to_string(),
+                port: 7880,
+                bind: DEFAULT_BIND.to_string(),
+            }
+        );
+        let options = parse_args(&args(&[
+            "--port",
+            "7880",
+            "--bind",
+            "0.0.0.0",
+            "--session",
+            "app-1",
+        ]))
+        .unwrap();
+        assert_eq!(options.bind, "0.0.0.0");
+
+        assert!(parse_args(&args(&["--session", "app-1"])).is_err());
+        assert!(parse_args(&args(&["--session", "app-1", "--port", "http"])).is_err());
+        assert!(parse_args(&args(&["--session"])).is_err());
+        assert!(parse_args(&args(&["--session", "a", "--port", "1", "--force", "x"])).is_err());
+    }
+
+    #[test]
+    fn test_query_param_decodes() {
+        let path = "/v1/events?token=abc&filter=%7B%22eventType%22%3A%22stderr%22%7D&x";
+        assert_eq!(query_param(path, "token").as_deref(), Some("abc"));
+        assert_eq!(
+            query_param(path, "filter").as_deref(),
+            Some(r#"{"eventType":"stderr"}"#)
+        );
+        assert_eq!(query_param(path, "x").as_deref(), Some(""));
+        assert_eq!(query_param(path, "missing"), None);
+        assert_eq!(percent_decode("a+b%2"), "a b%2");
+    }
+
+    fn share_with_events() -> Share {
+        let db = Database::open_in_memory().unwrap();
+        db.create_session("s1", "/bin/app", "/home", 1).unwrap();
+        db.insert_events_batch(&[
+            crate::db::Event {
+                id: "e1".into(),
+                session_id: "s1".into(),
+                timestamp_ns: 1,
+                event_type: crate::db::EventType::Stderr,
+                text: Some("boom".into()),
+                ..Default::default()
+            },
+            crate::db::Event {
+                id: "e2".into(),
+                session_id: "s1".into(),
+                timestamp_ns: 2,
+                function_name: "app::run".into(),
+                ..Default::default()
+            },
+        ])
+        .unwrap();
+        Share {
+            db,
+            session_id: "s1".to_string(),
+            token: "secret".to_string(),
+            query_timeout: Duration::from_secs(5),
+        }
+    }
+
+    fn request(method: &str, path: &str, authorization: Option<&str>, body: &str) -> Request {
+        Request {
+            method: method.to_string(),
+            path: path.to_string(),
+            authorization: authorization.map(String::from),
+            last_event_id: None,
+            body: body.as_bytes().to_vec(),
+        }
+    }
+
+    #[test]
+    fn test_requests_need_token_and_stay_read_only() {
+        let share = share_with_events();
+
+        let response = handle_request(&share, &request("GET", "/v1/session", None, ""));
+        assert_eq!(response.status, 401);
+
+        let response = handle_request(
+            &share,
+            &request("GET", "/v1/session?token=secret", None, ""),
+        );
+        assert_eq!(response.status, 200);
+        assert_eq!(response.body["sessionId"], "s1");
+        assert_eq!(response.body["eventCount"], 2);
+
+        let response = handle_request(
+            &share,
+            &request("DELETE", "/v1/events", Some("Bearer secret"), ""),
+        );
+        assert_eq!(response.status, 405);
+        let response = handle_request(
+            &share,
+            &request("GET", "/v1/tools", Some("Bearer secret"), ""),
+        );
+        assert_eq!(response.status, 404);
+    }
+
+    #[test]
+    fn test_events_use_query_filters_on_shared_session() {
+        let share = share_with_events();
+
+        // Body filter; a sessionId in it cannot point elsewhere
+        let response = handle_request(
+            &share,
+            &request(
+                "POST",
+                "/v1/events",
+                Some("Bearer secret"),
+                r#"{"sessionId":"other","eventType":"stderr"}"#,
+            ),
+        );
+        assert_eq!(response.status, 200);
+        assert_eq!(response.body["totalCount"], 1);
+        assert_eq!(response.body["events"][0]["text"], "boom");
+
+        // Browser-style filter in the query string
+        let response = handle_request(
+            &share,
+            &request(
+                "GET",
+                "/v1/events?token=secret&filter=%7B%22function%22%3A%7B%22contains%22%3A%22run%22%7D%7D",
+                None,
+                "",
+            ),
+        );
+        assert_eq!(response.status, 200);
+        assert_eq!(response.body["totalCount"], 1);
+
+        let response = handle_request(
+            &share,
+            &request(
+                "POST",
+                "/v1/events",
+                Some("Bearer secret"),
+                r#"{"limit":"x"}"#,
+            ),
+        );
+        assert_eq!(response.status, 400);
+    }
+}
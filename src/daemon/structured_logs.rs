@@ -0,0 +1,319 @@
+//! Structured log parsing (`debug_launch({ logFormat })`).
+//!
+//! The event writer splits each stdout/stderr event into one event per line
+//! and parses every line as a JSON object or a logfmt record. Parsed fields go
+//! into `events.log_fields`, where `debug_query({ fields })` can filter on
+//! them. Lines that don't parse are stored as plain output.
+//!
+//! A read can end mid-line, so each stream's unterminated last line is held
+//! until the rest of it arrives, for up to `PARTIAL_LINE_HOLD`.
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use crate::db::{Event, EventType};
+use crate::mcp::LogFormat;
+
+/// How long an unterminated line waits for its newline before it is stored
+/// as is (a prompt, or a process that never finishes the line).
+pub const PARTIAL_LINE_HOLD: Duration = Duration::from_millis(500);
+/// A partial line this long is stored without waiting.
+const MAX_PARTIAL_LINE: usize = 64 * 1024;
+
+/// One output stream: (pid, is stderr).
+type StreamKey = (Option<u32>, bool);
+
+/// Splits output events into lines and parses them for one session's writer.
+#[derive(Default)]
+pub struct LineBuffer {
+    /// None stores output events unsplit
+    format: Option<LogFormat>,
+    /// Per-stream unterminated line and when its first piece arrived
+    held: HashMap<StreamKey, (Event, Instant)>,
+}
+
+impl LineBuffer {
+    pub fn new(format: Option<LogFormat>) -> Self {
+        Self {
+            format,
+            held: HashMap::new(),
+        }
+    }
+
+    /// Accept one event; returns the events now ready to store, one per
+    /// complete line for output events. Other events pass through unchanged.
+    pub fn push(&mut self, event: Event, now: Instant) -> Vec<Event> {
+        let Some(format) = self.format else {
+            return vec![event];
+        };
+        if !matches!(event.event_type, EventType::Stdout | EventType::Stderr) {
+            return vec![event];
+        }
+        let Some(text) = event.text.clone() else {
+            return vec![event];
+        };
+        let key = (event.pid, event.event_type == EventType::Stderr);
+
+        let mut rest = text.as_str();
+        let mut lines = Vec::new();
+        if let Some((mut held, since)) = self.held.remove(&key) {
+            let end = rest.find('\n').map_or(rest.len(), |i| i + 1);
+            held.text
+                .get_or_insert_with(String::new)
+                .push_str(&rest[..end]);
+            rest = &rest[end..];
+            lines.push((held, since));
+        }
+        // Suffix ids stay deterministic so a retried batch still dedups.
+        lines.extend(rest.split_inclusive('\n').enumerate().map(|(i, line)| {
+            let line = Event {
+                id: if i == 0 {
+                    event.id.clone()
+                } else {
+                    format!("{}:{}", event.id, i)
+                },
+                text: Some(line.to_string()),
+                ..event.clone()
+            };
+            (line, now)
+        }));
+
+        if let Some((last, _)) = lines.last() {
+            let text = last.text.as_deref().unwrap_or_default();
+            if !text.ends_with('\n') && text.len() < MAX_PARTIAL_LINE {
+                if let Some(partial) = lines.pop() {
+                    self.held.insert(key, partial);
+                }
+            }
+        }
+        lines
+            .into_iter()
+            .map(|(line, _)| with_fields(format, line))
+            .collect()
+    }
+
+    /// Release partial lines held past `PARTIAL_LINE_HOLD`.
+    pub fn expire(&mut self, now: Instant) -> Vec<Event> {
+        let expired: Vec<StreamKey> = self
+            .held
+            .iter()
+            .filter(|(_, (_, since))| now.duration_since(*since) >= PARTIAL_LINE_HOLD)
+            .map(|(key, _)| *key)
+            .collect();
+        expired
+            .into_iter()
+            .filter_map(|key| self.release(key))
+            .collect()
+    }
+
+    /// Release every partial line (writer shutdown).
+    pub fn drain_all(&mut self) -> Vec<Event> {
+        let keys: Vec<StreamKey> = self.held.keys().copied().collect();
+        keys.into_iter()
+            .filter_map(|key| self.release(key))
+            .collect()
+    }
+
+    fn release(&mut self, key: StreamKey) -> Option<Event> {
+        let (line, _) = self.held.remove(&key)?;
+        Some(match self.format {
+            Some(format) => with_fields(format, line),
+            None => line,
+        })
+    }
+}
+
+fn with_fields(format: LogFormat, line: Event) -> Event {
+    let fields = line
+        .text
+        .as_deref()
+        .and_then(|text| parse_line(format, text));
+    Event {
+        log_fields: fields,
+        ..line
+    }
+}
+
+/// Parse one line into a JSON object of fields, or None if it isn't a record.
+pub fn parse_line(format: LogFormat, line: &str) -> Option<serde_json::Value> {
+    let line = line.trim();
+    if line.is_empty() {
+        return None;
+    }
+    match format {
+        LogFormat::Json => parse_json(line),
+        LogFormat::Logfmt => parse_logfmt(line),
+    }
+}
+
+fn parse_json(line: &str) -> Option<serde_json::Value> {
+    if !line.starts_with('{') {
+        return None;
+    }
+    match serde_json::from_str::<serde_json::Value>(line) {
+        Ok(v @ serde_json::Value::Object(_)) => Some(v),
+        _ => None,
+    }
+}
+
+/// logfmt: space-separated `key=value` pairs, values optionally double-quoted
+/// with backslash escapes. A bare `key` is a true flag. A line needs at least
+/// one `key=value` pair to count as a record.
+fn parse_logfmt(line: &str) -> Option<serde_json::Value> {
+    let mut fields = serde_json::Map::new();
+    let mut has_pair = false;
+    let mut chars = line.chars().peekable();
+
+    loop {
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+        if chars.peek().is_none() {
+            break;
+        }
+
+        let mut key = String::new();
+        while let Some(c) = chars.next_if(|c| !c.is_whitespace() && *c != '=') {
+            key.push(c);
+        }
+        if key.is_empty() || key.contains('"') {
+            return None;
+        }
+
+        if chars.next_if_eq(&'=').is_none() {
+            fields.insert(key, serde_json::Value::Bool(true));
+            continue;
+        }
+        has_pair = true;
+
+        let mut value = String::new();
+        if chars.next_if_eq(&'"').is_some() {
+            let mut closed = false;
+            while let Some(c) = chars.next() {
+                match c {
+                    '\\' => match chars.next() {
+                        Some('n') => value.push('\n'),
+                        Some('t') => value.push('\t'),
+                        Some(other) => value.push(other),
+                        None => break,
+                    },
+                    '"' => {
+                        closed = true;
+                        break;
+                    }
+                    _ => value.push(c),
+                }
+            }
+            if !closed {
+                return None;
+            }
+        } else {
+            while let Some(c) = chars.next_if(|c| !c.is_whitespace()) {
+                value.push(c);
+            }
+        }
+        fields.insert(key, serde_json::Value::String(value));
+    }
+
+    if has_pair {
+        Some(serde_json::Value::Object(fields))
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stdout(text: &str) -> Event {
+        Event {
+            id: "s1-out-1".into(),
+            session_id: "s1".into(),
+            event_type: EventType::Stdout,
+            text: Some(text.into()),
+            pid: Some(42),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_parse_logfmt() {
+        let v = parse_line(
+            LogFormat::Logfmt,
+            r#"level=error module=auth msg="login failed: \"bob\"" retry"#,
+        )
+        .unwrap();
+        assert_eq!(v["level"], "error");
+        assert_eq!(v["module"], "auth");
+        assert_eq!(v["msg"], "login failed: \"bob\"");
+        assert_eq!(v["retry"], true);
+
+        assert!(parse_line(LogFormat::Logfmt, "plain text line").is_none());
+        assert!(parse_line(LogFormat::Logfmt, r#"msg="unterminated"#).is_none());
+    }
+
+    #[test]
+    fn test_parse_json() {
+        let v = parse_line(LogFormat::Json, "{\"level\":\"info\",\"n\":3}\n").unwrap();
+        assert_eq!(v["level"], "info");
+        assert_eq!(v["n"], 3);
+        assert!(parse_line(LogFormat::Json, "[1, 2]").is_none());
+        assert!(parse_line(LogFormat::Json, "{not json").is_none());
+    }
+
+    #[test]
+    fn test_split_multiline_chunk() {
+        let mut lines = LineBuffer::new(Some(LogFormat::Json));
+        let events = lines.push(
+            stdout("{\"level\":\"warn\"}\nnot a record\n{\"level\":\"error\"}\n"),
+            Instant::now(),
+        );
+        assert_eq!(events.len(), 3);
+        assert_eq!(events[0].id, "s1-out-1");
+        assert_eq!(events[1].id, "s1-out-1:1");
+        assert_eq!(events[2].id, "s1-out-1:2");
+        assert_eq!(events[0].log_fields.as_ref().unwrap()["level"], "warn");
+        assert!(events[1].log_fields.is_none());
+        assert_eq!(events[1].text.as_deref(), Some("not a record\n"));
+        assert_eq!(events[2].pid, Some(42));
+
+        let mut lines = LineBuffer::new(Some(LogFormat::Logfmt));
+        let single = lines.push(stdout("level=info\n"), Instant::now());
+        assert_eq!(single.len(), 1);
+        assert_eq!(single[0].id, "s1-out-1");
+        assert_eq!(single[0].log_fields.as_ref().unwrap()["level"], "info");
+    }
+
+    #[test]
+    fn test_partial_line_joins_next_read() {
+        let mut lines = LineBuffer::new(Some(LogFormat::Logfmt));
+        let now = Instant::now();
+        assert!(lines.push(stdout("level=warn msg=\"disk"), now).is_empty());
+
+        let mut next = stdout(" full\"\nlevel=info\n");
+        next.id = "s1-out-2".into();
+        let events = lines.push(next, now);
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].id, "s1-out-1");
+        assert_eq!(events[0].log_fields.as_ref().unwrap()["msg"], "disk full");
+        assert_eq!(events[1].id, "s1-out-2");
+
+        // stderr is a separate stream
+        let mut err = stdout("level=error");
+        err.event_type = EventType::Stderr;
+        assert!(lines.push(err, now).is_empty());
+        assert!(lines.expire(now).is_empty());
+        let expired = lines.expire(now + PARTIAL_LINE_HOLD);
+        assert_eq!(expired.len(), 1);
+        assert_eq!(expired[0].log_fields.as_ref().unwrap()["level"], "error");
+        assert!(lines.drain_all().is_empty());
+    }
+
+    #[test]
+    fn test_unformatted_output_passes_through() {
+        let mut lines = LineBuffer::new(None);
+        let events = lines.push(stdout("a\nb"), Instant::now());
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].text.as_deref(), Some("a\nb"));
+        assert!(lines.drain_all().is_empty());
+    }
+}
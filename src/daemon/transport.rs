@@ -0,0 +1,165 @@
+//! Local transport between the stdio proxy and the daemon: a Unix socket at
+//! `~/.strobe/strobe.sock`, or a per-user named pipe on Windows.
+
+use std::path::{Path, PathBuf};
+
+#[cfg(unix)]
+pub type ServerStream = tokio::net::UnixStream;
+#[cfg(unix)]
+pub type ClientStream = tokio::net::UnixStream;
+
+#[cfg(windows)]
+pub type ServerStream = tokio::net::windows::named_pipe::NamedPipeServer;
+#[cfg(windows)]
+pub type ClientStream = tokio::net::windows::named_pipe::NamedPipeClient;
+
+/// Where the daemon listens. Named pipes live in their own namespace on
+/// Windows, so the user name keeps two accounts' daemons apart.
+pub fn endpoint(strobe_dir: &Path) -> PathBuf {
+    #[cfg(unix)]
+    {
+        strobe_dir.join("strobe.sock")
+    }
+    #[cfg(windows)]
+    {
+        let _ = strobe_dir;
+        let user = std::env::var("USERNAME").unwrap_or_else(|_| "default".to_string());
+        PathBuf::from(format!(r"\\.\pipe\strobe-{}", user))
+    }
+}
+
+pub struct Listener {
+    #[cfg(unix)]
+    inner: tokio::net::UnixListener,
+    #[cfg(windows)]
+    name: PathBuf,
+    /// The instance the next client connects to; replaced on every accept so
+    /// there is always one waiting.
+    #[cfg(windows)]
+    next: ServerStream,
+}
+
+impl Listener {
+    pub fn bind(endpoint: &Path) -> std::io::Result<Self> {
+        #[cfg(unix)]
+        {
+            // Remove stale socket
+            let _ = std::fs::remove_file(endpoint);
+            Ok(Self {
+                inner: tokio::net::UnixListener::bind(endpoint)?,
+            })
+        }
+        #[cfg(windows)]
+        {
+            use tokio::net::windows::named_pipe::ServerOptions;
+            let next = ServerOptions::new()
+                .first_pipe_instance(true)
+                .create(endpoint)?;
+            Ok(Self {
+                name: endpoint.to_path_buf(),
+                next,
+            })
+        }
+    }
+
+    /// Wait for the next client. Cancel safe, so it can sit in `select!`.
+    pub async fn accept(&mut self) -> std::io::Result<ServerStream> {
+        #[cfg(unix)]
+        {
+            self.inner.accept().await.map(|(stream, _)| stream)
+        }
+        #[cfg(windows)]
+        {
+            use tokio::net::windows::named_pipe::ServerOptions;
+            self.next.connect().await?;
+            let fresh = ServerOptions::new().create(&self.name)?;
+            Ok(std::mem::replace(&mut self.next, fresh))
+        }
+    }
+}
+
+pub async fn connect(endpoint: &Path) -> std::io::Result<ClientStream> {
+    #[cfg(unix)]
+    {
+        tokio::net::UnixStream::connect(endpoint).await
+    }
+    #[cfg(windows)]
+    {
+        tokio::net::windows::named_pipe::ClientOptions::new().open(endpoint)
+    }
+}
+
+/// Probe whether a daemon is actually serving requests on `endpoint`.
+///
+/// This is more reliable than PID-based liveness on macOS: a "UE" zombie
+/// (process stuck inside `proc_exit`) still appears in the kernel proc table
+/// with `pbi_status == SRUN` and `kill(pid, 0) == 0`, so naive checks lie.
+/// What it cannot do is `accept()` — so we just try to connect with a short
+/// timeout. A real daemon accepts and we return true; a stale socket either
+/// fails to connect or hangs past the timeout, and we return false.
+pub fn responsive(endpoint: &Path) -> bool {
+    #[cfg(unix)]
+    {
+        if !endpoint.exists() {
+            return false;
+        }
+        // Use the std (blocking) UnixStream so this works before the tokio runtime
+        // is fully wired into the lock-acquisition path.
+        std::os::unix::net::UnixStream::connect_addr(
+            &match std::os::unix::net::SocketAddr::from_pathname(endpoint) {
+                Ok(addr) => addr,
+                Err(_) => return false,
+            },
+        )
+        .map(|s| {
+            let _ = s.set_read_timeout(Some(std::time::Duration::from_millis(500)));
+            let _ = s.shutdown(std::net::Shutdown::Both);
+        })
+        .is_ok()
+    }
+    #[cfg(windows)]
+    {
+        // Opening the pipe as a file connects to a waiting server instance
+        std::fs::OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open(endpoint)
+            .is_ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+    #[tokio::test]
+    async fn test_listener_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let endpoint = endpoint(dir.path());
+        let mut listener = Listener::bind(&endpoint).unwrap();
+
+        let server = tokio::spawn(async move {
+            let stream = listener.accept().await.unwrap();
+            let (reader, mut writer) = tokio::io::split(stream);
+            let mut line = String::new();
+            BufReader::new(reader).read_line(&mut line).await.unwrap();
+            writer.write_all(line.as_bytes()).await.unwrap();
+        });
+
+        let stream = connect(&endpoint).await.unwrap();
+        let (reader, mut writer) = tokio::io::split(stream);
+        writer.write_all(b"ping\n").await.unwrap();
+        let mut line = String::new();
+        BufReader::new(reader).read_line(&mut line).await.unwrap();
+        assert_eq!(line, "ping\n");
+        server.await.unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_responsive_false_without_daemon() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(!responsive(&endpoint(dir.path())));
+    }
+}
@@ -1,6 +1,24 @@
-use rusqlite::params;
+use rusqlite::{params, OptionalExtension};
 use std::collections::HashMap;
 
+/// What the baselines table knows about one test, for the test inventory.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TestHistory {
+    /// Average of the last 10 passing runs; None if it never passed.
+    pub estimated_duration_ms: Option<u64>,
+    /// Status of the most recent recorded run.
+    pub last_status: String,
+}
+
+/// Spread of a test's recent passing durations, for adaptive stuck thresholds.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BaselineStats {
+    pub mean_ms: u64,
+    pub stddev_ms: u64,
+    /// Passing runs the statistics are drawn from (at most 10).
+    pub samples: u32,
+}
+
 impl super::Database {
     pub fn record_test_baseline(
         &self,
@@ -19,6 +37,38 @@ impl super::Database {
         Ok(())
     }
 
+    /// Store a run's provenance JSON as the project's latest, replacing the
+    /// previous run's.
+    pub fn record_test_provenance(
+        &self,
+        project_root: &str,
+        provenance: &str,
+    ) -> crate::Result<()> {
+        let conn = self.connection();
+        let now = chrono::Utc::now().timestamp();
+        conn.execute(
+            "INSERT INTO test_provenance (project_root, provenance, recorded_at)
+             VALUES (?1, ?2, ?3)
+             ON CONFLICT(project_root) DO UPDATE SET
+                provenance = excluded.provenance,
+                recorded_at = excluded.recorded_at",
+            params![project_root, provenance, now],
+        )?;
+        Ok(())
+    }
+
+    /// Provenance JSON of the project's most recent run that recorded one.
+    pub fn last_test_provenance(&self, project_root: &str) -> crate::Result<Option<String>> {
+        let conn = self.connection();
+        Ok(conn
+            .query_row(
+                "SELECT provenance FROM test_provenance WHERE project_root = ?1",
+                params![project_root],
+                |row| row.get::<_, String>(0),
+            )
+            .optional()?)
+    }
+
     pub fn get_test_baseline(
         &self,
         test_name: &str,
@@ -60,6 +110,131 @@ impl super::Database {
         Ok(map)
     }
 
+    /// Mean and standard deviation of each test's last 10 passing runs.
+    pub fn get_project_baseline_stats(
+        &self,
+        project_root: &str,
+    ) -> crate::Result<HashMap<String, BaselineStats>> {
+        let conn = self.connection();
+        let mut stmt = conn.prepare(
+            "SELECT test_name, duration_ms FROM (
+                SELECT test_name, duration_ms,
+                    ROW_NUMBER() OVER (
+                        PARTITION BY test_name ORDER BY recorded_at DESC, id DESC
+                    ) as rn
+                FROM test_baselines
+                WHERE project_root = ?1 AND status IN ('pass', 'passed')
+            ) WHERE rn <= 10",
+        )?;
+        let rows = stmt.query_map(params![project_root], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?))
+        })?;
+        let mut durations: HashMap<String, Vec<f64>> = HashMap::new();
+        for row in rows {
+            let (name, ms) = row?;
+            durations.entry(name).or_default().push(ms as f64);
+        }
+        Ok(durations
+            .into_iter()
+            .map(|(name, samples)| {
+                let n = samples.len() as f64;
+                let mean = samples.iter().sum::<f64>() / n;
+                let variance = samples.iter().map(|d| (d - mean).powi(2)).sum::<f64>() / n;
+                let stats = BaselineStats {
+                    mean_ms: mean.round() as u64,
+                    stddev_ms: variance.sqrt().round() as u64,
+                    samples: samples.len() as u32,
+                };
+                (name, stats)
+            })
+            .collect())
+    }
+
+    /// Tests that failed at least once in their last `window` recorded runs,
+    /// most recent failure first. Status strings come from `TestStatus::as_str`
+    /// ("fail"); "failed" is accepted too.
+    pub fn get_recent_failures(
+        &self,
+        project_root: &str,
+        window: usize,
+    ) -> crate::Result<Vec<String>> {
+        let conn = self.connection();
+        let mut stmt = conn.prepare(
+            "SELECT test_name FROM (
+                SELECT id, test_name, status,
+                    ROW_NUMBER() OVER (
+                        PARTITION BY test_name ORDER BY recorded_at DESC, id DESC
+                    ) as rn
+                FROM test_baselines
+                WHERE project_root = ?1
+            ) WHERE rn <= ?2 AND status IN ('fail', 'failed')
+            GROUP BY test_name
+            ORDER BY MAX(id) DESC",
+        )?;
+        let rows = stmt.query_map(params![project_root, window as i64], |row| {
+            row.get::<_, String>(0)
+        })?;
+        Ok(rows.collect::<std::result::Result<Vec<_>, _>>()?)
+    }
+
+    /// Every test name with recorded history for this project.
+    pub fn get_known_tests(&self, project_root: &str) -> crate::Result<Vec<String>> {
+        let conn = self.connection();
+        let mut stmt = conn.prepare(
+            "SELECT DISTINCT test_name FROM test_baselines WHERE project_root = ?1
+             ORDER BY test_name",
+        )?;
+        let rows = stmt.query_map(params![project_root], |row| row.get::<_, String>(0))?;
+        Ok(rows.collect::<std::result::Result<Vec<_>, _>>()?)
+    }
+
+    /// Per-test duration estimate and last status for a project. Accepts both
+    /// the "pass"/"fail" spelling recorded by runs and the older "passed".
+    pub fn get_test_history(
+        &self,
+        project_root: &str,
+    ) -> crate::Result<HashMap<String, TestHistory>> {
+        let conn = self.connection();
+        let mut stmt = conn.prepare(
+            "SELECT test_name,
+                (SELECT AVG(duration_ms) FROM (
+                    SELECT duration_ms FROM test_baselines p
+                    WHERE p.test_name = r.test_name AND p.project_root = ?1
+                        AND p.status IN ('pass', 'passed')
+                    ORDER BY recorded_at DESC, id DESC
+                    LIMIT 10
+                )),
+                status
+            FROM (
+                SELECT test_name, status,
+                    ROW_NUMBER() OVER (
+                        PARTITION BY test_name ORDER BY recorded_at DESC, id DESC
+                    ) as rn
+                FROM test_baselines
+                WHERE project_root = ?1
+            ) r WHERE rn = 1",
+        )?;
+        let rows = stmt.query_map(params![project_root], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, Option<f64>>(1)?,
+                row.get::<_, String>(2)?,
+            ))
+        })?;
+        let mut map = HashMap::new();
+        for row in rows {
+            let (name, avg, status) = row?;
+            map.insert(
+                name,
+                TestHistory {
+                    estimated_duration_ms: avg.map(|a| a.round() as u64),
+                    last_status: status,
+                },
+            );
+        }
+        Ok(map)
+    }
+
     pub fn cleanup_old_baselines(&self, project_root: &str) -> crate::Result<()> {
         let conn = self.connection();
         conn.execute(
@@ -124,6 +299,64 @@ mod tests {
         assert_eq!(baselines.get("test_b"), Some(&1500));
     }
 
+    #[test]
+    fn test_project_baseline_stats() {
+        let db = Database::open_in_memory().unwrap();
+
+        for ms in [800, 1000, 1200] {
+            db.record_test_baseline("test_a", "/project", ms, "pass")
+                .unwrap();
+        }
+        db.record_test_baseline("test_a", "/project", 60_000, "fail")
+            .unwrap();
+        db.record_test_baseline("test_b", "/project", 500, "passed")
+            .unwrap();
+
+        let stats = db.get_project_baseline_stats("/project").unwrap();
+        assert_eq!(stats["test_a"].mean_ms, 1000);
+        assert_eq!(stats["test_a"].stddev_ms, 163); // population stddev of 800/1000/1200
+        assert_eq!(stats["test_a"].samples, 3);
+        assert_eq!(stats["test_b"].stddev_ms, 0);
+    }
+
+    #[test]
+    fn test_test_history() {
+        let db = Database::open_in_memory().unwrap();
+
+        db.record_test_baseline("test_a", "/project", 400, "pass")
+            .unwrap();
+        db.record_test_baseline("test_a", "/project", 600, "passed")
+            .unwrap();
+        db.record_test_baseline("test_a", "/project", 9000, "fail")
+            .unwrap();
+        db.record_test_baseline("test_b", "/project", 50, "skip")
+            .unwrap();
+        db.record_test_baseline("test_c", "/other", 10, "pass")
+            .unwrap();
+
+        let history = db.get_test_history("/project").unwrap();
+        assert_eq!(history.len(), 2);
+        assert_eq!(history["test_a"].estimated_duration_ms, Some(500));
+        assert_eq!(history["test_a"].last_status, "fail");
+        assert_eq!(history["test_b"].estimated_duration_ms, None);
+        assert_eq!(history["test_b"].last_status, "skip");
+    }
+
+    #[test]
+    fn test_last_test_provenance() {
+        let db = Database::open_in_memory().unwrap();
+        assert_eq!(db.last_test_provenance("/project").unwrap(), None);
+
+        db.record_test_provenance("/project", r#"{"a":1}"#).unwrap();
+        db.record_test_provenance("/project", r#"{"a":2}"#).unwrap();
+        db.record_test_provenance("/other", r#"{"a":3}"#).unwrap();
+
+        assert_eq!(
+            db.last_test_provenance("/project").unwrap().as_deref(),
+            Some(r#"{"a":2}"#)
+        );
+    }
+
     #[test]
     fn test_cleanup_old_baselines() {
         let db = Database::open_in_memory().unwrap();
@@ -145,4 +378,31 @@ mod tests {
         ).unwrap();
         assert_eq!(count, 20);
     }
+
+    #[test]
+    fn test_recent_failures_most_recent_first() {
+        let db = Database::open_in_memory().unwrap();
+
+        db.record_test_baseline("test_old", "/project", 10, "fail")
+            .unwrap();
+        for _ in 0..5 {
+            db.record_test_baseline("test_old", "/project", 10, "pass")
+                .unwrap();
+        }
+        db.record_test_baseline("test_a", "/project", 10, "fail")
+            .unwrap();
+        db.record_test_baseline("test_b", "/project", 10, "pass")
+            .unwrap();
+        db.record_test_baseline("test_c", "/project", 10, "fail")
+            .unwrap();
+        db.record_test_baseline("test_c", "/other", 10, "fail")
+            .unwrap();
+
+        // test_old's failure fell out of the 5-run window
+        let failures = db.get_recent_failures("/project", 5).unwrap();
+        assert_eq!(failures, vec!["test_c".to_string(), "test_a".to_string()]);
+
+        let known = db.get_known_tests("/project").unwrap();
+        assert_eq!(known, vec!["test_a", "test_b", "test_c", "test_old"]);
+    }
 }
@@ -0,0 +1,305 @@
+use super::Event;
+use chrono::{Datelike, TimeZone};
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+
+/// Frames hashed into a crash signature. Deeper frames vary with the caller
+/// (test harness, event loop) without changing what actually broke.
+pub const SIGNATURE_FRAMES: usize = 5;
+
+/// A deduplicated crash, aggregated across every session of a project.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CrashRecord {
+    pub signature: String,
+    pub signal: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exception_type: Option<String>,
+    pub top_frames: Vec<String>,
+    pub occurrences: u64,
+    /// Unix epoch seconds
+    pub first_seen: i64,
+    /// Unix epoch seconds
+    pub last_seen: i64,
+    pub last_session_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_dump_path: Option<String>,
+}
+
+impl CrashRecord {
+    /// "seen 14 times since Tuesday", relative to `now` (epoch seconds).
+    pub fn history(&self, now: i64) -> String {
+        describe_history(self.occurrences, self.first_seen, now)
+    }
+}
+
+/// Top frames of a crash backtrace, reduced to symbol names so the signature
+/// is stable across ASLR slides and rebuilds. Frida's own frames are skipped.
+pub fn signature_frames(event: &Event) -> Vec<String> {
+    let Some(frames) = event.backtrace.as_ref().and_then(|bt| bt.as_array()) else {
+        return Vec::new();
+    };
+    frames
+        .iter()
+        .filter(|f| {
+            let module = f.get("moduleName").and_then(|m| m.as_str()).unwrap_or("");
+            !module.contains("frida")
+        })
+        .take(SIGNATURE_FRAMES)
+        .map(|f| {
+            f.get("name")
+                .and_then(|n| n.as_str())
+                .filter(|n| !n.is_empty())
+                .or_else(|| f.get("moduleName").and_then(|m| m.as_str()))
+                .unwrap_or("?")
+                .to_string()
+        })
+        .collect()
+}
+
+/// Stable signature for a crash: FNV-1a over signal, exception type and top frames.
+pub fn crash_signature(event: &Event) -> String {
+    let mut key = format!(
+        "{}|{}",
+        event.signal.as_deref().unwrap_or(""),
+        event.exception_type.as_deref().unwrap_or("")
+    );
+    for frame in signature_frames(event) {
+        key.push('|');
+        key.push_str(&frame);
+    }
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in key.bytes() {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    format!("{:016x}", hash)
+}
+
+fn describe_history(occurrences: u64, first_seen: i64, now: i64) -> String {
+    if occurrences <= 1 {
+        return "first occurrence".to_string();
+    }
+    let (Some(first), Some(now)) = (
+        chrono::Local.timestamp_opt(first_seen, 0).single(),
+        chrono::Local.timestamp_opt(now, 0).single(),
+    ) else {
+        return format!("seen {} times", occurrences);
+    };
+    let days = now.date_naive().num_days_from_ce() - first.date_naive().num_days_from_ce();
+    let since = match days {
+        d if d <= 0 => return format!("seen {} times today", occurrences),
+        1 => "yesterday".to_string(),
+        d if d < 7 => first.format("%A").to_string(),
+        _ => first.format("%Y-%m-%d").to_string(),
+    };
+    format!("seen {} times since {}", occurrences, since)
+}
+
+fn crash_from_row(row: &rusqlite::Row) -> rusqlite::Result<CrashRecord> {
+    let frames: String = row.get(3)?;
+    Ok(CrashRecord {
+        signature: row.get(0)?,
+        signal: row.get(1)?,
+        exception_type: row.get(2)?,
+        top_frames: serde_json::from_str(&frames).unwrap_or_default(),
+        occurrences: row.get::<_, i64>(4)? as u64,
+        first_seen: row.get(5)?,
+        last_seen: row.get(6)?,
+        last_session_id: row.get(7)?,
+        last_dump_path: row.get(8)?,
+    })
+}
+
+const CRASH_SELECT: &str = "SELECT signature, signal, exception_type, top_frames, occurrences,
+     first_seen, last_seen, last_session_id, last_dump_path FROM crashes";
+
+impl super::Database {
+    /// Count a crash event against its signature for `project_root` and return
+    /// the updated record.
+    pub fn record_crash(&self, project_root: &str, event: &Event) -> crate::Result<CrashRecord> {
+        let signature = crash_signature(event);
+        let frames = serde_json::to_string(&signature_frames(event))?;
+        let now = chrono::Utc::now().timestamp();
+        let conn = self.connection();
+        conn.execute(
+            "INSERT INTO crashes (project_root, signature, signal, exception_type, top_frames,
+                occurrences, first_seen, last_seen, last_session_id, last_dump_path)
+             VALUES (?1, ?2, ?3, ?4, ?5, 1, ?6, ?6, ?7, ?8)
+             ON CONFLICT(project_root, signature) DO UPDATE SET
+                occurrences = occurrences + 1,
+                last_seen = excluded.last_seen,
+                last_session_id = excluded.last_session_id,
+                last_dump_path = COALESCE(excluded.last_dump_path, last_dump_path)",
+            params![
+                project_root,
+                signature,
+                event.signal,
+                event.exception_type,
+                frames,
+                now,
+                event.session_id,
+                event.dump_path,
+            ],
+        )?;
+        let record = conn.query_row(
+            &format!(
+                "{} WHERE project_root = ?1 AND signature = ?2",
+                CRASH_SELECT
+            ),
+            params![project_root, signature],
+            crash_from_row,
+        )?;
+        Ok(record)
+    }
+
+    /// The aggregated record matching a crash event, if it has been recorded.
+    pub fn crash_for_event(
+        &self,
+        project_root: &str,
+        event: &Event,
+    ) -> crate::Result<Option<CrashRecord>> {
+        use rusqlite::OptionalExtension;
+        let conn = self.connection();
+        let record = conn
+            .query_row(
+                &format!(
+                    "{} WHERE project_root = ?1 AND signature = ?2",
+                    CRASH_SELECT
+                ),
+                params![project_root, crash_signature(event)],
+                crash_from_row,
+            )
+            .optional()?;
+        Ok(record)
+    }
+
+    /// History line ("seen 3 times since Tuesday") for a stored crash event,
+    /// looked up under its session's project.
+    pub fn crash_history(&self, event: &Event) -> Option<String> {
+        let session = self.get_session(&event.session_id).ok()??;
+        let record = self.crash_for_event(&session.project_root, event).ok()??;
+        Some(record.history(chrono::Utc::now().timestamp()))
+    }
+
+    /// Known crash signatures for a project, most recently seen first.
+    pub fn list_crashes(
+        &self,
+        project_root: &str,
+        limit: usize,
+    ) -> crate::Result<Vec<CrashRecord>> {
+        let conn = self.connection();
+        let mut stmt = conn.prepare(&format!(
+            "{} WHERE project_root = ?1 ORDER BY last_seen DESC, occurrences DESC LIMIT ?2",
+            CRASH_SELECT
+        ))?;
+        let rows = stmt.query_map(params![project_root, limit as i64], crash_from_row)?;
+        rows.collect::<std::result::Result<Vec<_>, _>>()
+            .map_err(Into::into)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{Database, EventType};
+
+    fn crash(session: &str, frames: &[&str], signal: &str) -> Event {
+        let backtrace: Vec<serde_json::Value> = frames
+            .iter()
+            .enumerate()
+            .map(|(i, name)| {
+                serde_json::json!({
+                    "address": format!("0x{:x}", 0x1000 * (i + 1) + session.len()),
+                    "moduleName": "app",
+                    "name": name,
+                })
+            })
+            .collect();
+        Event {
+            id: format!("{}-crash", session),
+            session_id: session.to_string(),
+            event_type: EventType::Crash,
+            signal: Some(signal.to_string()),
+            backtrace: Some(serde_json::Value::Array(backtrace)),
+            ..Event::default()
+        }
+    }
+
+    #[test]
+    fn test_signature_ignores_addresses_and_deep_frames() {
+        let a = crash("s1", &["f", "g", "h", "i", "j", "main"], "access-violation");
+        let b = crash(
+            "session-two",
+            &["f", "g", "h", "i", "j", "other_main"],
+            "access-violation",
+        );
+        assert_eq!(crash_signature(&a), crash_signature(&b));
+
+        let c = crash("s1", &["f", "g", "h", "i", "j"], "abort");
+        assert_ne!(crash_signature(&a), crash_signature(&c));
+    }
+
+    #[test]
+    fn test_signature_skips_frida_frames() {
+        let mut event = crash("s1", &["f", "g"], "abort");
+        if let Some(frames) = event.backtrace.as_mut().and_then(|b| b.as_array_mut()) {
+            frames.insert(
+                0,
+                serde_json::json!({"moduleName": "frida-agent-64.so", "name": "gum_handler"}),
+            );
+        }
+        assert_eq!(signature_frames(&event), vec!["f", "g"]);
+    }
+
+    #[test]
+    fn test_record_crash_counts_occurrences_per_project() {
+        let db = Database::open_in_memory().unwrap();
+        let first = db
+            .record_crash("/proj", &crash("s1", &["boom", "main"], "abort"))
+            .unwrap();
+        assert_eq!(first.occurrences, 1);
+
+        let mut again = crash("s2", &["boom", "main"], "abort");
+        again.dump_path = Some("/tmp/d.json".to_string());
+        let second = db.record_crash("/proj", &again).unwrap();
+        assert_eq!(second.occurrences, 2);
+        assert_eq!(second.last_session_id, "s2");
+        assert_eq!(second.last_dump_path.as_deref(), Some("/tmp/d.json"));
+        assert_eq!(second.first_seen, first.first_seen);
+
+        db.record_crash("/proj", &crash("s3", &["other"], "abort"))
+            .unwrap();
+        db.record_crash("/elsewhere", &again).unwrap();
+
+        let listed = db.list_crashes("/proj", 10).unwrap();
+        assert_eq!(listed.len(), 2);
+        let found = db.crash_for_event("/proj", &again).unwrap().unwrap();
+        assert_eq!(found.occurrences, 2);
+        assert!(db.crash_for_event("/nowhere", &again).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_describe_history() {
+        let now = chrono::Local
+            .with_ymd_and_hms(2026, 10, 15, 12, 0, 0)
+            .unwrap()
+            .timestamp();
+        let day = 86_400;
+        assert_eq!(describe_history(1, now, now), "first occurrence");
+        assert_eq!(describe_history(3, now - 60, now), "seen 3 times today");
+        assert_eq!(
+            describe_history(4, now - day, now),
+            "seen 4 times since yesterday"
+        );
+        // 2026-10-13 is a Tuesday
+        assert_eq!(
+            describe_history(14, now - 2 * day, now),
+            "seen 14 times since Tuesday"
+        );
+        assert_eq!(
+            describe_history(9, now - 30 * day, now),
+            "seen 9 times since 2026-09-15"
+        );
+    }
+}
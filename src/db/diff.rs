@@ -0,0 +1,375 @@
+//! Trace comparison between two sessions of the same binary (`debug_diff`).
+//!
+//! Works on stored function_enter/function_exit events only: which functions
+//! ran, how long they took on average, and what each call returned, matched
+//! by call order per function. Threads are not told apart: calls from several
+//! threads are matched in the order they were recorded, so a function called
+//! concurrently can diverge on scheduling alone.
+//!
+//! Reads go through the reader connection so a diff of large sessions doesn't
+//! hold up event inserts.
+
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+/// function_exit rows read per session for return value comparison.
+pub const MAX_DIFF_EXIT_ROWS: usize = 200_000;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FunctionStats {
+    pub function: String,
+    pub calls: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub avg_duration_ns: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_duration_ns: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DurationRegression {
+    pub function: String,
+    pub base_avg_ns: u64,
+    pub avg_ns: u64,
+    /// avg_ns / base_avg_ns
+    pub ratio: f64,
+    pub base_calls: u64,
+    pub calls: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReturnDivergence {
+    pub function: String,
+    /// 0-based index of the first call whose return value differs
+    pub first_call_index: u64,
+    pub base_return: serde_json::Value,
+    pub return_value: serde_json::Value,
+    /// Calls compared (the shorter of the two sequences)
+    pub compared_calls: u64,
+    pub differing_calls: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionDiff {
+    pub only_in_base: Vec<FunctionStats>,
+    pub only_in_session: Vec<FunctionStats>,
+    pub regressions: Vec<DurationRegression>,
+    pub return_divergences: Vec<ReturnDivergence>,
+    /// A session had more than MAX_DIFF_EXIT_ROWS exits; later calls weren't compared
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub truncated: bool,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct DiffOptions {
+    /// Minimum avg duration ratio to report a regression
+    pub min_ratio: f64,
+    /// Minimum avg duration increase to report a regression
+    pub min_delta_ns: u64,
+    /// Entries per list
+    pub limit: usize,
+}
+
+impl Default for DiffOptions {
+    fn default() -> Self {
+        Self {
+            min_ratio: 1.5,
+            min_delta_ns: 10_000,
+            limit: 50,
+        }
+    }
+}
+
+impl super::Database {
+    pub fn diff_sessions(
+        &self,
+        base_session_id: &str,
+        session_id: &str,
+        options: DiffOptions,
+    ) -> crate::Result<SessionDiff> {
+        let base_stats = self.function_stats(base_session_id)?;
+        let stats = self.function_stats(session_id)?;
+        let (base_returns, base_truncated) = self.return_sequences(base_session_id)?;
+        let (returns, truncated) = self.return_sequences(session_id)?;
+
+        let mut diff = compare_stats(&base_stats, &stats, options);
+        diff.return_divergences = compare_returns(&base_returns, &returns, options.limit);
+        diff.truncated = base_truncated || truncated;
+        Ok(diff)
+    }
+
+    fn function_stats(&self, session_id: &str) -> crate::Result<BTreeMap<String, FunctionStats>> {
+        let conn = self.reader();
+        let mut stmt = conn.prepare(
+            "SELECT function_name,
+                    SUM(event_type = 'function_enter'),
+                    AVG(CASE WHEN event_type = 'function_exit' THEN duration_ns END),
+                    MAX(CASE WHEN event_type = 'function_exit' THEN duration_ns END)
+             FROM events
+             WHERE session_id = ?1 AND event_type IN ('function_enter', 'function_exit')
+             GROUP BY function_name",
+        )?;
+        let rows = stmt.query_map(params![session_id], |row| {
+            Ok(FunctionStats {
+                function: row.get(0)?,
+                calls: row.get::<_, i64>(1)? as u64,
+                avg_duration_ns: row.get::<_, Option<f64>>(2)?.map(|v| v.round() as u64),
+                max_duration_ns: row.get::<_, Option<i64>>(3)?.map(|v| v as u64),
+            })
+        })?;
+        let mut map = BTreeMap::new();
+        for row in rows {
+            let stats = row?;
+            map.insert(stats.function.clone(), stats);
+        }
+        Ok(map)
+    }
+
+    /// Return values per function in call order. The bool is true when the
+    /// session had more exits than MAX_DIFF_EXIT_ROWS.
+    fn return_sequences(
+        &self,
+        session_id: &str,
+    ) -> crate::Result<(HashMap<String, Vec<serde_json::Value>>, bool)> {
+        let conn = self.reader();
+        let mut stmt = conn.prepare(
+            "SELECT function_name, return_value FROM events
+             WHERE session_id = ?1 AND event_type = 'function_exit'
+             ORDER BY seq, rowid
+             LIMIT ?2",
+        )?;
+        let rows = stmt.query_map(params![session_id, MAX_DIFF_EXIT_ROWS as i64 + 1], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                super::event::read_json_flexible(row, 1)?,
+            ))
+        })?;
+        let mut map: HashMap<String, Vec<serde_json::Value>> = HashMap::new();
+        let mut count = 0;
+        for row in rows {
+            count += 1;
+            if count > MAX_DIFF_EXIT_ROWS {
+                return Ok((map, true));
+            }
+            let (function, value) = row?;
+            map.entry(function)
+                .or_default()
+                .push(value.unwrap_or(serde_json::Value::Null));
+        }
+        Ok((map, false))
+    }
+}
+
+fn compare_stats(
+    base: &BTreeMap<String, FunctionStats>,
+    target: &BTreeMap<String, FunctionStats>,
+    options: DiffOptions,
+) -> SessionDiff {
+    let only = |a: &BTreeMap<String, FunctionStats>, b: &BTreeMap<String, FunctionStats>| {
+        let mut v: Vec<FunctionStats> = a
+            .values()
+            .filter(|s| !b.contains_key(&s.function))
+            .cloned()
+            .collect();
+        v.sort_by_key(|s| std::cmp::Reverse(s.calls));
+        v.truncate(options.limit);
+        v
+    };
+
+    let mut regressions: Vec<DurationRegression> = target
+        .values()
+        .filter_map(|t| {
+            let b = base.get(&t.function)?;
+            let (base_avg, avg) = (b.avg_duration_ns?, t.avg_duration_ns?);
+            if base_avg == 0 || avg < base_avg + options.min_delta_ns {
+                return None;
+            }
+            let ratio = avg as f64 / base_avg as f64;
+            (ratio >= options.min_ratio).then(|| DurationRegression {
+                function: t.function.clone(),
+                base_avg_ns: base_avg,
+                avg_ns: avg,
+                ratio: (ratio * 100.0).round() / 100.0,
+                base_calls: b.calls,
+                calls: t.calls,
+            })
+        })
+        .collect();
+    // Biggest total time lost first
+    regressions.sort_by_key(|r| {
+        std::cmp::Reverse((r.avg_ns - r.base_avg_ns).saturating_mul(r.calls.max(1)))
+    });
+    regressions.truncate(options.limit);
+
+    SessionDiff {
+        only_in_base: only(base, target),
+        only_in_session: only(target, base),
+        regressions,
+        return_divergences: Vec::new(),
+        truncated: false,
+    }
+}
+
+/// Pointers differ between runs under ASLR; treat hex strings past the
+/// first 64KiB as addresses and don't compare them.
+pub(super) fn looks_like_address(v: &serde_json::Value) -> bool {
+    v.as_str()
+        .and_then(|s| s.strip_prefix("0x"))
+        .and_then(|hex| u64::from_str_radix(hex, 16).ok())
+        .is_some_and(|n| n > 0xffff)
+}
+
+fn compare_returns(
+    base: &HashMap<String, Vec<serde_json::Value>>,
+    target: &HashMap<String, Vec<serde_json::Value>>,
+    limit: usize,
+) -> Vec<ReturnDivergence> {
+    let mut out: Vec<ReturnDivergence> = target
+        .iter()
+        .filter_map(|(function, values)| {
+            let base_values = base.get(function)?;
+            let mut first: Option<usize> = None;
+            let mut differing = 0u64;
+            let mut compared = 0u64;
+            for (i, (b, t)) in base_values.iter().zip(values).enumerate() {
+                if b.is_null() || t.is_null() || (looks_like_address(b) && looks_like_address(t)) {
+                    continue;
+                }
+                compared += 1;
+                if b != t {
+                    differing += 1;
+                    first.get_or_insert(i);
+                }
+            }
+            let i = first?;
+            Some(ReturnDivergence {
+                function: function.clone(),
+                first_call_index: i as u64,
+                base_return: base_values[i].clone(),
+                return_value: values[i].clone(),
+                compared_calls: compared,
+                differing_calls: differing,
+            })
+        })
+        .collect();
+    out.sort_by(|a, b| {
+        b.differing_calls
+            .cmp(&a.differing_calls)
+            .then_with(|| a.function.cmp(&b.function))
+    });
+    out.truncate(limit);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{Database, Event, EventType};
+    use serde_json::json;
+
+    fn call(
+        session: &str,
+        n: usize,
+        function: &str,
+        duration_ns: i64,
+        ret: serde_json::Value,
+    ) -> [Event; 2] {
+        let enter = Event {
+            id: format!("{}-{}-in", session, n),
+            session_id: session.into(),
+            timestamp_ns: n as i64 * 1000,
+            event_type: EventType::FunctionEnter,
+            function_name: function.into(),
+            ..Default::default()
+        };
+        let exit = Event {
+            id: format!("{}-{}-out", session, n),
+            event_type: EventType::FunctionExit,
+            duration_ns: Some(duration_ns),
+            return_value: Some(ret),
+            ..enter.clone()
+        };
+        [enter, exit]
+    }
+
+    #[test]
+    fn test_diff_sessions() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = Database::open(&dir.path().join("test.db")).unwrap();
+        db.create_session("old", "/bin/app", "/p", 1).unwrap();
+        db.create_session("new", "/bin/app", "/p", 2).unwrap();
+
+        let mut old = Vec::new();
+        old.extend(call("old", 0, "parse", 50_000, json!(0)));
+        old.extend(call("old", 1, "parse", 50_000, json!(0)));
+        old.extend(call("old", 2, "legacy_path", 1_000, json!(1)));
+        old.extend(call("old", 3, "alloc", 100, json!("0x7f0000001000")));
+        db.insert_events_batch(&old).unwrap();
+
+        let mut new = Vec::new();
+        new.extend(call("new", 0, "parse", 50_000, json!(0)));
+        new.extend(call("new", 1, "parse", 250_000, json!(-1)));
+        new.extend(call("new", 2, "fast_path", 1_000, json!(1)));
+        new.extend(call("new", 3, "alloc", 100, json!("0x7f0000009000")));
+        db.insert_events_batch(&new).unwrap();
+
+        let diff = db
+            .diff_sessions("old", "new", DiffOptions::default())
+            .unwrap();
+        assert_eq!(diff.only_in_base.len(), 1);
+        assert_eq!(diff.only_in_base[0].function, "legacy_path");
+        assert_eq!(diff.only_in_session[0].function, "fast_path");
+
+        assert_eq!(diff.regressions.len(), 1);
+        let r = &diff.regressions[0];
+        assert_eq!(r.function, "parse");
+        assert_eq!((r.base_avg_ns, r.avg_ns), (50_000, 150_000));
+        assert_eq!(r.ratio, 3.0);
+
+        // Addresses differ every run and are not reported.
+        assert_eq!(diff.return_divergences.len(), 1);
+        let d = &diff.return_divergences[0];
+        assert_eq!(d.function, "parse");
+        assert_eq!(d.first_call_index, 1);
+        assert_eq!(
+            (d.base_return.clone(), d.return_value.clone()),
+            (json!(0), json!(-1))
+        );
+        assert_eq!((d.compared_calls, d.differing_calls), (2, 1));
+        assert!(!diff.truncated);
+    }
+
+    #[test]
+    fn test_small_slowdowns_are_not_regressions() {
+        let stats = |avg: u64| {
+            BTreeMap::from([(
+                "f".to_string(),
+                FunctionStats {
+                    function: "f".into(),
+                    calls: 10,
+                    avg_duration_ns: Some(avg),
+                    max_duration_ns: Some(avg),
+                },
+            )])
+        };
+        let options = DiffOptions::default();
+        // 3x slower but only 2µs: below min_delta_ns.
+        assert!(compare_stats(&stats(1_000), &stats(3_000), options)
+            .regressions
+            .is_empty());
+        // 20µs slower but only 1.2x: below min_ratio.
+        assert!(compare_stats(&stats(100_000), &stats(120_000), options)
+            .regressions
+            .is_empty());
+        assert_eq!(
+            compare_stats(&stats(100_000), &stats(200_000), options)
+                .regressions
+                .len(),
+            1
+        );
+    }
+}
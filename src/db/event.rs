@@ -15,6 +15,43 @@ pub enum EventType {
     Pause,
     Logpoint,
     ConditionError,
+    /// Output on an auxiliary channel (extra fd or named pipe); see `Event::channel`
+    AuxOutput,
+    /// Write to a memory range armed via debug_watchpoint
+    Watchpoint,
+    /// Marker recorded when a launch `onOutput` trigger matched stdout/stderr
+    OutputTrigger,
+    /// Cumulative per-function call counts from `debug_trace` count mode
+    CallCounts,
+    /// Periodic stack sample of one thread from `debug_trace` sampling
+    Sample,
+    /// Non-async-signal-safe call inside a signal handler (`debug_trace` signalSafety)
+    SignalSafety,
+    /// Allocations never freed, grouped by backtrace (`debug_heap`, written at a retained stop)
+    Leak,
+    /// libc file or socket call traced by a `@syscalls:` pattern (`debug_trace`)
+    Syscall,
+    /// Connect, DNS lookup or TLS traffic traced by the `@network` pattern (`debug_trace`)
+    Network,
+    /// A normal trace hook fired on a thread with a real-time scheduling policy
+    RtWarning,
+    /// Written at daemon startup for a session the previous daemon left
+    /// running; marks where its recording was cut off
+    Recovery,
+    /// Rust panic, failed C assert or abort, recorded whatever is traced
+    Panic,
+    /// C++ throw, catch or rethrow traced by the `@exceptions` pattern
+    /// (`debug_trace`); a catch shares its throw's call id
+    Exception,
+    /// Signal delivered to a handler or sent with kill/raise, traced by the
+    /// `@signals` pattern (`debug_trace`)
+    Signal,
+    /// Contended or blocked lock acquisition traced by the `@locks` pattern
+    /// (`debug_trace`); duration_ns is the wait
+    Lock,
+    /// Written when a settings file change reaches a running session; the
+    /// text lists the settings that changed
+    ConfigChange,
 }
 
 impl EventType {
@@ -29,6 +66,22 @@ impl EventType {
             Self::Pause => "pause",
             Self::Logpoint => "logpoint",
             Self::ConditionError => "condition_error",
+            Self::AuxOutput => "aux_output",
+            Self::Watchpoint => "watchpoint",
+            Self::OutputTrigger => "output_trigger",
+            Self::CallCounts => "call_counts",
+            Self::Sample => "sample",
+            Self::SignalSafety => "signal_safety",
+            Self::Leak => "leak",
+            Self::Syscall => "syscall",
+            Self::Network => "network",
+            Self::RtWarning => "rt_warning",
+            Self::Recovery => "recovery",
+            Self::Panic => "panic",
+            Self::Exception => "exception",
+            Self::Signal => "signal",
+            Self::Lock => "lock",
+            Self::ConfigChange => "config_change",
         }
     }
 
@@ -43,6 +96,22 @@ impl EventType {
             "pause" => Some(Self::Pause),
             "logpoint" => Some(Self::Logpoint),
             "condition_error" => Some(Self::ConditionError),
+            "aux_output" => Some(Self::AuxOutput),
+            "watchpoint" => Some(Self::Watchpoint),
+            "output_trigger" => Some(Self::OutputTrigger),
+            "call_counts" => Some(Self::CallCounts),
+            "sample" => Some(Self::Sample),
+            "signal_safety" => Some(Self::SignalSafety),
+            "leak" => Some(Self::Leak),
+            "syscall" => Some(Self::Syscall),
+            "network" => Some(Self::Network),
+            "rt_warning" => Some(Self::RtWarning),
+            "recovery" => Some(Self::Recovery),
+            "panic" => Some(Self::Panic),
+            "exception" => Some(Self::Exception),
+            "signal" => Some(Self::Signal),
+            "lock" => Some(Self::Lock),
+            "config_change" => Some(Self::ConfigChange),
             _ => None,
         }
     }
@@ -53,6 +122,9 @@ pub struct Event {
     /// SQLite implicit rowid (populated by queries, not inserted)
     #[serde(skip)]
     pub rowid: Option<i64>,
+    /// Per-session sequence number, assigned by the writer at insert time.
+    /// Strictly increasing within a session, including across daemon restarts.
+    pub seq: Option<i64>,
     pub id: String,
     pub session_id: String,
     pub timestamp_ns: i64,
@@ -81,12 +153,28 @@ pub struct Event {
     pub exception_type: Option<String>,
     pub exception_message: Option<String>,
     pub throw_backtrace: Option<serde_json::Value>,
+    /// Source tag for aux_output events: "fd:3" or "pipe:/path/to/fifo";
+    /// for variable_snapshot events from a debug_memory poll, its poll id
+    pub channel: Option<String>,
+    /// Crash events: path of the JSON crash summary under `~/.strobe/crashes/`
+    pub dump_path: Option<String>,
+    /// libdispatch queue label the traced call ran on (GCD apps)
+    pub queue_name: Option<String>,
+    /// Output events: fields parsed from a JSON/logfmt log line (launch `logFormat`)
+    pub log_fields: Option<serde_json::Value>,
+    /// Events from a followed child of a test run: the test running when the
+    /// child was spawned
+    pub test_name: Option<String>,
+    /// function_enter/function_exit: shared by the two events of one call
+    /// (the enter's id)
+    pub call_id: Option<String>,
 }
 
 impl Default for Event {
     fn default() -> Self {
         Self {
             rowid: None,
+            seq: None,
             id: String::new(),
             session_id: String::new(),
             timestamp_ns: 0,
@@ -115,6 +203,12 @@ impl Default for Event {
             exception_type: None,
             exception_message: None,
             throw_backtrace: None,
+            channel: None,
+            dump_path: None,
+            queue_name: None,
+            log_fields: None,
+            test_name: None,
+            call_id: None,
         }
     }
 }
@@ -164,6 +258,16 @@ pub struct EventQuery {
     pub return_value_is_null: Option<bool>,
     pub thread_id_equals: Option<i64>,
     pub thread_name_contains: Option<String>,
+    pub queue_name_equals: Option<String>,
+    pub queue_name_contains: Option<String>,
+    /// Case-insensitive substring of output text (stdout/stderr/aux_output)
+    pub text_contains: Option<String>,
+    /// Regex over output text (stdout/stderr/aux_output)
+    pub text_regex: Option<String>,
+    /// Structured log field equality (key, value), case-insensitive on value
+    pub log_fields: Vec<(String, String)>,
+    pub test_name_equals: Option<String>,
+    pub call_id_equals: Option<String>,
     pub pid_equals: Option<u32>,
     pub timestamp_from_ns: Option<i64>,
     pub timestamp_to_ns: Option<i64>,
@@ -172,6 +276,8 @@ pub struct EventQuery {
     pub offset: u32,
     /// Cursor: return only events with rowid > after_rowid
     pub after_rowid: Option<i64>,
+    /// Cursor: return only events with seq > after_seq, oldest first
+    pub after_seq: Option<i64>,
 }
 
 impl Default for EventQuery {
@@ -185,6 +291,13 @@ impl Default for EventQuery {
             return_value_is_null: None,
             thread_id_equals: None,
             thread_name_contains: None,
+            queue_name_equals: None,
+            queue_name_contains: None,
+            text_contains: None,
+            text_regex: None,
+            log_fields: Vec::new(),
+            test_name_equals: None,
+            call_id_equals: None,
             pid_equals: None,
             timestamp_from_ns: None,
             timestamp_to_ns: None,
@@ -192,6 +305,7 @@ impl Default for EventQuery {
             limit: 50,
             offset: 0,
             after_rowid: None,
+            after_seq: None,
         }
     }
 }
@@ -243,6 +357,52 @@ impl EventQuery {
         self.thread_name_contains = Some(s.to_string());
         self
     }
+
+    pub fn text_contains(mut self, s: &str) -> Self {
+        self.text_contains = Some(s.to_string());
+        self
+    }
+
+    pub fn text_regex(mut self, s: &str) -> Self {
+        self.text_regex = Some(s.to_string());
+        self
+    }
+
+    pub fn queue_name_equals(mut self, s: &str) -> Self {
+        self.queue_name_equals = Some(s.to_string());
+        self
+    }
+
+    pub fn queue_name_contains(mut self, s: &str) -> Self {
+        self.queue_name_contains = Some(s.to_string());
+        self
+    }
+
+    pub fn log_field(mut self, key: &str, value: &str) -> Self {
+        self.log_fields.push((key.to_string(), value.to_string()));
+        self
+    }
+
+    pub fn test_name_equals(mut self, s: &str) -> Self {
+        self.test_name_equals = Some(s.to_string());
+        self
+    }
+
+    pub fn call_id_equals(mut self, s: &str) -> Self {
+        self.call_id_equals = Some(s.to_string());
+        self
+    }
+}
+
+/// Event types whose `text` is process output, i.e. what `events_fts` indexes.
+pub(crate) const OUTPUT_EVENT_TYPES_SQL: &str = "'stdout', 'stderr', 'aux_output'";
+
+/// Trigrams need at least three characters; shorter needles scan with LIKE.
+const MIN_FTS_NEEDLE_CHARS: usize = 3;
+
+/// Quote a needle as an FTS5 phrase so operators in it are taken literally.
+fn fts_phrase(s: &str) -> String {
+    format!("\"{}\"", s.replace('"', "\"\""))
 }
 
 fn escape_like_pattern(s: &str) -> String {
@@ -255,19 +415,94 @@ fn escape_like_pattern(s: &str) -> String {
 }
 
 const INSERT_EVENT_SQL: &str =
-    "INSERT INTO events (id, session_id, timestamp_ns, thread_id, thread_name, parent_event_id,
+    "INSERT OR IGNORE INTO events (id, session_id, timestamp_ns, thread_id, thread_name, parent_event_id,
      event_type, function_name, function_name_raw, source_file, line_number,
      arguments, return_value, duration_ns, text, sampled, watch_values, pid,
      signal, fault_address, registers, backtrace, locals, breakpoint_id, logpoint_message,
-     exception_type, exception_message, throw_backtrace)
-     VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)";
+     exception_type, exception_message, throw_backtrace, channel, seq, dump_path, queue_name,
+     log_fields, test_name, call_id)
+     VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)";
 
-/// Insert a single event row using a connection or transaction.
+/// Standard SELECT prefix matching `event_from_row` (rowid + 35 data columns).
+pub(crate) const SELECT_EVENT_SQL: &str =
+    "SELECT rowid, id, session_id, timestamp_ns, thread_id, thread_name, parent_event_id,
+     event_type, function_name, function_name_raw, source_file, line_number,
+     arguments, return_value, duration_ns, text, sampled, watch_values, pid,
+     signal, fault_address, registers, backtrace, locals, breakpoint_id, logpoint_message,
+     exception_type, exception_message, throw_backtrace, channel, seq, dump_path, queue_name,
+     log_fields, test_name, call_id
+     FROM events WHERE session_id = ?";
+
+/// Reserve `count` consecutive sequence numbers for a session and return the
+/// first. The high-water mark lives in `sessions.last_seq` so numbering
+/// survives FIFO eviction and daemon restarts; sessions without a row (or
+/// predating the column) continue from the highest stored seq.
+fn reserve_seqs(
+    conn: &rusqlite::Connection,
+    session_id: &str,
+    count: usize,
+) -> std::result::Result<i64, rusqlite::Error> {
+    use rusqlite::OptionalExtension;
+
+    let last: Option<i64> = conn
+        .query_row(
+            "SELECT last_seq FROM sessions WHERE id = ?",
+            params![session_id],
+            |row| row.get(0),
+        )
+        .optional()?
+        .flatten();
+    let base = match last {
+        Some(n) => n,
+        None => conn.query_row(
+            "SELECT COALESCE(MAX(seq), 0) FROM events WHERE session_id = ?",
+            params![session_id],
+            |row| row.get(0),
+        )?,
+    };
+    conn.execute(
+        "UPDATE sessions SET last_seq = ? WHERE id = ?",
+        params![base + count as i64, session_id],
+    )?;
+    Ok(base + 1)
+}
+
+/// JSON payloads shorter than this stay plain text; zstd framing would eat
+/// most of the saving.
+const COMPRESS_MIN_BYTES: usize = 256;
+const ZSTD_LEVEL: i32 = 3;
+
+/// Column value for a bulky JSON payload (arguments, registers, backtraces,
+/// locals): a zstd BLOB when `compress` is set and the JSON is large enough,
+/// TEXT otherwise. `read_json_flexible`/`read_json_text` accept both.
+fn payload_value(
+    value: Option<&serde_json::Value>,
+    compress: bool,
+) -> Option<rusqlite::types::Value> {
+    let json = value?.to_string();
+    if compress && json.len() >= COMPRESS_MIN_BYTES {
+        if let Ok(blob) = zstd::bulk::compress(json.as_bytes(), ZSTD_LEVEL) {
+            return Some(rusqlite::types::Value::Blob(blob));
+        }
+    }
+    Some(rusqlite::types::Value::Text(json))
+}
+
+/// Parse a payload stored by `payload_value`; undecodable values read as None.
+fn decompress_json(blob: &[u8]) -> Option<serde_json::Value> {
+    let json = zstd::stream::decode_all(blob).ok()?;
+    serde_json::from_slice(&json).ok()
+}
+
+/// Insert a single event row using a connection or transaction. Returns false
+/// when a row with the same id already exists (a retried batch).
 fn insert_event_row(
     conn: &rusqlite::Connection,
     event: &Event,
-) -> std::result::Result<(), rusqlite::Error> {
-    conn.execute(
+    seq: i64,
+    compress: bool,
+) -> std::result::Result<bool, rusqlite::Error> {
+    let inserted = conn.execute(
         INSERT_EVENT_SQL,
         params![
             &event.id,
@@ -281,7 +516,7 @@ fn insert_event_row(
             &event.function_name_raw,
             &event.source_file,
             event.line_number,
-            event.arguments.as_ref().map(|v| v.to_string()),
+            payload_value(event.arguments.as_ref(), compress),
             event.return_value.as_ref().map(|v| v.to_string()),
             event.duration_ns,
             &event.text,
@@ -290,21 +525,187 @@ fn insert_event_row(
             event.pid.map(|p| p as i64),
             &event.signal,
             &event.fault_address,
-            event.registers.as_ref().map(|v| v.to_string()),
-            event.backtrace.as_ref().map(|v| v.to_string()),
-            event.locals.as_ref().map(|v| v.to_string()),
+            payload_value(event.registers.as_ref(), compress),
+            payload_value(event.backtrace.as_ref(), compress),
+            payload_value(event.locals.as_ref(), compress),
             &event.breakpoint_id,
             &event.logpoint_message,
             &event.exception_type,
             &event.exception_message,
-            event.throw_backtrace.as_ref().map(|v| v.to_string()),
+            payload_value(event.throw_backtrace.as_ref(), compress),
+            &event.channel,
+            seq,
+            &event.dump_path,
+            &event.queue_name,
+            event.log_fields.as_ref().map(|v| v.to_string()),
+            &event.test_name,
+            &event.call_id,
         ],
     )?;
+    Ok(inserted > 0)
+}
+
+/// Insert one session's events under consecutive seqs. Duplicates don't
+/// consume a seq, so the reserved range is trimmed back when any are skipped.
+pub(crate) fn insert_session_events(
+    conn: &rusqlite::Connection,
+    session_id: &str,
+    events: &[&Event],
+    compress: bool,
+    stats: &mut EventInsertStats,
+) -> std::result::Result<(), rusqlite::Error> {
+    let first_seq = reserve_seqs(conn, session_id, events.len())?;
+    let mut seq = first_seq;
+    for event in events {
+        if insert_event_row(conn, event, seq, compress)? {
+            seq += 1;
+            stats.events_inserted += 1;
+        } else {
+            stats.duplicates_ignored += 1;
+        }
+    }
+    if seq < first_seq + events.len() as i64 {
+        conn.execute(
+            "UPDATE sessions SET last_seq = ? WHERE id = ?",
+            params![seq - 1, session_id],
+        )?;
+    }
     Ok(())
 }
 
+/// Append the `EventQuery` filters (everything but ordering and paging) to a
+/// `... FROM events WHERE session_id = ?` statement.
+pub(crate) fn push_event_filters(
+    query: &EventQuery,
+    sql: &mut String,
+    params_vec: &mut Vec<Box<dyn rusqlite::ToSql>>,
+) {
+    if query.text_events_only {
+        sql.push_str(" AND event_type IN ('stdout', 'stderr')");
+    } else if let Some(ref et) = query.event_type {
+        sql.push_str(" AND event_type = ?");
+        params_vec.push(Box::new(et.as_str().to_string()));
+    }
+
+    if let Some(ref f) = query.function_equals {
+        sql.push_str(
+            " AND event_type IN ('function_enter', 'function_exit') AND function_name = ?",
+        );
+        params_vec.push(Box::new(f.clone()));
+    }
+
+    if let Some(ref f) = query.function_contains {
+        sql.push_str(" AND event_type IN ('function_enter', 'function_exit') AND function_name LIKE ? ESCAPE '\\'");
+        params_vec.push(Box::new(format!("%{}%", escape_like_pattern(f))));
+    }
+
+    if let Some(ref f) = query.source_file_contains {
+        sql.push_str(" AND source_file LIKE ? ESCAPE '\\'");
+        params_vec.push(Box::new(format!("%{}%", escape_like_pattern(f))));
+    }
+
+    if let Some(is_null) = query.return_value_is_null {
+        if is_null {
+            sql.push_str(" AND return_value IS NULL");
+        } else {
+            sql.push_str(" AND return_value IS NOT NULL");
+        }
+    }
+
+    if let Some(tid) = query.thread_id_equals {
+        sql.push_str(" AND thread_id = ?");
+        params_vec.push(Box::new(tid));
+    }
+
+    if let Some(ref name) = query.thread_name_contains {
+        sql.push_str(" AND thread_name LIKE ? ESCAPE '\\'");
+        params_vec.push(Box::new(format!("%{}%", escape_like_pattern(name))));
+    }
+
+    if let Some(ref name) = query.queue_name_equals {
+        sql.push_str(" AND queue_name = ?");
+        params_vec.push(Box::new(name.clone()));
+    }
+
+    if let Some(ref name) = query.queue_name_contains {
+        sql.push_str(" AND queue_name LIKE ? ESCAPE '\\'");
+        params_vec.push(Box::new(format!("%{}%", escape_like_pattern(name))));
+    }
+
+    if let Some(ref name) = query.test_name_equals {
+        sql.push_str(" AND test_name = ?");
+        params_vec.push(Box::new(name.clone()));
+    }
+
+    if let Some(ref call_id) = query.call_id_equals {
+        sql.push_str(" AND call_id = ?");
+        params_vec.push(Box::new(call_id.clone()));
+    }
+
+    // json_extract turns JSON booleans into 1/0; compare them as "true"/"false".
+    for (key, value) in &query.log_fields {
+        sql.push_str(
+            " AND (CASE json_type(log_fields, ?) WHEN 'true' THEN 'true' WHEN 'false' THEN 'false'
+                   ELSE CAST(json_extract(log_fields, ?) AS TEXT) END) = ? COLLATE NOCASE",
+        );
+        let path = format!("$.\"{}\"", key);
+        params_vec.push(Box::new(path.clone()));
+        params_vec.push(Box::new(path));
+        params_vec.push(Box::new(value.clone()));
+    }
+
+    if let Some(ref needle) = query.text_contains {
+        if needle.chars().count() >= MIN_FTS_NEEDLE_CHARS {
+            sql.push_str(" AND rowid IN (SELECT rowid FROM events_fts WHERE events_fts MATCH ?)");
+            params_vec.push(Box::new(fts_phrase(needle)));
+        } else {
+            sql.push_str(&format!(
+                " AND event_type IN ({}) AND text LIKE ? ESCAPE '\\'",
+                OUTPUT_EVENT_TYPES_SQL
+            ));
+            params_vec.push(Box::new(format!("%{}%", escape_like_pattern(needle))));
+        }
+    }
+
+    if let Some(ref re) = query.text_regex {
+        sql.push_str(&format!(
+            " AND event_type IN ({}) AND text REGEXP ?",
+            OUTPUT_EVENT_TYPES_SQL
+        ));
+        params_vec.push(Box::new(re.clone()));
+    }
+
+    if let Some(pid) = query.pid_equals {
+        sql.push_str(" AND pid = ?");
+        params_vec.push(Box::new(pid as i64));
+    }
+
+    if let Some(from) = query.timestamp_from_ns {
+        sql.push_str(" AND timestamp_ns >= ?");
+        params_vec.push(Box::new(from));
+    }
+    if let Some(to) = query.timestamp_to_ns {
+        sql.push_str(" AND timestamp_ns <= ?");
+        params_vec.push(Box::new(to));
+    }
+    if let Some(min_dur) = query.min_duration_ns {
+        sql.push_str(" AND duration_ns IS NOT NULL AND duration_ns >= ?");
+        params_vec.push(Box::new(min_dur));
+    }
+
+    if let Some(after) = query.after_rowid {
+        sql.push_str(" AND rowid > ?");
+        params_vec.push(Box::new(after));
+    }
+
+    if let Some(after) = query.after_seq {
+        sql.push_str(" AND seq > ?");
+        params_vec.push(Box::new(after));
+    }
+}
+
 /// Read a JSON column that may be stored as Text, Integer, or Real.
-fn read_json_flexible(
+pub(crate) fn read_json_flexible(
     row: &rusqlite::Row,
     idx: usize,
 ) -> rusqlite::Result<Option<serde_json::Value>> {
@@ -315,23 +716,24 @@ fn read_json_flexible(
         }
         rusqlite::types::ValueRef::Integer(i) => Ok(Some(serde_json::json!(i))),
         rusqlite::types::ValueRef::Real(f) => Ok(Some(serde_json::json!(f))),
-        _ => Ok(None),
+        rusqlite::types::ValueRef::Blob(b) => Ok(decompress_json(b)),
     }
 }
 
-/// Read a JSON column stored only as Text.
+/// Read a JSON column stored as Text, or as a compressed payload Blob.
 fn read_json_text(row: &rusqlite::Row, idx: usize) -> rusqlite::Result<Option<serde_json::Value>> {
     match row.get_ref(idx)? {
         rusqlite::types::ValueRef::Null => Ok(None),
         rusqlite::types::ValueRef::Text(s) => {
             Ok(serde_json::from_str(std::str::from_utf8(s).unwrap_or("null")).ok())
         }
+        rusqlite::types::ValueRef::Blob(b) => Ok(decompress_json(b)),
         _ => Ok(None),
     }
 }
 
-/// Parse an Event from a row with the standard 26-column SELECT order (rowid + 25 data columns).
-fn event_from_row(row: &rusqlite::Row) -> rusqlite::Result<Event> {
+/// Parse an Event from a row with the standard SELECT order (rowid + 32 data columns).
+pub(crate) fn event_from_row(row: &rusqlite::Row) -> rusqlite::Result<Event> {
     let event_type_str: String = row.get(7)?;
     Ok(Event {
         rowid: row.get(0)?,
@@ -363,24 +765,44 @@ fn event_from_row(row: &rusqlite::Row) -> rusqlite::Result<Event> {
         exception_type: row.get(26)?,
         exception_message: row.get(27)?,
         throw_backtrace: read_json_text(row, 28)?,
+        channel: row.get(29)?,
+        seq: row.get(30)?,
+        dump_path: row.get(31)?,
+        queue_name: row.get(32)?,
+        log_fields: read_json_text(row, 33)?,
+        test_name: row.get(34)?,
+        call_id: row.get(35)?,
     })
 }
 
 impl Database {
     pub fn insert_event(&self, event: &Event) -> Result<()> {
-        let conn = self.connection();
-        insert_event_row(&conn, event)?;
-        Ok(())
+        self.insert_events_batch(std::slice::from_ref(event))
+            .map(|_| ())
     }
 
-    pub fn insert_events_batch(&self, events: &[Event]) -> Result<()> {
+    /// Insert events without limit enforcement. Idempotent: events whose id is
+    /// already stored are skipped and counted in `duplicates_ignored`.
+    pub fn insert_events_batch(&self, events: &[Event]) -> Result<EventInsertStats> {
+        let compress = self.payload_compression();
         let mut conn = self.connection();
         let tx = conn.transaction()?;
+        let mut stats = EventInsertStats::default();
+        let mut by_session: Vec<(&str, Vec<&Event>)> = Vec::new();
         for event in events {
-            insert_event_row(&tx, event)?;
+            match by_session
+                .iter_mut()
+                .find(|(sid, _)| *sid == event.session_id)
+            {
+                Some((_, group)) => group.push(event),
+                None => by_session.push((event.session_id.as_str(), vec![event])),
+            }
+        }
+        for (session_id, session_events) in by_session {
+            insert_session_events(&tx, session_id, &session_events, compress, &mut stats)?;
         }
         tx.commit()?;
-        Ok(())
+        Ok(stats)
     }
 
     pub fn query_events<F>(&self, session_id: &str, build_query: F) -> Result<Vec<Event>>
@@ -390,83 +812,17 @@ impl Database {
         let query = build_query(EventQuery::default());
         let conn = self.connection();
 
-        let mut sql = String::from(
-            "SELECT rowid, id, session_id, timestamp_ns, thread_id, thread_name, parent_event_id,
-             event_type, function_name, function_name_raw, source_file, line_number,
-             arguments, return_value, duration_ns, text, sampled, watch_values, pid,
-             signal, fault_address, registers, backtrace, locals, breakpoint_id, logpoint_message,
-             exception_type, exception_message, throw_backtrace
-             FROM events WHERE session_id = ?",
-        );
+        let mut sql = String::from(SELECT_EVENT_SQL);
 
         let mut params_vec: Vec<Box<dyn rusqlite::ToSql>> = vec![Box::new(session_id.to_string())];
+        push_event_filters(&query, &mut sql, &mut params_vec);
 
-        if query.text_events_only {
-            sql.push_str(" AND event_type IN ('stdout', 'stderr')");
-        } else if let Some(ref et) = query.event_type {
-            sql.push_str(" AND event_type = ?");
-            params_vec.push(Box::new(et.as_str().to_string()));
-        }
-
-        if let Some(ref f) = query.function_equals {
-            sql.push_str(
-                " AND event_type IN ('function_enter', 'function_exit') AND function_name = ?",
-            );
-            params_vec.push(Box::new(f.clone()));
-        }
-
-        if let Some(ref f) = query.function_contains {
-            sql.push_str(" AND event_type IN ('function_enter', 'function_exit') AND function_name LIKE ? ESCAPE '\\'");
-            params_vec.push(Box::new(format!("%{}%", escape_like_pattern(f))));
+        // A seq cursor pages forward in write order; otherwise newest first
+        if query.after_seq.is_some() {
+            sql.push_str(" ORDER BY seq ASC");
+        } else {
+            sql.push_str(" ORDER BY timestamp_ns DESC");
         }
-
-        if let Some(ref f) = query.source_file_contains {
-            sql.push_str(" AND source_file LIKE ? ESCAPE '\\'");
-            params_vec.push(Box::new(format!("%{}%", escape_like_pattern(f))));
-        }
-
-        if let Some(is_null) = query.return_value_is_null {
-            if is_null {
-                sql.push_str(" AND return_value IS NULL");
-            } else {
-                sql.push_str(" AND return_value IS NOT NULL");
-            }
-        }
-
-        if let Some(tid) = query.thread_id_equals {
-            sql.push_str(" AND thread_id = ?");
-            params_vec.push(Box::new(tid));
-        }
-
-        if let Some(ref name) = query.thread_name_contains {
-            sql.push_str(" AND thread_name LIKE ? ESCAPE '\\'");
-            params_vec.push(Box::new(format!("%{}%", escape_like_pattern(name))));
-        }
-
-        if let Some(pid) = query.pid_equals {
-            sql.push_str(" AND pid = ?");
-            params_vec.push(Box::new(pid as i64));
-        }
-
-        if let Some(from) = query.timestamp_from_ns {
-            sql.push_str(" AND timestamp_ns >= ?");
-            params_vec.push(Box::new(from));
-        }
-        if let Some(to) = query.timestamp_to_ns {
-            sql.push_str(" AND timestamp_ns <= ?");
-            params_vec.push(Box::new(to));
-        }
-        if let Some(min_dur) = query.min_duration_ns {
-            sql.push_str(" AND duration_ns IS NOT NULL AND duration_ns >= ?");
-            params_vec.push(Box::new(min_dur));
-        }
-
-        if let Some(after) = query.after_rowid {
-            sql.push_str(" AND rowid > ?");
-            params_vec.push(Box::new(after));
-        }
-
-        sql.push_str(" ORDER BY timestamp_ns DESC");
         sql.push_str(" LIMIT ? OFFSET ?");
         params_vec.push(Box::new(query.limit as i64));
         params_vec.push(Box::new(query.offset as i64));
@@ -512,63 +868,7 @@ impl Database {
 
         let mut sql = String::from("SELECT COUNT(*) FROM events WHERE session_id = ?");
         let mut params_vec: Vec<Box<dyn rusqlite::ToSql>> = vec![Box::new(session_id.to_string())];
-
-        if query.text_events_only {
-            sql.push_str(" AND event_type IN ('stdout', 'stderr')");
-        } else if let Some(ref et) = query.event_type {
-            sql.push_str(" AND event_type = ?");
-            params_vec.push(Box::new(et.as_str().to_string()));
-        }
-        if let Some(ref f) = query.function_equals {
-            sql.push_str(
-                " AND event_type IN ('function_enter', 'function_exit') AND function_name = ?",
-            );
-            params_vec.push(Box::new(f.clone()));
-        }
-        if let Some(ref f) = query.function_contains {
-            sql.push_str(" AND event_type IN ('function_enter', 'function_exit') AND function_name LIKE ? ESCAPE '\\'");
-            params_vec.push(Box::new(format!("%{}%", escape_like_pattern(f))));
-        }
-        if let Some(ref f) = query.source_file_contains {
-            sql.push_str(" AND source_file LIKE ? ESCAPE '\\'");
-            params_vec.push(Box::new(format!("%{}%", escape_like_pattern(f))));
-        }
-        if let Some(is_null) = query.return_value_is_null {
-            if is_null {
-                sql.push_str(" AND return_value IS NULL");
-            } else {
-                sql.push_str(" AND return_value IS NOT NULL");
-            }
-        }
-        if let Some(tid) = query.thread_id_equals {
-            sql.push_str(" AND thread_id = ?");
-            params_vec.push(Box::new(tid));
-        }
-        if let Some(ref name) = query.thread_name_contains {
-            sql.push_str(" AND thread_name LIKE ? ESCAPE '\\'");
-            params_vec.push(Box::new(format!("%{}%", escape_like_pattern(name))));
-        }
-        if let Some(pid) = query.pid_equals {
-            sql.push_str(" AND pid = ?");
-            params_vec.push(Box::new(pid as i64));
-        }
-        if let Some(from) = query.timestamp_from_ns {
-            sql.push_str(" AND timestamp_ns >= ?");
-            params_vec.push(Box::new(from));
-        }
-        if let Some(to) = query.timestamp_to_ns {
-            sql.push_str(" AND timestamp_ns <= ?");
-            params_vec.push(Box::new(to));
-        }
-        if let Some(min_dur) = query.min_duration_ns {
-            sql.push_str(" AND duration_ns IS NOT NULL AND duration_ns >= ?");
-            params_vec.push(Box::new(min_dur));
-        }
-
-        if let Some(after) = query.after_rowid {
-            sql.push_str(" AND rowid > ?");
-            params_vec.push(Box::new(after));
-        }
+        push_event_filters(&query, &mut sql, &mut params_vec);
 
         let params_refs: Vec<&dyn rusqlite::ToSql> =
             params_vec.iter().map(|p| p.as_ref()).collect();
@@ -596,6 +896,83 @@ impl Database {
         Ok(deleted as u64)
     }
 
+    /// Thin out trace events older than `policy.after_ns` before the session's
+    /// latest event: one call (enter and its exit) and one variable snapshot
+    /// in every `keep_every` survive, in seq order. Output, crashes and every other
+    /// event type are kept. Each stretch of time is thinned once; the boundary
+    /// is kept in `sessions.downsampled_until_ns`. Returns the events deleted.
+    pub fn downsample_events(&self, session_id: &str, policy: DownsamplePolicy) -> Result<u64> {
+        let mut conn = self.connection();
+        let tx = conn.transaction()?;
+        let (latest, from): (Option<i64>, Option<i64>) = tx.query_row(
+            "SELECT (SELECT MAX(timestamp_ns) FROM events WHERE session_id = ?1),
+                    (SELECT downsampled_until_ns FROM sessions WHERE id = ?1)",
+            params![session_id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )?;
+        let Some(latest) = latest else {
+            return Ok(0);
+        };
+        let until = latest - policy.after_ns;
+        let from = from.unwrap_or(i64::MIN);
+        if until <= from {
+            return Ok(0);
+        }
+
+        // Enters and exits interleave in seq, so events are counted per type
+        let dropped = |event_type: &str| {
+            format!(
+                "SELECT id FROM (
+                     SELECT id, ROW_NUMBER() OVER (ORDER BY seq) AS n FROM events
+                     WHERE session_id = ?1 AND event_type = '{}'
+                     AND timestamp_ns >= ?2 AND timestamp_ns < ?3
+                 ) WHERE n % ?4 != 0",
+                event_type
+            )
+        };
+        let window = params![session_id, from, until, policy.keep_every];
+        // Exits first, while their enters still identify them; an exit may be
+        // newer than the window
+        let mut deleted = tx.execute(
+            &format!(
+                "DELETE FROM events WHERE session_id = ?1 AND event_type = 'function_exit'
+                 AND parent_event_id IN ({})",
+                dropped("function_enter")
+            ),
+            window,
+        )?;
+        for event_type in ["function_enter", "variable_snapshot"] {
+            deleted += tx.execute(
+                &format!(
+                    "DELETE FROM events WHERE session_id = ?1 AND id IN ({})",
+                    dropped(event_type)
+                ),
+                window,
+            )?;
+        }
+        tx.execute(
+            "UPDATE sessions SET downsampled_until_ns = ?2 WHERE id = ?1",
+            params![session_id, until],
+        )?;
+        tx.commit()?;
+        Ok(deleted as u64)
+    }
+
+    /// Start of the full-detail part of a downsampled session (None when
+    /// nothing has been thinned).
+    pub fn downsampled_until(&self, session_id: &str) -> Result<Option<i64>> {
+        use rusqlite::OptionalExtension;
+        let conn = self.connection();
+        let until: Option<Option<i64>> = conn
+            .query_row(
+                "SELECT downsampled_until_ns FROM sessions WHERE id = ?",
+                params![session_id],
+                |row| row.get(0),
+            )
+            .optional()?;
+        Ok(until.flatten())
+    }
+
     /// Insert events with automatic cleanup to enforce per-session limits.
     /// If inserting would exceed max_events_per_session, oldest events are deleted first.
     ///
@@ -676,10 +1053,7 @@ impl Database {
                 }
             }
 
-            for event in session_events {
-                insert_event_row(&tx, event)?;
-                stats.events_inserted += 1;
-            }
+            insert_session_events(&tx, &session_id, &session_events, &mut stats)?;
         }
 
         tx.commit()?;
@@ -697,20 +1071,86 @@ impl Database {
         Ok(result)
     }
 
+    /// Highest seq stored for a session (0 when it has no events).
+    pub fn max_seq_for_session(&self, session_id: &str) -> Result<i64> {
+        let conn = self.connection();
+        let result: i64 = conn.query_row(
+            "SELECT COALESCE(MAX(seq), 0) FROM events WHERE session_id = ?",
+            params![session_id],
+            |row| row.get(0),
+        )?;
+        Ok(result)
+    }
+
+    /// Up to `limit` filtered events with seq > `after_seq`, in write order.
+    /// Tailing counterpart of `query_events`, which pages newest first.
+    pub fn query_events_since_seq<F>(
+        &self,
+        session_id: &str,
+        after_seq: i64,
+        limit: u32,
+        build_query: F,
+    ) -> Result<Vec<Event>>
+    where
+        F: FnOnce(EventQuery) -> EventQuery,
+    {
+        let mut query = build_query(EventQuery::default());
+        query.after_seq = Some(after_seq);
+        let conn = self.connection();
+
+        let mut sql = String::from(SELECT_EVENT_SQL);
+        let mut params_vec: Vec<Box<dyn rusqlite::ToSql>> = vec![Box::new(session_id.to_string())];
+        push_event_filters(&query, &mut sql, &mut params_vec);
+        sql.push_str(" ORDER BY seq ASC LIMIT ?");
+        params_vec.push(Box::new(limit as i64));
+
+        let params_refs: Vec<&dyn rusqlite::ToSql> =
+            params_vec.iter().map(|p| p.as_ref()).collect();
+        let mut stmt = conn.prepare(&sql)?;
+        let events = stmt.query_map(params_refs.as_slice(), event_from_row)?;
+        events
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .map_err(Into::into)
+    }
+
+    /// Get the minimum seq for events in a session. Seq counterpart of
+    /// `min_rowid_for_session` for FIFO drop detection.
+    pub fn min_seq_for_session(&self, session_id: &str) -> Result<Option<i64>> {
+        let conn = self.connection();
+        let result: Option<i64> = conn.query_row(
+            "SELECT MIN(seq) FROM events WHERE session_id = ?",
+            params![session_id],
+            |row| row.get(0),
+        )?;
+        Ok(result)
+    }
+
     pub fn update_event_locals(&self, event_id: &str, locals: &serde_json::Value) -> Result<()> {
+        let locals = payload_value(Some(locals), self.payload_compression());
         let conn = self.connection();
         conn.execute(
             "UPDATE events SET locals = ? WHERE id = ?",
-            params![locals.to_string(), event_id],
+            params![locals, event_id],
         )?;
         Ok(())
     }
 }
 
+/// Aging policy for long sessions (settings `events.downsampleAfterMinutes`
+/// and `events.downsampleKeepEvery`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DownsamplePolicy {
+    /// Age, relative to the session's latest event, past which events are thinned
+    pub after_ns: i64,
+    pub keep_every: u32,
+}
+
 /// Statistics returned from insert_events_with_limit
 #[derive(Debug, Default)]
 pub struct EventInsertStats {
     pub events_inserted: u64,
     pub events_deleted: u64,
     pub sessions_cleaned: Vec<String>,
+    /// Events skipped because their id was already stored (retried batches)
+    pub duplicates_ignored: u64,
 }
@@ -0,0 +1,364 @@
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+
+/// Artifacts one investigation lists.
+pub const MAX_INVESTIGATION_ITEMS: usize = 200;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InvestigationStatus {
+    Open,
+    Closed,
+}
+
+impl InvestigationStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Open => "open",
+            Self::Closed => "closed",
+        }
+    }
+
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "open" => Some(Self::Open),
+            "closed" => Some(Self::Closed),
+            _ => None,
+        }
+    }
+}
+
+/// One bug hunt: the sessions, test runs and notes that belong to it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Investigation {
+    pub id: String,
+    pub title: String,
+    pub project_root: String,
+    pub status: InvestigationStatus,
+    /// Unix epoch seconds
+    pub created_at: i64,
+    /// Unix epoch seconds
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub closed_at: Option<i64>,
+    /// What was found, given at close
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub conclusion: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InvestigationItemKind {
+    Session,
+    TestRun,
+    Note,
+}
+
+impl InvestigationItemKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Session => "session",
+            Self::TestRun => "test_run",
+            Self::Note => "note",
+        }
+    }
+
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "session" => Some(Self::Session),
+            "test_run" => Some(Self::TestRun),
+            "note" => Some(Self::Note),
+            _ => None,
+        }
+    }
+}
+
+/// An artifact linked to an investigation. Sessions and test runs are
+/// referenced by id; a test run's outcome is kept in `details` because the
+/// daemon forgets finished runs.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InvestigationItem {
+    pub kind: InvestigationItemKind,
+    /// Session or test run id
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ref_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub details: Option<serde_json::Value>,
+    /// Unix epoch seconds
+    pub added_at: i64,
+}
+
+fn investigation_from_row(row: &rusqlite::Row) -> rusqlite::Result<Investigation> {
+    Ok(Investigation {
+        id: row.get(0)?,
+        title: row.get(1)?,
+        project_root: row.get(2)?,
+        status: InvestigationStatus::from_str(&row.get::<_, String>(3)?)
+            .unwrap_or(InvestigationStatus::Closed),
+        created_at: row.get(4)?,
+        closed_at: row.get(5)?,
+        conclusion: row.get(6)?,
+    })
+}
+
+const INVESTIGATION_SELECT: &str =
+    "SELECT id, title, project_root, status, created_at, closed_at, conclusion FROM investigations";
+
+impl super::Database {
+    pub fn create_investigation(
+        &self,
+        id: &str,
+        title: &str,
+        project_root: &str,
+    ) -> crate::Result<Investigation> {
+        let investigation = Investigation {
+            id: id.to_string(),
+            title: title.to_string(),
+            project_root: project_root.to_string(),
+            status: InvestigationStatus::Open,
+            created_at: chrono::Utc::now().timestamp(),
+            closed_at: None,
+            conclusion: None,
+        };
+        let conn = self.connection();
+        conn.execute(
+            "INSERT INTO investigations (id, title, project_root, status, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                investigation.id,
+                investigation.title,
+                investigation.project_root,
+                investigation.status.as_str(),
+                investigation.created_at,
+            ],
+        )?;
+        Ok(investigation)
+    }
+
+    pub fn get_investigation(&self, id: &str) -> crate::Result<Option<Investigation>> {
+        use rusqlite::OptionalExtension;
+        let conn = self.connection();
+        let investigation = conn
+            .query_row(
+                &format!("{} WHERE id = ?1", INVESTIGATION_SELECT),
+                params![id],
+                investigation_from_row,
+            )
+            .optional()?;
+        Ok(investigation)
+    }
+
+    /// Investigations, newest first; all projects when `project_root` is None.
+    pub fn list_investigations(
+        &self,
+        project_root: Option<&str>,
+        limit: usize,
+    ) -> crate::Result<Vec<Investigation>> {
+        let conn = self.connection();
+        let mut stmt = conn.prepare(&format!(
+            "{} WHERE ?1 IS NULL OR project_root = ?1 ORDER BY created_at DESC, rowid DESC LIMIT ?2",
+            INVESTIGATION_SELECT
+        ))?;
+        let rows = stmt.query_map(params![project_root, limit as i64], investigation_from_row)?;
+        rows.collect::<std::result::Result<Vec<_>, _>>()
+            .map_err(Into::into)
+    }
+
+    /// Link an artifact. A session or test run already linked only gets its
+    /// note and details updated; returns whether a new item was added.
+    pub fn add_investigation_item(
+        &self,
+        investigation_id: &str,
+        kind: InvestigationItemKind,
+        ref_id: Option<&str>,
+        note: Option<&str>,
+        details: Option<&serde_json::Value>,
+    ) -> crate::Result<bool> {
+        let details = details.map(serde_json::to_string).transpose()?;
+        let conn = self.connection();
+        if ref_id.is_some() {
+            let updated = conn.execute(
+                "UPDATE investigation_items SET
+                    note = COALESCE(?4, note),
+                    details = COALESCE(?5, details)
+                 WHERE investigation_id = ?1 AND kind = ?2 AND ref_id = ?3",
+                params![investigation_id, kind.as_str(), ref_id, note, details],
+            )?;
+            if updated > 0 {
+                return Ok(false);
+            }
+        }
+        conn.execute(
+            "INSERT INTO investigation_items (investigation_id, kind, ref_id, note, details, added_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                investigation_id,
+                kind.as_str(),
+                ref_id,
+                note,
+                details,
+                chrono::Utc::now().timestamp(),
+            ],
+        )?;
+        Ok(true)
+    }
+
+    /// Items of an investigation in the order they were added.
+    pub fn investigation_items(
+        &self,
+        investigation_id: &str,
+    ) -> crate::Result<Vec<InvestigationItem>> {
+        let conn = self.connection();
+        let mut stmt = conn.prepare(
+            "SELECT kind, ref_id, note, details, added_at FROM investigation_items
+             WHERE investigation_id = ?1 ORDER BY id LIMIT ?2",
+        )?;
+        let rows = stmt.query_map(
+            params![investigation_id, MAX_INVESTIGATION_ITEMS as i64],
+            |row| {
+                let details: Option<String> = row.get(3)?;
+                Ok(InvestigationItem {
+                    kind: InvestigationItemKind::from_str(&row.get::<_, String>(0)?)
+                        .unwrap_or(InvestigationItemKind::Note),
+                    ref_id: row.get(1)?,
+                    note: row.get(2)?,
+                    details: details.and_then(|d| serde_json::from_str(&d).ok()),
+                    added_at: row.get(4)?,
+                })
+            },
+        )?;
+        rows.collect::<std::result::Result<Vec<_>, _>>()
+            .map_err(Into::into)
+    }
+
+    pub fn investigation_item_count(&self, investigation_id: &str) -> crate::Result<usize> {
+        let conn = self.connection();
+        let count: i64 = conn.query_row(
+            "SELECT COUNT(*) FROM investigation_items WHERE investigation_id = ?1",
+            params![investigation_id],
+            |row| row.get(0),
+        )?;
+        Ok(count as usize)
+    }
+
+    pub fn close_investigation(&self, id: &str, conclusion: Option<&str>) -> crate::Result<()> {
+        let conn = self.connection();
+        conn.execute(
+            "UPDATE investigations SET status = ?2, closed_at = ?3,
+                conclusion = COALESCE(?4, conclusion)
+             WHERE id = ?1",
+            params![
+                id,
+                InvestigationStatus::Closed.as_str(),
+                chrono::Utc::now().timestamp(),
+                conclusion,
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// The most recent investigation a session was added to.
+    pub fn investigation_for_session(
+        &self,
+        session_id: &str,
+    ) -> crate::Result<Option<Investigation>> {
+        use rusqlite::OptionalExtension;
+        let conn = self.connection();
+        let investigation = conn
+            .query_row(
+                &format!(
+                    "{} WHERE id = (SELECT investigation_id FROM investigation_items
+                        WHERE kind = 'session' AND ref_id = ?1 ORDER BY id DESC LIMIT 1)",
+                    INVESTIGATION_SELECT
+                ),
+                params![session_id],
+                investigation_from_row,
+            )
+            .optional()?;
+        Ok(investigation)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::Database;
+
+    #[test]
+    fn test_investigation_lifecycle() {
+        let db = Database::open_in_memory().unwrap();
+        let inv = db
+            .create_investigation("inv-1", "crash on resize", "/proj")
+            .unwrap();
+        assert_eq!(inv.status, InvestigationStatus::Open);
+        db.create_investigation("inv-2", "other", "/elsewhere")
+            .unwrap();
+
+        assert!(db
+            .add_investigation_item(
+                "inv-1",
+                InvestigationItemKind::Session,
+                Some("app-1"),
+                None,
+                None
+            )
+            .unwrap());
+        assert!(db
+            .add_investigation_item(
+                "inv-1",
+                InvestigationItemKind::Note,
+                None,
+                Some("only with 2 windows"),
+                None
+            )
+            .unwrap());
+        let details = serde_json::json!({ "status": "completed", "failed": 1 });
+        assert!(db
+            .add_investigation_item(
+                "inv-1",
+                InvestigationItemKind::TestRun,
+                Some("test-1"),
+                None,
+                Some(&details)
+            )
+            .unwrap());
+        // Re-adding a session annotates it instead of duplicating it
+        assert!(!db
+            .add_investigation_item(
+                "inv-1",
+                InvestigationItemKind::Session,
+                Some("app-1"),
+                Some("the crash"),
+                None
+            )
+            .unwrap());
+
+        let items = db.investigation_items("inv-1").unwrap();
+        assert_eq!(items.len(), 3);
+        assert_eq!(db.investigation_item_count("inv-1").unwrap(), 3);
+        assert_eq!(items[0].ref_id.as_deref(), Some("app-1"));
+        assert_eq!(items[0].note.as_deref(), Some("the crash"));
+        assert_eq!(items[1].kind, InvestigationItemKind::Note);
+        assert_eq!(items[2].details, Some(details));
+
+        assert_eq!(
+            db.investigation_for_session("app-1").unwrap().unwrap().id,
+            "inv-1"
+        );
+        assert!(db.investigation_for_session("app-2").unwrap().is_none());
+
+        db.close_investigation("inv-1", Some("off-by-one in layout"))
+            .unwrap();
+        let closed = db.get_investigation("inv-1").unwrap().unwrap();
+        assert_eq!(closed.status, InvestigationStatus::Closed);
+        assert!(closed.closed_at.is_some());
+        assert_eq!(closed.conclusion.as_deref(), Some("off-by-one in layout"));
+
+        assert_eq!(db.list_investigations(Some("/proj"), 10).unwrap().len(), 1);
+        assert_eq!(db.list_investigations(None, 10).unwrap().len(), 2);
+        assert!(db.get_investigation("missing").unwrap().is_none());
+    }
+}
@@ -0,0 +1,99 @@
+//! Lock contention per call site, from the `lock` events of `@locks` tracing.
+
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+
+/// Call sites reported in debug_session status.
+pub const MAX_LOCK_SITES: usize = 10;
+
+/// Contention at one call site of a lock function.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LockSite {
+    /// Function that called the lock
+    pub site: String,
+    /// Contended acquisitions that completed
+    pub contended: u64,
+    pub total_wait_ns: u64,
+    pub max_wait_ns: u64,
+    /// Threads that waited here
+    pub threads: u64,
+    /// Waits reported while still blocked (over a second)
+    pub blocked: u64,
+}
+
+impl super::Database {
+    /// Most contended lock call sites: those with blocked waits first, then by
+    /// total time spent waiting.
+    pub fn lock_contention(&self, session_id: &str, limit: usize) -> crate::Result<Vec<LockSite>> {
+        let conn = self.connection();
+        // Blocked reports carry no duration; the contended event that ends
+        // the wait does
+        let mut stmt = conn.prepare(
+            "SELECT function_name,
+                    COUNT(duration_ns),
+                    COALESCE(SUM(duration_ns), 0),
+                    COALESCE(MAX(duration_ns), 0),
+                    COUNT(DISTINCT thread_id),
+                    COUNT(*) - COUNT(duration_ns)
+             FROM events
+             WHERE session_id = ?1 AND event_type = 'lock'
+             GROUP BY function_name
+             ORDER BY COUNT(*) - COUNT(duration_ns) DESC, SUM(duration_ns) DESC
+             LIMIT ?2",
+        )?;
+        let rows = stmt.query_map(params![session_id, limit as i64], |row| {
+            Ok(LockSite {
+                site: row.get(0)?,
+                contended: row.get::<_, i64>(1)? as u64,
+                total_wait_ns: row.get::<_, i64>(2)?.max(0) as u64,
+                max_wait_ns: row.get::<_, i64>(3)?.max(0) as u64,
+                threads: row.get::<_, i64>(4)? as u64,
+                blocked: row.get::<_, i64>(5)? as u64,
+            })
+        })?;
+        rows.collect::<std::result::Result<Vec<_>, _>>()
+            .map_err(Into::into)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::db::{Database, Event, EventType};
+
+    fn lock(id: &str, site: &str, thread_id: i64, wait_ns: Option<i64>) -> Event {
+        Event {
+            id: id.to_string(),
+            session_id: "s".to_string(),
+            event_type: EventType::Lock,
+            function_name: site.to_string(),
+            thread_id,
+            duration_ns: wait_ns,
+            ..Event::default()
+        }
+    }
+
+    #[test]
+    fn test_lock_contention_ranks_blocked_sites_first() {
+        let db = Database::open_in_memory().unwrap();
+        db.create_session("s", "/bin/app", "/home", 1).unwrap();
+        db.insert_events_batch(&[
+            lock("1", "Cache::get", 1, Some(200_000)),
+            lock("2", "Cache::get", 2, Some(900_000)),
+            lock("3", "Pool::checkout", 3, None),
+            lock("4", "Pool::checkout", 4, Some(5_000)),
+        ])
+        .unwrap();
+
+        let sites = db.lock_contention("s", 10).unwrap();
+        assert_eq!(sites.len(), 2);
+        assert_eq!(sites[0].site, "Pool::checkout");
+        assert_eq!(sites[0].blocked, 1);
+        assert_eq!(sites[0].contended, 1);
+        assert_eq!(sites[1].site, "Cache::get");
+        assert_eq!(sites[1].contended, 2);
+        assert_eq!(sites[1].total_wait_ns, 1_100_000);
+        assert_eq!(sites[1].max_wait_ns, 900_000);
+        assert_eq!(sites[1].threads, 2);
+    }
+}
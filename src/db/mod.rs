@@ -1,11 +1,40 @@
 mod baselines;
+mod crashes;
+mod diff;
 mod event;
+mod investigation;
+mod locks;
+mod poll_series;
+mod projects;
+mod query_guard;
+mod report;
+mod return_baselines;
 mod schema;
 mod session;
-
-pub use event::{Event, EventInsertStats, EventType, TraceEventSummary, TraceEventVerbose};
-pub use schema::Database;
-pub use session::{Session, SessionStatus};
+mod timeline;
+
+pub use baselines::{BaselineStats, TestHistory};
+pub use crashes::{crash_signature, CrashRecord};
+pub use diff::{DiffOptions, SessionDiff, MAX_DIFF_EXIT_ROWS};
+pub use event::{
+    DownsamplePolicy, Event, EventInsertStats, EventQuery, EventType, TraceEventSummary,
+    TraceEventVerbose,
+};
+pub use investigation::{
+    Investigation, InvestigationItem, InvestigationItemKind, InvestigationStatus,
+    MAX_INVESTIGATION_ITEMS,
+};
+pub use locks::{LockSite, MAX_LOCK_SITES};
+pub use poll_series::{PollSeries, MAX_POLL_SAMPLES};
+pub use projects::{normalize_project_root, Project, ProjectCleanup};
+pub use query_guard::{EventGroups, Guarded, SlowQuery, MAX_SLOW_QUERIES, SLOW_QUERY_THRESHOLD};
+pub use report::{FunctionTime, MAX_WATCH_SAMPLES};
+pub use return_baselines::{
+    return_anomalies, FunctionProfile, ReturnAnomaly, ReturnAnomalyKind, MAX_RETURN_ANOMALIES,
+};
+pub use schema::{Database, SyncMode};
+pub use session::{Session, SessionLaunch, SessionStatus};
+pub use timeline::{CallConsistency, SiblingDirection, ThreadCallConsistency, MAX_STACK_DEPTH};
 
 #[cfg(test)]
 mod tests {
@@ -53,6 +82,24 @@ mod tests {
         assert!(db.get_session("s1").unwrap().is_none());
     }
 
+    #[test]
+    fn test_session_launch_round_trip() {
+        let (_dir, db) = test_db_with_session("s1");
+        assert!(db.get_session_launch("s1").unwrap().is_none());
+        assert!(db.get_session_launch("missing").unwrap().is_none());
+
+        let launch = SessionLaunch {
+            argv: vec!["/bin/test".into(), "--fast".into()],
+            cwd: "/home".into(),
+            env: [("RUST_LOG".to_string(), "debug".to_string())]
+                .into_iter()
+                .collect(),
+            request: serde_json::json!({ "command": "/bin/test", "args": ["--fast"] }),
+        };
+        db.set_session_launch("s1", &launch).unwrap();
+        assert_eq!(db.get_session_launch("s1").unwrap(), Some(launch));
+    }
+
     #[test]
     fn test_event_insertion_and_query() {
         let (_dir, db) = test_db_with_session("s1");
@@ -310,9 +357,64 @@ mod tests {
             SessionStatus::from_str("stopped"),
             Some(SessionStatus::Stopped)
         );
+        assert_eq!(
+            SessionStatus::from_str("interrupted"),
+            Some(SessionStatus::Interrupted)
+        );
         assert_eq!(SessionStatus::from_str("invalid"), None);
     }
 
+    #[test]
+    fn test_recover_interrupted_sessions() {
+        let (dir, db) = test_db_with_session("s1");
+        db.create_session("s2", "/bin/idle", "/home", 2000).unwrap();
+        db.create_session("s3", "/bin/done", "/home", 3000).unwrap();
+        db.update_session_status("s3", SessionStatus::Exited)
+            .unwrap();
+        db.insert_event(&Event {
+            id: "e1".to_string(),
+            session_id: "s1".to_string(),
+            timestamp_ns: 5_000,
+            event_type: EventType::Stdout,
+            text: Some("last line\n".into()),
+            ..Default::default()
+        })
+        .unwrap();
+        // The daemon dies here; the next one reopens the database
+        drop(db);
+        let db = Database::open(&dir.path().join("test.db")).unwrap();
+
+        let mut recovered = db.recover_interrupted_sessions().unwrap();
+        recovered.sort();
+        assert_eq!(recovered, vec!["s1", "s2"]);
+        assert!(db.get_running_sessions().unwrap().is_empty());
+        assert_eq!(
+            db.get_session("s1").unwrap().unwrap().status,
+            SessionStatus::Interrupted
+        );
+        assert_eq!(
+            db.get_session("s3").unwrap().unwrap().status,
+            SessionStatus::Exited
+        );
+
+        let events = db.query_events("s1", |q| q).unwrap();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[1].event_type, EventType::Recovery);
+        assert_eq!(events[1].timestamp_ns, 5_001);
+        let idle = db.query_events("s2", |q| q).unwrap();
+        assert_eq!(idle.len(), 1);
+        assert_eq!(idle[0].timestamp_ns, 0);
+
+        // Interrupted is terminal, and a second pass finds nothing
+        db.update_session_status("s1", SessionStatus::Exited)
+            .unwrap();
+        assert_eq!(
+            db.get_session("s1").unwrap().unwrap().status,
+            SessionStatus::Interrupted
+        );
+        assert!(db.recover_interrupted_sessions().unwrap().is_empty());
+    }
+
     #[test]
     fn test_event_type_serialization() {
         assert_eq!(EventType::FunctionEnter.as_str(), "function_enter");
@@ -475,6 +577,202 @@ mod tests {
         assert_eq!(no_pid.len(), 0);
     }
 
+    #[test]
+    fn test_queue_name_filter() {
+        let (_dir, db) = test_db_with_session("s1");
+
+        let queues = [
+            Some("com.apple.main-thread"),
+            Some("com.example.network"),
+            Some("com.example.network.decode"),
+            None,
+        ];
+        for (i, queue) in queues.iter().enumerate() {
+            db.insert_event(&Event {
+                id: format!("evt-{}", i),
+                session_id: "s1".into(),
+                timestamp_ns: i as i64 * 1000,
+                thread_id: 1,
+                function_name: "work".into(),
+                queue_name: queue.map(String::from),
+                ..Default::default()
+            })
+            .unwrap();
+        }
+
+        let exact = db
+            .query_events("s1", |q| q.queue_name_equals("com.example.network"))
+            .unwrap();
+        assert_eq!(exact.len(), 1);
+        assert_eq!(exact[0].queue_name.as_deref(), Some("com.example.network"));
+
+        let partial = db
+            .query_events("s1", |q| q.queue_name_contains("example"))
+            .unwrap();
+        assert_eq!(partial.len(), 2);
+
+        let all = db.query_events("s1", |q| q).unwrap();
+        assert_eq!(all.iter().filter(|e| e.queue_name.is_none()).count(), 1);
+    }
+
+    #[test]
+    fn test_test_name_filter() {
+        let (_dir, db) = test_db_with_session("s1");
+
+        let events = [
+            (100, None),
+            (200, Some("tests::spawns_helper")),
+            (200, Some("tests::spawns_helper")),
+            (300, Some("tests::other_helper")),
+        ];
+        for (i, (pid, test)) in events.iter().enumerate() {
+            db.insert_event(&Event {
+                id: format!("evt-{}", i),
+                session_id: "s1".into(),
+                timestamp_ns: i as i64 * 1000,
+                event_type: EventType::FunctionEnter,
+                function_name: "helper::run".into(),
+                pid: Some(*pid),
+                test_name: test.map(String::from),
+                ..Default::default()
+            })
+            .unwrap();
+        }
+
+        let helper = db
+            .query_events("s1", |q| q.test_name_equals("tests::spawns_helper"))
+            .unwrap();
+        assert_eq!(helper.len(), 2);
+        assert!(helper.iter().all(|e| e.pid == Some(200)));
+        assert_eq!(helper[0].test_name.as_deref(), Some("tests::spawns_helper"));
+        assert_eq!(
+            db.count_filtered_events("s1", |q| q.test_name_equals("tests::missing"))
+                .unwrap(),
+            0
+        );
+    }
+
+    #[test]
+    fn test_log_field_filters() {
+        let (_dir, db) = test_db_with_session("s1");
+
+        let records = [
+            Some(serde_json::json!({"level": "ERROR", "module": "auth", "status": 500})),
+            Some(serde_json::json!({"level": "info", "module": "auth", "cached": true})),
+            Some(serde_json::json!({"level": "error", "module": "db"})),
+            None,
+        ];
+        for (i, fields) in records.iter().enumerate() {
+            db.insert_event(&Event {
+                id: format!("out-{}", i),
+                session_id: "s1".into(),
+                timestamp_ns: i as i64 * 1000,
+                event_type: EventType::Stdout,
+                text: Some("line\n".into()),
+                log_fields: fields.clone(),
+                ..Default::default()
+            })
+            .unwrap();
+        }
+
+        let errors = db
+            .query_events("s1", |q| q.log_field("level", "error"))
+            .unwrap();
+        assert_eq!(errors.len(), 2, "value match is case-insensitive");
+
+        let auth_errors = db
+            .query_events("s1", |q| {
+                q.log_field("level", "error").log_field("module", "auth")
+            })
+            .unwrap();
+        assert_eq!(auth_errors.len(), 1);
+        assert_eq!(auth_errors[0].id, "out-0");
+        assert_eq!(auth_errors[0].log_fields.as_ref().unwrap()["status"], 500);
+
+        let by_number = db
+            .count_filtered_events("s1", |q| q.log_field("status", "500"))
+            .unwrap();
+        assert_eq!(by_number, 1);
+        let by_bool = db
+            .query_events("s1", |q| q.log_field("cached", "true"))
+            .unwrap();
+        assert_eq!(by_bool.len(), 1);
+        assert_eq!(by_bool[0].id, "out-1");
+    }
+
+    #[test]
+    fn test_text_search_filters() {
+        let (_dir, db) = test_db_with_session("s1");
+
+        let lines = [
+            (EventType::Stderr, "error: Connection refused (port 5432)"),
+            (EventType::Stdout, "connected to db"),
+            (EventType::Stdout, "retrying \"quoted\" ok"),
+            (EventType::Logpoint, "Connection refused in a logpoint"),
+        ];
+        for (i, (event_type, text)) in lines.iter().enumerate() {
+            db.insert_event(&Event {
+                id: format!("evt-{}", i),
+                session_id: "s1".into(),
+                timestamp_ns: i as i64 * 1000,
+                thread_id: 1,
+                event_type: event_type.clone(),
+                text: Some(text.to_string()),
+                ..Default::default()
+            })
+            .unwrap();
+        }
+
+        // Indexed substring search: case-insensitive, output events only
+        let refused = db
+            .query_events("s1", |q| q.text_contains("connection REFUSED"))
+            .unwrap();
+        assert_eq!(refused.len(), 1);
+        assert_eq!(refused[0].id, "evt-0");
+
+        // FTS operators and quotes are matched literally
+        let quoted = db
+            .query_events("s1", |q| q.text_contains("\"quoted\" OR"))
+            .unwrap();
+        assert!(quoted.is_empty());
+        assert_eq!(
+            db.query_events("s1", |q| q.text_contains("\"quoted\""))
+                .unwrap()
+                .len(),
+            1
+        );
+
+        // Needles shorter than a trigram fall back to LIKE
+        assert_eq!(
+            db.query_events("s1", |q| q.text_contains("db"))
+                .unwrap()
+                .len(),
+            1
+        );
+
+        let ports = db
+            .query_events("s1", |q| q.text_regex(r"port \d{4}\)$"))
+            .unwrap();
+        assert_eq!(ports.len(), 1);
+        assert_eq!(
+            db.count_filtered_events("s1", |q| q.text_regex("^conn"))
+                .unwrap(),
+            1
+        );
+
+        // Deleting events keeps the index in sync
+        db.delete_session("s1").unwrap();
+        let conn = db.connection();
+        let indexed: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM events_fts WHERE events_fts MATCH '\"refused\"'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(indexed, 0);
+    }
+
     #[test]
     fn test_min_duration_filter() {
         let (_dir, db) = test_db_with_session("s1");
@@ -601,6 +899,56 @@ mod tests {
         assert_eq!(arr[0]["name"], "counter");
     }
 
+    #[test]
+    fn test_large_payloads_stored_compressed() {
+        let (_dir, db) = test_db_with_session("s1");
+        let frames: Vec<serde_json::Value> = (0..50)
+            .map(|i| serde_json::json!({ "address": 0x1000 + i, "name": "render_frame" }))
+            .collect();
+        let backtrace = serde_json::Value::Array(frames);
+        let event = |id: &str, args: serde_json::Value| Event {
+            id: id.into(),
+            session_id: "s1".into(),
+            timestamp_ns: 1000,
+            event_type: EventType::Crash,
+            arguments: Some(args),
+            backtrace: Some(backtrace.clone()),
+            ..Default::default()
+        };
+        db.insert_event(&event("big", serde_json::json!(["x".repeat(4096)])))
+            .unwrap();
+        db.set_payload_compression(false);
+        db.insert_event(&event("plain", serde_json::json!([1, 2])))
+            .unwrap();
+
+        let storage = |id: &str| -> (String, String, i64) {
+            db.connection()
+                .query_row(
+                    "SELECT typeof(arguments), typeof(backtrace), LENGTH(arguments) FROM events WHERE id = ?",
+                    [id],
+                    |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+                )
+                .unwrap()
+        };
+        let (args_type, bt_type, args_len) = storage("big");
+        assert_eq!((args_type.as_str(), bt_type.as_str()), ("blob", "blob"));
+        assert!(args_len < 1024);
+        let (args_type, bt_type, _) = storage("plain");
+        assert_eq!((args_type.as_str(), bt_type.as_str()), ("text", "text"));
+
+        // Both layouts read back the same
+        let events = db.query_events("s1", |q| q).unwrap();
+        let big = events.iter().find(|e| e.id == "big").unwrap();
+        assert_eq!(
+            big.arguments.as_ref().unwrap()[0].as_str().unwrap().len(),
+            4096
+        );
+        assert_eq!(big.backtrace.as_ref(), Some(&backtrace));
+        let plain = events.iter().find(|e| e.id == "plain").unwrap();
+        assert_eq!(plain.arguments, Some(serde_json::json!([1, 2])));
+        assert_eq!(plain.backtrace.as_ref(), Some(&backtrace));
+    }
+
     #[test]
     fn test_breakpoint_event_columns() {
         let dir = tempdir().unwrap();
@@ -758,4 +1106,260 @@ mod tests {
             .unwrap();
         assert_eq!(all.len(), 8);
     }
+
+    #[test]
+    fn test_downsample_keeps_every_kth_call_and_all_output() {
+        let (_dir, db) = test_db_with_session("s1");
+
+        // 20 calls (enter + exit) 1ms apart, a stderr line and a crash among them
+        let mut events = Vec::new();
+        for i in 0..20 {
+            let ts = i as i64 * 1_000_000;
+            events.push(Event {
+                id: format!("enter-{}", i),
+                session_id: "s1".into(),
+                timestamp_ns: ts,
+                thread_id: 1,
+                event_type: EventType::FunctionEnter,
+                function_name: format!("f{}", i),
+                ..Default::default()
+            });
+            events.push(Event {
+                id: format!("exit-{}", i),
+                session_id: "s1".into(),
+                timestamp_ns: ts + 500,
+                thread_id: 1,
+                event_type: EventType::FunctionExit,
+                function_name: format!("f{}", i),
+                parent_event_id: Some(format!("enter-{}", i)),
+                ..Default::default()
+            });
+        }
+        events.push(Event {
+            id: "stderr-0".into(),
+            session_id: "s1".into(),
+            timestamp_ns: 2_000_100,
+            thread_id: 1,
+            event_type: EventType::Stderr,
+            text: Some("warning\n".into()),
+            ..Default::default()
+        });
+        events.push(Event {
+            id: "crash-0".into(),
+            session_id: "s1".into(),
+            timestamp_ns: 3_000_100,
+            thread_id: 1,
+            event_type: EventType::Crash,
+            ..Default::default()
+        });
+        db.insert_events_with_limit(&events, 1000).unwrap();
+        assert_eq!(db.downsampled_until("s1").unwrap(), None);
+
+        // Latest event is at ~19ms: thin everything older than 9ms (calls 0..=9)
+        let policy = DownsamplePolicy {
+            after_ns: 10_000_000,
+            keep_every: 5,
+        };
+        let deleted = db.downsample_events("s1", policy).unwrap();
+        // Calls 0, 1, 2, 3, 5, 6, 7, 8 go (ordinals 1-4 and 6-9), with their exits
+        assert_eq!(deleted, 16);
+
+        let enters = db
+            .query_events("s1", |q| q.event_type(EventType::FunctionEnter).limit(100))
+            .unwrap();
+        let mut kept: Vec<String> = enters.iter().map(|e| e.function_name.clone()).collect();
+        kept.sort_by_key(|name| name[1..].parse::<u32>().unwrap());
+        assert_eq!(kept[..2], ["f4".to_string(), "f9".to_string()]);
+        assert_eq!(kept.len(), 12, "recent calls are kept in full");
+        let exits = db
+            .query_events("s1", |q| q.event_type(EventType::FunctionExit).limit(100))
+            .unwrap();
+        assert_eq!(exits.len(), 12, "surviving calls keep their exits");
+
+        let stderr = db
+            .query_events("s1", |q| q.event_type(EventType::Stderr))
+            .unwrap();
+        assert_eq!(stderr.len(), 1);
+        let crashes = db
+            .query_events("s1", |q| q.event_type(EventType::Crash))
+            .unwrap();
+        assert_eq!(crashes.len(), 1);
+
+        // The thinned stretch isn't thinned again
+        assert_eq!(db.downsampled_until("s1").unwrap(), Some(9_000_500));
+        assert_eq!(db.downsample_events("s1", policy).unwrap(), 0);
+    }
+
+    fn trace_events(prefix: &str, count: usize, start_ns: i64) -> Vec<Event> {
+        (0..count)
+            .map(|i| Event {
+                id: format!("{}-{}", prefix, i),
+                session_id: "s1".into(),
+                timestamp_ns: start_ns + i as i64 * 100,
+                thread_id: 1,
+                event_type: EventType::FunctionEnter,
+                function_name: format!("func_{}", i),
+                ..Default::default()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_seq_assigned_per_session_in_insert_order() {
+        let (_dir, db) = test_db_with_session("s1");
+        db.create_session("s2", "/bin/test", "/home", 1).unwrap();
+
+        db.insert_events_batch(&trace_events("a", 3, 0)).unwrap();
+        db.insert_event(&Event {
+            id: "other".into(),
+            session_id: "s2".into(),
+            event_type: EventType::Stdout,
+            ..Default::default()
+        })
+        .unwrap();
+        // Older timestamps still get later seqs: seq reflects write order.
+        db.insert_events_with_limit(&trace_events("b", 2, -1000), 100)
+            .unwrap();
+
+        let mut seqs: Vec<(String, i64)> = db
+            .query_events("s1", |q| q)
+            .unwrap()
+            .into_iter()
+            .map(|e| (e.id, e.seq.unwrap()))
+            .collect();
+        seqs.sort_by_key(|(_, seq)| *seq);
+        let ids: Vec<&str> = seqs.iter().map(|(id, _)| id.as_str()).collect();
+        assert_eq!(ids, vec!["a-0", "a-1", "a-2", "b-0", "b-1"]);
+        assert_eq!(seqs.first().unwrap().1, 1);
+        assert_eq!(seqs.last().unwrap().1, 5);
+
+        let s2 = db.query_events("s2", |q| q).unwrap();
+        assert_eq!(s2[0].seq, Some(1));
+
+        let after = db
+            .query_events("s1", |q| {
+                let mut q = q;
+                q.after_seq = Some(3);
+                q
+            })
+            .unwrap();
+        assert_eq!(after.len(), 2);
+        assert!(after.iter().all(|e| e.id.starts_with("b-")));
+    }
+
+    #[test]
+    fn test_query_events_since_seq_in_write_order() {
+        let (_dir, db) = test_db_with_session("s1");
+        assert_eq!(db.max_seq_for_session("s1").unwrap(), 0);
+        db.insert_events_batch(&trace_events("a", 3, 0)).unwrap();
+        // Written later with older timestamps: still tailed after a-*.
+        db.insert_events_batch(&trace_events("b", 3, -1000))
+            .unwrap();
+        assert_eq!(db.max_seq_for_session("s1").unwrap(), 6);
+
+        let ids = |events: Vec<Event>| events.into_iter().map(|e| e.id).collect::<Vec<_>>();
+        assert_eq!(
+            ids(db.query_events_since_seq("s1", 2, 3, |q| q).unwrap()),
+            vec!["a-2", "b-0", "b-1"]
+        );
+        assert_eq!(
+            ids(db
+                .query_events_since_seq("s1", 0, 10, |q| q.function_equals("func_1"))
+                .unwrap()),
+            vec!["a-1", "b-1"]
+        );
+        assert!(db
+            .query_events_since_seq("s1", 6, 10, |q| q)
+            .unwrap()
+            .is_empty());
+
+        // A limited page after a cursor is the next events, not the newest
+        let page = db
+            .query_events("s1", |q| {
+                let mut q = q.limit(2);
+                q.after_seq = Some(1);
+                q
+            })
+            .unwrap();
+        assert_eq!(ids(page), vec!["a-1", "a-2"]);
+    }
+
+    #[test]
+    fn test_seq_survives_eviction_and_reopen() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("test.db");
+        {
+            let db = Database::open(&path).unwrap();
+            db.create_session("s1", "/bin/test", "/home", 1).unwrap();
+            db.insert_events_with_limit(&trace_events("a", 5, 0), 5)
+                .unwrap();
+            // Evicts every existing event; numbering must not restart.
+            db.insert_events_with_limit(&trace_events("b", 5, 1000), 5)
+                .unwrap();
+            assert_eq!(db.min_seq_for_session("s1").unwrap(), Some(6));
+        }
+
+        // Simulated daemon restart: a fresh connection continues the sequence.
+        let db = Database::open(&path).unwrap();
+        db.insert_events_batch(&trace_events("c", 1, 2000)).unwrap();
+        let latest = db
+            .query_events("s1", |q| {
+                let mut q = q;
+                q.after_seq = Some(10);
+                q
+            })
+            .unwrap();
+        assert_eq!(latest.len(), 1);
+        assert_eq!(latest[0].id, "c-0");
+        assert_eq!(latest[0].seq, Some(11));
+    }
+
+    #[test]
+    fn test_open_read_only_reads_but_never_writes() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("test.db");
+        assert!(Database::open_read_only(&path).is_err());
+
+        let db = Database::open(&path).unwrap();
+        db.create_session("s1", "/bin/test", "/home", 1).unwrap();
+        db.insert_events_batch(&trace_events("a", 2, 0)).unwrap();
+
+        let shared = Database::open_read_only(&path).unwrap();
+        assert!(shared.get_session("s1").unwrap().is_some());
+        assert_eq!(shared.query_events("s1", |q| q).unwrap().len(), 2);
+        assert!(shared
+            .create_session("s2", "/bin/test", "/home", 1)
+            .is_err());
+    }
+
+    #[test]
+    fn test_retried_batch_is_idempotent() {
+        let (_dir, db) = test_db_with_session("s1");
+
+        let stats = db.insert_events_batch(&trace_events("a", 3, 0)).unwrap();
+        assert_eq!(stats.events_inserted, 3);
+        assert_eq!(stats.duplicates_ignored, 0);
+
+        // A retry resends the same ids alongside new ones.
+        let mut retry = trace_events("a", 3, 0);
+        retry.extend(trace_events("b", 2, 1000));
+        let stats = db.insert_events_with_limit(&retry, 100).unwrap();
+        assert_eq!(stats.events_inserted, 2);
+        assert_eq!(stats.duplicates_ignored, 3);
+
+        assert_eq!(db.count_session_events("s1").unwrap(), 5);
+        // Skipped duplicates don't leave holes in the seq numbering.
+        let next = db.insert_events_batch(&trace_events("c", 1, 2000)).unwrap();
+        assert_eq!(next.events_inserted, 1);
+        let latest = db
+            .query_events("s1", |q| {
+                let mut q = q;
+                q.after_seq = Some(5);
+                q
+            })
+            .unwrap();
+        assert_eq!(latest.len(), 1);
+        assert_eq!(latest[0].id, "c-0");
+        assert_eq!(latest[0].seq, Some(6));
+    }
 }
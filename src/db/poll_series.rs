@@ -0,0 +1,185 @@
+//! debug_memory poll results read back as one aligned table.
+
+use rusqlite::params;
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// Samples read per poll; a 30s poll at 50ms takes 600.
+pub const MAX_POLL_SAMPLES: usize = 1_000;
+
+/// One poll's `variable_snapshot` events: a shared timestamp column and one
+/// column per target, all the same length. A target missing from a sample
+/// is null there.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PollSeries {
+    pub poll_id: String,
+    pub timestamps_ns: Vec<i64>,
+    pub columns: BTreeMap<String, Vec<serde_json::Value>>,
+}
+
+impl PollSeries {
+    /// `timestamp_ns` followed by one column per target. Strings are written
+    /// bare, structs as JSON, missing values empty.
+    pub fn to_csv(&self) -> String {
+        let mut out = String::from("timestamp_ns");
+        for label in self.columns.keys() {
+            out.push(',');
+            out.push_str(&csv_field(label));
+        }
+        out.push('\n');
+        for (i, ts) in self.timestamps_ns.iter().enumerate() {
+            out.push_str(&ts.to_string());
+            for values in self.columns.values() {
+                out.push(',');
+                let cell = match &values[i] {
+                    serde_json::Value::Null => String::new(),
+                    serde_json::Value::String(s) => s.clone(),
+                    other => other.to_string(),
+                };
+                out.push_str(&csv_field(&cell));
+            }
+            out.push('\n');
+        }
+        out
+    }
+}
+
+fn csv_field(s: &str) -> String {
+    if s.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+impl super::Database {
+    /// Samples of a debug_memory poll, oldest first; the session's most
+    /// recent poll when `poll_id` is None. None if there is no such poll.
+    pub fn memory_poll_series(
+        &self,
+        session_id: &str,
+        poll_id: Option<&str>,
+    ) -> crate::Result<Option<PollSeries>> {
+        use rusqlite::OptionalExtension;
+        let conn = self.connection();
+        let poll_id: Option<String> = match poll_id {
+            Some(id) => Some(id.to_string()),
+            None => conn
+                .query_row(
+                    "SELECT channel FROM events
+                     WHERE session_id = ?1 AND event_type = 'variable_snapshot'
+                       AND channel IS NOT NULL
+                     ORDER BY seq DESC, rowid DESC LIMIT 1",
+                    params![session_id],
+                    |row| row.get(0),
+                )
+                .optional()?,
+        };
+        let Some(poll_id) = poll_id else {
+            return Ok(None);
+        };
+
+        let mut stmt = conn.prepare(
+            "SELECT timestamp_ns, arguments FROM events
+             WHERE session_id = ?1 AND event_type = 'variable_snapshot' AND channel = ?2
+             ORDER BY timestamp_ns, seq
+             LIMIT ?3",
+        )?;
+        let rows = stmt.query_map(
+            params![session_id, poll_id, MAX_POLL_SAMPLES as i64],
+            |row| {
+                Ok((
+                    row.get::<_, i64>(0)?,
+                    super::event::read_json_flexible(row, 1)?,
+                ))
+            },
+        )?;
+
+        let mut series = PollSeries {
+            poll_id,
+            timestamps_ns: Vec::new(),
+            columns: BTreeMap::new(),
+        };
+        for row in rows {
+            let (ts, data) = row?;
+            let sample = series.timestamps_ns.len();
+            series.timestamps_ns.push(ts);
+            if let Some(serde_json::Value::Object(values)) = data {
+                for (label, value) in values {
+                    let column = series.columns.entry(label).or_default();
+                    column.resize(sample, serde_json::Value::Null);
+                    column.push(value);
+                }
+            }
+        }
+        if series.timestamps_ns.is_empty() {
+            return Ok(None);
+        }
+        let samples = series.timestamps_ns.len();
+        for column in series.columns.values_mut() {
+            column.resize(samples, serde_json::Value::Null);
+        }
+        Ok(Some(series))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::db::{Database, Event, EventType};
+    use serde_json::json;
+
+    fn snapshot(id: &str, poll_id: &str, ts: i64, data: serde_json::Value) -> Event {
+        Event {
+            id: id.to_string(),
+            session_id: "s".to_string(),
+            timestamp_ns: ts,
+            event_type: EventType::VariableSnapshot,
+            arguments: Some(data),
+            channel: Some(poll_id.to_string()),
+            ..Event::default()
+        }
+    }
+
+    #[test]
+    fn test_poll_series_aligns_targets() {
+        let db = Database::open_in_memory().unwrap();
+        db.create_session("s", "/bin/app", "/home", 1).unwrap();
+        db.insert_events_batch(&[
+            snapshot("a1", "poll-a", 100, json!({ "gTempo": 120 })),
+            snapshot(
+                "b2",
+                "poll-b",
+                2_000,
+                json!({ "gTempo": 90, "gName": "x,y" }),
+            ),
+            snapshot("b1", "poll-b", 1_000, json!({ "gTempo": 80 })),
+            snapshot("b3", "poll-b", 3_000, json!({ "gName": "z" })),
+        ])
+        .unwrap();
+
+        let latest = db.memory_poll_series("s", None).unwrap().unwrap();
+        assert_eq!(latest.poll_id, "poll-b");
+        assert_eq!(latest.timestamps_ns, vec![1_000, 2_000, 3_000]);
+        assert_eq!(
+            latest.columns["gTempo"],
+            vec![json!(80), json!(90), json!(null)]
+        );
+        assert_eq!(
+            latest.columns["gName"],
+            vec![json!(null), json!("x,y"), json!("z")]
+        );
+        assert_eq!(
+            latest.to_csv(),
+            "timestamp_ns,gName,gTempo\n1000,,80\n2000,\"x,y\",90\n3000,z,\n"
+        );
+
+        let first = db.memory_poll_series("s", Some("poll-a")).unwrap().unwrap();
+        assert_eq!(first.timestamps_ns, vec![100]);
+        assert!(db
+            .memory_poll_series("s", Some("poll-c"))
+            .unwrap()
+            .is_none());
+        assert!(db.memory_poll_series("other", None).unwrap().is_none());
+    }
+}
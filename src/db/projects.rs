@@ -0,0 +1,260 @@
+//! Projects: the repositories sessions, test baselines, crash history and
+//! investigations belong to. Every table keys its rows by `project_root`;
+//! this registry gives each root one normalized spelling and a display name,
+//! so data can be listed per project and removed once its repository is gone.
+
+use rusqlite::{params, Connection};
+use serde::Serialize;
+use std::path::Path;
+
+/// A registered project with what the database holds for it.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Project {
+    pub root: String,
+    pub name: String,
+    /// Unix epoch seconds
+    pub registered_at: i64,
+    /// Unix epoch seconds of the last session or test run
+    pub last_used_at: i64,
+    pub sessions: u64,
+    /// Sessions still marked running; their data is in use
+    pub running_sessions: u64,
+    /// Size of its retained sessions
+    pub retained_bytes: i64,
+    /// The root is still a directory on this machine
+    pub exists: bool,
+}
+
+/// Rows removed by `delete_project_data`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectCleanup {
+    pub sessions: u64,
+    pub events: u64,
+    pub baselines: u64,
+    pub crashes: u64,
+    pub investigations: u64,
+}
+
+/// The spelling a project root is stored under: the canonical path when it
+/// exists (symlinks and `..` resolved), otherwise the path without trailing
+/// slashes.
+pub fn normalize_project_root(root: &str) -> String {
+    if root.is_empty() {
+        return String::new();
+    }
+    if let Ok(path) = std::fs::canonicalize(root) {
+        return path.to_string_lossy().into_owned();
+    }
+    match root.trim_end_matches('/') {
+        "" => "/".to_string(),
+        trimmed => trimmed.to_string(),
+    }
+}
+
+/// The root's last path component, or the root itself.
+fn default_name(root: &str) -> String {
+    Path::new(root)
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_else(|| root.to_string())
+}
+
+/// Insert `root` if new and mark it used now. A given `name` replaces the
+/// stored one.
+pub(super) fn upsert_project(
+    conn: &Connection,
+    root: &str,
+    name: Option<&str>,
+) -> crate::Result<()> {
+    let now = chrono::Utc::now().timestamp();
+    conn.execute(
+        "INSERT INTO projects (root, name, registered_at, last_used_at)
+         VALUES (?1, ?2, ?3, ?3)
+         ON CONFLICT(root) DO UPDATE SET
+            name = COALESCE(?4, name),
+            last_used_at = excluded.last_used_at",
+        params![
+            root,
+            name.map(str::to_string)
+                .unwrap_or_else(|| default_name(root)),
+            now,
+            name
+        ],
+    )?;
+    Ok(())
+}
+
+/// Register the roots rows were recorded under before the project registry
+/// existed.
+pub(super) fn register_unknown_roots(conn: &Connection) -> crate::Result<()> {
+    let mut stmt = conn.prepare(
+        "SELECT project_root FROM sessions
+         UNION SELECT project_root FROM test_baselines
+         UNION SELECT project_root FROM crashes
+         UNION SELECT project_root FROM investigations
+         EXCEPT SELECT root FROM projects",
+    )?;
+    let roots = stmt
+        .query_map([], |row| row.get::<_, String>(0))?
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    for root in roots.iter().filter(|r| !r.is_empty()) {
+        upsert_project(conn, root, None)?;
+    }
+    Ok(())
+}
+
+impl super::Database {
+    /// Register `root` (or mark it used); `name` defaults to the directory name.
+    pub fn register_project(&self, root: &str, name: Option<&str>) -> crate::Result<()> {
+        let conn = self.connection();
+        upsert_project(&conn, root, name)
+    }
+
+    /// Registered projects, most recently used first.
+    pub fn list_projects(&self) -> crate::Result<Vec<Project>> {
+        let conn = self.connection();
+        let mut stmt = conn.prepare(
+            "SELECT p.root, p.name, p.registered_at, p.last_used_at,
+                    (SELECT COUNT(*) FROM sessions s WHERE s.project_root = p.root),
+                    (SELECT COUNT(*) FROM sessions s
+                     WHERE s.project_root = p.root AND s.status = 'running'),
+                    (SELECT COALESCE(SUM(size_bytes), 0) FROM sessions s
+                     WHERE s.project_root = p.root AND s.retained_at IS NOT NULL)
+             FROM projects p
+             ORDER BY p.last_used_at DESC, p.root",
+        )?;
+        let projects = stmt
+            .query_map([], |row| {
+                let root: String = row.get(0)?;
+                Ok(Project {
+                    exists: Path::new(&root).is_dir(),
+                    root,
+                    name: row.get(1)?,
+                    registered_at: row.get(2)?,
+                    last_used_at: row.get(3)?,
+                    sessions: row.get::<_, i64>(4)? as u64,
+                    running_sessions: row.get::<_, i64>(5)? as u64,
+                    retained_bytes: row.get(6)?,
+                })
+            })?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        Ok(projects)
+    }
+
+    /// Remove everything stored for `root`: its sessions and their events,
+    /// test and function baselines, test provenance, crash history,
+    /// investigations and the registration itself.
+    pub fn delete_project_data(&self, root: &str) -> crate::Result<ProjectCleanup> {
+        let mut conn = self.connection();
+        let tx = conn.transaction()?;
+        let events = tx.execute(
+            "DELETE FROM events WHERE session_id IN
+                (SELECT id FROM sessions WHERE project_root = ?1)",
+            params![root],
+        )?;
+        let sessions = tx.execute(
+            "DELETE FROM sessions WHERE project_root = ?1",
+            params![root],
+        )?;
+        let baselines = tx.execute(
+            "DELETE FROM test_baselines WHERE project_root = ?1",
+            params![root],
+        )? + tx.execute(
+            "DELETE FROM function_baselines WHERE project_root = ?1",
+            params![root],
+        )?;
+        tx.execute(
+            "DELETE FROM test_provenance WHERE project_root = ?1",
+            params![root],
+        )?;
+        let crashes = tx.execute("DELETE FROM crashes WHERE project_root = ?1", params![root])?;
+        tx.execute(
+            "DELETE FROM investigation_items WHERE investigation_id IN
+                (SELECT id FROM investigations WHERE project_root = ?1)",
+            params![root],
+        )?;
+        let investigations = tx.execute(
+            "DELETE FROM investigations WHERE project_root = ?1",
+            params![root],
+        )?;
+        tx.execute("DELETE FROM projects WHERE root = ?1", params![root])?;
+        tx.commit()?;
+        Ok(ProjectCleanup {
+            sessions: sessions as u64,
+            events: events as u64,
+            baselines: baselines as u64,
+            crashes: crashes as u64,
+            investigations: investigations as u64,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::normalize_project_root;
+    use crate::db::{Database, Event};
+
+    #[test]
+    fn test_normalize_project_root() {
+        let dir = tempfile::tempdir().unwrap();
+        let canonical = std::fs::canonicalize(dir.path()).unwrap();
+        let canonical = canonical.to_str().unwrap();
+        let nested = dir.path().join("src");
+        std::fs::create_dir(&nested).unwrap();
+
+        assert_eq!(
+            normalize_project_root(&format!("{}/", dir.path().display())),
+            canonical
+        );
+        assert_eq!(
+            normalize_project_root(&format!("{}/..", nested.display())),
+            canonical
+        );
+        assert_eq!(normalize_project_root("/no/such/repo//"), "/no/such/repo");
+        assert_eq!(normalize_project_root("/"), "/");
+    }
+
+    #[test]
+    fn test_projects_registered_listed_and_deleted() {
+        let db = Database::open_in_memory().unwrap();
+        db.create_session("a-1", "/bin/a", "/gone/alpha", 1)
+            .unwrap();
+        db.create_session("b-1", "/bin/b", "/gone/beta", 2).unwrap();
+        db.insert_events_batch(&[Event {
+            id: "e1".to_string(),
+            session_id: "a-1".to_string(),
+            ..Event::default()
+        }])
+        .unwrap();
+        db.record_test_baseline("t1", "/gone/alpha", 10, "passed")
+            .unwrap();
+        db.register_project("/gone/alpha", Some("Alpha")).unwrap();
+
+        let projects = db.list_projects().unwrap();
+        assert_eq!(projects.len(), 2);
+        let alpha = projects.iter().find(|p| p.root == "/gone/alpha").unwrap();
+        assert_eq!(alpha.name, "Alpha");
+        assert_eq!(alpha.sessions, 1);
+        assert_eq!(alpha.running_sessions, 1);
+        assert!(!alpha.exists);
+        let beta = projects.iter().find(|p| p.root == "/gone/beta").unwrap();
+        assert_eq!(beta.name, "beta");
+
+        // Registering again keeps the given name
+        db.register_project("/gone/alpha", None).unwrap();
+        let projects = db.list_projects().unwrap();
+        assert!(projects.iter().any(|p| p.name == "Alpha"));
+
+        let cleanup = db.delete_project_data("/gone/alpha").unwrap();
+        assert_eq!(cleanup.sessions, 1);
+        assert_eq!(cleanup.events, 1);
+        assert_eq!(cleanup.baselines, 1);
+        assert!(db.get_session("a-1").unwrap().is_none());
+        assert!(db.get_session("b-1").unwrap().is_some());
+        let remaining = db.list_projects().unwrap();
+        assert_eq!(remaining.len(), 1);
+        assert_eq!(remaining[0].root, "/gone/beta");
+    }
+}
@@ -0,0 +1,417 @@
+//! Guarded read path for `debug_query`.
+//!
+//! Interactive queries run on a dedicated read-only connection (WAL lets it
+//! read while the event writer commits) and are interrupted once they exceed
+//! a statement timeout, returning whatever rows were produced so far. Queries
+//! slower than `SLOW_QUERY_THRESHOLD` are kept in a small in-memory log that
+//! `debug_health` reports.
+
+use super::event::{event_from_row, push_event_filters, Event, EventQuery, SELECT_EVENT_SQL};
+use super::schema::Database;
+use crate::Result;
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+
+/// Queries at or above this duration are recorded in the slow-query log.
+pub const SLOW_QUERY_THRESHOLD: Duration = Duration::from_millis(250);
+/// Most recent slow queries retained for `debug_health`.
+pub const MAX_SLOW_QUERIES: usize = 50;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SlowQuery {
+    pub session_id: String,
+    /// The request's filters, as sent (minus sessionId)
+    pub filters: serde_json::Value,
+    pub duration_ms: u64,
+    pub timed_out: bool,
+    /// Unix epoch milliseconds
+    pub recorded_at: i64,
+}
+
+/// Result of a guarded query. `timed_out` means `value` may be incomplete.
+#[derive(Debug)]
+pub struct Guarded<T> {
+    pub value: T,
+    pub timed_out: bool,
+    pub elapsed: Duration,
+}
+
+/// Matching events per distinct value of one column.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EventGroups {
+    /// (value, matching events), one page of them
+    pub groups: Vec<(String, u64)>,
+    /// Distinct values before limit/offset (0 for a page past the end)
+    pub total: u64,
+}
+
+/// Run `f` on `conn`, interrupting any statement still running after `timeout`.
+/// The watchdog is joined before returning so a late interrupt can never hit
+/// the next statement on this connection.
+pub(crate) fn run_with_deadline<T>(
+    conn: &Connection,
+    timeout: Duration,
+    f: impl FnOnce(&Connection) -> T,
+) -> T {
+    let interrupt = conn.get_interrupt_handle();
+    let (done_tx, done_rx) = std::sync::mpsc::channel::<()>();
+    let watchdog = std::thread::spawn(move || {
+        if let Err(std::sync::mpsc::RecvTimeoutError::Timeout) = done_rx.recv_timeout(timeout) {
+            interrupt.interrupt();
+        }
+    });
+    let result = f(conn);
+    let _ = done_tx.send(());
+    let _ = watchdog.join();
+    result
+}
+
+fn is_interrupt(e: &rusqlite::Error) -> bool {
+    matches!(
+        e,
+        rusqlite::Error::SqliteFailure(err, _)
+            if err.code == rusqlite::ErrorCode::OperationInterrupted
+    )
+}
+
+impl Database {
+    /// `query_events` on the read-only connection with a statement timeout.
+    /// On timeout the rows read so far are returned with `timed_out` set.
+    pub fn query_events_guarded<F>(
+        &self,
+        session_id: &str,
+        timeout: Duration,
+        build_query: F,
+    ) -> Result<Guarded<Vec<Event>>>
+    where
+        F: FnOnce(EventQuery) -> EventQuery,
+    {
+        let query = build_query(EventQuery::default());
+
+        let mut sql = String::from(SELECT_EVENT_SQL);
+        let mut params_vec: Vec<Box<dyn rusqlite::ToSql>> = vec![Box::new(session_id.to_string())];
+        push_event_filters(&query, &mut sql, &mut params_vec);
+        sql.push_str(" ORDER BY timestamp_ns DESC LIMIT ? OFFSET ?");
+        params_vec.push(Box::new(query.limit as i64));
+        params_vec.push(Box::new(query.offset as i64));
+        let params_refs: Vec<&dyn rusqlite::ToSql> =
+            params_vec.iter().map(|p| p.as_ref()).collect();
+
+        let conn = self.reader();
+        let start = Instant::now();
+        let (events, timed_out) = run_with_deadline(&conn, timeout, |conn| {
+            let mut events = Vec::new();
+            let mut stmt = conn.prepare(&sql)?;
+            let mut rows = stmt.query(params_refs.as_slice())?;
+            loop {
+                match rows.next() {
+                    Ok(Some(row)) => events.push(event_from_row(row)?),
+                    Ok(None) => return Ok((events, false)),
+                    Err(e) if is_interrupt(&e) => return Ok((events, true)),
+                    Err(e) => return Err(e),
+                }
+            }
+        })
+        .or_else(|e| {
+            if is_interrupt(&e) {
+                Ok((Vec::new(), true))
+            } else {
+                Err(e)
+            }
+        })?;
+
+        Ok(Guarded {
+            value: events,
+            timed_out,
+            elapsed: start.elapsed(),
+        })
+    }
+
+    /// `count_filtered_events` on the read-only connection. `None` on timeout.
+    pub fn count_filtered_events_guarded<F>(
+        &self,
+        session_id: &str,
+        timeout: Duration,
+        build_query: F,
+    ) -> Result<Guarded<Option<u64>>>
+    where
+        F: FnOnce(EventQuery) -> EventQuery,
+    {
+        let query = build_query(EventQuery::default());
+
+        let mut sql = String::from("SELECT COUNT(*) FROM events WHERE session_id = ?");
+        let mut params_vec: Vec<Box<dyn rusqlite::ToSql>> = vec![Box::new(session_id.to_string())];
+        push_event_filters(&query, &mut sql, &mut params_vec);
+        let params_refs: Vec<&dyn rusqlite::ToSql> =
+            params_vec.iter().map(|p| p.as_ref()).collect();
+
+        let conn = self.reader();
+        let start = Instant::now();
+        let count = run_with_deadline(&conn, timeout, |conn| {
+            conn.query_row(&sql, params_refs.as_slice(), |row| row.get::<_, i64>(0))
+        });
+        let (value, timed_out) = match count {
+            Ok(n) => (Some(n as u64), false),
+            Err(e) if is_interrupt(&e) => (None, true),
+            Err(e) => return Err(e.into()),
+        };
+
+        Ok(Guarded {
+            value,
+            timed_out,
+            elapsed: start.elapsed(),
+        })
+    }
+
+    /// Events matching the query's filters, grouped by `column` (events
+    /// without a value are left out). Groups are ordered by event count,
+    /// most first, or by value with `by_value`; the query's limit and offset
+    /// page through the groups. On timeout the groups read so far are
+    /// returned with `timed_out` set.
+    pub fn group_events_guarded<F>(
+        &self,
+        session_id: &str,
+        timeout: Duration,
+        column: &'static str,
+        by_value: bool,
+        build_query: F,
+    ) -> Result<Guarded<EventGroups>>
+    where
+        F: FnOnce(EventQuery) -> EventQuery,
+    {
+        let query = build_query(EventQuery::default());
+
+        // COUNT(*) OVER () is evaluated after grouping: the number of groups
+        let mut sql = format!(
+            "SELECT {col}, COUNT(*), COUNT(*) OVER () FROM events WHERE session_id = ?",
+            col = column
+        );
+        let mut params_vec: Vec<Box<dyn rusqlite::ToSql>> = vec![Box::new(session_id.to_string())];
+        push_event_filters(&query, &mut sql, &mut params_vec);
+        sql.push_str(&format!(
+            " AND {col} IS NOT NULL AND {col} != '' GROUP BY {col} ORDER BY {order} LIMIT ? OFFSET ?",
+            col = column,
+            order = if by_value {
+                format!("{} ASC", column)
+            } else {
+                format!("COUNT(*) DESC, {} ASC", column)
+            }
+        ));
+        params_vec.push(Box::new(query.limit as i64));
+        params_vec.push(Box::new(query.offset as i64));
+        let params_refs: Vec<&dyn rusqlite::ToSql> =
+            params_vec.iter().map(|p| p.as_ref()).collect();
+
+        let conn = self.reader();
+        let start = Instant::now();
+        let (groups, total, timed_out) = run_with_deadline(&conn, timeout, |conn| {
+            let mut groups = Vec::new();
+            let mut total = 0;
+            let mut stmt = conn.prepare(&sql)?;
+            let mut rows = stmt.query(params_refs.as_slice())?;
+            loop {
+                match rows.next() {
+                    Ok(Some(row)) => {
+                        groups.push((row.get::<_, String>(0)?, row.get::<_, i64>(1)? as u64));
+                        total = row.get::<_, i64>(2)? as u64;
+                    }
+                    Ok(None) => return Ok((groups, total, false)),
+                    Err(e) if is_interrupt(&e) => return Ok((groups, total, true)),
+                    Err(e) => return Err(e),
+                }
+            }
+        })
+        .or_else(|e| {
+            if is_interrupt(&e) {
+                Ok((Vec::new(), 0, true))
+            } else {
+                Err(e)
+            }
+        })?;
+
+        Ok(Guarded {
+            value: EventGroups { groups, total },
+            timed_out,
+            elapsed: start.elapsed(),
+        })
+    }
+
+    /// Record a query in the slow-query log if it crossed the threshold
+    /// (or timed out). Oldest entries are dropped past `MAX_SLOW_QUERIES`.
+    pub fn record_query_timing(
+        &self,
+        session_id: &str,
+        filters: serde_json::Value,
+        elapsed: Duration,
+        timed_out: bool,
+    ) {
+        if elapsed < SLOW_QUERY_THRESHOLD && !timed_out {
+            return;
+        }
+        tracing::warn!(
+            "Slow debug_query on {} ({}ms{}): {}",
+            session_id,
+            elapsed.as_millis(),
+            if timed_out { ", timed out" } else { "" },
+            filters
+        );
+        let mut log = self.slow_queries.lock().unwrap_or_else(|e| e.into_inner());
+        if log.len() >= MAX_SLOW_QUERIES {
+            log.pop_front();
+        }
+        log.push_back(SlowQuery {
+            session_id: session_id.to_string(),
+            filters,
+            duration_ms: elapsed.as_millis() as u64,
+            timed_out,
+            recorded_at: chrono::Utc::now().timestamp_millis(),
+        });
+    }
+
+    /// Slow queries recorded since the daemon started, most recent first.
+    pub fn slow_queries(&self) -> Vec<SlowQuery> {
+        let log = self.slow_queries.lock().unwrap_or_else(|e| e.into_inner());
+        log.iter().rev().cloned().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::EventType;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_run_with_deadline_interrupts_long_statement() {
+        let conn = Connection::open_in_memory().unwrap();
+        let start = Instant::now();
+        let result = run_with_deadline(&conn, Duration::from_millis(50), |conn| {
+            conn.query_row(
+                "WITH RECURSIVE c(x) AS (SELECT 1 UNION ALL SELECT x + 1 FROM c)
+                 SELECT COUNT(*) FROM c",
+                [],
+                |row| row.get::<_, i64>(0),
+            )
+        });
+        assert!(is_interrupt(&result.unwrap_err()));
+        assert!(start.elapsed() < Duration::from_secs(5));
+
+        // The connection is usable again afterwards
+        let one: i64 = conn.query_row("SELECT 1", [], |row| row.get(0)).unwrap();
+        assert_eq!(one, 1);
+    }
+
+    #[test]
+    fn test_guarded_query_reads_through_readonly_connection() {
+        let dir = tempdir().unwrap();
+        let db = Database::open(&dir.path().join("strobe.db")).unwrap();
+        db.create_session("s1", "/bin/app", "/home", 1).unwrap();
+        for i in 0..3 {
+            db.insert_event(&Event {
+                id: format!("e{}", i),
+                session_id: "s1".to_string(),
+                timestamp_ns: i,
+                event_type: EventType::Stdout,
+                text: Some(format!("line {}", i)),
+                ..Event::default()
+            })
+            .unwrap();
+        }
+
+        let events = db
+            .query_events_guarded("s1", Duration::from_secs(5), |q| q.limit(10))
+            .unwrap();
+        assert!(!events.timed_out);
+        assert_eq!(events.value.len(), 3);
+
+        let count = db
+            .count_filtered_events_guarded("s1", Duration::from_secs(5), |q| q)
+            .unwrap();
+        assert_eq!(count.value, Some(3));
+
+        let err = db
+            .reader()
+            .execute("DELETE FROM events", [])
+            .expect_err("reader must be read-only");
+        assert!(err.to_string().contains("readonly"));
+    }
+
+    #[test]
+    fn test_group_events() {
+        let db = Database::open_in_memory().unwrap();
+        db.create_session("s1", "/bin/app", "/home", 1).unwrap();
+        let calls = [("parse", "main"), ("parse", "worker"), ("emit", "main")];
+        for (i, (function, thread)) in calls.iter().enumerate() {
+            db.insert_event(&Event {
+                id: format!("e{}", i),
+                session_id: "s1".to_string(),
+                timestamp_ns: i as i64,
+                event_type: EventType::FunctionEnter,
+                function_name: function.to_string(),
+                thread_name: Some(thread.to_string()),
+                ..Event::default()
+            })
+            .unwrap();
+        }
+        // No function name: not a group
+        db.insert_event(&Event {
+            id: "out".to_string(),
+            session_id: "s1".to_string(),
+            event_type: EventType::Stdout,
+            text: Some("hello".to_string()),
+            ..Event::default()
+        })
+        .unwrap();
+
+        let timeout = Duration::from_secs(5);
+        let by_count = db
+            .group_events_guarded("s1", timeout, "function_name", false, |q| q)
+            .unwrap();
+        assert!(!by_count.timed_out);
+        assert_eq!(
+            by_count.value,
+            EventGroups {
+                groups: vec![("parse".to_string(), 2), ("emit".to_string(), 1)],
+                total: 2,
+            }
+        );
+
+        let by_value = db
+            .group_events_guarded("s1", timeout, "thread_name", true, |q| {
+                q.function_equals("parse").limit(1)
+            })
+            .unwrap();
+        assert_eq!(by_value.value.groups, vec![("main".to_string(), 1)]);
+        assert_eq!(by_value.value.total, 2);
+    }
+
+    #[test]
+    fn test_slow_query_log_threshold_and_cap() {
+        let db = Database::open_in_memory().unwrap();
+        db.record_query_timing(
+            "s1",
+            serde_json::json!({}),
+            Duration::from_millis(10),
+            false,
+        );
+        assert!(db.slow_queries().is_empty());
+
+        for i in 0..(MAX_SLOW_QUERIES + 5) {
+            db.record_query_timing(
+                "s1",
+                serde_json::json!({ "n": i }),
+                SLOW_QUERY_THRESHOLD,
+                false,
+            );
+        }
+        db.record_query_timing("s2", serde_json::json!({}), Duration::ZERO, true);
+
+        let log = db.slow_queries();
+        assert_eq!(log.len(), MAX_SLOW_QUERIES);
+        assert_eq!(log[0].session_id, "s2");
+        assert!(log[0].timed_out);
+        assert_eq!(log[1].filters["n"], MAX_SLOW_QUERIES + 4);
+    }
+}
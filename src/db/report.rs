@@ -0,0 +1,164 @@
+//! Aggregates for the `strobe report` terminal summary.
+
+use rusqlite::params;
+use std::collections::BTreeMap;
+
+/// Watch samples read per session for sparklines, newest first.
+pub const MAX_WATCH_SAMPLES: usize = 2_000;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FunctionTime {
+    pub function: String,
+    pub calls: u64,
+    /// Sum of function_exit durations, callees included
+    pub total_ns: u64,
+    pub max_ns: u64,
+}
+
+impl super::Database {
+    /// Functions ranked by total time spent in them (inclusive of callees).
+    /// Every exit counts its full duration, so a recursive function's nested
+    /// calls are counted again inside the outer ones and its total can exceed
+    /// the time the session ran.
+    pub fn top_functions_by_time(
+        &self,
+        session_id: &str,
+        limit: usize,
+    ) -> crate::Result<Vec<FunctionTime>> {
+        let conn = self.connection();
+        let mut stmt = conn.prepare(
+            "SELECT function_name, COUNT(*), SUM(duration_ns), MAX(duration_ns)
+             FROM events
+             WHERE session_id = ?1 AND event_type = 'function_exit' AND duration_ns IS NOT NULL
+             GROUP BY function_name
+             ORDER BY SUM(duration_ns) DESC
+             LIMIT ?2",
+        )?;
+        let rows = stmt.query_map(params![session_id, limit as i64], |row| {
+            Ok(FunctionTime {
+                function: row.get(0)?,
+                calls: row.get::<_, i64>(1)? as u64,
+                total_ns: row.get::<_, i64>(2)?.max(0) as u64,
+                max_ns: row.get::<_, i64>(3)?.max(0) as u64,
+            })
+        })?;
+        rows.collect::<std::result::Result<Vec<_>, _>>()
+            .map_err(Into::into)
+    }
+
+    /// Numeric watch values per watch label, oldest first, from the latest
+    /// MAX_WATCH_SAMPLES events that carried watch values. Non-numeric
+    /// values are skipped.
+    pub fn watch_series(&self, session_id: &str) -> crate::Result<BTreeMap<String, Vec<f64>>> {
+        let conn = self.connection();
+        let mut stmt = conn.prepare(
+            "SELECT watch_values FROM events
+             WHERE session_id = ?1 AND watch_values IS NOT NULL
+             ORDER BY seq DESC, rowid DESC
+             LIMIT ?2",
+        )?;
+        let rows = stmt.query_map(params![session_id, MAX_WATCH_SAMPLES as i64], |row| {
+            super::event::read_json_flexible(row, 0)
+        })?;
+        let mut samples = Vec::new();
+        for row in rows {
+            if let Some(serde_json::Value::Object(values)) = row? {
+                samples.push(values);
+            }
+        }
+
+        let mut series: BTreeMap<String, Vec<f64>> = BTreeMap::new();
+        for values in samples.into_iter().rev() {
+            for (label, value) in values {
+                if let Some(v) = numeric_value(&value) {
+                    series.entry(label).or_default().push(v);
+                }
+            }
+        }
+        Ok(series)
+    }
+}
+
+/// Numbers, and strings holding a decimal or 0x-prefixed hex number
+/// (pointers are reported as hex strings). Watches with a delta threshold
+/// record `{ value, skipped }`; their value is used.
+fn numeric_value(value: &serde_json::Value) -> Option<f64> {
+    match value {
+        serde_json::Value::Object(recorded) => recorded.get("value").and_then(numeric_value),
+        serde_json::Value::Number(n) => n.as_f64(),
+        serde_json::Value::String(s) => {
+            let s = s.trim();
+            match s.strip_prefix("0x") {
+                Some(hex) => u64::from_str_radix(hex, 16).ok().map(|v| v as f64),
+                None => s.parse::<f64>().ok().filter(|v| v.is_finite()),
+            }
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::db::{Database, Event, EventType};
+
+    fn exit(id: &str, function: &str, duration_ns: i64) -> Event {
+        Event {
+            id: id.to_string(),
+            session_id: "s".to_string(),
+            event_type: EventType::FunctionExit,
+            function_name: function.to_string(),
+            duration_ns: Some(duration_ns),
+            ..Event::default()
+        }
+    }
+
+    #[test]
+    fn test_top_functions_by_time() {
+        let db = Database::open_in_memory().unwrap();
+        db.create_session("s", "/bin/app", "/home", 1).unwrap();
+        db.insert_events_batch(&[
+            exit("1", "fast", 10),
+            exit("2", "fast", 10),
+            exit("3", "slow", 500),
+            exit("4", "mid", 100),
+            exit("5", "mid", 150),
+        ])
+        .unwrap();
+
+        let top = db.top_functions_by_time("s", 2).unwrap();
+        assert_eq!(top.len(), 2);
+        assert_eq!(top[0].function, "slow");
+        assert_eq!(top[1].function, "mid");
+        assert_eq!(top[1].calls, 2);
+        assert_eq!(top[1].total_ns, 250);
+        assert_eq!(top[1].max_ns, 150);
+    }
+
+    #[test]
+    fn test_watch_series_oldest_first_numeric_only() {
+        let db = Database::open_in_memory().unwrap();
+        db.create_session("s", "/bin/app", "/home", 1).unwrap();
+        let events: Vec<Event> = [
+            serde_json::json!({"gCount": 1, "name": "a"}),
+            serde_json::json!({"gCount": "0x10"}),
+            serde_json::json!({"gCount": 3.5, "level": "-2"}),
+            serde_json::json!({"gCount": {"value": 7, "skipped": 4}}),
+        ]
+        .into_iter()
+        .enumerate()
+        .map(|(i, values)| Event {
+            id: format!("e{}", i),
+            session_id: "s".to_string(),
+            timestamp_ns: i as i64,
+            watch_values: Some(values),
+            ..Event::default()
+        })
+        .collect();
+        db.insert_events_batch(&events).unwrap();
+
+        let series = db.watch_series("s").unwrap();
+        assert_eq!(series["gCount"], vec![1.0, 16.0, 3.5, 7.0]);
+        assert_eq!(series["level"], vec![-2.0]);
+        assert!(!series.contains_key("name"));
+    }
+}
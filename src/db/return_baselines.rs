@@ -0,0 +1,470 @@
+//! Return value and duration profiles of traced functions across test runs.
+//!
+//! Each traced debug_test run stores, per function, how often it returned
+//! null, its most common return values and its average duration. A run is
+//! compared against the last green one before it with the same test filter
+//! (a single test exercises different code than the suite), which turns
+//! "the test fails now" into "parse_header returned null 90% of the time vs
+//! 0% in the last green run".
+
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// (function, return value) groups read per session.
+pub const MAX_PROFILE_ROWS: usize = 50_000;
+/// Functions stored per run, most called first.
+pub const MAX_PROFILE_FUNCTIONS: usize = 500;
+/// Anomalies reported per test run.
+pub const MAX_RETURN_ANOMALIES: usize = 10;
+/// Most common return values kept per function.
+const MAX_TOP_RETURNS: usize = 5;
+/// Traced runs kept per project.
+const MAX_BASELINE_RUNS: usize = 10;
+/// Calls a function needs in both runs before they are compared.
+const MIN_CALLS: u64 = 5;
+/// Change in the share of calls returning a value worth reporting.
+const MIN_SHARE_SHIFT: f64 = 0.5;
+/// A value returned this often in the green run is the function's usual result.
+const MIN_TYPICAL_SHARE: f64 = 0.8;
+const MIN_DURATION_RATIO: f64 = 3.0;
+const MIN_DURATION_DELTA_NS: u64 = 100_000;
+
+/// One function's calls in one run.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FunctionProfile {
+    pub function: String,
+    pub calls: u64,
+    /// Calls that returned null, None, nil, undefined or a null pointer
+    pub null_returns: u64,
+    /// Most common other return values with their call counts, most common
+    /// first. Addresses are left out; they change between runs.
+    pub top_returns: Vec<(serde_json::Value, u64)>,
+    pub avg_duration_ns: Option<u64>,
+}
+
+impl FunctionProfile {
+    fn share(&self, calls: u64) -> f64 {
+        calls as f64 / self.calls.max(1) as f64
+    }
+
+    fn returns_of(&self, value: &serde_json::Value) -> u64 {
+        self.top_returns
+            .iter()
+            .find(|(v, _)| v == value)
+            .map_or(0, |(_, calls)| *calls)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReturnAnomalyKind {
+    /// Returns null much more or much less often
+    NullRate,
+    /// Stopped (or started) returning its usual value
+    TypicalReturn,
+    /// Much slower on average
+    Duration,
+}
+
+/// A traced function behaving differently than in the last green run.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReturnAnomaly {
+    pub function: String,
+    pub kind: ReturnAnomalyKind,
+    /// e.g. "parse_header returned null 90% of the time vs 0% in the last green run"
+    pub message: String,
+    pub calls: u64,
+    pub base_calls: u64,
+    /// The green run's usual return value (typical_return only)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value: Option<serde_json::Value>,
+}
+
+/// Null in the spelling of each tracer: JSON null, a native null pointer,
+/// or the language's null literal.
+fn is_null_return(value: &serde_json::Value) -> bool {
+    match value {
+        serde_json::Value::Null => true,
+        serde_json::Value::String(s) => matches!(
+            s.as_str(),
+            "0x0" | "null" | "NULL" | "nullptr" | "None" | "nil" | "undefined"
+        ),
+        _ => false,
+    }
+}
+
+fn percent(share: f64) -> String {
+    format!("{}%", (share * 100.0).round() as u64)
+}
+
+/// How each function of `current` differs from `base`: return value
+/// shifts first, biggest first, then slowdowns. Functions missing from
+/// either run, or called fewer than MIN_CALLS times in one of them, are not
+/// compared.
+pub fn return_anomalies(
+    base: &[FunctionProfile],
+    current: &[FunctionProfile],
+) -> Vec<ReturnAnomaly> {
+    let base: HashMap<&str, &FunctionProfile> =
+        base.iter().map(|p| (p.function.as_str(), p)).collect();
+    // (score, anomaly); score is the share shift, or the duration ratio
+    let mut found: Vec<(f64, ReturnAnomaly)> = Vec::new();
+    for cur in current {
+        let Some(b) = base.get(cur.function.as_str()) else {
+            continue;
+        };
+        if cur.calls < MIN_CALLS || b.calls < MIN_CALLS {
+            continue;
+        }
+        let anomaly = |kind, message: String, value| ReturnAnomaly {
+            function: cur.function.clone(),
+            kind,
+            message,
+            calls: cur.calls,
+            base_calls: b.calls,
+            value,
+        };
+
+        let (null_share, base_null_share) = (cur.share(cur.null_returns), b.share(b.null_returns));
+        if (null_share - base_null_share).abs() >= MIN_SHARE_SHIFT {
+            let message = format!(
+                "{} returned null {} of the time vs {} in the last green run",
+                cur.function,
+                percent(null_share),
+                percent(base_null_share)
+            );
+            found.push((
+                (null_share - base_null_share).abs(),
+                anomaly(ReturnAnomalyKind::NullRate, message, None),
+            ));
+        } else if let Some((typical, base_returns)) = b.top_returns.first() {
+            let base_share = b.share(*base_returns);
+            let share = cur.share(cur.returns_of(typical));
+            if base_share >= MIN_TYPICAL_SHARE && base_share - share >= MIN_SHARE_SHIFT {
+                let message = format!(
+                    "{} returned {} {} of the time vs {} in the last green run",
+                    cur.function,
+                    typical,
+                    percent(share),
+                    percent(base_share)
+                );
+                found.push((
+                    base_share - share,
+                    anomaly(
+                        ReturnAnomalyKind::TypicalReturn,
+                        message,
+                        Some(typical.clone()),
+                    ),
+                ));
+            }
+        }
+
+        if let (Some(avg), Some(base_avg)) = (cur.avg_duration_ns, b.avg_duration_ns) {
+            let ratio = avg as f64 / base_avg.max(1) as f64;
+            if ratio >= MIN_DURATION_RATIO && avg >= base_avg + MIN_DURATION_DELTA_NS {
+                let message = format!(
+                    "{} took {} on average vs {} in the last green run",
+                    cur.function,
+                    crate::report::format_ns(avg),
+                    crate::report::format_ns(base_avg)
+                );
+                found.push((ratio, anomaly(ReturnAnomalyKind::Duration, message, None)));
+            }
+        }
+    }
+    // Return value changes before slowdowns
+    found.sort_by(|(a, x), (b, y)| {
+        let slow = |r: &ReturnAnomaly| r.kind == ReturnAnomalyKind::Duration;
+        slow(x)
+            .cmp(&slow(y))
+            .then_with(|| b.total_cmp(a))
+            .then_with(|| x.function.cmp(&y.function))
+    });
+    found
+        .into_iter()
+        .take(MAX_RETURN_ANOMALIES)
+        .map(|(_, anomaly)| anomaly)
+        .collect()
+}
+
+impl super::Database {
+    /// Per-function return profile of a session's function_exit events. Past
+    /// MAX_PROFILE_ROWS groups, the most frequent ones are kept.
+    pub fn function_profiles(&self, session_id: &str) -> crate::Result<Vec<FunctionProfile>> {
+        let conn = self.connection();
+        let mut stmt = conn.prepare(
+            "SELECT function_name, return_value, COUNT(*), TOTAL(duration_ns), COUNT(duration_ns)
+             FROM events
+             WHERE session_id = ?1 AND event_type = 'function_exit'
+             GROUP BY function_name, return_value
+             ORDER BY COUNT(*) DESC, function_name
+             LIMIT ?2",
+        )?;
+        let rows = stmt.query_map(params![session_id, MAX_PROFILE_ROWS as i64], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                super::event::read_json_flexible(row, 1)?,
+                row.get::<_, i64>(2)? as u64,
+                row.get::<_, f64>(3)?,
+                row.get::<_, i64>(4)? as u64,
+            ))
+        })?;
+
+        // function -> (profile, total duration, calls with a duration)
+        let mut by_function: HashMap<String, (FunctionProfile, f64, u64)> = HashMap::new();
+        for row in rows {
+            let (function, value, calls, duration_total, timed) = row?;
+            let (profile, total, timed_calls) =
+                by_function.entry(function.clone()).or_insert_with(|| {
+                    let profile = FunctionProfile {
+                        function,
+                        calls: 0,
+                        null_returns: 0,
+                        top_returns: Vec::new(),
+                        avg_duration_ns: None,
+                    };
+                    (profile, 0.0, 0)
+                });
+            profile.calls += calls;
+            *total += duration_total;
+            *timed_calls += timed;
+            let value = value.unwrap_or(serde_json::Value::Null);
+            if is_null_return(&value) {
+                profile.null_returns += calls;
+            } else if !super::diff::looks_like_address(&value) {
+                profile.top_returns.push((value, calls));
+            }
+        }
+
+        let mut profiles: Vec<FunctionProfile> = by_function
+            .into_values()
+            .map(|(mut profile, total, timed_calls)| {
+                profile.top_returns.sort_by(|(a, x), (b, y)| {
+                    y.cmp(x).then_with(|| a.to_string().cmp(&b.to_string()))
+                });
+                profile.top_returns.truncate(MAX_TOP_RETURNS);
+                if timed_calls > 0 {
+                    profile.avg_duration_ns = Some((total / timed_calls as f64).round() as u64);
+                }
+                profile
+            })
+            .collect();
+        profiles.sort_by(|a, b| {
+            b.calls
+                .cmp(&a.calls)
+                .then_with(|| a.function.cmp(&b.function))
+        });
+        profiles.truncate(MAX_PROFILE_FUNCTIONS);
+        Ok(profiles)
+    }
+
+    /// Store one run's profiles under its test filter ("" for the whole
+    /// suite), keeping the last MAX_BASELINE_RUNS runs of the project.
+    pub fn record_function_baselines(
+        &self,
+        project_root: &str,
+        test_filter: &str,
+        session_id: &str,
+        passed: bool,
+        profiles: &[FunctionProfile],
+    ) -> crate::Result<()> {
+        let now = chrono::Utc::now().timestamp();
+        let mut conn = self.connection();
+        let tx = conn.transaction()?;
+        for profile in profiles {
+            tx.execute(
+                "INSERT INTO function_baselines
+                    (project_root, test_filter, session_id, passed, function_name, calls,
+                     null_returns, top_returns, avg_duration_ns, recorded_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+                params![
+                    project_root,
+                    test_filter,
+                    session_id,
+                    passed,
+                    profile.function,
+                    profile.calls as i64,
+                    profile.null_returns as i64,
+                    serde_json::to_string(&profile.top_returns)?,
+                    profile.avg_duration_ns.map(|ns| ns as i64),
+                    now,
+                ],
+            )?;
+        }
+        tx.execute(
+            "DELETE FROM function_baselines WHERE project_root = ?1 AND session_id NOT IN (
+                SELECT session_id FROM function_baselines WHERE project_root = ?1
+                GROUP BY session_id ORDER BY MAX(id) DESC LIMIT ?2
+            )",
+            params![project_root, MAX_BASELINE_RUNS as i64],
+        )?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Profiles of the project's most recent passing run with `test_filter`,
+    /// other than `exclude_session`. Empty if none is stored.
+    pub fn last_green_function_baselines(
+        &self,
+        project_root: &str,
+        test_filter: &str,
+        exclude_session: &str,
+    ) -> crate::Result<Vec<FunctionProfile>> {
+        let conn = self.connection();
+        let mut stmt = conn.prepare(
+            "SELECT function_name, calls, null_returns, top_returns, avg_duration_ns
+             FROM function_baselines
+             WHERE session_id = (
+                SELECT session_id FROM function_baselines
+                WHERE project_root = ?1 AND test_filter = ?2 AND passed = 1
+                  AND session_id != ?3
+                ORDER BY id DESC LIMIT 1
+             ) AND project_root = ?1",
+        )?;
+        let rows = stmt.query_map(params![project_root, test_filter, exclude_session], |row| {
+            let top_returns: String = row.get(3)?;
+            Ok(FunctionProfile {
+                function: row.get(0)?,
+                calls: row.get::<_, i64>(1)? as u64,
+                null_returns: row.get::<_, i64>(2)? as u64,
+                top_returns: serde_json::from_str(&top_returns).unwrap_or_default(),
+                avg_duration_ns: row.get::<_, Option<i64>>(4)?.map(|ns| ns as u64),
+            })
+        })?;
+        rows.collect::<std::result::Result<Vec<_>, _>>()
+            .map_err(Into::into)
+    }
+
+    /// Compare a finished test run's traced functions with the last green
+    /// run of the project with the same test filter, then store the run as
+    /// the next baseline. A run without function events stores nothing.
+    pub fn check_return_anomalies(
+        &self,
+        project_root: &str,
+        test_filter: Option<&str>,
+        session_id: &str,
+        passed: bool,
+    ) -> crate::Result<Vec<ReturnAnomaly>> {
+        let profiles = self.function_profiles(session_id)?;
+        if profiles.is_empty() {
+            return Ok(Vec::new());
+        }
+        let test_filter = test_filter.unwrap_or("");
+        let base = self.last_green_function_baselines(project_root, test_filter, session_id)?;
+        let anomalies = return_anomalies(&base, &profiles);
+        self.record_function_baselines(project_root, test_filter, session_id, passed, &profiles)?;
+        Ok(anomalies)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{Database, Event, EventType};
+    use serde_json::json;
+
+    fn exits(
+        db: &Database,
+        session: &str,
+        function: &str,
+        returns: &[(serde_json::Value, usize)],
+        duration_ns: i64,
+    ) {
+        db.create_session(session, "/bin/app", "/proj", 1).unwrap();
+        let mut events = Vec::new();
+        for (value, count) in returns {
+            for _ in 0..*count {
+                events.push(Event {
+                    id: format!("{}-{}", session, events.len()),
+                    session_id: session.into(),
+                    timestamp_ns: events.len() as i64,
+                    event_type: EventType::FunctionExit,
+                    function_name: function.into(),
+                    return_value: Some(value.clone()),
+                    duration_ns: Some(duration_ns),
+                    ..Event::default()
+                });
+            }
+        }
+        db.insert_events_batch(&events).unwrap();
+    }
+
+    #[test]
+    fn test_return_anomalies_against_last_green_run() {
+        let db = Database::open_in_memory().unwrap();
+        exits(&db, "green", "parse_header", &[(json!("0x1"), 10)], 1_000);
+        let profiles = db.function_profiles("green").unwrap();
+        assert_eq!(profiles[0].calls, 10);
+        assert_eq!(profiles[0].null_returns, 0);
+        assert_eq!(profiles[0].top_returns, vec![(json!("0x1"), 10)]);
+        assert_eq!(profiles[0].avg_duration_ns, Some(1_000));
+        // No green run before the first one
+        assert!(db
+            .check_return_anomalies("/proj", None, "green", true)
+            .unwrap()
+            .is_empty());
+
+        exits(
+            &db,
+            "red",
+            "parse_header",
+            &[(json!("0x0"), 9), (json!("0x1"), 1)],
+            5_000_000,
+        );
+        let anomalies = db
+            .check_return_anomalies("/proj", None, "red", false)
+            .unwrap();
+        assert_eq!(anomalies.len(), 2);
+        assert_eq!(anomalies[0].kind, ReturnAnomalyKind::NullRate);
+        assert_eq!(
+            anomalies[0].message,
+            "parse_header returned null 90% of the time vs 0% in the last green run"
+        );
+        assert_eq!(anomalies[1].kind, ReturnAnomalyKind::Duration);
+
+        // A failing run is never the baseline
+        let base = db
+            .last_green_function_baselines("/proj", "", "other")
+            .unwrap();
+        assert_eq!(base.len(), 1);
+        assert_eq!(base[0].null_returns, 0);
+        // Runs of one test are not compared with suite runs
+        assert!(db
+            .last_green_function_baselines("/proj", "test_parse", "other")
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_typical_return_shift() {
+        let base = vec![FunctionProfile {
+            function: "status".into(),
+            calls: 20,
+            null_returns: 0,
+            top_returns: vec![(json!(200), 19), (json!(404), 1)],
+            avg_duration_ns: None,
+        }];
+        let current = vec![FunctionProfile {
+            function: "status".into(),
+            calls: 10,
+            null_returns: 0,
+            top_returns: vec![(json!(500), 8), (json!(200), 2)],
+            avg_duration_ns: None,
+        }];
+        let anomalies = return_anomalies(&base, &current);
+        assert_eq!(anomalies.len(), 1);
+        assert_eq!(anomalies[0].kind, ReturnAnomalyKind::TypicalReturn);
+        assert_eq!(anomalies[0].value, Some(json!(200)));
+        assert_eq!(
+            anomalies[0].message,
+            "status returned 200 20% of the time vs 95% in the last green run"
+        );
+
+        // Too few calls to tell
+        let mut few = current.clone();
+        few[0].calls = 3;
+        assert!(return_anomalies(&base, &few).is_empty());
+    }
+}
@@ -1,6 +1,11 @@
+use super::query_guard::SlowQuery;
 use crate::Result;
-use rusqlite::{params, Connection};
+use rusqlite::functions::FunctionFlags;
+use rusqlite::types::ValueRef;
+use rusqlite::{params, Connection, OpenFlags};
+use std::collections::VecDeque;
 use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
 
 /// Add a column to a table, ignoring "duplicate column" errors (idempotent migration).
@@ -20,8 +25,76 @@ fn add_column_if_not_exists(
     }
 }
 
+/// Register `regexp(pattern, text)` so SQL can use `text REGEXP ?`. The
+/// compiled pattern is cached per statement; non-text values never match.
+fn register_regexp(conn: &Connection) -> Result<()> {
+    conn.create_scalar_function(
+        "regexp",
+        2,
+        FunctionFlags::SQLITE_UTF8 | FunctionFlags::SQLITE_DETERMINISTIC,
+        |ctx| {
+            let re: Arc<regex::Regex> = ctx.get_or_create_aux(
+                0,
+                |v| -> std::result::Result<_, Box<dyn std::error::Error + Send + Sync>> {
+                    Ok(regex::Regex::new(v.as_str()?)?)
+                },
+            )?;
+            Ok(match ctx.get_raw(1) {
+                ValueRef::Text(t) => std::str::from_utf8(t).is_ok_and(|s| re.is_match(s)),
+                _ => false,
+            })
+        },
+    )?;
+    Ok(())
+}
+
+/// WAL size (pages) past which a checkpoint also shrinks the file (~64 MB).
+const WAL_TRUNCATE_PAGES: i64 = 16_384;
+
+/// WAL size (pages) at which SQLite checkpoints on its own (~128 MB).
+const WAL_AUTOCHECKPOINT_PAGES: i64 = 32_768;
+
+/// When commits reach the disk ("db.syncMode" in settings.json). In WAL mode
+/// a daemon crash loses nothing committed under any mode; they differ on OS
+/// crash or power loss.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SyncMode {
+    /// Never fsync; fastest, recent commits can be lost on power loss
+    Off,
+    /// fsync at checkpoints; the last commits can be lost on power loss
+    #[default]
+    Normal,
+    /// fsync every commit
+    Full,
+}
+
+impl SyncMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Off => "off",
+            Self::Normal => "normal",
+            Self::Full => "full",
+        }
+    }
+
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "off" => Some(Self::Off),
+            "normal" => Some(Self::Normal),
+            "full" => Some(Self::Full),
+            _ => None,
+        }
+    }
+}
+
 pub struct Database {
     pub(crate) conn: Arc<Mutex<Connection>>,
+    /// Read-only connection for interactive queries, so a slow filter never
+    /// holds the lock the event writer needs. Same as `conn` for in-memory DBs.
+    reader: Arc<Mutex<Connection>>,
+    pub(crate) slow_queries: Arc<Mutex<VecDeque<SlowQuery>>>,
+    /// Store large JSON payloads zstd-compressed (settings "db.compressPayloads")
+    compress_payloads: Arc<AtomicBool>,
 }
 
 impl Database {
@@ -31,22 +104,66 @@ impl Database {
         // Enable WAL mode for concurrent access
         // Use query_row to handle PRAGMA that returns a value
         let _: String = conn.query_row("PRAGMA journal_mode=WAL", [], |row| row.get(0))?;
-        conn.execute_batch(
-            "PRAGMA synchronous=NORMAL; PRAGMA busy_timeout=5000; PRAGMA foreign_keys=ON;",
-        )?;
+        // Event writers checkpoint between batches (see checkpoint_wal); the
+        // automatic checkpoint only bounds the WAL if they fall behind
+        conn.execute_batch(&format!(
+            "PRAGMA synchronous=NORMAL; PRAGMA busy_timeout=5000; PRAGMA foreign_keys=ON;
+             PRAGMA wal_autocheckpoint={};",
+            WAL_AUTOCHECKPOINT_PAGES
+        ))?;
+        register_regexp(&conn)?;
 
+        let conn = Arc::new(Mutex::new(conn));
         let db = Self {
-            conn: Arc::new(Mutex::new(conn)),
+            reader: Arc::clone(&conn),
+            conn,
+            slow_queries: Arc::new(Mutex::new(VecDeque::new())),
+            compress_payloads: Arc::new(AtomicBool::new(true)),
         };
 
         db.initialize_schema()?;
-        Ok(db)
+
+        // Open the reader only after the schema exists (read-only can't create it)
+        let reader = Connection::open_with_flags(
+            path,
+            OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+        )?;
+        reader.execute_batch("PRAGMA busy_timeout=5000;")?;
+        register_regexp(&reader)?;
+        Ok(Self {
+            reader: Arc::new(Mutex::new(reader)),
+            ..db
+        })
+    }
+
+    /// Open an existing database without writing to it: no schema setup or
+    /// migrations, and every statement that would write fails. For readers
+    /// running beside the daemon (`strobe serve`).
+    pub fn open_read_only(path: &Path) -> Result<Self> {
+        let conn = Connection::open_with_flags(
+            path,
+            OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+        )?;
+        conn.execute_batch("PRAGMA busy_timeout=5000;")?;
+        register_regexp(&conn)?;
+        let conn = Arc::new(Mutex::new(conn));
+        Ok(Self {
+            reader: Arc::clone(&conn),
+            conn,
+            slow_queries: Arc::new(Mutex::new(VecDeque::new())),
+            compress_payloads: Arc::new(AtomicBool::new(true)),
+        })
     }
 
     pub fn open_in_memory() -> Result<Self> {
         let conn = Connection::open_in_memory()?;
+        register_regexp(&conn)?;
+        let conn = Arc::new(Mutex::new(conn));
         let db = Self {
-            conn: Arc::new(Mutex::new(conn)),
+            reader: Arc::clone(&conn),
+            conn,
+            slow_queries: Arc::new(Mutex::new(VecDeque::new())),
+            compress_payloads: Arc::new(AtomicBool::new(true)),
         };
         db.initialize_schema()?;
         Ok(db)
@@ -96,6 +213,11 @@ impl Database {
         add_column_if_not_exists(&conn, "events", "thread_name", "TEXT")?;
         add_column_if_not_exists(&conn, "sessions", "retained_at", "INTEGER")?;
         add_column_if_not_exists(&conn, "sessions", "size_bytes", "INTEGER")?;
+        // How debug_launch started the process, for debug_session relaunch
+        add_column_if_not_exists(&conn, "sessions", "argv", "JSON")?;
+        add_column_if_not_exists(&conn, "sessions", "cwd", "TEXT")?;
+        add_column_if_not_exists(&conn, "sessions", "env", "JSON")?;
+        add_column_if_not_exists(&conn, "sessions", "launch_request", "JSON")?;
         add_column_if_not_exists(&conn, "events", "pid", "INTEGER")?;
         add_column_if_not_exists(&conn, "events", "signal", "TEXT")?;
         add_column_if_not_exists(&conn, "events", "fault_address", "TEXT")?;
@@ -112,6 +234,32 @@ impl Database {
         add_column_if_not_exists(&conn, "events", "exception_message", "TEXT")?;
         add_column_if_not_exists(&conn, "events", "throw_backtrace", "JSON")?;
 
+        // Auxiliary output channel tag (extra fds / named pipes)
+        add_column_if_not_exists(&conn, "events", "channel", "TEXT")?;
+
+        // Per-session sequence numbers: stable cursor across daemon restarts
+        // (rowids are not). sessions.last_seq is the writer's high-water mark.
+        add_column_if_not_exists(&conn, "events", "seq", "INTEGER")?;
+        add_column_if_not_exists(&conn, "sessions", "last_seq", "INTEGER")?;
+
+        // Trace events before this timestamp have been thinned (events.downsampleAfterMinutes)
+        add_column_if_not_exists(&conn, "sessions", "downsampled_until_ns", "INTEGER")?;
+
+        // Crash summary file written alongside crash events (outlives DB eviction)
+        add_column_if_not_exists(&conn, "events", "dump_path", "TEXT")?;
+
+        // libdispatch queue label per traced call (GCD apps on macOS)
+        add_column_if_not_exists(&conn, "events", "queue_name", "TEXT")?;
+
+        // Parsed structured log fields on output events (launch logFormat)
+        add_column_if_not_exists(&conn, "events", "log_fields", "JSON")?;
+
+        // Owning test of events from a followed child process (debug_test)
+        add_column_if_not_exists(&conn, "events", "test_name", "TEXT")?;
+
+        // Shared id of a call's function_enter and function_exit
+        add_column_if_not_exists(&conn, "events", "call_id", "TEXT")?;
+
         // Test baselines table for historical per-test durations
         conn.execute(
             "CREATE TABLE IF NOT EXISTS test_baselines (
@@ -129,6 +277,109 @@ impl Database {
              ON test_baselines(test_name, project_root, recorded_at DESC)",
             [],
         )?;
+        // Toolchain versions and lockfile hash of each project's latest run
+        // (test::provenance)
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS test_provenance (
+                project_root TEXT PRIMARY KEY,
+                provenance JSON NOT NULL,
+                recorded_at INTEGER NOT NULL
+            )",
+            [],
+        )?;
+
+        // Per-function return value and duration profile of recent traced
+        // test runs, compared against the last green run
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS function_baselines (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                project_root TEXT NOT NULL,
+                test_filter TEXT NOT NULL DEFAULT '',
+                session_id TEXT NOT NULL,
+                passed INTEGER NOT NULL,
+                function_name TEXT NOT NULL,
+                calls INTEGER NOT NULL,
+                null_returns INTEGER NOT NULL,
+                top_returns JSON NOT NULL,
+                avg_duration_ns INTEGER,
+                recorded_at INTEGER NOT NULL
+            )",
+            [],
+        )?;
+        conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_function_baselines_run
+             ON function_baselines(project_root, test_filter, passed, recorded_at DESC)",
+            [],
+        )?;
+
+        // Crash history: one row per distinct crash signature per project
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS crashes (
+                project_root TEXT NOT NULL,
+                signature TEXT NOT NULL,
+                signal TEXT,
+                exception_type TEXT,
+                top_frames JSON NOT NULL,
+                occurrences INTEGER NOT NULL,
+                first_seen INTEGER NOT NULL,
+                last_seen INTEGER NOT NULL,
+                last_session_id TEXT NOT NULL,
+                last_dump_path TEXT,
+                PRIMARY KEY (project_root, signature)
+            )",
+            [],
+        )?;
+
+        // Investigations: sessions, test runs and notes of one bug hunt.
+        // Items reference sessions by id only; deleted sessions stay listed.
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS investigations (
+                id TEXT PRIMARY KEY,
+                title TEXT NOT NULL,
+                project_root TEXT NOT NULL,
+                status TEXT NOT NULL,
+                created_at INTEGER NOT NULL,
+                closed_at INTEGER,
+                conclusion TEXT
+            )",
+            [],
+        )?;
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS investigation_items (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                investigation_id TEXT NOT NULL,
+                kind TEXT NOT NULL,
+                ref_id TEXT,
+                note TEXT,
+                details JSON,
+                added_at INTEGER NOT NULL,
+                FOREIGN KEY (investigation_id) REFERENCES investigations(id)
+            )",
+            [],
+        )?;
+        conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_investigation_items
+             ON investigation_items(investigation_id, id)",
+            [],
+        )?;
+        conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_investigation_refs
+             ON investigation_items(ref_id, kind)",
+            [],
+        )?;
+
+        // Projects the rows above belong to, one per normalized root.
+        // Roots recorded before registration existed are registered here.
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS projects (
+                root TEXT PRIMARY KEY,
+                name TEXT NOT NULL,
+                registered_at INTEGER NOT NULL,
+                last_used_at INTEGER NOT NULL
+            )",
+            [],
+        )?;
+        super::projects::register_unknown_roots(&conn)?;
 
         // Create indexes
         conn.execute(
@@ -152,15 +403,99 @@ impl Database {
             "CREATE INDEX IF NOT EXISTS idx_events_pid ON events(session_id, pid)",
             [],
         )?;
+        conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_events_queue ON events(session_id, queue_name)",
+            [],
+        )?;
 
         conn.execute(
             "CREATE INDEX IF NOT EXISTS idx_events_type ON events(session_id, event_type, timestamp_ns)",
             [],
         )?;
 
-        // Note: FTS5 virtual table is omitted for now due to linker issues
-        // with static SQLite builds. Full-text search can use LIKE queries
-        // or be added later with proper FTS5 linking.
+        conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_events_seq ON events(session_id, seq)",
+            [],
+        )?;
+
+        // Call tree navigation (debug_timeline): children and exits by parent
+        conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_events_parent ON events(session_id, parent_event_id)",
+            [],
+        )?;
+
+        conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_events_call_id ON events(session_id, call_id)",
+            [],
+        )?;
+
+        // A call has at most one exit; a re-delivered exit under a new id is
+        // ignored by INSERT OR IGNORE. Older databases may hold duplicates:
+        // keep the first of each and re-parent the rest under a marked id, so
+        // the index can be built without discarding recorded rows.
+        let exit_index_existed: bool = conn.query_row(
+            "SELECT COUNT(*) > 0 FROM sqlite_master WHERE type='index' AND name='idx_events_call_exit'",
+            [],
+            |row| row.get(0),
+        )?;
+        if !exit_index_existed {
+            let marked = conn.execute(
+                "UPDATE events SET parent_event_id = parent_event_id || '#duplicate-' || rowid
+                 WHERE event_type = 'function_exit'
+                 AND parent_event_id IS NOT NULL
+                 AND rowid NOT IN (
+                     SELECT MIN(rowid) FROM events
+                     WHERE event_type = 'function_exit' AND parent_event_id IS NOT NULL
+                     GROUP BY session_id, parent_event_id
+                 )",
+                [],
+            )?;
+            if marked > 0 {
+                tracing::warn!(
+                    "Marked {} duplicate function_exit event(s) with a '#duplicate-<rowid>' parent id",
+                    marked
+                );
+            }
+        }
+        conn.execute(
+            "CREATE UNIQUE INDEX IF NOT EXISTS idx_events_call_exit
+             ON events(session_id, parent_event_id) WHERE event_type = 'function_exit'",
+            [],
+        )?;
+
+        // Full-text index over process output. External content keyed by
+        // events.rowid, so the text is stored once; the trigram tokenizer
+        // answers substring searches, not just whole words.
+        let fts_existed: bool = conn.query_row(
+            "SELECT COUNT(*) > 0 FROM sqlite_master WHERE type='table' AND name='events_fts'",
+            [],
+            |row| row.get(0),
+        )?;
+        conn.execute_batch(&format!(
+            "CREATE VIRTUAL TABLE IF NOT EXISTS events_fts USING fts5(
+                text, content='events', content_rowid='rowid', tokenize='trigram'
+             );
+             CREATE TRIGGER IF NOT EXISTS events_fts_insert AFTER INSERT ON events
+             WHEN new.text IS NOT NULL AND new.event_type IN ({types}) BEGIN
+                INSERT INTO events_fts(rowid, text) VALUES (new.rowid, new.text);
+             END;
+             CREATE TRIGGER IF NOT EXISTS events_fts_delete AFTER DELETE ON events
+             WHEN old.text IS NOT NULL AND old.event_type IN ({types}) BEGIN
+                INSERT INTO events_fts(events_fts, rowid, text) VALUES ('delete', old.rowid, old.text);
+             END;",
+            types = super::event::OUTPUT_EVENT_TYPES_SQL
+        ))?;
+        if !fts_existed {
+            // Databases from before the index: backfill retained output once
+            conn.execute(
+                &format!(
+                    "INSERT INTO events_fts(rowid, text)
+                     SELECT rowid, text FROM events WHERE text IS NOT NULL AND event_type IN ({})",
+                    super::event::OUTPUT_EVENT_TYPES_SQL
+                ),
+                [],
+            )?;
+        }
 
         Ok(())
     }
@@ -175,15 +510,53 @@ impl Database {
         Ok(count > 0)
     }
 
+    /// Copy committed WAL frames into the database without waiting on
+    /// readers, and truncate the WAL once a burst has grown it past
+    /// `WAL_TRUNCATE_PAGES` and every frame made it in.
+    pub fn checkpoint_wal(&self) -> Result<()> {
+        let conn = self.connection();
+        let (busy, wal_pages, checkpointed): (i64, i64, i64) =
+            conn.query_row("PRAGMA wal_checkpoint(PASSIVE)", [], |row| {
+                Ok((row.get(0)?, row.get(1)?, row.get(2)?))
+            })?;
+        if busy == 0 && wal_pages >= WAL_TRUNCATE_PAGES && checkpointed == wal_pages {
+            conn.query_row("PRAGMA wal_checkpoint(TRUNCATE)", [], |_| Ok(()))?;
+        }
+        Ok(())
+    }
+
+    pub fn set_sync_mode(&self, mode: SyncMode) -> Result<()> {
+        self.connection()
+            .execute_batch(&format!("PRAGMA synchronous={};", mode.as_str()))?;
+        Ok(())
+    }
+
+    /// Whether events written from now on compress their bulky JSON columns.
+    /// Rows already stored are read either way.
+    pub fn set_payload_compression(&self, enabled: bool) {
+        self.compress_payloads.store(enabled, Ordering::Relaxed);
+    }
+
+    pub(crate) fn payload_compression(&self) -> bool {
+        self.compress_payloads.load(Ordering::Relaxed)
+    }
+
     pub(crate) fn connection(&self) -> std::sync::MutexGuard<'_, Connection> {
         self.conn.lock().unwrap()
     }
+
+    pub(crate) fn reader(&self) -> std::sync::MutexGuard<'_, Connection> {
+        self.reader.lock().unwrap()
+    }
 }
 
 impl Clone for Database {
     fn clone(&self) -> Self {
         Self {
             conn: Arc::clone(&self.conn),
+            reader: Arc::clone(&self.reader),
+            slow_queries: Arc::clone(&self.slow_queries),
+            compress_payloads: Arc::clone(&self.compress_payloads),
         }
     }
 }
@@ -2,6 +2,7 @@ use super::Database;
 use crate::Result;
 use rusqlite::params;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
@@ -9,6 +10,9 @@ pub enum SessionStatus {
     Running,
     Exited,
     Stopped,
+    /// Left running by a daemon that died without stopping it (see
+    /// `recover_interrupted_sessions`)
+    Interrupted,
 }
 
 impl SessionStatus {
@@ -17,6 +21,7 @@ impl SessionStatus {
             Self::Running => "running",
             Self::Exited => "exited",
             Self::Stopped => "stopped",
+            Self::Interrupted => "interrupted",
         }
     }
 
@@ -25,6 +30,7 @@ impl SessionStatus {
             "running" => Some(Self::Running),
             "exited" => Some(Self::Exited),
             "stopped" => Some(Self::Stopped),
+            "interrupted" => Some(Self::Interrupted),
             _ => None,
         }
     }
@@ -64,6 +70,20 @@ impl Session {
     }
 }
 
+/// How a session's process was started by debug_launch, kept so
+/// `debug_session` relaunch can start it again the same way.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SessionLaunch {
+    /// Command followed by its arguments
+    pub argv: Vec<String>,
+    /// Working directory the process started in
+    pub cwd: String,
+    /// The launch `env` overrides; the daemon's own environment is not stored
+    pub env: BTreeMap<String, String>,
+    /// The debug_launch arguments as given, minus `env`
+    pub request: serde_json::Value,
+}
+
 /// Convert QueryReturnedNoRows into Ok(None).
 fn optional_query<T>(result: rusqlite::Result<T>) -> Result<Option<T>> {
     match result {
@@ -77,22 +97,63 @@ const SESSION_SELECT: &str =
     "SELECT id, binary_path, project_root, pid, started_at, ended_at, status, retained_at, size_bytes";
 
 impl Database {
-    /// Mark all sessions with status='running' as 'stopped'.
-    /// Called on daemon startup to clean up stale sessions from previous runs.
-    pub fn cleanup_stale_sessions(&self) -> Result<()> {
-        let conn = self.connection();
-        let ended_at = chrono::Utc::now().timestamp();
-        let count = conn.execute(
-            "UPDATE sessions SET status = 'stopped', ended_at = ? WHERE status = 'running'",
-            params![ended_at],
-        )?;
-        if count > 0 {
+    /// Startup recovery after a daemon that died without stopping its
+    /// sessions (SIGKILL, power loss): each session still marked running
+    /// becomes `interrupted` and gets a `recovery` event after its last
+    /// stored one. Event batches are committed in one transaction each, so a
+    /// batch cut off mid-write was rolled back and the stored events end at a
+    /// batch boundary. The status and the event are written in one
+    /// transaction per session. Returns the recovered session ids.
+    pub fn recover_interrupted_sessions(&self) -> Result<Vec<String>> {
+        let orphaned: Vec<(String, Option<i64>)> = {
+            let conn = self.connection();
+            let mut stmt = conn.prepare(
+                "SELECT id, (SELECT MAX(timestamp_ns) FROM events WHERE session_id = sessions.id)
+                 FROM sessions WHERE status = 'running'",
+            )?;
+            let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+            rows.collect::<std::result::Result<Vec<_>, _>>()?
+        };
+
+        let compress = self.payload_compression();
+        for (id, last_ns) in &orphaned {
+            let event = super::Event {
+                id: format!("{}-recovery", id),
+                session_id: id.clone(),
+                timestamp_ns: last_ns.map_or(0, |ns| ns + 1),
+                event_type: super::EventType::Recovery,
+                text: Some(
+                    "The daemon stopped without ending this session; nothing after this point was recorded"
+                        .to_string(),
+                ),
+                ..Default::default()
+            };
+            let mut conn = self.connection();
+            let tx = conn.transaction()?;
+            tx.execute(
+                "UPDATE sessions SET status = ?, ended_at = ? WHERE id = ?",
+                params![
+                    SessionStatus::Interrupted.as_str(),
+                    chrono::Utc::now().timestamp(),
+                    id
+                ],
+            )?;
+            super::event::insert_session_events(
+                &tx,
+                id,
+                &[&event],
+                compress,
+                &mut super::EventInsertStats::default(),
+            )?;
+            tx.commit()?;
+        }
+        if !orphaned.is_empty() {
             tracing::info!(
-                "Cleaned up {} stale running sessions from previous daemon",
-                count
+                "Marked {} session(s) left running by a previous daemon as interrupted",
+                orphaned.len()
             );
         }
-        Ok(())
+        Ok(orphaned.into_iter().map(|(id, _)| id).collect())
     }
 
     pub fn create_session(
@@ -110,6 +171,7 @@ impl Database {
              VALUES (?, ?, ?, ?, ?, ?)",
             params![id, binary_path, project_root, pid, started_at, "running"],
         )?;
+        super::projects::upsert_project(&conn, project_root, None)?;
 
         Ok(Session {
             id: id.to_string(),
@@ -156,7 +218,7 @@ impl Database {
     pub fn update_session_status(&self, id: &str, new_status: SessionStatus) -> Result<()> {
         let conn = self.connection();
 
-        // Validate transition: Stopped is a terminal state
+        // Validate transition: Stopped and Interrupted are terminal states
         let current: Option<String> = conn
             .query_row(
                 "SELECT status FROM sessions WHERE id = ?",
@@ -166,9 +228,11 @@ impl Database {
             .ok();
 
         if let Some(ref current_str) = current {
-            if current_str == "stopped" && new_status != SessionStatus::Stopped {
+            let terminal = current_str == "stopped" || current_str == "interrupted";
+            if terminal && current_str != new_status.as_str() {
                 tracing::warn!(
-                    "Ignoring invalid transition from stopped to {} for session {}",
+                    "Ignoring invalid transition from {} to {} for session {}",
+                    current_str,
                     new_status.as_str(),
                     id
                 );
@@ -196,6 +260,46 @@ impl Database {
         Ok(())
     }
 
+    pub fn set_session_launch(&self, id: &str, launch: &SessionLaunch) -> Result<()> {
+        let conn = self.connection();
+        conn.execute(
+            "UPDATE sessions SET argv = ?, cwd = ?, env = ?, launch_request = ? WHERE id = ?",
+            params![
+                serde_json::to_string(&launch.argv)?,
+                launch.cwd,
+                serde_json::to_string(&launch.env)?,
+                serde_json::to_string(&launch.request)?,
+                id
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// None for unknown sessions and sessions not started by debug_launch.
+    pub fn get_session_launch(&self, id: &str) -> Result<Option<SessionLaunch>> {
+        let conn = self.connection();
+        type LaunchRow = (
+            Option<String>,
+            Option<String>,
+            Option<String>,
+            Option<String>,
+        );
+        let row: Option<LaunchRow> = optional_query(conn.query_row(
+            "SELECT argv, cwd, env, launch_request FROM sessions WHERE id = ?",
+            params![id],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
+        ))?;
+        let Some((Some(argv), Some(cwd), Some(env), Some(request))) = row else {
+            return Ok(None);
+        };
+        Ok(Some(SessionLaunch {
+            argv: serde_json::from_str(&argv)?,
+            cwd,
+            env: serde_json::from_str(&env)?,
+            request: serde_json::from_str(&request)?,
+        }))
+    }
+
     pub fn delete_session(&self, id: &str) -> Result<()> {
         let conn = self.connection();
         conn.execute("DELETE FROM events WHERE session_id = ?", params![id])?;
@@ -218,6 +322,8 @@ impl Database {
                 + LENGTH(COALESCE(function_name_raw,'')) + LENGTH(COALESCE(source_file,''))
                 + LENGTH(COALESCE(arguments,'')) + LENGTH(COALESCE(return_value,''))
                 + LENGTH(COALESCE(text,'')) + LENGTH(COALESCE(thread_name,''))
+                + LENGTH(COALESCE(queue_name,'')) + LENGTH(COALESCE(log_fields,''))
+                + LENGTH(COALESCE(test_name,''))
                 + LENGTH(COALESCE(watch_values,'')) + LENGTH(COALESCE(signal,''))
                 + LENGTH(COALESCE(fault_address,'')) + LENGTH(COALESCE(registers,''))
                 + LENGTH(COALESCE(backtrace,'')) + LENGTH(COALESCE(locals,''))
@@ -236,14 +342,20 @@ impl Database {
     }
 
     pub fn list_retained_sessions(&self) -> Result<Vec<Session>> {
+        self.list_retained_sessions_for(None)
+    }
+
+    /// Retained sessions, newest first; only `project_root`'s when given.
+    pub fn list_retained_sessions_for(&self, project_root: Option<&str>) -> Result<Vec<Session>> {
         let conn = self.connection();
         let mut stmt = conn.prepare(&format!(
-            "{} FROM sessions WHERE retained_at IS NOT NULL ORDER BY retained_at DESC",
+            "{} FROM sessions WHERE retained_at IS NOT NULL
+             AND (?1 IS NULL OR project_root = ?1) ORDER BY retained_at DESC",
             SESSION_SELECT
         ))?;
 
         let sessions = stmt
-            .query_map([], Session::from_row)?
+            .query_map(params![project_root], Session::from_row)?
             .collect::<std::result::Result<Vec<_>, _>>()?;
         Ok(sessions)
     }
@@ -0,0 +1,505 @@
+//! Call tree navigation over recorded events (`debug_timeline`).
+//!
+//! Calls are function_enter events. A call's children are the enters whose
+//! parent_event_id is its id; its function_exit carries the same
+//! parent_event_id. Siblings share a parent and a thread.
+
+use super::event::{event_from_row, SELECT_EVENT_SQL};
+use super::{Event, EventType};
+use rusqlite::{params, OptionalExtension};
+use std::collections::HashMap;
+
+/// Parent links followed when rebuilding a stack (guards against cycles).
+pub const MAX_STACK_DEPTH: usize = 512;
+
+/// Exit/enter pairs of session ?1 where the exit comes first by seq or time.
+const MISORDERED_PAIRS_SQL: &str = "events x JOIN events e ON e.id = x.parent_event_id
+     WHERE x.session_id = ?1 AND x.event_type = 'function_exit'
+     AND e.event_type = 'function_enter'
+     AND (x.seq < e.seq OR x.timestamp_ns < e.timestamp_ns)";
+
+/// Enter/exit pairing per thread (`debug_session` consistency-check).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ThreadCallConsistency {
+    pub pid: Option<u32>,
+    pub thread_id: i64,
+    /// function_exit events whose function_enter is not stored
+    pub orphan_exits: u64,
+    /// function_enter events with no stored function_exit
+    pub unclosed_enters: u64,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CallConsistency {
+    pub orphan_exits: u64,
+    pub unclosed_enters: u64,
+    /// Exits stored (seq) or stamped (timestamp) before their enter
+    pub misordered: u64,
+    /// Threads with orphans or unclosed calls, worst first
+    pub threads: Vec<ThreadCallConsistency>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SiblingDirection {
+    Prev,
+    Next,
+}
+
+impl super::Database {
+    pub fn get_event(&self, session_id: &str, event_id: &str) -> crate::Result<Option<Event>> {
+        let conn = self.connection();
+        let sql = format!("{} AND id = ?", SELECT_EVENT_SQL);
+        Ok(conn
+            .query_row(&sql, params![session_id, event_id], event_from_row)
+            .optional()?)
+    }
+
+    /// The function_exit that closes a call, if recorded.
+    pub fn call_exit(&self, session_id: &str, enter_id: &str) -> crate::Result<Option<Event>> {
+        let conn = self.connection();
+        let sql = format!(
+            "{} AND parent_event_id = ? AND event_type = 'function_exit' LIMIT 1",
+            SELECT_EVENT_SQL
+        );
+        Ok(conn
+            .query_row(&sql, params![session_id, enter_id], event_from_row)
+            .optional()?)
+    }
+
+    /// function_enter events of the given calls, by call id. Ids with no
+    /// stored enter (evicted, or recorded before call ids) are left out.
+    pub fn call_enters(
+        &self,
+        session_id: &str,
+        call_ids: &[String],
+    ) -> crate::Result<HashMap<String, Event>> {
+        let mut enters = HashMap::new();
+        if call_ids.is_empty() {
+            return Ok(enters);
+        }
+        let conn = self.connection();
+        let sql = format!(
+            "{} AND event_type = 'function_enter' AND call_id IN ({})",
+            SELECT_EVENT_SQL,
+            vec!["?"; call_ids.len()].join(", ")
+        );
+        let mut stmt = conn.prepare(&sql)?;
+        let params = std::iter::once(session_id).chain(call_ids.iter().map(String::as_str));
+        let rows = stmt.query_map(rusqlite::params_from_iter(params), event_from_row)?;
+        for event in rows {
+            let event = event?;
+            if let Some(call_id) = event.call_id.clone() {
+                enters.insert(call_id, event);
+            }
+        }
+        Ok(enters)
+    }
+
+    /// Direct callees of a call in call order. Fetches `limit + 1` so the
+    /// caller can tell whether there are more.
+    pub fn call_children(
+        &self,
+        session_id: &str,
+        enter_id: &str,
+        offset: u32,
+        limit: u32,
+    ) -> crate::Result<Vec<Event>> {
+        let conn = self.connection();
+        let sql = format!(
+            "{} AND parent_event_id = ? AND event_type = 'function_enter'
+             ORDER BY timestamp_ns, rowid LIMIT ? OFFSET ?",
+            SELECT_EVENT_SQL
+        );
+        let mut stmt = conn.prepare(&sql)?;
+        let rows = stmt.query_map(
+            params![session_id, enter_id, limit as i64 + 1, offset as i64],
+            event_from_row,
+        )?;
+        Ok(rows.collect::<std::result::Result<Vec<_>, _>>()?)
+    }
+
+    /// The call before or after `call` under the same parent on the same thread.
+    pub fn call_sibling(
+        &self,
+        session_id: &str,
+        call: &Event,
+        direction: SiblingDirection,
+    ) -> crate::Result<Option<Event>> {
+        let conn = self.connection();
+        let (cmp, order) = match direction {
+            SiblingDirection::Prev => ("<", "DESC"),
+            SiblingDirection::Next => (">", "ASC"),
+        };
+        let sql = format!(
+            "{} AND event_type = 'function_enter' AND thread_id = ?
+             AND parent_event_id IS ?
+             AND (timestamp_ns, rowid) {} (?, ?)
+             ORDER BY timestamp_ns {}, rowid {} LIMIT 1",
+            SELECT_EVENT_SQL, cmp, order, order
+        );
+        Ok(conn
+            .query_row(
+                &sql,
+                params![
+                    session_id,
+                    call.thread_id,
+                    call.parent_event_id,
+                    call.timestamp_ns,
+                    call.rowid.unwrap_or(0)
+                ],
+                event_from_row,
+            )
+            .optional()?)
+    }
+
+    /// Count orphan exits, unclosed enters and misordered pairs in a session.
+    pub fn call_consistency(&self, session_id: &str) -> crate::Result<CallConsistency> {
+        let conn = self.connection();
+        let mut threads: Vec<ThreadCallConsistency> = Vec::new();
+        let mut per_thread = |sql: &str, orphans: bool| -> crate::Result<()> {
+            let mut stmt = conn.prepare(sql)?;
+            let rows = stmt.query_map(params![session_id], |row| {
+                Ok((
+                    row.get::<_, Option<u32>>(0)?,
+                    row.get::<_, i64>(1)?,
+                    row.get::<_, i64>(2)? as u64,
+                ))
+            })?;
+            for row in rows {
+                let (pid, thread_id, count) = row?;
+                let index = match threads
+                    .iter()
+                    .position(|t| t.pid == pid && t.thread_id == thread_id)
+                {
+                    Some(index) => index,
+                    None => {
+                        threads.push(ThreadCallConsistency {
+                            pid,
+                            thread_id,
+                            ..Default::default()
+                        });
+                        threads.len() - 1
+                    }
+                };
+                if orphans {
+                    threads[index].orphan_exits = count;
+                } else {
+                    threads[index].unclosed_enters = count;
+                }
+            }
+            Ok(())
+        };
+        per_thread(
+            "SELECT x.pid, x.thread_id, COUNT(*) FROM events x
+             WHERE x.session_id = ?1 AND x.event_type = 'function_exit'
+             AND x.parent_event_id IS NOT NULL
+             AND NOT EXISTS (SELECT 1 FROM events e WHERE e.id = x.parent_event_id)
+             GROUP BY x.pid, x.thread_id",
+            true,
+        )?;
+        per_thread(
+            "SELECT e.pid, e.thread_id, COUNT(*) FROM events e
+             WHERE e.session_id = ?1 AND e.event_type = 'function_enter'
+             AND NOT EXISTS (
+                 SELECT 1 FROM events x WHERE x.session_id = ?1
+                 AND x.parent_event_id = e.id AND x.event_type = 'function_exit'
+             )
+             GROUP BY e.pid, e.thread_id",
+            false,
+        )?;
+        threads.sort_by(|a, b| {
+            (b.orphan_exits + b.unclosed_enters)
+                .cmp(&(a.orphan_exits + a.unclosed_enters))
+                .then(a.thread_id.cmp(&b.thread_id))
+        });
+
+        let misordered: i64 = conn.query_row(
+            &format!("SELECT COUNT(*) FROM {}", MISORDERED_PAIRS_SQL),
+            params![session_id],
+            |row| row.get(0),
+        )?;
+
+        Ok(CallConsistency {
+            orphan_exits: threads.iter().map(|t| t.orphan_exits).sum(),
+            unclosed_enters: threads.iter().map(|t| t.unclosed_enters).sum(),
+            misordered: misordered as u64,
+            threads,
+        })
+    }
+
+    /// Put exits stored before their enter back after it: the pair swaps seq
+    /// values (so the session's seq range is unchanged) and an exit stamped
+    /// before its enter is moved to enter + duration. Returns pairs repaired.
+    pub fn repair_call_order(&self, session_id: &str) -> crate::Result<u64> {
+        let mut conn = self.connection();
+        let tx = conn.transaction()?;
+        let pairs = {
+            let mut stmt = tx.prepare(&format!(
+                "SELECT x.id, x.seq, x.timestamp_ns, e.id, e.seq, e.timestamp_ns,
+                        COALESCE(x.duration_ns, 0) FROM {}",
+                MISORDERED_PAIRS_SQL
+            ))?;
+            let rows = stmt.query_map(params![session_id], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, Option<i64>>(1)?,
+                    row.get::<_, i64>(2)?,
+                    row.get::<_, String>(3)?,
+                    row.get::<_, Option<i64>>(4)?,
+                    row.get::<_, i64>(5)?,
+                    row.get::<_, i64>(6)?,
+                ))
+            })?;
+            rows.collect::<std::result::Result<Vec<_>, _>>()?
+        };
+        for (exit_id, exit_seq, exit_ts, enter_id, enter_seq, enter_ts, duration) in &pairs {
+            if let (Some(exit_seq), Some(enter_seq)) = (exit_seq, enter_seq) {
+                if exit_seq < enter_seq {
+                    tx.execute(
+                        "UPDATE events SET seq = ? WHERE id = ?",
+                        params![enter_seq, exit_id],
+                    )?;
+                    tx.execute(
+                        "UPDATE events SET seq = ? WHERE id = ?",
+                        params![exit_seq, enter_id],
+                    )?;
+                }
+            }
+            if exit_ts < enter_ts {
+                tx.execute(
+                    "UPDATE events SET timestamp_ns = ? WHERE id = ?",
+                    params![enter_ts + duration, exit_id],
+                )?;
+            }
+        }
+        tx.commit()?;
+        Ok(pairs.len() as u64)
+    }
+
+    /// Calls active on a thread at `timestamp_ns`, innermost first.
+    ///
+    /// Calls on one thread nest, so every active call is an ancestor of the
+    /// last call entered at or before the timestamp; ancestors that already
+    /// returned are dropped.
+    pub fn call_stack_at(
+        &self,
+        session_id: &str,
+        thread_id: i64,
+        timestamp_ns: i64,
+    ) -> crate::Result<Vec<Event>> {
+        let latest = {
+            let conn = self.connection();
+            let sql = format!(
+                "{} AND event_type = 'function_enter' AND thread_id = ? AND timestamp_ns <= ?
+                 ORDER BY timestamp_ns DESC, rowid DESC LIMIT 1",
+                SELECT_EVENT_SQL
+            );
+            conn.query_row(
+                &sql,
+                params![session_id, thread_id, timestamp_ns],
+                event_from_row,
+            )
+            .optional()?
+        };
+
+        let mut stack = Vec::new();
+        let mut current = latest;
+        while let Some(call) = current {
+            if stack.len() >= MAX_STACK_DEPTH {
+                break;
+            }
+            let returned = self
+                .call_exit(session_id, &call.id)?
+                .is_some_and(|exit| exit.timestamp_ns < timestamp_ns);
+            current = match call.parent_event_id.as_deref() {
+                Some(parent) => self.get_event(session_id, parent)?,
+                None => None,
+            };
+            if !returned && call.event_type == EventType::FunctionEnter {
+                stack.push(call);
+            }
+        }
+        Ok(stack)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::Database;
+
+    /// main [0, 100] -> { parse [10, 40] -> lex [20, 30], eval [50, 90] }
+    fn call_tree(db: &Database) {
+        let call = |id: &str, parent: Option<&str>, name: &str, start: i64, end: i64| {
+            let enter = Event {
+                id: id.into(),
+                session_id: "s1".into(),
+                timestamp_ns: start,
+                thread_id: 1,
+                parent_event_id: parent.map(String::from),
+                event_type: EventType::FunctionEnter,
+                function_name: name.into(),
+                call_id: Some(id.into()),
+                ..Default::default()
+            };
+            let exit = Event {
+                id: format!("{}-exit", id),
+                timestamp_ns: end,
+                parent_event_id: Some(id.into()),
+                event_type: EventType::FunctionExit,
+                duration_ns: Some(end - start),
+                ..enter.clone()
+            };
+            (enter, exit)
+        };
+        let (main_in, main_out) = call("main", None, "main", 0, 100);
+        let (parse_in, parse_out) = call("parse", Some("main"), "parse", 10, 40);
+        let (lex_in, lex_out) = call("lex", Some("parse"), "lex", 20, 30);
+        let (eval_in, eval_out) = call("eval", Some("main"), "eval", 50, 90);
+        db.insert_events_batch(&[
+            main_in, parse_in, lex_in, lex_out, parse_out, eval_in, eval_out, main_out,
+        ])
+        .unwrap();
+    }
+
+    fn ids(events: &[Event]) -> Vec<&str> {
+        events.iter().map(|e| e.id.as_str()).collect()
+    }
+
+    #[test]
+    fn test_navigate_call_tree() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = Database::open(&dir.path().join("test.db")).unwrap();
+        db.create_session("s1", "/bin/app", "/p", 1).unwrap();
+        call_tree(&db);
+
+        assert_eq!(
+            ids(&db.call_children("s1", "main", 0, 10).unwrap()),
+            vec!["parse", "eval"]
+        );
+        assert_eq!(
+            db.call_exit("s1", "parse").unwrap().unwrap().id,
+            "parse-exit"
+        );
+
+        let parse = db.get_event("s1", "parse").unwrap().unwrap();
+        let next = db
+            .call_sibling("s1", &parse, SiblingDirection::Next)
+            .unwrap();
+        assert_eq!(next.unwrap().id, "eval");
+        assert!(db
+            .call_sibling("s1", &parse, SiblingDirection::Prev)
+            .unwrap()
+            .is_none());
+
+        let main = db.get_event("s1", "main").unwrap().unwrap();
+        assert!(db
+            .call_sibling("s1", &main, SiblingDirection::Next)
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn test_call_enters_by_call_id() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = Database::open(&dir.path().join("test.db")).unwrap();
+        db.create_session("s1", "/bin/app", "/p", 1).unwrap();
+        call_tree(&db);
+
+        let both = db
+            .query_events("s1", |q| q.call_id_equals("parse"))
+            .unwrap();
+        let mut both = ids(&both);
+        both.sort();
+        assert_eq!(both, vec!["parse", "parse-exit"]);
+
+        let enters = db
+            .call_enters("s1", &["lex".into(), "eval".into(), "gone".into()])
+            .unwrap();
+        assert_eq!(enters.len(), 2);
+        assert_eq!(enters["lex"].event_type, EventType::FunctionEnter);
+        assert_eq!(enters["eval"].timestamp_ns, 50);
+        assert!(db.call_enters("s1", &[]).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_call_stack_at() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = Database::open(&dir.path().join("test.db")).unwrap();
+        db.create_session("s1", "/bin/app", "/p", 1).unwrap();
+        call_tree(&db);
+
+        assert_eq!(
+            ids(&db.call_stack_at("s1", 1, 25).unwrap()),
+            vec!["lex", "parse", "main"]
+        );
+        // lex and parse have returned; eval hasn't started.
+        assert_eq!(ids(&db.call_stack_at("s1", 1, 45).unwrap()), vec!["main"]);
+        assert_eq!(
+            ids(&db.call_stack_at("s1", 1, 60).unwrap()),
+            vec!["eval", "main"]
+        );
+        assert!(db.call_stack_at("s1", 1, 200).unwrap().is_empty());
+        assert!(db.call_stack_at("s1", 2, 25).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_call_consistency_and_repair() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = Database::open(&dir.path().join("test.db")).unwrap();
+        db.create_session("s1", "/bin/app", "/p", 1).unwrap();
+        let event = |id: &str, event_type: EventType, parent: Option<&str>, ts: i64| Event {
+            id: id.into(),
+            session_id: "s1".into(),
+            timestamp_ns: ts,
+            thread_id: 7,
+            parent_event_id: parent.map(String::from),
+            event_type,
+            function_name: "f".into(),
+            ..Default::default()
+        };
+        // a's exit is stored (and stamped) before a; b never returns; c's enter is gone
+        db.insert_events_batch(&[
+            event("a-exit", EventType::FunctionExit, Some("a"), 5),
+            event("a", EventType::FunctionEnter, None, 10),
+            event("b", EventType::FunctionEnter, None, 20),
+            event("c-exit", EventType::FunctionExit, Some("c"), 30),
+        ])
+        .unwrap();
+
+        let report = db.call_consistency("s1").unwrap();
+        assert_eq!(report.orphan_exits, 1);
+        assert_eq!(report.unclosed_enters, 1);
+        assert_eq!(report.misordered, 1);
+        assert_eq!(report.threads.len(), 1);
+        assert_eq!(report.threads[0].thread_id, 7);
+
+        assert_eq!(db.repair_call_order("s1").unwrap(), 1);
+        let enter = db.get_event("s1", "a").unwrap().unwrap();
+        let exit = db.get_event("s1", "a-exit").unwrap().unwrap();
+        assert!(exit.seq > enter.seq);
+        assert!(exit.timestamp_ns >= enter.timestamp_ns);
+        assert_eq!(db.call_consistency("s1").unwrap().misordered, 0);
+    }
+
+    #[test]
+    fn test_second_exit_for_a_call_is_ignored() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = Database::open(&dir.path().join("test.db")).unwrap();
+        db.create_session("s1", "/bin/app", "/p", 1).unwrap();
+        call_tree(&db);
+
+        let duplicate = Event {
+            id: "lex-exit-again".into(),
+            session_id: "s1".into(),
+            timestamp_ns: 35,
+            thread_id: 1,
+            parent_event_id: Some("lex".into()),
+            event_type: EventType::FunctionExit,
+            ..Default::default()
+        };
+        let stats = db.insert_events_batch(&[duplicate]).unwrap();
+        assert_eq!(stats.duplicates_ignored, 1);
+        assert_eq!(db.call_exit("s1", "lex").unwrap().unwrap().id, "lex-exit");
+    }
+}
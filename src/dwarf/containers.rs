@@ -0,0 +1,452 @@
+//! Standard-library container layouts for argument decoding.
+//!
+//! Traced arguments are raw register values. When DWARF says a parameter is a
+//! `std::string`, `std::vector`, `std::map`, Rust `String` or `Vec<T>` (by
+//! value, reference or pointer — all of which arrive as an object address),
+//! the daemon ships one of these layouts with the hook so the agent can read
+//! the contents instead of printing a pointer.
+
+use serde::Serialize;
+
+/// Only the first two arguments are captured by the tracer.
+pub const MAX_DECODED_ARGS: usize = 2;
+
+/// How to decode one container in place. Offsets are relative to the object;
+/// map `value_offset` is relative to the start of the node's `pair<const K, V>`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum ContainerLayout {
+    /// Data pointer + length: libstdc++ `std::string`, Rust `String`.
+    #[serde(rename_all = "camelCase")]
+    PtrLenString { ptr_offset: u64, len_offset: u64 },
+    /// libc++ `std::string`: short/long flag in the low bit of the first byte.
+    LibcxxString,
+    /// Elements between two pointers: `std::vector` (both standard libraries).
+    #[serde(rename_all = "camelCase")]
+    BeginEndVector {
+        begin_offset: u64,
+        end_offset: u64,
+        element: ContainerElement,
+    },
+    /// Data pointer + element count: Rust `Vec<T>`.
+    #[serde(rename_all = "camelCase")]
+    PtrLenVector {
+        ptr_offset: u64,
+        len_offset: u64,
+        element: ContainerElement,
+    },
+    /// libstdc++ `std::map`: header node embedded at `header_offset`.
+    #[serde(rename_all = "camelCase")]
+    LibstdcxxMap {
+        header_offset: u64,
+        count_offset: u64,
+        key: ContainerElement,
+        value: ContainerElement,
+        value_offset: u64,
+    },
+    /// libc++ `std::map`: begin-node pointer, embedded end node, size.
+    #[serde(rename_all = "camelCase")]
+    LibcxxMap {
+        begin_offset: u64,
+        end_offset: u64,
+        count_offset: u64,
+        key: ContainerElement,
+        value: ContainerElement,
+        value_offset: u64,
+    },
+}
+
+/// Element/key/value type, in the shape of the agent's `TypeInfo`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContainerElement {
+    /// "int" | "uint" | "float" | "pointer" | "struct"
+    pub type_kind: String,
+    pub byte_size: u64,
+    pub type_name: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub members: Vec<ElementMember>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub container: Option<Box<ContainerLayout>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ElementMember {
+    pub name: String,
+    pub offset: u64,
+    pub byte_size: u64,
+    pub type_kind: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub type_name: Option<String>,
+}
+
+/// Decodable containers for one function; `None` entries are left as pointers.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FunctionContainers {
+    pub args: Vec<Option<ContainerLayout>>,
+    /// Only set for pointer/reference returns — by-value returns go through a
+    /// hidden out-pointer that the exit hook can't see on every ABI.
+    pub ret: Option<ContainerLayout>,
+}
+
+impl FunctionContainers {
+    pub fn is_empty(&self) -> bool {
+        self.ret.is_none() && self.args.iter().all(Option::is_none)
+    }
+}
+
+/// A (possibly nested) data member of a class, flattened with its absolute
+/// offset. `path` joins member names with '.'; base classes contribute no
+/// path segment.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct FlatField {
+    pub path: String,
+    pub offset: u64,
+}
+
+impl FlatField {
+    fn leaf(&self) -> &str {
+        self.path.rsplit('.').next().unwrap_or(&self.path)
+    }
+}
+
+fn field_offset(fields: &[FlatField], leaf: &str) -> Option<u64> {
+    fields.iter().find(|f| f.leaf() == leaf).map(|f| f.offset)
+}
+
+fn path_offset(fields: &[FlatField], path: &str) -> Option<u64> {
+    fields.iter().find(|f| f.path == path).map(|f| f.offset)
+}
+
+/// Rust `RawVec` data pointer: the first `ptr`/`pointer` member under `buf`.
+/// Nested wrappers (`Unique`, `NonNull`) all sit at the same offset.
+fn rust_buf_ptr(fields: &[FlatField], prefix: &str) -> Option<u64> {
+    fields
+        .iter()
+        .find(|f| f.path.starts_with(prefix) && matches!(f.leaf(), "ptr" | "pointer"))
+        .map(|f| f.offset)
+}
+
+/// Pick a layout from the class name (DW_AT_name, unqualified), its flattened
+/// members and its template type parameters (already resolved to elements).
+pub(crate) fn classify(
+    type_name: &str,
+    fields: &[FlatField],
+    params: &[ContainerElement],
+) -> Option<ContainerLayout> {
+    if type_name.starts_with("basic_string<char,") || type_name == "basic_string<char>" {
+        if let (Some(ptr_offset), Some(len_offset)) = (
+            field_offset(fields, "_M_p"),
+            field_offset(fields, "_M_string_length"),
+        ) {
+            return Some(ContainerLayout::PtrLenString {
+                ptr_offset,
+                len_offset,
+            });
+        }
+        if field_offset(fields, "__r_").is_some() || field_offset(fields, "__rep_").is_some() {
+            return Some(ContainerLayout::LibcxxString);
+        }
+        return None;
+    }
+
+    if type_name == "String" {
+        let ptr_offset = rust_buf_ptr(fields, "vec.buf.")?;
+        let len_offset = path_offset(fields, "vec.len")?;
+        return Some(ContainerLayout::PtrLenString {
+            ptr_offset,
+            len_offset,
+        });
+    }
+
+    if type_name.starts_with("Vec<") {
+        let element = params.first()?.clone();
+        let ptr_offset = rust_buf_ptr(fields, "buf.")?;
+        let len_offset = path_offset(fields, "len")?;
+        return Some(ContainerLayout::PtrLenVector {
+            ptr_offset,
+            len_offset,
+            element,
+        });
+    }
+
+    // vector<bool> is a bitset specialization with a different layout.
+    if type_name.starts_with("vector<") && !type_name.starts_with("vector<bool") {
+        let element = params.first()?.clone();
+        let (begin_offset, end_offset) = match (
+            field_offset(fields, "_M_start"),
+            field_offset(fields, "_M_finish"),
+        ) {
+            (Some(b), Some(e)) => (b, e),
+            _ => (
+                field_offset(fields, "__begin_")?,
+                field_offset(fields, "__end_")?,
+            ),
+        };
+        if element.byte_size == 0 {
+            return None;
+        }
+        return Some(ContainerLayout::BeginEndVector {
+            begin_offset,
+            end_offset,
+            element,
+        });
+    }
+
+    if type_name.starts_with("map<") {
+        let key = params.first()?.clone();
+        let value = params.get(1)?.clone();
+        let value_offset = pair_value_offset(&key, &value);
+        if let (Some(header_offset), Some(count_offset)) = (
+            field_offset(fields, "_M_header"),
+            field_offset(fields, "_M_node_count"),
+        ) {
+            return Some(ContainerLayout::LibstdcxxMap {
+                header_offset,
+                count_offset,
+                key,
+                value,
+                value_offset,
+            });
+        }
+        // __tree layout: __begin_node_, then the end node, then the size.
+        let begin_offset = field_offset(fields, "__begin_node_")?;
+        return Some(ContainerLayout::LibcxxMap {
+            begin_offset,
+            end_offset: begin_offset + 8,
+            count_offset: begin_offset + 16,
+            key,
+            value,
+            value_offset,
+        });
+    }
+
+    None
+}
+
+/// Offset of `second` in `std::pair<const K, V>`.
+pub(crate) fn pair_value_offset(key: &ContainerElement, value: &ContainerElement) -> u64 {
+    let align = match value.type_kind.as_str() {
+        "int" | "uint" | "float" | "pointer" if value.byte_size.is_power_of_two() => {
+            value.byte_size.min(8)
+        }
+        _ => 8,
+    };
+    key.byte_size.div_ceil(align) * align
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn f(path: &str, offset: u64) -> FlatField {
+        FlatField {
+            path: path.to_string(),
+            offset,
+        }
+    }
+
+    fn prim(kind: &str, size: u64, name: &str) -> ContainerElement {
+        ContainerElement {
+            type_kind: kind.to_string(),
+            byte_size: size,
+            type_name: name.to_string(),
+            members: vec![],
+            container: None,
+        }
+    }
+
+    #[test]
+    fn test_libstdcxx_string() {
+        let fields = vec![
+            f("_M_dataplus", 0),
+            f("_M_dataplus._M_p", 0),
+            f("_M_string_length", 8),
+            f("_M_local_buf", 16),
+        ];
+        let layout = classify(
+            "basic_string<char, std::char_traits<char>, std::allocator<char> >",
+            &fields,
+            &[],
+        );
+        assert_eq!(
+            layout,
+            Some(ContainerLayout::PtrLenString {
+                ptr_offset: 0,
+                len_offset: 8
+            })
+        );
+    }
+
+    #[test]
+    fn test_libcxx_string() {
+        let fields = vec![f("__r_", 0)];
+        let layout = classify(
+            "basic_string<char, std::__1::char_traits<char>, std::__1::allocator<char> >",
+            &fields,
+            &[],
+        );
+        assert_eq!(layout, Some(ContainerLayout::LibcxxString));
+    }
+
+    #[test]
+    fn test_wide_string_not_decoded() {
+        let fields = vec![f("_M_dataplus._M_p", 0), f("_M_string_length", 8)];
+        assert_eq!(
+            classify(
+                "basic_string<wchar_t, std::char_traits<wchar_t> >",
+                &fields,
+                &[]
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn test_rust_string_uses_dwarf_offsets() {
+        // Field order in Rust structs is compiler-chosen; offsets come from DWARF.
+        let fields = vec![
+            f("vec", 0),
+            f("vec.buf", 0),
+            f("vec.buf.inner", 0),
+            f("vec.buf.inner.ptr", 8),
+            f("vec.buf.inner.ptr.pointer", 8),
+            f("vec.buf.inner.cap", 0),
+            f("vec.len", 16),
+        ];
+        assert_eq!(
+            classify("String", &fields, &[]),
+            Some(ContainerLayout::PtrLenString {
+                ptr_offset: 8,
+                len_offset: 16
+            })
+        );
+    }
+
+    #[test]
+    fn test_rust_vec() {
+        let fields = vec![
+            f("buf", 0),
+            f("buf.ptr", 0),
+            f("buf.ptr.pointer", 0),
+            f("buf.cap", 8),
+            f("len", 16),
+        ];
+        let elem = prim("int", 4, "i32");
+        assert_eq!(
+            classify(
+                "Vec<i32, alloc::alloc::Global>",
+                &fields,
+                std::slice::from_ref(&elem)
+            ),
+            Some(ContainerLayout::PtrLenVector {
+                ptr_offset: 0,
+                len_offset: 16,
+                element: elem
+            })
+        );
+    }
+
+    #[test]
+    fn test_std_vector_both_libraries() {
+        let elem = prim("float", 8, "double");
+        let gnu = vec![
+            f("_M_impl", 0),
+            f("_M_impl._M_start", 0),
+            f("_M_impl._M_finish", 8),
+            f("_M_impl._M_end_of_storage", 16),
+        ];
+        let llvm = vec![f("__begin_", 0), f("__end_", 8), f("__end_cap_", 16)];
+        let expected = Some(ContainerLayout::BeginEndVector {
+            begin_offset: 0,
+            end_offset: 8,
+            element: elem.clone(),
+        });
+        assert_eq!(
+            classify(
+                "vector<double, std::allocator<double> >",
+                &gnu,
+                std::slice::from_ref(&elem)
+            ),
+            expected
+        );
+        assert_eq!(
+            classify(
+                "vector<double, std::__1::allocator<double> >",
+                &llvm,
+                std::slice::from_ref(&elem)
+            ),
+            expected
+        );
+        assert_eq!(
+            classify("vector<bool, std::allocator<bool> >", &gnu, &[elem]),
+            None
+        );
+    }
+
+    #[test]
+    fn test_std_map_libstdcxx() {
+        let fields = vec![
+            f("_M_t._M_impl._M_key_compare", 0),
+            f("_M_t._M_impl._M_header", 8),
+            f("_M_t._M_impl._M_header._M_left", 24),
+            f("_M_t._M_impl._M_node_count", 40),
+        ];
+        let key = prim("int", 4, "int");
+        let value = prim("float", 8, "double");
+        match classify("map<int, double, std::less<int> >", &fields, &[key, value]) {
+            Some(ContainerLayout::LibstdcxxMap {
+                header_offset,
+                count_offset,
+                value_offset,
+                ..
+            }) => {
+                assert_eq!(header_offset, 8);
+                assert_eq!(count_offset, 40);
+                assert_eq!(value_offset, 8);
+            }
+            other => panic!("unexpected layout {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_std_map_libcxx() {
+        let fields = vec![f("__tree_", 0), f("__tree_.__begin_node_", 0)];
+        let key = prim("int", 4, "int");
+        let value = prim("int", 4, "int");
+        match classify("map<int, int>", &fields, &[key, value]) {
+            Some(ContainerLayout::LibcxxMap {
+                begin_offset,
+                end_offset,
+                count_offset,
+                value_offset,
+                ..
+            }) => {
+                assert_eq!((begin_offset, end_offset, count_offset), (0, 8, 16));
+                assert_eq!(value_offset, 4);
+            }
+            other => panic!("unexpected layout {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_missing_template_params_not_decoded() {
+        let fields = vec![f("__begin_", 0), f("__end_", 8)];
+        assert_eq!(classify("vector<Foo>", &fields, &[]), None);
+    }
+
+    #[test]
+    fn test_layout_json_shape() {
+        let layout = ContainerLayout::PtrLenVector {
+            ptr_offset: 0,
+            len_offset: 16,
+            element: prim("uint", 1, "u8"),
+        };
+        let json = serde_json::to_value(&layout).unwrap();
+        assert_eq!(json["kind"], "ptrLenVector");
+        assert_eq!(json["lenOffset"], 16);
+        assert_eq!(json["element"]["typeKind"], "uint");
+        assert!(json["element"].get("container").is_none());
+    }
+}
@@ -10,6 +10,20 @@ pub struct FunctionInfo {
     pub line_number: Option<u32>,
 }
 
+/// Copies of a function inlined into one out-of-line function
+/// (DW_TAG_inlined_subroutine). They have no entry point to hook; the caller
+/// does.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InlinedCall {
+    pub name: String,
+    pub caller: String,
+    /// Call site of the first copy
+    pub call_file: Option<String>,
+    pub call_line: Option<u32>,
+    /// Copies inlined into `caller`
+    pub sites: u32,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum TypeKind {
     Integer { signed: bool },
@@ -31,6 +45,16 @@ pub struct VariableInfo {
     pub source_file: Option<String>,
 }
 
+/// The full storage of a global (structs and arrays included), for raw dumps
+/// such as crash-time capture. `address` is static; the agent applies the slide.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GlobalRegion {
+    pub name: String,
+    pub address: u64,
+    pub byte_size: u64,
+    pub type_name: Option<String>,
+}
+
 /// Recipe for reading a watched value at runtime.
 /// Simple global: deref_chain is empty, read directly at base_address.
 /// ptr->member: deref_chain = [member_offset], read pointer at base, add offset, read final value.
@@ -54,6 +78,8 @@ pub struct LocalVariableInfo {
     pub type_name: Option<String>,
     /// Location: either a simple expression or a location list
     pub location: LocalVarLocation,
+    /// The struct's members, or for a struct pointer its pointee's
+    pub(crate) members: Option<Vec<super::parser::StructMember>>,
 }
 
 #[derive(Debug, Clone)]
@@ -70,6 +70,15 @@ impl DwarfHandle {
             .map_err(|e| crate::Error::Frida(e))
     }
 
+    /// addr2line for a runtime address in this binary loaded at `load_base`,
+    /// once the parse has finished; None while it is still running, when it
+    /// failed, or outside known code.
+    pub fn addr2line(&self, address: u64, load_base: u64) -> Option<super::SourceLocation> {
+        let dwarf = self.try_borrow_parser()?.ok()?;
+        let offset = address.checked_sub(load_base)?;
+        dwarf.addr2line(dwarf.image_base.wrapping_add(offset))
+    }
+
     /// Try to synchronously borrow the parsed result.
     /// Returns None if parse is still pending, Some(Ok(...)) if successful, Some(Err(...)) if failed.
     pub fn try_borrow_parser(&self) -> Option<Result<Arc<DwarfParser>, String>> {
@@ -93,9 +102,14 @@ mod tests {
             variables_by_name: std::collections::HashMap::new(),
             struct_members: std::sync::Mutex::new(std::collections::HashMap::new()),
             lazy_struct_info: std::collections::HashMap::new(),
+            type_layouts: std::sync::Mutex::new(std::collections::HashMap::new()),
+            function_params: std::sync::Mutex::new(std::collections::HashMap::new()),
             line_table: std::sync::Mutex::new(None),
             image_base: 0x100000,
             binary_path: None,
+            go_functions: Default::default(),
+            goid_offset: None,
+            swift: false,
         })
     }
 
@@ -0,0 +1,507 @@
+//! Member paths below a paused frame's locals (`buf.len`,
+//! `chain->next->params->gain`) for debug_eval, and below a function's
+//! parameters for logpoint templates (`{buffer.sample_rate}`). A path resolves
+//! to where the variable is and the pointer reads and member offsets that
+//! lead from there to the value; the reads themselves are left to the caller.
+
+use super::function::{LocalVarLocation, LocalVariableInfo, StructFieldRecipe, TypeKind};
+use super::parser::StructMember;
+use super::DwarfParser;
+use crate::{Error, Result};
+
+/// Where a path starts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LocalRoot {
+    /// The local is stored at this address (stack slot)
+    Memory(u64),
+    /// The local lives in a register; this is its value
+    Value(u64),
+    /// The Nth integer argument register at function entry, read by the
+    /// agent when a logpoint fires
+    Argument(usize),
+}
+
+/// How integer arguments are passed, for placing parameters in argument
+/// registers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CallingConvention {
+    /// System V x86-64: six integer registers
+    SysV,
+    /// AAPCS64, Apple's arm64 variant included: eight
+    Aapcs64,
+}
+
+impl CallingConvention {
+    /// The convention of processes on this platform; None where arguments
+    /// are passed some other way (Windows x64).
+    pub fn native() -> Option<Self> {
+        if cfg!(target_arch = "aarch64") {
+            Some(Self::Aapcs64)
+        } else if cfg!(all(target_arch = "x86_64", not(windows))) {
+            Some(Self::SysV)
+        } else {
+            None
+        }
+    }
+
+    fn integer_registers(self) -> usize {
+        match self {
+            Self::SysV => 6,
+            Self::Aapcs64 => 8,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PathOp {
+    /// Read the pointer at the current address and continue at its target
+    Deref,
+    /// Move to a member this many bytes in
+    Offset(u64),
+}
+
+#[derive(Debug, Clone)]
+pub struct LocalPath {
+    pub root: LocalRoot,
+    pub ops: Vec<PathOp>,
+    pub size: u8,
+    pub type_kind: TypeKind,
+    pub type_name: Option<String>,
+    /// Members to read when the path ends at a struct rather than a scalar
+    pub fields: Vec<StructFieldRecipe>,
+}
+
+/// Resolve `expr` — a local's name, then any `.member` / `->member` steps —
+/// against a frame's locals. None when its first name isn't one of them.
+/// `frame_base` is None when the frame's stack slots can't be trusted (a
+/// thread paused before the function's prologue).
+pub fn resolve_local_path(
+    locals: &[LocalVariableInfo],
+    expr: &str,
+    registers: &serde_json::Value,
+    frame_base: Option<u64>,
+    arch: &str,
+) -> Option<Result<LocalPath>> {
+    let (root_name, steps) = split_member_path(expr);
+    let local = locals.iter().find(|l| l.name == root_name)?;
+    Some(resolve_steps(
+        local, expr, &steps, registers, frame_base, arch,
+    ))
+}
+
+/// Resolve a logpoint placeholder against the parameters of the function it
+/// is set on, in declaration order. Only the argument registers are known at
+/// entry, so the parameter must be passed in one; `.` and `->` both follow a
+/// pointer, as C++ references are pointers in DWARF. None when the first name
+/// isn't a parameter; an error for a parameter when `convention` is unknown.
+pub fn resolve_param_path(
+    params: &[LocalVariableInfo],
+    expr: &str,
+    convention: Option<CallingConvention>,
+) -> Option<Result<LocalPath>> {
+    let (root_name, steps) = split_member_path(expr);
+    let at = params.iter().position(|p| p.name == root_name)?;
+    Some(argument_register(params, at, convention).and_then(|index| {
+        walk_members(&params[at], LocalRoot::Argument(index), expr, &steps, true)
+    }))
+}
+
+/// Integer argument register of `params[at]` under `convention`: floats go
+/// to vector registers and don't take one.
+fn argument_register(
+    params: &[LocalVariableInfo],
+    at: usize,
+    convention: Option<CallingConvention>,
+) -> Result<usize> {
+    let by_value = |p: &LocalVariableInfo| p.byte_size == 0 || p.byte_size > 8;
+    let param = &params[at];
+    let Some(convention) = convention else {
+        return Err(Error::ValidationError(format!(
+            "'{}': parameters are only placed for the SysV x86-64 and AAPCS64 calling conventions; use {{args[N]}}",
+            param.name
+        )));
+    };
+    if matches!(param.type_kind, TypeKind::Float) {
+        return Err(Error::ValidationError(format!(
+            "'{}' is a floating-point parameter, passed in a register logpoints can't read",
+            param.name
+        )));
+    }
+    if by_value(param) {
+        return Err(Error::ValidationError(format!(
+            "'{}' is passed by value; logpoints read parameters that fit in a register",
+            param.name
+        )));
+    }
+
+    let mut index = 0;
+    for earlier in &params[..at] {
+        if matches!(earlier.type_kind, TypeKind::Float) {
+            continue;
+        }
+        if by_value(earlier) {
+            return Err(Error::ValidationError(format!(
+                "'{}' follows by-value parameter '{}', so its register isn't known",
+                param.name, earlier.name
+            )));
+        }
+        index += 1;
+    }
+    let registers = convention.integer_registers();
+    if index >= registers {
+        return Err(Error::ValidationError(format!(
+            "'{}' is passed on the stack; logpoints read the first {} integer parameters",
+            param.name, registers
+        )));
+    }
+    Ok(index)
+}
+
+fn resolve_steps(
+    local: &LocalVariableInfo,
+    expr: &str,
+    steps: &[(bool, &str)],
+    registers: &serde_json::Value,
+    frame_base: Option<u64>,
+    arch: &str,
+) -> Result<LocalPath> {
+    let register = |reg: u16| {
+        super::register_value(registers, reg, arch).ok_or_else(|| {
+            Error::ValidationError(format!(
+                "'{}' is in a register that isn't known in this frame",
+                local.name
+            ))
+        })
+    };
+    let root = match local.location {
+        LocalVarLocation::FrameBaseRelative(offset) => {
+            let base = frame_base.ok_or_else(|| {
+                Error::ValidationError(format!(
+                    "'{}' lives on the stack, which isn't set up at function entry; step once first",
+                    local.name
+                ))
+            })?;
+            LocalRoot::Memory(base.wrapping_add_signed(offset))
+        }
+        LocalVarLocation::RegisterOffset(reg, offset) => {
+            LocalRoot::Memory(register(reg)?.wrapping_add_signed(offset))
+        }
+        LocalVarLocation::Register(reg) => LocalRoot::Value(register(reg)?),
+        LocalVarLocation::Address(_) | LocalVarLocation::Complex => {
+            return Err(Error::ValidationError(format!(
+                "'{}' has a DWARF location debug_eval can't follow",
+                local.name
+            )))
+        }
+    };
+    walk_members(local, root, expr, steps, false)
+}
+
+/// Follow `steps` through member layouts from `local` at `root`. With
+/// `dot_derefs`, `.` on a pointer reads through it like `->`.
+fn walk_members(
+    local: &LocalVariableInfo,
+    root: LocalRoot,
+    expr: &str,
+    steps: &[(bool, &str)],
+    dot_derefs: bool,
+) -> Result<LocalPath> {
+    let mut path = LocalPath {
+        root,
+        ops: Vec::new(),
+        size: local.byte_size,
+        type_kind: local.type_kind.clone(),
+        type_name: local.type_name.clone(),
+        fields: Vec::new(),
+    };
+    let mut members: Option<&[StructMember]> = local.members.as_deref();
+    let mut walked = local.name.clone();
+    for &(arrow, field) in steps {
+        let is_pointer = matches!(path.type_kind, TypeKind::Pointer);
+        if arrow && !is_pointer {
+            return Err(Error::ValidationError(format!(
+                "'{}' is not a pointer; use '.'",
+                walked
+            )));
+        }
+        if !arrow && is_pointer && !dot_derefs {
+            return Err(Error::ValidationError(format!(
+                "'{}' is a pointer; use '->'",
+                walked
+            )));
+        }
+        let member = members
+            .ok_or_else(|| {
+                Error::ValidationError(format!("No struct layout known for '{}'", walked))
+            })?
+            .iter()
+            .find(|m| m.name == field)
+            .ok_or_else(|| {
+                Error::ValidationError(format!("'{}' has no member '{}'", walked, field))
+            })?;
+
+        if is_pointer {
+            path.ops.push(PathOp::Deref);
+        }
+        path.ops.push(PathOp::Offset(member.offset));
+        path.size = member.byte_size;
+        path.type_kind = member.type_kind.clone();
+        path.type_name = member.type_name.clone();
+        // Only pointer members carry their target's layout
+        members = member.pointed_struct_members.as_deref();
+        walked.push_str(if arrow { "->" } else { "." });
+        walked.push_str(field);
+    }
+
+    if !matches!(path.type_kind, TypeKind::Pointer) && path.size == 0 {
+        // A struct: its own members when known (the local itself)
+        match members {
+            Some(members) if steps.is_empty() => {
+                path.fields = DwarfParser::struct_members_to_recipes(members, 1);
+            }
+            _ => {
+                return Err(Error::ValidationError(format!(
+                    "'{}' is not a scalar; read one of its members",
+                    expr
+                )))
+            }
+        }
+    }
+    Ok(path)
+}
+
+/// `a->b.c` as ("a", [(true, "b"), (false, "c")]); true for `->`.
+fn split_member_path(expr: &str) -> (&str, Vec<(bool, &str)>) {
+    let next_separator = |s: &str| {
+        let arrow = s.find("->").map(|i| (i, true));
+        let dot = s.find('.').map(|i| (i, false));
+        match (arrow, dot) {
+            (Some(a), Some(d)) => Some(if d.0 < a.0 { d } else { a }),
+            (a, d) => a.or(d),
+        }
+    };
+    let skip = |arrow: bool| if arrow { 2 } else { 1 };
+
+    let Some((at, mut arrow)) = next_separator(expr) else {
+        return (expr, Vec::new());
+    };
+    let root = &expr[..at];
+    let mut rest = &expr[at + skip(arrow)..];
+    let mut steps = Vec::new();
+    while let Some((at, next_arrow)) = next_separator(rest) {
+        steps.push((arrow, &rest[..at]));
+        rest = &rest[at + skip(next_arrow)..];
+        arrow = next_arrow;
+    }
+    steps.push((arrow, rest));
+    (root, steps)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn member(name: &str, offset: u64, type_kind: TypeKind, size: u8) -> StructMember {
+        StructMember {
+            name: name.to_string(),
+            offset,
+            byte_size: size,
+            is_pointer: matches!(type_kind, TypeKind::Pointer),
+            type_kind,
+            type_name: None,
+            pointed_struct_members: None,
+        }
+    }
+
+    fn local(
+        name: &str,
+        location: LocalVarLocation,
+        type_kind: TypeKind,
+        size: u8,
+    ) -> LocalVariableInfo {
+        LocalVariableInfo {
+            name: name.to_string(),
+            byte_size: size,
+            type_kind,
+            type_name: None,
+            location,
+            members: None,
+        }
+    }
+
+    #[test]
+    fn test_split_member_path() {
+        assert_eq!(split_member_path("buf"), ("buf", vec![]));
+        assert_eq!(
+            split_member_path("chain->next.params->gain"),
+            (
+                "chain",
+                vec![(true, "next"), (false, "params"), (true, "gain")]
+            )
+        );
+    }
+
+    #[test]
+    fn test_resolve_pointer_chain() {
+        let mut params = member("params", 16, TypeKind::Pointer, 8);
+        params.pointed_struct_members = Some(vec![member("gain", 4, TypeKind::Float, 4)]);
+        let mut next = member("next", 8, TypeKind::Pointer, 8);
+        next.pointed_struct_members = Some(vec![params.clone()]);
+        let mut chain = local(
+            "chain",
+            LocalVarLocation::FrameBaseRelative(-24),
+            TypeKind::Pointer,
+            8,
+        );
+        chain.members = Some(vec![next, params]);
+        let registers = serde_json::json!({});
+
+        let path = resolve_local_path(
+            &[chain.clone()],
+            "chain->next->params->gain",
+            &registers,
+            Some(0x7000),
+            "x64",
+        )
+        .unwrap()
+        .unwrap();
+        assert_eq!(path.root, LocalRoot::Memory(0x7000 - 24));
+        assert_eq!(
+            path.ops,
+            vec![
+                PathOp::Deref,
+                PathOp::Offset(8),
+                PathOp::Deref,
+                PathOp::Offset(16),
+                PathOp::Deref,
+                PathOp::Offset(4),
+            ]
+        );
+        assert!(matches!(path.type_kind, TypeKind::Float));
+        assert_eq!(path.size, 4);
+
+        assert!(
+            resolve_local_path(&[chain.clone()], "gGlobal->x", &registers, None, "x64").is_none()
+        );
+        assert!(resolve_local_path(
+            &[chain.clone()],
+            "chain.next",
+            &registers,
+            Some(0x7000),
+            "x64"
+        )
+        .unwrap()
+        .is_err());
+        assert!(resolve_local_path(
+            &[chain.clone()],
+            "chain->missing",
+            &registers,
+            Some(0x7000),
+            "x64"
+        )
+        .unwrap()
+        .is_err());
+        // At function entry the stack slot isn't the local's yet
+        assert!(
+            resolve_local_path(&[chain], "chain", &registers, None, "x64")
+                .unwrap()
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_resolve_param_path() {
+        let sysv = Some(CallingConvention::SysV);
+        let aapcs64 = Some(CallingConvention::Aapcs64);
+
+        let param = |name: &str, type_kind: TypeKind, size: u8| {
+            local(name, LocalVarLocation::Complex, type_kind, size)
+        };
+        let mut buffer = param("buffer", TypeKind::Pointer, 8);
+        buffer.members = Some(vec![member(
+            "sample_rate",
+            16,
+            TypeKind::Integer { signed: false },
+            4,
+        )]);
+        let params = [
+            param("this", TypeKind::Pointer, 8),
+            param("gain", TypeKind::Float, 4),
+            buffer,
+            param("velocity", TypeKind::Integer { signed: true }, 4),
+            param("settings", TypeKind::Unknown, 0),
+            param("late", TypeKind::Integer { signed: true }, 4),
+        ];
+
+        // Floats don't take an integer register
+        let velocity = resolve_param_path(&params, "velocity", sysv)
+            .unwrap()
+            .unwrap();
+        assert_eq!(velocity.root, LocalRoot::Argument(2));
+        assert!(velocity.ops.is_empty());
+
+        // References and pointers both take '.'
+        let rate = resolve_param_path(&params, "buffer.sample_rate", aapcs64)
+            .unwrap()
+            .unwrap();
+        assert_eq!(rate.root, LocalRoot::Argument(1));
+        assert_eq!(rate.ops, vec![PathOp::Deref, PathOp::Offset(16)]);
+        assert_eq!(rate.size, 4);
+
+        assert!(resolve_param_path(&params, "gain", sysv).unwrap().is_err());
+        assert!(resolve_param_path(&params, "settings", sysv)
+            .unwrap()
+            .is_err());
+        assert!(resolve_param_path(&params, "late", sysv).unwrap().is_err());
+        assert!(resolve_param_path(&params, "missing", sysv).is_none());
+        // Windows x64 passes arguments differently
+        assert!(resolve_param_path(&params, "velocity", None)
+            .unwrap()
+            .is_err());
+
+        let many: Vec<_> = (0..7)
+            .map(|i| param(&format!("p{}", i), TypeKind::Pointer, 8))
+            .collect();
+        assert!(resolve_param_path(&many, "p6", sysv).unwrap().is_err());
+        assert!(resolve_param_path(&many, "p6", aapcs64).unwrap().is_ok());
+    }
+
+    #[test]
+    fn test_resolve_struct_and_register_locals() {
+        let mut buf = local(
+            "buf",
+            LocalVarLocation::RegisterOffset(7, 16),
+            TypeKind::Unknown,
+            0,
+        );
+        buf.members = Some(vec![
+            member("data", 0, TypeKind::Pointer, 8),
+            member("len", 8, TypeKind::Integer { signed: false }, 8),
+        ]);
+        let n = local(
+            "n",
+            LocalVarLocation::Register(3),
+            TypeKind::Integer { signed: true },
+            4,
+        );
+        let registers = serde_json::json!({ "rsp": "0x7ff0", "rbx": "0x2a" });
+        let locals = [buf, n];
+
+        let len = resolve_local_path(&locals, "buf.len", &registers, None, "x64")
+            .unwrap()
+            .unwrap();
+        assert_eq!(len.root, LocalRoot::Memory(0x8000));
+        assert_eq!(len.ops, vec![PathOp::Offset(8)]);
+
+        let whole = resolve_local_path(&locals, "buf", &registers, None, "x64")
+            .unwrap()
+            .unwrap();
+        assert_eq!(whole.fields.len(), 2);
+
+        let n = resolve_local_path(&locals, "n", &registers, None, "x64")
+            .unwrap()
+            .unwrap();
+        assert_eq!(n.root, LocalRoot::Value(0x2a));
+        assert!(n.ops.is_empty());
+    }
+}
@@ -1,13 +1,27 @@
+mod containers;
 mod function;
 mod handle;
+mod locals;
 mod parser;
+mod pretty;
+mod search;
 
+pub use containers::{
+    ContainerElement, ContainerLayout, ElementMember, FunctionContainers, MAX_DECODED_ARGS,
+};
 pub use function::{
-    FunctionInfo, LocalVarLocation, LocalVariableInfo, StructFieldRecipe, TypeKind, VariableInfo,
-    WatchRecipe,
+    FunctionInfo, GlobalRegion, InlinedCall, LocalVarLocation, LocalVariableInfo,
+    StructFieldRecipe, TypeKind, VariableInfo, WatchRecipe,
 };
 pub use handle::DwarfHandle;
-pub use parser::{DwarfParser, LineEntry};
+pub use locals::{
+    resolve_local_path, resolve_param_path, CallingConvention, LocalPath, LocalRoot, PathOp,
+};
+pub use parser::{DwarfParser, LineEntry, SourceLocation};
+pub use pretty::{
+    Enumerator, LayoutField, TypeLayout, MAX_ARRAY_ELEMENTS, MAX_LAYOUT_NODES, MAX_STRING_BYTES,
+};
+pub use search::SymbolQuery;
 
 // Re-export PatternMatcher for integration tests
 pub use parser::PatternMatcher;
@@ -42,20 +56,10 @@ pub fn resolve_crash_locals(
                     let addr = (fp_addr as i64 + offset) as u64;
                     read_from_frame(&frame_bytes, frame_start, addr, local.byte_size)
                 }
-                LocalVarLocation::Register(reg_num) => {
-                    let reg_name = register_name(*reg_num, arch);
-                    registers
-                        .get(&reg_name)
-                        .and_then(|v| v.as_str())
-                        .and_then(|s| u64::from_str_radix(s.trim_start_matches("0x"), 16).ok())
-                        .map(|v| format_value(v, local.byte_size, &local.type_kind))
-                }
+                LocalVarLocation::Register(reg_num) => register_value(registers, *reg_num, arch)
+                    .map(|v| format_value(v, local.byte_size, &local.type_kind)),
                 LocalVarLocation::RegisterOffset(reg_num, offset) => {
-                    let reg_name = register_name(*reg_num, arch);
-                    let base = registers
-                        .get(&reg_name)
-                        .and_then(|v| v.as_str())
-                        .and_then(|s| u64::from_str_radix(s.trim_start_matches("0x"), 16).ok())?;
+                    let base = register_value(registers, *reg_num, arch)?;
                     let addr = (base as i64 + offset) as u64;
                     read_from_frame(&frame_bytes, frame_start, addr, local.byte_size)
                 }
@@ -106,6 +110,14 @@ fn hex_to_bytes(hex: &str) -> Vec<u8> {
         .collect()
 }
 
+/// A DWARF register's value in a `{name: "0x..."}` register map.
+fn register_value(registers: &serde_json::Value, dwarf_reg: u16, arch: &str) -> Option<u64> {
+    registers
+        .get(register_name(dwarf_reg, arch))
+        .and_then(|v| v.as_str())
+        .and_then(|s| u64::from_str_radix(s.trim_start_matches("0x"), 16).ok())
+}
+
 fn register_name(dwarf_reg: u16, arch: &str) -> String {
     match arch {
         "arm64" => match dwarf_reg {
@@ -1,5 +1,11 @@
+use super::containers::{
+    classify, ContainerElement, ContainerLayout, ElementMember, FlatField, FunctionContainers,
+    MAX_DECODED_ARGS,
+};
+use super::pretty::{Enumerator, LayoutField, TypeLayout, MAX_ARRAY_ELEMENTS, MAX_LAYOUT_NODES};
 use super::{
-    FunctionInfo, LocalVarLocation, LocalVariableInfo, TypeKind, VariableInfo, WatchRecipe,
+    FunctionInfo, GlobalRegion, InlinedCall, LocalVarLocation, LocalVariableInfo, TypeKind,
+    VariableInfo, WatchRecipe,
 };
 use crate::symbols::demangle_symbol;
 use crate::{Error, Result};
@@ -8,7 +14,8 @@ use memmap2::Mmap;
 use object::read::macho::{FatArch, MachOFatFile32, MachOFatFile64};
 use object::{FileKind, Object, ObjectSection, ObjectSegment};
 use rayon::prelude::*;
-use std::collections::HashMap;
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
 use std::fs::File;
 use std::path::Path;
 use std::sync::Mutex;
@@ -105,9 +112,11 @@ fn load_dwarf_sections(path: &Path) -> Result<LoadedDwarf> {
                 let macho_name = name.replace(".debug_", "__debug_");
                 object.section_by_name(&macho_name)
             })
-            .and_then(|section| section.data().ok())
-            .unwrap_or(&[]);
-        Ok(data.to_vec())
+            // Go's linker compresses DWARF (.zdebug_* / SHF_COMPRESSED)
+            .and_then(|section| section.uncompressed_data().ok())
+            .map(|data| data.into_owned())
+            .unwrap_or_default();
+        Ok(data)
     };
 
     let sections = gimli::DwarfSections::load(&load_section)
@@ -137,6 +146,19 @@ pub struct LineEntry {
     pub is_statement: bool,
 }
 
+/// Where a code address comes from, as addr2line reports it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SourceLocation {
+    /// Function containing the address
+    pub function: String,
+    pub file: String,
+    pub line: u32,
+    pub column: u32,
+}
+
+/// A global's static address and its pretty-print layout.
+pub type ReadLayout = (u64, TypeLayout);
+
 #[derive(Debug, Clone)]
 pub(crate) struct StructMember {
     pub name: String,
@@ -161,6 +183,10 @@ pub struct DwarfParser {
     /// Stored DWARF offsets for pointer variables, enabling lazy struct member resolution.
     /// Maps variable name to (CU section offset, type DIE unit offset).
     pub(crate) lazy_struct_info: HashMap<String, (usize, usize)>,
+    /// Pretty-print layouts by (variable, depth), resolved on first read.
+    pub(crate) type_layouts: Mutex<HashMap<(String, u32), Option<ReadLayout>>>,
+    /// Formal parameters by function low_pc, read on first use.
+    pub(crate) function_params: Mutex<HashMap<u64, Vec<LocalVariableInfo>>>,
     /// The image base address from the Mach-O/ELF binary (e.g., __TEXT vmaddr).
     /// Used to compute offsets for ASLR adjustment at runtime.
     pub image_base: u64,
@@ -168,6 +194,23 @@ pub struct DwarfParser {
     pub(crate) binary_path: Option<std::path::PathBuf>,
     /// Parsed line table entries, sorted by address. Lazily populated on first line query.
     pub(crate) line_table: Mutex<Option<Vec<LineEntry>>>,
+    /// low_pc of every function from a Go compilation unit (empty for non-Go binaries)
+    pub(crate) go_functions: HashSet<u64>,
+    /// Offset of `goid` in `runtime.g`, when the Go runtime keeps g in a
+    /// register at function entry (Go 1.17+ on amd64, always on arm64)
+    pub(crate) goid_offset: Option<u64>,
+    /// Whether any function has a Swift linkage name
+    pub(crate) swift: bool,
+}
+
+/// First Go release that passes g in R14 on amd64 (register ABI).
+const GO_REGABI_MINOR: u32 = 17;
+
+/// `1.21` from a Go CU producer like `Go cmd/compile go1.21.5; regabi`.
+fn go_minor_version(producer: &str) -> Option<u32> {
+    let rest = &producer[producer.find("go1.")? + 4..];
+    let digits: String = rest.chars().take_while(|c| c.is_ascii_digit()).collect();
+    digits.parse().ok()
 }
 
 impl DwarfParser {
@@ -329,14 +372,20 @@ impl DwarfParser {
         Ok(None)
     }
 
-    /// Extract the image base address from a binary's __TEXT segment (Mach-O) or
-    /// first LOAD segment (ELF). This is the expected load address before ASLR.
+    /// Extract the image base address from a binary's __TEXT segment (Mach-O),
+    /// first LOAD segment (ELF) or optional header (PE). This is the expected
+    /// load address before ASLR.
     pub fn extract_image_base(binary_path: &Path) -> Result<u64> {
         let file = File::open(binary_path)?;
         let mmap = unsafe { Mmap::map(&file)? };
         let object = parse_object_file(&mmap)
             .map_err(|e| Error::Frida(format!("Failed to parse binary: {}", e)))?;
 
+        // PE: PDB addresses are RVAs from the preferred image base
+        if object.format() == object::BinaryFormat::Pe {
+            return Ok(object.relative_address_base());
+        }
+
         // Mach-O: use the __TEXT segment address directly
         for segment in object.segments() {
             if let Some(name) = segment.name().ok().flatten() {
@@ -414,11 +463,16 @@ impl DwarfParser {
                 let mut lazy_infos: Vec<(String, (usize, usize))> = Vec::new();
                 let mut declarations: Vec<(usize, String)> = Vec::new();
                 let mut unresolved: Vec<UnresolvedFunc> = Vec::new();
+                let mut go_functions: Vec<u64> = Vec::new();
+                let mut go_minor: Option<u32> = None;
+                let mut goid_offset: Option<u64> = None;
 
                 let mut entries = unit.entries();
                 let mut in_subprogram = false;
                 let mut subprogram_depth: isize = 0;
                 let mut current_depth: isize = 0;
+                let mut go_unit = false;
+                let mut runtime_g_depth: Option<isize> = None;
 
                 while let Ok(Some((delta, entry))) = entries.next_dfs() {
                     current_depth += delta;
@@ -426,8 +480,54 @@ impl DwarfParser {
                     if in_subprogram && current_depth <= subprogram_depth {
                         in_subprogram = false;
                     }
+                    if runtime_g_depth.is_some_and(|d| current_depth <= d) {
+                        runtime_g_depth = None;
+                    }
 
                     match entry.tag() {
+                        gimli::DW_TAG_compile_unit => {
+                            go_unit = matches!(
+                                entry.attr_value(gimli::DW_AT_language).ok().flatten(),
+                                Some(gimli::AttributeValue::Language(gimli::DW_LANG_Go))
+                            );
+                            if go_unit {
+                                go_minor = Self::resolve_string_attr(
+                                    &dwarf,
+                                    &unit,
+                                    entry,
+                                    gimli::DW_AT_producer,
+                                )
+                                .as_deref()
+                                .and_then(go_minor_version);
+                            }
+                        }
+                        // The goroutine id lives in runtime.g; its offset moves between releases
+                        gimli::DW_TAG_structure_type
+                            if go_unit
+                                && Self::resolve_string_attr(
+                                    &dwarf,
+                                    &unit,
+                                    entry,
+                                    gimli::DW_AT_name,
+                                )
+                                .as_deref()
+                                    == Some("runtime.g") =>
+                        {
+                            runtime_g_depth = Some(current_depth);
+                        }
+                        gimli::DW_TAG_member
+                            if runtime_g_depth.is_some_and(|d| current_depth == d + 1)
+                                && Self::resolve_string_attr(
+                                    &dwarf,
+                                    &unit,
+                                    entry,
+                                    gimli::DW_AT_name,
+                                )
+                                .as_deref()
+                                    == Some("goid") =>
+                        {
+                            goid_offset = Some(Self::parse_member_offset(entry));
+                        }
                         gimli::DW_TAG_subprogram => {
                             in_subprogram = true;
                             subprogram_depth = current_depth;
@@ -458,6 +558,12 @@ impl DwarfParser {
                             }
 
                             match Self::parse_function(&dwarf, &unit, entry) {
+                                Ok(Some(func)) if go_unit => {
+                                    if let Some(func) = Self::go_function(entry, func) {
+                                        go_functions.push(func.low_pc);
+                                        functions.push(func);
+                                    }
+                                }
                                 Ok(Some(func)) => functions.push(func),
                                 Ok(None) => {
                                     // Function had no name AND no same-CU reference — check
@@ -551,7 +657,15 @@ impl DwarfParser {
                     }
                 }
 
-                Some((functions, variables, lazy_infos, declarations, unresolved))
+                let go = go_unit.then_some((go_functions, go_minor, goid_offset));
+                Some((
+                    functions,
+                    variables,
+                    lazy_infos,
+                    declarations,
+                    unresolved,
+                    go,
+                ))
             })
             .collect();
 
@@ -561,13 +675,23 @@ impl DwarfParser {
         let mut lazy_struct_info = HashMap::new();
         let mut declaration_map: HashMap<usize, String> = HashMap::new();
         let mut all_unresolved: Vec<UnresolvedFunc> = Vec::new();
-        for (funcs, vars, infos, decls, unres) in results {
+        let mut go_functions = HashSet::new();
+        let mut go_minor: Option<u32> = None;
+        let mut goid_offset: Option<u64> = None;
+        for (funcs, vars, infos, decls, unres, go) in results {
             functions.extend(funcs);
             variables.extend(vars);
             lazy_struct_info.extend(infos);
             declaration_map.extend(decls);
             all_unresolved.extend(unres);
+            if let Some((go_funcs, minor, goid)) = go {
+                go_functions.extend(go_funcs);
+                go_minor = go_minor.max(minor);
+                goid_offset = goid_offset.or(goid);
+            }
         }
+        // Older toolchains keep g in TLS on amd64; reading it there is not worth the risk
+        let goid_offset = goid_offset.filter(|_| go_minor.is_some_and(|m| m >= GO_REGABI_MINOR));
 
         // Resolve cross-CU function references: match unresolved functions
         // (code with no name) to declarations (name with no code)
@@ -630,6 +754,13 @@ impl DwarfParser {
             .collect();
         functions_by_addr.sort_unstable_by_key(|&(low, _)| low);
 
+        let swift = functions.iter().any(|f| {
+            f.name_raw.as_deref().is_some_and(|raw| {
+                crate::symbols::demangler_registry().detect_language(raw)
+                    == Some(crate::symbols::SymbolLanguage::Swift)
+            })
+        });
+
         Ok(Self {
             functions,
             functions_by_name,
@@ -638,12 +769,45 @@ impl DwarfParser {
             variables_by_name,
             struct_members: Mutex::new(HashMap::new()),
             lazy_struct_info,
+            type_layouts: Mutex::new(HashMap::new()),
+            function_params: Mutex::new(HashMap::new()),
             image_base: 0, // Set by parse() from the actual binary
             binary_path: Some(path.to_path_buf()),
             line_table: Mutex::new(None),
+            go_functions,
+            goid_offset,
+            swift,
         })
     }
 
+    /// Go functions are named by their Go symbol (`main.(*Server).handle`)
+    /// even when the shape check wouldn't recognise it, and compiler-generated
+    /// wrappers (method value/interface trampolines, type equality and hash
+    /// functions) are left out.
+    fn go_function<R: gimli::Reader>(
+        entry: &gimli::DebuggingInformationEntry<R>,
+        mut func: FunctionInfo,
+    ) -> Option<FunctionInfo> {
+        let raw = func.name_raw.take().unwrap_or_else(|| func.name.clone());
+        let trampoline = entry
+            .attr_value(gimli::DW_AT_trampoline)
+            .ok()
+            .flatten()
+            .is_some();
+        if trampoline
+            || raw.starts_with("type:")
+            || raw.starts_with("type..")
+            || raw.starts_with("go:")
+            || raw.starts_with("go.")
+            || raw.ends_with("-fm")
+        {
+            return None;
+        }
+        func.name = crate::symbols::demangle_go(&raw);
+        func.name_raw = (func.name != raw).then_some(raw);
+        Some(func)
+    }
+
     /// Resolve a string attribute from an entry, handling DWARF v4/v5 string forms.
     fn resolve_string_attr<R: gimli::Reader>(
         dwarf: &gimli::Dwarf<R>,
@@ -714,6 +878,21 @@ impl DwarfParser {
         }
     }
 
+    /// Raw name of the function an inlined subroutine is a copy of, through its
+    /// DW_AT_abstract_origin (and that entry's DW_AT_specification).
+    fn inlined_name<R: gimli::Reader>(
+        dwarf: &gimli::Dwarf<R>,
+        unit: &gimli::Unit<R>,
+        entry: &gimli::DebuggingInformationEntry<R>,
+    ) -> Option<String> {
+        let origin = Self::resolve_reference(unit, entry)?;
+        let name_of = |e: &gimli::DebuggingInformationEntry<R>| {
+            Self::resolve_string_attr(dwarf, unit, e, gimli::DW_AT_linkage_name)
+                .or_else(|| Self::resolve_string_attr(dwarf, unit, e, gimli::DW_AT_name))
+        };
+        name_of(&origin).or_else(|| name_of(&Self::resolve_reference(unit, &origin)?))
+    }
+
     fn parse_function<R: gimli::Reader>(
         dwarf: &gimli::Dwarf<R>,
         unit: &gimli::Unit<R>,
@@ -1053,31 +1232,32 @@ impl DwarfParser {
         entry: &gimli::DebuggingInformationEntry<R>,
     ) -> Option<String> {
         match entry.attr_value(gimli::DW_AT_decl_file).ok()? {
-            Some(gimli::AttributeValue::FileIndex(index)) => {
-                if let Some(line_program) = &unit.line_program {
-                    let header = line_program.header();
-                    if let Some(file) = header.file(index) {
-                        let mut path = String::new();
-                        if let Some(dir) = file.directory(header) {
-                            if let Ok(s) = dwarf.attr_string(unit, dir) {
-                                path.push_str(&s.to_string_lossy().unwrap_or_default());
-                                path.push('/');
-                            }
-                        }
-                        if let Ok(s) = dwarf.attr_string(unit, file.path_name()) {
-                            path.push_str(&s.to_string_lossy().unwrap_or_default());
-                        }
-                        if !path.is_empty() {
-                            return Some(path);
-                        }
-                    }
-                }
-                None
-            }
+            Some(gimli::AttributeValue::FileIndex(index)) => Self::file_path(dwarf, unit, index),
             _ => None,
         }
     }
 
+    /// Path of entry `index` in the unit's line program file table.
+    fn file_path<R: gimli::Reader>(
+        dwarf: &gimli::Dwarf<R>,
+        unit: &gimli::Unit<R>,
+        index: u64,
+    ) -> Option<String> {
+        let header = unit.line_program.as_ref()?.header();
+        let file = header.file(index)?;
+        let mut path = String::new();
+        if let Some(dir) = file.directory(header) {
+            if let Ok(s) = dwarf.attr_string(unit, dir) {
+                path.push_str(&s.to_string_lossy().unwrap_or_default());
+                path.push('/');
+            }
+        }
+        if let Ok(s) = dwarf.attr_string(unit, file.path_name()) {
+            path.push_str(&s.to_string_lossy().unwrap_or_default());
+        }
+        (!path.is_empty()).then_some(path)
+    }
+
     /// Follow type chain (through typedefs, const, volatile) to find a struct/class
     /// and parse its members.
     fn parse_struct_members_from_type<R: gimli::Reader>(
@@ -1256,6 +1436,13 @@ impl DwarfParser {
     }
 
     pub fn resolve_watch_expression(&self, expr: &str) -> Result<WatchRecipe> {
+        // Member recipes start from a pointer; there is no offset-only step
+        if expr.contains('.') && self.find_variable_by_name(expr).is_none() {
+            return Err(Error::Frida(format!(
+                "'{}': '.' member access works on paused-frame locals only; for globals, use '->' through a pointer or read the whole struct",
+                expr
+            )));
+        }
         if !expr.contains("->") {
             // Simple variable — direct read
             let var = self
@@ -1419,14 +1606,150 @@ impl DwarfParser {
             .unwrap_or_default()
     }
 
+    /// A Go- or Swift-spelled pattern rewritten to the `::` form functions
+    /// are indexed under, when the binary has code in that language.
+    pub fn native_pattern(&self, pattern: &str) -> Option<String> {
+        if self.is_go() {
+            crate::symbols::go_pattern(pattern)
+        } else if self.swift {
+            crate::symbols::swift_pattern(pattern)
+        } else {
+            None
+        }
+    }
+
     pub fn find_by_pattern(&self, pattern: &str) -> Vec<&FunctionInfo> {
-        let matcher = PatternMatcher::new(pattern);
+        let native = self.native_pattern(pattern);
+        let matcher = PatternMatcher::new(native.as_deref().unwrap_or(pattern));
         self.functions
             .iter()
             .filter(|f| matcher.matches(&f.name))
             .collect()
     }
 
+    /// Inlined copies of the functions matching `pattern`, one entry per
+    /// (function, caller), sorted by function then caller.
+    /// Re-reads DWARF from disk, so only call it once a pattern has matched
+    /// nothing hookable.
+    pub fn find_inlined(&self, pattern: &str) -> Result<Vec<InlinedCall>> {
+        let binary_path = self
+            .binary_path
+            .as_ref()
+            .ok_or_else(|| Error::Frida("No binary path for DWARF re-parse".into()))?;
+
+        let native = self.native_pattern(pattern);
+        let matcher = PatternMatcher::new(native.as_deref().unwrap_or(pattern));
+        let callers: HashMap<u64, &str> = self
+            .functions
+            .iter()
+            .filter(|f| f.low_pc > 0)
+            .map(|f| (f.low_pc, f.name.as_str()))
+            .collect();
+
+        let loaded = load_dwarf_sections(binary_path)?;
+        let dwarf = loaded.borrow();
+
+        // Demangled name by raw name, None when it doesn't match
+        let mut names: HashMap<String, Option<String>> = HashMap::new();
+        let mut calls: HashMap<(String, &str), InlinedCall> = HashMap::new();
+
+        let mut units = dwarf.units();
+        while let Ok(Some(header)) = units.next() {
+            let unit = match dwarf.unit(header) {
+                Ok(u) => u,
+                Err(_) => continue,
+            };
+
+            let mut entries = unit.entries();
+            let mut caller: Option<&str> = None;
+            let mut subprogram_depth: isize = 0;
+            let mut current_depth: isize = 0;
+
+            while let Ok(Some((delta, entry))) = entries.next_dfs() {
+                current_depth += delta;
+
+                if current_depth <= subprogram_depth {
+                    caller = None;
+                }
+
+                match entry.tag() {
+                    gimli::DW_TAG_subprogram => {
+                        subprogram_depth = current_depth;
+                        // Named as in the function index, which already resolved
+                        // cross-CU specifications and Go symbol names
+                        caller = entry
+                            .attr_value(gimli::DW_AT_low_pc)
+                            .ok()
+                            .flatten()
+                            .and_then(|v| dwarf.attr_address(&unit, v).ok().flatten())
+                            .and_then(|low_pc| callers.get(&low_pc).copied());
+                    }
+                    gimli::DW_TAG_inlined_subroutine => {
+                        let (Some(caller), Some(raw)) =
+                            (caller, Self::inlined_name(&dwarf, &unit, entry))
+                        else {
+                            continue;
+                        };
+                        let name = names.entry(raw).or_insert_with_key(|raw| {
+                            let name = demangle_symbol(raw);
+                            matcher.matches(&name).then_some(name)
+                        });
+                        let Some(name) = name else {
+                            continue;
+                        };
+                        if let Some(call) = calls.get_mut(&(name.clone(), caller)) {
+                            call.sites += 1;
+                            continue;
+                        }
+                        let call_file = match entry.attr_value(gimli::DW_AT_call_file) {
+                            Ok(Some(gimli::AttributeValue::FileIndex(index))) => {
+                                Self::file_path(&dwarf, &unit, index)
+                            }
+                            _ => None,
+                        };
+                        let call_line = entry
+                            .attr_value(gimli::DW_AT_call_line)
+                            .ok()
+                            .flatten()
+                            .and_then(|v| v.udata_value())
+                            .map(|n| n as u32);
+                        calls.insert(
+                            (name.clone(), caller),
+                            InlinedCall {
+                                name: name.clone(),
+                                caller: caller.to_string(),
+                                call_file,
+                                call_line,
+                                sites: 1,
+                            },
+                        );
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        let mut calls: Vec<InlinedCall> = calls.into_values().collect();
+        calls.sort_unstable_by(|a, b| a.name.cmp(&b.name).then_with(|| a.caller.cmp(&b.caller)));
+        Ok(calls)
+    }
+
+    /// Whether the binary contains Go code.
+    pub fn is_go(&self) -> bool {
+        !self.go_functions.is_empty()
+    }
+
+    /// Whether the function starting at `low_pc` was compiled from Go.
+    pub fn is_go_function(&self, low_pc: u64) -> bool {
+        self.go_functions.contains(&low_pc)
+    }
+
+    /// Offset of `goid` in `runtime.g`, if goroutine ids can be read at
+    /// function entry in this binary.
+    pub fn goroutine_id_offset(&self) -> Option<u64> {
+        self.goid_offset
+    }
+
     pub fn user_code_functions(&self, project_root: &str) -> Vec<&FunctionInfo> {
         self.functions
             .iter()
@@ -1523,6 +1846,96 @@ impl DwarfParser {
         Ok(locals)
     }
 
+    /// Formal parameters of the function starting at `low_pc` (static), in
+    /// declaration order. Parameters of an out-of-line instance take their
+    /// name and type from the abstract origin; unnamed ones are kept with an
+    /// empty name so the ones after them stay in place. Re-reads DWARF from
+    /// disk the first time a function is asked for.
+    pub fn parse_params(&self, low_pc: u64) -> Result<Vec<LocalVariableInfo>> {
+        if let Some(cached) = self.function_params.lock().unwrap().get(&low_pc) {
+            return Ok(cached.clone());
+        }
+        let params = self.read_params(low_pc)?;
+        self.function_params
+            .lock()
+            .unwrap()
+            .insert(low_pc, params.clone());
+        Ok(params)
+    }
+
+    fn read_params(&self, low_pc: u64) -> Result<Vec<LocalVariableInfo>> {
+        let binary_path = self
+            .binary_path
+            .as_ref()
+            .ok_or_else(|| Error::Frida("No binary path for DWARF re-parse".into()))?;
+
+        let loaded = load_dwarf_sections(binary_path)?;
+        let dwarf = loaded.borrow();
+
+        let mut units = dwarf.units();
+        while let Ok(Some(header)) = units.next() {
+            let unit = match dwarf.unit(header) {
+                Ok(u) => u,
+                Err(_) => continue,
+            };
+
+            let mut entries = unit.entries();
+            let mut target_depth: Option<isize> = None;
+            let mut current_depth: isize = 0;
+            let mut params = Vec::new();
+
+            while let Ok(Some((delta, entry))) = entries.next_dfs() {
+                current_depth += delta;
+
+                if let Some(depth) = target_depth {
+                    if current_depth <= depth {
+                        return Ok(params);
+                    }
+                    if current_depth == depth + 1 && entry.tag() == gimli::DW_TAG_formal_parameter {
+                        let param = Self::parse_local_variable(&dwarf, &unit, entry)
+                            .or_else(|| {
+                                let origin = Self::resolve_reference(&unit, entry)?;
+                                Self::parse_local_variable(&dwarf, &unit, &origin)
+                            })
+                            .unwrap_or_else(|| {
+                                let (byte_size, type_kind, type_name) = Self::resolve_type_info(
+                                    &dwarf, &unit, entry,
+                                )
+                                .unwrap_or((0, TypeKind::Unknown, None));
+                                LocalVariableInfo {
+                                    name: String::new(),
+                                    byte_size,
+                                    type_kind,
+                                    type_name,
+                                    location: LocalVarLocation::Complex,
+                                    members: None,
+                                }
+                            });
+                        params.push(param);
+                    }
+                    continue;
+                }
+
+                if entry.tag() == gimli::DW_TAG_subprogram {
+                    let entry_low_pc = entry
+                        .attr_value(gimli::DW_AT_low_pc)
+                        .ok()
+                        .flatten()
+                        .and_then(|v| dwarf.attr_address(&unit, v).ok().flatten());
+                    if entry_low_pc == Some(low_pc) {
+                        target_depth = Some(current_depth);
+                    }
+                }
+            }
+
+            if target_depth.is_some() {
+                return Ok(params);
+            }
+        }
+
+        Ok(Vec::new())
+    }
+
     fn parse_local_variable<R: gimli::Reader>(
         dwarf: &gimli::Dwarf<R>,
         unit: &gimli::Unit<R>,
@@ -1562,83 +1975,1135 @@ impl DwarfParser {
         let (byte_size, type_kind, type_name) =
             Self::resolve_type_info(dwarf, unit, entry).unwrap_or((0, TypeKind::Unknown, None));
 
+        // Members reachable with `.` (struct locals) or `->` (struct pointers)
+        let type_attr = entry.attr_value(gimli::DW_AT_type).ok().flatten();
+        let members = match (type_attr, &type_kind) {
+            (Some(t), TypeKind::Pointer) => Self::parse_pointee_members(dwarf, unit, t, 0),
+            (Some(t), _) => Self::parse_struct_members_from_type(dwarf, unit, t, 0),
+            (None, _) => None,
+        };
+
         Some(LocalVariableInfo {
             name,
             byte_size,
             type_kind,
             type_name,
             location,
+            members,
         })
     }
 
-    // ========== Phase 2: Line table support ==========
+    /// Members of the struct a pointer type points at, through typedefs and
+    /// qualifiers on the pointer.
+    fn parse_pointee_members<R: gimli::Reader>(
+        dwarf: &gimli::Dwarf<R>,
+        unit: &gimli::Unit<R>,
+        type_attr: gimli::AttributeValue<R>,
+        depth: usize,
+    ) -> Option<Vec<StructMember>> {
+        if depth > 10 {
+            return None;
+        }
 
-    /// Resolve file:line to instruction address. Snaps to nearest is_statement line.
-    /// Returns (address, actual_line) or None if no code at that location.
-    pub fn resolve_line(&self, file: &str, line: u32) -> Option<(u64, u32)> {
-        self.ensure_line_table();
-        let table = self.line_table.lock().unwrap();
-        let entries = table.as_ref()?;
+        let offset = match type_attr {
+            gimli::AttributeValue::UnitRef(o) => o,
+            gimli::AttributeValue::DebugInfoRef(di_off) => di_off.to_unit_offset(&unit.header)?,
+            _ => return None,
+        };
 
-        // Find entries matching file.
-        // Use path-component-aware matching: require separator before the match
-        // to avoid "main.c" matching "not_main.c". Also handle OS separator
-        // differences in DWARF paths.
-        let sep_file = format!("/{}", file);
-        let sep_file_win = format!("\\{}", file);
-        let mut matches: Vec<_> = entries
-            .iter()
-            .filter(|e| {
-                e.is_statement
-                    && (e.file == file
-                        || e.file.ends_with(&sep_file)
-                        || e.file.ends_with(&sep_file_win))
-            })
-            .collect();
+        let mut tree = unit.entries_tree(Some(offset)).ok()?;
+        let root = tree.root().ok()?;
+        let type_entry = root.entry();
+        let next = type_entry.attr_value(gimli::DW_AT_type).ok()??;
 
-        if matches.is_empty() {
-            return None;
+        match type_entry.tag() {
+            gimli::DW_TAG_pointer_type | gimli::DW_TAG_reference_type => {
+                Self::parse_struct_members_from_type(dwarf, unit, next, 0)
+            }
+            gimli::DW_TAG_typedef
+            | gimli::DW_TAG_const_type
+            | gimli::DW_TAG_volatile_type
+            | gimli::DW_TAG_restrict_type => {
+                Self::parse_pointee_members(dwarf, unit, next, depth + 1)
+            }
+            _ => None,
         }
-
-        // Find closest line >= requested line
-        matches.sort_by_key(|e| e.line);
-        matches
-            .iter()
-            .find(|e| e.line >= line)
-            .map(|e| (e.address, e.line))
     }
 
-    /// Find nearest valid line numbers for error messages
-    pub fn find_nearest_lines(&self, file: &str, target_line: u32, count: usize) -> String {
-        self.ensure_line_table();
-        let table = self.line_table.lock().unwrap();
-        let entries = match table.as_ref() {
-            Some(e) => e,
-            None => return "unknown".to_string(),
-        };
-
-        // Get all unique statement lines for this file
-        let sep_file = format!("/{}", file);
-        let sep_file_win = format!("\\{}", file);
-        let mut lines: Vec<u32> = entries
-            .iter()
-            .filter(|e| {
-                e.is_statement
-                    && (e.file == file
-                        || e.file.ends_with(&sep_file)
-                        || e.file.ends_with(&sep_file_win))
+    /// Resolve globals to their full storage extent. Unlike `variables`, which
+    /// only indexes scalars for watches, this sizes structs, unions and arrays.
+    /// The result follows `names` order and omits names with no addressed
+    /// definition. Re-reads DWARF from disk.
+    pub fn resolve_global_regions(&self, names: &[String]) -> Result<Vec<GlobalRegion>> {
+        self.find_global_definitions(names, |dwarf, unit, name, address, type_attr| {
+            let byte_size = Self::storage_size(unit, type_attr, 0)?;
+            let type_name = Self::type_ref_offset(unit, type_attr)
+                .and_then(|o| unit.entry(o).ok())
+                .and_then(|t| Self::resolve_string_attr(dwarf, unit, &t, gimli::DW_AT_name));
+            Some(GlobalRegion {
+                name: name.to_string(),
+                address,
+                byte_size,
+                type_name,
             })
-            .map(|e| e.line)
-            .collect();
+        })
+    }
 
-        lines.sort_unstable();
-        lines.dedup();
+    /// Address and pretty-print layout of a global for `debug_memory` reads,
+    /// expanding `depth` levels of struct nesting (pointers to structs count
+    /// as the level they point at). None for `->` expressions and for plain
+    /// scalars the watch index already reads, which need no type tree.
+    pub fn resolve_read_layout(&self, variable: &str, depth: u32) -> Result<Option<ReadLayout>> {
+        if variable.contains("->") {
+            return Ok(None);
+        }
+        if let Some(var) = self.find_variable_by_name(variable) {
+            let is_enum = var.type_name.as_deref() == Some("enum");
+            if !matches!(var.type_kind, TypeKind::Pointer) && !is_enum {
+                return Ok(None);
+            }
+        }
 
-        if lines.is_empty() {
-            return "none".to_string();
+        let key = (variable.to_string(), depth);
+        if let Some(cached) = self.type_layouts.lock().unwrap().get(&key) {
+            return Ok(cached.clone());
         }
 
-        // Find nearest lines (before and after target)
+        let names = [variable.to_string()];
+        let resolved = self
+            .find_global_definitions(&names, |dwarf, unit, _, address, type_attr| {
+                let mut budget = MAX_LAYOUT_NODES;
+                let layout = Self::type_layout(dwarf, unit, type_attr, depth, &mut budget, 0);
+                Some((address, layout))
+            })?
+            .pop()
+            .filter(|(_, layout)| !layout.is_scalar());
+
+        self.type_layouts
+            .lock()
+            .unwrap()
+            .insert(key, resolved.clone());
+        Ok(resolved)
+    }
+
+    /// Visit the addressed definition of each name (the demangled qualified
+    /// name or the short DW_AT_name) and collect what `build` makes of its
+    /// address and type. Results follow `names` order; names with no
+    /// definition, or that `build` rejects, are omitted. Re-reads DWARF.
+    fn find_global_definitions<T>(
+        &self,
+        names: &[String],
+        mut build: impl for<'a> FnMut(
+            &gimli::Dwarf<EndianSlice<'a, RunTimeEndian>>,
+            &gimli::Unit<EndianSlice<'a, RunTimeEndian>>,
+            &str,
+            u64,
+            gimli::AttributeValue<EndianSlice<'a, RunTimeEndian>>,
+        ) -> Option<T>,
+    ) -> Result<Vec<T>> {
+        let binary_path = self
+            .binary_path
+            .as_ref()
+            .ok_or_else(|| Error::Frida("No binary path for DWARF re-parse".into()))?;
+
+        let loaded = load_dwarf_sections(binary_path)?;
+        let dwarf = loaded.borrow();
+
+        let mut found: HashMap<&str, T> = HashMap::new();
+
+        let mut units = dwarf.units();
+        while let Ok(Some(header)) = units.next() {
+            if found.len() == names.len() {
+                break;
+            }
+            let unit = match dwarf.unit(header) {
+                Ok(u) => u,
+                Err(_) => continue,
+            };
+
+            let mut entries = unit.entries();
+            while let Ok(Some((_, entry))) = entries.next_dfs() {
+                if entry.tag() != gimli::DW_TAG_variable {
+                    continue;
+                }
+                // Declarations carry no location; only the definition is addressed
+                let Some(address) = Self::parse_variable_address(&dwarf, &unit, entry) else {
+                    continue;
+                };
+                let reference = Self::resolve_reference(&unit, entry);
+                let attr = |attr| {
+                    Self::resolve_string_attr(&dwarf, &unit, entry, attr).or_else(|| {
+                        reference
+                            .as_ref()
+                            .and_then(|r| Self::resolve_string_attr(&dwarf, &unit, r, attr))
+                    })
+                };
+                let short_name = attr(gimli::DW_AT_name);
+                let qualified = attr(gimli::DW_AT_linkage_name).map(|n| demangle_symbol(&n));
+                let Some(wanted) = names.iter().find(|n| {
+                    !found.contains_key(n.as_str())
+                        && (short_name.as_deref() == Some(n.as_str())
+                            || qualified.as_deref() == Some(n.as_str()))
+                }) else {
+                    continue;
+                };
+
+                let type_attr = entry
+                    .attr_value(gimli::DW_AT_type)
+                    .ok()
+                    .flatten()
+                    .or_else(|| {
+                        reference
+                            .as_ref()
+                            .and_then(|r| r.attr_value(gimli::DW_AT_type).ok().flatten())
+                    });
+                let Some(type_attr) = type_attr else {
+                    continue;
+                };
+                if let Some(value) = build(&dwarf, &unit, wanted, address, type_attr) {
+                    found.insert(wanted.as_str(), value);
+                }
+            }
+        }
+
+        Ok(names
+            .iter()
+            .filter_map(|n| found.remove(n.as_str()))
+            .collect())
+    }
+
+    /// Build the pretty-print layout of a type. Structs and unions use up one
+    /// level of `depth` and become opaque at zero; every node draws on
+    /// `budget`, and members past it are opaque too.
+    fn type_layout<R: gimli::Reader>(
+        dwarf: &gimli::Dwarf<R>,
+        unit: &gimli::Unit<R>,
+        type_attr: gimli::AttributeValue<R>,
+        depth: u32,
+        budget: &mut usize,
+        nesting: usize,
+    ) -> TypeLayout {
+        let opaque = |type_name, size| TypeLayout::Opaque { type_name, size };
+        let Some(offset) = Self::type_ref_offset(unit, type_attr.clone()) else {
+            return opaque(None, 0);
+        };
+        let Ok(entry) = unit.entry(offset) else {
+            return opaque(None, 0);
+        };
+        let name = Self::resolve_string_attr(dwarf, unit, &entry, gimli::DW_AT_name);
+        let size = Self::storage_size(unit, type_attr, 0).unwrap_or(0);
+        if nesting > 32 || *budget == 0 {
+            return opaque(name, size);
+        }
+
+        match entry.tag() {
+            gimli::DW_TAG_typedef
+            | gimli::DW_TAG_const_type
+            | gimli::DW_TAG_volatile_type
+            | gimli::DW_TAG_restrict_type
+            | gimli::DW_TAG_atomic_type => {
+                let Some(next) = entry.attr_value(gimli::DW_AT_type).ok().flatten() else {
+                    return opaque(name, size);
+                };
+                let mut layout = Self::type_layout(dwarf, unit, next, depth, budget, nesting + 1);
+                // `typedef struct { ... } engine_t;` names the anonymous struct
+                if let TypeLayout::Struct { type_name, .. }
+                | TypeLayout::Union { type_name, .. }
+                | TypeLayout::Enum { type_name, .. }
+                | TypeLayout::Opaque { type_name, .. } = &mut layout
+                {
+                    if type_name.is_none() && entry.tag() == gimli::DW_TAG_typedef {
+                        *type_name = name;
+                    }
+                }
+                return layout;
+            }
+            _ => *budget -= 1,
+        }
+
+        match entry.tag() {
+            gimli::DW_TAG_base_type => {
+                let encoding = match entry.attr_value(gimli::DW_AT_encoding).ok().flatten() {
+                    Some(gimli::AttributeValue::Encoding(e)) => Some(e),
+                    _ => None,
+                };
+                match encoding {
+                    Some(gimli::DW_ATE_float) => TypeLayout::Float { size },
+                    Some(gimli::DW_ATE_boolean) => TypeLayout::Bool { size },
+                    Some(gimli::DW_ATE_signed_char)
+                    | Some(gimli::DW_ATE_unsigned_char)
+                    | Some(gimli::DW_ATE_UTF)
+                        if size == 1 =>
+                    {
+                        TypeLayout::Char { size }
+                    }
+                    Some(gimli::DW_ATE_signed) | Some(gimli::DW_ATE_signed_char) => {
+                        TypeLayout::Int { size, signed: true }
+                    }
+                    Some(gimli::DW_ATE_unsigned)
+                    | Some(gimli::DW_ATE_unsigned_char)
+                    | Some(gimli::DW_ATE_UTF) => TypeLayout::Int {
+                        size,
+                        signed: false,
+                    },
+                    _ => opaque(name, size),
+                }
+            }
+            gimli::DW_TAG_enumeration_type => {
+                let signed = entry
+                    .attr_value(gimli::DW_AT_type)
+                    .ok()
+                    .flatten()
+                    .and_then(|t| Self::follow_type_chain(dwarf, unit, t, 0))
+                    .is_some_and(|(_, kind, _)| matches!(kind, TypeKind::Integer { signed: true }));
+                let mut enumerators = Vec::new();
+                if let Ok(mut tree) = unit.entries_tree(Some(offset)) {
+                    if let Ok(root) = tree.root() {
+                        let mut children = root.children();
+                        while let Ok(Some(child)) = children.next() {
+                            let e = child.entry();
+                            if e.tag() != gimli::DW_TAG_enumerator {
+                                continue;
+                            }
+                            let value = e.attr_value(gimli::DW_AT_const_value).ok().flatten();
+                            let value = value.and_then(|v| {
+                                if signed {
+                                    v.sdata_value()
+                                } else {
+                                    v.udata_value().map(|u| u as i64)
+                                }
+                            });
+                            let label =
+                                Self::resolve_string_attr(dwarf, unit, e, gimli::DW_AT_name);
+                            if let (Some(name), Some(value)) = (label, value) {
+                                enumerators.push(Enumerator { name, value });
+                            }
+                        }
+                    }
+                }
+                TypeLayout::Enum {
+                    size,
+                    signed,
+                    type_name: name,
+                    enumerators,
+                }
+            }
+            gimli::DW_TAG_pointer_type
+            | gimli::DW_TAG_reference_type
+            | gimli::DW_TAG_rvalue_reference_type => {
+                // void* has no DW_AT_type
+                let Some(pointee_attr) = entry.attr_value(gimli::DW_AT_type).ok().flatten() else {
+                    return TypeLayout::Pointer {
+                        type_name: Some("void*".to_string()),
+                        pointee: None,
+                    };
+                };
+                let target = Self::strip_qualifiers(unit, pointee_attr.clone(), 0)
+                    .and_then(|o| unit.entry(o).ok());
+                let Some(target) = target else {
+                    return TypeLayout::Pointer {
+                        type_name: None,
+                        pointee: None,
+                    };
+                };
+                let target_name =
+                    Self::resolve_string_attr(dwarf, unit, &target, gimli::DW_AT_name);
+                let type_name = target_name.as_ref().map(|n| format!("{}*", n));
+                match target.tag() {
+                    gimli::DW_TAG_base_type if Self::is_char_type(&target) => TypeLayout::CString,
+                    gimli::DW_TAG_structure_type
+                    | gimli::DW_TAG_class_type
+                    | gimli::DW_TAG_union_type
+                        if depth > 0 =>
+                    {
+                        TypeLayout::Pointer {
+                            type_name,
+                            pointee: Some(Box::new(Self::type_layout(
+                                dwarf,
+                                unit,
+                                pointee_attr,
+                                depth,
+                                budget,
+                                nesting + 1,
+                            ))),
+                        }
+                    }
+                    _ => TypeLayout::Pointer {
+                        type_name,
+                        pointee: None,
+                    },
+                }
+            }
+            gimli::DW_TAG_structure_type | gimli::DW_TAG_class_type | gimli::DW_TAG_union_type => {
+                if depth == 0 {
+                    return opaque(name, size);
+                }
+                let fields = Self::layout_fields(dwarf, unit, offset, depth - 1, budget, nesting);
+                if entry.tag() == gimli::DW_TAG_union_type {
+                    TypeLayout::Union {
+                        type_name: name,
+                        size,
+                        fields,
+                    }
+                } else {
+                    TypeLayout::Struct {
+                        type_name: name,
+                        size,
+                        fields,
+                    }
+                }
+            }
+            gimli::DW_TAG_array_type => {
+                let Some(element_attr) = entry.attr_value(gimli::DW_AT_type).ok().flatten() else {
+                    return opaque(name, size);
+                };
+                let mut dims = Vec::new();
+                if let Ok(mut tree) = unit.entries_tree(Some(offset)) {
+                    if let Ok(root) = tree.root() {
+                        let mut children = root.children();
+                        while let Ok(Some(child)) = children.next() {
+                            let dim = child.entry();
+                            if dim.tag() != gimli::DW_TAG_subrange_type {
+                                continue;
+                            }
+                            let udata = |attr| {
+                                dim.attr_value(attr)
+                                    .ok()
+                                    .flatten()
+                                    .and_then(|v| v.udata_value())
+                            };
+                            // Flexible array members have no extent; show none
+                            let extent = udata(gimli::DW_AT_count).or_else(|| {
+                                let upper = udata(gimli::DW_AT_upper_bound)?;
+                                let lower = udata(gimli::DW_AT_lower_bound).unwrap_or(0);
+                                (upper + 1).checked_sub(lower)
+                            });
+                            dims.push(extent.unwrap_or(0));
+                        }
+                    }
+                }
+                if dims.is_empty() {
+                    dims.push(0);
+                }
+
+                let element_is_char = Self::strip_qualifiers(unit, element_attr.clone(), 0)
+                    .and_then(|o| unit.entry(o).ok())
+                    .is_some_and(|e| e.tag() == gimli::DW_TAG_base_type && Self::is_char_type(&e));
+                let (mut layout, mut stride) = if element_is_char {
+                    // The innermost dimension of a char array is a string
+                    let len = dims.pop().unwrap_or(0);
+                    (TypeLayout::CharArray { len }, len)
+                } else {
+                    let element = Self::type_layout(
+                        dwarf,
+                        unit,
+                        element_attr.clone(),
+                        depth,
+                        budget,
+                        nesting + 1,
+                    );
+                    let element_size = Self::storage_size(unit, element_attr, 0).unwrap_or(0);
+                    (element, element_size)
+                };
+                for &count in dims.iter().rev() {
+                    layout = TypeLayout::Array {
+                        count,
+                        shown: count.min(MAX_ARRAY_ELEMENTS),
+                        stride,
+                        element: Box::new(layout),
+                    };
+                    stride = stride.saturating_mul(count);
+                }
+                layout
+            }
+            _ => opaque(name, size),
+        }
+    }
+
+    /// Data members and base classes of the struct/union at `offset`.
+    /// Static members have no storage in the object and are skipped.
+    fn layout_fields<R: gimli::Reader>(
+        dwarf: &gimli::Dwarf<R>,
+        unit: &gimli::Unit<R>,
+        offset: gimli::UnitOffset<R::Offset>,
+        depth: u32,
+        budget: &mut usize,
+        nesting: usize,
+    ) -> Vec<LayoutField> {
+        let mut fields = Vec::new();
+        let Ok(mut tree) = unit.entries_tree(Some(offset)) else {
+            return fields;
+        };
+        let Ok(root) = tree.root() else {
+            return fields;
+        };
+        let mut children = root.children();
+        while let Ok(Some(child)) = children.next() {
+            let member = child.entry();
+            let is_base = member.tag() == gimli::DW_TAG_inheritance;
+            if member.tag() != gimli::DW_TAG_member && !is_base {
+                continue;
+            }
+            let flag = |attr| {
+                matches!(
+                    member.attr_value(attr).ok().flatten(),
+                    Some(gimli::AttributeValue::Flag(true))
+                )
+            };
+            if flag(gimli::DW_AT_declaration) || flag(gimli::DW_AT_external) {
+                continue;
+            }
+            let Some(member_type) = member.attr_value(gimli::DW_AT_type).ok().flatten() else {
+                continue;
+            };
+
+            let layout =
+                Self::type_layout(dwarf, unit, member_type.clone(), depth, budget, nesting + 1);
+            let name = Self::resolve_string_attr(dwarf, unit, member, gimli::DW_AT_name)
+                .or_else(|| {
+                    // Base classes are named after their type
+                    let base = Self::type_ref_offset(unit, member_type.clone())?;
+                    let base = unit.entry(base).ok()?;
+                    Self::resolve_string_attr(dwarf, unit, &base, gimli::DW_AT_name)
+                })
+                .unwrap_or_else(|| "<anonymous>".to_string());
+
+            let udata = |attr| {
+                member
+                    .attr_value(attr)
+                    .ok()
+                    .flatten()
+                    .and_then(|v| v.udata_value())
+            };
+            let mut field_offset = Self::parse_member_offset(member);
+            let bit_size = udata(gimli::DW_AT_bit_size);
+            let mut bit_offset = None;
+            if bit_size.is_some() {
+                if let Some(data_bit_offset) = udata(gimli::DW_AT_data_bit_offset) {
+                    // DWARF 4+: bits from the start of the struct
+                    field_offset = data_bit_offset / 8;
+                    bit_offset = Some(data_bit_offset % 8);
+                } else if let (Some(msb_offset), Some(bits)) =
+                    (udata(gimli::DW_AT_bit_offset), bit_size)
+                {
+                    // DWARF 2/3: bits from the most significant end of a
+                    // storage unit of DW_AT_byte_size at the member offset
+                    let storage = udata(gimli::DW_AT_byte_size)
+                        .or_else(|| Self::storage_size(unit, member_type, 0))
+                        .unwrap_or(0);
+                    let lsb = (storage * 8).saturating_sub(msb_offset + bits);
+                    field_offset += lsb / 8;
+                    bit_offset = Some(lsb % 8);
+                } else {
+                    bit_offset = Some(0);
+                }
+            }
+
+            fields.push(LayoutField {
+                name,
+                offset: field_offset,
+                bit_offset,
+                bit_size,
+                layout,
+            });
+        }
+        fields
+    }
+
+    /// Follow typedef/cv qualifiers to the underlying type entry.
+    fn strip_qualifiers<R: gimli::Reader>(
+        unit: &gimli::Unit<R>,
+        type_attr: gimli::AttributeValue<R>,
+        depth: usize,
+    ) -> Option<gimli::UnitOffset<R::Offset>> {
+        if depth > 10 {
+            return None;
+        }
+        let offset = Self::type_ref_offset(unit, type_attr)?;
+        let entry = unit.entry(offset).ok()?;
+        match entry.tag() {
+            gimli::DW_TAG_typedef
+            | gimli::DW_TAG_const_type
+            | gimli::DW_TAG_volatile_type
+            | gimli::DW_TAG_restrict_type
+            | gimli::DW_TAG_atomic_type => {
+                let next = entry.attr_value(gimli::DW_AT_type).ok()??;
+                Self::strip_qualifiers(unit, next, depth + 1)
+            }
+            _ => Some(offset),
+        }
+    }
+
+    /// One-byte character base types (`char`, `signed/unsigned char`, `char8_t`).
+    fn is_char_type<R: gimli::Reader>(entry: &gimli::DebuggingInformationEntry<R>) -> bool {
+        let size = entry
+            .attr_value(gimli::DW_AT_byte_size)
+            .ok()
+            .flatten()
+            .and_then(|v| v.udata_value());
+        let encoding = entry.attr_value(gimli::DW_AT_encoding).ok().flatten();
+        size == Some(1)
+            && matches!(
+                encoding,
+                Some(gimli::AttributeValue::Encoding(
+                    gimli::DW_ATE_signed_char | gimli::DW_ATE_unsigned_char | gimli::DW_ATE_UTF
+                ))
+            )
+    }
+
+    /// Size in bytes of an object of this type: follows typedef/cv qualifiers
+    /// and multiplies array element sizes by every dimension.
+    fn storage_size<R: gimli::Reader>(
+        unit: &gimli::Unit<R>,
+        type_attr: gimli::AttributeValue<R>,
+        depth: usize,
+    ) -> Option<u64> {
+        if depth > 10 {
+            return None;
+        }
+        let offset = Self::type_ref_offset(unit, type_attr)?;
+        let entry = unit.entry(offset).ok()?;
+        let declared = entry
+            .attr_value(gimli::DW_AT_byte_size)
+            .ok()
+            .flatten()
+            .and_then(|v| v.udata_value());
+
+        match entry.tag() {
+            gimli::DW_TAG_typedef
+            | gimli::DW_TAG_const_type
+            | gimli::DW_TAG_volatile_type
+            | gimli::DW_TAG_restrict_type
+            | gimli::DW_TAG_atomic_type => {
+                let next = entry.attr_value(gimli::DW_AT_type).ok()??;
+                Self::storage_size(unit, next, depth + 1)
+            }
+            gimli::DW_TAG_pointer_type
+            | gimli::DW_TAG_reference_type
+            | gimli::DW_TAG_rvalue_reference_type => {
+                Some(declared.unwrap_or(unit.encoding().address_size as u64))
+            }
+            gimli::DW_TAG_array_type => {
+                if declared.is_some() {
+                    return declared;
+                }
+                let element = entry.attr_value(gimli::DW_AT_type).ok()??;
+                let element_size = Self::storage_size(unit, element, depth + 1)?;
+
+                let mut tree = unit.entries_tree(Some(offset)).ok()?;
+                let root = tree.root().ok()?;
+                let mut children = root.children();
+                let mut count = 1u64;
+                while let Ok(Some(child)) = children.next() {
+                    let dim = child.entry();
+                    if dim.tag() != gimli::DW_TAG_subrange_type {
+                        continue;
+                    }
+                    let udata = |attr| {
+                        dim.attr_value(attr)
+                            .ok()
+                            .flatten()
+                            .and_then(|v| v.udata_value())
+                    };
+                    // Flexible/variable-length arrays have neither: no static size
+                    let extent = match udata(gimli::DW_AT_count) {
+                        Some(n) => n,
+                        None => {
+                            let upper = udata(gimli::DW_AT_upper_bound)?;
+                            let lower = udata(gimli::DW_AT_lower_bound).unwrap_or(0);
+                            (upper + 1).checked_sub(lower)?
+                        }
+                    };
+                    count = count.checked_mul(extent)?;
+                }
+                element_size.checked_mul(count)
+            }
+            _ => declared,
+        }
+    }
+
+    // ========== Container-aware argument decoding ==========
+
+    /// Resolve standard-library container layouts for the parameters and return
+    /// type of the functions starting at `addresses` (static low_pc). Only
+    /// functions with at least one decodable container appear in the result.
+    /// Re-reads DWARF from disk, so callers should batch addresses.
+    pub fn resolve_container_args(
+        &self,
+        addresses: &HashSet<u64>,
+    ) -> Result<HashMap<u64, FunctionContainers>> {
+        let binary_path = self
+            .binary_path
+            .as_ref()
+            .ok_or_else(|| Error::Frida("No binary path for DWARF re-parse".into()))?;
+
+        let loaded = load_dwarf_sections(binary_path)?;
+        let dwarf = loaded.borrow();
+
+        let mut found: HashMap<u64, FunctionContainers> = HashMap::new();
+        let mut seen: HashSet<u64> = HashSet::new();
+
+        let mut units = dwarf.units();
+        while let Ok(Some(header)) = units.next() {
+            if seen.len() == addresses.len() {
+                break;
+            }
+            let unit = match dwarf.unit(header) {
+                Ok(u) => u,
+                Err(_) => continue,
+            };
+
+            let mut entries = unit.entries();
+            let mut current_depth: isize = 0;
+            // (low_pc, subprogram depth, collected layouts)
+            let mut target: Option<(u64, isize, FunctionContainers)> = None;
+
+            while let Ok(Some((delta, entry))) = entries.next_dfs() {
+                current_depth += delta;
+
+                if matches!(&target, Some((_, depth, _)) if current_depth <= *depth) {
+                    let (low_pc, _, containers) = target.take().unwrap();
+                    if !containers.is_empty() {
+                        found.insert(low_pc, containers);
+                    }
+                }
+
+                match entry.tag() {
+                    gimli::DW_TAG_subprogram => {
+                        let low_pc = entry
+                            .attr_value(gimli::DW_AT_low_pc)
+                            .ok()
+                            .flatten()
+                            .and_then(|v| dwarf.attr_address(&unit, v).ok().flatten());
+                        let Some(lp) = low_pc.filter(|lp| addresses.contains(lp)) else {
+                            continue;
+                        };
+                        if !seen.insert(lp) {
+                            continue;
+                        }
+                        // Return type lives on the declaration for out-of-line definitions.
+                        let ret_attr =
+                            entry
+                                .attr_value(gimli::DW_AT_type)
+                                .ok()
+                                .flatten()
+                                .or_else(|| {
+                                    Self::resolve_reference(&unit, entry)?
+                                        .attr_value(gimli::DW_AT_type)
+                                        .ok()
+                                        .flatten()
+                                });
+                        let ret = ret_attr.and_then(|attr| {
+                            Self::container_for_type(&dwarf, &unit, attr, true, 0)
+                        });
+                        target = Some((
+                            lp,
+                            current_depth,
+                            FunctionContainers {
+                                args: Vec::new(),
+                                ret,
+                            },
+                        ));
+                    }
+                    gimli::DW_TAG_formal_parameter => {
+                        let Some((_, depth, containers)) = target.as_mut() else {
+                            continue;
+                        };
+                        if current_depth != *depth + 1 || containers.args.len() >= MAX_DECODED_ARGS
+                        {
+                            continue;
+                        }
+                        // Concrete instances of inlined/abstract functions carry the
+                        // type on the abstract origin.
+                        let type_attr =
+                            entry
+                                .attr_value(gimli::DW_AT_type)
+                                .ok()
+                                .flatten()
+                                .or_else(|| {
+                                    Self::resolve_reference(&unit, entry)?
+                                        .attr_value(gimli::DW_AT_type)
+                                        .ok()
+                                        .flatten()
+                                });
+                        containers.args.push(type_attr.and_then(|attr| {
+                            Self::container_for_type(&dwarf, &unit, attr, false, 0)
+                        }));
+                    }
+                    _ => {}
+                }
+            }
+
+            if let Some((low_pc, _, containers)) = target.take() {
+                if !containers.is_empty() {
+                    found.insert(low_pc, containers);
+                }
+            }
+        }
+
+        Ok(found)
+    }
+
+    /// Same-CU DIE offset for a type reference.
+    fn type_ref_offset<R: gimli::Reader>(
+        unit: &gimli::Unit<R>,
+        type_attr: gimli::AttributeValue<R>,
+    ) -> Option<gimli::UnitOffset<R::Offset>> {
+        match type_attr {
+            gimli::AttributeValue::UnitRef(o) => Some(o),
+            gimli::AttributeValue::DebugInfoRef(di_off) => di_off.to_unit_offset(&unit.header),
+            _ => None,
+        }
+    }
+
+    /// Follow typedef/cv qualifiers (and at most one pointer/reference) to a
+    /// class and decode it as a container. With `require_indirect`, a class
+    /// reached without a pointer/reference is rejected (by-value returns).
+    fn container_for_type<R: gimli::Reader>(
+        dwarf: &gimli::Dwarf<R>,
+        unit: &gimli::Unit<R>,
+        type_attr: gimli::AttributeValue<R>,
+        require_indirect: bool,
+        depth: usize,
+    ) -> Option<ContainerLayout> {
+        Self::container_for_type_inner(dwarf, unit, type_attr, require_indirect, false, depth)
+    }
+
+    fn container_for_type_inner<R: gimli::Reader>(
+        dwarf: &gimli::Dwarf<R>,
+        unit: &gimli::Unit<R>,
+        type_attr: gimli::AttributeValue<R>,
+        require_indirect: bool,
+        indirect: bool,
+        depth: usize,
+    ) -> Option<ContainerLayout> {
+        if depth > 10 {
+            return None;
+        }
+        let offset = Self::type_ref_offset(unit, type_attr)?;
+        let entry = unit.entry(offset).ok()?;
+
+        match entry.tag() {
+            gimli::DW_TAG_typedef
+            | gimli::DW_TAG_const_type
+            | gimli::DW_TAG_volatile_type
+            | gimli::DW_TAG_restrict_type => {
+                let next = entry.attr_value(gimli::DW_AT_type).ok()??;
+                Self::container_for_type_inner(
+                    dwarf,
+                    unit,
+                    next,
+                    require_indirect,
+                    indirect,
+                    depth + 1,
+                )
+            }
+            gimli::DW_TAG_pointer_type
+            | gimli::DW_TAG_reference_type
+            | gimli::DW_TAG_rvalue_reference_type
+                if !indirect =>
+            {
+                let next = entry.attr_value(gimli::DW_AT_type).ok()??;
+                Self::container_for_type_inner(dwarf, unit, next, require_indirect, true, depth + 1)
+            }
+            gimli::DW_TAG_structure_type | gimli::DW_TAG_class_type
+                if indirect || !require_indirect =>
+            {
+                Self::container_from_class(dwarf, unit, offset, 0)
+            }
+            _ => None,
+        }
+    }
+
+    /// Decode a class DIE as a known container, resolving its template
+    /// parameters as element types. `nesting` bounds containers-of-containers.
+    fn container_from_class<R: gimli::Reader>(
+        dwarf: &gimli::Dwarf<R>,
+        unit: &gimli::Unit<R>,
+        offset: gimli::UnitOffset<R::Offset>,
+        nesting: usize,
+    ) -> Option<ContainerLayout> {
+        const PREFIXES: &[&str] = &["basic_string<", "vector<", "map<", "Vec<"];
+        if nesting > 2 {
+            return None;
+        }
+        let entry = unit.entry(offset).ok()?;
+        let name = Self::resolve_string_attr(dwarf, unit, &entry, gimli::DW_AT_name)?;
+        if name != "String" && !PREFIXES.iter().any(|p| name.starts_with(p)) {
+            return None;
+        }
+
+        let mut fields = Vec::new();
+        Self::flatten_fields(dwarf, unit, offset, 0, "", 0, &mut fields);
+
+        let mut params = Vec::new();
+        let mut tree = unit.entries_tree(Some(offset)).ok()?;
+        let root = tree.root().ok()?;
+        let mut children = root.children();
+        while let Ok(Some(child)) = children.next() {
+            if child.entry().tag() != gimli::DW_TAG_template_type_parameter {
+                continue;
+            }
+            let element = child
+                .entry()
+                .attr_value(gimli::DW_AT_type)
+                .ok()
+                .flatten()
+                .and_then(|attr| Self::element_for_type(dwarf, unit, attr, nesting));
+            match element {
+                Some(e) => params.push(e),
+                // Keep positions aligned: an unresolvable key must not shift the value.
+                None => break,
+            }
+        }
+
+        classify(&name, &fields, &params)
+    }
+
+    /// Flatten data members (recursing into nested classes and base classes)
+    /// into dotted paths with absolute offsets.
+    fn flatten_fields<R: gimli::Reader>(
+        dwarf: &gimli::Dwarf<R>,
+        unit: &gimli::Unit<R>,
+        offset: gimli::UnitOffset<R::Offset>,
+        base: u64,
+        prefix: &str,
+        depth: usize,
+        out: &mut Vec<FlatField>,
+    ) {
+        if depth > 8 {
+            return;
+        }
+        let Ok(mut tree) = unit.entries_tree(Some(offset)) else {
+            return;
+        };
+        let Ok(root) = tree.root() else {
+            return;
+        };
+        let mut children = root.children();
+        while let Ok(Some(child)) = children.next() {
+            let child_entry = child.entry();
+            let is_member = match child_entry.tag() {
+                gimli::DW_TAG_member => true,
+                gimli::DW_TAG_inheritance => false,
+                _ => continue,
+            };
+            // Static members have no data_member_location and no storage in the object.
+            if is_member
+                && child_entry
+                    .attr_value(gimli::DW_AT_external)
+                    .ok()
+                    .flatten()
+                    .is_some()
+            {
+                continue;
+            }
+            let member_offset = base + Self::parse_member_offset(child_entry);
+
+            let path = if is_member {
+                let Some(name) =
+                    Self::resolve_string_attr(dwarf, unit, child_entry, gimli::DW_AT_name)
+                else {
+                    continue;
+                };
+                let path = if prefix.is_empty() {
+                    name
+                } else {
+                    format!("{}.{}", prefix, name)
+                };
+                out.push(FlatField {
+                    path: path.clone(),
+                    offset: member_offset,
+                });
+                path
+            } else {
+                prefix.to_string()
+            };
+
+            let Some(nested) = child_entry
+                .attr_value(gimli::DW_AT_type)
+                .ok()
+                .flatten()
+                .and_then(|attr| Self::strip_to_aggregate(unit, attr, 0))
+            else {
+                continue;
+            };
+            Self::flatten_fields(dwarf, unit, nested, member_offset, &path, depth + 1, out);
+        }
+    }
+
+    /// Follow typedef/cv qualifiers to a struct/class/union DIE.
+    fn strip_to_aggregate<R: gimli::Reader>(
+        unit: &gimli::Unit<R>,
+        type_attr: gimli::AttributeValue<R>,
+        depth: usize,
+    ) -> Option<gimli::UnitOffset<R::Offset>> {
+        if depth > 10 {
+            return None;
+        }
+        let offset = Self::type_ref_offset(unit, type_attr)?;
+        let entry = unit.entry(offset).ok()?;
+        match entry.tag() {
+            gimli::DW_TAG_structure_type | gimli::DW_TAG_class_type | gimli::DW_TAG_union_type => {
+                Some(offset)
+            }
+            gimli::DW_TAG_typedef
+            | gimli::DW_TAG_const_type
+            | gimli::DW_TAG_volatile_type
+            | gimli::DW_TAG_restrict_type => {
+                let next = entry.attr_value(gimli::DW_AT_type).ok()??;
+                Self::strip_to_aggregate(unit, next, depth + 1)
+            }
+            _ => None,
+        }
+    }
+
+    /// Describe a container element/key/value type in the agent's TypeInfo shape.
+    fn element_for_type<R: gimli::Reader>(
+        dwarf: &gimli::Dwarf<R>,
+        unit: &gimli::Unit<R>,
+        type_attr: gimli::AttributeValue<R>,
+        nesting: usize,
+    ) -> Option<ContainerElement> {
+        fn agent_kind(tk: &TypeKind) -> &'static str {
+            match tk {
+                TypeKind::Integer { signed: true } => "int",
+                TypeKind::Integer { signed: false } | TypeKind::Unknown => "uint",
+                TypeKind::Float => "float",
+                TypeKind::Pointer => "pointer",
+            }
+        }
+
+        if let Some(class) = Self::strip_to_aggregate(unit, type_attr.clone(), 0) {
+            let entry = unit.entry(class).ok()?;
+            let type_name = Self::resolve_string_attr(dwarf, unit, &entry, gimli::DW_AT_name)
+                .unwrap_or_else(|| "struct".to_string());
+            let byte_size = entry
+                .attr_value(gimli::DW_AT_byte_size)
+                .ok()
+                .flatten()
+                .and_then(|v| v.udata_value())
+                .unwrap_or(0);
+
+            if let Some(layout) = Self::container_from_class(dwarf, unit, class, nesting + 1) {
+                return Some(ContainerElement {
+                    type_kind: "struct".to_string(),
+                    byte_size,
+                    type_name,
+                    members: Vec::new(),
+                    container: Some(Box::new(layout)),
+                });
+            }
+            // Newtypes collapse to their inner scalar, same as watch resolution.
+            if let Some((size, kind, name)) =
+                Self::follow_type_chain(dwarf, unit, type_attr.clone(), 0)
+            {
+                return Some(ContainerElement {
+                    type_kind: agent_kind(&kind).to_string(),
+                    byte_size: size as u64,
+                    type_name: name.unwrap_or(type_name),
+                    members: Vec::new(),
+                    container: None,
+                });
+            }
+            let members = Self::parse_struct_members_from_type(dwarf, unit, type_attr, 0)
+                .unwrap_or_default()
+                .into_iter()
+                .map(|m| ElementMember {
+                    name: m.name,
+                    offset: m.offset,
+                    byte_size: m.byte_size as u64,
+                    type_kind: agent_kind(&m.type_kind).to_string(),
+                    type_name: m.type_name,
+                })
+                .collect();
+            return Some(ContainerElement {
+                type_kind: "struct".to_string(),
+                byte_size,
+                type_name,
+                members,
+                container: None,
+            });
+        }
+
+        let (size, kind, name) = Self::follow_type_chain(dwarf, unit, type_attr, 0)?;
+        Some(ContainerElement {
+            type_kind: agent_kind(&kind).to_string(),
+            byte_size: size as u64,
+            type_name: name.unwrap_or_else(|| agent_kind(&kind).to_string()),
+            members: Vec::new(),
+            container: None,
+        })
+    }
+
+    // ========== Phase 2: Line table support ==========
+
+    /// Resolve file:line to instruction address. Snaps to nearest is_statement line.
+    /// Returns (address, actual_line) or None if no code at that location.
+    pub fn resolve_line(&self, file: &str, line: u32) -> Option<(u64, u32)> {
+        self.ensure_line_table();
+        let table = self.line_table.lock().unwrap();
+        let entries = table.as_ref()?;
+
+        // Find entries matching file.
+        // Use path-component-aware matching: require separator before the match
+        // to avoid "main.c" matching "not_main.c". Also handle OS separator
+        // differences in DWARF paths.
+        let sep_file = format!("/{}", file);
+        let sep_file_win = format!("\\{}", file);
+        let mut matches: Vec<_> = entries
+            .iter()
+            .filter(|e| {
+                e.is_statement
+                    && (e.file == file
+                        || e.file.ends_with(&sep_file)
+                        || e.file.ends_with(&sep_file_win))
+            })
+            .collect();
+
+        if matches.is_empty() {
+            return None;
+        }
+
+        // Find closest line >= requested line
+        matches.sort_by_key(|e| e.line);
+        matches
+            .iter()
+            .find(|e| e.line >= line)
+            .map(|e| (e.address, e.line))
+    }
+
+    /// Find nearest valid line numbers for error messages
+    pub fn find_nearest_lines(&self, file: &str, target_line: u32, count: usize) -> String {
+        self.ensure_line_table();
+        let table = self.line_table.lock().unwrap();
+        let entries = match table.as_ref() {
+            Some(e) => e,
+            None => return "unknown".to_string(),
+        };
+
+        // Get all unique statement lines for this file
+        let sep_file = format!("/{}", file);
+        let sep_file_win = format!("\\{}", file);
+        let mut lines: Vec<u32> = entries
+            .iter()
+            .filter(|e| {
+                e.is_statement
+                    && (e.file == file
+                        || e.file.ends_with(&sep_file)
+                        || e.file.ends_with(&sep_file_win))
+            })
+            .map(|e| e.line)
+            .collect();
+
+        lines.sort_unstable();
+        lines.dedup();
+
+        if lines.is_empty() {
+            return "none".to_string();
+        }
+
+        // Find nearest lines (before and after target)
         let mut nearest: Vec<(u32, u32)> = lines
             .iter()
             .map(|&l| ((l as i64 - target_line as i64).unsigned_abs() as u32, l))
@@ -1698,6 +3163,22 @@ impl DwarfParser {
         Some((entry.file.clone(), entry.line, entry.column))
     }
 
+    /// addr2line: the function and line-table row containing a static address.
+    pub fn addr2line(&self, address: u64) -> Option<SourceLocation> {
+        let (low, high) = self.function_containing(address)?;
+        let (file, line, column) = self.resolve_address(address)?;
+        let function = self
+            .functions
+            .iter()
+            .find(|f| f.low_pc == low && f.high_pc == high)?;
+        Some(SourceLocation {
+            function: function.name.clone(),
+            file,
+            line,
+            column,
+        })
+    }
+
     /// Find next statement line in the same function. Used for step-over.
     /// Respects function boundaries using the DWARF function table (high_pc).
     /// Find the next source line address after `address` within the same function.
@@ -1753,6 +3234,33 @@ impl DwarfParser {
     pub fn callee_entry_addresses(&self, _address: u64) -> Vec<u64> {
         Vec::new()
     }
+
+    /// Statement lines of `[low_pc, high_pc)` as (start, end, file, line).
+    /// Each line's code runs to the next line table entry, clamped to high_pc.
+    pub fn line_spans(&self, low_pc: u64, high_pc: u64) -> Vec<(u64, u64, String, u32)> {
+        self.ensure_line_table();
+        let table = self.line_table.lock().unwrap();
+        let Some(entries) = table.as_ref() else {
+            return Vec::new();
+        };
+
+        let first = entries.partition_point(|e| e.address < low_pc);
+        let mut spans = Vec::new();
+        for (i, entry) in entries.iter().enumerate().skip(first) {
+            if entry.address >= high_pc {
+                break;
+            }
+            if !entry.is_statement {
+                continue;
+            }
+            let end = entries
+                .get(i + 1)
+                .map_or(high_pc, |next| next.address.min(high_pc))
+                .max(entry.address + 1);
+            spans.push((entry.address, end, entry.file.clone(), entry.line));
+        }
+        spans
+    }
     /// Parse line table on first access (lazy initialization)
     fn ensure_line_table(&self) {
         let mut guard = self.line_table.lock().unwrap();
@@ -1843,27 +3351,39 @@ impl DwarfParser {
 
 /// Glob-style pattern matcher for function names
 pub struct PatternMatcher<'a> {
-    pattern: &'a str,
+    pattern: Cow<'a, str>,
     separator: &'static str,
 }
 
 impl<'a> PatternMatcher<'a> {
     pub fn new(pattern: &'a str) -> Self {
         Self {
-            pattern,
+            pattern: Cow::Borrowed(pattern),
             separator: "::",
         }
     }
 
+    /// With `.`, patterns written with `::` are read as dotted
+    /// (`mypkg::handlers::*` matches `mypkg.handlers.run`).
     pub fn new_with_separator(pattern: &'a str, sep: char) -> Self {
         let separator: &'static str = match sep {
             '.' => ".",
             ':' => "::",
             _ => "::",
         };
+        let pattern = if separator == "." && pattern.contains("::") {
+            Cow::Owned(pattern.replace("::", "."))
+        } else {
+            Cow::Borrowed(pattern)
+        };
         Self { pattern, separator }
     }
 
+    /// Matcher using the scope separator of `language`'s function names.
+    pub fn for_language(pattern: &'a str, language: crate::symbols::Language) -> Self {
+        Self::new_with_separator(pattern, language.scope_separator())
+    }
+
     pub fn matches(&self, name: &str) -> bool {
         // Strip C++ parameter signature before matching (only for :: separator).
         // e.g. "timing::fast()" → "timing::fast"
@@ -1877,7 +3397,7 @@ impl<'a> PatternMatcher<'a> {
         } else {
             name
         };
-        self.glob_match(self.pattern, name)
+        self.glob_match(&self.pattern, name)
     }
 
     fn glob_match(&self, pattern: &str, text: &str) -> bool {
@@ -1963,6 +3483,21 @@ impl<'a> PatternMatcher<'a> {
 mod pattern_tests {
     use super::*;
 
+    #[test]
+    fn test_go_producer_and_patterns() {
+        assert_eq!(
+            go_minor_version("Go cmd/compile go1.21.5; regabi"),
+            Some(21)
+        );
+        assert_eq!(go_minor_version("GNU C17 13.2.0"), None);
+
+        let pattern = crate::symbols::go_pattern("main.(*Server).handle**").unwrap();
+        let m = PatternMatcher::new(&pattern);
+        let name = crate::symbols::demangle_go("main.(*Server).handleConn");
+        assert!(m.matches(&name), "{} should match {}", pattern, name);
+        assert!(!m.matches(&crate::symbols::demangle_go("main.(*Client).handleConn")));
+    }
+
     #[test]
     fn test_exact_match() {
         let m = PatternMatcher::new("foo::bar");
@@ -1970,6 +3505,20 @@ mod pattern_tests {
         assert!(!m.matches("foo::baz"));
     }
 
+    #[test]
+    fn test_dot_separator_reads_colon_patterns() {
+        let m = PatternMatcher::new_with_separator("mypkg::handlers::*", '.');
+        assert!(m.matches("mypkg.handlers.on_request"));
+        assert!(!m.matches("mypkg.handlers.sub.on_request"));
+
+        let m = PatternMatcher::for_language("mypkg.handlers.*", crate::symbols::Language::Python);
+        assert!(m.matches("mypkg.handlers.on_request"));
+        // Native keeps `::` scopes, so dots are literal
+        let m = PatternMatcher::for_language("mypkg.*", crate::symbols::Language::Native);
+        assert!(m.matches("mypkg.handlers"));
+        assert!(!m.matches("mypkg::handlers"));
+    }
+
     #[test]
     fn test_single_star() {
         let m = PatternMatcher::new("foo::*");
@@ -2190,3 +3739,210 @@ mod struct_expansion_tests {
         assert!(recipes.is_empty());
     }
 }
+
+#[cfg(test)]
+mod global_region_tests {
+    use super::*;
+    use std::process::Command;
+
+    const SOURCE: &str = r#"
+struct ring { unsigned head, tail; char data[1000]; };
+typedef struct ring ring_t;
+struct ring gRingBuffer;
+const ring_t gSpare = { 1, 2, { 0 } };
+double gHistory[4][8];
+int gEngineState = 3;
+int main(void) { return gEngineState + gRingBuffer.head + (int)gHistory[0][0] + gSpare.tail; }
+"#;
+
+    #[test]
+    fn test_resolve_global_regions_sizes_aggregates() {
+        if Command::new("cc").arg("--version").output().is_err() {
+            return;
+        }
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("globals.c");
+        let binary = dir.path().join("globals");
+        std::fs::write(&source, SOURCE).unwrap();
+        let status = Command::new("cc")
+            .args(["-g", "-O0", "-o"])
+            .arg(&binary)
+            .arg(&source)
+            .status()
+            .unwrap();
+        assert!(status.success());
+        if cfg!(target_os = "macos") {
+            let _ = Command::new("dsymutil").arg(&binary).status();
+        }
+
+        let parser = DwarfParser::parse(&binary).unwrap();
+        // Scalars are the only globals the watch index keeps
+        assert!(parser.find_variable_by_name("gRingBuffer").is_none());
+
+        let names: Vec<String> = [
+            "gHistory",
+            "gMissing",
+            "gRingBuffer",
+            "gSpare",
+            "gEngineState",
+        ]
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+        let regions = parser.resolve_global_regions(&names).unwrap();
+        let sizes: Vec<(&str, u64)> = regions
+            .iter()
+            .map(|r| (r.name.as_str(), r.byte_size))
+            .collect();
+        assert_eq!(
+            sizes,
+            vec![
+                ("gHistory", 256),
+                ("gRingBuffer", 1008),
+                ("gSpare", 1008),
+                ("gEngineState", 4)
+            ]
+        );
+        assert_eq!(regions[1].type_name.as_deref(), Some("ring"));
+        assert!(regions.iter().all(|r| r.address != 0));
+    }
+
+    const LAYOUT_SOURCE: &str = r#"
+enum mode { MODE_IDLE, MODE_RUNNING = 5 };
+struct voice { int note; float gain; };
+typedef struct {
+    enum mode mode;
+    const char *name;
+    char tag[8];
+    struct voice voices[100];
+    union { int i; float f; } u;
+    unsigned armed : 1, level : 3;
+    struct voice *current;
+} engine_t;
+engine_t gEngine;
+engine_t *gEnginePtr = &gEngine;
+int gPlain = 1;
+int main(void) { return gEngine.voices[0].note + gPlain + (gEnginePtr != 0); }
+"#;
+
+    fn compile(source: &str) -> Option<(tempfile::TempDir, std::path::PathBuf)> {
+        if Command::new("cc").arg("--version").output().is_err() {
+            return None;
+        }
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("layout.c");
+        let binary = dir.path().join("layout");
+        std::fs::write(&path, source).unwrap();
+        let status = Command::new("cc")
+            .args(["-g", "-O0", "-o"])
+            .arg(&binary)
+            .arg(&path)
+            .status()
+            .unwrap();
+        assert!(status.success());
+        if cfg!(target_os = "macos") {
+            let _ = Command::new("dsymutil").arg(&binary).status();
+        }
+        Some((dir, binary))
+    }
+
+    fn field<'a>(fields: &'a [LayoutField], name: &str) -> &'a LayoutField {
+        fields.iter().find(|f| f.name == name).unwrap()
+    }
+
+    #[test]
+    fn test_resolve_read_layout_type_tree() {
+        let Some((_dir, binary)) = compile(LAYOUT_SOURCE) else {
+            return;
+        };
+        let parser = DwarfParser::parse(&binary).unwrap();
+
+        // Scalars keep the fast path
+        assert!(parser.resolve_read_layout("gPlain", 2).unwrap().is_none());
+
+        let (address, layout) = parser.resolve_read_layout("gEngine", 2).unwrap().unwrap();
+        assert_ne!(address, 0);
+        let TypeLayout::Struct {
+            type_name, fields, ..
+        } = &layout
+        else {
+            panic!("expected struct, got {:?}", layout);
+        };
+        assert_eq!(type_name.as_deref(), Some("engine_t"));
+
+        let TypeLayout::Enum { enumerators, .. } = &field(fields, "mode").layout else {
+            panic!("mode should be an enum");
+        };
+        assert_eq!(
+            enumerators[1],
+            Enumerator {
+                name: "MODE_RUNNING".to_string(),
+                value: 5
+            }
+        );
+        assert_eq!(field(fields, "name").layout, TypeLayout::CString);
+        assert_eq!(
+            field(fields, "tag").layout,
+            TypeLayout::CharArray { len: 8 }
+        );
+
+        let TypeLayout::Array {
+            count,
+            shown,
+            stride,
+            element,
+        } = &field(fields, "voices").layout
+        else {
+            panic!("voices should be an array");
+        };
+        assert_eq!((*count, *shown, *stride), (100, MAX_ARRAY_ELEMENTS, 8));
+        let TypeLayout::Struct { fields: voice, .. } = element.as_ref() else {
+            panic!("voices should hold structs at depth 2");
+        };
+        assert_eq!(field(voice, "gain").offset, 4);
+        assert_eq!(field(voice, "gain").layout, TypeLayout::Float { size: 4 });
+
+        assert!(matches!(
+            field(fields, "u").layout,
+            TypeLayout::Union { .. }
+        ));
+        let level = field(fields, "level");
+        assert_eq!((level.bit_offset, level.bit_size), (Some(1), Some(3)));
+        assert!(matches!(
+            &field(fields, "current").layout,
+            TypeLayout::Pointer {
+                pointee: Some(_),
+                ..
+            }
+        ));
+
+        // Depth 1: nested structs are named but not expanded
+        let (_, shallow) = parser.resolve_read_layout("gEngine", 1).unwrap().unwrap();
+        let TypeLayout::Struct { fields, .. } = &shallow else {
+            panic!("expected struct");
+        };
+        let TypeLayout::Array { element, .. } = &field(fields, "voices").layout else {
+            panic!("voices should be an array");
+        };
+        assert_eq!(
+            element.as_ref(),
+            &TypeLayout::Opaque {
+                type_name: Some("voice".to_string()),
+                size: 8
+            }
+        );
+
+        // A struct pointer renders what it points at
+        let (_, pointer) = parser
+            .resolve_read_layout("gEnginePtr", 1)
+            .unwrap()
+            .unwrap();
+        assert!(matches!(
+            pointer,
+            TypeLayout::Pointer {
+                pointee: Some(_),
+                ..
+            }
+        ));
+    }
+}
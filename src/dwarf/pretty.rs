@@ -0,0 +1,270 @@
+//! Type layouts for pretty-printing memory reads.
+//!
+//! `debug_memory` reads of a global ship the variable's DWARF type tree to the
+//! agent, which walks it over live memory: structs and unions become objects,
+//! arrays become lists, enums show their enumerator, `char*` and `char[N]` are
+//! read as strings. Struct nesting stops at the requested depth; arrays and
+//! the tree as a whole are capped so a huge global can't flood the response.
+
+use serde::Serialize;
+
+/// Elements shown per array; the rest are only counted.
+pub const MAX_ARRAY_ELEMENTS: u64 = 64;
+/// Bytes read for a `char*` or `char[N]` string.
+pub const MAX_STRING_BYTES: u64 = 256;
+/// Layout nodes per variable; past it, members are left opaque.
+pub const MAX_LAYOUT_NODES: usize = 2000;
+
+/// How to render the object at some address. Offsets are relative to the
+/// enclosing struct/union; array elements sit `stride` bytes apart.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum TypeLayout {
+    #[serde(rename_all = "camelCase")]
+    Int {
+        size: u64,
+        signed: bool,
+    },
+    Float {
+        size: u64,
+    },
+    Bool {
+        size: u64,
+    },
+    /// A single character: printable ones as the character, others as the code.
+    Char {
+        size: u64,
+    },
+    #[serde(rename_all = "camelCase")]
+    Enum {
+        size: u64,
+        signed: bool,
+        type_name: Option<String>,
+        enumerators: Vec<Enumerator>,
+    },
+    /// `char*` / `const char*`: the NUL-terminated string it points at.
+    CString,
+    /// `char[N]`: the bytes up to the first NUL.
+    CharArray {
+        len: u64,
+    },
+    /// Any other pointer. `pointee` is set when it points at a struct or union
+    /// within the depth limit, and the target is rendered under it.
+    #[serde(rename_all = "camelCase")]
+    Pointer {
+        type_name: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pointee: Option<Box<TypeLayout>>,
+    },
+    #[serde(rename_all = "camelCase")]
+    Struct {
+        type_name: Option<String>,
+        size: u64,
+        fields: Vec<LayoutField>,
+    },
+    /// Every member is read from offset 0.
+    #[serde(rename_all = "camelCase")]
+    Union {
+        type_name: Option<String>,
+        size: u64,
+        fields: Vec<LayoutField>,
+    },
+    /// `shown` elements of `count`; multi-dimensional arrays nest.
+    Array {
+        count: u64,
+        shown: u64,
+        stride: u64,
+        element: Box<TypeLayout>,
+    },
+    /// Beyond the depth limit or not decodable: shown by type name only.
+    #[serde(rename_all = "camelCase")]
+    Opaque {
+        type_name: Option<String>,
+        size: u64,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LayoutField {
+    pub name: String,
+    pub offset: u64,
+    /// Bitfield position: `bit_size` bits starting `bit_offset` bits
+    /// (little-endian order) into the bytes at `offset`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bit_offset: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bit_size: Option<u64>,
+    pub layout: TypeLayout,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Enumerator {
+    pub name: String,
+    pub value: i64,
+}
+
+impl TypeLayout {
+    /// Leaves the agent can already read without the type tree.
+    pub fn is_scalar(&self) -> bool {
+        matches!(
+            self,
+            TypeLayout::Int { .. } | TypeLayout::Float { .. } | TypeLayout::Bool { .. }
+        ) || matches!(self, TypeLayout::Pointer { pointee: None, .. })
+    }
+
+    /// Byte offset and dotted path of every member, nested structs and unions
+    /// flattened; arrays are listed once as `name[count]`. Used to annotate
+    /// hexdumps of the variable.
+    pub fn field_offsets(&self) -> Vec<(u64, String)> {
+        let mut out = Vec::new();
+        self.collect_field_offsets(0, "", &mut out);
+        out
+    }
+
+    fn collect_field_offsets(&self, base: u64, prefix: &str, out: &mut Vec<(u64, String)>) {
+        let (TypeLayout::Struct { fields, .. } | TypeLayout::Union { fields, .. }) = self else {
+            return;
+        };
+        for field in fields {
+            let offset = base + field.offset;
+            let path = if prefix.is_empty() {
+                field.name.clone()
+            } else {
+                format!("{}.{}", prefix, field.name)
+            };
+            match &field.layout {
+                TypeLayout::Struct { .. } | TypeLayout::Union { .. } => {
+                    field.layout.collect_field_offsets(offset, &path, out)
+                }
+                TypeLayout::Array { count, .. } => {
+                    out.push((offset, format!("{}[{}]", path, count)))
+                }
+                _ => out.push((offset, path)),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_layout_json_shape() {
+        let layout = TypeLayout::Struct {
+            type_name: Some("engine".to_string()),
+            size: 16,
+            fields: vec![
+                LayoutField {
+                    name: "state".to_string(),
+                    offset: 0,
+                    bit_offset: None,
+                    bit_size: None,
+                    layout: TypeLayout::Enum {
+                        size: 4,
+                        signed: false,
+                        type_name: Some("state_t".to_string()),
+                        enumerators: vec![Enumerator {
+                            name: "RUNNING".to_string(),
+                            value: 1,
+                        }],
+                    },
+                },
+                LayoutField {
+                    name: "name".to_string(),
+                    offset: 8,
+                    bit_offset: None,
+                    bit_size: None,
+                    layout: TypeLayout::CString,
+                },
+            ],
+        };
+        let json = serde_json::to_value(&layout).unwrap();
+        assert_eq!(json["kind"], "struct");
+        assert_eq!(json["typeName"], "engine");
+        assert_eq!(json["fields"][0]["layout"]["kind"], "enum");
+        assert_eq!(
+            json["fields"][0]["layout"]["enumerators"][0]["name"],
+            "RUNNING"
+        );
+        assert!(json["fields"][0].get("bitSize").is_none());
+        assert_eq!(json["fields"][1]["layout"]["kind"], "cString");
+    }
+
+    #[test]
+    fn test_field_offsets_flatten_nested_members() {
+        let field = |name: &str, offset, layout| LayoutField {
+            name: name.to_string(),
+            offset,
+            bit_offset: None,
+            bit_size: None,
+            layout,
+        };
+        let int = TypeLayout::Int {
+            size: 4,
+            signed: true,
+        };
+        let layout = TypeLayout::Struct {
+            type_name: Some("packet".to_string()),
+            size: 40,
+            fields: vec![
+                field("len", 0, int.clone()),
+                field(
+                    "hdr",
+                    8,
+                    TypeLayout::Struct {
+                        type_name: None,
+                        size: 8,
+                        fields: vec![
+                            field("kind", 0, int.clone()),
+                            field("flags", 4, int.clone()),
+                        ],
+                    },
+                ),
+                field(
+                    "payload",
+                    16,
+                    TypeLayout::Array {
+                        count: 24,
+                        shown: 24,
+                        stride: 1,
+                        element: Box::new(TypeLayout::Char { size: 1 }),
+                    },
+                ),
+            ],
+        };
+        assert_eq!(
+            layout.field_offsets(),
+            vec![
+                (0, "len".to_string()),
+                (8, "hdr.kind".to_string()),
+                (12, "hdr.flags".to_string()),
+                (16, "payload[24]".to_string()),
+            ]
+        );
+        assert!(int.field_offsets().is_empty());
+    }
+
+    #[test]
+    fn test_scalar_layouts() {
+        assert!(TypeLayout::Int {
+            size: 4,
+            signed: true
+        }
+        .is_scalar());
+        assert!(TypeLayout::Pointer {
+            type_name: None,
+            pointee: None
+        }
+        .is_scalar());
+        assert!(!TypeLayout::CString.is_scalar());
+        assert!(!TypeLayout::Enum {
+            size: 4,
+            signed: false,
+            type_name: None,
+            enumerators: vec![]
+        }
+        .is_scalar());
+    }
+}
@@ -0,0 +1,187 @@
+//! Name and source-file search over the parsed symbols, for `debug_symbols`.
+
+use super::{DwarfParser, FunctionInfo, VariableInfo};
+use std::collections::HashSet;
+
+/// Name filter: everything, a case-insensitive substring, or a regular
+/// expression.
+pub enum SymbolQuery {
+    Any,
+    Substring(String),
+    Regex(regex::Regex),
+}
+
+impl SymbolQuery {
+    pub fn new(query: Option<&str>, regex: bool) -> crate::Result<Self> {
+        match query {
+            None | Some("") => Ok(Self::Any),
+            Some(q) if regex => regex::Regex::new(q).map(Self::Regex).map_err(|e| {
+                crate::Error::ValidationError(format!("Invalid regex '{}': {}", q, e))
+            }),
+            Some(q) => Ok(Self::Substring(q.to_lowercase())),
+        }
+    }
+
+    pub fn matches(&self, name: &str) -> bool {
+        match self {
+            Self::Any => true,
+            Self::Substring(s) => name.to_lowercase().contains(s.as_str()),
+            Self::Regex(re) => re.is_match(name),
+        }
+    }
+}
+
+impl DwarfParser {
+    /// Functions whose demangled or linkage name matches `query`, from source
+    /// files whose path contains `file`, sorted by name.
+    pub fn search_functions(&self, query: &SymbolQuery, file: Option<&str>) -> Vec<&FunctionInfo> {
+        let mut found: Vec<&FunctionInfo> = self
+            .functions
+            .iter()
+            .filter(|f| {
+                file.is_none_or(|file| f.source_file.as_ref().is_some_and(|s| s.contains(file)))
+            })
+            .filter(|f| {
+                query.matches(&f.name) || f.name_raw.as_deref().is_some_and(|r| query.matches(r))
+            })
+            .collect();
+        found.sort_by(|a, b| a.name.cmp(&b.name).then(a.low_pc.cmp(&b.low_pc)));
+        found
+    }
+
+    /// Globals whose qualified or short name matches `query`, sorted by name.
+    pub fn search_globals(&self, query: &SymbolQuery) -> Vec<&VariableInfo> {
+        let mut found: Vec<&VariableInfo> = self
+            .variables
+            .iter()
+            .filter(|v| {
+                query.matches(&v.name) || v.short_name.as_deref().is_some_and(|s| query.matches(s))
+            })
+            .collect();
+        found.sort_by(|a, b| a.name.cmp(&b.name));
+        found
+    }
+
+    /// Distinct source files that define functions.
+    pub fn source_file_count(&self) -> usize {
+        self.functions
+            .iter()
+            .filter_map(|f| f.source_file.as_deref())
+            .collect::<HashSet<_>>()
+            .len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dwarf::TypeKind;
+    use std::collections::HashMap;
+    use std::sync::Mutex;
+
+    fn function(name: &str, raw: Option<&str>, low_pc: u64, file: &str) -> FunctionInfo {
+        FunctionInfo {
+            name: name.to_string(),
+            name_raw: raw.map(str::to_string),
+            low_pc,
+            high_pc: low_pc + 0x40,
+            source_file: Some(file.to_string()),
+            line_number: Some(1),
+        }
+    }
+
+    fn global(name: &str, short: &str) -> VariableInfo {
+        VariableInfo {
+            name: name.to_string(),
+            name_raw: None,
+            short_name: Some(short.to_string()),
+            address: 0x4000,
+            byte_size: 4,
+            type_name: Some("int".to_string()),
+            type_kind: TypeKind::Integer { signed: true },
+            source_file: None,
+        }
+    }
+
+    fn parser() -> DwarfParser {
+        DwarfParser {
+            functions: vec![
+                function(
+                    "audio::Mixer::render",
+                    Some("_ZN5audio5Mixer6renderEv"),
+                    0x1100,
+                    "/proj/src/mixer.cpp",
+                ),
+                function("audio::fft", None, 0x1000, "/proj/src/dsp.cpp"),
+                function("main", None, 0x2000, "/proj/src/main.cpp"),
+            ],
+            functions_by_name: HashMap::new(),
+            functions_by_addr: vec![],
+            variables: vec![
+                global("audio::gVolume", "gVolume"),
+                global("gFrames", "gFrames"),
+            ],
+            variables_by_name: HashMap::new(),
+            struct_members: Mutex::new(HashMap::new()),
+            lazy_struct_info: HashMap::new(),
+            type_layouts: Mutex::new(HashMap::new()),
+            function_params: Mutex::new(HashMap::new()),
+            image_base: 0,
+            binary_path: None,
+            line_table: Mutex::new(None),
+            go_functions: Default::default(),
+            goid_offset: None,
+            swift: false,
+        }
+    }
+
+    fn names<'a>(functions: &[&'a FunctionInfo]) -> Vec<&'a str> {
+        functions.iter().map(|f| f.name.as_str()).collect()
+    }
+
+    #[test]
+    fn test_search_functions() {
+        let dwarf = parser();
+        let query = SymbolQuery::new(Some("AUDIO"), false).unwrap();
+        assert_eq!(
+            names(&dwarf.search_functions(&query, None)),
+            vec!["audio::Mixer::render", "audio::fft"]
+        );
+        // Linkage names match too
+        let query = SymbolQuery::new(Some("6render"), false).unwrap();
+        assert_eq!(
+            names(&dwarf.search_functions(&query, None)),
+            vec!["audio::Mixer::render"]
+        );
+
+        let query = SymbolQuery::new(Some(r"^audio::\w+$"), true).unwrap();
+        assert_eq!(
+            names(&dwarf.search_functions(&query, None)),
+            vec!["audio::fft"]
+        );
+
+        let any = SymbolQuery::new(None, false).unwrap();
+        assert_eq!(
+            names(&dwarf.search_functions(&any, Some("dsp.cpp"))),
+            vec!["audio::fft"]
+        );
+        assert_eq!(dwarf.source_file_count(), 3);
+
+        assert!(SymbolQuery::new(Some("audio::("), true).is_err());
+    }
+
+    #[test]
+    fn test_search_globals() {
+        let dwarf = parser();
+        let query = SymbolQuery::new(Some("gvolume"), false).unwrap();
+        let found: Vec<&str> = dwarf
+            .search_globals(&query)
+            .iter()
+            .map(|v| v.name.as_str())
+            .collect();
+        assert_eq!(found, vec!["audio::gVolume"]);
+
+        let query = SymbolQuery::new(Some("^gFrames$"), true).unwrap();
+        assert_eq!(dwarf.search_globals(&query).len(), 1);
+    }
+}
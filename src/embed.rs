@@ -0,0 +1,192 @@
+//! Embedding strobe's collection engine in another Rust process.
+//!
+//! [`Strobe`] runs the same tool implementations the daemon serves over MCP,
+//! minus the Unix socket, PID file and idle shutdown. A CI runner or test
+//! harness can launch a target, hook functions, query the recorded timeline
+//! and stop the session with plain async calls:
+//!
+//! ```no_run
+//! # async fn demo() -> strobe::Result<()> {
+//! use strobe::embed::Strobe;
+//! use strobe::mcp::{DebugLaunchRequest, DebugQueryRequest, DebugTraceRequest};
+//!
+//! let strobe = Strobe::open("/tmp/strobe-ci".as_ref())?;
+//! let launch = strobe
+//!     .launch(DebugLaunchRequest {
+//!         command: "./target/debug/server".into(),
+//!         project_root: ".".into(),
+//!         ..Default::default()
+//!     })
+//!     .await?;
+//! strobe
+//!     .trace(DebugTraceRequest {
+//!         session_id: Some(launch.session_id.clone()),
+//!         add: Some(vec!["server::handle_*".into()]),
+//!         ..Default::default()
+//!     })
+//!     .await?;
+//! let events = strobe
+//!     .query(DebugQueryRequest {
+//!         session_id: launch.session_id.clone(),
+//!         ..Default::default()
+//!     })
+//!     .await?;
+//! println!("{} events", events.total_count);
+//! strobe.stop(&launch.session_id, false).await?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! # Stability
+//!
+//! The request/response types are the MCP tool types and change with them;
+//! no compatibility guarantee is made across releases yet. Build requests
+//! with `..Default::default()` so added optional fields don't break callers.
+//! [`Strobe::call_tool`] reaches every other tool by its MCP name and JSON
+//! arguments.
+//!
+//! Open the engine on its own data directory. Opening the event store marks
+//! every session still recorded as running as stale, which would cut off a
+//! daemon sharing `~/.strobe`.
+
+use std::path::Path;
+
+use crate::daemon::Daemon;
+use crate::mcp::{
+    DebugLaunchRequest, DebugLaunchResponse, DebugQueryGroupsResponse, DebugQueryRequest,
+    DebugQueryResponse, DebugStopResponse, DebugTraceRequest, DebugTraceResponse,
+};
+use crate::Result;
+
+/// Connection id every embedded call runs under. Pending trace patterns
+/// (debug_trace without a session) are kept per connection.
+const EMBEDDED_CONNECTION_ID: &str = "embedded";
+
+/// An in-process strobe engine. Cheap to share behind an `Arc`; all methods
+/// take `&self`.
+pub struct Strobe {
+    daemon: Daemon,
+}
+
+impl Strobe {
+    /// Open (or create) the event store at `data_dir/strobe.db`.
+    pub fn open(data_dir: &Path) -> Result<Self> {
+        std::fs::create_dir_all(data_dir)?;
+        Ok(Self {
+            daemon: Daemon::embedded(&data_dir.join("strobe.db"))?,
+        })
+    }
+
+    /// Spawn a process under Frida and start capturing its output.
+    pub async fn launch(&self, request: DebugLaunchRequest) -> Result<DebugLaunchResponse> {
+        self.call("debug_launch", &request).await
+    }
+
+    /// Add or remove trace patterns and watches. Without a session id the
+    /// patterns are staged for the next `launch`.
+    pub async fn trace(&self, request: DebugTraceRequest) -> Result<DebugTraceResponse> {
+        self.call("debug_trace", &request).await
+    }
+
+    /// Read recorded events. Events are returned in the same JSON shape as
+    /// the MCP tool.
+    pub async fn query(&self, request: DebugQueryRequest) -> Result<DebugQueryResponse> {
+        self.call("debug_query", &request).await
+    }
+
+    /// Count recorded events per function, thread or source file
+    /// (`request.group_by`), or list the distinct values (`request.distinct`).
+    pub async fn query_groups(
+        &self,
+        request: DebugQueryRequest,
+    ) -> Result<DebugQueryGroupsResponse> {
+        if request.group_field().is_none() {
+            return Err(crate::Error::ValidationError(
+                "query_groups needs distinct or group_by".to_string(),
+            ));
+        }
+        self.call("debug_query", &request).await
+    }
+
+    /// Stop a session. With `retain`, its events stay queryable afterwards.
+    pub async fn stop(&self, session_id: &str, retain: bool) -> Result<DebugStopResponse> {
+        let args = serde_json::json!({
+            "action": "stop",
+            "sessionId": session_id,
+            "retain": retain,
+        });
+        let value = self
+            .daemon
+            .call_tool("debug_session", &args, EMBEDDED_CONNECTION_ID)
+            .await?;
+        Ok(serde_json::from_value(value)?)
+    }
+
+    /// Call any tool by its MCP name (`debug_test`, `debug_breakpoint`, ...)
+    /// with its JSON arguments.
+    pub async fn call_tool(
+        &self,
+        name: &str,
+        args: serde_json::Value,
+    ) -> Result<serde_json::Value> {
+        self.daemon
+            .call_tool(name, &args, EMBEDDED_CONNECTION_ID)
+            .await
+    }
+
+    /// Stop every session launched through this engine that is still
+    /// running. Test runs are left to finish, as on an MCP disconnect.
+    pub async fn shutdown(&self) {
+        self.daemon.handle_disconnect(EMBEDDED_CONNECTION_ID).await;
+    }
+
+    async fn call<Req, Resp>(&self, tool: &str, request: &Req) -> Result<Resp>
+    where
+        Req: serde::Serialize,
+        Resp: serde::de::DeserializeOwned,
+    {
+        let args = serde_json::to_value(request)?;
+        let value = self
+            .daemon
+            .call_tool(tool, &args, EMBEDDED_CONNECTION_ID)
+            .await?;
+        Ok(serde_json::from_value(value)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_embedded_pending_trace_and_errors() {
+        let dir = tempfile::tempdir().unwrap();
+        let strobe = Strobe::open(&dir.path().join("data")).unwrap();
+
+        let staged = strobe
+            .trace(DebugTraceRequest {
+                add: Some(vec!["app::*".into()]),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(staged.mode, "pending");
+        assert_eq!(staged.active_patterns, vec!["app::*".to_string()]);
+
+        let missing = strobe
+            .query(DebugQueryRequest {
+                session_id: "nope".into(),
+                ..Default::default()
+            })
+            .await;
+        assert!(matches!(missing, Err(crate::Error::SessionNotFound(_))));
+        assert!(strobe.stop("nope", false).await.is_err());
+
+        let health = strobe
+            .call_tool("debug_health", serde_json::json!({}))
+            .await
+            .unwrap();
+        assert!(health.is_object());
+        strobe.shutdown().await;
+    }
+}
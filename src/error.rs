@@ -41,6 +41,13 @@ pub enum Error {
     #[error("UI_NOT_AVAILABLE: {0}")]
     UiNotAvailable(String),
 
+    #[error("PERMISSION_REQUIRED: {permission} permission is not granted. Enable it for the app that started strobe (your terminal or editor) in {pane} (open \"{url}\"), then retry; restart the strobe daemon if it still fails. `strobe setup-ui` asks for every UI permission at once.")]
+    PermissionRequired {
+        permission: String,
+        pane: String,
+        url: String,
+    },
+
     #[error("TEST_RUN_NOT_FOUND: No test run found with ID '{0}'.")]
     TestRunNotFound(String),
 
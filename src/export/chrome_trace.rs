@@ -0,0 +1,386 @@
+//! Chrome Trace Event Format export.
+//!
+//! Produces the JSON object format (`{"traceEvents": [...]}`) understood by
+//! chrome://tracing and ui.perfetto.dev. Function enter/exit pairs become
+//! duration begin/end events per thread; output, crashes, logpoints and pauses
+//! become instant events so they line up with the call stacks on the timeline.
+//! Count-mode call totals become counter tracks; stack samples are instant
+//! events named after their leaf frame.
+
+use crate::db::{Event, EventType};
+use serde_json::{json, Value};
+
+/// Convert session events into a Chrome trace document.
+///
+/// Events may be passed in any order; they are sorted by timestamp so that
+/// begin/end pairs nest correctly per thread.
+pub fn to_chrome_trace(session_id: &str, events: &[Event]) -> Value {
+    let mut sorted: Vec<&Event> = events.iter().collect();
+    // Stable sort keeps enter-before-exit for events sharing a timestamp
+    sorted.sort_by_key(|e| e.timestamp_ns);
+
+    let mut trace_events = Vec::with_capacity(sorted.len() + 8);
+    let mut named_threads = std::collections::HashSet::new();
+
+    for event in sorted {
+        let pid = event.pid.unwrap_or(0);
+        let tid = event.thread_id;
+
+        if let Some(ref name) = event.thread_name {
+            if named_threads.insert((pid, tid)) {
+                trace_events.push(json!({
+                    "name": "thread_name",
+                    "ph": "M",
+                    "pid": pid,
+                    "tid": tid,
+                    "args": { "name": name },
+                }));
+            }
+        }
+
+        if let Some(te) = convert_event(event, pid, tid) {
+            trace_events.push(te);
+        }
+    }
+
+    json!({
+        "traceEvents": trace_events,
+        "displayTimeUnit": "ns",
+        "otherData": { "sessionId": session_id, "exporter": "strobe" },
+    })
+}
+
+/// Chrome traces use microseconds; keep sub-microsecond precision as a fraction.
+fn ts_us(timestamp_ns: i64) -> f64 {
+    timestamp_ns as f64 / 1000.0
+}
+
+fn convert_event(event: &Event, pid: u32, tid: i64) -> Option<Value> {
+    let ts = ts_us(event.timestamp_ns);
+    let value = match event.event_type {
+        EventType::FunctionEnter => {
+            let mut args = serde_json::Map::new();
+            if let Some(ref a) = event.arguments {
+                args.insert("arguments".to_string(), a.clone());
+            }
+            if let (Some(file), Some(line)) = (&event.source_file, event.line_number) {
+                args.insert("source".to_string(), json!(format!("{}:{}", file, line)));
+            }
+            if let Some(ref queue) = event.queue_name {
+                args.insert("queue".to_string(), json!(queue));
+            }
+            json!({
+                "name": event.function_name,
+                "cat": "function",
+                "ph": "B",
+                "ts": ts,
+                "pid": pid,
+                "tid": tid,
+                "args": args,
+            })
+        }
+        EventType::FunctionExit => {
+            let mut args = serde_json::Map::new();
+            if let Some(ref r) = event.return_value {
+                args.insert("returnValue".to_string(), r.clone());
+            }
+            json!({
+                "name": event.function_name,
+                "cat": "function",
+                "ph": "E",
+                "ts": ts,
+                "pid": pid,
+                "tid": tid,
+                "args": args,
+            })
+        }
+        EventType::Stdout | EventType::Stderr => instant(
+            event.event_type.as_str(),
+            "output",
+            ts,
+            pid,
+            tid,
+            json!({ "text": event.text }),
+        ),
+        EventType::AuxOutput => instant(
+            event.channel.as_deref().unwrap_or("aux_output"),
+            "output",
+            ts,
+            pid,
+            tid,
+            json!({ "text": event.text }),
+        ),
+        EventType::Crash => instant(
+            event.signal.as_deref().unwrap_or("crash"),
+            "crash",
+            ts,
+            pid,
+            tid,
+            json!({
+                "signal": event.signal,
+                "faultAddress": event.fault_address,
+                "exceptionType": event.exception_type,
+                "exceptionMessage": event.exception_message,
+            }),
+        ),
+        EventType::Logpoint => instant(
+            &event.function_name,
+            "logpoint",
+            ts,
+            pid,
+            tid,
+            json!({ "message": event.logpoint_message }),
+        ),
+        EventType::Pause => instant(
+            &event.function_name,
+            "pause",
+            ts,
+            pid,
+            tid,
+            json!({ "breakpointId": event.breakpoint_id }),
+        ),
+        EventType::Watchpoint => instant(
+            event.text.as_deref().unwrap_or("watchpoint"),
+            "watchpoint",
+            ts,
+            pid,
+            tid,
+            json!({
+                "watchpointId": event.breakpoint_id,
+                "address": event.fault_address,
+                "function": event.function_name,
+            }),
+        ),
+        EventType::OutputTrigger => instant(
+            event
+                .arguments
+                .as_ref()
+                .and_then(|t| t.get("match"))
+                .and_then(|m| m.as_str())
+                .unwrap_or("output trigger"),
+            "output_trigger",
+            ts,
+            pid,
+            tid,
+            json!({ "action": event.arguments.as_ref().and_then(|t| t.get("action")) }),
+        ),
+        // One counter track per process; each series is a function's running total
+        EventType::CallCounts => json!({
+            "name": "call_counts",
+            "ph": "C",
+            "ts": ts,
+            "pid": pid,
+            "args": event
+                .arguments
+                .as_ref()
+                .and_then(|c| c.as_array())
+                .into_iter()
+                .flatten()
+                .filter_map(|c| Some((c.get("function")?.as_str()?.to_string(), c.get("count")?.clone())))
+                .collect::<serde_json::Map<_, _>>(),
+        }),
+        EventType::Sample => instant(
+            &event.function_name,
+            "sample",
+            ts,
+            pid,
+            tid,
+            json!({ "backtrace": event.backtrace }),
+        ),
+        EventType::SignalSafety => instant(
+            event.text.as_deref().unwrap_or(&event.function_name),
+            "signal_safety",
+            ts,
+            pid,
+            tid,
+            json!({ "signal": event.signal, "backtrace": event.backtrace }),
+        ),
+        EventType::Leak => instant(
+            &event.function_name,
+            "leak",
+            ts,
+            pid,
+            tid,
+            json!({ "leak": event.arguments, "backtrace": event.backtrace }),
+        ),
+        // A complete slice: syscall events carry their start time and duration
+        EventType::Syscall => json!({
+            "name": event.function_name,
+            "cat": "syscall",
+            "ph": "X",
+            "ts": ts,
+            "dur": ts_us(event.duration_ns.unwrap_or(0)),
+            "pid": pid,
+            "tid": tid,
+            "args": { "call": event.arguments, "result": event.return_value },
+        }),
+        EventType::Network => json!({
+            "name": event.text.as_deref().unwrap_or(&event.function_name),
+            "cat": "network",
+            "ph": "X",
+            "ts": ts,
+            "dur": ts_us(event.duration_ns.unwrap_or(0)),
+            "pid": pid,
+            "tid": tid,
+            "args": { "call": event.function_name, "network": event.arguments },
+        }),
+        EventType::RtWarning => instant(
+            event.text.as_deref().unwrap_or(&event.function_name),
+            "rt_warning",
+            ts,
+            pid,
+            tid,
+            json!({ "warning": event.arguments }),
+        ),
+        EventType::Panic => instant(
+            event.text.as_deref().unwrap_or(&event.function_name),
+            "panic",
+            ts,
+            pid,
+            tid,
+            json!({ "panic": event.arguments, "backtrace": event.backtrace }),
+        ),
+        EventType::Exception => instant(
+            event.text.as_deref().unwrap_or(&event.function_name),
+            "exception",
+            ts,
+            pid,
+            tid,
+            json!({ "exception": event.arguments, "callId": event.call_id }),
+        ),
+        EventType::Signal => instant(
+            event.text.as_deref().unwrap_or(&event.function_name),
+            "signal",
+            ts,
+            pid,
+            tid,
+            json!({ "signal": event.signal, "details": event.arguments }),
+        ),
+        EventType::Lock => instant(
+            event.text.as_deref().unwrap_or(&event.function_name),
+            "lock",
+            ts,
+            pid,
+            tid,
+            json!({ "waitNs": event.duration_ns, "details": event.arguments }),
+        ),
+        EventType::Recovery => instant(
+            "recording interrupted",
+            "recovery",
+            ts,
+            pid,
+            tid,
+            json!({ "message": event.text }),
+        ),
+        EventType::ConfigChange => instant(
+            "settings changed",
+            "config",
+            ts,
+            pid,
+            tid,
+            json!({ "message": event.text }),
+        ),
+        EventType::VariableSnapshot | EventType::ConditionError => return None,
+    };
+    Some(value)
+}
+
+fn instant(name: &str, cat: &str, ts: f64, pid: u32, tid: i64, args: Value) -> Value {
+    json!({
+        "name": name,
+        "cat": cat,
+        "ph": "i",
+        "s": "t",
+        "ts": ts,
+        "pid": pid,
+        "tid": tid,
+        "args": args,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(id: &str, ts: i64, event_type: EventType, function: &str) -> Event {
+        Event {
+            id: id.to_string(),
+            session_id: "s1".to_string(),
+            timestamp_ns: ts,
+            thread_id: 7,
+            event_type,
+            function_name: function.to_string(),
+            pid: Some(42),
+            ..Event::default()
+        }
+    }
+
+    #[test]
+    fn test_enter_exit_become_begin_end_in_order() {
+        let mut exit = event("e2", 5_000, EventType::FunctionExit, "foo");
+        exit.return_value = Some(json!(3));
+        let mut enter = event("e1", 1_500, EventType::FunctionEnter, "foo");
+        enter.arguments = Some(json!([1, 2]));
+
+        // Deliberately out of order (DB queries return newest first)
+        let trace = to_chrome_trace("s1", &[exit, enter]);
+        let events = trace["traceEvents"].as_array().unwrap();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0]["ph"], "B");
+        assert_eq!(events[0]["ts"], 1.5);
+        assert_eq!(events[0]["args"]["arguments"], json!([1, 2]));
+        assert_eq!(events[1]["ph"], "E");
+        assert_eq!(events[1]["args"]["returnValue"], json!(3));
+        assert_eq!(events[1]["pid"], 42);
+        assert_eq!(events[1]["tid"], 7);
+    }
+
+    #[test]
+    fn test_output_and_crash_are_instants() {
+        let mut out = event("o1", 10, EventType::Stdout, "");
+        out.text = Some("hello\n".to_string());
+        let mut crash = event("c1", 20, EventType::Crash, "");
+        crash.signal = Some("SIGSEGV".to_string());
+
+        let trace = to_chrome_trace("s1", &[out, crash]);
+        let events = trace["traceEvents"].as_array().unwrap();
+        assert_eq!(events[0]["ph"], "i");
+        assert_eq!(events[0]["name"], "stdout");
+        assert_eq!(events[0]["args"]["text"], "hello\n");
+        assert_eq!(events[1]["name"], "SIGSEGV");
+        assert_eq!(events[1]["cat"], "crash");
+    }
+
+    #[test]
+    fn test_call_counts_become_counter_series() {
+        let mut counts = event("n1", 30, EventType::CallCounts, "");
+        counts.arguments = Some(json!([
+            { "function": "audio::mix", "address": "0x10", "count": 12 },
+            { "function": "audio::fill", "address": "0x20", "count": 3 }
+        ]));
+
+        let trace = to_chrome_trace("s1", &[counts]);
+        let counter = &trace["traceEvents"][0];
+        assert_eq!(counter["ph"], "C");
+        assert_eq!(counter["args"]["audio::mix"], 12);
+        assert_eq!(counter["args"]["audio::fill"], 3);
+    }
+
+    #[test]
+    fn test_thread_name_metadata_emitted_once() {
+        let mut a = event("a", 1, EventType::FunctionEnter, "f");
+        a.thread_name = Some("worker".to_string());
+        let mut b = event("b", 2, EventType::FunctionExit, "f");
+        b.thread_name = Some("worker".to_string());
+
+        let trace = to_chrome_trace("s1", &[a, b]);
+        let meta: Vec<_> = trace["traceEvents"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .filter(|e| e["ph"] == "M")
+            .collect();
+        assert_eq!(meta.len(), 1);
+        assert_eq!(meta[0]["args"]["name"], "worker");
+    }
+}
@@ -0,0 +1,432 @@
+//! Code coverage export.
+//!
+//! The agent follows threads with Stalker and reports every basic block the
+//! first time it executes, as (module, offset, size). This module writes those
+//! blocks as a DynamoRIO drcov log (lighthouse, bncov, Cutter) and maps the
+//! executable's blocks through DWARF to per-file function and line coverage,
+//! written as an lcov tracefile (genhtml, IDE gutters). Coverage is hit/miss
+//! only: a block is compiled once, so there are no hit counts.
+
+use std::collections::{BTreeMap, HashSet};
+use std::fmt::Write as _;
+
+use serde::{Deserialize, Serialize};
+
+use crate::dwarf::DwarfParser;
+use crate::mcp::CoverageGranularity;
+
+/// A module the agent recorded blocks in; `base` is its runtime load address.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CoverageModule {
+    pub path: String,
+    pub base: u64,
+    pub size: u64,
+}
+
+/// A basic block executed at least once, relative to its module
+/// (the field widths are drcov's).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CoveredBlock {
+    pub module: u16,
+    pub offset: u32,
+    pub size: u16,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct CoverageData {
+    pub modules: Vec<CoverageModule>,
+    pub blocks: Vec<CoveredBlock>,
+    /// Index of the traced executable in `modules`, if any of its code ran
+    pub main_module: Option<u16>,
+}
+
+#[derive(Deserialize)]
+struct AgentModule {
+    path: String,
+    base: String,
+    size: u64,
+    #[serde(default)]
+    main: bool,
+}
+
+#[derive(Deserialize)]
+struct AgentCoverage {
+    #[serde(default)]
+    modules: Vec<AgentModule>,
+    #[serde(default)]
+    blocks: Vec<(u16, u32, u16)>,
+}
+
+impl CoverageData {
+    /// Parse the blocks of the agent's `coverage_response`.
+    pub fn from_agent(payload: &serde_json::Value) -> crate::Result<Self> {
+        let malformed = |what: &str| crate::Error::Frida(format!("Malformed coverage: {}", what));
+        let raw: AgentCoverage =
+            serde_json::from_value(payload.clone()).map_err(|e| malformed(&e.to_string()))?;
+
+        let mut data = CoverageData::default();
+        for (index, module) in raw.modules.into_iter().enumerate() {
+            let base = u64::from_str_radix(module.base.trim_start_matches("0x"), 16)
+                .map_err(|_| malformed(&format!("module base '{}'", module.base)))?;
+            if module.main {
+                data.main_module = Some(index as u16);
+            }
+            data.modules.push(CoverageModule {
+                path: module.path,
+                base,
+                size: module.size,
+            });
+        }
+        for (module, offset, size) in raw.blocks {
+            if module as usize >= data.modules.len() {
+                return Err(malformed(&format!("block in unknown module {}", module)));
+            }
+            data.blocks.push(CoveredBlock {
+                module,
+                offset,
+                size,
+            });
+        }
+        Ok(data)
+    }
+}
+
+/// drcov version 2 log: a text header with the module table, then one
+/// little-endian `{u32 start, u16 size, u16 module}` record per block.
+pub fn to_drcov(data: &CoverageData) -> Vec<u8> {
+    let mut header = String::new();
+    header.push_str("DRCOV VERSION: 2\nDRCOV FLAVOR: strobe\n");
+    let _ = writeln!(
+        header,
+        "Module Table: version 2, count {}",
+        data.modules.len()
+    );
+    header.push_str("Columns: id, base, end, entry, checksum, timestamp, path\n");
+    for (id, module) in data.modules.iter().enumerate() {
+        let _ = writeln!(
+            header,
+            "{:3}, 0x{:016x}, 0x{:016x}, 0x{:016x}, 0x{:08x}, 0x{:08x}, {}",
+            id,
+            module.base,
+            module.base + module.size,
+            0,
+            0,
+            0,
+            module.path
+        );
+    }
+    let _ = writeln!(header, "BB Table: {} bbs", data.blocks.len());
+
+    let mut out = header.into_bytes();
+    out.reserve(data.blocks.len() * 8);
+    for block in &data.blocks {
+        out.extend_from_slice(&block.offset.to_le_bytes());
+        out.extend_from_slice(&block.size.to_le_bytes());
+        out.extend_from_slice(&block.module.to_le_bytes());
+    }
+    out
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileCoverage {
+    pub file: String,
+    pub covered: u32,
+    pub total: u32,
+}
+
+/// Executed address ranges of the executable, in DWARF (unslid) addresses.
+struct CoveredRanges {
+    /// (start, end) sorted by start
+    ranges: Vec<(u64, u64)>,
+    /// Running maximum of `end` over `ranges[..=i]`
+    max_end: Vec<u64>,
+}
+
+impl CoveredRanges {
+    fn new(data: &CoverageData, image_base: u64) -> Self {
+        let mut ranges: Vec<(u64, u64)> = match data.main_module {
+            Some(main) => data
+                .blocks
+                .iter()
+                .filter(|b| b.module == main)
+                .map(|b| {
+                    let start = image_base + b.offset as u64;
+                    (start, start + b.size.max(1) as u64)
+                })
+                .collect(),
+            None => Vec::new(),
+        };
+        ranges.sort_unstable();
+        let max_end = ranges
+            .iter()
+            .scan(0, |max, &(_, end)| {
+                *max = end.max(*max);
+                Some(*max)
+            })
+            .collect();
+        Self { ranges, max_end }
+    }
+
+    /// Whether any executed block overlaps `[low, high)`.
+    fn hit(&self, low: u64, high: u64) -> bool {
+        let before = self.ranges.partition_point(|&(start, _)| start < high);
+        before > 0 && self.max_end[before - 1] > low
+    }
+}
+
+/// Source-level coverage of the executable: every function and statement
+/// line in scope, executed or not.
+#[derive(Debug, Default)]
+pub struct SourceCoverage {
+    /// file -> (first line, name, executed)
+    functions: BTreeMap<String, Vec<(u32, String, bool)>>,
+    /// file -> line -> executed
+    lines: BTreeMap<String, BTreeMap<u32, bool>>,
+}
+
+impl SourceCoverage {
+    /// Map the executable's blocks onto its DWARF functions, and onto line
+    /// table entries when `with_lines`. With `project_root`, only functions
+    /// and lines in project sources count.
+    pub fn build(
+        data: &CoverageData,
+        dwarf: &DwarfParser,
+        project_root: Option<&str>,
+        with_lines: bool,
+    ) -> Self {
+        let covered = CoveredRanges::new(data, dwarf.image_base);
+        let in_scope = |file: &str| project_root.is_none_or(|root| file.starts_with(root));
+
+        let mut source = SourceCoverage::default();
+        let mut seen = HashSet::new();
+        for func in &dwarf.functions {
+            let Some(ref file) = func.source_file else {
+                continue;
+            };
+            if func.low_pc >= func.high_pc || !in_scope(file) || !seen.insert(func.low_pc) {
+                continue;
+            }
+            source.functions.entry(file.clone()).or_default().push((
+                func.line_number.unwrap_or(0),
+                func.name.clone(),
+                covered.hit(func.low_pc, func.high_pc),
+            ));
+
+            if with_lines {
+                for (start, end, line_file, line) in dwarf.line_spans(func.low_pc, func.high_pc) {
+                    if !in_scope(&line_file) {
+                        continue;
+                    }
+                    let hit = covered.hit(start, end);
+                    *source
+                        .lines
+                        .entry(line_file)
+                        .or_default()
+                        .entry(line)
+                        .or_default() |= hit;
+                }
+            }
+        }
+        for functions in source.functions.values_mut() {
+            functions.sort();
+        }
+        source
+    }
+
+    /// Covered/total per file, in lines (block) or functions (function).
+    pub fn files(&self, granularity: CoverageGranularity) -> Vec<FileCoverage> {
+        match granularity {
+            CoverageGranularity::Block => self
+                .lines
+                .iter()
+                .map(|(file, lines)| FileCoverage {
+                    file: file.clone(),
+                    covered: lines.values().filter(|&&hit| hit).count() as u32,
+                    total: lines.len() as u32,
+                })
+                .collect(),
+            CoverageGranularity::Function => self
+                .functions
+                .iter()
+                .map(|(file, functions)| FileCoverage {
+                    file: file.clone(),
+                    covered: functions.iter().filter(|f| f.2).count() as u32,
+                    total: functions.len() as u32,
+                })
+                .collect(),
+        }
+    }
+
+    /// lcov tracefile with FN/FNDA records, plus DA records when lines were mapped.
+    pub fn to_lcov(&self) -> String {
+        let files: std::collections::BTreeSet<&String> =
+            self.functions.keys().chain(self.lines.keys()).collect();
+
+        let mut out = String::new();
+        for file in files {
+            out.push_str("TN:\n");
+            let _ = writeln!(out, "SF:{}", file);
+            let functions = self.functions.get(file).map_or(&[][..], |f| f.as_slice());
+            for (line, name, _) in functions {
+                let _ = writeln!(out, "FN:{},{}", line, name);
+            }
+            for (_, name, hit) in functions {
+                let _ = writeln!(out, "FNDA:{},{}", *hit as u8, name);
+            }
+            let _ = writeln!(out, "FNF:{}", functions.len());
+            let _ = writeln!(out, "FNH:{}", functions.iter().filter(|f| f.2).count());
+            if let Some(lines) = self.lines.get(file) {
+                for (line, hit) in lines {
+                    let _ = writeln!(out, "DA:{},{}", line, *hit as u8);
+                }
+                let _ = writeln!(out, "LF:{}", lines.len());
+                let _ = writeln!(out, "LH:{}", lines.values().filter(|&&hit| hit).count());
+            }
+            out.push_str("end_of_record\n");
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dwarf::{FunctionInfo, LineEntry};
+    use std::collections::HashMap;
+    use std::sync::Mutex;
+
+    fn function(name: &str, low_pc: u64, high_pc: u64, file: &str, line: u32) -> FunctionInfo {
+        FunctionInfo {
+            name: name.to_string(),
+            name_raw: None,
+            low_pc,
+            high_pc,
+            source_file: Some(file.to_string()),
+            line_number: Some(line),
+        }
+    }
+
+    fn line(address: u64, file: &str, line: u32) -> LineEntry {
+        LineEntry {
+            address,
+            file: file.to_string(),
+            line,
+            column: 0,
+            is_statement: true,
+        }
+    }
+
+    fn parser() -> DwarfParser {
+        let functions = vec![
+            function("app::run", 0x1000, 0x1040, "/proj/src/app.c", 10),
+            function("app::idle", 0x1040, 0x1080, "/proj/src/app.c", 20),
+            function("memcpy", 0x2000, 0x2040, "/usr/include/string.h", 5),
+        ];
+        DwarfParser {
+            functions_by_addr: functions.iter().map(|f| (f.low_pc, f.high_pc)).collect(),
+            functions,
+            functions_by_name: HashMap::new(),
+            variables: vec![],
+            variables_by_name: HashMap::new(),
+            struct_members: Mutex::new(HashMap::new()),
+            lazy_struct_info: HashMap::new(),
+            type_layouts: Mutex::new(HashMap::new()),
+            function_params: Mutex::new(HashMap::new()),
+            line_table: Mutex::new(Some(vec![
+                line(0x1000, "/proj/src/app.c", 10),
+                line(0x1010, "/proj/src/app.c", 11),
+                line(0x1030, "/proj/src/app.c", 12),
+                line(0x1040, "/proj/src/app.c", 20),
+                line(0x1060, "/proj/src/app.c", 21),
+                line(0x2000, "/usr/include/string.h", 5),
+            ])),
+            image_base: 0x1000,
+            binary_path: None,
+            go_functions: Default::default(),
+            goid_offset: None,
+            swift: false,
+        }
+    }
+
+    fn agent_payload() -> serde_json::Value {
+        // Blocks at image offsets 0x0..0x18 (lines 10-11) and 0x1000 (memcpy)
+        serde_json::json!({
+            "modules": [
+                { "path": "/proj/app", "base": "0x55550000", "size": 0x4000, "main": true },
+                { "path": "/lib/libc.so.6", "base": "0x7f0000000000", "size": 0x1000 }
+            ],
+            "blocks": [[0, 0, 0x18], [0, 0x1000, 0x10], [1, 0x40, 8]]
+        })
+    }
+
+    #[test]
+    fn test_drcov_layout() {
+        let data = CoverageData::from_agent(&agent_payload()).unwrap();
+        assert_eq!(data.main_module, Some(0));
+        let bytes = to_drcov(&data);
+
+        let header_end = bytes
+            .windows(13)
+            .position(|w| w == b"BB Table: 3 b")
+            .expect("bb table header");
+        let header = String::from_utf8_lossy(&bytes[..header_end]);
+        assert!(header.starts_with("DRCOV VERSION: 2\n"));
+        assert!(header.contains("Module Table: version 2, count 2"));
+        assert!(header.contains("0x0000000055550000, 0x0000000055554000"));
+        assert!(header.contains("/lib/libc.so.6"));
+
+        let table = &bytes[bytes.len() - 3 * 8..];
+        assert_eq!(&table[16..20], &0x40u32.to_le_bytes());
+        assert_eq!(&table[20..22], &8u16.to_le_bytes());
+        assert_eq!(&table[22..24], &1u16.to_le_bytes());
+    }
+
+    #[test]
+    fn test_source_coverage_scoped_to_project() {
+        let data = CoverageData::from_agent(&agent_payload()).unwrap();
+        let source = SourceCoverage::build(&data, &parser(), Some("/proj"), true);
+
+        let functions = source.files(CoverageGranularity::Function);
+        assert_eq!(
+            functions,
+            vec![FileCoverage {
+                file: "/proj/src/app.c".to_string(),
+                covered: 1,
+                total: 2,
+            }]
+        );
+        let lines = source.files(CoverageGranularity::Block);
+        assert_eq!(lines[0].covered, 2);
+        assert_eq!(lines[0].total, 5);
+
+        let lcov = source.to_lcov();
+        assert!(lcov.contains("SF:/proj/src/app.c\n"));
+        assert!(lcov.contains("FNDA:1,app::run\n"));
+        assert!(lcov.contains("FNDA:0,app::idle\n"));
+        assert!(lcov.contains("DA:11,1\nDA:12,0\n"));
+        assert!(lcov.contains("LF:5\nLH:2\n"));
+        assert!(!lcov.contains("string.h"));
+    }
+
+    #[test]
+    fn test_source_coverage_all_files() {
+        let data = CoverageData::from_agent(&agent_payload()).unwrap();
+        let source = SourceCoverage::build(&data, &parser(), None, false);
+        let files = source.files(CoverageGranularity::Function);
+        assert_eq!(files.len(), 2);
+        assert_eq!(files[1].file, "/usr/include/string.h");
+        assert_eq!(files[1].covered, 1);
+        assert!(source.files(CoverageGranularity::Block).is_empty());
+    }
+
+    #[test]
+    fn test_malformed_agent_coverage() {
+        let bad_module = serde_json::json!({ "modules": [], "blocks": [[0, 0, 4]] });
+        assert!(CoverageData::from_agent(&bad_module).is_err());
+        let bad_base = serde_json::json!({
+            "modules": [{ "path": "/a", "base": "zz", "size": 1 }], "blocks": []
+        });
+        assert!(CoverageData::from_agent(&bad_base).is_err());
+    }
+}
@@ -0,0 +1,4 @@
+//! Session export to external trace viewers and coverage tools.
+
+pub mod chrome_trace;
+pub mod coverage;
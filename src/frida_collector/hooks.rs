@@ -6,6 +6,8 @@ use std::collections::HashSet;
 pub enum HookMode {
     Full,  // enter + exit, no sampling
     Light, // enter + exit, adaptive sampling
+    Count, // call counter only, no events
+    Rt,    // enter + exit, native only, safe on real-time threads
 }
 
 pub struct HookManager {
@@ -148,6 +150,11 @@ mod tests {
             serde_json::from_str::<HookMode>("\"light\"").unwrap(),
             HookMode::Light
         );
+        assert_eq!(
+            serde_json::to_string(&HookMode::Count).unwrap(),
+            "\"count\""
+        );
+        assert_eq!(serde_json::to_string(&HookMode::Rt).unwrap(), "\"rt\"");
     }
 
     #[test]
@@ -168,6 +175,9 @@ mod tests {
             match mode {
                 HookMode::Full => full_count += match_count,
                 HookMode::Light => light_count += match_count,
+                HookMode::Count | HookMode::Rt => {
+                    unreachable!("count and rt modes are requested, never classified")
+                }
             }
         }
 
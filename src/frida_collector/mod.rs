@@ -3,11 +3,15 @@ mod spawner;
 
 pub use hooks::HookManager;
 pub use hooks::HookMode;
+pub use spawner::child_session_key;
+pub use spawner::process_executable;
+pub use spawner::AttachedChild;
 pub use spawner::ExprWatchTarget;
 pub use spawner::FridaSpawner;
 pub use spawner::HookResult;
 pub use spawner::PauseNotification;
 pub use spawner::PauseNotifyTx;
+pub use spawner::SymbolModule;
 pub use spawner::WatchTarget;
 
 #[cfg(test)]
@@ -1,7 +1,8 @@
 use super::{HookManager, HookMode};
+use crate::daemon::EventSender;
 use crate::db::{Event, EventType};
-use crate::dwarf::{DwarfHandle, DwarfParser, FunctionInfo};
-use crate::symbols::Language;
+use crate::dwarf::{DwarfHandle, DwarfParser, FunctionInfo, InlinedCall};
+use crate::symbols::{is_objc_pattern, Language};
 use crate::Result;
 use libc;
 use std::collections::HashMap;
@@ -245,11 +246,13 @@ unsafe fn device_raw_ptr(device: &frida::Device) -> *mut frida_sys::_FridaDevice
 struct OutputContext {
     pid: u32,
     session_id: String,
-    event_tx: mpsc::Sender<Event>,
+    event_tx: EventSender,
     event_counter: AtomicU64,
     start_ns: i64,
     /// Accumulated stderr output — shared with process_death_monitor for ASAN parsing.
     stderr_buffer: Arc<Mutex<String>>,
+    /// Extra fds the agent captures as aux_output (inherited by child processes)
+    capture_fds: Vec<i32>,
 }
 
 /// Shared registry of active output contexts, keyed by PID.
@@ -392,8 +395,9 @@ pub type PauseNotifyTx = mpsc::Sender<PauseNotification>;
 /// No longer implements `ScriptHandler` — messages are parsed directly in `raw_on_message`.
 type WriteResponseSignal = Arc<Mutex<Option<std::sync::mpsc::Sender<serde_json::Value>>>>;
 
+#[derive(Clone)]
 struct AgentMessageHandler {
-    event_tx: mpsc::Sender<Event>,
+    event_tx: EventSender,
     session_id: String,
     hooks_ready: HooksReadySignal,
     read_response: ReadResponseSignal,
@@ -403,6 +407,9 @@ struct AgentMessageHandler {
     /// Wall-clock epoch nanos at process start, subtracted from event timestamps
     /// to produce process-relative timestamps consistent with trace events.
     start_ns: i64,
+    /// Process the script runs in, stamped on events the agent sends without
+    /// a pid (trace events) so parent and child calls can be told apart.
+    pid: Option<u32>,
 }
 
 impl AgentMessageHandler {
@@ -417,7 +424,10 @@ impl AgentMessageHandler {
                         self.session_id
                     );
                     for event_json in events {
-                        if let Some(event) = parse_event(&self.session_id, event_json) {
+                        if let Some(mut event) = parse_event(&self.session_id, event_json) {
+                            if event.pid.is_none() {
+                                event.pid = self.pid;
+                            }
                             if event.event_type == EventType::Crash {
                                 self.crash_reported.store(true, Ordering::Release);
                                 tracing::info!(
@@ -455,6 +465,21 @@ impl AgentMessageHandler {
                     payload,
                 );
             }
+            "flight_recorder_flushed" => {
+                tracing::info!(
+                    "[{}] Flight recorder flushed {} events ({}, {} overwritten)",
+                    self.session_id,
+                    payload.get("events").and_then(|v| v.as_u64()).unwrap_or(0),
+                    payload
+                        .get("reason")
+                        .and_then(|v| v.as_str())
+                        .unwrap_or("unknown"),
+                    payload
+                        .get("overwritten")
+                        .and_then(|v| v.as_u64())
+                        .unwrap_or(0)
+                );
+            }
             "log" => {
                 if let Some(msg) = payload.get("message").and_then(|v| v.as_str()) {
                     tracing::info!("Agent [{}]: {}", self.session_id, msg);
@@ -512,7 +537,14 @@ impl AgentMessageHandler {
                     );
                 }
             }
-            "read_response" | "eval_response" => {
+            "read_response"
+            | "eval_response"
+            | "coverage_response"
+            | "heap_response"
+            | "flight_recorder_response"
+            | "call_response"
+            | "frame_response"
+            | "objc_response" => {
                 if let Ok(mut guard) = self.read_response.lock() {
                     if let Some(tx) = guard.take() {
                         let _ = tx.send(payload.clone());
@@ -669,6 +701,11 @@ impl AgentMessageHandler {
                 // Signal the hooks_ready channel so set_breakpoint_async can unblock
                 signal_ready(&self.hooks_ready, msg_type, &self.session_id, payload);
             }
+            "watchpointsSet" | "samplingSet" | "threadFilterSet" | "rateLimitSet"
+            | "flightRecorderSet" | "crashCaptureSet" | "signalSafetySet" | "syscallsSet"
+            | "networkSet" | "exceptionsSet" | "signalsSet" | "locksSet" => {
+                signal_ready(&self.hooks_ready, msg_type, &self.session_id, payload);
+            }
             "breakpointRemoved" | "logpointRemoved" => {
                 let id = payload
                     .get("id")
@@ -760,6 +797,8 @@ pub struct HookResult {
 ///   ~150+ hooks: crash risk with hot functions
 const MAX_HOOKS_PER_CALL: usize = 100;
 const CHUNK_SIZE: usize = 50;
+/// Count hooks carry no argument layouts, so they ship in larger batches
+const COUNT_CHUNK_SIZE: usize = 500;
 const TIMEOUT_PER_CHUNK_SECS: u64 = 45;
 
 /// Wrapper to move raw script pointer across threads.
@@ -767,13 +806,100 @@ const TIMEOUT_PER_CHUNK_SECS: u64 = 45;
 struct SendScriptPtr(*mut frida_sys::_FridaScript);
 unsafe impl Send for SendScriptPtr {}
 
+/// Wrapper to move a raw Frida session pointer to a session worker.
+/// Safety: the coordinator keeps the session alive until the worker has
+/// shut down (see `FridaSpawner::stop`).
+struct SendSessionPtr(*mut frida_sys::_FridaSession);
+unsafe impl Send for SendSessionPtr {}
+
+/// What a session worker needs to replace its agent script in place
+/// (debug_session "reload-agent").
+struct ScriptReload {
+    session_ptr: SendSessionPtr,
+    language: Language,
+    is_bun: bool,
+    /// Cloned onto each new script; shares the original's channels and the
+    /// response signals the worker waits on
+    handler: AgentMessageHandler,
+    /// `initialize` message the agent was started with
+    init_msg: serde_json::Value,
+}
+
 /// Result returned by coordinator after spawning a process.
 struct SpawnResult {
     pid: u32,
     script_ptr: SendScriptPtr,
-    hooks_ready: HooksReadySignal,
-    read_response: ReadResponseSignal,
-    write_response: WriteResponseSignal,
+    reload: ScriptReload,
+}
+
+/// A fork/exec child of a traced process with the agent loaded but the
+/// process still suspended. Handed to the child follower (if one is set),
+/// which installs hooks via `FridaSpawner::follow_child` and then resumes it.
+pub struct AttachedChild {
+    pub session_id: String,
+    pub pid: u32,
+    script_ptr: SendScriptPtr,
+    reload: ScriptReload,
+}
+
+/// Receiver of newly attached children, shared with the coordinator thread.
+type ChildFollowerSlot = Arc<Mutex<Option<tokio::sync::mpsc::UnboundedSender<AttachedChild>>>>;
+
+/// Key under which a followed child's worker and hook state are stored.
+pub fn child_session_key(session_id: &str, pid: u32) -> String {
+    format!("{}#child-{}", session_id, pid)
+}
+
+/// Path of a running process's executable.
+pub fn process_executable(pid: u32) -> Option<std::path::PathBuf> {
+    #[cfg(target_os = "linux")]
+    {
+        std::fs::read_link(format!("/proc/{}/exe", pid)).ok()
+    }
+    #[cfg(target_os = "macos")]
+    {
+        let mut buf = vec![0u8; libc::PROC_PIDPATHINFO_MAXSIZE as usize];
+        let len = unsafe {
+            libc::proc_pidpath(
+                pid as i32,
+                buf.as_mut_ptr() as *mut c_void,
+                buf.len() as u32,
+            )
+        };
+        if len <= 0 {
+            return None;
+        }
+        buf.truncate(len as usize);
+        String::from_utf8(buf).ok().map(std::path::PathBuf::from)
+    }
+    #[cfg(windows)]
+    {
+        use std::os::windows::ffi::OsStringExt;
+        use windows_sys::Win32::Foundation::CloseHandle;
+        use windows_sys::Win32::System::Threading::{
+            OpenProcess, QueryFullProcessImageNameW, PROCESS_NAME_WIN32,
+            PROCESS_QUERY_LIMITED_INFORMATION,
+        };
+
+        let mut buf = vec![0u16; 32 * 1024];
+        let mut len = buf.len() as u32;
+        let ok = unsafe {
+            let handle = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, 0, pid);
+            if handle.is_null() {
+                return None;
+            }
+            let queried =
+                QueryFullProcessImageNameW(handle, PROCESS_NAME_WIN32, buf.as_mut_ptr(), &mut len);
+            CloseHandle(handle);
+            queried != 0
+        };
+        ok.then(|| std::ffi::OsString::from_wide(&buf[..len as usize]).into())
+    }
+    #[cfg(not(any(target_os = "linux", target_os = "macos", windows)))]
+    {
+        let _ = pid;
+        None
+    }
 }
 
 /// Commands for the coordinator thread (device-level operations).
@@ -784,10 +910,11 @@ enum CoordinatorCommand {
         args: Vec<String>,
         cwd: Option<String>,
         env: Option<HashMap<String, String>>,
-        event_tx: mpsc::Sender<Event>,
+        event_tx: EventSender,
         defer_resume: bool,
         pause_notify_tx: Option<PauseNotifyTx>,
         language: Language,
+        capture_fds: Vec<i32>,
         response: oneshot::Sender<Result<SpawnResult>>,
     },
     Resume {
@@ -848,6 +975,12 @@ enum SessionCommand {
         return_address: Option<u64>,
         response: oneshot::Sender<Result<()>>,
     },
+    /// Swap the agent for a freshly created script running `source`.
+    /// Hooks installed by the old script are gone afterwards.
+    ReloadAgent {
+        source: String,
+        response: oneshot::Sender<Result<()>>,
+    },
     Shutdown,
 }
 
@@ -863,6 +996,10 @@ pub struct WatchTarget {
     pub on_patterns: Option<Vec<String>>,
     /// If true, address is already absolute (user-provided) — don't apply ASLR slide.
     pub no_slide: bool,
+    /// Record only changes of at least this much (see mcp::WatchTarget)
+    pub delta_threshold: Option<f64>,
+    /// Symbol module the variable lives in; `address` is then relative to its base
+    pub module: Option<String>,
 }
 
 #[derive(Clone)]
@@ -871,6 +1008,7 @@ pub struct ExprWatchTarget {
     pub expr: String,
     pub is_global: bool,
     pub on_patterns: Option<Vec<String>>,
+    pub delta_threshold: Option<f64>,
 }
 
 #[derive(Clone)]
@@ -880,6 +1018,89 @@ struct FunctionTarget {
     name_raw: Option<String>,
     source_file: Option<String>,
     line_number: Option<u32>,
+    /// DWARF container layouts for arguments/return value, if any decode.
+    containers: Option<crate::dwarf::FunctionContainers>,
+    /// Set on Go functions: offset of `goid` in `runtime.g`, so the agent can
+    /// tell which goroutine made each call
+    goid_offset: Option<u64>,
+    /// Symbol module the function lives in; `address` is then relative to its base
+    module: Option<String>,
+}
+
+/// A shared library indexed alongside the main binary (debug_launch
+/// `symbolExtra`), so patterns and watches resolve into it up front.
+#[derive(Clone)]
+pub struct SymbolModule {
+    pub path: String,
+    /// File name the agent looks the loaded module up by
+    pub name: String,
+    /// Static base from the file; addresses sent to the agent are relative to it
+    pub image_base: u64,
+    pub dwarf_handle: DwarfHandle,
+}
+
+/// Matches for `pattern` in the symbol modules, with module-relative addresses.
+fn module_targets(
+    modules: &[(SymbolModule, Arc<DwarfParser>)],
+    pattern: &str,
+    project_root: &str,
+) -> Vec<FunctionTarget> {
+    let mut targets = Vec::new();
+    for (module, dwarf) in modules {
+        for func in resolve_pattern(dwarf, pattern, project_root) {
+            if func.low_pc <= module.image_base {
+                continue;
+            }
+            let mut target = FunctionTarget::from(func);
+            target.address = func.low_pc - module.image_base;
+            target.module = Some(module.name.clone());
+            targets.push(target);
+        }
+    }
+    targets
+}
+
+/// Targets from the agent's `objc_response`: `{ name, module, offset }`.
+fn objc_targets(response: &serde_json::Value) -> Vec<FunctionTarget> {
+    let Some(targets) = response.get("targets").and_then(|v| v.as_array()) else {
+        return Vec::new();
+    };
+    targets
+        .iter()
+        .filter_map(|t| {
+            let offset = t.get("offset")?.as_str()?;
+            let offset = u64::from_str_radix(offset.trim_start_matches("0x"), 16).ok()?;
+            Some(FunctionTarget {
+                address: offset,
+                name: t.get("name")?.as_str()?.to_string(),
+                name_raw: None,
+                source_file: None,
+                line_number: None,
+                containers: None,
+                goid_offset: None,
+                module: Some(t.get("module")?.as_str()?.to_string()),
+            })
+        })
+        .collect()
+}
+
+/// Await the symbol modules' DWARF; failures become warnings.
+async fn ready_modules(
+    modules: Vec<SymbolModule>,
+    warnings: &mut Vec<String>,
+) -> Vec<(SymbolModule, Arc<DwarfParser>)> {
+    let mut ready = Vec::new();
+    for module in modules {
+        let mut handle = module.dwarf_handle.clone();
+        match handle.get().await {
+            Ok(dwarf) => ready.push((module, dwarf)),
+            Err(e) => warnings.push(format!(
+                "No debug symbols for {}; its functions were not matched: {}",
+                module.path, e
+            )),
+        }
+    }
+    ready
 }
 
 impl From<&FunctionInfo> for FunctionTarget {
@@ -890,6 +1111,43 @@ impl From<&FunctionInfo> for FunctionTarget {
             name_raw: f.name_raw.clone(),
             source_file: f.source_file.clone(),
             line_number: f.line_number,
+            containers: None,
+            goid_offset: None,
+            module: None,
+        }
+    }
+}
+
+impl From<crate::symbols::ResolvedTarget> for FunctionTarget {
+    fn from(target: crate::symbols::ResolvedTarget) -> Self {
+        match target {
+            // Interpreted functions have no address; the agent hooks by file and line
+            crate::symbols::ResolvedTarget::SourceLocation { file, line, name } => Self {
+                address: 0,
+                name_raw: Some(name.clone()),
+                name,
+                source_file: Some(file),
+                line_number: Some(line),
+                containers: None,
+                goid_offset: None,
+                module: None,
+            },
+            crate::symbols::ResolvedTarget::Address {
+                address,
+                name,
+                name_raw,
+                file,
+                line,
+            } => Self {
+                address,
+                name,
+                name_raw,
+                source_file: file,
+                line_number: line,
+                containers: None,
+                goid_offset: None,
+                module: None,
+            },
         }
     }
 }
@@ -916,7 +1174,10 @@ unsafe extern "C" fn destroy_spawn_tx(data: *mut c_void, _closure: *mut frida_sy
 
 /// Coordinator thread: handles device-level operations (spawn, kill, child processes).
 /// Per-session script operations are delegated to dedicated session_worker threads.
-fn coordinator_worker(cmd_rx: std::sync::mpsc::Receiver<CoordinatorCommand>) {
+fn coordinator_worker(
+    cmd_rx: std::sync::mpsc::Receiver<CoordinatorCommand>,
+    child_follower: ChildFollowerSlot,
+) {
     use frida::{DeviceManager, DeviceType, Frida, SpawnOptions, SpawnStdio};
 
     // Frida's global state (GLib g_slice allocator, GMainLoop, etc.) must never be
@@ -998,7 +1259,13 @@ fn coordinator_worker(cmd_rx: std::sync::mpsc::Receiver<CoordinatorCommand>) {
     loop {
         // Check for spawn notifications (non-blocking)
         while let Ok(child_pid) = spawn_rx.try_recv() {
-            handle_child_spawn(&mut device, child_pid, &output_registry, &mut session_ptrs);
+            handle_child_spawn(
+                &mut device,
+                child_pid,
+                &output_registry,
+                &mut session_ptrs,
+                &child_follower,
+            );
         }
 
         // Wait for commands with timeout so we periodically check for spawns
@@ -1025,6 +1292,7 @@ fn coordinator_worker(cmd_rx: std::sync::mpsc::Receiver<CoordinatorCommand>) {
                 defer_resume,
                 pause_notify_tx,
                 language,
+                capture_fds,
                 response,
             } => {
                 let result = (|| -> Result<SpawnResult> {
@@ -1178,6 +1446,7 @@ fn coordinator_worker(cmd_rx: std::sync::mpsc::Receiver<CoordinatorCommand>) {
                             event_counter: AtomicU64::new(0),
                             start_ns,
                             stderr_buffer: stderr_buffer_outer.clone(),
+                            capture_fds: capture_fds.clone(),
                         });
                         if let Ok(mut reg) = output_registry.lock() {
                             reg.insert(pid, output_ctx);
@@ -1276,6 +1545,7 @@ fn coordinator_worker(cmd_rx: std::sync::mpsc::Receiver<CoordinatorCommand>) {
                                 .unwrap_or_default()
                                 .as_nanos() as i64,
                             stderr_buffer: stderr_buffer_outer.clone(),
+                            capture_fds: capture_fds.clone(),
                         });
                         if let Ok(mut reg) = output_registry.lock() {
                             reg.insert(pid, output_ctx);
@@ -1312,7 +1582,7 @@ fn coordinator_worker(cmd_rx: std::sync::mpsc::Receiver<CoordinatorCommand>) {
                         }
                         attached.ok_or_else(|| {
                             // Check if process is still alive
-                            let alive = unsafe { libc::kill(pid as i32, 0) == 0 };
+                            let alive = crate::process::is_alive(pid);
                             if !alive && is_interpreted {
                                 tracing::error!("Process {} exited before Frida could attach (short-lived program?)", pid);
                                 crate::Error::FridaAttachFailed(format!(
@@ -1401,7 +1671,9 @@ fn coordinator_worker(cmd_rx: std::sync::mpsc::Receiver<CoordinatorCommand>) {
                             .duration_since(std::time::UNIX_EPOCH)
                             .unwrap_or_default()
                             .as_nanos() as i64,
+                        pid: Some(pid),
                     };
+                    let handler_template = handler.clone();
 
                     unsafe { register_handler_raw(script_ptr, handler) };
 
@@ -1422,8 +1694,11 @@ fn coordinator_worker(cmd_rx: std::sync::mpsc::Receiver<CoordinatorCommand>) {
                     tracing::debug!("PERF: script load + handler setup took {:?}", t.elapsed());
 
                     // Initialize agent
-                    let init_msg =
-                        serde_json::json!({ "type": "initialize", "sessionId": session_id });
+                    let init_msg = serde_json::json!({
+                        "type": "initialize",
+                        "sessionId": session_id,
+                        "captureFds": capture_fds,
+                    });
                     if let Err(e) = unsafe {
                         post_message_raw(script_ptr, &serde_json::to_string(&init_msg).unwrap())
                     } {
@@ -1495,9 +1770,13 @@ fn coordinator_worker(cmd_rx: std::sync::mpsc::Receiver<CoordinatorCommand>) {
                     Ok(SpawnResult {
                         pid,
                         script_ptr: SendScriptPtr(script_ptr),
-                        hooks_ready,
-                        read_response,
-                        write_response,
+                        reload: ScriptReload {
+                            session_ptr: SendSessionPtr(raw_session),
+                            language,
+                            is_bun,
+                            handler: handler_template,
+                            init_msg,
+                        },
                     })
                 })();
 
@@ -1572,13 +1851,15 @@ fn coordinator_worker(cmd_rx: std::sync::mpsc::Receiver<CoordinatorCommand>) {
 fn session_worker(
     session_id: String,
     script_ptr: SendScriptPtr,
-    hooks_ready: HooksReadySignal,
-    read_response: ReadResponseSignal,
-    write_response: WriteResponseSignal,
+    reload: ScriptReload,
     pid: u32,
     cmd_rx: std::sync::mpsc::Receiver<SessionCommand>,
 ) {
-    let raw_ptr = script_ptr.0;
+    let mut raw_ptr = script_ptr.0;
+    let mut reload_generation: u32 = 0;
+    let hooks_ready = reload.handler.hooks_ready.clone();
+    let read_response = reload.handler.read_response.clone();
+    let write_response = reload.handler.write_response.clone();
 
     loop {
         let cmd = match cmd_rx.recv() {
@@ -1796,21 +2077,76 @@ fn session_worker(
                 let _ = response.send(result);
             }
 
+            SessionCommand::ReloadAgent { source, response } => {
+                let result = unsafe { reload_script(&reload, &source) }.map(|new_ptr| {
+                    unsafe { unload_and_unref_script(raw_ptr) };
+                    raw_ptr = new_ptr;
+                    reload_generation += 1;
+                    let init_msg = reload_init_msg(&reload.init_msg, reload_generation);
+                    if let Err(e) = unsafe { post_message_raw(raw_ptr, &init_msg.to_string()) } {
+                        tracing::warn!(
+                            "Failed to initialize reloaded agent for {}: {}",
+                            session_id,
+                            e
+                        );
+                    }
+                    tracing::info!("Reloaded agent for session {}", session_id);
+                });
+                let _ = response.send(result);
+            }
+
             SessionCommand::Shutdown => {
                 tracing::info!("Session worker {} shutting down", session_id);
                 // Unload and unref the script to prevent memory leaks
-                unsafe {
-                    let mut error: *mut frida_sys::GError = std::ptr::null_mut();
-                    frida_sys::frida_script_unload_sync(raw_ptr, std::ptr::null_mut(), &mut error);
-                    let _ = check_gerror(error);
-                    frida_sys::frida_unref(raw_ptr as *mut c_void);
-                }
+                unsafe { unload_and_unref_script(raw_ptr) };
                 break;
             }
         }
     }
 }
 
+unsafe fn unload_and_unref_script(script_ptr: *mut frida_sys::_FridaScript) {
+    let mut error: *mut frida_sys::GError = std::ptr::null_mut();
+    frida_sys::frida_script_unload_sync(script_ptr, std::ptr::null_mut(), &mut error);
+    let _ = check_gerror(error);
+    frida_sys::frida_unref(script_ptr as *mut c_void);
+}
+
+/// The `initialize` message for the `generation`th reload of an agent. The new
+/// script's per-thread event sequences start over, so the generation goes
+/// into its event ids; otherwise they would repeat ids already stored and be
+/// dropped as duplicates.
+fn reload_init_msg(init_msg: &serde_json::Value, generation: u32) -> serde_json::Value {
+    let mut msg = init_msg.clone();
+    msg["reloadGeneration"] = generation.into();
+    msg
+}
+
+/// Create and load a replacement agent script in the worker's Frida session.
+/// The old script stays loaded until this succeeds, so a bundle that fails
+/// to compile leaves the session as it was.
+unsafe fn reload_script(
+    reload: &ScriptReload,
+    source: &str,
+) -> Result<*mut frida_sys::_FridaScript> {
+    let script_ptr = create_script_raw_with_options(
+        reload.session_ptr.0,
+        source,
+        reload.language,
+        reload.is_bun,
+    )
+    .map_err(|e| crate::Error::Frida(format!("Agent script creation failed: {}", e)))?;
+    register_handler_raw(script_ptr, reload.handler.clone());
+    if let Err(e) = load_script_raw(script_ptr) {
+        frida_sys::frida_unref(script_ptr as *mut c_void);
+        return Err(crate::Error::Frida(format!(
+            "Agent script failed to load: {}",
+            e
+        )));
+    }
+    Ok(script_ptr)
+}
+
 /// Handle AddPatterns on a session worker thread.
 fn handle_add_patterns(
     script_ptr: *mut frida_sys::_FridaScript,
@@ -1842,13 +2178,26 @@ fn handle_add_patterns(
             }));
         } else {
             // Native binary target
-            native_funcs.push(serde_json::json!({
+            let mut func = serde_json::json!({
                 "address": format!("0x{:x}", f.address),
                 "name": f.name,
                 "nameRaw": f.name_raw,
                 "sourceFile": f.source_file,
                 "lineNumber": f.line_number,
-            }));
+            });
+            if let Some(ref containers) = f.containers {
+                func["argLayouts"] = serde_json::json!(containers.args);
+                if let Some(ref ret) = containers.ret {
+                    func["returnLayout"] = serde_json::json!(ret);
+                }
+            }
+            if let Some(offset) = f.goid_offset {
+                func["goidOffset"] = serde_json::json!(offset);
+            }
+            if let Some(ref module) = f.module {
+                func["module"] = serde_json::json!(module);
+            }
+            native_funcs.push(func);
         }
     }
 
@@ -1876,6 +2225,8 @@ fn handle_add_patterns(
     let mode_str = match mode {
         HookMode::Full => "full",
         HookMode::Light => "light",
+        HookMode::Count => "count",
+        HookMode::Rt => "rt",
     };
 
     let mut hooks_msg = serde_json::json!({
@@ -1956,6 +2307,9 @@ fn handle_remove_patterns(
             } else {
                 entry["funcName"] = serde_json::json!(f.name);
             }
+            if let Some(ref module) = f.module {
+                entry["module"] = serde_json::json!(module);
+            }
             native_funcs.push(entry);
         }
     }
@@ -2008,7 +2362,7 @@ fn handle_set_watches(
     watches: &[WatchTarget],
     expr_watches: &[ExprWatchTarget],
 ) -> Result<()> {
-    let is_alive = unsafe { libc::kill(pid as i32, 0) == 0 };
+    let is_alive = crate::process::is_alive(pid);
     if !is_alive {
         return Err(crate::Error::WatchFailed(format!(
             "Process {} is no longer running",
@@ -2046,6 +2400,12 @@ fn handle_set_watches(
             if w.no_slide {
                 obj["noSlide"] = serde_json::json!(true);
             }
+            if let Some(ref module) = w.module {
+                obj["module"] = serde_json::json!(module);
+            }
+            if let Some(threshold) = w.delta_threshold {
+                obj["deltaThreshold"] = serde_json::json!(threshold);
+            }
             obj
         })
         .collect();
@@ -2053,12 +2413,16 @@ fn handle_set_watches(
     let expr_watch_list: Vec<serde_json::Value> = expr_watches
         .iter()
         .map(|e| {
-            serde_json::json!({
+            let mut obj = serde_json::json!({
                 "label": e.label,
                 "expr": e.expr,
                 "isGlobal": e.is_global,
                 "onPatterns": e.on_patterns,
-            })
+            });
+            if let Some(threshold) = e.delta_threshold {
+                obj["deltaThreshold"] = serde_json::json!(threshold);
+            }
+            obj
         })
         .collect();
 
@@ -2077,12 +2441,20 @@ fn handle_set_watches(
     }
 
     match signal_rx.recv_timeout(std::time::Duration::from_secs(5)) {
+        Ok(count) if (count as usize) < watches.len() + expr_watches.len() => {
+            // The agent reports 0 when it rejected the batch; the reason is in its log
+            Err(crate::Error::WatchFailed(format!(
+                "Agent armed {} of {} watches (see the session log)",
+                count,
+                watches.len() + expr_watches.len()
+            )))
+        }
         Ok(count) => {
             tracing::info!("Agent confirmed {} watches active", count);
             Ok(())
         }
         Err(_) => {
-            let still_alive = unsafe { libc::kill(pid as i32, 0) == 0 };
+            let still_alive = crate::process::is_alive(pid);
             if !still_alive {
                 tracing::warn!("Watch confirmation timeout — process {} is dead", pid);
                 Err(crate::Error::WatchFailed(format!(
@@ -2145,7 +2517,7 @@ fn handle_read_memory(
                 ));
             }
             Err(std::sync::mpsc::RecvTimeoutError::Timeout) => {
-                let alive = unsafe { libc::kill(pid as i32, 0) == 0 };
+                let alive = crate::process::is_alive(pid);
                 if !alive {
                     return Err(crate::Error::ReadFailed(
                         "Process exited before memory read completed".to_string(),
@@ -2186,7 +2558,7 @@ fn handle_write_memory(
                 ));
             }
             Err(std::sync::mpsc::RecvTimeoutError::Timeout) => {
-                let alive = unsafe { libc::kill(pid as i32, 0) == 0 };
+                let alive = crate::process::is_alive(pid);
                 if !alive {
                     return Err(crate::Error::WriteFailed(
                         "Process exited before memory write completed".to_string(),
@@ -2215,11 +2587,14 @@ fn get_ppid(pid: u32) -> Option<u32> {
 
 /// Handle a child process spawned via fork/exec.
 /// Attaches Frida to the child, loads the agent, and registers it for output capture.
+/// With a child follower set, the still-suspended child is handed over to it
+/// (the follower resumes it); otherwise the child is resumed here.
 fn handle_child_spawn(
     device: &mut frida::Device,
     child_pid: u32,
     output_registry: &OutputRegistry,
     session_ptrs: &mut HashMap<u32, *mut frida_sys::_FridaSession>,
+    child_follower: &ChildFollowerSlot,
 ) {
     // Find which session this child belongs to by checking the output registry.
     // Use the child's PPID to find the correct parent session.
@@ -2244,10 +2619,17 @@ fn handle_child_spawn(
                 None
             }
         });
-        ctx.map(|c| (c.session_id.clone(), c.event_tx.clone(), c.start_ns))
+        ctx.map(|c| {
+            (
+                c.session_id.clone(),
+                c.event_tx.clone(),
+                c.start_ns,
+                c.capture_fds.clone(),
+            )
+        })
     };
 
-    let (session_id, event_tx, start_ns) = match parent_info {
+    let (session_id, event_tx, start_ns, capture_fds) = match parent_info {
         Some(info) => info,
         None => {
             tracing::debug!(
@@ -2273,6 +2655,7 @@ fn handle_child_spawn(
         event_counter: AtomicU64::new(0),
         start_ns,
         stderr_buffer: Arc::new(Mutex::new(String::new())),
+        capture_fds: capture_fds.clone(),
     });
     if let Ok(mut reg) = output_registry.lock() {
         reg.insert(child_pid, output_ctx);
@@ -2304,7 +2687,9 @@ fn handle_child_spawn(
                             .duration_since(std::time::UNIX_EPOCH)
                             .unwrap_or_default()
                             .as_nanos() as i64,
+                        pid: Some(child_pid),
                     };
+                    let handler_template = handler.clone();
                     unsafe {
                         register_handler_raw(script_ptr, handler);
                         if let Err(e) = load_script_raw(script_ptr) {
@@ -2319,6 +2704,7 @@ fn handle_child_spawn(
                     let init_msg = serde_json::json!({
                         "type": "initialize",
                         "sessionId": session_id,
+                        "captureFds": capture_fds,
                     });
                     unsafe {
                         let _ = post_message_raw(
@@ -2328,6 +2714,25 @@ fn handle_child_spawn(
                     }
 
                     tracing::info!("Agent loaded in child process {}", child_pid);
+
+                    let follower = child_follower.lock().ok().and_then(|f| f.clone());
+                    if let Some(follower) = follower {
+                        let child = AttachedChild {
+                            session_id: session_id.clone(),
+                            pid: child_pid,
+                            script_ptr: SendScriptPtr(script_ptr),
+                            reload: ScriptReload {
+                                session_ptr: SendSessionPtr(raw_session),
+                                language: Language::Native,
+                                is_bun: false,
+                                handler: handler_template,
+                                init_msg,
+                            },
+                        };
+                        if follower.send(child).is_ok() {
+                            return;
+                        }
+                    }
                 }
                 Err(e) => {
                     tracing::error!("Failed to create script in child {}: {}", child_pid, e);
@@ -2354,6 +2759,18 @@ fn parse_event(session_id: &str, json: &serde_json::Value) -> Option<Event> {
         "pause" => EventType::Pause,
         "logpoint" => EventType::Logpoint,
         "condition_error" => EventType::ConditionError,
+        "aux_output" => EventType::AuxOutput,
+        "watchpoint" => EventType::Watchpoint,
+        "call_counts" => EventType::CallCounts,
+        "sample" => EventType::Sample,
+        "signal_safety" => EventType::SignalSafety,
+        "syscall" => EventType::Syscall,
+        "network" => EventType::Network,
+        "rt_warning" => EventType::RtWarning,
+        "panic" => EventType::Panic,
+        "exception" => EventType::Exception,
+        "signal" => EventType::Signal,
+        "lock" => EventType::Lock,
         _ => return None,
     };
 
@@ -2377,69 +2794,118 @@ fn parse_event(session_id: &str, json: &serde_json::Value) -> Option<Event> {
             thread_id: json.get("threadId")?.as_i64()?,
             event_type,
             arguments: json.get("data").cloned(),
+            channel: json
+                .get("pollId")
+                .and_then(|v| v.as_str())
+                .map(|s| s.to_string()),
             pid,
             ..Event::default()
         });
     }
 
-    if event_type == EventType::Crash {
+    if event_type == EventType::CallCounts {
         return Some(Event {
-            id: json
-                .get("id")
-                .and_then(|v| v.as_str())
-                .map(|s| s.to_string())
-                .unwrap_or_else(|| {
-                    format!(
-                        "{}-crash-{}",
-                        session_id,
-                        chrono::Utc::now().timestamp_millis()
-                    )
-                }),
+            id: json.get("id")?.as_str()?.to_string(),
             session_id: session_id.to_string(),
             timestamp_ns: json.get("timestampNs")?.as_i64()?,
-            thread_id: json.get("threadId")?.as_i64()?,
+            thread_id: 0,
             event_type,
-            // Store frameMemory/frameBase in text as JSON for later local variable resolution
-            text: {
-                let fm = json.get("frameMemory");
-                let fb = json.get("frameBase");
-                if fm.is_some() || fb.is_some() {
-                    Some(
-                        serde_json::json!({
-                            "frameMemory": fm,
-                            "frameBase": fb,
-                        })
-                        .to_string(),
-                    )
-                } else {
-                    None
-                }
-            },
+            arguments: json.get("counts").cloned(),
             pid,
+            ..Event::default()
+        });
+    }
+
+    if event_type == EventType::SignalSafety {
+        return Some(Event {
+            id: json.get("id")?.as_str()?.to_string(),
+            session_id: session_id.to_string(),
+            timestamp_ns: json.get("timestampNs")?.as_i64()?,
+            thread_id: json.get("threadId")?.as_i64()?,
+            event_type,
+            // The unsafe function called from the handler
+            function_name: json.get("functionName")?.as_str()?.to_string(),
             signal: json
                 .get("signal")
                 .and_then(|v| v.as_str())
                 .map(|s| s.to_string()),
-            fault_address: json
-                .get("faultAddress")
+            text: json
+                .get("text")
                 .and_then(|v| v.as_str())
                 .map(|s| s.to_string()),
-            registers: json.get("registers").cloned(),
+            arguments: Some(serde_json::json!({
+                "handler": json.get("handler"),
+                "handlerAddress": json.get("handlerAddress"),
+                "category": json.get("category"),
+            })),
             backtrace: json.get("backtrace").cloned(),
-            exception_type: json
-                .get("exceptionType")
+            pid,
+            ..Event::default()
+        });
+    }
+
+    if event_type == EventType::Syscall {
+        return Some(Event {
+            id: json.get("id")?.as_str()?.to_string(),
+            session_id: session_id.to_string(),
+            timestamp_ns: json.get("timestampNs")?.as_i64()?,
+            thread_id: json.get("threadId")?.as_i64()?,
+            thread_name: json
+                .get("threadName")
                 .and_then(|v| v.as_str())
                 .map(|s| s.to_string()),
-            exception_message: json
-                .get("exceptionMessage")
+            event_type,
+            // The libc call, e.g. "read"
+            function_name: json.get("functionName")?.as_str()?.to_string(),
+            arguments: Some(serde_json::json!({
+                "group": json.get("group"),
+                "fd": json.get("fd"),
+                "path": json.get("path"),
+                "address": json.get("address"),
+                "bytes": json.get("bytes"),
+                "errno": json.get("errno"),
+            })),
+            return_value: json.get("result").cloned(),
+            duration_ns: json.get("durationNs").and_then(|v| v.as_i64()),
+            text: json
+                .get("text")
                 .and_then(|v| v.as_str())
                 .map(|s| s.to_string()),
-            throw_backtrace: json.get("throwBacktrace").cloned(),
+            pid,
             ..Event::default()
         });
     }
 
-    if event_type == EventType::Stdout || event_type == EventType::Stderr {
+    if event_type == EventType::Network {
+        return Some(Event {
+            id: json.get("id")?.as_str()?.to_string(),
+            session_id: session_id.to_string(),
+            timestamp_ns: json.get("timestampNs")?.as_i64()?,
+            thread_id: json.get("threadId")?.as_i64()?,
+            event_type,
+            // The hooked call, e.g. "connect" or "SSL_write"
+            function_name: json.get("functionName")?.as_str()?.to_string(),
+            arguments: Some(serde_json::json!({
+                "kind": json.get("kind"),
+                "host": json.get("host"),
+                "port": json.get("port"),
+                "address": json.get("address"),
+                "addresses": json.get("addresses"),
+                "bytes": json.get("bytes"),
+                "direction": json.get("direction"),
+            })),
+            return_value: json.get("result").cloned(),
+            duration_ns: json.get("durationNs").and_then(|v| v.as_i64()),
+            text: json
+                .get("text")
+                .and_then(|v| v.as_str())
+                .map(|s| s.to_string()),
+            pid,
+            ..Event::default()
+        });
+    }
+
+    if event_type == EventType::RtWarning {
         return Some(Event {
             id: json.get("id")?.as_str()?.to_string(),
             session_id: session_id.to_string(),
@@ -2450,6 +2916,12 @@ fn parse_event(session_id: &str, json: &serde_json::Value) -> Option<Event> {
                 .and_then(|v| v.as_str())
                 .map(|s| s.to_string()),
             event_type,
+            // The function whose normal hook fired on the real-time thread
+            function_name: json.get("functionName")?.as_str()?.to_string(),
+            arguments: Some(serde_json::json!({
+                "policy": json.get("policy"),
+                "jsHook": json.get("jsHook"),
+            })),
             text: json
                 .get("text")
                 .and_then(|v| v.as_str())
@@ -2459,45 +2931,336 @@ fn parse_event(session_id: &str, json: &serde_json::Value) -> Option<Event> {
         });
     }
 
-    // Phase 2: Breakpoint events (Pause, Logpoint, ConditionError)
-    if event_type == EventType::Pause
-        || event_type == EventType::Logpoint
-        || event_type == EventType::ConditionError
-    {
+    if event_type == EventType::Panic {
         return Some(Event {
             id: json.get("id")?.as_str()?.to_string(),
             session_id: session_id.to_string(),
             timestamp_ns: json.get("timestampNs")?.as_i64()?,
             thread_id: json.get("threadId")?.as_i64()?,
-            thread_name: json
-                .get("threadName")
+            event_type,
+            // The hooked function: rust_panic, __assert_fail, __assert_rtn or abort
+            function_name: json.get("functionName")?.as_str()?.to_string(),
+            arguments: Some(serde_json::json!({
+                "kind": json.get("kind"),
+                "assertion": json.get("assertion"),
+                "file": json.get("file"),
+                "line": json.get("line"),
+            })),
+            exception_type: json
+                .get("exceptionType")
+                .and_then(|v| v.as_str())
+                .map(|s| s.to_string()),
+            exception_message: json
+                .get("exceptionMessage")
+                .and_then(|v| v.as_str())
+                .map(|s| s.to_string()),
+            text: json
+                .get("text")
                 .and_then(|v| v.as_str())
                 .map(|s| s.to_string()),
+            backtrace: json.get("backtrace").cloned(),
+            pid,
+            ..Event::default()
+        });
+    }
+
+    if event_type == EventType::Exception {
+        return Some(Event {
+            id: json.get("id")?.as_str()?.to_string(),
+            session_id: session_id.to_string(),
+            timestamp_ns: json.get("timestampNs")?.as_i64()?,
+            thread_id: json.get("threadId")?.as_i64()?,
             event_type,
-            breakpoint_id: json
-                .get("breakpointId")
+            // The throwing function for a throw, the catching one for a catch
+            function_name: json.get("functionName")?.as_str()?.to_string(),
+            arguments: Some(serde_json::json!({
+                "kind": json.get("kind"),
+                "throwFunction": json.get("throwFunction"),
+                "catchFunction": json.get("catchFunction"),
+                "propagation": json.get("propagation"),
+            })),
+            exception_type: json
+                .get("exceptionType")
                 .and_then(|v| v.as_str())
                 .map(|s| s.to_string()),
-            logpoint_message: json
-                .get("message")
+            exception_message: json
+                .get("exceptionMessage")
                 .and_then(|v| v.as_str())
                 .map(|s| s.to_string()),
-            function_name: json
-                .get("functionName")
+            text: json
+                .get("text")
                 .and_then(|v| v.as_str())
-                .map(|s| s.to_string())
-                .unwrap_or_default(),
-            source_file: json
-                .get("file")
+                .map(|s| s.to_string()),
+            backtrace: json.get("backtrace").cloned(),
+            // A catch or rethrow carries its throw's id
+            call_id: json
+                .get("callId")
                 .and_then(|v| v.as_str())
                 .map(|s| s.to_string()),
-            line_number: json.get("line").and_then(|v| v.as_i64()).map(|n| n as i32),
             pid,
             ..Event::default()
         });
     }
 
-    Some(Event {
+    if event_type == EventType::Signal {
+        return Some(Event {
+            id: json.get("id")?.as_str()?.to_string(),
+            session_id: session_id.to_string(),
+            timestamp_ns: json.get("timestampNs")?.as_i64()?,
+            thread_id: json.get("threadId")?.as_i64()?,
+            event_type,
+            // The handler for a delivery, the libc call (kill, raise, ...) for a send
+            function_name: json.get("functionName")?.as_str()?.to_string(),
+            signal: json
+                .get("signal")
+                .and_then(|v| v.as_str())
+                .map(|s| s.to_string()),
+            arguments: Some(serde_json::json!({
+                "kind": json.get("kind"),
+                "signo": json.get("signo"),
+                "handler": json.get("handler"),
+                "senderPid": json.get("senderPid"),
+                "senderUid": json.get("senderUid"),
+                "code": json.get("code"),
+                "targetPid": json.get("targetPid"),
+            })),
+            return_value: json.get("result").cloned(),
+            text: json
+                .get("text")
+                .and_then(|v| v.as_str())
+                .map(|s| s.to_string()),
+            pid,
+            ..Event::default()
+        });
+    }
+
+    if event_type == EventType::Lock {
+        return Some(Event {
+            id: json.get("id")?.as_str()?.to_string(),
+            session_id: session_id.to_string(),
+            timestamp_ns: json.get("timestampNs")?.as_i64()?,
+            thread_id: json.get("threadId")?.as_i64()?,
+            event_type,
+            // The call site that waited for the lock
+            function_name: json.get("functionName")?.as_str()?.to_string(),
+            // Only a wait that ended; blocked reports have none
+            duration_ns: json.get("durationNs").and_then(|v| v.as_i64()),
+            arguments: Some(serde_json::json!({
+                "kind": json.get("kind"),
+                "lock": json.get("lock"),
+                "primitive": json.get("primitive"),
+                "waitNs": json.get("waitNs"),
+                "holderThreadId": json.get("holderThreadId"),
+                "holderSite": json.get("holderSite"),
+            })),
+            text: json
+                .get("text")
+                .and_then(|v| v.as_str())
+                .map(|s| s.to_string()),
+            pid,
+            ..Event::default()
+        });
+    }
+
+    if event_type == EventType::Sample {
+        return Some(Event {
+            id: json.get("id")?.as_str()?.to_string(),
+            session_id: session_id.to_string(),
+            timestamp_ns: json.get("timestampNs")?.as_i64()?,
+            thread_id: json.get("threadId")?.as_i64()?,
+            thread_name: json
+                .get("threadName")
+                .and_then(|v| v.as_str())
+                .map(|s| s.to_string()),
+            event_type,
+            // Leaf frame, so function filters and grouping work on samples
+            function_name: json
+                .get("functionName")
+                .and_then(|v| v.as_str())
+                .map(|s| s.to_string())
+                .unwrap_or_default(),
+            source_file: json
+                .get("file")
+                .and_then(|v| v.as_str())
+                .map(|s| s.to_string()),
+            line_number: json.get("line").and_then(|v| v.as_i64()).map(|n| n as i32),
+            backtrace: json.get("backtrace").cloned(),
+            pid,
+            ..Event::default()
+        });
+    }
+
+    if event_type == EventType::Crash {
+        return Some(Event {
+            id: json
+                .get("id")
+                .and_then(|v| v.as_str())
+                .map(|s| s.to_string())
+                .unwrap_or_else(|| {
+                    format!(
+                        "{}-crash-{}",
+                        session_id,
+                        chrono::Utc::now().timestamp_millis()
+                    )
+                }),
+            session_id: session_id.to_string(),
+            timestamp_ns: json.get("timestampNs")?.as_i64()?,
+            thread_id: json.get("threadId")?.as_i64()?,
+            event_type,
+            // Store frameMemory/frameBase in text as JSON for later local variable
+            // resolution, next to the crashCapture globals. threads/modules ride
+            // along until the event writer moves them into the on-disk crash summary.
+            text: {
+                let fm = json.get("frameMemory");
+                let fb = json.get("frameBase");
+                let threads = json.get("threads");
+                let modules = json.get("modules");
+                let globals = json.get("globals");
+                if fm.is_some()
+                    || fb.is_some()
+                    || threads.is_some()
+                    || modules.is_some()
+                    || globals.is_some()
+                {
+                    Some(
+                        serde_json::json!({
+                            "frameMemory": fm,
+                            "frameBase": fb,
+                            "globals": globals,
+                            "threads": threads,
+                            "modules": modules,
+                        })
+                        .to_string(),
+                    )
+                } else {
+                    None
+                }
+            },
+            pid,
+            signal: json
+                .get("signal")
+                .and_then(|v| v.as_str())
+                .map(|s| s.to_string()),
+            fault_address: json
+                .get("faultAddress")
+                .and_then(|v| v.as_str())
+                .map(|s| s.to_string()),
+            registers: json.get("registers").cloned(),
+            backtrace: json.get("backtrace").cloned(),
+            exception_type: json
+                .get("exceptionType")
+                .and_then(|v| v.as_str())
+                .map(|s| s.to_string()),
+            exception_message: json
+                .get("exceptionMessage")
+                .and_then(|v| v.as_str())
+                .map(|s| s.to_string()),
+            throw_backtrace: json.get("throwBacktrace").cloned(),
+            ..Event::default()
+        });
+    }
+
+    if event_type == EventType::Stdout
+        || event_type == EventType::Stderr
+        || event_type == EventType::AuxOutput
+    {
+        return Some(Event {
+            id: json.get("id")?.as_str()?.to_string(),
+            session_id: session_id.to_string(),
+            timestamp_ns: json.get("timestampNs")?.as_i64()?,
+            thread_id: json.get("threadId")?.as_i64()?,
+            thread_name: json
+                .get("threadName")
+                .and_then(|v| v.as_str())
+                .map(|s| s.to_string()),
+            event_type,
+            text: json
+                .get("text")
+                .and_then(|v| v.as_str())
+                .map(|s| s.to_string()),
+            pid,
+            channel: json
+                .get("channel")
+                .and_then(|v| v.as_str())
+                .map(|s| s.to_string()),
+            ..Event::default()
+        });
+    }
+
+    if event_type == EventType::Watchpoint {
+        return Some(Event {
+            id: json.get("id")?.as_str()?.to_string(),
+            session_id: session_id.to_string(),
+            timestamp_ns: json.get("timestampNs")?.as_i64()?,
+            thread_id: json.get("threadId")?.as_i64()?,
+            event_type,
+            breakpoint_id: json
+                .get("watchpointId")
+                .and_then(|v| v.as_str())
+                .map(|s| s.to_string()),
+            text: json
+                .get("label")
+                .and_then(|v| v.as_str())
+                .map(|s| s.to_string()),
+            fault_address: json
+                .get("address")
+                .and_then(|v| v.as_str())
+                .map(|s| s.to_string()),
+            function_name: json
+                .get("functionName")
+                .and_then(|v| v.as_str())
+                .map(|s| s.to_string())
+                .unwrap_or_default(),
+            source_file: json
+                .get("file")
+                .and_then(|v| v.as_str())
+                .map(|s| s.to_string()),
+            line_number: json.get("line").and_then(|v| v.as_i64()).map(|n| n as i32),
+            backtrace: json.get("backtrace").cloned(),
+            pid,
+            ..Event::default()
+        });
+    }
+
+    // Phase 2: Breakpoint events (Pause, Logpoint, ConditionError)
+    if event_type == EventType::Pause
+        || event_type == EventType::Logpoint
+        || event_type == EventType::ConditionError
+    {
+        return Some(Event {
+            id: json.get("id")?.as_str()?.to_string(),
+            session_id: session_id.to_string(),
+            timestamp_ns: json.get("timestampNs")?.as_i64()?,
+            thread_id: json.get("threadId")?.as_i64()?,
+            thread_name: json
+                .get("threadName")
+                .and_then(|v| v.as_str())
+                .map(|s| s.to_string()),
+            event_type,
+            breakpoint_id: json
+                .get("breakpointId")
+                .and_then(|v| v.as_str())
+                .map(|s| s.to_string()),
+            logpoint_message: json
+                .get("message")
+                .and_then(|v| v.as_str())
+                .map(|s| s.to_string()),
+            function_name: json
+                .get("functionName")
+                .and_then(|v| v.as_str())
+                .map(|s| s.to_string())
+                .unwrap_or_default(),
+            source_file: json
+                .get("file")
+                .and_then(|v| v.as_str())
+                .map(|s| s.to_string()),
+            line_number: json.get("line").and_then(|v| v.as_i64()).map(|n| n as i32),
+            pid,
+            ..Event::default()
+        });
+    }
+
+    Some(Event {
         id: json.get("id")?.as_str()?.to_string(),
         session_id: session_id.to_string(),
         timestamp_ns: json.get("timestampNs")?.as_i64()?,
@@ -2506,6 +3269,11 @@ fn parse_event(session_id: &str, json: &serde_json::Value) -> Option<Event> {
             .get("threadName")
             .and_then(|v| v.as_str())
             .map(|s| s.to_string()),
+        queue_name: json
+            .get("queueName")
+            .and_then(|v| v.as_str())
+            .filter(|s| !s.is_empty())
+            .map(|s| s.to_string()),
         parent_event_id: json
             .get("parentEventId")
             .and_then(|v| v.as_str())
@@ -2527,8 +3295,21 @@ fn parse_event(session_id: &str, json: &serde_json::Value) -> Option<Event> {
         arguments: json.get("arguments").cloned(),
         return_value: json.get("returnValue").cloned(),
         duration_ns: json.get("durationNs").and_then(|v| v.as_i64()),
+        // Interpreted tracers report the exception that unwound the frame
+        exception_type: json
+            .get("exceptionType")
+            .and_then(|v| v.as_str())
+            .map(|s| s.to_string()),
+        exception_message: json
+            .get("exceptionMessage")
+            .and_then(|v| v.as_str())
+            .map(|s| s.to_string()),
         sampled: json.get("sampled").and_then(|v| v.as_bool()),
         watch_values: json.get("watchValues").cloned(),
+        call_id: json
+            .get("callId")
+            .and_then(|v| v.as_str())
+            .map(|s| s.to_string()),
         pid,
         ..Event::default()
     })
@@ -2549,6 +3330,58 @@ fn resolve_pattern<'a>(
     }
 }
 
+/// Callers suggested in place of an inlined-only function.
+const MAX_INLINED_CALLERS: usize = 5;
+
+/// For a pattern with no out-of-line function to hook: the functions it
+/// matches that only exist inlined, where they were inlined, and the callers
+/// to trace instead.
+fn inlined_warning(dwarf: &DwarfParser, pattern: &str) -> Option<String> {
+    if pattern.starts_with('@') {
+        return None;
+    }
+    match dwarf.find_inlined(pattern) {
+        Ok(calls) => inlined_message(pattern, &calls),
+        Err(e) => {
+            tracing::debug!("Inlined lookup for '{}' failed: {}", pattern, e);
+            None
+        }
+    }
+}
+
+fn inlined_message(pattern: &str, calls: &[InlinedCall]) -> Option<String> {
+    let first = calls.first()?;
+    let mut callers: Vec<&str> = calls.iter().map(|c| c.caller.as_str()).collect();
+    callers.sort_unstable();
+    callers.dedup();
+
+    let mut text = format!(
+        "'{}' matched {} but it is inlined into {}",
+        pattern, first.name, first.caller
+    );
+    if let (Some(file), Some(line)) = (&first.call_file, first.call_line) {
+        text.push_str(&format!(" at {}:{}", file, line));
+    }
+    let sites: u32 = calls.iter().map(|c| c.sites).sum();
+    if sites > 1 {
+        text.push_str(&format!(
+            " ({} inlined copies in {} functions)",
+            sites,
+            callers.len()
+        ));
+    }
+    let suggested: Vec<String> = callers
+        .iter()
+        .take(MAX_INLINED_CALLERS)
+        .map(|c| format!("\"{}\"", c))
+        .collect();
+    text.push_str(&format!(
+        " and has no entry point to hook. To trace its callers instead: debug_trace({{ add: [{}] }})",
+        suggested.join(", ")
+    ));
+    Some(text)
+}
+
 /// Monitor a spawned process for crash detection.
 /// When the process dies, checks for a crash file written by the agent's
 /// exception handler (synchronous native I/O). Falls back to ASAN parsing
@@ -2556,14 +3389,14 @@ fn resolve_pattern<'a>(
 fn process_death_monitor(
     pid: u32,
     session_id: String,
-    event_tx: mpsc::Sender<Event>,
+    event_tx: EventSender,
     crash_reported: Arc<AtomicBool>,
     start_ns: i64,
     stderr_buffer: Arc<Mutex<String>>,
 ) {
     // Poll until process is dead
     loop {
-        let alive = unsafe { libc::kill(pid as i32, 0) == 0 };
+        let alive = crate::process::is_alive(pid);
         if !alive {
             break;
         }
@@ -2680,6 +3513,14 @@ fn process_death_monitor(
     }
 
     // Last resort: check waitpid for signal-based termination
+    #[cfg(unix)]
+    report_signal_exit(pid, &session_id, start_ns, &event_tx);
+}
+
+/// Report a crash for a child killed by a fatal signal. Windows exits carry
+/// no signal; crashes there come from the agent's exception handler instead.
+#[cfg(unix)]
+fn report_signal_exit(pid: u32, session_id: &str, start_ns: i64, event_tx: &EventSender) {
     let mut status: i32 = 0;
     let result = unsafe { libc::waitpid(pid as i32, &mut status, libc::WNOHANG) };
 
@@ -2720,7 +3561,7 @@ fn process_death_monitor(
                     session_id,
                     chrono::Utc::now().timestamp_millis()
                 ),
-                session_id: session_id.clone(),
+                session_id: session_id.to_string(),
                 timestamp_ns: now_ns,
                 event_type: EventType::Crash,
                 signal: Some(signal_name.to_string()),
@@ -2925,6 +3766,7 @@ pub struct FridaSession {
     hook_manager: HookManager,
     dwarf_handle: DwarfHandle,
     image_base: u64,
+    symbol_modules: Vec<SymbolModule>,
 }
 
 /// Spawner that communicates with the coordinator and per-session worker threads
@@ -2940,6 +3782,8 @@ pub struct FridaSpawner {
     /// Without this, stop() races: coordinator unrefs the session while the worker
     /// is still unloading/unreffing the script → use-after-free SIGSEGV.
     session_worker_handles: std::sync::Mutex<HashMap<String, thread::JoinHandle<()>>>,
+    /// Where the coordinator hands newly attached children (see `set_child_follower`).
+    child_follower: ChildFollowerSlot,
     /// JoinHandle for the coordinator thread — joined on Drop to ensure Frida's
     /// global state (DeviceManager, Device, GLib) is fully cleaned up before
     /// any new FridaSpawner is created.
@@ -2949,9 +3793,11 @@ pub struct FridaSpawner {
 impl FridaSpawner {
     pub fn new() -> Self {
         let (cmd_tx, cmd_rx) = std::sync::mpsc::channel();
+        let child_follower: ChildFollowerSlot = Arc::new(Mutex::new(None));
 
+        let coordinator_follower = Arc::clone(&child_follower);
         let coordinator_handle = thread::spawn(move || {
-            coordinator_worker(cmd_rx);
+            coordinator_worker(cmd_rx, coordinator_follower);
         });
 
         Self {
@@ -2959,6 +3805,7 @@ impl FridaSpawner {
             coordinator_tx: cmd_tx,
             session_workers: std::sync::RwLock::new(HashMap::new()),
             session_worker_handles: std::sync::Mutex::new(HashMap::new()),
+            child_follower,
             coordinator_handle: std::sync::Mutex::new(Some(coordinator_handle)),
         }
     }
@@ -2973,10 +3820,11 @@ impl FridaSpawner {
         env: Option<&HashMap<String, String>>,
         dwarf_handle: DwarfHandle,
         image_base: u64,
-        event_sender: mpsc::Sender<Event>,
+        event_sender: EventSender,
         defer_resume: bool,
         pause_notify_tx: Option<PauseNotifyTx>,
         language: Language,
+        capture_fds: Vec<i32>,
     ) -> Result<u32> {
         let (response_tx, response_rx) = oneshot::channel();
 
@@ -2991,6 +3839,7 @@ impl FridaSpawner {
                 defer_resume,
                 pause_notify_tx,
                 language,
+                capture_fds,
                 response: response_tx,
             })
             .map_err(|_| crate::Error::Frida("Coordinator thread died".to_string()))?;
@@ -3009,9 +3858,7 @@ impl FridaSpawner {
             session_worker(
                 sid,
                 spawn_result.script_ptr,
-                spawn_result.hooks_ready,
-                spawn_result.read_response,
-                spawn_result.write_response,
+                spawn_result.reload,
                 pid,
                 session_rx,
             );
@@ -3032,6 +3879,7 @@ impl FridaSpawner {
             hook_manager: HookManager::new(),
             dwarf_handle,
             image_base,
+            symbol_modules: Vec::new(),
         };
 
         self.sessions
@@ -3042,30 +3890,91 @@ impl FridaSpawner {
         Ok(pid)
     }
 
-    /// Resume a previously suspended process (used with defer_resume=true).
-    pub async fn resume(&self, pid: u32) -> Result<()> {
-        let (response_tx, response_rx) = oneshot::channel();
-        self.coordinator_tx
-            .send(CoordinatorCommand::Resume {
-                pid,
-                response: response_tx,
-            })
-            .map_err(|_| crate::Error::Frida("Coordinator thread died".to_string()))?;
-
-        response_rx
-            .await
-            .map_err(|_| crate::Error::Frida("Coordinator response lost".to_string()))?
+    /// Hand children attached by spawn gating to `tx` instead of resuming them
+    /// right away. The receiver must eventually `resume` every child it gets.
+    pub fn set_child_follower(&self, tx: tokio::sync::mpsc::UnboundedSender<AttachedChild>) {
+        if let Ok(mut slot) = self.child_follower.lock() {
+            *slot = Some(tx);
+        }
     }
 
-    pub async fn add_patterns(
+    /// Start a worker for an attached child so patterns can be hooked in it.
+    /// The child's hook state lives under `child_session_key` (project root
+    /// taken from the parent session); returns that key for `add_patterns`.
+    pub fn follow_child(
         &self,
-        session_id: &str,
-        patterns: &[String],
-        serialization_depth: Option<u32>,
-        resolver: Option<&dyn crate::symbols::SymbolResolver>,
+        child: AttachedChild,
+        dwarf_handle: DwarfHandle,
+        image_base: u64,
+    ) -> Result<String> {
+        let project_root = self
+            .sessions
+            .read()
+            .unwrap()
+            .get(&child.session_id)
+            .map(|s| s.project_root.clone())
+            .ok_or_else(|| crate::Error::SessionNotFound(child.session_id.clone()))?;
+        let key = child_session_key(&child.session_id, child.pid);
+
+        let (worker_tx, worker_rx) = std::sync::mpsc::channel();
+        let worker_key = key.clone();
+        let pid = child.pid;
+        let handle = thread::spawn(move || {
+            session_worker(worker_key, child.script_ptr, child.reload, pid, worker_rx);
+        });
+        self.session_workers
+            .write()
+            .unwrap()
+            .insert(key.clone(), worker_tx);
+        self.session_worker_handles
+            .lock()
+            .unwrap()
+            .insert(key.clone(), handle);
+        self.sessions.write().unwrap().insert(
+            key.clone(),
+            FridaSession {
+                project_root,
+                hook_manager: HookManager::new(),
+                dwarf_handle,
+                image_base,
+                symbol_modules: Vec::new(),
+            },
+        );
+        Ok(key)
+    }
+
+    /// Index `modules` alongside the session's main binary for pattern matching.
+    pub fn set_symbol_modules(&self, session_id: &str, modules: Vec<SymbolModule>) {
+        if let Some(session) = self.sessions.write().unwrap().get_mut(session_id) {
+            session.symbol_modules = modules;
+        }
+    }
+
+    /// Resume a previously suspended process (used with defer_resume=true).
+    pub async fn resume(&self, pid: u32) -> Result<()> {
+        let (response_tx, response_rx) = oneshot::channel();
+        self.coordinator_tx
+            .send(CoordinatorCommand::Resume {
+                pid,
+                response: response_tx,
+            })
+            .map_err(|_| crate::Error::Frida("Coordinator thread died".to_string()))?;
+
+        response_rx
+            .await
+            .map_err(|_| crate::Error::Frida("Coordinator response lost".to_string()))?
+    }
+
+    pub async fn add_patterns(
+        &self,
+        session_id: &str,
+        patterns: &[String],
+        serialization_depth: Option<u32>,
+        resolver: Option<&dyn crate::symbols::SymbolResolver>,
+        trace_mode: crate::mcp::TraceMode,
     ) -> Result<HookResult> {
         // Brief write lock: update hook_manager state and extract session data
-        let (mut dwarf_handle, image_base, project_root) = {
+        let (mut dwarf_handle, image_base, project_root, symbol_modules) = {
             let mut sessions = self.sessions.write().unwrap();
             let session = sessions
                 .get_mut(session_id)
@@ -3075,24 +3984,57 @@ impl FridaSpawner {
                 session.dwarf_handle.clone(),
                 session.image_base,
                 session.project_root.clone(),
+                session.symbol_modules.clone(),
             )
         };
 
         // Group functions by mode — no lock held during expensive DWARF/resolver work
         let mut full_funcs: Vec<FunctionTarget> = Vec::new();
         let mut light_funcs: Vec<FunctionTarget> = Vec::new();
+        let mut module_warnings: Vec<String> = Vec::new();
+        let modules = ready_modules(symbol_modules, &mut module_warnings).await;
+
+        for pattern in patterns.iter().filter(|p| is_objc_pattern(p)) {
+            let targets = match self.resolve_objc(session_id, pattern).await {
+                Ok(targets) => targets,
+                Err(e) => {
+                    module_warnings.push(format!("'{}' was not resolved: {}", pattern, e));
+                    continue;
+                }
+            };
+            let mode = HookManager::classify_with_count(pattern, targets.len());
+            tracing::info!(
+                "Pattern '{}' -> {:?} mode ({} methods, ObjC runtime)",
+                pattern,
+                mode,
+                targets.len()
+            );
+            if mode == HookMode::Full {
+                full_funcs.extend(targets);
+            } else {
+                light_funcs.extend(targets);
+            }
+        }
+        let native_patterns: Vec<String> = patterns
+            .iter()
+            .filter(|p| !is_objc_pattern(p))
+            .cloned()
+            .collect();
+        let patterns = native_patterns.as_slice();
 
         // Use SymbolResolver if available, otherwise fall back to DWARF
         if let Some(resolver) = resolver {
             use std::path::Path;
             for pattern in patterns {
                 let targets = resolver.resolve_pattern(pattern, Path::new(&project_root))?;
-                let mode = HookManager::classify_with_count(pattern, targets.len());
+                let extra = module_targets(&modules, pattern, &project_root);
+                let mode = HookManager::classify_with_count(pattern, targets.len() + extra.len());
                 tracing::info!(
-                    "Pattern '{}' -> {:?} mode ({} targets, resolver)",
+                    "Pattern '{}' -> {:?} mode ({} targets, resolver; {} in symbol modules)",
                     pattern,
                     mode,
-                    targets.len()
+                    targets.len(),
+                    extra.len()
                 );
 
                 let target_list = if mode == HookMode::Full {
@@ -3100,42 +4042,18 @@ impl FridaSpawner {
                 } else {
                     &mut light_funcs
                 };
+                target_list.extend(extra);
                 for target in targets {
-                    match target {
-                        crate::symbols::ResolvedTarget::SourceLocation { file, line, name } => {
-                            target_list.push(FunctionTarget {
-                                address: 0, // No address for interpreted
-                                name: name.clone(),
-                                name_raw: Some(name.clone()),
-                                source_file: Some(file),
-                                line_number: Some(line),
-                            });
-                        }
-                        crate::symbols::ResolvedTarget::Address {
-                            address,
-                            name,
-                            name_raw,
-                            file,
-                            line,
-                        } => {
-                            // Skip functions with address 0 — these are declarations or
-                            // fully-inlined functions with no out-of-line body to hook.
-                            if address == 0 {
-                                tracing::debug!(
-                                    "Skipping unhookable function {} (address 0x0)",
-                                    name
-                                );
-                                continue;
-                            }
-                            target_list.push(FunctionTarget {
-                                address,
-                                name: name.clone(),
-                                name_raw: name_raw.clone(),
-                                source_file: file.clone(),
-                                line_number: line,
-                            });
-                        }
+                    // Skip functions with address 0 — these are declarations or
+                    // fully-inlined functions with no out-of-line body to hook.
+                    if let crate::symbols::ResolvedTarget::Address {
+                        address: 0, name, ..
+                    } = &target
+                    {
+                        tracing::debug!("Skipping unhookable function {} (address 0x0)", name);
+                        continue;
                     }
+                    target_list.push(FunctionTarget::from(target));
                 }
             }
         } else {
@@ -3143,12 +4061,17 @@ impl FridaSpawner {
             let dwarf = dwarf_handle.get().await?;
             for pattern in patterns {
                 let matches: Vec<&FunctionInfo> = resolve_pattern(&dwarf, pattern, &project_root);
-                let mode = HookManager::classify_with_count(pattern, matches.len());
+                let extra = module_targets(&modules, pattern, &project_root);
+                if extra.is_empty() && matches.iter().all(|f| f.low_pc == 0) {
+                    module_warnings.extend(inlined_warning(&dwarf, pattern));
+                }
+                let mode = HookManager::classify_with_count(pattern, matches.len() + extra.len());
                 tracing::info!(
-                    "Pattern '{}' -> {:?} mode ({} functions, DWARF)",
+                    "Pattern '{}' -> {:?} mode ({} functions, DWARF; {} in symbol modules)",
                     pattern,
                     mode,
-                    matches.len()
+                    matches.len(),
+                    extra.len()
                 );
 
                 let target = if mode == HookMode::Full {
@@ -3156,6 +4079,7 @@ impl FridaSpawner {
                 } else {
                     &mut light_funcs
                 };
+                target.extend(extra);
                 for func in matches {
                     if func.low_pc == 0 {
                         tracing::debug!("Skipping unhookable function {} (low_pc 0x0)", func.name);
@@ -3166,8 +4090,17 @@ impl FridaSpawner {
             }
         }
 
+        if trace_mode == crate::mcp::TraceMode::Count {
+            full_funcs.append(&mut light_funcs);
+            let mut result = self
+                .add_count_hooks(session_id, full_funcs, image_base)
+                .await?;
+            result.warnings.splice(0..0, module_warnings);
+            return Ok(result);
+        }
+
         let matched = (full_funcs.len() + light_funcs.len()) as u32;
-        let mut warnings: Vec<String> = Vec::new();
+        let mut warnings: Vec<String> = module_warnings;
 
         // Enforce hook cap — truncate light funcs first (cheaper to skip), then full
         let total = full_funcs.len() + light_funcs.len();
@@ -3194,13 +4127,70 @@ impl FridaSpawner {
             );
         }
 
+        // Container-aware argument decoding only matters once the caller opted
+        // into deep serialization; it costs a DWARF re-read, so do it per batch.
+        if serialization_depth.is_some() {
+            let addresses: std::collections::HashSet<u64> = full_funcs
+                .iter()
+                .filter(|f| f.address != 0 && f.module.is_none())
+                .map(|f| f.address)
+                .collect();
+            if !addresses.is_empty() {
+                if let Ok(dwarf) = dwarf_handle.get().await {
+                    match dwarf.resolve_container_args(&addresses) {
+                        Ok(mut layouts) => {
+                            for f in full_funcs.iter_mut().filter(|f| f.module.is_none()) {
+                                f.containers = layouts.remove(&f.address);
+                            }
+                        }
+                        Err(e) => {
+                            tracing::debug!("Container layout resolution skipped: {}", e)
+                        }
+                    }
+                }
+            }
+        }
+
+        // Go functions carry the goid offset so events name their goroutine
+        if full_funcs
+            .iter()
+            .chain(light_funcs.iter())
+            .any(|f| f.address != 0)
+        {
+            if let Ok(dwarf) = dwarf_handle.get().await {
+                if let Some(offset) = dwarf.goroutine_id_offset() {
+                    for f in full_funcs.iter_mut().chain(light_funcs.iter_mut()) {
+                        if f.module.is_none() && dwarf.is_go_function(f.address) {
+                            f.goid_offset = Some(offset);
+                        }
+                    }
+                }
+            }
+        }
+
         // image_base already extracted above from sessions lock
         let mut total_hooks = 0u32;
 
-        // Send chunks for both modes (serialization_depth only on the first chunk overall)
+        // RT hooks are native only: an interpreted target would run JS on the thread
+        let rt = trace_mode == crate::mcp::TraceMode::Rt;
+        let batches: Vec<(Vec<FunctionTarget>, HookMode)> = if rt {
+            full_funcs.append(&mut light_funcs);
+            let (native, interpreted): (Vec<_>, Vec<_>) =
+                full_funcs.into_iter().partition(|f| f.address != 0);
+            if !interpreted.is_empty() {
+                warnings.push(format!(
+                    "Mode 'rt' hooks native functions only; {} interpreted target(s) were not hooked. \
+                     Trace them without mode 'rt' instead.",
+                    interpreted.len()
+                ));
+            }
+            vec![(native, HookMode::Rt)]
+        } else {
+            vec![(full_funcs, HookMode::Full), (light_funcs, HookMode::Light)]
+        };
+
+        // Send chunks for each mode (serialization_depth only on the first chunk overall)
         let mut depth_sent = false;
-        let batches: [(Vec<FunctionTarget>, HookMode); 2] =
-            [(full_funcs, HookMode::Full), (light_funcs, HookMode::Light)];
 
         'outer: for (funcs, mode) in &batches {
             for chunk in funcs.chunks(CHUNK_SIZE) {
@@ -3231,6 +4221,63 @@ impl FridaSpawner {
         })
     }
 
+    /// Install counter-only hooks. They skip the ring buffer and argument
+    /// decoding, so the cap is far above the event-tracing one.
+    async fn add_count_hooks(
+        &self,
+        session_id: &str,
+        funcs: Vec<FunctionTarget>,
+        image_base: u64,
+    ) -> Result<HookResult> {
+        let matched = funcs.len() as u32;
+        let mut warnings: Vec<String> = Vec::new();
+
+        let (mut native, interpreted): (Vec<_>, Vec<_>) =
+            funcs.into_iter().partition(|f| f.address != 0);
+        if !interpreted.is_empty() {
+            warnings.push(format!(
+                "Count mode hooks native functions only; {} interpreted target(s) were not hooked. \
+                 Trace them without mode 'count' instead.",
+                interpreted.len()
+            ));
+        }
+        if native.len() > crate::mcp::MAX_COUNT_HOOKS_PER_CALL {
+            warnings.push(format!(
+                "Pattern matched {} functions (count-mode limit: {}). Only {} were hooked.",
+                native.len(),
+                crate::mcp::MAX_COUNT_HOOKS_PER_CALL,
+                crate::mcp::MAX_COUNT_HOOKS_PER_CALL
+            ));
+            native.truncate(crate::mcp::MAX_COUNT_HOOKS_PER_CALL);
+        }
+
+        let mut total_hooks = 0u32;
+        for chunk in native.chunks(COUNT_CHUNK_SIZE) {
+            match self
+                .send_add_chunk(
+                    session_id,
+                    chunk.to_vec(),
+                    image_base,
+                    HookMode::Count,
+                    None,
+                )
+                .await
+            {
+                Ok(count) => total_hooks = count,
+                Err(e) => {
+                    warnings.push(format!("Hook installation error: {}", e));
+                    break;
+                }
+            }
+        }
+
+        Ok(HookResult {
+            installed: total_hooks,
+            matched,
+            warnings,
+        })
+    }
+
     async fn send_add_chunk(
         &self,
         session_id: &str,
@@ -3269,49 +4316,41 @@ impl FridaSpawner {
         resolver: Option<&dyn crate::symbols::SymbolResolver>,
     ) -> Result<u32> {
         // Brief lock to extract session data needed for resolution
-        let (mut dwarf_handle, project_root) = {
+        let (mut dwarf_handle, project_root, symbol_modules) = {
             let sessions = self.sessions.read().unwrap();
             let session = sessions
                 .get(session_id)
                 .ok_or_else(|| crate::Error::SessionNotFound(session_id.to_string()))?;
-            (session.dwarf_handle.clone(), session.project_root.clone())
+            (
+                session.dwarf_handle.clone(),
+                session.project_root.clone(),
+                session.symbol_modules.clone(),
+            )
         };
 
         let mut functions: Vec<FunctionTarget> = Vec::new();
+        for pattern in patterns.iter().filter(|p| is_objc_pattern(p)) {
+            functions.extend(self.resolve_objc(session_id, pattern).await?);
+        }
+        let all_patterns = patterns;
+        let native_patterns: Vec<String> = patterns
+            .iter()
+            .filter(|p| !is_objc_pattern(p))
+            .cloned()
+            .collect();
+        let patterns = native_patterns.as_slice();
+
+        let modules = ready_modules(symbol_modules, &mut Vec::new()).await;
+        for pattern in patterns {
+            functions.extend(module_targets(&modules, pattern, &project_root));
+        }
 
         if let Some(resolver) = resolver {
             // For interpreted languages (Python, etc.) — use SymbolResolver
             use std::path::Path;
             for pattern in patterns {
                 let targets = resolver.resolve_pattern(pattern, Path::new(&project_root))?;
-                for target in targets {
-                    match target {
-                        crate::symbols::ResolvedTarget::SourceLocation { file, line, name } => {
-                            functions.push(FunctionTarget {
-                                address: 0,
-                                name: name.clone(),
-                                name_raw: Some(name.clone()),
-                                source_file: Some(file),
-                                line_number: Some(line),
-                            });
-                        }
-                        crate::symbols::ResolvedTarget::Address {
-                            address,
-                            name,
-                            name_raw,
-                            file,
-                            line,
-                        } => {
-                            functions.push(FunctionTarget {
-                                address,
-                                name: name.clone(),
-                                name_raw: name_raw.clone(),
-                                source_file: file.clone(),
-                                line_number: line,
-                            });
-                        }
-                    }
-                }
+                functions.extend(targets.into_iter().map(FunctionTarget::from));
             }
         } else {
             // For native binaries — use DWARF
@@ -3327,7 +4366,7 @@ impl FridaSpawner {
         {
             let mut sessions = self.sessions.write().unwrap();
             if let Some(session) = sessions.get_mut(session_id) {
-                session.hook_manager.remove_patterns(patterns);
+                session.hook_manager.remove_patterns(all_patterns);
             }
         }
 
@@ -3351,6 +4390,121 @@ impl FridaSpawner {
             .map_err(|_| crate::Error::Frida("Session worker response lost".to_string()))?
     }
 
+    /// The functions each of `patterns` would hook, resolved the way
+    /// `add_patterns` resolves them, without touching the session's hooks.
+    /// ObjC patterns that can't be looked up become warnings.
+    pub async fn preview_patterns(
+        &self,
+        session_id: &str,
+        patterns: &[String],
+        resolver: Option<&dyn crate::symbols::SymbolResolver>,
+    ) -> Result<crate::mcp::DebugTracePreviewResponse> {
+        let (mut dwarf_handle, project_root, symbol_modules) = {
+            let sessions = self.sessions.read().unwrap();
+            let session = sessions
+                .get(session_id)
+                .ok_or_else(|| crate::Error::SessionNotFound(session_id.to_string()))?;
+            (
+                session.dwarf_handle.clone(),
+                session.project_root.clone(),
+                session.symbol_modules.clone(),
+            )
+        };
+
+        let mut warnings = Vec::new();
+        let modules = ready_modules(symbol_modules, &mut warnings).await;
+        let mut previews = Vec::with_capacity(patterns.len());
+        // The same function matched by two patterns is hooked once
+        let mut distinct = std::collections::HashSet::new();
+        for pattern in patterns {
+            let mut targets = if is_objc_pattern(pattern) {
+                match self.resolve_objc(session_id, pattern).await {
+                    Ok(targets) => targets,
+                    Err(e) => {
+                        warnings.push(format!("'{}' was not resolved: {}", pattern, e));
+                        Vec::new()
+                    }
+                }
+            } else {
+                let mut targets = module_targets(&modules, pattern, &project_root);
+                if let Some(resolver) = resolver {
+                    let resolved =
+                        resolver.resolve_pattern(pattern, std::path::Path::new(&project_root))?;
+                    targets.extend(
+                        resolved
+                            .into_iter()
+                            // Declarations and fully-inlined functions aren't hooked
+                            .filter(|target| {
+                                !matches!(
+                                    target,
+                                    crate::symbols::ResolvedTarget::Address { address: 0, .. }
+                                )
+                            })
+                            .map(FunctionTarget::from),
+                    );
+                } else {
+                    let dwarf = dwarf_handle.get().await?;
+                    targets.extend(
+                        resolve_pattern(&dwarf, pattern, &project_root)
+                            .into_iter()
+                            .filter(|f| f.low_pc != 0)
+                            .map(FunctionTarget::from),
+                    );
+                    if targets.is_empty() {
+                        warnings.extend(inlined_warning(&dwarf, pattern));
+                    }
+                }
+                targets
+            };
+            targets.sort_by(|a, b| a.name.cmp(&b.name));
+            distinct.extend(
+                targets
+                    .iter()
+                    .map(|t| (t.module.clone(), t.address, t.name.clone())),
+            );
+            let functions = targets
+                .into_iter()
+                .map(|t| crate::mcp::PreviewFunction {
+                    name: t.name,
+                    file: t.source_file,
+                    line: t.line_number,
+                    module: t.module,
+                })
+                .collect();
+            previews.push(crate::mcp::PatternPreview::new(pattern.clone(), functions));
+        }
+
+        if distinct.len() > MAX_HOOKS_PER_CALL {
+            warnings.push(format!(
+                "These patterns match {} functions; one debug_trace call hooks at most {} \
+                 (count mode: {}). Narrow them, e.g. with @file:, before adding.",
+                distinct.len(),
+                MAX_HOOKS_PER_CALL,
+                crate::mcp::MAX_COUNT_HOOKS_PER_CALL
+            ));
+        }
+        Ok(crate::mcp::DebugTracePreviewResponse {
+            patterns: previews,
+            total_functions: distinct.len(),
+            hook_limit: MAX_HOOKS_PER_CALL,
+            warnings,
+        })
+    }
+
+    /// Look an Objective-C pattern up in the process's ObjC runtime. The
+    /// agent answers with module-relative implementation addresses, hooked
+    /// the same way as symbol-module targets.
+    async fn resolve_objc(&self, session_id: &str, pattern: &str) -> Result<Vec<FunctionTarget>> {
+        let message = serde_json::json!({ "type": "resolve_objc", "pattern": pattern });
+        let response = self
+            .read_memory(session_id, serde_json::to_string(&message)?)
+            .await?;
+        if let Some(err) = response.get("error").and_then(|v| v.as_str()) {
+            return Err(crate::Error::Frida(err.to_string()));
+        }
+        Ok(objc_targets(&response))
+    }
+
     pub async fn read_memory(
         &self,
         session_id: &str,
@@ -3401,13 +4555,8 @@ impl FridaSpawner {
             .map_err(|_| crate::Error::Frida("Session worker response lost".to_string()))?
     }
 
-    pub async fn stop(&self, session_id: &str) -> Result<()> {
-        self.sessions.write().unwrap().remove(session_id);
-
-        // Phase 1: Shut down session worker (unloads + unrefs script).
-        // CRITICAL: We must wait for the worker thread to finish before Phase 2,
-        // because the coordinator unrefs the Frida *session* GObject. If the worker
-        // is still unreffing the *script* (child of session), that's a use-after-free.
+    /// Stop a session worker and wait (bounded) for its thread to exit.
+    async fn shutdown_worker(&self, session_id: &str) {
         if let Some(worker_tx) = self.session_workers.write().unwrap().remove(session_id) {
             let _ = worker_tx.send(SessionCommand::Shutdown);
         }
@@ -3430,6 +4579,27 @@ impl FridaSpawner {
                 Err(_) => tracing::warn!("Timed out waiting for session worker {} to exit (5s) — proceeding with cleanup", session_id),
             }
         }
+    }
+
+    pub async fn stop(&self, session_id: &str) -> Result<()> {
+        // Phase 1: Shut down session workers (unloads + unrefs scripts), followed
+        // children first. CRITICAL: We must wait for the worker threads to finish
+        // before Phase 2, because the coordinator unrefs the Frida *session* GObject.
+        // If a worker is still unreffing the *script* (child of session), that's a
+        // use-after-free.
+        let child_prefix = format!("{}#child-", session_id);
+        let child_keys: Vec<String> = self
+            .session_workers
+            .read()
+            .unwrap()
+            .keys()
+            .filter(|k| k.starts_with(&child_prefix))
+            .cloned()
+            .collect();
+        for key in child_keys.iter().map(String::as_str).chain([session_id]) {
+            self.sessions.write().unwrap().remove(key);
+            self.shutdown_worker(key).await;
+        }
 
         // Phase 2: Kill processes via coordinator (device.kill)
         let (response_tx, response_rx) = oneshot::channel();
@@ -3492,6 +4662,34 @@ impl FridaSpawner {
             .unwrap_or_default()
     }
 
+    /// Replace the session's agent script with `source` (the built-in agent
+    /// when None). Hook state is reset: the caller re-applies instrumentation.
+    pub async fn reload_agent(&self, session_id: &str, source: Option<String>) -> Result<()> {
+        let (response_tx, response_rx) = oneshot::channel();
+
+        {
+            let workers = self.session_workers.read().unwrap();
+            let worker_tx = workers
+                .get(session_id)
+                .ok_or_else(|| crate::Error::SessionNotFound(session_id.to_string()))?;
+            worker_tx
+                .send(SessionCommand::ReloadAgent {
+                    source: source.unwrap_or_else(|| AGENT_CODE.to_string()),
+                    response: response_tx,
+                })
+                .map_err(|_| crate::Error::Frida("Session worker died".to_string()))?;
+        }
+
+        response_rx
+            .await
+            .map_err(|_| crate::Error::Frida("Session worker response lost".to_string()))??;
+
+        if let Some(session) = self.sessions.write().unwrap().get_mut(session_id) {
+            session.hook_manager = HookManager::new();
+        }
+        Ok(())
+    }
+
     // Phase 2: Breakpoint support
     /// Send a hook setup message (breakpoint or logpoint) to the agent.
     /// Both use the same SessionCommand since the message type field
@@ -3650,6 +4848,138 @@ mod tests {
     use super::*;
     use serde_json::json;
 
+    #[test]
+    fn test_module_targets_are_module_relative() {
+        let dwarf = Arc::new(DwarfParser {
+            functions: vec![FunctionInfo {
+                name: "engine::render".to_string(),
+                name_raw: None,
+                low_pc: 0x11040,
+                high_pc: 0x11080,
+                source_file: None,
+                line_number: None,
+            }],
+            functions_by_name: HashMap::new(),
+            functions_by_addr: vec![],
+            variables: vec![],
+            variables_by_name: HashMap::new(),
+            struct_members: Mutex::new(HashMap::new()),
+            lazy_struct_info: HashMap::new(),
+            type_layouts: Mutex::new(HashMap::new()),
+            function_params: Mutex::new(HashMap::new()),
+            line_table: Mutex::new(None),
+            image_base: 0x10000,
+            binary_path: None,
+            go_functions: Default::default(),
+            goid_offset: None,
+            swift: false,
+        });
+        let module = SymbolModule {
+            path: "/opt/lib/libengine.dylib".to_string(),
+            name: "libengine.dylib".to_string(),
+            image_base: 0x10000,
+            dwarf_handle: DwarfHandle::ready(Arc::clone(&dwarf)),
+        };
+        let modules = vec![(module, dwarf)];
+
+        let targets = module_targets(&modules, "engine::*", "/tmp");
+        assert_eq!(targets.len(), 1);
+        assert_eq!(targets[0].address, 0x1040);
+        assert_eq!(targets[0].module.as_deref(), Some("libengine.dylib"));
+        assert!(module_targets(&modules, "audio::*", "/tmp").is_empty());
+    }
+
+    #[test]
+    fn test_inlined_warning_suggests_callers() {
+        let call = |name: &str, caller: &str, line: u32, sites: u32| InlinedCall {
+            name: name.to_string(),
+            caller: caller.to_string(),
+            call_file: Some("/proj/src/mixer.rs".to_string()),
+            call_line: Some(line),
+            sites,
+        };
+        let calls = vec![
+            call("audio::clamp", "audio::Mixer::render", 42, 3),
+            call("audio::clamp", "audio::Mixer::tick", 90, 1),
+        ];
+
+        assert_eq!(
+            inlined_message("audio::clamp", &calls).unwrap(),
+            "'audio::clamp' matched audio::clamp but it is inlined into audio::Mixer::render \
+             at /proj/src/mixer.rs:42 (4 inlined copies in 2 functions) and has no entry point \
+             to hook. To trace its callers instead: \
+             debug_trace({ add: [\"audio::Mixer::render\", \"audio::Mixer::tick\"] })"
+        );
+        assert!(inlined_message("audio::mix", &[]).is_none());
+        let dwarf = DwarfParser {
+            functions: vec![],
+            functions_by_name: HashMap::new(),
+            functions_by_addr: vec![],
+            variables: vec![],
+            variables_by_name: HashMap::new(),
+            struct_members: Mutex::new(HashMap::new()),
+            lazy_struct_info: HashMap::new(),
+            type_layouts: Mutex::new(HashMap::new()),
+            function_params: Mutex::new(HashMap::new()),
+            line_table: Mutex::new(None),
+            image_base: 0,
+            binary_path: None,
+            go_functions: Default::default(),
+            goid_offset: None,
+            swift: false,
+        };
+        assert!(inlined_warning(&dwarf, "@file:mixer.rs").is_none());
+    }
+
+    #[test]
+    fn test_objc_targets_from_agent_response() {
+        let response = json!({ "targets": [
+            { "name": "-[AppDelegate applicationDidFinishLaunching:]", "module": "MyApp", "offset": "0x3f20" },
+            { "name": "-[AppDelegate broken]", "module": "MyApp" },
+        ]});
+        let targets = objc_targets(&response);
+        assert_eq!(targets.len(), 1);
+        assert_eq!(targets[0].address, 0x3f20);
+        assert_eq!(targets[0].module.as_deref(), Some("MyApp"));
+        assert!(objc_targets(&json!({ "error": "no ObjC runtime" })).is_empty());
+    }
+
+    #[test]
+    fn test_child_session_key_and_executable() {
+        assert_eq!(child_session_key("app-1", 4242), "app-1#child-4242");
+        if cfg!(any(target_os = "linux", target_os = "macos")) {
+            let exe = process_executable(std::process::id()).unwrap();
+            assert_eq!(
+                exe.canonicalize().unwrap(),
+                std::env::current_exe().unwrap().canonicalize().unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_event_function_exit_with_exception() {
+        let event = parse_event(
+            "s1",
+            &serde_json::json!({
+                "id": "py-1-2",
+                "timestampNs": 1000,
+                "threadId": 7,
+                "eventType": "function_exit",
+                "functionName": "parse_config",
+                "sourceFile": "app/config.py",
+                "lineNumber": 12,
+                "durationNs": 5000,
+                "exceptionType": "KeyError",
+                "exceptionMessage": "'port'"
+            }),
+        )
+        .unwrap();
+        assert_eq!(event.event_type, EventType::FunctionExit);
+        assert_eq!(event.exception_type.as_deref(), Some("KeyError"));
+        assert_eq!(event.exception_message.as_deref(), Some("'port'"));
+        assert!(event.return_value.is_none());
+    }
+
     #[test]
     fn test_parse_event_stdout() {
         let event = parse_event(
@@ -3690,6 +5020,388 @@ mod tests {
         assert_eq!(e.text.as_deref(), Some("Error: crash\n"));
     }
 
+    #[test]
+    fn test_parse_event_aux_output() {
+        let event = parse_event(
+            "session-1",
+            &json!({
+                "id": "evt-3",
+                "timestampNs": 3000,
+                "threadId": 1,
+                "eventType": "aux_output",
+                "channel": "fd:3",
+                "text": "diag\n"
+            }),
+        );
+
+        let e = event.expect("should parse aux_output event");
+        assert_eq!(e.event_type, EventType::AuxOutput);
+        assert_eq!(e.channel.as_deref(), Some("fd:3"));
+        assert_eq!(e.text.as_deref(), Some("diag\n"));
+    }
+
+    #[test]
+    fn test_parse_event_watchpoint() {
+        let event = parse_event(
+            "session-1",
+            &json!({
+                "id": "evt-4",
+                "timestampNs": 4000,
+                "threadId": 9,
+                "eventType": "watchpoint",
+                "watchpointId": "wp-1",
+                "label": "g_counter",
+                "address": "0x100008000",
+                "functionName": "increment",
+                "backtrace": [{ "address": "0x100003f00", "name": "increment" }]
+            }),
+        );
+
+        let e = event.expect("should parse watchpoint event");
+        assert_eq!(e.event_type, EventType::Watchpoint);
+        assert_eq!(e.breakpoint_id.as_deref(), Some("wp-1"));
+        assert_eq!(e.text.as_deref(), Some("g_counter"));
+        assert_eq!(e.fault_address.as_deref(), Some("0x100008000"));
+        assert_eq!(e.function_name, "increment");
+        assert!(e.backtrace.is_some());
+    }
+
+    #[test]
+    fn test_parse_event_call_counts() {
+        let event = parse_event(
+            "session-1",
+            &json!({
+                "id": "session-1-counts-2",
+                "timestampNs": 5000,
+                "eventType": "call_counts",
+                "pid": 77,
+                "counts": [{ "function": "audio::process", "address": "0x1000", "count": 48000 }]
+            }),
+        );
+
+        let e = event.expect("should parse call_counts event");
+        assert_eq!(e.event_type, EventType::CallCounts);
+        assert_eq!(e.pid, Some(77));
+        assert_eq!(e.arguments.unwrap()[0]["count"], 48000);
+    }
+
+    #[test]
+    fn test_parse_event_sample() {
+        let event = parse_event(
+            "session-1",
+            &json!({
+                "id": "session-1-sample-7",
+                "timestampNs": 9000,
+                "threadId": 4242,
+                "threadName": "audio",
+                "eventType": "sample",
+                "pid": 77,
+                "functionName": "dsp::mix",
+                "file": "mix.cpp",
+                "line": 31,
+                "backtrace": [
+                    { "address": "0x1000", "moduleName": "app", "name": "dsp::mix", "fileName": "mix.cpp", "lineNumber": 31 },
+                    { "address": "0x2000", "moduleName": "app", "name": "main", "fileName": null, "lineNumber": null }
+                ]
+            }),
+        );
+
+        let e = event.expect("should parse sample event");
+        assert_eq!(e.event_type, EventType::Sample);
+        assert_eq!(e.thread_id, 4242);
+        assert_eq!(e.thread_name.as_deref(), Some("audio"));
+        assert_eq!(e.function_name, "dsp::mix");
+        assert_eq!(e.line_number, Some(31));
+        assert_eq!(e.backtrace.unwrap()[1]["name"], "main");
+    }
+
+    #[test]
+    fn test_parse_event_syscall() {
+        let event = parse_event(
+            "session-1",
+            &json!({
+                "id": "session-1-sys-77-3-1",
+                "timestampNs": 12000,
+                "threadId": 3,
+                "eventType": "syscall",
+                "pid": 77,
+                "functionName": "read",
+                "group": "file",
+                "fd": 5,
+                "path": "/etc/hosts",
+                "bytes": 128,
+                "result": 128,
+                "durationNs": 4200,
+                "text": "read(5 /etc/hosts, 128) = 128"
+            }),
+        );
+
+        let e = event.expect("should parse syscall event");
+        assert_eq!(e.event_type, EventType::Syscall);
+        assert_eq!(e.function_name, "read");
+        assert_eq!(e.duration_ns, Some(4200));
+        assert_eq!(e.return_value, Some(json!(128)));
+        let details = e.arguments.unwrap();
+        assert_eq!(details["fd"], 5);
+        assert_eq!(details["path"], "/etc/hosts");
+        assert!(details["errno"].is_null());
+    }
+
+    #[test]
+    fn test_parse_event_network() {
+        let event = parse_event(
+            "session-1",
+            &json!({
+                "id": "session-1-net-77-3-2",
+                "timestampNs": 15000,
+                "threadId": 3,
+                "eventType": "network",
+                "pid": 77,
+                "functionName": "SSL_write",
+                "kind": "tls",
+                "host": "api.example.com",
+                "port": 443,
+                "bytes": 512,
+                "direction": "out",
+                "result": 512,
+                "durationNs": 900,
+                "text": "TLS api.example.com:443 sent 512 bytes"
+            }),
+        );
+
+        let e = event.expect("should parse network event");
+        assert_eq!(e.event_type, EventType::Network);
+        assert_eq!(e.function_name, "SSL_write");
+        assert_eq!(e.duration_ns, Some(900));
+        let details = e.arguments.unwrap();
+        assert_eq!(details["kind"], "tls");
+        assert_eq!(details["host"], "api.example.com");
+        assert_eq!(details["direction"], "out");
+    }
+
+    #[test]
+    fn test_parse_event_rt_warning() {
+        let event = parse_event(
+            "session-1",
+            &json!({
+                "id": "session-1-rtwarn-77-1",
+                "timestampNs": 18000,
+                "threadId": 9,
+                "threadName": "com.apple.audio.IOThread.client",
+                "eventType": "rt_warning",
+                "pid": 77,
+                "functionName": "audio::render",
+                "policy": "time-constraint",
+                "jsHook": false,
+                "text": "audio::render runs on time-constraint thread com.apple.audio.IOThread.client with a normal hook; trace it with mode 'rt'"
+            }),
+        );
+
+        let e = event.expect("should parse rt_warning event");
+        assert_eq!(e.event_type, EventType::RtWarning);
+        assert_eq!(e.function_name, "audio::render");
+        assert_eq!(
+            e.thread_name.as_deref(),
+            Some("com.apple.audio.IOThread.client")
+        );
+        let details = e.arguments.unwrap();
+        assert_eq!(details["policy"], "time-constraint");
+        assert_eq!(details["jsHook"], false);
+    }
+
+    #[test]
+    fn test_parse_event_panic() {
+        let event = parse_event(
+            "session-1",
+            &json!({
+                "id": "session-1-panic-77-1",
+                "timestampNs": 21000,
+                "threadId": 4,
+                "eventType": "panic",
+                "pid": 77,
+                "functionName": "abort",
+                "kind": "abort",
+                "exceptionType": "std::runtime_error",
+                "exceptionMessage": "bad header",
+                "text": "terminate called after throwing 'std::runtime_error': bad header",
+                "backtrace": [{ "address": "0x1000", "name": "parse_header" }]
+            }),
+        );
+
+        let e = event.expect("should parse panic event");
+        assert_eq!(e.event_type, EventType::Panic);
+        assert_eq!(e.function_name, "abort");
+        assert_eq!(e.exception_type.as_deref(), Some("std::runtime_error"));
+        assert_eq!(e.backtrace.unwrap()[0]["name"], "parse_header");
+        assert_eq!(e.arguments.unwrap()["kind"], "abort");
+    }
+
+    #[test]
+    fn test_parse_event_exception_catch() {
+        let event = parse_event(
+            "session-1",
+            &json!({
+                "id": "session-1-exc-77-2",
+                "timestampNs": 22000,
+                "threadId": 4,
+                "eventType": "exception",
+                "pid": 77,
+                "callId": "session-1-exc-77-1",
+                "functionName": "load_config",
+                "kind": "catch",
+                "exceptionType": "std::runtime_error",
+                "exceptionMessage": "bad header",
+                "throwFunction": "parse_header",
+                "catchFunction": "load_config",
+                "propagation": ["parse_header", "read_file", "load_config"],
+                "text": "std::runtime_error thrown in parse_header caught in load_config"
+            }),
+        );
+
+        let e = event.expect("should parse exception event");
+        assert_eq!(e.event_type, EventType::Exception);
+        assert_eq!(e.function_name, "load_config");
+        assert_eq!(e.call_id.as_deref(), Some("session-1-exc-77-1"));
+        assert_eq!(e.exception_type.as_deref(), Some("std::runtime_error"));
+        let details = e.arguments.unwrap();
+        assert_eq!(details["kind"], "catch");
+        assert_eq!(details["propagation"][1], "read_file");
+    }
+
+    #[test]
+    fn test_parse_event_signal_delivered() {
+        let event = parse_event(
+            "session-1",
+            &json!({
+                "id": "session-1-sig-77-1",
+                "timestampNs": 23000,
+                "threadId": 1,
+                "eventType": "signal",
+                "pid": 77,
+                "functionName": "on_term",
+                "kind": "delivered",
+                "signal": "SIGTERM",
+                "signo": 15,
+                "handler": "on_term",
+                "senderPid": 4242,
+                "senderUid": 501,
+                "code": 0,
+                "text": "SIGTERM from pid 4242 delivered to on_term"
+            }),
+        );
+
+        let e = event.expect("should parse signal event");
+        assert_eq!(e.event_type, EventType::Signal);
+        assert_eq!(e.function_name, "on_term");
+        assert_eq!(e.signal.as_deref(), Some("SIGTERM"));
+        let details = e.arguments.unwrap();
+        assert_eq!(details["kind"], "delivered");
+        assert_eq!(details["senderPid"], 4242);
+    }
+
+    #[test]
+    fn test_parse_event_lock_contended() {
+        let event = parse_event(
+            "session-1",
+            &json!({
+                "id": "session-1-lock-77-1",
+                "timestampNs": 31000,
+                "threadId": 2,
+                "eventType": "lock",
+                "pid": 77,
+                "functionName": "Cache::get",
+                "kind": "contended",
+                "lock": "0x7f001000",
+                "primitive": "pthread_mutex_lock",
+                "waitNs": 1500000,
+                "durationNs": 1500000,
+                "holderThreadId": 1,
+                "holderSite": "Cache::evict",
+                "text": "Cache::get waited 1.50ms for pthread_mutex_lock 0x7f001000 held by thread 1 (Cache::evict)"
+            }),
+        );
+
+        let e = event.expect("should parse lock event");
+        assert_eq!(e.event_type, EventType::Lock);
+        assert_eq!(e.function_name, "Cache::get");
+        assert_eq!(e.duration_ns, Some(1_500_000));
+        let details = e.arguments.unwrap();
+        assert_eq!(details["holderThreadId"], 1);
+        assert_eq!(details["holderSite"], "Cache::evict");
+
+        let blocked = parse_event(
+            "session-1",
+            &json!({
+                "id": "session-1-lock-77-2",
+                "timestampNs": 32000,
+                "threadId": 3,
+                "eventType": "lock",
+                "functionName": "Pool::checkout",
+                "kind": "blocked",
+                "waitNs": 1000000000i64,
+                "durationNs": null
+            }),
+        )
+        .expect("should parse blocked lock event");
+        assert_eq!(blocked.duration_ns, None);
+    }
+
+    #[test]
+    fn test_parse_event_signal_safety() {
+        let event = parse_event(
+            "session-1",
+            &json!({
+                "id": "session-1-sigsafe-77-1-1",
+                "timestampNs": 9000,
+                "threadId": 1,
+                "eventType": "signal_safety",
+                "pid": 77,
+                "functionName": "malloc",
+                "signal": "SIGINT",
+                "text": "malloc() called inside the SIGINT handler on_sigint: not async-signal-safe (allocation)",
+                "handler": "on_sigint",
+                "handlerAddress": "0x1000",
+                "category": "allocation",
+                "backtrace": [{ "address": "0x1010", "name": "on_sigint" }]
+            }),
+        );
+
+        let e = event.expect("should parse signal_safety event");
+        assert_eq!(e.event_type, EventType::SignalSafety);
+        assert_eq!(e.function_name, "malloc");
+        assert_eq!(e.signal.as_deref(), Some("SIGINT"));
+        let details = e.arguments.unwrap();
+        assert_eq!(details["handler"], "on_sigint");
+        assert_eq!(details["category"], "allocation");
+        assert_eq!(e.backtrace.unwrap()[0]["name"], "on_sigint");
+    }
+
+    #[test]
+    fn test_parse_event_crash_keeps_globals() {
+        let event = parse_event(
+            "session-1",
+            &json!({
+                "id": "session-1-crash-1",
+                "timestampNs": 9000,
+                "threadId": 1,
+                "eventType": "crash",
+                "pid": 77,
+                "signal": "access-violation",
+                "faultAddress": "0x0",
+                "globals": [
+                    { "name": "gEngineState", "address": "0x4010", "size": 4, "data": "03000000" },
+                    { "name": "gMissing", "error": "no global with a static address in DWARF" }
+                ]
+            }),
+        );
+
+        let e = event.expect("should parse crash event");
+        let text: serde_json::Value = serde_json::from_str(e.text.as_deref().unwrap()).unwrap();
+        assert_eq!(text["globals"][0]["data"], "03000000");
+        assert!(text["globals"][1]["error"].is_string());
+        assert!(text["frameMemory"].is_null());
+    }
+
     #[test]
     fn test_parse_event_stdout_missing_text() {
         let event = parse_event(
@@ -3748,6 +5460,7 @@ mod tests {
                 "sourceFile": "/src/main.rs",
                 "lineNumber": 10,
                 "parentEventId": null,
+                "callId": "evt-4",
                 "arguments": [1, 2]
             }),
         );
@@ -3756,9 +5469,49 @@ mod tests {
         assert_eq!(e.event_type, EventType::FunctionEnter);
         assert_eq!(e.function_name, "main::run");
         assert_eq!(e.source_file.as_deref(), Some("/src/main.rs"));
+        assert_eq!(e.call_id.as_deref(), Some("evt-4"));
         assert!(e.text.is_none());
     }
 
+    #[test]
+    fn test_reloaded_agent_events_are_stored() {
+        let init_msg = json!({ "type": "initialize", "sessionId": "s1", "captureFds": [] });
+        let reinit = reload_init_msg(&init_msg, 1);
+        assert_eq!(reinit["sessionId"], "s1");
+        assert_eq!(reinit["reloadGeneration"], 1);
+
+        // Ids as the agent's EventIdAllocator builds them: the reloaded
+        // script's sequence restarts at 1 on the same thread
+        let event = |generation: u64, seq: u64| {
+            parse_event(
+                "s1",
+                &json!({
+                    "id": format!("s1-fn-77-{}-5-{}", generation, seq),
+                    "timestampNs": generation * 1000 + seq,
+                    "threadId": 5,
+                    "eventType": "function_enter",
+                    "functionName": "main::run",
+                }),
+            )
+            .unwrap()
+        };
+        let reloaded = reinit["reloadGeneration"].as_u64().unwrap();
+        let events = vec![
+            event(0, 1),
+            event(0, 2),
+            event(reloaded, 1),
+            event(reloaded, 2),
+        ];
+
+        let dir = tempfile::tempdir().unwrap();
+        let db = crate::db::Database::open(&dir.path().join("test.db")).unwrap();
+        db.create_session("s1", "/bin/app", "/home", 77).unwrap();
+        let stats = db.insert_events_batch(&events).unwrap();
+        assert_eq!(stats.events_inserted, 4);
+        assert_eq!(stats.duplicates_ignored, 0);
+        assert_eq!(db.count_session_events("s1").unwrap(), 4);
+    }
+
     #[test]
     fn test_parse_event_unknown_type() {
         assert!(parse_event(
@@ -3866,7 +5619,7 @@ mod tests {
     // --- AgentMessageHandler tests ---
 
     fn make_handler() -> (AgentMessageHandler, mpsc::Receiver<Event>, HooksReadySignal) {
-        let (event_tx, event_rx) = mpsc::channel(1000);
+        let (event_tx, event_rx) = crate::daemon::event_channel(1000);
         let hooks_ready: HooksReadySignal = Arc::new(Mutex::new(None));
         let read_response: ReadResponseSignal = Arc::new(Mutex::new(None));
         let write_response: WriteResponseSignal = Arc::new(Mutex::new(None));
@@ -3879,6 +5632,7 @@ mod tests {
             crash_reported: Arc::new(AtomicBool::new(false)),
             pause_notify_tx: None,
             start_ns: 1_000_000_000, // 1s offset for test determinism
+            pid: None,
         };
         (handler, event_rx, hooks_ready)
     }
@@ -3959,7 +5713,7 @@ mod tests {
     #[tokio::test]
     async fn test_handler_paused_creates_event_and_notification() {
         let (pause_tx, mut pause_rx) = mpsc::channel(10);
-        let (event_tx, mut event_rx) = mpsc::channel(1000);
+        let (event_tx, mut event_rx) = crate::daemon::event_channel(1000);
         let hooks_ready: HooksReadySignal = Arc::new(Mutex::new(None));
         let read_response: ReadResponseSignal = Arc::new(Mutex::new(None));
         let write_response: WriteResponseSignal = Arc::new(Mutex::new(None));
@@ -3972,6 +5726,7 @@ mod tests {
             crash_reported: Arc::new(AtomicBool::new(false)),
             pause_notify_tx: Some(pause_tx),
             start_ns: 1_000_000_000,
+            pid: None,
         };
 
         // Simulate a "paused" message from agent
@@ -4025,7 +5780,7 @@ mod tests {
     #[tokio::test]
     async fn test_handler_paused_with_backtrace_and_arguments() {
         let (pause_tx, mut pause_rx) = mpsc::channel(10);
-        let (event_tx, mut event_rx) = mpsc::channel(1000);
+        let (event_tx, mut event_rx) = crate::daemon::event_channel(1000);
         let hooks_ready: HooksReadySignal = Arc::new(Mutex::new(None));
         let read_response: ReadResponseSignal = Arc::new(Mutex::new(None));
         let write_response: WriteResponseSignal = Arc::new(Mutex::new(None));
@@ -4038,6 +5793,7 @@ mod tests {
             crash_reported: Arc::new(AtomicBool::new(false)),
             pause_notify_tx: Some(pause_tx),
             start_ns: 1_000_000_000,
+            pid: None,
         };
 
         let payload = json!({
@@ -35,9 +35,14 @@ pub fn install() -> Result<()> {
         Some(AgentSystem::ClaudeCode { config_dir }) => {
             install_claude_code(&config_dir)?;
             println!("Strobe installed for Claude Code.");
+            install_tutorial_demo();
             println!();
             println!("Optional: Enable AI vision for UI observation:");
             println!("  strobe setup-vision");
+            if cfg!(target_os = "macos") {
+                println!("Grant the macOS permissions UI observation needs:");
+                println!("  strobe setup-ui");
+            }
         }
         None => {
             println!("No supported coding agent detected.");
@@ -45,15 +50,29 @@ pub fn install() -> Result<()> {
             println!("\nManual setup: add strobe to your MCP config with:");
             println!("  command: \"strobe\"");
             println!("  args: [\"mcp\"]");
+            install_tutorial_demo();
             println!();
             println!("Optional: Enable AI vision for UI observation:");
             println!("  strobe setup-vision");
+            if cfg!(target_os = "macos") {
+                println!("Grant the macOS permissions UI observation needs:");
+                println!("  strobe setup-ui");
+            }
         }
     }
 
     Ok(())
 }
 
+/// Pre-build the debug_tutorial demo. Not fatal: the tutorial retries the
+/// build on first use and reports what is missing.
+fn install_tutorial_demo() {
+    match crate::tutorial::build_demo(&crate::tutorial::tutorial_dir()) {
+        Ok(path) => println!("Tutorial demo built: {}", path.display()),
+        Err(e) => println!("Skipped tutorial demo: {}", e),
+    }
+}
+
 fn install_claude_code(config_dir: &Path) -> Result<()> {
     let binary = strobe_binary_path()?;
 
@@ -3,13 +3,20 @@ pub mod config;
 pub mod daemon;
 pub mod db;
 pub mod dwarf;
+pub mod embed;
 pub mod error;
+pub mod export;
 pub mod frida_collector;
 pub mod install;
 pub mod mcp;
+pub mod process;
+pub mod projects;
+pub mod report;
+pub mod setup_ui;
 pub mod setup_vision;
 pub mod symbols;
 pub mod test;
+pub mod tutorial;
 pub mod ui;
 
 pub use error::{Error, Result};
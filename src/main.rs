@@ -9,12 +9,16 @@ async fn main() {
     let subcommand = args.get(1).map(|s| s.as_str());
 
     let result: Result<()> = match subcommand {
-        Some("daemon") => Daemon::run().await,
+        Some("daemon") => Daemon::run(&args[2..]).await,
         Some("mcp") => strobe::mcp::stdio_proxy().await,
         Some("install") => strobe::install::install(),
         Some("setup-vision") => strobe::setup_vision::setup_vision(),
+        Some("setup-ui") => strobe::setup_ui::setup_ui(),
+        Some("serve") => strobe::daemon::serve_session(&args[2..]).await,
+        Some("report") => strobe::report::report(args.get(2).map(|s| s.as_str())).await,
+        Some("projects") => strobe::projects::projects(&args[2..]),
         _ => {
-            eprintln!("Usage: strobe <daemon|mcp|install|setup-vision>");
+            eprintln!("Usage: strobe <daemon [--web [port]]|mcp|install|setup-vision|setup-ui|report <sessionId>|projects [list|clean|remove|rename]|serve --session <sessionId> --port <port>>");
             std::process::exit(1);
         }
     };
@@ -45,6 +49,7 @@ async fn main() {
     // entirely, since several of our long-lived background pieces (signal
     // handlers, accept loops, the vision sidecar) have their own teardown
     // paths that don't need to be re-driven through tokio's drop sequence.
+    #[cfg(unix)]
     if matches!(subcommand, Some("mcp") | Some("daemon")) {
         unsafe {
             libc::close(0);
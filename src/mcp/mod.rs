@@ -19,6 +19,15 @@ mod tests {
             project_root: "/home/user/project".to_string(),
             env: None,
             symbols_path: None,
+            capture_fds: None,
+            capture_pipes: None,
+            instrumentation_file: None,
+            on_output: None,
+            log_format: None,
+            symbol_extra: None,
+            crash_capture: None,
+            inherit_instrumentation: None,
+            profile: None,
         };
 
         let json = serde_json::to_string(&req).unwrap();
@@ -41,14 +50,24 @@ mod tests {
             source_file: None,
             return_value: None,
             thread_name: None,
+            queue_name: None,
+            text: None,
+            fields: None,
             time_from: None,
             time_to: None,
             min_duration_ns: None,
             pid: None,
+            test_name: None,
+            call_id: None,
+            format: None,
             limit: Some(100),
             offset: None,
             verbose: Some(true),
             after_event_id: None,
+            after_seq: None,
+            select: None,
+            distinct: None,
+            group_by: None,
         };
 
         let json = serde_json::to_string(&req).unwrap();
@@ -67,6 +86,34 @@ mod tests {
         assert!(json.contains("SESSION_NOT_FOUND"));
     }
 
+    #[test]
+    fn test_watch_delta_threshold_validation() {
+        let request = |delta_threshold: Option<f64>| DebugTraceRequest {
+            watches: Some(WatchUpdate {
+                add: Some(vec![WatchTarget {
+                    variable: Some("gCounter".to_string()),
+                    address: None,
+                    type_hint: None,
+                    label: None,
+                    expr: None,
+                    on: None,
+                    delta_threshold,
+                }]),
+                remove: None,
+            }),
+            ..serde_json::from_str(r#"{"sessionId": "s"}"#).unwrap()
+        };
+        assert!(request(Some(100.0)).validate().is_ok());
+        assert!(request(None).validate().is_ok());
+        assert!(request(Some(0.0)).validate().is_err());
+        assert!(request(Some(-5.0)).validate().is_err());
+        assert!(request(Some(f64::NAN)).validate().is_err());
+
+        let parsed: WatchTarget =
+            serde_json::from_str(r#"{"variable": "gCounter", "deltaThreshold": 100}"#).unwrap();
+        assert_eq!(parsed.delta_threshold, Some(100.0));
+    }
+
     #[test]
     fn test_watch_types_serialization() {
         let target = WatchTarget {
@@ -76,6 +123,7 @@ mod tests {
             label: None,
             expr: None,
             on: Some(vec!["NoteOn".to_string()]),
+            delta_threshold: None,
         };
         let json = serde_json::to_string(&target).unwrap();
         assert!(json.contains("gClock->counter"));
@@ -137,6 +185,7 @@ mod tests {
             label: Some("counter".to_string()),
             expr: None,
             on: Some(vec!["audio::process".to_string(), "midi::*".to_string()]),
+            delta_threshold: None,
         };
 
         assert_eq!(watch_with_on.on.as_ref().unwrap().len(), 2);
@@ -149,6 +198,7 @@ mod tests {
             label: Some("tempo".to_string()),
             expr: None,
             on: None,
+            delta_threshold: None,
         };
         assert!(global_watch.on.is_none());
 
@@ -170,6 +220,7 @@ mod tests {
                 label: None,
                 expr: None,
                 on: None,
+                delta_threshold: None,
             })
             .collect();
 
@@ -182,6 +233,15 @@ mod tests {
                 remove: None,
             }),
             project_root: None,
+            mode: None,
+            sampling: None,
+            threads: None,
+            rate_limit: None,
+            flight_recorder: None,
+            flush: None,
+            lint: None,
+            preview: None,
+            signal_safety: None,
             serialization_depth: None,
         };
 
@@ -206,10 +266,20 @@ mod tests {
                     label: Some("test".to_string()),
                     expr: Some(long_expr),
                     on: None,
+                    delta_threshold: None,
                 }]),
                 remove: None,
             }),
             project_root: None,
+            mode: None,
+            sampling: None,
+            threads: None,
+            rate_limit: None,
+            flight_recorder: None,
+            flush: None,
+            lint: None,
+            preview: None,
+            signal_safety: None,
             serialization_depth: None,
         };
 
@@ -234,10 +304,20 @@ mod tests {
                     label: Some("test".to_string()),
                     expr: Some(deep_expr.to_string()),
                     on: None,
+                    delta_threshold: None,
                 }]),
                 remove: None,
             }),
             project_root: None,
+            mode: None,
+            sampling: None,
+            threads: None,
+            rate_limit: None,
+            flight_recorder: None,
+            flush: None,
+            lint: None,
+            preview: None,
+            signal_safety: None,
             serialization_depth: None,
         };
 
@@ -260,10 +340,20 @@ mod tests {
                     label: Some("counter".to_string()),
                     expr: None,
                     on: Some(vec!["process::*".to_string()]),
+                    delta_threshold: None,
                 }]),
                 remove: None,
             }),
             project_root: None,
+            mode: None,
+            sampling: None,
+            threads: None,
+            rate_limit: None,
+            flight_recorder: None,
+            flush: None,
+            lint: None,
+            preview: None,
+            signal_safety: None,
             serialization_depth: None,
         };
         assert!(req.validate().is_ok());
@@ -278,6 +368,15 @@ mod tests {
             remove: None,
             watches: None,
             project_root: None,
+            mode: None,
+            sampling: None,
+            threads: None,
+            rate_limit: None,
+            flight_recorder: None,
+            flush: None,
+            lint: None,
+            preview: None,
+            signal_safety: None,
             serialization_depth: Some(0),
         };
         assert!(req.validate().is_err());
@@ -289,6 +388,15 @@ mod tests {
             remove: None,
             watches: None,
             project_root: None,
+            mode: None,
+            sampling: None,
+            threads: None,
+            rate_limit: None,
+            flight_recorder: None,
+            flush: None,
+            lint: None,
+            preview: None,
+            signal_safety: None,
             serialization_depth: Some(11),
         };
         assert!(req.validate().is_err());
@@ -301,6 +409,15 @@ mod tests {
                 remove: None,
                 watches: None,
                 project_root: None,
+                mode: None,
+                sampling: None,
+                threads: None,
+                rate_limit: None,
+                flight_recorder: None,
+                flush: None,
+                lint: None,
+                preview: None,
+                signal_safety: None,
                 serialization_depth: Some(depth),
             };
             assert!(req.validate().is_ok(), "depth={} should be valid", depth);
@@ -313,6 +430,15 @@ mod tests {
             remove: None,
             watches: None,
             project_root: None,
+            mode: None,
+            sampling: None,
+            threads: None,
+            rate_limit: None,
+            flight_recorder: None,
+            flush: None,
+            lint: None,
+            preview: None,
+            signal_safety: None,
             serialization_depth: None,
         };
         assert!(req.validate().is_ok());
@@ -325,6 +451,15 @@ mod tests {
                 remove: None,
                 watches: None,
                 project_root: None,
+                mode: None,
+                sampling: None,
+                threads: None,
+                rate_limit: None,
+                flight_recorder: None,
+                flush: None,
+                lint: None,
+                preview: None,
+                signal_safety: None,
                 serialization_depth: Some(depth),
             };
             assert!(
@@ -343,6 +478,15 @@ mod tests {
             remove: None,
             watches: None,
             project_root: None,
+            mode: None,
+            sampling: None,
+            threads: None,
+            rate_limit: None,
+            flight_recorder: None,
+            flush: None,
+            lint: None,
+            preview: None,
+            signal_safety: None,
             serialization_depth: Some(5),
         };
 
@@ -359,6 +503,15 @@ mod tests {
             remove: None,
             watches: None,
             project_root: None,
+            mode: None,
+            sampling: None,
+            threads: None,
+            rate_limit: None,
+            flight_recorder: None,
+            flush: None,
+            lint: None,
+            preview: None,
+            signal_safety: None,
             serialization_depth: None,
         };
         let json = serde_json::to_string(&req_none).unwrap();
@@ -370,4 +523,362 @@ mod tests {
         assert_eq!(req.serialization_depth, Some(3));
         assert!(req.validate().is_ok());
     }
+
+    #[test]
+    fn test_count_mode_requires_session_and_patterns() {
+        let json = r#"{"sessionId":"s1","add":["dsp::**"],"mode":"count"}"#;
+        let req: DebugTraceRequest = serde_json::from_str(json).unwrap();
+        assert_eq!(req.mode, Some(TraceMode::Count));
+        assert!(req.validate().is_ok());
+
+        let pending: DebugTraceRequest =
+            serde_json::from_str(r#"{"add":["dsp::**"],"mode":"count"}"#).unwrap();
+        assert!(pending
+            .validate()
+            .unwrap_err()
+            .to_string()
+            .contains("sessionId"));
+
+        let no_add: DebugTraceRequest =
+            serde_json::from_str(r#"{"sessionId":"s1","remove":["dsp::**"],"mode":"count"}"#)
+                .unwrap();
+        assert!(no_add.validate().is_err());
+    }
+
+    #[test]
+    fn test_rt_mode_validation() {
+        let req: DebugTraceRequest =
+            serde_json::from_str(r#"{"sessionId":"s1","add":["audio::render"],"mode":"rt"}"#)
+                .unwrap();
+        assert_eq!(req.mode, Some(TraceMode::Rt));
+        assert!(req.validate().is_ok());
+
+        let pending: DebugTraceRequest =
+            serde_json::from_str(r#"{"add":["audio::render"],"mode":"rt"}"#).unwrap();
+        assert!(pending
+            .validate()
+            .unwrap_err()
+            .to_string()
+            .contains("sessionId"));
+
+        let no_add: DebugTraceRequest =
+            serde_json::from_str(r#"{"sessionId":"s1","remove":["audio::render"],"mode":"rt"}"#)
+                .unwrap();
+        assert!(no_add.validate().is_err());
+
+        // Meta patterns hook with JS
+        let network: DebugTraceRequest =
+            serde_json::from_str(r#"{"sessionId":"s1","add":["@network"],"mode":"rt"}"#).unwrap();
+        assert!(network.validate().unwrap_err().to_string().contains("'rt'"));
+    }
+
+    #[test]
+    fn test_sampling_validation() {
+        let req: DebugTraceRequest = serde_json::from_str(
+            r#"{"sessionId":"s1","sampling":{"enabled":true,"hz":250,"threadName":"audio"}}"#,
+        )
+        .unwrap();
+        let sampling = req.sampling.as_ref().unwrap();
+        assert_eq!(sampling.hz, Some(250));
+        assert_eq!(sampling.thread_name.as_deref(), Some("audio"));
+        assert!(req.validate().is_ok());
+
+        // Stopping needs nothing but the flag
+        let stop: DebugTraceRequest =
+            serde_json::from_str(r#"{"sessionId":"s1","sampling":{"enabled":false}}"#).unwrap();
+        assert!(stop.validate().is_ok());
+
+        let pending: DebugTraceRequest =
+            serde_json::from_str(r#"{"sampling":{"enabled":true}}"#).unwrap();
+        assert!(pending
+            .validate()
+            .unwrap_err()
+            .to_string()
+            .contains("sessionId"));
+
+        for bad in [
+            r#"{"enabled":true,"hz":0}"#,
+            r#"{"enabled":true,"hz":1001}"#,
+            r#"{"enabled":true,"threadName":""}"#,
+        ] {
+            let req: DebugTraceRequest =
+                serde_json::from_str(&format!(r#"{{"sessionId":"s1","sampling":{}}}"#, bad))
+                    .unwrap();
+            assert!(req.validate().is_err(), "{} should be rejected", bad);
+        }
+    }
+
+    #[test]
+    fn test_thread_filter_validation() {
+        let req: DebugTraceRequest = serde_json::from_str(
+            r#"{"sessionId":"s1","add":["midi::*"],"threads":{"nameContains":"midi","ids":[4242]}}"#,
+        )
+        .unwrap();
+        let threads = req.threads.as_ref().unwrap();
+        assert_eq!(threads.name_contains.as_deref(), Some("midi"));
+        assert!(!threads.is_empty());
+        assert!(req.validate().is_ok());
+
+        // `{}` clears the filter
+        let clear: DebugTraceRequest =
+            serde_json::from_str(r#"{"sessionId":"s1","threads":{}}"#).unwrap();
+        assert!(clear.threads.as_ref().unwrap().is_empty());
+        assert!(clear.validate().is_ok());
+
+        let pending: DebugTraceRequest =
+            serde_json::from_str(r#"{"add":["midi::*"],"threads":{"nameContains":"midi"}}"#)
+                .unwrap();
+        assert!(pending
+            .validate()
+            .unwrap_err()
+            .to_string()
+            .contains("sessionId"));
+
+        let empty_name: DebugTraceRequest =
+            serde_json::from_str(r#"{"sessionId":"s1","threads":{"nameContains":""}}"#).unwrap();
+        assert!(empty_name.validate().is_err());
+    }
+
+    #[test]
+    fn test_rate_limit_validation() {
+        let req: DebugTraceRequest = serde_json::from_str(
+            r#"{"sessionId":"s1","add":["dsp::*"],"rateLimit":{"maxPerSecond":100,"sampleEvery":10}}"#,
+        )
+        .unwrap();
+        let limit = req.rate_limit.as_ref().unwrap();
+        assert_eq!(limit.max_per_second, Some(100));
+        assert_eq!(limit.sample_every, Some(10));
+        assert!(!limit.is_empty());
+        assert!(req.validate().is_ok());
+
+        // `{}` and 1-in-1 both record every call
+        for clear in [r#"{}"#, r#"{"sampleEvery":1}"#] {
+            let req: DebugTraceRequest =
+                serde_json::from_str(&format!(r#"{{"sessionId":"s1","rateLimit":{}}}"#, clear))
+                    .unwrap();
+            assert!(req.rate_limit.as_ref().unwrap().is_empty());
+            assert!(req.validate().is_ok());
+        }
+
+        let pending: DebugTraceRequest =
+            serde_json::from_str(r#"{"add":["dsp::*"],"rateLimit":{"maxPerSecond":100}}"#).unwrap();
+        assert!(pending
+            .validate()
+            .unwrap_err()
+            .to_string()
+            .contains("sessionId"));
+
+        for bad in [
+            r#"{"maxPerSecond":0}"#,
+            r#"{"sampleEvery":0}"#,
+            r#"{"maxPerSecond":1000001}"#,
+        ] {
+            let req: DebugTraceRequest =
+                serde_json::from_str(&format!(r#"{{"sessionId":"s1","rateLimit":{}}}"#, bad))
+                    .unwrap();
+            assert!(req.validate().is_err(), "{} should be rejected", bad);
+        }
+    }
+
+    #[test]
+    fn test_meta_patterns() {
+        let req: DebugTraceRequest = serde_json::from_str(
+            r#"{"sessionId":"s1","add":["@syscalls:file","net::*","@syscalls:net"]}"#,
+        )
+        .unwrap();
+        assert!(req.validate().is_ok());
+        let split = MetaPatterns::split(req.add.as_ref().unwrap());
+        assert_eq!(split.syscalls, vec![SyscallGroup::File, SyscallGroup::Net]);
+        assert!(!split.network);
+        assert_eq!(split.functions, vec!["net::*".to_string()]);
+        assert_eq!(SyscallGroup::Net.pattern(), "@syscalls:net");
+
+        let pending: DebugTraceRequest =
+            serde_json::from_str(r#"{"add":["@syscalls:file"]}"#).unwrap();
+        assert!(pending
+            .validate()
+            .unwrap_err()
+            .to_string()
+            .contains("sessionId"));
+
+        let counted: DebugTraceRequest =
+            serde_json::from_str(r#"{"sessionId":"s1","add":["@syscalls:file"],"mode":"count"}"#)
+                .unwrap();
+        assert!(counted.validate().is_err());
+
+        let network = MetaPatterns::split(&["@network".to_string()]);
+        assert!(network.network);
+        assert_eq!(network.function_patterns(), None);
+        let pending: DebugTraceRequest = serde_json::from_str(r#"{"add":["@network"]}"#).unwrap();
+        assert!(pending.validate().is_err());
+
+        let exceptions = MetaPatterns::split(&["@exceptions".to_string(), "app::*".to_string()]);
+        assert!(exceptions.exceptions);
+        assert!(!exceptions.network);
+        assert!(!exceptions.signals);
+        assert!(MetaPatterns::split(&["@signals".to_string()]).signals);
+        assert!(MetaPatterns::split(&["@locks".to_string()]).locks);
+        assert!(!exceptions.locks);
+        assert_eq!(exceptions.functions, vec!["app::*".to_string()]);
+        let counted: DebugTraceRequest =
+            serde_json::from_str(r#"{"sessionId":"s1","add":["@exceptions"],"mode":"count"}"#)
+                .unwrap();
+        assert!(counted.validate().is_err());
+
+        let unknown: DebugTraceRequest =
+            serde_json::from_str(r#"{"sessionId":"s1","add":["@syscalls:disk"]}"#).unwrap();
+        assert!(matches!(
+            unknown.validate(),
+            Err(crate::Error::InvalidPattern { .. })
+        ));
+    }
+
+    #[test]
+    fn test_signal_safety_validation() {
+        let req: DebugTraceRequest =
+            serde_json::from_str(r#"{"sessionId":"s1","signalSafety":{"enabled":true}}"#).unwrap();
+        assert!(req.validate().is_ok());
+        let req: DebugTraceRequest = serde_json::from_str(
+            r#"{"sessionId":"s1","signalSafety":{"enabled":true,"functions":["malloc","my_log"]}}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            req.signal_safety
+                .as_ref()
+                .unwrap()
+                .functions
+                .as_ref()
+                .unwrap()
+                .len(),
+            2
+        );
+        assert!(req.validate().is_ok());
+
+        let pending: DebugTraceRequest =
+            serde_json::from_str(r#"{"signalSafety":{"enabled":true}}"#).unwrap();
+        assert!(pending
+            .validate()
+            .unwrap_err()
+            .to_string()
+            .contains("sessionId"));
+
+        for bad in [
+            r#"{"enabled":true,"functions":[]}"#,
+            r#"{"enabled":true,"functions":["std::malloc"]}"#,
+            r#"{"enabled":true,"functions":[""]}"#,
+        ] {
+            let req: DebugTraceRequest =
+                serde_json::from_str(&format!(r#"{{"sessionId":"s1","signalSafety":{}}}"#, bad))
+                    .unwrap();
+            assert!(req.validate().is_err(), "{} should be rejected", bad);
+        }
+    }
+
+    #[test]
+    fn test_flight_recorder_validation() {
+        let req: DebugTraceRequest = serde_json::from_str(
+            r#"{"sessionId":"s1","add":["net::*"],"flightRecorder":{"enabled":true}}"#,
+        )
+        .unwrap();
+        assert!(req.validate().is_ok());
+        let resolved = req.flight_recorder.unwrap().resolved();
+        assert_eq!(resolved.capacity, Some(DEFAULT_FLIGHT_RECORDER_CAPACITY));
+        assert!(resolved
+            .flush_on_output
+            .unwrap()
+            .contains(&"panicked at".to_string()));
+
+        // An explicit empty trigger list stays empty
+        let req: DebugTraceRequest = serde_json::from_str(
+            r#"{"sessionId":"s1","flightRecorder":{"enabled":true,"capacity":10,"flushOnOutput":[]}}"#,
+        )
+        .unwrap();
+        let resolved = req.flight_recorder.unwrap().resolved();
+        assert_eq!(resolved.capacity, Some(10));
+        assert_eq!(resolved.flush_on_output, Some(vec![]));
+
+        for pending in [
+            r#"{"flightRecorder":{"enabled":true}}"#,
+            r#"{"flush":true}"#,
+        ] {
+            let req: DebugTraceRequest = serde_json::from_str(pending).unwrap();
+            assert!(req
+                .validate()
+                .unwrap_err()
+                .to_string()
+                .contains("sessionId"));
+        }
+
+        for bad in [
+            r#"{"enabled":true,"capacity":0}"#,
+            r#"{"enabled":true,"capacity":1000001}"#,
+            r#"{"enabled":true,"flushOnOutput":[""]}"#,
+        ] {
+            let req: DebugTraceRequest =
+                serde_json::from_str(&format!(r#"{{"sessionId":"s1","flightRecorder":{}}}"#, bad))
+                    .unwrap();
+            assert!(req.validate().is_err(), "{} should be rejected", bad);
+        }
+    }
+
+    #[test]
+    fn test_preview_validation() {
+        let req: DebugTraceRequest =
+            serde_json::from_str(r#"{"sessionId":"s1","preview":["audio::**"]}"#).unwrap();
+        assert!(req.validate().is_ok());
+
+        for (bad, reason) in [
+            (r#"{"preview":["audio::**"]}"#, "sessionId"),
+            (r#"{"sessionId":"s1","preview":[]}"#, "1-20"),
+            (
+                r#"{"sessionId":"s1","preview":["a::*"],"add":["b::*"]}"#,
+                "separate call",
+            ),
+            (
+                r#"{"sessionId":"s1","preview":["a::*"],"lint":["a::*"]}"#,
+                "separate call",
+            ),
+        ] {
+            let req: DebugTraceRequest = serde_json::from_str(bad).unwrap();
+            let err = req.validate().unwrap_err().to_string();
+            assert!(err.contains(reason), "{}: {}", bad, err);
+        }
+    }
+
+    #[test]
+    fn test_pattern_preview_summary() {
+        let function = |name: &str, file: Option<&str>| PreviewFunction {
+            name: name.to_string(),
+            file: file.map(str::to_string),
+            line: None,
+            module: None,
+        };
+        let mut functions = vec![
+            function("audio::mix", Some("src/mix.cpp")),
+            function("audio::fft", Some("src/dsp.cpp")),
+            function("audio::ifft", Some("src/dsp.cpp")),
+            function("audio::stub", None),
+        ];
+        functions.extend(
+            (0..MAX_PREVIEW_FUNCTIONS).map(|i| function(&format!("audio::gen{}", i), None)),
+        );
+
+        let preview = PatternPreview::new("audio::**".to_string(), functions);
+        assert_eq!(preview.matched, MAX_PREVIEW_FUNCTIONS + 4);
+        assert_eq!(preview.functions.len(), MAX_PREVIEW_FUNCTIONS);
+        assert_eq!(preview.functions[0].name, "audio::mix");
+        assert_eq!(
+            preview.files,
+            vec![
+                PreviewFile {
+                    file: "src/dsp.cpp".to_string(),
+                    functions: 2,
+                },
+                PreviewFile {
+                    file: "src/mix.cpp".to_string(),
+                    functions: 1,
+                },
+            ]
+        );
+    }
 }
@@ -1,9 +1,9 @@
+use crate::daemon::transport::{self, ClientStream};
 use crate::Result;
 use std::path::{Path, PathBuf};
 use std::process::Stdio;
 use std::time::Duration;
 use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
-use tokio::net::UnixStream;
 
 /// Max reconnection attempts within the reset window before giving up.
 const MAX_RECONNECT_ATTEMPTS: u32 = 3;
@@ -26,7 +26,7 @@ pub async fn stdio_proxy() -> Result<()> {
 
     std::fs::create_dir_all(&strobe_dir)?;
 
-    let socket_path = strobe_dir.join("strobe.sock");
+    let socket_path = transport::endpoint(&strobe_dir);
 
     // Create stdin reader ONCE — persists across reconnections to avoid losing buffered data
     let stdin = tokio::io::stdin();
@@ -41,7 +41,7 @@ pub async fn stdio_proxy() -> Result<()> {
     loop {
         // Phase 1: Ensure daemon is running and connect
         let stream = ensure_daemon_and_connect(&strobe_dir, &socket_path).await?;
-        let (reader, mut writer) = stream.into_split();
+        let (reader, mut writer) = tokio::io::split(stream);
         let mut daemon_reader = BufReader::new(reader);
         let mut daemon_line = String::new();
 
@@ -150,10 +150,10 @@ pub async fn stdio_proxy() -> Result<()> {
 }
 
 /// Try to connect to an existing daemon, or spawn one and connect.
-async fn ensure_daemon_and_connect(strobe_dir: &Path, socket_path: &Path) -> Result<UnixStream> {
+async fn ensure_daemon_and_connect(strobe_dir: &Path, socket_path: &Path) -> Result<ClientStream> {
     // Fast path: daemon may already be running
     if let Ok(Ok(stream)) =
-        tokio::time::timeout(Duration::from_millis(500), UnixStream::connect(socket_path)).await
+        tokio::time::timeout(Duration::from_millis(500), transport::connect(socket_path)).await
     {
         return Ok(stream);
     }
@@ -166,7 +166,7 @@ async fn ensure_daemon_and_connect(strobe_dir: &Path, socket_path: &Path) -> Res
     for _ in 0..50 {
         tokio::time::sleep(Duration::from_millis(100)).await;
         if let Ok(Ok(stream)) =
-            tokio::time::timeout(Duration::from_millis(100), UnixStream::connect(socket_path)).await
+            tokio::time::timeout(Duration::from_millis(100), transport::connect(socket_path)).await
         {
             return Ok(stream);
         }
@@ -181,11 +181,11 @@ async fn ensure_daemon_and_connect(strobe_dir: &Path, socket_path: &Path) -> Res
 /// Remove stale PID and socket files if the daemon process is dead.
 fn cleanup_stale_files(strobe_dir: &Path) {
     let pid_path = strobe_dir.join("strobe.pid");
-    let socket_path = strobe_dir.join("strobe.sock");
+    let socket_path = transport::endpoint(strobe_dir);
 
     if let Ok(pid_str) = std::fs::read_to_string(&pid_path) {
-        if let Ok(pid) = pid_str.trim().parse::<i32>() {
-            if unsafe { libc::kill(pid, 0) } != 0 {
+        if let Ok(pid) = pid_str.trim().parse::<u32>() {
+            if !crate::process::is_alive(pid) {
                 // Process dead — clean up stale files
                 let _ = std::fs::remove_file(&socket_path);
                 let _ = std::fs::remove_file(&pid_path);
@@ -49,7 +49,7 @@ pub struct RuntimeCapabilities {
 
 // ============ debug_launch ============
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct DebugLaunchRequest {
     pub command: String,
@@ -64,6 +64,92 @@ pub struct DebugLaunchRequest {
     /// Use when automatic symbol resolution fails in complex projects.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub symbols_path: Option<String>,
+    /// Extra file descriptors (>= 3) to capture as aux_output events.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub capture_fds: Option<Vec<i32>>,
+    /// Named pipe paths to read as aux_output events (created if missing).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub capture_pipes: Option<Vec<String>>,
+    /// Instrumentation snapshot written by debug_session "export-instrumentation",
+    /// re-applied to the new session after spawn.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub instrumentation_file: Option<String>,
+    /// Actions to run when a stdout/stderr line contains a given string.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub on_output: Option<Vec<OutputTrigger>>,
+    /// Parse each stdout/stderr line as a structured log record; the fields
+    /// become filterable with debug_query `fields`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub log_format: Option<LogFormat>,
+    /// Shared libraries whose DWARF is indexed alongside the main binary, so
+    /// patterns and watches resolve into them from the start.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub symbol_extra: Option<Vec<String>>,
+    /// Globals whose memory the crash handler dumps into the crash event.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub crash_capture: Option<CrashCapture>,
+    /// Re-apply the instrumentation of this binary's last stopped session in
+    /// the same project (default false). Ignored when `instrumentation_file`
+    /// is given.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub inherit_instrumentation: Option<bool>,
+    /// Named profile from .strobe/settings.json `profiles`; supplies command,
+    /// args, cwd, env, trace patterns, watches and breakpoints the call omits.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub profile: Option<String>,
+}
+
+pub const MAX_CAPTURE_CHANNELS: usize = 8;
+pub const MAX_SYMBOL_EXTRA: usize = 16;
+pub const MAX_CRASH_CAPTURE_VARIABLES: usize = 16;
+pub const DEFAULT_CRASH_CAPTURE_BYTES: usize = 65536;
+pub const MAX_CRASH_CAPTURE_BYTES: usize = 1024 * 1024;
+pub const MAX_OUTPUT_TRIGGERS: usize = 16;
+pub const MAX_OUTPUT_TRIGGER_MATCH_LENGTH: usize = 256;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum OutputTriggerAction {
+    /// SIGSTOP the process that wrote the line; debug_continue resumes it
+    Pause,
+    /// Read all active watches into a variable_snapshot event
+    SnapshotWatches,
+    /// Only record the output_trigger marker event
+    Annotate,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogFormat {
+    /// One JSON object per line
+    Json,
+    /// `key=value key2="quoted value"` pairs
+    Logfmt,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OutputTrigger {
+    /// Plain substring matched against stdout/stderr (case-sensitive)
+    #[serde(rename = "match")]
+    pub pattern: String,
+    pub action: OutputTriggerAction,
+}
+
+/// Globals to dump at crash time, resolved via DWARF once at launch.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CrashCapture {
+    pub variables: Vec<String>,
+    /// Byte budget across all regions, in `variables` order; the region that
+    /// crosses it is truncated and the rest are skipped.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_bytes: Option<usize>,
+}
+
+impl CrashCapture {
+    pub fn budget(&self) -> usize {
+        self.max_bytes.unwrap_or(DEFAULT_CRASH_CAPTURE_BYTES)
+    }
 }
 
 impl DebugLaunchRequest {
@@ -78,6 +164,114 @@ impl DebugLaunchRequest {
                 "projectRoot must not be empty".to_string(),
             ));
         }
+        if let Some(ref fds) = self.capture_fds {
+            if fds.len() > MAX_CAPTURE_CHANNELS {
+                return Err(crate::Error::ValidationError(format!(
+                    "captureFds has {} entries, max is {}",
+                    fds.len(),
+                    MAX_CAPTURE_CHANNELS
+                )));
+            }
+            if let Some(fd) = fds.iter().find(|fd| **fd < 3) {
+                return Err(crate::Error::ValidationError(format!(
+                    "captureFds: fd {} is invalid. stdout/stderr are always captured; use fds >= 3.",
+                    fd
+                )));
+            }
+        }
+        if let Some(ref extra) = self.symbol_extra {
+            if extra.len() > MAX_SYMBOL_EXTRA {
+                return Err(crate::Error::ValidationError(format!(
+                    "symbolExtra has {} entries, max is {}",
+                    extra.len(),
+                    MAX_SYMBOL_EXTRA
+                )));
+            }
+            if let Some(p) = extra.iter().find(|p| {
+                let path = std::path::Path::new(p);
+                !path.is_absolute()
+                    || path
+                        .components()
+                        .any(|c| c == std::path::Component::ParentDir)
+            }) {
+                return Err(crate::Error::ValidationError(format!(
+                    "symbolExtra: '{}' must be an absolute path without '..' components",
+                    p
+                )));
+            }
+        }
+        if let Some(ref pipes) = self.capture_pipes {
+            if pipes.len() > MAX_CAPTURE_CHANNELS {
+                return Err(crate::Error::ValidationError(format!(
+                    "capturePipes has {} entries, max is {}",
+                    pipes.len(),
+                    MAX_CAPTURE_CHANNELS
+                )));
+            }
+            if cfg!(windows) && !pipes.is_empty() {
+                return Err(crate::Error::ValidationError(
+                    "capturePipes is not supported on Windows".to_string(),
+                ));
+            }
+            if let Some(p) = pipes.iter().find(|p| !p.starts_with('/')) {
+                return Err(crate::Error::ValidationError(format!(
+                    "capturePipes: '{}' must be an absolute path",
+                    p
+                )));
+            }
+        }
+        if let Some(ref triggers) = self.on_output {
+            if triggers.len() > MAX_OUTPUT_TRIGGERS {
+                return Err(crate::Error::ValidationError(format!(
+                    "onOutput has {} triggers, max is {}",
+                    triggers.len(),
+                    MAX_OUTPUT_TRIGGERS
+                )));
+            }
+            for trigger in triggers {
+                if trigger.pattern.is_empty()
+                    || trigger.pattern.len() > MAX_OUTPUT_TRIGGER_MATCH_LENGTH
+                {
+                    return Err(crate::Error::ValidationError(format!(
+                        "onOutput match must be 1-{} bytes",
+                        MAX_OUTPUT_TRIGGER_MATCH_LENGTH
+                    )));
+                }
+            }
+        }
+        if let Some(ref file) = self.instrumentation_file {
+            if file.is_empty() || file.contains("..") {
+                return Err(crate::Error::ValidationError(
+                    "instrumentationFile must be a non-empty path without '..' components"
+                        .to_string(),
+                ));
+            }
+        }
+        if let Some(ref capture) = self.crash_capture {
+            if capture.variables.is_empty() || capture.variables.len() > MAX_CRASH_CAPTURE_VARIABLES
+            {
+                return Err(crate::Error::ValidationError(format!(
+                    "crashCapture.variables must list 1-{} globals",
+                    MAX_CRASH_CAPTURE_VARIABLES
+                )));
+            }
+            if let Some(v) = capture
+                .variables
+                .iter()
+                .find(|v| v.is_empty() || v.len() > MAX_WATCH_EXPRESSION_LENGTH)
+            {
+                return Err(crate::Error::ValidationError(format!(
+                    "crashCapture: invalid variable name '{}'",
+                    v
+                )));
+            }
+            if capture.budget() == 0 || capture.budget() > MAX_CRASH_CAPTURE_BYTES {
+                return Err(crate::Error::ValidationError(format!(
+                    "crashCapture.maxBytes must be 1-{}",
+                    MAX_CRASH_CAPTURE_BYTES
+                )));
+            }
+        }
         Ok(())
     }
 }
@@ -96,11 +290,17 @@ pub struct DebugLaunchResponse {
     /// Runtime capabilities — what this session can and can't do, with prescriptive guidance
     #[serde(skip_serializing_if = "Option::is_none")]
     pub capabilities: Option<RuntimeCapabilities>,
+    /// Session whose instrumentation was carried over to this one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub instrumentation_from: Option<String>,
+    /// Parts of the instrumentation file that could not be re-applied
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub warnings: Vec<String>,
 }
 
 // ============ debug_trace ============
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct DebugTraceRequest {
     /// Session ID - if omitted, modifies pending patterns for next launch
@@ -118,8 +318,402 @@ pub struct DebugTraceRequest {
     /// Project root for settings resolution
     #[serde(skip_serializing_if = "Option::is_none")]
     pub project_root: Option<String>,
+    /// How the patterns in `add` are hooked (default: events)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mode: Option<TraceMode>,
+    /// Start, change or stop periodic stack sampling (needs no patterns)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sampling: Option<SamplingConfig>,
+    /// Only record traced calls from these threads; `{}` traces all threads again
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub threads: Option<ThreadFilter>,
+    /// Cap recorded calls per traced function; `{}` records every call again
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rate_limit: Option<RateLimit>,
+    /// Keep traced calls in the agent until a trigger fires instead of recording them
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub flight_recorder: Option<FlightRecorderConfig>,
+    /// Write the flight recorder's buffered calls to the database now
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub flush: Option<bool>,
+    /// Check these patterns for common mistakes without hooking anything
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lint: Option<Vec<String>>,
+    /// List the functions these patterns would hook without hooking anything
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub preview: Option<Vec<String>>,
+    /// Report non-async-signal-safe calls made inside signal handlers
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signal_safety: Option<SignalSafetyConfig>,
+}
+
+/// Patterns one `debug_trace({ lint })` call may check.
+pub const MAX_LINT_PATTERNS: usize = 100;
+/// Patterns one `debug_trace({ preview })` call may resolve.
+pub const MAX_PREVIEW_PATTERNS: usize = 20;
+/// Functions listed per previewed pattern; `matched` still counts them all.
+pub const MAX_PREVIEW_FUNCTIONS: usize = 50;
+/// Source files listed per previewed pattern.
+pub const MAX_PREVIEW_FILES: usize = 20;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TraceMode {
+    /// function_enter/function_exit events for every call
+    #[default]
+    Events,
+    /// In-agent call counters only, flushed as periodic call_counts events
+    Count,
+    /// function_enter/function_exit events from native-only hooks that are
+    /// safe on real-time threads: no JS and no locks on the hooked thread
+    Rt,
+}
+
+/// Periodic stack sampling of the target's threads. Each tick the agent
+/// captures one backtrace per selected thread and records it as a `sample`
+/// event, so hot code shows up without choosing trace patterns.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SamplingConfig {
+    /// false stops sampling
+    pub enabled: bool,
+    /// Samples per second per thread (default: settings "sampling.hz")
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hz: Option<u32>,
+    /// Only sample these OS thread ids
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub thread_ids: Option<Vec<u64>>,
+    /// Only sample threads whose name contains this substring
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub thread_name: Option<String>,
+}
+
+pub const MAX_SAMPLING_HZ: u32 = 1000;
+pub const MAX_SAMPLING_THREAD_IDS: usize = 64;
+
+impl SamplingConfig {
+    pub fn validate(&self) -> crate::Result<()> {
+        if let Some(hz) = self.hz {
+            if hz == 0 || hz > MAX_SAMPLING_HZ {
+                return Err(crate::Error::ValidationError(format!(
+                    "sampling.hz must be between 1 and {}",
+                    MAX_SAMPLING_HZ
+                )));
+            }
+        }
+        if self
+            .thread_ids
+            .as_ref()
+            .is_some_and(|ids| ids.len() > MAX_SAMPLING_THREAD_IDS)
+        {
+            return Err(crate::Error::ValidationError(format!(
+                "sampling.threadIds accepts at most {} threads",
+                MAX_SAMPLING_THREAD_IDS
+            )));
+        }
+        if self.thread_name.as_ref().is_some_and(|n| n.is_empty()) {
+            return Err(crate::Error::ValidationError(
+                "sampling.threadName must not be empty".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Threads whose traced calls are recorded. The agent drops other threads'
+/// calls before sending them, so they never reach the database. A thread
+/// matches when either its name or its id matches.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ThreadFilter {
+    /// Threads whose name contains this substring
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name_contains: Option<String>,
+    /// OS thread ids
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ids: Option<Vec<u64>>,
+}
+
+pub const MAX_THREAD_FILTER_IDS: usize = 64;
+
+impl ThreadFilter {
+    /// No criteria: every thread is traced.
+    pub fn is_empty(&self) -> bool {
+        self.name_contains.is_none() && self.ids.as_ref().is_none_or(|ids| ids.is_empty())
+    }
+
+    pub fn validate(&self) -> crate::Result<()> {
+        if self.name_contains.as_ref().is_some_and(|n| n.is_empty()) {
+            return Err(crate::Error::ValidationError(
+                "threads.nameContains must not be empty".to_string(),
+            ));
+        }
+        if self
+            .ids
+            .as_ref()
+            .is_some_and(|ids| ids.len() > MAX_THREAD_FILTER_IDS)
+        {
+            return Err(crate::Error::ValidationError(format!(
+                "threads.ids accepts at most {} threads",
+                MAX_THREAD_FILTER_IDS
+            )));
+        }
+        Ok(())
+    }
 }
 
+/// Per-function limits on recorded calls, applied by the agent when it drains
+/// the ring buffer. A call is kept or dropped as a whole (enter and exit
+/// together). Both limits can be combined: 1-in-K first, then the cap.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RateLimit {
+    /// Record at most this many calls per function per second
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_per_second: Option<u32>,
+    /// Record one call in every K per function
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sample_every: Option<u32>,
+}
+
+pub const MAX_RATE_LIMIT: u32 = 1_000_000;
+
+impl RateLimit {
+    /// No limits: every call is recorded.
+    pub fn is_empty(&self) -> bool {
+        self.max_per_second.is_none() && self.sample_every.is_none_or(|k| k == 1)
+    }
+
+    pub fn validate(&self) -> crate::Result<()> {
+        for (name, value) in [
+            ("maxPerSecond", self.max_per_second),
+            ("sampleEvery", self.sample_every),
+        ] {
+            if value.is_some_and(|v| v == 0 || v > MAX_RATE_LIMIT) {
+                return Err(crate::Error::ValidationError(format!(
+                    "rateLimit.{} must be between 1 and {}",
+                    name, MAX_RATE_LIMIT
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Flight recorder mode: traced calls go to a ring buffer in the agent and
+/// reach the database only when a trigger fires — a crash, a breakpoint hit,
+/// a matching stderr line or an explicit flush — so tracing can stay on for
+/// intermittent bugs. Output, crashes and other events are recorded as usual.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FlightRecorderConfig {
+    /// false stops buffering; what is buffered is written out first
+    pub enabled: bool,
+    /// Traced events kept; older ones are overwritten (default 50000)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub capacity: Option<u32>,
+    /// stderr substrings that flush the buffer (default: common assertion
+    /// and panic messages); `[]` flushes only on crash, breakpoint or request
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub flush_on_output: Option<Vec<String>>,
+}
+
+pub const DEFAULT_FLIGHT_RECORDER_CAPACITY: u32 = 50_000;
+pub const MAX_FLIGHT_RECORDER_CAPACITY: u32 = 1_000_000;
+/// stderr substrings that flush the flight recorder unless flushOnOutput is given
+pub const DEFAULT_FLIGHT_RECORDER_TRIGGERS: &[&str] = &[
+    "Assertion",
+    "assertion failed",
+    "panicked at",
+    "AddressSanitizer",
+];
+
+impl FlightRecorderConfig {
+    pub fn validate(&self) -> crate::Result<()> {
+        if self
+            .capacity
+            .is_some_and(|c| c == 0 || c > MAX_FLIGHT_RECORDER_CAPACITY)
+        {
+            return Err(crate::Error::ValidationError(format!(
+                "flightRecorder.capacity must be between 1 and {}",
+                MAX_FLIGHT_RECORDER_CAPACITY
+            )));
+        }
+        if let Some(ref triggers) = self.flush_on_output {
+            if triggers.len() > MAX_OUTPUT_TRIGGERS {
+                return Err(crate::Error::ValidationError(format!(
+                    "flightRecorder.flushOnOutput has {} entries, max is {}",
+                    triggers.len(),
+                    MAX_OUTPUT_TRIGGERS
+                )));
+            }
+            if triggers
+                .iter()
+                .any(|t| t.is_empty() || t.len() > MAX_OUTPUT_TRIGGER_MATCH_LENGTH)
+            {
+                return Err(crate::Error::ValidationError(format!(
+                    "flightRecorder.flushOnOutput entries must be 1-{} bytes",
+                    MAX_OUTPUT_TRIGGER_MATCH_LENGTH
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Capacity and triggers filled in with their defaults.
+    pub fn resolved(mut self) -> Self {
+        self.capacity = Some(self.capacity.unwrap_or(DEFAULT_FLIGHT_RECORDER_CAPACITY));
+        if self.flush_on_output.is_none() {
+            self.flush_on_output = Some(
+                DEFAULT_FLIGHT_RECORDER_TRIGGERS
+                    .iter()
+                    .map(|t| t.to_string())
+                    .collect(),
+            );
+        }
+        self
+    }
+}
+
+/// Signal-handler safety audit. The agent hooks signal handler registration
+/// (and the handlers already installed), and while a thread runs a handler
+/// every call to a function that is not async-signal-safe is recorded as a
+/// `signal_safety` event with a backtrace, once per handler/function/call site.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SignalSafetyConfig {
+    /// false stops the audit
+    pub enabled: bool,
+    /// Exported functions to flag instead of the built-in list (allocation,
+    /// stdio, pthread locks, exit, libc functions with static state)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub functions: Option<Vec<String>>,
+}
+
+pub const MAX_SIGNAL_SAFETY_FUNCTIONS: usize = 64;
+
+impl SignalSafetyConfig {
+    pub fn validate(&self) -> crate::Result<()> {
+        if let Some(ref functions) = self.functions {
+            if functions.is_empty() || functions.len() > MAX_SIGNAL_SAFETY_FUNCTIONS {
+                return Err(crate::Error::ValidationError(format!(
+                    "signalSafety.functions must list 1-{} functions",
+                    MAX_SIGNAL_SAFETY_FUNCTIONS
+                )));
+            }
+            if let Some(f) = functions
+                .iter()
+                .find(|f| f.is_empty() || !f.chars().all(|c| c.is_ascii_alphanumeric() || c == '_'))
+            {
+                return Err(crate::Error::ValidationError(format!(
+                    "signalSafety.functions: '{}' is not an exported symbol name",
+                    f
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Meta-patterns for `debug_trace` add/remove that trace libc file and socket
+/// calls (`@syscalls:file`, `@syscalls:net`) instead of resolving functions.
+pub const SYSCALL_PATTERN_PREFIX: &str = "@syscalls:";
+
+/// A set of libc calls traced by a `@syscalls:` meta-pattern. Each call is a
+/// `syscall` event with its fd, path or peer address, byte count and result.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SyscallGroup {
+    /// open/openat/creat, read/write/pread/pwrite, close
+    File,
+    /// socket, connect/accept/bind/listen, send/sendto/recv/recvfrom, close
+    Net,
+}
+
+impl SyscallGroup {
+    pub const ALL: [SyscallGroup; 2] = [SyscallGroup::File, SyscallGroup::Net];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::File => "file",
+            Self::Net => "net",
+        }
+    }
+
+    /// The group a `@syscalls:<group>` pattern names, None for any other pattern
+    /// or an unknown group.
+    pub fn from_pattern(pattern: &str) -> Option<Self> {
+        match pattern.strip_prefix(SYSCALL_PATTERN_PREFIX)? {
+            "file" => Some(Self::File),
+            "net" => Some(Self::Net),
+            _ => None,
+        }
+    }
+
+    pub fn pattern(self) -> String {
+        format!("{}{}", SYSCALL_PATTERN_PREFIX, self.as_str())
+    }
+}
+
+/// Meta-pattern for `debug_trace` add/remove that records the hosts the
+/// process talks to: connects, DNS lookups, TLS server names and bytes.
+pub const NETWORK_PATTERN: &str = "@network";
+
+/// Meta-pattern for `debug_trace` add/remove that records each C++ throw
+/// (type via RTTI) and the catch site that received it.
+pub const EXCEPTIONS_PATTERN: &str = "@exceptions";
+
+/// Meta-pattern for `debug_trace` add/remove that records signals delivered
+/// to the process's handlers and the signals it sends with kill/raise.
+pub const SIGNALS_PATTERN: &str = "@signals";
+
+/// Meta-pattern for `debug_trace` add/remove that records contended and
+/// blocked mutex acquisitions with their wait and holder.
+pub const LOCKS_PATTERN: &str = "@locks";
+
+/// A `debug_trace` add/remove list split into the meta-patterns the agent
+/// hooks itself and the function patterns resolved against symbols.
+#[derive(Debug, Default, PartialEq)]
+pub struct MetaPatterns {
+    pub syscalls: Vec<SyscallGroup>,
+    pub network: bool,
+    pub exceptions: bool,
+    pub signals: bool,
+    pub locks: bool,
+    pub functions: Vec<String>,
+}
+
+impl MetaPatterns {
+    pub fn split(patterns: &[String]) -> Self {
+        let mut split = Self::default();
+        for pattern in patterns {
+            if let Some(group) = SyscallGroup::from_pattern(pattern) {
+                split.syscalls.push(group);
+            } else if pattern == NETWORK_PATTERN {
+                split.network = true;
+            } else if pattern == EXCEPTIONS_PATTERN {
+                split.exceptions = true;
+            } else if pattern == SIGNALS_PATTERN {
+                split.signals = true;
+            } else if pattern == LOCKS_PATTERN {
+                split.locks = true;
+            } else {
+                split.functions.push(pattern.clone());
+            }
+        }
+        split
+    }
+
+    /// The function patterns, None when there are none
+    pub fn function_patterns(&self) -> Option<Vec<String>> {
+        (!self.functions.is_empty()).then(|| self.functions.clone())
+    }
+}
+
+/// Functions one `debug_trace` call may hook in count mode. Counting skips the
+/// ring buffer entirely, so it allows far more hooks than event tracing.
+pub const MAX_COUNT_HOOKS_PER_CALL: usize = 5000;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct WatchUpdate {
@@ -148,6 +742,10 @@ pub struct WatchTarget {
     /// If omitted, watch is global (captured on all traced functions).
     #[serde(skip_serializing_if = "Option::is_none")]
     pub on: Option<Vec<String>>,
+    /// Only record the value when it moved by at least this much since the
+    /// last recorded value; recorded values carry the number of reads skipped.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub delta_threshold: Option<f64>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -160,6 +758,94 @@ pub struct ActiveWatch {
     pub type_name: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub on: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub delta_threshold: Option<f64>,
+}
+
+/// Result of `debug_trace({ lint })`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DebugTraceLintResponse {
+    pub lints: Vec<crate::symbols::PatternLint>,
+    /// Patterns with nothing to report
+    pub clean: Vec<String>,
+    /// Whether namespaces were checked against the session's debug symbols
+    pub symbols_checked: bool,
+}
+
+/// Result of `debug_trace({ preview })`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DebugTracePreviewResponse {
+    pub patterns: Vec<PatternPreview>,
+    /// Distinct functions matched across all patterns
+    pub total_functions: usize,
+    /// Functions one `debug_trace({ add })` call hooks in the default mode
+    pub hook_limit: usize,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<String>,
+}
+
+/// What one pattern would hook.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PatternPreview {
+    pub pattern: String,
+    /// Functions the pattern matches
+    pub matched: usize,
+    /// Matched functions, up to MAX_PREVIEW_FUNCTIONS
+    pub functions: Vec<PreviewFunction>,
+    /// Matches per source file, most first, up to MAX_PREVIEW_FILES
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub files: Vec<PreviewFile>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PreviewFunction {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line: Option<u32>,
+    /// Symbol module the function lives in, when not the main binary
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub module: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PreviewFile {
+    pub file: String,
+    pub functions: usize,
+}
+
+impl PatternPreview {
+    /// Summarize `functions`, the full match list of `pattern`.
+    pub fn new(pattern: String, mut functions: Vec<PreviewFunction>) -> Self {
+        let mut counts: std::collections::HashMap<&str, usize> = std::collections::HashMap::new();
+        for file in functions.iter().filter_map(|f| f.file.as_deref()) {
+            *counts.entry(file).or_default() += 1;
+        }
+        let mut files: Vec<PreviewFile> = counts
+            .into_iter()
+            .map(|(file, functions)| PreviewFile {
+                file: file.to_string(),
+                functions,
+            })
+            .collect();
+        files.sort_by(|a, b| b.functions.cmp(&a.functions).then(a.file.cmp(&b.file)));
+        files.truncate(MAX_PREVIEW_FILES);
+
+        let matched = functions.len();
+        functions.truncate(MAX_PREVIEW_FUNCTIONS);
+        Self {
+            pattern,
+            matched,
+            functions,
+            files,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -169,6 +855,12 @@ pub struct DebugTraceResponse {
     pub mode: String,
     /// Active trace patterns
     pub active_patterns: Vec<String>,
+    /// Subset of active_patterns hooked in count mode
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub counted_patterns: Vec<String>,
+    /// Subset of active_patterns hooked in rt mode
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub rt_patterns: Vec<String>,
     /// Number of functions actually hooked (0 if pending or no matches)
     pub hooked_functions: u32,
     /// If different from hooked_functions, shows total matched before hook limit
@@ -178,11 +870,45 @@ pub struct DebugTraceResponse {
     pub active_watches: Vec<ActiveWatch>,
     #[serde(skip_serializing_if = "Vec::is_empty", default)]
     pub warnings: Vec<String>,
-    pub event_limit: usize,
-    /// Contextual status message explaining current state
+    /// Active stack sampling (rate resolved), if any
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub status: Option<String>,
-}
+    pub sampling: Option<SamplingConfig>,
+    /// Active thread filter, if any
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub threads: Option<ThreadFilter>,
+    /// Active per-function rate limit, if any
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rate_limit: Option<RateLimit>,
+    /// Active flight recorder (defaults resolved), if any
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub flight_recorder: Option<FlightRecorderConfig>,
+    /// Buffered calls written to the database by this request (flush, or
+    /// disabling the recorder)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub flushed_events: Option<u64>,
+    /// Active signal-handler safety audit, if any
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signal_safety: Option<SignalSafetyConfig>,
+    /// Traced `@syscalls:` groups
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub syscalls: Vec<SyscallGroup>,
+    /// `@network` is traced
+    #[serde(skip_serializing_if = "std::ops::Not::not", default)]
+    pub network: bool,
+    /// `@exceptions` is traced
+    #[serde(skip_serializing_if = "std::ops::Not::not", default)]
+    pub exceptions: bool,
+    /// `@signals` is traced
+    #[serde(skip_serializing_if = "std::ops::Not::not", default)]
+    pub signals: bool,
+    /// `@locks` is traced
+    #[serde(skip_serializing_if = "std::ops::Not::not", default)]
+    pub locks: bool,
+    pub event_limit: usize,
+    /// Contextual status message explaining current state
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<String>,
+}
 
 // Validation limits
 pub const MAX_WATCHES_PER_SESSION: usize = 32;
@@ -225,6 +951,54 @@ impl DebugTraceRequest {
             }
         }
 
+        if let Some(ref lint) = self.lint {
+            if lint.is_empty() || lint.len() > MAX_LINT_PATTERNS {
+                return Err(crate::Error::ValidationError(format!(
+                    "lint takes 1-{} patterns",
+                    MAX_LINT_PATTERNS
+                )));
+            }
+            if self.add.is_some() || self.remove.is_some() {
+                return Err(crate::Error::ValidationError(
+                    "lint only checks patterns; send add/remove in a separate call".to_string(),
+                ));
+            }
+        }
+
+        if let Some(ref preview) = self.preview {
+            if preview.is_empty() || preview.len() > MAX_PREVIEW_PATTERNS {
+                return Err(crate::Error::ValidationError(format!(
+                    "preview takes 1-{} patterns",
+                    MAX_PREVIEW_PATTERNS
+                )));
+            }
+            if self.session_id.is_none() {
+                return Err(crate::Error::ValidationError(
+                    "preview requires sessionId (patterns are matched against a running session's symbols)"
+                        .to_string(),
+                ));
+            }
+            if self.add.is_some() || self.remove.is_some() || self.lint.is_some() {
+                return Err(crate::Error::ValidationError(
+                    "preview only lists matches; send add/remove/lint in a separate call"
+                        .to_string(),
+                ));
+            }
+        }
+
+        // Patterns that can't match as written are rejected with a rewrite
+        for pattern in self.add.iter().flatten() {
+            if let Some(lint) = crate::symbols::lint_pattern(pattern)
+                .into_iter()
+                .find(|l| l.severity == crate::symbols::LintSeverity::Error)
+            {
+                return Err(crate::Error::InvalidPattern {
+                    pattern: pattern.clone(),
+                    reason: lint.reason(),
+                });
+            }
+        }
+
         if let Some(ref watch_update) = self.watches {
             if let Some(ref add_watches) = watch_update.add {
                 if add_watches.len() > MAX_WATCHES_PER_SESSION {
@@ -242,10 +1016,127 @@ impl DebugTraceRequest {
                     if let Some(ref var) = watch.variable {
                         validate_watch_field(var, "variable")?;
                     }
+                    if watch
+                        .delta_threshold
+                        .is_some_and(|t| !t.is_finite() || t <= 0.0)
+                    {
+                        return Err(crate::Error::ValidationError(
+                            "deltaThreshold must be a positive number".to_string(),
+                        ));
+                    }
                 }
             }
         }
 
+        if let Some(ref sampling) = self.sampling {
+            if self.session_id.is_none() {
+                return Err(crate::Error::ValidationError(
+                    "sampling requires sessionId (samples are taken from a running session)"
+                        .to_string(),
+                ));
+            }
+            sampling.validate()?;
+        }
+
+        if let Some(ref threads) = self.threads {
+            if self.session_id.is_none() {
+                return Err(crate::Error::ValidationError(
+                    "threads requires sessionId (the filter is applied by a running session's agent)"
+                        .to_string(),
+                ));
+            }
+            threads.validate()?;
+        }
+
+        if let Some(ref rate_limit) = self.rate_limit {
+            if self.session_id.is_none() {
+                return Err(crate::Error::ValidationError(
+                    "rateLimit requires sessionId (the limit is applied by a running session's agent)"
+                        .to_string(),
+                ));
+            }
+            rate_limit.validate()?;
+        }
+
+        if self.session_id.is_none() && (self.flight_recorder.is_some() || self.flush.is_some()) {
+            return Err(crate::Error::ValidationError(
+                "flightRecorder and flush require sessionId (the buffer lives in a running session's agent)"
+                    .to_string(),
+            ));
+        }
+        if let Some(ref recorder) = self.flight_recorder {
+            recorder.validate()?;
+        }
+
+        let has_meta_patterns = self
+            .add
+            .iter()
+            .chain(self.remove.iter())
+            .flatten()
+            .any(|p| {
+                p.starts_with(SYSCALL_PATTERN_PREFIX)
+                    || p == NETWORK_PATTERN
+                    || p == EXCEPTIONS_PATTERN
+                    || p == SIGNALS_PATTERN
+                    || p == LOCKS_PATTERN
+            });
+        if has_meta_patterns && self.session_id.is_none() {
+            return Err(crate::Error::ValidationError(
+                "@syscalls:, @network, @exceptions, @signals and @locks require sessionId (the calls are hooked in a running session)"
+                    .to_string(),
+            ));
+        }
+        if has_meta_patterns && self.mode == Some(TraceMode::Count) {
+            return Err(crate::Error::ValidationError(
+                "@syscalls:, @network, @exceptions, @signals and @locks record events; they can't be counted"
+                    .to_string(),
+            ));
+        }
+        if has_meta_patterns && self.mode == Some(TraceMode::Rt) {
+            return Err(crate::Error::ValidationError(
+                "@syscalls:, @network, @exceptions, @signals and @locks run JS hooks; they can't be traced in mode 'rt'"
+                    .to_string(),
+            ));
+        }
+
+        if let Some(ref audit) = self.signal_safety {
+            if self.session_id.is_none() {
+                return Err(crate::Error::ValidationError(
+                    "signalSafety requires sessionId (handlers are hooked in a running session)"
+                        .to_string(),
+                ));
+            }
+            audit.validate()?;
+        }
+
+        if self.mode == Some(TraceMode::Count) {
+            if self.session_id.is_none() {
+                return Err(crate::Error::ValidationError(
+                    "mode 'count' requires sessionId (count hooks are installed on a running session)"
+                        .to_string(),
+                ));
+            }
+            if self.add.as_ref().is_none_or(|a| a.is_empty()) {
+                return Err(crate::Error::ValidationError(
+                    "mode 'count' applies to the patterns in 'add'".to_string(),
+                ));
+            }
+        }
+
+        if self.mode == Some(TraceMode::Rt) {
+            if self.session_id.is_none() {
+                return Err(crate::Error::ValidationError(
+                    "mode 'rt' requires sessionId (rt hooks are installed on a running session)"
+                        .to_string(),
+                ));
+            }
+            if self.add.as_ref().is_none_or(|a| a.is_empty()) {
+                return Err(crate::Error::ValidationError(
+                    "mode 'rt' applies to the patterns in 'add'".to_string(),
+                ));
+            }
+        }
+
         Ok(())
     }
 }
@@ -264,6 +1155,22 @@ pub enum EventTypeFilter {
     Pause,
     Logpoint,
     ConditionError,
+    AuxOutput,
+    Watchpoint,
+    OutputTrigger,
+    CallCounts,
+    Sample,
+    SignalSafety,
+    Leak,
+    Syscall,
+    Network,
+    RtWarning,
+    Recovery,
+    Panic,
+    Exception,
+    Signal,
+    Lock,
+    ConfigChange,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -304,6 +1211,49 @@ pub struct ThreadNameFilter {
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
+pub struct QueueNameFilter {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub equals: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub contains: Option<String>,
+}
+
+/// Search over stdout/stderr/aux_output text. `contains` is case-insensitive
+/// and served by the full-text index; `regex` uses Rust regex syntax.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TextFilter {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub contains: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub regex: Option<String>,
+}
+
+pub const MAX_TEXT_FILTER_LENGTH: usize = 1024;
+
+impl TextFilter {
+    pub fn validate(&self) -> crate::Result<()> {
+        for (name, value) in [("contains", &self.contains), ("regex", &self.regex)] {
+            if let Some(v) = value {
+                if v.is_empty() || v.len() > MAX_TEXT_FILTER_LENGTH {
+                    return Err(crate::Error::ValidationError(format!(
+                        "text.{} must be 1-{} bytes",
+                        name, MAX_TEXT_FILTER_LENGTH
+                    )));
+                }
+            }
+        }
+        if let Some(ref re) = self.regex {
+            regex::Regex::new(re).map_err(|e| {
+                crate::Error::ValidationError(format!("text.regex is not a valid regex: {}", e))
+            })?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct DebugQueryRequest {
     pub session_id: String,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -316,6 +1266,15 @@ pub struct DebugQueryRequest {
     pub return_value: Option<ReturnValueFilter>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub thread_name: Option<ThreadNameFilter>,
+    /// libdispatch queue label (GCD apps)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub queue_name: Option<QueueNameFilter>,
+    /// Output text search (stdout/stderr/aux_output only)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub text: Option<TextFilter>,
+    /// Structured log field equality, e.g. `{ "level": "error" }` (launch `logFormat`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fields: Option<std::collections::BTreeMap<String, serde_json::Value>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub time_from: Option<serde_json::Value>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -324,6 +1283,15 @@ pub struct DebugQueryRequest {
     pub min_duration_ns: Option<i64>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub pid: Option<u32>,
+    /// Events from child processes spawned while this test was running (debug_test)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub test_name: Option<String>,
+    /// Both events of one call (function_enter/function_exit `callId`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub call_id: Option<String>,
+    /// `paired` returns one record per completed call instead of events
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub format: Option<QueryFormat>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub limit: Option<u32>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -333,6 +1301,147 @@ pub struct DebugQueryRequest {
     /// Cursor: return only events with rowid > after_event_id (for incremental polling)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub after_event_id: Option<i64>,
+    /// Cursor: return only events with seq > after_seq, in seq order. Unlike
+    /// rowids, seqs are stable across daemon restarts for retained sessions.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub after_seq: Option<i64>,
+    /// Projection: only these fields per event, dotted paths reaching into
+    /// JSON values (`watchValues.gTempo`, `arguments.0`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub select: Option<Vec<String>>,
+    /// Overview: the distinct values of this field among matching events
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub distinct: Option<QueryGroupField>,
+    /// Overview: matching events counted per value of this field
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub group_by: Option<QueryGroupField>,
+}
+
+pub const MAX_LOG_FIELD_FILTERS: usize = 16;
+pub const MAX_SELECT_FIELDS: usize = 32;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QueryFormat {
+    #[default]
+    Events,
+    /// Each function_exit merged with its function_enter: arguments, return
+    /// value and duration in one record
+    Paired,
+}
+
+/// Event field a debug_query `distinct` or `groupBy` aggregates over.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum QueryGroupField {
+    Function,
+    ThreadName,
+    SourceFile,
+}
+
+impl QueryGroupField {
+    /// The events column holding the field.
+    pub fn column(&self) -> &'static str {
+        match self {
+            QueryGroupField::Function => "function_name",
+            QueryGroupField::ThreadName => "thread_name",
+            QueryGroupField::SourceFile => "source_file",
+        }
+    }
+}
+
+impl DebugQueryRequest {
+    pub fn validate(&self) -> crate::Result<()> {
+        if let Some(ref text) = self.text {
+            text.validate()?;
+        }
+        if let Some(ref fields) = self.fields {
+            if fields.len() > MAX_LOG_FIELD_FILTERS {
+                return Err(crate::Error::ValidationError(format!(
+                    "fields has {} entries, max is {}",
+                    fields.len(),
+                    MAX_LOG_FIELD_FILTERS
+                )));
+            }
+            for (key, value) in fields {
+                if key.is_empty() || key.contains('"') {
+                    return Err(crate::Error::ValidationError(format!(
+                        "fields: invalid key '{}'",
+                        key
+                    )));
+                }
+                if value.is_object() || value.is_array() || value.is_null() {
+                    return Err(crate::Error::ValidationError(format!(
+                        "fields.{} must be a string, number or boolean",
+                        key
+                    )));
+                }
+            }
+        }
+        if let Some(ref select) = self.select {
+            if select.is_empty() || select.len() > MAX_SELECT_FIELDS {
+                return Err(crate::Error::ValidationError(format!(
+                    "select needs 1 to {} fields, got {}",
+                    MAX_SELECT_FIELDS,
+                    select.len()
+                )));
+            }
+            for path in select {
+                if path.split('.').any(|segment| segment.is_empty()) {
+                    return Err(crate::Error::ValidationError(format!(
+                        "select: invalid field path '{}'",
+                        path
+                    )));
+                }
+            }
+        }
+        if self.format == Some(QueryFormat::Paired)
+            && !matches!(
+                self.event_type,
+                None | Some(EventTypeFilter::FunctionEnter) | Some(EventTypeFilter::FunctionExit)
+            )
+        {
+            return Err(crate::Error::ValidationError(
+                "format 'paired' returns function calls; eventType must be omitted or function_enter/function_exit"
+                    .to_string(),
+            ));
+        }
+        if self.distinct.is_some() || self.group_by.is_some() {
+            if self.distinct.is_some() && self.group_by.is_some() {
+                return Err(crate::Error::ValidationError(
+                    "Pass either distinct or groupBy, not both".to_string(),
+                ));
+            }
+            if self.format == Some(QueryFormat::Paired) || self.select.is_some() {
+                return Err(crate::Error::ValidationError(
+                    "distinct and groupBy return values, not events; they can't be combined with format 'paired' or select"
+                        .to_string(),
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// The aggregated field and whether only distinct values were asked for.
+    pub fn group_field(&self) -> Option<(QueryGroupField, bool)> {
+        match (self.distinct, self.group_by) {
+            (Some(field), _) => Some((field, true)),
+            (None, Some(field)) => Some((field, false)),
+            (None, None) => None,
+        }
+    }
+
+    /// Field filters as (key, value) strings, the form stored values compare against.
+    pub fn log_field_filters(&self) -> Vec<(String, String)> {
+        self.fields
+            .iter()
+            .flatten()
+            .map(|(k, v)| match v {
+                serde_json::Value::String(s) => (k.clone(), s.clone()),
+                other => (k.clone(), other.to_string()),
+            })
+            .collect()
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -347,12 +1456,46 @@ pub struct DebugQueryResponse {
     /// Highest event rowid in this response (use as next cursor)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub last_event_id: Option<i64>,
+    /// Highest event seq in this response (next `afterSeq` cursor)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_seq: Option<i64>,
     /// True if FIFO eviction happened since the cursor position
     #[serde(skip_serializing_if = "Option::is_none")]
     pub events_dropped: Option<bool>,
     /// Crash event, if the process crashed. Always included regardless of eventType filter.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub crash: Option<serde_json::Value>,
+    /// True when the query hit the statement timeout: `events` holds only the
+    /// rows read before the cutoff and `totalCount` may be a lower bound.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub partial: Option<bool>,
+}
+
+/// debug_query with `distinct` or `groupBy`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DebugQueryGroupsResponse {
+    pub field: QueryGroupField,
+    /// distinct: the values, sorted
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub values: Option<Vec<String>>,
+    /// groupBy: each value with its number of matching events, most frequent first
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub groups: Option<Vec<QueryGroup>>,
+    /// Distinct values among matching events (before limit/offset)
+    pub total_groups: u64,
+    pub has_more: bool,
+    /// True when the query hit the statement timeout and returned nothing
+    /// past the cutoff.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub partial: Option<bool>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QueryGroup {
+    pub value: String,
+    pub count: u64,
 }
 
 // ============ debug_stop ============
@@ -370,6 +1513,12 @@ pub struct DebugStopRequest {
 pub struct DebugStopResponse {
     pub success: bool,
     pub events_collected: u64,
+    /// Final debug_heap report when allocation tracking was running
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub heap: Option<DebugHeapResponse>,
+    /// Leak groups stored as `leak` events (retain: true with heap tracking)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub leak_events: Option<usize>,
 }
 
 // ============ debug_read ============
@@ -389,6 +1538,35 @@ pub struct ReadTarget {
     /// Type hint for raw address reads: i8/u8/i16/u16/i32/u32/i64/u64/f32/f64/pointer/bytes
     #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
     pub type_hint: Option<String>,
+    /// "hexdump" returns the bytes as annotated hexdump lines instead of a value
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub format: Option<ReadFormat>,
+    /// Raw memory range, always returned as a hexdump
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub region: Option<MemoryRegion>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReadFormat {
+    #[default]
+    Value,
+    Hexdump,
+}
+
+/// `length` bytes from the absolute runtime address `base`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MemoryRegion {
+    pub base: String,
+    pub length: u32,
+}
+
+impl MemoryRegion {
+    pub fn base_address(&self) -> Option<u64> {
+        let hex = self.base.trim_start_matches("0x").trim_start_matches("0X");
+        u64::from_str_radix(hex, 16).ok()
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -417,6 +1595,8 @@ pub const MAX_POLL_INTERVAL_MS: u32 = 5000;
 pub const MIN_POLL_DURATION_MS: u32 = 100;
 pub const MAX_POLL_DURATION_MS: u32 = 30000;
 pub const MAX_RAW_READ_SIZE: u32 = 65536;
+/// Bytes shown by a hexdump; longer variables are cut off.
+pub const MAX_HEXDUMP_BYTES: u32 = 4096;
 const VALID_TYPE_HINTS: &[&str] = &[
     "i8", "u8", "i16", "u16", "i32", "u32", "i64", "u64", "f32", "f64", "pointer", "bytes",
 ];
@@ -458,12 +1638,51 @@ impl DebugReadRequest {
             }
         }
         for target in &self.targets {
+            let hexdump = target.format == Some(ReadFormat::Hexdump) || target.region.is_some();
+            if hexdump && self.poll.is_some() {
+                return Err(crate::Error::ValidationError(
+                    "Hexdump and region targets can't be polled".to_string(),
+                ));
+            }
+            if let Some(ref region) = target.region {
+                if target.variable.is_some() || target.address.is_some() {
+                    return Err(crate::Error::ValidationError(
+                        "A region target can't also have 'variable' or 'address'".to_string(),
+                    ));
+                }
+                if region.base_address().is_none() {
+                    return Err(crate::Error::ValidationError(format!(
+                        "region.base '{}' is not a hex address",
+                        region.base
+                    )));
+                }
+                if region.length == 0 || region.length > MAX_HEXDUMP_BYTES {
+                    return Err(crate::Error::ValidationError(format!(
+                        "region.length must be between 1 and {}",
+                        MAX_HEXDUMP_BYTES
+                    )));
+                }
+                continue;
+            }
             if target.variable.is_none() && target.address.is_none() {
                 return Err(crate::Error::ValidationError(
-                    "Each target must have either 'variable' or 'address'".to_string(),
+                    "Each target must have 'variable', 'address' or 'region'".to_string(),
                 ));
             }
-            if target.address.is_some() {
+            if hexdump {
+                if let Some(size) = target.size {
+                    if size == 0 || size > MAX_HEXDUMP_BYTES {
+                        return Err(crate::Error::ValidationError(format!(
+                            "size must be between 1 and {} for hexdump",
+                            MAX_HEXDUMP_BYTES
+                        )));
+                    }
+                } else if target.address.is_some() {
+                    return Err(crate::Error::ValidationError(
+                        "Hexdump of a raw address requires 'size'".to_string(),
+                    ));
+                }
+            } else if target.address.is_some() {
                 if target.size.is_none() || target.type_hint.is_none() {
                     return Err(crate::Error::ValidationError(
                         "Raw address targets require 'size' and 'type'".to_string(),
@@ -518,6 +1737,9 @@ pub struct ReadResult {
     /// Hex preview for bytes-type reads
     #[serde(skip_serializing_if = "Option::is_none")]
     pub preview: Option<String>,
+    /// Annotated hexdump lines for region and `format: "hexdump"` reads
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hexdump: Option<Vec<String>>,
 }
 
 impl Default for ReadResult {
@@ -532,6 +1754,7 @@ impl Default for ReadResult {
             error: None,
             file: None,
             preview: None,
+            hexdump: None,
         }
     }
 }
@@ -553,6 +1776,8 @@ pub struct DebugReadPollResponse {
     pub duration_ms: u32,
     pub expected_samples: u32,
     pub event_type: String,
+    /// Tags the poll's events; read them back with debug_memory action "series"
+    pub poll_id: String,
     pub hint: String,
 }
 
@@ -563,12 +1788,46 @@ pub struct DebugReadPollResponse {
 pub enum TestAction {
     Run,
     Status,
+    List,
+}
+
+/// How the test process is spawned.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TestInstrumentation {
+    /// Plain subprocess: output capture, progress, baselines and stuck detection
+    /// still work, but tracing, breakpoints, watches and memory access are disabled.
+    None,
+    /// Spawn inside Frida so trace patterns can be added at any time (default).
+    #[default]
+    Frida,
+}
+
+impl TestInstrumentation {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::None => "none",
+            Self::Frida => "frida",
+        }
+    }
+}
+
+/// Execution order for a full-suite run.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum TestOrder {
+    /// Whatever order the framework chooses (default).
+    #[default]
+    Default,
+    /// Recently-failed tests first, then tests touched by recent git changes.
+    FailFastSmart,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct DebugTestRequest {
-    /// Action: "run" (default) starts a test, "status" polls for results
+    /// Action: "run" (default) starts a test, "status" polls for results,
+    /// "list" returns the test inventory without running anything
     #[serde(skip_serializing_if = "Option::is_none")]
     pub action: Option<TestAction>,
     /// Required for action: "status" — the test run ID to poll
@@ -597,6 +1856,12 @@ pub struct DebugTestRequest {
     /// Use when automatic symbol resolution fails in complex projects.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub symbols_path: Option<String>,
+    /// "frida" (default) runs tests inside Frida; "none" runs a plain subprocess.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub instrumentation: Option<TestInstrumentation>,
+    /// "fail-fast-smart" runs likely failures first (full-suite runs only).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub order: Option<TestOrder>,
 }
 
 impl DebugTestRequest {
@@ -609,12 +1874,36 @@ impl DebugTestRequest {
                     ));
                 }
             }
+            TestAction::List => {
+                if self.project_root.is_empty() {
+                    return Err(crate::Error::ValidationError(
+                        "projectRoot is required for action: 'list'".to_string(),
+                    ));
+                }
+            }
             TestAction::Run => {
                 if self.project_root.is_empty() {
                     return Err(crate::Error::ValidationError(
                         "projectRoot is required for action: 'run'".to_string(),
                     ));
                 }
+                if self.instrumentation == Some(TestInstrumentation::None) {
+                    let has_traces = self.trace_patterns.as_ref().is_some_and(|p| !p.is_empty());
+                    if has_traces || self.watches.is_some() {
+                        return Err(crate::Error::ValidationError(
+                            "tracePatterns and watches require instrumentation: 'frida'. \
+                             Remove them or omit instrumentation to run inside Frida."
+                                .to_string(),
+                        ));
+                    }
+                }
+                if self.order == Some(TestOrder::FailFastSmart) && self.test.is_some() {
+                    return Err(crate::Error::ValidationError(
+                        "order: 'fail-fast-smart' applies to full-suite runs. \
+                         Remove 'test' or omit order."
+                            .to_string(),
+                    ));
+                }
             }
         }
         Ok(())
@@ -643,16 +1932,36 @@ pub struct DebugTestResponse {
     pub hint: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub crash_info: Option<CrashSummary>,
-}
-
-// ============ debug_test (async start response) ============
-
+    /// Set to "none" when the run was not instrumented (omitted for Frida runs).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub instrumentation: Option<TestInstrumentation>,
+    /// Tests (or test files) moved to the front by order: "fail-fast-smart".
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub prioritized: Vec<String>,
+    /// Traced functions returning or timing differently than in the
+    /// project's last green traced run.
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub return_anomalies: Vec<crate::db::ReturnAnomaly>,
+    /// Toolchain versions and dependency lockfile hash the run used.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub provenance: Option<crate::test::provenance::Provenance>,
+    /// Toolchain or dependency changes since the project's previous run,
+    /// e.g. "rustc: rustc 1.78.0 → rustc 1.79.0".
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub toolchain_changes: Vec<String>,
+}
+
+// ============ debug_test (async start response) ============
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct DebugTestStartResponse {
     pub test_run_id: String,
     pub status: String,
     pub framework: String,
+    /// Set to "none" when the run was not instrumented (omitted for Frida runs).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub instrumentation: Option<TestInstrumentation>,
 }
 
 // ============ debug_test_status ============
@@ -750,6 +2059,7 @@ pub enum ErrorCode {
     WriteFailed,
     UiQueryFailed,
     UiNotAvailable,
+    PermissionRequired,
     InternalError,
 }
 
@@ -776,6 +2086,7 @@ impl From<crate::Error> for McpError {
             crate::Error::WriteFailed(_) => ErrorCode::WriteFailed,
             crate::Error::UiQueryFailed(_) => ErrorCode::UiQueryFailed,
             crate::Error::UiNotAvailable(_) => ErrorCode::UiNotAvailable,
+            crate::Error::PermissionRequired { .. } => ErrorCode::PermissionRequired,
             _ => ErrorCode::InternalError,
         };
 
@@ -898,20 +2209,43 @@ pub struct DebugBreakpointRequest {
 pub struct BreakpointTarget {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub function: Option<String>,
+    /// Bytes past the start of `function`, for a spot inside it that line
+    /// info doesn't reach
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub offset: Option<u64>,
+    /// Static address in the binary (as DWARF and disassembly show it), hex
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub address: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub file: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub line: Option<u32>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub condition: Option<String>,
+    /// Breakpoints pause on this hit only; logpoints log from this hit on
     #[serde(skip_serializing_if = "Option::is_none")]
     pub hit_count: Option<u32>,
+    /// Logpoints only: log every Nth hit (counting from `hit_count`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub every_n: Option<u32>,
+    /// Logpoints only: stop logging after this many messages
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_hits: Option<u32>,
     /// If present, this entry is a logpoint (non-blocking log on hit).
-    /// Use {args[0]}, {args[1]} for arguments, {threadId} for thread ID.
+    /// Use {args[0]}, {args[1]} for arguments, {threadId} for thread ID, and
+    /// on function logpoints {param} or {param.member} for typed parameters.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub message: Option<String>,
 }
 
+impl BreakpointTarget {
+    pub fn address_value(&self) -> Option<u64> {
+        let hex = self.address.as_ref()?;
+        let hex = hex.trim_start_matches("0x").trim_start_matches("0X");
+        u64::from_str_radix(hex, 16).ok()
+    }
+}
+
 impl DebugBreakpointRequest {
     pub fn validate(&self) -> crate::Result<()> {
         if self.session_id.is_empty() {
@@ -930,17 +2264,36 @@ impl DebugBreakpointRequest {
             }
 
             for target in targets {
-                // Must specify either function OR file:line
+                // Must specify either function OR file:line OR address
                 let has_function = target.function.is_some();
                 let has_file_line = target.file.is_some() && target.line.is_some();
 
-                if !has_function && !has_file_line {
+                if let Some(ref address) = target.address {
+                    if has_function || target.file.is_some() || target.line.is_some() {
+                        return Err(crate::Error::ValidationError(
+                            "Breakpoint target with 'address' cannot also specify 'function' or 'file'+'line'"
+                                .to_string(),
+                        ));
+                    }
+                    if target.address_value().is_none() {
+                        return Err(crate::Error::ValidationError(format!(
+                            "Invalid breakpoint address '{}': expected hex like 0x10000abcd",
+                            address
+                        )));
+                    }
+                } else if !has_function && !has_file_line {
                     return Err(crate::Error::ValidationError(
-                        "Breakpoint target must specify either 'function' or 'file'+'line'"
+                        "Breakpoint target must specify either 'function', 'file'+'line' or 'address'"
                             .to_string(),
                     ));
                 }
 
+                if target.offset.is_some() && !has_function {
+                    return Err(crate::Error::ValidationError(
+                        "'offset' is only valid with 'function'".to_string(),
+                    ));
+                }
+
                 if has_function && has_file_line {
                     return Err(crate::Error::ValidationError(
                         "Breakpoint target cannot specify both 'function' and 'file'+'line'"
@@ -973,6 +2326,27 @@ impl DebugBreakpointRequest {
                     }
                 }
 
+                if target.message.is_none()
+                    && (target.every_n.is_some() || target.max_hits.is_some())
+                {
+                    return Err(crate::Error::ValidationError(
+                        "everyN and maxHits are only valid for logpoints (entries with 'message')"
+                            .to_string(),
+                    ));
+                }
+                for (name, value) in [
+                    ("hitCount", target.hit_count),
+                    ("everyN", target.every_n),
+                    ("maxHits", target.max_hits),
+                ] {
+                    if value == Some(0) {
+                        return Err(crate::Error::ValidationError(format!(
+                            "{} must be at least 1",
+                            name
+                        )));
+                    }
+                }
+
                 // Logpoint-specific validation (message present)
                 if let Some(ref message) = target.message {
                     if message.is_empty() {
@@ -987,9 +2361,9 @@ impl DebugBreakpointRequest {
                             MAX_LOGPOINT_MESSAGE_LENGTH
                         )));
                     }
-                    if target.hit_count.is_some() {
+                    if target.address.is_some() || target.offset.is_some() {
                         return Err(crate::Error::ValidationError(
-                            "hit_count is not valid for logpoints (entries with 'message')"
+                            "Logpoints take 'function' or 'file'+'line'; 'address' and 'offset' are for breakpoints"
                                 .to_string(),
                         ));
                     }
@@ -1016,6 +2390,8 @@ pub struct BreakpointInfo {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub function: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    pub offset: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub file: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub line: Option<u32>,
@@ -1030,8 +2406,30 @@ pub struct DebugContinueRequest {
     pub session_id: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub action: Option<String>, // "continue", "step-over", "step-into", "step-out"
+    /// Continue to this function or line and pause there once
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub until: Option<ContinueUntil>,
+    /// Repeat the step action this many times
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub count: Option<u32>,
+}
+
+/// Target of `debug_continue` `until`: a temporary breakpoint removed by the
+/// next debug_continue.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContinueUntil {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub function: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line: Option<u32>,
 }
 
+/// Most steps one debug_continue `count` takes.
+pub const MAX_STEP_COUNT: u32 = 100;
+
 impl DebugContinueRequest {
     pub fn validate(&self) -> crate::Result<()> {
         if self.session_id.is_empty() {
@@ -1052,6 +2450,38 @@ impl DebugContinueRequest {
             }
         }
 
+        let stepping = self.action.as_deref().is_some_and(|a| a != "continue");
+        if let Some(until) = &self.until {
+            if stepping {
+                return Err(crate::Error::ValidationError(
+                    "until runs to a location; use it with action 'continue'".to_string(),
+                ));
+            }
+            match (&until.function, &until.file, until.line) {
+                (Some(f), None, None) if !f.is_empty() => {}
+                (None, Some(f), Some(_)) if !f.is_empty() => {}
+                _ => {
+                    return Err(crate::Error::ValidationError(
+                        "until needs either function or file+line".to_string(),
+                    ));
+                }
+            }
+        }
+        if let Some(count) = self.count {
+            if !stepping {
+                return Err(crate::Error::ValidationError(
+                    "count repeats a step; use it with step-over, step-into or step-out"
+                        .to_string(),
+                ));
+            }
+            if count == 0 || count > MAX_STEP_COUNT {
+                return Err(crate::Error::ValidationError(format!(
+                    "count must be between 1 and {}",
+                    MAX_STEP_COUNT
+                )));
+            }
+        }
+
         Ok(())
     }
 }
@@ -1068,6 +2498,13 @@ pub struct DebugContinueResponse {
     pub line: Option<u32>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub function: Option<String>,
+    /// Temporary breakpoint set by `until`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub until: Option<BreakpointInfo>,
+    /// Steps taken of a `count`; fewer when a breakpoint or a slow step
+    /// ended the run early
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub steps: Option<u32>,
 }
 
 // ============ debug_logpoint ============
@@ -1085,7 +2522,8 @@ pub struct DebugLogpointRequest {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct LogpointTarget {
-    /// Log message template. Use `{args[0]}`, `{args[1]}` etc for arguments.
+    /// Log message template. Use `{args[0]}`, `{args[1]}` etc for arguments,
+    /// or `{param}` / `{param.member}` for a function's typed parameters.
     pub message: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub function: Option<String>,
@@ -1095,6 +2533,12 @@ pub struct LogpointTarget {
     pub line: Option<u32>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub condition: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hit_count: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub every_n: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_hits: Option<u32>,
 }
 
 impl DebugLogpointRequest {
@@ -1184,6 +2628,123 @@ pub struct LogpointInfo {
     pub address: String,
 }
 
+// ============ debug_watchpoint ============
+
+pub const MAX_WATCHPOINTS_PER_SESSION: usize = 16;
+pub const MAX_WATCHPOINT_SIZE: u32 = 4096;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DebugWatchpointRequest {
+    pub session_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub add: Option<Vec<WatchpointTarget>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub remove: Option<Vec<String>>, // Watchpoint IDs
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WatchpointTarget {
+    /// Global/static variable (DWARF), e.g. "g_counter" or "gConfig->mode"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub variable: Option<String>,
+    /// Raw runtime address (hex), e.g. "0x7ff800001000"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub address: Option<String>,
+    /// Bytes to watch. Defaults to the variable's type size; required for raw addresses.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub size: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+}
+
+impl DebugWatchpointRequest {
+    pub fn validate(&self) -> crate::Result<()> {
+        if self.session_id.is_empty() {
+            return Err(crate::Error::ValidationError(
+                "sessionId must not be empty".to_string(),
+            ));
+        }
+
+        if let Some(targets) = &self.add {
+            if targets.len() > MAX_WATCHPOINTS_PER_SESSION {
+                return Err(crate::Error::ValidationError(format!(
+                    "Too many watchpoints: {} (max {})",
+                    targets.len(),
+                    MAX_WATCHPOINTS_PER_SESSION
+                )));
+            }
+
+            for target in targets {
+                match (&target.variable, &target.address) {
+                    (None, None) => {
+                        return Err(crate::Error::ValidationError(
+                            "Watchpoint target must specify either 'variable' or 'address'"
+                                .to_string(),
+                        ));
+                    }
+                    (Some(_), Some(_)) => {
+                        return Err(crate::Error::ValidationError(
+                            "Watchpoint target cannot specify both 'variable' and 'address'"
+                                .to_string(),
+                        ));
+                    }
+                    (None, Some(addr)) => {
+                        let hex = addr.trim_start_matches("0x").trim_start_matches("0X");
+                        if hex.is_empty() || u64::from_str_radix(hex, 16).is_err() {
+                            return Err(crate::Error::ValidationError(format!(
+                                "Invalid watchpoint address '{}': expected hex like 0x1000",
+                                addr
+                            )));
+                        }
+                        if target.size.is_none() {
+                            return Err(crate::Error::ValidationError(
+                                "Raw address watchpoints require 'size'".to_string(),
+                            ));
+                        }
+                    }
+                    (Some(var), None) => {
+                        if var.len() > MAX_WATCH_EXPRESSION_LENGTH {
+                            return Err(crate::Error::ValidationError(format!(
+                                "Watchpoint variable length ({} bytes) exceeds maximum of {} bytes",
+                                var.len(),
+                                MAX_WATCH_EXPRESSION_LENGTH
+                            )));
+                        }
+                    }
+                }
+
+                if let Some(size) = target.size {
+                    if size == 0 || size > MAX_WATCHPOINT_SIZE {
+                        return Err(crate::Error::ValidationError(format!(
+                            "size must be between 1 and {}",
+                            MAX_WATCHPOINT_SIZE
+                        )));
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DebugWatchpointResponse {
+    pub watchpoints: Vec<WatchpointInfo>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WatchpointInfo {
+    pub id: String,
+    pub label: String,
+    pub address: String, // Hex (DWARF-static for variables, runtime for raw addresses)
+    pub size: u32,
+}
+
 // ============ debug_memory (consolidated read + write) ============
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -1191,6 +2752,10 @@ pub struct LogpointInfo {
 pub enum MemoryAction {
     Read,
     Write,
+    /// A finished or running poll's samples as aligned columns
+    Series,
+    /// Local variables of a paused thread's frame
+    Locals,
 }
 
 impl Default for MemoryAction {
@@ -1211,6 +2776,10 @@ pub struct MemoryTarget {
     pub size: Option<u32>,
     #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
     pub type_hint: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub format: Option<ReadFormat>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub region: Option<MemoryRegion>,
     /// Value to write (required for action: "write", ignored for "read")
     #[serde(skip_serializing_if = "Option::is_none")]
     pub value: Option<serde_json::Value>,
@@ -1222,6 +2791,7 @@ pub struct DebugMemoryRequest {
     pub session_id: String,
     #[serde(default)]
     pub action: MemoryAction,
+    #[serde(default)]
     pub targets: Vec<MemoryTarget>,
     /// Max struct traversal depth for reads (1-5)
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -1229,6 +2799,22 @@ pub struct DebugMemoryRequest {
     /// Poll config for reads
     #[serde(skip_serializing_if = "Option::is_none")]
     pub poll: Option<PollConfig>,
+    /// Series: poll to read (default: the session's latest)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub poll_id: Option<String>,
+    /// Series: write a CSV file instead of returning the columns
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub csv: Option<bool>,
+    /// Series: CSV destination inside the project root
+    /// (default: <projectRoot>/exports/<sessionId>-<pollId>.csv)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output_path: Option<String>,
+    /// Locals: paused thread (default: first paused thread)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub thread_id: Option<u64>,
+    /// Locals: 0 = where the thread paused, N = its Nth caller (default 0)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub frame: Option<u32>,
 }
 
 impl DebugMemoryRequest {
@@ -1238,12 +2824,47 @@ impl DebugMemoryRequest {
                 "sessionId must not be empty".to_string(),
             ));
         }
+        if self.action == MemoryAction::Series {
+            if !self.targets.is_empty() || self.poll.is_some() {
+                return Err(crate::Error::ValidationError(
+                    "action 'series' reads a previous poll; pass pollId, not targets or poll"
+                        .to_string(),
+                ));
+            }
+            return Ok(());
+        }
+        if self.csv.is_some() || self.output_path.is_some() {
+            return Err(crate::Error::ValidationError(
+                "csv and outputPath only apply to action 'series'".to_string(),
+            ));
+        }
+        if self.action == MemoryAction::Locals {
+            if !self.targets.is_empty() || self.poll.is_some() {
+                return Err(crate::Error::ValidationError(
+                    "action 'locals' reads a paused frame; pass threadId and frame, not targets or poll"
+                        .to_string(),
+                ));
+            }
+            if self.frame.unwrap_or(0) > MAX_LOCALS_FRAME {
+                return Err(crate::Error::ValidationError(format!(
+                    "frame must be at most {}",
+                    MAX_LOCALS_FRAME
+                )));
+            }
+            return Ok(());
+        }
+        if self.thread_id.is_some() || self.frame.is_some() {
+            return Err(crate::Error::ValidationError(
+                "threadId and frame only apply to action 'locals'".to_string(),
+            ));
+        }
         if self.targets.is_empty() {
             return Err(crate::Error::ValidationError(
                 "targets must not be empty".to_string(),
             ));
         }
         match self.action {
+            MemoryAction::Series | MemoryAction::Locals => Ok(()),
             MemoryAction::Read => {
                 // Delegate validation to DebugReadRequest
                 let read_req = DebugReadRequest {
@@ -1256,6 +2877,8 @@ impl DebugMemoryRequest {
                             address: t.address.clone(),
                             size: t.size,
                             type_hint: t.type_hint.clone(),
+                            format: t.format,
+                            region: t.region.clone(),
                         })
                         .collect(),
                     depth: self.depth,
@@ -1292,6 +2915,104 @@ impl DebugMemoryRequest {
     }
 }
 
+/// Deepest caller debug_memory action "locals" walks to.
+pub const MAX_LOCALS_FRAME: u32 = 64;
+
+/// debug_memory action "locals"
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FrameLocalsResponse {
+    pub thread_id: u64,
+    pub frame: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub function: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line: Option<u32>,
+    /// DWARF-static address of the frame's pc
+    pub address: String,
+    /// `{name, value, type}` of each local whose location could be read
+    pub locals: Vec<serde_json::Value>,
+    /// Locals DWARF describes but whose value isn't available here
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub unavailable: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+}
+
+/// debug_memory action "series" with `csv: true`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MemorySeriesExport {
+    pub poll_id: String,
+    pub path: String,
+    pub samples: usize,
+    /// Target labels, in CSV column order after timestamp_ns
+    pub columns: Vec<String>,
+}
+
+// ============ debug_eval ============
+
+pub const MAX_EVAL_EXPR_LENGTH: usize = 1024;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DebugEvalRequest {
+    pub session_id: String,
+    pub expr: String,
+    /// Paused thread whose locals and arguments the expression reads
+    /// (default: first paused thread)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub thread_id: Option<u64>,
+    /// Frame whose locals names resolve to: 0 = where the thread paused,
+    /// N = its Nth caller (default 0)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub frame: Option<u32>,
+}
+
+impl DebugEvalRequest {
+    pub fn validate(&self) -> crate::Result<()> {
+        if self.session_id.is_empty() {
+            return Err(crate::Error::ValidationError(
+                "sessionId must not be empty".to_string(),
+            ));
+        }
+        if self.expr.trim().is_empty() {
+            return Err(crate::Error::ValidationError(
+                "expr must not be empty".to_string(),
+            ));
+        }
+        if self.expr.len() > MAX_EVAL_EXPR_LENGTH {
+            return Err(crate::Error::ValidationError(format!(
+                "expr exceeds {} character limit",
+                MAX_EVAL_EXPR_LENGTH
+            )));
+        }
+        if self.frame.unwrap_or(0) > MAX_LOCALS_FRAME {
+            return Err(crate::Error::ValidationError(format!(
+                "frame must be at most {}",
+                MAX_LOCALS_FRAME
+            )));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DebugEvalResponse {
+    pub expr: String,
+    pub value: serde_json::Value,
+    /// "int", "float", "bool" or "value" (struct/string, returned as read)
+    pub kind: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hex: Option<String>,
+    /// Functions called in the target while evaluating, with their results
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub calls: Vec<String>,
+}
+
 // ============ debug_session (consolidated stop + list + delete + status) ============
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -1301,6 +3022,14 @@ pub enum SessionAction {
     Stop,
     List,
     Delete,
+    #[serde(rename = "export-instrumentation")]
+    ExportInstrumentation,
+    #[serde(rename = "reload-agent")]
+    ReloadAgent,
+    #[serde(rename = "consistency-check")]
+    ConsistencyCheck,
+    /// Launch a stopped session's command again with its argv, cwd and env
+    Relaunch,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -1311,12 +3040,65 @@ pub struct DebugSessionRequest {
     pub session_id: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub retain: Option<bool>,
+    /// Output file for action: "export-instrumentation"
+    /// (default: <projectRoot>/.strobe/instrumentation/<sessionId>.json);
+    /// agent bundle for action: "reload-agent" (default: the built-in agent)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    /// For action: "consistency-check", also move exits stored before their
+    /// enter back after it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub repair: Option<bool>,
+    /// For action: "relaunch", whether the new session re-applies the
+    /// binary's last trace patterns and watches (default true)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub inherit_instrumentation: Option<bool>,
+    /// For action: "list", only sessions of this project
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub project_root: Option<String>,
 }
 
 impl DebugSessionRequest {
     pub fn validate(&self) -> crate::Result<()> {
+        if let Some(ref path) = self.path {
+            if !matches!(
+                self.action,
+                SessionAction::ExportInstrumentation | SessionAction::ReloadAgent
+            ) {
+                return Err(crate::Error::ValidationError(
+                    "path is only valid for actions: export-instrumentation, reload-agent"
+                        .to_string(),
+                ));
+            }
+            if path.is_empty() || path.contains("..") {
+                return Err(crate::Error::ValidationError(
+                    "path must be a non-empty path without '..' components".to_string(),
+                ));
+            }
+        }
+        if self.repair.is_some() && self.action != SessionAction::ConsistencyCheck {
+            return Err(crate::Error::ValidationError(
+                "repair is only valid for action: consistency-check".to_string(),
+            ));
+        }
+        if self.inherit_instrumentation.is_some() && self.action != SessionAction::Relaunch {
+            return Err(crate::Error::ValidationError(
+                "inheritInstrumentation is only valid for action: relaunch".to_string(),
+            ));
+        }
+        if self.project_root.is_some() && self.action != SessionAction::List {
+            return Err(crate::Error::ValidationError(
+                "projectRoot is only valid for action: list".to_string(),
+            ));
+        }
         match self.action {
-            SessionAction::Status | SessionAction::Stop | SessionAction::Delete => {
+            SessionAction::Status
+            | SessionAction::Stop
+            | SessionAction::Delete
+            | SessionAction::ExportInstrumentation
+            | SessionAction::ReloadAgent
+            | SessionAction::ConsistencyCheck
+            | SessionAction::Relaunch => {
                 if self.session_id.as_ref().map_or(true, |s| s.is_empty()) {
                     return Err(crate::Error::ValidationError(format!(
                         "sessionId is required for action: {:?}",
@@ -1330,17 +3112,206 @@ impl DebugSessionRequest {
     }
 }
 
+// ============ instrumentation snapshot ============
+
+pub const INSTRUMENTATION_SNAPSHOT_VERSION: u32 = 1;
+
+/// A session's observation setup, as written by debug_session
+/// "export-instrumentation" and read by debug_launch `instrumentationFile`.
+/// Entries use the same shapes as the tools that create them; resolved
+/// addresses are left out because they do not survive a relaunch.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
-pub struct BacktraceFrame {
-    pub address: String,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub module_name: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub function_name: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub file: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+pub struct InstrumentationSnapshot {
+    pub version: u32,
+    /// Session the snapshot was taken from
+    pub session_id: String,
+    pub binary_path: String,
+    pub exported_at: String,
+    #[serde(default)]
+    pub patterns: Vec<String>,
+    /// Subset of `patterns` hooked in count mode
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub counted_patterns: Vec<String>,
+    /// Subset of `patterns` hooked in rt mode
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub rt_patterns: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub watches: Vec<WatchTarget>,
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub watchpoints: Vec<WatchpointTarget>,
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub breakpoints: Vec<BreakpointTarget>,
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub logpoints: Vec<LogpointTarget>,
+    #[serde(default)]
+    pub settings: InstrumentationSettings,
+}
+
+/// Effective session settings that differ from the built-in defaults, so the
+/// replaying machine does not depend on its own `.strobe/settings.json`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InstrumentationSettings {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub event_limit: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub serialization_depth: Option<u32>,
+}
+
+impl InstrumentationSnapshot {
+    /// Nothing to install (settings alone don't count).
+    pub fn is_empty(&self) -> bool {
+        self.patterns.is_empty()
+            && self.watches.is_empty()
+            && self.watchpoints.is_empty()
+            && self.breakpoints.is_empty()
+            && self.logpoints.is_empty()
+    }
+
+    pub fn validate(&self) -> crate::Result<()> {
+        if self.version != INSTRUMENTATION_SNAPSHOT_VERSION {
+            return Err(crate::Error::ValidationError(format!(
+                "Unsupported instrumentation file version {} (expected {})",
+                self.version, INSTRUMENTATION_SNAPSHOT_VERSION
+            )));
+        }
+        DebugTraceRequest {
+            session_id: None,
+            add: None,
+            remove: None,
+            watches: Some(WatchUpdate {
+                add: Some(self.watches.clone()),
+                remove: None,
+            }),
+            serialization_depth: self.settings.serialization_depth,
+            project_root: None,
+            mode: None,
+            sampling: None,
+            threads: None,
+            rate_limit: None,
+            flight_recorder: None,
+            flush: None,
+            lint: None,
+            preview: None,
+            signal_safety: None,
+        }
+        .validate()?;
+        if let Some(limit) = self.settings.event_limit {
+            if limit == 0 || limit > crate::config::MAX_EVENT_LIMIT {
+                return Err(crate::Error::ValidationError(format!(
+                    "settings.eventLimit ({}) out of range (1..{})",
+                    limit,
+                    crate::config::MAX_EVENT_LIMIT
+                )));
+            }
+        }
+        if self.breakpoints.len() > MAX_BREAKPOINTS_PER_SESSION {
+            return Err(crate::Error::ValidationError(format!(
+                "Instrumentation file has {} breakpoints (max {})",
+                self.breakpoints.len(),
+                MAX_BREAKPOINTS_PER_SESSION
+            )));
+        }
+        if self.logpoints.len() > MAX_LOGPOINTS_PER_SESSION {
+            return Err(crate::Error::ValidationError(format!(
+                "Instrumentation file has {} logpoints (max {})",
+                self.logpoints.len(),
+                MAX_LOGPOINTS_PER_SESSION
+            )));
+        }
+        if self.watchpoints.len() > MAX_WATCHPOINTS_PER_SESSION {
+            return Err(crate::Error::ValidationError(format!(
+                "Instrumentation file has {} watchpoints (max {})",
+                self.watchpoints.len(),
+                MAX_WATCHPOINTS_PER_SESSION
+            )));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportInstrumentationResponse {
+    pub path: String,
+    pub patterns: usize,
+    pub watches: usize,
+    pub watchpoints: usize,
+    pub breakpoints: usize,
+    pub logpoints: usize,
+    /// Instrumentation that exists only as runtime addresses and was left out
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub omitted: Vec<String>,
+}
+
+/// Result of debug_session "reload-agent".
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReloadAgentResponse {
+    pub session_id: String,
+    /// Bundle that was loaded, or "built-in"
+    pub agent: String,
+    /// Time to replace the script
+    pub reload_ms: u64,
+    /// Time to re-install instrumentation on the new script
+    pub reapply_ms: u64,
+    pub patterns: usize,
+    pub hooked_functions: u32,
+    pub watches: usize,
+    pub watchpoints: usize,
+    pub breakpoints: usize,
+    pub logpoints: usize,
+    /// Instrumentation that could not be re-applied
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub warnings: Vec<String>,
+}
+
+/// Enter/exit pairing report for `debug_session` action: "consistency-check".
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConsistencyCheckResponse {
+    pub session_id: String,
+    /// function_exit events whose function_enter is not stored
+    pub orphan_exits: u64,
+    /// function_enter events with no stored function_exit
+    pub unclosed_enters: u64,
+    /// Exits stored or stamped before their enter (before any repair)
+    pub misordered_pairs: u64,
+    /// Pairs put back in order by this call (repair: true)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub repaired_pairs: Option<u64>,
+    /// Threads with orphans or unclosed calls, worst first
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub threads: Vec<ThreadConsistency>,
+    /// How to read the counts
+    pub note: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ThreadConsistency {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pid: Option<u32>,
+    pub thread_id: i64,
+    pub orphan_exits: u64,
+    pub unclosed_enters: u64,
+}
+
+/// Threads listed in a consistency-check report.
+pub const MAX_CONSISTENCY_THREADS: usize = 32;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BacktraceFrame {
+    pub address: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub module_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub function_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub line: Option<u32>,
 }
 
@@ -1373,11 +3344,60 @@ pub struct PausedThreadInfo {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct SessionStatusResponse {
-    pub status: String, // "running" | "paused" | "exited" | "crashed"
+    pub status: String, // "running" | "paused" | "exited" | "crashed" | "interrupted"
     pub pid: u32,
     pub event_count: u64,
     pub hooked_functions: u32,
+    /// Older trace events than this timestamp have been thinned by the
+    /// session's downsampling policy; later ones are complete
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub downsampled_until_ns: Option<i64>,
     pub trace_patterns: Vec<String>,
+    /// Subset of trace_patterns hooked in count mode
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub counted_patterns: Vec<String>,
+    /// Subset of trace_patterns hooked in rt mode
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub rt_patterns: Vec<String>,
+    /// Latest count-mode totals, highest first (refreshed about once a second)
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub call_counts: Vec<CallCount>,
+    /// Active stack sampling, if any
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sampling: Option<SamplingConfig>,
+    /// Active thread filter for traced calls, if any
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub threads: Option<ThreadFilter>,
+    /// Active per-function rate limit for traced calls, if any
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rate_limit: Option<RateLimit>,
+    /// Active flight recorder, if any
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub flight_recorder: Option<FlightRecorderConfig>,
+    /// Active signal-handler safety audit, if any
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signal_safety: Option<SignalSafetyConfig>,
+    /// Traced `@syscalls:` groups
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub syscalls: Vec<SyscallGroup>,
+    /// `@network` is traced
+    #[serde(skip_serializing_if = "std::ops::Not::not", default)]
+    pub network: bool,
+    /// `@exceptions` is traced
+    #[serde(skip_serializing_if = "std::ops::Not::not", default)]
+    pub exceptions: bool,
+    /// `@signals` is traced
+    #[serde(skip_serializing_if = "std::ops::Not::not", default)]
+    pub signals: bool,
+    /// `@locks` is traced
+    #[serde(skip_serializing_if = "std::ops::Not::not", default)]
+    pub locks: bool,
+    /// Most contended lock call sites recorded by `@locks`
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub lock_contention: Vec<crate::db::LockSite>,
+    /// Indexing status of each debug_launch `symbolExtra` library
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub symbol_modules: Vec<SymbolModuleStatus>,
     pub breakpoints: Vec<BreakpointInfo>,
     pub logpoints: Vec<LogpointInfo>,
     pub watches: Vec<ActiveWatch>,
@@ -1387,6 +3407,53 @@ pub struct SessionStatusResponse {
     /// Runtime capabilities — what this session can and can't do
     #[serde(skip_serializing_if = "Option::is_none")]
     pub capabilities: Option<RuntimeCapabilities>,
+    /// Event writer counters, while the session's writer runs
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub writer: Option<WriterStatus>,
+}
+
+/// Throughput of a session's event writer. A growing `dropped` means tracing
+/// produces events faster than they can be stored; narrow the patterns or
+/// use count mode.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WriterStatus {
+    /// Events waiting to be written
+    pub queued: u64,
+    /// Queue size; producers drop events once it is full
+    pub capacity: usize,
+    /// Events dropped because the queue was full or their batch failed to
+    /// insert
+    pub dropped: u64,
+    /// Events written
+    pub flushed: u64,
+    pub batches: u64,
+    /// Current batch size; grows with the backlog
+    pub batch_size: usize,
+    /// WAL checkpoints run by the writer
+    pub checkpoints: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SymbolModuleStatus {
+    pub path: String,
+    /// "indexing" | "ready" | "failed"
+    pub status: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub functions: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CallCount {
+    pub function: String,
+    pub count: u64,
+    /// Process the count came from, when the session spans several
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pid: Option<u32>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -1401,6 +3468,12 @@ pub struct CrashSummary {
     pub top_frame: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub throw_top_frame: Option<String>,
+    /// On-disk crash summary (all threads, module list); survives session eviction
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dump_path: Option<String>,
+    /// How often this crash signature has been seen in the project ("seen 3 times since Tuesday")
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub history: Option<String>,
 }
 
 // ============ debug_ui ============
@@ -1411,8 +3484,21 @@ pub enum UiMode {
     Tree,
     Screenshot,
     Both,
+    /// Capture the tree, perform `action` and/or wait `waitMs`, capture again
+    /// and return only what changed
+    Diff,
+    /// Capture screenshots at `fps` for `durationMs` into a frame sequence
+    Record,
 }
 
+/// Upper bound on debug_ui's `waitMs` between diff snapshots.
+pub const MAX_UI_DIFF_WAIT_MS: u64 = 10_000;
+
+pub const DEFAULT_UI_RECORD_MS: u64 = 2_000;
+pub const MAX_UI_RECORD_MS: u64 = 10_000;
+pub const DEFAULT_UI_RECORD_FPS: u32 = 10;
+pub const MAX_UI_RECORD_FPS: u32 = 30;
+
 impl Default for UiMode {
     fn default() -> Self {
         Self::Tree
@@ -1431,6 +3517,27 @@ pub struct DebugUiRequest {
     pub vision: Option<bool>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub verbose: Option<bool>,
+    /// Tree/both mode: only the nodes whose title, value or recognized text
+    /// contains this (case-insensitive)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+    /// Screenshot/both/record mode: crop to this screen-space rect (the
+    /// coordinates of the tree's bounds) instead of an element's
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub region: Option<crate::ui::tree::Rect>,
+    /// Record mode: how long to capture (default 2000)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duration_ms: Option<u64>,
+    /// Record mode: frames per second (default 10)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fps: Option<u32>,
+    /// Diff mode: debug_ui_action arguments (sessionId may be omitted) to
+    /// perform between the snapshots
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub action: Option<serde_json::Value>,
+    /// Diff mode: time to wait before the second snapshot (after the action)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub wait_ms: Option<u64>,
 }
 
 impl DebugUiRequest {
@@ -1440,8 +3547,108 @@ impl DebugUiRequest {
                 "sessionId must not be empty".to_string(),
             ));
         }
+        if let Some(ref text) = self.text {
+            if !matches!(self.mode, UiMode::Tree | UiMode::Both) {
+                return Err(crate::Error::ValidationError(
+                    "text is only used with mode 'tree' or 'both'".to_string(),
+                ));
+            }
+            if text.trim().is_empty() {
+                return Err(crate::Error::ValidationError(
+                    "text must not be empty".to_string(),
+                ));
+            }
+        }
+        if let Some(ref region) = self.region {
+            if !matches!(
+                self.mode,
+                UiMode::Screenshot | UiMode::Both | UiMode::Record
+            ) {
+                return Err(crate::Error::ValidationError(
+                    "region is only used with mode 'screenshot', 'both' or 'record'".to_string(),
+                ));
+            }
+            if self.id.is_some() {
+                return Err(crate::Error::ValidationError(
+                    "Pass either id or region to crop the screenshot, not both".to_string(),
+                ));
+            }
+            if !(region.w > 0.0 && region.h > 0.0) {
+                return Err(crate::Error::ValidationError(
+                    "region must have a positive width and height".to_string(),
+                ));
+            }
+        }
+        if self.mode == UiMode::Record {
+            if self
+                .duration_ms
+                .is_some_and(|ms| ms == 0 || ms > MAX_UI_RECORD_MS)
+            {
+                return Err(crate::Error::ValidationError(format!(
+                    "durationMs must be between 1 and {}",
+                    MAX_UI_RECORD_MS
+                )));
+            }
+            if self
+                .fps
+                .is_some_and(|fps| fps == 0 || fps > MAX_UI_RECORD_FPS)
+            {
+                return Err(crate::Error::ValidationError(format!(
+                    "fps must be between 1 and {}",
+                    MAX_UI_RECORD_FPS
+                )));
+            }
+        } else if self.duration_ms.is_some() || self.fps.is_some() {
+            return Err(crate::Error::ValidationError(
+                "durationMs and fps are only used with mode 'record'".to_string(),
+            ));
+        }
+        if self.mode != UiMode::Diff {
+            if self.action.is_some() || self.wait_ms.is_some() {
+                return Err(crate::Error::ValidationError(
+                    "action and waitMs are only used with mode 'diff'".to_string(),
+                ));
+            }
+            return Ok(());
+        }
+        if self.action.is_none() && self.wait_ms.is_none() {
+            return Err(crate::Error::ValidationError(
+                "mode 'diff' needs an action to perform or waitMs to wait between snapshots"
+                    .to_string(),
+            ));
+        }
+        if self.wait_ms.is_some_and(|ms| ms > MAX_UI_DIFF_WAIT_MS) {
+            return Err(crate::Error::ValidationError(format!(
+                "waitMs must be at most {}",
+                MAX_UI_DIFF_WAIT_MS
+            )));
+        }
+        if self.action.as_ref().is_some_and(|a| !a.is_object()) {
+            return Err(crate::Error::ValidationError(
+                "action must be an object of debug_ui_action arguments".to_string(),
+            ));
+        }
         Ok(())
     }
+
+    /// The diff mode action as a debug_ui_action request on this session.
+    pub fn diff_action(&self) -> crate::Result<Option<DebugUiActionRequest>> {
+        let Some(serde_json::Value::Object(args)) = &self.action else {
+            return Ok(None);
+        };
+        let mut args = args.clone();
+        args.entry("sessionId")
+            .or_insert_with(|| self.session_id.clone().into());
+        let action: DebugUiActionRequest = serde_json::from_value(args.into())
+            .map_err(|e| crate::Error::ValidationError(format!("Invalid action: {}", e)))?;
+        if action.session_id != self.session_id {
+            return Err(crate::Error::ValidationError(
+                "action must target the same session".to_string(),
+            ));
+        }
+        action.validate()?;
+        Ok(Some(action))
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -1450,6 +3657,9 @@ pub struct UiStats {
     pub ax_nodes: usize,
     pub vision_nodes: usize,
     pub merged_nodes: usize,
+    /// Lines of text recognized by the vision OCR pass
+    #[serde(default)]
+    pub ocr_texts: usize,
     pub latency_ms: u64,
 }
 
@@ -1460,6 +3670,15 @@ pub struct DebugUiResponse {
     pub tree: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub stats: Option<UiStats>,
+    /// Diff mode: the changes between the two snapshots
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub diff: Option<String>,
+    /// Diff mode: result of the action performed between the snapshots
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub action: Option<DebugUiActionResponse>,
+    /// Record mode: the captured frames
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub recording: Option<crate::ui::record::Recording>,
 }
 
 // ============ debug_ui_action ============
@@ -1473,6 +3692,8 @@ pub enum UiActionType {
     Key,
     Scroll,
     Drag,
+    /// Give the element keyboard focus without clicking it
+    Focus,
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -1560,7 +3781,7 @@ impl DebugUiActionRequest {
                     ));
                 }
             }
-            UiActionType::Click => {}
+            UiActionType::Click | UiActionType::Focus => {}
         }
 
         Ok(())
@@ -1583,73 +3804,1206 @@ pub struct DebugUiActionResponse {
     pub error: Option<String>,
 }
 
-#[cfg(test)]
-mod write_tests {
-    use super::*;
-
-    #[test]
-    fn test_debug_write_request_validation_valid_variable() {
-        let req = DebugWriteRequest {
-            session_id: "s1".to_string(),
-            targets: vec![WriteTarget {
-                variable: Some("g_counter".to_string()),
-                address: None,
-                value: serde_json::json!(42),
-                type_hint: None,
-            }],
-        };
-        assert!(req.validate().is_ok());
-    }
+// ============ debug_export ============
 
-    #[test]
-    fn test_debug_write_request_validation_valid_address() {
-        let req = DebugWriteRequest {
-            session_id: "s1".to_string(),
-            targets: vec![WriteTarget {
-                variable: None,
-                address: Some("0x7ff800".to_string()),
-                value: serde_json::json!(100),
-                type_hint: Some("u32".to_string()),
-            }],
-        };
-        assert!(req.validate().is_ok());
-    }
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportFormat {
+    /// Chrome Trace Event JSON — opens in chrome://tracing and ui.perfetto.dev
+    #[default]
+    ChromeTrace,
+}
 
-    #[test]
-    fn test_debug_write_request_validation_empty_targets() {
-        let req = DebugWriteRequest {
-            session_id: "s1".to_string(),
-            targets: vec![],
-        };
-        assert!(req.validate().is_err());
+impl ExportFormat {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::ChromeTrace => "chrome_trace",
+        }
     }
+}
 
-    #[test]
-    fn test_debug_write_request_validation_no_variable_or_address() {
-        let req = DebugWriteRequest {
-            session_id: "s1".to_string(),
-            targets: vec![WriteTarget {
-                variable: None,
-                address: None,
-                value: serde_json::json!(42),
-                type_hint: None,
-            }],
-        };
-        assert!(req.validate().is_err());
-    }
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DebugExportRequest {
+    pub session_id: String,
+    #[serde(default)]
+    pub format: ExportFormat,
+    /// Destination file. Defaults to /tmp/strobe/exports/<sessionId>.trace.json
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output_path: Option<String>,
+}
 
-    #[test]
-    fn test_debug_write_request_validation_address_requires_type() {
-        let req = DebugWriteRequest {
-            session_id: "s1".to_string(),
-            targets: vec![WriteTarget {
-                variable: None,
-                address: Some("0x1000".to_string()),
-                value: serde_json::json!(42),
-                type_hint: None, // missing
-            }],
-        };
-        assert!(req.validate().is_err());
+impl DebugExportRequest {
+    pub fn validate(&self) -> crate::Result<()> {
+        if self.session_id.is_empty() {
+            return Err(crate::Error::ValidationError(
+                "sessionId must not be empty".to_string(),
+            ));
+        }
+        if let Some(ref path) = self.output_path {
+            if path.is_empty() {
+                return Err(crate::Error::ValidationError(
+                    "outputPath must not be empty".to_string(),
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DebugExportResponse {
+    pub path: String,
+    pub format: ExportFormat,
+    pub event_count: usize,
+}
+
+// ============ debug_health ============
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DebugHealthResponse {
+    pub active_sessions: usize,
+    /// Statement timeout applied to debug_query (daemon-wide `query.timeoutMs`)
+    pub query_timeout_ms: u64,
+    pub slow_query_threshold_ms: u64,
+    /// Most recent slow or timed-out debug_query calls, newest first
+    pub slow_queries: Vec<crate::db::SlowQuery>,
+}
+
+// ============ debug_config ============
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DebugConfigRequest {
+    /// Include this project's settings file. Without it or `sessionId`, only
+    /// the global file and environment apply.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub project_root: Option<String>,
+    /// Use this session's project root
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub session_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigSetting {
+    pub value: serde_json::Value,
+    /// default, global, project or env
+    pub source: crate::config::ConfigSource,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DebugConfigResponse {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub project_root: Option<String>,
+    /// Settings files and variables consulted, lowest precedence first
+    pub layers: Vec<crate::config::ConfigLayer>,
+    /// Every setting's effective value and where it came from
+    pub settings: std::collections::BTreeMap<String, ConfigSetting>,
+    /// Settings that were skipped; the layer below's value applies
+    pub issues: Vec<crate::config::ConfigIssue>,
+}
+
+// ============ debug_timeline ============
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TimelineAction {
+    /// The call itself, with its exit
+    Event,
+    /// The calling function
+    Parent,
+    /// Direct callees, in call order
+    Children,
+    /// Previous call under the same parent on the same thread
+    Prev,
+    /// Next call under the same parent on the same thread
+    Next,
+    /// Calls active at a point in time, innermost first
+    Stack,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DebugTimelineRequest {
+    pub session_id: String,
+    pub action: TimelineAction,
+    /// Call to navigate from (a function_exit id resolves to its call)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub event_id: Option<String>,
+    /// stack: point in time (with threadId) instead of eventId
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timestamp_ns: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub thread_id: Option<i64>,
+    /// children: page size (default 50, max 200)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub offset: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub verbose: Option<bool>,
+}
+
+pub const MAX_TIMELINE_CHILDREN: u32 = 200;
+
+impl DebugTimelineRequest {
+    pub fn validate(&self) -> crate::Result<()> {
+        if self.session_id.is_empty() {
+            return Err(crate::Error::ValidationError(
+                "sessionId is required".to_string(),
+            ));
+        }
+        let has_event = self.event_id.as_ref().is_some_and(|e| !e.is_empty());
+        let has_point = self.timestamp_ns.is_some() && self.thread_id.is_some();
+        if self.action == TimelineAction::Stack {
+            if !has_event && !has_point {
+                return Err(crate::Error::ValidationError(
+                    "stack requires eventId, or timestampNs with threadId".to_string(),
+                ));
+            }
+        } else if !has_event {
+            return Err(crate::Error::ValidationError(
+                "eventId is required for this action".to_string(),
+            ));
+        }
+        if self
+            .limit
+            .is_some_and(|l| l == 0 || l > MAX_TIMELINE_CHILDREN)
+        {
+            return Err(crate::Error::ValidationError(format!(
+                "limit must be between 1 and {}",
+                MAX_TIMELINE_CHILDREN
+            )));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DebugTimelineResponse {
+    /// Calls found, each with its `exit` (return value, duration) when recorded.
+    /// Empty when there is nothing in that direction.
+    pub calls: Vec<serde_json::Value>,
+    /// children: more callees past this page
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub has_more: bool,
+}
+
+// ============ debug_diff ============
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DebugDiffRequest {
+    /// The known-good session ("yesterday")
+    pub base_session_id: String,
+    /// The session to compare against it
+    pub session_id: String,
+    /// Minimum avg duration ratio reported as a regression (default 1.5)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_ratio: Option<f64>,
+    /// Entries per list (default 50)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<u32>,
+}
+
+pub const MAX_DIFF_LIMIT: u32 = 500;
+
+impl DebugDiffRequest {
+    pub fn validate(&self) -> crate::Result<()> {
+        if self.base_session_id.is_empty() || self.session_id.is_empty() {
+            return Err(crate::Error::ValidationError(
+                "baseSessionId and sessionId are required".to_string(),
+            ));
+        }
+        if self.base_session_id == self.session_id {
+            return Err(crate::Error::ValidationError(
+                "baseSessionId and sessionId must be different sessions".to_string(),
+            ));
+        }
+        if self.min_ratio.is_some_and(|r| !r.is_finite() || r < 1.0) {
+            return Err(crate::Error::ValidationError(
+                "minRatio must be at least 1.0".to_string(),
+            ));
+        }
+        if self.limit.is_some_and(|l| l == 0 || l > MAX_DIFF_LIMIT) {
+            return Err(crate::Error::ValidationError(format!(
+                "limit must be between 1 and {}",
+                MAX_DIFF_LIMIT
+            )));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DebugDiffResponse {
+    pub base_session_id: String,
+    pub session_id: String,
+    #[serde(flatten)]
+    pub diff: crate::db::SessionDiff,
+}
+
+// ============ debug_coverage ============
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CoverageAction {
+    /// Start following all threads with Stalker
+    Start,
+    /// Report and write what was covered so far, keep collecting
+    Snapshot,
+    /// Report, write, and stop following threads
+    Stop,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CoverageScope {
+    /// Blocks of the traced executable, reported for project sources only
+    #[default]
+    Usercode,
+    /// Blocks of every loaded module
+    All,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CoverageGranularity {
+    /// Source lines touched by an executed basic block
+    #[default]
+    Block,
+    /// Functions with at least one executed basic block
+    Function,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CoverageFormat {
+    /// DynamoRIO drcov log — lighthouse, bncov, Cutter
+    #[default]
+    Drcov,
+    /// lcov tracefile — genhtml, IDE coverage gutters (needs debug symbols)
+    Lcov,
+}
+
+impl CoverageFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Drcov => "drcov",
+            Self::Lcov => "info",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DebugCoverageRequest {
+    pub session_id: String,
+    pub action: CoverageAction,
+    /// start: which code to record (default usercode)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scope: Option<CoverageScope>,
+    /// snapshot/stop: unit of the per-file report (default block)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub granularity: Option<CoverageGranularity>,
+    /// snapshot/stop: file format (default drcov)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub format: Option<CoverageFormat>,
+    /// snapshot/stop: destination file inside the project root. Defaults to
+    /// /tmp/strobe/coverage/<sessionId>.<drcov|info>
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output_path: Option<String>,
+}
+
+/// Files listed in a coverage report; the totals still count every file.
+pub const MAX_COVERAGE_FILES: usize = 200;
+
+impl DebugCoverageRequest {
+    pub fn validate(&self) -> crate::Result<()> {
+        if self.session_id.is_empty() {
+            return Err(crate::Error::ValidationError(
+                "sessionId is required".to_string(),
+            ));
+        }
+        if self.action == CoverageAction::Start {
+            if self.granularity.is_some() || self.format.is_some() || self.output_path.is_some() {
+                return Err(crate::Error::ValidationError(
+                    "granularity, format and outputPath apply to snapshot/stop".to_string(),
+                ));
+            }
+        } else if self.scope.is_some() {
+            return Err(crate::Error::ValidationError(
+                "scope is set when coverage starts".to_string(),
+            ));
+        }
+        if self.output_path.as_ref().is_some_and(|p| p.is_empty()) {
+            return Err(crate::Error::ValidationError(
+                "outputPath must not be empty".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DebugCoverageResponse {
+    pub session_id: String,
+    /// Still collecting (false after stop)
+    pub active: bool,
+    pub scope: CoverageScope,
+    /// Threads followed by Stalker
+    pub threads: u32,
+    /// Distinct basic blocks executed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub blocks: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub granularity: Option<CoverageGranularity>,
+    /// Covered lines (block) or functions (function), over all files
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub covered: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total: Option<u32>,
+    /// Per source file, sorted by path
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub files: Vec<crate::export::coverage::FileCoverage>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub files_omitted: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub format: Option<CoverageFormat>,
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub warnings: Vec<String>,
+}
+
+// ============ debug_heap ============
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HeapAction {
+    /// Hook the allocators and start recording
+    Start,
+    /// Report what was recorded so far, keep recording
+    Report,
+    /// Report and unhook
+    Stop,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DebugHeapRequest {
+    pub session_id: String,
+    pub action: HeapAction,
+    /// report/stop: call sites listed per section (default 20)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<u32>,
+}
+
+/// Call sites listed per section of a heap report.
+pub const MAX_HEAP_SITES: u32 = 200;
+
+impl DebugHeapRequest {
+    pub fn validate(&self) -> crate::Result<()> {
+        if self.session_id.is_empty() {
+            return Err(crate::Error::ValidationError(
+                "sessionId is required".to_string(),
+            ));
+        }
+        if let Some(limit) = self.limit {
+            if self.action == HeapAction::Start {
+                return Err(crate::Error::ValidationError(
+                    "limit applies to report/stop".to_string(),
+                ));
+            }
+            if limit == 0 || limit > MAX_HEAP_SITES {
+                return Err(crate::Error::ValidationError(format!(
+                    "limit must be between 1 and {}",
+                    MAX_HEAP_SITES
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HeapTotals {
+    pub allocations: u64,
+    pub frees: u64,
+    pub bytes_allocated: u64,
+    /// Allocated since tracking started and not freed yet
+    pub live_bytes: u64,
+    pub live_count: u64,
+    pub peak_live_bytes: u64,
+    /// Frees of blocks allocated before tracking started
+    pub untracked_frees: u64,
+    /// Distinct call sites
+    pub sites: u64,
+}
+
+/// One allocating call site: the return address of the outermost allocator
+/// call, symbolized.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HeapSite {
+    pub address: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub function: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub module: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line: Option<u32>,
+    pub allocations: u64,
+    pub frees: u64,
+    pub bytes: u64,
+    /// Largest single allocation
+    pub largest: u64,
+    pub live_bytes: u64,
+    pub live_count: u64,
+    /// A few addresses still live from this site (leaks only)
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub samples: Vec<String>,
+}
+
+impl HeapSite {
+    /// From the agent's symbolized frame plus counters.
+    fn from_agent(value: &serde_json::Value) -> Option<Self> {
+        let text = |key: &str| {
+            value
+                .get(key)
+                .and_then(|v| v.as_str())
+                .filter(|s| !s.is_empty())
+                .map(String::from)
+        };
+        let count = |key: &str| value.get(key).and_then(|v| v.as_u64()).unwrap_or(0);
+        Some(Self {
+            address: text("address")?,
+            function: text("name"),
+            module: text("moduleName"),
+            file: text("fileName"),
+            line: value
+                .get("lineNumber")
+                .and_then(|v| v.as_u64())
+                .filter(|&l| l > 0)
+                .map(|l| l as u32),
+            allocations: count("allocations"),
+            frees: count("frees"),
+            bytes: count("bytes"),
+            largest: count("largest"),
+            live_bytes: count("liveBytes"),
+            live_count: count("liveCount"),
+            samples: value
+                .get("samples")
+                .and_then(|v| v.as_array())
+                .map(|a| {
+                    a.iter()
+                        .filter_map(|s| s.as_str().map(String::from))
+                        .collect()
+                })
+                .unwrap_or_default(),
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DebugHeapResponse {
+    pub session_id: String,
+    /// Still recording (false after stop)
+    pub active: bool,
+    /// Allocator functions hooked
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub hooked: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub totals: Option<HeapTotals>,
+    /// Call sites that allocated the most bytes
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub top_sites: Vec<HeapSite>,
+    /// Call sites holding the most live (not yet freed) bytes
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub leaks: Vec<HeapSite>,
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub warnings: Vec<String>,
+}
+
+impl DebugHeapResponse {
+    /// Build from the agent's heap_response payload.
+    pub fn from_agent(session_id: &str, payload: &serde_json::Value) -> Self {
+        let sites = |key: &str| -> Vec<HeapSite> {
+            payload
+                .get(key)
+                .and_then(|v| v.as_array())
+                .map(|a| a.iter().filter_map(HeapSite::from_agent).collect())
+                .unwrap_or_default()
+        };
+        let mut warnings = Vec::new();
+        if payload.get("truncated").and_then(|v| v.as_bool()) == Some(true) {
+            warnings.push(
+                "Tracking limits reached: some allocations were counted but not tracked individually"
+                    .to_string(),
+            );
+        }
+        Self {
+            session_id: session_id.to_string(),
+            active: payload
+                .get("active")
+                .and_then(|v| v.as_bool())
+                .unwrap_or(false),
+            hooked: payload
+                .get("hooked")
+                .and_then(|v| serde_json::from_value(v.clone()).ok())
+                .unwrap_or_default(),
+            totals: payload
+                .get("totals")
+                .and_then(|v| serde_json::from_value(v.clone()).ok()),
+            top_sites: sites("topSites"),
+            leaks: sites("leaks"),
+            warnings,
+        }
+    }
+}
+
+// ============ debug_tutorial ============
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TutorialAction {
+    /// Build the demo if needed and list the steps
+    #[default]
+    Start,
+    /// Verify one step's checkpoint against the session
+    Check,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DebugTutorialRequest {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub action: Option<TutorialAction>,
+    /// Step to check (1-based)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub step: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub session_id: Option<String>,
+    /// Answer for steps that ask a question
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub answer: Option<String>,
+}
+
+impl DebugTutorialRequest {
+    pub fn validate(&self) -> crate::Result<()> {
+        if self.action.unwrap_or_default() == TutorialAction::Check {
+            let steps = crate::tutorial::STEPS.len() as u32;
+            if !self.step.is_some_and(|s| (1..=steps).contains(&s)) {
+                return Err(crate::Error::ValidationError(format!(
+                    "check requires step between 1 and {}",
+                    steps
+                )));
+            }
+            if self.session_id.as_ref().is_none_or(|s| s.is_empty()) {
+                return Err(crate::Error::ValidationError(
+                    "check requires sessionId".to_string(),
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TutorialStepInfo {
+    pub step: u32,
+    pub title: String,
+    pub instructions: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TutorialCheckpoint {
+    pub step: u32,
+    pub passed: bool,
+    pub detail: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DebugTutorialResponse {
+    /// Demo binary to launch
+    pub binary: String,
+    /// projectRoot to launch it with
+    pub project_root: String,
+    /// Every step (start only)
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub steps: Vec<TutorialStepInfo>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub checkpoint: Option<TutorialCheckpoint>,
+    /// Step to do now: the first step on start, the following one after a pass
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next: Option<TutorialStepInfo>,
+}
+
+// ============ debug_crashes ============
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DebugCrashesRequest {
+    /// Project to list crashes for. Either this or `sessionId` is required.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub project_root: Option<String>,
+    /// Use this session's project root
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub session_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<u32>,
+}
+
+impl DebugCrashesRequest {
+    pub fn validate(&self) -> crate::Result<()> {
+        let has_root = self.project_root.as_ref().is_some_and(|r| !r.is_empty());
+        let has_session = self.session_id.as_ref().is_some_and(|s| !s.is_empty());
+        if !has_root && !has_session {
+            return Err(crate::Error::ValidationError(
+                "Either projectRoot or sessionId is required".to_string(),
+            ));
+        }
+        if self.limit == Some(0) {
+            return Err(crate::Error::ValidationError(
+                "limit must be at least 1".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CrashHistoryEntry {
+    #[serde(flatten)]
+    pub record: crate::db::CrashRecord,
+    /// "seen 14 times since Tuesday"
+    pub history: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DebugCrashesResponse {
+    pub project_root: String,
+    /// Distinct crash signatures, most recently seen first
+    pub crashes: Vec<CrashHistoryEntry>,
+}
+
+// ============ debug_investigation ============
+
+pub const MAX_INVESTIGATION_TITLE_LENGTH: usize = 200;
+pub const MAX_INVESTIGATION_NOTE_LENGTH: usize = 4096;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum InvestigationAction {
+    Create,
+    AddSession,
+    AddTestRun,
+    AddNote,
+    Summarize,
+    List,
+    Close,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DebugInvestigationRequest {
+    pub action: InvestigationAction,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub investigation_id: Option<String>,
+    /// For action: "create"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    /// Project of a new investigation (create), or to list (list)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub project_root: Option<String>,
+    /// Session to link (add-session); for create, its project root is used
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub session_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub test_run_id: Option<String>,
+    /// Note text (add-note), or a note attached to the linked artifact
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+    /// What was found, for action: "close"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub conclusion: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<u32>,
+}
+
+impl DebugInvestigationRequest {
+    pub fn validate(&self) -> crate::Result<()> {
+        use InvestigationAction::*;
+        let has = |v: &Option<String>| v.as_ref().is_some_and(|s| !s.is_empty());
+
+        let scoped: [(&str, bool, &[InvestigationAction]); 5] = [
+            ("title", self.title.is_some(), &[Create]),
+            ("testRunId", self.test_run_id.is_some(), &[AddTestRun]),
+            (
+                "note",
+                self.note.is_some(),
+                &[AddSession, AddTestRun, AddNote],
+            ),
+            ("conclusion", self.conclusion.is_some(), &[Close]),
+            ("limit", self.limit.is_some(), &[List]),
+        ];
+        for (field, present, actions) in scoped {
+            if present && !actions.contains(&self.action) {
+                return Err(crate::Error::ValidationError(format!(
+                    "{} is not valid for action: {:?}",
+                    field, self.action
+                )));
+            }
+        }
+
+        let required: &[(&str, bool)] = match self.action {
+            Create => &[
+                ("title", has(&self.title)),
+                (
+                    "projectRoot or sessionId",
+                    has(&self.project_root) || has(&self.session_id),
+                ),
+            ],
+            AddSession => &[
+                ("investigationId", has(&self.investigation_id)),
+                ("sessionId", has(&self.session_id)),
+            ],
+            AddTestRun => &[
+                ("investigationId", has(&self.investigation_id)),
+                ("testRunId", has(&self.test_run_id)),
+            ],
+            AddNote => &[
+                ("investigationId", has(&self.investigation_id)),
+                ("note", has(&self.note)),
+            ],
+            Summarize | Close => &[("investigationId", has(&self.investigation_id))],
+            List => &[],
+        };
+        if let Some((field, _)) = required.iter().find(|(_, present)| !present) {
+            return Err(crate::Error::ValidationError(format!(
+                "{} is required for action: {:?}",
+                field, self.action
+            )));
+        }
+
+        if self
+            .title
+            .as_ref()
+            .is_some_and(|t| t.len() > MAX_INVESTIGATION_TITLE_LENGTH)
+        {
+            return Err(crate::Error::ValidationError(format!(
+                "title exceeds {} bytes",
+                MAX_INVESTIGATION_TITLE_LENGTH
+            )));
+        }
+        for (field, text) in [("note", &self.note), ("conclusion", &self.conclusion)] {
+            if text
+                .as_ref()
+                .is_some_and(|t| t.len() > MAX_INVESTIGATION_NOTE_LENGTH)
+            {
+                return Err(crate::Error::ValidationError(format!(
+                    "{} exceeds {} bytes",
+                    field, MAX_INVESTIGATION_NOTE_LENGTH
+                )));
+            }
+        }
+        if self.limit == Some(0) {
+            return Err(crate::Error::ValidationError(
+                "limit must be at least 1".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InvestigationItemAdded {
+    pub investigation_id: String,
+    /// false when the artifact was already linked (its note was updated)
+    pub added: bool,
+    pub items: usize,
+}
+
+/// A linked session as it is now.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InvestigationSessionInfo {
+    pub binary_path: String,
+    pub status: String,
+    pub started_at: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ended_at: Option<i64>,
+    pub retained: bool,
+    pub event_count: u64,
+    pub crashes: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InvestigationSummaryItem {
+    #[serde(flatten)]
+    pub item: crate::db::InvestigationItem,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub session: Option<InvestigationSessionInfo>,
+    /// The linked session's events are gone (stopped without retain, or evicted)
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub deleted: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InvestigationSummary {
+    #[serde(flatten)]
+    pub investigation: crate::db::Investigation,
+    pub sessions: usize,
+    pub test_runs: usize,
+    pub notes: usize,
+    /// Crash events across the linked sessions still stored
+    pub crashes: u64,
+    /// Everything linked, in the order it was added
+    pub items: Vec<InvestigationSummaryItem>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InvestigationListEntry {
+    #[serde(flatten)]
+    pub investigation: crate::db::Investigation,
+    pub items: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DebugInvestigationListResponse {
+    /// Newest first
+    pub investigations: Vec<InvestigationListEntry>,
+}
+
+// ============ debug_batch ============
+
+/// Calls per debug_batch.
+pub const MAX_BATCH_CALLS: usize = 16;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchCall {
+    /// Tool name, e.g. "debug_query"
+    pub tool: String,
+    #[serde(default)]
+    pub arguments: serde_json::Value,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DebugBatchRequest {
+    /// Checked once up front and filled into every call that doesn't name a session
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub session_id: Option<String>,
+    /// Run in order; the first failing call ends the batch
+    pub calls: Vec<BatchCall>,
+}
+
+impl DebugBatchRequest {
+    pub fn validate(&self) -> crate::Result<()> {
+        if self.calls.is_empty() || self.calls.len() > MAX_BATCH_CALLS {
+            return Err(crate::Error::ValidationError(format!(
+                "calls needs 1 to {} entries, got {}",
+                MAX_BATCH_CALLS,
+                self.calls.len()
+            )));
+        }
+        if self.session_id.as_ref().is_some_and(|s| s.is_empty()) {
+            return Err(crate::Error::ValidationError(
+                "sessionId must not be empty".to_string(),
+            ));
+        }
+        for (i, call) in self.calls.iter().enumerate() {
+            if matches!(
+                call.tool.as_str(),
+                "debug_batch" | "debug_ui" | "debug_ui_action"
+            ) {
+                return Err(crate::Error::ValidationError(format!(
+                    "calls[{}]: {} cannot be batched",
+                    i, call.tool
+                )));
+            }
+            if !(call.arguments.is_object() || call.arguments.is_null()) {
+                return Err(crate::Error::ValidationError(format!(
+                    "calls[{}].arguments must be an object",
+                    i
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Arguments of call `index`, with the batch's sessionId filled in when
+    /// the call doesn't set its own.
+    pub fn arguments(&self, index: usize) -> serde_json::Value {
+        let mut arguments = match self.calls[index].arguments {
+            serde_json::Value::Null => serde_json::json!({}),
+            ref args => args.clone(),
+        };
+        if let (Some(session_id), Some(obj)) = (&self.session_id, arguments.as_object_mut()) {
+            obj.entry("sessionId")
+                .or_insert_with(|| serde_json::json!(session_id));
+        }
+        arguments
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchResult {
+    pub tool: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub result: Option<serde_json::Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<McpError>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DebugBatchResponse {
+    /// One per call that ran, in order; the last carries the error when the batch stopped early
+    pub results: Vec<BatchResult>,
+    /// Calls skipped after a failure
+    pub skipped: usize,
+}
+
+// ============ debug_symbols ============
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SymbolsAction {
+    /// Functions by name and source file
+    Functions,
+    /// Global variables with types and addresses
+    Globals,
+    /// Function, global and source file counts per indexed binary
+    Modules,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DebugSymbolsRequest {
+    pub session_id: String,
+    pub action: SymbolsAction,
+    /// functions/globals: case-insensitive substring of the name (default: all)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub query: Option<String>,
+    /// Read `query` as a regular expression
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub regex: Option<bool>,
+    /// functions: only those from source files whose path contains this
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+    /// functions/globals: results listed (default 50)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<u32>,
+}
+
+/// Functions or globals one `debug_symbols` call lists.
+pub const MAX_SYMBOL_RESULTS: u32 = 1000;
+pub const MAX_SYMBOL_QUERY_LEN: usize = 256;
+
+impl DebugSymbolsRequest {
+    pub fn validate(&self) -> crate::Result<()> {
+        if self.session_id.is_empty() {
+            return Err(crate::Error::ValidationError(
+                "sessionId is required".to_string(),
+            ));
+        }
+        if self.action == SymbolsAction::Modules
+            && (self.query.is_some() || self.file.is_some() || self.limit.is_some())
+        {
+            return Err(crate::Error::ValidationError(
+                "modules lists every indexed binary; query, file and limit apply to functions/globals"
+                    .to_string(),
+            ));
+        }
+        if self.file.is_some() && self.action != SymbolsAction::Functions {
+            return Err(crate::Error::ValidationError(
+                "file filters functions only".to_string(),
+            ));
+        }
+        if self.regex == Some(true) && self.query.is_none() {
+            return Err(crate::Error::ValidationError(
+                "regex needs a query".to_string(),
+            ));
+        }
+        if self
+            .query
+            .iter()
+            .chain(self.file.iter())
+            .any(|q| q.len() > MAX_SYMBOL_QUERY_LEN)
+        {
+            return Err(crate::Error::ValidationError(format!(
+                "query and file are limited to {} characters",
+                MAX_SYMBOL_QUERY_LEN
+            )));
+        }
+        if let Some(limit) = self.limit {
+            if limit == 0 || limit > MAX_SYMBOL_RESULTS {
+                return Err(crate::Error::ValidationError(format!(
+                    "limit must be between 1 and {}",
+                    MAX_SYMBOL_RESULTS
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A function from the debug symbols. Addresses are static (as in the
+/// binary); the loaded address adds the ASLR slide.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SymbolFunction {
+    pub name: String,
+    pub address: String,
+    pub size: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line: Option<u32>,
+    /// Symbol module (debug_launch `symbolExtra`) the function lives in
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub module: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SymbolGlobal {
+    pub name: String,
+    pub address: String,
+    pub size: u8,
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+    pub type_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub module: Option<String>,
+}
+
+/// Symbol counts of one indexed binary: the session's executable or a
+/// symbol module.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SymbolModuleSummary {
+    pub path: String,
+    /// "indexing" | "ready" | "failed"
+    pub status: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub functions: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub globals: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source_files: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DebugSymbolsResponse {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub functions: Vec<SymbolFunction>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub globals: Vec<SymbolGlobal>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub modules: Vec<SymbolModuleSummary>,
+    /// functions/globals: matches before `limit` was applied
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub matched: Option<usize>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<String>,
+}
+
+#[cfg(test)]
+mod write_tests {
+    use super::*;
+
+    #[test]
+    fn test_debug_write_request_validation_valid_variable() {
+        let req = DebugWriteRequest {
+            session_id: "s1".to_string(),
+            targets: vec![WriteTarget {
+                variable: Some("g_counter".to_string()),
+                address: None,
+                value: serde_json::json!(42),
+                type_hint: None,
+            }],
+        };
+        assert!(req.validate().is_ok());
+    }
+
+    #[test]
+    fn test_debug_write_request_validation_valid_address() {
+        let req = DebugWriteRequest {
+            session_id: "s1".to_string(),
+            targets: vec![WriteTarget {
+                variable: None,
+                address: Some("0x7ff800".to_string()),
+                value: serde_json::json!(100),
+                type_hint: Some("u32".to_string()),
+            }],
+        };
+        assert!(req.validate().is_ok());
+    }
+
+    #[test]
+    fn test_debug_write_request_validation_empty_targets() {
+        let req = DebugWriteRequest {
+            session_id: "s1".to_string(),
+            targets: vec![],
+        };
+        assert!(req.validate().is_err());
+    }
+
+    #[test]
+    fn test_debug_write_request_validation_no_variable_or_address() {
+        let req = DebugWriteRequest {
+            session_id: "s1".to_string(),
+            targets: vec![WriteTarget {
+                variable: None,
+                address: None,
+                value: serde_json::json!(42),
+                type_hint: None,
+            }],
+        };
+        assert!(req.validate().is_err());
+    }
+
+    #[test]
+    fn test_debug_write_request_validation_address_requires_type() {
+        let req = DebugWriteRequest {
+            session_id: "s1".to_string(),
+            targets: vec![WriteTarget {
+                variable: None,
+                address: Some("0x1000".to_string()),
+                value: serde_json::json!(42),
+                type_hint: None, // missing
+            }],
+        };
+        assert!(req.validate().is_err());
     }
 
     #[test]
@@ -1683,10 +5037,147 @@ mod write_tests {
             id: None,
             vision: None,
             verbose: None,
+            text: None,
+            region: None,
+            duration_ms: None,
+            fps: None,
+            action: None,
+            wait_ms: None,
         };
         assert!(req.validate().is_err());
     }
 
+    #[test]
+    fn test_debug_ui_request_text_filter() {
+        let parse =
+            |json: serde_json::Value| -> DebugUiRequest { serde_json::from_value(json).unwrap() };
+        let req = parse(serde_json::json!({"sessionId": "s1", "mode": "tree", "text": "Error 42"}));
+        assert!(req.validate().is_ok());
+        assert_eq!(req.text.as_deref(), Some("Error 42"));
+        assert!(
+            parse(serde_json::json!({"sessionId": "s1", "mode": "tree", "text": " "}))
+                .validate()
+                .is_err()
+        );
+        assert!(
+            parse(serde_json::json!({"sessionId": "s1", "mode": "screenshot", "text": "OK"}))
+                .validate()
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_debug_ui_request_record_mode() {
+        let parse =
+            |json: serde_json::Value| -> DebugUiRequest { serde_json::from_value(json).unwrap() };
+        let req = parse(serde_json::json!({"sessionId": "s1", "mode": "record"}));
+        assert_eq!(req.mode, UiMode::Record);
+        assert!(req.validate().is_ok());
+        assert!(parse(serde_json::json!({
+            "sessionId": "s1",
+            "mode": "record",
+            "durationMs": 3000,
+            "fps": 30,
+            "id": "w_0001"
+        }))
+        .validate()
+        .is_ok());
+
+        for bad in [
+            serde_json::json!({"sessionId": "s1", "mode": "record", "durationMs": 60_000}),
+            serde_json::json!({"sessionId": "s1", "mode": "record", "durationMs": 0}),
+            serde_json::json!({"sessionId": "s1", "mode": "record", "fps": 120}),
+            serde_json::json!({"sessionId": "s1", "mode": "screenshot", "fps": 10}),
+        ] {
+            assert!(parse(bad).validate().is_err());
+        }
+    }
+
+    #[test]
+    fn test_debug_ui_request_region() {
+        let parse =
+            |json: serde_json::Value| -> DebugUiRequest { serde_json::from_value(json).unwrap() };
+        let region = serde_json::json!({ "x": 10, "y": 20, "w": 300, "h": 40 });
+        let req =
+            parse(serde_json::json!({"sessionId": "s1", "mode": "screenshot", "region": region}));
+        assert!(req.validate().is_ok());
+        assert_eq!(req.region.as_ref().unwrap().w, 300.0);
+
+        assert!(
+            parse(serde_json::json!({"sessionId": "s1", "mode": "tree", "region": region}))
+                .validate()
+                .is_err()
+        );
+        assert!(parse(
+            serde_json::json!({"sessionId": "s1", "mode": "both", "id": "btn_1", "region": region})
+        )
+        .validate()
+        .is_err());
+        let empty = serde_json::json!({ "x": 10, "y": 20, "w": 0, "h": 40 });
+        assert!(parse(
+            serde_json::json!({"sessionId": "s1", "mode": "screenshot", "region": empty})
+        )
+        .validate()
+        .is_err());
+    }
+
+    #[test]
+    fn test_debug_ui_request_diff_mode() {
+        let parse =
+            |json: serde_json::Value| -> DebugUiRequest { serde_json::from_value(json).unwrap() };
+        let req = parse(serde_json::json!({
+            "sessionId": "s1",
+            "mode": "diff",
+            "action": { "action": "click", "id": "btn_a1b2" },
+            "waitMs": 200
+        }));
+        assert!(req.validate().is_ok());
+        let action = req.diff_action().unwrap().unwrap();
+        assert_eq!(action.session_id, "s1");
+        assert_eq!(action.action, UiActionType::Click);
+
+        // Waiting alone is enough; nothing at all is not
+        assert!(
+            parse(serde_json::json!({"sessionId": "s1", "mode": "diff", "waitMs": 500}))
+                .validate()
+                .is_ok()
+        );
+        assert!(
+            parse(serde_json::json!({"sessionId": "s1", "mode": "diff"}))
+                .validate()
+                .is_err()
+        );
+        assert!(
+            parse(serde_json::json!({"sessionId": "s1", "mode": "diff", "waitMs": 60_000}))
+                .validate()
+                .is_err()
+        );
+        assert!(
+            parse(serde_json::json!({"sessionId": "s1", "mode": "tree", "waitMs": 100}))
+                .validate()
+                .is_err()
+        );
+        assert!(
+            parse(serde_json::json!({"sessionId": "s1", "mode": "diff", "action": "click"}))
+                .validate()
+                .is_err()
+        );
+
+        // The action is validated as a debug_ui_action request
+        let req = parse(serde_json::json!({
+            "sessionId": "s1",
+            "mode": "diff",
+            "action": { "action": "type", "id": "txt_1" }
+        }));
+        assert!(req.diff_action().is_err());
+        let req = parse(serde_json::json!({
+            "sessionId": "s1",
+            "mode": "diff",
+            "action": { "sessionId": "s2", "action": "click", "id": "btn_a1b2" }
+        }));
+        assert!(req.diff_action().is_err());
+    }
+
     #[test]
     fn test_debug_ui_response_serde() {
         let resp = DebugUiResponse {
@@ -1695,8 +5186,12 @@ mod write_tests {
                 ax_nodes: 5,
                 vision_nodes: 0,
                 merged_nodes: 0,
+                ocr_texts: 0,
                 latency_ms: 12,
             }),
+            diff: None,
+            action: None,
+            recording: None,
         };
         let json = serde_json::to_value(&resp).unwrap();
         assert!(json.get("tree").is_some());
@@ -1876,6 +5371,21 @@ mod ui_action_tests {
         assert!(err.to_string().contains("value"));
     }
 
+    #[test]
+    fn test_ui_action_request_focus() {
+        let req: DebugUiActionRequest = serde_json::from_value(
+            serde_json::json!({"sessionId": "s1", "action": "focus", "id": "txt_1234"}),
+        )
+        .unwrap();
+        assert_eq!(req.action, UiActionType::Focus);
+        assert!(req.validate().is_ok());
+
+        let req: DebugUiActionRequest =
+            serde_json::from_value(serde_json::json!({"sessionId": "s1", "action": "focus"}))
+                .unwrap();
+        assert!(req.validate().unwrap_err().to_string().contains("id"));
+    }
+
     #[test]
     fn test_ui_action_request_camel_case_wire_format() {
         let req = DebugUiActionRequest {
@@ -1925,10 +5435,14 @@ mod breakpoint_tests {
             session_id: "test".to_string(),
             add: Some(vec![BreakpointTarget {
                 function: Some("foo".to_string()),
+                offset: None,
+                address: None,
                 file: None,
                 line: None,
                 condition: None,
                 hit_count: None,
+                every_n: None,
+                max_hits: None,
                 message: None,
             }]),
             remove: None,
@@ -1940,10 +5454,14 @@ mod breakpoint_tests {
             session_id: "test".to_string(),
             add: Some(vec![BreakpointTarget {
                 function: None,
+                offset: None,
+                address: None,
                 file: Some("main.cpp".to_string()),
                 line: Some(42),
                 condition: None,
                 hit_count: None,
+                every_n: None,
+                max_hits: None,
                 message: None,
             }]),
             remove: None,
@@ -1955,10 +5473,14 @@ mod breakpoint_tests {
             session_id: "test".to_string(),
             add: Some(vec![BreakpointTarget {
                 function: None,
+                offset: None,
+                address: None,
                 file: None,
                 line: None,
                 condition: None,
                 hit_count: None,
+                every_n: None,
+                max_hits: None,
                 message: None,
             }]),
             remove: None,
@@ -1970,10 +5492,14 @@ mod breakpoint_tests {
             session_id: "test".to_string(),
             add: Some(vec![BreakpointTarget {
                 function: None,
+                offset: None,
+                address: None,
                 file: Some("main.cpp".to_string()),
                 line: None,
                 condition: None,
                 hit_count: None,
+                every_n: None,
+                max_hits: None,
                 message: None,
             }]),
             remove: None,
@@ -1981,12 +5507,66 @@ mod breakpoint_tests {
         assert!(req.validate().is_err());
     }
 
+    #[test]
+    fn test_breakpoint_address_and_offset_targets() {
+        let target = |json: serde_json::Value| DebugBreakpointRequest {
+            session_id: "test".to_string(),
+            add: Some(vec![serde_json::from_value(json).unwrap()]),
+            remove: None,
+        };
+
+        let req = target(serde_json::json!({ "address": "0x10000abcd" }));
+        assert!(req.validate().is_ok());
+        assert_eq!(req.add.unwrap()[0].address_value(), Some(0x10000abcd));
+        let req = target(serde_json::json!({ "function": "process_buffer", "offset": 36 }));
+        assert!(req.validate().is_ok());
+
+        for invalid in [
+            serde_json::json!({ "address": "abcz" }),
+            serde_json::json!({ "address": "0x1000", "function": "f" }),
+            serde_json::json!({ "address": "0x1000", "file": "a.c", "line": 3 }),
+            serde_json::json!({ "file": "a.c", "line": 3, "offset": 4 }),
+            serde_json::json!({ "address": "0x1000", "offset": 4 }),
+            serde_json::json!({ "address": "0x1000", "message": "hit" }),
+            serde_json::json!({ "function": "f", "offset": 4, "message": "hit" }),
+        ] {
+            assert!(target(invalid.clone()).validate().is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_logpoint_hit_filters() {
+        let target = |json: serde_json::Value| DebugBreakpointRequest {
+            session_id: "test".to_string(),
+            add: Some(vec![serde_json::from_value(json).unwrap()]),
+            remove: None,
+        };
+
+        assert!(target(serde_json::json!({
+            "function": "audio_callback", "message": "rms {args[1]}",
+            "hitCount": 10, "everyN": 1000, "maxHits": 50
+        }))
+        .validate()
+        .is_ok());
+        // everyN / maxHits without a message would be a breakpoint
+        assert!(target(serde_json::json!({ "function": "f", "everyN": 10 }))
+            .validate()
+            .is_err());
+        assert!(
+            target(serde_json::json!({ "function": "f", "message": "m", "everyN": 0 }))
+                .validate()
+                .is_err()
+        );
+    }
+
     #[test]
     fn test_debug_continue_request_validation() {
         // Valid: no action (defaults to continue)
         let req = DebugContinueRequest {
             session_id: "test".to_string(),
             action: None,
+            until: None,
+            count: None,
         };
         assert!(req.validate().is_ok());
 
@@ -1994,6 +5574,8 @@ mod breakpoint_tests {
         let req = DebugContinueRequest {
             session_id: "test".to_string(),
             action: Some("continue".to_string()),
+            until: None,
+            count: None,
         };
         assert!(req.validate().is_ok());
 
@@ -2001,6 +5583,8 @@ mod breakpoint_tests {
         let req = DebugContinueRequest {
             session_id: "test".to_string(),
             action: Some("step-over".to_string()),
+            until: None,
+            count: None,
         };
         assert!(req.validate().is_ok());
 
@@ -2008,6 +5592,8 @@ mod breakpoint_tests {
         let req = DebugContinueRequest {
             session_id: "".to_string(),
             action: None,
+            until: None,
+            count: None,
         };
         assert!(req.validate().is_err());
 
@@ -2015,6 +5601,55 @@ mod breakpoint_tests {
         let req = DebugContinueRequest {
             session_id: "test".to_string(),
             action: Some("invalid-action".to_string()),
+            until: None,
+            count: None,
+        };
+        assert!(req.validate().is_err());
+
+        // until: function or file+line, with continue only
+        let until = |function: Option<&str>, file: Option<&str>, line: Option<u32>| {
+            Some(ContinueUntil {
+                function: function.map(str::to_string),
+                file: file.map(str::to_string),
+                line,
+            })
+        };
+        let req = DebugContinueRequest {
+            session_id: "test".to_string(),
+            action: None,
+            until: until(None, Some("main.c"), Some(42)),
+            count: None,
+        };
+        assert!(req.validate().is_ok());
+        let req = DebugContinueRequest {
+            until: until(Some("parse"), Some("main.c"), Some(42)),
+            ..req
+        };
+        assert!(req.validate().is_err());
+        let req = DebugContinueRequest {
+            action: Some("step-over".to_string()),
+            until: until(Some("parse"), None, None),
+            ..req
+        };
+        assert!(req.validate().is_err());
+
+        // count: step actions only, bounded
+        let req = DebugContinueRequest {
+            session_id: "test".to_string(),
+            action: Some("step-over".to_string()),
+            until: None,
+            count: Some(10),
+        };
+        assert!(req.validate().is_ok());
+        let req = DebugContinueRequest {
+            count: Some(MAX_STEP_COUNT + 1),
+            ..req
+        };
+        assert!(req.validate().is_err());
+        let req = DebugContinueRequest {
+            action: None,
+            count: Some(3),
+            ..req
         };
         assert!(req.validate().is_err());
     }
@@ -2030,6 +5665,9 @@ mod breakpoint_tests {
                 file: None,
                 line: None,
                 condition: None,
+                hit_count: None,
+                every_n: None,
+                max_hits: None,
             }]),
             remove: None,
         };
@@ -2044,6 +5682,9 @@ mod breakpoint_tests {
                 file: Some("main.cpp".to_string()),
                 line: Some(42),
                 condition: None,
+                hit_count: None,
+                every_n: None,
+                max_hits: None,
             }]),
             remove: None,
         };
@@ -2058,6 +5699,9 @@ mod breakpoint_tests {
                 file: None,
                 line: None,
                 condition: None,
+                hit_count: None,
+                every_n: None,
+                max_hits: None,
             }]),
             remove: None,
         };
@@ -2072,6 +5716,9 @@ mod breakpoint_tests {
                 file: None,
                 line: None,
                 condition: None,
+                hit_count: None,
+                every_n: None,
+                max_hits: None,
             }]),
             remove: None,
         };
@@ -2110,6 +5757,8 @@ mod read_tests {
                 address: None,
                 size: None,
                 type_hint: None,
+                format: None,
+                region: None,
             })
             .collect();
         let req = DebugReadRequest {
@@ -2130,6 +5779,8 @@ mod read_tests {
                 address: None,
                 size: None,
                 type_hint: None,
+                format: None,
+                region: None,
             }],
             depth: None,
             poll: None,
@@ -2146,6 +5797,8 @@ mod read_tests {
                 address: None,
                 size: None,
                 type_hint: None,
+                format: None,
+                region: None,
             }],
             depth: None,
             poll: Some(PollConfig {
@@ -2165,6 +5818,8 @@ mod read_tests {
                 address: None,
                 size: None,
                 type_hint: None,
+                format: None,
+                region: None,
             }],
             depth: Some(10), // above max 5
             poll: None,
@@ -2181,6 +5836,8 @@ mod read_tests {
                 address: Some("0x7ff800".to_string()),
                 size: None,      // missing
                 type_hint: None, // missing
+                format: None,
+                region: None,
             }],
             depth: None,
             poll: None,
@@ -2188,6 +5845,109 @@ mod read_tests {
         assert!(req.validate().is_err());
     }
 
+    #[test]
+    fn test_debug_read_request_hexdump_and_region_validation() {
+        let target = |address: Option<&str>, size, format, region| ReadTarget {
+            variable: None,
+            address: address.map(str::to_string),
+            size,
+            type_hint: None,
+            format,
+            region,
+        };
+        let request = |targets, poll| DebugReadRequest {
+            session_id: "s1".to_string(),
+            targets,
+            depth: None,
+            poll,
+        };
+        let region = |base: &str, length| {
+            Some(MemoryRegion {
+                base: base.to_string(),
+                length,
+            })
+        };
+
+        // An address hexdump needs a size but no type
+        assert!(request(
+            vec![target(
+                Some("0x1000"),
+                Some(64),
+                Some(ReadFormat::Hexdump),
+                None
+            )],
+            None
+        )
+        .validate()
+        .is_ok());
+        assert!(request(
+            vec![target(
+                Some("0x1000"),
+                None,
+                Some(ReadFormat::Hexdump),
+                None
+            )],
+            None
+        )
+        .validate()
+        .is_err());
+        assert!(request(
+            vec![target(
+                Some("0x1000"),
+                Some(MAX_HEXDUMP_BYTES + 1),
+                Some(ReadFormat::Hexdump),
+                None
+            )],
+            None
+        )
+        .validate()
+        .is_err());
+
+        assert!(request(
+            vec![target(None, None, None, region("0x7ff800", 256))],
+            None
+        )
+        .validate()
+        .is_ok());
+        assert!(
+            request(vec![target(None, None, None, region("0x7ff800", 0))], None)
+                .validate()
+                .is_err()
+        );
+        assert!(request(
+            vec![target(
+                None,
+                None,
+                None,
+                region("0x7ff800", MAX_HEXDUMP_BYTES + 1)
+            )],
+            None
+        )
+        .validate()
+        .is_err());
+        assert!(
+            request(vec![target(None, None, None, region("main+4", 16))], None)
+                .validate()
+                .is_err()
+        );
+        assert!(request(
+            vec![target(Some("0x1000"), None, None, region("0x1000", 16))],
+            None
+        )
+        .validate()
+        .is_err());
+
+        let poll = Some(PollConfig {
+            interval_ms: 100,
+            duration_ms: 1000,
+        });
+        assert!(
+            request(vec![target(None, None, None, region("0x7ff800", 16))], poll)
+                .validate()
+                .is_err()
+        );
+    }
+
     #[test]
     fn test_debug_read_request_validation_depth_zero() {
         let req = DebugReadRequest {
@@ -2197,6 +5957,8 @@ mod read_tests {
                 address: None,
                 size: None,
                 type_hint: None,
+                format: None,
+                region: None,
             }],
             depth: Some(0),
             poll: None,
@@ -2213,6 +5975,8 @@ mod read_tests {
                 address: None,
                 size: None,
                 type_hint: None,
+                format: None,
+                region: None,
             }],
             depth: None,
             poll: Some(PollConfig {
@@ -2232,6 +5996,8 @@ mod read_tests {
                 address: None,
                 size: None,
                 type_hint: None,
+                format: None,
+                region: None,
             }],
             depth: None,
             poll: Some(PollConfig {
@@ -2251,6 +6017,8 @@ mod read_tests {
                 address: None,
                 size: None,
                 type_hint: None,
+                format: None,
+                region: None,
             }],
             depth: None,
             poll: Some(PollConfig {
@@ -2270,6 +6038,8 @@ mod read_tests {
                 address: Some("0x1000".to_string()),
                 size: Some(4),
                 type_hint: Some("int64".to_string()), // invalid — should be "i64"
+                format: None,
+                region: None,
             }],
             depth: None,
             poll: None,
@@ -2286,6 +6056,8 @@ mod read_tests {
                 address: Some("0x1000".to_string()),
                 size: Some(0), // invalid
                 type_hint: Some("u32".to_string()),
+                format: None,
+                region: None,
             }],
             depth: None,
             poll: None,
@@ -2302,6 +6074,8 @@ mod read_tests {
                 address: Some("0x1000".to_string()),
                 size: Some(100000), // above max 65536
                 type_hint: Some("bytes".to_string()),
+                format: None,
+                region: None,
             }],
             depth: None,
             poll: None,
@@ -2318,6 +6092,8 @@ mod read_tests {
                 address: None,
                 size: None,
                 type_hint: None,
+                format: None,
+                region: None,
             }],
             depth: None,
             poll: None,
@@ -2334,6 +6110,8 @@ mod read_tests {
                 address: Some("0x7ff800".to_string()),
                 size: Some(64),
                 type_hint: Some("bytes".to_string()),
+                format: None,
+                region: None,
             }],
             depth: None,
             poll: None,
@@ -2350,6 +6128,8 @@ mod read_tests {
                 address: None,
                 size: None,
                 type_hint: None,
+                format: None,
+                region: None,
             }],
             depth: Some(1),
             poll: Some(PollConfig {
@@ -2373,6 +6153,8 @@ mod read_tests {
                     address: Some("0x1000".to_string()),
                     size: Some(8),
                     type_hint: Some(type_hint.to_string()),
+                    format: None,
+                    region: None,
                 }],
                 depth: None,
                 poll: None,
@@ -2434,12 +6216,154 @@ mod query_pagination_tests {
             has_more: false,
             pids: None,
             last_event_id: Some(99),
+            last_seq: Some(1200),
             events_dropped: Some(false),
             crash: None,
+            partial: None,
         };
         let json = serde_json::to_value(&resp).unwrap();
         assert_eq!(json["lastEventId"], 99);
+        assert_eq!(json["lastSeq"], 1200);
         assert_eq!(json["eventsDropped"], false);
+        assert!(json.get("partial").is_none());
+    }
+
+    #[test]
+    fn test_query_request_with_after_seq() {
+        let json = serde_json::json!({
+            "sessionId": "s1",
+            "afterSeq": 1200
+        });
+        let req: DebugQueryRequest = serde_json::from_value(json).unwrap();
+        assert_eq!(req.after_seq, Some(1200));
+        assert_eq!(req.after_event_id, None);
+    }
+
+    #[test]
+    fn test_query_request_text_filter() {
+        let json = serde_json::json!({
+            "sessionId": "s1",
+            "text": { "contains": "connection refused" }
+        });
+        let req: DebugQueryRequest = serde_json::from_value(json).unwrap();
+        let text = req.text.unwrap();
+        assert_eq!(text.contains.as_deref(), Some("connection refused"));
+        assert!(text.validate().is_ok());
+
+        let bad = TextFilter {
+            contains: None,
+            regex: Some("(unclosed".to_string()),
+        };
+        assert!(bad.validate().is_err());
+
+        let empty = TextFilter {
+            contains: Some(String::new()),
+            regex: None,
+        };
+        assert!(empty.validate().is_err());
+    }
+
+    #[test]
+    fn test_query_request_field_filters() {
+        let json = serde_json::json!({
+            "sessionId": "s1",
+            "fields": { "level": "error", "status": 500, "cached": true }
+        });
+        let req: DebugQueryRequest = serde_json::from_value(json).unwrap();
+        assert!(req.validate().is_ok());
+        assert_eq!(
+            req.log_field_filters(),
+            vec![
+                ("cached".to_string(), "true".to_string()),
+                ("level".to_string(), "error".to_string()),
+                ("status".to_string(), "500".to_string()),
+            ]
+        );
+
+        let nested: DebugQueryRequest = serde_json::from_value(serde_json::json!({
+            "sessionId": "s1",
+            "fields": { "ctx": { "user": "bob" } }
+        }))
+        .unwrap();
+        assert!(nested.validate().is_err());
+    }
+
+    #[test]
+    fn test_query_request_select() {
+        let req: DebugQueryRequest = serde_json::from_value(serde_json::json!({
+            "sessionId": "s1",
+            "select": ["function", "duration_ns", "watchValues.gTempo"]
+        }))
+        .unwrap();
+        assert!(req.validate().is_ok());
+        assert_eq!(req.select.as_ref().unwrap().len(), 3);
+
+        for bad in [
+            serde_json::json!([]),
+            serde_json::json!(["watchValues."]),
+            serde_json::json!([".function"]),
+        ] {
+            let req: DebugQueryRequest = serde_json::from_value(serde_json::json!({
+                "sessionId": "s1",
+                "select": bad
+            }))
+            .unwrap();
+            assert!(req.validate().is_err());
+        }
+    }
+
+    #[test]
+    fn test_query_request_paired_format() {
+        let req: DebugQueryRequest = serde_json::from_value(serde_json::json!({
+            "sessionId": "s1",
+            "format": "paired",
+            "callId": "t1-42"
+        }))
+        .unwrap();
+        assert!(req.validate().is_ok());
+        assert_eq!(req.format, Some(QueryFormat::Paired));
+        assert_eq!(req.call_id.as_deref(), Some("t1-42"));
+
+        let req: DebugQueryRequest = serde_json::from_value(serde_json::json!({
+            "sessionId": "s1",
+            "format": "paired",
+            "eventType": "stderr"
+        }))
+        .unwrap();
+        assert!(req.validate().is_err());
+    }
+
+    #[test]
+    fn test_query_request_group_modes() {
+        let req: DebugQueryRequest = serde_json::from_value(serde_json::json!({
+            "sessionId": "s1",
+            "groupBy": "threadName"
+        }))
+        .unwrap();
+        assert!(req.validate().is_ok());
+        assert_eq!(
+            req.group_field(),
+            Some((QueryGroupField::ThreadName, false))
+        );
+        assert_eq!(QueryGroupField::ThreadName.column(), "thread_name");
+
+        let req: DebugQueryRequest = serde_json::from_value(serde_json::json!({
+            "sessionId": "s1",
+            "distinct": "function"
+        }))
+        .unwrap();
+        assert_eq!(req.group_field(), Some((QueryGroupField::Function, true)));
+
+        for bad in [
+            serde_json::json!({ "distinct": "function", "groupBy": "sourceFile" }),
+            serde_json::json!({ "groupBy": "function", "format": "paired" }),
+            serde_json::json!({ "distinct": "function", "select": ["function"] }),
+        ] {
+            let mut args = bad;
+            args["sessionId"] = serde_json::json!("s1");
+            let req: DebugQueryRequest = serde_json::from_value(args).unwrap();
+            assert!(req.validate().is_err());
+        }
     }
 }
 
@@ -2473,6 +6397,7 @@ mod unified_breakpoint_tests {
             breakpoints: vec![BreakpointInfo {
                 id: "bp-1".to_string(),
                 function: Some("foo".to_string()),
+                offset: None,
                 file: None,
                 line: None,
                 address: "0x1000".to_string(),
@@ -2520,48 +6445,213 @@ mod memory_consolidation_tests {
     }
 
     #[test]
-    fn test_memory_action_default_read() {
+    fn test_memory_series_request() {
+        let json = serde_json::json!({
+            "sessionId": "s1",
+            "action": "series",
+            "pollId": "poll-1a2b3c4d",
+            "csv": true
+        });
+        let req: DebugMemoryRequest = serde_json::from_value(json).unwrap();
+        assert_eq!(req.action, MemoryAction::Series);
+        assert!(req.targets.is_empty());
+        assert!(req.validate().is_ok());
+
+        let with_targets = serde_json::json!({
+            "sessionId": "s1",
+            "action": "series",
+            "targets": [{ "variable": "gTempo" }]
+        });
+        let req: DebugMemoryRequest = serde_json::from_value(with_targets).unwrap();
+        assert!(req.validate().is_err());
+
+        let csv_read = serde_json::json!({
+            "sessionId": "s1",
+            "targets": [{ "variable": "gTempo" }],
+            "csv": true
+        });
+        let req: DebugMemoryRequest = serde_json::from_value(csv_read).unwrap();
+        assert!(req.validate().is_err());
+    }
+
+    #[test]
+    fn test_memory_locals_request() {
+        let json = serde_json::json!({
+            "sessionId": "s1",
+            "action": "locals",
+            "threadId": 4242,
+            "frame": 2
+        });
+        let req: DebugMemoryRequest = serde_json::from_value(json).unwrap();
+        assert_eq!(req.action, MemoryAction::Locals);
+        assert_eq!(req.thread_id, Some(4242));
+        assert_eq!(req.frame, Some(2));
+        assert!(req.validate().is_ok());
+
+        let with_targets = serde_json::json!({
+            "sessionId": "s1",
+            "action": "locals",
+            "targets": [{ "variable": "gTempo" }]
+        });
+        let req: DebugMemoryRequest = serde_json::from_value(with_targets).unwrap();
+        assert!(req.validate().is_err());
+
+        let too_deep = serde_json::json!({
+            "sessionId": "s1",
+            "action": "locals",
+            "frame": MAX_LOCALS_FRAME + 1
+        });
+        let req: DebugMemoryRequest = serde_json::from_value(too_deep).unwrap();
+        assert!(req.validate().is_err());
+
+        let frame_on_read = serde_json::json!({
+            "sessionId": "s1",
+            "targets": [{ "variable": "gTempo" }],
+            "frame": 1
+        });
+        let req: DebugMemoryRequest = serde_json::from_value(frame_on_read).unwrap();
+        assert!(req.validate().is_err());
+    }
+
+    #[test]
+    fn test_memory_action_default_read() {
+        let json = serde_json::json!({
+            "sessionId": "s1",
+            "targets": [{ "variable": "gTempo" }]
+        });
+        let req: DebugMemoryRequest = serde_json::from_value(json).unwrap();
+        assert_eq!(req.action, MemoryAction::Read);
+    }
+
+    #[test]
+    fn test_eval_request_validation() {
+        let json = serde_json::json!({ "sessionId": "s1", "expr": "gClock->counter + 1" });
+        let req: DebugEvalRequest = serde_json::from_value(json).unwrap();
+        assert!(req.validate().is_ok());
+        assert_eq!(req.thread_id, None);
+
+        let empty = DebugEvalRequest {
+            session_id: "s1".to_string(),
+            expr: "  ".to_string(),
+            thread_id: None,
+            frame: None,
+        };
+        assert!(empty.validate().is_err());
+
+        let long = DebugEvalRequest {
+            session_id: "s1".to_string(),
+            expr: "1+".repeat(MAX_EVAL_EXPR_LENGTH),
+            thread_id: None,
+            frame: None,
+        };
+        assert!(long.validate().is_err());
+
+        let json = serde_json::json!({
+            "sessionId": "s1",
+            "expr": "chain->next->params->gain * buf.len",
+            "frame": 1
+        });
+        let req: DebugEvalRequest = serde_json::from_value(json).unwrap();
+        assert_eq!(req.frame, Some(1));
+        assert!(req.validate().is_ok());
+
+        let too_deep = DebugEvalRequest {
+            frame: Some(MAX_LOCALS_FRAME + 1),
+            ..req
+        };
+        assert!(too_deep.validate().is_err());
+    }
+}
+
+#[cfg(test)]
+mod test_consolidation_tests {
+    use super::*;
+
+    #[test]
+    fn test_debug_test_with_action_run() {
+        let json = serde_json::json!({
+            "action": "run",
+            "projectRoot": "/tmp/proj"
+        });
+        let req: DebugTestRequest = serde_json::from_value(json).unwrap();
+        assert_eq!(req.action, Some(TestAction::Run));
+    }
+
+    #[test]
+    fn test_debug_test_with_action_status() {
+        let json = serde_json::json!({
+            "action": "status",
+            "testRunId": "tr-123"
+        });
+        let req: DebugTestRequest = serde_json::from_value(json).unwrap();
+        assert_eq!(req.action, Some(TestAction::Status));
+        assert_eq!(req.test_run_id.as_deref(), Some("tr-123"));
+    }
+
+    #[test]
+    fn test_debug_test_with_action_list() {
+        let json = serde_json::json!({ "action": "list", "projectRoot": "/tmp/proj" });
+        let req: DebugTestRequest = serde_json::from_value(json).unwrap();
+        assert_eq!(req.action, Some(TestAction::List));
+        assert!(req.validate().is_ok());
+
+        let json = serde_json::json!({ "action": "list" });
+        let req: DebugTestRequest = serde_json::from_value(json).unwrap();
+        assert!(req.validate().is_err());
+    }
+
+    #[test]
+    fn test_debug_test_default_action_is_run() {
+        let json = serde_json::json!({
+            "projectRoot": "/tmp/proj"
+        });
+        let req: DebugTestRequest = serde_json::from_value(json).unwrap();
+        assert!(req.action.is_none()); // None treated as "run"
+    }
+
+    #[test]
+    fn test_debug_test_instrumentation_none() {
         let json = serde_json::json!({
-            "sessionId": "s1",
-            "targets": [{ "variable": "gTempo" }]
+            "projectRoot": "/tmp/proj",
+            "instrumentation": "none"
         });
-        let req: DebugMemoryRequest = serde_json::from_value(json).unwrap();
-        assert_eq!(req.action, MemoryAction::Read);
+        let req: DebugTestRequest = serde_json::from_value(json).unwrap();
+        assert_eq!(req.instrumentation, Some(TestInstrumentation::None));
+        assert!(req.validate().is_ok());
     }
-}
-
-#[cfg(test)]
-mod test_consolidation_tests {
-    use super::*;
 
     #[test]
-    fn test_debug_test_with_action_run() {
+    fn test_debug_test_instrumentation_none_rejects_trace_patterns() {
         let json = serde_json::json!({
-            "action": "run",
-            "projectRoot": "/tmp/proj"
+            "projectRoot": "/tmp/proj",
+            "instrumentation": "none",
+            "tracePatterns": ["foo::*"]
         });
         let req: DebugTestRequest = serde_json::from_value(json).unwrap();
-        assert_eq!(req.action, Some(TestAction::Run));
+        let err = req.validate().unwrap_err().to_string();
+        assert!(err.contains("instrumentation: 'frida'"), "got: {}", err);
     }
 
     #[test]
-    fn test_debug_test_with_action_status() {
+    fn test_debug_test_order_fail_fast_smart() {
         let json = serde_json::json!({
-            "action": "status",
-            "testRunId": "tr-123"
+            "projectRoot": "/tmp/proj",
+            "order": "fail-fast-smart"
         });
         let req: DebugTestRequest = serde_json::from_value(json).unwrap();
-        assert_eq!(req.action, Some(TestAction::Status));
-        assert_eq!(req.test_run_id.as_deref(), Some("tr-123"));
+        assert_eq!(req.order, Some(TestOrder::FailFastSmart));
+        assert!(req.validate().is_ok());
     }
 
     #[test]
-    fn test_debug_test_default_action_is_run() {
+    fn test_debug_test_order_rejects_single_test() {
         let json = serde_json::json!({
-            "projectRoot": "/tmp/proj"
+            "projectRoot": "/tmp/proj",
+            "test": "test_login",
+            "order": "fail-fast-smart"
         });
         let req: DebugTestRequest = serde_json::from_value(json).unwrap();
-        assert!(req.action.is_none()); // None treated as "run"
+        assert!(req.validate().is_err());
     }
 }
 
@@ -2615,6 +6705,23 @@ mod session_consolidation_tests {
         assert!(req.validate().is_ok());
     }
 
+    #[test]
+    fn test_session_consistency_check_repair_flag() {
+        let json =
+            serde_json::json!({ "action": "consistency-check", "sessionId": "s1", "repair": true });
+        let req: DebugSessionRequest = serde_json::from_value(json).unwrap();
+        assert_eq!(req.action, SessionAction::ConsistencyCheck);
+        assert!(req.validate().is_ok());
+
+        let json = serde_json::json!({ "action": "status", "sessionId": "s1", "repair": true });
+        let req: DebugSessionRequest = serde_json::from_value(json).unwrap();
+        assert!(req.validate().is_err());
+
+        let json = serde_json::json!({ "action": "consistency-check" });
+        let req: DebugSessionRequest = serde_json::from_value(json).unwrap();
+        assert!(req.validate().is_err());
+    }
+
     #[test]
     fn test_session_status_response_serde() {
         let resp = SessionStatusResponse {
@@ -2622,13 +6729,30 @@ mod session_consolidation_tests {
             pid: 1234,
             event_count: 100,
             hooked_functions: 5,
+            downsampled_until_ns: None,
             trace_patterns: vec!["foo::*".to_string()],
+            counted_patterns: vec![],
+            rt_patterns: vec![],
+            call_counts: vec![],
+            sampling: None,
+            threads: None,
+            rate_limit: None,
+            flight_recorder: None,
+            signal_safety: None,
+            syscalls: vec![],
+            network: false,
+            exceptions: false,
+            signals: false,
+            locks: false,
+            lock_contention: vec![],
+            symbol_modules: vec![],
             breakpoints: vec![],
             logpoints: vec![],
             watches: vec![],
             paused_threads: vec![],
             crash_info: None,
             capabilities: None,
+            writer: None,
         };
         let json = serde_json::to_value(&resp).unwrap();
         assert_eq!(json["status"], "running");
@@ -2657,3 +6781,694 @@ mod session_consolidation_tests {
         assert!(json.get("file").is_none());
     }
 }
+
+#[cfg(test)]
+mod export_tests {
+    use super::*;
+
+    #[test]
+    fn test_export_request_defaults_to_chrome_trace() {
+        let req: DebugExportRequest =
+            serde_json::from_value(serde_json::json!({ "sessionId": "s1" })).unwrap();
+        assert_eq!(req.format, ExportFormat::ChromeTrace);
+        assert!(req.output_path.is_none());
+        assert!(req.validate().is_ok());
+    }
+
+    #[test]
+    fn test_export_request_rejects_empty_session() {
+        let req: DebugExportRequest = serde_json::from_value(
+            serde_json::json!({ "sessionId": "", "format": "chrome_trace" }),
+        )
+        .unwrap();
+        assert!(req.validate().is_err());
+    }
+}
+
+#[cfg(test)]
+mod timeline_tests {
+    use super::*;
+
+    #[test]
+    fn test_timeline_request_validation() {
+        let req: DebugTimelineRequest = serde_json::from_value(serde_json::json!({
+            "sessionId": "s1", "action": "children", "eventId": "s1-fn-1-7-3"
+        }))
+        .unwrap();
+        assert_eq!(req.action, TimelineAction::Children);
+        assert!(req.validate().is_ok());
+
+        let no_event: DebugTimelineRequest =
+            serde_json::from_value(serde_json::json!({ "sessionId": "s1", "action": "parent" }))
+                .unwrap();
+        assert!(no_event.validate().is_err());
+
+        let stack_at: DebugTimelineRequest = serde_json::from_value(serde_json::json!({
+            "sessionId": "s1", "action": "stack", "timestampNs": 1000, "threadId": 7
+        }))
+        .unwrap();
+        assert!(stack_at.validate().is_ok());
+
+        let stack_no_thread = DebugTimelineRequest {
+            thread_id: None,
+            ..stack_at
+        };
+        assert!(stack_no_thread.validate().is_err());
+    }
+}
+
+#[cfg(test)]
+mod diff_tests {
+    use super::*;
+
+    #[test]
+    fn test_diff_request_validation() {
+        let req: DebugDiffRequest = serde_json::from_value(serde_json::json!({
+            "baseSessionId": "app-1", "sessionId": "app-2", "minRatio": 2.0
+        }))
+        .unwrap();
+        assert!(req.validate().is_ok());
+
+        let same = DebugDiffRequest {
+            session_id: "app-1".into(),
+            ..req.clone()
+        };
+        assert!(same.validate().is_err());
+
+        let low_ratio = DebugDiffRequest {
+            min_ratio: Some(0.5),
+            ..req
+        };
+        assert!(low_ratio.validate().is_err());
+    }
+}
+
+#[cfg(test)]
+mod coverage_tests {
+    use super::*;
+
+    #[test]
+    fn test_coverage_request_validation() {
+        let start: DebugCoverageRequest = serde_json::from_value(serde_json::json!({
+            "sessionId": "app-1", "action": "start", "scope": "all"
+        }))
+        .unwrap();
+        assert_eq!(start.scope, Some(CoverageScope::All));
+        assert!(start.validate().is_ok());
+
+        let start_with_format = DebugCoverageRequest {
+            format: Some(CoverageFormat::Lcov),
+            ..start.clone()
+        };
+        assert!(start_with_format.validate().is_err());
+
+        let stop: DebugCoverageRequest = serde_json::from_value(serde_json::json!({
+            "sessionId": "app-1", "action": "stop", "granularity": "function", "format": "lcov"
+        }))
+        .unwrap();
+        assert!(stop.validate().is_ok());
+        assert_eq!(stop.format.unwrap().extension(), "info");
+
+        let stop_with_scope = DebugCoverageRequest {
+            scope: Some(CoverageScope::Usercode),
+            ..stop.clone()
+        };
+        assert!(stop_with_scope.validate().is_err());
+
+        let empty_path = DebugCoverageRequest {
+            output_path: Some(String::new()),
+            ..stop
+        };
+        assert!(empty_path.validate().is_err());
+    }
+}
+
+#[cfg(test)]
+mod heap_tests {
+    use super::*;
+
+    #[test]
+    fn test_heap_request_validation() {
+        let report: DebugHeapRequest = serde_json::from_value(serde_json::json!({
+            "sessionId": "app-1", "action": "report", "limit": 5
+        }))
+        .unwrap();
+        assert_eq!(report.action, HeapAction::Report);
+        assert!(report.validate().is_ok());
+
+        let too_many = DebugHeapRequest {
+            limit: Some(MAX_HEAP_SITES + 1),
+            ..report.clone()
+        };
+        assert!(too_many.validate().is_err());
+
+        let start_with_limit = DebugHeapRequest {
+            action: HeapAction::Start,
+            ..report
+        };
+        assert!(start_with_limit.validate().is_err());
+    }
+
+    #[test]
+    fn test_heap_response_from_agent() {
+        let payload = serde_json::json!({
+            "type": "heap_response",
+            "active": true,
+            "hooked": ["malloc", "free"],
+            "totals": {
+                "allocations": 10, "frees": 7, "bytesAllocated": 4096, "liveBytes": 96,
+                "liveCount": 3, "peakLiveBytes": 1024, "untrackedFrees": 1, "sites": 2
+            },
+            "topSites": [{
+                "address": "0x1000", "moduleName": "app", "name": "load_config",
+                "fileName": "config.c", "lineNumber": 42,
+                "allocations": 8, "frees": 5, "bytes": 4000, "largest": 1024,
+                "liveBytes": 96, "liveCount": 3
+            }],
+            "leaks": [{
+                "address": "0x1000", "moduleName": "app", "name": "load_config",
+                "fileName": "", "lineNumber": 0,
+                "allocations": 8, "frees": 5, "bytes": 4000, "largest": 1024,
+                "liveBytes": 96, "liveCount": 3, "samples": ["0x7f0010", "0x7f0040"]
+            }],
+            "truncated": true
+        });
+        let response = DebugHeapResponse::from_agent("app-1", &payload);
+        assert!(response.active);
+        assert_eq!(response.hooked, vec!["malloc", "free"]);
+        assert_eq!(response.totals.as_ref().unwrap().live_bytes, 96);
+        assert_eq!(
+            response.top_sites[0].function.as_deref(),
+            Some("load_config")
+        );
+        assert_eq!(response.top_sites[0].line, Some(42));
+        assert_eq!(response.leaks[0].samples.len(), 2);
+        // Unknown source positions are left out
+        assert!(response.leaks[0].file.is_none() && response.leaks[0].line.is_none());
+        assert_eq!(response.warnings.len(), 1);
+
+        let json = serde_json::to_value(&response).unwrap();
+        assert_eq!(json["totals"]["peakLiveBytes"], 1024);
+        assert!(json["topSites"][0].get("samples").is_none());
+    }
+}
+
+#[cfg(test)]
+mod tutorial_tests {
+    use super::*;
+
+    #[test]
+    fn test_tutorial_request_validation() {
+        let start: DebugTutorialRequest = serde_json::from_value(serde_json::json!({})).unwrap();
+        assert!(start.validate().is_ok());
+
+        let check: DebugTutorialRequest = serde_json::from_value(serde_json::json!({
+            "action": "check", "step": 2, "sessionId": "strobe-demo-1", "answer": "7"
+        }))
+        .unwrap();
+        assert_eq!(check.action, Some(TutorialAction::Check));
+        assert!(check.validate().is_ok());
+
+        let no_session: DebugTutorialRequest =
+            serde_json::from_value(serde_json::json!({ "action": "check", "step": 1 })).unwrap();
+        assert!(no_session.validate().is_err());
+
+        let bad_step: DebugTutorialRequest = serde_json::from_value(serde_json::json!({
+            "action": "check", "step": 9, "sessionId": "s1"
+        }))
+        .unwrap();
+        assert!(bad_step.validate().is_err());
+    }
+}
+
+#[cfg(test)]
+mod crashes_tests {
+    use super::*;
+
+    #[test]
+    fn test_crashes_request_needs_project_or_session() {
+        let req: DebugCrashesRequest = serde_json::from_value(serde_json::json!({})).unwrap();
+        assert!(req.validate().is_err());
+
+        let req: DebugCrashesRequest =
+            serde_json::from_value(serde_json::json!({ "sessionId": "s1" })).unwrap();
+        assert!(req.validate().is_ok());
+
+        let req: DebugCrashesRequest =
+            serde_json::from_value(serde_json::json!({ "projectRoot": "/proj", "limit": 0 }))
+                .unwrap();
+        assert!(req.validate().is_err());
+    }
+
+    #[test]
+    fn test_crash_history_entry_flattens_record() {
+        let entry = CrashHistoryEntry {
+            record: crate::db::CrashRecord {
+                signature: "00ff".to_string(),
+                signal: Some("abort".to_string()),
+                exception_type: None,
+                top_frames: vec!["boom".to_string()],
+                occurrences: 2,
+                first_seen: 1,
+                last_seen: 2,
+                last_session_id: "s1".to_string(),
+                last_dump_path: None,
+            },
+            history: "seen 2 times today".to_string(),
+        };
+        let json = serde_json::to_value(&entry).unwrap();
+        assert_eq!(json["signature"], "00ff");
+        assert_eq!(json["topFrames"][0], "boom");
+        assert_eq!(json["history"], "seen 2 times today");
+        assert!(json.get("record").is_none());
+    }
+}
+
+#[cfg(test)]
+mod batch_tests {
+    use super::*;
+
+    #[test]
+    fn test_batch_request_validation() {
+        let req: DebugBatchRequest =
+            serde_json::from_value(serde_json::json!({ "calls": [] })).unwrap();
+        assert!(req.validate().is_err());
+
+        let req: DebugBatchRequest = serde_json::from_value(serde_json::json!({
+            "calls": [{ "tool": "debug_batch", "arguments": { "calls": [] } }]
+        }))
+        .unwrap();
+        assert!(req.validate().is_err());
+
+        let req: DebugBatchRequest = serde_json::from_value(serde_json::json!({
+            "calls": [{ "tool": "debug_query", "arguments": ["s1"] }]
+        }))
+        .unwrap();
+        assert!(req.validate().is_err());
+
+        let calls: Vec<_> = (0..=MAX_BATCH_CALLS)
+            .map(|_| serde_json::json!({ "tool": "debug_health" }))
+            .collect();
+        let req: DebugBatchRequest =
+            serde_json::from_value(serde_json::json!({ "calls": calls })).unwrap();
+        assert!(req.validate().is_err());
+    }
+
+    #[test]
+    fn test_batch_arguments_inherit_session() {
+        let req: DebugBatchRequest = serde_json::from_value(serde_json::json!({
+            "sessionId": "s1",
+            "calls": [
+                { "tool": "debug_session", "arguments": { "action": "status" } },
+                { "tool": "debug_query", "arguments": { "sessionId": "s2", "eventType": "stderr" } },
+                { "tool": "debug_health" }
+            ]
+        }))
+        .unwrap();
+        assert!(req.validate().is_ok());
+        assert_eq!(
+            req.arguments(0),
+            serde_json::json!({ "action": "status", "sessionId": "s1" })
+        );
+        assert_eq!(req.arguments(1)["sessionId"], "s2");
+        assert_eq!(req.arguments(2), serde_json::json!({ "sessionId": "s1" }));
+    }
+}
+
+#[cfg(test)]
+mod instrumentation_tests {
+    use super::*;
+
+    #[test]
+    fn test_export_instrumentation_action() {
+        let req: DebugSessionRequest = serde_json::from_value(serde_json::json!({
+            "action": "export-instrumentation",
+            "sessionId": "s1",
+            "path": "repro/setup.json"
+        }))
+        .unwrap();
+        assert_eq!(req.action, SessionAction::ExportInstrumentation);
+        assert!(req.validate().is_ok());
+
+        let missing: DebugSessionRequest =
+            serde_json::from_value(serde_json::json!({ "action": "export-instrumentation" }))
+                .unwrap();
+        assert!(missing.validate().is_err());
+
+        let traversal: DebugSessionRequest = serde_json::from_value(serde_json::json!({
+            "action": "export-instrumentation",
+            "sessionId": "s1",
+            "path": "../outside.json"
+        }))
+        .unwrap();
+        assert!(traversal.validate().is_err());
+
+        let wrong_action: DebugSessionRequest = serde_json::from_value(serde_json::json!({
+            "action": "stop",
+            "sessionId": "s1",
+            "path": "x.json"
+        }))
+        .unwrap();
+        assert!(wrong_action.validate().is_err());
+    }
+
+    #[test]
+    fn test_reload_agent_action() {
+        let req: DebugSessionRequest = serde_json::from_value(serde_json::json!({
+            "action": "reload-agent",
+            "sessionId": "s1",
+            "path": "agent/dist/agent.js"
+        }))
+        .unwrap();
+        assert_eq!(req.action, SessionAction::ReloadAgent);
+        assert!(req.validate().is_ok());
+
+        let missing: DebugSessionRequest =
+            serde_json::from_value(serde_json::json!({ "action": "reload-agent" })).unwrap();
+        assert!(missing.validate().is_err());
+    }
+
+    #[test]
+    fn test_investigation_request_validation() {
+        let parse = |v: serde_json::Value| -> DebugInvestigationRequest {
+            serde_json::from_value(v).unwrap()
+        };
+        let create = parse(serde_json::json!({
+            "action": "create", "title": "crash on resize", "projectRoot": "/proj"
+        }));
+        assert_eq!(create.action, InvestigationAction::Create);
+        assert!(create.validate().is_ok());
+        assert!(
+            parse(serde_json::json!({ "action": "create", "title": "x" }))
+                .validate()
+                .is_err()
+        );
+
+        let add = parse(serde_json::json!({
+            "action": "add-test-run", "investigationId": "inv-1", "testRunId": "test-1", "note": "flaky"
+        }));
+        assert_eq!(add.action, InvestigationAction::AddTestRun);
+        assert!(add.validate().is_ok());
+        assert!(
+            parse(serde_json::json!({ "action": "add-session", "investigationId": "inv-1" }))
+                .validate()
+                .is_err()
+        );
+        assert!(
+            parse(serde_json::json!({ "action": "add-note", "investigationId": "inv-1" }))
+                .validate()
+                .is_err()
+        );
+
+        assert!(parse(serde_json::json!({ "action": "list" }))
+            .validate()
+            .is_ok());
+        assert!(parse(serde_json::json!({ "action": "summarize" }))
+            .validate()
+            .is_err());
+        // Fields of other actions are rejected
+        assert!(parse(serde_json::json!({
+            "action": "summarize", "investigationId": "inv-1", "conclusion": "done"
+        }))
+        .validate()
+        .is_err());
+        assert!(parse(serde_json::json!({
+            "action": "close", "investigationId": "inv-1", "note": "done"
+        }))
+        .validate()
+        .is_err());
+    }
+
+    #[test]
+    fn test_relaunch_action() {
+        let req: DebugSessionRequest = serde_json::from_value(serde_json::json!({
+            "action": "relaunch",
+            "sessionId": "s1",
+            "inheritInstrumentation": false
+        }))
+        .unwrap();
+        assert_eq!(req.action, SessionAction::Relaunch);
+        assert_eq!(req.inherit_instrumentation, Some(false));
+        assert!(req.validate().is_ok());
+
+        let missing: DebugSessionRequest =
+            serde_json::from_value(serde_json::json!({ "action": "relaunch" })).unwrap();
+        assert!(missing.validate().is_err());
+
+        let scoped: DebugSessionRequest = serde_json::from_value(serde_json::json!({
+            "action": "list", "projectRoot": "/home/user/project"
+        }))
+        .unwrap();
+        assert!(scoped.validate().is_ok());
+        let scoped_stop: DebugSessionRequest = serde_json::from_value(serde_json::json!({
+            "action": "stop", "sessionId": "s1", "projectRoot": "/home/user/project"
+        }))
+        .unwrap();
+        assert!(scoped_stop.validate().is_err());
+
+        let wrong_action: DebugSessionRequest = serde_json::from_value(serde_json::json!({
+            "action": "stop",
+            "sessionId": "s1",
+            "inheritInstrumentation": true
+        }))
+        .unwrap();
+        assert!(wrong_action.validate().is_err());
+    }
+
+    #[test]
+    fn test_snapshot_parses_minimal_file_and_checks_version() {
+        let snapshot: InstrumentationSnapshot = serde_json::from_value(serde_json::json!({
+            "version": 1,
+            "sessionId": "app-1",
+            "binaryPath": "/bin/app",
+            "exportedAt": "2026-10-15T12:00:00Z",
+            "patterns": ["app::*"],
+            "breakpoints": [{ "file": "main.cpp", "line": 10 }]
+        }))
+        .unwrap();
+        assert!(snapshot.validate().is_ok());
+        assert!(snapshot.watches.is_empty());
+        assert_eq!(snapshot.settings, InstrumentationSettings::default());
+
+        let json = serde_json::to_value(&snapshot).unwrap();
+        assert!(json.get("watches").is_none());
+        assert_eq!(json["breakpoints"][0]["line"], 10);
+
+        let mut future = snapshot.clone();
+        future.version = 2;
+        assert!(future.validate().is_err());
+
+        let mut deep = snapshot;
+        deep.settings.serialization_depth = Some(11);
+        assert!(deep.validate().is_err());
+    }
+
+    #[test]
+    fn test_launch_on_output_triggers() {
+        let mut req: DebugLaunchRequest = serde_json::from_value(serde_json::json!({
+            "command": "/bin/app",
+            "projectRoot": "/tmp",
+            "onOutput": [
+                { "match": "Segmentation fault imminent", "action": "pause" },
+                { "match": "tick", "action": "snapshot-watches" },
+                { "match": "ready", "action": "annotate" }
+            ]
+        }))
+        .unwrap();
+        let triggers = req.on_output.as_ref().unwrap();
+        assert_eq!(triggers[0].action, OutputTriggerAction::Pause);
+        assert_eq!(triggers[1].action, OutputTriggerAction::SnapshotWatches);
+        assert!(req.validate().is_ok());
+
+        req.on_output = Some(vec![OutputTrigger {
+            pattern: String::new(),
+            action: OutputTriggerAction::Annotate,
+        }]);
+        assert!(req.validate().is_err());
+
+        let bad_action = serde_json::from_value::<DebugLaunchRequest>(serde_json::json!({
+            "command": "/bin/app",
+            "projectRoot": "/tmp",
+            "onOutput": [{ "match": "x", "action": "explode" }]
+        }));
+        assert!(bad_action.is_err());
+    }
+
+    #[test]
+    fn test_launch_instrumentation_file_validation() {
+        let mut req: DebugLaunchRequest = serde_json::from_value(serde_json::json!({
+            "command": "/bin/app",
+            "projectRoot": "/tmp",
+            "instrumentationFile": ".strobe/instrumentation/app-1.json"
+        }))
+        .unwrap();
+        assert!(req.validate().is_ok());
+        req.instrumentation_file = Some("../../etc/passwd".to_string());
+        assert!(req.validate().is_err());
+    }
+}
+
+#[cfg(test)]
+mod capture_channel_tests {
+    use super::*;
+
+    fn launch(extra: serde_json::Value) -> DebugLaunchRequest {
+        let mut json = serde_json::json!({ "command": "/bin/app", "projectRoot": "/tmp" });
+        json.as_object_mut()
+            .unwrap()
+            .extend(extra.as_object().unwrap().clone());
+        serde_json::from_value(json).unwrap()
+    }
+
+    #[test]
+    fn test_capture_fds_valid() {
+        let req = launch(serde_json::json!({ "captureFds": [3, 4] }));
+        assert_eq!(req.capture_fds, Some(vec![3, 4]));
+        assert!(req.validate().is_ok());
+    }
+
+    #[test]
+    fn test_capture_fds_rejects_stdio() {
+        let req = launch(serde_json::json!({ "captureFds": [1] }));
+        assert!(req.validate().unwrap_err().to_string().contains("fd 1"));
+    }
+
+    #[test]
+    fn test_capture_pipes_requires_absolute_path() {
+        let req = launch(serde_json::json!({ "capturePipes": ["diag.fifo"] }));
+        assert!(req.validate().is_err());
+        let req = launch(serde_json::json!({ "capturePipes": ["/tmp/diag.fifo"] }));
+        assert!(req.validate().is_ok());
+    }
+
+    #[test]
+    fn test_symbol_extra_requires_absolute_paths() {
+        let req = launch(serde_json::json!({ "symbolExtra": ["/opt/lib/libengine.dylib"] }));
+        assert!(req.validate().is_ok());
+        let req = launch(serde_json::json!({ "symbolExtra": ["libengine.dylib"] }));
+        assert!(req.validate().is_err());
+        let req = launch(serde_json::json!({ "symbolExtra": ["/opt/../lib/libengine.so"] }));
+        assert!(req.validate().is_err());
+    }
+
+    #[test]
+    fn test_crash_capture_validation() {
+        let req = launch(serde_json::json!({
+            "crashCapture": { "variables": ["gEngineState", "gRingBuffer"], "maxBytes": 65536 }
+        }));
+        let capture = req.crash_capture.as_ref().unwrap();
+        assert_eq!(capture.variables.len(), 2);
+        assert_eq!(capture.budget(), 65536);
+        assert!(req.validate().is_ok());
+
+        let req = launch(serde_json::json!({ "crashCapture": { "variables": ["gState"] } }));
+        assert_eq!(
+            req.crash_capture.unwrap().budget(),
+            DEFAULT_CRASH_CAPTURE_BYTES
+        );
+
+        let req = launch(serde_json::json!({ "crashCapture": { "variables": [] } }));
+        assert!(req.validate().is_err());
+        let req = launch(serde_json::json!({
+            "crashCapture": { "variables": ["gState"], "maxBytes": 0 }
+        }));
+        assert!(req.validate().is_err());
+        let req = launch(serde_json::json!({
+            "crashCapture": { "variables": ["gState"], "maxBytes": 16 * 1024 * 1024 }
+        }));
+        assert!(req.validate().is_err());
+    }
+}
+
+#[cfg(test)]
+mod watchpoint_tests {
+    use super::*;
+
+    fn request(add: serde_json::Value) -> DebugWatchpointRequest {
+        serde_json::from_value(serde_json::json!({ "sessionId": "s1", "add": add })).unwrap()
+    }
+
+    #[test]
+    fn test_watchpoint_variable_valid() {
+        let req = request(serde_json::json!([{ "variable": "g_counter" }]));
+        assert!(req.validate().is_ok());
+    }
+
+    #[test]
+    fn test_watchpoint_requires_exactly_one_target() {
+        assert!(request(serde_json::json!([{ "label": "x" }]))
+            .validate()
+            .is_err());
+        assert!(
+            request(serde_json::json!([{ "variable": "g", "address": "0x10", "size": 4 }]))
+                .validate()
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_watchpoint_raw_address_needs_valid_hex_and_size() {
+        assert!(request(serde_json::json!([{ "address": "0x1000" }]))
+            .validate()
+            .is_err());
+        assert!(
+            request(serde_json::json!([{ "address": "nope", "size": 4 }]))
+                .validate()
+                .is_err()
+        );
+        assert!(
+            request(serde_json::json!([{ "address": "0x1000", "size": 8 }]))
+                .validate()
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn test_watchpoint_size_bounds() {
+        let req = request(serde_json::json!([{ "variable": "g", "size": 0 }]));
+        assert!(req.validate().is_err());
+        let req =
+            request(serde_json::json!([{ "variable": "g", "size": MAX_WATCHPOINT_SIZE + 1 }]));
+        assert!(req.validate().is_err());
+    }
+}
+
+#[cfg(test)]
+mod symbols_tests {
+    use super::*;
+
+    fn request(json: serde_json::Value) -> DebugSymbolsRequest {
+        serde_json::from_value(json).unwrap()
+    }
+
+    #[test]
+    fn test_symbols_request_validation() {
+        assert!(request(serde_json::json!({
+            "sessionId": "s1", "action": "functions", "query": "^audio::", "regex": true,
+            "file": "mixer.cpp", "limit": 200
+        }))
+        .validate()
+        .is_ok());
+        assert!(
+            request(serde_json::json!({ "sessionId": "s1", "action": "modules" }))
+                .validate()
+                .is_ok()
+        );
+
+        for bad in [
+            serde_json::json!({ "sessionId": "", "action": "functions" }),
+            serde_json::json!({ "sessionId": "s1", "action": "modules", "query": "a" }),
+            serde_json::json!({ "sessionId": "s1", "action": "globals", "file": "a.c" }),
+            serde_json::json!({ "sessionId": "s1", "action": "globals", "regex": true }),
+            serde_json::json!({ "sessionId": "s1", "action": "functions", "limit": 0 }),
+            serde_json::json!({ "sessionId": "s1", "action": "functions", "limit": 1001 }),
+            serde_json::json!({ "sessionId": "s1", "action": "functions", "query": "a".repeat(257) }),
+        ] {
+            assert!(
+                request(bad.clone()).validate().is_err(),
+                "{} should be rejected",
+                bad
+            );
+        }
+    }
+}
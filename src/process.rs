@@ -0,0 +1,205 @@
+//! Process liveness, suspension and termination by PID.
+//!
+//! Signals on Unix; `OpenProcess` + `GetExitCodeProcess` / `TerminateProcess`
+//! and per-thread suspension on Windows.
+
+/// Check if a process is alive. Returns true if the process exists,
+/// even if we lack permission to signal or open it.
+#[cfg(unix)]
+pub fn is_alive(pid: u32) -> bool {
+    let result = unsafe { libc::kill(pid as i32, 0) };
+    if result == 0 {
+        return true;
+    }
+    // EPERM means alive but no permission, ESRCH means dead
+    std::io::Error::last_os_error().raw_os_error() == Some(libc::EPERM)
+}
+
+#[cfg(windows)]
+pub fn is_alive(pid: u32) -> bool {
+    use windows_sys::Win32::Foundation::{
+        CloseHandle, GetLastError, ERROR_ACCESS_DENIED, STILL_ACTIVE,
+    };
+    use windows_sys::Win32::System::Threading::{
+        GetExitCodeProcess, OpenProcess, PROCESS_QUERY_LIMITED_INFORMATION,
+    };
+
+    unsafe {
+        let handle = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, 0, pid);
+        if handle.is_null() {
+            return GetLastError() == ERROR_ACCESS_DENIED;
+        }
+        let mut code: u32 = 0;
+        let ok = GetExitCodeProcess(handle, &mut code) != 0;
+        CloseHandle(handle);
+        ok && code == STILL_ACTIVE as u32
+    }
+}
+
+/// Forcefully terminate a process (SIGKILL on Unix). Errors are ignored:
+/// the process may already be gone.
+#[cfg(unix)]
+pub fn kill(pid: u32) {
+    unsafe {
+        libc::kill(pid as i32, libc::SIGKILL);
+    }
+}
+
+#[cfg(windows)]
+pub fn kill(pid: u32) {
+    use windows_sys::Win32::Foundation::CloseHandle;
+    use windows_sys::Win32::System::Threading::{OpenProcess, TerminateProcess, PROCESS_TERMINATE};
+
+    unsafe {
+        let handle = OpenProcess(PROCESS_TERMINATE, 0, pid);
+        if !handle.is_null() {
+            TerminateProcess(handle, 1);
+            CloseHandle(handle);
+        }
+    }
+}
+
+/// Stop a process (SIGSTOP) until `resume` is called.
+#[cfg(unix)]
+pub fn suspend(pid: u32) -> std::io::Result<()> {
+    send_signal(pid, libc::SIGSTOP)
+}
+
+/// Continue a process stopped by `suspend` (SIGCONT).
+#[cfg(unix)]
+pub fn resume(pid: u32) -> std::io::Result<()> {
+    send_signal(pid, libc::SIGCONT)
+}
+
+#[cfg(unix)]
+fn send_signal(pid: u32, signal: libc::c_int) -> std::io::Result<()> {
+    if unsafe { libc::kill(pid as libc::pid_t, signal) } == 0 {
+        Ok(())
+    } else {
+        Err(std::io::Error::last_os_error())
+    }
+}
+
+/// Windows has no process-wide stop: suspend every thread of the process.
+#[cfg(windows)]
+pub fn suspend(pid: u32) -> std::io::Result<()> {
+    for_each_thread(pid, |thread| unsafe {
+        windows_sys::Win32::System::Threading::SuspendThread(thread) != u32::MAX
+    })
+}
+
+#[cfg(windows)]
+pub fn resume(pid: u32) -> std::io::Result<()> {
+    for_each_thread(pid, |thread| unsafe {
+        windows_sys::Win32::System::Threading::ResumeThread(thread) != u32::MAX
+    })
+}
+
+#[cfg(windows)]
+fn for_each_thread(
+    pid: u32,
+    op: impl Fn(windows_sys::Win32::Foundation::HANDLE) -> bool,
+) -> std::io::Result<()> {
+    use windows_sys::Win32::Foundation::{CloseHandle, INVALID_HANDLE_VALUE};
+    use windows_sys::Win32::System::Diagnostics::ToolHelp::{
+        CreateToolhelp32Snapshot, Thread32First, Thread32Next, TH32CS_SNAPTHREAD, THREADENTRY32,
+    };
+    use windows_sys::Win32::System::Threading::{OpenThread, THREAD_SUSPEND_RESUME};
+
+    unsafe {
+        let snapshot = CreateToolhelp32Snapshot(TH32CS_SNAPTHREAD, 0);
+        if snapshot == INVALID_HANDLE_VALUE {
+            return Err(std::io::Error::last_os_error());
+        }
+        let mut entry: THREADENTRY32 = std::mem::zeroed();
+        entry.dwSize = std::mem::size_of::<THREADENTRY32>() as u32;
+        let mut found = false;
+        let mut more = Thread32First(snapshot, &mut entry) != 0;
+        while more {
+            if entry.th32OwnerProcessID == pid {
+                let thread = OpenThread(THREAD_SUSPEND_RESUME, 0, entry.th32ThreadID);
+                if !thread.is_null() {
+                    found |= op(thread);
+                    CloseHandle(thread);
+                }
+            }
+            more = Thread32Next(snapshot, &mut entry) != 0;
+        }
+        CloseHandle(snapshot);
+        if found {
+            Ok(())
+        } else {
+            Err(std::io::Error::last_os_error())
+        }
+    }
+}
+
+/// Reap an exited child without blocking and return its exit code (128 +
+/// signal number when killed by a signal). `None` while it is still running
+/// or when it is not our child.
+#[cfg(unix)]
+pub fn try_reap(pid: u32) -> Option<i32> {
+    let mut status: i32 = 0;
+    if unsafe { libc::waitpid(pid as i32, &mut status, libc::WNOHANG) } <= 0 {
+        return None;
+    }
+    if libc::WIFEXITED(status) {
+        Some(libc::WEXITSTATUS(status))
+    } else if libc::WIFSIGNALED(status) {
+        Some(128 + libc::WTERMSIG(status))
+    } else {
+        Some(-1)
+    }
+}
+
+#[cfg(windows)]
+pub fn try_reap(pid: u32) -> Option<i32> {
+    use windows_sys::Win32::Foundation::{CloseHandle, STILL_ACTIVE};
+    use windows_sys::Win32::System::Threading::{
+        GetExitCodeProcess, OpenProcess, PROCESS_QUERY_LIMITED_INFORMATION,
+    };
+
+    unsafe {
+        let handle = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, 0, pid);
+        if handle.is_null() {
+            return None;
+        }
+        let mut code: u32 = 0;
+        let ok = GetExitCodeProcess(handle, &mut code) != 0;
+        CloseHandle(handle);
+        (ok && code != STILL_ACTIVE as u32).then_some(code as i32)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_current_process_is_alive() {
+        assert!(is_alive(std::process::id()));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_killed_child_is_not_alive() {
+        let child = std::process::Command::new("sleep")
+            .arg("30")
+            .spawn()
+            .unwrap();
+        let pid = child.id();
+        assert!(is_alive(pid));
+        kill(pid);
+        let mut code = None;
+        for _ in 0..100 {
+            code = try_reap(pid);
+            if code.is_some() {
+                break;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(20));
+        }
+        assert_eq!(code, Some(128 + libc::SIGKILL));
+        assert!(!is_alive(pid));
+        drop(child);
+    }
+}
@@ -0,0 +1,167 @@
+//! `strobe projects`: review what the event database holds per project and
+//! clean out data of repositories that no longer exist.
+//!
+//! ```text
+//! strobe projects                     list projects
+//! strobe projects clean [--dry-run]   remove data of projects whose root is gone
+//! strobe projects remove <root>       remove one project's data
+//! strobe projects rename <root> <name>
+//! ```
+//!
+//! Works on the database directly, so the daemon doesn't need to run. Projects
+//! with a running session are never removed.
+
+use crate::db::{normalize_project_root, Database, Project, ProjectCleanup};
+use crate::Result;
+use std::fmt::Write as _;
+use std::path::PathBuf;
+
+const USAGE: &str =
+    "Usage: strobe projects [list | clean [--dry-run] | remove <root> | rename <root> <name>]";
+
+pub fn projects(args: &[String]) -> Result<()> {
+    let db_path = dirs::home_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join(".strobe/strobe.db");
+    if !db_path.exists() {
+        println!("No strobe database yet ({}).", db_path.display());
+        return Ok(());
+    }
+    let db = Database::open(&db_path)?;
+
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    match args.as_slice() {
+        [] | ["list"] => print!("{}", render_list(&db.list_projects()?)),
+        ["clean"] => clean(&db, false)?,
+        ["clean", "--dry-run"] => clean(&db, true)?,
+        ["remove", root] => {
+            let project = find(&db, root)?;
+            let cleanup = db.delete_project_data(&project.root)?;
+            println!("Removed {}: {}", project.root, describe_cleanup(&cleanup));
+        }
+        ["rename", root, name] => {
+            let project = find(&db, root)?;
+            db.register_project(&project.root, Some(name))?;
+            println!("{} is now \"{}\"", project.root, name);
+        }
+        _ => return Err(crate::Error::ValidationError(USAGE.to_string())),
+    }
+    Ok(())
+}
+
+/// The registered project `root` refers to, when nothing of it is running.
+fn find(db: &Database, root: &str) -> Result<Project> {
+    let normalized = normalize_project_root(root);
+    let project = db
+        .list_projects()?
+        .into_iter()
+        .find(|p| p.root == normalized || p.root == root)
+        .ok_or_else(|| crate::Error::ValidationError(format!("No project at {}", root)))?;
+    if project.running_sessions > 0 {
+        return Err(crate::Error::ValidationError(format!(
+            "{} has {} running session(s); stop them first",
+            project.root, project.running_sessions
+        )));
+    }
+    Ok(project)
+}
+
+fn clean(db: &Database, dry_run: bool) -> Result<()> {
+    let gone: Vec<Project> = db
+        .list_projects()?
+        .into_iter()
+        .filter(|p| !p.exists && p.running_sessions == 0)
+        .collect();
+    if gone.is_empty() {
+        println!("Every project root still exists; nothing to clean.");
+        return Ok(());
+    }
+    for project in &gone {
+        if dry_run {
+            println!(
+                "Would remove {} ({} sessions, {})",
+                project.root,
+                project.sessions,
+                format_bytes(project.retained_bytes)
+            );
+        } else {
+            let cleanup = db.delete_project_data(&project.root)?;
+            println!("Removed {}: {}", project.root, describe_cleanup(&cleanup));
+        }
+    }
+    Ok(())
+}
+
+fn render_list(projects: &[Project]) -> String {
+    let mut out = String::new();
+    if projects.is_empty() {
+        let _ = writeln!(out, "No projects recorded.");
+        return out;
+    }
+    let _ = writeln!(
+        out,
+        "{:<24}  {:>8}  {:>9}  {:<10}  root",
+        "name", "sessions", "retained", "last used"
+    );
+    for p in projects {
+        let last_used = chrono::DateTime::from_timestamp(p.last_used_at, 0)
+            .map(|t| t.format("%Y-%m-%d").to_string())
+            .unwrap_or_default();
+        let mut root = p.root.clone();
+        if !p.exists {
+            root.push_str("  (missing)");
+        } else if p.running_sessions > 0 {
+            let _ = write!(root, "  ({} running)", p.running_sessions);
+        }
+        let _ = writeln!(
+            out,
+            "{:<24}  {:>8}  {:>9}  {:<10}  {}",
+            p.name,
+            p.sessions,
+            format_bytes(p.retained_bytes),
+            last_used,
+            root
+        );
+    }
+    out
+}
+
+fn describe_cleanup(c: &ProjectCleanup) -> String {
+    format!(
+        "{} sessions, {} events, {} baselines, {} crash signatures, {} investigations",
+        c.sessions, c.events, c.baselines, c.crashes, c.investigations
+    )
+}
+
+fn format_bytes(bytes: i64) -> String {
+    const UNITS: [&str; 4] = ["B", "KB", "MB", "GB"];
+    let mut value = bytes.max(0) as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} B", bytes.max(0))
+    } else {
+        format!("{:.1} {}", value, UNITS[unit])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_list_flags_missing_roots() {
+        let db = Database::open_in_memory().unwrap();
+        db.create_session("a-1", "/bin/a", "/gone/alpha", 1)
+            .unwrap();
+        let out = render_list(&db.list_projects().unwrap());
+        assert!(out.starts_with("name"));
+        assert!(out.contains("alpha"));
+        assert!(out.contains("/gone/alpha  (missing)"));
+        assert_eq!(format_bytes(512), "512 B");
+        assert_eq!(format_bytes(3 * 1024 * 1024), "3.0 MB");
+    }
+}
@@ -0,0 +1,533 @@
+//! `strobe report <sessionId>`: a terminal summary of one session, for a
+//! human reviewing what an agent did with it.
+//!
+//! Everything except the active instrumentation comes from the event
+//! database, so the report works for stopped and retained sessions too.
+//! Patterns, watches, breakpoints and logpoints only live in the daemon;
+//! they are fetched over its socket when the session is still loaded there.
+
+use crate::db::{Database, Event, EventType, FunctionTime, Session};
+use crate::mcp::SessionStatusResponse;
+use crate::Result;
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+const TOP_FUNCTIONS: usize = 10;
+const STDERR_LINES: u32 = 15;
+const CRASHES: u32 = 3;
+const SPARKLINE_WIDTH: usize = 40;
+/// Give up on the daemon after this long; the report still prints from the DB.
+const DAEMON_TIMEOUT: Duration = Duration::from_secs(3);
+/// Longest function name or output line printed before truncation.
+const MAX_TEXT_WIDTH: usize = 100;
+
+const SPARK_LEVELS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+pub struct SessionReport {
+    pub session: Session,
+    pub event_count: u64,
+    pub top_functions: Vec<FunctionTime>,
+    /// Most recent stderr lines, oldest first
+    pub stderr: Vec<String>,
+    /// Most recent crash events, newest first, with their crash history
+    pub crashes: Vec<(Event, Option<String>)>,
+    pub watches: BTreeMap<String, Vec<f64>>,
+    /// `debug_session` status from the daemon, when the session is loaded there
+    pub live: Option<SessionStatusResponse>,
+}
+
+impl SessionReport {
+    pub fn gather(db: &Database, session_id: &str) -> Result<Self> {
+        let session = db
+            .get_session(session_id)?
+            .ok_or_else(|| crate::Error::SessionNotFound(session_id.to_string()))?;
+
+        let mut stderr: Vec<String> = db
+            .query_events(session_id, |q| {
+                q.event_type(EventType::Stderr).limit(STDERR_LINES)
+            })?
+            .into_iter()
+            .filter_map(|e| e.text)
+            .flat_map(|text| text.lines().rev().map(str::to_string).collect::<Vec<_>>())
+            .take(STDERR_LINES as usize)
+            .collect();
+        stderr.reverse();
+
+        let crashes = db
+            .query_events(session_id, |q| {
+                q.event_type(EventType::Crash).limit(CRASHES)
+            })?
+            .into_iter()
+            .map(|event| {
+                let history = db.crash_history(&event);
+                (event, history)
+            })
+            .collect();
+
+        Ok(Self {
+            event_count: db.count_session_events(session_id)?,
+            top_functions: db.top_functions_by_time(session_id, TOP_FUNCTIONS)?,
+            stderr,
+            crashes,
+            watches: db.watch_series(session_id)?,
+            live: None,
+            session,
+        })
+    }
+
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        let s = &self.session;
+
+        let _ = writeln!(out, "Session {}", s.id);
+        let status = match &self.live {
+            Some(live) => live.status.clone(),
+            None => s.status.as_str().to_string(),
+        };
+        let duration = s.ended_at.unwrap_or_else(|| chrono::Utc::now().timestamp()) - s.started_at;
+        let _ = writeln!(out, "  binary   {}", s.binary_path);
+        let _ = writeln!(out, "  project  {}", s.project_root);
+        let _ = writeln!(
+            out,
+            "  status   {} (pid {}, {})",
+            status,
+            s.pid,
+            format_seconds(duration.max(0))
+        );
+        let started = chrono::DateTime::from_timestamp(s.started_at, 0)
+            .map(|t| t.format("%Y-%m-%d %H:%M:%S UTC").to_string())
+            .unwrap_or_default();
+        let _ = writeln!(out, "  started  {}", started);
+        let retained = if s.retained { ", retained" } else { "" };
+        let _ = writeln!(out, "  events   {}{}", self.event_count, retained);
+
+        section(&mut out, "Top functions by time (inclusive)");
+        if self.top_functions.is_empty() {
+            let _ = writeln!(out, "  (no traced calls with durations)");
+        } else {
+            let _ = writeln!(
+                out,
+                "  {:>10}  {:>8}  {:>10}  function",
+                "total", "calls", "max"
+            );
+            for f in &self.top_functions {
+                let _ = writeln!(
+                    out,
+                    "  {:>10}  {:>8}  {:>10}  {}",
+                    format_ns(f.total_ns),
+                    f.calls,
+                    format_ns(f.max_ns),
+                    truncate(&f.function)
+                );
+            }
+        }
+
+        section(&mut out, "Recent stderr");
+        if self.stderr.is_empty() {
+            let _ = writeln!(out, "  (none)");
+        }
+        for line in &self.stderr {
+            let _ = writeln!(out, "  {}", truncate(line));
+        }
+
+        section(&mut out, "Crashes");
+        if self.crashes.is_empty() {
+            let _ = writeln!(out, "  (none)");
+        }
+        for (event, history) in &self.crashes {
+            let mut headline = event
+                .signal
+                .clone()
+                .or_else(|| event.exception_type.clone())
+                .unwrap_or_else(|| "crash".to_string());
+            if let Some(ref addr) = event.fault_address {
+                let _ = write!(headline, " at {}", addr);
+            }
+            let _ = writeln!(out, "  {}", headline);
+            if let Some(ref message) = event.exception_message {
+                let _ = writeln!(out, "    {}", truncate(message));
+            }
+            if let Some(frame) = top_frame(event) {
+                let _ = writeln!(out, "    in {}", truncate(&frame));
+            }
+            if let Some(history) = history {
+                let _ = writeln!(out, "    {}", history);
+            }
+        }
+
+        section(&mut out, "Watches");
+        if self.watches.is_empty() {
+            let _ = writeln!(out, "  (no numeric watch values recorded)");
+        }
+        let label_width = self.watches.keys().map(|l| l.len()).max().unwrap_or(0);
+        for (label, values) in &self.watches {
+            let (min, max) = min_max(values);
+            let last = values.last().copied().unwrap_or_default();
+            let _ = writeln!(
+                out,
+                "  {:<width$}  {}  last {} (min {}, max {}, {} samples)",
+                label,
+                sparkline(values, SPARKLINE_WIDTH),
+                format_value(last),
+                format_value(min),
+                format_value(max),
+                values.len(),
+                width = label_width
+            );
+        }
+
+        section(&mut out, "Active instrumentation");
+        match &self.live {
+            None => {
+                let _ = writeln!(
+                    out,
+                    "  (session not loaded in a running daemon; instrumentation is not stored)"
+                );
+            }
+            Some(live) => render_instrumentation(&mut out, live),
+        }
+
+        out
+    }
+}
+
+fn render_instrumentation(out: &mut String, live: &SessionStatusResponse) {
+    let _ = writeln!(out, "  hooked functions  {}", live.hooked_functions);
+    for pattern in &live.trace_patterns {
+        let mode = if live.counted_patterns.contains(pattern) {
+            " (count)"
+        } else if live.rt_patterns.contains(pattern) {
+            " (rt)"
+        } else {
+            ""
+        };
+        let _ = writeln!(out, "  trace      {}{}", pattern, mode);
+    }
+    for watch in &live.watches {
+        let scope = watch
+            .on
+            .as_ref()
+            .map(|on| format!(" on {}", on.join(", ")))
+            .unwrap_or_default();
+        let _ = writeln!(
+            out,
+            "  watch      {} @ {}{}",
+            watch.label, watch.address, scope
+        );
+    }
+    for bp in &live.breakpoints {
+        let _ = writeln!(
+            out,
+            "  breakpoint {} {}",
+            bp.id,
+            location(&bp.function, &bp.file, bp.line, &bp.address)
+        );
+    }
+    for lp in &live.logpoints {
+        let _ = writeln!(
+            out,
+            "  logpoint   {} {} \"{}\"",
+            lp.id,
+            location(&lp.function, &lp.file, lp.line, &lp.address),
+            truncate(&lp.message)
+        );
+    }
+    if let Some(ref sampling) = live.sampling {
+        let hz = sampling
+            .hz
+            .map(|hz| format!(" at {} Hz", hz))
+            .unwrap_or_default();
+        let _ = writeln!(out, "  sampling   on{}", hz);
+    }
+    if let Some(ref threads) = live.threads {
+        let mut parts = Vec::new();
+        if let Some(ref name) = threads.name_contains {
+            parts.push(format!("name contains \"{}\"", name));
+        }
+        if let Some(ref ids) = threads.ids {
+            let ids: Vec<String> = ids.iter().map(|id| id.to_string()).collect();
+            parts.push(format!("ids {}", ids.join(",")));
+        }
+        let _ = writeln!(out, "  threads    {}", parts.join(" or "));
+    }
+    if let Some(ref limit) = live.rate_limit {
+        let mut parts = Vec::new();
+        if let Some(max) = limit.max_per_second {
+            parts.push(format!("{}/s per function", max));
+        }
+        if let Some(k) = limit.sample_every {
+            parts.push(format!("1 in {}", k));
+        }
+        let _ = writeln!(out, "  rate limit {}", parts.join(", "));
+    }
+    if let Some(ref recorder) = live.flight_recorder {
+        let _ = writeln!(
+            out,
+            "  recorder   last {} traced events, flushed on trigger",
+            recorder.capacity.unwrap_or(0)
+        );
+    }
+    if !live.paused_threads.is_empty() {
+        let _ = writeln!(out, "  paused     {} thread(s)", live.paused_threads.len());
+    }
+}
+
+fn section(out: &mut String, title: &str) {
+    let _ = writeln!(out);
+    let _ = writeln!(out, "{}", title);
+    let _ = writeln!(out, "{}", "─".repeat(title.chars().count()));
+}
+
+fn location(
+    function: &Option<String>,
+    file: &Option<String>,
+    line: Option<u32>,
+    address: &str,
+) -> String {
+    match (function, file, line) {
+        (_, Some(file), Some(line)) => format!("{}:{}", file, line),
+        (Some(function), _, _) => function.clone(),
+        _ => address.to_string(),
+    }
+}
+
+fn top_frame(event: &Event) -> Option<String> {
+    event
+        .backtrace
+        .as_ref()?
+        .as_array()?
+        .iter()
+        .find_map(|frame| frame.get("name").and_then(|n| n.as_str()))
+        .map(str::to_string)
+}
+
+fn truncate(text: &str) -> String {
+    if text.chars().count() <= MAX_TEXT_WIDTH {
+        return text.to_string();
+    }
+    let mut cut: String = text.chars().take(MAX_TEXT_WIDTH - 1).collect();
+    cut.push('…');
+    cut
+}
+
+fn min_max(values: &[f64]) -> (f64, f64) {
+    values
+        .iter()
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), &v| {
+            (lo.min(v), hi.max(v))
+        })
+}
+
+/// Unicode sparkline of the last `width` values, scaled to their own range.
+pub fn sparkline(values: &[f64], width: usize) -> String {
+    let values = &values[values.len().saturating_sub(width)..];
+    if values.is_empty() {
+        return String::new();
+    }
+    let (min, max) = min_max(values);
+    let range = max - min;
+    values
+        .iter()
+        .map(|&v| {
+            if range <= 0.0 {
+                return SPARK_LEVELS[SPARK_LEVELS.len() / 2];
+            }
+            let level = ((v - min) / range * (SPARK_LEVELS.len() - 1) as f64).round() as usize;
+            SPARK_LEVELS[level.min(SPARK_LEVELS.len() - 1)]
+        })
+        .collect()
+}
+
+pub(crate) fn format_ns(ns: u64) -> String {
+    let ns = ns as f64;
+    if ns >= 1e9 {
+        format!("{:.2}s", ns / 1e9)
+    } else if ns >= 1e6 {
+        format!("{:.2}ms", ns / 1e6)
+    } else if ns >= 1e3 {
+        format!("{:.1}µs", ns / 1e3)
+    } else {
+        format!("{}ns", ns)
+    }
+}
+
+fn format_seconds(secs: i64) -> String {
+    if secs >= 3600 {
+        format!("{}h{:02}m", secs / 3600, secs % 3600 / 60)
+    } else if secs >= 60 {
+        format!("{}m{:02}s", secs / 60, secs % 60)
+    } else {
+        format!("{}s", secs)
+    }
+}
+
+fn format_value(v: f64) -> String {
+    if v.fract() == 0.0 && v.abs() < 1e15 {
+        format!("{}", v as i64)
+    } else {
+        format!("{:.3}", v)
+    }
+}
+
+/// Print the report for a session in `~/.strobe/strobe.db`.
+pub async fn report(session_id: Option<&str>) -> Result<()> {
+    let Some(session_id) = session_id else {
+        return Err(crate::Error::ValidationError(
+            "Usage: strobe report <sessionId>".to_string(),
+        ));
+    };
+    let strobe_dir = dirs::home_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join(".strobe");
+    let db_path = strobe_dir.join("strobe.db");
+    if !db_path.exists() {
+        return Err(crate::Error::SessionNotFound(session_id.to_string()));
+    }
+
+    let db = Database::open(&db_path)?;
+    let mut report = SessionReport::gather(&db, session_id)?;
+    report.live = tokio::time::timeout(
+        DAEMON_TIMEOUT,
+        live_status(&crate::daemon::transport::endpoint(&strobe_dir), session_id),
+    )
+    .await
+    .ok()
+    .flatten();
+
+    print!("{}", report.render());
+    Ok(())
+}
+
+/// Ask a running daemon for the session's `debug_session` status. None when
+/// no daemon is listening or the session isn't loaded there.
+async fn live_status(socket_path: &Path, session_id: &str) -> Option<SessionStatusResponse> {
+    let stream = crate::daemon::transport::connect(socket_path).await.ok()?;
+    let (reader, mut writer) = tokio::io::split(stream);
+    let mut lines = BufReader::new(reader).lines();
+
+    let messages = [
+        serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "initialize",
+            "params": {
+                "protocolVersion": "2024-11-05",
+                "capabilities": {},
+                "clientInfo": { "name": "strobe-report", "version": env!("CARGO_PKG_VERSION") }
+            }
+        }),
+        serde_json::json!({ "jsonrpc": "2.0", "method": "notifications/initialized" }),
+        serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 2,
+            "method": "tools/call",
+            "params": {
+                "name": "debug_session",
+                "arguments": { "action": "status", "sessionId": session_id }
+            }
+        }),
+    ];
+    for message in messages {
+        writer
+            .write_all(format!("{}\n", message).as_bytes())
+            .await
+            .ok()?;
+    }
+
+    while let Some(line) = lines.next_line().await.ok()? {
+        let Ok(response) = serde_json::from_str::<serde_json::Value>(&line) else {
+            continue;
+        };
+        if response.get("id") != Some(&serde_json::json!(2)) {
+            continue;
+        }
+        let result = response.get("result")?;
+        if result.get("isError").and_then(|v| v.as_bool()) == Some(true) {
+            return None;
+        }
+        let text = result.get("content")?.get(0)?.get("text")?.as_str()?;
+        return serde_json::from_str(text).ok();
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sparkline_scales_to_range() {
+        assert_eq!(sparkline(&[0.0, 7.0, 3.5, 7.0], 10), "▁█▅█");
+        // Flat series sit mid-height
+        assert_eq!(sparkline(&[2.0, 2.0], 10), "▅▅");
+        // Only the most recent `width` values are drawn
+        assert_eq!(sparkline(&[100.0, 0.0, 1.0], 2), "▁█");
+        assert_eq!(sparkline(&[], 10), "");
+    }
+
+    #[test]
+    fn test_report_renders_db_sections() {
+        let db = Database::open_in_memory().unwrap();
+        db.create_session("s1", "/bin/app", "/home/proj", 42)
+            .unwrap();
+        db.insert_events_batch(&[
+            Event {
+                id: "enter".into(),
+                session_id: "s1".into(),
+                timestamp_ns: 1,
+                function_name: "app::render".into(),
+                watch_values: Some(serde_json::json!({ "gFrames": 1 })),
+                ..Event::default()
+            },
+            Event {
+                id: "exit".into(),
+                session_id: "s1".into(),
+                timestamp_ns: 2,
+                event_type: EventType::FunctionExit,
+                parent_event_id: Some("enter".into()),
+                function_name: "app::render".into(),
+                duration_ns: Some(2_500_000),
+                watch_values: Some(serde_json::json!({ "gFrames": 5 })),
+                ..Event::default()
+            },
+            Event {
+                id: "err".into(),
+                session_id: "s1".into(),
+                timestamp_ns: 3,
+                event_type: EventType::Stderr,
+                text: Some("warning: one\nwarning: two\n".into()),
+                ..Event::default()
+            },
+            Event {
+                id: "crash".into(),
+                session_id: "s1".into(),
+                timestamp_ns: 4,
+                event_type: EventType::Crash,
+                signal: Some("SIGSEGV".into()),
+                fault_address: Some("0x0".into()),
+                backtrace: Some(serde_json::json!([{ "address": "0x1000", "name": "app::draw" }])),
+                ..Event::default()
+            },
+        ])
+        .unwrap();
+
+        let report = SessionReport::gather(&db, "s1").unwrap();
+        assert_eq!(report.stderr, ["warning: one", "warning: two"]);
+        let text = report.render();
+        assert!(text.contains("Session s1"));
+        assert!(text.contains("2.50ms"));
+        assert!(text.contains("app::render"));
+        assert!(text.contains("SIGSEGV at 0x0"));
+        assert!(text.contains("in app::draw"));
+        assert!(text.contains("gFrames  ▁█  last 5"));
+        assert!(text.contains("session not loaded in a running daemon"));
+
+        assert!(matches!(
+            SessionReport::gather(&db, "missing"),
+            Err(crate::Error::SessionNotFound(_))
+        ));
+    }
+}
@@ -0,0 +1,76 @@
+//! `strobe setup-ui` — ask macOS for the permissions debug_ui needs.
+
+use crate::ui::permissions::Permission;
+use std::time::{Duration, Instant};
+
+/// How long to wait for the user to flip an Accessibility switch. Screen
+/// Recording isn't polled: it only applies after a restart anyway.
+const GRANT_WAIT: Duration = Duration::from_secs(60);
+
+/// Main entry point for `strobe setup-ui`.
+pub fn setup_ui() -> crate::Result<()> {
+    println!("Strobe UI Permissions Setup");
+    println!("===========================\n");
+
+    if !cfg!(target_os = "macos") {
+        println!("No permissions needed on this platform. debug_ui is ready.");
+        return Ok(());
+    }
+
+    println!("macOS grants these to the app that runs strobe (this terminal or your editor).\n");
+
+    let mut missing = Vec::new();
+    for permission in Permission::ALL {
+        if permission.is_granted(false) {
+            println!("  {:<17} granted", permission.name());
+            continue;
+        }
+        // Shows the system dialog the first time; afterwards only Settings can grant it
+        if permission.is_granted(true) {
+            println!("  {:<17} granted", permission.name());
+            continue;
+        }
+        println!("  {:<17} not granted", permission.name());
+        println!("    Enable it in {}", permission.settings_pane());
+        let _ = std::process::Command::new("open")
+            .arg(permission.settings_url())
+            .status();
+        missing.push(permission);
+    }
+
+    if missing.contains(&Permission::Accessibility) {
+        println!(
+            "\nWaiting up to {}s for Accessibility to be granted...",
+            GRANT_WAIT.as_secs()
+        );
+        let start = Instant::now();
+        while start.elapsed() < GRANT_WAIT {
+            if Permission::Accessibility.is_granted(false) {
+                println!("  Accessibility granted");
+                missing.retain(|p| *p != Permission::Accessibility);
+                break;
+            }
+            std::thread::sleep(Duration::from_secs(1));
+        }
+    }
+
+    if missing.is_empty() {
+        println!("\ndebug_ui is ready.");
+    } else {
+        println!(
+            "\nStill missing: {}. Run `strobe setup-ui` again once enabled.",
+            missing
+                .iter()
+                .map(|p| p.name())
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+    }
+    for permission in missing.iter().filter(|p| p.needs_restart()) {
+        println!(
+            "{} applies after restarting the app running strobe and the strobe daemon.",
+            permission.name()
+        );
+    }
+    Ok(())
+}
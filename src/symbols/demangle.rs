@@ -1,22 +1,532 @@
 use cpp_demangle::Symbol as CppSymbol;
-use rustc_demangle::demangle as rust_demangle;
+use std::sync::OnceLock;
 
-/// Demangle a symbol name from any supported format (Rust, C++, or plain C).
-/// Returns the demangled name, or the original if demangling fails.
+/// Source language a mangled symbol was recognised as.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SymbolLanguage {
+    Rust,
+    Cpp,
+    Swift,
+    D,
+    Go,
+}
+
+impl SymbolLanguage {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Rust => "rust",
+            Self::Cpp => "cpp",
+            Self::Swift => "swift",
+            Self::D => "d",
+            Self::Go => "go",
+        }
+    }
+}
+
+/// A demangled name together with the language it was recognised as.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DemangledSymbol {
+    pub name: String,
+    pub language: SymbolLanguage,
+}
+
+/// One language's demangling scheme.
+///
+/// Output is normalized to `::` path separators regardless of the language's
+/// own convention, so trace patterns like `audio::*` work the same for every
+/// native language in a mixed binary.
+pub trait Demangler: Send + Sync {
+    fn language(&self) -> SymbolLanguage;
+
+    /// Cheap shape check (prefix, characteristic punctuation). A `true` here
+    /// doesn't guarantee `demangle` succeeds.
+    fn detect(&self, symbol: &str) -> bool;
+
+    /// Demangle and normalize. None if the symbol doesn't parse.
+    fn demangle(&self, symbol: &str) -> Option<String>;
+}
+
+/// Ordered set of demanglers; the first that detects and parses a symbol wins.
+pub struct DemanglerRegistry {
+    demanglers: Vec<Box<dyn Demangler>>,
+}
+
+impl DemanglerRegistry {
+    /// Registry with every built-in demangler. Rust comes before C++ because
+    /// legacy Rust symbols are also valid Itanium names.
+    pub fn new() -> Self {
+        Self {
+            demanglers: vec![
+                Box::new(RustDemangler),
+                Box::new(CppDemangler),
+                Box::new(SwiftDemangler),
+                Box::new(DDemangler),
+                Box::new(GoDemangler),
+            ],
+        }
+    }
+
+    pub fn register(&mut self, demangler: Box<dyn Demangler>) {
+        self.demanglers.push(demangler);
+    }
+
+    pub fn demangle(&self, symbol: &str) -> Option<DemangledSymbol> {
+        self.demanglers
+            .iter()
+            .filter(|d| d.detect(symbol))
+            .find_map(|d| {
+                d.demangle(symbol).map(|name| DemangledSymbol {
+                    name,
+                    language: d.language(),
+                })
+            })
+    }
+
+    /// Language of a symbol by shape alone (no full parse).
+    pub fn detect_language(&self, symbol: &str) -> Option<SymbolLanguage> {
+        self.demanglers
+            .iter()
+            .find(|d| d.detect(symbol))
+            .map(|d| d.language())
+    }
+}
+
+impl Default for DemanglerRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The shared built-in registry.
+pub fn registry() -> &'static DemanglerRegistry {
+    static REGISTRY: OnceLock<DemanglerRegistry> = OnceLock::new();
+    REGISTRY.get_or_init(DemanglerRegistry::new)
+}
+
+/// Demangle a symbol name from any supported format (Rust, C++, Swift, D, Go,
+/// or plain C). Returns the demangled name, or the original if demangling fails.
 pub fn demangle_symbol(mangled: &str) -> String {
-    // Try Rust demangling first
-    let rust_demangled = rust_demangle(mangled).to_string();
-    if rust_demangled != mangled {
-        return rust_demangled;
+    registry()
+        .demangle(mangled)
+        .map(|d| d.name)
+        .unwrap_or_else(|| mangled.to_string())
+}
+
+// ---------------------------------------------------------------------------
+// Rust / C++ (library-backed)
+// ---------------------------------------------------------------------------
+
+struct RustDemangler;
+
+impl Demangler for RustDemangler {
+    fn language(&self) -> SymbolLanguage {
+        SymbolLanguage::Rust
+    }
+
+    fn detect(&self, symbol: &str) -> bool {
+        rustc_demangle::try_demangle(symbol).is_ok()
+    }
+
+    fn demangle(&self, symbol: &str) -> Option<String> {
+        // Keep the hash suffix: `midi::*` deliberately doesn't match through it.
+        let demangled = rustc_demangle::try_demangle(symbol).ok()?.to_string();
+        (demangled != symbol).then_some(demangled)
+    }
+}
+
+struct CppDemangler;
+
+impl Demangler for CppDemangler {
+    fn language(&self) -> SymbolLanguage {
+        SymbolLanguage::Cpp
+    }
+
+    fn detect(&self, symbol: &str) -> bool {
+        symbol.starts_with("_Z") || symbol.starts_with("__Z")
     }
 
-    // Try C++ (Itanium ABI) demangling
-    if let Ok(symbol) = CppSymbol::new(mangled) {
-        if let Ok(demangled) = symbol.demangle(&cpp_demangle::DemangleOptions::default()) {
-            return demangled;
+    fn demangle(&self, symbol: &str) -> Option<String> {
+        CppSymbol::new(symbol)
+            .ok()?
+            .demangle(&cpp_demangle::DemangleOptions::default())
+            .ok()
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Swift
+// ---------------------------------------------------------------------------
+
+/// Swift 5+ mangling (`$s`, `_$s`, and the Swift 4.x `$S` variants).
+///
+/// Decodes the entity path — module, nominal types and the member name — plus
+/// common initializer/accessor suffixes. Symbols using word substitutions or
+/// standard-library shortcuts are left to the original name.
+struct SwiftDemangler;
+
+const SWIFT_PREFIXES: &[&str] = &["_$s", "$s", "_$S", "$S", "_$e", "$e"];
+
+impl Demangler for SwiftDemangler {
+    fn language(&self) -> SymbolLanguage {
+        SymbolLanguage::Swift
+    }
+
+    fn detect(&self, symbol: &str) -> bool {
+        SWIFT_PREFIXES.iter().any(|p| symbol.starts_with(p))
+    }
+
+    fn demangle(&self, symbol: &str) -> Option<String> {
+        let body = SWIFT_PREFIXES.iter().find_map(|p| symbol.strip_prefix(p))?;
+
+        let mut rest = body;
+        let mut path: Vec<&str> = Vec::new();
+        loop {
+            if rest.starts_with('0') {
+                return None; // word substitution
+            }
+            let Some((ident, after)) = take_length_prefixed(rest) else {
+                break;
+            };
+            path.push(ident);
+            rest = after;
+            // Nominal type kinds continue the context path
+            if let Some(b'V' | b'C' | b'O' | b'P') = rest.as_bytes().first() {
+                rest = &rest[1..];
+            }
+        }
+        if path.is_empty() {
+            return None;
         }
+
+        let mut name = path.join("::");
+        let suffix = if symbol.ends_with("fC") || symbol.ends_with("fc") {
+            Some("init")
+        } else if symbol.ends_with("fD") || symbol.ends_with("fd") {
+            Some("deinit")
+        } else if symbol.ends_with("vg") {
+            Some("getter")
+        } else if symbol.ends_with("vs") {
+            Some("setter")
+        } else {
+            None
+        };
+        if let Some(s) = suffix {
+            name.push_str("::");
+            name.push_str(s);
+        }
+        Some(name)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// D
+// ---------------------------------------------------------------------------
+
+/// D ABI mangling: `_D` followed by length-prefixed qualified name parts and
+/// a type signature (ignored). `_Dmain` is the program entry point.
+struct DDemangler;
+
+impl Demangler for DDemangler {
+    fn language(&self) -> SymbolLanguage {
+        SymbolLanguage::D
     }
 
-    // Return original if no demangling worked (plain C or unknown)
-    mangled.to_string()
+    fn detect(&self, symbol: &str) -> bool {
+        symbol == "_Dmain"
+            || symbol
+                .strip_prefix("_D")
+                .is_some_and(|r| r.starts_with(|c: char| c.is_ascii_digit() && c != '0'))
+    }
+
+    fn demangle(&self, symbol: &str) -> Option<String> {
+        if symbol == "_Dmain" {
+            return Some("main".to_string());
+        }
+        let mut rest = symbol.strip_prefix("_D")?;
+        let mut parts: Vec<&str> = Vec::new();
+        while let Some((ident, after)) = take_length_prefixed(rest) {
+            // Template instances (`__T...`) and other internals end the path
+            if ident.starts_with("__") {
+                break;
+            }
+            parts.push(ident);
+            rest = after;
+        }
+        (!parts.is_empty()).then(|| parts.join("::"))
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Go
+// ---------------------------------------------------------------------------
+
+/// Go linker symbols: `import/path.pkg.(*Type).Method`, `pkg.Func.func1`.
+///
+/// Go doesn't mangle so much as qualify: the package path keeps its slashes,
+/// and everything after it is split on `.` (outside generic brackets) into
+/// `::` segments, with pointer receivers unwrapped. `%XX` escapes in the path
+/// are decoded.
+struct GoDemangler;
+
+/// GCC/LLVM clone suffixes on C symbols (`foo.cold`, `bar.isra.0`) — not Go.
+const C_CLONE_SUFFIXES: &[&str] = &[
+    ".cold",
+    ".part.",
+    ".isra.",
+    ".constprop.",
+    ".lto_priv.",
+    ".llvm.",
+    ".localalias",
+];
+
+impl Demangler for GoDemangler {
+    fn language(&self) -> SymbolLanguage {
+        SymbolLanguage::Go
+    }
+
+    fn detect(&self, symbol: &str) -> bool {
+        if !symbol.contains('.')
+            || symbol.starts_with('.')
+            || symbol.starts_with('_')
+            || symbol.starts_with("go:")
+            || symbol.starts_with("type:")
+            || C_CLONE_SUFFIXES.iter().any(|s| symbol.contains(s))
+        {
+            return false;
+        }
+        symbol.starts_with("main.")
+            || symbol.starts_with("runtime.")
+            || symbol.contains(".(*")
+            || symbol.contains(").")
+            || go_package_split(symbol).is_some_and(|(path, _)| path.contains('/'))
+    }
+
+    fn demangle(&self, symbol: &str) -> Option<String> {
+        let (path, rest) = go_package_split(symbol)?;
+        let path = go_unescape(path);
+
+        let mut segments = vec![path];
+        let mut depth = 0usize;
+        let mut current = String::new();
+        for c in rest.chars() {
+            match c {
+                '[' => {
+                    depth += 1;
+                    current.push(c);
+                }
+                ']' => {
+                    depth = depth.saturating_sub(1);
+                    current.push(c);
+                }
+                '.' if depth == 0 => segments.push(std::mem::take(&mut current)),
+                _ => current.push(c),
+            }
+        }
+        segments.push(current);
+
+        let segments: Vec<String> = segments
+            .into_iter()
+            .map(|s| {
+                s.trim_start_matches("(*")
+                    .trim_start_matches('(')
+                    .trim_end_matches(')')
+                    .to_string()
+            })
+            .filter(|s| !s.is_empty())
+            .collect();
+        Some(segments.join("::"))
+    }
+}
+
+/// Demangle a name known to come from Go code (a Go DWARF unit), where the
+/// shape check would miss plain `pkg.Func` names outside `main`.
+pub fn demangle_go(symbol: &str) -> String {
+    GoDemangler
+        .demangle(symbol)
+        .unwrap_or_else(|| symbol.to_string())
+}
+
+/// Rewrite a trace pattern written the Go way (`main.(*Server).handle**`,
+/// `main.Map[...]`) into the `::` form Go functions are indexed under. None
+/// when the pattern doesn't look like a Go name.
+pub fn go_pattern(pattern: &str) -> Option<String> {
+    if pattern.starts_with('@') || pattern.contains("::") || !pattern.contains('.') {
+        return None;
+    }
+    // `[...]` is how Go prints any instantiation of a generic function
+    GoDemangler.demangle(&pattern.replace("[...]", "[**]"))
+}
+
+/// Rewrite a Swift-spelled pattern (`MyApp.AppDelegate.application*`) into
+/// the `::` form Swift functions are indexed under. None when the pattern
+/// doesn't look like a dotted Swift name.
+pub fn swift_pattern(pattern: &str) -> Option<String> {
+    if pattern.starts_with('@')
+        || pattern.contains("::")
+        || !pattern.contains('.')
+        || is_objc_pattern(pattern)
+    {
+        return None;
+    }
+    Some(pattern.replace('.', "::"))
+}
+
+/// `-[Class selector:]`, `+[Class selector]` or `*[...]` for both kinds. These
+/// are looked up in the Objective-C runtime rather than in DWARF.
+pub fn is_objc_pattern(pattern: &str) -> bool {
+    (pattern.starts_with("-[") || pattern.starts_with("+[") || pattern.starts_with("*["))
+        && pattern.ends_with(']')
+}
+
+/// Split `github.com/a/b.Func` into (`github.com/a/b`, `Func`). The package is
+/// everything up to the first `.` after the last `/`.
+fn go_package_split(symbol: &str) -> Option<(&str, &str)> {
+    let last_slash = symbol.rfind('/').map(|i| i + 1).unwrap_or(0);
+    let dot = symbol[last_slash..].find('.')? + last_slash;
+    let (pkg, rest) = (&symbol[..dot], &symbol[dot + 1..]);
+    (!pkg.is_empty() && !rest.is_empty()).then_some((pkg, rest))
+}
+
+fn go_unescape(path: &str) -> String {
+    let mut out = String::with_capacity(path.len());
+    let mut chars = path.char_indices();
+    while let Some((i, c)) = chars.next() {
+        if c == '%' {
+            if let Some(v) = path
+                .get(i + 1..i + 3)
+                .and_then(|h| u8::from_str_radix(h, 16).ok())
+            {
+                out.push(v as char);
+                chars.nth(1);
+                continue;
+            }
+        }
+        out.push(c);
+    }
+    out
+}
+
+/// Parse `<decimal length><identifier>` from the front of `s`.
+fn take_length_prefixed(s: &str) -> Option<(&str, &str)> {
+    let digits = s.bytes().take_while(|b| b.is_ascii_digit()).count();
+    if digits == 0 {
+        return None;
+    }
+    let len: usize = s[..digits].parse().ok()?;
+    let body = &s[digits..];
+    if len == 0 || body.len() < len || !body.is_char_boundary(len) {
+        return None;
+    }
+    Some((&body[..len], &body[len..]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lang(symbol: &str) -> Option<SymbolLanguage> {
+        registry().demangle(symbol).map(|d| d.language)
+    }
+
+    #[test]
+    fn test_swift_entity_path() {
+        assert_eq!(demangle_symbol("$s4main3FooV3baryyF"), "main::Foo::bar");
+        assert_eq!(
+            demangle_symbol("_$s5Audio6EngineC5startyyF"),
+            "Audio::Engine::start"
+        );
+        assert_eq!(
+            demangle_symbol("$s4main3FooV5countSivg"),
+            "main::Foo::count::getter"
+        );
+        assert_eq!(lang("$s4main3FooV3baryyF"), Some(SymbolLanguage::Swift));
+    }
+
+    #[test]
+    fn test_swift_and_objc_patterns() {
+        assert_eq!(
+            swift_pattern("MyApp.AppDelegate.application*").as_deref(),
+            Some("MyApp::AppDelegate::application*")
+        );
+        assert_eq!(swift_pattern("audio::*"), None);
+        assert_eq!(swift_pattern("@file:main.swift"), None);
+        assert_eq!(swift_pattern("-[MyApp.AppDelegate window]"), None);
+
+        assert!(is_objc_pattern(
+            "-[AppDelegate application:didFinishLaunching*]"
+        ));
+        assert!(is_objc_pattern("*[NSView set*]"));
+        assert!(!is_objc_pattern("-[AppDelegate"));
+        assert!(!is_objc_pattern("ns::[weird]"));
+    }
+
+    #[test]
+    fn test_swift_word_substitution_left_alone() {
+        assert_eq!(demangle_symbol("$s04main3FooV"), "$s04main3FooV");
+    }
+
+    #[test]
+    fn test_d_qualified_name() {
+        assert_eq!(
+            demangle_symbol("_D3std5stdio7writelnFAyaZv"),
+            "std::stdio::writeln"
+        );
+        assert_eq!(demangle_symbol("_Dmain"), "main");
+        assert_eq!(lang("_D3foo3barFZv"), Some(SymbolLanguage::D));
+    }
+
+    #[test]
+    fn test_go_symbols() {
+        assert_eq!(demangle_symbol("main.main"), "main::main");
+        assert_eq!(
+            demangle_symbol("main.(*Server).handle"),
+            "main::Server::handle"
+        );
+        assert_eq!(
+            demangle_symbol("github.com/acme/audio%2ev2/mix.(*Bus).Process.func1"),
+            "github.com/acme/audio.v2/mix::Bus::Process::func1"
+        );
+        assert_eq!(
+            demangle_symbol("main.Map[go.shape.int,go.shape.string]"),
+            "main::Map[go.shape.int,go.shape.string]"
+        );
+        assert_eq!(lang("runtime.mallocgc"), Some(SymbolLanguage::Go));
+    }
+
+    #[test]
+    fn test_go_unit_names_and_patterns() {
+        // Outside `main` the shape check alone leaves these untouched
+        assert_eq!(demangle_symbol("mixer.Render"), "mixer.Render");
+        assert_eq!(demangle_go("mixer.Render"), "mixer::Render");
+
+        assert_eq!(
+            go_pattern("main.(*Server).handle**").as_deref(),
+            Some("main::Server::handle**")
+        );
+        assert_eq!(go_pattern("main.*").as_deref(), Some("main::*"));
+        assert_eq!(
+            go_pattern("main.Map[...]").as_deref(),
+            Some("main::Map[**]")
+        );
+        assert_eq!(go_pattern("audio::*"), None);
+        assert_eq!(go_pattern("@file:main.go"), None);
+        assert_eq!(go_pattern("process"), None);
+    }
+
+    #[test]
+    fn test_c_clone_suffixes_are_not_go() {
+        assert_eq!(demangle_symbol("process.cold"), "process.cold");
+        assert_eq!(demangle_symbol("mix.isra.0"), "mix.isra.0");
+        assert_eq!(demangle_symbol("main"), "main");
+    }
+
+    #[test]
+    fn test_rust_and_cpp_detection() {
+        assert_eq!(
+            lang("_ZN4test7example17h1234567890abcdefE"),
+            Some(SymbolLanguage::Rust)
+        );
+        assert_eq!(lang("_ZN4test7exampleEv"), Some(SymbolLanguage::Cpp));
+        assert_eq!(registry().detect_language("plain_c"), None);
+    }
 }
@@ -0,0 +1,306 @@
+use super::js_resolver::strip_line_comment;
+use super::resolver::*;
+use regex::Regex;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+/// Parsed method table: `package.Class.method` → (absolute_file_path, 1-indexed line).
+/// Nested classes use their binary name (`Outer$Inner`) so the agent can pass
+/// the class straight to `Java.use`.
+pub type MethodTable = HashMap<String, (PathBuf, u32)>;
+
+const SKIP_DIRS: &[&str] = &[
+    "build",
+    "target",
+    "out",
+    ".git",
+    ".gradle",
+    ".idea",
+    ".mvn",
+    "node_modules",
+];
+
+// Words that can precede `name(` at member level without being a return type
+const NOT_A_TYPE: &[&str] = &[
+    "return", "new", "throw", "else", "case", "yield", "assert", "package", "import",
+];
+
+/// Line-by-line extraction of Java method declarations. Only direct members
+/// of a class body count, so calls inside method bodies are never mistaken
+/// for declarations. Constructors and bodiless (abstract/interface) methods
+/// are skipped; overloads share one entry, since the agent hooks every
+/// overload of a name.
+pub fn extract_methods_from_source(source: &str, file: &Path) -> MethodTable {
+    let re_package = Regex::new(r"^\s*package\s+([\w.]+)\s*;").unwrap();
+    let re_type = Regex::new(
+        r"^\s*(?:@\w+(?:\([^)]*\))?\s+)*(?:(?:public|protected|private|static|final|abstract|sealed|non-sealed|strictfp)\s+)*(?:class|interface|enum|record|@interface)\s+(\w+)",
+    )
+    .unwrap();
+    let re_method = Regex::new(
+        r"^\s*(?:@\w+(?:\([^)]*\))?\s+)*(?:(?:public|protected|private|static|final|abstract|synchronized|native|default|strictfp)\s+)*(?:<[^>]+>\s+)?([\w.$<>\[\]?, ]+?)\s+(\w+)\s*\(",
+    )
+    .unwrap();
+
+    let mut result = MethodTable::new();
+    let mut package: Option<String> = None;
+    // Stack of (binary class name, brace depth outside the class body, body opened)
+    let mut class_stack: Vec<(String, i32, bool)> = Vec::new();
+    let mut brace_depth: i32 = 0;
+    let mut in_block_comment = false;
+
+    for (i, line) in source.lines().enumerate() {
+        let line_num = (i + 1) as u32;
+
+        let mut text = line;
+        if in_block_comment {
+            match text.find("*/") {
+                Some(end) => {
+                    in_block_comment = false;
+                    text = &text[end + 2..];
+                }
+                None => continue,
+            }
+        }
+        let mut stripped = strip_line_comment(text);
+        if let Some(start) = stripped.find("/*") {
+            if !stripped[start..].contains("*/") {
+                in_block_comment = true;
+            }
+            stripped = &stripped[..start];
+        }
+
+        let opens = stripped.chars().filter(|&c| c == '{').count() as i32;
+        let closes = stripped.chars().filter(|&c| c == '}').count() as i32;
+
+        if let Some(cap) = re_package.captures(stripped) {
+            package = Some(cap[1].to_string());
+        } else if let Some(cap) = re_type.captures(stripped) {
+            let name = match class_stack.last() {
+                Some((outer, _, _)) => format!("{}${}", outer, &cap[1]),
+                None => cap[1].to_string(),
+            };
+            class_stack.push((name, brace_depth, false));
+        } else if let Some((class, depth, true)) = class_stack.last() {
+            if brace_depth == depth + 1 && !stripped.trim_end().ends_with(';') {
+                if let Some(cap) = re_method.captures(stripped) {
+                    let return_type = cap[1].trim();
+                    let method = &cap[2];
+                    let simple_class = class.rsplit('$').next().unwrap_or(class);
+                    let is_constructor = method == simple_class;
+                    if !is_constructor && !NOT_A_TYPE.contains(&return_type) {
+                        let qualified = match &package {
+                            Some(pkg) => format!("{}.{}.{}", pkg, class, method),
+                            None => format!("{}.{}", class, method),
+                        };
+                        result
+                            .entry(qualified)
+                            .or_insert_with(|| (file.to_path_buf(), line_num));
+                    }
+                }
+            }
+        }
+
+        brace_depth += opens - closes;
+        if brace_depth < 0 {
+            brace_depth = 0;
+        }
+        // The body's `{` may sit on the line after the declaration
+        if let Some((_, _, opened)) = class_stack.last_mut() {
+            *opened |= opens > 0;
+        }
+        class_stack.retain(|(_, depth, opened)| !opened || brace_depth > *depth);
+    }
+
+    result
+}
+
+pub struct JavaResolver {
+    methods: MethodTable,
+}
+
+impl JavaResolver {
+    pub fn from_project(root: &Path) -> crate::Result<Self> {
+        let mut methods = MethodTable::new();
+
+        for entry in WalkDir::new(root)
+            .follow_links(false)
+            .into_iter()
+            .filter_entry(|e| {
+                if e.file_type().is_dir() {
+                    let name = e.file_name().to_str().unwrap_or("");
+                    return !SKIP_DIRS.contains(&name);
+                }
+                true
+            })
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_type().is_file())
+        {
+            let path = entry.path();
+            if path.extension().and_then(|e| e.to_str()) == Some("java") {
+                if let Ok(src) = std::fs::read_to_string(path) {
+                    methods.extend(extract_methods_from_source(&src, path));
+                }
+            }
+        }
+
+        Ok(Self { methods })
+    }
+
+    pub fn method_count(&self) -> usize {
+        self.methods.len()
+    }
+
+    fn target(name: &str, file: &Path, line: u32) -> ResolvedTarget {
+        ResolvedTarget::SourceLocation {
+            file: file.to_string_lossy().to_string(),
+            line,
+            name: name.to_string(),
+        }
+    }
+}
+
+impl SymbolResolver for JavaResolver {
+    fn resolve_pattern(&self, pattern: &str, _root: &Path) -> crate::Result<Vec<ResolvedTarget>> {
+        if let Some(file_pattern) = pattern.strip_prefix("@file:") {
+            return Ok(self
+                .methods
+                .iter()
+                .filter(|(_, (file, _))| file.to_string_lossy().contains(file_pattern))
+                .map(|(name, (file, line))| Self::target(name, file, *line))
+                .collect());
+        }
+
+        // Fully qualified names are dot-separated, like JS; `::` is read as `.`
+        let matcher = crate::dwarf::PatternMatcher::for_language(pattern, self.language());
+        Ok(self
+            .methods
+            .iter()
+            .filter(|(name, _)| matcher.matches(name))
+            .map(|(name, (file, line))| Self::target(name, file, *line))
+            .collect())
+    }
+
+    fn resolve_line(&self, file: &str, line: u32) -> crate::Result<Option<ResolvedTarget>> {
+        Ok(self
+            .methods
+            .iter()
+            .find(|(_, (fpath, fline))| fpath.to_string_lossy().ends_with(file) && *fline == line)
+            .map(|(name, (fpath, fline))| Self::target(name, fpath, *fline)))
+    }
+
+    fn resolve_variable(&self, name: &str) -> crate::Result<VariableResolution> {
+        // Static fields, read by the agent through the Java bridge
+        Ok(VariableResolution::RuntimeExpression {
+            expr: name.to_string(),
+        })
+    }
+
+    fn image_base(&self) -> u64 {
+        0
+    }
+    fn language(&self) -> Language {
+        Language::Java
+    }
+    fn supports_runtime_resolution(&self) -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_methods_and_packages() {
+        let src = r#"
+package com.example;
+
+import java.util.List;
+
+/* public void commentedOut() { */
+public class Service {
+    private final List<String> names = List.of();
+
+    public Service(int size) {
+        init(size);
+    }
+
+    @Override
+    public String toString() { return "Service"; }
+
+    public static <T> List<T> handleAll(List<T> items) {
+        return items;
+    }
+
+    void handle(String request) {
+        if (request.isEmpty()) {
+            return;
+        }
+        process(request);
+    }
+
+    void handle(int code) {}
+
+    abstract void pending();
+
+    static class Worker {
+        int[] run(int n) { return new int[n]; }
+    }
+}
+"#;
+        let methods = extract_methods_from_source(src, Path::new("/tmp/Service.java"));
+        let mut names: Vec<&str> = methods.keys().map(|s| s.as_str()).collect();
+        names.sort();
+        assert_eq!(
+            names,
+            vec![
+                "com.example.Service$Worker.run",
+                "com.example.Service.handle",
+                "com.example.Service.handleAll",
+                "com.example.Service.toString",
+            ]
+        );
+        // First overload's line wins
+        assert_eq!(methods["com.example.Service.handle"].1, 21);
+    }
+
+    #[test]
+    fn test_resolve_pattern() {
+        let dir = tempfile::tempdir().unwrap();
+        let src_dir = dir.path().join("src/main/java/com/example");
+        std::fs::create_dir_all(&src_dir).unwrap();
+        std::fs::write(
+            src_dir.join("Service.java"),
+            "package com.example;\npublic class Service {\n    void handleGet() {}\n    void handlePost() {}\n    void close() {}\n}\n",
+        )
+        .unwrap();
+        std::fs::create_dir_all(dir.path().join("target")).unwrap();
+        std::fs::write(
+            dir.path().join("target/Generated.java"),
+            "public class Generated {\n    void handleGenerated() {}\n}\n",
+        )
+        .unwrap();
+
+        let resolver = JavaResolver::from_project(dir.path()).unwrap();
+        assert_eq!(resolver.method_count(), 3);
+
+        let handlers = resolver
+            .resolve_pattern("com.example.Service.handle*", dir.path())
+            .unwrap();
+        assert_eq!(handlers.len(), 2);
+
+        // * stays within one name segment, ** crosses packages
+        assert!(resolver
+            .resolve_pattern("*.close", dir.path())
+            .unwrap()
+            .is_empty());
+        assert_eq!(
+            resolver
+                .resolve_pattern("**.close", dir.path())
+                .unwrap()
+                .len(),
+            1
+        );
+    }
+}
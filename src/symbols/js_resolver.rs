@@ -23,7 +23,7 @@ const SKIP_DIRS: &[&str] = &[
 const JS_EXTENSIONS: &[&str] = &["js", "ts", "jsx", "tsx", "mjs", "cjs", "mts", "cts"];
 
 /// Strip `//` line comments, but not `//` inside string literals.
-fn strip_line_comment(line: &str) -> &str {
+pub(super) fn strip_line_comment(line: &str) -> &str {
     let mut in_single_quote = false;
     let mut in_double_quote = false;
     let mut in_backtick = false;
@@ -271,7 +271,7 @@ impl SymbolResolver for JsResolver {
         }
 
         // Use project-standard PatternMatcher with '.' as separator for JS
-        let matcher = crate::dwarf::PatternMatcher::new_with_separator(pattern, '.');
+        let matcher = crate::dwarf::PatternMatcher::for_language(pattern, self.language());
         Ok(self
             .functions
             .iter()
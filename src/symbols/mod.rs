@@ -1,12 +1,21 @@
 mod demangle;
 pub mod dwarf_resolver;
+pub mod java_resolver;
 pub mod js_resolver;
+mod pattern_lint;
+pub mod pdb_resolver;
 pub mod python_resolver;
 pub mod resolver;
 
-pub use demangle::demangle_symbol;
+pub use demangle::{
+    demangle_go, demangle_symbol, go_pattern, is_objc_pattern, registry as demangler_registry,
+    swift_pattern, DemangledSymbol, Demangler, DemanglerRegistry, SymbolLanguage,
+};
 pub use dwarf_resolver::DwarfResolver;
+pub use java_resolver::JavaResolver;
 pub use js_resolver::JsResolver;
+pub use pattern_lint::{lint_namespace, lint_pattern, LintSeverity, PatternLint};
+pub use pdb_resolver::PdbResolver;
 pub use python_resolver::PythonResolver;
 pub use resolver::{Language, ResolvedTarget, SymbolResolver, VariableResolution};
 
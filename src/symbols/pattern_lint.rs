@@ -0,0 +1,400 @@
+//! Lint for trace patterns: catches patterns that can never match or that
+//! match far more than intended, and suggests a rewrite.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+
+use crate::dwarf::DwarfParser;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LintSeverity {
+    /// The pattern can't do what was meant; debug_trace rejects it in `add`
+    Error,
+    /// Probably not what was meant; hooked anyway
+    Warning,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PatternLint {
+    pub pattern: String,
+    pub severity: LintSeverity,
+    pub message: String,
+    /// Pattern to use instead, when there is an obvious one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub suggestion: Option<String>,
+}
+
+impl PatternLint {
+    fn new(pattern: &str, severity: LintSeverity, message: String) -> Self {
+        Self {
+            pattern: pattern.to_string(),
+            severity,
+            message,
+            suggestion: None,
+        }
+    }
+
+    fn suggest(mut self, suggestion: impl Into<String>) -> Self {
+        self.suggestion = Some(suggestion.into());
+        self
+    }
+
+    /// The message with the suggestion appended.
+    pub fn reason(&self) -> String {
+        match self.suggestion {
+            Some(ref s) => format!("{} Try '{}'.", self.message, s),
+            None => self.message.clone(),
+        }
+    }
+
+    /// One-line form for response warnings.
+    pub fn describe(&self) -> String {
+        format!("{}: {}", self.pattern, self.reason())
+    }
+}
+
+/// Checks that need no symbols: syntax the matcher can't satisfy and
+/// patterns that hook far more than anyone means to.
+pub fn lint_pattern(pattern: &str) -> Vec<PatternLint> {
+    let mut lints = Vec::new();
+
+    if pattern.trim() != pattern {
+        lints.push(
+            PatternLint::new(
+                pattern,
+                LintSeverity::Warning,
+                "Leading or trailing whitespace is matched literally.".to_string(),
+            )
+            .suggest(pattern.trim()),
+        );
+    }
+
+    match pattern.trim() {
+        "" => {
+            lints.push(PatternLint::new(
+                pattern,
+                LintSeverity::Error,
+                "Empty pattern.".to_string(),
+            ));
+            return lints;
+        }
+        "*" | "**" => {
+            lints.push(
+                PatternLint::new(
+                    pattern,
+                    LintSeverity::Error,
+                    "Matches every function in the process, including libc and the runtime; the event budget is gone in seconds.".to_string(),
+                )
+                .suggest("@usercode"),
+            );
+            return lints;
+        }
+        _ => {}
+    }
+
+    if let Some(scope) = pattern.strip_prefix("@file:") {
+        lints.extend(lint_file_scope(pattern, scope));
+        return lints;
+    }
+    if pattern.starts_with(crate::mcp::SYSCALL_PATTERN_PREFIX) {
+        if crate::mcp::SyscallGroup::from_pattern(pattern).is_none() {
+            lints.push(
+                PatternLint::new(
+                    pattern,
+                    LintSeverity::Error,
+                    "Unknown syscall group; use @syscalls:file or @syscalls:net.".to_string(),
+                )
+                .suggest(crate::mcp::SyscallGroup::File.pattern()),
+            );
+        }
+        return lints;
+    }
+    if pattern.starts_with('@') {
+        if pattern != "@usercode"
+            && pattern != crate::mcp::NETWORK_PATTERN
+            && pattern != crate::mcp::EXCEPTIONS_PATTERN
+            && pattern != crate::mcp::SIGNALS_PATTERN
+            && pattern != crate::mcp::LOCKS_PATTERN
+        {
+            lints.push(
+                PatternLint::new(
+                    pattern,
+                    LintSeverity::Error,
+                    "Unknown @ pattern; only @usercode, @file:<name>, @syscalls:<group>, @network, @exceptions, @signals and @locks exist."
+                        .to_string(),
+                )
+                .suggest("@usercode"),
+            );
+        }
+        return lints;
+    }
+
+    // `.(` is a Go method receiver (`main.(*Server).handle`), not a parameter list
+    if let Some(paren) = pattern.find('(').filter(|&i| !pattern[..i].ends_with('.')) {
+        // Parameter lists are stripped from names before matching
+        lints.push(
+            PatternLint::new(
+                pattern,
+                LintSeverity::Error,
+                "Parameter lists never match; overloads are matched by name.".to_string(),
+            )
+            .suggest(pattern[..paren].trim_end()),
+        );
+    }
+
+    if let Some(lint) = lint_template_args(pattern) {
+        lints.push(lint);
+    }
+
+    lints
+}
+
+fn lint_file_scope(pattern: &str, scope: &str) -> Vec<PatternLint> {
+    let scope = scope.trim();
+    if scope.is_empty() || scope == "." || scope == "/" {
+        return vec![PatternLint::new(
+            pattern,
+            LintSeverity::Error,
+            "@file: matches source paths by substring, so this matches every file.".to_string(),
+        )
+        .suggest("@file:parser.cpp")];
+    }
+
+    let last = scope
+        .trim_end_matches('/')
+        .rsplit('/')
+        .next()
+        .unwrap_or(scope);
+    if scope.ends_with('/') || !last.contains('.') {
+        let file = if scope.ends_with('/') {
+            format!("{}<file>.cpp", scope)
+        } else {
+            format!("{}.cpp", scope)
+        };
+        return vec![PatternLint::new(
+            pattern,
+            LintSeverity::Warning,
+            format!(
+                "@file: matches source paths by substring, so '{}' hooks every file under a directory named like it.",
+                scope
+            ),
+        )
+        .suggest(format!("@file:{}", file))];
+    }
+    Vec::new()
+}
+
+/// `*` never crosses `::`, and demangled template arguments are spelled out
+/// in full (`std::__cxx11::basic_string<char, ...>`), so anything but a
+/// literal copy of the demangled name or `**` inside `<...>` misses.
+fn lint_template_args(pattern: &str) -> Option<PatternLint> {
+    let open = pattern.find('<')?;
+    let Some(close) = pattern.rfind('>').filter(|&c| c > open) else {
+        return Some(PatternLint::new(
+            pattern,
+            LintSeverity::Error,
+            "Unbalanced '<' never matches a demangled name.".to_string(),
+        ));
+    };
+    let args = &pattern[open + 1..close];
+    if args == "**" {
+        return None;
+    }
+    if args.contains("::") || args.contains('*') || args.contains('<') || args.contains(',') {
+        let rewrite = format!("{}<**>{}", &pattern[..open], &pattern[close + 1..]);
+        return Some(
+            PatternLint::new(
+                pattern,
+                LintSeverity::Warning,
+                "Template arguments must match the demangled name exactly and '*' stops at '::'; match them with '**'.".to_string(),
+            )
+            .suggest(rewrite),
+        );
+    }
+    None
+}
+
+/// Flags a `ns::...` pattern whose namespace has no function in the DWARF
+/// index, suggesting a close spelling or a deeper match.
+pub fn lint_namespace(pattern: &str, dwarf: &DwarfParser) -> Option<PatternLint> {
+    if pattern.starts_with('@') {
+        return None;
+    }
+    let native = dwarf.native_pattern(pattern);
+    let original = pattern;
+    let pattern = native.as_deref().unwrap_or(pattern);
+    let literal = &pattern[..pattern.find('*').unwrap_or(pattern.len())];
+    let namespace = &literal[..literal.rfind("::")?];
+    if namespace.is_empty() {
+        return None;
+    }
+
+    let prefix = format!("{}::", namespace);
+    let infix = format!("::{}::", namespace);
+    let depth = namespace.split("::").count();
+    let mut nested = false;
+    let mut known: BTreeSet<String> = BTreeSet::new();
+    for f in &dwarf.functions {
+        let name = f.name.split('(').next().unwrap_or(&f.name);
+        if name.starts_with(&prefix) {
+            return None;
+        }
+        nested |= name.contains(&infix);
+        let segments: Vec<&str> = name.split("::").collect();
+        if segments.len() > depth {
+            known.insert(segments[..depth].join("::"));
+        }
+    }
+
+    let lint = PatternLint::new(
+        original,
+        LintSeverity::Warning,
+        format!(
+            "No function in namespace '{}' in the debug symbols.",
+            namespace
+        ),
+    );
+    if nested {
+        return Some(lint.suggest(format!("**::{}", pattern)));
+    }
+    let closest = known
+        .iter()
+        .map(|ns| (edit_distance(ns, namespace), ns))
+        .filter(|(d, _)| *d <= (namespace.len() / 3).max(2))
+        .min_by_key(|(d, _)| *d);
+    match closest {
+        Some((_, ns)) => Some(lint.suggest(format!("{}{}", ns, &pattern[namespace.len()..]))),
+        None => Some(lint),
+    }
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut diag = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let above = row[j + 1];
+            row[j + 1] = if ca == *cb {
+                diag
+            } else {
+                1 + diag.min(above).min(row[j])
+            };
+            diag = above;
+        }
+    }
+    row[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn suggestion(pattern: &str) -> Option<String> {
+        lint_pattern(pattern).into_iter().find_map(|l| l.suggestion)
+    }
+
+    #[test]
+    fn test_lint_pattern_static_rules() {
+        assert!(lint_pattern("audio::process").is_empty());
+        assert!(lint_pattern("@usercode").is_empty());
+        assert!(lint_pattern("@file:parser.cpp").is_empty());
+        assert!(lint_pattern("@syscalls:file").is_empty());
+        assert!(lint_pattern("@syscalls:net").is_empty());
+        assert!(lint_pattern("@network").is_empty());
+        assert!(lint_pattern("@exceptions").is_empty());
+        assert!(lint_pattern("@signals").is_empty());
+        assert!(lint_pattern("@locks").is_empty());
+        assert_eq!(
+            lint_pattern("@syscalls:disk")[0].severity,
+            LintSeverity::Error
+        );
+        assert_eq!(
+            suggestion("@syscalls:disk").as_deref(),
+            Some("@syscalls:file")
+        );
+        assert!(lint_pattern("std::vector<**>::push_back").is_empty());
+
+        let star = lint_pattern("*");
+        assert_eq!(star[0].severity, LintSeverity::Error);
+        assert_eq!(star[0].suggestion.as_deref(), Some("@usercode"));
+
+        assert_eq!(
+            suggestion("audio::mix(float*, int)").as_deref(),
+            Some("audio::mix")
+        );
+        assert_eq!(suggestion("@file:src").as_deref(), Some("@file:src.cpp"));
+        assert_eq!(lint_pattern("@file:src")[0].severity, LintSeverity::Warning);
+        assert_eq!(lint_pattern("@file:")[0].severity, LintSeverity::Error);
+        assert_eq!(
+            suggestion("std::vector<std::string>::push_back").as_deref(),
+            Some("std::vector<**>::push_back")
+        );
+        assert_eq!(suggestion(" audio::* ").as_deref(), Some("audio::*"));
+        assert_eq!(lint_pattern("@user")[0].severity, LintSeverity::Error);
+        // Go receivers aren't parameter lists
+        assert!(lint_pattern("main.(*Server).handle").is_empty());
+    }
+
+    fn parser(names: &[&str]) -> DwarfParser {
+        use crate::dwarf::FunctionInfo;
+        use std::collections::HashMap;
+        use std::sync::Mutex;
+
+        DwarfParser {
+            functions: names
+                .iter()
+                .map(|n| FunctionInfo {
+                    name: n.to_string(),
+                    name_raw: None,
+                    low_pc: 0,
+                    high_pc: 0,
+                    source_file: None,
+                    line_number: None,
+                })
+                .collect(),
+            functions_by_name: HashMap::new(),
+            functions_by_addr: vec![],
+            variables: vec![],
+            variables_by_name: HashMap::new(),
+            struct_members: Mutex::new(HashMap::new()),
+            lazy_struct_info: HashMap::new(),
+            type_layouts: Mutex::new(HashMap::new()),
+            function_params: Mutex::new(HashMap::new()),
+            line_table: Mutex::new(None),
+            image_base: 0,
+            binary_path: None,
+            go_functions: Default::default(),
+            goid_offset: None,
+            swift: false,
+        }
+    }
+
+    #[test]
+    fn test_lint_namespace() {
+        let dwarf = parser(&["audio::mix(float*)", "app::engine::run()", "main"]);
+        assert!(lint_namespace("audio::*", &dwarf).is_none());
+        assert!(lint_namespace("mix", &dwarf).is_none());
+        assert!(lint_namespace("@file:audio", &dwarf).is_none());
+
+        let typo = lint_namespace("audoi::*", &dwarf).unwrap();
+        assert_eq!(typo.suggestion.as_deref(), Some("audio::*"));
+
+        let nested = lint_namespace("engine::run", &dwarf).unwrap();
+        assert_eq!(nested.suggestion.as_deref(), Some("**::engine::run"));
+
+        let unknown = lint_namespace("network::*", &dwarf).unwrap();
+        assert_eq!(unknown.severity, LintSeverity::Warning);
+        assert!(unknown.suggestion.is_none());
+    }
+
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(edit_distance("audio", "audio"), 0);
+        assert_eq!(edit_distance("audoi", "audio"), 2);
+        assert_eq!(edit_distance("", "abc"), 3);
+    }
+}
@@ -0,0 +1,317 @@
+use super::resolver::*;
+use crate::dwarf::{FunctionInfo, LineEntry, PatternMatcher};
+use crate::symbols::demangle_symbol;
+use object::Object;
+use pdb::FallibleIterator;
+use std::path::{Path, PathBuf};
+
+/// Native symbols of a Windows (PE) binary from its PDB, for MSVC and
+/// `*-pc-windows-msvc` Rust builds, which carry no DWARF. Addresses are
+/// static (preferred image base + RVA) like DWARF addresses, so the agent
+/// applies the load slide the same way.
+pub struct PdbResolver {
+    functions: Vec<FunctionInfo>,
+    /// Sorted by address
+    lines: Vec<LineEntry>,
+    image_base: u64,
+}
+
+impl PdbResolver {
+    /// Parse the PDB of the PE binary at `binary_path`. NoDebugSymbols when the
+    /// binary isn't a PE file or its PDB can't be found.
+    pub fn load(binary_path: &Path) -> crate::Result<Self> {
+        let data = std::fs::read(binary_path)?;
+        let object = object::File::parse(&*data).map_err(|e| {
+            crate::Error::Internal(format!("Failed to parse {}: {}", binary_path.display(), e))
+        })?;
+        if object.format() != object::BinaryFormat::Pe {
+            return Err(crate::Error::NoDebugSymbols);
+        }
+        let recorded = object
+            .pdb_info()
+            .ok()
+            .flatten()
+            .map(|cv| String::from_utf8_lossy(cv.path()).into_owned());
+        let pdb_path = pdb_candidates(binary_path, recorded.as_deref())
+            .into_iter()
+            .find(|p| p.is_file())
+            .ok_or(crate::Error::NoDebugSymbols)?;
+        Self::parse(&pdb_path, object.relative_address_base())
+    }
+
+    fn parse(pdb_path: &Path, image_base: u64) -> crate::Result<Self> {
+        let pdb_error = |e: pdb::Error| {
+            crate::Error::Internal(format!("Failed to read {}: {}", pdb_path.display(), e))
+        };
+        let mut pdb = pdb::PDB::open(std::fs::File::open(pdb_path)?).map_err(pdb_error)?;
+        let address_map = pdb.address_map().map_err(pdb_error)?;
+        let strings = pdb.string_table().ok();
+        let dbi = pdb.debug_information().map_err(pdb_error)?;
+        let mut modules = dbi.modules().map_err(pdb_error)?;
+
+        let mut functions = Vec::new();
+        let mut lines = Vec::new();
+        while let Some(module) = modules.next().map_err(pdb_error)? {
+            // Import stubs and objects built without debug info have no module stream
+            let Ok(Some(info)) = pdb.module_info(&module) else {
+                continue;
+            };
+            let program = info.line_program().ok();
+            let file_name = |index| {
+                let file = program.as_ref()?.get_file_info(index).ok()?;
+                let name = file.name.to_string_lossy(strings.as_ref()?).ok()?;
+                Some(name.into_owned())
+            };
+
+            if let Some(ref program) = program {
+                let mut rows = program.lines();
+                while let Ok(Some(row)) = rows.next() {
+                    let (Some(rva), Some(file)) =
+                        (row.offset.to_rva(&address_map), file_name(row.file_index))
+                    else {
+                        continue;
+                    };
+                    lines.push(LineEntry {
+                        address: image_base + u64::from(rva.0),
+                        file,
+                        line: row.line_start,
+                        column: row.column_start.map(u32::from).unwrap_or(0),
+                        is_statement: true,
+                    });
+                }
+            }
+
+            let mut symbols = info.symbols().map_err(pdb_error)?;
+            while let Some(symbol) = symbols.next().map_err(pdb_error)? {
+                let Ok(pdb::SymbolData::Procedure(proc)) = symbol.parse() else {
+                    continue;
+                };
+                let Some(rva) = proc.offset.to_rva(&address_map) else {
+                    continue;
+                };
+                let first_line = program
+                    .as_ref()
+                    .and_then(|p| p.lines_for_symbol(proc.offset).next().ok().flatten());
+                let raw = proc.name.to_string().into_owned();
+                let name = demangle_symbol(&raw);
+                let low_pc = image_base + u64::from(rva.0);
+                functions.push(FunctionInfo {
+                    name_raw: (name != raw).then_some(raw),
+                    name,
+                    low_pc,
+                    high_pc: low_pc + u64::from(proc.len),
+                    source_file: first_line.as_ref().and_then(|l| file_name(l.file_index)),
+                    line_number: first_line.map(|l| l.line_start),
+                });
+            }
+        }
+        lines.sort_by_key(|l| l.address);
+
+        Ok(Self {
+            functions,
+            lines,
+            image_base,
+        })
+    }
+
+    pub fn function_count(&self) -> usize {
+        self.functions.len()
+    }
+}
+
+/// Where the PDB of `binary` may be: the path its CodeView record names (as
+/// built), that file name next to the binary, then `<binary>.pdb`.
+fn pdb_candidates(binary: &Path, recorded: Option<&str>) -> Vec<PathBuf> {
+    let mut candidates = Vec::new();
+    let dir = binary.parent().unwrap_or(Path::new("."));
+    if let Some(recorded) = recorded.filter(|r| !r.is_empty()) {
+        candidates.push(PathBuf::from(recorded));
+        // Recorded paths use the build machine's separators
+        if let Some(file_name) = recorded.rsplit(['\\', '/']).next() {
+            candidates.push(dir.join(file_name));
+        }
+    }
+    candidates.push(binary.with_extension("pdb"));
+    candidates.dedup();
+    candidates
+}
+
+impl SymbolResolver for PdbResolver {
+    fn resolve_pattern(
+        &self,
+        pattern: &str,
+        _project_root: &Path,
+    ) -> crate::Result<Vec<ResolvedTarget>> {
+        let functions: Vec<&FunctionInfo> = match pattern.strip_prefix("@file:") {
+            Some(file_pattern) => self
+                .functions
+                .iter()
+                .filter(|f| {
+                    f.source_file
+                        .as_ref()
+                        .is_some_and(|sf| sf.contains(file_pattern))
+                })
+                .collect(),
+            None => {
+                let matcher = PatternMatcher::new(pattern);
+                self.functions
+                    .iter()
+                    .filter(|f| matcher.matches(&f.name))
+                    .collect()
+            }
+        };
+
+        Ok(functions
+            .iter()
+            .map(|f| ResolvedTarget::Address {
+                address: f.low_pc,
+                name: f.name.clone(),
+                name_raw: f.name_raw.clone(),
+                file: f.source_file.clone(),
+                line: f.line_number,
+            })
+            .collect())
+    }
+
+    fn resolve_line(&self, file: &str, line: u32) -> crate::Result<Option<ResolvedTarget>> {
+        // PDB paths are absolute with backslashes; match on path components
+        let sep_file = format!("\\{}", file.replace('/', "\\"));
+        let mut matches: Vec<&LineEntry> = self
+            .lines
+            .iter()
+            .filter(|e| e.file == file || e.file.replace('/', "\\").ends_with(&sep_file))
+            .collect();
+        // Stable: the lowest address of the closest line comes first
+        matches.sort_by_key(|e| e.line);
+
+        Ok(matches
+            .into_iter()
+            .find(|e| e.line >= line)
+            .map(|e| ResolvedTarget::Address {
+                address: e.address,
+                name: format!("{}:{}", file, e.line),
+                name_raw: None,
+                file: Some(file.to_string()),
+                line: Some(e.line),
+            }))
+    }
+
+    fn resolve_variable(&self, name: &str) -> crate::Result<VariableResolution> {
+        Err(crate::Error::Internal(format!(
+            "Reading '{}' is not supported from PDB symbols yet",
+            name
+        )))
+    }
+
+    fn image_base(&self) -> u64 {
+        self.image_base
+    }
+
+    fn language(&self) -> Language {
+        Language::Native
+    }
+
+    fn supports_runtime_resolution(&self) -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn function(name: &str, low_pc: u64, file: &str, line: u32) -> FunctionInfo {
+        FunctionInfo {
+            name: name.to_string(),
+            name_raw: None,
+            low_pc,
+            high_pc: low_pc + 0x40,
+            source_file: Some(file.to_string()),
+            line_number: Some(line),
+        }
+    }
+
+    fn line(address: u64, file: &str, line: u32) -> LineEntry {
+        LineEntry {
+            address,
+            file: file.to_string(),
+            line,
+            column: 0,
+            is_statement: true,
+        }
+    }
+
+    fn resolver() -> PdbResolver {
+        PdbResolver {
+            functions: vec![
+                function(
+                    "audio::Mixer::render",
+                    0x1_4000_1000,
+                    "C:\\proj\\src\\mixer.rs",
+                    10,
+                ),
+                function("audio::fft", 0x1_4000_1100, "C:\\proj\\src\\dsp.rs", 3),
+                function("main", 0x1_4000_2000, "C:\\proj\\src\\main.rs", 1),
+            ],
+            lines: vec![
+                line(0x1_4000_1000, "C:\\proj\\src\\mixer.rs", 10),
+                line(0x1_4000_1010, "C:\\proj\\src\\mixer.rs", 12),
+                line(0x1_4000_1030, "C:\\proj\\src\\mixer.rs", 12),
+                line(0x1_4000_2000, "C:\\proj\\src\\main.rs", 1),
+            ],
+            image_base: 0x1_4000_0000,
+        }
+    }
+
+    #[test]
+    fn test_resolve_pattern() {
+        let resolver = resolver();
+        let root = Path::new("C:\\proj");
+        let names = |pattern: &str| -> Vec<String> {
+            resolver
+                .resolve_pattern(pattern, root)
+                .unwrap()
+                .iter()
+                .map(|t| t.name().to_string())
+                .collect()
+        };
+        assert_eq!(
+            names("audio::**"),
+            vec!["audio::Mixer::render", "audio::fft"]
+        );
+        assert_eq!(names("audio::*"), vec!["audio::fft"]);
+        assert_eq!(names("@file:dsp.rs"), vec!["audio::fft"]);
+        assert!(names("net::*").is_empty());
+        assert_eq!(resolver.image_base(), 0x1_4000_0000);
+    }
+
+    #[test]
+    fn test_resolve_line() {
+        let resolver = resolver();
+        match resolver.resolve_line("src/mixer.rs", 11).unwrap() {
+            Some(ResolvedTarget::Address { address, line, .. }) => {
+                assert_eq!(address, 0x1_4000_1010);
+                assert_eq!(line, Some(12));
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+        assert!(resolver.resolve_line("mixer.rs", 13).unwrap().is_none());
+        assert!(resolver.resolve_line("xmixer.rs", 10).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_pdb_candidates() {
+        let binary = Path::new("/mnt/build/app.exe");
+        assert_eq!(
+            pdb_candidates(binary, Some("C:\\ci\\target\\release\\deps\\app.pdb")),
+            vec![
+                PathBuf::from("C:\\ci\\target\\release\\deps\\app.pdb"),
+                PathBuf::from("/mnt/build/app.pdb"),
+            ]
+        );
+        assert_eq!(
+            pdb_candidates(binary, None),
+            vec![PathBuf::from("/mnt/build/app.pdb")]
+        );
+    }
+}
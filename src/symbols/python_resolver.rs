@@ -279,8 +279,8 @@ impl SymbolResolver for PythonResolver {
                 .collect());
         }
 
-        // Use PatternMatcher with `.` as separator for Python
-        let matcher = crate::dwarf::PatternMatcher::new_with_separator(pattern, '.');
+        // Use PatternMatcher with `.` as separator for Python (`::` is read as `.`)
+        let matcher = crate::dwarf::PatternMatcher::for_language(pattern, self.language());
         Ok(self
             .functions
             .iter()
@@ -319,7 +319,7 @@ impl SymbolResolver for PythonResolver {
                 .collect());
         }
 
-        let matcher = crate::dwarf::PatternMatcher::new_with_separator(pattern, '.');
+        let matcher = crate::dwarf::PatternMatcher::for_language(pattern, self.language());
         Ok(self
             .functions
             .iter()
@@ -445,6 +445,12 @@ def outer():
             .resolve_pattern("modules.audio.*", Path::new("."))
             .unwrap();
         assert_eq!(targets.len(), 2);
+
+        // Native-style scopes resolve the same functions
+        let targets = resolver
+            .resolve_pattern("modules::audio::*", Path::new("."))
+            .unwrap();
+        assert_eq!(targets.len(), 2);
     }
 
     #[test]
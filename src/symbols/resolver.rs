@@ -7,6 +7,7 @@ pub enum Language {
     Native,
     Python,
     JavaScript,
+    Java,
 }
 
 impl std::fmt::Display for Language {
@@ -15,6 +16,29 @@ impl std::fmt::Display for Language {
             Language::Native => write!(f, "native"),
             Language::Python => write!(f, "python"),
             Language::JavaScript => write!(f, "javascript"),
+            Language::Java => write!(f, "java"),
+        }
+    }
+}
+
+impl Language {
+    /// Separator between scopes in this language's function names.
+    pub fn scope_separator(&self) -> char {
+        match self {
+            Language::Native => ':',
+            Language::Python | Language::JavaScript | Language::Java => '.',
+        }
+    }
+
+    /// Spell a trace pattern the way this language names functions:
+    /// `mypkg::handlers::*` becomes `mypkg.handlers.*` for Python, JS and
+    /// Java. Native patterns are kept as written, since Go and Objective-C
+    /// names contain dots; `@` patterns are never rewritten.
+    pub fn normalize_pattern(&self, pattern: &str) -> String {
+        if self.scope_separator() == '.' && !pattern.starts_with('@') {
+            pattern.replace("::", ".")
+        } else {
+            pattern.to_string()
         }
     }
 }
@@ -63,7 +87,7 @@ pub enum VariableResolution {
 }
 
 /// Trait for language-specific symbol resolution.
-/// Implementations: DwarfResolver (native), PythonResolver, JSResolver
+/// Implementations: DwarfResolver and PdbResolver (native), PythonResolver, JSResolver, JavaResolver
 pub trait SymbolResolver: Send + Sync {
     /// Resolve a glob pattern to concrete function targets.
     /// For tracing hooks: returns the function definition line (matches co_firstlineno).
@@ -2,6 +2,19 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::path::Path;
 
+/// Lockfiles of the JavaScript package managers, for `TestAdapter::lockfiles`.
+pub const NODE_LOCKFILES: &[&str] = &[
+    "package-lock.json",
+    "yarn.lock",
+    "pnpm-lock.yaml",
+    "bun.lockb",
+    "bun.lock",
+];
+
+/// Lockfiles (or pinned requirements) of the Python package managers.
+pub const PYTHON_LOCKFILES: &[&str] =
+    &["uv.lock", "poetry.lock", "Pipfile.lock", "requirements.txt"];
+
 #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum TestLevel {
@@ -118,6 +131,15 @@ pub struct TestDetail {
     pub message: Option<String>,
 }
 
+/// A test enumerated by an adapter's list mode, without running it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListedTest {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ProjectInfo {
@@ -187,6 +209,38 @@ pub trait TestAdapter: Send + Sync {
         600_000 // 10 minutes
     }
 
+    /// Commands printing the toolchain versions a run depends on, recorded
+    /// in its provenance under each command's program. Default: none.
+    fn version_commands(&self) -> &'static [&'static [&'static str]] {
+        &[]
+    }
+
+    /// Dependency lockfiles in the project root, hashed into a run's
+    /// provenance. Default: none.
+    fn lockfiles(&self) -> &'static [&'static str] {
+        &[]
+    }
+
+    /// Rewrite a suite command so `priority` entries (test names or test-file
+    /// paths, most urgent first) run ahead of the rest of the suite.
+    /// Returns false when the framework can't control execution order.
+    fn apply_priority_order(&self, _cmd: &mut TestCommand, _priority: &[String]) -> bool {
+        false
+    }
+
+    /// Build a command that enumerates tests without executing them.
+    /// `command` is the user-provided binary for binary-based adapters.
+    /// Default: None — the inventory falls back to tests with recorded history.
+    fn list_command(&self, _project_root: &Path, _command: Option<&str>) -> Option<TestCommand> {
+        None
+    }
+
+    /// Parse `list_command` stdout. Names must match what `parse_output`
+    /// reports so baselines line up with the listing.
+    fn parse_test_list(&self, _stdout: &str) -> Vec<ListedTest> {
+        Vec::new()
+    }
+
     /// Detect and return a pretest setup command from package.json scripts.
     /// Checks for `pretest:<level>` first, then `pretest`. The returned command
     /// is run synchronously (outside Frida) before the test process spawns.
@@ -57,6 +57,14 @@ impl TestAdapter for BunAdapter {
         "bun"
     }
 
+    fn version_commands(&self) -> &'static [&'static [&'static str]] {
+        &[&["bun", "--version"]]
+    }
+
+    fn lockfiles(&self) -> &'static [&'static str] {
+        &["bun.lockb", "bun.lock"]
+    }
+
     fn suite_command(
         &self,
         project_root: &Path,
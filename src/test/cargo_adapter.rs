@@ -18,6 +18,14 @@ impl TestAdapter for CargoTestAdapter {
         "cargo"
     }
 
+    fn version_commands(&self) -> &'static [&'static [&'static str]] {
+        &[&["rustc", "--version"], &["cargo", "--version"]]
+    }
+
+    fn lockfiles(&self) -> &'static [&'static str] {
+        &["Cargo.lock"]
+    }
+
     fn suite_command(
         &self,
         _project_root: &Path,
@@ -343,6 +351,35 @@ impl TestAdapter for CargoTestAdapter {
             None => 900_000, // 15 min: compilation + multiple binaries
         }
     }
+
+    fn list_command(&self, _project_root: &Path, _command: Option<&str>) -> Option<TestCommand> {
+        Some(TestCommand {
+            program: "cargo".to_string(),
+            args: vec![
+                "test".to_string(),
+                "--tests".to_string(),
+                "--".to_string(),
+                "--list".to_string(),
+                "--format".to_string(),
+                "terse".to_string(),
+            ],
+            env: HashMap::new(),
+            cwd: None,
+            remove_env: vec![],
+        })
+    }
+
+    fn parse_test_list(&self, stdout: &str) -> Vec<ListedTest> {
+        // One `path::to::test: test` line per test; benchmarks are skipped
+        stdout
+            .lines()
+            .filter_map(|line| line.trim().strip_suffix(": test"))
+            .map(|name| ListedTest {
+                name: name.to_string(),
+                file: None,
+            })
+            .collect()
+    }
 }
 
 /// Parse crash messages from cargo stderr.
@@ -725,4 +762,12 @@ mod tests {
             .message
             .contains("killed before completion"));
     }
+
+    #[test]
+    fn test_parse_test_list() {
+        let stdout = "parser::tests::test_parse: test\nbench_decode: benchmark\nit_works: test\n";
+        let tests = CargoTestAdapter.parse_test_list(stdout);
+        let names: Vec<&str> = tests.iter().map(|t| t.name.as_str()).collect();
+        assert_eq!(names, vec!["parser::tests::test_parse", "it_works"]);
+    }
 }
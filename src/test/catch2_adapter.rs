@@ -23,6 +23,10 @@ impl TestAdapter for Catch2Adapter {
         "catch2"
     }
 
+    fn version_commands(&self) -> &'static [&'static [&'static str]] {
+        &[&["c++", "--version"]]
+    }
+
     fn suite_command(
         &self,
         _project_root: &Path,
@@ -212,6 +216,86 @@ impl TestAdapter for Catch2Adapter {
             remove_env: vec![],
         })
     }
+
+    fn list_command(&self, _project_root: &Path, command: Option<&str>) -> Option<TestCommand> {
+        Some(TestCommand {
+            program: command?.to_string(),
+            args: vec![
+                "--list-tests".to_string(),
+                "--reporter".to_string(),
+                "xml".to_string(),
+            ],
+            env: HashMap::new(),
+            cwd: None,
+            remove_env: vec![],
+        })
+    }
+
+    fn parse_test_list(&self, stdout: &str) -> Vec<ListedTest> {
+        parse_catch2_list(stdout)
+    }
+}
+
+/// Parse `--list-tests --reporter xml` (Catch2 v3): one `<TestCase>` per test
+/// with `<Name>` and `<SourceInfo><File>`. Catch2 v2 ignores the reporter when
+/// listing, so fall back to its console format (indented names, tags below).
+fn parse_catch2_list(stdout: &str) -> Vec<ListedTest> {
+    use quick_xml::events::Event;
+    use quick_xml::Reader;
+
+    let mut reader = Reader::from_str(stdout);
+    reader.config_mut().trim_text(true);
+
+    let mut tests = Vec::new();
+    let mut current: Option<ListedTest> = None;
+    let mut field: Option<Vec<u8>> = None;
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(ref e)) => match e.local_name().as_ref() {
+                b"TestCase" => {
+                    current = Some(ListedTest {
+                        name: String::new(),
+                        file: None,
+                    })
+                }
+                name @ (b"Name" | b"File") => field = Some(name.to_vec()),
+                _ => {}
+            },
+            Ok(Event::Text(ref t)) => {
+                if let (Some(test), Some(tag)) = (current.as_mut(), field.as_deref()) {
+                    let text = t.unescape().unwrap_or_default().to_string();
+                    match tag {
+                        b"Name" => test.name = text,
+                        _ => test.file = Some(text),
+                    }
+                }
+            }
+            Ok(Event::End(ref e)) => {
+                field = None;
+                if e.local_name().as_ref() == b"TestCase" {
+                    if let Some(test) = current.take().filter(|t| !t.name.is_empty()) {
+                        tests.push(test);
+                    }
+                }
+            }
+            Ok(Event::Eof) | Err(_) => break,
+            _ => {}
+        }
+    }
+    if !tests.is_empty() {
+        return tests;
+    }
+
+    stdout
+        .lines()
+        .filter(|line| line.starts_with("  ") && !line.starts_with("      "))
+        .map(str::trim)
+        .filter(|name| !name.is_empty() && !name.starts_with('['))
+        .map(|name| ListedTest {
+            name: name.to_string(),
+            file: None,
+        })
+        .collect()
 }
 
 /// Parse Catch2 XML reporter output into TestResult.
@@ -487,4 +571,33 @@ mod tests {
         assert_eq!(f.line, Some(18));
         assert!(f.message.contains("nullptr == 0x42"));
     }
+
+    #[test]
+    fn test_parse_test_list() {
+        let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<MatchingTests>
+  <TestCase>
+    <Name>Parser handles &lt;empty&gt; input</Name>
+    <ClassName/>
+    <Tags>[parser]</Tags>
+    <SourceInfo>
+      <File>/src/test_parser.cpp</File>
+      <Line>12</Line>
+    </SourceInfo>
+  </TestCase>
+</MatchingTests>"#;
+        let tests = Catch2Adapter.parse_test_list(xml);
+        assert_eq!(tests.len(), 1);
+        assert_eq!(tests[0].name, "Parser handles <empty> input");
+        assert_eq!(tests[0].file.as_deref(), Some("/src/test_parser.cpp"));
+
+        // Catch2 v2 lists in console format regardless of reporter
+        let console = "All available test cases:\n  Parser basics\n      [parser]\n  Lexer\n      [lexer]\n2 test cases\n";
+        let names: Vec<String> = Catch2Adapter
+            .parse_test_list(console)
+            .into_iter()
+            .map(|t| t.name)
+            .collect();
+        assert_eq!(names, vec!["Parser basics", "Lexer"]);
+    }
 }
@@ -0,0 +1,403 @@
+//! Lock wait-for graph of a stuck process, for the stuck detector's deadlock
+//! diagnosis. On Linux a thread sleeping on a lock sits in a `futex` wait on
+//! the lock word; glibc's pthread_mutex_t keeps the owner's tid next to that
+//! word, so each wait gives an edge "waiter -> owner". The owner is only read
+//! for waits whose stack holds a return address into glibc's mutex lock
+//! functions; other futexes (Rust std's futex mutex, condition variables,
+//! idle thread pools) record no owner and are left out of the diagnosis.
+
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write as _;
+#[cfg(target_os = "linux")]
+use std::ops::Range;
+
+/// Waits listed when no cycle is found.
+const MAX_LISTED_WAITS: usize = 4;
+
+/// A thread sleeping on a lock.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LockWait {
+    pub tid: u32,
+    pub thread_name: String,
+    /// Address of the lock word
+    pub lock: u64,
+    /// Thread holding the lock, when the lock records it
+    pub owner: Option<u32>,
+}
+
+/// A concrete diagnosis of `pid`'s lock waits, or None when no thread waits
+/// on a lock with a known holder (or they can't be read).
+pub fn diagnose(pid: u32) -> Option<String> {
+    describe(&lock_waits(pid))
+}
+
+/// "thread A holds X and waits for Y; thread B holds Y and waits for X" for
+/// a wait-for cycle, otherwise the waits with a known holder.
+pub fn describe(waits: &[LockWait]) -> Option<String> {
+    let by_tid: HashMap<u32, &LockWait> = waits.iter().map(|w| (w.tid, w)).collect();
+    let label = |tid: u32| match by_tid.get(&tid) {
+        Some(w) if !w.thread_name.is_empty() => format!("thread {} ({})", tid, w.thread_name),
+        _ => format!("thread {}", tid),
+    };
+
+    if let Some(cycle) = find_cycle(waits, &by_tid) {
+        let mut text = String::from("Deadlock: ");
+        for (i, wait) in cycle.iter().enumerate() {
+            // Each thread holds the lock the previous one in the cycle waits for
+            let held = cycle[(i + cycle.len() - 1) % cycle.len()].lock;
+            if i > 0 {
+                text.push_str("; ");
+            }
+            let _ = write!(
+                text,
+                "{} holds mutex {:#x} and waits for mutex {:#x}",
+                label(wait.tid),
+                held,
+                wait.lock
+            );
+        }
+        return Some(text);
+    }
+
+    let held: Vec<(&LockWait, u32)> = waits
+        .iter()
+        .filter_map(|w| w.owner.map(|owner| (w, owner)))
+        .collect();
+    if held.is_empty() {
+        return None;
+    }
+    let mut text = format!(
+        "Lock wait: {} thread(s) waiting on held mutexes",
+        held.len()
+    );
+    for (wait, owner) in held.iter().take(MAX_LISTED_WAITS) {
+        let _ = write!(
+            text,
+            "; {} waits for mutex {:#x} held by {}",
+            label(wait.tid),
+            wait.lock,
+            label(*owner)
+        );
+        if !by_tid.contains_key(owner) {
+            text.push_str(", which isn't waiting on a lock");
+        }
+    }
+    if held.len() > MAX_LISTED_WAITS {
+        let _ = write!(text, "; {} more", held.len() - MAX_LISTED_WAITS);
+    }
+    Some(text)
+}
+
+/// The waits of the first cycle in the waiter -> owner graph, in wait order.
+fn find_cycle<'a>(
+    waits: &'a [LockWait],
+    by_tid: &HashMap<u32, &'a LockWait>,
+) -> Option<Vec<&'a LockWait>> {
+    let mut checked = HashSet::new();
+    for start in waits {
+        let mut path: Vec<&LockWait> = Vec::new();
+        let mut on_path = HashSet::new();
+        let mut current = Some(start);
+        while let Some(wait) = current {
+            if on_path.contains(&wait.tid) {
+                let begin = path.iter().position(|w| w.tid == wait.tid)?;
+                return Some(path.split_off(begin));
+            }
+            if !checked.insert(wait.tid) {
+                break;
+            }
+            on_path.insert(wait.tid);
+            path.push(wait);
+            current = wait.owner.and_then(|owner| by_tid.get(&owner).copied());
+        }
+    }
+    None
+}
+
+/// Threads of `pid` sleeping in a futex wait, with the holder where the lock
+/// records it.
+#[cfg(target_os = "linux")]
+pub fn lock_waits(pid: u32) -> Vec<LockWait> {
+    use std::os::unix::fs::FileExt as _;
+
+    let task_dir = format!("/proc/{}/task", pid);
+    let Ok(entries) = std::fs::read_dir(&task_dir) else {
+        return vec![];
+    };
+    let tids: HashSet<u32> = entries
+        .flatten()
+        .filter_map(|e| e.file_name().to_string_lossy().parse().ok())
+        .collect();
+    // Reading another process's memory needs ptrace access to it
+    let Ok(mem) = std::fs::File::open(format!("/proc/{}/mem", pid)) else {
+        return vec![];
+    };
+    let read_u32 = |addr: u64| {
+        let mut buf = [0u8; 4];
+        mem.read_exact_at(&mut buf, addr).ok()?;
+        Some(u32::from_ne_bytes(buf))
+    };
+    // Resolved on the first non-PI wait
+    let mut lock_ranges: Option<Vec<Range<u64>>> = None;
+    let mut in_mutex_lock = |sp: u64| {
+        let ranges = lock_ranges.get_or_insert_with(|| mutex_lock_ranges(pid));
+        if ranges.is_empty() {
+            return false;
+        }
+        // The stack may end inside the window, so take what can be read
+        let mut buf = vec![0u8; STACK_SCAN_BYTES];
+        let Ok(read) = mem.read_at(&mut buf, sp) else {
+            return false;
+        };
+        buf[..read]
+            .chunks_exact(8)
+            .map(|word| u64::from_ne_bytes(word.try_into().unwrap()))
+            .any(|addr| ranges.iter().any(|r| r.contains(&addr)))
+    };
+
+    let mut waits = Vec::new();
+    for &tid in &tids {
+        let Ok(line) = std::fs::read_to_string(format!("{}/{}/syscall", task_dir, tid)) else {
+            continue;
+        };
+        let Some(wait) = parse_futex_wait(&line) else {
+            continue;
+        };
+        let lock = wait.lock;
+        let owner = if wait.pi {
+            // PI futexes hold the owner's tid in the lock word itself
+            read_u32(lock).map(|word| word & FUTEX_TID_MASK)
+        } else if in_mutex_lock(wait.sp) {
+            // glibc's __owner follows __lock and __count
+            read_u32(lock + 8)
+        } else {
+            None
+        };
+        waits.push(LockWait {
+            tid,
+            thread_name: std::fs::read_to_string(format!("{}/{}/comm", task_dir, tid))
+                .map(|s| s.trim().to_string())
+                .unwrap_or_default(),
+            lock,
+            owner: owner.filter(|o| *o != tid && tids.contains(o)),
+        });
+    }
+    waits.sort_by_key(|w| w.tid);
+    waits
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn lock_waits(pid: u32) -> Vec<LockWait> {
+    let _ = pid;
+    vec![]
+}
+
+#[cfg(target_os = "linux")]
+const FUTEX_TID_MASK: u32 = 0x3fff_ffff;
+
+/// glibc functions that sleep on a pthread_mutex_t's lock word.
+#[cfg(target_os = "linux")]
+const MUTEX_LOCK_SYMBOLS: &[&str] = &[
+    "pthread_mutex_lock",
+    "__pthread_mutex_lock",
+    "___pthread_mutex_lock",
+    "pthread_mutex_timedlock",
+    "__pthread_mutex_timedlock",
+    "___pthread_mutex_timedlock",
+    "pthread_mutex_clocklock",
+    "__pthread_mutex_clocklock",
+    "__pthread_mutex_lock_full",
+];
+
+/// Bytes above a waiting thread's stack pointer searched for a return
+/// address into [`MUTEX_LOCK_SYMBOLS`].
+#[cfg(target_os = "linux")]
+const STACK_SCAN_BYTES: usize = 2048;
+
+/// A thread blocked in a futex wait.
+#[cfg(target_os = "linux")]
+#[derive(Debug, PartialEq)]
+struct FutexWait {
+    lock: u64,
+    /// Priority-inheritance lock (owner tid in the lock word)
+    pi: bool,
+    sp: u64,
+}
+
+/// Where glibc's mutex lock functions sit in `pid`, or nothing when libc
+/// can't be found or read.
+#[cfg(target_os = "linux")]
+fn mutex_lock_ranges(pid: u32) -> Vec<Range<u64>> {
+    use object::{Object as _, ObjectSymbol as _};
+
+    let Ok(maps) = std::fs::read_to_string(format!("/proc/{}/maps", pid)) else {
+        return vec![];
+    };
+    let Some((base, path)) = maps.lines().find_map(parse_libc_mapping) else {
+        return vec![];
+    };
+    // Through the process's root, in case it runs in another mount namespace
+    let Ok(data) = std::fs::read(format!("/proc/{}/root{}", pid, path)) else {
+        return vec![];
+    };
+    let Ok(file) = object::File::parse(&*data) else {
+        return vec![];
+    };
+    file.dynamic_symbols()
+        .chain(file.symbols())
+        .filter(|s| s.size() > 0 && matches!(s.name(), Ok(n) if MUTEX_LOCK_SYMBOLS.contains(&n)))
+        .map(|s| base + s.address()..base + s.address() + s.size())
+        .collect()
+}
+
+/// Load base and path of libc from its `/proc/<pid>/maps` line at file
+/// offset 0 ("<start>-<end> <perms> <offset> <dev> <inode> <path>").
+#[cfg(target_os = "linux")]
+fn parse_libc_mapping(line: &str) -> Option<(u64, &str)> {
+    let mut fields = line.split_whitespace();
+    let range = fields.next()?;
+    let offset = fields.nth(1)?;
+    let path = fields.nth(2)?;
+    let file = path.rsplit('/').next()?;
+    if !(file.starts_with("libc.so") || file.starts_with("libc-")) {
+        return None;
+    }
+    if u64::from_str_radix(offset, 16).ok()? != 0 {
+        return None;
+    }
+    let start = u64::from_str_radix(range.split('-').next()?, 16).ok()?;
+    Some((start, path))
+}
+
+/// The futex a thread is blocked on, from its `/proc/<pid>/task/<tid>/syscall`
+/// line ("<nr> <arg0> ... <arg5> <sp> <pc>").
+#[cfg(target_os = "linux")]
+fn parse_futex_wait(line: &str) -> Option<FutexWait> {
+    const FUTEX_CMD_MASK: u64 = 0x7f;
+    const FUTEX_WAIT: u64 = 0;
+    const FUTEX_LOCK_PI: u64 = 6;
+    const FUTEX_WAIT_BITSET: u64 = 9;
+    const FUTEX_LOCK_PI2: u64 = 13;
+
+    let mut fields = line.split_whitespace();
+    let nr: i64 = fields.next()?.parse().ok()?;
+    if nr != libc::SYS_futex as i64 {
+        return None;
+    }
+    let hex = |s: &str| u64::from_str_radix(s.trim_start_matches("0x"), 16).ok();
+    let lock = hex(fields.next()?)?;
+    let pi = match hex(fields.next()?)? & FUTEX_CMD_MASK {
+        FUTEX_WAIT | FUTEX_WAIT_BITSET => false,
+        FUTEX_LOCK_PI | FUTEX_LOCK_PI2 => true,
+        _ => return None,
+    };
+    let sp = hex(fields.nth(4)?)?;
+    Some(FutexWait { lock, pi, sp })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn wait(tid: u32, lock: u64, owner: Option<u32>) -> LockWait {
+        LockWait {
+            tid,
+            thread_name: format!("worker-{}", tid),
+            lock,
+            owner,
+        }
+    }
+
+    #[test]
+    fn test_describe_two_thread_cycle() {
+        let waits = vec![
+            wait(7, 0x1000, None),
+            wait(10, 0xa000, Some(11)),
+            wait(11, 0xb000, Some(10)),
+        ];
+        assert_eq!(
+            describe(&waits).unwrap(),
+            "Deadlock: thread 10 (worker-10) holds mutex 0xb000 and waits for mutex 0xa000; \
+             thread 11 (worker-11) holds mutex 0xa000 and waits for mutex 0xb000"
+        );
+    }
+
+    #[test]
+    fn test_describe_waits_without_cycle() {
+        let waits = vec![
+            wait(10, 0xa000, Some(12)),
+            wait(11, 0xb000, None),
+            wait(13, 0xc000, Some(10)),
+        ];
+        assert_eq!(
+            describe(&waits).unwrap(),
+            "Lock wait: 2 thread(s) waiting on held mutexes; \
+             thread 10 (worker-10) waits for mutex 0xa000 held by thread 12, \
+             which isn't waiting on a lock; \
+             thread 13 (worker-13) waits for mutex 0xc000 held by thread 10 (worker-10)"
+        );
+        // Waits without a holder alone could be idle threads
+        assert!(describe(&[wait(11, 0xb000, None)]).is_none());
+        assert!(describe(&[]).is_none());
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_parse_futex_wait() {
+        let nr = libc::SYS_futex;
+        assert_eq!(
+            parse_futex_wait(&format!("{} 0x7f10 0x80 0x2 0x0 0x0 0x0 0x7ffd 0x7f00", nr)),
+            Some(FutexWait {
+                lock: 0x7f10,
+                pi: false,
+                sp: 0x7ffd
+            })
+        );
+        assert_eq!(
+            parse_futex_wait(&format!("{} 0x7f10 0x86 0x0 0x0 0x0 0x0 0x7ffd 0x7f00", nr)),
+            Some(FutexWait {
+                lock: 0x7f10,
+                pi: true,
+                sp: 0x7ffd
+            })
+        );
+        // FUTEX_WAKE
+        assert_eq!(
+            parse_futex_wait(&format!("{} 0x7f10 0x81 0x1 0x0 0x0 0x0 0x7ffd 0x7f00", nr)),
+            None
+        );
+        assert_eq!(parse_futex_wait("running"), None);
+        assert_eq!(parse_futex_wait("-1 0x7ffd 0x7f00"), None);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_parse_libc_mapping() {
+        let path = "/usr/lib/x86_64-linux-gnu/libc.so.6";
+        assert_eq!(
+            parse_libc_mapping(&format!(
+                "7f3a1c000000-7f3a1c028000 r--p 00000000 08:01 1234 {}",
+                path
+            )),
+            Some((0x7f3a_1c00_0000, path))
+        );
+        // Later segments of libc, and other libraries
+        assert_eq!(
+            parse_libc_mapping(&format!(
+                "7f3a1c028000-7f3a1c1bd000 r-xp 00028000 08:01 1234 {}",
+                path
+            )),
+            None
+        );
+        assert_eq!(
+            parse_libc_mapping(
+                "7f3a1d000000-7f3a1d001000 r--p 00000000 08:01 99 /usr/lib/libcap.so.2"
+            ),
+            None
+        );
+        assert_eq!(
+            parse_libc_mapping("7ffd0000-7ffd1000 rw-p 00000000 00:00 0 [stack]"),
+            None
+        );
+    }
+}
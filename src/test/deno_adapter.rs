@@ -26,6 +26,14 @@ impl TestAdapter for DenoAdapter {
         "deno"
     }
 
+    fn version_commands(&self) -> &'static [&'static [&'static str]] {
+        &[&["deno", "--version"]]
+    }
+
+    fn lockfiles(&self) -> &'static [&'static str] {
+        &["deno.lock"]
+    }
+
     fn suite_command(
         &self,
         _project_root: &Path,
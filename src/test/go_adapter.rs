@@ -39,6 +39,14 @@ impl TestAdapter for GoTestAdapter {
         "go"
     }
 
+    fn version_commands(&self) -> &'static [&'static [&'static str]] {
+        &[&["go", "version"]]
+    }
+
+    fn lockfiles(&self) -> &'static [&'static str] {
+        &["go.sum"]
+    }
+
     fn suite_command(
         &self,
         _project_root: &Path,
@@ -108,6 +116,40 @@ impl TestAdapter for GoTestAdapter {
             None => 300_000,
         }
     }
+
+    fn list_command(&self, _project_root: &Path, _command: Option<&str>) -> Option<TestCommand> {
+        Some(TestCommand {
+            program: "go".to_string(),
+            args: vec![
+                "test".to_string(),
+                "-list".to_string(),
+                "^(Test|Example|Fuzz)".to_string(),
+                "./...".to_string(),
+            ],
+            env: HashMap::new(),
+            cwd: None,
+            remove_env: vec![],
+        })
+    }
+
+    fn parse_test_list(&self, stdout: &str) -> Vec<ListedTest> {
+        // Test names are bare words; `ok  <pkg>  0.01s` summaries have spaces
+        stdout
+            .lines()
+            .map(str::trim_end)
+            .filter(|line| {
+                !line.is_empty()
+                    && !line.contains(char::is_whitespace)
+                    && ["Test", "Example", "Fuzz"]
+                        .iter()
+                        .any(|prefix| line.starts_with(prefix))
+            })
+            .map(|name| ListedTest {
+                name: name.to_string(),
+                file: None,
+            })
+            .collect()
+    }
 }
 
 /// Parse `go test -json` streaming output into structured results.
@@ -590,4 +632,12 @@ mod tests {
         assert_eq!(adapter.default_timeout(Some(TestLevel::E2e)), 600_000);
         assert_eq!(adapter.default_timeout(None), 300_000);
     }
+
+    #[test]
+    fn test_parse_test_list() {
+        let stdout = "TestAdd\nExampleAdd\nok  \texample.com/calc\t0.004s\n?   \texample.com/cmd\t[no test files]\n";
+        let tests = GoTestAdapter.parse_test_list(stdout);
+        let names: Vec<&str> = tests.iter().map(|t| t.name.as_str()).collect();
+        assert_eq!(names, vec!["TestAdd", "ExampleAdd"]);
+    }
 }
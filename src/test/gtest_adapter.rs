@@ -76,6 +76,10 @@ impl TestAdapter for GTestAdapter {
         "gtest"
     }
 
+    fn version_commands(&self) -> &'static [&'static [&'static str]] {
+        &[&["c++", "--version"]]
+    }
+
     fn suite_command(
         &self,
         _project_root: &Path,
@@ -184,6 +188,42 @@ impl TestAdapter for GTestAdapter {
     fn single_test_for_binary(&self, cmd: &str, test_name: &str) -> crate::Result<TestCommand> {
         Ok(GTestAdapter::single_test_for_binary(cmd, test_name))
     }
+
+    fn list_command(&self, _project_root: &Path, command: Option<&str>) -> Option<TestCommand> {
+        Some(TestCommand {
+            program: command?.to_string(),
+            args: vec!["--gtest_list_tests".to_string()],
+            env: HashMap::new(),
+            cwd: None,
+            remove_env: vec![],
+        })
+    }
+
+    fn parse_test_list(&self, stdout: &str) -> Vec<ListedTest> {
+        parse_gtest_list(stdout)
+    }
+}
+
+/// Parse `--gtest_list_tests`: unindented `Suite.` lines followed by indented
+/// test names. Parameterized entries carry a `# GetParam() = ...` comment.
+fn parse_gtest_list(stdout: &str) -> Vec<ListedTest> {
+    let mut tests = Vec::new();
+    let mut suite: Option<&str> = None;
+    for line in stdout.lines() {
+        let entry = line.split('#').next().unwrap_or("").trim_end();
+        if entry.trim().is_empty() {
+            continue;
+        }
+        if !entry.starts_with(char::is_whitespace) {
+            suite = entry.strip_suffix('.');
+        } else if let Some(suite) = suite {
+            tests.push(ListedTest {
+                name: format!("{}.{}", suite, entry.trim()),
+                file: None,
+            });
+        }
+    }
+    tests
 }
 
 impl GTestAdapter {
@@ -810,4 +850,16 @@ mod tests {
             .iter()
             .any(|a| a.contains("gtest_filter=Suite.Test")));
     }
+
+    #[test]
+    fn test_parse_test_list() {
+        let stdout = "MathTest.\n  Add\n  Sub\nParam/RangeTest.\n  InBounds/0  # GetParam() = 1\n";
+        let tests = GTestAdapter.parse_test_list(stdout);
+        let names: Vec<&str> = tests.iter().map(|t| t.name.as_str()).collect();
+        assert_eq!(
+            names,
+            vec!["MathTest.Add", "MathTest.Sub", "Param/RangeTest.InBounds/0"]
+        );
+        assert!(GTestAdapter.list_command(Path::new("/tmp"), None).is_none());
+    }
 }
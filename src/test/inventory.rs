@@ -0,0 +1,280 @@
+//! Test inventory for `action: "list"`: what each adapter would run, without
+//! running it. Names come from the framework's own list mode (or, when it has
+//! none, from tests with recorded history), annotated with baseline durations
+//! and the last recorded status so the caller can pick a `test` filter.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::Read;
+use std::path::Path;
+use std::process::Stdio;
+use std::time::{Duration, Instant};
+
+use super::adapter::{ListedTest, TestAdapter, TestCommand};
+use crate::db::TestHistory;
+
+/// List modes of compiled frameworks (cargo, go) build the tests first.
+pub const LIST_TIMEOUT_MS: u64 = 300_000;
+/// Tests returned per adapter; the rest are only counted.
+pub const MAX_LISTED_TESTS: usize = 2000;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InventoryTest {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub estimated_duration_ms: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_status: Option<String>,
+}
+
+/// Where an adapter's test names came from.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum InventorySource {
+    /// The framework's list mode (`cargo test -- --list`, `pytest --collect-only`, ...)
+    List,
+    /// Tests recorded by earlier runs — the framework can't list, or listing failed.
+    History,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AdapterInventory {
+    pub framework: String,
+    pub source: InventorySource,
+    pub total_tests: usize,
+    /// Sum of the estimates that exist; tests without a baseline add nothing.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub estimated_total_ms: Option<u64>,
+    pub tests: Vec<InventoryTest>,
+    #[serde(skip_serializing_if = "std::ops::Not::not", default)]
+    pub truncated: bool,
+    /// Why the list command produced nothing, when it failed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Enumerate one adapter's tests and annotate them with `history`.
+pub fn build_inventory(
+    adapter: &dyn TestAdapter,
+    project_root: &Path,
+    command: Option<&str>,
+    history: &HashMap<String, TestHistory>,
+) -> AdapterInventory {
+    let mut error = None;
+    let listed = match adapter.list_command(project_root, command) {
+        Some(cmd) => match run_list_command(&cmd, project_root) {
+            Ok(output) => {
+                let tests = adapter.parse_test_list(&output.stdout);
+                if tests.is_empty() && !output.success {
+                    error = Some(failure_message(&cmd, &output.stderr));
+                    None
+                } else {
+                    Some(tests)
+                }
+            }
+            Err(e) => {
+                error = Some(e.to_string());
+                None
+            }
+        },
+        None => None,
+    };
+
+    let (source, listed) = match listed {
+        Some(tests) => (InventorySource::List, tests),
+        None => (InventorySource::History, history_tests(history)),
+    };
+    let mut tests = annotate(listed, history);
+    let total_tests = tests.len();
+    let estimated_total_ms = tests
+        .iter()
+        .filter_map(|t| t.estimated_duration_ms)
+        .reduce(|a, b| a + b);
+    let truncated = total_tests > MAX_LISTED_TESTS;
+    tests.truncate(MAX_LISTED_TESTS);
+
+    AdapterInventory {
+        framework: adapter.name().to_string(),
+        source,
+        total_tests,
+        estimated_total_ms,
+        tests,
+        truncated,
+        error,
+    }
+}
+
+/// Attach baselines and last status; duplicate names (e.g. the same test
+/// name in two packages) are listed once.
+fn annotate(listed: Vec<ListedTest>, history: &HashMap<String, TestHistory>) -> Vec<InventoryTest> {
+    let mut seen = std::collections::HashSet::new();
+    listed
+        .into_iter()
+        .filter(|t| seen.insert(t.name.clone()))
+        .map(|t| {
+            let known = history.get(&t.name);
+            InventoryTest {
+                estimated_duration_ms: known.and_then(|h| h.estimated_duration_ms),
+                last_status: known.map(|h| h.last_status.clone()),
+                name: t.name,
+                file: t.file,
+            }
+        })
+        .collect()
+}
+
+fn history_tests(history: &HashMap<String, TestHistory>) -> Vec<ListedTest> {
+    let mut names: Vec<&String> = history.keys().collect();
+    names.sort();
+    names
+        .into_iter()
+        .map(|name| ListedTest {
+            name: name.clone(),
+            file: None,
+        })
+        .collect()
+}
+
+struct ListOutput {
+    stdout: String,
+    stderr: String,
+    success: bool,
+}
+
+/// Run a list command outside Frida, killing it after `LIST_TIMEOUT_MS`.
+fn run_list_command(cmd: &TestCommand, project_root: &Path) -> crate::Result<ListOutput> {
+    let cwd = cmd.cwd.as_deref().map(Path::new).unwrap_or(project_root);
+    let mut command = std::process::Command::new(&cmd.program);
+    command
+        .args(&cmd.args)
+        .current_dir(cwd)
+        .envs(&cmd.env)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    for key in &cmd.remove_env {
+        command.env_remove(key);
+    }
+    let mut child = command.spawn().map_err(|e| {
+        crate::Error::ValidationError(format!("Failed to run '{}': {}", cmd.program, e))
+    })?;
+
+    // Drain both pipes on threads so a chatty build can't fill them and stall
+    let stdout = drain(child.stdout.take());
+    let stderr = drain(child.stderr.take());
+
+    let deadline = Instant::now() + Duration::from_millis(LIST_TIMEOUT_MS);
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break status;
+        }
+        if Instant::now() >= deadline {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(crate::Error::ValidationError(format!(
+                "'{}' did not finish listing tests within {}s",
+                cmd.program,
+                LIST_TIMEOUT_MS / 1000
+            )));
+        }
+        std::thread::sleep(Duration::from_millis(50));
+    };
+
+    Ok(ListOutput {
+        stdout: stdout.join().unwrap_or_default(),
+        stderr: stderr.join().unwrap_or_default(),
+        success: status.success(),
+    })
+}
+
+fn drain<R: Read + Send + 'static>(pipe: Option<R>) -> std::thread::JoinHandle<String> {
+    std::thread::spawn(move || {
+        let mut buf = Vec::new();
+        if let Some(mut pipe) = pipe {
+            let _ = pipe.read_to_end(&mut buf);
+        }
+        String::from_utf8_lossy(&buf).into_owned()
+    })
+}
+
+fn failure_message(cmd: &TestCommand, stderr: &str) -> String {
+    let lines: Vec<&str> = stderr.lines().filter(|l| !l.trim().is_empty()).collect();
+    let tail = lines[lines.len().saturating_sub(5)..].join("\n");
+    format!(
+        "'{} {}' failed without listing tests{}{}",
+        cmd.program,
+        cmd.args.join(" "),
+        if tail.is_empty() { "" } else { ":\n" },
+        tail
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn history() -> HashMap<String, TestHistory> {
+        HashMap::from([
+            (
+                "tests::slow".to_string(),
+                TestHistory {
+                    estimated_duration_ms: Some(1200),
+                    last_status: "pass".to_string(),
+                },
+            ),
+            (
+                "tests::flaky".to_string(),
+                TestHistory {
+                    estimated_duration_ms: Some(300),
+                    last_status: "fail".to_string(),
+                },
+            ),
+        ])
+    }
+
+    #[test]
+    fn test_annotate_with_history() {
+        let listed = vec![
+            ListedTest {
+                name: "tests::slow".to_string(),
+                file: None,
+            },
+            ListedTest {
+                name: "tests::new".to_string(),
+                file: Some("tests/new.rs".to_string()),
+            },
+            ListedTest {
+                name: "tests::slow".to_string(),
+                file: None,
+            },
+        ];
+        let tests = annotate(listed, &history());
+        assert_eq!(tests.len(), 2);
+        assert_eq!(tests[0].estimated_duration_ms, Some(1200));
+        assert_eq!(tests[0].last_status.as_deref(), Some("pass"));
+        assert_eq!(tests[1].estimated_duration_ms, None);
+        assert_eq!(tests[1].last_status, None);
+        assert_eq!(tests[1].file.as_deref(), Some("tests/new.rs"));
+    }
+
+    #[test]
+    fn test_inventory_falls_back_to_history() {
+        // Mocha has no list mode — the inventory is what earlier runs recorded
+        let dir = tempfile::tempdir().unwrap();
+        let inventory = build_inventory(
+            &super::super::mocha_adapter::MochaAdapter,
+            dir.path(),
+            None,
+            &history(),
+        );
+        assert_eq!(inventory.source, InventorySource::History);
+        assert_eq!(inventory.total_tests, 2);
+        assert_eq!(inventory.estimated_total_ms, Some(1500));
+        assert_eq!(inventory.tests[0].name, "tests::flaky");
+        assert!(inventory.error.is_none());
+    }
+}
@@ -67,6 +67,14 @@ impl TestAdapter for JestAdapter {
         "jest"
     }
 
+    fn version_commands(&self) -> &'static [&'static [&'static str]] {
+        &[&["node", "--version"]]
+    }
+
+    fn lockfiles(&self) -> &'static [&'static str] {
+        NODE_LOCKFILES
+    }
+
     fn suite_command(
         &self,
         _project_root: &Path,
@@ -2,13 +2,17 @@ pub mod adapter;
 pub mod bun_adapter;
 pub mod cargo_adapter;
 pub mod catch2_adapter;
+pub mod deadlock;
 pub mod deno_adapter;
 pub mod go_adapter;
 pub mod gtest_adapter;
+pub mod inventory;
 pub mod jest_adapter;
 pub mod mocha_adapter;
+pub mod ordering;
 pub mod output;
 pub mod playwright_adapter;
+pub mod provenance;
 pub mod pytest_adapter;
 pub mod stacks;
 pub mod stuck_detector;
@@ -119,6 +123,15 @@ impl TestProgress {
             .map(|(name, _)| name.clone())
     }
 
+    /// Get the most recently started running test — the likeliest owner of a
+    /// process spawned right now.
+    pub fn latest_test(&self) -> Option<String> {
+        self.running_tests
+            .iter()
+            .max_by_key(|(_, started)| *started)
+            .map(|(name, _)| name.clone())
+    }
+
     /// Get when the current (longest-running) test started.
     pub fn current_test_started_at(&self) -> Option<Instant> {
         self.running_tests.values().min().copied()
@@ -231,6 +244,35 @@ impl TestRunner {
         }
     }
 
+    /// Every adapter that recognizes the project, most confident first, for
+    /// listing. An explicit framework narrows this to that one adapter.
+    pub fn detect_adapters(
+        &self,
+        project_root: &Path,
+        framework: Option<&str>,
+        command: Option<&str>,
+    ) -> crate::Result<Vec<&dyn TestAdapter>> {
+        if framework.is_some() {
+            return Ok(vec![self.detect_adapter(
+                project_root,
+                framework,
+                command,
+            )?]);
+        }
+        let mut detected: Vec<(&dyn TestAdapter, u8)> = self
+            .adapters
+            .iter()
+            .map(|a| (a.as_ref(), a.detect(project_root, command)))
+            .filter(|(_, confidence)| *confidence > 0)
+            .collect();
+        if detected.is_empty() {
+            // Reuse the "no framework detected" guidance
+            return Ok(vec![self.detect_adapter(project_root, None, command)?]);
+        }
+        detected.sort_by_key(|(_, confidence)| std::cmp::Reverse(*confidence));
+        Ok(detected.into_iter().map(|(a, _)| a).collect())
+    }
+
     /// Run tests inside Frida with DB-based progress polling.
     /// Always spawns via Frida — the LLM can add trace patterns at any time via debug_trace.
     pub async fn run(
@@ -248,12 +290,14 @@ impl TestRunner {
         _connection_id: &str,
         session_id: &str,
         progress: Arc<Mutex<TestProgress>>,
+        instrumentation: crate::mcp::TestInstrumentation,
+        order: crate::mcp::TestOrder,
     ) -> crate::Result<TestRunResult> {
         let adapter = self.detect_adapter(project_root, framework, command)?;
         let framework_name = adapter.name().to_string();
 
         // Build command — dispatch through trait methods for binary-based adapters
-        let test_cmd = if let Some(cmd) = command {
+        let mut test_cmd = if let Some(cmd) = command {
             if let Some(test_name) = test {
                 adapter.single_test_for_binary(cmd, test_name)?
             } else {
@@ -264,6 +308,25 @@ impl TestRunner {
         } else {
             adapter.suite_command(project_root, level, env)?
         };
+        // The caller's env wins over the adapter's. Merged before any adapter
+        // rewrite, so one that extends a variable (PYTHONPATH) extends theirs.
+        test_cmd.env.extend(env.clone());
+
+        // fail-fast-smart: move recently-failed / recently-changed tests to the front
+        let mut prioritized = Vec::new();
+        if order == crate::mcp::TestOrder::FailFastSmart && test.is_none() {
+            let root = project_root.to_str().unwrap_or(".");
+            let db = session_manager.db();
+            let failures = db
+                .get_recent_failures(root, ordering::RECENT_RUN_WINDOW)
+                .unwrap_or_default();
+            let known = db.get_known_tests(root).unwrap_or_default();
+            let changed = ordering::changed_files(project_root);
+            let priority = ordering::smart_priority(&failures, &known, &changed);
+            if !priority.is_empty() && adapter.apply_priority_order(&mut test_cmd, &priority) {
+                prioritized = priority;
+            }
+        }
 
         // Timeout priority: explicit param > settings.json > adapter default
         let settings = crate::config::resolve(Some(project_root));
@@ -308,6 +371,9 @@ impl TestRunner {
             }
         }
 
+        // After the pretest script, which may install dependencies
+        let provenance = provenance::capture(adapter, project_root, &test_cmd.env).await;
+
         // Resolve program to absolute path (Frida's Device.spawn doesn't do PATH lookup)
         let program = resolve_program(&test_cmd.program);
 
@@ -318,7 +384,6 @@ impl TestRunner {
             combined_env.remove(key);
         }
         combined_env.extend(test_cmd.env.clone());
-        combined_env.extend(env.clone());
 
         // Create session BEFORE spawning — writer task needs FK to exist
         // when events arrive (interpreted processes start immediately)
@@ -328,43 +393,59 @@ impl TestRunner {
             project_root.to_str().unwrap_or("."),
             0,
         )?;
+        session_manager.set_test_progress(session_id, Arc::clone(&progress));
 
-        // Spawn via Frida — defer resume if we need to install hooks first
-        let has_trace_patterns = !trace_patterns.is_empty();
         let spawn_cwd = test_cmd
             .cwd
             .as_deref()
             .unwrap_or(project_root.to_str().unwrap_or("."));
-        let pid = session_manager
-            .spawn_with_frida(
-                session_id,
-                &program,
-                &test_cmd.args,
-                Some(spawn_cwd),
-                project_root.to_str().unwrap_or("."),
-                Some(&combined_env),
-                has_trace_patterns, // defer_resume: install hooks before running
-                None,               // symbols_path: test runner uses automatic resolution
-            )
-            .await?;
-
-        // Apply trace patterns BEFORE resuming the process
-        if has_trace_patterns {
-            session_manager.add_patterns(session_id, trace_patterns)?;
-            match session_manager
-                .update_frida_patterns(session_id, Some(trace_patterns), None, None)
-                .await
-            {
-                Ok(result) => {
-                    session_manager.set_hook_count(session_id, result.installed);
-                }
-                Err(e) => {
-                    tracing::warn!("Failed to apply trace patterns for test session: {}", e);
+        let pid = if instrumentation == crate::mcp::TestInstrumentation::None {
+            // Plain subprocess — output is still captured into the session's events
+            session_manager
+                .spawn_plain(
+                    session_id,
+                    &program,
+                    &test_cmd.args,
+                    Some(spawn_cwd),
+                    project_root.to_str().unwrap_or("."),
+                    Some(&combined_env),
+                )
+                .await?
+        } else {
+            // Spawn via Frida — defer resume if we need to install hooks first
+            let has_trace_patterns = !trace_patterns.is_empty();
+            let pid = session_manager
+                .spawn_with_frida(
+                    session_id,
+                    &program,
+                    &test_cmd.args,
+                    Some(spawn_cwd),
+                    project_root.to_str().unwrap_or("."),
+                    Some(&combined_env),
+                    has_trace_patterns, // defer_resume: install hooks before running
+                    None,               // symbols_path: test runner uses automatic resolution
+                )
+                .await?;
+
+            // Apply trace patterns BEFORE resuming the process
+            if has_trace_patterns {
+                session_manager.add_patterns(session_id, trace_patterns)?;
+                match session_manager
+                    .update_frida_patterns(session_id, Some(trace_patterns), None, None)
+                    .await
+                {
+                    Ok(result) => {
+                        session_manager.set_hook_count(session_id, result.installed);
+                    }
+                    Err(e) => {
+                        tracing::warn!("Failed to apply trace patterns for test session: {}", e);
+                    }
                 }
+                // NOW resume — hooks are installed
+                session_manager.resume_process(pid).await?;
             }
-            // NOW resume — hooks are installed
-            session_manager.resume_process(pid).await?;
-        }
+            pid
+        };
 
         // Select progress updater based on adapter
         let progress_fn: Option<fn(&str, &Arc<Mutex<TestProgress>>)> = match framework_name.as_str()
@@ -391,7 +472,12 @@ impl TestRunner {
 
         // Spawn stuck detector as background monitor
         let detector_progress = Arc::clone(&progress);
-        let mut detector = StuckDetector::new(pid, hard_timeout, detector_progress);
+        let mut detector = StuckDetector::new(pid, hard_timeout, detector_progress).with_baselines(
+            session_manager
+                .db()
+                .get_project_baseline_stats(project_root.to_str().unwrap_or("."))
+                .unwrap_or_default(),
+        );
 
         // Phase 2: Wire up breakpoint pause awareness so the stuck detector
         // doesn't false-positive on threads paused at breakpoints.
@@ -426,19 +512,15 @@ impl TestRunner {
         let is_playwright = framework_name == "playwright";
 
         loop {
-            let process_alive = stacks::is_process_alive(pid);
+            let process_alive = crate::process::is_alive(pid);
 
             // Try to reap zombie — kill(pid, 0) returns true for zombies but
             // waitpid detects actual exit. Without this, the loop runs until
             // hard_timeout for every normal test completion.
             let mut reaped = false;
-            {
-                let mut status: i32 = 0;
-                let wp = unsafe { libc::waitpid(pid as i32, &mut status, libc::WNOHANG) };
-                if wp > 0 {
-                    reaped_status = Some(status);
-                    reaped = true;
-                }
+            if let Some(code) = crate::process::try_reap(pid) {
+                reaped_status = Some(code);
+                reaped = true;
             }
 
             // Hard timeout — kill the process tree (stuck detector has already written warnings)
@@ -602,18 +684,10 @@ impl TestRunner {
             crate::db::EventType::Stderr,
         );
 
-        // Get exit code: use already-reaped status from polling loop, or try waitpid
-        let exit_code = {
-            let status = reaped_status.unwrap_or_else(|| {
-                let mut s: i32 = 0;
-                let r = unsafe { libc::waitpid(pid as i32, &mut s, libc::WNOHANG) };
-                if r > 0 {
-                    s
-                } else {
-                    -1
-                }
-            });
-            if status == -1 {
+        // Get exit code: use already-reaped status from polling loop, or try reaping now
+        let exit_code = match reaped_status.or_else(|| crate::process::try_reap(pid)) {
+            Some(code) => code,
+            None => {
                 // Not our child or already reaped — infer from test results
                 let p = progress.lock().unwrap();
                 if p.failed > 0 {
@@ -621,12 +695,6 @@ impl TestRunner {
                 } else {
                     0
                 }
-            } else if unsafe { libc::WIFEXITED(status) } {
-                unsafe { libc::WEXITSTATUS(status) }
-            } else if unsafe { libc::WIFSIGNALED(status) } {
-                128 + unsafe { libc::WTERMSIG(status) }
-            } else {
-                -1
             }
         };
 
@@ -675,9 +743,14 @@ impl TestRunner {
                 }
             }
 
+            // Then wall-clock durations the progress parser measured live
+            let progress_durations = progress.lock().unwrap().test_durations.clone();
             for test in &mut result.all_tests {
                 if test.duration_ms == 0 {
-                    if let Some(&dur) = test_durations.get(&test.name) {
+                    if let Some(&dur) = test_durations
+                        .get(&test.name)
+                        .or_else(|| progress_durations.get(&test.name))
+                    {
                         test.duration_ms = dur;
                     }
                 }
@@ -696,6 +769,8 @@ impl TestRunner {
             session_id: Some(session_id.to_string()),
             raw_stdout: stdout_buf,
             raw_stderr: stderr_buf,
+            prioritized,
+            provenance,
         })
     }
 }
@@ -896,6 +971,10 @@ pub struct TestRunResult {
     pub session_id: Option<String>,
     pub raw_stdout: String,
     pub raw_stderr: String,
+    /// Entries moved to the front by order: "fail-fast-smart" (empty otherwise)
+    pub prioritized: Vec<String>,
+    /// Toolchain versions and lockfile hash the run used
+    pub provenance: provenance::Provenance,
 }
 
 #[cfg(test)]
@@ -994,6 +1073,19 @@ mod tests {
         assert_eq!(run.session_id.as_deref(), Some("session-xyz"));
     }
 
+    #[test]
+    fn test_latest_test() {
+        let mut progress = TestProgress::new();
+        assert_eq!(progress.latest_test(), None);
+        progress.start_test("tests::first".into());
+        std::thread::sleep(std::time::Duration::from_millis(2));
+        progress.start_test("tests::second".into());
+        assert_eq!(progress.latest_test().as_deref(), Some("tests::second"));
+        assert_eq!(progress.current_test().as_deref(), Some("tests::first"));
+        progress.finish_test("tests::second");
+        assert_eq!(progress.latest_test().as_deref(), Some("tests::first"));
+    }
+
     #[test]
     fn test_progress_warnings() {
         let mut progress = TestProgress::new();
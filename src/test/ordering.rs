@@ -0,0 +1,163 @@
+//! Priority ordering for `order: "fail-fast-smart"` runs.
+//!
+//! Likely failures are moved to the front of the suite: tests that failed in
+//! recent runs (from the baselines table), then test files changed in the
+//! working tree or recent commits, then known tests whose name mentions a
+//! changed source file. The adapter decides how (and whether) the framework
+//! can honour the order.
+
+use std::collections::HashSet;
+use std::path::Path;
+
+/// How many recorded runs per test count as "recent" for failure history.
+pub const RECENT_RUN_WINDOW: usize = 5;
+/// How many commits back count as "recently changed".
+pub const RECENT_COMMITS: usize = 3;
+/// Upper bound on prioritized entries handed to the adapter.
+pub const MAX_PRIORITIZED: usize = 200;
+
+/// Files changed relative to `HEAD~RECENT_COMMITS` (committed, staged and
+/// unstaged) plus untracked files, as paths relative to `project_root`.
+/// Empty when `project_root` is not inside a git repository.
+pub fn changed_files(project_root: &Path) -> Vec<String> {
+    let base = format!("HEAD~{}", RECENT_COMMITS);
+    let diff = git_lines(project_root, &["diff", "--name-only", "--relative", &base])
+        .or_else(|| git_lines(project_root, &["diff", "--name-only", "--relative", "HEAD"]))
+        .unwrap_or_default();
+    let untracked = git_lines(
+        project_root,
+        &["ls-files", "--others", "--exclude-standard"],
+    )
+    .unwrap_or_default();
+
+    let mut seen = HashSet::new();
+    diff.into_iter()
+        .chain(untracked)
+        .filter(|f| seen.insert(f.clone()))
+        .collect()
+}
+
+fn git_lines(cwd: &Path, args: &[&str]) -> Option<Vec<String>> {
+    let output = std::process::Command::new("git")
+        .args(args)
+        .current_dir(cwd)
+        .stderr(std::process::Stdio::null())
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    Some(
+        String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .map(|l| l.trim().to_string())
+            .filter(|l| !l.is_empty())
+            .collect(),
+    )
+}
+
+/// Build the priority list, most urgent first.
+///
+/// Entries are either test names (from `recent_failures` / `known_tests`) or
+/// changed test-file paths; adapters match both.
+pub fn smart_priority(
+    recent_failures: &[String],
+    known_tests: &[String],
+    changed: &[String],
+) -> Vec<String> {
+    let mut out: Vec<String> = Vec::new();
+    let mut seen: HashSet<String> = HashSet::new();
+    let mut push = |entry: &str, out: &mut Vec<String>| {
+        if seen.insert(entry.to_string()) {
+            out.push(entry.to_string());
+        }
+    };
+
+    for name in recent_failures {
+        push(name, &mut out);
+    }
+
+    let (test_files, sources): (Vec<&String>, Vec<&String>) =
+        changed.iter().partition(|f| is_test_file(f));
+    for file in test_files {
+        push(file, &mut out);
+    }
+
+    let stems: Vec<String> = sources.iter().filter_map(|f| source_stem(f)).collect();
+    for name in known_tests {
+        let lower = name.to_lowercase();
+        if stems.iter().any(|stem| lower.contains(stem.as_str())) {
+            push(name, &mut out);
+        }
+    }
+
+    out.truncate(MAX_PRIORITIZED);
+    out
+}
+
+fn is_test_file(path: &str) -> bool {
+    let file = path.rsplit('/').next().unwrap_or(path);
+    file.starts_with("test_")
+        || file.contains("_test.")
+        || file.contains(".test.")
+        || file.contains(".spec.")
+        || path.starts_with("tests/")
+        || path.contains("/tests/")
+}
+
+/// Lowercased file stem of a changed source file, if it's distinctive enough
+/// to match on (short or generic stems like `mod`/`lib` would match everything).
+fn source_stem(path: &str) -> Option<String> {
+    const GENERIC: &[&str] = &["mod", "lib", "main", "index", "init", "__init__", "utils"];
+    let file = path.rsplit('/').next()?;
+    let stem = file.split('.').next()?.to_lowercase();
+    if stem.len() < 3 || GENERIC.contains(&stem.as_str()) {
+        return None;
+    }
+    Some(stem)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn s(v: &[&str]) -> Vec<String> {
+        v.iter().map(|x| x.to_string()).collect()
+    }
+
+    #[test]
+    fn test_failures_then_changed_tests_then_stem_matches() {
+        let priority = smart_priority(
+            &s(&["tests/test_api.py::test_get"]),
+            &s(&[
+                "auth::tests::login",
+                "parser::tests::empty",
+                "tests/test_api.py::test_get",
+            ]),
+            &s(&["src/auth.rs", "tests/test_cache.py", "src/lib.rs"]),
+        );
+        assert_eq!(
+            priority,
+            s(&[
+                "tests/test_api.py::test_get",
+                "tests/test_cache.py",
+                "auth::tests::login",
+            ])
+        );
+    }
+
+    #[test]
+    fn test_generic_stems_are_ignored() {
+        let priority = smart_priority(&[], &s(&["module::tests::a"]), &s(&["src/mod.rs"]));
+        assert!(priority.is_empty());
+    }
+
+    #[test]
+    fn test_is_test_file() {
+        assert!(is_test_file("tests/integration.rs"));
+        assert!(is_test_file("pkg/test_models.py"));
+        assert!(is_test_file("src/button.spec.ts"));
+        assert!(is_test_file("server_test.go"));
+        assert!(!is_test_file("src/server.rs"));
+    }
+}
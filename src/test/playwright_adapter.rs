@@ -48,6 +48,14 @@ impl TestAdapter for PlaywrightAdapter {
         "playwright"
     }
 
+    fn version_commands(&self) -> &'static [&'static [&'static str]] {
+        &[&["bun", "--version"], &["node", "--version"]]
+    }
+
+    fn lockfiles(&self) -> &'static [&'static str] {
+        NODE_LOCKFILES
+    }
+
     fn suite_command(
         &self,
         project_root: &Path,
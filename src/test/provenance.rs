@@ -0,0 +1,217 @@
+//! Toolchain provenance of a test run: the compiler and runtime versions the
+//! adapter depends on and a hash of the project's dependency lockfiles.
+//!
+//! Stored once per run for the project, so the next run can report what
+//! changed and a duration regression or new failure can be put down to a
+//! toolchain or dependency bump rather than a code change.
+
+use super::adapter::TestAdapter;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::ffi::OsString;
+use std::path::Path;
+use std::time::Duration;
+
+/// Project-local tool directories searched before the run's PATH, so the
+/// versions recorded are those of the venv or node_modules the tests use.
+const PROJECT_BIN_DIRS: &[&str] = &[".venv/bin", "venv/bin", "node_modules/.bin"];
+
+/// A version command that hangs (a shim prompting for input) is dropped.
+const VERSION_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Provenance {
+    /// First line of each tool's version output, keyed by program
+    #[serde(skip_serializing_if = "BTreeMap::is_empty", default)]
+    pub toolchain: BTreeMap<String, String>,
+    /// FNV-1a over the lockfiles found in the project root
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lockfile_hash: Option<String>,
+}
+
+impl Provenance {
+    pub fn is_empty(&self) -> bool {
+        self.toolchain.is_empty() && self.lockfile_hash.is_none()
+    }
+}
+
+/// Capture the provenance of a run of `adapter` in `project_root`, whose
+/// test command runs with `env`. Tools that aren't installed, fail or time
+/// out are left out.
+pub async fn capture(
+    adapter: &dyn TestAdapter,
+    project_root: &Path,
+    env: &HashMap<String, String>,
+) -> Provenance {
+    let path = search_path(project_root, env.get("PATH").map(String::as_str));
+    let mut toolchain = BTreeMap::new();
+    for cmd in adapter.version_commands() {
+        let Some((program, args)) = cmd.split_first() else {
+            continue;
+        };
+        if let Some(line) = version_line(program, args, project_root, path.as_deref()).await {
+            toolchain.insert(program.to_string(), line);
+        }
+    }
+    Provenance {
+        toolchain,
+        lockfile_hash: lockfile_hash(project_root, adapter.lockfiles()),
+    }
+}
+
+/// The project's tool directories that exist, then `base` (the daemon's
+/// PATH when the run doesn't set one).
+fn search_path(project_root: &Path, base: Option<&str>) -> Option<OsString> {
+    let base = base
+        .map(OsString::from)
+        .or_else(|| std::env::var_os("PATH"))
+        .unwrap_or_default();
+    let project_dirs = PROJECT_BIN_DIRS
+        .iter()
+        .map(|dir| project_root.join(dir))
+        .filter(|dir| dir.is_dir());
+    std::env::join_paths(project_dirs.chain(std::env::split_paths(&base))).ok()
+}
+
+async fn version_line(
+    program: &str,
+    args: &[&str],
+    cwd: &Path,
+    path: Option<&std::ffi::OsStr>,
+) -> Option<String> {
+    let mut cmd = tokio::process::Command::new(program);
+    cmd.args(args)
+        .current_dir(cwd)
+        .stdin(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .kill_on_drop(true);
+    if let Some(path) = path {
+        cmd.env("PATH", path);
+    }
+    let output = tokio::time::timeout(VERSION_TIMEOUT, cmd.output())
+        .await
+        .ok()?
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(str::trim)
+        .find(|l| !l.is_empty())
+        .map(str::to_string)
+}
+
+fn lockfile_hash(project_root: &Path, lockfiles: &[&str]) -> Option<String> {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    let mut found = false;
+    for name in lockfiles {
+        let Ok(contents) = std::fs::read(project_root.join(name)) else {
+            continue;
+        };
+        found = true;
+        // The name too, so moving between package managers counts as a change
+        for byte in name.bytes().chain(contents) {
+            hash ^= byte as u64;
+            hash = hash.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+    found.then(|| format!("{:016x}", hash))
+}
+
+/// What differs between the previous run's provenance and this one's, e.g.
+/// "rustc: rustc 1.78.0 → rustc 1.79.0". Tools missing from either side
+/// aren't compared.
+pub fn describe_changes(previous: &Provenance, current: &Provenance) -> Vec<String> {
+    let mut changes: Vec<String> = current
+        .toolchain
+        .iter()
+        .filter_map(|(tool, version)| {
+            let before = previous.toolchain.get(tool)?;
+            (before != version).then(|| format!("{}: {} → {}", tool, before, version))
+        })
+        .collect();
+    if let (Some(before), Some(after)) = (&previous.lockfile_hash, &current.lockfile_hash) {
+        if before != after {
+            changes.push("dependency lockfile changed".to_string());
+        }
+    }
+    changes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn provenance(tools: &[(&str, &str)], lockfile_hash: Option<&str>) -> Provenance {
+        Provenance {
+            toolchain: tools
+                .iter()
+                .map(|(t, v)| (t.to_string(), v.to_string()))
+                .collect(),
+            lockfile_hash: lockfile_hash.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn test_describe_changes() {
+        let previous = provenance(
+            &[("rustc", "rustc 1.78.0"), ("cargo", "cargo 1.78.0")],
+            Some("aa"),
+        );
+        assert!(describe_changes(&previous, &previous).is_empty());
+
+        let current = provenance(&[("rustc", "rustc 1.79.0")], Some("bb"));
+        assert_eq!(
+            describe_changes(&previous, &current),
+            vec![
+                "rustc: rustc 1.78.0 → rustc 1.79.0",
+                "dependency lockfile changed",
+            ]
+        );
+
+        // Nothing to compare against
+        assert!(describe_changes(&Provenance::default(), &current).is_empty());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_version_line_prefers_project_venv() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let bin = dir.path().join(".venv/bin");
+        std::fs::create_dir_all(&bin).unwrap();
+        let tool = bin.join("strobe-test-tool");
+        std::fs::write(&tool, "#!/bin/sh\necho 'tool 9.9.9'\n").unwrap();
+        std::fs::set_permissions(&tool, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+        let path = search_path(dir.path(), Some("/usr/bin:/bin"));
+        let line = version_line("strobe-test-tool", &["-v"], dir.path(), path.as_deref()).await;
+        assert_eq!(line.as_deref(), Some("tool 9.9.9"));
+
+        // Not on the run's PATH without the project directories
+        let elsewhere = tempfile::tempdir().unwrap();
+        let path = search_path(elsewhere.path(), Some("/usr/bin:/bin"));
+        let line = version_line("strobe-test-tool", &["-v"], dir.path(), path.as_deref()).await;
+        assert_eq!(line, None);
+    }
+
+    #[test]
+    fn test_lockfile_hash() {
+        let dir = tempfile::tempdir().unwrap();
+        assert_eq!(lockfile_hash(dir.path(), &["Cargo.lock"]), None);
+
+        std::fs::write(dir.path().join("Cargo.lock"), "version = 3\n").unwrap();
+        let first = lockfile_hash(dir.path(), &["Cargo.lock"]).unwrap();
+        assert_eq!(first.len(), 16);
+        assert_eq!(
+            lockfile_hash(dir.path(), &["Cargo.lock"]),
+            Some(first.clone())
+        );
+
+        std::fs::write(dir.path().join("Cargo.lock"), "version = 4\n").unwrap();
+        assert_ne!(lockfile_hash(dir.path(), &["Cargo.lock"]), Some(first));
+    }
+}
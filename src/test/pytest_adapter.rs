@@ -1,11 +1,61 @@
 use serde::Deserialize;
 use std::collections::HashMap;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 use super::adapter::*;
 
 pub struct PytestAdapter;
 
+/// pytest plugin that sorts collected items by STROBE_TEST_PRIORITY.
+const PRIORITY_PLUGIN_PY: &str = include_str!("reporters/strobe_priority.py");
+
+/// Per-user, so nobody else can put a module on the test's import path.
+fn priority_plugin_dir() -> Option<PathBuf> {
+    dirs::home_dir().map(|home| home.join(".strobe/pytest"))
+}
+
+/// Write the priority plugin into `dir`, where `-p strobe_priority` can
+/// import it. Content is static so concurrent writes are safe.
+fn ensure_priority_plugin(dir: &Path) -> Option<()> {
+    std::fs::create_dir_all(dir).ok()?;
+    std::fs::write(dir.join("strobe_priority.py"), PRIORITY_PLUGIN_PY).ok()
+}
+
+/// Load the priority plugin from `plugin_dir`, ahead of whatever PYTHONPATH
+/// the run already has (the caller's `env`, else the daemon's).
+fn apply_priority_plugin(cmd: &mut TestCommand, priority: &[String], plugin_dir: &Path) -> bool {
+    if ensure_priority_plugin(plugin_dir).is_none() {
+        return false;
+    }
+    let existing = cmd
+        .env
+        .get("PYTHONPATH")
+        .cloned()
+        .or_else(|| std::env::var("PYTHONPATH").ok())
+        .unwrap_or_default();
+    let paths = std::iter::once(plugin_dir.to_path_buf())
+        .chain(std::env::split_paths(&existing).filter(|p| !p.as_os_str().is_empty()));
+    let Ok(python_path) = std::env::join_paths(paths) else {
+        return false;
+    };
+    cmd.env.insert(
+        "PYTHONPATH".into(),
+        python_path.to_string_lossy().into_owned(),
+    );
+    cmd.env.insert(
+        "STROBE_TEST_PRIORITY".into(),
+        serde_json::to_string(priority).unwrap_or_else(|_| "[]".into()),
+    );
+    cmd.args.extend(["-p".into(), "strobe_priority".into()]);
+    true
+}
+
+/// `-v` writes each test's node id before running it; an unbuffered stdout
+/// gets that to the daemon while the test is still in flight.
+fn live_output_env() -> HashMap<String, String> {
+    HashMap::from([("PYTHONUNBUFFERED".to_string(), "1".to_string())])
+}
+
 /// Check whether the project uses uv (presence of uv.lock).
 fn use_uv(project_root: &Path) -> bool {
     project_root.join("uv.lock").exists()
@@ -45,6 +95,14 @@ impl TestAdapter for PytestAdapter {
         "pytest"
     }
 
+    fn version_commands(&self) -> &'static [&'static [&'static str]] {
+        &[&["python3", "--version"]]
+    }
+
+    fn lockfiles(&self) -> &'static [&'static str] {
+        PYTHON_LOCKFILES
+    }
+
     fn suite_command(
         &self,
         project_root: &Path,
@@ -59,7 +117,7 @@ impl TestAdapter for PytestAdapter {
         };
         args.extend([
             "--tb=short".into(),
-            "-q".into(),
+            "-v".into(),
             "--json-report".into(),
             "--json-report-file=-".into(),
         ]);
@@ -78,7 +136,7 @@ impl TestAdapter for PytestAdapter {
         Ok(TestCommand {
             program: if uv { "uv".into() } else { "python3".into() },
             args,
-            env: HashMap::new(),
+            env: live_output_env(),
             cwd: None,
             remove_env: vec![],
         })
@@ -97,11 +155,12 @@ impl TestAdapter for PytestAdapter {
             "--json-report".into(),
             "--json-report-file=-".into(),
             "--tb=short".into(),
+            "-v".into(),
         ]);
         Ok(TestCommand {
             program: if uv { "uv".into() } else { "python3".into() },
             args,
-            env: HashMap::new(),
+            env: live_output_env(),
             cwd: None,
             remove_env: vec![],
         })
@@ -128,6 +187,44 @@ impl TestAdapter for PytestAdapter {
             None => 120_000,
         }
     }
+
+    fn apply_priority_order(&self, cmd: &mut TestCommand, priority: &[String]) -> bool {
+        match priority_plugin_dir() {
+            Some(dir) => apply_priority_plugin(cmd, priority, &dir),
+            None => false,
+        }
+    }
+
+    fn list_command(&self, project_root: &Path, _command: Option<&str>) -> Option<TestCommand> {
+        let uv = use_uv(project_root);
+        let mut args: Vec<String> = if uv {
+            vec!["run".into(), "pytest".into()]
+        } else {
+            vec!["-m".into(), "pytest".into()]
+        };
+        args.extend(["--collect-only".into(), "-q".into()]);
+        Some(TestCommand {
+            program: if uv { "uv".into() } else { "python3".into() },
+            args,
+            env: HashMap::new(),
+            cwd: None,
+            remove_env: vec![],
+        })
+    }
+
+    fn parse_test_list(&self, stdout: &str) -> Vec<ListedTest> {
+        // `-q` prints bare node IDs, then a blank line and the summary
+        stdout
+            .lines()
+            .map(str::trim)
+            .take_while(|line| !line.is_empty())
+            .filter(|line| is_nodeid(line))
+            .map(|nodeid| ListedTest {
+                name: nodeid.to_string(),
+                file: nodeid.split("::").next().map(str::to_string),
+            })
+            .collect()
+    }
 }
 
 fn has_python_test_files(root: &Path) -> bool {
@@ -196,6 +293,14 @@ fn parse_pytest_json_report(stdout: &str, _stderr: &str, _exit_code: i32) -> Tes
 
     let report: PytestJsonReport = if let Ok(r) = serde_json::from_str(json_str) {
         r
+    } else if let Some(r) = json_str
+        .lines()
+        .rev()
+        .filter(|l| l.trim_start().starts_with('{'))
+        .find_map(|l| serde_json::from_str(l.trim()).ok())
+    {
+        // Verbose output precedes the report, which is written as one line
+        r
     } else {
         // If that fails, look for JSON content (starts with { and ends with })
         let json_start = json_str.find('{').unwrap_or(0);
@@ -321,39 +426,105 @@ fn extract_python_traces(failure: &TestFailure) -> Vec<String> {
     traces
 }
 
-/// Update progress from pytest output (line-by-line incremental parsing).
+/// Result words pytest `-v` prints after a node id.
+const VERBOSE_OUTCOMES: &[&str] = &["PASSED", "FAILED", "ERROR", "SKIPPED", "XFAIL", "XPASS"];
+
+/// One line of `pytest -v` output: the node id it names and, once the test
+/// has finished, its outcome word.
+fn parse_verbose_line(line: &str) -> (Option<&str>, Option<&'static str>) {
+    let line = line.trim();
+    let outcome_at = |word: &str| VERBOSE_OUTCOMES.iter().find(|o| **o == word).copied();
+
+    // pytest-xdist: "[gw0] [ 50%] PASSED tests/test_a.py::test_x"
+    if line.starts_with("[gw") {
+        let mut words = line
+            .split_whitespace()
+            .skip_while(|w| outcome_at(w).is_none());
+        let outcome = words.next().and_then(outcome_at);
+        let nodeid = words.next().filter(|w| is_nodeid(w));
+        return (nodeid, outcome);
+    }
+
+    let mut words = line.split_whitespace();
+    match words.next() {
+        // "tests/test_a.py::test_x PASSED   [ 50%]", or just the node id
+        // while the test is running
+        Some(first) if is_nodeid(first) => (Some(first), words.next().and_then(outcome_at)),
+        // "PASSED   [ 50%]" completing a node id written by an earlier chunk.
+        // The short summary ("FAILED tests/x.py::test - msg") repeats results
+        // already counted, so an outcome followed by a node id is skipped.
+        Some(first) => match outcome_at(first) {
+            Some(outcome) if !words.next().is_some_and(is_nodeid) => (None, Some(outcome)),
+            _ => (None, None),
+        },
+        None => (None, None),
+    }
+}
+
+fn is_nodeid(word: &str) -> bool {
+    word.contains(".py::")
+}
+
+/// Update progress from `pytest -v` output. The runner hands over output
+/// chunks as written, so a node id and its result can arrive separately.
 pub fn update_progress(
-    line: &str,
+    text: &str,
     progress: &std::sync::Arc<std::sync::Mutex<super::TestProgress>>,
 ) {
-    let trimmed = line.trim();
+    let mut p = progress.lock().unwrap();
+    for line in text.lines() {
+        let trimmed = line.trim();
+
+        // Detect test collection phase
+        if trimmed.starts_with("collecting") || trimmed.starts_with("collected") {
+            if p.phase == super::TestPhase::Compiling {
+                p.phase = super::TestPhase::Running;
+            }
+            continue;
+        }
+
+        let (nodeid, outcome) = parse_verbose_line(trimmed);
+        let name = match nodeid {
+            Some(id) => id.to_string(),
+            // A bare result belongs to the test most recently started
+            None if outcome.is_some() => match p
+                .running_tests
+                .iter()
+                .max_by_key(|(_, started)| *started)
+                .map(|(name, _)| name.clone())
+            {
+                Some(name) => name,
+                None => continue,
+            },
+            None => continue,
+        };
 
-    // Detect test collection phase
-    if trimmed.starts_with("collecting") || trimmed.starts_with("collected") {
-        let mut p = progress.lock().unwrap();
         if p.phase == super::TestPhase::Compiling {
             p.phase = super::TestPhase::Running;
         }
-    }
-
-    // Detect individual test results from verbose output
-    // "tests/test_audio.py::test_generate PASSED"
-    if trimmed.contains(" PASSED") {
-        let mut p = progress.lock().unwrap();
-        p.passed += 1;
-    } else if trimmed.contains(" FAILED") {
-        let mut p = progress.lock().unwrap();
-        p.failed += 1;
-    } else if trimmed.contains(" SKIPPED") || trimmed.contains(" XFAIL") {
-        let mut p = progress.lock().unwrap();
-        p.skipped += 1;
+        let Some(outcome) = outcome else {
+            p.start_test(name);
+            continue;
+        };
+        // A teardown ERROR repeats a node id that already reported a result
+        if p.test_durations.contains_key(&name) && !p.running_tests.contains_key(&name) {
+            continue;
+        }
+        match outcome {
+            "PASSED" | "XPASS" => p.passed += 1,
+            "FAILED" | "ERROR" => p.failed += 1,
+            _ => p.skipped += 1,
+        }
+        p.finish_test(&name);
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test::{TestPhase, TestProgress};
     use std::path::PathBuf;
+    use std::sync::{Arc, Mutex};
 
     #[test]
     fn test_detect_pytest_config() {
@@ -379,6 +550,68 @@ mod tests {
         assert!(result.failures[0].name.contains("intentional_failure"));
     }
 
+    #[test]
+    fn test_parse_pytest_json_report_after_verbose_output() {
+        let stdout = concat!(
+            "tests/test_a.py::test_cfg[{'a': 1}] PASSED [100%]\n",
+            r#"{"summary":{"passed":1,"total":1,"collected":1},"tests":[]}"#,
+            "\n"
+        );
+        let result = parse_pytest_json_report(stdout, "", 0);
+        assert_eq!(result.summary.passed, 1);
+    }
+
+    #[test]
+    fn test_update_progress_tracks_running_tests_across_chunks() {
+        let progress = Arc::new(Mutex::new(TestProgress::new()));
+        update_progress("collected 3 items\n\n", &progress);
+        update_progress("tests/test_a.py::test_slow ", &progress);
+        {
+            let p = progress.lock().unwrap();
+            assert_eq!(p.phase, TestPhase::Running);
+            assert!(p.running_tests.contains_key("tests/test_a.py::test_slow"));
+        }
+        update_progress(
+            "PASSED [ 33%]\ntests/test_a.py::test_bad FAILED [ 66%]\n",
+            &progress,
+        );
+        update_progress(
+            "tests/test_b.py::test_skip SKIPPED (no gpu) [100%]\n",
+            &progress,
+        );
+        // Short summary repeats the failure; it must not be counted again
+        update_progress(
+            "FAILED tests/test_a.py::test_bad - assert 1 == 2\n",
+            &progress,
+        );
+
+        let p = progress.lock().unwrap();
+        assert_eq!((p.passed, p.failed, p.skipped), (1, 1, 1));
+        assert!(p.running_tests.is_empty());
+        assert!(p.test_durations.contains_key("tests/test_a.py::test_slow"));
+    }
+
+    #[test]
+    fn test_update_progress_counts_teardown_error_once() {
+        let progress = Arc::new(Mutex::new(TestProgress::new()));
+        update_progress("tests/test_a.py::test_x ", &progress);
+        update_progress(
+            "PASSED [100%]\ntests/test_a.py::test_x ERROR [100%]\n",
+            &progress,
+        );
+        let p = progress.lock().unwrap();
+        assert_eq!((p.passed, p.failed), (1, 0));
+    }
+
+    #[test]
+    fn test_parse_verbose_line_xdist() {
+        assert_eq!(
+            parse_verbose_line("[gw1] [ 50%] FAILED tests/test_a.py::test_x"),
+            (Some("tests/test_a.py::test_x"), Some("FAILED"))
+        );
+        assert_eq!(parse_verbose_line("E   assert 1 == 2"), (None, None));
+    }
+
     #[test]
     fn test_suite_command_uses_uv_when_uv_lock_exists() {
         let adapter = PytestAdapter;
@@ -392,6 +625,8 @@ mod tests {
         assert_eq!(cmd.args[0], "run");
         assert_eq!(cmd.args[1], "pytest");
         assert!(cmd.args.contains(&"--json-report".to_string()));
+        assert!(cmd.args.contains(&"-v".to_string()));
+        assert_eq!(cmd.env["PYTHONUNBUFFERED"], "1");
     }
 
     #[test]
@@ -463,4 +698,44 @@ mod tests {
         assert!(!traces.is_empty());
         assert!(traces.iter().any(|t| t.contains("audio")));
     }
+
+    #[test]
+    fn test_apply_priority_order_loads_plugin() {
+        let adapter = PytestAdapter;
+        let dir = tempfile::tempdir().unwrap();
+        let plugin_dir = dir.path().join("plugin");
+        let mut cmd = adapter
+            .suite_command(dir.path(), None, &HashMap::new())
+            .unwrap();
+        cmd.env.insert("PYTHONPATH".into(), "/work/src".into());
+        let priority = vec!["tests/test_api.py::test_get".to_string()];
+
+        assert!(apply_priority_plugin(&mut cmd, &priority, &plugin_dir));
+        assert!(cmd
+            .args
+            .ends_with(&["-p".to_string(), "strobe_priority".to_string()]));
+        // Prepended to the run's own PYTHONPATH, not replacing it
+        let python_path: Vec<PathBuf> = std::env::split_paths(&cmd.env["PYTHONPATH"]).collect();
+        assert_eq!(
+            python_path,
+            vec![plugin_dir.clone(), PathBuf::from("/work/src")]
+        );
+        assert_eq!(
+            cmd.env["STROBE_TEST_PRIORITY"],
+            r#"["tests/test_api.py::test_get"]"#
+        );
+        assert!(plugin_dir.join("strobe_priority.py").exists());
+    }
+
+    #[test]
+    fn test_parse_test_list() {
+        let stdout = "tests/test_api.py::test_get\ntests/test_api.py::TestAuth::test_login[admin]\n\n2 tests collected in 0.01s\n";
+        let tests = PytestAdapter.parse_test_list(stdout);
+        assert_eq!(tests.len(), 2);
+        assert_eq!(
+            tests[1].name,
+            "tests/test_api.py::TestAuth::test_login[admin]"
+        );
+        assert_eq!(tests[1].file.as_deref(), Some("tests/test_api.py"));
+    }
 }
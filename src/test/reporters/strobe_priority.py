@@ -0,0 +1,39 @@
+# Strobe pytest plugin — reorders collected items for order: "fail-fast-smart".
+# Loaded with `-p strobe_priority` (directory added to PYTHONPATH).
+#
+# STROBE_TEST_PRIORITY is a JSON array, most urgent first. Each entry is a
+# nodeid ("tests/test_api.py::test_get") or a test-file path ("tests/test_api.py").
+# Items keep their collection order within the same rank; unmatched items run last.
+
+import json
+import os
+
+import pytest
+
+
+def _load_priority():
+    try:
+        entries = json.loads(os.environ.get("STROBE_TEST_PRIORITY", "[]"))
+    except ValueError:
+        return []
+    return [e for e in entries if isinstance(e, str) and e]
+
+
+@pytest.hookimpl(trylast=True)
+def pytest_collection_modifyitems(session, config, items):
+    priority = _load_priority()
+    if not priority:
+        return
+
+    exact = {}
+    for rank, entry in enumerate(priority):
+        exact.setdefault(entry, rank)
+
+    def rank_of(item):
+        nodeid = item.nodeid
+        if nodeid in exact:
+            return exact[nodeid]
+        path = nodeid.split("::", 1)[0]
+        return exact.get(path, len(priority))
+
+    items.sort(key=rank_of)
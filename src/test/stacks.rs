@@ -1,16 +1,7 @@
 use super::adapter::ThreadStack;
 
-/// Check if a process is alive. Returns true if the process exists,
-/// even if we lack permission to signal it (EPERM).
-pub fn is_process_alive(pid: u32) -> bool {
-    let result = unsafe { libc::kill(pid as i32, 0) };
-    if result == 0 {
-        return true;
-    }
-    std::io::Error::last_os_error().raw_os_error() == Some(libc::EPERM)
-}
-
 /// Collect all descendant PIDs of a process via recursive `pgrep -P`.
+#[cfg(unix)]
 fn collect_descendants(pid: u32, result: &mut Vec<i32>) {
     let output = std::process::Command::new("pgrep")
         .args(["-P", &pid.to_string()])
@@ -31,6 +22,7 @@ fn collect_descendants(pid: u32, result: &mut Vec<i32>) {
 /// Kill a process and its entire descendant tree.
 /// Finds all children recursively via `pgrep -P`, kills bottom-up (leaves first),
 /// then kills the root. Also reaps zombies.
+#[cfg(unix)]
 pub fn kill_process_tree(pid: u32) {
     let mut descendants = Vec::new();
     collect_descendants(pid, &mut descendants);
@@ -46,15 +38,11 @@ pub fn kill_process_tree(pid: u32) {
 
     // Kill descendants bottom-up (deepest children first)
     for &child_pid in &descendants {
-        unsafe {
-            libc::kill(child_pid, libc::SIGKILL);
-        }
+        crate::process::kill(child_pid as u32);
     }
 
     // Kill the root process
-    unsafe {
-        libc::kill(pid as i32, libc::SIGKILL);
-    }
+    crate::process::kill(pid);
 
     // Brief wait for signals to be delivered
     std::thread::sleep(std::time::Duration::from_millis(50));
@@ -70,6 +58,14 @@ pub fn kill_process_tree(pid: u32) {
     }
 }
 
+/// Kill a process and its entire descendant tree via `taskkill /T`.
+#[cfg(windows)]
+pub fn kill_process_tree(pid: u32) {
+    let _ = std::process::Command::new("taskkill")
+        .args(["/PID", &pid.to_string(), "/T", "/F"])
+        .output();
+}
+
 /// Capture thread stacks using OS-level tools. Works for native code (Rust, C, C++).
 pub fn capture_native_stacks(pid: u32) -> Vec<ThreadStack> {
     #[cfg(target_os = "macos")]
@@ -1,8 +1,10 @@
+use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 
 use super::adapter::ThreadStack;
 use super::TestProgress;
+use crate::db::BaselineStats;
 
 /// Tracks the suspicion state for stuck detection. The triplet of fields
 /// (since, zero_delta_count, constant_high_count) is reset together
@@ -38,6 +40,39 @@ impl SuspicionState {
 /// that show non-zero but minimal CPU usage.
 const PER_TEST_STALL_THRESHOLD: Duration = Duration::from_secs(30);
 
+/// How long CPU signals must look suspicious before stacks are sampled.
+const IDLE_WINDOW: Duration = Duration::from_secs(6);
+
+/// Fewer passing runs than this and a baseline is too noisy to trust.
+const MIN_BASELINE_SAMPLES: u32 = 3;
+
+/// A test is within its normal range until mean + this many stddevs.
+const BASELINE_STDDEV_FACTOR: u64 = 3;
+
+/// Longest a test normally runs according to its baseline. The stddev is
+/// floored at 10% of the mean so perfectly steady tests still get slack.
+fn baseline_limit(stats: &BaselineStats) -> Duration {
+    let spread = stats.stddev_ms.max(stats.mean_ms / 10);
+    Duration::from_millis(stats.mean_ms + BASELINE_STDDEV_FACTOR * spread)
+}
+
+/// True when a test is historically slower than the idle window and hasn't
+/// yet run past its baseline limit — quiet stretches are its normal behavior.
+fn within_baseline(stats: &BaselineStats, elapsed: Duration) -> bool {
+    Duration::from_millis(stats.mean_ms) > IDLE_WINDOW && elapsed < baseline_limit(stats)
+}
+
+fn baseline_context(stats: &BaselineStats, elapsed: Duration) -> String {
+    format!(
+        "baseline {:.1}s ± {:.1}s over {} passing runs, running {:.1}s ({:.1}× mean)",
+        stats.mean_ms as f64 / 1000.0,
+        stats.stddev_ms as f64 / 1000.0,
+        stats.samples,
+        elapsed.as_secs_f64(),
+        elapsed.as_millis() as f64 / stats.mean_ms.max(1) as f64
+    )
+}
+
 /// Multi-signal stuck detector — continuous advisory monitor.
 /// Runs in parallel with test subprocess, monitors:
 /// 1. CPU time delta (every 2s)
@@ -60,6 +95,8 @@ pub struct StuckDetector {
     /// Returns true if any threads are paused at breakpoints for this session.
     /// When set, suppresses deadlock diagnosis when breakpoints are active.
     has_paused_threads: Option<Arc<dyn Fn() -> bool + Send + Sync>>,
+    /// Per-test duration baselines; thresholds adapt to tests that have them.
+    baselines: HashMap<String, BaselineStats>,
 }
 
 impl StuckDetector {
@@ -69,6 +106,7 @@ impl StuckDetector {
             hard_timeout_ms,
             progress,
             has_paused_threads: None,
+            baselines: HashMap::new(),
         }
     }
 
@@ -77,6 +115,19 @@ impl StuckDetector {
         self
     }
 
+    pub fn with_baselines(mut self, mut baselines: HashMap<String, BaselineStats>) -> Self {
+        baselines.retain(|_, stats| stats.samples >= MIN_BASELINE_SAMPLES);
+        self.baselines = baselines;
+        self
+    }
+
+    /// Baseline and elapsed time of the running test, if it has a usable baseline.
+    fn current_baseline(&self) -> Option<(BaselineStats, Duration)> {
+        let p = self.progress.lock().unwrap();
+        let stats = *self.baselines.get(&p.current_test()?)?;
+        Some((stats, p.current_test_started_at()?.elapsed()))
+    }
+
     fn current_phase(&self) -> super::TestPhase {
         self.progress.lock().unwrap().phase.clone()
     }
@@ -89,7 +140,13 @@ impl StuckDetector {
         self.progress.lock().unwrap().current_test_started_at()
     }
 
-    fn write_warning(&self, diagnosis: &str, idle_ms: u64) {
+    fn write_warning(&self, diagnosis: &str, idle_ms: u64, suggested_traces: Vec<String>) {
+        let diagnosis = match self.current_baseline() {
+            Some((stats, elapsed)) => {
+                format!("{} ({})", diagnosis, baseline_context(&stats, elapsed))
+            }
+            None => diagnosis.to_string(),
+        };
         let mut p = self.progress.lock().unwrap();
         let test_name = p.current_test();
         // Clear any previous warning for this test (replace, don't accumulate)
@@ -97,8 +154,8 @@ impl StuckDetector {
         p.warnings.push(super::StuckWarning {
             test_name,
             idle_ms,
-            diagnosis: diagnosis.to_string(),
-            suggested_traces: vec![],
+            diagnosis,
+            suggested_traces,
         });
     }
 
@@ -116,7 +173,7 @@ impl StuckDetector {
         let mut prev_test: Option<String> = None;
 
         loop {
-            if !super::stacks::is_process_alive(self.pid) {
+            if !crate::process::is_alive(self.pid) {
                 return; // Process exited
             }
 
@@ -150,6 +207,7 @@ impl StuckDetector {
                     self.write_warning(
                         "Hard timeout reached — consider stopping the test with debug_stop(sessionId)",
                         start.elapsed().as_millis() as u64,
+                        vec![],
                     );
                     // Keep running — LLM may want to investigate before killing
                     tokio::time::sleep(Duration::from_secs(5)).await;
@@ -213,9 +271,19 @@ impl StuckDetector {
                     self.clear_warnings();
                 }
 
+                // A historically slow test that is still inside its normal
+                // range: long quiet or busy stretches are expected, not stuck
+                if suspicion.since.is_some()
+                    && self
+                        .current_baseline()
+                        .is_some_and(|(stats, elapsed)| within_baseline(&stats, elapsed))
+                {
+                    suspicion.reset();
+                }
+
                 // After ~6s of suspicious CPU signals, confirm with stack sampling
                 if let Some(since) = suspicion.since {
-                    if since.elapsed() > Duration::from_secs(6) {
+                    if since.elapsed() > IDLE_WINDOW {
                         let diagnosis_type = if suspicion.zero_delta_count >= 3 {
                             "deadlock"
                         } else if suspicion.constant_high_count >= 3 {
@@ -228,7 +296,11 @@ impl StuckDetector {
 
                         if let Some(diagnosis) = self.confirm_with_stacks(diagnosis_type).await {
                             let idle_ms = since.elapsed().as_millis() as u64;
-                            self.write_warning(&diagnosis, idle_ms);
+                            self.write_warning(
+                                &diagnosis,
+                                idle_ms,
+                                suggested_traces(diagnosis_type),
+                            );
                             // DON'T return — continue monitoring
                             // Reset suspicious counters but keep the warning
                         }
@@ -243,9 +315,13 @@ impl StuckDetector {
                     let test_started = self.current_test_started_at();
                     if let Some(started) = test_started {
                         let test_elapsed = started.elapsed();
-                        if test_elapsed > PER_TEST_STALL_THRESHOLD
-                            && delta < sample_interval_ns / 10
-                        {
+                        // Tests with a baseline stall only once past their usual limit
+                        let threshold = self
+                            .current_baseline()
+                            .map_or(PER_TEST_STALL_THRESHOLD, |(stats, _)| {
+                                baseline_limit(&stats).max(PER_TEST_STALL_THRESHOLD)
+                            });
+                        if test_elapsed > threshold && delta < sample_interval_ns / 10 {
                             let test_name = self.current_test().unwrap_or_default();
                             let elapsed_s = test_elapsed.as_secs();
                             let cpu_pct = (delta as f64 / sample_interval_ns as f64) * 100.0;
@@ -256,6 +332,7 @@ impl StuckDetector {
                                     test_name, elapsed_s, cpu_pct
                                 ),
                                 test_elapsed.as_millis() as u64,
+                                suggested_traces("stall"),
                             );
                         }
                     }
@@ -284,7 +361,7 @@ impl StuckDetector {
         tokio::time::sleep(Duration::from_secs(2)).await;
 
         // Check if process exited or suites finished during wait
-        if !super::stacks::is_process_alive(self.pid) {
+        if !crate::process::is_alive(self.pid) {
             return None;
         }
         if self.current_phase() == super::TestPhase::SuitesFinished {
@@ -301,6 +378,20 @@ impl StuckDetector {
         .unwrap_or_default();
 
         if stacks_match(&stacks1, &stacks2) {
+            // Threads asleep on locks: name who holds what instead
+            if matches!(diagnosis_type, "deadlock" | "stall") {
+                let locks = tokio::time::timeout(
+                    Duration::from_secs(5),
+                    tokio::task::spawn_blocking(move || super::deadlock::diagnose(pid)),
+                )
+                .await
+                .ok()
+                .and_then(|r| r.ok())
+                .flatten();
+                if let Some(diagnosis) = locks {
+                    return Some(diagnosis);
+                }
+            }
             let diagnosis = match diagnosis_type {
                 "deadlock" => "Deadlock: 0% CPU, stacks unchanged across samples",
                 "infinite_loop" => "Infinite loop: 100% CPU, stacks unchanged across samples",
@@ -316,6 +407,15 @@ impl StuckDetector {
     }
 }
 
+/// Traces that help explain a diagnosis: threads parked with no CPU are
+/// usually waiting on a lock, which `@locks` shows with its holder.
+fn suggested_traces(diagnosis_type: &str) -> Vec<String> {
+    match diagnosis_type {
+        "deadlock" | "stall" => vec![crate::mcp::LOCKS_PATTERN.to_string()],
+        _ => vec![],
+    }
+}
+
 /// Compare two stack snapshots. Returns true if they represent the same
 /// stuck state (top N frames are identical for all threads).
 fn stacks_match(a: &[ThreadStack], b: &[ThreadStack]) -> bool {
@@ -604,6 +704,66 @@ mod tests {
         assert_eq!(PER_TEST_STALL_THRESHOLD, Duration::from_secs(30));
     }
 
+    #[test]
+    fn test_baseline_limits() {
+        let slow = BaselineStats {
+            mean_ms: 20_000,
+            stddev_ms: 4_000,
+            samples: 5,
+        };
+        assert_eq!(baseline_limit(&slow), Duration::from_secs(32));
+        assert!(within_baseline(&slow, Duration::from_secs(25)));
+        assert!(!within_baseline(&slow, Duration::from_secs(40)));
+
+        // Zero variance still leaves 30% slack
+        let steady = BaselineStats {
+            mean_ms: 10_000,
+            stddev_ms: 0,
+            samples: 10,
+        };
+        assert_eq!(baseline_limit(&steady), Duration::from_secs(13));
+
+        // Fast tests never outlast the idle window — no suppression
+        let fast = BaselineStats {
+            mean_ms: 200,
+            stddev_ms: 50,
+            samples: 10,
+        };
+        assert!(!within_baseline(&fast, Duration::from_millis(100)));
+
+        let context = baseline_context(&slow, Duration::from_secs(40));
+        assert!(context.contains("baseline 20.0s ± 4.0s over 5 passing runs"));
+        assert!(context.contains("2.0× mean"));
+    }
+
+    #[test]
+    fn test_with_baselines_drops_thin_history() {
+        let progress = Arc::new(Mutex::new(super::super::TestProgress::new()));
+        let stats = |samples| BaselineStats {
+            mean_ms: 1000,
+            stddev_ms: 100,
+            samples,
+        };
+        let detector =
+            StuckDetector::new(1, 5000, Arc::clone(&progress)).with_baselines(HashMap::from([
+                ("steady".to_string(), stats(5)),
+                ("new".to_string(), stats(1)),
+            ]));
+        assert!(detector.baselines.contains_key("steady"));
+        assert!(!detector.baselines.contains_key("new"));
+
+        progress.lock().unwrap().start_test("steady".to_string());
+        let (found, _) = detector.current_baseline().unwrap();
+        assert_eq!(found.mean_ms, 1000);
+    }
+
+    #[test]
+    fn test_suggested_traces_for_deadlocks() {
+        assert_eq!(suggested_traces("deadlock"), vec!["@locks".to_string()]);
+        assert_eq!(suggested_traces("stall"), vec!["@locks".to_string()]);
+        assert!(suggested_traces("infinite_loop").is_empty());
+    }
+
     #[test]
     fn test_stacks_match_empty() {
         assert!(!stacks_match(&[], &[]));
@@ -1,7 +1,7 @@
 use regex::Regex;
 use std::collections::HashMap;
 use std::path::Path;
-use std::sync::{Arc, Mutex};
+use std::sync::{Arc, Mutex, OnceLock};
 
 use super::adapter::*;
 use super::TestProgress;
@@ -27,6 +27,14 @@ impl TestAdapter for UnittestAdapter {
         "unittest"
     }
 
+    fn version_commands(&self) -> &'static [&'static [&'static str]] {
+        &[&["python3", "--version"]]
+    }
+
+    fn lockfiles(&self) -> &'static [&'static str] {
+        PYTHON_LOCKFILES
+    }
+
     fn suite_command(
         &self,
         _project_root: &Path,
@@ -50,16 +58,27 @@ impl TestAdapter for UnittestAdapter {
         })
     }
 
-    fn single_test_command(&self, _root: &Path, test_name: &str) -> crate::Result<TestCommand> {
-        // test_name format: "test_module.TestClass.test_method"
+    fn single_test_command(&self, root: &Path, test_name: &str) -> crate::Result<TestCommand> {
+        // Names from all_tests may carry a subtest suffix; rerun the whole method
+        let test_name = test_id(test_name);
+        let mut args: Vec<String> = vec!["-m".into(), "unittest".into()];
+        if is_importable_id(root, test_name) {
+            // "test_module.TestClass.test_method"
+            args.extend([test_name.into(), "-v".into()]);
+        } else {
+            // "TestClass.test_method", "test_method": -k substring match over discovery
+            args.extend([
+                "discover".into(),
+                "-v".into(),
+                "-s".into(),
+                ".".into(),
+                "-k".into(),
+                test_name.into(),
+            ]);
+        }
         Ok(TestCommand {
             program: "python3".into(),
-            args: vec![
-                "-m".into(),
-                "unittest".into(),
-                test_name.into(),
-                "-v".into(),
-            ],
+            args,
             env: HashMap::new(),
             cwd: None,
             remove_env: vec![],
@@ -71,24 +90,7 @@ impl TestAdapter for UnittestAdapter {
     }
 
     fn suggest_traces(&self, failure: &TestFailure) -> Vec<String> {
-        // Same logic as pytest
-        let mut traces = Vec::new();
-
-        if let Some(ref file) = failure.file {
-            if let Some(filename) = Path::new(file).file_stem().and_then(|s| s.to_str()) {
-                let module = filename.strip_prefix("test_").unwrap_or(filename);
-                traces.push(format!("{}.*", module));
-            }
-            traces.push(format!(
-                "@file:{}",
-                Path::new(file)
-                    .file_name()
-                    .unwrap_or_default()
-                    .to_string_lossy()
-            ));
-        }
-
-        traces
+        extract_python_traces_from_unittest(failure)
     }
 
     fn capture_stacks(&self, pid: u32) -> Vec<ThreadStack> {
@@ -135,15 +137,120 @@ fn has_unittest_test_files(root: &Path) -> bool {
     false
 }
 
+/// Status line in unittest `-v` output: `<description> ... <outcome>`.
+/// `test` is None on the second line of a docstring description and
+/// `outcome` is None while the test is still running.
+#[derive(Debug, PartialEq)]
+struct VerboseLine {
+    test: Option<TestRef>,
+    outcome: Option<(TestStatus, String)>,
+}
+
+/// A test named by a unittest description, down to the subtest.
+#[derive(Debug, Clone, PartialEq)]
+struct TestRef {
+    /// "test_module.TestClass.test_method"
+    id: String,
+    /// "(i=1)" or "[message] (i=1)" for a subTest() block
+    subtest: Option<String>,
+}
+
+impl TestRef {
+    fn name(&self) -> String {
+        match &self.subtest {
+            Some(sub) => format!("{} {}", self.id, sub),
+            None => self.id.clone(),
+        }
+    }
+}
+
+/// Parse a test description: `test_method (module.Class)` before Python 3.11,
+/// `test_method (module.Class.test_method)` since, optionally followed by a
+/// subtest description.
+fn parse_description(desc: &str) -> Option<TestRef> {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    let re = RE.get_or_init(|| Regex::new(r"^(\w+) \(([\w.]+)\)(?: (.+))?$").unwrap());
+    let caps = re.captures(desc.trim())?;
+    let method = &caps[1];
+    let class_path = &caps[2];
+    let id = if class_path.ends_with(&format!(".{}", method)) {
+        class_path.to_string()
+    } else {
+        format!("{}.{}", class_path, method)
+    };
+    Some(TestRef {
+        id,
+        subtest: caps.get(3).map(|m| m.as_str().to_string()),
+    })
+}
+
+fn parse_outcome(text: &str) -> Option<(TestStatus, String)> {
+    let text = text.trim();
+    let status = match text {
+        "ok" | "expected failure" => TestStatus::Pass,
+        "FAIL" | "ERROR" | "unexpected success" => TestStatus::Fail,
+        _ if text.starts_with("skipped") => TestStatus::Skip,
+        _ => return None,
+    };
+    Some((status, text.to_string()))
+}
+
+fn parse_verbose_line(line: &str) -> Option<VerboseLine> {
+    let line = line.trim();
+    if let Some((head, outcome)) = line
+        .rsplit_once(" ... ")
+        .or_else(|| line.strip_suffix(" ...").map(|head| (head, "")))
+    {
+        // Before 3.11 a test whose subtests failed leaves its line open and
+        // the next test's description follows on the same line.
+        let desc = head.rsplit_once(" ... ").map_or(head, |(_, desc)| desc);
+        return Some(VerboseLine {
+            test: parse_description(desc),
+            outcome: parse_outcome(outcome),
+        });
+    }
+    // First line of a description with a docstring; the status follows on
+    // the docstring line. Only test* methods, so test output can't pose as one.
+    if line.starts_with("test") {
+        if let Some(test) = parse_description(line) {
+            return Some(VerboseLine {
+                test: Some(test),
+                outcome: None,
+            });
+        }
+    }
+    // Result written after output the test printed itself
+    parse_outcome(line).map(|outcome| VerboseLine {
+        test: None,
+        outcome: Some(outcome),
+    })
+}
+
+/// Strip a subtest suffix: "mod.Class.test_x (i=1)" → "mod.Class.test_x".
+fn test_id(name: &str) -> &str {
+    name.split_once(' ').map_or(name, |(id, _)| id)
+}
+
+/// Whether `name` starts with a module or package under `root`, so unittest
+/// can load it by dotted path.
+fn is_importable_id(root: &Path, name: &str) -> bool {
+    let Some((module, _)) = name.split_once('.') else {
+        return false;
+    };
+    root.join(format!("{}.py", module)).is_file() || root.join(module).is_dir()
+}
+
 /// Parse unittest verbose output.
-/// Format:
+/// Format (Python 3.11+ repeats the method in the class path):
 /// ```text
-/// test_method (test_module.TestClass) ... ok
-/// test_another (test_module.TestClass) ... FAIL
-/// test_skip (test_module.TestClass) ... skipped 'reason'
+/// test_method (test_module.TestClass.test_method) ... ok
+/// test_another (test_module.TestClass.test_another) ... FAIL
+/// test_even (test_module.TestClass.test_even) ...
+///   test_even (test_module.TestClass.test_even) (i=1) ... FAIL
+/// test_skip (test_module.TestClass.test_skip) ... skipped 'reason'
 ///
 /// ======================================================================
-/// FAIL: test_another (test_module.TestClass)
+/// FAIL: test_another (test_module.TestClass.test_another)
 /// ----------------------------------------------------------------------
 /// Traceback (most recent call last):
 ///   File "/path/to/test.py", line 15, in test_another
@@ -151,10 +258,12 @@ fn has_unittest_test_files(root: &Path) -> bool {
 /// AssertionError: 1 != 2
 ///
 /// ----------------------------------------------------------------------
-/// Ran 3 tests in 0.001s
+/// Ran 4 tests in 0.001s
 ///
-/// FAILED (failures=1, skipped=1)
+/// FAILED (failures=2, skipped=1)
 /// ```
+/// Each failing subTest() is its own entry; a test whose subtests failed
+/// prints no status of its own.
 fn parse_unittest_output(stdout: &str, stderr: &str) -> TestResult {
     let combined = format!("{}\n{}", stdout, stderr);
 
@@ -162,88 +271,99 @@ fn parse_unittest_output(stdout: &str, stderr: &str) -> TestResult {
     let mut failed = 0u32;
     let mut skipped = 0u32;
     let mut failures = Vec::new();
-    let mut all_tests = Vec::new();
-
-    // Parse test result lines
-    let test_line_re =
-        Regex::new(r"^(\w+)\s+\(([^)]+)\)\s+\.\.\.\s+(ok|FAIL|ERROR|skipped)").unwrap();
+    let mut all_tests: Vec<TestDetail> = Vec::new();
+
+    let mut record =
+        |all_tests: &mut Vec<TestDetail>, name: String, status: TestStatus, message| {
+            match status {
+                TestStatus::Pass => passed += 1,
+                TestStatus::Skip => skipped += 1,
+                _ => failed += 1,
+            }
+            all_tests.push(TestDetail {
+                name,
+                status,
+                duration_ms: 0,
+                stdout: None,
+                stderr: None,
+                message,
+            });
+        };
 
+    // The test a status without a description of its own belongs to
+    let mut pending: Option<TestRef> = None;
     for line in combined.lines() {
-        if let Some(caps) = test_line_re.captures(line) {
-            let test_method = caps.get(1).map(|m| m.as_str()).unwrap_or("");
-            let test_class = caps.get(2).map(|m| m.as_str()).unwrap_or("");
-            let outcome = caps.get(3).map(|m| m.as_str()).unwrap_or("");
-
-            let test_name = format!("{}.{}", test_class, test_method);
-
-            match outcome {
-                "ok" => {
-                    passed += 1;
-                    all_tests.push(TestDetail {
-                        name: test_name,
-                        status: TestStatus::Pass,
-                        duration_ms: 0,
-                        stdout: None,
-                        stderr: None,
-                        message: None,
-                    });
-                }
-                "FAIL" | "ERROR" => {
-                    failed += 1;
-                    all_tests.push(TestDetail {
-                        name: test_name.clone(),
-                        status: TestStatus::Fail,
-                        duration_ms: 0,
-                        stdout: None,
-                        stderr: None,
-                        message: Some(format!("{} test", outcome)),
-                    });
-                }
-                "skipped" => {
-                    skipped += 1;
-                    all_tests.push(TestDetail {
-                        name: test_name,
-                        status: TestStatus::Skip,
-                        duration_ms: 0,
-                        stdout: None,
-                        stderr: None,
-                        message: Some("skipped".to_string()),
-                    });
-                }
-                _ => {}
+        let Some(parsed) = parse_verbose_line(line) else {
+            continue;
+        };
+        let Some((status, outcome)) = parsed.outcome else {
+            if parsed.test.is_some() {
+                pending = parsed.test;
             }
-        }
+            continue;
+        };
+        let Some(test) = parsed.test.or_else(|| pending.take()) else {
+            continue;
+        };
+        let message = (status != TestStatus::Pass || outcome != "ok").then_some(outcome);
+        record(&mut all_tests, test.name(), status, message);
     }
 
-    // Parse failure details
-    let fail_block_re =
-        Regex::new(r"(?s)(?:FAIL|ERROR):\s+(\w+)\s+\(([^)]+)\)\s*\n-+\n(.*?)(?:\n-+|\n=+|$)")
-            .unwrap();
-
-    for caps in fail_block_re.captures_iter(&combined) {
-        let test_method = caps.get(1).map(|m| m.as_str()).unwrap_or("");
-        let test_class = caps.get(2).map(|m| m.as_str()).unwrap_or("");
-        let message_block = caps.get(3).map(|m| m.as_str()).unwrap_or("");
-
-        let test_name = format!("{}.{}", test_class, test_method);
+    // Failure blocks: "FAIL: <description>", dashes, traceback
+    let lines: Vec<&str> = combined.lines().collect();
+    let is_rule = |line: &str, c: char| line.len() >= 10 && line.chars().all(|x| x == c);
+    let file_line_re = Regex::new(r#"File "([^"]+)", line (\d+)"#).unwrap();
+    let mut i = 0;
+    while i < lines.len() {
+        let header = lines[i]
+            .strip_prefix("FAIL: ")
+            .or_else(|| lines[i].strip_prefix("ERROR: "));
+        i += 1;
+        let Some(test) = header.and_then(parse_description) else {
+            continue;
+        };
+        // A docstring line may sit between the header and the rule
+        while i < lines.len() && !is_rule(lines[i], '-') {
+            i += 1;
+        }
+        i += 1;
+        let body_start = i;
+        while i < lines.len() && !is_rule(lines[i], '=') && !is_rule(lines[i], '-') {
+            i += 1;
+        }
+        let body = lines[body_start.min(lines.len())..i].join("\n");
+        let body = body.trim();
 
-        // Try to extract file and line from traceback
-        let file_line_re = Regex::new(r#"File "([^"]+)", line (\d+)"#).unwrap();
-        let (file, line) = if let Some(caps) = file_line_re.captures(message_block) {
-            (
-                caps.get(1).map(|m| m.as_str().to_string()),
+        let (file, line) = match file_line_re.captures(body) {
+            Some(caps) => (
+                Some(caps[1].to_string()),
                 caps.get(2).and_then(|m| m.as_str().parse::<u32>().ok()),
-            )
-        } else {
-            (None, None)
+            ),
+            None => (None, None),
         };
+        let name = test.name();
+        let error = body.lines().rev().find(|l| !l.trim().is_empty());
+        match all_tests.iter_mut().find(|t| t.name == name) {
+            Some(detail) => {
+                if let Some(error) = error {
+                    detail.message = Some(error.trim().to_string());
+                }
+            }
+            // Before 3.11 verbose output doesn't list failed subtests
+            None => record(
+                &mut all_tests,
+                name.clone(),
+                TestStatus::Fail,
+                error.map(|e| e.trim().to_string()),
+            ),
+        }
 
         failures.push(TestFailure {
-            name: test_name.clone(),
+            name,
             file,
             line,
-            message: message_block.trim().to_string(),
-            rerun: Some(test_name),
+            message: body.to_string(),
+            rerun: Some(test.id),
             suggested_traces: vec![],
         });
     }
@@ -279,70 +399,131 @@ fn parse_unittest_output(stdout: &str, stderr: &str) -> TestResult {
     }
 }
 
+/// Trace patterns for a failing test, from its TestCase module path:
+/// "app.tests.test_audio.TestAudio.test_process" → `audio.*`, `app.audio.*`,
+/// `@file:test_audio.py`, plus the innermost traceback file when the error was
+/// raised outside the test module.
 fn extract_python_traces_from_unittest(failure: &TestFailure) -> Vec<String> {
     let mut traces = Vec::new();
 
-    // Extract module from class name: "test_module.TestClass"
-    if let Some(dot_pos) = failure.name.rfind('.') {
-        let module_class = &failure.name[..dot_pos];
-        if let Some(module_name) = module_class.split('.').next() {
-            // "test_module" → trace corresponding module
-            let target_module = module_name.strip_prefix("test_").unwrap_or(module_name);
-            traces.push(format!("{}.*", target_module));
+    let segments: Vec<&str> = test_id(&failure.name).split('.').collect();
+    // Drop "TestClass.test_method"
+    let modules = &segments[..segments.len().saturating_sub(2)];
+    let test_file = if let Some((test_module, packages)) = modules.split_last() {
+        let target = test_module
+            .strip_prefix("test_")
+            .or_else(|| test_module.strip_suffix("_test"))
+            .unwrap_or(test_module);
+        traces.push(format!("{}.*", target));
+        let packages: Vec<&str> = packages
+            .iter()
+            .copied()
+            .filter(|p| !matches!(*p, "tests" | "test"))
+            .collect();
+        if !packages.is_empty() {
+            traces.push(format!("{}.{}.*", packages.join("."), target));
         }
+        Some(format!("{}.py", test_module))
+    } else {
+        None
+    };
+
+    let file_name = |path: &str| {
+        Path::new(path)
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .to_string()
+    };
+    let test_file = failure.file.as_deref().map(file_name).or(test_file);
+    if let Some(ref test_file) = test_file {
+        traces.push(format!("@file:{}", test_file));
     }
 
-    if let Some(ref file) = failure.file {
-        traces.push(format!(
-            "@file:{}",
-            Path::new(file)
-                .file_name()
-                .unwrap_or_default()
-                .to_string_lossy()
-        ));
+    let file_re = Regex::new(r#"File "([^"]+)", line \d+"#).unwrap();
+    if let Some(innermost) = file_re
+        .captures_iter(&failure.message)
+        .last()
+        .map(|caps| file_name(&caps[1]))
+    {
+        if test_file.as_deref() != Some(innermost.as_str()) {
+            traces.push(format!("@file:{}", innermost));
+        }
     }
 
     traces
 }
 
-/// Parse unittest verbose output lines and update progress incrementally.
-/// unittest -v writes to stderr: "test_method (test_module.TestClass) ... ok/FAIL/skipped"
+/// Update progress from `unittest -v` output (written to stderr). unittest
+/// prints a test's description before running it and its result after, so
+/// the two can arrive in separate chunks with the test's own output between.
+/// Tests run one at a time: a test starting finishes any still open, such as
+/// one whose subtests failed (it prints no result of its own).
 pub fn update_progress(text: &str, progress: &Arc<Mutex<TestProgress>>) {
-    let re = Regex::new(r"^(\w+)\s+\(([^)]+)\)\s+\.\.\.\s+(ok|FAIL|ERROR|skipped)").unwrap();
-
+    let mut p = progress.lock().unwrap();
     for line in text.lines() {
-        let trimmed = line.trim();
-        if trimmed.is_empty() {
+        if line.starts_with("Ran ") {
+            let open: Vec<String> = p.running_tests.keys().cloned().collect();
+            for name in open {
+                p.finish_test(&name);
+            }
             continue;
         }
+        let Some(parsed) = parse_verbose_line(line) else {
+            continue;
+        };
+        if p.phase == super::TestPhase::Compiling {
+            p.phase = super::TestPhase::Running;
+        }
 
-        if let Some(caps) = re.captures(trimmed) {
-            let test_method = caps.get(1).map(|m| m.as_str()).unwrap_or("");
-            let test_class = caps.get(2).map(|m| m.as_str()).unwrap_or("");
-            let outcome = caps.get(3).map(|m| m.as_str()).unwrap_or("");
-            let test_name = format!("{}.{}", test_class, test_method);
-
-            let mut p = progress.lock().unwrap();
-            // Transition phase on first test result
-            if p.phase == super::TestPhase::Compiling {
-                p.phase = super::TestPhase::Running;
-            }
-            match outcome {
-                "ok" => {
-                    p.passed += 1;
-                    p.finish_test(&test_name);
-                }
-                "FAIL" | "ERROR" => {
-                    p.failed += 1;
-                    p.finish_test(&test_name);
-                }
-                "skipped" => {
-                    p.skipped += 1;
-                    p.finish_test(&test_name);
+        let test = match parsed.test {
+            Some(test) if test.subtest.is_none() => {
+                if !p.running_tests.contains_key(&test.id) {
+                    let open: Vec<String> = p.running_tests.keys().cloned().collect();
+                    for name in open {
+                        p.finish_test(&name);
+                    }
+                    p.start_test(test.id.clone());
                 }
-                _ => {}
+                test
             }
+            Some(subtest) => subtest,
+            // A bare result belongs to the test that is running
+            None => match p.running_tests.keys().next() {
+                Some(id) => TestRef {
+                    id: id.clone(),
+                    subtest: None,
+                },
+                None => continue,
+            },
+        };
+        let Some((status, _)) = parsed.outcome else {
+            continue;
+        };
+        match status {
+            TestStatus::Pass => p.passed += 1,
+            TestStatus::Skip => p.skipped += 1,
+            _ => p.failed += 1,
+        }
+        if test.subtest.is_none() {
+            p.finish_test(&test.id);
+            continue;
         }
+        // A subtest runs from the end of the previous one (or the start of
+        // its test) until its result is printed.
+        let elapsed = p
+            .running_tests
+            .get(&test.id)
+            .map_or(0, |started| started.elapsed().as_millis() as u64);
+        let prefix = format!("{} ", test.id);
+        let earlier: u64 = p
+            .test_durations
+            .iter()
+            .filter(|(name, _)| name.starts_with(&prefix))
+            .map(|(_, ms)| *ms)
+            .sum();
+        p.test_durations
+            .insert(test.name(), elapsed.saturating_sub(earlier));
     }
 }
 
@@ -394,4 +575,158 @@ FAILED (failures=1, skipped=1)
         assert!(!traces.is_empty());
         assert!(traces.iter().any(|t| t.contains("audio")));
     }
+
+    #[test]
+    fn test_parse_subtests() {
+        let stderr = r#"test_add (tests.test_math.TestMath.test_add) ... ok
+test_even (tests.test_math.TestMath.test_even) ...
+  test_even (tests.test_math.TestMath.test_even) (i=1) ... FAIL
+  test_even (tests.test_math.TestMath.test_even) (i=3) ... FAIL
+test_docs (tests.test_math.TestMath.test_docs)
+Checks the docs. ... ok
+
+======================================================================
+FAIL: test_even (tests.test_math.TestMath.test_even) (i=1)
+----------------------------------------------------------------------
+Traceback (most recent call last):
+  File "/p/tests/test_math.py", line 9, in test_even
+    self.assertEqual(i % 2, 0)
+AssertionError: 1 != 0
+
+======================================================================
+FAIL: test_even (tests.test_math.TestMath.test_even) (i=3)
+----------------------------------------------------------------------
+Traceback (most recent call last):
+  File "/p/tests/test_math.py", line 9, in test_even
+    self.assertEqual(i % 2, 0)
+AssertionError: 3 != 0
+
+----------------------------------------------------------------------
+Ran 3 tests in 0.002s
+
+FAILED (failures=2)
+"#;
+        let result = parse_unittest_output("", stderr);
+        assert_eq!(result.summary.passed, 2);
+        assert_eq!(result.summary.failed, 2);
+        let names: Vec<&str> = result.all_tests.iter().map(|t| t.name.as_str()).collect();
+        assert_eq!(
+            names,
+            vec![
+                "tests.test_math.TestMath.test_add",
+                "tests.test_math.TestMath.test_even (i=1)",
+                "tests.test_math.TestMath.test_even (i=3)",
+                "tests.test_math.TestMath.test_docs",
+            ]
+        );
+        assert_eq!(
+            result.all_tests[2].message.as_deref(),
+            Some("AssertionError: 3 != 0")
+        );
+        assert_eq!(result.failures.len(), 2);
+        assert_eq!(
+            result.failures[0].name,
+            "tests.test_math.TestMath.test_even (i=1)"
+        );
+        assert_eq!(
+            result.failures[0].rerun.as_deref(),
+            Some("tests.test_math.TestMath.test_even")
+        );
+        assert_eq!(result.failures[0].line, Some(9));
+    }
+
+    #[test]
+    fn test_parse_subtests_before_311() {
+        // Older verbose output names neither the method twice nor the subtests
+        let stderr = r#"test_even (test_math.TestMath) ... test_odd (test_math.TestMath) ... ok
+
+======================================================================
+FAIL: test_even (test_math.TestMath) (i=1)
+----------------------------------------------------------------------
+AssertionError: 1 != 0
+
+----------------------------------------------------------------------
+Ran 2 tests in 0.001s
+"#;
+        let result = parse_unittest_output("", stderr);
+        assert_eq!(result.summary.passed, 1);
+        assert_eq!(result.summary.failed, 1);
+        assert!(result.all_tests.iter().any(
+            |t| t.name == "test_math.TestMath.test_even (i=1)" && t.status == TestStatus::Fail
+        ));
+    }
+
+    #[test]
+    fn test_update_progress_subtests() {
+        let progress = Arc::new(Mutex::new(TestProgress::new()));
+        // Description and result arrive in separate chunks
+        update_progress("test_add (t.T.test_add) ... ", &progress);
+        {
+            let p = progress.lock().unwrap();
+            assert_eq!(p.phase, super::super::TestPhase::Running);
+            assert_eq!(p.current_test().as_deref(), Some("t.T.test_add"));
+        }
+        update_progress("ok\n", &progress);
+        update_progress(
+            "test_even (t.T.test_even) ... \n  test_even (t.T.test_even) (i=1) ... FAIL\n",
+            &progress,
+        );
+        {
+            let p = progress.lock().unwrap();
+            assert_eq!((p.passed, p.failed), (1, 1));
+            assert!(p.test_durations.contains_key("t.T.test_add"));
+            assert!(p.test_durations.contains_key("t.T.test_even (i=1)"));
+            // Still running until the next test starts
+            assert_eq!(p.current_test().as_deref(), Some("t.T.test_even"));
+        }
+        update_progress("test_skip (t.T.test_skip) ... skipped 'later'\n", &progress);
+        let p = progress.lock().unwrap();
+        assert_eq!(p.skipped, 1);
+        assert!(p.running_tests.is_empty());
+    }
+
+    #[test]
+    fn test_single_test_command() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("test_math.py"), "import unittest\n").unwrap();
+        let adapter = UnittestAdapter;
+
+        let cmd = adapter
+            .single_test_command(dir.path(), "test_math.TestMath.test_even (i=1)")
+            .unwrap();
+        assert_eq!(
+            cmd.args,
+            vec!["-m", "unittest", "test_math.TestMath.test_even", "-v"]
+        );
+
+        let cmd = adapter
+            .single_test_command(dir.path(), "TestMath.test_even")
+            .unwrap();
+        assert_eq!(&cmd.args[2..4], &["discover", "-v"]);
+        assert_eq!(
+            &cmd.args[cmd.args.len() - 2..],
+            &["-k", "TestMath.test_even"]
+        );
+    }
+
+    #[test]
+    fn test_suggest_traces_from_module_path() {
+        let failure = TestFailure {
+            name: "app.tests.test_audio.TestAudio.test_mix (rate=44100)".to_string(),
+            file: None,
+            line: None,
+            message: "Traceback (most recent call last):\n  File \"/p/app/tests/test_audio.py\", line 8, in test_mix\n  File \"/p/app/audio.py\", line 40, in mix\nValueError".to_string(),
+            rerun: None,
+            suggested_traces: vec![],
+        };
+        assert_eq!(
+            extract_python_traces_from_unittest(&failure),
+            vec![
+                "audio.*",
+                "app.audio.*",
+                "@file:test_audio.py",
+                "@file:audio.py"
+            ]
+        );
+    }
 }
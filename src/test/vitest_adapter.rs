@@ -117,6 +117,14 @@ impl TestAdapter for VitestAdapter {
         "vitest"
     }
 
+    fn version_commands(&self) -> &'static [&'static [&'static str]] {
+        &[&["node", "--version"]]
+    }
+
+    fn lockfiles(&self) -> &'static [&'static str] {
+        NODE_LOCKFILES
+    }
+
     fn suite_command(
         &self,
         project_root: &Path,
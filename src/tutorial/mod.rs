@@ -0,0 +1,213 @@
+//! Guided onboarding (`debug_tutorial`) on a bundled demo target.
+//!
+//! The demo is a small C program embedded in the binary and compiled into
+//! `~/.strobe/tutorial/` by `strobe install`, or on the first tutorial call.
+//! Each tutorial step ends in a checkpoint that the daemon verifies against
+//! the live session, so a first run shows whether launch, output capture,
+//! tracing, watches and breakpoints all work on this machine.
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+const DEMO_SOURCE: &str = include_str!("strobe_demo.c");
+const DEMO_SOURCE_NAME: &str = "strobe_demo.c";
+pub const DEMO_BINARY_NAME: &str = "strobe-demo";
+
+/// Order id the demo rejects on stderr (the step 2 answer).
+const REJECTED_ORDER_ID: &str = "7";
+
+pub struct TutorialStep {
+    pub title: &'static str,
+    /// `{binary}`, `{projectRoot}` and `{sessionId}` are filled in by `render`
+    instructions: &'static str,
+}
+
+pub const STEPS: &[TutorialStep] = &[
+    TutorialStep {
+        title: "Launch the demo",
+        instructions: "Launch the demo target: debug_launch({ command: \"{binary}\", projectRoot: \"{projectRoot}\" }). \
+            Then check in with debug_tutorial({ action: \"check\", step: 1, sessionId: <the new sessionId> }).",
+    },
+    TutorialStep {
+        title: "Read stderr",
+        instructions: "The demo rejects one order. Find it: debug_query({ sessionId: \"{sessionId}\", eventType: \"stderr\" }). \
+            Check in with debug_tutorial({ action: \"check\", step: 2, sessionId: \"{sessionId}\", answer: \"<rejected order id>\" }).",
+    },
+    TutorialStep {
+        title: "Trace function calls",
+        instructions: "Hook the order pipeline: debug_trace({ sessionId: \"{sessionId}\", add: [\"process_order\", \"validate_order\"] }). \
+            Wait a second, then debug_query({ sessionId: \"{sessionId}\", function: { equals: \"validate_order\" }, eventType: \"function_exit\" }) \
+            and look for the call that returned 0. Check in with step 3.",
+    },
+    TutorialStep {
+        title: "Watch a global",
+        instructions: "Record revenue on every processed order: debug_trace({ sessionId: \"{sessionId}\", watches: { add: [{ variable: \"g_revenue_cents\", on: [\"process_order\"] }] } }). \
+            New function events now carry watchValues. Check in with step 4.",
+    },
+    TutorialStep {
+        title: "Pause on a breakpoint",
+        instructions: "Stop inside validation: debug_breakpoint({ sessionId: \"{sessionId}\", add: [{ function: \"validate_order\" }] }). \
+            Once it hits, inspect with debug_memory and resume with debug_continue({ sessionId: \"{sessionId}\" }). \
+            Check in with step 5, then stop the demo: debug_session({ action: \"stop\", sessionId: \"{sessionId}\" }).",
+    },
+];
+
+impl TutorialStep {
+    pub fn render(&self, binary: &Path, project_root: &Path, session_id: Option<&str>) -> String {
+        self.instructions
+            .replace("{binary}", &binary.to_string_lossy())
+            .replace("{projectRoot}", &project_root.to_string_lossy())
+            .replace("{sessionId}", session_id.unwrap_or("<sessionId>"))
+    }
+}
+
+/// `~/.strobe/tutorial`: demo source, binary and the session's projectRoot.
+pub fn tutorial_dir() -> PathBuf {
+    dirs::home_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join(".strobe")
+        .join("tutorial")
+}
+
+/// Compile the embedded demo into `dir` unless an up-to-date build is there.
+/// Returns the binary path.
+pub fn build_demo(dir: &Path) -> crate::Result<PathBuf> {
+    let source = dir.join(DEMO_SOURCE_NAME);
+    let binary = dir.join(DEMO_BINARY_NAME);
+    let current = std::fs::read_to_string(&source).ok();
+    if binary.exists() && current.as_deref() == Some(DEMO_SOURCE) {
+        return Ok(binary);
+    }
+
+    std::fs::create_dir_all(dir)?;
+    std::fs::write(&source, DEMO_SOURCE)?;
+    let output = Command::new("cc")
+        .args(["-g", "-O0", "-o"])
+        .arg(&binary)
+        .arg(&source)
+        .output()
+        .map_err(|e| {
+            crate::Error::Internal(format!(
+                "Cannot build the tutorial demo: failed to run cc ({}). Install a C compiler.",
+                e
+            ))
+        })?;
+    if !output.status.success() {
+        return Err(crate::Error::Internal(format!(
+            "Tutorial demo build failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    if cfg!(target_os = "macos") {
+        let _ = Command::new("dsymutil").arg(&binary).status();
+    }
+    Ok(binary)
+}
+
+/// What the daemon knows about the tutorial session, gathered for a checkpoint.
+#[derive(Debug, Default)]
+pub struct SessionFacts {
+    pub is_demo: bool,
+    pub running: bool,
+    pub rejection_logged: bool,
+    pub traced_calls: u64,
+    pub watching_revenue: bool,
+    pub breakpoint_hits: u64,
+}
+
+/// Verify a step's checkpoint. Returns (passed, detail).
+pub fn check(step: u32, facts: &SessionFacts, answer: Option<&str>) -> (bool, String) {
+    if !facts.is_demo {
+        return (
+            false,
+            "This session isn't running the tutorial demo. Launch the binary from step 1.".into(),
+        );
+    }
+    match step {
+        1 if facts.running => (true, "Demo is running under strobe.".into()),
+        1 => (false, "The session has stopped. Launch the demo again.".into()),
+        2 if !facts.rejection_logged => (
+            false,
+            "No rejection on stderr yet. Give the demo a moment, then query again.".into(),
+        ),
+        2 => match answer.map(str::trim) {
+            Some(REJECTED_ORDER_ID) => (true, "Right: order 7 has a negative quantity.".into()),
+            Some(other) => (
+                false,
+                format!("Order {} isn't the rejected one. Read the stderr text again.", other),
+            ),
+            None => (false, "Pass the rejected order id as answer.".into()),
+        },
+        3 if facts.traced_calls > 0 => (
+            true,
+            format!("{} process_order call(s) recorded.", facts.traced_calls),
+        ),
+        3 => (
+            false,
+            "No process_order calls recorded yet. Check debug_trace's hookedFunctions, then wait a second.".into(),
+        ),
+        4 if facts.watching_revenue => (true, "g_revenue_cents is being watched.".into()),
+        4 => (false, "No active watch on g_revenue_cents.".into()),
+        5 if facts.breakpoint_hits > 0 => (
+            true,
+            "Breakpoint hit. Tutorial complete: stop the session with debug_session.".into(),
+        ),
+        5 => (
+            false,
+            "No breakpoint hit yet. Set one on validate_order; it pauses within half a second.".into(),
+        ),
+        _ => (false, format!("There is no step {}.", step)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_fills_placeholders() {
+        let text = STEPS[1].render(Path::new("/t/strobe-demo"), Path::new("/t"), Some("demo-1"));
+        assert!(text.contains("sessionId: \"demo-1\""));
+        assert!(!text.contains("{sessionId}"));
+        let launch = STEPS[0].render(Path::new("/t/strobe-demo"), Path::new("/t"), None);
+        assert!(launch.contains("command: \"/t/strobe-demo\""));
+    }
+
+    #[test]
+    fn test_checkpoints() {
+        let mut facts = SessionFacts {
+            is_demo: true,
+            running: true,
+            ..Default::default()
+        };
+        assert!(check(1, &facts, None).0);
+        assert!(!check(2, &facts, Some("7")).0, "nothing on stderr yet");
+        facts.rejection_logged = true;
+        assert!(check(2, &facts, Some(" 7 ")).0);
+        assert!(!check(2, &facts, Some("3")).0);
+        assert!(!check(3, &facts, None).0);
+        facts.traced_calls = 12;
+        assert!(check(3, &facts, None).0);
+        assert!(!check(6, &facts, None).0);
+
+        let other = SessionFacts::default();
+        assert!(!check(1, &other, None).0);
+    }
+
+    #[test]
+    fn test_build_demo() {
+        if Command::new("cc").arg("--version").output().is_err() {
+            return;
+        }
+        let dir = tempfile::tempdir().unwrap();
+        let binary = build_demo(dir.path()).unwrap();
+        assert!(binary.exists());
+        let built = std::fs::metadata(&binary).unwrap().modified().unwrap();
+        // Unchanged source: reuse the existing binary.
+        build_demo(dir.path()).unwrap();
+        assert_eq!(
+            std::fs::metadata(&binary).unwrap().modified().unwrap(),
+            built
+        );
+    }
+}
@@ -0,0 +1,59 @@
+/*
+ * strobe-demo: the debug_tutorial target.
+ *
+ * Processes the same small batch of orders twice a second for about five
+ * minutes. Order 7 has a negative quantity, fails validation and is reported
+ * on stderr; every other order adds to g_revenue_cents.
+ *
+ * Built by `strobe install` (or on first debug_tutorial call) with -g -O0 so
+ * every function and global has debug info.
+ */
+#include <stdio.h>
+#include <unistd.h>
+
+struct order {
+    int id;
+    int quantity;
+    int price_cents;
+};
+
+static const struct order ORDERS[] = {
+    {1, 2, 1299},
+    {2, 1, 4500},
+    {3, 5, 250},
+    {7, -3, 899},
+    {9, 1, 12000},
+};
+
+#define ORDER_COUNT (sizeof(ORDERS) / sizeof(ORDERS[0]))
+#define ROUNDS 600
+
+int g_processed = 0;
+long g_revenue_cents = 0;
+
+__attribute__((noinline)) int validate_order(const struct order *o) {
+    return o->quantity > 0 && o->price_cents > 0;
+}
+
+__attribute__((noinline)) long process_order(const struct order *o) {
+    if (!validate_order(o)) {
+        fprintf(stderr, "ERROR: order %d rejected: invalid quantity %d\n", o->id, o->quantity);
+        return -1;
+    }
+    long total = (long)o->quantity * o->price_cents;
+    g_revenue_cents += total;
+    g_processed++;
+    printf("order %d ok: %ld cents (revenue %ld)\n", o->id, total, g_revenue_cents);
+    return total;
+}
+
+int main(void) {
+    setvbuf(stdout, NULL, _IOLBF, 0);
+    for (int round = 0; round < ROUNDS; round++) {
+        for (unsigned i = 0; i < ORDER_COUNT; i++) {
+            process_order(&ORDERS[i]);
+        }
+        usleep(500 * 1000);
+    }
+    return 0;
+}
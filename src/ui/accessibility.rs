@@ -3,6 +3,7 @@
 //! Walks the accessibility tree for a given PID, collecting role, title, value,
 //! enabled, focused, bounds, and actions for each element.
 
+use crate::ui::permissions::Permission;
 use crate::ui::tree::{generate_id, NodeSource, Rect, UiNode};
 use crate::Result;
 use accessibility_sys::*;
@@ -37,9 +38,7 @@ pub fn query_ax_tree(pid: u32) -> Result<Vec<UiNode>> {
     if !check_accessibility_permission(false) {
         // Try with prompt on first call
         if !check_accessibility_permission(true) {
-            return Err(crate::Error::UiNotAvailable(
-                "Accessibility permission required. Grant in System Settings > Privacy & Security > Accessibility".to_string()
-            ));
+            return Err(Permission::Accessibility.required_error());
         }
     }
 
@@ -158,6 +157,7 @@ unsafe fn build_node(
         role,
         title,
         value,
+        text: None,
         enabled,
         focused,
         bounds,
@@ -320,9 +320,7 @@ unsafe fn get_ax_children(element: AXUIElementRef) -> Vec<AXUIElementRef> {
 /// Caller must CFRelease the returned ref when done.
 pub fn find_ax_element(pid: u32, target_id: &str) -> crate::Result<Option<AXUIElementRef>> {
     if !check_accessibility_permission(false) {
-        return Err(crate::Error::UiNotAvailable(
-            "Accessibility permission required".to_string(),
-        ));
+        return Err(Permission::Accessibility.required_error());
     }
 
     unsafe {
@@ -323,6 +323,7 @@ async fn build_node(
         role,
         title,
         value,
+        text: None,
         enabled,
         focused,
         bounds,
@@ -544,6 +545,7 @@ async fn find_in_subtree(
                 role,
                 title,
                 value,
+                text: None,
                 enabled: states.contains(atspi::State::Enabled)
                     || states.contains(atspi::State::Sensitive),
                 focused: states.contains(atspi::State::Focused),
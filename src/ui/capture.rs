@@ -1,5 +1,6 @@
 //! Screenshot capture via macOS CGWindowListCreateImage.
 
+use crate::ui::permissions::Permission;
 use crate::Result;
 use core_foundation::array::CFArray;
 use core_foundation::base::TCFType;
@@ -51,20 +52,20 @@ pub fn capture_element_screenshot(
         };
 
         // Convert element screen-space bounds to window-relative pixel coords
-        let crop_x_f = (element_bounds.x - win.x) * scale_x;
-        let crop_y_f = (element_bounds.y - win.y) * scale_y;
-        if crop_x_f < 0.0 || crop_y_f < 0.0 {
-            return Err(crate::Error::UiQueryFailed(
-                "Element is outside the visible window area".to_string(),
-            ));
-        }
-
+        let visible = element_bounds.visible_in(&crate::ui::tree::Rect {
+            x: win.x,
+            y: win.y,
+            w: win.w,
+            h: win.h,
+        })?;
         let img_w = image.width();
         let img_h = image.height();
-        let crop_x = (crop_x_f.round() as usize).min(img_w.saturating_sub(1));
-        let crop_y = (crop_y_f.round() as usize).min(img_h.saturating_sub(1));
-        let crop_w = ((element_bounds.w * scale_x).round() as usize).min(img_w - crop_x);
-        let crop_h = ((element_bounds.h * scale_y).round() as usize).min(img_h - crop_y);
+        let crop_x =
+            (((visible.x - win.x) * scale_x).round() as usize).min(img_w.saturating_sub(1));
+        let crop_y =
+            (((visible.y - win.y) * scale_y).round() as usize).min(img_h.saturating_sub(1));
+        let crop_w = ((visible.w * scale_x).round() as usize).min(img_w - crop_x);
+        let crop_h = ((visible.h * scale_y).round() as usize).min(img_h - crop_y);
 
         if crop_w == 0 || crop_h == 0 {
             return Err(crate::Error::UiQueryFailed(
@@ -78,6 +79,8 @@ pub fn capture_element_screenshot(
 
 /// Capture the CGImage for the main window.
 unsafe fn capture_window_image(pid: u32, win: &WindowInfo) -> Result<CGImage> {
+    // Without it the capture "succeeds" with the wallpaper instead of the window
+    Permission::ScreenRecording.require()?;
     CGDisplay::screenshot(
         CGRect::new(&CGPoint::new(0.0, 0.0), &CGSize::new(0.0, 0.0)),
         kCGWindowListOptionIncludingWindow,
@@ -344,12 +344,17 @@ pub fn capture_element_screenshot(
 
     // Compute crop coordinates relative to window origin, adjusting for scale factor.
     // AT-SPI2 bounds are in logical (screen) coords; X11 image is in physical (pixel) coords.
-    let crop_x = (((element_bounds.x - win.x as f64) * scale).round().max(0.0) as usize)
-        .min(w.saturating_sub(1));
-    let crop_y = (((element_bounds.y - win.y as f64) * scale).round().max(0.0) as usize)
-        .min(h.saturating_sub(1));
-    let crop_w = ((element_bounds.w * scale).round() as usize).min(w - crop_x);
-    let crop_h = ((element_bounds.h * scale).round() as usize).min(h - crop_y);
+    let window = crate::ui::tree::Rect {
+        x: win.x as f64,
+        y: win.y as f64,
+        w: w as f64 / scale,
+        h: h as f64 / scale,
+    };
+    let visible = element_bounds.visible_in(&window)?;
+    let crop_x = (((visible.x - window.x) * scale).round() as usize).min(w.saturating_sub(1));
+    let crop_y = (((visible.y - window.y) * scale).round() as usize).min(h.saturating_sub(1));
+    let crop_w = ((visible.w * scale).round() as usize).min(w - crop_x);
+    let crop_h = ((visible.h * scale).round() as usize).min(h - crop_y);
 
     if crop_w == 0 || crop_h == 0 {
         return Err(crate::Error::UiQueryFailed(
@@ -21,6 +21,16 @@ pub const MOD_ALTERNATE: u64 = 0x8; // X11 Mod1Mask (Alt)
 #[cfg(target_os = "linux")]
 pub const MOD_COMMAND: u64 = 0x40; // X11 Mod4Mask (Super/Meta)
 
+// No input backend elsewhere; distinct bits keep modifier parsing consistent
+#[cfg(not(any(target_os = "macos", target_os = "linux")))]
+pub const MOD_SHIFT: u64 = 0x1;
+#[cfg(not(any(target_os = "macos", target_os = "linux")))]
+pub const MOD_CONTROL: u64 = 0x2;
+#[cfg(not(any(target_os = "macos", target_os = "linux")))]
+pub const MOD_ALTERNATE: u64 = 0x4;
+#[cfg(not(any(target_os = "macos", target_os = "linux")))]
+pub const MOD_COMMAND: u64 = 0x8;
+
 /// Compute center point of an element's bounding box.
 pub fn element_center(bounds: &Rect) -> (f64, f64) {
     (bounds.x + bounds.w / 2.0, bounds.y + bounds.h / 2.0)
@@ -69,10 +79,15 @@ pub async fn execute_ui_action(
         crate::ui::input_mac::execute_action(pid, req).await
     }
 
-    #[cfg(not(target_os = "macos"))]
+    #[cfg(target_os = "linux")]
     {
         crate::ui::input_linux::execute_action(pid, req).await
     }
+
+    #[cfg(not(any(target_os = "macos", target_os = "linux")))]
+    {
+        crate::ui::unsupported::execute_action(pid, req).await
+    }
 }
 
 #[cfg(test)]
@@ -166,6 +166,28 @@ async fn try_atspi_click(target: &FindResult) -> Option<(bool, String, Option<St
     None
 }
 
+/// Try to focus an element via AT-SPI2 Component.grab_focus. Returns Some if attempted.
+async fn try_atspi_focus(target: &FindResult) -> Option<(bool, String, Option<String>)> {
+    let connection = crate::ui::accessibility_linux::connect().await.ok()?;
+    let conn = connection.connection();
+
+    let component_proxy: atspi::proxy::component::ComponentProxy<'_> =
+        atspi::proxy::component::ComponentProxy::builder(conn)
+            .destination(target.destination.as_str())
+            .ok()?
+            .path(target.path.as_str())
+            .ok()?
+            .cache_properties(atspi::zbus::proxy::CacheProperties::No)
+            .build()
+            .await
+            .ok()?;
+
+    match component_proxy.grab_focus().await {
+        Ok(true) => Some((true, "atspi".into(), None)),
+        _ => None,
+    }
+}
+
 /// Try to set a value via AT-SPI2 Value interface. Returns Some if attempted.
 async fn try_atspi_set_value(
     target: &FindResult,
@@ -228,6 +250,7 @@ pub async fn execute_action(
         UiActionType::Type => execute_type(&find_result, req).await,
         UiActionType::Scroll => execute_scroll(&find_result, req).await,
         UiActionType::Drag => execute_drag(pid, &find_result, req).await,
+        UiActionType::Focus => execute_focus(&find_result).await,
         UiActionType::Key => unreachable!(),
     };
 
@@ -369,6 +392,24 @@ async fn execute_type(
     }
 }
 
+/// Only through AT-SPI2 Component.grab_focus: a synthesized click would
+/// also activate the control, which focus must not do.
+async fn execute_focus(target: &FindResult) -> (bool, String, Option<String>) {
+    if target.interfaces.contains(atspi::Interface::Component) {
+        if let Some(result) = try_atspi_focus(target).await {
+            return result;
+        }
+    }
+    (
+        false,
+        "atspi".into(),
+        Some(
+            "Element does not accept accessibility focus; use action 'click' if activating it is intended"
+                .into(),
+        ),
+    )
+}
+
 async fn execute_scroll(
     target: &FindResult,
     req: &DebugUiActionRequest,
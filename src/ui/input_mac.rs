@@ -3,6 +3,7 @@
 use crate::mcp::{DebugUiActionRequest, DebugUiActionResponse, ScrollDirection, UiActionType};
 use crate::ui::accessibility::{find_ax_element, query_ax_tree};
 use crate::ui::input::{drag_interpolation_points, element_center, modifier_string_to_flags};
+use crate::ui::permissions::Permission;
 use crate::ui::tree::{diff_nodes, UiNode};
 use accessibility_sys::*;
 use core_foundation::base::{CFRelease, TCFType};
@@ -24,6 +25,8 @@ pub async fn execute_action(
     pid: u32,
     req: &DebugUiActionRequest,
 ) -> crate::Result<DebugUiActionResponse> {
+    // Events posted without it are silently dropped
+    Permission::Accessibility.require()?;
     let settle_ms = req.settle_ms.unwrap_or(DEFAULT_SETTLE_MS);
 
     // For key action, no node resolution needed
@@ -177,6 +180,7 @@ fn execute_action_blocking(
         UiActionType::Type => execute_type(pid, target_id, req, bounds),
         UiActionType::Scroll => execute_scroll(pid, req, bounds),
         UiActionType::Drag => execute_drag(pid, bounds, to_bounds),
+        UiActionType::Focus => execute_focus(pid, target_id),
         UiActionType::Key => unreachable!("Key handled before blocking dispatch"),
     }
 }
@@ -253,7 +257,34 @@ fn execute_type(
     let text = req.text.as_ref().unwrap();
 
     // Try AX focus first
-    let focused = unsafe {
+    if !ax_focus(pid, target_id) {
+        // Fall back to CGEvent click to focus
+        if let Some(bounds) = bounds {
+            let (cx, cy) = element_center(bounds);
+            cg_click(pid, cx, cy)?;
+            std::thread::sleep(std::time::Duration::from_millis(50));
+        }
+    }
+
+    // Type characters via CGEvent
+    cg_type_string(pid, text)?;
+    Ok("cgevent".to_string())
+}
+
+/// Only through AXFocused: a synthesized click would also activate the
+/// control (press a button, toggle a checkbox), which focus must not do.
+fn execute_focus(pid: u32, target_id: &str) -> crate::Result<String> {
+    if ax_focus(pid, target_id) {
+        return Ok("ax".to_string());
+    }
+    Err(crate::Error::UiQueryFailed(
+        "Element does not accept accessibility focus; use action 'click' if activating it is intended".to_string(),
+    ))
+}
+
+/// Set AXFocused on the element. Returns whether the element accepted it.
+fn ax_focus(pid: u32, target_id: &str) -> bool {
+    unsafe {
         if let Ok(Some(ax_ref)) = find_ax_element(pid, target_id) {
             let attr = CFString::new(kAXFocusedAttribute);
             let true_val = core_foundation::boolean::CFBoolean::true_value();
@@ -267,20 +298,7 @@ fn execute_type(
         } else {
             false
         }
-    };
-
-    if !focused {
-        // Fall back to CGEvent click to focus
-        if let Some(bounds) = bounds {
-            let (cx, cy) = element_center(bounds);
-            cg_click(pid, cx, cy)?;
-            std::thread::sleep(std::time::Duration::from_millis(50));
-        }
     }
-
-    // Type characters via CGEvent
-    cg_type_string(pid, text)?;
-    Ok("cgevent".to_string())
 }
 
 fn execute_scroll(
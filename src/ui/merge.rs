@@ -1,7 +1,8 @@
-//! Merge AX tree nodes with vision-detected elements via IoU matching.
+//! Merge AX tree nodes with vision-detected elements via IoU matching, and
+//! OCR text into the nodes it appears in.
 
 use crate::ui::tree::{generate_id, NodeSource, Rect, UiNode};
-use crate::ui::vision::{VisionBounds, VisionElement};
+use crate::ui::vision::{RecognizedText, VisionBounds, VisionElement};
 
 /// Compute Intersection over Union for two rectangles.
 pub fn iou(a: &Rect, b: &Rect) -> f64 {
@@ -83,6 +84,7 @@ pub fn merge_vision_into_tree(
                 Some(ve.description.clone())
             },
             value: None,
+            text: None,
             enabled: true,
             focused: false,
             bounds: Some(vr),
@@ -106,6 +108,75 @@ pub fn merge_vision_into_tree(
     (merged_count, added_count)
 }
 
+/// Attach OCR lines to the tree. Each line goes to the deepest node whose
+/// bounds contain its center, appended to the node's `text` in reading order;
+/// a line outside every node is added at the root as a vision `staticText`.
+/// Returns (attached, added).
+pub fn merge_text_into_tree(nodes: &mut Vec<UiNode>, texts: &[RecognizedText]) -> (usize, usize) {
+    let mut attached = 0;
+    let mut added = 0;
+
+    let mut ordered: Vec<&RecognizedText> = texts.iter().filter(|t| !t.text.is_empty()).collect();
+    ordered.sort_by_key(|t| (t.bounds.y, t.bounds.x));
+
+    for rt in ordered {
+        let cx = rt.bounds.x as f64 + rt.bounds.w as f64 / 2.0;
+        let cy = rt.bounds.y as f64 + rt.bounds.h as f64 / 2.0;
+        let mut path = vec![];
+        if find_container(nodes, cx, cy, &mut path) {
+            if let Some(node) = get_node_mut(nodes, &path) {
+                match node.text {
+                    Some(ref mut text) => {
+                        text.push(' ');
+                        text.push_str(&rt.text);
+                    }
+                    None => node.text = Some(rt.text.clone()),
+                }
+                attached += 1;
+                continue;
+            }
+        }
+
+        nodes.push(UiNode {
+            id: generate_id("staticText", Some(&rt.text), added),
+            role: "staticText".to_string(),
+            title: Some(rt.text.clone()),
+            value: None,
+            text: None,
+            enabled: true,
+            focused: false,
+            bounds: Some(vision_bounds_to_rect(&rt.bounds)),
+            actions: vec![],
+            source: NodeSource::Vision {
+                confidence: rt.confidence,
+            },
+            children: vec![],
+        });
+        added += 1;
+    }
+
+    (attached, added)
+}
+
+/// Path to the deepest node whose bounds contain the point.
+fn find_container(nodes: &[UiNode], cx: f64, cy: f64, path: &mut Vec<usize>) -> bool {
+    for (i, node) in nodes.iter().enumerate() {
+        let Some(ref bounds) = node.bounds else {
+            continue;
+        };
+        if cx >= bounds.x
+            && cx <= bounds.x + bounds.w
+            && cy >= bounds.y
+            && cy <= bounds.y + bounds.h
+        {
+            path.push(i);
+            find_container(&node.children, cx, cy, path);
+            return true;
+        }
+    }
+    false
+}
+
 fn find_best_match(
     nodes: &[UiNode],
     target: &Rect,
@@ -239,6 +310,7 @@ mod tests {
             role: "button".to_string(),
             title: Some("Play".to_string()),
             value: None,
+            text: None,
             enabled: true,
             focused: false,
             bounds: Some(Rect {
@@ -277,6 +349,7 @@ mod tests {
             role: "window".to_string(),
             title: Some("Test".to_string()),
             value: None,
+            text: None,
             enabled: true,
             focused: false,
             bounds: Some(Rect {
@@ -351,6 +424,7 @@ mod tests {
             role: "button".to_string(),
             title: Some("Test".to_string()),
             value: None,
+            text: None,
             enabled: true,
             focused: false,
             bounds: Some(normal),
@@ -410,6 +484,7 @@ mod tests {
             role: "button".to_string(),
             title: Some("AX Button".to_string()),
             value: None,
+            text: None,
             enabled: true,
             focused: false,
             bounds: Some(Rect {
@@ -451,6 +526,7 @@ mod tests {
             role: "window".to_string(),
             title: Some("App".to_string()),
             value: None,
+            text: None,
             enabled: true,
             focused: false,
             bounds: Some(Rect {
@@ -466,6 +542,7 @@ mod tests {
                 role: "panel".to_string(),
                 title: None,
                 value: None,
+                text: None,
                 enabled: true,
                 focused: false,
                 bounds: Some(Rect {
@@ -481,6 +558,7 @@ mod tests {
                     role: "group".to_string(),
                     title: None,
                     value: None,
+                    text: None,
                     enabled: true,
                     focused: false,
                     bounds: Some(Rect {
@@ -496,6 +574,7 @@ mod tests {
                         role: "button".to_string(),
                         title: Some("Click Me".to_string()),
                         value: None,
+                        text: None,
                         enabled: true,
                         focused: false,
                         bounds: Some(Rect {
@@ -542,6 +621,7 @@ mod tests {
             role: "window".to_string(),
             title: Some("App".to_string()),
             value: None,
+            text: None,
             enabled: true,
             focused: false,
             bounds: Some(Rect {
@@ -585,6 +665,7 @@ mod tests {
                 role: "button".to_string(),
                 title: Some("Button 1".to_string()),
                 value: None,
+                text: None,
                 enabled: true,
                 focused: false,
                 bounds: Some(Rect {
@@ -602,6 +683,7 @@ mod tests {
                 role: "button".to_string(),
                 title: Some("Button 2".to_string()),
                 value: None,
+                text: None,
                 enabled: true,
                 focused: false,
                 bounds: Some(Rect {
@@ -641,4 +723,53 @@ mod tests {
             .count();
         assert_eq!(merged_count, 1, "Exactly one button should be merged");
     }
+
+    #[test]
+    fn test_merge_text_into_deepest_container() {
+        let node = |id: &str, role: &str, bounds: Rect, children: Vec<UiNode>| UiNode {
+            id: id.to_string(),
+            role: role.to_string(),
+            title: None,
+            value: None,
+            text: None,
+            enabled: true,
+            focused: false,
+            bounds: Some(bounds),
+            actions: vec![],
+            source: NodeSource::Ax,
+            children,
+        };
+        let rect = |x, y, w, h| Rect { x, y, w, h };
+        let mut tree = vec![node(
+            "w_1",
+            "window",
+            rect(0.0, 0.0, 400.0, 300.0),
+            vec![node(
+                "pnl_1",
+                "group",
+                rect(10.0, 10.0, 200.0, 100.0),
+                vec![],
+            )],
+        )];
+        let text = |text: &str, x, y| RecognizedText {
+            text: text.to_string(),
+            confidence: 0.9,
+            bounds: VisionBounds { x, y, w: 60, h: 16 },
+        };
+        // Out of reading order on purpose
+        let texts = vec![
+            text("Retry", 20, 50),
+            text("Error 42", 20, 20),
+            text("Ready", 250, 250),
+            text("Offscreen", 900, 900),
+        ];
+
+        let (attached, added) = merge_text_into_tree(&mut tree, &texts);
+        assert_eq!((attached, added), (3, 1));
+        assert_eq!(tree[0].children[0].text.as_deref(), Some("Error 42 Retry"));
+        assert_eq!(tree[0].text.as_deref(), Some("Ready"));
+        assert_eq!(tree[1].role, "staticText");
+        assert_eq!(tree[1].title.as_deref(), Some("Offscreen"));
+        assert!(matches!(tree[1].source, NodeSource::Vision { .. }));
+    }
 }
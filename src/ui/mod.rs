@@ -19,8 +19,23 @@ pub mod capture_linux;
 #[cfg(target_os = "linux")]
 pub use capture_linux as capture;
 
+#[cfg(not(any(target_os = "macos", target_os = "linux")))]
+pub mod unsupported;
+
+#[cfg(not(any(target_os = "macos", target_os = "linux")))]
+pub use unsupported as accessibility;
+
+#[cfg(not(any(target_os = "macos", target_os = "linux")))]
+pub use unsupported as capture;
+
 pub mod merge;
+pub mod permissions;
+pub mod record;
 pub mod vision;
+pub mod vision_http;
+
+#[cfg(feature = "onnx")]
+pub mod vision_onnx;
 
 pub mod input;
 
@@ -0,0 +1,122 @@
+//! macOS privacy permissions (TCC) that debug_ui and debug_ui_action need.
+//!
+//! macOS grants them to the "responsible" app, i.e. the terminal or editor
+//! that started strobe, not to strobe itself. A missing permission fails
+//! with `Error::PermissionRequired` naming the System Settings pane to open;
+//! `strobe setup-ui` asks for both up front. Other platforms have no
+//! equivalent, so every permission counts as granted there.
+
+/// A TCC permission needed for UI observation or input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Permission {
+    /// Accessibility tree queries, AX actions and synthetic input events
+    Accessibility,
+    /// Window screenshots (otherwise only the desktop is captured)
+    ScreenRecording,
+}
+
+impl Permission {
+    pub const ALL: [Permission; 2] = [Permission::Accessibility, Permission::ScreenRecording];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Permission::Accessibility => "Accessibility",
+            Permission::ScreenRecording => "Screen Recording",
+        }
+    }
+
+    /// Where the permission is granted, as labelled in System Settings.
+    pub fn settings_pane(&self) -> &'static str {
+        match self {
+            Permission::Accessibility => "System Settings > Privacy & Security > Accessibility",
+            // Called "Screen & System Audio Recording" since macOS 15
+            Permission::ScreenRecording => {
+                "System Settings > Privacy & Security > Screen Recording"
+            }
+        }
+    }
+
+    /// URL that opens the pane directly (`open <url>`).
+    pub fn settings_url(&self) -> &'static str {
+        match self {
+            Permission::Accessibility => {
+                "x-apple.systempreferences:com.apple.preference.security?Privacy_Accessibility"
+            }
+            Permission::ScreenRecording => {
+                "x-apple.systempreferences:com.apple.preference.security?Privacy_ScreenCapture"
+            }
+        }
+    }
+
+    /// Whether the permission takes effect only after the process restarts.
+    pub fn needs_restart(&self) -> bool {
+        *self == Permission::ScreenRecording
+    }
+
+    pub fn required_error(&self) -> crate::Error {
+        crate::Error::PermissionRequired {
+            permission: self.name().to_string(),
+            pane: self.settings_pane().to_string(),
+            url: self.settings_url().to_string(),
+        }
+    }
+
+    /// Whether this process has the permission. With `prompt`, macOS shows
+    /// its request dialog if it hasn't been asked before.
+    #[cfg(target_os = "macos")]
+    pub fn is_granted(&self, prompt: bool) -> bool {
+        match self {
+            Permission::Accessibility => {
+                crate::ui::accessibility::check_accessibility_permission(prompt)
+            }
+            Permission::ScreenRecording => unsafe {
+                if prompt {
+                    CGRequestScreenCaptureAccess()
+                } else {
+                    CGPreflightScreenCaptureAccess()
+                }
+            },
+        }
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    pub fn is_granted(&self, _prompt: bool) -> bool {
+        true
+    }
+
+    /// `Err(PermissionRequired)` unless the permission is granted.
+    pub fn require(&self) -> crate::Result<()> {
+        if self.is_granted(false) {
+            Ok(())
+        } else {
+            Err(self.required_error())
+        }
+    }
+}
+
+#[cfg(target_os = "macos")]
+#[link(name = "CoreGraphics", kind = "framework")]
+extern "C" {
+    // macOS 10.15+
+    fn CGPreflightScreenCaptureAccess() -> bool;
+    fn CGRequestScreenCaptureAccess() -> bool;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_permission_required_error_names_pane() {
+        let err = Permission::ScreenRecording.required_error();
+        let message = err.to_string();
+        assert!(message.starts_with("PERMISSION_REQUIRED: Screen Recording"));
+        assert!(message.contains("Privacy & Security > Screen Recording"));
+        assert!(message.contains("Privacy_ScreenCapture"));
+        assert!(message.contains("strobe setup-ui"));
+        assert!(matches!(
+            crate::mcp::McpError::from(err).code,
+            crate::mcp::ErrorCode::PermissionRequired
+        ));
+    }
+}
@@ -0,0 +1,151 @@
+//! Frame sequence capture for debug_ui's record mode: screenshots at a fixed
+//! rate, saved as numbered PNGs, for flicker and animation bugs that a single
+//! screenshot misses. A frame identical to the previous one isn't written
+//! again; it points at the earlier file.
+
+use crate::Result;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Recording {
+    /// Directory holding the frame PNGs
+    pub dir: String,
+    pub frames: Vec<RecordedFrame>,
+    /// Frames that differ from the one before
+    pub distinct_frames: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecordedFrame {
+    /// Capture time since the first frame started
+    pub offset_ms: u64,
+    pub path: String,
+    /// Whether the image differs from the previous frame
+    pub changed: bool,
+}
+
+/// Sets its flag when dropped, so a recording stops once the request that
+/// started it goes away.
+pub struct StopOnDrop(pub Arc<AtomicBool>);
+
+impl Drop for StopOnDrop {
+    fn drop(&mut self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Capture `fps` frames a second for `duration` into `dir`, or until `stop`
+/// is set. Frames are scheduled from the start, so a slow capture shortens
+/// the gap to the next one rather than drifting; captures slower than the
+/// frame interval run back to back.
+pub fn record(
+    dir: &Path,
+    duration: Duration,
+    fps: u32,
+    stop: &AtomicBool,
+    mut capture: impl FnMut() -> Result<Vec<u8>>,
+) -> Result<Recording> {
+    std::fs::create_dir_all(dir)?;
+    let interval = Duration::from_secs(1) / fps.max(1);
+    let count = (duration.as_nanos() / interval.as_nanos()).max(1) as u32;
+
+    let start = Instant::now();
+    let mut frames: Vec<RecordedFrame> = Vec::with_capacity(count as usize);
+    let mut previous: Option<Vec<u8>> = None;
+    for i in 0..count {
+        let due = start + interval * i;
+        let now = Instant::now();
+        if due > now {
+            std::thread::sleep(due - now);
+        }
+        if stop.load(Ordering::Relaxed) {
+            break;
+        }
+        let png = capture()?;
+        let offset_ms = start.elapsed().as_millis() as u64;
+        let frame = match (&previous, frames.last()) {
+            (Some(prev), Some(last)) if *prev == png => RecordedFrame {
+                offset_ms,
+                path: last.path.clone(),
+                changed: false,
+            },
+            _ => {
+                let path: PathBuf = dir.join(format!("frame_{:04}.png", i));
+                std::fs::write(&path, &png)?;
+                previous = Some(png);
+                RecordedFrame {
+                    offset_ms,
+                    path: path.to_string_lossy().into_owned(),
+                    changed: true,
+                }
+            }
+        };
+        frames.push(frame);
+    }
+
+    Ok(Recording {
+        dir: dir.to_string_lossy().into_owned(),
+        distinct_frames: frames.iter().filter(|f| f.changed).count(),
+        frames,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_skips_unchanged_frames() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut images =
+            vec![b"A".to_vec(), b"A".to_vec(), b"B".to_vec(), b"A".to_vec()].into_iter();
+        let stop = AtomicBool::new(false);
+        let recording = record(dir.path(), Duration::from_millis(4), 1000, &stop, || {
+            Ok(images.next().unwrap())
+        })
+        .unwrap();
+
+        assert_eq!(recording.frames.len(), 4);
+        assert_eq!(recording.distinct_frames, 3);
+        let changed: Vec<bool> = recording.frames.iter().map(|f| f.changed).collect();
+        assert_eq!(changed, vec![true, false, true, true]);
+        assert_eq!(recording.frames[1].path, recording.frames[0].path);
+        assert!(recording.frames[3].path.ends_with("frame_0003.png"));
+        assert_eq!(std::fs::read(&recording.frames[2].path).unwrap(), b"B");
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 3);
+    }
+
+    #[test]
+    fn test_record_captures_at_least_one_frame() {
+        let dir = tempfile::tempdir().unwrap();
+        let stop = AtomicBool::new(false);
+        let recording =
+            record(dir.path(), Duration::ZERO, 10, &stop, || Ok(vec![1, 2, 3])).unwrap();
+        assert_eq!(recording.frames.len(), 1);
+        assert!(recording.frames[0].changed);
+    }
+
+    #[test]
+    fn test_record_stops_when_flag_set() {
+        let dir = tempfile::tempdir().unwrap();
+        let stop = Arc::new(AtomicBool::new(false));
+        let mut guard = Some(StopOnDrop(Arc::clone(&stop)));
+        let mut captured = 0;
+        let recording = record(dir.path(), Duration::from_secs(60), 1000, &stop, || {
+            captured += 1;
+            if captured == 3 {
+                // The caller went away mid-recording
+                guard.take();
+            }
+            Ok(vec![captured])
+        })
+        .unwrap();
+        assert_eq!(recording.frames.len(), 3);
+    }
+}
@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -9,6 +10,29 @@ pub struct Rect {
     pub h: f64,
 }
 
+impl Rect {
+    /// The part of this crop rect inside `window`, or a validation error when
+    /// they don't overlap (a crop must not degrade to a sliver of the edge).
+    pub fn visible_in(&self, window: &Rect) -> crate::Result<Rect> {
+        let x = self.x.max(window.x);
+        let y = self.y.max(window.y);
+        let right = (self.x + self.w).min(window.x + window.w);
+        let bottom = (self.y + self.h).min(window.y + window.h);
+        if right <= x || bottom <= y {
+            return Err(crate::Error::ValidationError(format!(
+                "Crop area {}x{} at ({}, {}) is outside the window {}x{} at ({}, {})",
+                self.w, self.h, self.x, self.y, window.w, window.h, window.x, window.y
+            )));
+        }
+        Ok(Rect {
+            x,
+            y,
+            w: right - x,
+            h: bottom - y,
+        })
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum NodeSource {
@@ -26,6 +50,9 @@ pub struct UiNode {
     pub title: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub value: Option<String>,
+    /// Text recognized on screen within the node's bounds (vision OCR)
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub text: Option<String>,
     pub enabled: bool,
     pub focused: bool,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -112,6 +139,10 @@ fn format_node(out: &mut String, node: &UiNode, depth: usize) {
         }
     }
 
+    if let Some(ref text) = node.text {
+        out.push_str(&format!(" text≈\"{}\"", text));
+    }
+
     if node.enabled {
         out.push_str(" enabled");
     }
@@ -150,6 +181,29 @@ pub fn find_node_by_id(nodes: &[UiNode], target_id: &str) -> Option<UiNode> {
     None
 }
 
+/// Nodes whose title, value or recognized text contains `needle`, ignoring
+/// case, in tree order. Returned without their children.
+pub fn find_nodes_by_text(nodes: &[UiNode], needle: &str) -> Vec<UiNode> {
+    fn walk(nodes: &[UiNode], needle: &str, out: &mut Vec<UiNode>) {
+        for node in nodes {
+            let matches = [&node.title, &node.value, &node.text]
+                .into_iter()
+                .flatten()
+                .any(|s| s.to_lowercase().contains(needle));
+            if matches {
+                out.push(UiNode {
+                    children: vec![],
+                    ..node.clone()
+                });
+            }
+            walk(&node.children, needle, out);
+        }
+    }
+    let mut out = Vec::new();
+    walk(nodes, &needle.to_lowercase(), &mut out);
+    out
+}
+
 /// Compare two UiNode snapshots. Returns true if any observable field changed.
 /// Compares: value, enabled, focused, title. Ignores children and bounds.
 pub fn diff_nodes(before: &UiNode, after: &UiNode) -> bool {
@@ -164,6 +218,143 @@ pub fn count_nodes(nodes: &[UiNode]) -> usize {
     nodes.iter().map(|n| 1 + count_nodes(&n.children)).sum()
 }
 
+/// Changes between two snapshots of a tree, matched by node ID. An added or
+/// removed node is listed with its subtree, whose nodes aren't listed again.
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TreeDiff {
+    pub added: Vec<UiNode>,
+    pub removed: Vec<UiNode>,
+    pub changed: Vec<NodeChange>,
+}
+
+/// A node present in both snapshots whose attributes changed.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NodeChange {
+    pub id: String,
+    pub role: String,
+    pub changes: Vec<AttributeChange>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AttributeChange {
+    pub attribute: String,
+    pub before: serde_json::Value,
+    pub after: serde_json::Value,
+}
+
+impl TreeDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+/// Diff two snapshots. Compares the same attributes as `diff_nodes`; since IDs
+/// include the title, a retitled node shows up as removed and added.
+pub fn diff_trees(before: &[UiNode], after: &[UiNode]) -> TreeDiff {
+    let old = index_by_id(before);
+    let new = index_by_id(after);
+    let mut diff = TreeDiff::default();
+    collect_missing(after, &old, &mut diff.added);
+    collect_missing(before, &new, &mut diff.removed);
+    collect_changed(after, &old, &mut diff.changed);
+    diff
+}
+
+fn index_by_id(nodes: &[UiNode]) -> HashMap<&str, &UiNode> {
+    fn walk<'a>(nodes: &'a [UiNode], index: &mut HashMap<&'a str, &'a UiNode>) {
+        for node in nodes {
+            index.insert(node.id.as_str(), node);
+            walk(&node.children, index);
+        }
+    }
+    let mut index = HashMap::new();
+    walk(nodes, &mut index);
+    index
+}
+
+/// Topmost nodes of `nodes` whose ID isn't in `other`.
+fn collect_missing(nodes: &[UiNode], other: &HashMap<&str, &UiNode>, out: &mut Vec<UiNode>) {
+    for node in nodes {
+        if other.contains_key(node.id.as_str()) {
+            collect_missing(&node.children, other, out);
+        } else {
+            out.push(node.clone());
+        }
+    }
+}
+
+fn collect_changed(nodes: &[UiNode], old: &HashMap<&str, &UiNode>, out: &mut Vec<NodeChange>) {
+    for node in nodes {
+        let Some(before) = old.get(node.id.as_str()) else {
+            continue;
+        };
+        let mut changes = Vec::new();
+        let mut compare = |attribute: &str, before: serde_json::Value, after: serde_json::Value| {
+            if before != after {
+                changes.push(AttributeChange {
+                    attribute: attribute.to_string(),
+                    before,
+                    after,
+                });
+            }
+        };
+        compare(
+            "title",
+            before.title.clone().into(),
+            node.title.clone().into(),
+        );
+        compare(
+            "value",
+            before.value.clone().into(),
+            node.value.clone().into(),
+        );
+        compare("enabled", before.enabled.into(), node.enabled.into());
+        compare("focused", before.focused.into(), node.focused.into());
+        if !changes.is_empty() {
+            out.push(NodeChange {
+                id: node.id.clone(),
+                role: node.role.clone(),
+                changes,
+            });
+        }
+        collect_changed(&node.children, old, out);
+    }
+}
+
+/// Format a diff as compact text: `+`/`-` before added and removed subtrees
+/// (in the `format_compact` layout), `~` before attribute changes.
+pub fn format_diff_compact(diff: &TreeDiff) -> String {
+    if diff.is_empty() {
+        return "No changes\n".to_string();
+    }
+    let mut out = String::new();
+    for (mark, nodes) in [("+", &diff.added), ("-", &diff.removed)] {
+        for node in nodes {
+            let mut subtree = String::new();
+            format_node(&mut subtree, node, 0);
+            for line in subtree.lines() {
+                out.push_str(&format!("{} {}\n", mark, line));
+            }
+        }
+    }
+    for change in &diff.changed {
+        let changes: Vec<String> = change
+            .changes
+            .iter()
+            .map(|c| format!("{}: {} -> {}", c.attribute, c.before, c.after))
+            .collect();
+        out.push_str(&format!(
+            "~ [{} id={}] {}\n",
+            change.role,
+            change.id,
+            changes.join(", ")
+        ));
+    }
+    out
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -174,6 +365,7 @@ mod tests {
             role: "window".to_string(),
             title: Some("Test App".to_string()),
             value: None,
+            text: None,
             enabled: true,
             focused: false,
             bounds: Some(Rect {
@@ -190,6 +382,7 @@ mod tests {
                     role: "button".to_string(),
                     title: Some("Play".to_string()),
                     value: None,
+                    text: None,
                     enabled: true,
                     focused: true,
                     bounds: Some(Rect {
@@ -207,6 +400,7 @@ mod tests {
                     role: "knob".to_string(),
                     title: Some("Filter".to_string()),
                     value: Some("0.6".to_string()),
+                    text: None,
                     enabled: true,
                     focused: false,
                     bounds: Some(Rect {
@@ -271,6 +465,36 @@ mod tests {
         assert_eq!(count_nodes(&tree), 3); // window + button + knob
     }
 
+    #[test]
+    fn test_rect_visible_in_window() {
+        let window = Rect {
+            x: 100.0,
+            y: 100.0,
+            w: 400.0,
+            h: 300.0,
+        };
+        let partly = Rect {
+            x: 450.0,
+            y: 80.0,
+            w: 100.0,
+            h: 40.0,
+        };
+        let visible = partly.visible_in(&window).unwrap();
+        assert_eq!(
+            (visible.x, visible.y, visible.w, visible.h),
+            (450.0, 100.0, 50.0, 20.0)
+        );
+
+        let outside = Rect {
+            x: 600.0,
+            y: 120.0,
+            w: 50.0,
+            h: 50.0,
+        };
+        let err = outside.visible_in(&window).unwrap_err();
+        assert!(err.to_string().contains("outside the window"));
+    }
+
     #[test]
     fn test_diff_nodes_detects_value_change() {
         let before = UiNode {
@@ -278,6 +502,7 @@ mod tests {
             role: "AXSlider".to_string(),
             title: Some("Volume".to_string()),
             value: Some("0.5".to_string()),
+            text: None,
             enabled: true,
             focused: false,
             bounds: Some(Rect {
@@ -302,6 +527,7 @@ mod tests {
             role: "AXButton".to_string(),
             title: Some("Play".to_string()),
             value: None,
+            text: None,
             enabled: true,
             focused: false,
             bounds: Some(Rect {
@@ -324,6 +550,7 @@ mod tests {
             role: "AXTextField".to_string(),
             title: Some("Name".to_string()),
             value: Some("hello".to_string()),
+            text: None,
             enabled: true,
             focused: false,
             bounds: None,
@@ -343,6 +570,7 @@ mod tests {
             role: "AXButton".to_string(),
             title: None,
             value: None,
+            text: None,
             enabled: true,
             focused: false,
             bounds: None,
@@ -362,6 +590,7 @@ mod tests {
             role: "AXButton".to_string(),
             title: Some("Play".to_string()),
             value: None,
+            text: None,
             enabled: true,
             focused: false,
             bounds: None,
@@ -374,6 +603,81 @@ mod tests {
         assert!(diff_nodes(&before, &after));
     }
 
+    #[test]
+    fn test_find_nodes_by_text() {
+        let mut tree = sample_tree();
+        tree[0].children[1].text = Some("Cutoff 440 Hz".to_string());
+
+        let found = find_nodes_by_text(&tree, "440 hz");
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].id, "knb_c3d4");
+
+        // Titles match too; matches come without their subtree
+        let found = find_nodes_by_text(&tree, "test app");
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].id, "w_0001");
+        assert!(found[0].children.is_empty());
+
+        assert!(find_nodes_by_text(&tree, "Error 42").is_empty());
+
+        let text = format_compact(&find_nodes_by_text(&tree, "cutoff"));
+        assert!(text.contains("text≈\"Cutoff 440 Hz\""));
+    }
+
+    #[test]
+    fn test_diff_trees() {
+        let before = sample_tree();
+        let mut after = sample_tree();
+        // Play button disabled, knob gone, a dialog with a label appears
+        after[0].children[0].enabled = false;
+        after[0].children[0].value = Some("on".to_string());
+        after[0].children.remove(1);
+        let mut label = after[0].children[0].clone();
+        label.id = "lbl_9999".to_string();
+        label.role = "staticText".to_string();
+        label.title = Some("Saved".to_string());
+        let mut dialog = label.clone();
+        dialog.id = "w_0002".to_string();
+        dialog.role = "window".to_string();
+        dialog.title = Some("Done".to_string());
+        dialog.children = vec![label];
+        after.push(dialog);
+
+        let diff = diff_trees(&before, &after);
+        assert_eq!(diff.added.len(), 1);
+        assert_eq!(diff.added[0].id, "w_0002");
+        assert_eq!(diff.removed.len(), 1);
+        assert_eq!(diff.removed[0].id, "knb_c3d4");
+        assert_eq!(diff.changed.len(), 1);
+        assert_eq!(diff.changed[0].id, "btn_a1b2");
+        assert_eq!(
+            diff.changed[0].changes,
+            vec![
+                AttributeChange {
+                    attribute: "value".to_string(),
+                    before: serde_json::Value::Null,
+                    after: serde_json::json!("on"),
+                },
+                AttributeChange {
+                    attribute: "enabled".to_string(),
+                    before: serde_json::json!(true),
+                    after: serde_json::json!(false),
+                },
+            ]
+        );
+
+        let text = format_diff_compact(&diff);
+        assert!(text.contains("+ [window \"Done\" id=w_0002"));
+        assert!(text.contains("+   [staticText \"Saved\" id=lbl_9999"));
+        assert!(text.contains("- [knob \"Filter\" id=knb_c3d4"));
+        assert!(
+            text.contains("~ [button id=btn_a1b2] value: null -> \"on\", enabled: true -> false")
+        );
+
+        assert!(diff_trees(&before, &before).is_empty());
+        assert_eq!(format_diff_compact(&TreeDiff::default()), "No changes\n");
+    }
+
     #[test]
     fn test_find_node_by_id_root() {
         let tree = sample_tree();
@@ -0,0 +1,31 @@
+//! debug_ui on platforms without an accessibility or capture backend
+//! (Windows). Every call reports UI_NOT_AVAILABLE.
+
+use crate::mcp::{DebugUiActionRequest, DebugUiActionResponse};
+use crate::ui::tree::{Rect, UiNode};
+use crate::Result;
+
+fn not_available<T>() -> Result<T> {
+    Err(crate::Error::UiNotAvailable(
+        "debug_ui is only supported on macOS and Linux".to_string(),
+    ))
+}
+
+pub async fn query_ax_tree(_pid: u32) -> Result<Vec<UiNode>> {
+    not_available()
+}
+
+pub fn capture_window_screenshot(_pid: u32) -> Result<Vec<u8>> {
+    not_available()
+}
+
+pub fn capture_element_screenshot(_pid: u32, _element_bounds: &Rect) -> Result<Vec<u8>> {
+    not_available()
+}
+
+pub async fn execute_action(
+    _pid: u32,
+    _req: &DebugUiActionRequest,
+) -> Result<DebugUiActionResponse> {
+    not_available()
+}
@@ -1,7 +1,11 @@
-//! Vision sidecar process management.
+//! Vision backends and sidecar process management.
 //!
-//! Manages a long-running Python process that runs OmniParser v2 for
-//! UI element detection. Communication via JSON over stdin/stdout.
+//! The default backend manages a long-running Python process that runs
+//! OmniParser v2 for UI element detection, plus an optional OCR pass
+//! (Vision.framework on macOS, Tesseract elsewhere). Communication via JSON
+//! over stdin/stdout. `vision.backend` in settings selects another backend:
+//! an ONNX export of the detection model run in-process (`vision_onnx`), or a
+//! remote service speaking the same JSON protocol over HTTP (`vision_http`).
 
 use crate::Result;
 use serde::{Deserialize, Serialize};
@@ -25,6 +29,201 @@ pub struct VisionBounds {
     pub h: i32,
 }
 
+/// A line of text found by the OCR pass.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecognizedText {
+    pub text: String,
+    pub confidence: f32,
+    pub bounds: VisionBounds,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Detection {
+    pub elements: Vec<VisionElement>,
+    /// Empty unless OCR was requested and the sidecar has an OCR backend
+    pub texts: Vec<RecognizedText>,
+}
+
+/// A UI element detector behind debug_ui's vision pipeline.
+pub trait VisionBackend: Send {
+    /// Detect UI elements in a base64-encoded PNG screenshot and, with `ocr`,
+    /// recognize its text where the backend can.
+    fn analyze(
+        &mut self,
+        screenshot_b64: &str,
+        confidence_threshold: f32,
+        iou_threshold: f32,
+        ocr: bool,
+    ) -> Result<Detection>;
+
+    /// Release resources after `timeout_seconds` without use.
+    fn check_idle_timeout(&mut self, _timeout_seconds: u64) {}
+
+    fn shutdown(&mut self) {}
+}
+
+/// Backend selected by "vision.backend" in settings.json.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum VisionBackendKind {
+    /// OmniParser in the Python sidecar (`strobe setup-vision`)
+    #[default]
+    Sidecar,
+    /// ONNX export of the detection model, in-process (the `onnx` feature)
+    Onnx,
+    /// Remote service at "vision.endpoint"
+    Http,
+}
+
+impl VisionBackendKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Sidecar => "sidecar",
+            Self::Onnx => "onnx",
+            Self::Http => "http",
+        }
+    }
+
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "sidecar" => Some(Self::Sidecar),
+            "onnx" => Some(Self::Onnx),
+            "http" => Some(Self::Http),
+            _ => None,
+        }
+    }
+}
+
+/// Settings that pick and configure a backend; a change replaces it.
+#[derive(Debug, Clone, PartialEq)]
+struct BackendConfig {
+    kind: VisionBackendKind,
+    onnx_model: Option<String>,
+    endpoint: Option<String>,
+}
+
+/// The backend the settings select, created on first use.
+pub struct VisionPipeline {
+    active: Option<(BackendConfig, Box<dyn VisionBackend>)>,
+}
+
+impl VisionPipeline {
+    pub fn new() -> Self {
+        Self { active: None }
+    }
+
+    pub fn backend(
+        &mut self,
+        settings: &crate::config::StrobeSettings,
+    ) -> Result<&mut dyn VisionBackend> {
+        let config = BackendConfig {
+            kind: settings.vision_backend,
+            onnx_model: settings.vision_onnx_model.clone(),
+            endpoint: settings.vision_endpoint.clone(),
+        };
+        if self.active.as_ref().map(|(c, _)| c) != Some(&config) {
+            self.shutdown();
+            let backend = create_backend(&config)?;
+            tracing::info!("Vision backend: {}", config.kind.as_str());
+            self.active = Some((config, backend));
+        }
+        Ok(self.active.as_mut().unwrap().1.as_mut())
+    }
+
+    pub fn check_idle_timeout(&mut self, timeout_seconds: u64) {
+        if let Some((_, ref mut backend)) = self.active {
+            backend.check_idle_timeout(timeout_seconds);
+        }
+    }
+
+    pub fn shutdown(&mut self) {
+        if let Some((_, mut backend)) = self.active.take() {
+            backend.shutdown();
+        }
+    }
+}
+
+fn create_backend(config: &BackendConfig) -> Result<Box<dyn VisionBackend>> {
+    match config.kind {
+        VisionBackendKind::Sidecar => Ok(Box::new(VisionSidecar::new())),
+        VisionBackendKind::Http => {
+            let endpoint = config.endpoint.as_deref().ok_or_else(|| {
+                crate::Error::UiQueryFailed(
+                    "vision.backend is \"http\" but vision.endpoint is not set".to_string(),
+                )
+            })?;
+            Ok(Box::new(super::vision_http::HttpVisionBackend::new(
+                endpoint,
+            )))
+        }
+        #[cfg(feature = "onnx")]
+        VisionBackendKind::Onnx => {
+            let model = match config.onnx_model {
+                Some(ref path) => std::path::PathBuf::from(path),
+                None => super::vision_onnx::default_model_path().ok_or_else(|| {
+                    crate::Error::UiQueryFailed("Cannot locate home directory".to_string())
+                })?,
+            };
+            Ok(Box::new(super::vision_onnx::OnnxVisionBackend::load(
+                &model,
+            )?))
+        }
+        #[cfg(not(feature = "onnx"))]
+        VisionBackendKind::Onnx => Err(crate::Error::UiQueryFailed(
+            "vision.backend is \"onnx\" but strobe was built without the `onnx` feature"
+                .to_string(),
+        )),
+    }
+}
+
+/// A detect request in the sidecar protocol, which the HTTP backend shares.
+pub(crate) fn detect_request(
+    id: &str,
+    screenshot_b64: &str,
+    confidence_threshold: f32,
+    iou_threshold: f32,
+    ocr: bool,
+) -> serde_json::Value {
+    serde_json::json!({
+        "id": id,
+        "type": "detect",
+        "image": screenshot_b64,
+        "options": {
+            "confidence_threshold": confidence_threshold,
+            "iou_threshold": iou_threshold,
+            "ocr": ocr,
+        }
+    })
+}
+
+/// The detection in a sidecar protocol response; `source` names the backend
+/// in errors.
+pub(crate) fn parse_detection(source: &str, response: &serde_json::Value) -> Result<Detection> {
+    if response.get("type").and_then(|t| t.as_str()) == Some("error") {
+        return Err(crate::Error::UiQueryFailed(format!(
+            "{} error: {}",
+            source,
+            response
+                .get("message")
+                .and_then(|m| m.as_str())
+                .unwrap_or("unknown")
+        )));
+    }
+
+    let elements: Vec<VisionElement> = response
+        .get("elements")
+        .and_then(|e| serde_json::from_value(e.clone()).ok())
+        .unwrap_or_default();
+    let texts: Vec<RecognizedText> = response
+        .get("texts")
+        .and_then(|t| serde_json::from_value(t.clone()).ok())
+        .unwrap_or_default();
+    if let Some(warning) = response.get("ocr_warning").and_then(|w| w.as_str()) {
+        tracing::warn!("{} OCR: {}", source, warning);
+    }
+
+    Ok(Detection { elements, texts })
+}
+
 pub struct VisionSidecar {
     process: Option<Child>,
     last_used: Instant,
@@ -47,40 +246,33 @@ impl VisionSidecar {
         confidence_threshold: f32,
         iou_threshold: f32,
     ) -> Result<Vec<VisionElement>> {
+        self.analyze(screenshot_b64, confidence_threshold, iou_threshold, false)
+            .map(|d| d.elements)
+    }
+
+    /// Detect UI elements and, with `ocr`, recognize the screenshot's text.
+    pub fn analyze(
+        &mut self,
+        screenshot_b64: &str,
+        confidence_threshold: f32,
+        iou_threshold: f32,
+        ocr: bool,
+    ) -> Result<Detection> {
         self.ensure_running()?;
         self.last_used = Instant::now();
 
         let req_id = format!("req_{}", self.request_counter);
         self.request_counter += 1;
 
-        let request = serde_json::json!({
-            "id": req_id,
-            "type": "detect",
-            "image": screenshot_b64,
-            "options": {
-                "confidence_threshold": confidence_threshold,
-                "iou_threshold": iou_threshold,
-            }
-        });
-
+        let request = detect_request(
+            &req_id,
+            screenshot_b64,
+            confidence_threshold,
+            iou_threshold,
+            ocr,
+        );
         let response = self.send_request(&request)?;
-
-        if response.get("type").and_then(|t| t.as_str()) == Some("error") {
-            return Err(crate::Error::UiQueryFailed(format!(
-                "Vision sidecar error: {}",
-                response
-                    .get("message")
-                    .and_then(|m| m.as_str())
-                    .unwrap_or("unknown")
-            )));
-        }
-
-        let elements: Vec<VisionElement> = response
-            .get("elements")
-            .and_then(|e| serde_json::from_value(e.clone()).ok())
-            .unwrap_or_default();
-
-        Ok(elements)
+        parse_detection("Vision sidecar", &response)
     }
 
     /// Check if sidecar should be shut down due to idle timeout.
@@ -226,6 +418,8 @@ impl VisionSidecar {
             .as_mut()
             .ok_or_else(|| crate::Error::UiQueryFailed("Sidecar stdout closed".to_string()))?;
 
+        // Pipes can't be polled on Windows; the read there blocks until the sidecar answers
+        #[cfg(unix)]
         {
             use std::os::unix::io::AsRawFd;
             let raw_fd = stdout.as_raw_fd();
@@ -301,6 +495,32 @@ impl VisionSidecar {
     }
 }
 
+impl VisionBackend for VisionSidecar {
+    fn analyze(
+        &mut self,
+        screenshot_b64: &str,
+        confidence_threshold: f32,
+        iou_threshold: f32,
+        ocr: bool,
+    ) -> Result<Detection> {
+        VisionSidecar::analyze(
+            self,
+            screenshot_b64,
+            confidence_threshold,
+            iou_threshold,
+            ocr,
+        )
+    }
+
+    fn check_idle_timeout(&mut self, timeout_seconds: u64) {
+        VisionSidecar::check_idle_timeout(self, timeout_seconds);
+    }
+
+    fn shutdown(&mut self) {
+        VisionSidecar::shutdown(self);
+    }
+}
+
 impl Drop for VisionSidecar {
     fn drop(&mut self) {
         self.shutdown();
@@ -311,6 +531,55 @@ impl Drop for VisionSidecar {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_parse_detection() {
+        let response = serde_json::json!({
+            "id": "req_0",
+            "type": "result",
+            "elements": [{
+                "label": "button",
+                "description": "Save button",
+                "confidence": 0.8,
+                "bounds": { "x": 1, "y": 2, "w": 30, "h": 12 }
+            }],
+            "texts": [{ "text": "Save", "confidence": 0.9, "bounds": { "x": 4, "y": 3, "w": 20, "h": 10 } }],
+            "latency_ms": 40
+        });
+        let detection = parse_detection("Vision endpoint", &response).unwrap();
+        assert_eq!(detection.elements[0].label, "button");
+        assert_eq!(detection.texts[0].text, "Save");
+
+        let error = serde_json::json!({ "id": "req_1", "type": "error", "message": "bad image" });
+        let err = parse_detection("Vision endpoint", &error).unwrap_err();
+        assert!(err.to_string().contains("Vision endpoint error: bad image"));
+    }
+
+    #[test]
+    fn test_pipeline_follows_settings() {
+        let mut pipeline = VisionPipeline::new();
+        let mut settings = crate::config::StrobeSettings {
+            vision_backend: VisionBackendKind::Http,
+            ..Default::default()
+        };
+        let err = pipeline.backend(&settings).err().unwrap();
+        assert!(err.to_string().contains("vision.endpoint"));
+        assert!(pipeline.active.is_none());
+
+        settings.vision_endpoint = Some("http://127.0.0.1:9/detect".to_string());
+        pipeline.backend(&settings).unwrap();
+        settings.vision_backend = VisionBackendKind::Sidecar;
+        pipeline.backend(&settings).unwrap();
+        assert_eq!(
+            pipeline.active.as_ref().unwrap().0.kind,
+            VisionBackendKind::Sidecar
+        );
+        assert_eq!(
+            VisionBackendKind::from_str("onnx"),
+            Some(VisionBackendKind::Onnx)
+        );
+        assert_eq!(VisionBackendKind::from_str("gpu"), None);
+    }
+
     // TEST-1: Sidecar crash recovery test
     #[test]
     #[cfg(target_os = "macos")]
@@ -340,9 +609,7 @@ mod tests {
             let pid = child.id();
 
             // Kill the sidecar process to simulate crash
-            unsafe {
-                libc::kill(pid as i32, libc::SIGKILL);
-            }
+            crate::process::kill(pid);
 
             // Wait briefly for process to die
             std::thread::sleep(std::time::Duration::from_millis(100));
@@ -0,0 +1,161 @@
+//! Vision backend for a remote detection service: each screenshot is POSTed
+//! as a sidecar protocol `detect` request to "vision.endpoint" and the reply
+//! is read as a sidecar response, so a sidecar behind any HTTP server works.
+//!
+//! Requests go through `curl` for TLS and proxy support. Its options,
+//! including the `STROBE_VISION_API_KEY` bearer token, are passed as a config
+//! on stdin so they never show up in the process list.
+
+use super::vision::{detect_request, parse_detection, Detection, VisionBackend};
+use crate::Result;
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+/// Environment variable holding the endpoint's bearer token, if it needs one.
+pub const API_KEY_ENV: &str = "STROBE_VISION_API_KEY";
+
+/// Same bound as a sidecar response.
+const REQUEST_TIMEOUT_SECS: u64 = 30;
+
+pub struct HttpVisionBackend {
+    endpoint: String,
+    request_counter: u64,
+}
+
+impl HttpVisionBackend {
+    pub fn new(endpoint: &str) -> Self {
+        Self {
+            endpoint: endpoint.to_string(),
+            request_counter: 0,
+        }
+    }
+
+    fn post(&self, body: &str) -> Result<serde_json::Value> {
+        let mut config = format!(
+            "url = {}\nrequest = \"POST\"\nheader = \"Content-Type: application/json\"\n",
+            curl_string(&self.endpoint)
+        );
+        if let Ok(key) = std::env::var(API_KEY_ENV) {
+            config.push_str(&format!(
+                "header = {}\n",
+                curl_string(&format!("Authorization: Bearer {}", key))
+            ));
+        }
+        config.push_str(&format!("data-binary = {}\n", curl_string(body)));
+
+        let mut child = Command::new("curl")
+            .args(["--silent", "--show-error", "--max-time"])
+            .arg(REQUEST_TIMEOUT_SECS.to_string())
+            .args(["--write-out", "\n%{http_code}", "--config", "-"])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| {
+                crate::Error::UiQueryFailed(format!(
+                    "Failed to run curl for vision.endpoint: {}",
+                    e
+                ))
+            })?;
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin.write_all(config.as_bytes()).map_err(|e| {
+                crate::Error::UiQueryFailed(format!("Failed to write to curl: {}", e))
+            })?;
+        }
+        let output = child
+            .wait_with_output()
+            .map_err(|e| crate::Error::UiQueryFailed(format!("curl failed: {}", e)))?;
+        if !output.status.success() {
+            return Err(crate::Error::UiQueryFailed(format!(
+                "Vision endpoint {} unreachable: {}",
+                self.endpoint,
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let (body, status) = split_status(&stdout);
+        if !(200..300).contains(&status) {
+            return Err(crate::Error::UiQueryFailed(format!(
+                "Vision endpoint returned HTTP {}: {}",
+                status,
+                body.chars().take(200).collect::<String>()
+            )));
+        }
+        serde_json::from_str(body).map_err(|e| {
+            crate::Error::UiQueryFailed(format!("Invalid vision endpoint JSON: {}", e))
+        })
+    }
+}
+
+impl VisionBackend for HttpVisionBackend {
+    fn analyze(
+        &mut self,
+        screenshot_b64: &str,
+        confidence_threshold: f32,
+        iou_threshold: f32,
+        ocr: bool,
+    ) -> Result<Detection> {
+        let req_id = format!("req_{}", self.request_counter);
+        self.request_counter += 1;
+        let request = detect_request(
+            &req_id,
+            screenshot_b64,
+            confidence_threshold,
+            iou_threshold,
+            ocr,
+        );
+        let response = self.post(&serde_json::to_string(&request)?)?;
+        parse_detection("Vision endpoint", &response)
+    }
+}
+
+/// A double-quoted curl config value.
+fn curl_string(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            _ => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+/// The body and status code of curl output ending in `--write-out "\n%{http_code}"`.
+fn split_status(output: &str) -> (&str, u16) {
+    match output.rsplit_once('\n') {
+        Some((body, code)) => (body, code.trim().parse().unwrap_or(0)),
+        None => ("", output.trim().parse().unwrap_or(0)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_curl_string() {
+        assert_eq!(
+            curl_string(r#"{"image":"a\/b","x":"q\"t"}"#),
+            r#""{\"image\":\"a\\/b\",\"x\":\"q\\\"t\"}""#
+        );
+        assert_eq!(curl_string("a\nb"), r#""a\nb""#);
+    }
+
+    #[test]
+    fn test_split_status() {
+        assert_eq!(
+            split_status("{\"type\":\"result\"}\n200"),
+            ("{\"type\":\"result\"}", 200)
+        );
+        assert_eq!(split_status("line1\nline2\n503"), ("line1\nline2", 503));
+        assert_eq!(split_status("000"), ("", 0));
+    }
+}
@@ -0,0 +1,391 @@
+//! In-process vision backend: OmniParser's icon detector exported to ONNX and
+//! run with ONNX Runtime, for machines without the Python sidecar. Built with
+//! the `onnx` feature.
+//!
+//! Only detection runs here. Elements come back as "icon" without captions,
+//! and there is no OCR pass.
+
+use super::vision::{Detection, VisionBackend, VisionBounds, VisionElement};
+use crate::Result;
+use ort::session::Session;
+use std::path::{Path, PathBuf};
+
+/// Input size of the exported YOLO model.
+const INPUT_SIZE: u32 = 640;
+/// Letterbox padding, as in ultralytics preprocessing.
+const PAD_VALUE: f32 = 114.0 / 255.0;
+
+/// Where `yolo export` writes the sidecar's detector.
+pub fn default_model_path() -> Option<PathBuf> {
+    dirs::home_dir().map(|h| h.join(".strobe/models/icon_detect/model.onnx"))
+}
+
+pub struct OnnxVisionBackend {
+    session: Session,
+}
+
+impl OnnxVisionBackend {
+    pub fn load(model: &Path) -> Result<Self> {
+        if !model.is_file() {
+            return Err(crate::Error::UiQueryFailed(format!(
+                "ONNX model not found at {}. Export it with `yolo export model=~/.strobe/models/icon_detect/model.pt format=onnx`, or set vision.onnxModel.",
+                model.display()
+            )));
+        }
+        let session = Session::builder()
+            .and_then(|b| b.commit_from_file(model))
+            .map_err(|e| {
+                crate::Error::UiQueryFailed(format!(
+                    "Failed to load ONNX model {}: {}",
+                    model.display(),
+                    e
+                ))
+            })?;
+        tracing::info!("Loaded ONNX vision model from {}", model.display());
+        Ok(Self { session })
+    }
+
+    fn detect(
+        &self,
+        png: &[u8],
+        confidence_threshold: f32,
+        iou_threshold: f32,
+    ) -> Result<Vec<VisionElement>> {
+        let image = decode_png(png)?;
+        let (input, letterbox) = letterbox(&image);
+        let ort_error = |e: ort::Error| crate::Error::UiQueryFailed(format!("ONNX: {}", e));
+
+        let size = INPUT_SIZE as usize;
+        let tensor =
+            ort::value::Tensor::from_array(([1usize, 3, size, size], input)).map_err(ort_error)?;
+        let outputs = self
+            .session
+            .run(ort::inputs![tensor].map_err(ort_error)?)
+            .map_err(ort_error)?;
+        let (shape, data) = outputs[0]
+            .try_extract_raw_tensor::<f32>()
+            .map_err(ort_error)?;
+        let [_, rows, anchors] = shape[..] else {
+            return Err(crate::Error::UiQueryFailed(format!(
+                "Unexpected ONNX output shape {:?}",
+                shape
+            )));
+        };
+
+        let candidates = decode_boxes(
+            data,
+            rows as usize,
+            anchors as usize,
+            confidence_threshold,
+            &letterbox,
+        );
+        Ok(remove_overlap(
+            non_max_suppression(candidates, iou_threshold),
+            iou_threshold,
+        )
+        .into_iter()
+        .map(|c| VisionElement {
+            label: "icon".to_string(),
+            description: String::new(),
+            confidence: (c.confidence * 1000.0).round() / 1000.0,
+            bounds: VisionBounds {
+                x: c.x1 as i32,
+                y: c.y1 as i32,
+                w: (c.x2 - c.x1) as i32,
+                h: (c.y2 - c.y1) as i32,
+            },
+        })
+        .collect())
+    }
+}
+
+impl VisionBackend for OnnxVisionBackend {
+    fn analyze(
+        &mut self,
+        screenshot_b64: &str,
+        confidence_threshold: f32,
+        iou_threshold: f32,
+        ocr: bool,
+    ) -> Result<Detection> {
+        use base64::Engine;
+        let png = base64::engine::general_purpose::STANDARD
+            .decode(screenshot_b64)
+            .map_err(|e| crate::Error::UiQueryFailed(format!("Invalid screenshot: {}", e)))?;
+        if ocr {
+            tracing::debug!("ONNX vision backend has no OCR pass, skipping");
+        }
+        Ok(Detection {
+            elements: self.detect(&png, confidence_threshold, iou_threshold)?,
+            texts: Vec::new(),
+        })
+    }
+}
+
+/// An 8-bit RGB image.
+struct RgbImage {
+    width: u32,
+    height: u32,
+    pixels: Vec<u8>,
+}
+
+fn decode_png(data: &[u8]) -> Result<RgbImage> {
+    let png_error = |e: png::DecodingError| {
+        crate::Error::UiQueryFailed(format!("Failed to decode screenshot: {}", e))
+    };
+    let mut decoder = png::Decoder::new(data);
+    decoder.set_transformations(png::Transformations::normalize_to_color8());
+    let mut reader = decoder.read_info().map_err(png_error)?;
+    let mut buf = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut buf).map_err(png_error)?;
+    buf.truncate(info.buffer_size());
+
+    let pixels = match info.color_type {
+        png::ColorType::Rgb => buf,
+        png::ColorType::Rgba => buf
+            .chunks_exact(4)
+            .flat_map(|p| [p[0], p[1], p[2]])
+            .collect(),
+        png::ColorType::Grayscale => buf.iter().flat_map(|&g| [g, g, g]).collect(),
+        png::ColorType::GrayscaleAlpha => buf
+            .chunks_exact(2)
+            .flat_map(|p| [p[0], p[0], p[0]])
+            .collect(),
+        png::ColorType::Indexed => {
+            return Err(crate::Error::UiQueryFailed(
+                "Unexpected indexed PNG after expansion".to_string(),
+            ))
+        }
+    };
+    Ok(RgbImage {
+        width: info.width,
+        height: info.height,
+        pixels,
+    })
+}
+
+/// How the image was placed in the model input, to map boxes back.
+#[derive(Debug, PartialEq)]
+struct Letterbox {
+    scale: f32,
+    pad_x: f32,
+    pad_y: f32,
+    width: f32,
+    height: f32,
+}
+
+/// Scale the image to fit INPUT_SIZE, keeping its aspect ratio, centre it on
+/// padding and lay it out as normalized CHW floats.
+fn letterbox(image: &RgbImage) -> (Vec<f32>, Letterbox) {
+    let size = INPUT_SIZE as usize;
+    let scale =
+        (INPUT_SIZE as f32 / image.width as f32).min(INPUT_SIZE as f32 / image.height as f32);
+    let new_w = ((image.width as f32 * scale).round() as usize).clamp(1, size);
+    let new_h = ((image.height as f32 * scale).round() as usize).clamp(1, size);
+    let pad_x = (size - new_w) / 2;
+    let pad_y = (size - new_h) / 2;
+
+    let mut input = vec![PAD_VALUE; 3 * size * size];
+    for y in 0..new_h {
+        // Nearest neighbour is enough for icon-sized features
+        let src_y = ((y as f32 + 0.5) / scale) as usize;
+        let src_y = src_y.min(image.height as usize - 1);
+        for x in 0..new_w {
+            let src_x = (((x as f32 + 0.5) / scale) as usize).min(image.width as usize - 1);
+            let src = (src_y * image.width as usize + src_x) * 3;
+            let dst = (y + pad_y) * size + x + pad_x;
+            for c in 0..3 {
+                input[c * size * size + dst] = image.pixels[src + c] as f32 / 255.0;
+            }
+        }
+    }
+
+    (
+        input,
+        Letterbox {
+            scale,
+            pad_x: pad_x as f32,
+            pad_y: pad_y as f32,
+            width: image.width as f32,
+            height: image.height as f32,
+        },
+    )
+}
+
+/// A detection in image pixels.
+#[derive(Debug, Clone, PartialEq)]
+struct Candidate {
+    x1: f32,
+    y1: f32,
+    x2: f32,
+    y2: f32,
+    confidence: f32,
+}
+
+impl Candidate {
+    fn area(&self) -> f32 {
+        (self.x2 - self.x1).max(0.0) * (self.y2 - self.y1).max(0.0)
+    }
+
+    fn iou(&self, other: &Candidate) -> f32 {
+        let w = (self.x2.min(other.x2) - self.x1.max(other.x1)).max(0.0);
+        let h = (self.y2.min(other.y2) - self.y1.max(other.y1)).max(0.0);
+        let intersection = w * h;
+        let union = self.area() + other.area() - intersection;
+        if union <= 0.0 {
+            0.0
+        } else {
+            intersection / union
+        }
+    }
+}
+
+/// Boxes above the threshold in YOLOv8 output: `rows` = 4 box coordinates
+/// (centre x, centre y, width, height) then one score per class, each a row
+/// of `anchors` values.
+fn decode_boxes(
+    data: &[f32],
+    rows: usize,
+    anchors: usize,
+    confidence_threshold: f32,
+    letterbox: &Letterbox,
+) -> Vec<Candidate> {
+    if rows < 5 || data.len() < rows * anchors {
+        return Vec::new();
+    }
+    let at = |row: usize, anchor: usize| data[row * anchors + anchor];
+    (0..anchors)
+        .filter_map(|a| {
+            let confidence = (4..rows).map(|r| at(r, a)).fold(f32::MIN, f32::max);
+            if confidence < confidence_threshold {
+                return None;
+            }
+            let (cx, cy, w, h) = (at(0, a), at(1, a), at(2, a), at(3, a));
+            let to_x =
+                |v: f32| ((v - letterbox.pad_x) / letterbox.scale).clamp(0.0, letterbox.width);
+            let to_y =
+                |v: f32| ((v - letterbox.pad_y) / letterbox.scale).clamp(0.0, letterbox.height);
+            Some(Candidate {
+                x1: to_x(cx - w / 2.0),
+                y1: to_y(cy - h / 2.0),
+                x2: to_x(cx + w / 2.0),
+                y2: to_y(cy + h / 2.0),
+                confidence,
+            })
+        })
+        .collect()
+}
+
+/// Greedy NMS: keep the most confident box of each overlapping group.
+fn non_max_suppression(mut candidates: Vec<Candidate>, iou_threshold: f32) -> Vec<Candidate> {
+    candidates.sort_by(|a, b| b.confidence.total_cmp(&a.confidence));
+    let mut kept: Vec<Candidate> = Vec::new();
+    for candidate in candidates {
+        if kept.iter().all(|k| k.iou(&candidate) <= iou_threshold) {
+            kept.push(candidate);
+        }
+    }
+    kept
+}
+
+/// The sidecar's OmniParser overlap filter: of two boxes overlapping more
+/// than the threshold, drop the larger.
+fn remove_overlap(candidates: Vec<Candidate>, iou_threshold: f32) -> Vec<Candidate> {
+    candidates
+        .iter()
+        .filter(|c| {
+            !candidates
+                .iter()
+                .any(|o| !std::ptr::eq(*c, o) && c.iou(o) > iou_threshold && c.area() > o.area())
+        })
+        .cloned()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candidate(x1: f32, y1: f32, x2: f32, y2: f32, confidence: f32) -> Candidate {
+        Candidate {
+            x1,
+            y1,
+            x2,
+            y2,
+            confidence,
+        }
+    }
+
+    #[test]
+    fn test_decode_png() {
+        let mut data = Vec::new();
+        {
+            let mut encoder = png::Encoder::new(&mut data, 2, 1);
+            encoder.set_color(png::ColorType::Rgba);
+            encoder.set_depth(png::BitDepth::Eight);
+            let mut writer = encoder.write_header().unwrap();
+            writer
+                .write_image_data(&[255, 0, 0, 255, 0, 0, 255, 128])
+                .unwrap();
+        }
+        let image = decode_png(&data).unwrap();
+        assert_eq!((image.width, image.height), (2, 1));
+        assert_eq!(image.pixels, vec![255, 0, 0, 0, 0, 255]);
+        assert!(decode_png(b"not a png").is_err());
+    }
+
+    #[test]
+    fn test_letterbox() {
+        // 1280x640 scales to 640x320, centred vertically
+        let image = RgbImage {
+            width: 1280,
+            height: 640,
+            pixels: vec![255; 1280 * 640 * 3],
+        };
+        let (input, letterbox) = letterbox(&image);
+        assert_eq!(input.len(), 3 * 640 * 640);
+        assert_eq!(letterbox.scale, 0.5);
+        assert_eq!((letterbox.pad_x, letterbox.pad_y), (0.0, 160.0));
+        assert_eq!(input[0], PAD_VALUE); // Top padding
+        assert_eq!(input[160 * 640], 1.0); // First image row
+        assert_eq!(input[480 * 640], PAD_VALUE); // Bottom padding
+    }
+
+    #[test]
+    fn test_decode_boxes() {
+        let letterbox = Letterbox {
+            scale: 0.5,
+            pad_x: 0.0,
+            pad_y: 160.0,
+            width: 1280.0,
+            height: 640.0,
+        };
+        // Two anchors, one class: cx, cy, w, h, score rows
+        let data = [
+            100.0, 300.0, // cx
+            200.0, 300.0, // cy
+            20.0, 10.0, // w
+            10.0, 10.0, // h
+            0.9, 0.1, // score
+        ];
+        let boxes = decode_boxes(&data, 5, 2, 0.3, &letterbox);
+        assert_eq!(boxes, vec![candidate(180.0, 70.0, 220.0, 90.0, 0.9)]);
+    }
+
+    #[test]
+    fn test_overlap_filters() {
+        let boxes = vec![
+            candidate(0.0, 0.0, 10.0, 10.0, 0.5),
+            candidate(1.0, 1.0, 10.0, 10.0, 0.8),
+            candidate(50.0, 50.0, 60.0, 60.0, 0.4),
+        ];
+        let kept = non_max_suppression(boxes.clone(), 0.5);
+        assert_eq!(kept, vec![boxes[1].clone(), boxes[2].clone()]);
+
+        // Nested boxes: the smaller one stays, whatever its confidence
+        let nested = vec![
+            candidate(0.0, 0.0, 100.0, 20.0, 0.9),
+            candidate(0.0, 0.0, 80.0, 20.0, 0.3),
+        ];
+        assert_eq!(remove_overlap(nested.clone(), 0.5), vec![nested[1].clone()]);
+    }
+}
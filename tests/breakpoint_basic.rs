@@ -39,6 +39,8 @@ async fn test_breakpoint_suite() {
                 None,
                 None,
                 None,
+                None,
+                None,
             )
             .await;
 
@@ -84,6 +86,8 @@ async fn test_breakpoint_suite() {
                 session_id,
                 None,
                 None,
+                None,
+                None,
                 Some("main.cpp".to_string()),
                 Some(10),
                 None,
@@ -135,6 +139,8 @@ async fn test_breakpoint_suite() {
                 Some("audio::process_buffer".to_string()),
                 None,
                 None,
+                None,
+                None,
                 Some("args[0] > 5".to_string()),
                 None,
             )
@@ -180,6 +186,8 @@ async fn test_breakpoint_suite() {
                 None,
                 None,
                 None,
+                None,
+                None,
             )
             .await;
 
@@ -228,6 +236,8 @@ async fn test_breakpoint_suite() {
                 None,
                 None,
                 None,
+                None,
+                None,
             )
             .await;
         assert!(result.is_err(), "Should fail for non-existent function");
@@ -239,6 +249,8 @@ async fn test_breakpoint_suite() {
                 session_id,
                 None,
                 None,
+                None,
+                None,
                 Some("main.cpp".to_string()),
                 Some(1),
                 None,
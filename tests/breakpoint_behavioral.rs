@@ -69,6 +69,8 @@ async fn test_breakpoint_behavioral_suite() {
                 None,
                 None,
                 None,
+                None,
+                None,
             )
             .await;
         assert!(bp.is_ok(), "Failed to set breakpoint: {:?}", bp.err());
@@ -172,6 +174,8 @@ async fn test_breakpoint_behavioral_suite() {
                 Some("audio::process_buffer".to_string()),
                 None,
                 None,
+                None,
+                None,
                 Some("false".to_string()),
                 None,
             )
@@ -243,6 +247,8 @@ async fn test_breakpoint_behavioral_suite() {
                 Some("audio::process_buffer".to_string()),
                 None,
                 None,
+                None,
+                None,
                 Some("true".to_string()),
                 None,
             )
@@ -293,6 +299,8 @@ async fn test_breakpoint_behavioral_suite() {
                 None,
                 None,
                 None,
+                None,
+                None,
                 Some(3),
             )
             .await;
@@ -361,6 +369,8 @@ async fn test_breakpoint_behavioral_suite() {
                 None,
                 None,
                 None,
+                None,
+                None,
             )
             .await;
         assert!(bp.is_ok(), "Failed to set breakpoint: {:?}", bp.err());
@@ -440,6 +450,8 @@ async fn test_breakpoint_behavioral_suite() {
                 None,
                 None,
                 None,
+                None,
+                None,
             )
             .await;
         assert!(bp.is_ok());
@@ -517,6 +529,8 @@ async fn test_breakpoint_behavioral_suite() {
                 None,
                 None,
                 None,
+                None,
+                None,
             )
             .await;
         assert!(bp.is_ok());
@@ -587,6 +601,8 @@ async fn test_breakpoint_behavioral_suite() {
                 None,
                 None,
                 None,
+                None,
+                None,
             )
             .await;
         assert!(bp1.is_ok());
@@ -600,6 +616,8 @@ async fn test_breakpoint_behavioral_suite() {
                 None,
                 None,
                 None,
+                None,
+                None,
             )
             .await;
         assert!(bp2.is_ok());
@@ -862,6 +862,8 @@ async fn scenario_watch_variables(
         type_name: recipe.type_name.clone(),
         on_patterns: None,
         no_slide: false,
+        delta_threshold: None,
+        module: None,
     }];
 
     sm.update_frida_watches(session_id, watch_targets, vec![])
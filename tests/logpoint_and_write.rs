@@ -68,6 +68,7 @@ async fn test_logpoint_and_write_suite() {
                 None,
                 "process_buffer called on thread {threadId}".to_string(),
                 None,
+                strobe::daemon::LogpointHits::default(),
             )
             .await;
         assert!(lp.is_ok(), "Failed to set logpoint: {:?}", lp.err());
@@ -179,6 +180,7 @@ async fn test_logpoint_and_write_suite() {
                 None,
                 "logged".to_string(),
                 None,
+                strobe::daemon::LogpointHits::default(),
             )
             .await;
         assert!(lp.is_ok());
@@ -238,6 +240,7 @@ async fn test_logpoint_and_write_suite() {
                 None,
                 "should never appear".to_string(),
                 Some("false".to_string()),
+                strobe::daemon::LogpointHits::default(),
             )
             .await;
         assert!(lp.is_ok());
@@ -382,6 +385,8 @@ async fn test_logpoint_and_write_suite() {
                 None,
                 None,
                 None,
+                None,
+                None,
             )
             .await;
         assert!(bp.is_ok(), "Failed to set breakpoint: {:?}", bp.err());
@@ -506,6 +511,8 @@ async fn test_logpoint_and_write_suite() {
                 None,
                 None,
                 None,
+                None,
+                None,
             )
             .await;
         assert!(bp.is_ok(), "Failed to set breakpoint: {:?}", bp.err());
@@ -582,6 +589,7 @@ async fn test_logpoint_and_write_suite() {
                 None,
                 "sine generated".to_string(),
                 None,
+                strobe::daemon::LogpointHits::default(),
             )
             .await;
         assert!(lp.is_ok());
@@ -596,6 +604,8 @@ async fn test_logpoint_and_write_suite() {
                 None,
                 None,
                 None,
+                None,
+                None,
             )
             .await;
         assert!(bp.is_ok());
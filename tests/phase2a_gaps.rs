@@ -50,6 +50,8 @@ async fn test_phase2a_gap_suite() {
                 None,
                 None,
                 None,
+                None,
+                None,
             )
             .await;
         assert!(bp.is_ok(), "Failed to set breakpoint: {:?}", bp.err());
@@ -157,6 +159,8 @@ async fn test_phase2a_gap_suite() {
                 None,
                 None,
                 None,
+                None,
+                None,
             )
             .await;
         assert!(bp.is_ok(), "Failed to set breakpoint: {:?}", bp.err());
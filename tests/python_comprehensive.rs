@@ -363,6 +363,8 @@ async fn test_pytest_execution(sm: &strobe::daemon::SessionManager) {
             "test-conn",
             &session_id,
             progress,
+            strobe::mcp::TestInstrumentation::Frida,
+            strobe::mcp::TestOrder::Default,
         )
         .await;
 
@@ -417,6 +419,8 @@ async fn test_pytest_stuck_detection(sm: &strobe::daemon::SessionManager) {
             "test-conn",
             &session_id,
             progress.clone(),
+            strobe::mcp::TestInstrumentation::Frida,
+            strobe::mcp::TestOrder::Default,
         )
         .await;
 
@@ -257,6 +257,8 @@ async fn scenario_breakpoints(
             session_id,
             Some("bp-test-1".to_string()),
             None,
+            None,
+            None,
             Some("audio.py".to_string()),
             Some(7),
             None,
@@ -41,6 +41,8 @@ async fn test_stepping_suite() {
                 None,
                 None,
                 None,
+                None,
+                None,
             )
             .await;
         assert!(
@@ -95,6 +97,8 @@ async fn test_stepping_suite() {
                 None,
                 None,
                 None,
+                None,
+                None,
             )
             .await;
         assert!(bp_result.is_ok());
@@ -143,6 +147,8 @@ async fn test_stepping_suite() {
                 None,
                 None,
                 None,
+                None,
+                None,
             )
             .await;
         assert!(bp_result.is_ok());
@@ -193,6 +199,8 @@ async fn test_stepping_suite() {
                 None,
                 None,
                 None,
+                None,
+                None,
             )
             .await
             .unwrap();
@@ -330,6 +338,8 @@ async fn test_stepping_suite() {
                 None,
                 None,
                 None,
+                None,
+                None,
             )
             .await;
         let bp2 = sm
@@ -341,6 +351,8 @@ async fn test_stepping_suite() {
                 None,
                 None,
                 None,
+                None,
+                None,
             )
             .await;
 
@@ -68,6 +68,8 @@ async fn test_stepping_behavioral_suite() {
                 None,
                 None,
                 None,
+                None,
+                None,
             )
             .await;
         assert!(bp.is_ok(), "Failed to set breakpoint: {:?}", bp.err());
@@ -175,6 +177,8 @@ async fn test_stepping_behavioral_suite() {
                 None,
                 None,
                 None,
+                None,
+                None,
             )
             .await;
         assert!(bp.is_ok(), "Failed to set breakpoint: {:?}", bp.err());
@@ -246,6 +250,8 @@ async fn test_stepping_behavioral_suite() {
                 None,
                 None,
                 None,
+                None,
+                None,
             )
             .await;
         assert!(bp.is_ok());
@@ -343,6 +349,8 @@ async fn test_stepping_behavioral_suite() {
                 None,
                 None,
                 None,
+                None,
+                None,
             )
             .await;
         assert!(bp.is_ok());
@@ -440,6 +448,8 @@ async fn test_stepping_behavioral_suite() {
                 None,
                 None,
                 None,
+                None,
+                None,
             )
             .await;
         assert!(bp.is_ok());
@@ -140,6 +140,12 @@ fn create_event(session_id: &str, seq: usize) -> Event {
         exception_type: None,
         exception_message: None,
         throw_backtrace: None,
+        channel: None,
         rowid: None,
+        seq: None,
+        dump_path: None,
+        queue_name: None,
+        log_fields: None,
+        test_name: None,
     }
 }
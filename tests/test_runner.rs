@@ -59,6 +59,8 @@ async fn test_cargo_execution(sm: &strobe::daemon::SessionManager) {
             "test-conn",
             &session_id,
             progress,
+            strobe::mcp::TestInstrumentation::Frida,
+            strobe::mcp::TestOrder::Default,
         )
         .await
         .unwrap();
@@ -108,6 +110,8 @@ async fn test_cargo_single_test(sm: &strobe::daemon::SessionManager) {
             "test-conn",
             &session_id,
             progress,
+            strobe::mcp::TestInstrumentation::Frida,
+            strobe::mcp::TestOrder::Default,
         )
         .await
         .unwrap();
@@ -145,6 +149,8 @@ async fn test_catch2_execution(sm: &strobe::daemon::SessionManager) {
             "test-conn",
             &session_id,
             progress,
+            strobe::mcp::TestInstrumentation::Frida,
+            strobe::mcp::TestOrder::Default,
         )
         .await
         .unwrap();
@@ -200,6 +206,8 @@ async fn test_catch2_single_test(sm: &strobe::daemon::SessionManager) {
             "test-conn",
             &session_id,
             progress,
+            strobe::mcp::TestInstrumentation::Frida,
+            strobe::mcp::TestOrder::Default,
         )
         .await
         .unwrap();
@@ -237,6 +245,8 @@ async fn test_catch2_stuck_detection(sm: &strobe::daemon::SessionManager) {
             "test-conn",
             &session_id,
             progress.clone(),
+            strobe::mcp::TestInstrumentation::Frida,
+            strobe::mcp::TestOrder::Default,
         )
         .await;
 
@@ -415,6 +425,8 @@ async fn test_details_file_writing(sm: &strobe::daemon::SessionManager) {
             "test-conn",
             &session_id,
             progress,
+            strobe::mcp::TestInstrumentation::Frida,
+            strobe::mcp::TestOrder::Default,
         )
         .await
         .unwrap();
@@ -111,6 +111,8 @@ async fn test_vitest_full_suite(sm: &strobe::daemon::SessionManager) {
             "test-conn",
             &session_id,
             progress.clone(),
+            strobe::mcp::TestInstrumentation::Frida,
+            strobe::mcp::TestOrder::Default,
         )
         .await
         .unwrap();
@@ -167,6 +169,8 @@ async fn test_vitest_single_test(sm: &strobe::daemon::SessionManager) {
             "test-conn",
             &session_id,
             progress,
+            strobe::mcp::TestInstrumentation::Frida,
+            strobe::mcp::TestOrder::Default,
         )
         .await
         .unwrap();
@@ -203,6 +207,8 @@ async fn test_vitest_custom_command(sm: &strobe::daemon::SessionManager) {
             "test-conn",
             &session_id,
             progress,
+            strobe::mcp::TestInstrumentation::Frida,
+            strobe::mcp::TestOrder::Default,
         )
         .await
         .unwrap();
@@ -254,6 +260,8 @@ async fn test_strobe_fallback(sm: &strobe::daemon::SessionManager) {
             "test-conn",
             &session_id,
             progress.clone(),
+            strobe::mcp::TestInstrumentation::Frida,
+            strobe::mcp::TestOrder::Default,
         )
         .await
         .unwrap();
@@ -313,6 +321,8 @@ async fn test_multi_file_threads_pool(sm: &strobe::daemon::SessionManager) {
             "test-conn",
             &session_id,
             progress.clone(),
+            strobe::mcp::TestInstrumentation::Frida,
+            strobe::mcp::TestOrder::Default,
         )
         .await
         .unwrap();
@@ -367,6 +377,8 @@ async fn test_forks_pool_deadlocks(sm: &strobe::daemon::SessionManager) {
             "test-conn",
             &session_id,
             progress.clone(),
+            strobe::mcp::TestInstrumentation::Frida,
+            strobe::mcp::TestOrder::Default,
         )
         .await
         .unwrap();
@@ -0,0 +1,111 @@
+"""Text recognition for detect requests with "ocr": true.
+
+Uses Vision.framework on macOS (pyobjc-framework-Vision) and Tesseract
+elsewhere (pytesseract + the tesseract binary). Both are optional: without
+a backend, recognize() returns no text and the reason, and detection works
+as before.
+"""
+
+import sys
+from dataclasses import dataclass
+from PIL import Image
+
+
+@dataclass
+class RecognizedText:
+    text: str
+    confidence: float
+    bounds: dict  # {"x": int, "y": int, "w": int, "h": int}, image pixels
+
+
+class OcrUnavailable(Exception):
+    pass
+
+
+def recognize(image: Image.Image) -> list[RecognizedText]:
+    """Lines of text in the image, top to bottom. Raises OcrUnavailable
+    when no backend is installed."""
+    if sys.platform == "darwin":
+        try:
+            return _recognize_vision(image)
+        except ImportError:
+            pass  # Fall back to Tesseract
+    try:
+        return _recognize_tesseract(image)
+    except ImportError:
+        raise OcrUnavailable(
+            "no OCR backend: install pyobjc-framework-Vision (macOS) or pytesseract"
+        )
+
+
+def _recognize_vision(image: Image.Image) -> list[RecognizedText]:
+    import io
+    import Vision
+    from Foundation import NSData
+
+    buf = io.BytesIO()
+    image.save(buf, format="PNG")
+    png = buf.getvalue()
+    data = NSData.dataWithBytes_length_(png, len(png))
+
+    request = Vision.VNRecognizeTextRequest.alloc().init()
+    request.setRecognitionLevel_(Vision.VNRequestTextRecognitionLevelAccurate)
+    request.setUsesLanguageCorrection_(False)  # UI text is often not prose
+    handler = Vision.VNImageRequestHandler.alloc().initWithData_options_(data, None)
+    ok, error = handler.performRequests_error_([request], None)
+    if not ok:
+        raise RuntimeError(f"Vision text recognition failed: {error}")
+
+    w, h = image.size
+    texts = []
+    for observation in request.results() or []:
+        candidates = observation.topCandidates_(1)
+        if not candidates:
+            continue
+        candidate = candidates[0]
+        # Normalized coordinates with the origin at the bottom left
+        box = observation.boundingBox()
+        texts.append(RecognizedText(
+            text=str(candidate.string()),
+            confidence=round(float(candidate.confidence()), 3),
+            bounds={
+                "x": int(box.origin.x * w),
+                "y": int((1.0 - box.origin.y - box.size.height) * h),
+                "w": int(box.size.width * w),
+                "h": int(box.size.height * h),
+            },
+        ))
+    texts.sort(key=lambda t: (t.bounds["y"], t.bounds["x"]))
+    return texts
+
+
+def _recognize_tesseract(image: Image.Image) -> list[RecognizedText]:
+    import pytesseract
+
+    data = pytesseract.image_to_data(image, output_type=pytesseract.Output.DICT)
+    return group_words(data)
+
+
+def group_words(data: dict) -> list[RecognizedText]:
+    """Join Tesseract's words into lines, with the lowest word confidence."""
+    lines: dict[tuple, list[int]] = {}
+    for i, word in enumerate(data["text"]):
+        # Non-word boxes (blocks, paragraphs, empty words) have confidence -1
+        if not word.strip() or float(data["conf"][i]) < 0:
+            continue
+        key = (data["block_num"][i], data["par_num"][i], data["line_num"][i])
+        lines.setdefault(key, []).append(i)
+
+    texts = []
+    for words in lines.values():
+        x1 = min(data["left"][i] for i in words)
+        y1 = min(data["top"][i] for i in words)
+        x2 = max(data["left"][i] + data["width"][i] for i in words)
+        y2 = max(data["top"][i] + data["height"][i] for i in words)
+        texts.append(RecognizedText(
+            text=" ".join(data["text"][i].strip() for i in words),
+            confidence=round(min(float(data["conf"][i]) for i in words) / 100.0, 3),
+            bounds={"x": x1, "y": y1, "w": x2 - x1, "h": y2 - y1},
+        ))
+    texts.sort(key=lambda t: (t.bounds["y"], t.bounds["x"]))
+    return texts
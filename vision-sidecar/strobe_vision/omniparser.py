@@ -8,6 +8,23 @@ from .models import models_dir, select_device
 from .protocol import DetectedElement
 
 
+def decode_image(image_b64: str) -> Image.Image:
+    """Decode a base64-encoded PNG, rejecting oversized input."""
+    # SEC-3: Validate base64 size to prevent memory exhaustion
+    MAX_IMAGE_SIZE = 50 * 1024 * 1024  # 50MB base64 limit
+    if len(image_b64) > MAX_IMAGE_SIZE:
+        raise ValueError(f"Image too large: {len(image_b64)} bytes exceeds 50MB limit")
+
+    img_bytes = base64.b64decode(image_b64)
+    image = Image.open(io.BytesIO(img_bytes)).convert("RGB")
+
+    # SEC-3: Validate image dimensions (4K limit)
+    MAX_PIXELS = 3840 * 2160
+    if image.width * image.height > MAX_PIXELS:
+        raise ValueError(f"Image too large: {image.width}x{image.height} exceeds 4K limit")
+    return image
+
+
 class OmniParser:
     def __init__(self):
         self.device = select_device()
@@ -63,20 +80,7 @@ class OmniParser:
         Default thresholds match OmniParser v2 reference: conf=0.01, iou=0.1.
         """
         self.load()
-
-        # SEC-3: Validate base64 size to prevent memory exhaustion
-        MAX_IMAGE_SIZE = 50 * 1024 * 1024  # 50MB base64 limit
-        if len(image_b64) > MAX_IMAGE_SIZE:
-            raise ValueError(f"Image too large: {len(image_b64)} bytes exceeds 50MB limit")
-
-        # Decode image
-        img_bytes = base64.b64decode(image_b64)
-        image = Image.open(io.BytesIO(img_bytes)).convert("RGB")
-
-        # SEC-3: Validate image dimensions (4K limit)
-        MAX_PIXELS = 3840 * 2160
-        if image.width * image.height > MAX_PIXELS:
-            raise ValueError(f"Image too large: {image.width}x{image.height} exceeds 4K limit")
+        image = decode_image(image_b64)
 
         # Run YOLO detection
         results = self.yolo_model(
@@ -12,6 +12,7 @@ class DetectRequest:
     image: str  # base64 PNG
     confidence_threshold: float = 0.3
     iou_threshold: float = 0.5
+    ocr: bool = False
 
     @classmethod
     def from_json(cls, data: dict) -> "DetectRequest":
@@ -22,6 +23,7 @@ class DetectRequest:
             image=data["image"],
             confidence_threshold=opts.get("confidence_threshold", 0.3),
             iou_threshold=opts.get("iou_threshold", 0.5),
+            ocr=bool(opts.get("ocr", False)),
         )
 
 
@@ -38,6 +40,8 @@ class DetectResponse:
     id: str
     type: str = "result"
     elements: list = field(default_factory=list)
+    texts: list = field(default_factory=list)  # OCR lines, when requested
+    ocr_warning: Optional[str] = None
     latency_ms: int = 0
 
     def to_json(self) -> str:
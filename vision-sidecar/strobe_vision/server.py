@@ -4,7 +4,8 @@ import json
 import sys
 import time
 from .protocol import DetectRequest, DetectResponse, ErrorResponse, PongResponse, DetectedElement
-from .omniparser import OmniParser
+from .omniparser import OmniParser, decode_image
+from .ocr import OcrUnavailable, recognize
 from .models import select_device
 
 
@@ -49,6 +50,14 @@ def main():
                     confidence_threshold=req.confidence_threshold,
                     iou_threshold=req.iou_threshold,
                 )
+
+                texts = []
+                ocr_warning = None
+                if req.ocr:
+                    try:
+                        texts = recognize(decode_image(req.image))
+                    except OcrUnavailable as e:
+                        ocr_warning = str(e)
                 elapsed_ms = int((time.monotonic() - start) * 1000)
 
                 resp = DetectResponse(
@@ -62,6 +71,11 @@ def main():
                         }
                         for e in elements
                     ],
+                    texts=[
+                        {"text": t.text, "confidence": t.confidence, "bounds": t.bounds}
+                        for t in texts
+                    ],
+                    ocr_warning=ocr_warning,
                     latency_ms=elapsed_ms,
                 )
                 sys.stdout.write(resp.to_json() + "\n")